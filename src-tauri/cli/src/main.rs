@@ -0,0 +1,616 @@
+//! Headless command line interface.
+//!
+//! `pacioli-cli` runs wallet syncs, CSV exports, and ledger reports without
+//! the desktop app, for cron jobs and servers. It opens the same SQLite
+//! database the app uses, applying pending migrations first as the app
+//! does, and reads explorer API keys from the same environment variables.
+//! Results are printed to stdout as JSON; sync progress goes to stderr.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use sqlx::SqlitePool;
+
+use pacioli_core::chains::{create_chain_manager_state, plugins};
+use pacioli_core::db::Database;
+use pacioli_core::jobs::CancelToken;
+use pacioli_core::services::accounting::{account_balances, trial_balance};
+use pacioli_core::services::export::{write_transactions_csv, ExportFile};
+use pacioli_core::services::export_templates::{
+    find_template, write_export, ExportLayout, ExportProgress,
+};
+use pacioli_core::services::persistence::{DatabaseState, Profile};
+use pacioli_core::services::profile_scope::profile_wallets;
+use pacioli_core::services::tax_tool_export::{write_tax_tool_export, TaxToolFormat};
+use pacioli_core::services::wallet_sync::{
+    load_sync_statuses, run_wallet_sync, SyncEvents, SyncProgress, SYNC_COMPLETED_EVENT,
+    SYNC_ERROR_EVENT, SYNC_PAGE_EVENT, SYNC_STARTED_EVENT,
+};
+use pacioli_core::DATABASE_FILE;
+
+/// Environment variable overriding the database path.
+const ENV_DATABASE: &str = "PACIOLI_DB";
+
+/// Bundle identifier from `tauri.conf.json`, which names the app's data
+/// directory.
+const APP_IDENTIFIER: &str = "com.civicmastery.numbers";
+
+const USAGE: &str = "\
+Usage: pacioli-cli [--db <path>] <command>
+
+Commands:
+  profiles                                List profiles
+  status --profile <id>                   Show each wallet's sync status
+  sync --profile <id> [--wallet <id>]...  Sync wallet transactions
+  export --profile <id> --out <file> [--from <date>] [--to <date>]
+         [--template <name> | --format koinly|cointracking|accointing]
+         [--gzip]                         Export transactions to CSV, laid
+                                          out by a saved export template or
+                                          for another tax tool's import
+  report trial-balance|account-balances --profile <id>
+                                          Print a profile's ledger report
+
+The database defaults to the desktop app's, or $PACIOLI_DB if set.";
+
+/// Parsed command line.
+#[derive(Debug, PartialEq)]
+struct Args {
+    db: Option<PathBuf>,
+    command: Command,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Help,
+    Profiles,
+    Status {
+        profile: String,
+    },
+    Sync {
+        profile: String,
+        wallets: Vec<String>,
+    },
+    Export {
+        profile: String,
+        out: String,
+        from: Option<String>,
+        to: Option<String>,
+        template: Option<String>,
+        format: Option<TaxToolFormat>,
+        gzip: bool,
+    },
+    Report {
+        report: Report,
+        profile: String,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+enum Report {
+    TrialBalance,
+    AccountBalances,
+}
+
+/// Entry point of the `pacioli-cli` binary.
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    if args.command == Command::Help {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    match runtime.block_on(run(args)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut args = args.into_iter();
+    let mut db = None;
+    let mut positional = Vec::new();
+    let mut profile = None;
+    let mut wallets = Vec::new();
+    let mut out = None;
+    let mut from = None;
+    let mut to = None;
+    let mut template = None;
+    let mut format = None;
+    let mut gzip = false;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+        match arg.as_str() {
+            "-h" | "--help" => positional.insert(0, "help".to_string()),
+            "--db" => db = Some(PathBuf::from(value("--db")?)),
+            "--profile" => profile = Some(value("--profile")?),
+            "--wallet" => wallets.push(value("--wallet")?),
+            "--out" => out = Some(value("--out")?),
+            "--from" => from = Some(value("--from")?),
+            "--to" => to = Some(value("--to")?),
+            "--template" => template = Some(value("--template")?),
+            "--format" => format = Some(value("--format")?.parse()?),
+            "--gzip" => gzip = true,
+            flag if flag.starts_with('-') => return Err(format!("Unknown option {}", flag)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let need_profile = || {
+        profile
+            .clone()
+            .ok_or_else(|| "--profile is required".to_string())
+    };
+    let command = match positional.first().map(String::as_str) {
+        None | Some("help") => Command::Help,
+        Some("profiles") => Command::Profiles,
+        Some("status") => Command::Status {
+            profile: need_profile()?,
+        },
+        Some("sync") => Command::Sync {
+            profile: need_profile()?,
+            wallets,
+        },
+        Some("export") if template.is_some() && format.is_some() => {
+            return Err("--template and --format can't be combined".to_string())
+        }
+        Some("export") => Command::Export {
+            profile: need_profile()?,
+            out: out.ok_or("--out is required")?,
+            from,
+            to,
+            template,
+            format,
+            gzip,
+        },
+        Some("report") => {
+            let report = match positional.get(1).map(String::as_str) {
+                Some("trial-balance") => Report::TrialBalance,
+                Some("account-balances") => Report::AccountBalances,
+                Some(other) => return Err(format!("Unknown report {}", other)),
+                None => return Err("report needs a report name".to_string()),
+            };
+            Command::Report {
+                report,
+                profile: need_profile()?,
+            }
+        }
+        Some(other) => return Err(format!("Unknown command {}", other)),
+    };
+
+    Ok(Args { db, command })
+}
+
+/// Where the desktop app keeps its database on this platform.
+fn default_database_path() -> Option<PathBuf> {
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let data_dir = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home.map(|home| home.join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| home.map(|home| home.join(".local/share")))
+    };
+    Some(data_dir?.join(APP_IDENTIFIER).join(DATABASE_FILE))
+}
+
+/// Opens the database, which must already exist, and registers the chain
+/// plugins beside it.
+async fn open_database(path: Option<PathBuf>) -> Result<SqlitePool, String> {
+    let path = path
+        .or_else(|| env::var_os(ENV_DATABASE).map(PathBuf::from))
+        .or_else(default_database_path)
+        .ok_or("Couldn't find the database; pass --db")?;
+    if !path.exists() {
+        return Err(format!("No database at {}", path.display()));
+    }
+
+    // Chains added by manifests live next to the database, as in the app
+    if let Some(data_dir) = path.parent() {
+        for error in plugins::load_dir(&data_dir.join(plugins::PLUGIN_DIR)) {
+            eprintln!("warning: skipped chain plugin {}", error);
+        }
+    }
+
+    let url = format!("sqlite:{}?mode=rw", path.display());
+    let state = DatabaseState::new(&url)
+        .await
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    Ok(state.pool)
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    let pool = open_database(args.db).await?;
+
+    match args.command {
+        Command::Help => Ok(()),
+        Command::Profiles => {
+            let profiles =
+                sqlx::query_as::<_, Profile>("SELECT * FROM profiles ORDER BY created_at")
+                    .fetch_all(&pool)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            print_json(&profiles)
+        }
+        Command::Status { profile } => {
+            let statuses = load_sync_statuses(&pool, &profile)
+                .await
+                .map_err(|e| e.to_string())?;
+            print_json(&statuses)
+        }
+        Command::Sync { profile, wallets } => sync(&pool, &profile, &wallets).await,
+        Command::Export {
+            profile,
+            out,
+            from,
+            to,
+            template: None,
+            format: None,
+            gzip,
+        } => {
+            let db = Database { pool };
+            let written = write_transactions_csv(&db, &out, &profile, from, to, gzip).await?;
+            eprintln!("Exported {} transactions to {}", written, out);
+            Ok(())
+        }
+        Command::Export {
+            profile,
+            out,
+            from,
+            to,
+            template: Some(name),
+            format: _,
+            gzip,
+        } => {
+            let result = export_with_template(&pool, &profile, &name, &out, from, to, gzip).await;
+            if result.is_err() {
+                let _ = std::fs::remove_file(&out);
+            }
+            let written = result?;
+            eprintln!("Exported {} transactions to {}", written, out);
+            Ok(())
+        }
+        Command::Export {
+            profile,
+            out,
+            from,
+            to,
+            template: None,
+            format: Some(format),
+            gzip,
+        } => {
+            let result = export_for_tool(&pool, &profile, format, &out, from, to, gzip).await;
+            if result.is_err() {
+                let _ = std::fs::remove_file(&out);
+            }
+            let written = result?;
+            eprintln!("Exported {} transactions to {}", written, out);
+            Ok(())
+        }
+        Command::Report {
+            report: Report::TrialBalance,
+            profile,
+        } => print_json(&trial_balance(&pool, &profile).await?),
+        Command::Report {
+            report: Report::AccountBalances,
+            profile,
+        } => print_json(&account_balances(&pool, &profile).await?),
+    }
+}
+
+fn print_json<T: serde::Serialize>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Exports a profile's transactions laid out by the template called `name`,
+/// printing progress to stderr. Ctrl-C stops the export between pages.
+async fn export_with_template(
+    pool: &SqlitePool,
+    profile_id: &str,
+    name: &str,
+    out: &str,
+    from: Option<String>,
+    to: Option<String>,
+    gzip: bool,
+) -> Result<usize, String> {
+    let layout = ExportLayout::from_template(&find_template(pool, profile_id, name).await?)?;
+    let progress = ExportProgress {
+        path: out.to_string(),
+        ..ExportProgress::default()
+    };
+    let (file, written) = write_export(
+        pool,
+        profile_id,
+        &layout,
+        from.as_deref(),
+        to.as_deref(),
+        Default::default(),
+        ExportFile::create(out, gzip)?,
+        &cancel_on_interrupt(),
+        progress,
+        |progress| {
+            eprintln!(
+                "exported {}/{} transactions",
+                progress.processed, progress.total
+            )
+        },
+    )
+    .await?;
+    file.finish()?;
+    Ok(written)
+}
+
+/// Exports a profile's transactions in another tax tool's import format,
+/// printing progress to stderr. Ctrl-C stops the export between pages.
+async fn export_for_tool(
+    pool: &SqlitePool,
+    profile_id: &str,
+    format: TaxToolFormat,
+    out: &str,
+    from: Option<String>,
+    to: Option<String>,
+    gzip: bool,
+) -> Result<usize, String> {
+    let progress = ExportProgress {
+        path: out.to_string(),
+        ..ExportProgress::default()
+    };
+    let (file, written) = write_tax_tool_export(
+        pool,
+        profile_id,
+        format,
+        from.as_deref(),
+        to.as_deref(),
+        ExportFile::create(out, gzip)?,
+        &cancel_on_interrupt(),
+        progress,
+        |progress| {
+            eprintln!(
+                "exported {}/{} transactions",
+                progress.processed, progress.total
+            )
+        },
+    )
+    .await?;
+    file.finish()?;
+    Ok(written)
+}
+
+/// A token that Ctrl-C cancels.
+fn cancel_on_interrupt() -> CancelToken {
+    let cancel = CancelToken::new();
+    let on_interrupt = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            on_interrupt.cancel();
+        }
+    });
+    cancel
+}
+
+/// Prints sync progress to stderr.
+struct ConsoleEvents;
+
+impl SyncEvents for ConsoleEvents {
+    fn emit(&self, event: &str, progress: &SyncProgress) {
+        let wallet = format!("{} {}", progress.chain, progress.address);
+        match event {
+            SYNC_STARTED_EVENT => eprintln!("{}: syncing", wallet),
+            SYNC_PAGE_EVENT => eprintln!(
+                "{}: saved {}/{} transactions",
+                wallet, progress.processed, progress.total
+            ),
+            SYNC_COMPLETED_EVENT => eprintln!("{}: up to date", wallet),
+            SYNC_ERROR_EVENT => eprintln!(
+                "{}: failed: {}",
+                wallet,
+                progress.error.as_deref().unwrap_or("unknown error")
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// Active owner of a profile, whom synced transactions are recorded for.
+async fn profile_owner(pool: &SqlitePool, profile_id: &str) -> Result<String, String> {
+    sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT user_id FROM user_profile_roles
+        WHERE profile_id = ? AND role = 'owner' AND status = 'active'
+        LIMIT 1
+        "#,
+    )
+    .bind(profile_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Profile not found: {}", profile_id))
+}
+
+/// Syncs a profile's wallets one at a time, or only `wallet_ids` when
+/// given. Ctrl-C stops after the current page, keeping what was saved.
+async fn sync(pool: &SqlitePool, profile_id: &str, wallet_ids: &[String]) -> Result<(), String> {
+    let user_id = profile_owner(pool, profile_id).await?;
+    let wallets: Vec<_> = profile_wallets(pool, profile_id)
+        .await?
+        .into_iter()
+        .filter(|w| wallet_ids.is_empty() || wallet_ids.contains(&w.id))
+        .collect();
+    if let Some(missing) = wallet_ids
+        .iter()
+        .find(|id| !wallets.iter().any(|w| &w.id == *id))
+    {
+        return Err(format!("Wallet not found: {}", missing));
+    }
+
+    let chains = create_chain_manager_state();
+
+    let cancel = cancel_on_interrupt();
+
+    let mut failed = 0;
+    for wallet in &wallets {
+        cancel.check()?;
+        let result = run_wallet_sync(
+            &ConsoleEvents,
+            pool,
+            &chains,
+            profile_id,
+            &user_id,
+            &wallet.id,
+            &cancel,
+        )
+        .await;
+        if result.is_err() {
+            failed += 1;
+        }
+    }
+
+    match failed {
+        0 => Ok(()),
+        n => Err(format!("{} of {} wallets failed to sync", n, wallets.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse(&[]).unwrap().command, Command::Help);
+        assert_eq!(
+            parse(&["profiles", "--help"]).unwrap().command,
+            Command::Help
+        );
+
+        let args = parse(&[
+            "--db",
+            "/tmp/p.db",
+            "sync",
+            "--profile",
+            "p1",
+            "--wallet",
+            "w1",
+            "--wallet",
+            "w2",
+        ])
+        .unwrap();
+        assert_eq!(args.db, Some(PathBuf::from("/tmp/p.db")));
+        assert_eq!(
+            args.command,
+            Command::Sync {
+                profile: "p1".to_string(),
+                wallets: vec!["w1".to_string(), "w2".to_string()],
+            }
+        );
+
+        assert_eq!(
+            parse(&[
+                "export",
+                "--profile",
+                "p1",
+                "--out",
+                "tx.csv",
+                "--from",
+                "2025-01-01",
+                "--template",
+                "Accountant",
+                "--gzip"
+            ])
+            .unwrap()
+            .command,
+            Command::Export {
+                profile: "p1".to_string(),
+                out: "tx.csv".to_string(),
+                from: Some("2025-01-01".to_string()),
+                to: None,
+                template: Some("Accountant".to_string()),
+                format: None,
+                gzip: true,
+            }
+        );
+        assert_eq!(
+            parse(&["report", "trial-balance", "--profile", "p1"])
+                .unwrap()
+                .command,
+            Command::Report {
+                report: Report::TrialBalance,
+                profile: "p1".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse(&["sync"]).unwrap_err(), "--profile is required");
+        assert_eq!(
+            parse(&["export", "--profile", "p1"]).unwrap_err(),
+            "--out is required"
+        );
+        assert_eq!(
+            parse(&["export", "--profile", "p1", "--format", "cointracker"]).unwrap_err(),
+            "Unsupported export format: cointracker"
+        );
+        assert_eq!(
+            parse(&[
+                "export",
+                "--profile",
+                "p1",
+                "--out",
+                "tx.csv",
+                "--template",
+                "Accountant",
+                "--format",
+                "koinly"
+            ])
+            .unwrap_err(),
+            "--template and --format can't be combined"
+        );
+        assert_eq!(
+            parse(&["status", "--profile"]).unwrap_err(),
+            "--profile needs a value"
+        );
+        assert_eq!(
+            parse(&["report", "cash-flow"]).unwrap_err(),
+            "Unknown report cash-flow"
+        );
+        assert_eq!(
+            parse(&["report", "trial-balance"]).unwrap_err(),
+            "--profile is required"
+        );
+        assert_eq!(
+            parse(&["frobnicate"]).unwrap_err(),
+            "Unknown command frobnicate"
+        );
+        assert_eq!(
+            parse(&["--verbose"]).unwrap_err(),
+            "Unknown option --verbose"
+        );
+    }
+}
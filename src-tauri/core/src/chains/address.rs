@@ -0,0 +1,316 @@
+//! Cross-chain address detection.
+//!
+//! Runs every chain family's validator over an address and reports which
+//! chains it could belong to, so a wallet added with the wrong chain selected
+//! can be caught before anything is fetched.
+
+use std::str::FromStr;
+
+use bitcoin::address::NetworkUnchecked;
+use bitcoin::{Address, Network};
+use serde::{Deserialize, Serialize};
+
+use super::{bitcoin as btc, evm, plugins, solana, substrate::ss58, ChainType};
+
+/// Encoding an address was recognized in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressFormat {
+    /// `0x`-prefixed 20-byte hex, optionally EIP-55 checksummed.
+    EvmHex,
+    /// Substrate SS58.
+    Ss58,
+    /// Base58-encoded 32-byte Solana public key.
+    SolanaBase58,
+    /// Bitcoin legacy or script address (Base58Check).
+    BitcoinBase58,
+    /// Bitcoin SegWit or Taproot address (Bech32/Bech32m).
+    BitcoinBech32,
+}
+
+/// A chain family an address is plausibly valid for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressMatch {
+    /// Chain family.
+    pub chain_type: ChainType,
+    /// Encoding the address was recognized in.
+    pub format: AddressFormat,
+    /// Supported chains the address can be used on.
+    pub chain_ids: Vec<String>,
+    /// Network named by the address itself, e.g. the SS58 prefix's network.
+    pub network: Option<String>,
+    /// SS58 network prefix, for Substrate addresses.
+    pub ss58_prefix: Option<u16>,
+    /// Whether the address carried a checksum that verified. EVM addresses
+    /// written in a single case carry none.
+    pub checksum_verified: bool,
+    /// Canonical form of the address for the chain family.
+    pub normalized: String,
+}
+
+/// Result of checking an address against every chain family.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressValidation {
+    /// Address as given, trimmed.
+    pub address: String,
+    /// Chain families the address is plausibly valid for.
+    pub matches: Vec<AddressMatch>,
+    /// Problems found, such as a failed EVM checksum.
+    pub warnings: Vec<String>,
+}
+
+impl AddressValidation {
+    /// Whether the address is valid for `chain_id`.
+    pub fn is_valid_for(&self, chain_id: &str) -> bool {
+        self.matches.iter().any(|m| {
+            m.chain_ids
+                .iter()
+                .any(|id| id.eq_ignore_ascii_case(chain_id))
+        })
+    }
+}
+
+/// IDs of plugin chains of `chain_type` that `accepts`.
+fn plugin_chain_ids(
+    chain_type: ChainType,
+    accepts: impl Fn(&plugins::ChainManifest) -> bool,
+) -> impl Iterator<Item = String> {
+    plugins::all()
+        .into_iter()
+        .filter(move |m| m.chain_type == chain_type && accepts(m))
+        .map(|m| m.id)
+}
+
+/// Checks an EVM hex address, verifying the EIP-55 checksum when mixed case.
+fn match_evm(address: &str, warnings: &mut Vec<String>) -> Option<AddressMatch> {
+    let hex_part = address.strip_prefix("0x")?;
+    if hex_part.len() != 40 || !hex_part.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let normalized = evm::checksum_address(address);
+    let single_case = hex_part == hex_part.to_lowercase() || hex_part == hex_part.to_uppercase();
+    if !single_case && normalized != address {
+        warnings.push(format!(
+            "EVM checksum does not match; expected {}",
+            normalized
+        ));
+        return None;
+    }
+
+    Some(AddressMatch {
+        chain_type: ChainType::Evm,
+        format: AddressFormat::EvmHex,
+        chain_ids: evm::config::get_all_chains()
+            .into_iter()
+            .map(|c| c.name)
+            .chain(plugin_chain_ids(ChainType::Evm, |_| true))
+            .collect(),
+        network: None,
+        ss58_prefix: None,
+        checksum_verified: !single_case,
+        normalized,
+    })
+}
+
+/// Checks an SS58 address and maps its prefix to a network.
+fn match_substrate(address: &str, warnings: &mut Vec<String>) -> Option<AddressMatch> {
+    let decoded = ss58::decode(address).ok()?;
+    let network = ss58::network_for_prefix(decoded.prefix);
+    if network.is_none() {
+        warnings.push(format!(
+            "SS58 prefix {} is not a supported network",
+            decoded.prefix
+        ));
+    }
+
+    Some(AddressMatch {
+        chain_type: ChainType::Substrate,
+        format: AddressFormat::Ss58,
+        chain_ids: network
+            .map(|(chain_id, _)| vec![chain_id.to_string()])
+            .unwrap_or_default(),
+        network: network.map(|(_, name)| name.to_string()),
+        ss58_prefix: Some(decoded.prefix),
+        checksum_verified: true,
+        normalized: address.to_string(),
+    })
+}
+
+/// Checks a base58 Solana public key.
+fn match_solana(address: &str) -> Option<AddressMatch> {
+    solana::validate_solana_address(address).ok()?;
+    Some(AddressMatch {
+        chain_type: ChainType::Solana,
+        format: AddressFormat::SolanaBase58,
+        chain_ids: solana::get_all_configs()
+            .into_iter()
+            .map(|c| c.name)
+            .collect(),
+        network: None,
+        ss58_prefix: None,
+        // Solana keys have no checksum.
+        checksum_verified: false,
+        normalized: address.to_string(),
+    })
+}
+
+/// Checks a Bitcoin address against each supported network.
+fn match_bitcoin(address: &str) -> Option<AddressMatch> {
+    let parsed = Address::<NetworkUnchecked>::from_str(address).ok()?;
+    let networks = [
+        (Network::Bitcoin, btc::BitcoinConfig::mainnet()),
+        (Network::Testnet, btc::BitcoinConfig::testnet()),
+        (Network::Signet, btc::BitcoinConfig::signet()),
+    ];
+    let valid_for = |network| parsed.is_valid_for_network(network);
+    let chain_ids: Vec<String> = networks
+        .into_iter()
+        .filter(|(network, _)| valid_for(*network))
+        .map(|(_, config)| config.name)
+        .chain(plugin_chain_ids(ChainType::Bitcoin, |m| {
+            valid_for(if m.is_testnet {
+                Network::Testnet
+            } else {
+                Network::Bitcoin
+            })
+        }))
+        .collect();
+    if chain_ids.is_empty() {
+        return None;
+    }
+
+    let lower = address.to_lowercase();
+    let format = if lower.starts_with("bc1") || lower.starts_with("tb1") {
+        AddressFormat::BitcoinBech32
+    } else {
+        AddressFormat::BitcoinBase58
+    };
+
+    Some(AddressMatch {
+        chain_type: ChainType::Bitcoin,
+        format,
+        chain_ids,
+        network: None,
+        ss58_prefix: None,
+        checksum_verified: true,
+        normalized: match format {
+            AddressFormat::BitcoinBech32 => lower,
+            _ => address.to_string(),
+        },
+    })
+}
+
+/// Whether `address` is a well-formed EVM address. The same EVM address is
+/// the same account on every EVM chain.
+pub fn is_evm_address(address: &str) -> bool {
+    match_evm(address.trim(), &mut Vec::new()).is_some()
+}
+
+/// Key identifying the account behind an address, independent of encoding.
+///
+/// SS58 addresses map to their public key, so the same account encoded for
+/// Polkadot and Kusama shares a key. EVM addresses are case-folded; anything
+/// else is used as given.
+pub fn identity_key(address: &str) -> String {
+    let address = address.trim();
+    if let Ok(decoded) = ss58::decode(address) {
+        return format!("substrate:{}", hex::encode(decoded.public_key));
+    }
+    if is_evm_address(address) {
+        return format!("evm:{}", address.to_lowercase());
+    }
+    address.to_string()
+}
+
+/// Runs every chain family's validator over `address`.
+pub fn validate_any_address(address: &str) -> AddressValidation {
+    let address = address.trim();
+    let mut warnings = Vec::new();
+
+    let matches = [
+        match_evm(address, &mut warnings),
+        match_substrate(address, &mut warnings),
+        match_solana(address),
+        match_bitcoin(address),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    AddressValidation {
+        address: address.to_string(),
+        matches,
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn families(address: &str) -> Vec<ChainType> {
+        validate_any_address(address)
+            .matches
+            .iter()
+            .map(|m| m.chain_type)
+            .collect()
+    }
+
+    #[test]
+    fn test_evm_checksum() {
+        let valid = validate_any_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+        assert_eq!(valid.matches.len(), 1);
+        assert!(valid.matches[0].checksum_verified);
+        assert!(valid.is_valid_for("ethereum"));
+
+        let lower = validate_any_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+        assert!(!lower.matches[0].checksum_verified);
+        assert_eq!(
+            lower.matches[0].normalized,
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        );
+
+        let typo = validate_any_address("0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+        assert!(typo.matches.is_empty());
+        assert_eq!(typo.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_ss58_prefix_selects_network() {
+        let validation = validate_any_address("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5");
+        assert_eq!(
+            families("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5"),
+            vec![ChainType::Substrate]
+        );
+        assert!(validation.is_valid_for("polkadot"));
+        assert!(!validation.is_valid_for("kusama"));
+    }
+
+    #[test]
+    fn test_identity_key_ignores_encoding() {
+        let generic = identity_key("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY");
+        let polkadot = identity_key("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5");
+        assert_eq!(generic, polkadot);
+        assert!(generic.starts_with("substrate:"));
+        assert_eq!(
+            identity_key("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+            identity_key("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed")
+        );
+    }
+
+    #[test]
+    fn test_other_families() {
+        assert_eq!(
+            families("So11111111111111111111111111111111111111112"),
+            vec![ChainType::Solana]
+        );
+        assert_eq!(
+            families("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"),
+            vec![ChainType::Bitcoin]
+        );
+        assert!(families("not an address").is_empty());
+    }
+}
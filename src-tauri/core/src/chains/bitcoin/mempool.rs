@@ -9,7 +9,8 @@ use crate::chains::{ChainError, ChainResult};
 use crate::fetchers::{FetcherConfig, ResilientFetcher};
 
 use super::types::{
-    BitcoinBalance, BitcoinTransaction, BitcoinUtxo, MempoolAddressInfo, MempoolTransaction,
+    BitcoinBalance, BitcoinTransaction, BitcoinUtxo, MempoolAddressInfo, MempoolOutspend,
+    MempoolTransaction,
 };
 
 /// Default Mempool.space API base URL
@@ -47,6 +48,7 @@ impl MempoolClient {
             requests_per_second: RATE_LIMIT_RPS,
             timeout_secs: 30,
             max_retries: 3,
+            provider: None,
         };
 
         let fetcher = ResilientFetcher::new(config)
@@ -172,12 +174,35 @@ impl MempoolClient {
         Ok(all_txs)
     }
 
+    /// Get the unconfirmed transactions of an address
+    pub async fn get_address_mempool_txs(
+        &self,
+        address: &str,
+    ) -> ChainResult<Vec<MempoolTransaction>> {
+        validate_bitcoin_address(address)?;
+
+        let url = format!("{}/address/{}/txs/mempool", self.base_url, address);
+        self.get_json(&url).await
+    }
+
     /// Get a specific transaction by txid
     pub async fn get_transaction(&self, txid: &str) -> ChainResult<MempoolTransaction> {
         let url = format!("{}/tx/{}", self.base_url, txid);
         self.get_json(&url).await
     }
 
+    /// Get the spending status of one output
+    pub async fn get_outspend(&self, txid: &str, vout: u32) -> ChainResult<MempoolOutspend> {
+        let url = format!("{}/tx/{}/outspend/{}", self.base_url, txid, vout);
+        self.get_json(&url).await
+    }
+
+    /// Get the spending status of every output of a transaction
+    pub async fn get_outspends(&self, txid: &str) -> ChainResult<Vec<MempoolOutspend>> {
+        let url = format!("{}/tx/{}/outspends", self.base_url, txid);
+        self.get_json(&url).await
+    }
+
     /// Fetch address balance
     pub async fn fetch_address_balance(&self, address: &str) -> ChainResult<BitcoinBalance> {
         let info = self.get_address_info(address).await?;
@@ -281,6 +306,9 @@ pub fn validate_bitcoin_address(address: &str) -> ChainResult<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::chains::mock_http::{fixture, mount_get, repeat_record, requests_to, MockServer};
+
+    const ADDRESS: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
 
     #[test]
     fn test_validate_bitcoin_address_legacy() {
@@ -321,4 +349,94 @@ mod tests {
         // Testnet SegWit
         assert!(validate_bitcoin_address("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx").is_ok());
     }
+
+    #[tokio::test]
+    async fn test_address_transactions_paginate() {
+        let server = MockServer::start().await;
+        let recorded = fixture("mempool/address_txs.json");
+        let full_page = repeat_record(&recorded[0], TXS_PER_PAGE, "txid");
+        let last_txid = full_page[TXS_PER_PAGE - 1]["txid"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        mount_get(
+            &server,
+            "/blocks/tip/height",
+            &[],
+            serde_json::json!(840_099),
+        )
+        .await;
+        mount_get(
+            &server,
+            &format!("/address/{}/txs", ADDRESS),
+            &[],
+            full_page,
+        )
+        .await;
+        mount_get(
+            &server,
+            &format!("/address/{}/txs/chain/{}", ADDRESS, last_txid),
+            &[],
+            recorded,
+        )
+        .await;
+
+        let client = MempoolClient::with_base_url(&server.uri()).unwrap();
+        let txs = client
+            .fetch_address_transactions(ADDRESS, None)
+            .await
+            .unwrap();
+
+        // A short second page ends the walk
+        assert_eq!(txs.len(), TXS_PER_PAGE + 1);
+        assert_eq!(
+            requests_to(
+                &server,
+                &format!("/address/{}/txs/chain/{}", ADDRESS, last_txid)
+            )
+            .await,
+            1
+        );
+
+        let tx = &txs[TXS_PER_PAGE];
+        assert_eq!(tx.block_height, Some(840_000));
+        assert_eq!(tx.confirmations, 100);
+        assert_eq!(tx.fee, 2_820);
+        assert_eq!(tx.total_input, tx.total_output + tx.fee);
+        assert_eq!(tx.outputs[0].address.as_deref(), Some(ADDRESS));
+        assert!(!tx.is_coinbase);
+        assert!(tx.rbf_signaled);
+    }
+
+    #[tokio::test]
+    async fn test_max_pages_stops_early() {
+        let server = MockServer::start().await;
+        let recorded = fixture("mempool/address_txs.json");
+        mount_get(
+            &server,
+            "/blocks/tip/height",
+            &[],
+            serde_json::json!(840_099),
+        )
+        .await;
+        mount_get(
+            &server,
+            &format!("/address/{}/txs", ADDRESS),
+            &[],
+            repeat_record(&recorded[0], TXS_PER_PAGE, "txid"),
+        )
+        .await;
+
+        let client = MempoolClient::with_base_url(&server.uri()).unwrap();
+        let txs = client
+            .get_address_transactions(ADDRESS, Some(1))
+            .await
+            .unwrap();
+
+        assert_eq!(txs.len(), TXS_PER_PAGE);
+        assert_eq!(
+            requests_to(&server, &format!("/address/{}/txs", ADDRESS)).await,
+            1
+        );
+    }
 }
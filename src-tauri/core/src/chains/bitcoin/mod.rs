@@ -8,6 +8,9 @@
 /// transactions, allowing querying, updating, and interacting with the
 /// transaction memory pool.
 pub mod mempool;
+/// Tracking of unconfirmed transactions through confirmation, RBF
+/// replacement, or eviction.
+pub mod replacement;
 /// Module containing types used within the Bitcoin chain implementation.
 /// Module containing Bitcoin-specific type definitions.
 /// This module defines data structures such as blocks, transactions, and other types used for interacting with the Bitcoin chain.
@@ -17,7 +20,7 @@ pub mod xpub;
 
 use async_trait::async_trait;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::OnceCell;
 
 use crate::chains::{
     ChainAdapter, ChainError, ChainId, ChainResult, ChainTransaction, ChainType, NativeBalance,
@@ -25,6 +28,7 @@ use crate::chains::{
 };
 
 pub use mempool::{validate_bitcoin_address, MempoolClient};
+pub use replacement::{PendingTxState, TxOutpoint};
 pub use types::{BitcoinBalance, BitcoinTransaction, BitcoinUtxo};
 pub use xpub::{derive_addresses, is_xpub, parse_xpub, DerivedAddress, XpubInfo, XpubPortfolio};
 
@@ -104,7 +108,7 @@ pub struct BitcoinAdapter {
     /// Network configuration
     config: BitcoinConfig,
     /// Mempool.space API client
-    client: Arc<RwLock<Option<MempoolClient>>>,
+    client: OnceCell<Arc<MempoolClient>>,
 }
 
 impl BitcoinAdapter {
@@ -124,7 +128,7 @@ impl BitcoinAdapter {
         Ok(Self {
             chain_id,
             config,
-            client: Arc::new(RwLock::new(None)),
+            client: OnceCell::new(),
         })
     }
 
@@ -136,19 +140,13 @@ impl BitcoinAdapter {
     }
 
     /// Get or initialize the Mempool client
-    async fn get_client(&self) -> ChainResult<MempoolClient> {
-        {
-            let guard = self.client.read().await;
-            if guard.is_some() {
-                return MempoolClient::with_base_url(&self.config.api_url);
-            }
-        }
-
-        let client = MempoolClient::with_base_url(&self.config.api_url)?;
-        let mut guard = self.client.write().await;
-        *guard = Some(MempoolClient::with_base_url(&self.config.api_url)?);
-
-        Ok(client)
+    async fn get_client(&self) -> ChainResult<Arc<MempoolClient>> {
+        self.client
+            .get_or_try_init(|| async {
+                MempoolClient::with_base_url(&self.config.api_url).map(Arc::new)
+            })
+            .await
+            .cloned()
     }
 
     /// Get configuration
@@ -172,6 +170,38 @@ impl BitcoinAdapter {
         client.fetch_address_balance(address).await
     }
 
+    /// Fetch an address's transactions still in the mempool (native format)
+    pub async fn fetch_mempool_transactions(
+        &self,
+        address: &str,
+    ) -> ChainResult<Vec<BitcoinTransaction>> {
+        let client = self.get_client().await?;
+        let transactions = client.get_address_mempool_txs(address).await?;
+        Ok(transactions
+            .into_iter()
+            .map(|tx| tx.to_bitcoin_transaction(None))
+            .collect())
+    }
+
+    /// Fetch one Bitcoin transaction (native format)
+    pub async fn fetch_transaction(&self, txid: &str) -> ChainResult<BitcoinTransaction> {
+        let client = self.get_client().await?;
+        let current_height = client.get_block_height().await.ok();
+        let mempool_tx = client.get_transaction(txid).await?;
+        Ok(mempool_tx.to_bitcoin_transaction(current_height))
+    }
+
+    /// Check whether an unconfirmed transaction has confirmed, been
+    /// replaced, or been dropped
+    pub async fn check_pending(
+        &self,
+        txid: &str,
+        inputs: &[TxOutpoint],
+    ) -> ChainResult<PendingTxState> {
+        let client = self.get_client().await?;
+        replacement::check_pending(&client, txid, inputs).await
+    }
+
     /// Fetch UTXOs for an address
     pub async fn fetch_utxos(&self, address: &str) -> ChainResult<Vec<BitcoinUtxo>> {
         let client = self.get_client().await?;
@@ -211,8 +241,7 @@ impl ChainAdapter for BitcoinAdapter {
     }
 
     async fn disconnect(&mut self) -> ChainResult<()> {
-        let mut guard = self.client.write().await;
-        *guard = None;
+        self.client.take();
         Ok(())
     }
 
@@ -263,6 +292,12 @@ impl ChainAdapter for BitcoinAdapter {
         Ok(self.normalize_transaction(&btc_tx, ""))
     }
 
+    async fn has_activity(&self, address: &str) -> ChainResult<bool> {
+        let client = self.get_client().await?;
+        let info = client.get_address_info(address).await?;
+        Ok(info.chain_stats.tx_count + info.mempool_stats.tx_count > 0)
+    }
+
     fn validate_address(&self, address: &str) -> bool {
         validate_bitcoin_address(address).is_ok()
     }
@@ -275,7 +310,7 @@ impl ChainAdapter for BitcoinAdapter {
 
 impl BitcoinAdapter {
     /// Convert Bitcoin transaction to normalized ChainTransaction
-    fn normalize_transaction(
+    pub fn normalize_transaction(
         &self,
         tx: &BitcoinTransaction,
         for_address: &str,
@@ -342,6 +377,10 @@ impl BitcoinAdapter {
             status,
             tx_type,
             token_transfers,
+            swaps: Vec::new(),
+            fee_breakdown: None,
+            user_operations: Vec::new(),
+            inner_calls: Vec::new(),
             raw_data: None,
         }
     }
@@ -351,6 +390,18 @@ impl BitcoinAdapter {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_client_is_reused() {
+        let mut adapter = BitcoinAdapter::new().unwrap();
+        let first = adapter.get_client().await.unwrap();
+        let second = adapter.get_client().await.unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        adapter.disconnect().await.unwrap();
+        let fresh = adapter.get_client().await.unwrap();
+        assert!(!Arc::ptr_eq(&first, &fresh));
+    }
+
     #[test]
     fn test_bitcoin_config_mainnet() {
         let config = BitcoinConfig::mainnet();
@@ -0,0 +1,222 @@
+//! Replacement Tracking
+//!
+//! Follows an unconfirmed transaction until it confirms, is replaced, or
+//! drops out of the mempool. A transaction is identified by the outputs it
+//! spends: once any of them is spent by a different transaction, whether an
+//! RBF fee bump or a conflicting double-spend, the original can never
+//! confirm. While it waits, unconfirmed children spending its outputs are
+//! reported as CPFP bumps.
+
+use serde::{Deserialize, Serialize};
+
+use crate::chains::ChainResult;
+
+use super::mempool::MempoolClient;
+use super::types::{BitcoinTransaction, MempoolOutspend};
+
+/// An output a transaction spends.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxOutpoint {
+    /// Transaction that created the output
+    pub txid: String,
+    /// Output index
+    pub vout: u32,
+}
+
+impl TxOutpoint {
+    /// The outputs `tx` spends
+    pub fn spent_by(tx: &BitcoinTransaction) -> Vec<Self> {
+        tx.inputs
+            .iter()
+            .map(|input| Self {
+                txid: input.prev_txid.clone(),
+                vout: input.prev_vout,
+            })
+            .collect()
+    }
+}
+
+/// Where an unconfirmed transaction stands.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum PendingTxState {
+    /// Still in the mempool.
+    Pending {
+        /// Unconfirmed children spending its outputs (CPFP)
+        bumped_by: Vec<String>,
+    },
+    /// Mined.
+    Confirmed {
+        /// Block the transaction confirmed in
+        block_height: Option<u64>,
+        /// Block time as Unix timestamp
+        block_time: Option<i64>,
+    },
+    /// An input was spent by another transaction.
+    Replaced {
+        /// The replacing transaction
+        by: String,
+        /// Whether the replacement has confirmed
+        confirmed: bool,
+    },
+    /// Evicted from the mempool with its inputs unspent.
+    Dropped,
+}
+
+/// Classifies `txid` from the spending status of its inputs and, while it
+/// is pending, of its own outputs.
+pub fn classify(
+    txid: &str,
+    input_spends: &[MempoolOutspend],
+    output_spends: &[MempoolOutspend],
+) -> PendingTxState {
+    if let Some(replacement) = input_spends
+        .iter()
+        .find(|s| s.spent && s.txid.as_deref().is_some_and(|t| t != txid))
+    {
+        return PendingTxState::Replaced {
+            by: replacement.txid.clone().unwrap_or_default(),
+            confirmed: replacement.status.as_ref().is_some_and(|s| s.confirmed),
+        };
+    }
+
+    let Some(own) = input_spends
+        .iter()
+        .find(|s| s.spent && s.txid.as_deref() == Some(txid))
+    else {
+        return PendingTxState::Dropped;
+    };
+
+    match own.status.as_ref().filter(|s| s.confirmed) {
+        Some(status) => PendingTxState::Confirmed {
+            block_height: status.block_height,
+            block_time: status.block_time,
+        },
+        None => {
+            let mut bumped_by: Vec<String> = output_spends
+                .iter()
+                .filter(|s| s.spent && !s.status.as_ref().is_some_and(|st| st.confirmed))
+                .filter_map(|s| s.txid.clone())
+                .collect();
+            bumped_by.sort();
+            bumped_by.dedup();
+            PendingTxState::Pending { bumped_by }
+        }
+    }
+}
+
+/// Checks where `txid`, which spends `inputs`, stands now.
+pub async fn check_pending(
+    client: &MempoolClient,
+    txid: &str,
+    inputs: &[TxOutpoint],
+) -> ChainResult<PendingTxState> {
+    let mut input_spends = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        input_spends.push(client.get_outspend(&input.txid, input.vout).await?);
+    }
+
+    let state = classify(txid, &input_spends, &[]);
+    if !matches!(state, PendingTxState::Pending { .. }) {
+        return Ok(state);
+    }
+    let output_spends = client.get_outspends(txid).await?;
+    Ok(classify(txid, &input_spends, &output_spends))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::mock_http::{mount_get, MockServer};
+    use serde_json::json;
+
+    const TXID: &str = "9dbf237a0d9c18a4e51975f28577294ebca7420e6c061eeab8e86a0f5c02bba2";
+    const FUNDING: &str = "48aa8ccd77cbc2a6138f1cd1f73da1dd0e606a3bc7cb4b20c49ba7cdb9ffd476";
+
+    fn spend(txid: Option<&str>, confirmed: bool) -> MempoolOutspend {
+        serde_json::from_value(match txid {
+            Some(txid) => json!({
+                "spent": true,
+                "txid": txid,
+                "vin": 0,
+                "status": { "confirmed": confirmed, "block_height": confirmed.then_some(840_001) }
+            }),
+            None => json!({ "spent": false }),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_classify_states() {
+        assert_eq!(
+            classify(TXID, &[spend(Some(TXID), true)], &[]),
+            PendingTxState::Confirmed {
+                block_height: Some(840_001),
+                block_time: None
+            }
+        );
+        assert_eq!(
+            classify(
+                TXID,
+                &[spend(Some(TXID), false), spend(Some("r1"), false)],
+                &[]
+            ),
+            PendingTxState::Replaced {
+                by: "r1".to_string(),
+                confirmed: false
+            }
+        );
+        assert_eq!(
+            classify(TXID, &[spend(None, false)], &[]),
+            PendingTxState::Dropped
+        );
+
+        // Confirmed children don't bump anything
+        assert_eq!(
+            classify(
+                TXID,
+                &[spend(Some(TXID), false)],
+                &[
+                    spend(Some("c1"), false),
+                    spend(None, false),
+                    spend(Some("c0"), true)
+                ],
+            ),
+            PendingTxState::Pending {
+                bumped_by: vec!["c1".to_string()]
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_pending_detects_replacement() {
+        let server = MockServer::start().await;
+        mount_get(
+            &server,
+            &format!("/tx/{}/outspend/1", FUNDING),
+            &[],
+            json!({
+                "spent": true,
+                "txid": "f00d",
+                "vin": 0,
+                "status": { "confirmed": true, "block_height": 840_001 }
+            }),
+        )
+        .await;
+
+        let client = MempoolClient::with_base_url(&server.uri()).unwrap();
+        let inputs = [TxOutpoint {
+            txid: FUNDING.to_string(),
+            vout: 1,
+        }];
+        let state = check_pending(&client, TXID, &inputs).await.unwrap();
+
+        assert_eq!(
+            state,
+            PendingTxState::Replaced {
+                by: "f00d".to_string(),
+                confirmed: true
+            }
+        );
+    }
+}
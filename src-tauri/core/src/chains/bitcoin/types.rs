@@ -124,6 +124,9 @@ pub struct BitcoinTransaction {
     pub total_input: u64,
     /// Total output value in satoshis
     pub total_output: u64,
+    /// Whether any input signals opt-in replace-by-fee (BIP 125)
+    #[serde(default)]
+    pub rbf_signaled: bool,
 }
 
 /// Simplified input for normalized transaction
@@ -165,6 +168,22 @@ pub struct BitcoinUtxo {
     pub status: BitcoinTxStatus,
 }
 
+/// Spending status of a transaction output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolOutspend {
+    /// Whether the output has been spent, in a block or the mempool
+    pub spent: bool,
+    /// Transaction spending the output (if spent)
+    #[serde(default)]
+    pub txid: Option<String>,
+    /// Input index in the spending transaction (if spent)
+    #[serde(default)]
+    pub vin: Option<u32>,
+    /// Status of the spending transaction (if spent)
+    #[serde(default)]
+    pub status: Option<BitcoinTxStatus>,
+}
+
 /// Bitcoin address balance
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BitcoinBalance {
@@ -213,6 +232,12 @@ pub struct MempoolAddressInfo {
 }
 
 impl MempoolTransaction {
+    /// Whether the transaction opts in to replace-by-fee: BIP 125 treats any
+    /// input with a sequence below `0xfffffffe` as signaling.
+    pub fn signals_rbf(&self) -> bool {
+        self.vin.iter().any(|input| input.sequence < 0xffff_fffe)
+    }
+
     /// Convert to normalized BitcoinTransaction
     pub fn to_bitcoin_transaction(&self, current_height: Option<u64>) -> BitcoinTransaction {
         let inputs: Vec<BitcoinTxInput> = self
@@ -264,6 +289,7 @@ impl MempoolTransaction {
             is_coinbase,
             total_input,
             total_output,
+            rbf_signaled: self.signals_rbf(),
         }
     }
 }
@@ -0,0 +1,89 @@
+//! Short-lived cache for chain reads.
+//!
+//! Balances and block heights are read on every UI refresh but change slowly
+//! relative to that, so [`ChainManager`](super::ChainManager) keeps them for
+//! a few seconds instead of asking the RPC each time.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Map whose entries expire `ttl` after they were inserted.
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    entries: Mutex<HashMap<K, (Instant, V)>>,
+}
+
+impl<K: Eq + Hash, V: Clone> TtlCache<K, V> {
+    /// Creates an empty cache whose entries live for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the value for `key` if it hasn't expired.
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(key) {
+            Some((inserted, value)) if inserted.elapsed() < self.ttl => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Stores `value` for `key`, dropping any expired entries.
+    pub fn insert(&self, key: K, value: V) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, (inserted, _)| inserted.elapsed() < self.ttl);
+        entries.insert(key, (Instant::now(), value));
+    }
+
+    /// Removes the entry for `key`.
+    pub fn invalidate(&self, key: &K) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.remove(key);
+    }
+
+    /// Removes every entry whose key matches `predicate`.
+    pub fn invalidate_where(&self, predicate: impl Fn(&K) -> bool) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|key, _| !predicate(key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_expire() {
+        let cache = TtlCache::new(Duration::from_millis(20));
+        cache.insert("ethereum", 100u64);
+        assert_eq!(cache.get(&"ethereum"), Some(100));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.get(&"ethereum"), None);
+    }
+
+    #[test]
+    fn test_invalidate() {
+        let cache = TtlCache::new(Duration::from_secs(60));
+        cache.insert(("ethereum", "0xa"), 1u64);
+        cache.insert(("ethereum", "0xb"), 2);
+        cache.insert(("polkadot", "1abc"), 3);
+
+        cache.invalidate(&("ethereum", "0xa"));
+        assert_eq!(cache.get(&("ethereum", "0xa")), None);
+        assert_eq!(cache.get(&("ethereum", "0xb")), Some(2));
+
+        cache.invalidate_where(|(chain, _)| *chain == "polkadot");
+        assert_eq!(cache.get(&("polkadot", "1abc")), None);
+        assert_eq!(cache.get(&("ethereum", "0xb")), Some(2));
+    }
+}
@@ -0,0 +1,424 @@
+//! dYdX v4 Indexer Client
+//!
+//! Reads a subaccount's transfers, funding payments, and closed perpetual
+//! positions from the dYdX v4 indexer REST API. Amounts are USDC.
+//!
+//! API documentation: https://docs.dydx.exchange/api_integration-indexer/indexer_api
+
+use std::str::FromStr;
+
+use chrono::DateTime;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::chains::{ChainError, ChainResult, TransactionType};
+use crate::fetchers::{FetcherConfig, ResilientFetcher};
+
+use super::{fetch_error, PerpActivity, PerpVenue};
+
+/// Default dYdX v4 indexer base URL
+const DEFAULT_BASE_URL: &str = "https://indexer.dydx.trade/v4";
+
+/// Records per page requested from the indexer
+const PAGE_SIZE: usize = 100;
+
+/// Rate limit for the public indexer (requests per second)
+const RATE_LIMIT_RPS: u32 = 5;
+
+/// Collateral asset of dYdX v4 subaccounts
+const COLLATERAL_ASSET: &str = "USDC";
+
+/// Decimals of the collateral asset
+const COLLATERAL_DECIMALS: u8 = 6;
+
+// =============================================================================
+// RESPONSE TYPES
+// =============================================================================
+
+/// One side of a transfer.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DydxTransferParty {
+    /// Address of the account.
+    pub address: String,
+    /// Subaccount, absent for the wallet itself.
+    pub subaccount_number: Option<u32>,
+}
+
+/// A deposit, withdrawal, or transfer between subaccounts.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DydxTransfer {
+    /// Indexer ID of the transfer.
+    pub id: String,
+    /// Sending side.
+    pub sender: DydxTransferParty,
+    /// Receiving side.
+    pub recipient: DydxTransferParty,
+    /// Amount in whole units.
+    pub size: String,
+    /// When the transfer was made, RFC 3339.
+    pub created_at: String,
+    /// Block height.
+    pub created_at_height: String,
+    /// Asset transferred.
+    pub symbol: String,
+    /// `DEPOSIT`, `WITHDRAWAL`, `TRANSFER_IN`, or `TRANSFER_OUT`.
+    #[serde(rename = "type")]
+    pub transfer_type: String,
+    /// On-chain transaction hash.
+    pub transaction_hash: Option<String>,
+}
+
+/// A funding payment on an open position.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DydxFundingPayment {
+    /// When funding was settled, RFC 3339.
+    pub created_at: String,
+    /// Block height.
+    pub created_at_height: String,
+    /// Market, e.g. `ETH-USD`.
+    pub ticker: String,
+    /// Amount paid to the subaccount; negative when it paid.
+    pub payment: String,
+}
+
+/// A perpetual position.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DydxPerpetualPosition {
+    /// Market, e.g. `ETH-USD`.
+    pub market: String,
+    /// `OPEN`, `CLOSED`, or `LIQUIDATED`.
+    pub status: String,
+    /// PnL realized over the position's life, including funding.
+    pub realized_pnl: String,
+    /// Funding received over the position's life; negative when paid.
+    pub net_funding: String,
+    /// Block height the position was opened at.
+    pub created_at_height: String,
+    /// When the position was closed, RFC 3339.
+    pub closed_at: Option<String>,
+    /// Block height the position was closed at.
+    pub closed_at_height: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransfersPage {
+    transfers: Vec<DydxTransfer>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FundingPaymentsPage {
+    funding_payments: Vec<DydxFundingPayment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PositionsPage {
+    positions: Vec<DydxPerpetualPosition>,
+}
+
+// =============================================================================
+// CLIENT
+// =============================================================================
+
+/// dYdX v4 indexer client
+pub struct DydxClient {
+    /// Resilient fetcher with Governor rate limiting
+    fetcher: ResilientFetcher,
+    /// Base URL for API requests
+    base_url: String,
+}
+
+impl DydxClient {
+    /// Create a new dYdX client against the public indexer
+    pub fn new() -> ChainResult<Self> {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    /// Create a new dYdX client with custom base URL
+    pub fn with_base_url(base_url: &str) -> ChainResult<Self> {
+        let base_url = base_url.trim_end_matches('/').to_string();
+
+        let config = FetcherConfig {
+            base_url: base_url.clone(),
+            api_key: None,
+            requests_per_second: RATE_LIMIT_RPS,
+            timeout_secs: 30,
+            max_retries: 3,
+            provider: None,
+        };
+
+        let fetcher = ResilientFetcher::new(config)
+            .map_err(|e| ChainError::Internal(format!("Failed to create fetcher: {}", e)))?;
+
+        Ok(Self { fetcher, base_url })
+    }
+
+    /// Fetches every page of `route` for a subaccount, taking the records
+    /// out of each page with `records`.
+    async fn get_all<P: DeserializeOwned, T>(
+        &self,
+        route: &str,
+        address: &str,
+        subaccount: u32,
+        extra: &str,
+        records: impl Fn(P) -> Vec<T>,
+    ) -> ChainResult<Vec<T>> {
+        let mut all = Vec::new();
+        let mut page = 1;
+        loop {
+            let url = format!(
+                "{}/{}?address={}&subaccountNumber={}&limit={}&page={}{}",
+                self.base_url, route, address, subaccount, PAGE_SIZE, page, extra
+            );
+            let text = self.fetcher.get(&url).await.map_err(fetch_error)?;
+            let parsed: P =
+                serde_json::from_str(&text).map_err(|e| ChainError::ParseError(e.to_string()))?;
+            let batch = records(parsed);
+            let count = batch.len();
+            all.extend(batch);
+            if count < PAGE_SIZE {
+                break;
+            }
+            page += 1;
+        }
+        Ok(all)
+    }
+
+    /// Deposits, withdrawals, and transfers of a subaccount
+    pub async fn get_transfers(
+        &self,
+        address: &str,
+        subaccount: u32,
+    ) -> ChainResult<Vec<DydxTransfer>> {
+        self.get_all("transfers", address, subaccount, "", |p: TransfersPage| {
+            p.transfers
+        })
+        .await
+    }
+
+    /// Funding payments of a subaccount
+    pub async fn get_funding_payments(
+        &self,
+        address: &str,
+        subaccount: u32,
+    ) -> ChainResult<Vec<DydxFundingPayment>> {
+        self.get_all(
+            "fundingPayments",
+            address,
+            subaccount,
+            "",
+            |p: FundingPaymentsPage| p.funding_payments,
+        )
+        .await
+    }
+
+    /// Closed perpetual positions of a subaccount
+    pub async fn get_closed_positions(
+        &self,
+        address: &str,
+        subaccount: u32,
+    ) -> ChainResult<Vec<DydxPerpetualPosition>> {
+        self.get_all(
+            "perpetualPositions",
+            address,
+            subaccount,
+            "&status=CLOSED",
+            |p: PositionsPage| p.positions,
+        )
+        .await
+    }
+
+    /// Funding, realized PnL, and collateral movements of a subaccount,
+    /// oldest first
+    pub async fn fetch_activity(
+        &self,
+        address: &str,
+        subaccount: u32,
+    ) -> ChainResult<Vec<PerpActivity>> {
+        let mut activity = Vec::new();
+        for transfer in self.get_transfers(address, subaccount).await? {
+            activity.extend(transfer_activity(&transfer)?);
+        }
+        for payment in self.get_funding_payments(address, subaccount).await? {
+            activity.push(funding_activity(&payment)?);
+        }
+        for position in self.get_closed_positions(address, subaccount).await? {
+            activity.extend(position_activity(&position)?);
+        }
+        activity.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.id.cmp(&b.id)));
+        Ok(activity)
+    }
+}
+
+// =============================================================================
+// NORMALIZATION
+// =============================================================================
+
+fn parse_amount(value: &str) -> ChainResult<Decimal> {
+    Decimal::from_str(value.trim())
+        .map_err(|e| ChainError::ParseError(format!("Invalid amount {}: {}", value, e)))
+}
+
+fn parse_height(value: &str) -> ChainResult<u64> {
+    value
+        .trim()
+        .parse()
+        .map_err(|e| ChainError::ParseError(format!("Invalid height {}: {}", value, e)))
+}
+
+fn parse_time(value: &str) -> ChainResult<i64> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.timestamp())
+        .map_err(|e| ChainError::ParseError(format!("Invalid time {}: {}", value, e)))
+}
+
+/// A deposit or withdrawal as collateral moved in or out. Transfers
+/// between subaccounts move collateral the same way.
+fn transfer_activity(transfer: &DydxTransfer) -> ChainResult<Option<PerpActivity>> {
+    let (tx_type, sign) = match transfer.transfer_type.as_str() {
+        "DEPOSIT" | "TRANSFER_IN" => (TransactionType::CollateralDeposit, Decimal::ONE),
+        "WITHDRAWAL" | "TRANSFER_OUT" => {
+            (TransactionType::CollateralWithdrawal, Decimal::NEGATIVE_ONE)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(PerpActivity {
+        id: format!("dydx:transfer:{}", transfer.id),
+        venue: PerpVenue::Dydx,
+        tx_type,
+        market: None,
+        asset: transfer.symbol.clone(),
+        decimals: COLLATERAL_DECIMALS,
+        amount: parse_amount(&transfer.size)?.abs() * sign,
+        timestamp: parse_time(&transfer.created_at)?,
+        block_number: parse_height(&transfer.created_at_height)?,
+        tx_hash: transfer.transaction_hash.clone(),
+    }))
+}
+
+fn funding_activity(payment: &DydxFundingPayment) -> ChainResult<PerpActivity> {
+    Ok(PerpActivity {
+        id: format!(
+            "dydx:funding:{}:{}",
+            payment.ticker, payment.created_at_height
+        ),
+        venue: PerpVenue::Dydx,
+        tx_type: TransactionType::FundingPayment,
+        market: Some(payment.ticker.clone()),
+        asset: COLLATERAL_ASSET.to_string(),
+        decimals: COLLATERAL_DECIMALS,
+        amount: parse_amount(&payment.payment)?,
+        timestamp: parse_time(&payment.created_at)?,
+        block_number: parse_height(&payment.created_at_height)?,
+        tx_hash: None,
+    })
+}
+
+/// Realized PnL of a closed position, less the funding already imported
+/// as separate payments.
+fn position_activity(position: &DydxPerpetualPosition) -> ChainResult<Option<PerpActivity>> {
+    let (Some(closed_at), Some(closed_height)) = (&position.closed_at, &position.closed_at_height)
+    else {
+        return Ok(None);
+    };
+    let pnl = parse_amount(&position.realized_pnl)? - parse_amount(&position.net_funding)?;
+    if pnl.is_zero() {
+        return Ok(None);
+    }
+    Ok(Some(PerpActivity {
+        id: format!(
+            "dydx:pnl:{}:{}",
+            position.market, position.created_at_height
+        ),
+        venue: PerpVenue::Dydx,
+        tx_type: TransactionType::RealizedPnl,
+        market: Some(position.market.clone()),
+        asset: COLLATERAL_ASSET.to_string(),
+        decimals: COLLATERAL_DECIMALS,
+        amount: pnl,
+        timestamp: parse_time(closed_at)?,
+        block_number: parse_height(closed_height)?,
+        tx_hash: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::mock_http::{fixture, mount_get, MockServer};
+
+    const ADDRESS: &str = "dydx1q8m2kq3f7vw0c4l9nqgd5yq7a8x4r0yk6m3s2e";
+
+    async fn mount_subaccount(server: &MockServer) {
+        let query = [("address", ADDRESS), ("subaccountNumber", "0")];
+        mount_get(server, "/transfers", &query, fixture("dydx/transfers.json")).await;
+        mount_get(
+            server,
+            "/fundingPayments",
+            &query,
+            fixture("dydx/funding_payments.json"),
+        )
+        .await;
+        mount_get(
+            server,
+            "/perpetualPositions",
+            &query,
+            fixture("dydx/perpetual_positions.json"),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_fetch_activity_normalizes_history() {
+        let server = MockServer::start().await;
+        mount_subaccount(&server).await;
+        let client = DydxClient::with_base_url(&server.uri()).unwrap();
+
+        let activity = client.fetch_activity(ADDRESS, 0).await.unwrap();
+        let kinds: Vec<&TransactionType> = activity.iter().map(|a| &a.tx_type).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &TransactionType::CollateralDeposit,
+                &TransactionType::FundingPayment,
+                &TransactionType::FundingPayment,
+                &TransactionType::RealizedPnl,
+                &TransactionType::CollateralWithdrawal,
+            ]
+        );
+
+        let deposit = &activity[0];
+        assert_eq!(deposit.amount, Decimal::from(5000));
+        assert_eq!(deposit.asset, "USDC");
+        assert!(deposit.tx_hash.is_some());
+
+        assert_eq!(activity[1].amount, Decimal::from_str("-1.25").unwrap());
+        assert_eq!(activity[1].market.as_deref(), Some("ETH-USD"));
+
+        // 412.5 realized including 0.75 of net funding
+        let pnl = &activity[3];
+        assert_eq!(pnl.amount, Decimal::from_str("411.75").unwrap());
+        assert_eq!(pnl.id, "dydx:pnl:ETH-USD:1140000");
+
+        assert_eq!(activity[4].amount, Decimal::from(-1500));
+    }
+
+    #[test]
+    fn test_open_position_has_no_pnl() {
+        let position = DydxPerpetualPosition {
+            market: "BTC-USD".to_string(),
+            status: "OPEN".to_string(),
+            realized_pnl: "10".to_string(),
+            net_funding: "0".to_string(),
+            created_at_height: "1".to_string(),
+            closed_at: None,
+            closed_at_height: None,
+        };
+        assert!(position_activity(&position).unwrap().is_none());
+    }
+}
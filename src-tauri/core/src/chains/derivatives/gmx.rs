@@ -0,0 +1,486 @@
+//! GMX v2 Subgraph Client
+//!
+//! Reads an account's executed orders and claimed funding from the GMX v2
+//! (synthetics) subgraph. Increase orders move collateral into a position
+//! and decrease orders move it back out, realizing the position's PnL.
+//! Funding paid is deducted from collateral on the decrease; funding earned
+//! is imported when it is claimed.
+//!
+//! Subgraph schema: https://github.com/gmx-io/gmx-synthetics-subgraph
+
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::chains::units::{from_smallest_units, parse_decimal, U256};
+use crate::chains::{ChainError, ChainResult, TransactionType};
+use crate::fetchers::{FetcherConfig, ResilientFetcher};
+
+use super::{fetch_error, PerpActivity, PerpVenue};
+
+/// GMX v2 subgraph on Arbitrum
+const ARBITRUM_SUBGRAPH_URL: &str =
+    "https://subgraph.satsuma-prod.com/gmx/synthetics-arbitrum-stats/api";
+
+/// GMX v2 subgraph on Avalanche
+const AVALANCHE_SUBGRAPH_URL: &str =
+    "https://subgraph.satsuma-prod.com/gmx/synthetics-avalanche-stats/api";
+
+/// Records per page requested from the subgraph
+const PAGE_SIZE: usize = 1000;
+
+/// Rate limit for the public subgraph (requests per second)
+const RATE_LIMIT_RPS: u32 = 5;
+
+/// Decimals of GMX USD amounts
+const USD_DECIMALS: u8 = 30;
+
+/// Decimals PnL is stored with; GMX USD precision is finer than needed
+const PNL_DECIMALS: u8 = 6;
+
+/// Collateral tokens, by lowercased address, with symbol and decimals.
+const COLLATERAL_TOKENS: &[(&str, &str, u8)] = &[
+    // Arbitrum
+    ("0xaf88d065e77c8cc2239327c5edb3a432268e5831", "USDC", 6),
+    ("0xff970a61a04b1ca14834a43f5de4533ebddb5cc8", "USDC.e", 6),
+    ("0xfd086bc7cd5c481dcc9c85ebe478a1c0b69fcbb9", "USDT", 6),
+    ("0x82af49447d8a07e3bd95bd0d56f35241523fbab1", "WETH", 18),
+    ("0x2f2a2543b76a4166549f7aab2e75bef0aefc5b0f", "WBTC", 8),
+    ("0x912ce59144191c1204e64559fe8253a0e49e6548", "ARB", 18),
+    // Avalanche
+    ("0xb97ef9ef8734c71904d8002f8b6bc66dd9c48a6e", "USDC", 6),
+    ("0xb31f66aa3c1e785363f0875a1b74e27b85fd66c7", "WAVAX", 18),
+];
+
+/// Order types that add to a position.
+const INCREASE_ORDER_TYPES: &[u8] = &[2, 3];
+
+/// Order types that reduce or close a position, including liquidations.
+const DECREASE_ORDER_TYPES: &[u8] = &[4, 5, 6, 7];
+
+const TRADE_ACTIONS_QUERY: &str = r#"
+query TradeActions($account: String!, $first: Int!, $skip: Int!) {
+  tradeActions(
+    first: $first
+    skip: $skip
+    orderBy: timestamp
+    orderDirection: asc
+    where: { account: $account, eventName: "OrderExecuted" }
+  ) {
+    id
+    orderType
+    marketAddress
+    initialCollateralTokenAddress
+    initialCollateralDeltaAmount
+    pnlUsd
+    timestamp
+    transaction { hash blockNumber }
+  }
+}
+"#;
+
+const CLAIM_ACTIONS_QUERY: &str = r#"
+query ClaimActions($account: String!, $first: Int!, $skip: Int!) {
+  claimActions(
+    first: $first
+    skip: $skip
+    orderBy: timestamp
+    orderDirection: asc
+    where: { account: $account, eventName: "ClaimFunding" }
+  ) {
+    id
+    marketAddresses
+    tokenAddresses
+    amounts
+    timestamp
+    transaction { hash blockNumber }
+  }
+}
+"#;
+
+// =============================================================================
+// RESPONSE TYPES
+// =============================================================================
+
+/// Transaction a subgraph entity was recorded in.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmxTransaction {
+    /// Transaction hash.
+    pub hash: String,
+    /// Block number.
+    pub block_number: String,
+}
+
+/// An executed order.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmxTradeAction {
+    /// Subgraph ID of the action.
+    pub id: String,
+    /// GMX order type; 2-3 increase and 4-7 decrease a position.
+    pub order_type: u8,
+    /// Market the order was executed in.
+    pub market_address: String,
+    /// Collateral token of the order.
+    pub initial_collateral_token_address: Option<String>,
+    /// Collateral moved, in the token's smallest units.
+    pub initial_collateral_delta_amount: Option<String>,
+    /// Realized PnL in 30-decimal USD; set on decreases.
+    pub pnl_usd: Option<String>,
+    /// Block time in seconds.
+    pub timestamp: i64,
+    /// Transaction the order executed in.
+    pub transaction: GmxTransaction,
+}
+
+/// A claim of funding fees earned across one or more markets.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GmxClaimAction {
+    /// Subgraph ID of the action.
+    pub id: String,
+    /// Market of each claimed amount.
+    pub market_addresses: Vec<String>,
+    /// Token of each claimed amount.
+    pub token_addresses: Vec<String>,
+    /// Amounts claimed, in each token's smallest units.
+    pub amounts: Vec<String>,
+    /// Block time in seconds.
+    pub timestamp: i64,
+    /// Transaction the claim was made in.
+    pub transaction: GmxTransaction,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlResponse<T> {
+    data: Option<T>,
+    errors: Option<Vec<GraphqlError>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TradeActionsData {
+    trade_actions: Vec<GmxTradeAction>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClaimActionsData {
+    claim_actions: Vec<GmxClaimAction>,
+}
+
+// =============================================================================
+// CLIENT
+// =============================================================================
+
+/// GMX v2 subgraph client
+pub struct GmxClient {
+    /// Resilient fetcher with Governor rate limiting
+    fetcher: ResilientFetcher,
+    /// GraphQL endpoint
+    endpoint: String,
+}
+
+impl GmxClient {
+    /// Create a client for the subgraph of `chain`, `arbitrum` or `avalanche`
+    pub fn for_chain(chain: &str) -> ChainResult<Self> {
+        match chain.to_lowercase().as_str() {
+            "arbitrum" => Self::with_endpoint(ARBITRUM_SUBGRAPH_URL),
+            "avalanche" => Self::with_endpoint(AVALANCHE_SUBGRAPH_URL),
+            other => Err(ChainError::UnsupportedChain(format!(
+                "GMX is not deployed on {}",
+                other
+            ))),
+        }
+    }
+
+    /// Create a client against a custom GraphQL endpoint
+    pub fn with_endpoint(endpoint: &str) -> ChainResult<Self> {
+        let config = FetcherConfig {
+            base_url: endpoint.to_string(),
+            api_key: None,
+            requests_per_second: RATE_LIMIT_RPS,
+            timeout_secs: 30,
+            max_retries: 3,
+            provider: None,
+        };
+
+        let fetcher = ResilientFetcher::new(config)
+            .map_err(|e| ChainError::Internal(format!("Failed to create fetcher: {}", e)))?;
+
+        Ok(Self {
+            fetcher,
+            endpoint: endpoint.to_string(),
+        })
+    }
+
+    /// Runs `query` for `account` page by page, taking the records out of
+    /// each page with `records`.
+    async fn query_all<D: DeserializeOwned, T>(
+        &self,
+        query: &str,
+        account: &str,
+        records: impl Fn(D) -> Vec<T>,
+    ) -> ChainResult<Vec<T>> {
+        let mut all = Vec::new();
+        let mut skip = 0;
+        loop {
+            let body = json!({
+                "query": query,
+                "variables": { "account": account.to_lowercase(), "first": PAGE_SIZE, "skip": skip },
+            });
+            let text = self
+                .fetcher
+                .post(&self.endpoint, &body)
+                .await
+                .map_err(fetch_error)?;
+            let response: GraphqlResponse<D> =
+                serde_json::from_str(&text).map_err(|e| ChainError::ParseError(e.to_string()))?;
+            if let Some(error) = response.errors.and_then(|e| e.into_iter().next()) {
+                return Err(ChainError::ApiError(format!(
+                    "GMX subgraph error: {}",
+                    error.message
+                )));
+            }
+            let data = response.data.ok_or_else(|| {
+                ChainError::ParseError("GMX subgraph response missing data".to_string())
+            })?;
+            let batch = records(data);
+            let count = batch.len();
+            all.extend(batch);
+            if count < PAGE_SIZE {
+                break;
+            }
+            skip += PAGE_SIZE;
+        }
+        Ok(all)
+    }
+
+    /// Executed orders of an account, oldest first
+    pub async fn get_trade_actions(&self, account: &str) -> ChainResult<Vec<GmxTradeAction>> {
+        self.query_all(TRADE_ACTIONS_QUERY, account, |d: TradeActionsData| {
+            d.trade_actions
+        })
+        .await
+    }
+
+    /// Funding claims of an account, oldest first
+    pub async fn get_claim_actions(&self, account: &str) -> ChainResult<Vec<GmxClaimAction>> {
+        self.query_all(CLAIM_ACTIONS_QUERY, account, |d: ClaimActionsData| {
+            d.claim_actions
+        })
+        .await
+    }
+
+    /// Funding, realized PnL, and collateral movements of an account,
+    /// oldest first
+    pub async fn fetch_activity(&self, account: &str) -> ChainResult<Vec<PerpActivity>> {
+        let mut activity = Vec::new();
+        for action in self.get_trade_actions(account).await? {
+            activity.extend(trade_activity(&action)?);
+        }
+        for claim in self.get_claim_actions(account).await? {
+            activity.extend(claim_activity(&claim)?);
+        }
+        activity.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.id.cmp(&b.id)));
+        Ok(activity)
+    }
+}
+
+// =============================================================================
+// NORMALIZATION
+// =============================================================================
+
+/// Symbol and decimals of a collateral token; unknown tokens keep their
+/// address and are assumed to have 18 decimals.
+fn collateral_token(address: &str) -> (String, u8) {
+    let address = address.to_lowercase();
+    COLLATERAL_TOKENS
+        .iter()
+        .find(|(a, _, _)| *a == address)
+        .map(|(_, symbol, decimals)| (symbol.to_string(), *decimals))
+        .unwrap_or((address, 18))
+}
+
+/// Parses a signed integer amount and converts it to whole units.
+fn signed_units(raw: &str, decimals: u8) -> ChainResult<Decimal> {
+    let raw = raw.trim();
+    let (negative, digits) = match raw.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, raw),
+    };
+    let value = from_smallest_units(parse_decimal(digits)?, decimals)
+        .ok_or_else(|| ChainError::ParseError(format!("Amount out of range: {}", raw)))?;
+    Ok(if negative { -value } else { value })
+}
+
+/// A 30-decimal USD amount, truncated to [`PNL_DECIMALS`].
+fn usd_units(raw: &str) -> ChainResult<Decimal> {
+    let raw = raw.trim();
+    let (sign, digits) = match raw.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", raw),
+    };
+    let scale = U256::from(10u64).pow(U256::from(USD_DECIMALS - PNL_DECIMALS));
+    let reduced = parse_decimal(digits)? / scale;
+    signed_units(&format!("{}{}", sign, reduced), PNL_DECIMALS)
+}
+
+fn parse_block(transaction: &GmxTransaction) -> ChainResult<u64> {
+    u64::from_str(transaction.block_number.trim()).map_err(|e| {
+        ChainError::ParseError(format!("Invalid block {}: {}", transaction.block_number, e))
+    })
+}
+
+/// Collateral moved by an executed order, and the PnL a decrease realized.
+fn trade_activity(action: &GmxTradeAction) -> ChainResult<Vec<PerpActivity>> {
+    let increase = INCREASE_ORDER_TYPES.contains(&action.order_type);
+    let decrease = DECREASE_ORDER_TYPES.contains(&action.order_type);
+    if !increase && !decrease {
+        return Ok(Vec::new());
+    }
+
+    let block_number = parse_block(&action.transaction)?;
+    let mut activity = Vec::new();
+
+    if let (Some(token), Some(delta)) = (
+        &action.initial_collateral_token_address,
+        &action.initial_collateral_delta_amount,
+    ) {
+        let (asset, decimals) = collateral_token(token);
+        let amount = signed_units(delta, decimals)?.abs();
+        if !amount.is_zero() {
+            let (tx_type, amount) = if increase {
+                (TransactionType::CollateralDeposit, amount)
+            } else {
+                (TransactionType::CollateralWithdrawal, -amount)
+            };
+            activity.push(PerpActivity {
+                id: format!("gmx:collateral:{}", action.id),
+                venue: PerpVenue::Gmx,
+                tx_type,
+                market: Some(action.market_address.clone()),
+                asset,
+                decimals,
+                amount,
+                timestamp: action.timestamp,
+                block_number,
+                tx_hash: Some(action.transaction.hash.clone()),
+            });
+        }
+    }
+
+    if decrease {
+        if let Some(pnl) = &action.pnl_usd {
+            let amount = usd_units(pnl)?;
+            if !amount.is_zero() {
+                activity.push(PerpActivity {
+                    id: format!("gmx:pnl:{}", action.id),
+                    venue: PerpVenue::Gmx,
+                    tx_type: TransactionType::RealizedPnl,
+                    market: Some(action.market_address.clone()),
+                    asset: "USD".to_string(),
+                    decimals: PNL_DECIMALS,
+                    amount,
+                    timestamp: action.timestamp,
+                    block_number,
+                    tx_hash: Some(action.transaction.hash.clone()),
+                });
+            }
+        }
+    }
+
+    Ok(activity)
+}
+
+/// One funding payment per market and token claimed.
+fn claim_activity(claim: &GmxClaimAction) -> ChainResult<Vec<PerpActivity>> {
+    let block_number = parse_block(&claim.transaction)?;
+    claim
+        .token_addresses
+        .iter()
+        .zip(&claim.amounts)
+        .enumerate()
+        .map(|(i, (token, amount))| {
+            let (asset, decimals) = collateral_token(token);
+            Ok(PerpActivity {
+                id: format!("gmx:funding:{}:{}", claim.id, i),
+                venue: PerpVenue::Gmx,
+                tx_type: TransactionType::FundingPayment,
+                market: claim.market_addresses.get(i).cloned(),
+                asset,
+                decimals,
+                amount: signed_units(amount, decimals)?,
+                timestamp: claim.timestamp,
+                block_number,
+                tx_hash: Some(claim.transaction.hash.clone()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::mock_http::{fixture, mount_graphql, MockServer};
+
+    const ACCOUNT: &str = "0x7F3a2b9C4d1E6f8A0B5c7D9e1F3a5B7c9D0e2F4a";
+
+    #[tokio::test]
+    async fn test_fetch_activity_normalizes_orders_and_claims() {
+        let server = MockServer::start().await;
+        mount_graphql(&server, "tradeActions", fixture("gmx/trade_actions.json")).await;
+        mount_graphql(&server, "claimActions", fixture("gmx/claim_actions.json")).await;
+        let client = GmxClient::with_endpoint(&server.uri()).unwrap();
+
+        let activity = client.fetch_activity(ACCOUNT).await.unwrap();
+        let kinds: Vec<&TransactionType> = activity.iter().map(|a| &a.tx_type).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &TransactionType::CollateralDeposit,
+                &TransactionType::FundingPayment,
+                &TransactionType::CollateralWithdrawal,
+                &TransactionType::RealizedPnl,
+            ]
+        );
+
+        let deposit = &activity[0];
+        assert_eq!(deposit.asset, "USDC");
+        assert_eq!(deposit.amount, Decimal::from(2000));
+
+        assert_eq!(activity[1].amount, Decimal::from_str("3.5").unwrap());
+
+        assert_eq!(activity[2].amount, Decimal::from(-2000));
+        let pnl = &activity[3];
+        assert_eq!(pnl.asset, "USD");
+        assert_eq!(pnl.amount, Decimal::from_str("-154.321098").unwrap());
+        assert_eq!(pnl.tx_hash, activity[2].tx_hash);
+    }
+
+    #[test]
+    fn test_unknown_collateral_keeps_address() {
+        let (asset, decimals) = collateral_token("0xAbC0000000000000000000000000000000000001");
+        assert_eq!(asset, "0xabc0000000000000000000000000000000000001");
+        assert_eq!(decimals, 18);
+        assert_eq!(
+            collateral_token("0xAF88d065e77c8cC2239327C5EDb3A432268e5831").0,
+            "USDC"
+        );
+    }
+
+    #[test]
+    fn test_for_chain_rejects_unsupported() {
+        assert!(GmxClient::for_chain("Arbitrum").is_ok());
+        assert!(GmxClient::for_chain("ethereum").is_err());
+    }
+}
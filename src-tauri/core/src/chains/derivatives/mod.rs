@@ -0,0 +1,78 @@
+//! Perpetual Futures Venues
+//!
+//! Imports trading history from derivatives exchanges whose activity doesn't
+//! show up as ordinary token transfers: funding payments, realized PnL on
+//! closed positions, and collateral moved in and out of the trading account.
+//! Each venue's history is normalized to [`PerpActivity`] items, which are
+//! stored with the account's other transactions.
+
+/// dYdX v4 indexer client.
+pub mod dydx;
+/// GMX v2 subgraph client.
+pub mod gmx;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::fetchers::FetchError;
+
+use super::{ChainError, TransactionType};
+
+/// A derivatives exchange history can be imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PerpVenue {
+    /// dYdX v4, read from its indexer.
+    Dydx,
+    /// GMX v2 on Arbitrum or Avalanche, read from its subgraph.
+    Gmx,
+}
+
+impl PerpVenue {
+    /// Venue name, used as the counterparty of imported activity.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PerpVenue::Dydx => "dydx",
+            PerpVenue::Gmx => "gmx",
+        }
+    }
+}
+
+/// One funding payment, realized PnL, or collateral movement on a
+/// derivatives account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerpActivity {
+    /// Stable identifier, unique per account; stored as the transaction hash.
+    pub id: String,
+    /// Venue the activity happened on.
+    pub venue: PerpVenue,
+    /// `FundingPayment`, `RealizedPnl`, `CollateralDeposit`, or
+    /// `CollateralWithdrawal`.
+    pub tx_type: TransactionType,
+    /// Market traded, e.g. `ETH-USD`; `None` for collateral movements.
+    pub market: Option<String>,
+    /// Asset the amount is settled in.
+    pub asset: String,
+    /// Decimals of the settlement asset.
+    pub decimals: u8,
+    /// Amount in whole units; positive when credited to the account.
+    pub amount: Decimal,
+    /// When it happened, in seconds since the Unix epoch.
+    pub timestamp: i64,
+    /// Block or indexer height.
+    pub block_number: u64,
+    /// On-chain transaction, where the venue reports one.
+    pub tx_hash: Option<String>,
+}
+
+/// Converts a fetcher error from a venue request.
+pub(super) fn fetch_error(error: FetchError) -> ChainError {
+    match error {
+        FetchError::RateLimited => ChainError::RateLimited,
+        FetchError::Timeout => ChainError::ConnectionFailed("Request timeout".to_string()),
+        FetchError::HttpError(msg) => ChainError::ApiError(msg),
+        FetchError::ParseError(msg) => ChainError::ParseError(msg),
+        FetchError::ApiError(msg) => ChainError::ApiError(msg),
+        FetchError::ConfigError(msg) => ChainError::ConfigError(msg),
+    }
+}
@@ -0,0 +1,226 @@
+//! First-run wallet discovery.
+//!
+//! Given a single address, works out which supported chains it could belong
+//! to, asks each of them at once whether the address has been used there,
+//! and suggests a wallet for every chain with activity. A new user pastes
+//! one address instead of adding the same account chain by chain.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinSet;
+use tokio::time::timeout;
+
+use super::address::{validate_any_address, AddressValidation};
+use super::{format_chain_name, ChainManager, ChainManagerState, ChainType};
+use crate::log_error;
+
+/// How long one chain may take to answer before it is reported as failed.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Wallet type given to suggested wallets: watched by address, no keys.
+const SUGGESTED_WALLET_TYPE: &str = "external";
+
+/// A chain to ask about an address.
+#[derive(Debug, Clone, PartialEq)]
+struct Candidate {
+    chain_id: String,
+    chain_type: ChainType,
+    /// The address in the chain family's canonical form.
+    address: String,
+}
+
+/// Whether an address has been used on one chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainProbe {
+    /// Chain asked.
+    pub chain_id: String,
+    /// Chain family.
+    pub chain_type: ChainType,
+    /// Whether the address has a balance or transactions on the chain.
+    pub active: bool,
+    /// Why the chain couldn't be asked, if it couldn't.
+    pub error: Option<String>,
+}
+
+/// A wallet the wizard offers to create.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletSuggestion {
+    /// Chain the wallet is on.
+    pub chain_id: String,
+    /// Chain family.
+    pub chain_type: ChainType,
+    /// Address in the chain family's canonical form.
+    pub address: String,
+    /// Suggested display name.
+    pub name: String,
+    /// Suggested wallet type.
+    pub wallet_type: String,
+}
+
+/// Result of probing every matching chain for an address.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletDiscovery {
+    /// Address as given, trimmed.
+    pub address: String,
+    /// One wallet per chain with activity, by chain.
+    pub suggestions: Vec<WalletSuggestion>,
+    /// Every chain asked, by chain.
+    pub probes: Vec<ChainProbe>,
+    /// Problems with the address itself, such as a failed EVM checksum.
+    pub warnings: Vec<String>,
+}
+
+/// Chains `validation` matched, leaving out testnets unless asked for.
+fn candidates(validation: &AddressValidation, include_testnets: bool) -> Vec<Candidate> {
+    let testnets: HashSet<String> = ChainManager::get_supported_chains()
+        .into_iter()
+        .filter(|c| c.is_testnet)
+        .map(|c| c.chain_id)
+        .collect();
+
+    validation
+        .matches
+        .iter()
+        .flat_map(|m| {
+            m.chain_ids.iter().map(|chain_id| Candidate {
+                chain_id: chain_id.clone(),
+                chain_type: m.chain_type,
+                address: m.normalized.clone(),
+            })
+        })
+        .filter(|c| include_testnets || !testnets.contains(&c.chain_id))
+        .collect()
+}
+
+/// Asks one chain whether `candidate.address` has been used there.
+async fn probe(chains: ChainManagerState, candidate: Candidate) -> ChainProbe {
+    let answer = timeout(PROBE_TIMEOUT, async {
+        let manager = chains.read().await;
+        manager
+            .has_activity(&candidate.chain_id, &candidate.address)
+            .await
+    })
+    .await;
+    let (active, error) = match answer {
+        Ok(Ok(active)) => (active, None),
+        Ok(Err(e)) => (false, Some(e.to_string())),
+        Err(_) => (
+            false,
+            Some(format!(
+                "No answer within {} seconds",
+                PROBE_TIMEOUT.as_secs()
+            )),
+        ),
+    };
+    ChainProbe {
+        chain_id: candidate.chain_id,
+        chain_type: candidate.chain_type,
+        active,
+        error,
+    }
+}
+
+/// Probes every mainnet `address` could belong to, or testnets too when
+/// `include_testnets` is set, and suggests a wallet for each with activity.
+/// Chains that fail to answer are reported in the probes and never
+/// suggested.
+pub async fn discover_wallets(
+    chains: &ChainManagerState,
+    address: &str,
+    include_testnets: bool,
+) -> WalletDiscovery {
+    let validation = validate_any_address(address);
+    let mut warnings = validation.warnings.clone();
+    if validation.matches.is_empty() {
+        warnings.push("Address is not valid on any supported chain".to_string());
+    }
+
+    let candidates = candidates(&validation, include_testnets);
+    let addresses: Vec<(String, String)> = candidates
+        .iter()
+        .map(|c| (c.chain_id.clone(), c.address.clone()))
+        .collect();
+
+    let mut tasks = JoinSet::new();
+    for candidate in candidates {
+        tasks.spawn(probe(Arc::clone(chains), candidate));
+    }
+    let mut probes = Vec::with_capacity(addresses.len());
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(probe) => probes.push(probe),
+            Err(e) => log_error!("Chain probe failed: {e}"),
+        }
+    }
+    probes.sort_by(|a, b| a.chain_id.cmp(&b.chain_id));
+
+    let suggestions = probes
+        .iter()
+        .filter(|p| p.active)
+        .filter_map(|p| {
+            let (_, address) = addresses.iter().find(|(id, _)| *id == p.chain_id)?;
+            Some(WalletSuggestion {
+                chain_id: p.chain_id.clone(),
+                chain_type: p.chain_type,
+                address: address.clone(),
+                name: format!("{} wallet", format_chain_name(&p.chain_id)),
+                wallet_type: SUGGESTED_WALLET_TYPE.to_string(),
+            })
+        })
+        .collect();
+
+    WalletDiscovery {
+        address: validation.address,
+        suggestions,
+        probes,
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates_use_normalized_address() {
+        let address = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045";
+        let found = candidates(&validate_any_address(address), false);
+
+        let ethereum = found.iter().find(|c| c.chain_id == "ethereum").unwrap();
+        assert_eq!(ethereum.chain_type, ChainType::Evm);
+        assert_eq!(
+            ethereum.address,
+            "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"
+        );
+        assert!(found.iter().all(|c| c.chain_type == ChainType::Evm));
+    }
+
+    #[test]
+    fn test_candidates_skip_testnets() {
+        let validation = validate_any_address("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx");
+        assert!(candidates(&validation, false).is_empty());
+
+        let testnets: Vec<String> = candidates(&validation, true)
+            .into_iter()
+            .map(|c| c.chain_id)
+            .collect();
+        assert!(testnets.contains(&"bitcoin_testnet".to_string()));
+        assert!(testnets.contains(&"bitcoin_signet".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_address_probes_nothing() {
+        let chains = crate::chains::create_chain_manager_state();
+        let discovery = discover_wallets(&chains, "not-an-address", false).await;
+
+        assert!(discovery.probes.is_empty());
+        assert!(discovery.suggestions.is_empty());
+        assert_eq!(discovery.warnings.len(), 1);
+    }
+}
@@ -3,14 +3,22 @@
 //! Provides RPC access to EVM chains via Alchemy or any standard JSON-RPC endpoint.
 //! Used for real-time balance queries, contract reads, gas estimation, and other
 //! operations that Etherscan doesn't provide well.
+//!
+//! A client can hold several endpoints for a chain. Each call goes to the
+//! healthy endpoint with the lowest observed latency; an endpoint that fails
+//! is skipped for the rest of the call, and rested for a while after repeated
+//! failures, so one flaky public endpoint doesn't break sync.
 
 use super::config::{get_chain_config, EvmChainConfig};
+use crate::chains::units::{self, U256};
 use crate::chains::{ChainError, ChainResult, NativeBalance, TokenBalance};
+use crate::fetchers::{usage, ApiProvider};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Duration;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 // =============================================================================
 // JSON-RPC TYPES
@@ -93,6 +101,23 @@ pub struct RpcTransaction {
     /// Access list (EIP-2930)
     #[serde(default)]
     pub access_list: Option<Vec<AccessListItem>>,
+    /// EIP-4844 max fee per blob gas
+    #[serde(default)]
+    pub max_fee_per_blob_gas: Option<String>,
+    /// EIP-4844 versioned hashes of the blobs carried
+    #[serde(default)]
+    pub blob_versioned_hashes: Option<Vec<String>>,
+}
+
+impl RpcTransaction {
+    /// Check if this is an EIP-4844 blob-carrying transaction
+    pub fn is_blob(&self) -> bool {
+        self.tx_type.as_deref() == Some("0x3")
+            || self
+                .blob_versioned_hashes
+                .as_ref()
+                .is_some_and(|hashes| !hashes.is_empty())
+    }
 }
 
 /// Access list item for EIP-2930 transactions
@@ -142,6 +167,18 @@ pub struct TransactionReceipt {
     /// State root (pre-Byzantium)
     #[serde(default)]
     pub root: Option<String>,
+    /// L1 data fee charged on top of L2 gas (OP Stack)
+    #[serde(default)]
+    pub l1_fee: Option<String>,
+    /// Gas of the L1 data fee, included in `gas_used` (Arbitrum)
+    #[serde(default, rename = "gasUsedForL1")]
+    pub gas_used_for_l1: Option<String>,
+    /// Blob gas used (EIP-4844)
+    #[serde(default)]
+    pub blob_gas_used: Option<String>,
+    /// Price per blob gas charged (EIP-4844)
+    #[serde(default)]
+    pub blob_gas_price: Option<String>,
 }
 
 impl TransactionReceipt {
@@ -245,14 +282,121 @@ impl Block {
         hex_to_u64(&self.timestamp).unwrap_or(0)
     }
 
-    /// Get base fee as u128
-    pub fn base_fee_u128(&self) -> Option<u128> {
+    /// Get base fee
+    pub fn base_fee(&self) -> Option<U256> {
         self.base_fee_per_gas
             .as_ref()
-            .and_then(|f| hex_to_u128(f).ok())
+            .and_then(|f| units::parse_hex(f).ok())
     }
 }
 
+// =============================================================================
+// ENDPOINT HEALTH
+// =============================================================================
+
+/// Consecutive failures after which an endpoint is rested.
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// How long a rested endpoint is tried only after every other endpoint.
+const ENDPOINT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Weight of the latest response time in an endpoint's smoothed latency.
+const LATENCY_SMOOTHING: f64 = 0.3;
+
+/// JSON-RPC error code some public endpoints return when rate limiting.
+const RPC_LIMIT_EXCEEDED: i64 = -32005;
+
+/// Phrases in `eth_getLogs` errors from endpoints that refuse a block range
+/// as too wide or matching too many logs.
+const LOG_RANGE_ERRORS: &[&str] = &[
+    "query returned more than",
+    "block range",
+    "range is too large",
+    "range too large",
+    "too many results",
+    "response size exceeded",
+    "exceeds max results",
+];
+
+/// Whether an `eth_getLogs` error means a narrower range may succeed
+fn is_log_range_error(error: &ChainError) -> bool {
+    match error {
+        ChainError::RpcError(message) => {
+            let message = message.to_lowercase();
+            LOG_RANGE_ERRORS
+                .iter()
+                .any(|phrase| message.contains(phrase))
+        }
+        _ => false,
+    }
+}
+
+/// How an endpoint has been responding.
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    /// Smoothed response time, unknown until the endpoint first answers.
+    latency_ms: Option<f64>,
+    /// Failures since the endpoint last answered.
+    consecutive_failures: u32,
+    /// When a rested endpoint is preferred again.
+    cooldown_until: Option<Instant>,
+}
+
+/// One JSON-RPC endpoint of a chain.
+#[derive(Debug)]
+struct RpcEndpoint {
+    url: String,
+    /// Whether calls count against Alchemy usage (the URL is an Alchemy endpoint).
+    metered: bool,
+    health: Mutex<EndpointHealth>,
+}
+
+impl RpcEndpoint {
+    fn new(url: String) -> Self {
+        Self {
+            metered: url.contains(".alchemy.com"),
+            url,
+            health: Mutex::new(EndpointHealth::default()),
+        }
+    }
+
+    fn record_success(&self, elapsed: Duration) {
+        let mut health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let ms = elapsed.as_secs_f64() * 1000.0;
+        health.latency_ms = Some(match health.latency_ms {
+            Some(prev) => prev + LATENCY_SMOOTHING * (ms - prev),
+            None => ms,
+        });
+        health.consecutive_failures = 0;
+        health.cooldown_until = None;
+    }
+
+    fn record_failure(&self) {
+        let mut health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        health.consecutive_failures += 1;
+        if health.consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+            health.cooldown_until = Some(Instant::now() + ENDPOINT_COOLDOWN);
+        }
+    }
+
+    /// Sort key: rested endpoints last, then by latency. Endpoints not yet
+    /// measured sort after measured ones, keeping the configured order.
+    fn rank(&self, now: Instant) -> (bool, f64) {
+        let health = self.health.lock().unwrap_or_else(|e| e.into_inner());
+        let resting = health.cooldown_until.is_some_and(|until| until > now);
+        (resting, health.latency_ms.unwrap_or(f64::MAX))
+    }
+}
+
+/// Failure of one endpoint during a call.
+enum EndpointError {
+    /// The endpoint is unreachable, rate limited, or answered badly; the
+    /// call moves on to the next endpoint.
+    Unavailable(ChainError),
+    /// The request itself was rejected; another endpoint would reject it too.
+    Request(ChainError),
+}
+
 // =============================================================================
 // ALCHEMY CLIENT
 // =============================================================================
@@ -260,7 +404,8 @@ impl Block {
 /// Alchemy/JSON-RPC client for EVM chains
 pub struct AlchemyClient {
     client: Client,
-    rpc_url: String,
+    /// Endpoints in configured order of preference; never empty.
+    endpoints: Vec<RpcEndpoint>,
     chain_config: EvmChainConfig,
     request_id: AtomicU64,
 }
@@ -274,32 +419,50 @@ impl AlchemyClient {
             .ok_or_else(|| ChainError::UnsupportedChain(format!("chain_id: {}", chain_id)))?;
 
         // If API key provided, construct URL with it
-        let rpc_url = if let Some(key) = api_key {
-            format!("{}/{}", config.rpc_url, key)
+        let rpc_urls = if let Some(key) = api_key {
+            let mut urls = vec![format!("{}/{}", config.rpc_url, key)];
+            urls.extend(config.fallback_rpc_urls.iter().cloned());
+            urls
         } else {
             config
-                .get_rpc_url()
+                .get_rpc_urls()
                 .map_err(|e| ChainError::ConfigError(e.to_string()))?
         };
 
-        Self::with_url(&config, &rpc_url)
+        Self::with_urls(&config, rpc_urls)
     }
 
     /// Create a new RPC client from config
-    pub fn new(config: &EvmChainConfig, rpc_url: Option<&str>) -> ChainResult<Self> {
-        let url = if let Some(override_url) = rpc_url {
-            override_url.to_string()
-        } else {
+    ///
+    /// Uses the given endpoints when any are set, otherwise the chain's
+    /// primary endpoint followed by its public fallbacks.
+    pub fn new(config: &EvmChainConfig, rpc_urls: &[String]) -> ChainResult<Self> {
+        let urls = if rpc_urls.is_empty() {
             config
-                .get_rpc_url()
+                .get_rpc_urls()
                 .map_err(|e| ChainError::ConfigError(e.to_string()))?
+        } else {
+            rpc_urls.to_vec()
         };
 
-        Self::with_url(config, &url)
+        Self::with_urls(config, urls)
     }
 
     /// Create a new RPC client with explicit URL
     pub fn with_url(config: &EvmChainConfig, rpc_url: &str) -> ChainResult<Self> {
+        Self::with_urls(config, vec![rpc_url.to_string()])
+    }
+
+    /// Create a new RPC client that fails over between explicit URLs, in
+    /// order of preference
+    pub fn with_urls(config: &EvmChainConfig, rpc_urls: Vec<String>) -> ChainResult<Self> {
+        if rpc_urls.is_empty() {
+            return Err(ChainError::ConfigError(format!(
+                "No RPC endpoints configured for {}",
+                config.name
+            )));
+        }
+
         let client = Client::builder()
             .timeout(Duration::from_secs(30))
             .build()
@@ -307,7 +470,7 @@ impl AlchemyClient {
 
         Ok(Self {
             client,
-            rpc_url: rpc_url.to_string(),
+            endpoints: rpc_urls.into_iter().map(RpcEndpoint::new).collect(),
             chain_config: config.clone(),
             request_id: AtomicU64::new(1),
         })
@@ -318,9 +481,23 @@ impl AlchemyClient {
         &self.chain_config
     }
 
-    /// Get the RPC URL
+    /// Get the primary RPC URL
     pub fn rpc_url(&self) -> &str {
-        &self.rpc_url
+        &self.endpoints[0].url
+    }
+
+    /// Get every RPC URL, in configured order of preference
+    pub fn rpc_urls(&self) -> Vec<&str> {
+        self.endpoints.iter().map(|e| e.url.as_str()).collect()
+    }
+
+    /// Endpoints in the order a call should try them
+    fn ranked_endpoints(&self) -> Vec<&RpcEndpoint> {
+        let now = Instant::now();
+        let mut ranked: Vec<(&RpcEndpoint, (bool, f64))> =
+            self.endpoints.iter().map(|e| (e, e.rank(now))).collect();
+        ranked.sort_by(|(_, a), (_, b)| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        ranked.into_iter().map(|(e, _)| e).collect()
     }
 
     // =========================================================================
@@ -345,6 +522,9 @@ impl AlchemyClient {
     }
 
     /// Make a raw JSON-RPC call returning Value
+    ///
+    /// Tries each endpoint in ranked order until one answers, returning the
+    /// last failure when none do.
     async fn call_raw(&self, method: &str, params: Value) -> ChainResult<Value> {
         let request = RpcRequest {
             jsonrpc: "2.0",
@@ -353,16 +533,50 @@ impl AlchemyClient {
             id: self.next_id(),
         };
 
+        let mut last_error = None;
+        for endpoint in self.ranked_endpoints() {
+            let started = Instant::now();
+            match self.call_endpoint(endpoint, &request).await {
+                Ok(result) => {
+                    endpoint.record_success(started.elapsed());
+                    return Ok(result);
+                }
+                Err(EndpointError::Request(e)) => {
+                    endpoint.record_success(started.elapsed());
+                    return Err(e);
+                }
+                Err(EndpointError::Unavailable(e)) => {
+                    endpoint.record_failure();
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| ChainError::RpcError("No RPC endpoints".to_string())))
+    }
+
+    /// Send a request to one endpoint
+    async fn call_endpoint(
+        &self,
+        endpoint: &RpcEndpoint,
+        request: &RpcRequest,
+    ) -> Result<Value, EndpointError> {
+        if endpoint.metered {
+            usage::record_call(ApiProvider::Alchemy);
+        }
+
         let response = self
             .client
-            .post(&self.rpc_url)
-            .json(&request)
+            .post(&endpoint.url)
+            .json(request)
             .send()
             .await
-            .map_err(|e| ChainError::RpcError(format!("Network error: {}", e)))?;
+            .map_err(|e| {
+                EndpointError::Unavailable(ChainError::RpcError(format!("Network error: {}", e)))
+            })?;
 
         if response.status() == 429 {
-            return Err(ChainError::RateLimited);
+            return Err(EndpointError::Unavailable(ChainError::RateLimited));
         }
 
         if !response.status().is_success() {
@@ -371,24 +585,31 @@ impl AlchemyClient {
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(ChainError::RpcError(format!("HTTP {}: {}", status, body)));
+            return Err(EndpointError::Unavailable(ChainError::RpcError(format!(
+                "HTTP {}: {}",
+                status, body
+            ))));
         }
 
         let rpc_response: RpcResponse = response
             .json()
             .await
-            .map_err(|e| ChainError::ParseError(e.to_string()))?;
+            .map_err(|e| EndpointError::Unavailable(ChainError::ParseError(e.to_string())))?;
 
         if let Some(error) = rpc_response.error {
-            return Err(ChainError::RpcError(format!(
-                "RPC error {}: {}",
-                error.code, error.message
-            )));
+            let rpc_error =
+                ChainError::RpcError(format!("RPC error {}: {}", error.code, error.message));
+            // Some endpoints also use the rate limit code for a log query
+            // over too wide a range, which a narrower query fixes
+            if error.code == RPC_LIMIT_EXCEEDED && !is_log_range_error(&rpc_error) {
+                return Err(EndpointError::Unavailable(ChainError::RateLimited));
+            }
+            return Err(EndpointError::Request(rpc_error));
         }
 
         rpc_response
             .result
-            .ok_or_else(|| ChainError::RpcError("Empty result".to_string()))
+            .ok_or_else(|| EndpointError::Request(ChainError::RpcError("Empty result".to_string())))
     }
 
     // Backward compatibility alias
@@ -410,8 +631,8 @@ impl AlchemyClient {
             .as_str()
             .ok_or_else(|| ChainError::ParseError("Expected string".to_string()))?;
 
-        let balance_wei = hex_to_u128(hex_str).unwrap_or(0);
-        let balance_formatted = format_wei(balance_wei, self.chain_config.decimals);
+        let balance_wei = units::parse_hex(hex_str)?;
+        let balance_formatted = units::format_units(balance_wei, self.chain_config.decimals);
 
         Ok(NativeBalance {
             symbol: self.chain_config.symbol.clone(),
@@ -431,8 +652,7 @@ impl AlchemyClient {
             .as_str()
             .ok_or_else(|| ChainError::ParseError("Expected string".to_string()))?;
 
-        let balance = hex_to_u128(hex_str).unwrap_or(0);
-        Ok(balance.to_string())
+        Ok(units::parse_hex(hex_str)?.to_string())
     }
 
     /// Get ERC-20 token balance
@@ -461,9 +681,7 @@ impl AlchemyClient {
             .as_str()
             .ok_or_else(|| ChainError::ParseError("Expected string".to_string()))?;
 
-        // Parse the returned uint256 (handle both u128 and larger values)
-        let balance = hex_to_decimal_string(hex_str);
-        Ok(balance)
+        hex_to_decimal_string(hex_str)
     }
 
     /// Get token decimals
@@ -548,8 +766,7 @@ impl AlchemyClient {
         let symbol = self.get_token_symbol(token_address).await.ok();
         let name = self.get_token_name(token_address).await.ok();
 
-        let balance_u128: u128 = balance.parse().unwrap_or(0);
-        let balance_formatted = format_wei(balance_u128, decimals);
+        let balance_formatted = units::format_units(units::parse_decimal(&balance)?, decimals);
 
         Ok(TokenBalance {
             token_address: token_address.to_string(),
@@ -558,6 +775,7 @@ impl AlchemyClient {
             token_decimals: decimals,
             balance,
             balance_formatted,
+            extensions: None,
         })
     }
 
@@ -707,7 +925,7 @@ impl AlchemyClient {
             .as_str()
             .ok_or_else(|| ChainError::ParseError("Expected string".to_string()))?;
 
-        Ok(hex_to_decimal_string(hex_str))
+        hex_to_decimal_string(hex_str)
     }
 
     /// Get gas price as hex
@@ -728,7 +946,7 @@ impl AlchemyClient {
             .as_str()
             .ok_or_else(|| ChainError::ParseError("Expected string".to_string()))?;
 
-        Ok(hex_to_decimal_string(hex_str))
+        hex_to_decimal_string(hex_str)
     }
 
     /// Estimate gas for a transaction (eth_estimateGas)
@@ -791,10 +1009,7 @@ impl AlchemyClient {
         address: Option<&str>,
         topics: Option<Vec<Option<String>>>,
     ) -> ChainResult<Vec<Log>> {
-        let mut filter = json!({
-            "fromBlock": format!("0x{:x}", from_block),
-            "toBlock": format!("0x{:x}", to_block)
-        });
+        let mut filter = json!({});
 
         if let Some(addr) = address {
             filter["address"] = json!(addr);
@@ -804,38 +1019,71 @@ impl AlchemyClient {
             filter["topics"] = json!(t);
         }
 
-        let result = self.call("eth_getLogs", json!([filter])).await?;
-
-        serde_json::from_value(result).map_err(|e| ChainError::ParseError(e.to_string()))
+        self.scan_logs(&filter, from_block, Some(to_block)).await
     }
 
-    /// Get logs as raw Value
-    pub async fn get_logs_raw(
+    /// Get logs matching `filter` (its address and topics) from `from_block`
+    /// to `to_block`, or to the latest block
+    ///
+    /// Requests at most the chain's `max_log_block_range` blocks at a time.
+    /// When an endpoint refuses a range as too wide or matching too many
+    /// logs, the range is halved and the rest of the scan keeps the
+    /// narrower range.
+    pub async fn scan_logs(
         &self,
+        filter: &Value,
         from_block: u64,
-        to_block: u64,
-        address: Option<&str>,
-        topics: Option<Vec<Option<String>>>,
-    ) -> ChainResult<Vec<Value>> {
-        let mut filter = json!({
-            "fromBlock": format!("0x{:x}", from_block),
-            "toBlock": format!("0x{:x}", to_block)
-        });
-
-        if let Some(addr) = address {
-            filter["address"] = json!(addr);
-        }
+        to_block: Option<u64>,
+    ) -> ChainResult<Vec<Log>> {
+        let mut range = self.chain_config.max_log_block_range.filter(|r| *r > 0);
+        let mut to_block = match (to_block, range) {
+            (None, Some(_)) => Some(self.get_block_number().await?),
+            (to_block, _) => to_block,
+        };
 
-        if let Some(t) = topics {
-            filter["topics"] = json!(t);
+        let mut logs = Vec::new();
+        let mut start = from_block;
+        loop {
+            // Without an end block the request runs to "latest"
+            let end = match (range, to_block) {
+                (Some(range), Some(to)) => Some(start.saturating_add(range - 1).min(to)),
+                (_, to) => to,
+            };
+
+            let mut chunk_filter = filter.clone();
+            chunk_filter["fromBlock"] = json!(format!("0x{:x}", start));
+            chunk_filter["toBlock"] =
+                json!(end.map_or_else(|| "latest".to_string(), |block| format!("0x{:x}", block)));
+
+            match self
+                .rpc_call::<Vec<Log>>("eth_getLogs", json!([chunk_filter]))
+                .await
+            {
+                Ok(mut chunk) => {
+                    logs.append(&mut chunk);
+                    match (end, to_block) {
+                        (Some(end), Some(to)) if end < to => start = end + 1,
+                        _ => return Ok(logs),
+                    }
+                }
+                Err(e) if is_log_range_error(&e) => {
+                    let to = match to_block {
+                        Some(to) => to,
+                        None => {
+                            let latest = self.get_block_number().await?;
+                            to_block = Some(latest);
+                            latest
+                        }
+                    };
+                    let width = end.unwrap_or(to).saturating_sub(start) + 1;
+                    if width <= 1 {
+                        return Err(e);
+                    }
+                    range = Some(width / 2);
+                }
+                Err(e) => return Err(e),
+            }
         }
-
-        let result = self.call("eth_getLogs", json!([filter])).await?;
-
-        result
-            .as_array()
-            .cloned()
-            .ok_or_else(|| ChainError::ParseError("Expected array".to_string()))
     }
 
     // =========================================================================
@@ -894,84 +1142,25 @@ fn encode_balance_of_call(address: &str) -> String {
     )
 }
 
+/// Encode ERC-20 transfer(address,uint256) call data
+pub fn encode_transfer_call(to: &str, amount: U256) -> String {
+    // transfer(address,uint256) selector: 0xa9059cbb
+    format!(
+        "0xa9059cbb000000000000000000000000{}{}",
+        to.trim_start_matches("0x").to_lowercase(),
+        hex::encode(amount.to_be_bytes::<32>())
+    )
+}
+
 /// Convert hex string to u64
 pub fn hex_to_u64(hex: &str) -> ChainResult<u64> {
     u64::from_str_radix(hex.trim_start_matches("0x"), 16)
         .map_err(|e| ChainError::ParseError(format!("Invalid hex u64: {}", e)))
 }
 
-/// Convert hex string to u128
-pub fn hex_to_u128(hex: &str) -> ChainResult<u128> {
-    u128::from_str_radix(hex.trim_start_matches("0x"), 16)
-        .map_err(|e| ChainError::ParseError(format!("Invalid hex u128: {}", e)))
-}
-
-/// Convert hex string to decimal string (handles large numbers)
-pub fn hex_to_decimal_string(hex: &str) -> String {
-    let hex_clean = hex.trim_start_matches("0x");
-
-    // Try u128 first (covers most cases)
-    if let Ok(value) = u128::from_str_radix(hex_clean, 16) {
-        return value.to_string();
-    }
-
-    // For larger numbers, use big integer parsing
-    // Parse hex digit by digit
-    let mut result = vec![0u8];
-
-    for c in hex_clean.chars() {
-        let digit = c.to_digit(16).unwrap_or(0) as u8;
-
-        // Multiply result by 16
-        let mut carry = 0u16;
-        for byte in result.iter_mut().rev() {
-            let prod = (*byte as u16) * 16 + carry;
-            *byte = (prod % 10) as u8;
-            carry = prod / 10;
-        }
-        while carry > 0 {
-            result.insert(0, (carry % 10) as u8);
-            carry /= 10;
-        }
-
-        // Add digit
-        let mut carry = digit as u16;
-        for byte in result.iter_mut().rev() {
-            let sum = (*byte as u16) + carry;
-            *byte = (sum % 10) as u8;
-            carry = sum / 10;
-        }
-        while carry > 0 {
-            result.insert(0, (carry % 10) as u8);
-            carry /= 10;
-        }
-    }
-
-    // Convert to string
-    result.iter().map(|d| (b'0' + d) as char).collect()
-}
-
-/// Format wei balance with decimals
-pub fn format_wei(wei: u128, decimals: u8) -> String {
-    if decimals == 0 {
-        return wei.to_string();
-    }
-
-    let divisor = 10u128.pow(decimals as u32);
-    let whole = wei / divisor;
-    let frac = wei % divisor;
-
-    if frac == 0 {
-        whole.to_string()
-    } else {
-        let frac_str = format!("{:0width$}", frac, width = decimals as usize);
-        let trimmed = frac_str.trim_end_matches('0');
-        if trimmed.is_empty() {
-            whole.to_string()
-        } else {
-            format!("{}.{}", whole, trimmed)
-        }
-    }
+/// Convert hex quantity to decimal string
+pub fn hex_to_decimal_string(hex: &str) -> ChainResult<String> {
+    Ok(units::parse_hex(hex)?.to_string())
 }
 
 /// Decode ABI-encoded string
@@ -1003,6 +1192,7 @@ pub fn decode_abi_string(hex: &str) -> ChainResult<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::chains::mock_http::{fixture, mount_rpc, MockServer};
 
     #[test]
     fn test_decode_abi_string() {
@@ -1022,30 +1212,34 @@ mod tests {
         assert_eq!(hex_to_u64("1234").unwrap(), 0x1234);
     }
 
-    #[test]
-    fn test_hex_to_u128() {
-        assert_eq!(
-            hex_to_u128("0xde0b6b3a7640000").unwrap(),
-            1_000_000_000_000_000_000
-        ); // 1 ETH in wei
-    }
-
     #[test]
     fn test_hex_to_decimal_string() {
-        assert_eq!(hex_to_decimal_string("0x1"), "1");
-        assert_eq!(hex_to_decimal_string("0xff"), "255");
+        assert_eq!(hex_to_decimal_string("0x1").unwrap(), "1");
+        assert_eq!(hex_to_decimal_string("0xff").unwrap(), "255");
         assert_eq!(
-            hex_to_decimal_string("0xde0b6b3a7640000"),
+            hex_to_decimal_string("0xde0b6b3a7640000").unwrap(),
             "1000000000000000000"
         );
+        // balanceOf on an address with no code
+        assert_eq!(hex_to_decimal_string("0x").unwrap(), "0");
+        // A full uint256 word, past u128
+        assert_eq!(
+            hex_to_decimal_string(&format!("0x{}", "f".repeat(64))).unwrap(),
+            U256::MAX.to_string()
+        );
+        assert!(hex_to_decimal_string("0xnot-hex").is_err());
     }
 
     #[test]
-    fn test_format_wei() {
-        assert_eq!(format_wei(1_000_000_000_000_000_000, 18), "1");
-        assert_eq!(format_wei(1_500_000_000_000_000_000, 18), "1.5");
-        assert_eq!(format_wei(1_234_567_890_000_000_000, 18), "1.23456789");
-        assert_eq!(format_wei(100_000, 6), "0.1"); // USDC style
+    fn test_encode_transfer_call() {
+        let to = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
+        let data = encode_transfer_call(to, U256::from(1_000_000u64));
+        assert!(data.starts_with("0xa9059cbb"));
+        assert_eq!(data.len(), 138); // 0x + 8 (selector) + 64 (address) + 64 (amount)
+        assert!(data.ends_with("00000000000f4240"));
+
+        let data = encode_transfer_call(to, U256::MAX);
+        assert!(data.ends_with(&"f".repeat(64)));
     }
 
     #[test]
@@ -1063,6 +1257,8 @@ mod tests {
 
         let client = result.unwrap();
         assert!(client.rpc_url().contains("test_key"));
+        // Bundled public endpoints follow the keyed one
+        assert!(client.rpc_urls().len() > 1);
     }
 
     #[test]
@@ -1083,10 +1279,126 @@ mod tests {
             tx_type: Some("0x2".to_string()),
             status: Some("0x1".to_string()),
             root: None,
+            l1_fee: None,
+            gas_used_for_l1: None,
+            blob_gas_used: None,
+            blob_gas_price: None,
         };
 
         assert!(receipt.is_success());
         assert_eq!(receipt.gas_used_u64(), 21000);
         assert_eq!(receipt.block_number_u64(), 256);
     }
+
+    #[tokio::test]
+    async fn test_rpc_calls_from_fixtures() {
+        let server = MockServer::start().await;
+        mount_rpc(
+            &server,
+            "eth_blockNumber",
+            fixture("alchemy/eth_blockNumber.json"),
+        )
+        .await;
+        mount_rpc(
+            &server,
+            "eth_getBalance",
+            fixture("alchemy/eth_getBalance.json"),
+        )
+        .await;
+
+        let config = get_chain_config(1).unwrap();
+        let client = AlchemyClient::with_url(&config, &server.uri()).unwrap();
+
+        assert_eq!(client.get_block_number().await.unwrap(), 19_531_250);
+
+        let balance = client
+            .get_balance("0xd8da6bf26964af9d7eed9e03e53415d37aa96045")
+            .await
+            .unwrap();
+        assert_eq!(balance.balance, "12345000000000000000");
+        assert_eq!(balance.balance_formatted, "12.345");
+        assert_eq!(balance.symbol, "ETH");
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_to_next_endpoint() {
+        // Answers every request with 404
+        let down = MockServer::start().await;
+        let healthy = MockServer::start().await;
+        mount_rpc(
+            &healthy,
+            "eth_blockNumber",
+            fixture("alchemy/eth_blockNumber.json"),
+        )
+        .await;
+
+        let config = get_chain_config(1).unwrap();
+        let client = AlchemyClient::with_urls(&config, vec![down.uri(), healthy.uri()]).unwrap();
+        assert_eq!(client.rpc_url(), down.uri());
+
+        assert_eq!(client.get_block_number().await.unwrap(), 19_531_250);
+        assert_eq!(client.ranked_endpoints()[0].url, healthy.uri());
+
+        for _ in 1..MAX_CONSECUTIVE_FAILURES {
+            client.endpoints[0].record_failure();
+        }
+        let (resting, _) = client.endpoints[0].rank(Instant::now());
+        assert!(resting);
+    }
+
+    #[tokio::test]
+    async fn test_scan_logs_halves_a_refused_range() {
+        use wiremock::matchers::{body_string_contains, method};
+        use wiremock::{Mock, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let log = fixture("alchemy/eth_getLogs_approvals.json")["result"][0].clone();
+        // Blocks 0-99 at once match too many logs; each half is fine
+        Mock::given(method("POST"))
+            .and(body_string_contains(r#""fromBlock":"0x0""#))
+            .and(body_string_contains(r#""toBlock":"0x63""#))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": -32005, "message": "query returned more than 10000 results" }
+            })))
+            .mount(&server)
+            .await;
+        for (from, to) in [("0x0", "0x31"), ("0x32", "0x63")] {
+            Mock::given(method("POST"))
+                .and(body_string_contains(format!(r#""fromBlock":"{}""#, from)))
+                .and(body_string_contains(format!(r#""toBlock":"{}""#, to)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": [log.clone()]
+                })))
+                .mount(&server)
+                .await;
+        }
+
+        let config = get_chain_config(1).unwrap();
+        let client = AlchemyClient::with_url(&config, &server.uri()).unwrap();
+        let logs = client.scan_logs(&json!({}), 0, Some(99)).await.unwrap();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+
+        // A capped chain never asks for more than its range
+        let config = get_chain_config(1).unwrap().with_max_log_block_range(50);
+        let client = AlchemyClient::with_url(&config, &server.uri()).unwrap();
+        let logs = client.scan_logs(&json!({}), 0, Some(99)).await.unwrap();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(server.received_requests().await.unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_recognizes_log_range_errors() {
+        assert!(is_log_range_error(&ChainError::RpcError(
+            "RPC error -32600: exceed maximum block range: 10000".to_string()
+        )));
+        assert!(!is_log_range_error(&ChainError::RpcError(
+            "RPC error -32000: execution reverted".to_string()
+        )));
+        assert!(!is_log_range_error(&ChainError::RateLimited));
+    }
 }
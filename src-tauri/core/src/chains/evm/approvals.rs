@@ -0,0 +1,278 @@
+//! Token approval scanning
+//!
+//! Finds the approvals an owner has granted from their ERC-20 `Approval`
+//! and `ApprovalForAll` events, then reads each approval still in force
+//! from the token, since `transferFrom` spends allowances down without
+//! always emitting a new event. Single-token ERC-721 approvals are cleared
+//! by the next transfer and are not reported.
+
+use std::collections::HashMap;
+
+use serde_json::json;
+
+use super::alchemy::{AlchemyClient, Log};
+use crate::chains::units::{parse_hex, U256};
+use crate::chains::{ApprovalKind, ChainResult, TokenApproval};
+
+/// Approval(address,address,uint256)
+const APPROVAL_TOPIC: &str = "0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925";
+
+/// ApprovalForAll(address,address,bool)
+const APPROVAL_FOR_ALL_TOPIC: &str =
+    "0x17307eab39ab6107e8899845ad3d59bd9653f200f220920489ca2b5937696c31";
+
+/// allowance(address,address) selector
+const ALLOWANCE_SELECTOR: &str = "0xdd62ed3e";
+/// isApprovedForAll(address,address) selector
+const IS_APPROVED_FOR_ALL_SELECTOR: &str = "0xe985e9c5";
+
+/// Allowances at or above `2^96 - 1` are treated as unlimited. Wallets
+/// approve `uint256`, `uint160`, or `uint96` maximums, all far beyond any
+/// real balance.
+fn unlimited_threshold() -> U256 {
+    (U256::from(1u64) << 96) - U256::from(1u64)
+}
+
+/// An approval event, decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ApprovalGrant {
+    token: String,
+    spender: String,
+    kind: ApprovalKind,
+    value: U256,
+    block: u64,
+    log_index: u64,
+    tx_hash: Option<String>,
+}
+
+/// Left-pads an address to a 32-byte topic.
+fn address_topic(address: &str) -> String {
+    format!("0x{:0>64}", address.trim_start_matches("0x").to_lowercase())
+}
+
+/// Address in the low 20 bytes of a topic.
+fn topic_address(topic: &str) -> Option<String> {
+    let digits = topic.trim_start_matches("0x");
+    (digits.len() == 64).then(|| format!("0x{}", digits[24..].to_lowercase()))
+}
+
+fn parse_quantity(value: Option<&String>) -> u64 {
+    value
+        .and_then(|v| u64::from_str_radix(v.trim_start_matches("0x"), 16).ok())
+        .unwrap_or(0)
+}
+
+/// Decodes an ERC-20 `Approval` or an `ApprovalForAll`. ERC-721 `Approval`
+/// has the token ID as a third indexed topic and is skipped.
+fn decode_approval_log(log: &Log) -> Option<ApprovalGrant> {
+    let kind = match log.topics.first().map(|t| t.to_lowercase()).as_deref() {
+        Some(APPROVAL_TOPIC) if log.topics.len() == 3 => ApprovalKind::Erc20,
+        Some(APPROVAL_FOR_ALL_TOPIC) if log.topics.len() == 3 => ApprovalKind::NftOperator,
+        _ => return None,
+    };
+    let digits = log.data.trim_start_matches("0x");
+    let value = U256::from_str_radix(digits.get(..64)?, 16).ok()?;
+    Some(ApprovalGrant {
+        token: log.address.to_lowercase(),
+        spender: topic_address(&log.topics[2])?,
+        kind,
+        value,
+        block: parse_quantity(log.block_number.as_ref()),
+        log_index: parse_quantity(log.log_index.as_ref()),
+        tx_hash: log.transaction_hash.clone(),
+    })
+}
+
+/// Folds approval events, oldest first, into each token and spender's
+/// latest grant and any revocation after it. Zero approvals with no
+/// earlier grant are ignored.
+fn fold_approvals(logs: &[Log]) -> Vec<TokenApproval> {
+    let mut grants: Vec<ApprovalGrant> = logs.iter().filter_map(decode_approval_log).collect();
+    grants.sort_by_key(|g| (g.block, g.log_index));
+
+    let threshold = unlimited_threshold();
+    let mut approvals: Vec<TokenApproval> = Vec::new();
+    let mut index: HashMap<(String, String, ApprovalKind), usize> = HashMap::new();
+
+    for grant in grants {
+        let key = (grant.token.clone(), grant.spender.clone(), grant.kind);
+        match (index.get(&key).copied(), grant.value.is_zero()) {
+            (Some(i), true) => {
+                let approval = &mut approvals[i];
+                if approval.revoked_block.is_none() {
+                    approval.revoked_block = Some(grant.block);
+                    approval.revoked_tx = grant.tx_hash;
+                    approval.allowance = "0".to_string();
+                    approval.unlimited = false;
+                    approval.active = false;
+                }
+            }
+            (None, true) => {}
+            (existing, false) => {
+                let approval = TokenApproval {
+                    token: grant.token,
+                    spender: grant.spender,
+                    kind: grant.kind,
+                    allowance: grant.value.to_string(),
+                    unlimited: grant.kind == ApprovalKind::NftOperator || grant.value >= threshold,
+                    active: true,
+                    approved_block: grant.block,
+                    approved_tx: grant.tx_hash,
+                    revoked_block: None,
+                    revoked_tx: None,
+                };
+                match existing {
+                    Some(i) => approvals[i] = approval,
+                    None => {
+                        index.insert(key, approvals.len());
+                        approvals.push(approval);
+                    }
+                }
+            }
+        }
+    }
+
+    approvals
+}
+
+/// Reads an approval's current allowance, or whether the operator is still
+/// approved.
+async fn current_allowance(
+    rpc: &AlchemyClient,
+    owner: &str,
+    approval: &TokenApproval,
+) -> ChainResult<U256> {
+    let selector = match approval.kind {
+        ApprovalKind::Erc20 => ALLOWANCE_SELECTOR,
+        ApprovalKind::NftOperator => IS_APPROVED_FOR_ALL_SELECTOR,
+    };
+    let data = format!(
+        "{}{}{}",
+        selector,
+        &address_topic(owner)[2..],
+        &address_topic(&approval.spender)[2..]
+    );
+    let result = rpc.eth_call(&approval.token, &data).await?;
+    parse_hex(&result)
+}
+
+/// Approvals `owner` has granted, with the current allowance of each one
+/// not revoked.
+pub async fn scan_approvals(rpc: &AlchemyClient, owner: &str) -> ChainResult<Vec<TokenApproval>> {
+    let filter = json!({
+        "topics": [[APPROVAL_TOPIC, APPROVAL_FOR_ALL_TOPIC], address_topic(owner)],
+    });
+    let logs = rpc.scan_logs(&filter, 0, None).await?;
+
+    let threshold = unlimited_threshold();
+    let mut approvals = fold_approvals(&logs);
+    for approval in approvals.iter_mut().filter(|a| a.revoked_block.is_none()) {
+        // A token that can't be read keeps the allowance from its event
+        let Ok(current) = current_allowance(rpc, owner, approval).await else {
+            continue;
+        };
+        approval.active = !current.is_zero();
+        match approval.kind {
+            ApprovalKind::Erc20 => {
+                approval.allowance = current.to_string();
+                approval.unlimited = current >= threshold;
+            }
+            ApprovalKind::NftOperator => {
+                approval.allowance = if approval.active { "1" } else { "0" }.to_string();
+            }
+        }
+    }
+
+    Ok(approvals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWNER: &str = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045";
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    const SPENDER: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+
+    fn log(token: &str, topic: &str, spender: &str, value: U256, block: u64) -> Log {
+        Log {
+            address: token.to_string(),
+            topics: vec![
+                topic.to_string(),
+                address_topic(OWNER),
+                address_topic(spender),
+            ],
+            data: format!("0x{:0>64}", format!("{:x}", value)),
+            block_number: Some(format!("0x{:x}", block)),
+            transaction_hash: Some(format!("0x{:064x}", block)),
+            transaction_index: None,
+            block_hash: None,
+            log_index: Some("0x0".to_string()),
+            removed: None,
+        }
+    }
+
+    #[test]
+    fn test_topics_match_event_signatures() {
+        use alloy_primitives::keccak256;
+        let topic = |sig: &str| format!("0x{}", hex::encode(keccak256(sig.as_bytes())));
+        assert_eq!(topic("Approval(address,address,uint256)"), APPROVAL_TOPIC);
+        assert_eq!(
+            topic("ApprovalForAll(address,address,bool)"),
+            APPROVAL_FOR_ALL_TOPIC
+        );
+    }
+
+    #[test]
+    fn test_fold_tracks_grants_and_revocations() {
+        let logs = vec![
+            log(USDC, APPROVAL_TOPIC, SPENDER, U256::from(0u64), 90),
+            log(USDC, APPROVAL_TOPIC, SPENDER, U256::MAX, 100),
+            log(USDC, APPROVAL_TOPIC, SPENDER, U256::from(0u64), 120),
+            log(USDC, APPROVAL_TOPIC, OWNER, U256::from(5_000_000u64), 110),
+        ];
+        let approvals = fold_approvals(&logs);
+        assert_eq!(approvals.len(), 2);
+
+        let revoked = approvals.iter().find(|a| a.spender == SPENDER).unwrap();
+        assert_eq!(revoked.approved_block, 100);
+        assert_eq!(revoked.revoked_block, Some(120));
+        assert!(!revoked.active && !revoked.unlimited);
+
+        let limited = approvals.iter().find(|a| a.spender == OWNER).unwrap();
+        assert_eq!(limited.allowance, "5000000");
+        assert!(limited.active && !limited.unlimited);
+    }
+
+    #[test]
+    fn test_regrant_after_revoke_reopens_approval() {
+        let logs = vec![
+            log(USDC, APPROVAL_TOPIC, SPENDER, U256::from(10u64), 100),
+            log(USDC, APPROVAL_TOPIC, SPENDER, U256::from(0u64), 110),
+            log(USDC, APPROVAL_TOPIC, SPENDER, U256::MAX, 120),
+        ];
+        let approvals = fold_approvals(&logs);
+        assert_eq!(approvals.len(), 1);
+        assert_eq!(approvals[0].approved_block, 120);
+        assert_eq!(approvals[0].revoked_block, None);
+        assert!(approvals[0].unlimited);
+    }
+
+    #[test]
+    fn test_operator_approvals_are_unlimited_and_nft_approvals_skipped() {
+        let operator = log(USDC, APPROVAL_FOR_ALL_TOPIC, SPENDER, U256::from(1u64), 100);
+        let mut single = log(USDC, APPROVAL_TOPIC, SPENDER, U256::ZERO, 101);
+        single.topics.push(format!("0x{:064x}", 7));
+        let approvals = fold_approvals(&[operator, single]);
+        assert_eq!(approvals.len(), 1);
+        assert_eq!(approvals[0].kind, ApprovalKind::NftOperator);
+        assert!(approvals[0].unlimited);
+    }
+
+    #[test]
+    fn test_unlimited_threshold() {
+        let uint96_max = unlimited_threshold();
+        assert_eq!(uint96_max.to_string(), "79228162514264337593543950335");
+        assert!(U256::MAX >= uint96_max);
+    }
+}
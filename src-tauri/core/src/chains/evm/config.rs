@@ -27,6 +27,17 @@ pub enum ConfigError {
 /// Result type for configuration operations.
 pub type ConfigResult<T> = Result<T, ConfigError>;
 
+/// Rollup stack of a Layer 2, which decides how its receipts report the
+/// cost of posting transaction data to Layer 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RollupStack {
+    /// OP Stack (Optimism, Base): an `l1Fee` charged on top of L2 gas.
+    OpStack,
+    /// Arbitrum Nitro: `gasUsedForL1` gas included in the gas used.
+    Arbitrum,
+}
+
 /// EVM chain configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvmChainConfig {
@@ -38,6 +49,10 @@ pub struct EvmChainConfig {
     pub symbol: String,
     /// Alchemy RPC URL pattern (without API key).
     pub rpc_url: String,
+    /// Public RPC endpoints tried, in order, when the primary endpoint is
+    /// unavailable or has no API key.
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
     /// Block explorer API base URL.
     pub explorer_api_url: String,
     /// Environment variable name for the explorer API key.
@@ -46,8 +61,23 @@ pub struct EvmChainConfig {
     pub decimals: u8,
     /// Whether this is a Layer 2 network.
     pub is_l2: bool,
+    /// Rollup stack, for Layer 2s whose receipts carry an L1 data fee.
+    #[serde(default)]
+    pub rollup: Option<RollupStack>,
     /// Average block time in seconds (for rate limiting).
     pub block_time_seconds: u64,
+    /// Confirmations before a transaction is treated as final.
+    #[serde(default = "default_confirmations")]
+    pub confirmations: u32,
+    /// Most blocks one `eth_getLogs` request may span. Without a cap a log
+    /// scan asks for its whole range and splits it only when the endpoint
+    /// refuses.
+    #[serde(default)]
+    pub max_log_block_range: Option<u64>,
+}
+
+fn default_confirmations() -> u32 {
+    1
 }
 
 impl EvmChainConfig {
@@ -66,14 +96,62 @@ impl EvmChainConfig {
             name: name.into(),
             symbol: symbol.into(),
             rpc_url: rpc_url.into(),
+            fallback_rpc_urls: Vec::new(),
             explorer_api_url: explorer_api_url.into(),
             explorer_api_key_env: "ETHERSCAN_API_KEY".to_string(),
             decimals: 18,
             is_l2,
+            rollup: None,
             block_time_seconds,
+            confirmations: default_confirmations(),
+            max_log_block_range: None,
         }
     }
 
+    /// Returns a new config for a rollup built on the given stack.
+    pub fn with_rollup(mut self, rollup: RollupStack) -> Self {
+        self.rollup = Some(rollup);
+        self
+    }
+
+    /// Returns a new config requiring `confirmations` before a transaction
+    /// is treated as final. Layer 2s keep the default of one.
+    pub fn with_confirmations(mut self, confirmations: u32) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// Returns a new config whose log scans request at most `blocks` blocks
+    /// at a time.
+    pub fn with_max_log_block_range(mut self, blocks: u64) -> Self {
+        self.max_log_block_range = Some(blocks);
+        self
+    }
+
+    /// Returns a new config with public RPC endpoints to fail over to.
+    pub fn with_fallback_rpcs(mut self, urls: &[&str]) -> Self {
+        self.fallback_rpc_urls = urls.iter().map(|u| u.to_string()).collect();
+        self
+    }
+
+    /// Gets every RPC endpoint in order of preference: the primary endpoint
+    /// (skipped when it needs an Alchemy API key that isn't set), then the
+    /// public fallbacks.
+    pub fn get_rpc_urls(&self) -> ConfigResult<Vec<String>> {
+        let primary = self.get_rpc_url();
+        if self.fallback_rpc_urls.is_empty() {
+            return primary.map(|url| vec![url]);
+        }
+
+        let mut urls: Vec<String> = primary.into_iter().collect();
+        for url in &self.fallback_rpc_urls {
+            if !urls.contains(url) {
+                urls.push(url.clone());
+            }
+        }
+        Ok(urls)
+    }
+
     /// Gets the full RPC URL, appending the Alchemy API key for Alchemy-hosted endpoints.
     /// Public RPC endpoints are returned as-is.
     pub fn get_rpc_url(&self) -> ConfigResult<String> {
@@ -143,7 +221,13 @@ fn get_configs() -> &'static Vec<EvmChainConfig> {
                 "https://api.etherscan.io/v2/api",
                 false, // not L2
                 12,    // ~12 second block time
-            ),
+            )
+            .with_fallback_rpcs(&[
+                "https://ethereum-rpc.publicnode.com",
+                "https://eth.llamarpc.com",
+                "https://cloudflare-eth.com",
+            ])
+            .with_confirmations(12),
             // Arbitrum One
             EvmChainConfig::new(
                 42161,
@@ -153,7 +237,12 @@ fn get_configs() -> &'static Vec<EvmChainConfig> {
                 "https://api.etherscan.io/v2/api",
                 true, // L2
                 1,    // ~0.25s but use 1 for rate limiting
-            ),
+            )
+            .with_fallback_rpcs(&[
+                "https://arb1.arbitrum.io/rpc",
+                "https://arbitrum-one-rpc.publicnode.com",
+            ])
+            .with_rollup(RollupStack::Arbitrum),
             // Base
             EvmChainConfig::new(
                 8453,
@@ -163,7 +252,12 @@ fn get_configs() -> &'static Vec<EvmChainConfig> {
                 "https://api.etherscan.io/v2/api",
                 true, // L2
                 2,    // ~2 second block time
-            ),
+            )
+            .with_fallback_rpcs(&[
+                "https://mainnet.base.org",
+                "https://base-rpc.publicnode.com",
+            ])
+            .with_rollup(RollupStack::OpStack),
             // Optimism
             EvmChainConfig::new(
                 10,
@@ -173,7 +267,12 @@ fn get_configs() -> &'static Vec<EvmChainConfig> {
                 "https://api.etherscan.io/v2/api",
                 true, // L2
                 2,    // ~2 second block time
-            ),
+            )
+            .with_fallback_rpcs(&[
+                "https://mainnet.optimism.io",
+                "https://optimism-rpc.publicnode.com",
+            ])
+            .with_rollup(RollupStack::OpStack),
             // Polygon
             EvmChainConfig::new(
                 137,
@@ -183,7 +282,12 @@ fn get_configs() -> &'static Vec<EvmChainConfig> {
                 "https://api.etherscan.io/v2/api",
                 false, // Sidechain, not technically L2
                 2,     // ~2 second block time
-            ),
+            )
+            .with_fallback_rpcs(&[
+                "https://polygon-rpc.com",
+                "https://polygon-bor-rpc.publicnode.com",
+            ])
+            .with_confirmations(12),
             // BSC (BNB Smart Chain)
             EvmChainConfig::new(
                 56,
@@ -193,7 +297,12 @@ fn get_configs() -> &'static Vec<EvmChainConfig> {
                 "https://api.etherscan.io/v2/api",
                 false, // Standalone sidechain, like Polygon
                 3,     // ~3 second block time
-            ),
+            )
+            .with_fallback_rpcs(&[
+                "https://bsc-dataseed.bnbchain.org",
+                "https://bsc-rpc.publicnode.com",
+            ])
+            .with_confirmations(12),
             // Moonbeam (Polkadot parachain, EVM-compatible)
             EvmChainConfig::new(
                 1284,
@@ -204,6 +313,10 @@ fn get_configs() -> &'static Vec<EvmChainConfig> {
                 false, // Parachain
                 12,    // ~12 second block time
             )
+            .with_fallback_rpcs(&[
+                "https://moonbeam-rpc.publicnode.com",
+                "https://moonbeam.public.blastapi.io",
+            ])
             .with_explorer_key_env("MOONSCAN_API_KEY"),
             // Moonriver (Kusama parachain, EVM-compatible)
             EvmChainConfig::new(
@@ -215,6 +328,10 @@ fn get_configs() -> &'static Vec<EvmChainConfig> {
                 false, // Parachain
                 12,    // ~12 second block time
             )
+            .with_fallback_rpcs(&[
+                "https://moonriver-rpc.publicnode.com",
+                "https://moonriver.public.blastapi.io",
+            ])
             .with_explorer_key_env("MOONSCAN_API_KEY"),
             // Astar (Polkadot parachain, EVM-compatible)
             EvmChainConfig::new(
@@ -226,6 +343,10 @@ fn get_configs() -> &'static Vec<EvmChainConfig> {
                 false, // Parachain
                 12,    // ~12 second block time
             )
+            .with_fallback_rpcs(&[
+                "https://astar-rpc.dwellir.com",
+                "https://astar.public.blastapi.io",
+            ])
             .with_explorer_key_env("BLOCKSCOUT_API_KEY"),
         ]
     })
@@ -329,6 +450,7 @@ mod tests {
         assert_eq!(eth.symbol, "ETH");
         assert_eq!(eth.decimals, 18);
         assert!(!eth.is_l2);
+        assert_eq!(eth.confirmations, 12);
     }
 
     #[test]
@@ -338,6 +460,14 @@ mod tests {
         let arb = arb.unwrap();
         assert_eq!(arb.name, "arbitrum");
         assert!(arb.is_l2);
+        assert_eq!(arb.rollup, Some(RollupStack::Arbitrum));
+        assert_eq!(arb.confirmations, 1);
+
+        assert_eq!(
+            get_chain_config(8453).unwrap().rollup,
+            Some(RollupStack::OpStack)
+        );
+        assert_eq!(get_chain_config(137).unwrap().rollup, None);
     }
 
     #[test]
@@ -391,6 +521,15 @@ mod tests {
         assert!(eth.rpc_url.ends_with("/v2"));
     }
 
+    #[test]
+    fn test_rpc_urls_end_with_public_fallbacks() {
+        for chain in get_all_chains() {
+            let urls = chain.get_rpc_urls().unwrap();
+            assert!(!chain.fallback_rpc_urls.is_empty(), "{}", chain.name);
+            assert!(urls.ends_with(&chain.fallback_rpc_urls), "{}", chain.name);
+        }
+    }
+
     #[test]
     fn test_explorer_api_url() {
         let eth = get_chain_config(1).unwrap();
@@ -0,0 +1,22 @@
+//! ERC-20 contract bindings used to read token metadata and balances.
+
+#![allow(dead_code)]
+
+use ethers::contract::abigen;
+
+abigen!(
+    IERC20,
+    r#"[
+        function name() external view returns (string)
+        function symbol() external view returns (string)
+        function decimals() external view returns (uint8)
+        function totalSupply() external view returns (uint256)
+        function balanceOf(address owner) external view returns (uint256)
+        function transfer(address to, uint256 amount) external returns (bool)
+        function allowance(address owner, address spender) external view returns (uint256)
+        function approve(address spender, uint256 amount) external returns (bool)
+        function transferFrom(address from, address to, uint256 amount) external returns (bool)
+        event Transfer(address indexed from, address indexed to, uint256 value)
+        event Approval(address indexed owner, address indexed spender, uint256 value)
+    ]"#
+);
@@ -0,0 +1,333 @@
+//! Lending markets: Aave V3 and Compound V2 and its forks.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::contract::abigen;
+use ethers::prelude::*;
+use std::sync::Arc;
+
+use super::{address, token_amount, AssetAmount, DeFiPosition, DefiProtocolAdapter, ProtocolType};
+
+abigen!(
+    IAaveToken,
+    r#"[
+        function balanceOf(address owner) external view returns (uint256)
+        function UNDERLYING_ASSET_ADDRESS() external view returns (address)
+    ]"#
+);
+
+abigen!(
+    IAaveRewardsController,
+    r#"[
+        function getAllUserRewards(address[] assets, address user) external view returns (address[] rewardsList, uint256[] unclaimedAmounts)
+    ]"#
+);
+
+abigen!(
+    IComptroller,
+    r#"[
+        function getAllMarkets() external view returns (address[])
+        function compAccrued(address holder) external view returns (uint256)
+    ]"#
+);
+
+abigen!(
+    ICToken,
+    r#"[
+        function getAccountSnapshot(address account) external view returns (uint256, uint256, uint256, uint256)
+        function underlying() external view returns (address)
+    ]"#
+);
+
+/// One Aave reserve: the interest-bearing aToken and its variable debt token.
+#[derive(Clone, Debug)]
+pub struct AaveV3Reserve {
+    /// aToken minted for deposits.
+    pub a_token: Address,
+    /// Token tracking variable-rate borrows.
+    pub variable_debt_token: Address,
+}
+
+/// Aave V3 deposits and variable-rate borrows, read from each reserve's
+/// aToken and debt token balances.
+pub struct AaveV3Adapter {
+    id: String,
+    name: String,
+    reserves: Vec<AaveV3Reserve>,
+    rewards_controller: Option<Address>,
+}
+
+impl AaveV3Adapter {
+    /// Creates an adapter for an Aave V3 market.
+    pub fn new(
+        id: &str,
+        name: &str,
+        reserves: Vec<AaveV3Reserve>,
+        rewards_controller: Option<Address>,
+    ) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            reserves,
+            rewards_controller,
+        }
+    }
+
+    /// The Aave V3 Ethereum market's USDC and WETH reserves.
+    pub fn ethereum() -> Self {
+        Self::new(
+            "aave_v3",
+            "Aave V3",
+            vec![
+                AaveV3Reserve {
+                    a_token: address("0x98C23E9d8f34FEFb1B7BD6a91B7FF122F4e16F5c"), // aEthUSDC
+                    variable_debt_token: address("0x72E95b8931767C79bA4EeE721354d6E99a61D004"),
+                },
+                AaveV3Reserve {
+                    a_token: address("0x4d5F47FA6A74757f35C14fD3a6Ef8E3C9BC514E8"), // aEthWETH
+                    variable_debt_token: address("0xeA51d7853EEFb32b6ee06b1C12E6dcCA88Be0fFE"),
+                },
+            ],
+            Some(address("0x8164Cc65827dcFe994AB23944CBC90e0aa80bFcb")),
+        )
+    }
+}
+
+#[async_trait]
+impl DefiProtocolAdapter for AaveV3Adapter {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn protocol_type(&self) -> ProtocolType {
+        ProtocolType::Lending
+    }
+
+    async fn positions(
+        &self,
+        provider: Arc<Provider<Http>>,
+        user: Address,
+    ) -> Result<Vec<DeFiPosition>> {
+        let mut positions = Vec::new();
+
+        for reserve in &self.reserves {
+            let a_token = IAaveToken::new(reserve.a_token, provider.clone());
+            let debt_token = IAaveToken::new(reserve.variable_debt_token, provider.clone());
+            let supplied = a_token.balance_of(user).call().await?;
+            let borrowed = debt_token.balance_of(user).call().await?;
+            if supplied.is_zero() && borrowed.is_zero() {
+                continue;
+            }
+
+            let underlying = a_token.underlying_asset_address().call().await?;
+            let mut position = DeFiPosition::new(&self.name, "lending");
+            position.position_id = Some(format!("{:?}", reserve.a_token));
+            if !supplied.is_zero() {
+                position.assets = vec![token_amount(&provider, underlying, supplied).await?];
+            }
+            if !borrowed.is_zero() {
+                position.debt = vec![token_amount(&provider, underlying, borrowed).await?];
+            }
+            positions.push(position);
+        }
+
+        Ok(positions)
+    }
+
+    async fn rewards(
+        &self,
+        provider: Arc<Provider<Http>>,
+        user: Address,
+    ) -> Result<Vec<AssetAmount>> {
+        let Some(controller) = self.rewards_controller else {
+            return Ok(Vec::new());
+        };
+
+        let assets = self
+            .reserves
+            .iter()
+            .flat_map(|r| [r.a_token, r.variable_debt_token])
+            .collect();
+        let (tokens, amounts) = IAaveRewardsController::new(controller, provider.clone())
+            .get_all_user_rewards(assets, user)
+            .call()
+            .await?;
+
+        let mut rewards = Vec::new();
+        for (token, amount) in tokens.into_iter().zip(amounts) {
+            if !amount.is_zero() {
+                rewards.push(token_amount(&provider, token, amount).await?);
+            }
+        }
+        Ok(rewards)
+    }
+}
+
+/// A Compound V2 comptroller's reward token, accrued via `compAccrued`.
+#[derive(Clone, Debug)]
+pub struct CompoundV2Reward {
+    /// Reward token contract.
+    pub token: Address,
+}
+
+/// Supplies and borrows in every market of a Compound V2 style comptroller.
+pub struct CompoundV2Adapter {
+    id: String,
+    name: String,
+    comptroller: Address,
+    native_symbol: String,
+    reward: Option<CompoundV2Reward>,
+}
+
+impl CompoundV2Adapter {
+    /// Creates an adapter for a comptroller. `native_symbol` names the
+    /// underlying of the market that holds the chain's native currency.
+    pub fn new(
+        id: &str,
+        name: &str,
+        comptroller: Address,
+        native_symbol: &str,
+        reward: Option<CompoundV2Reward>,
+    ) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            comptroller,
+            native_symbol: native_symbol.to_string(),
+            reward,
+        }
+    }
+
+    /// Compound V2 on Ethereum, accruing COMP.
+    pub fn ethereum() -> Self {
+        Self::new(
+            "compound_v2",
+            "Compound",
+            address("0x3d9819210A31b4961b30EF54bE2aeD79B9c9Cd3B"),
+            "ETH",
+            Some(CompoundV2Reward {
+                token: address("0xc00e94Cb662C3520282E6f5717214004A7f26888"),
+            }),
+        )
+    }
+
+    /// Moonwell on Moonbeam.
+    pub fn moonwell() -> Self {
+        Self::new(
+            "moonwell",
+            "Moonwell",
+            address("0x8E00D5e02E65A19337Cdba98bbA9F84d4186a180"),
+            "GLMR",
+            None,
+        )
+    }
+
+    /// `amount` of the market's underlying token, or of the native currency
+    /// for the native market.
+    async fn underlying_amount(
+        &self,
+        provider: &Arc<Provider<Http>>,
+        market: &ICToken<Provider<Http>>,
+        amount: U256,
+    ) -> Result<AssetAmount> {
+        match market.underlying().call().await {
+            Ok(token) => token_amount(provider, token, amount).await,
+            // The native currency market has no underlying token.
+            Err(_) => Ok(AssetAmount {
+                token_address: None,
+                token_symbol: self.native_symbol.clone(),
+                amount,
+                decimals: 18,
+            }),
+        }
+    }
+}
+
+/// Underlying amount of `ctoken_balance` at `exchange_rate_mantissa`.
+fn ctoken_to_underlying(ctoken_balance: U256, exchange_rate_mantissa: U256) -> U256 {
+    let underlying = ctoken_balance.full_mul(exchange_rate_mantissa) / U512::exp10(18);
+    U256::try_from(underlying).unwrap_or(U256::MAX)
+}
+
+#[async_trait]
+impl DefiProtocolAdapter for CompoundV2Adapter {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn protocol_type(&self) -> ProtocolType {
+        ProtocolType::Lending
+    }
+
+    async fn positions(
+        &self,
+        provider: Arc<Provider<Http>>,
+        user: Address,
+    ) -> Result<Vec<DeFiPosition>> {
+        let comptroller = IComptroller::new(self.comptroller, provider.clone());
+        let mut positions = Vec::new();
+
+        for market_address in comptroller.get_all_markets().call().await? {
+            let market = ICToken::new(market_address, provider.clone());
+            let (error, ctoken_balance, borrowed, exchange_rate) =
+                market.get_account_snapshot(user).call().await?;
+            if !error.is_zero() || (ctoken_balance.is_zero() && borrowed.is_zero()) {
+                continue;
+            }
+
+            let mut position = DeFiPosition::new(&self.name, "lending");
+            position.position_id = Some(format!("{:?}", market_address));
+            if !ctoken_balance.is_zero() {
+                let supplied = ctoken_to_underlying(ctoken_balance, exchange_rate);
+                position.assets = vec![self.underlying_amount(&provider, &market, supplied).await?];
+            }
+            if !borrowed.is_zero() {
+                position.debt = vec![self.underlying_amount(&provider, &market, borrowed).await?];
+            }
+            positions.push(position);
+        }
+
+        Ok(positions)
+    }
+
+    async fn rewards(
+        &self,
+        provider: Arc<Provider<Http>>,
+        user: Address,
+    ) -> Result<Vec<AssetAmount>> {
+        let Some(reward) = &self.reward else {
+            return Ok(Vec::new());
+        };
+
+        let accrued = IComptroller::new(self.comptroller, provider.clone())
+            .comp_accrued(user)
+            .call()
+            .await?;
+        if accrued.is_zero() {
+            return Ok(Vec::new());
+        }
+        Ok(vec![token_amount(&provider, reward.token, accrued).await?])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ctoken_to_underlying() {
+        // 50 cTokens (8 decimals) at 0.02 underlying (18 decimals) each.
+        let balance = U256::from(50u64) * U256::exp10(8);
+        let rate = U256::from(2u64) * U256::exp10(26);
+        assert_eq!(ctoken_to_underlying(balance, rate), U256::exp10(18));
+        assert_eq!(ctoken_to_underlying(U256::zero(), rate), U256::zero());
+    }
+}
@@ -0,0 +1,332 @@
+#![allow(dead_code)]
+
+//! DeFi position scanning.
+//!
+//! Each protocol is a [`DefiProtocolAdapter`] that reads a user's positions
+//! and unclaimed rewards from the protocol's contracts. Adapters are kept in
+//! a [`DefiProtocolRegistry`] keyed by chain, so supporting another protocol
+//! or deployment means registering an adapter rather than editing the
+//! scanner.
+
+mod erc20;
+mod lending;
+mod uniswap;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+pub use lending::{AaveV3Adapter, AaveV3Reserve, CompoundV2Adapter, CompoundV2Reward};
+pub use uniswap::{PairSource, UniswapV2Adapter, UniswapV3Adapter};
+
+use crate::log_error;
+use erc20::IERC20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Enumeration of supported decentralized finance protocol types.
+pub enum ProtocolType {
+    /// Decentralized exchange protocol.
+    Dex,
+    /// Lending protocol.
+    Lending,
+    /// Staking protocol.
+    Staking,
+    /// Farming protocol.
+    Farming,
+    /// Bridge protocol for asset transfers between networks.
+    Bridge,
+}
+
+/// A DeFi protocol deployment whose positions can be read on chain.
+#[async_trait]
+pub trait DefiProtocolAdapter: Send + Sync {
+    /// Stable identifier, e.g. `uniswap_v3`.
+    fn id(&self) -> &str;
+
+    /// Display name, e.g. "Uniswap V3".
+    fn name(&self) -> &str;
+
+    /// Kind of protocol.
+    fn protocol_type(&self) -> ProtocolType;
+
+    /// The user's open positions.
+    async fn positions(
+        &self,
+        provider: Arc<Provider<Http>>,
+        user: Address,
+    ) -> Result<Vec<DeFiPosition>>;
+
+    /// Rewards the user has earned but not claimed, beyond those already
+    /// reported on a position.
+    async fn rewards(
+        &self,
+        _provider: Arc<Provider<Http>>,
+        _user: Address,
+    ) -> Result<Vec<AssetAmount>> {
+        Ok(Vec::new())
+    }
+
+    /// Value of a position given prices by token symbol: assets and rewards
+    /// less debt. `None` if any token in the position has no price.
+    fn valuation(
+        &self,
+        position: &DeFiPosition,
+        prices: &HashMap<String, Decimal>,
+    ) -> Option<Decimal> {
+        let total = |amounts: &[AssetAmount]| -> Option<Decimal> {
+            amounts.iter().try_fold(Decimal::ZERO, |sum, asset| {
+                let price = prices.get(&asset.token_symbol.to_uppercase())?;
+                Some(sum + asset.to_decimal()? * price)
+            })
+        };
+        Some(total(&position.assets)? + total(&position.rewards)? - total(&position.debt)?)
+    }
+}
+
+/// DeFi protocol adapters by chain.
+#[derive(Default)]
+pub struct DefiProtocolRegistry {
+    adapters: HashMap<String, Vec<Arc<dyn DefiProtocolAdapter>>>,
+}
+
+impl DefiProtocolRegistry {
+    /// Creates a registry with no adapters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with the built-in protocol deployments.
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+
+        // Ethereum
+        registry.register("ethereum", Arc::new(UniswapV2Adapter::ethereum()));
+        registry.register("ethereum", Arc::new(UniswapV3Adapter::ethereum()));
+        registry.register("ethereum", Arc::new(AaveV3Adapter::ethereum()));
+        registry.register("ethereum", Arc::new(CompoundV2Adapter::ethereum()));
+
+        // Moonbeam
+        registry.register("moonbeam", Arc::new(UniswapV2Adapter::stellaswap()));
+        registry.register("moonbeam", Arc::new(CompoundV2Adapter::moonwell()));
+
+        registry
+    }
+
+    /// Adds an adapter for `chain`, replacing one with the same ID.
+    pub fn register(&mut self, chain: &str, adapter: Arc<dyn DefiProtocolAdapter>) {
+        let adapters = self.adapters.entry(chain.to_string()).or_default();
+        adapters.retain(|existing| existing.id() != adapter.id());
+        adapters.push(adapter);
+    }
+
+    /// Adapters registered for `chain`.
+    pub fn for_chain(&self, chain: &str) -> &[Arc<dyn DefiProtocolAdapter>] {
+        self.adapters.get(chain).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The adapter with `id` on `chain`.
+    pub fn get(&self, chain: &str, id: &str) -> Option<Arc<dyn DefiProtocolAdapter>> {
+        self.for_chain(chain)
+            .iter()
+            .find(|adapter| adapter.id() == id)
+            .cloned()
+    }
+
+    /// Scans every protocol registered for `chain`. Unclaimed rewards not
+    /// tied to a position are reported as a separate `rewards` position.
+    /// Protocols that fail are logged and skipped.
+    pub async fn scan(
+        &self,
+        chain: &str,
+        provider: Arc<Provider<Http>>,
+        user: Address,
+    ) -> Vec<DeFiPosition> {
+        let mut all_positions = Vec::new();
+
+        for adapter in self.for_chain(chain) {
+            match adapter.positions(provider.clone(), user).await {
+                Ok(mut positions) => all_positions.append(&mut positions),
+                Err(e) => {
+                    log_error!("Error scanning DeFi positions for {}: {}", adapter.id(), e);
+                }
+            }
+            match adapter.rewards(provider.clone(), user).await {
+                Ok(rewards) if !rewards.is_empty() => {
+                    let mut position = DeFiPosition::new(adapter.name(), "rewards");
+                    position.rewards = rewards;
+                    all_positions.push(position);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    log_error!("Error scanning DeFi rewards for {}: {}", adapter.id(), e);
+                }
+            }
+        }
+
+        all_positions
+    }
+}
+
+/// A position in a decentralized finance (DeFi) protocol, including supplied assets, debts, and earned rewards.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeFiPosition {
+    /// The name of the DeFi protocol (e.g., Compound, Aave).
+    pub protocol: String,
+    /// The type of position (e.g., "lending", "borrowing", "staking").
+    pub position_type: String,
+    /// Identifies the position within the protocol, e.g. the LP token,
+    /// market, or NFT position ID.
+    #[serde(default)]
+    pub position_id: Option<String>,
+    /// Assets supplied or staked by the user.
+    pub assets: Vec<AssetAmount>,
+    /// Assets borrowed by the user.
+    pub debt: Vec<AssetAmount>,
+    /// Rewards earned by the user (e.g., liquidity mining rewards).
+    pub rewards: Vec<AssetAmount>,
+    /// The total USD value of the position, if available.
+    pub value_usd: Option<Decimal>,
+}
+
+impl DeFiPosition {
+    /// An empty position of `position_type` in `protocol`.
+    pub fn new(protocol: &str, position_type: &str) -> Self {
+        Self {
+            protocol: protocol.to_string(),
+            position_type: position_type.to_string(),
+            position_id: None,
+            assets: Vec::new(),
+            debt: Vec::new(),
+            rewards: Vec::new(),
+            value_usd: None,
+        }
+    }
+}
+
+/// Represents an amount of a specific token, identified by its address or symbol.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetAmount {
+    /// The blockchain address of the token contract, if available.
+    pub token_address: Option<Address>,
+    /// The symbol of the token (e.g., "ETH", "DAI").
+    pub token_symbol: String,
+    /// The raw token amount in the smallest units.
+    pub amount: U256,
+    /// Number of decimal places used by the token.
+    pub decimals: u8,
+}
+
+impl AssetAmount {
+    /// The amount in whole tokens, if it fits in a `Decimal`.
+    pub fn to_decimal(&self) -> Option<Decimal> {
+        let formatted = ethers::utils::format_units(self.amount, self.decimals as u32).ok()?;
+        Decimal::from_str(&formatted).ok()
+    }
+}
+
+/// Reads a token's symbol and decimals.
+async fn token_metadata(provider: &Arc<Provider<Http>>, token: Address) -> Result<(String, u8)> {
+    let contract = IERC20::new(token, provider.clone());
+    let symbol = contract.symbol().call().await?;
+    let decimals = contract.decimals().call().await?;
+    Ok((symbol, decimals))
+}
+
+/// An amount of `token`, with its symbol and decimals read from the chain.
+async fn token_amount(
+    provider: &Arc<Provider<Http>>,
+    token: Address,
+    amount: U256,
+) -> Result<AssetAmount> {
+    let (token_symbol, decimals) = token_metadata(provider, token).await?;
+    Ok(AssetAmount {
+        token_address: Some(token),
+        token_symbol,
+        amount,
+        decimals,
+    })
+}
+
+/// Parses a contract address literal.
+fn address(literal: &str) -> Address {
+    literal.parse().expect("valid address literal")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(symbol: &str, amount: u64, decimals: u8) -> AssetAmount {
+        AssetAmount {
+            token_address: None,
+            token_symbol: symbol.to_string(),
+            amount: U256::from(amount),
+            decimals,
+        }
+    }
+
+    #[test]
+    fn test_defaults_are_keyed_by_chain() {
+        let registry = DefiProtocolRegistry::with_defaults();
+        let ids = |chain| {
+            registry
+                .for_chain(chain)
+                .iter()
+                .map(|a| a.id().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ids("ethereum"),
+            vec!["uniswap_v2", "uniswap_v3", "aave_v3", "compound_v2"]
+        );
+        assert_eq!(ids("moonbeam"), vec!["stellaswap", "moonwell"]);
+        assert!(ids("astar").is_empty());
+        assert_eq!(
+            registry
+                .get("moonbeam", "moonwell")
+                .unwrap()
+                .protocol_type(),
+            ProtocolType::Lending
+        );
+    }
+
+    #[test]
+    fn test_register_replaces_same_id() {
+        let mut registry = DefiProtocolRegistry::new();
+        registry.register("moonbeam", Arc::new(UniswapV2Adapter::stellaswap()));
+        registry.register("moonbeam", Arc::new(UniswapV2Adapter::stellaswap()));
+        registry.register("moonriver", Arc::new(UniswapV2Adapter::stellaswap()));
+
+        assert_eq!(registry.for_chain("moonbeam").len(), 1);
+        assert_eq!(registry.for_chain("moonriver").len(), 1);
+    }
+
+    #[test]
+    fn test_valuation_nets_debt_and_needs_every_price() {
+        let adapter = CompoundV2Adapter::moonwell();
+        let mut position = DeFiPosition::new("Moonwell", "lending");
+        position.assets = vec![asset("GLMR", 2_000_000_000_000_000_000, 18)];
+        position.debt = vec![asset("USDC", 1_500_000, 6)];
+        position.rewards = vec![asset("WELL", 10_000_000_000_000_000_000, 18)];
+
+        let mut prices = HashMap::from([
+            ("GLMR".to_string(), Decimal::new(25, 2)),
+            ("USDC".to_string(), Decimal::ONE),
+        ]);
+        assert_eq!(adapter.valuation(&position, &prices), None);
+
+        prices.insert("WELL".to_string(), Decimal::new(2, 2));
+        // 2 × 0.25 + 10 × 0.02 − 1.5 × 1
+        assert_eq!(
+            adapter.valuation(&position, &prices),
+            Some(Decimal::new(-8, 1))
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Uniswap V2 and V3 liquidity positions, and their forks.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::contract::abigen;
+use ethers::prelude::*;
+use std::sync::Arc;
+
+use super::{address, token_amount, DeFiPosition, DefiProtocolAdapter, ProtocolType};
+
+abigen!(
+    IUniswapV2Factory,
+    r#"[
+        function allPairsLength() external view returns (uint256)
+        function allPairs(uint256 index) external view returns (address)
+    ]"#
+);
+
+abigen!(
+    IUniswapV2Pair,
+    r#"[
+        function balanceOf(address owner) external view returns (uint256)
+        function totalSupply() external view returns (uint256)
+        function token0() external view returns (address)
+        function token1() external view returns (address)
+        function getReserves() external view returns (uint112 reserve0, uint112 reserve1, uint32 blockTimestampLast)
+    ]"#
+);
+
+abigen!(
+    INonfungiblePositionManager,
+    r#"[
+        function balanceOf(address owner) external view returns (uint256)
+        function tokenOfOwnerByIndex(address owner, uint256 index) external view returns (uint256)
+        function positions(uint256 tokenId) external view returns (uint96 nonce, address operator, address token0, address token1, uint24 fee, int24 tickLower, int24 tickUpper, uint128 liquidity, uint256 feeGrowthInside0LastX128, uint256 feeGrowthInside1LastX128, uint128 tokensOwed0, uint128 tokensOwed1)
+    ]"#
+);
+
+abigen!(
+    IUniswapV3Factory,
+    r#"[
+        function getPool(address tokenA, address tokenB, uint24 fee) external view returns (address)
+    ]"#
+);
+
+abigen!(
+    IUniswapV3Pool,
+    r#"[
+        function slot0() external view returns (uint160 sqrtPriceX96, int24 tick, uint16 observationIndex, uint16 observationCardinality, uint16 observationCardinalityNext, uint8 feeProtocol, bool unlocked)
+    ]"#
+);
+
+/// Where a V2 deployment's pairs come from.
+#[derive(Clone, Debug)]
+pub enum PairSource {
+    /// A fixed list of pairs, for factories with too many to enumerate.
+    Known(Vec<Address>),
+    /// The factory's first `limit` pairs.
+    Factory {
+        /// Factory contract.
+        factory: Address,
+        /// Most pairs checked.
+        limit: usize,
+    },
+}
+
+/// Liquidity in a Uniswap V2 style AMM: LP token balances converted to the
+/// user's share of each pair's reserves.
+pub struct UniswapV2Adapter {
+    id: String,
+    name: String,
+    pairs: PairSource,
+}
+
+impl UniswapV2Adapter {
+    /// Creates an adapter for a V2 deployment.
+    pub fn new(id: &str, name: &str, pairs: PairSource) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            pairs,
+        }
+    }
+
+    /// Uniswap V2 on Ethereum: the USDC, USDT, and DAI pairs against WETH.
+    pub fn ethereum() -> Self {
+        Self::new(
+            "uniswap_v2",
+            "Uniswap V2",
+            PairSource::Known(vec![
+                address("0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc"), // USDC/WETH
+                address("0x0d4a11d5EEaaC28EC3F61d100daF4d40471f1852"), // WETH/USDT
+                address("0xA478c2975Ab1Ea89e8196811F51A7B7Ade33eB11"), // DAI/WETH
+            ]),
+        )
+    }
+
+    /// StellaSwap on Moonbeam.
+    pub fn stellaswap() -> Self {
+        Self::new(
+            "stellaswap",
+            "StellaSwap",
+            PairSource::Factory {
+                factory: address("0x68A384D826D3678f78BB9FB1533c7E9577dACc0E"),
+                limit: 200,
+            },
+        )
+    }
+
+    async fn pair_addresses(&self, provider: &Arc<Provider<Http>>) -> Result<Vec<Address>> {
+        match &self.pairs {
+            PairSource::Known(pairs) => Ok(pairs.clone()),
+            PairSource::Factory { factory, limit } => {
+                let factory = IUniswapV2Factory::new(*factory, provider.clone());
+                let count = factory.all_pairs_length().call().await?;
+                let count = count.min(U256::from(*limit)).as_usize();
+                let mut pairs = Vec::with_capacity(count);
+                for index in 0..count {
+                    pairs.push(factory.all_pairs(U256::from(index)).call().await?);
+                }
+                Ok(pairs)
+            }
+        }
+    }
+}
+
+/// `balance / total_supply` of `reserve`.
+fn pro_rata(reserve: U256, balance: U256, total_supply: U256) -> U256 {
+    if total_supply.is_zero() {
+        return U256::zero();
+    }
+    let share = reserve.full_mul(balance) / U512::from(total_supply);
+    U256::try_from(share).unwrap_or(U256::MAX)
+}
+
+#[async_trait]
+impl DefiProtocolAdapter for UniswapV2Adapter {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn protocol_type(&self) -> ProtocolType {
+        ProtocolType::Dex
+    }
+
+    async fn positions(
+        &self,
+        provider: Arc<Provider<Http>>,
+        user: Address,
+    ) -> Result<Vec<DeFiPosition>> {
+        let mut positions = Vec::new();
+
+        for pair_address in self.pair_addresses(&provider).await? {
+            let pair = IUniswapV2Pair::new(pair_address, provider.clone());
+            let balance = pair.balance_of(user).call().await?;
+            if balance.is_zero() {
+                continue;
+            }
+
+            let total_supply = pair.total_supply().call().await?;
+            let (reserve0, reserve1, _) = pair.get_reserves().call().await?;
+            let token0 = pair.token_0().call().await?;
+            let token1 = pair.token_1().call().await?;
+
+            let mut position = DeFiPosition::new(&self.name, "liquidity");
+            position.position_id = Some(format!("{:?}", pair_address));
+            position.assets = vec![
+                token_amount(
+                    &provider,
+                    token0,
+                    pro_rata(U256::from(reserve0), balance, total_supply),
+                )
+                .await?,
+                token_amount(
+                    &provider,
+                    token1,
+                    pro_rata(U256::from(reserve1), balance, total_supply),
+                )
+                .await?,
+            ];
+            positions.push(position);
+        }
+
+        Ok(positions)
+    }
+}
+
+/// Concentrated liquidity NFTs in a Uniswap V3 style AMM. Amounts are
+/// computed from the position's liquidity at the pool's current tick, and
+/// fees owed to the position are reported as rewards.
+pub struct UniswapV3Adapter {
+    id: String,
+    name: String,
+    position_manager: Address,
+    factory: Address,
+}
+
+impl UniswapV3Adapter {
+    /// Creates an adapter for a V3 deployment.
+    pub fn new(id: &str, name: &str, position_manager: Address, factory: Address) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            position_manager,
+            factory,
+        }
+    }
+
+    /// Uniswap V3 on Ethereum.
+    pub fn ethereum() -> Self {
+        Self::new(
+            "uniswap_v3",
+            "Uniswap V3",
+            address("0xC36442b4a4522E871399CD717aBDD847Ab11FE88"),
+            address("0x1F98431c8aD98523631AE4a59f267346ea31F984"),
+        )
+    }
+}
+
+/// Token amounts held by `liquidity` between `tick_lower` and `tick_upper`
+/// when the pool is at `tick`, in the tokens' smallest units.
+fn position_amounts(liquidity: u128, tick: i32, tick_lower: i32, tick_upper: i32) -> (U256, U256) {
+    let sqrt_price = |tick: i32| 1.0001f64.powf(tick as f64 / 2.0);
+    let (lower, upper) = (sqrt_price(tick_lower), sqrt_price(tick_upper));
+    let liquidity = liquidity as f64;
+
+    let (amount0, amount1) = if tick < tick_lower {
+        (liquidity * (upper - lower) / (lower * upper), 0.0)
+    } else if tick >= tick_upper {
+        (0.0, liquidity * (upper - lower))
+    } else {
+        let current = sqrt_price(tick);
+        (
+            liquidity * (upper - current) / (current * upper),
+            liquidity * (current - lower),
+        )
+    };
+    // Float-to-int casts saturate, and amounts are well inside u128.
+    (U256::from(amount0 as u128), U256::from(amount1 as u128))
+}
+
+#[async_trait]
+impl DefiProtocolAdapter for UniswapV3Adapter {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn protocol_type(&self) -> ProtocolType {
+        ProtocolType::Dex
+    }
+
+    async fn positions(
+        &self,
+        provider: Arc<Provider<Http>>,
+        user: Address,
+    ) -> Result<Vec<DeFiPosition>> {
+        let manager = INonfungiblePositionManager::new(self.position_manager, provider.clone());
+        let factory = IUniswapV3Factory::new(self.factory, provider.clone());
+        let count = manager.balance_of(user).call().await?.as_usize();
+        let mut positions = Vec::new();
+
+        for index in 0..count {
+            let token_id = manager
+                .token_of_owner_by_index(user, U256::from(index))
+                .call()
+                .await?;
+            let (_, _, token0, token1, fee, tick_lower, tick_upper, liquidity, _, _, owed0, owed1) =
+                manager.positions(token_id).call().await?;
+            if liquidity == 0 && owed0 == 0 && owed1 == 0 {
+                continue;
+            }
+
+            let pool = factory.get_pool(token0, token1, fee).call().await?;
+            let (_, tick, _, _, _, _, _) = IUniswapV3Pool::new(pool, provider.clone())
+                .slot_0()
+                .call()
+                .await?;
+            let (amount0, amount1) = position_amounts(liquidity, tick, tick_lower, tick_upper);
+
+            let mut position = DeFiPosition::new(&self.name, "liquidity");
+            position.position_id = Some(token_id.to_string());
+            position.assets = vec![
+                token_amount(&provider, token0, amount0).await?,
+                token_amount(&provider, token1, amount1).await?,
+            ];
+            position.rewards = vec![
+                token_amount(&provider, token0, U256::from(owed0)).await?,
+                token_amount(&provider, token1, U256::from(owed1)).await?,
+            ];
+            position.rewards.retain(|reward| !reward.amount.is_zero());
+            positions.push(position);
+        }
+
+        Ok(positions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pro_rata_share_of_reserves() {
+        let reserve = U256::exp10(24);
+        assert_eq!(
+            pro_rata(reserve, U256::from(25u64), U256::from(100u64)),
+            U256::exp10(24) / 4
+        );
+        assert_eq!(
+            pro_rata(U256::MAX, U256::from(1u64), U256::from(2u64)),
+            U256::MAX / 2
+        );
+        assert_eq!(
+            pro_rata(reserve, U256::from(1u64), U256::zero()),
+            U256::zero()
+        );
+    }
+
+    #[test]
+    fn test_position_amounts_by_price_range() {
+        let liquidity = 1_000_000_000_000u128;
+
+        // Below the range the position is all token0, above it all token1.
+        let (below0, below1) = position_amounts(liquidity, -200, -100, 100);
+        assert!(!below0.is_zero());
+        assert!(below1.is_zero());
+        let (above0, above1) = position_amounts(liquidity, 200, -100, 100);
+        assert!(above0.is_zero());
+        assert!(!above1.is_zero());
+
+        // Centered in a symmetric range at price 1 it holds equal amounts.
+        let (in0, in1) = position_amounts(liquidity, 0, -100, 100);
+        let diff = if in0 > in1 { in0 - in1 } else { in1 - in0 };
+        assert!(diff <= U256::from(1u64));
+        assert!(in0 < below0);
+    }
+}
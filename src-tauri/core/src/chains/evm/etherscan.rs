@@ -7,8 +7,13 @@
 //!
 //! - **Default Mode**: Works out of the box with 1 req/sec (no API key required)
 //! - **Turbo Mode**: Add your API key in Settings to unlock 5 req/sec
+//!
+//! Account history requests for block ranges that are already final are
+//! served from the on-disk [`response_cache`](super::response_cache) when
+//! the client knows the finalized block.
 
 use super::config::{get_chain_config, EvmChainConfig};
+use super::response_cache::{self, CacheKey};
 use super::types::{
     Erc1155Transfer, Erc20Transfer, Erc721Transfer, EvmTransaction, InternalTransaction,
 };
@@ -16,6 +21,7 @@ use crate::chains::{ChainError, ChainResult};
 use crate::fetchers::{ApiKeyManager, ApiProvider, FetcherConfig, ResilientFetcher};
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -35,6 +41,9 @@ const MAX_RETRIES: u32 = 5;
 /// Base delay for exponential backoff (milliseconds)
 const BASE_RETRY_DELAY_MS: u64 = 200;
 
+/// Error message for an empty result set
+const NO_RESULTS: &str = "No results";
+
 // =============================================================================
 // API RESPONSE TYPES
 // =============================================================================
@@ -47,14 +56,24 @@ struct ApiResponse<T> {
     result: T,
 }
 
-/// Etherscan API error response (when result is a string error message)
+/// Etherscan API error response (result is an error message, or an empty
+/// list when nothing was found)
 #[derive(Debug, Deserialize)]
 struct ApiErrorResponse {
     status: String,
     message: String,
+    #[serde(deserialize_with = "result_as_string")]
     result: String,
 }
 
+/// Reads an error response's result as text whatever its JSON type
+fn result_as_string<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(text) => text,
+        other => other.to_string(),
+    })
+}
+
 // =============================================================================
 // HELPER FUNCTIONS
 // =============================================================================
@@ -72,6 +91,61 @@ fn get_api_provider_for_chain(chain_id: u64) -> ApiProvider {
     }
 }
 
+/// Parse an API response body into its result
+fn parse_response<T: DeserializeOwned>(text: &str) -> ChainResult<T> {
+    // First try to parse as success response
+    if let Ok(api_response) = serde_json::from_str::<ApiResponse<T>>(text) {
+        if api_response.status == "1" || api_response.message == "OK" {
+            return Ok(api_response.result);
+        }
+    }
+
+    // Try to parse as error response
+    if let Ok(error_response) = serde_json::from_str::<ApiErrorResponse>(text) {
+        // Check for "No transactions found" which is not an error
+        if error_response.message.contains("No transactions found")
+            || error_response.message.contains("No records found")
+            || error_response.result.contains("No transactions found")
+        {
+            return Err(ChainError::ApiError(NO_RESULTS.to_string()));
+        }
+
+        // Check for rate limit message
+        if error_response.result.contains("rate limit")
+            || error_response.message.contains("rate limit")
+        {
+            return Err(ChainError::RateLimited);
+        }
+
+        // Check for invalid address
+        if error_response.message.contains("Invalid address")
+            || error_response.result.contains("Invalid address")
+        {
+            return Err(ChainError::InvalidAddress(error_response.result));
+        }
+
+        return Err(ChainError::ApiError(format!(
+            "{}: {}",
+            error_response.message, error_response.result
+        )));
+    }
+
+    Err(ChainError::ParseError(format!(
+        "Failed to parse response: {}",
+        &text[..text.len().min(200)]
+    )))
+}
+
+/// Whether a parsed response is an answer worth caching: results, or an
+/// empty range
+fn is_final_response<T>(result: &ChainResult<T>) -> bool {
+    match result {
+        Ok(_) => true,
+        Err(ChainError::ApiError(msg)) => msg == NO_RESULTS,
+        Err(_) => false,
+    }
+}
+
 /// Get the rate limit for a chain based on API key availability
 fn get_rate_limit_for_chain(chain_id: u64, has_api_key: bool) -> u32 {
     let provider = get_api_provider_for_chain(chain_id);
@@ -106,6 +180,9 @@ pub struct EtherscanClient {
     chain_id: u64,
     /// Chain name
     chain_name: String,
+    /// Highest block known to be final, 0 until known; ranges ending at or
+    /// below it are cached
+    finalized_block: AtomicU64,
 }
 
 impl EtherscanClient {
@@ -141,6 +218,7 @@ impl EtherscanClient {
             requests_per_second: rate_limit,
             timeout_secs: 30,
             max_retries: MAX_RETRIES,
+            provider: Some(provider),
         };
 
         // Create the resilient fetcher
@@ -153,9 +231,16 @@ impl EtherscanClient {
             api_key: effective_api_key,
             chain_id: config.chain_id,
             chain_name: config.name.clone(),
+            finalized_block: AtomicU64::new(0),
         })
     }
 
+    /// Record the highest final block, enabling the response cache for
+    /// requests whose range ends at or below it. The block never moves back.
+    pub fn set_finalized_block(&self, block: u64) {
+        self.finalized_block.fetch_max(block, Ordering::Relaxed);
+    }
+
     /// Check if running in Turbo Mode (has API key)
     pub fn is_turbo_mode(&self) -> bool {
         self.fetcher.is_turbo_mode()
@@ -203,26 +288,66 @@ impl EtherscanClient {
     // REQUEST HANDLING
     // =========================================================================
 
+    /// Cache key for a request, if its range ends at or below the finalized
+    /// block and so its response can't change
+    fn closed_range_key(
+        &self,
+        action: &str,
+        params: &[(&str, &str)],
+        end_block: Option<u64>,
+    ) -> Option<CacheKey> {
+        let finalized = self.finalized_block.load(Ordering::Relaxed);
+        if finalized == 0 || end_block? > finalized {
+            return None;
+        }
+        Some(CacheKey::new(self.chain_id, action, params))
+    }
+
     /// Make API request with Governor rate limiting and automatic retries.
     ///
     /// The ResilientFetcher handles:
     /// - Proactive rate limiting (waits before request to prevent 429s)
     /// - Exponential backoff retries for transient failures
     async fn request<T: DeserializeOwned>(&self, url: &str) -> ChainResult<T> {
+        self.request_cached(url, None).await
+    }
+
+    /// Make API request, answering from the response cache when `cache_key`
+    /// is given and saving the response there after a successful fetch
+    async fn request_cached<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        cache_key: Option<CacheKey>,
+    ) -> ChainResult<T> {
+        let cache = cache_key.and_then(|key| response_cache::shared().map(|c| (c, key)));
+        if let Some((cache, key)) = &cache {
+            if let Some(text) = cache.get(key).await {
+                return parse_response(&text);
+            }
+        }
+
         // Wait for rate limiter (Governor GCRA algorithm)
         self.fetcher.wait_for_permit().await;
 
         // Execute request
-        self.execute_request::<T>(url).await
+        let text = self.execute_request(url).await?;
+        let result = parse_response(&text);
+        if let Some((cache, key)) = &cache {
+            if is_final_response(&result) {
+                cache.put(key, &text).await;
+            }
+        }
+        result
     }
 
-    /// Execute a single request with retry handling for rate limits
-    async fn execute_request<T: DeserializeOwned>(&self, url: &str) -> ChainResult<T> {
+    /// Execute a single request with retry handling for rate limits,
+    /// returning the response body
+    async fn execute_request(&self, url: &str) -> ChainResult<String> {
         let mut last_error = ChainError::Internal("No attempts made".to_string());
 
         for attempt in 0..MAX_RETRIES {
-            match self.do_request::<T>(url).await {
-                Ok(result) => return Ok(result),
+            match self.do_request(url).await {
+                Ok(text) => return Ok(text),
                 Err(ChainError::RateLimited) => {
                     // Exponential backoff for rate limits (in case we still get 429)
                     let delay = BASE_RETRY_DELAY_MS * 2u64.pow(attempt);
@@ -239,8 +364,8 @@ impl EtherscanClient {
         Err(last_error)
     }
 
-    /// Execute a single HTTP request
-    async fn do_request<T: DeserializeOwned>(&self, url: &str) -> ChainResult<T> {
+    /// Execute a single HTTP request, failing if the API reports a rate limit
+    async fn do_request(&self, url: &str) -> ChainResult<String> {
         let text = self.fetcher.get(url).await.map_err(|e| match e {
             crate::fetchers::FetchError::RateLimited => ChainError::RateLimited,
             crate::fetchers::FetchError::Timeout => {
@@ -252,47 +377,10 @@ impl EtherscanClient {
             crate::fetchers::FetchError::ConfigError(msg) => ChainError::ConfigError(msg),
         })?;
 
-        // First try to parse as success response
-        if let Ok(api_response) = serde_json::from_str::<ApiResponse<T>>(&text) {
-            if api_response.status == "1" || api_response.message == "OK" {
-                return Ok(api_response.result);
-            }
+        match parse_response::<serde_json::Value>(&text) {
+            Err(ChainError::RateLimited) => Err(ChainError::RateLimited),
+            _ => Ok(text),
         }
-
-        // Try to parse as error response
-        if let Ok(error_response) = serde_json::from_str::<ApiErrorResponse>(&text) {
-            // Check for "No transactions found" which is not an error
-            if error_response.message.contains("No transactions found")
-                || error_response.message.contains("No records found")
-                || error_response.result.contains("No transactions found")
-            {
-                return Err(ChainError::ApiError("No results".to_string()));
-            }
-
-            // Check for rate limit message
-            if error_response.result.contains("rate limit")
-                || error_response.message.contains("rate limit")
-            {
-                return Err(ChainError::RateLimited);
-            }
-
-            // Check for invalid address
-            if error_response.message.contains("Invalid address")
-                || error_response.result.contains("Invalid address")
-            {
-                return Err(ChainError::InvalidAddress(error_response.result));
-            }
-
-            return Err(ChainError::ApiError(format!(
-                "{}: {}",
-                error_response.message, error_response.result
-            )));
-        }
-
-        Err(ChainError::ParseError(format!(
-            "Failed to parse response: {}",
-            &text[..text.len().min(200)]
-        )))
     }
 
     // =========================================================================
@@ -330,22 +418,20 @@ impl EtherscanClient {
         let page_str = page.to_string();
         let offset_str = offset.min(MAX_RESULTS_PER_PAGE).to_string();
 
-        let url = self.build_url(
-            "account",
-            "txlist",
-            &[
-                ("address", address),
-                ("startblock", &start),
-                ("endblock", &end),
-                ("page", &page_str),
-                ("offset", &offset_str),
-                ("sort", "desc"),
-            ],
-        );
+        let params: [(&str, &str); 6] = [
+            ("address", address),
+            ("startblock", &start),
+            ("endblock", &end),
+            ("page", &page_str),
+            ("offset", &offset_str),
+            ("sort", "desc"),
+        ];
+        let url = self.build_url("account", "txlist", &params);
+        let cache_key = self.closed_range_key("txlist", &params, end_block);
 
-        match self.request(&url).await {
+        match self.request_cached(&url, cache_key).await {
             Ok(txs) => Ok(txs),
-            Err(ChainError::ApiError(msg)) if msg == "No results" => Ok(Vec::new()),
+            Err(ChainError::ApiError(msg)) if msg == NO_RESULTS => Ok(Vec::new()),
             Err(e) => Err(e),
         }
     }
@@ -421,22 +507,20 @@ impl EtherscanClient {
         let page_str = page.to_string();
         let offset_str = offset.min(MAX_RESULTS_PER_PAGE).to_string();
 
-        let url = self.build_url(
-            "account",
-            "txlistinternal",
-            &[
-                ("address", address),
-                ("startblock", &start),
-                ("endblock", &end),
-                ("page", &page_str),
-                ("offset", &offset_str),
-                ("sort", "desc"),
-            ],
-        );
+        let params: [(&str, &str); 6] = [
+            ("address", address),
+            ("startblock", &start),
+            ("endblock", &end),
+            ("page", &page_str),
+            ("offset", &offset_str),
+            ("sort", "desc"),
+        ];
+        let url = self.build_url("account", "txlistinternal", &params);
+        let cache_key = self.closed_range_key("txlistinternal", &params, end_block);
 
-        match self.request(&url).await {
+        match self.request_cached(&url, cache_key).await {
             Ok(txs) => Ok(txs),
-            Err(ChainError::ApiError(msg)) if msg == "No results" => Ok(Vec::new()),
+            Err(ChainError::ApiError(msg)) if msg == NO_RESULTS => Ok(Vec::new()),
             Err(e) => Err(e),
         }
     }
@@ -494,10 +578,11 @@ impl EtherscanClient {
         }
 
         let url = self.build_url("account", "tokentx", &params);
+        let cache_key = self.closed_range_key("tokentx", &params, end_block);
 
-        match self.request(&url).await {
+        match self.request_cached(&url, cache_key).await {
             Ok(txs) => Ok(txs),
-            Err(ChainError::ApiError(msg)) if msg == "No results" => Ok(Vec::new()),
+            Err(ChainError::ApiError(msg)) if msg == NO_RESULTS => Ok(Vec::new()),
             Err(e) => Err(e),
         }
     }
@@ -551,10 +636,11 @@ impl EtherscanClient {
         }
 
         let url = self.build_url("account", "tokennfttx", &params);
+        let cache_key = self.closed_range_key("tokennfttx", &params, end_block);
 
-        match self.request(&url).await {
+        match self.request_cached(&url, cache_key).await {
             Ok(txs) => Ok(txs),
-            Err(ChainError::ApiError(msg)) if msg == "No results" => Ok(Vec::new()),
+            Err(ChainError::ApiError(msg)) if msg == NO_RESULTS => Ok(Vec::new()),
             Err(e) => Err(e),
         }
     }
@@ -608,10 +694,11 @@ impl EtherscanClient {
         }
 
         let url = self.build_url("account", "token1155tx", &params);
+        let cache_key = self.closed_range_key("token1155tx", &params, end_block);
 
-        match self.request(&url).await {
+        match self.request_cached(&url, cache_key).await {
             Ok(txs) => Ok(txs),
-            Err(ChainError::ApiError(msg)) if msg == "No results" => Ok(Vec::new()),
+            Err(ChainError::ApiError(msg)) if msg == NO_RESULTS => Ok(Vec::new()),
             Err(e) => Err(e),
         }
     }
@@ -863,6 +950,9 @@ impl EtherscanClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::chains::mock_http::{fixture, mount_get, repeat_record, requests_to, MockServer};
+
+    const ADDRESS: &str = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045";
 
     fn create_test_client() -> EtherscanClient {
         let config = EvmChainConfig::new(
@@ -908,6 +998,149 @@ mod tests {
         assert!(!url.contains("apikey="));
     }
 
+    fn create_mock_client(server: &MockServer) -> EtherscanClient {
+        let config = EvmChainConfig::new(
+            1,
+            "ethereum",
+            "ETH",
+            "https://eth-mainnet.g.alchemy.com/v2",
+            format!("{}/api", server.uri()),
+            false,
+            12,
+        );
+
+        EtherscanClient::new(&config, Some("TEST_KEY".to_string())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_normal_transactions_from_fixture() {
+        let server = MockServer::start().await;
+        mount_get(
+            &server,
+            "/api",
+            &[
+                ("chainid", "1"),
+                ("action", "txlist"),
+                ("address", ADDRESS),
+                ("page", "2"),
+                ("offset", "3"),
+                ("apikey", "TEST_KEY"),
+            ],
+            fixture("etherscan/txlist.json"),
+        )
+        .await;
+
+        let client = create_mock_client(&server);
+        let txs = client
+            .get_normal_transactions_paginated(ADDRESS, None, None, 2, 3)
+            .await
+            .unwrap();
+
+        assert_eq!(txs.len(), 3);
+        assert!(txs[0].to.is_empty());
+        assert_eq!(
+            txs[1].function_name,
+            "transfer(address _to, uint256 _value)"
+        );
+        assert_eq!(txs[2].value, "1500000000000000000");
+    }
+
+    #[tokio::test]
+    async fn test_no_results_is_empty() {
+        let server = MockServer::start().await;
+        mount_get(&server, "/api", &[], fixture("etherscan/no_results.json")).await;
+
+        let client = create_mock_client(&server);
+        let txs = client
+            .get_normal_transactions(ADDRESS, None, None)
+            .await
+            .unwrap();
+        let transfers = client
+            .get_token_transfers(ADDRESS, None, None)
+            .await
+            .unwrap();
+
+        assert!(txs.is_empty());
+        assert!(transfers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_all_normal_transactions_paginates() {
+        let server = MockServer::start().await;
+        let recorded = fixture("etherscan/txlist.json");
+        let full_page = repeat_record(
+            &recorded["result"][2],
+            MAX_RESULTS_PER_PAGE as usize,
+            "hash",
+        );
+        mount_get(
+            &server,
+            "/api",
+            &[("action", "txlist"), ("page", "1"), ("offset", "10000")],
+            serde_json::json!({ "status": "1", "message": "OK", "result": full_page }),
+        )
+        .await;
+        mount_get(
+            &server,
+            "/api",
+            &[("action", "txlist"), ("page", "2"), ("offset", "10000")],
+            recorded,
+        )
+        .await;
+
+        let client = create_mock_client(&server);
+        let txs = client
+            .get_all_normal_transactions(ADDRESS, None, None)
+            .await
+            .unwrap();
+
+        // A short second page ends the walk
+        assert_eq!(txs.len(), MAX_RESULTS_PER_PAGE as usize + 3);
+        assert_eq!(requests_to(&server, "/api").await, 2);
+        assert_ne!(txs[0].hash, txs[1].hash);
+    }
+
+    #[test]
+    fn test_closed_range_key_only_below_finalized() {
+        let params = [("address", "0x123"), ("startblock", "0")];
+        let client = create_test_client();
+        assert!(client
+            .closed_range_key("txlist", &params, Some(100))
+            .is_none());
+
+        client.set_finalized_block(1_000);
+        client.set_finalized_block(900);
+        assert!(client.closed_range_key("txlist", &params, None).is_none());
+        assert!(client
+            .closed_range_key("txlist", &params, Some(1_001))
+            .is_none());
+        assert_eq!(
+            client.closed_range_key("txlist", &params, Some(1_000)),
+            Some(CacheKey::new(1, "txlist", &params))
+        );
+    }
+
+    #[test]
+    fn test_parse_response_outcomes() {
+        let ok: ChainResult<Vec<String>> =
+            parse_response(r#"{"status":"1","message":"OK","result":["a"]}"#);
+        assert_eq!(ok.unwrap(), vec!["a".to_string()]);
+
+        let empty: ChainResult<Vec<String>> =
+            parse_response(r#"{"status":"0","message":"No transactions found","result":"[]"}"#);
+        assert!(is_final_response(&empty));
+
+        // As actually returned for an address with no history
+        let empty: ChainResult<Vec<String>> =
+            parse_response(&fixture("etherscan/no_results.json").to_string());
+        assert!(matches!(&empty, Err(ChainError::ApiError(msg)) if msg == NO_RESULTS));
+
+        let limited: ChainResult<Vec<String>> =
+            parse_response(r#"{"status":"0","message":"NOTOK","result":"Max rate limit reached"}"#);
+        assert!(matches!(limited, Err(ChainError::RateLimited)));
+        assert!(!is_final_response(&limited));
+    }
+
     #[test]
     fn test_from_chain_id() {
         let client = EtherscanClient::from_chain_id(1, Some("KEY".to_string()));
@@ -0,0 +1,295 @@
+//! Transaction fee breakdown.
+//!
+//! Since EIP-1559 the price charged per gas is
+//! `min(max_fee_per_gas, base_fee_per_gas + max_priority_fee_per_gas)`.
+//! The base fee part is burned and the rest is the block producer's tip.
+//! Legacy transactions pay their gas price, of which the block's base fee
+//! is likewise burned.
+//!
+//! Rollups also pay to post transaction data to Layer 1. OP Stack receipts
+//! report it as an `l1Fee` charged on top of L2 gas, which explorers leave
+//! out of the fee; Arbitrum charges it as extra gas, reported as
+//! `gasUsedForL1` and already part of the gas used.
+//!
+//! EIP-4844 blob transactions pay for blob gas at its own price, on top of
+//! execution gas. Only the receipt reports the blob gas used and its price.
+
+use super::alchemy::TransactionReceipt;
+use super::config::RollupStack;
+use crate::chains::units::{self, U256};
+use crate::chains::FeeBreakdown;
+
+/// Per-gas prices of one transaction, in wei.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GasPrices {
+    /// Price per gas actually charged.
+    pub effective: U256,
+    /// Base fee of the including block, when known.
+    pub base_fee: Option<U256>,
+    /// Max fee per gas of a type-2 transaction.
+    pub max_fee: Option<U256>,
+    /// Max priority fee per gas of a type-2 transaction.
+    pub max_priority_fee: Option<U256>,
+}
+
+impl GasPrices {
+    /// The block's base fee, or one derived from a type-2 transaction's
+    /// caps. When the effective price is below the max fee, the sender paid
+    /// their full priority fee on top of the base fee; at the max fee the
+    /// split can't be recovered without the block.
+    fn resolved_base_fee(&self) -> Option<U256> {
+        if let Some(base_fee) = self.base_fee {
+            return Some(base_fee.min(self.effective));
+        }
+        match (self.max_fee, self.max_priority_fee) {
+            (Some(max_fee), Some(max_priority_fee)) if self.effective < max_fee => {
+                self.effective.checked_sub(max_priority_fee)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Splits the fee of a transaction that used `gas_used` gas.
+pub fn breakdown(gas_used: U256, prices: GasPrices) -> FeeBreakdown {
+    let total = units::gas_fee(gas_used, prices.effective);
+    let base_fee = prices
+        .resolved_base_fee()
+        .map(|base_fee| units::gas_fee(gas_used, base_fee));
+    let priority_fee = base_fee.map(|base_fee| total.saturating_sub(base_fee));
+    let refunded = prices
+        .max_fee
+        .map(|max_fee| units::gas_fee(gas_used, max_fee.saturating_sub(prices.effective)));
+
+    FeeBreakdown {
+        gas_used: gas_used.to_string(),
+        effective_gas_price: prices.effective.to_string(),
+        base_fee: base_fee.map(|fee| fee.to_string()),
+        priority_fee: priority_fee.map(|fee| fee.to_string()),
+        refunded: refunded.map(|fee| fee.to_string()),
+        l1_data_fee: None,
+        blob_fee: None,
+        total: total.to_string(),
+    }
+}
+
+/// Records a rollup receipt's L1 data fee in a breakdown, adding it to the
+/// total where the chain charges it separately from L2 gas. Receipts
+/// without the rollup's fee fields leave the breakdown unchanged.
+pub fn apply_l1_fee(
+    breakdown: &mut FeeBreakdown,
+    rollup: RollupStack,
+    receipt: &TransactionReceipt,
+) {
+    let l1_data_fee = match rollup {
+        RollupStack::OpStack => optional_hex(receipt.l1_fee.as_ref()),
+        RollupStack::Arbitrum => optional_hex(receipt.gas_used_for_l1.as_ref()).map(|gas| {
+            let price = units::parse_decimal(&breakdown.effective_gas_price).unwrap_or_default();
+            units::gas_fee(gas, price)
+        }),
+    };
+    let Some(l1_data_fee) = l1_data_fee else {
+        return;
+    };
+
+    if rollup == RollupStack::OpStack {
+        let total = units::parse_decimal(&breakdown.total).unwrap_or_default();
+        breakdown.total = total.saturating_add(l1_data_fee).to_string();
+    }
+    breakdown.l1_data_fee = Some(l1_data_fee.to_string());
+}
+
+/// Adds the blob gas fee in a receipt to a breakdown. Receipts of
+/// transactions without blobs leave the breakdown unchanged.
+pub fn apply_blob_fee(breakdown: &mut FeeBreakdown, receipt: &TransactionReceipt) {
+    let (Some(blob_gas_used), Some(blob_gas_price)) = (
+        optional_hex(receipt.blob_gas_used.as_ref()),
+        optional_hex(receipt.blob_gas_price.as_ref()),
+    ) else {
+        return;
+    };
+
+    let blob_fee = units::gas_fee(blob_gas_used, blob_gas_price);
+    let total = units::parse_decimal(&breakdown.total).unwrap_or_default();
+    breakdown.total = total.saturating_add(blob_fee).to_string();
+    breakdown.blob_fee = Some(blob_fee.to_string());
+}
+
+/// Parses an optional decimal amount, treating empty and zero as absent,
+/// as explorers report them for legacy transactions.
+pub fn nonzero_decimal(value: &str) -> Option<U256> {
+    units::parse_decimal(value).ok().filter(|v| !v.is_zero())
+}
+
+/// Parses an optional hex quantity from an RPC response.
+pub fn optional_hex(value: Option<&String>) -> Option<U256> {
+    value.and_then(|v| units::parse_hex(v).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GWEI: u64 = 1_000_000_000;
+
+    fn gwei(amount: u64) -> U256 {
+        U256::from(amount * GWEI)
+    }
+
+    #[test]
+    fn test_type2_with_block_base_fee() {
+        let fee = breakdown(
+            U256::from(21_000u64),
+            GasPrices {
+                effective: gwei(32),
+                base_fee: Some(gwei(30)),
+                max_fee: Some(gwei(50)),
+                max_priority_fee: Some(gwei(2)),
+            },
+        );
+
+        assert_eq!(fee.total, (21_000 * 32 * GWEI).to_string());
+        assert_eq!(fee.base_fee, Some((21_000 * 30 * GWEI).to_string()));
+        assert_eq!(fee.priority_fee, Some((21_000 * 2 * GWEI).to_string()));
+        assert_eq!(fee.refunded, Some((21_000 * 18 * GWEI).to_string()));
+    }
+
+    #[test]
+    fn test_type2_base_fee_derived_from_caps() {
+        let prices = GasPrices {
+            effective: gwei(32),
+            base_fee: None,
+            max_fee: Some(gwei(50)),
+            max_priority_fee: Some(gwei(2)),
+        };
+        let fee = breakdown(U256::from(100u64), prices);
+        assert_eq!(fee.base_fee, Some((100 * 30 * GWEI).to_string()));
+        assert_eq!(fee.priority_fee, Some((100 * 2 * GWEI).to_string()));
+
+        // Capped at the max fee: the tip may have been cut short
+        let capped = breakdown(
+            U256::from(100u64),
+            GasPrices {
+                effective: gwei(50),
+                ..prices
+            },
+        );
+        assert_eq!(capped.base_fee, None);
+        assert_eq!(capped.priority_fee, None);
+        assert_eq!(capped.refunded, Some("0".to_string()));
+    }
+
+    fn receipt(l1_fee: Option<&str>, gas_used_for_l1: Option<&str>) -> TransactionReceipt {
+        serde_json::from_value(serde_json::json!({
+            "transactionHash": "0x1",
+            "transactionIndex": "0x0",
+            "blockHash": "0x2",
+            "blockNumber": "0x10",
+            "from": "0xabc",
+            "to": "0xdef",
+            "cumulativeGasUsed": "0x5208",
+            "effectiveGasPrice": "0x3b9aca00",
+            "gasUsed": "0x5208",
+            "contractAddress": null,
+            "logs": [],
+            "logsBloom": "0x00",
+            "type": "0x2",
+            "status": "0x1",
+            "l1Fee": l1_fee,
+            "gasUsedForL1": gas_used_for_l1,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_blob_fee_is_added() {
+        let mut receipt = receipt(None, None);
+        receipt.blob_gas_used = Some("0x20000".to_string());
+        receipt.blob_gas_price = Some("0x3".to_string());
+
+        let mut fee = breakdown(
+            U256::from(21_000u64),
+            GasPrices {
+                effective: gwei(10),
+                base_fee: Some(gwei(9)),
+                ..Default::default()
+            },
+        );
+        apply_blob_fee(&mut fee, &receipt);
+
+        // 131072 blob gas at 3 wei
+        assert_eq!(fee.blob_fee.as_deref(), Some("393216"));
+        assert_eq!(fee.total, (21_000 * 10 * GWEI + 393_216).to_string());
+        assert_eq!(fee.priority_fee, Some((21_000 * GWEI).to_string()));
+    }
+
+    #[test]
+    fn test_op_stack_l1_fee_is_added() {
+        let prices = GasPrices {
+            effective: gwei(1),
+            base_fee: Some(gwei(1)),
+            ..Default::default()
+        };
+        let mut fee = breakdown(U256::from(21_000u64), prices);
+        apply_l1_fee(
+            &mut fee,
+            RollupStack::OpStack,
+            &receipt(Some("0x5af3107a4000"), None),
+        );
+
+        assert_eq!(fee.l1_data_fee.as_deref(), Some("100000000000000"));
+        assert_eq!(fee.total, (21_000 * GWEI + 100_000_000_000_000).to_string());
+        // The burn and tip are L2 execution only
+        assert_eq!(fee.base_fee, Some((21_000 * GWEI).to_string()));
+        assert_eq!(fee.priority_fee, Some("0".to_string()));
+    }
+
+    #[test]
+    fn test_arbitrum_l1_gas_is_split_out() {
+        let mut fee = breakdown(
+            U256::from(21_000u64),
+            GasPrices {
+                effective: gwei(1),
+                ..Default::default()
+            },
+        );
+        apply_l1_fee(
+            &mut fee,
+            RollupStack::Arbitrum,
+            &receipt(None, Some("0x1f4")),
+        );
+
+        assert_eq!(fee.l1_data_fee, Some((500 * GWEI).to_string()));
+        assert_eq!(fee.total, (21_000 * GWEI).to_string());
+
+        // No rollup fields, nothing to record
+        let mut plain = breakdown(U256::from(21_000u64), GasPrices::default());
+        apply_l1_fee(&mut plain, RollupStack::OpStack, &receipt(None, None));
+        assert_eq!(plain.l1_data_fee, None);
+    }
+
+    #[test]
+    fn test_legacy_transaction() {
+        let fee = breakdown(
+            U256::from(21_000u64),
+            GasPrices {
+                effective: gwei(40),
+                base_fee: Some(gwei(25)),
+                ..Default::default()
+            },
+        );
+        assert_eq!(fee.priority_fee, Some((21_000 * 15 * GWEI).to_string()));
+        assert_eq!(fee.refunded, None);
+
+        // Before London there is no base fee to burn
+        let pre_london = breakdown(
+            U256::from(21_000u64),
+            GasPrices {
+                effective: gwei(40),
+                ..Default::default()
+            },
+        );
+        assert_eq!(pre_london.base_fee, None);
+        assert_eq!(pre_london.total, (21_000 * 40 * GWEI).to_string());
+    }
+}
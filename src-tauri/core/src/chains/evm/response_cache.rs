@@ -0,0 +1,294 @@
+//! On-disk cache of explorer responses for closed block ranges.
+//!
+//! Blocks that are final never change, so an explorer response for a range
+//! ending at or below the finalized block is saved and reused by later
+//! re-syncs and re-classifications. Each response is one file under the app
+//! data directory, named after its chain, address, endpoint, and query so it
+//! can be purged selectively. Once the cache grows past its size cap the
+//! oldest files are evicted.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::log_error;
+
+/// Default size cap for the cache.
+pub const DEFAULT_MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+static CACHE: RwLock<Option<Arc<ResponseCache>>> = RwLock::new(None);
+
+/// Sets up the shared cache in `dir`. Until this is called nothing is cached.
+pub fn init(dir: PathBuf) {
+    let mut cache = CACHE.write().unwrap_or_else(|e| e.into_inner());
+    *cache = Some(Arc::new(ResponseCache::new(dir, DEFAULT_MAX_BYTES)));
+}
+
+/// The shared cache, if it has been set up.
+pub fn shared() -> Option<Arc<ResponseCache>> {
+    CACHE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Identifies one explorer request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey {
+    chain_id: u64,
+    address: String,
+    endpoint: String,
+    query: String,
+}
+
+impl CacheKey {
+    /// Key for a request to `endpoint` with `params`, which must include
+    /// the address and the block range.
+    pub fn new(chain_id: u64, endpoint: &str, params: &[(&str, &str)]) -> Self {
+        let address = params
+            .iter()
+            .find(|(name, _)| *name == "address")
+            .map(|(_, value)| value.to_lowercase())
+            .unwrap_or_default();
+        let query = params
+            .iter()
+            .filter(|(name, _)| *name != "address")
+            .map(|(name, value)| format!("{}-{}", name, value.to_lowercase()))
+            .collect::<Vec<_>>()
+            .join("_");
+        Self {
+            chain_id,
+            address,
+            endpoint: endpoint.to_string(),
+            query,
+        }
+    }
+
+    fn file_name(&self) -> String {
+        let name = format!(
+            "{}_{}_{}_{}",
+            self.chain_id, self.address, self.endpoint, self.query
+        );
+        let name: String = name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        format!("{}.json", name)
+    }
+}
+
+/// Whether a cache file belongs to `chain_id` and `address`, where given.
+fn file_matches(name: &str, chain_id: Option<u64>, address: Option<&str>) -> bool {
+    let mut parts = name.splitn(3, '_');
+    let (Some(chain), Some(addr)) = (parts.next(), parts.next()) else {
+        return false;
+    };
+    chain_id.is_none_or(|id| chain == id.to_string())
+        && address.is_none_or(|a| addr.eq_ignore_ascii_case(a))
+}
+
+/// Size of the cache, or of what a purge removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplorerCacheStats {
+    /// Number of cached responses.
+    pub entries: usize,
+    /// Total size in bytes.
+    pub bytes: u64,
+    /// Size cap in bytes.
+    pub max_bytes: u64,
+}
+
+/// A cached response file.
+struct CacheEntry {
+    path: PathBuf,
+    name: String,
+    bytes: u64,
+    modified: SystemTime,
+}
+
+/// Explorer responses stored as files in one directory.
+pub struct ResponseCache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl ResponseCache {
+    /// Creates a cache in `dir` holding at most `max_bytes`.
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self { dir, max_bytes }
+    }
+
+    /// The saved response for `key`, if any.
+    pub async fn get(&self, key: &CacheKey) -> Option<String> {
+        tokio::fs::read_to_string(self.dir.join(key.file_name()))
+            .await
+            .ok()
+    }
+
+    /// Saves the response for `key`, then evicts the oldest responses if
+    /// the cache is over its cap. Failures only cost a cache miss later, so
+    /// they are logged rather than returned.
+    pub async fn put(&self, key: &CacheKey, body: &str) {
+        let result = async {
+            tokio::fs::create_dir_all(&self.dir).await?;
+            tokio::fs::write(self.dir.join(key.file_name()), body).await?;
+            self.evict().await
+        }
+        .await;
+        if let Err(e) = result {
+            log_error!("Failed to cache explorer response: {}", e);
+        }
+    }
+
+    /// Size of the cache.
+    pub async fn stats(&self) -> std::io::Result<ExplorerCacheStats> {
+        let entries = self.entries().await?;
+        Ok(ExplorerCacheStats {
+            entries: entries.len(),
+            bytes: entries.iter().map(|e| e.bytes).sum(),
+            max_bytes: self.max_bytes,
+        })
+    }
+
+    /// Removes the responses for `chain_id` and `address`, or all of them
+    /// when neither is given, and reports what was removed.
+    pub async fn purge(
+        &self,
+        chain_id: Option<u64>,
+        address: Option<&str>,
+    ) -> std::io::Result<ExplorerCacheStats> {
+        let mut removed = ExplorerCacheStats {
+            max_bytes: self.max_bytes,
+            ..Default::default()
+        };
+        for entry in self.entries().await? {
+            if file_matches(&entry.name, chain_id, address) {
+                tokio::fs::remove_file(&entry.path).await?;
+                removed.entries += 1;
+                removed.bytes += entry.bytes;
+            }
+        }
+        Ok(removed)
+    }
+
+    async fn evict(&self) -> std::io::Result<()> {
+        let mut entries = self.entries().await?;
+        let mut total: u64 = entries.iter().map(|e| e.bytes).sum();
+        entries.sort_by_key(|e| e.modified);
+        for entry in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            tokio::fs::remove_file(&entry.path).await?;
+            total -= entry.bytes;
+        }
+        Ok(())
+    }
+
+    async fn entries(&self) -> std::io::Result<Vec<CacheEntry>> {
+        let mut dir = match tokio::fs::read_dir(&self.dir).await {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut entries = Vec::new();
+        while let Some(entry) = dir.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let metadata = entry.metadata().await?;
+            entries.push(CacheEntry {
+                name: file_name(&path),
+                path,
+                bytes: metadata.len(),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            });
+        }
+        Ok(entries)
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(chain_id: u64, address: &str, end: &str) -> CacheKey {
+        CacheKey::new(
+            chain_id,
+            "txlist",
+            &[
+                ("address", address),
+                ("startblock", "0"),
+                ("endblock", end),
+                ("page", "1"),
+            ],
+        )
+    }
+
+    fn temp_cache(max_bytes: u64) -> ResponseCache {
+        let dir = std::env::temp_dir().join(format!("explorer-cache-{}", uuid::Uuid::new_v4()));
+        ResponseCache::new(dir, max_bytes)
+    }
+
+    #[test]
+    fn test_file_name_identifies_request() {
+        let name = key(1, "0xABC", "100").file_name();
+        assert_eq!(name, "1_0xabc_txlist_startblock-0_endblock-100_page-1.json");
+        assert!(file_matches(&name, Some(1), Some("0xAbc")));
+        assert!(file_matches(&name, None, None));
+        assert!(!file_matches(&name, Some(137), None));
+        assert!(!file_matches(&name, None, Some("0xdef")));
+    }
+
+    #[tokio::test]
+    async fn test_put_get_and_purge() {
+        let cache = temp_cache(DEFAULT_MAX_BYTES);
+        assert!(cache.get(&key(1, "0xabc", "100")).await.is_none());
+
+        cache
+            .put(&key(1, "0xabc", "100"), r#"{"status":"1"}"#)
+            .await;
+        cache
+            .put(&key(1, "0xdef", "100"), r#"{"status":"1"}"#)
+            .await;
+        assert_eq!(
+            cache.get(&key(1, "0xabc", "100")).await.as_deref(),
+            Some(r#"{"status":"1"}"#)
+        );
+        assert_eq!(cache.stats().await.unwrap().entries, 2);
+
+        let removed = cache.purge(Some(1), Some("0xabc")).await.unwrap();
+        assert_eq!(removed.entries, 1);
+        assert!(cache.get(&key(1, "0xabc", "100")).await.is_none());
+        assert_eq!(cache.purge(None, None).await.unwrap().entries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_evicts_oldest_over_cap() {
+        let cache = temp_cache(25);
+        cache.put(&key(1, "0xabc", "100"), &"a".repeat(10)).await;
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        cache.put(&key(1, "0xabc", "200"), &"b".repeat(10)).await;
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        cache.put(&key(1, "0xabc", "300"), &"c".repeat(10)).await;
+
+        assert!(cache.get(&key(1, "0xabc", "100")).await.is_none());
+        assert!(cache.get(&key(1, "0xabc", "300")).await.is_some());
+        assert_eq!(cache.stats().await.unwrap().bytes, 20);
+        cache.purge(None, None).await.unwrap();
+    }
+}
@@ -0,0 +1,292 @@
+//! Safe{Wallet} transaction unwrapping
+//!
+//! A Safe acts through `execTransaction`, signed by its owners, or
+//! `execTransactionFromModule`, sent by an enabled module. Either way the
+//! outer call only says "execute this", so the wrapped call is decoded to
+//! classify what the Safe actually did. Batches are a delegate call to the
+//! `MultiSend` library, whose argument packs each call as
+//! `operation (1 byte) | to (20) | value (32) | data length (32) | data`.
+
+use super::alchemy::AlchemyClient;
+use crate::chains::units::U256;
+use crate::chains::{CallOperation, ChainResult};
+
+/// execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)
+const EXEC_TRANSACTION_SELECTOR: &str = "0x6a761202";
+/// execTransactionFromModule(address,uint256,bytes,uint8)
+const EXEC_FROM_MODULE_SELECTOR: &str = "0x468721a7";
+/// execTransactionFromModuleReturnData(address,uint256,bytes,uint8)
+const EXEC_FROM_MODULE_RETURN_DATA_SELECTOR: &str = "0x5229073f";
+/// multiSend(bytes), on both `MultiSend` and `MultiSendCallOnly`
+const MULTI_SEND_SELECTOR: &str = "0x8d80ff0a";
+/// getOwners()
+const GET_OWNERS_SELECTOR: &str = "0xa0e67e2b";
+
+/// One call a Safe executed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafeCall {
+    /// Call or delegate call.
+    pub operation: CallOperation,
+    /// Target, lowercase.
+    pub to: String,
+    /// Native value sent, in wei.
+    pub value: U256,
+    /// Calldata, `0x`-prefixed.
+    pub data: String,
+}
+
+impl SafeCall {
+    /// Selector of the function called, if the call had data.
+    pub fn method_id(&self) -> Option<&str> {
+        self.data.get(..10)
+    }
+}
+
+/// The `index`th 32-byte word of hex digits.
+fn word(digits: &str, index: usize) -> Option<&str> {
+    digits.get(index * 64..(index + 1) * 64)
+}
+
+/// A word holding a length or offset small enough to index with.
+fn word_usize(digits: &str, index: usize) -> Option<usize> {
+    let (high, low) = word(digits, index)?.split_at(48);
+    if !high.bytes().all(|b| b == b'0') {
+        return None;
+    }
+    usize::from_str_radix(low, 16).ok()
+}
+
+/// The dynamic `bytes` argument whose offset is in head word `index`, as
+/// hex digits.
+fn bytes_arg(digits: &str, index: usize) -> Option<&str> {
+    let start = word_usize(digits, index)?.checked_mul(2)?;
+    let tail = digits.get(start..)?;
+    let len = word_usize(tail, 0)?.checked_mul(2)?;
+    tail.get(64..64usize.checked_add(len)?)
+}
+
+fn operation(value: u64) -> Option<CallOperation> {
+    match value {
+        0 => Some(CallOperation::Call),
+        1 => Some(CallOperation::DelegateCall),
+        _ => None,
+    }
+}
+
+/// Decodes the call wrapped by `execTransaction` or a module's
+/// `execTransactionFromModule`. Other calldata gives `None`.
+pub fn decode_exec_transaction(input: &str) -> Option<SafeCall> {
+    let selector = input.get(..10)?.to_lowercase();
+    if ![
+        EXEC_TRANSACTION_SELECTOR,
+        EXEC_FROM_MODULE_SELECTOR,
+        EXEC_FROM_MODULE_RETURN_DATA_SELECTOR,
+    ]
+    .contains(&selector.as_str())
+    {
+        return None;
+    }
+
+    // All three start (to, value, data, operation)
+    let digits = &input[10..];
+    let to = word(digits, 0)?.get(24..)?;
+    let value = U256::from_str_radix(word(digits, 1)?, 16).ok()?;
+    let data = bytes_arg(digits, 2)?;
+    let operation = operation(word_usize(digits, 3)? as u64)?;
+
+    Some(SafeCall {
+        operation,
+        to: format!("0x{}", to.to_lowercase()),
+        value,
+        data: format!("0x{}", data.to_lowercase()),
+    })
+}
+
+/// Decodes the calls packed into a `multiSend` call. Other calldata gives
+/// `None`, as does a batch that doesn't decode to the end.
+pub fn decode_multi_send(input: &str) -> Option<Vec<SafeCall>> {
+    if input.get(..10)?.to_lowercase() != MULTI_SEND_SELECTOR {
+        return None;
+    }
+    let mut packed = bytes_arg(&input[10..], 0)?;
+
+    let mut calls = Vec::new();
+    while !packed.is_empty() {
+        let operation = operation(u64::from_str_radix(packed.get(..2)?, 16).ok()?)?;
+        let to = packed.get(2..42)?;
+        let value = U256::from_str_radix(packed.get(42..106)?, 16).ok()?;
+        let len = word_usize(packed.get(106..170)?, 0)?.checked_mul(2)?;
+        let end = 170usize.checked_add(len)?;
+        let data = packed.get(170..end)?;
+
+        calls.push(SafeCall {
+            operation,
+            to: format!("0x{}", to.to_lowercase()),
+            value,
+            data: format!("0x{}", data.to_lowercase()),
+        });
+        packed = &packed[end..];
+    }
+    Some(calls)
+}
+
+/// The calls a Safe transaction executed: the wrapped call, or each call
+/// of a wrapped `multiSend` batch. `None` if `input` isn't a Safe
+/// execution.
+pub fn unwrap_calls(input: &str) -> Option<Vec<SafeCall>> {
+    let call = decode_exec_transaction(input)?;
+    Some(decode_multi_send(&call.data).unwrap_or_else(|| vec![call]))
+}
+
+/// Decodes the `address[]` returned by `getOwners()`, lowercase. Anything
+/// else gives `None`, including the empty result of calling an account
+/// without code.
+pub fn decode_owners(result: &str) -> Option<Vec<String>> {
+    let digits = result.strip_prefix("0x").unwrap_or(result);
+    let start = word_usize(digits, 0)?.checked_mul(2)?;
+    let tail = digits.get(start..)?;
+    let count = word_usize(tail, 0)?;
+    (1..=count)
+        .map(|i| Some(format!("0x{}", word(tail, i)?.get(24..)?.to_lowercase())))
+        .collect()
+}
+
+/// The owners of the Safe at `address`, lowercase. Accounts that aren't
+/// Safes have none.
+pub async fn safe_owners(rpc: &AlchemyClient, address: &str) -> ChainResult<Vec<String>> {
+    if !rpc.is_contract(address).await? {
+        return Ok(Vec::new());
+    }
+    // A contract that isn't a Safe reverts the call.
+    Ok(rpc
+        .eth_call(address, GET_OWNERS_SELECTOR)
+        .await
+        .ok()
+        .and_then(|result| decode_owners(&result))
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAFE_MULTI_SEND: &str = "0x40a2accbd92bca938b02010e17a5b8929b49130d";
+
+    fn word_hex(value: usize) -> String {
+        format!("{:064x}", value)
+    }
+
+    fn padded(data: &str) -> String {
+        let digits = data.trim_start_matches("0x");
+        format!("{}{}", digits, "0".repeat((64 - digits.len() % 64) % 64))
+    }
+
+    /// `execTransaction` calldata wrapping one call, with empty signatures
+    fn exec_transaction(to: &str, value: usize, data: &str, delegate: bool) -> String {
+        let data = data.trim_start_matches("0x");
+        let data_offset = 10 * 32;
+        let data_words = padded(data).len() / 64;
+        let signatures_offset = data_offset + 32 + data_words * 32;
+        format!(
+            "{}{:0>64}{}{}{}{}{}{}{}{}{}{}{}",
+            EXEC_TRANSACTION_SELECTOR,
+            to.trim_start_matches("0x"),
+            word_hex(value),
+            word_hex(data_offset),
+            word_hex(delegate as usize),
+            word_hex(0),
+            word_hex(0),
+            word_hex(0),
+            word_hex(0),
+            word_hex(0),
+            word_hex(signatures_offset),
+            word_hex(data.len() / 2),
+            padded(data),
+        ) + &word_hex(0)
+    }
+
+    /// `multiSend` calldata packing plain calls
+    fn multi_send(calls: &[(&str, usize, &str)]) -> String {
+        let packed: String = calls
+            .iter()
+            .map(|(to, value, data)| {
+                let data = data.trim_start_matches("0x");
+                format!(
+                    "00{}{}{}{}",
+                    to.trim_start_matches("0x"),
+                    word_hex(*value),
+                    word_hex(data.len() / 2),
+                    data
+                )
+            })
+            .collect();
+        format!(
+            "{}{}{}{}",
+            MULTI_SEND_SELECTOR,
+            word_hex(32),
+            word_hex(packed.len() / 2),
+            padded(&packed)
+        )
+    }
+
+    const ROUTER: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+    const TOKEN: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+
+    #[test]
+    fn test_decode_exec_transaction() {
+        let swap = format!("0x38ed1739{}", word_hex(5));
+        let input = exec_transaction(ROUTER, 0, &swap, false);
+
+        let call = decode_exec_transaction(&input).unwrap();
+        assert_eq!(call.operation, CallOperation::Call);
+        assert_eq!(call.to, ROUTER);
+        assert_eq!(call.value, U256::ZERO);
+        assert_eq!(call.data, swap);
+        assert_eq!(call.method_id(), Some("0x38ed1739"));
+
+        // A plain ETH payment has no data
+        let payment = decode_exec_transaction(&exec_transaction(TOKEN, 1000, "", false)).unwrap();
+        assert_eq!(payment.value, U256::from(1000u64));
+        assert_eq!(payment.method_id(), None);
+
+        assert_eq!(decode_exec_transaction("0xa9059cbb"), None);
+        assert_eq!(decode_exec_transaction(&input[..200]), None);
+    }
+
+    #[test]
+    fn test_unwrap_multi_send_batch() {
+        let approve = format!("0x095ea7b3{}{}", word_hex(1), word_hex(2));
+        let swap = format!("0x38ed1739{}", word_hex(5));
+        let batch = multi_send(&[(TOKEN, 0, &approve), (ROUTER, 0, &swap), (TOKEN, 7, "")]);
+        let input = exec_transaction(SAFE_MULTI_SEND, 0, &batch, true);
+
+        let calls = unwrap_calls(&input).unwrap();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0].to, TOKEN);
+        assert_eq!(calls[0].data, approve);
+        assert_eq!(calls[1].method_id(), Some("0x38ed1739"));
+        assert_eq!(calls[2].value, U256::from(7u64));
+        assert_eq!(calls[2].data, "0x");
+
+        // Truncated batches don't decode
+        assert_eq!(decode_multi_send(&batch[..batch.len() - 64]), None);
+    }
+
+    #[test]
+    fn test_decode_owners() {
+        let result = format!(
+            "0x{}{}{:0>64}{:0>64}",
+            word_hex(32),
+            word_hex(2),
+            ROUTER.trim_start_matches("0x"),
+            TOKEN.trim_start_matches("0x")
+        );
+        assert_eq!(
+            decode_owners(&result),
+            Some(vec![ROUTER.to_string(), TOKEN.to_string()])
+        );
+
+        assert_eq!(decode_owners("0x"), None);
+        assert_eq!(decode_owners(&result[..result.len() - 64]), None);
+    }
+}
@@ -0,0 +1,433 @@
+//! DEX swap event decoding
+//!
+//! Reads the exact amounts of each swap from the pools' events in a
+//! transaction receipt: Uniswap V2 `Swap`, Uniswap V3 `Swap`, and Curve
+//! `TokenExchange`. The events name tokens by position in the pool, so the
+//! token addresses are read from the pool contract.
+
+use std::collections::HashMap;
+
+use super::alchemy::{AlchemyClient, Log};
+use crate::chains::units::U256;
+use crate::chains::{ChainError, ChainResult, SwapDetail, TokenTransfer};
+
+/// Swap(address,uint256,uint256,uint256,uint256,address)
+const UNISWAP_V2_SWAP_TOPIC: &str =
+    "0xd78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822";
+
+/// Swap(address,address,int256,int256,uint160,uint128,int24)
+const UNISWAP_V3_SWAP_TOPIC: &str =
+    "0xc42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67";
+
+/// TokenExchange(address,int128,uint256,int128,uint256), stable pools
+const CURVE_TOKEN_EXCHANGE_TOPIC: &str =
+    "0x8b3e96f2b889fa771c53c981b40daf005f63f637f1869f707052d15a3dd97140";
+
+/// TokenExchange(address,uint256,uint256,uint256,uint256), crypto pools
+const CURVE_CRYPTO_TOKEN_EXCHANGE_TOPIC: &str =
+    "0xb2e76ae99761dc136e598d4a629bb347eccb9532a5f8bbd72e18467c3c34cc98";
+
+/// token0() selector
+const TOKEN0_SELECTOR: &str = "0x0dfe1681";
+/// token1() selector
+const TOKEN1_SELECTOR: &str = "0xd21220a7";
+/// coins(uint256) selector
+const CURVE_COINS_SELECTOR: &str = "0xc6610657";
+/// coins(int128) selector, used by older Curve pools
+const CURVE_COINS_INT128_SELECTOR: &str = "0x23746eb8";
+
+/// DEX whose swap event was decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapProtocol {
+    /// Uniswap V2 and its forks
+    UniswapV2,
+    /// Uniswap V3 and its forks
+    UniswapV3,
+    /// Curve
+    Curve,
+}
+
+impl SwapProtocol {
+    /// Identifier stored on [`SwapDetail::protocol`]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SwapProtocol::UniswapV2 => "uniswap_v2",
+            SwapProtocol::UniswapV3 => "uniswap_v3",
+            SwapProtocol::Curve => "curve",
+        }
+    }
+}
+
+/// A swap event with its tokens still identified by position in the pool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolSwap {
+    /// DEX the pool belongs to
+    pub protocol: SwapProtocol,
+    /// Pool that emitted the event
+    pub pool: String,
+    /// Position of the token paid in
+    pub index_in: u64,
+    /// Amount paid in
+    pub amount_in: U256,
+    /// Position of the token received
+    pub index_out: u64,
+    /// Amount received
+    pub amount_out: U256,
+}
+
+/// The 32-byte words of a log's data
+fn data_words(data: &str) -> Option<Vec<U256>> {
+    let digits = data.trim_start_matches("0x");
+    if digits.len() % 64 != 0 {
+        return None;
+    }
+    (0..digits.len() / 64)
+        .map(|i| U256::from_str_radix(&digits[i * 64..(i + 1) * 64], 16).ok())
+        .collect()
+}
+
+/// Magnitude and sign of a two's complement int256 word
+fn signed(word: U256) -> (U256, bool) {
+    if word.bit(255) {
+        ((!word).wrapping_add(U256::from(1u64)), true)
+    } else {
+        (word, false)
+    }
+}
+
+/// A pool index word, which is small for every real pool
+fn index(word: U256) -> Option<u64> {
+    u64::try_from(word).ok()
+}
+
+/// Decode a swap event, or `None` if the log isn't one
+pub fn decode_swap_log(log: &Log) -> Option<PoolSwap> {
+    let topic = log.topics.first()?.to_lowercase();
+    let words = data_words(&log.data)?;
+    let pool = log.address.to_lowercase();
+
+    match topic.as_str() {
+        UNISWAP_V2_SWAP_TOPIC => {
+            let [amount0_in, amount1_in, amount0_out, amount1_out] = words.get(..4)? else {
+                return None;
+            };
+            // A pair swaps one way; the side with input is the side sold
+            let (index_in, amount_in, index_out, amount_out) = if amount0_in > amount1_in {
+                (0, *amount0_in, 1, *amount1_out)
+            } else {
+                (1, *amount1_in, 0, *amount0_out)
+            };
+            Some(PoolSwap {
+                protocol: SwapProtocol::UniswapV2,
+                pool,
+                index_in,
+                amount_in,
+                index_out,
+                amount_out,
+            })
+        }
+        UNISWAP_V3_SWAP_TOPIC => {
+            // Pool deltas: positive was paid in, negative was paid out
+            let (amount0, negative0) = signed(*words.first()?);
+            let (amount1, negative1) = signed(*words.get(1)?);
+            let (index_in, amount_in, index_out, amount_out) = match (negative0, negative1) {
+                (false, true) => (0, amount0, 1, amount1),
+                (true, false) => (1, amount1, 0, amount0),
+                _ => return None,
+            };
+            Some(PoolSwap {
+                protocol: SwapProtocol::UniswapV3,
+                pool,
+                index_in,
+                amount_in,
+                index_out,
+                amount_out,
+            })
+        }
+        CURVE_TOKEN_EXCHANGE_TOPIC | CURVE_CRYPTO_TOKEN_EXCHANGE_TOPIC => {
+            let [sold_id, tokens_sold, bought_id, tokens_bought] = words.get(..4)? else {
+                return None;
+            };
+            Some(PoolSwap {
+                protocol: SwapProtocol::Curve,
+                pool,
+                index_in: index(*sold_id)?,
+                amount_in: *tokens_sold,
+                index_out: index(*bought_id)?,
+                amount_out: *tokens_bought,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The address in an ABI-encoded address return value
+fn decode_address(result: &str) -> Option<String> {
+    let digits = result.trim_start_matches("0x");
+    (digits.len() >= 64).then(|| format!("0x{}", digits[24..64].to_lowercase()))
+}
+
+/// Token addresses of pools, read once per pool and position
+#[derive(Default)]
+pub struct PoolTokens {
+    tokens: HashMap<(String, u64), String>,
+}
+
+impl PoolTokens {
+    /// Address of the token at `index` in a pool
+    pub async fn get(
+        &mut self,
+        rpc: &AlchemyClient,
+        protocol: SwapProtocol,
+        pool: &str,
+        index: u64,
+    ) -> ChainResult<String> {
+        let key = (pool.to_string(), index);
+        if let Some(token) = self.tokens.get(&key) {
+            return Ok(token.clone());
+        }
+
+        let result = match protocol {
+            SwapProtocol::UniswapV2 | SwapProtocol::UniswapV3 => {
+                let selector = if index == 0 {
+                    TOKEN0_SELECTOR
+                } else {
+                    TOKEN1_SELECTOR
+                };
+                rpc.eth_call(pool, selector).await?
+            }
+            SwapProtocol::Curve => {
+                let argument = format!("{:064x}", index);
+                match rpc
+                    .eth_call(pool, &format!("{}{}", CURVE_COINS_SELECTOR, argument))
+                    .await
+                {
+                    Ok(result) => result,
+                    Err(_) => {
+                        rpc.eth_call(
+                            pool,
+                            &format!("{}{}", CURVE_COINS_INT128_SELECTOR, argument),
+                        )
+                        .await?
+                    }
+                }
+            }
+        };
+
+        let token = decode_address(&result).ok_or_else(|| {
+            ChainError::ParseError(format!("Invalid token address from pool {}", pool))
+        })?;
+        self.tokens.insert(key, token.clone());
+        Ok(token)
+    }
+}
+
+/// Symbol and decimals of a token, from the transaction's token transfers
+fn transfer_metadata(transfers: &[TokenTransfer], token: &str) -> (Option<String>, Option<u8>) {
+    transfers
+        .iter()
+        .find(|t| t.token_address.eq_ignore_ascii_case(token))
+        .map(|t| (t.token_symbol.clone(), t.token_decimals))
+        .unwrap_or_default()
+}
+
+/// Decode every swap in a receipt's logs, in log order, resolving token
+/// addresses from the pools and symbols from `transfers`
+pub async fn decode_swaps(
+    rpc: &AlchemyClient,
+    pool_tokens: &mut PoolTokens,
+    logs: &[Log],
+    transfers: &[TokenTransfer],
+) -> ChainResult<Vec<SwapDetail>> {
+    let mut swaps = Vec::new();
+
+    for swap in logs.iter().filter_map(decode_swap_log) {
+        let token_in = pool_tokens
+            .get(rpc, swap.protocol, &swap.pool, swap.index_in)
+            .await?;
+        let token_out = pool_tokens
+            .get(rpc, swap.protocol, &swap.pool, swap.index_out)
+            .await?;
+        let (token_in_symbol, token_in_decimals) = transfer_metadata(transfers, &token_in);
+        let (token_out_symbol, token_out_decimals) = transfer_metadata(transfers, &token_out);
+
+        swaps.push(SwapDetail {
+            protocol: swap.protocol.as_str().to_string(),
+            pool: swap.pool,
+            token_in,
+            token_in_symbol,
+            token_in_decimals,
+            amount_in: swap.amount_in.to_string(),
+            token_out,
+            token_out_symbol,
+            token_out_decimals,
+            amount_out: swap.amount_out.to_string(),
+        });
+    }
+
+    Ok(swaps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+
+    fn word(value: &U256) -> String {
+        hex::encode(value.to_be_bytes::<32>())
+    }
+
+    fn log(topic: &str, words: &[U256]) -> Log {
+        Log {
+            address: "0xB4e16d0168e52d35CaCD2c6185b44281Ec28C9Dc".to_string(),
+            topics: vec![topic.to_string()],
+            data: format!("0x{}", words.iter().map(word).collect::<String>()),
+            block_number: None,
+            transaction_hash: None,
+            transaction_index: None,
+            block_hash: None,
+            log_index: None,
+            removed: None,
+        }
+    }
+
+    fn negative(value: u64) -> U256 {
+        (!U256::from(value)).wrapping_add(U256::from(1u64))
+    }
+
+    #[test]
+    fn test_topics_match_event_signatures() {
+        let topic = |signature: &str| format!("0x{}", hex::encode(keccak256(signature.as_bytes())));
+        assert_eq!(
+            topic("Swap(address,uint256,uint256,uint256,uint256,address)"),
+            UNISWAP_V2_SWAP_TOPIC
+        );
+        assert_eq!(
+            topic("Swap(address,address,int256,int256,uint160,uint128,int24)"),
+            UNISWAP_V3_SWAP_TOPIC
+        );
+        assert_eq!(
+            topic("TokenExchange(address,int128,uint256,int128,uint256)"),
+            CURVE_TOKEN_EXCHANGE_TOPIC
+        );
+        assert_eq!(
+            topic("TokenExchange(address,uint256,uint256,uint256,uint256)"),
+            CURVE_CRYPTO_TOKEN_EXCHANGE_TOPIC
+        );
+    }
+
+    #[test]
+    fn test_decode_uniswap_v2_swap() {
+        // 1,000 USDC (token0) in, 0.5 WETH (token1) out
+        let swap = decode_swap_log(&log(
+            UNISWAP_V2_SWAP_TOPIC,
+            &[
+                U256::from(1_000_000_000u64),
+                U256::ZERO,
+                U256::ZERO,
+                U256::from(500_000_000_000_000_000u64),
+            ],
+        ))
+        .unwrap();
+
+        assert_eq!(swap.protocol, SwapProtocol::UniswapV2);
+        assert_eq!(swap.pool, "0xb4e16d0168e52d35cacd2c6185b44281ec28c9dc");
+        assert_eq!((swap.index_in, swap.index_out), (0, 1));
+        assert_eq!(swap.amount_in, U256::from(1_000_000_000u64));
+        assert_eq!(swap.amount_out, U256::from(500_000_000_000_000_000u64));
+    }
+
+    #[test]
+    fn test_decode_uniswap_v3_swap_signs() {
+        // The pool received token1 and paid out token0
+        let swap = decode_swap_log(&log(
+            UNISWAP_V3_SWAP_TOPIC,
+            &[
+                negative(2_500),
+                U256::from(1_000u64),
+                U256::from(1u64) << 96,
+                U256::from(10u64),
+                U256::ZERO,
+            ],
+        ))
+        .unwrap();
+
+        assert_eq!(swap.protocol, SwapProtocol::UniswapV3);
+        assert_eq!((swap.index_in, swap.index_out), (1, 0));
+        assert_eq!(swap.amount_in, U256::from(1_000u64));
+        assert_eq!(swap.amount_out, U256::from(2_500u64));
+
+        // Both deltas positive is not a swap this decoder understands
+        assert!(decode_swap_log(&log(
+            UNISWAP_V3_SWAP_TOPIC,
+            &[U256::from(1u64), U256::from(1u64)],
+        ))
+        .is_none());
+    }
+
+    #[test]
+    fn test_decode_curve_token_exchange() {
+        let swap = decode_swap_log(&log(
+            CURVE_TOKEN_EXCHANGE_TOPIC,
+            &[
+                U256::from(2u64),
+                U256::from(700u64),
+                U256::from(1u64),
+                U256::from(699u64),
+            ],
+        ))
+        .unwrap();
+
+        assert_eq!(swap.protocol, SwapProtocol::Curve);
+        assert_eq!((swap.index_in, swap.index_out), (2, 1));
+        assert_eq!(swap.amount_in, U256::from(700u64));
+        assert_eq!(swap.amount_out, U256::from(699u64));
+    }
+
+    #[test]
+    fn test_ignores_other_logs() {
+        let transfer = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+        assert!(decode_swap_log(&log(transfer, &[U256::from(1u64)])).is_none());
+
+        let mut truncated = log(UNISWAP_V2_SWAP_TOPIC, &[U256::from(1u64)]);
+        assert!(decode_swap_log(&truncated).is_none());
+        truncated.data = "0x1234".to_string();
+        assert!(decode_swap_log(&truncated).is_none());
+    }
+
+    #[test]
+    fn test_decode_address() {
+        let result = format!(
+            "0x000000000000000000000000{}",
+            "A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"
+        );
+        assert_eq!(
+            decode_address(&result).as_deref(),
+            Some("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")
+        );
+        assert_eq!(decode_address("0x"), None);
+    }
+
+    #[test]
+    fn test_transfer_metadata() {
+        let transfers = vec![TokenTransfer {
+            token_address: "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string(),
+            token_symbol: Some("USDC".to_string()),
+            token_decimals: Some(6),
+            from: "0x1".to_string(),
+            to: "0x2".to_string(),
+            value: "1".to_string(),
+        }];
+        assert_eq!(
+            transfer_metadata(&transfers, "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"),
+            (Some("USDC".to_string()), Some(6))
+        );
+        assert_eq!(transfer_metadata(&transfers, "0xdead"), (None, None));
+    }
+
+    #[test]
+    fn test_signed_words() {
+        assert_eq!(signed(U256::from(5u64)), (U256::from(5u64), false));
+        assert_eq!(signed(negative(5)), (U256::from(5u64), true));
+    }
+}
@@ -3,7 +3,12 @@
 //! Types for EVM chain data including transactions, token transfers, and balances.
 //! Includes conversion methods to unified chain types for the accounting engine.
 
-use crate::chains::{ChainId, ChainTransaction, TokenTransfer, TransactionStatus, TransactionType};
+use super::fees;
+use crate::chains::units::{self, U256};
+use crate::chains::{
+    ChainId, ChainResult, ChainTransaction, FeeBreakdown, TokenTransfer, TransactionStatus,
+    TransactionType,
+};
 use serde::{Deserialize, Serialize};
 
 // =============================================================================
@@ -81,10 +86,11 @@ impl EvmTransaction {
 
         let tx_type = self.classify_transaction_type();
 
-        // Calculate fee: gas_used * gas_price
-        let gas_used: u128 = self.gas_used.parse().unwrap_or(0);
-        let gas_price: u128 = self.gas_price.parse().unwrap_or(0);
-        let fee = (gas_used * gas_price).to_string();
+        // Calculate fee: gas_used * gas_price (missing for pending transactions)
+        let fee_breakdown = self.fee_breakdown();
+        let fee = fee_breakdown
+            .as_ref()
+            .map_or_else(|| "0".to_string(), |breakdown| breakdown.total.clone());
 
         ChainTransaction {
             hash: self.hash.clone(),
@@ -102,6 +108,10 @@ impl EvmTransaction {
             status,
             tx_type,
             token_transfers: Vec::new(),
+            swaps: Vec::new(),
+            fee_breakdown,
+            user_operations: Vec::new(),
+            inner_calls: Vec::new(),
             raw_data: Some(serde_json::to_value(self).unwrap_or_default()),
         }
     }
@@ -201,6 +211,21 @@ impl EvmTransaction {
     pub fn effective_gas_price(&self) -> String {
         self.gas_price.clone()
     }
+
+    /// Split of the fee into base fee and tip, with the refund of a type-2
+    /// transaction. Explorers don't report the block's base fee, so it is
+    /// derived from the transaction's caps where they pin it down. `None`
+    /// for pending transactions, which haven't used any gas yet.
+    pub fn fee_breakdown(&self) -> Option<FeeBreakdown> {
+        let gas_used = units::parse_decimal(&self.gas_used).ok()?;
+        let prices = fees::GasPrices {
+            effective: units::parse_decimal(&self.effective_gas_price()).unwrap_or(U256::ZERO),
+            base_fee: None,
+            max_fee: fees::nonzero_decimal(&self.max_fee_per_gas),
+            max_priority_fee: units::parse_decimal(&self.max_priority_fee_per_gas).ok(),
+        };
+        Some(fees::breakdown(gas_used, prices))
+    }
 }
 
 // =============================================================================
@@ -288,7 +313,8 @@ pub struct Erc721Transfer {
     pub to: String,
     /// NFT contract address
     pub contract_address: String,
-    /// Token ID
+    /// Token ID (explorers send it as `tokenID`)
+    #[serde(alias = "tokenID")]
     pub token_id: String,
     /// Collection name
     pub token_name: String,
@@ -343,7 +369,8 @@ pub struct Erc1155Transfer {
     pub to: String,
     /// Contract address
     pub contract_address: String,
-    /// Token ID
+    /// Token ID (explorers send it as `tokenID`)
+    #[serde(alias = "tokenID")]
     pub token_id: String,
     /// Token value (amount for semi-fungible)
     pub token_value: String,
@@ -404,9 +431,9 @@ impl InternalTransaction {
         self.is_error == "1"
     }
 
-    /// Get value as u128
-    pub fn value_u128(&self) -> u128 {
-        self.value.parse().unwrap_or(0)
+    /// Get value in wei
+    pub fn value_wei(&self) -> ChainResult<U256> {
+        units::parse_decimal(&self.value)
     }
 
     /// Check if this is a contract creation
@@ -499,6 +526,7 @@ impl EvmTokenBalance {
             token_decimals: self.decimals,
             balance: self.balance.clone(),
             balance_formatted: self.balance_formatted.clone(),
+            extensions: None,
         }
     }
 
@@ -660,6 +688,84 @@ mod tests {
         assert_eq!(tx.classify_transaction_type(), TransactionType::Transfer);
     }
 
+    #[test]
+    fn test_fee_beyond_u128() {
+        let mut tx = EvmTransaction {
+            hash: "0x123".to_string(),
+            block_number: "1000".to_string(),
+            time_stamp: "1234567890".to_string(),
+            from: "0xabc".to_string(),
+            to: "0xdef".to_string(),
+            value: "0".to_string(),
+            gas: "21000".to_string(),
+            gas_price: "20000000000".to_string(),
+            gas_used: "21000".to_string(),
+            nonce: "1".to_string(),
+            is_error: "0".to_string(),
+            tx_receipt_status: "1".to_string(),
+            input: "0x".to_string(),
+            contract_address: String::default(),
+            function_name: String::default(),
+            method_id: String::default(),
+            confirmations: "100".to_string(),
+            cumulative_gas_used: "21000".to_string(),
+            max_fee_per_gas: String::default(),
+            max_priority_fee_per_gas: String::default(),
+        };
+        let chain = ChainId::evm("ethereum", 1);
+        assert_eq!(
+            tx.to_chain_transaction(chain.clone()).fee,
+            "420000000000000"
+        );
+
+        // A garbage gas price that would overflow u128 multiplication
+        tx.gas_price = u128::MAX.to_string();
+        assert_eq!(
+            tx.to_chain_transaction(chain.clone()).fee,
+            (U256::from(u128::MAX) * U256::from(21_000u64)).to_string()
+        );
+
+        // Pending transactions have no gas used yet
+        tx.gas_used = String::default();
+        assert_eq!(tx.to_chain_transaction(chain).fee, "0");
+    }
+
+    #[test]
+    fn test_type2_fee_breakdown() {
+        let tx = EvmTransaction {
+            hash: "0x123".to_string(),
+            block_number: "1000".to_string(),
+            time_stamp: "1234567890".to_string(),
+            from: "0xabc".to_string(),
+            to: "0xdef".to_string(),
+            value: "0".to_string(),
+            gas: "30000".to_string(),
+            gas_price: "12000000000".to_string(),
+            gas_used: "21000".to_string(),
+            nonce: "1".to_string(),
+            is_error: "0".to_string(),
+            tx_receipt_status: "1".to_string(),
+            input: "0x".to_string(),
+            contract_address: String::default(),
+            function_name: String::default(),
+            method_id: String::default(),
+            confirmations: "100".to_string(),
+            cumulative_gas_used: "21000".to_string(),
+            max_fee_per_gas: "20000000000".to_string(),
+            max_priority_fee_per_gas: "2000000000".to_string(),
+        };
+
+        let chain_tx = tx.to_chain_transaction(ChainId::evm("ethereum", 1));
+        assert_eq!(chain_tx.fee, "252000000000000");
+
+        // 10 gwei burned, 2 gwei tip, 8 gwei of the max fee refunded
+        let breakdown = chain_tx.fee_breakdown.unwrap();
+        assert_eq!(breakdown.total, chain_tx.fee);
+        assert_eq!(breakdown.base_fee.as_deref(), Some("210000000000000"));
+        assert_eq!(breakdown.priority_fee.as_deref(), Some("42000000000000"));
+        assert_eq!(breakdown.refunded.as_deref(), Some("168000000000000"));
+    }
+
     #[test]
     fn test_classify_swap() {
         let mut tx = EvmTransaction {
@@ -792,6 +898,9 @@ mod tests {
 
         assert!(!itx.is_failed());
         assert!(!itx.is_create());
-        assert_eq!(itx.value_u128(), 1000000000000000000u128);
+        assert_eq!(
+            itx.value_wei().unwrap(),
+            U256::from(1_000_000_000_000_000_000u128)
+        );
     }
 }
@@ -0,0 +1,212 @@
+//! ERC-4337 user operations
+//!
+//! Smart accounts act through an EntryPoint contract: a bundler submits
+//! `handleOps` with a batch of user operations and pays the gas, then each
+//! account refunds it from its deposit unless a paymaster sponsors the
+//! operation. The bundle's transaction is from the bundler, so the
+//! account's own actions and fees are read from the `UserOperationEvent`
+//! the EntryPoint emits for each operation.
+
+use serde_json::json;
+
+use super::alchemy::{AlchemyClient, Log};
+use crate::chains::units::U256;
+use crate::chains::{ChainResult, UserOperation};
+
+/// EntryPoint v0.6
+pub const ENTRY_POINT_V06: &str = "0x5ff137d4b0fdcd49dca30c7cf57e578a026d2789";
+/// EntryPoint v0.7
+pub const ENTRY_POINT_V07: &str = "0x0000000071727de22e5e9d8baf0edac6f37da032";
+
+/// UserOperationEvent(bytes32,address,address,uint256,bool,uint256,uint256),
+/// unchanged between EntryPoint versions
+const USER_OPERATION_EVENT_TOPIC: &str =
+    "0x49628fd1471006c1482da88028e9ce4dbb080b815c9b0344d39e5a8e6ec1419f";
+
+/// `handleOps` and `handleAggregatedOps` selectors of both EntryPoint
+/// versions
+const HANDLE_OPS_SELECTORS: [&str; 4] = [
+    "0x1fad948c", // handleOps (v0.6)
+    "0x4b1d7cf5", // handleAggregatedOps (v0.6)
+    "0x765e827f", // handleOps (v0.7)
+    "0xdbed18e0", // handleAggregatedOps (v0.7)
+];
+
+/// Whether `address` is a known EntryPoint.
+pub fn is_entry_point(address: &str) -> bool {
+    let address = address.to_lowercase();
+    address == ENTRY_POINT_V06 || address == ENTRY_POINT_V07
+}
+
+/// Whether a call to `to` with `input` submits a bundle of user
+/// operations.
+pub fn is_handle_ops(to: Option<&str>, input: &str) -> bool {
+    let selector = input.get(..10).unwrap_or_default().to_lowercase();
+    to.is_some_and(is_entry_point) && HANDLE_OPS_SELECTORS.contains(&selector.as_str())
+}
+
+/// Left-pads an address to a 32-byte topic.
+fn address_topic(address: &str) -> String {
+    format!("0x{:0>64}", address.trim_start_matches("0x").to_lowercase())
+}
+
+/// Address in the low 20 bytes of a topic.
+fn topic_address(topic: &str) -> Option<String> {
+    let digits = topic.trim_start_matches("0x");
+    (digits.len() == 64).then(|| format!("0x{}", digits[24..].to_lowercase()))
+}
+
+/// The `index`th 32-byte word of a log's data.
+fn data_word(data: &str, index: usize) -> Option<U256> {
+    let digits = data.trim_start_matches("0x");
+    let word = digits.get(index * 64..(index + 1) * 64)?;
+    U256::from_str_radix(word, 16).ok()
+}
+
+/// Decodes a `UserOperationEvent`. Other logs give `None`.
+pub fn decode_user_operation_event(log: &Log) -> Option<UserOperation> {
+    let topic = log.topics.first()?.to_lowercase();
+    if topic != USER_OPERATION_EVENT_TOPIC || log.topics.len() != 4 {
+        return None;
+    }
+    let paymaster = topic_address(&log.topics[3])?;
+    let zero_address = format!("0x{}", "0".repeat(40));
+
+    Some(UserOperation {
+        user_op_hash: log.topics[1].to_lowercase(),
+        sender: topic_address(&log.topics[2])?,
+        paymaster: (paymaster != zero_address).then_some(paymaster),
+        entry_point: log.address.to_lowercase(),
+        nonce: data_word(&log.data, 0)?.to_string(),
+        success: !data_word(&log.data, 1)?.is_zero(),
+        actual_gas_cost: data_word(&log.data, 2)?.to_string(),
+        actual_gas_used: data_word(&log.data, 3)?.to_string(),
+    })
+}
+
+/// A decoded user operation and the bundle transaction that carried it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundledOperation {
+    /// Hash of the bundler's `handleOps` transaction.
+    pub tx_hash: String,
+    /// The operation.
+    pub operation: UserOperation,
+}
+
+/// User operations `sender` submitted through either EntryPoint, in the
+/// given block range.
+pub async fn scan_user_operations(
+    rpc: &AlchemyClient,
+    sender: &str,
+    from_block: Option<u64>,
+    to_block: Option<u64>,
+) -> ChainResult<Vec<BundledOperation>> {
+    let filter = json!({
+        "address": [ENTRY_POINT_V06, ENTRY_POINT_V07],
+        "topics": [USER_OPERATION_EVENT_TOPIC, null, address_topic(sender)],
+    });
+    let logs = rpc
+        .scan_logs(&filter, from_block.unwrap_or(0), to_block)
+        .await?;
+
+    Ok(logs
+        .iter()
+        .filter_map(|log| {
+            Some(BundledOperation {
+                tx_hash: log.transaction_hash.clone()?,
+                operation: decode_user_operation_event(log)?,
+            })
+        })
+        .collect())
+}
+
+/// The fee an account paid for its operations: their actual gas cost,
+/// except where a paymaster sponsored them.
+pub fn account_fee(operations: &[UserOperation]) -> U256 {
+    operations
+        .iter()
+        .filter(|op| op.paymaster.is_none())
+        .filter_map(|op| U256::from_str_radix(&op.actual_gas_cost, 10).ok())
+        .fold(U256::ZERO, |total, cost| total.saturating_add(cost))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCOUNT: &str = "0x3a1b2c3d4e5f60718293a4b5c6d7e8f901234567";
+    const PAYMASTER: &str = "0x9406cc6185a346906296840746125a0e44976454";
+
+    fn word(value: u64) -> String {
+        format!("{:064x}", value)
+    }
+
+    fn event(paymaster: &str, success: bool, cost: u64) -> Log {
+        Log {
+            address: ENTRY_POINT_V07.to_string(),
+            topics: vec![
+                USER_OPERATION_EVENT_TOPIC.to_string(),
+                format!("0x{}", "ab".repeat(32)),
+                address_topic(ACCOUNT),
+                address_topic(paymaster),
+            ],
+            data: format!(
+                "0x{}{}{}{}",
+                word(7),
+                word(success as u64),
+                word(cost),
+                word(150_000)
+            ),
+            block_number: Some("0x10".to_string()),
+            transaction_hash: Some("0xbundle".to_string()),
+            transaction_index: None,
+            block_hash: None,
+            log_index: Some("0x1".to_string()),
+            removed: None,
+        }
+    }
+
+    #[test]
+    fn test_decode_user_operation_event() {
+        let op = decode_user_operation_event(&event(&"0".repeat(40), true, 42_000)).unwrap();
+        assert_eq!(op.sender, ACCOUNT);
+        assert_eq!(op.paymaster, None);
+        assert_eq!(op.entry_point, ENTRY_POINT_V07);
+        assert_eq!(op.nonce, "7");
+        assert!(op.success);
+        assert_eq!(op.actual_gas_cost, "42000");
+        assert_eq!(op.actual_gas_used, "150000");
+
+        let sponsored = decode_user_operation_event(&event(PAYMASTER, false, 1)).unwrap();
+        assert_eq!(sponsored.paymaster.as_deref(), Some(PAYMASTER));
+        assert!(!sponsored.success);
+
+        let mut other = event(PAYMASTER, true, 1);
+        other.topics[0] =
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".into();
+        assert_eq!(decode_user_operation_event(&other), None);
+    }
+
+    #[test]
+    fn test_account_fee_skips_sponsored_operations() {
+        let ops = [
+            decode_user_operation_event(&event(&"0".repeat(40), true, 42_000)).unwrap(),
+            decode_user_operation_event(&event(PAYMASTER, true, 99_000)).unwrap(),
+            decode_user_operation_event(&event(&"0".repeat(40), false, 8_000)).unwrap(),
+        ];
+        assert_eq!(account_fee(&ops), U256::from(50_000u64));
+    }
+
+    #[test]
+    fn test_is_handle_ops() {
+        let input = format!("0x765e827f{}", word(32));
+        assert!(is_handle_ops(Some(ENTRY_POINT_V07), &input));
+        assert!(is_handle_ops(
+            Some("0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789"),
+            "0x1fad948c"
+        ));
+        assert!(!is_handle_ops(Some(PAYMASTER), &input));
+        assert!(!is_handle_ops(None, &input));
+        assert!(!is_handle_ops(Some(ENTRY_POINT_V07), "0xa9059cbb"));
+    }
+}
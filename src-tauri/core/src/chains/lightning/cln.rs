@@ -0,0 +1,232 @@
+//! Core Lightning REST Client
+//!
+//! Reads completed payments and paid invoices through the `clnrest` plugin,
+//! which exposes each RPC method as `POST /v1/<method>` and authenticates
+//! with the `Rune` header.
+//!
+//! API documentation: https://docs.corelightning.org/docs/rest
+
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::chains::ChainResult;
+
+use super::{
+    de_u64, http_client, read_json, LightningNodeConfig, LightningPayment, PaymentDirection,
+};
+
+/// Header carrying the rune
+const RUNE_HEADER: &str = "Rune";
+
+// =============================================================================
+// RESPONSE TYPES
+// =============================================================================
+
+/// A payment the node sent, from `listpays`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClnPay {
+    /// Payment hash, hex.
+    pub payment_hash: String,
+    /// `pending`, `failed`, or `complete`.
+    pub status: String,
+    /// Destination node.
+    pub destination: Option<String>,
+    /// When the payment was initiated, in seconds since the Unix epoch.
+    pub created_at: u64,
+    /// When the payment completed, in seconds since the Unix epoch.
+    pub completed_at: Option<u64>,
+    /// Amount delivered, in millisatoshis.
+    #[serde(default, deserialize_with = "de_u64")]
+    pub amount_msat: u64,
+    /// Amount sent including routing fees, in millisatoshis.
+    #[serde(default, deserialize_with = "de_u64")]
+    pub amount_sent_msat: u64,
+    /// Invoice description.
+    pub description: Option<String>,
+}
+
+/// An invoice the node issued, from `listinvoices`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClnInvoice {
+    /// Payment hash, hex.
+    pub payment_hash: String,
+    /// `unpaid`, `paid`, or `expired`.
+    pub status: String,
+    /// Invoice description.
+    pub description: Option<String>,
+    /// Amount received, in millisatoshis.
+    #[serde(default, deserialize_with = "de_u64")]
+    pub amount_received_msat: u64,
+    /// When the invoice was paid, in seconds since the Unix epoch.
+    pub paid_at: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PaysResponse {
+    #[serde(default)]
+    pays: Vec<ClnPay>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InvoicesResponse {
+    #[serde(default)]
+    invoices: Vec<ClnInvoice>,
+}
+
+// =============================================================================
+// CLIENT
+// =============================================================================
+
+/// Core Lightning `clnrest` client
+pub struct ClnClient {
+    /// HTTP client trusting the node's certificate
+    client: Client,
+    /// REST endpoint
+    base_url: String,
+    /// Rune authorizing the calls
+    rune: String,
+}
+
+impl ClnClient {
+    /// Create a client for the node in `config`
+    pub fn new(config: &LightningNodeConfig) -> ChainResult<Self> {
+        Ok(Self {
+            client: http_client(config)?,
+            base_url: config.url.trim_end_matches('/').to_string(),
+            rune: config.credential.trim().to_string(),
+        })
+    }
+
+    async fn call<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> ChainResult<T> {
+        let response = self
+            .client
+            .post(format!("{}/v1/{}", self.base_url, method))
+            .header(RUNE_HEADER, &self.rune)
+            .json(&params)
+            .send()
+            .await;
+        read_json(response, "Core Lightning").await
+    }
+
+    /// Completed payments
+    pub async fn list_pays(&self) -> ChainResult<Vec<ClnPay>> {
+        let response: PaysResponse = self
+            .call("listpays", json!({ "status": "complete" }))
+            .await?;
+        Ok(response.pays)
+    }
+
+    /// Every invoice
+    pub async fn list_invoices(&self) -> ChainResult<Vec<ClnInvoice>> {
+        let response: InvoicesResponse = self.call("listinvoices", json!({})).await?;
+        Ok(response.invoices)
+    }
+
+    /// Completed payments and paid invoices, normalized
+    pub async fn fetch_payments(&self) -> ChainResult<Vec<LightningPayment>> {
+        let mut payments: Vec<LightningPayment> = self
+            .list_pays()
+            .await?
+            .iter()
+            .filter_map(pay_activity)
+            .collect();
+        payments.extend(
+            self.list_invoices()
+                .await?
+                .iter()
+                .filter_map(invoice_activity),
+        );
+        Ok(payments)
+    }
+}
+
+// =============================================================================
+// NORMALIZATION
+// =============================================================================
+
+/// A completed payment; the fee is what was sent beyond what was delivered.
+fn pay_activity(pay: &ClnPay) -> Option<LightningPayment> {
+    if pay.status != "complete" {
+        return None;
+    }
+    Some(LightningPayment {
+        amount_msat: pay.amount_msat,
+        fee_msat: pay.amount_sent_msat.saturating_sub(pay.amount_msat),
+        timestamp: pay.completed_at.unwrap_or(pay.created_at) as i64,
+        counterparty: pay.destination.clone(),
+        memo: pay.description.clone().filter(|d| !d.is_empty()),
+        ..LightningPayment::new(&pay.payment_hash, PaymentDirection::Sent)
+    })
+}
+
+fn invoice_activity(invoice: &ClnInvoice) -> Option<LightningPayment> {
+    if invoice.status != "paid" {
+        return None;
+    }
+    Some(LightningPayment {
+        amount_msat: invoice.amount_received_msat,
+        timestamp: invoice.paid_at.unwrap_or(0) as i64,
+        memo: invoice.description.clone().filter(|d| !d.is_empty()),
+        ..LightningPayment::new(&invoice.payment_hash, PaymentDirection::Received)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::lightning::LightningImplementation;
+    use crate::chains::mock_http::{fixture, mount_post, MockServer};
+
+    #[tokio::test]
+    async fn test_fetch_payments_normalizes_history() {
+        let server = MockServer::start().await;
+        mount_post(
+            &server,
+            "/v1/listpays",
+            json!({ "status": "complete" }),
+            fixture("cln/listpays.json"),
+        )
+        .await;
+        mount_post(
+            &server,
+            "/v1/listinvoices",
+            json!({}),
+            fixture("cln/listinvoices.json"),
+        )
+        .await;
+
+        let config = LightningNodeConfig {
+            implementation: LightningImplementation::Cln,
+            url: server.uri(),
+            credential: "tU-RLjMiDpY2U0o3W1oFowar36RFGpWloPbW9-RuZdo9MyZpZD0wMjRi".to_string(),
+            tls_cert: None,
+        };
+        let client = ClnClient::new(&config).unwrap();
+        let payments = client.fetch_payments().await.unwrap();
+
+        // The unpaid invoice is left out
+        assert_eq!(payments.len(), 2);
+
+        let sent = &payments[0];
+        assert_eq!(sent.direction, PaymentDirection::Sent);
+        assert_eq!(sent.amount_msat, 150_000_000);
+        assert_eq!(sent.fee_msat, 2_150);
+        assert_eq!(sent.timestamp, 1_713_571_603);
+
+        // Older releases report amounts as "<n>msat" strings
+        let received = &payments[1];
+        assert_eq!(received.direction, PaymentDirection::Received);
+        assert_eq!(received.amount_msat, 21_000_000);
+        assert_eq!(received.memo.as_deref(), Some("Membership dues"));
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests
+            .iter()
+            .all(|r| r.headers.get(RUNE_HEADER).is_some()));
+    }
+}
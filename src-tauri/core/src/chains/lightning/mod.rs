@@ -0,0 +1,217 @@
+//! Lightning Network Nodes
+//!
+//! Imports off-chain activity from the organization's own Lightning node.
+//! Payments sent and invoices settled never touch the chain, so they are read
+//! from the node's REST API, normalized to [`LightningPayment`] items, and
+//! stored as transactions of the wallet that represents the node.
+//!
+//! - LND: the REST proxy, authenticated with a hex-encoded macaroon;
+//!   `readonly.macaroon` is enough.
+//! - Core Lightning: the `clnrest` plugin, authenticated with a rune; one
+//!   restricted to the `list` methods is enough.
+//!
+//! Only completed activity is imported: payments still in flight or failed,
+//! and invoices unpaid or expired, moved nothing.
+
+/// Core Lightning `clnrest` client.
+pub mod cln;
+/// LND REST client.
+pub mod lnd;
+
+use std::time::Duration;
+
+use reqwest::{Certificate, Client};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::{ChainError, ChainResult};
+
+/// Timeout for node requests; nodes are usually on the local network
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// Node implementation a connection talks to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LightningImplementation {
+    /// LND, through its REST proxy.
+    Lnd,
+    /// Core Lightning, through the `clnrest` plugin.
+    Cln,
+}
+
+/// How to reach a Lightning node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LightningNodeConfig {
+    /// Node implementation.
+    pub implementation: LightningImplementation,
+    /// REST endpoint, e.g. `https://umbrel.local:8080`.
+    pub url: String,
+    /// Hex-encoded macaroon for LND, or a rune for Core Lightning.
+    pub credential: String,
+    /// PEM certificate of the node's self-signed TLS certificate, if any.
+    #[serde(default)]
+    pub tls_cert: Option<String>,
+}
+
+/// Whether a payment left or reached the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentDirection {
+    /// A payment the node sent.
+    Sent,
+    /// An invoice the node was paid.
+    Received,
+}
+
+/// A completed Lightning payment or settled invoice.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LightningPayment {
+    /// Stable identifier, unique per node; stored as the transaction hash.
+    pub id: String,
+    /// Payment hash shared by the payment and its invoice.
+    pub payment_hash: String,
+    /// Sent or received.
+    pub direction: PaymentDirection,
+    /// Amount delivered to the payee, in millisatoshis.
+    pub amount_msat: u64,
+    /// Routing fees paid on top of the amount, in millisatoshis; zero for
+    /// received invoices.
+    pub fee_msat: u64,
+    /// When the payment completed, in seconds since the Unix epoch.
+    pub timestamp: i64,
+    /// Destination node of a sent payment, where known.
+    pub counterparty: Option<String>,
+    /// Invoice description or memo.
+    pub memo: Option<String>,
+}
+
+impl LightningPayment {
+    /// Builds a payment, deriving its ID from the direction and hash so a
+    /// node paying its own invoice yields two distinct records.
+    pub fn new(payment_hash: &str, direction: PaymentDirection) -> Self {
+        let prefix = match direction {
+            PaymentDirection::Sent => "lightning:sent",
+            PaymentDirection::Received => "lightning:received",
+        };
+        Self {
+            id: format!("{}:{}", prefix, payment_hash),
+            payment_hash: payment_hash.to_string(),
+            direction,
+            amount_msat: 0,
+            fee_msat: 0,
+            timestamp: 0,
+            counterparty: None,
+            memo: None,
+        }
+    }
+}
+
+/// Fetches every completed payment and settled invoice of the node, oldest
+/// first.
+pub async fn fetch_payments(config: &LightningNodeConfig) -> ChainResult<Vec<LightningPayment>> {
+    let mut payments = match config.implementation {
+        LightningImplementation::Lnd => lnd::LndClient::new(config)?.fetch_payments().await?,
+        LightningImplementation::Cln => cln::ClnClient::new(config)?.fetch_payments().await?,
+    };
+    payments.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.id.cmp(&b.id)));
+    Ok(payments)
+}
+
+/// HTTP client trusting the node's own certificate when one is given.
+fn http_client(config: &LightningNodeConfig) -> ChainResult<Client> {
+    let mut builder = Client::builder().timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS));
+    if let Some(pem) = config.tls_cert.as_deref().filter(|p| !p.trim().is_empty()) {
+        let cert = Certificate::from_pem(pem.as_bytes())
+            .map_err(|e| ChainError::ConfigError(format!("Invalid TLS certificate: {}", e)))?;
+        builder = builder.add_root_certificate(cert);
+    }
+    builder
+        .build()
+        .map_err(|e| ChainError::Internal(format!("Failed to create HTTP client: {}", e)))
+}
+
+/// Reads a JSON response, mapping HTTP failures to chain errors.
+async fn read_json<T: serde::de::DeserializeOwned>(
+    response: reqwest::Result<reqwest::Response>,
+    node: &str,
+) -> ChainResult<T> {
+    let response = response.map_err(|e| {
+        if e.is_timeout() {
+            ChainError::ConnectionFailed(format!("{} request timeout", node))
+        } else {
+            ChainError::ConnectionFailed(format!("{} request failed: {}", node, e))
+        }
+    })?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        return Err(ChainError::ApiError(format!(
+            "{} rejected the credential (HTTP {})",
+            node, status
+        )));
+    }
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(ChainError::ApiError(format!(
+            "{} HTTP {}: {}",
+            node, status, body
+        )));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| ChainError::ParseError(format!("Failed to parse {} response: {}", node, e)))
+}
+
+/// Reads an amount or count sent as a number, a numeric string, or (older
+/// Core Lightning) a string with an `msat` suffix.
+fn de_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(u64),
+        Text(String),
+    }
+
+    match Raw::deserialize(deserializer)? {
+        Raw::Number(n) => Ok(n),
+        Raw::Text(s) => s
+            .trim()
+            .trim_end_matches("msat")
+            .parse()
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Amount {
+        #[serde(deserialize_with = "de_u64")]
+        value: u64,
+    }
+
+    #[test]
+    fn test_amount_forms() {
+        for raw in [
+            r#"{"value": 1500}"#,
+            r#"{"value": "1500"}"#,
+            r#"{"value": "1500msat"}"#,
+        ] {
+            let amount: Amount = serde_json::from_str(raw).unwrap();
+            assert_eq!(amount.value, 1500);
+        }
+        assert!(serde_json::from_str::<Amount>(r#"{"value": "abc"}"#).is_err());
+    }
+
+    #[test]
+    fn test_payment_ids_are_distinct_by_direction() {
+        let sent = LightningPayment::new("ab12", PaymentDirection::Sent);
+        let received = LightningPayment::new("ab12", PaymentDirection::Received);
+        assert_eq!(sent.id, "lightning:sent:ab12");
+        assert_ne!(sent.id, received.id);
+    }
+}
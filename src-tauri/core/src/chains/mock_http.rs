@@ -0,0 +1,104 @@
+//! HTTP mocking for provider client tests.
+//!
+//! Serves recorded provider responses from `tests/fixtures` on a local
+//! [`MockServer`], so pagination and normalization can be exercised without
+//! network access or API keys. Point a client at [`MockServer::uri`] instead
+//! of the provider's base URL.
+
+use serde_json::{json, Value};
+use wiremock::matchers::{body_partial_json, body_string_contains, method, path, query_param};
+use wiremock::{Mock, ResponseTemplate};
+
+pub use wiremock::MockServer;
+
+/// Reads a recorded response, e.g. `fixture("etherscan/txlist.json")`.
+pub fn fixture(name: &str) -> Value {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+    serde_json::from_str(&text).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+/// `count` copies of `record`, each with `id_field` made unique by a
+/// numeric suffix, for filling a full page from one recorded record.
+pub fn repeat_record(record: &Value, count: usize, id_field: &str) -> Value {
+    let id = record[id_field].as_str().unwrap_or_default();
+    let records = (0..count)
+        .map(|i| {
+            let mut copy = record.clone();
+            copy[id_field] = json!(format!("{}{:04}", id, i));
+            copy
+        })
+        .collect();
+    Value::Array(records)
+}
+
+/// Answers GET requests to `route` whose query contains every pair in
+/// `query` with `body`. Mocks matching more pairs take precedence, so a
+/// follow-up page can be mounted alongside the first.
+pub async fn mount_get(server: &MockServer, route: &str, query: &[(&str, &str)], body: Value) {
+    let mut mock = Mock::given(method("GET")).and(path(route));
+    for (name, value) in query {
+        mock = mock.and(query_param(*name, *value));
+    }
+    let priority = 10u8.saturating_sub(query.len() as u8).max(1);
+    mock.respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .with_priority(priority)
+        .mount(server)
+        .await;
+}
+
+/// Answers JSON-RPC calls to `rpc_method` with a recorded response.
+pub async fn mount_rpc(server: &MockServer, rpc_method: &str, body: Value) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": rpc_method })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .mount(server)
+        .await;
+}
+
+/// Answers POST requests to `route` whose JSON body contains `fields` with
+/// `body`. Mocks matching more fields take precedence, so a follow-up page
+/// can be mounted alongside the first.
+pub async fn mount_post(server: &MockServer, route: &str, fields: Value, body: Value) {
+    let field_count = fields.as_object().map_or(0, |f| f.len());
+    let priority = 10u8.saturating_sub(field_count as u8).max(1);
+    Mock::given(method("POST"))
+        .and(path(route))
+        .and(body_partial_json(fields))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .with_priority(priority)
+        .mount(server)
+        .await;
+}
+
+/// Answers `eth_call` requests whose call data contains `selector`, for
+/// contract reads that share the one JSON-RPC method.
+pub async fn mount_eth_call(server: &MockServer, selector: &str, body: Value) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": "eth_call" })))
+        .and(body_string_contains(selector))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .mount(server)
+        .await;
+}
+
+/// Answers GraphQL queries whose body contains `field`, e.g. the queried
+/// entity, for subgraphs that serve every query from one endpoint.
+pub async fn mount_graphql(server: &MockServer, field: &str, body: Value) {
+    Mock::given(method("POST"))
+        .and(body_string_contains(field))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .mount(server)
+        .await;
+}
+
+/// Number of requests the server has received for `route`.
+pub async fn requests_to(server: &MockServer, route: &str) -> usize {
+    server
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .iter()
+        .filter(|request| request.url.path() == route)
+        .count()
+}
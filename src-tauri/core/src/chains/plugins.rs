@@ -0,0 +1,492 @@
+//! Chain definitions loaded from manifests at runtime.
+//!
+//! A chain that speaks an explorer API and address format this crate
+//! already supports can be added without forking: put a JSON manifest in
+//! the [`PLUGIN_DIR`] directory next to the database and it is registered
+//! at startup alongside the built-in chains.
+//!
+//! ```json
+//! {
+//!   "id": "zora",
+//!   "name": "Zora",
+//!   "chainType": "evm",
+//!   "chainId": 7777777,
+//!   "symbol": "ETH",
+//!   "rpcUrl": "https://rpc.zora.energy",
+//!   "explorerApi": "blockscout",
+//!   "explorerApiUrl": "https://explorer.zora.energy/api",
+//!   "addressFormat": "evm_hex",
+//!   "isL2": true
+//! }
+//! ```
+//!
+//! `rpcUrl` may contain `{apiKey}`, filled from the environment variable
+//! named by `rpcApiKeyEnv`.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{OnceLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use super::address::AddressFormat;
+use super::bitcoin::{self, BitcoinAdapter, BitcoinConfig};
+use super::evm::config::{EvmChainConfig, RollupStack};
+use super::evm::{self, EvmAdapter};
+use super::solana;
+use super::{format_chain_name, ChainAdapter, ChainError, ChainInfo, ChainResult, ChainType};
+
+/// Directory, next to the database, that manifests are loaded from.
+pub const PLUGIN_DIR: &str = "chain_plugins";
+
+/// Placeholder in `rpcUrl` replaced with the RPC API key.
+const API_KEY_PLACEHOLDER: &str = "{apiKey}";
+
+/// Explorer API a plugin chain's explorer implements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExplorerApi {
+    /// Etherscan's `?module=account&action=txlist` API.
+    Etherscan,
+    /// Blockscout, through its Etherscan-compatible `/api` endpoint.
+    Blockscout,
+    /// Esplora's REST API, as served by mempool.space and Blockstream.
+    Esplora,
+}
+
+/// A chain defined by a plugin manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainManifest {
+    /// Chain identifier used for wallets, e.g. "zora".
+    pub id: String,
+    /// Display name; derived from `id` when omitted.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Chain family: `evm` or `bitcoin`.
+    pub chain_type: ChainType,
+    /// Numeric chain ID, required for EVM chains.
+    #[serde(default)]
+    pub chain_id: Option<u64>,
+    /// Native currency symbol.
+    pub symbol: String,
+    /// Native currency decimals; 18 for EVM and 8 for Bitcoin when omitted.
+    #[serde(default)]
+    pub decimals: Option<u8>,
+    /// JSON-RPC endpoint, required for EVM chains.
+    #[serde(default)]
+    pub rpc_url: Option<String>,
+    /// Environment variable holding the key substituted for `{apiKey}`.
+    #[serde(default)]
+    pub rpc_api_key_env: Option<String>,
+    /// Public JSON-RPC endpoints to fail over to when `rpcUrl` is down.
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    /// API family of the explorer.
+    pub explorer_api: ExplorerApi,
+    /// Explorer API base URL.
+    pub explorer_api_url: String,
+    /// Environment variable holding the explorer API key.
+    #[serde(default)]
+    pub explorer_api_key_env: Option<String>,
+    /// Explorer website, shown in the UI.
+    #[serde(default)]
+    pub explorer_url: Option<String>,
+    /// Address encoding the chain uses.
+    pub address_format: AddressFormat,
+    /// Whether the chain is a testnet.
+    #[serde(default)]
+    pub is_testnet: bool,
+    /// Whether the chain is a Layer 2 network.
+    #[serde(default)]
+    pub is_l2: bool,
+    /// Rollup stack, so the L1 data fee in receipts is counted.
+    #[serde(default)]
+    pub rollup: Option<RollupStack>,
+    /// Average block time in seconds.
+    #[serde(default)]
+    pub block_time_seconds: Option<u64>,
+    /// Most blocks the RPC endpoint allows one `eth_getLogs` request to span.
+    #[serde(default)]
+    pub max_log_block_range: Option<u64>,
+}
+
+/// Whether `url` is HTTPS, or plain HTTP to this machine.
+fn is_allowed_url(url: &str) -> bool {
+    url.starts_with("https://")
+        || url.starts_with("http://localhost")
+        || url.starts_with("http://127.0.0.1")
+}
+
+/// Whether `chain_id` names a built-in chain.
+fn is_builtin(chain_id: &str) -> bool {
+    evm::config::get_chain_by_name(chain_id).is_some()
+        || bitcoin::get_config_by_name(chain_id).is_some()
+        || solana::get_config_by_name(chain_id).is_some()
+        || chain_id
+            .parse::<u64>()
+            .is_ok_and(|id| evm::config::get_chain_config(id).is_some())
+}
+
+impl ChainManifest {
+    /// Checks the manifest describes a chain this crate can talk to.
+    pub fn validate(&self) -> Result<(), String> {
+        let id_chars_ok = self
+            .id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if self.id.is_empty() || !id_chars_ok {
+            return Err(format!(
+                "id \"{}\" must be lowercase letters, digits, and underscores",
+                self.id
+            ));
+        }
+        if self.id.parse::<u64>().is_ok() || is_builtin(&self.id) {
+            return Err(format!("id \"{}\" is taken by a built-in chain", self.id));
+        }
+        if !is_allowed_url(&self.explorer_api_url) {
+            return Err("explorerApiUrl must use https".to_string());
+        }
+
+        match self.chain_type {
+            ChainType::Evm => {
+                let chain_id = self.chain_id.ok_or("chainId is required for EVM chains")?;
+                if evm::config::get_chain_config(chain_id).is_some() {
+                    return Err(format!("chainId {} is a built-in chain", chain_id));
+                }
+                let rpc_url = self
+                    .rpc_url
+                    .as_deref()
+                    .ok_or("rpcUrl is required for EVM chains")?;
+                if !is_allowed_url(rpc_url) {
+                    return Err("rpcUrl must use https".to_string());
+                }
+                if !self.fallback_rpc_urls.iter().all(|url| is_allowed_url(url)) {
+                    return Err("fallbackRpcUrls must use https".to_string());
+                }
+                if self.max_log_block_range == Some(0) {
+                    return Err("maxLogBlockRange must be at least 1".to_string());
+                }
+                if rpc_url.contains(API_KEY_PLACEHOLDER) && self.rpc_api_key_env.is_none() {
+                    return Err(format!(
+                        "rpcUrl uses {} but rpcApiKeyEnv is not set",
+                        API_KEY_PLACEHOLDER
+                    ));
+                }
+                if !matches!(
+                    self.explorer_api,
+                    ExplorerApi::Etherscan | ExplorerApi::Blockscout
+                ) {
+                    return Err("EVM chains need an etherscan or blockscout explorer".to_string());
+                }
+                if self.address_format != AddressFormat::EvmHex {
+                    return Err("EVM chains use the evm_hex address format".to_string());
+                }
+            }
+            ChainType::Bitcoin => {
+                if self.explorer_api != ExplorerApi::Esplora {
+                    return Err("Bitcoin chains need an esplora explorer".to_string());
+                }
+                if !matches!(
+                    self.address_format,
+                    AddressFormat::BitcoinBase58 | AddressFormat::BitcoinBech32
+                ) {
+                    return Err("Bitcoin chains use a bitcoin address format".to_string());
+                }
+            }
+            other => {
+                return Err(format!("{:?} chains can't be added by manifest", other));
+            }
+        }
+        Ok(())
+    }
+
+    /// Native currency decimals.
+    pub fn decimals(&self) -> u8 {
+        self.decimals.unwrap_or(match self.chain_type {
+            ChainType::Bitcoin => 8,
+            _ => 18,
+        })
+    }
+
+    /// RPC URL with `{apiKey}` filled in.
+    pub fn rpc_url(&self) -> ChainResult<String> {
+        let url = self
+            .rpc_url
+            .clone()
+            .ok_or_else(|| ChainError::ConfigError(format!("{} has no rpcUrl", self.id)))?;
+        if !url.contains(API_KEY_PLACEHOLDER) {
+            return Ok(url);
+        }
+
+        let env_var = self.rpc_api_key_env.as_deref().unwrap_or_default();
+        let key = std::env::var(env_var).map_err(|_| {
+            ChainError::ConfigError(format!("{} needs {} to be set", self.id, env_var))
+        })?;
+        Ok(url.replace(API_KEY_PLACEHOLDER, &key))
+    }
+
+    /// Configuration for the EVM adapter.
+    pub fn evm_config(&self) -> ChainResult<EvmChainConfig> {
+        let mut config = EvmChainConfig::new(
+            self.chain_id.unwrap_or_default(),
+            &self.id,
+            &self.symbol,
+            self.rpc_url()?,
+            &self.explorer_api_url,
+            self.is_l2,
+            self.block_time_seconds.unwrap_or(12),
+        );
+        config.decimals = self.decimals();
+        config.fallback_rpc_urls = self.fallback_rpc_urls.clone();
+        config.max_log_block_range = self.max_log_block_range;
+        if let Some(env_var) = &self.explorer_api_key_env {
+            config = config.with_explorer_key_env(env_var);
+        }
+        if let Some(rollup) = self.rollup {
+            config = config.with_rollup(rollup);
+        }
+        Ok(config)
+    }
+
+    /// Configuration for the Bitcoin adapter.
+    pub fn bitcoin_config(&self) -> BitcoinConfig {
+        BitcoinConfig {
+            name: self.id.clone(),
+            is_testnet: self.is_testnet,
+            api_url: self.explorer_api_url.clone(),
+            symbol: self.symbol.clone(),
+            decimals: self.decimals(),
+        }
+    }
+
+    /// Creates an adapter for the chain.
+    pub fn create_adapter(
+        &self,
+        explorer_key: Option<String>,
+        rpc_overrides: Vec<String>,
+    ) -> ChainResult<Box<dyn ChainAdapter>> {
+        match self.chain_type {
+            ChainType::Evm => {
+                let mut adapter = EvmAdapter::from_config(self.evm_config()?);
+                if let Some(key) = explorer_key {
+                    adapter = adapter.with_explorer_api_key(key);
+                }
+                if !rpc_overrides.is_empty() {
+                    adapter = adapter.with_rpc_urls(rpc_overrides);
+                }
+                Ok(Box::new(adapter))
+            }
+            ChainType::Bitcoin => Ok(Box::new(BitcoinAdapter::with_config(
+                self.bitcoin_config(),
+            )?)),
+            _ => Err(ChainError::UnsupportedChain(self.id.clone())),
+        }
+    }
+
+    /// Chain details for the UI.
+    pub fn chain_info(&self) -> ChainInfo {
+        ChainInfo {
+            chain_id: self.id.clone(),
+            name: self
+                .name
+                .clone()
+                .unwrap_or_else(|| format_chain_name(&self.id)),
+            symbol: self.symbol.clone(),
+            chain_type: self.chain_type,
+            numeric_chain_id: self.chain_id,
+            decimals: self.decimals(),
+            logo_url: None,
+            is_testnet: self.is_testnet,
+            explorer_url: self.explorer_url.clone(),
+        }
+    }
+}
+
+// =============================================================================
+// REGISTRY
+// =============================================================================
+
+/// Registered plugin chains, by ID.
+fn registry() -> &'static RwLock<HashMap<String, ChainManifest>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<String, ChainManifest>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Validates and registers a manifest, replacing one with the same ID.
+pub fn register(manifest: ChainManifest) -> Result<(), String> {
+    manifest.validate()?;
+    let mut chains = registry().write().unwrap_or_else(|e| e.into_inner());
+    let clashes = chains.values().any(|other| {
+        other.id != manifest.id
+            && manifest.chain_id.is_some()
+            && other.chain_id == manifest.chain_id
+    });
+    if clashes {
+        return Err(format!(
+            "chainId {} is already registered",
+            manifest.chain_id.unwrap_or_default()
+        ));
+    }
+    chains.insert(manifest.id.clone(), manifest);
+    Ok(())
+}
+
+/// The plugin chain `chain_id` names, by ID or numeric EVM chain ID.
+pub fn find(chain_id: &str) -> Option<ChainManifest> {
+    let chains = registry().read().unwrap_or_else(|e| e.into_inner());
+    if let Some(manifest) = chains.get(&chain_id.to_lowercase()) {
+        return Some(manifest.clone());
+    }
+    let numeric = chain_id.parse::<u64>().ok()?;
+    chains
+        .values()
+        .find(|m| m.chain_type == ChainType::Evm && m.chain_id == Some(numeric))
+        .cloned()
+}
+
+/// All plugin chains, sorted by ID.
+pub fn all() -> Vec<ChainManifest> {
+    let chains = registry().read().unwrap_or_else(|e| e.into_inner());
+    let mut manifests: Vec<_> = chains.values().cloned().collect();
+    manifests.sort_by(|a, b| a.id.cmp(&b.id));
+    manifests
+}
+
+/// Registers every `*.json` manifest in `dir`. A missing directory has no
+/// plugins. Returns one message per manifest that couldn't be registered.
+pub fn load_dir(dir: &Path) -> Vec<String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut errors = Vec::new();
+    for path in paths {
+        let result = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| {
+                serde_json::from_str::<ChainManifest>(&text).map_err(|e| e.to_string())
+            })
+            .and_then(register);
+        if let Err(e) = result {
+            errors.push(format!("{}: {}", path.display(), e));
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn evm_manifest(id: &str, chain_id: u64) -> ChainManifest {
+        serde_json::from_value(json!({
+            "id": id,
+            "chainType": "evm",
+            "chainId": chain_id,
+            "symbol": "ETH",
+            "rpcUrl": "https://rpc.example.org",
+            "explorerApi": "blockscout",
+            "explorerApiUrl": "https://explorer.example.org/api",
+            "addressFormat": "evm_hex"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validate_rejects_unusable_manifests() {
+        assert!(evm_manifest("plugin_valid", 990001).validate().is_ok());
+
+        let builtin = evm_manifest("ethereum", 990002);
+        assert!(builtin.validate().unwrap_err().contains("built-in"));
+
+        let builtin_id = evm_manifest("plugin_mainnet_copy", 1);
+        assert!(builtin_id.validate().unwrap_err().contains("built-in"));
+
+        let mut wrong_explorer = evm_manifest("plugin_esplora", 990003);
+        wrong_explorer.explorer_api = ExplorerApi::Esplora;
+        assert!(wrong_explorer.validate().is_err());
+
+        let mut plain_http = evm_manifest("plugin_http", 990004);
+        plain_http.rpc_url = Some("http://rpc.example.org".to_string());
+        assert_eq!(plain_http.validate().unwrap_err(), "rpcUrl must use https");
+
+        let mut no_key_env = evm_manifest("plugin_keyed", 990005);
+        no_key_env.rpc_url = Some("https://rpc.example.org/v2/{apiKey}".to_string());
+        assert!(no_key_env.validate().unwrap_err().contains("rpcApiKeyEnv"));
+
+        let mut solana = evm_manifest("plugin_solana", 990006);
+        solana.chain_type = ChainType::Solana;
+        assert!(solana.validate().is_err());
+    }
+
+    #[test]
+    fn test_rpc_url_template() {
+        let mut manifest = evm_manifest("plugin_template", 990010);
+        manifest.rpc_url = Some("https://rpc.example.org/v2/{apiKey}".to_string());
+        manifest.rpc_api_key_env = Some("PACIOLI_TEST_PLUGIN_RPC_KEY".to_string());
+
+        assert!(matches!(
+            manifest.rpc_url(),
+            Err(ChainError::ConfigError(_))
+        ));
+        std::env::set_var("PACIOLI_TEST_PLUGIN_RPC_KEY", "secret");
+        assert_eq!(
+            manifest.rpc_url().unwrap(),
+            "https://rpc.example.org/v2/secret"
+        );
+    }
+
+    #[test]
+    fn test_registered_chains_are_found_and_listed() {
+        register(evm_manifest("plugin_listed", 990020)).unwrap();
+
+        assert_eq!(find("plugin_listed").unwrap().chain_id, Some(990020));
+        assert_eq!(find("990020").unwrap().id, "plugin_listed");
+        assert!(find("plugin_unknown").is_none());
+
+        let clash = register(evm_manifest("plugin_clash", 990020)).unwrap_err();
+        assert!(clash.contains("already registered"));
+
+        let info = find("plugin_listed").unwrap().chain_info();
+        assert_eq!(info.name, "Plugin Listed");
+        assert_eq!(info.decimals, 18);
+        assert!(all().iter().any(|m| m.id == "plugin_listed"));
+    }
+
+    #[test]
+    fn test_load_dir_reports_bad_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = json!({
+            "id": "plugin_regtest",
+            "chainType": "bitcoin",
+            "symbol": "rBTC",
+            "explorerApi": "esplora",
+            "explorerApiUrl": "http://localhost:3002/api",
+            "addressFormat": "bitcoin_bech32",
+            "isTestnet": true
+        });
+        std::fs::write(dir.path().join("regtest.json"), good.to_string()).unwrap();
+        std::fs::write(dir.path().join("broken.json"), "{").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let errors = load_dir(dir.path());
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("broken.json"));
+
+        let manifest = find("plugin_regtest").unwrap();
+        assert_eq!(manifest.decimals(), 8);
+        let adapter = manifest.create_adapter(None, Vec::new()).unwrap();
+        assert_eq!(adapter.chain_id().chain_type, ChainType::Bitcoin);
+
+        assert!(load_dir(&dir.path().join("missing")).is_empty());
+    }
+}
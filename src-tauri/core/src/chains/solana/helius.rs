@@ -4,7 +4,7 @@
 //! and the DAS (Digital Asset Standard) API for token balances.
 
 use crate::chains::{ChainError, ChainResult};
-use crate::fetchers::{FetcherConfig, ResilientFetcher};
+use crate::fetchers::{usage, ApiProvider, FetcherConfig, ResilientFetcher};
 
 use std::num::NonZeroU32;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -44,6 +44,8 @@ pub struct HeliusClient {
     rpc_limiter: Arc<GovernorLimiter>,
     /// API key
     api_key: String,
+    /// REST API base URL
+    rest_base: String,
     /// RPC endpoint URL (with API key)
     rpc_url: String,
     /// RPC request ID counter
@@ -58,13 +60,26 @@ impl HeliusClient {
 
     /// Create a new Helius client with custom rate limit
     pub fn with_rate_limit(api_key: &str, rate_limit_rps: u32) -> ChainResult<Self> {
+        Self::with_endpoints(api_key, HELIUS_REST_BASE, HELIUS_RPC_BASE, rate_limit_rps)
+    }
+
+    /// Create a new Helius client against custom REST and RPC base URLs
+    pub fn with_endpoints(
+        api_key: &str,
+        rest_base: &str,
+        rpc_base: &str,
+        rate_limit_rps: u32,
+    ) -> ChainResult<Self> {
+        let rest_base = rest_base.trim_end_matches('/').to_string();
+
         // REST fetcher for GET requests
         let rest_config = FetcherConfig {
-            base_url: HELIUS_REST_BASE.to_string(),
+            base_url: rest_base.clone(),
             api_key: Some(api_key.to_string()),
             requests_per_second: rate_limit_rps,
             timeout_secs: 30,
             max_retries: 3,
+            provider: Some(ApiProvider::Helius),
         };
 
         let rest_fetcher = ResilientFetcher::new(rest_config)
@@ -80,13 +95,14 @@ impl HeliusClient {
             .ok_or_else(|| ChainError::ConfigError("Rate limit must be > 0".to_string()))?;
         let rpc_limiter = Arc::new(RateLimiter::direct(Quota::per_second(rps)));
 
-        let rpc_url = format!("{}/?api-key={}", HELIUS_RPC_BASE, api_key);
+        let rpc_url = format!("{}/?api-key={}", rpc_base.trim_end_matches('/'), api_key);
 
         Ok(Self {
             rest_fetcher,
             rpc_client,
             rpc_limiter,
             api_key: api_key.to_string(),
+            rest_base,
             rpc_url,
             request_id: AtomicU64::new(1),
         })
@@ -104,6 +120,7 @@ impl HeliusClient {
         params: serde_json::Value,
     ) -> ChainResult<T> {
         self.rpc_limiter.until_ready().await;
+        usage::record_call(ApiProvider::Helius);
 
         let id = self.next_id();
         let body = json!({
@@ -170,7 +187,7 @@ impl HeliusClient {
 
         let mut url = format!(
             "{}/addresses/{}/transactions?api-key={}&type=ALL&limit={}",
-            HELIUS_REST_BASE, address, self.api_key, limit
+            self.rest_base, address, self.api_key, limit
         );
 
         if let Some(before_sig) = before {
@@ -265,6 +282,14 @@ impl HeliusClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::chains::mock_http::{fixture, mount_get, mount_rpc, repeat_record, MockServer};
+
+    const OWNER: &str = "86xCnPeV69n6t3DnyGvkKobf9FdN2H9oiVDdaMpo2MMY";
+
+    fn create_mock_client(server: &MockServer) -> HeliusClient {
+        let rest_base = format!("{}/v0", server.uri());
+        HeliusClient::with_endpoints("test_key", &rest_base, &server.uri(), 100).unwrap()
+    }
 
     #[test]
     fn test_rest_url_construction() {
@@ -304,4 +329,65 @@ mod tests {
         assert_eq!(DEFAULT_RATE_LIMIT_RPS, 5);
         assert_eq!(TURBO_RATE_LIMIT_RPS, 30);
     }
+
+    #[tokio::test]
+    async fn test_parsed_transactions_from_fixture() {
+        let server = MockServer::start().await;
+        mount_get(
+            &server,
+            &format!("/v0/addresses/{}/transactions", OWNER),
+            &[("api-key", "test_key"), ("type", "ALL"), ("limit", "100")],
+            fixture("helius/transactions.json"),
+        )
+        .await;
+
+        let client = create_mock_client(&server);
+        let txs: Vec<SolanaTransaction> = client
+            .get_parsed_transactions(OWNER, None, None)
+            .await
+            .unwrap()
+            .iter()
+            .map(|tx| tx.to_solana_transaction())
+            .collect();
+
+        assert_eq!(txs.len(), 2);
+        assert_eq!(txs[0].tx_type, SolanaTransactionType::Transfer);
+        assert_eq!(txs[0].native_transfers[0].to, OWNER);
+        assert_eq!(txs[0].native_transfers[0].amount, 500_000_000);
+        assert_eq!(txs[1].tx_type, SolanaTransactionType::Swap);
+        assert_eq!(txs[1].source_program, "Jupiter");
+        assert_eq!(txs[1].token_transfers.len(), 2);
+        assert_eq!(txs[1].fee, 10_250);
+    }
+
+    #[tokio::test]
+    async fn test_all_transactions_paginate_with_before() {
+        let server = MockServer::start().await;
+        let route = format!("/v0/addresses/{}/transactions", OWNER);
+        let recorded = fixture("helius/transactions.json");
+        let full_page = repeat_record(&recorded[0], TXS_PER_PAGE, "signature");
+        let cursor = full_page[TXS_PER_PAGE - 1]["signature"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        mount_get(&server, &route, &[], full_page).await;
+        mount_get(&server, &route, &[("before", cursor.as_str())], recorded).await;
+
+        let client = create_mock_client(&server);
+        let txs = client.get_all_transactions(OWNER, None).await.unwrap();
+
+        // A short second page ends the walk
+        assert_eq!(txs.len(), TXS_PER_PAGE + 2);
+        assert_eq!(txs[TXS_PER_PAGE].tx_type, "TRANSFER");
+        assert_eq!(txs[TXS_PER_PAGE + 1].tx_type, "SWAP");
+    }
+
+    #[tokio::test]
+    async fn test_balance_from_fixture() {
+        let server = MockServer::start().await;
+        mount_rpc(&server, "getBalance", fixture("helius/getBalance.json")).await;
+
+        let client = create_mock_client(&server);
+        assert_eq!(client.get_balance(OWNER).await.unwrap(), 2_500_000_000);
+    }
 }
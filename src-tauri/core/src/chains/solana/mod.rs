@@ -6,21 +6,29 @@
 
 /// Helius Enhanced API client for enriched Solana data.
 pub mod helius;
+/// Parsing and program-based classification of standard RPC transactions.
+pub mod parsed;
 /// Solana JSON-RPC client (public endpoint fallback).
 pub mod rpc;
+/// SPL Token-2022 extension parsing (transfer fees, interest-bearing mints).
+pub mod token2022;
 /// Solana-specific types for transactions, tokens, and DAS assets.
 pub mod types;
 
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::OnceCell;
 
+use crate::chains::units::{self, U256};
 use crate::chains::{
     ChainAdapter, ChainError, ChainId, ChainResult, ChainTransaction, ChainType, NativeBalance,
-    TokenBalance, TokenTransfer, TransactionStatus, TransactionType,
+    TokenBalance, TokenExtensions, TokenTransfer, TransactionStatus, TransactionType,
 };
+use crate::log_error;
 
 pub use types::{SolanaBalance, SolanaTokenAccount, SolanaTransaction};
+use types::{TOKEN_2022_PROGRAM, TOKEN_PROGRAM};
 
 /// Solana network configuration
 #[derive(Debug, Clone)]
@@ -86,9 +94,9 @@ pub struct SolanaAdapter {
     /// Network configuration
     config: SolanaConfig,
     /// Standard RPC client (always available)
-    rpc_client: Arc<RwLock<Option<rpc::SolanaRpcClient>>>,
+    rpc_client: OnceCell<Arc<rpc::SolanaRpcClient>>,
     /// Helius client (only when API key is provided)
-    helius_client: Arc<RwLock<Option<helius::HeliusClient>>>,
+    helius_client: OnceCell<Arc<helius::HeliusClient>>,
     /// Helius API key (if configured)
     helius_api_key: Option<String>,
 }
@@ -105,8 +113,8 @@ impl SolanaAdapter {
         Ok(Self {
             chain_id,
             config,
-            rpc_client: Arc::new(RwLock::new(None)),
-            helius_client: Arc::new(RwLock::new(None)),
+            rpc_client: OnceCell::new(),
+            helius_client: OnceCell::new(),
             helius_api_key: None,
         })
     }
@@ -125,38 +133,23 @@ impl SolanaAdapter {
     }
 
     /// Get or initialize the standard RPC client
-    async fn get_rpc_client(&self) -> ChainResult<rpc::SolanaRpcClient> {
-        {
-            let guard = self.rpc_client.read().await;
-            if guard.is_some() {
-                return rpc::SolanaRpcClient::with_url(&self.config.rpc_url, 2);
-            }
-        }
-
-        let client = rpc::SolanaRpcClient::with_url(&self.config.rpc_url, 2)?;
-        let mut guard = self.rpc_client.write().await;
-        *guard = Some(rpc::SolanaRpcClient::with_url(&self.config.rpc_url, 2)?);
-
-        Ok(client)
+    async fn get_rpc_client(&self) -> ChainResult<Arc<rpc::SolanaRpcClient>> {
+        self.rpc_client
+            .get_or_try_init(|| async {
+                rpc::SolanaRpcClient::with_url(&self.config.rpc_url, 2).map(Arc::new)
+            })
+            .await
+            .cloned()
     }
 
     /// Get or initialize the Helius client (only if API key is available)
-    async fn get_helius_client(&self) -> Option<ChainResult<helius::HeliusClient>> {
+    async fn get_helius_client(&self) -> Option<ChainResult<Arc<helius::HeliusClient>>> {
         let api_key = self.helius_api_key.as_ref()?;
-
-        {
-            let guard = self.helius_client.read().await;
-            if guard.is_some() {
-                return Some(helius::HeliusClient::new(api_key));
-            }
-        }
-
-        let client = helius::HeliusClient::new(api_key);
-        let mut guard = self.helius_client.write().await;
-        if let Ok(stored) = helius::HeliusClient::new(api_key) {
-            *guard = Some(stored);
-        }
-
+        let client = self
+            .helius_client
+            .get_or_try_init(|| async { helius::HeliusClient::new(api_key).map(Arc::new) })
+            .await
+            .cloned();
         Some(client)
     }
 
@@ -182,32 +175,44 @@ impl SolanaAdapter {
             return Ok(txs);
         }
 
-        // Fallback: use standard RPC
+        // Fallback: use standard RPC, fetching each transaction to read its
+        // legs and the programs it invoked
         let rpc = self.get_rpc_client().await?;
         let sigs = rpc
             .get_signatures_for_address(address, None, Some(100))
             .await?;
 
-        let txs = sigs
-            .into_iter()
-            .map(|sig| SolanaTransaction {
-                signature: sig.signature,
-                slot: sig.slot,
-                timestamp: sig.block_time.unwrap_or(0),
-                fee: 0, // Not available from signatures endpoint
-                status: if sig.err.is_some() {
-                    types::SolanaTransactionStatus::Failed
-                } else {
-                    types::SolanaTransactionStatus::Success
-                },
-                tx_type: types::SolanaTransactionType::Unknown,
-                native_transfers: vec![],
-                token_transfers: vec![],
-                description: String::default(),
-                source_program: String::default(),
-                fee_payer: String::default(),
-            })
-            .collect();
+        let mut txs = Vec::with_capacity(sigs.len());
+        for sig in sigs {
+            let tx = match rpc.get_transaction(&sig.signature).await {
+                Ok(raw) => parsed::parse_transaction(&raw, &sig.signature, address),
+                Err(e) => {
+                    log_error!(
+                        "Failed to fetch Solana transaction {}: {}",
+                        sig.signature,
+                        e
+                    );
+                    SolanaTransaction {
+                        signature: sig.signature,
+                        slot: sig.slot,
+                        timestamp: sig.block_time.unwrap_or(0),
+                        fee: 0, // Not available from signatures endpoint
+                        status: if sig.err.is_some() {
+                            types::SolanaTransactionStatus::Failed
+                        } else {
+                            types::SolanaTransactionStatus::Success
+                        },
+                        tx_type: types::SolanaTransactionType::Unknown,
+                        native_transfers: vec![],
+                        token_transfers: vec![],
+                        description: String::default(),
+                        source_program: String::default(),
+                        fee_payer: String::default(),
+                    }
+                }
+            };
+            txs.push(tx);
+        }
 
         Ok(txs)
     }
@@ -232,8 +237,28 @@ impl SolanaAdapter {
                             .and_then(|c| c.metadata)
                             .map(|m| m.name)
                             .unwrap_or_default();
-                        let ui_balance =
-                            format_token_balance(&token_info.balance, token_info.decimals);
+                        // DAS reports the raw balance, so interest-bearing
+                        // Token-2022 balances are scaled here.
+                        let mint_extensions = (token_info.token_program == TOKEN_2022_PROGRAM)
+                            .then(|| {
+                                token2022::parse_mint_extensions(
+                                    a.mint_extensions
+                                        .as_ref()
+                                        .unwrap_or(&serde_json::Value::Null),
+                                )
+                            });
+                        let ui_balance = match mint_extensions.as_ref().and_then(|m| m.interest) {
+                            Some(interest) => format!(
+                                "{}",
+                                token2022::ui_amount(
+                                    &token_info.balance,
+                                    token_info.decimals,
+                                    Some(&interest),
+                                    chrono::Utc::now().timestamp(),
+                                )
+                            ),
+                            None => format_token_balance(&token_info.balance, token_info.decimals),
+                        };
                         Some(SolanaTokenAccount {
                             mint: a.id,
                             symbol: if token_info.symbol.is_empty() {
@@ -245,6 +270,7 @@ impl SolanaAdapter {
                             balance: token_info.balance,
                             decimals: token_info.decimals,
                             ui_balance,
+                            extensions: mint_extensions.map(|m| m.summary),
                         })
                     })
                     .collect()
@@ -262,30 +288,53 @@ impl SolanaAdapter {
         // Fallback: standard RPC
         let rpc = self.get_rpc_client().await?;
         let balance = rpc.get_balance(address).await?;
-        let token_entries = rpc
-            .get_token_accounts_by_owner(address)
-            .await
-            .unwrap_or_default();
-
-        let token_accounts = token_entries
-            .into_iter()
-            .map(|entry| {
+        let mut token_accounts = Vec::new();
+        for program_id in [TOKEN_PROGRAM, TOKEN_2022_PROGRAM] {
+            let token_entries = rpc
+                .get_token_accounts_by_owner(address, program_id)
+                .await
+                .unwrap_or_default();
+
+            // Extensions live on the mint, fetched once per mint
+            let mut mints: HashMap<String, TokenExtensions> = HashMap::new();
+            for entry in token_entries {
                 let info = &entry.account.data.parsed.info;
+                // The RPC's UI amount already includes accrued interest
                 let ui_balance = info
                     .token_amount
                     .ui_amount
                     .map(|a| format!("{}", a))
                     .unwrap_or_else(|| info.token_amount.ui_amount_string.clone());
-                SolanaTokenAccount {
+                let extensions = if program_id == TOKEN_2022_PROGRAM {
+                    if !mints.contains_key(&info.mint) {
+                        let mint = rpc.get_account_info(&info.mint).await.unwrap_or_default();
+                        let parsed = token2022::parse_mint_extensions(
+                            mint.pointer("/data/parsed/info/extensions")
+                                .unwrap_or(&serde_json::Value::Null),
+                        );
+                        mints.insert(info.mint.clone(), parsed.summary);
+                    }
+                    mints
+                        .get(&info.mint)
+                        .cloned()
+                        .map(|summary| TokenExtensions {
+                            withheld_amount: token2022::withheld_amount(&info.extensions),
+                            ..summary
+                        })
+                } else {
+                    None
+                };
+                token_accounts.push(SolanaTokenAccount {
                     mint: info.mint.clone(),
                     symbol: None, // Not available from standard RPC
                     name: None,
                     balance: info.token_amount.amount.clone(),
                     decimals: info.token_amount.decimals,
                     ui_balance,
-                }
-            })
-            .collect();
+                    extensions,
+                });
+            }
+        }
 
         Ok(SolanaBalance {
             address: address.to_string(),
@@ -332,6 +381,8 @@ impl SolanaAdapter {
             types::SolanaTransactionType::Swap => TransactionType::Swap,
             types::SolanaTransactionType::Stake => TransactionType::Stake,
             types::SolanaTransactionType::Unstake => TransactionType::Unstake,
+            types::SolanaTransactionType::AddLiquidity => TransactionType::AddLiquidity,
+            types::SolanaTransactionType::RemoveLiquidity => TransactionType::RemoveLiquidity,
             types::SolanaTransactionType::Mint => TransactionType::Mint,
             types::SolanaTransactionType::Burn => TransactionType::Burn,
             types::SolanaTransactionType::CreateAccount => TransactionType::ContractCall,
@@ -340,16 +391,26 @@ impl SolanaAdapter {
             types::SolanaTransactionType::Unknown => TransactionType::Unknown,
         };
 
+        // A Token-2022 transfer fee is withheld from what the recipient
+        // gets, so the net and the fee are recorded as separate legs; the
+        // fee leg goes to the mint, which collects it.
         let token_transfers: Vec<TokenTransfer> = tx
             .token_transfers
             .iter()
-            .map(|t| TokenTransfer {
-                token_address: t.mint.clone(),
-                token_symbol: None,
-                token_decimals: None,
-                from: t.from.clone(),
-                to: t.to.clone(),
-                value: t.amount.to_string(),
+            .flat_map(|t| {
+                let leg = |to: &str, amount: f64| TokenTransfer {
+                    token_address: t.mint.clone(),
+                    token_symbol: None,
+                    token_decimals: None,
+                    from: t.from.clone(),
+                    to: to.to_string(),
+                    value: amount.to_string(),
+                };
+                let mut legs = vec![leg(&t.to, t.amount - t.transfer_fee)];
+                if t.transfer_fee > 0.0 {
+                    legs.push(leg(&t.mint, t.transfer_fee));
+                }
+                legs
             })
             .collect();
 
@@ -365,6 +426,10 @@ impl SolanaAdapter {
             status,
             tx_type,
             token_transfers,
+            swaps: Vec::new(),
+            fee_breakdown: None,
+            user_operations: Vec::new(),
+            inner_calls: Vec::new(),
             raw_data: None,
         }
     }
@@ -408,17 +473,12 @@ fn format_token_balance(raw: &str, decimals: u8) -> String {
         return raw.to_string();
     }
 
-    let raw_num: u128 = raw.parse().unwrap_or(0);
-    let divisor = 10u128.pow(decimals as u32);
-    let whole = raw_num / divisor;
-    let frac = raw_num % divisor;
-
-    if frac == 0 {
-        format!("{}.0", whole)
+    let raw_num = units::parse_decimal(raw).unwrap_or(U256::ZERO);
+    let formatted = units::format_units(raw_num, decimals);
+    if formatted.contains('.') {
+        formatted
     } else {
-        let frac_str = format!("{:0>width$}", frac, width = decimals as usize);
-        let trimmed = frac_str.trim_end_matches('0');
-        format!("{}.{}", whole, trimmed)
+        format!("{}.0", formatted)
     }
 }
 
@@ -441,10 +501,8 @@ impl ChainAdapter for SolanaAdapter {
     }
 
     async fn disconnect(&mut self) -> ChainResult<()> {
-        let mut guard = self.rpc_client.write().await;
-        *guard = None;
-        let mut guard = self.helius_client.write().await;
-        *guard = None;
+        self.rpc_client.take();
+        self.helius_client.take();
         Ok(())
     }
 
@@ -477,6 +535,7 @@ impl ChainAdapter for SolanaAdapter {
                 token_decimals: ta.decimals,
                 balance: ta.balance,
                 balance_formatted: ta.ui_balance,
+                extensions: ta.extensions,
             })
             .collect();
 
@@ -500,44 +559,22 @@ impl ChainAdapter for SolanaAdapter {
     }
 
     async fn get_transaction(&self, hash: &str) -> ChainResult<ChainTransaction> {
-        // Fetch via RPC and build a minimal transaction
         let rpc = self.get_rpc_client().await?;
         let raw = rpc.get_transaction(hash).await?;
-
-        // Build a minimal SolanaTransaction from the raw data
-        let slot = raw.get("slot").and_then(|s| s.as_u64()).unwrap_or(0);
-        let block_time = raw.get("blockTime").and_then(|t| t.as_i64()).unwrap_or(0);
-
-        let meta = raw.get("meta");
-        let fee = meta
-            .and_then(|m| m.get("fee"))
-            .and_then(|f| f.as_u64())
-            .unwrap_or(0);
-
-        let err = meta.and_then(|m| m.get("err"));
-        let status = if err.is_some() && !err.unwrap().is_null() {
-            types::SolanaTransactionStatus::Failed
-        } else {
-            types::SolanaTransactionStatus::Success
-        };
-
-        let sol_tx = SolanaTransaction {
-            signature: hash.to_string(),
-            slot,
-            timestamp: block_time,
-            fee,
-            status,
-            tx_type: types::SolanaTransactionType::Unknown,
-            native_transfers: vec![],
-            token_transfers: vec![],
-            description: String::default(),
-            source_program: String::default(),
-            fee_payer: String::default(),
-        };
+        let sol_tx = parsed::parse_transaction(&raw, hash, "");
 
         Ok(self.normalize_transaction(&sol_tx, ""))
     }
 
+    async fn has_activity(&self, address: &str) -> ChainResult<bool> {
+        validate_solana_address(address)?;
+        let rpc = self.get_rpc_client().await?;
+        let signatures = rpc
+            .get_signatures_for_address(address, None, Some(1))
+            .await?;
+        Ok(!signatures.is_empty())
+    }
+
     fn validate_address(&self, address: &str) -> bool {
         validate_solana_address(address).is_ok()
     }
@@ -551,6 +588,21 @@ impl ChainAdapter for SolanaAdapter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::chains::mock_http::{fixture, mount_rpc, MockServer};
+
+    #[tokio::test]
+    async fn test_clients_are_reused() {
+        let adapter = SolanaAdapter::from_network("solana")
+            .unwrap()
+            .with_helius_api_key("test-key".to_string());
+        let first = adapter.get_rpc_client().await.unwrap();
+        let second = adapter.get_rpc_client().await.unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let helius = adapter.get_helius_client().await.unwrap().unwrap();
+        let again = adapter.get_helius_client().await.unwrap().unwrap();
+        assert!(Arc::ptr_eq(&helius, &again));
+    }
 
     #[test]
     fn test_solana_config_mainnet() {
@@ -612,6 +664,11 @@ mod tests {
         assert_eq!(format_token_balance("100", 2), "1.0");
         assert_eq!(format_token_balance("0", 6), "0.0");
         assert_eq!(format_token_balance("1234567", 6), "1.234567");
+        // Past what 10u128.pow can hold
+        assert_eq!(
+            format_token_balance("5", 40),
+            format!("0.{}5", "0".repeat(39))
+        );
     }
 
     #[test]
@@ -648,6 +705,39 @@ mod tests {
         assert_eq!(chain_tx.tx_type, TransactionType::Transfer);
     }
 
+    #[test]
+    fn test_normalize_splits_transfer_fee() {
+        let adapter = SolanaAdapter::new(SolanaConfig::mainnet()).unwrap();
+
+        let sol_tx = SolanaTransaction {
+            signature: "FeeSig".to_string(),
+            slot: 1,
+            timestamp: 1700000000,
+            fee: 5000,
+            status: types::SolanaTransactionStatus::Success,
+            tx_type: types::SolanaTransactionType::TokenTransfer,
+            native_transfers: vec![],
+            token_transfers: vec![types::SolanaTokenTransfer {
+                from: "Sender".to_string(),
+                to: "Receiver".to_string(),
+                mint: "Mint2022".to_string(),
+                amount: 2.0,
+                transfer_fee: 0.5,
+                token_standard: "Fungible".to_string(),
+            }],
+            description: String::new(),
+            source_program: String::new(),
+            fee_payer: "Sender".to_string(),
+        };
+
+        let chain_tx = adapter.normalize_transaction(&sol_tx, "Sender");
+        assert_eq!(chain_tx.token_transfers.len(), 2);
+        assert_eq!(chain_tx.token_transfers[0].to, "Receiver");
+        assert_eq!(chain_tx.token_transfers[0].value, "1.5");
+        assert_eq!(chain_tx.token_transfers[1].to, "Mint2022");
+        assert_eq!(chain_tx.token_transfers[1].value, "0.5");
+    }
+
     #[tokio::test]
     async fn test_adapter_creation() {
         let adapter = SolanaAdapter::new(SolanaConfig::mainnet()).unwrap();
@@ -655,6 +745,63 @@ mod tests {
         assert_eq!(adapter.chain_id().name, "solana");
     }
 
+    #[tokio::test]
+    async fn test_rpc_fallback_classifies_by_program() {
+        let server = MockServer::start().await;
+        let signature = "3vZ8C2sZ7fYxPmN9ku1GkRQ3vW7a6LkQK5pvWbS5rBqT1JxkJ8uXnLc9g5Hp7hxDnT2J6yTgXbDKyq1N3rP9uFq";
+        mount_rpc(
+            &server,
+            "getSignaturesForAddress",
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": [{ "signature": signature, "slot": 261034012, "blockTime": 1713571540, "err": null, "memo": null }]
+            }),
+        )
+        .await;
+        mount_rpc(
+            &server,
+            "getTransaction",
+            fixture("solana/getTransaction_jupiter.json"),
+        )
+        .await;
+
+        let mut config = SolanaConfig::mainnet();
+        config.rpc_url = server.uri();
+        let adapter = SolanaAdapter::new(config).unwrap();
+        let address = "86xCnPeV69n6t3DnyGvkKobf9FdN2H9oiVDdaMpo2MMY";
+        let txs = adapter.get_transactions(address, None, None).await.unwrap();
+
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].hash, signature);
+        assert_eq!(txs[0].tx_type, TransactionType::Swap);
+        assert_eq!(txs[0].fee, "10250");
+        assert_eq!(txs[0].token_transfers.len(), 2);
+        assert_eq!(
+            txs[0].token_transfers[0].token_address,
+            types::WRAPPED_SOL_MINT
+        );
+        assert_eq!(txs[0].token_transfers[0].from, address);
+    }
+
+    #[tokio::test]
+    async fn test_has_activity_from_signatures() {
+        let server = MockServer::start().await;
+        mount_rpc(
+            &server,
+            "getSignaturesForAddress",
+            serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": [] }),
+        )
+        .await;
+
+        let mut config = SolanaConfig::mainnet();
+        config.rpc_url = server.uri();
+        let adapter = SolanaAdapter::new(config).unwrap();
+        let address = "86xCnPeV69n6t3DnyGvkKobf9FdN2H9oiVDdaMpo2MMY";
+        assert!(!adapter.has_activity(address).await.unwrap());
+        assert!(adapter.has_activity("not-a-key").await.is_err());
+    }
+
     #[test]
     fn test_adapter_with_helius_key() {
         let adapter = SolanaAdapter::new(SolanaConfig::mainnet())
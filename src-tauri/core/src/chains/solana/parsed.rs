@@ -0,0 +1,546 @@
+//! Standard RPC Transaction Parsing
+//!
+//! Without Helius there is no enriched type or transfer list, so the
+//! `jsonParsed` transaction from `getTransaction` is read directly. Native
+//! and SPL token legs come from the parsed System and Token program
+//! instructions, including the inner instructions DeFi programs issue; the
+//! type comes from the programs invoked and the shape of the wallet's legs:
+//!
+//! - Jupiter: a swap.
+//! - Raydium and Orca: a swap when one token goes out and another comes
+//!   back, adding liquidity when two or more go out, removing it when two or
+//!   more come back.
+//! - Marinade: staking when mSOL comes back, unstaking otherwise.
+
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value;
+
+use super::types::*;
+
+/// A token account's owner and mint, from the transaction's token balances
+struct TokenAccount {
+    owner: String,
+    mint: String,
+    decimals: i32,
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+/// Account keys in message order; `jsonParsed` gives objects, other
+/// encodings plain strings.
+fn account_keys(raw: &Value) -> Vec<&str> {
+    raw.pointer("/transaction/message/accountKeys")
+        .and_then(Value::as_array)
+        .map(|keys| {
+            keys.iter()
+                .map(|k| k.as_str().unwrap_or_else(|| str_field(k, "pubkey")))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Top-level instructions followed by the inner instructions they issued
+fn instructions(raw: &Value) -> Vec<&Value> {
+    let top = raw
+        .pointer("/transaction/message/instructions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten();
+    let inner = raw
+        .pointer("/meta/innerInstructions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|set| set.get("instructions").and_then(Value::as_array))
+        .flatten();
+    top.chain(inner).collect()
+}
+
+/// Token accounts touched by the transaction, keyed by address
+fn token_accounts(raw: &Value, keys: &[&str]) -> HashMap<String, TokenAccount> {
+    let mut accounts = HashMap::new();
+    for balances in ["/meta/preTokenBalances", "/meta/postTokenBalances"] {
+        let entries = raw.pointer(balances).and_then(Value::as_array);
+        for entry in entries.into_iter().flatten() {
+            let Some(address) = entry
+                .get("accountIndex")
+                .and_then(Value::as_u64)
+                .and_then(|i| keys.get(i as usize))
+            else {
+                continue;
+            };
+            let decimals = entry
+                .pointer("/uiTokenAmount/decimals")
+                .and_then(Value::as_i64)
+                .unwrap_or(0);
+            accounts.insert(
+                address.to_string(),
+                TokenAccount {
+                    owner: str_field(entry, "owner").to_string(),
+                    mint: str_field(entry, "mint").to_string(),
+                    decimals: decimals as i32,
+                },
+            );
+        }
+    }
+    accounts
+}
+
+/// Whole-token amount of a raw integer amount
+fn ui_amount(raw: &str, decimals: i32) -> f64 {
+    raw.parse::<f64>().unwrap_or(0.0) / 10f64.powi(decimals)
+}
+
+/// The token leg of a parsed SPL Token instruction, if it moves tokens
+fn token_leg(
+    instruction: &Value,
+    accounts: &HashMap<String, TokenAccount>,
+) -> Option<SolanaTokenTransfer> {
+    let kind = instruction.pointer("/parsed/type")?.as_str()?;
+    let info = instruction.pointer("/parsed/info")?;
+    let owner = |account: &str, fallback: &str| {
+        accounts
+            .get(account)
+            .map(|a| a.owner.clone())
+            .filter(|o| !o.is_empty())
+            .unwrap_or_else(|| fallback.to_string())
+    };
+    let amount_of = |mint: &str, account: &str| {
+        let decimals = accounts
+            .get(account)
+            .filter(|a| a.mint == mint || mint.is_empty())
+            .map_or(0, |a| a.decimals);
+        let of = |token_amount: &Value| {
+            token_amount
+                .get("uiAmount")
+                .and_then(Value::as_f64)
+                .unwrap_or_else(|| ui_amount(str_field(token_amount, "amount"), decimals))
+        };
+        let amount = match info.get("tokenAmount") {
+            Some(token_amount) => of(token_amount),
+            None => ui_amount(str_field(info, "amount"), decimals),
+        };
+        // Token-2022 transfer fees are taken out of the amount sent
+        (amount, info.get("feeAmount").map_or(0.0, of))
+    };
+
+    let (from, to, account) = match kind {
+        "transfer" | "transferChecked" | "transferCheckedWithFee" => {
+            let source = str_field(info, "source");
+            let destination = str_field(info, "destination");
+            (
+                owner(source, str_field(info, "authority")),
+                owner(destination, destination),
+                source,
+            )
+        }
+        "mintTo" | "mintToChecked" => {
+            let account = str_field(info, "account");
+            (String::new(), owner(account, account), account)
+        }
+        "burn" | "burnChecked" => {
+            let account = str_field(info, "account");
+            (
+                owner(account, str_field(info, "authority")),
+                String::new(),
+                account,
+            )
+        }
+        _ => return None,
+    };
+
+    let mint = match str_field(info, "mint") {
+        "" => accounts.get(account).map(|a| a.mint.clone())?,
+        mint => mint.to_string(),
+    };
+    let (amount, transfer_fee) = amount_of(&mint, account);
+    Some(SolanaTokenTransfer {
+        amount,
+        transfer_fee,
+        from,
+        to,
+        mint,
+        token_standard: "Fungible".to_string(),
+    })
+}
+
+/// Classify a transaction by the programs it invoked and the wallet's legs,
+/// returning the type and the protocol behind it
+pub fn classify_programs(
+    programs: &[&str],
+    native_transfers: &[SolanaNativeTransfer],
+    token_transfers: &[SolanaTokenTransfer],
+    wallet: &str,
+) -> (SolanaTransactionType, Option<&'static str>) {
+    let source = programs
+        .iter()
+        .filter_map(|p| program_source(p))
+        .find(|s| *s != "System");
+
+    // Native SOL counts as wrapped SOL, so wrapping for a swap isn't a
+    // second asset.
+    let mut sent = HashSet::new();
+    let mut received = HashSet::new();
+    for t in token_transfers {
+        if t.from == wallet {
+            sent.insert(t.mint.as_str());
+        }
+        if t.to == wallet {
+            received.insert(t.mint.as_str());
+        }
+    }
+    for t in native_transfers {
+        if t.from == wallet {
+            sent.insert(WRAPPED_SOL_MINT);
+        }
+        if t.to == wallet {
+            received.insert(WRAPPED_SOL_MINT);
+        }
+    }
+
+    let tx_type = match source {
+        Some("Jupiter") => SolanaTransactionType::Swap,
+        Some("Marinade") if received.contains(MSOL_MINT) => SolanaTransactionType::Stake,
+        Some("Marinade") => SolanaTransactionType::Unstake,
+        Some("Raydium") | Some("Orca") => {
+            if sent.len() >= 2 && received.len() <= 1 {
+                SolanaTransactionType::AddLiquidity
+            } else if received.len() >= 2 && sent.len() <= 1 {
+                SolanaTransactionType::RemoveLiquidity
+            } else {
+                SolanaTransactionType::Swap
+            }
+        }
+        _ if !token_transfers.is_empty() => SolanaTransactionType::TokenTransfer,
+        _ if !native_transfers.is_empty() => SolanaTransactionType::Transfer,
+        _ => SolanaTransactionType::Unknown,
+    };
+    (tx_type, source)
+}
+
+/// Parse a `jsonParsed` `getTransaction` result into our normalized format
+///
+/// Legs are kept when they involve `for_address`; with an empty address all
+/// legs are kept.
+pub fn parse_transaction(raw: &Value, signature: &str, for_address: &str) -> SolanaTransaction {
+    let keys = account_keys(raw);
+    let accounts = token_accounts(raw, &keys);
+    let failed = raw.pointer("/meta/err").is_some_and(|e| !e.is_null());
+    let involves =
+        |from: &str, to: &str| for_address.is_empty() || from == for_address || to == for_address;
+
+    let mut programs: Vec<&str> = Vec::new();
+    let mut native_transfers = Vec::new();
+    let mut token_transfers = Vec::new();
+    for instruction in instructions(raw) {
+        let program_id = str_field(instruction, "programId");
+        if !programs.contains(&program_id) {
+            programs.push(program_id);
+        }
+        // A failed transaction's instructions never ran.
+        if failed {
+            continue;
+        }
+
+        match str_field(instruction, "program") {
+            "system" => {
+                let kind = instruction.pointer("/parsed/type").and_then(Value::as_str);
+                let (Some("transfer"), Some(info)) = (kind, instruction.pointer("/parsed/info"))
+                else {
+                    continue;
+                };
+                let from = str_field(info, "source");
+                let to = str_field(info, "destination");
+                // Wrapping SOL into the sender's own token account moves
+                // nothing; the wrapped SOL's token leg carries the value.
+                let wrapping = accounts
+                    .get(to)
+                    .is_some_and(|a| a.owner == from && a.mint == WRAPPED_SOL_MINT);
+                if wrapping || !involves(from, to) {
+                    continue;
+                }
+                native_transfers.push(SolanaNativeTransfer {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    amount: info.get("lamports").and_then(Value::as_u64).unwrap_or(0),
+                });
+            }
+            "spl-token" | "spl-token-2022" => {
+                if let Some(leg) = token_leg(instruction, &accounts) {
+                    if involves(&leg.from, &leg.to) {
+                        token_transfers.push(leg);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let (tx_type, source) =
+        classify_programs(&programs, &native_transfers, &token_transfers, for_address);
+
+    SolanaTransaction {
+        signature: signature.to_string(),
+        slot: raw.get("slot").and_then(Value::as_u64).unwrap_or(0),
+        timestamp: raw.get("blockTime").and_then(Value::as_i64).unwrap_or(0),
+        fee: raw
+            .pointer("/meta/fee")
+            .and_then(Value::as_u64)
+            .unwrap_or(0),
+        status: if failed {
+            SolanaTransactionStatus::Failed
+        } else {
+            SolanaTransactionStatus::Success
+        },
+        tx_type,
+        native_transfers,
+        token_transfers,
+        description: String::default(),
+        source_program: source.unwrap_or_default().to_string(),
+        fee_payer: keys.first().map(|k| k.to_string()).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::mock_http::fixture;
+    use serde_json::json;
+
+    const WALLET: &str = "86xCnPeV69n6t3DnyGvkKobf9FdN2H9oiVDdaMpo2MMY";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const RAY: &str = "4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R";
+    const LP: &str = "8HoQnePLqPj4M7PUDzfw8e3Ymdwgc7NLGnaTUapubyvu";
+    const POOL: &str = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1";
+
+    fn balance(index: u64, mint: &str, owner: &str, decimals: u8) -> Value {
+        json!({
+            "accountIndex": index,
+            "mint": mint,
+            "owner": owner,
+            "uiTokenAmount": { "amount": "0", "decimals": decimals }
+        })
+    }
+
+    fn spl(kind: &str, info: Value) -> Value {
+        json!({
+            "parsed": { "type": kind, "info": info },
+            "program": "spl-token",
+            "programId": TOKEN_PROGRAM
+        })
+    }
+
+    fn transaction(program: &str, keys: &[&str], balances: Vec<Value>, legs: Vec<Value>) -> Value {
+        json!({
+            "slot": 1,
+            "blockTime": 1713571540,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preTokenBalances": balances,
+                "postTokenBalances": [],
+                "innerInstructions": [{ "index": 0, "instructions": legs }]
+            },
+            "transaction": {
+                "message": {
+                    "accountKeys": keys,
+                    "instructions": [{ "programId": program, "accounts": [], "data": "" }]
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_jupiter_swap_from_fixture() {
+        let raw = fixture("solana/getTransaction_jupiter.json");
+        let tx = parse_transaction(&raw["result"], "sig", WALLET);
+
+        assert_eq!(tx.tx_type, SolanaTransactionType::Swap);
+        assert_eq!(tx.source_program, "Jupiter");
+        assert_eq!(tx.fee, 10250);
+        assert_eq!(tx.fee_payer, WALLET);
+        // Wrapping SOL isn't a transfer of its own.
+        assert!(tx.native_transfers.is_empty());
+
+        let legs: Vec<(&str, &str, &str, f64)> = tx
+            .token_transfers
+            .iter()
+            .map(|t| (t.from.as_str(), t.to.as_str(), t.mint.as_str(), t.amount))
+            .collect();
+        assert_eq!(
+            legs,
+            vec![
+                (WALLET, POOL, WRAPPED_SOL_MINT, 1.25),
+                (POOL, WALLET, USDC, 180.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_raydium_liquidity() {
+        let keys = [
+            "wallet-ray",
+            "wallet-usdc",
+            "wallet-lp",
+            "vault-ray",
+            "vault-usdc",
+        ];
+        let balances = vec![
+            balance(0, RAY, WALLET, 6),
+            balance(1, USDC, WALLET, 6),
+            balance(2, LP, WALLET, 6),
+            balance(3, RAY, POOL, 6),
+            balance(4, USDC, POOL, 6),
+        ];
+        let deposit = transaction(
+            RAYDIUM_AMM,
+            &keys,
+            balances.clone(),
+            vec![
+                spl(
+                    "transfer",
+                    json!({ "source": "wallet-ray", "destination": "vault-ray", "amount": "10000000" }),
+                ),
+                spl(
+                    "transfer",
+                    json!({ "source": "wallet-usdc", "destination": "vault-usdc", "amount": "25000000" }),
+                ),
+                spl(
+                    "mintTo",
+                    json!({ "account": "wallet-lp", "mint": LP, "amount": "15800000" }),
+                ),
+            ],
+        );
+        let tx = parse_transaction(&deposit, "sig", WALLET);
+        assert_eq!(tx.tx_type, SolanaTransactionType::AddLiquidity);
+        assert_eq!(tx.source_program, "Raydium");
+        assert_eq!(tx.token_transfers.len(), 3);
+        assert_eq!(tx.token_transfers[2].to, WALLET);
+        assert_eq!(tx.token_transfers[2].amount, 15.8);
+
+        let withdrawal = transaction(
+            RAYDIUM_AMM,
+            &keys,
+            balances,
+            vec![
+                spl(
+                    "burn",
+                    json!({ "account": "wallet-lp", "mint": LP, "amount": "15800000" }),
+                ),
+                spl(
+                    "transfer",
+                    json!({ "source": "vault-ray", "destination": "wallet-ray", "amount": "10100000" }),
+                ),
+                spl(
+                    "transfer",
+                    json!({ "source": "vault-usdc", "destination": "wallet-usdc", "amount": "24900000" }),
+                ),
+            ],
+        );
+        let tx = parse_transaction(&withdrawal, "sig", WALLET);
+        assert_eq!(tx.tx_type, SolanaTransactionType::RemoveLiquidity);
+        assert_eq!(tx.token_transfers[0].from, WALLET);
+    }
+
+    #[test]
+    fn test_marinade_stake_and_unstake() {
+        let keys = ["wallet-msol", "reserve"];
+        let balances = vec![balance(0, MSOL_MINT, WALLET, 9)];
+        let stake = transaction(
+            MARINADE_FINANCE,
+            &keys,
+            balances.clone(),
+            vec![
+                json!({
+                    "parsed": { "type": "transfer", "info": { "source": WALLET, "destination": "reserve", "lamports": 2000000000u64 } },
+                    "program": "system",
+                    "programId": SYSTEM_PROGRAM
+                }),
+                spl(
+                    "mintTo",
+                    json!({ "account": "wallet-msol", "mint": MSOL_MINT, "amount": "1750000000" }),
+                ),
+            ],
+        );
+        let tx = parse_transaction(&stake, "sig", WALLET);
+        assert_eq!(tx.tx_type, SolanaTransactionType::Stake);
+        assert_eq!(tx.source_program, "Marinade");
+        assert_eq!(tx.native_transfers[0].amount, 2_000_000_000);
+        assert_eq!(tx.token_transfers[0].amount, 1.75);
+
+        let unstake = transaction(
+            MARINADE_FINANCE,
+            &keys,
+            balances,
+            vec![spl(
+                "transfer",
+                json!({ "source": "wallet-msol", "destination": "liq-pool", "amount": "1750000000" }),
+            )],
+        );
+        let tx = parse_transaction(&unstake, "sig", WALLET);
+        assert_eq!(tx.tx_type, SolanaTransactionType::Unstake);
+    }
+
+    #[test]
+    fn test_failed_transaction_has_no_legs() {
+        let mut raw = fixture("solana/getTransaction_jupiter.json")["result"].clone();
+        raw["meta"]["err"] = json!({ "InstructionError": [3, { "Custom": 6001 }] });
+        let tx = parse_transaction(&raw, "sig", WALLET);
+
+        assert_eq!(tx.status, SolanaTransactionStatus::Failed);
+        assert_eq!(tx.tx_type, SolanaTransactionType::Swap);
+        assert!(tx.token_transfers.is_empty());
+    }
+
+    #[test]
+    fn test_token_2022_transfer_fee() {
+        let keys = [WALLET, "WalletToken", "PoolToken"];
+        let mut leg = spl(
+            "transferCheckedWithFee",
+            json!({
+                "source": "WalletToken",
+                "destination": "PoolToken",
+                "mint": USDC,
+                "authority": WALLET,
+                "tokenAmount": { "amount": "2000000", "decimals": 6, "uiAmount": 2.0 },
+                "feeAmount": { "amount": "30000", "decimals": 6, "uiAmount": 0.03 }
+            }),
+        );
+        leg["program"] = json!("spl-token-2022");
+        leg["programId"] = json!(TOKEN_2022_PROGRAM);
+        let raw = transaction(
+            TOKEN_2022_PROGRAM,
+            &keys,
+            vec![balance(1, USDC, WALLET, 6), balance(2, USDC, POOL, 6)],
+            vec![leg],
+        );
+
+        let tx = parse_transaction(&raw, "sig", WALLET);
+        assert_eq!(tx.token_transfers.len(), 1);
+        assert_eq!(tx.token_transfers[0].to, POOL);
+        assert_eq!(tx.token_transfers[0].amount, 2.0);
+        assert_eq!(tx.token_transfers[0].transfer_fee, 0.03);
+    }
+
+    #[test]
+    fn test_plain_transfers() {
+        let (tx_type, source) = classify_programs(
+            &[SYSTEM_PROGRAM],
+            &[SolanaNativeTransfer {
+                from: WALLET.to_string(),
+                to: POOL.to_string(),
+                amount: 1,
+            }],
+            &[],
+            WALLET,
+        );
+        assert_eq!(tx_type, SolanaTransactionType::Transfer);
+        assert_eq!(source, None);
+
+        let (tx_type, _) = classify_programs(&["unknown-program"], &[], &[], WALLET);
+        assert_eq!(tx_type, SolanaTransactionType::Unknown);
+    }
+}
@@ -143,17 +143,19 @@ impl SolanaRpcClient {
         self.rpc_call("getBlockHeight", json!([])).await
     }
 
-    /// Get token accounts by owner (parsed JSON encoding)
+    /// Get token accounts by owner under a token program (parsed JSON
+    /// encoding); SPL Token and Token-2022 accounts are queried separately
     pub async fn get_token_accounts_by_owner(
         &self,
         owner: &str,
+        program_id: &str,
     ) -> ChainResult<Vec<RpcTokenAccountEntry>> {
         let result: RpcTokenAccountsResult = self
             .rpc_call(
                 "getTokenAccountsByOwner",
                 json!([
                     owner,
-                    { "programId": TOKEN_PROGRAM },
+                    { "programId": program_id },
                     { "encoding": "jsonParsed" }
                 ]),
             )
@@ -161,6 +163,17 @@ impl SolanaRpcClient {
         Ok(result.value)
     }
 
+    /// Get an account's parsed data, e.g. a Token-2022 mint's extensions
+    pub async fn get_account_info(&self, pubkey: &str) -> ChainResult<serde_json::Value> {
+        let result: serde_json::Value = self
+            .rpc_call(
+                "getAccountInfo",
+                json!([pubkey, { "encoding": "jsonParsed" }]),
+            )
+            .await?;
+        Ok(result.get("value").cloned().unwrap_or_default())
+    }
+
     /// Get transaction signatures for an address
     ///
     /// # Arguments
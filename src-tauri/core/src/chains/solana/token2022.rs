@@ -0,0 +1,283 @@
+//! SPL Token-2022 Extensions
+//!
+//! Token-2022 mints can carry extensions that change what an amount means.
+//! Two matter for bookkeeping:
+//!
+//! - Transfer fees: the mint withholds a share of every transfer in the
+//!   recipient's account, so the amount sent and the amount received differ.
+//! - Interest-bearing: the raw balance never changes, but its UI amount
+//!   grows continuously at the mint's rate.
+//!
+//! Standard RPC returns extensions as a `jsonParsed` list of
+//! `{ extension, state }` with camelCase fields; Helius DAS returns an object
+//! keyed by snake_case extension name. Both are read here.
+
+use serde_json::Value;
+
+use crate::chains::TokenExtensions;
+
+/// Program name reported for Token-2022 mints
+pub const TOKEN_2022: &str = "spl-token-2022";
+
+/// Seconds per year used by the interest-bearing extension
+const SECONDS_PER_YEAR: f64 = 60.0 * 60.0 * 24.0 * 365.24;
+
+/// Rate state of an interest-bearing mint
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InterestBearingConfig {
+    /// When interest started accruing (Unix seconds)
+    pub initialization_timestamp: i64,
+    /// Average rate before the last rate change, in basis points
+    pub pre_update_average_rate: i16,
+    /// When the rate last changed (Unix seconds)
+    pub last_update_timestamp: i64,
+    /// Rate since the last change, in basis points
+    pub current_rate: i16,
+}
+
+impl InterestBearingConfig {
+    /// Factor a raw balance's whole-token amount is multiplied by at `now`,
+    /// compounding continuously as the token program does
+    pub fn scale_at(&self, now: i64) -> f64 {
+        let accrued = |rate: i16, seconds: i64| {
+            (rate as f64 * seconds.max(0) as f64 / (SECONDS_PER_YEAR * 10_000.0)).exp()
+        };
+        accrued(
+            self.pre_update_average_rate,
+            self.last_update_timestamp - self.initialization_timestamp,
+        ) * accrued(self.current_rate, now - self.last_update_timestamp)
+    }
+}
+
+/// A mint's extensions, with the interest state kept for scaling balances
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MintExtensions {
+    /// Metadata surfaced on token balances
+    pub summary: TokenExtensions,
+    /// Interest state, for interest-bearing mints
+    pub interest: Option<InterestBearingConfig>,
+}
+
+/// `snake_case` extension name to the camelCase RPC uses
+fn camel_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// A field by its camelCase (RPC) or snake_case (DAS) name
+fn field<'a>(value: &'a Value, camel: &str, snake: &str) -> Option<&'a Value> {
+    value.get(camel).or_else(|| value.get(snake))
+}
+
+/// An integer field, which RPC sends as numbers and sometimes as strings
+fn int_field(value: &Value, camel: &str, snake: &str) -> Option<i64> {
+    let v = field(value, camel, snake)?;
+    v.as_i64()
+        .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+}
+
+/// A raw amount field as a string; amounts can exceed `i64`
+fn amount_field(value: &Value, camel: &str, snake: &str) -> Option<String> {
+    let v = field(value, camel, snake)?;
+    match v {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Extension names and states, from either the RPC list or the DAS object
+fn entries(extensions: &Value) -> Vec<(String, &Value)> {
+    match extensions {
+        Value::Array(list) => list
+            .iter()
+            .filter_map(|e| {
+                let name = e.get("extension")?.as_str()?;
+                Some((name.to_string(), e.get("state").unwrap_or(&Value::Null)))
+            })
+            .collect(),
+        Value::Object(map) => map.iter().map(|(k, v)| (camel_case(k), v)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Parse a mint's extensions
+///
+/// The transfer fee is the newest configured schedule, which takes effect
+/// from its epoch onward.
+pub fn parse_mint_extensions(extensions: &Value) -> MintExtensions {
+    let mut parsed = MintExtensions {
+        summary: TokenExtensions {
+            program: TOKEN_2022.to_string(),
+            ..Default::default()
+        },
+        interest: None,
+    };
+
+    for (name, state) in entries(extensions) {
+        match name.as_str() {
+            "transferFeeConfig" => {
+                let newer = field(state, "newerTransferFee", "newer_transfer_fee");
+                if let Some(fee) = newer {
+                    parsed.summary.transfer_fee_basis_points =
+                        int_field(fee, "transferFeeBasisPoints", "transfer_fee_basis_points")
+                            .and_then(|bps| u16::try_from(bps).ok());
+                    parsed.summary.maximum_transfer_fee =
+                        amount_field(fee, "maximumFee", "maximum_fee");
+                }
+            }
+            "interestBearingConfig" => {
+                let rate = |camel, snake| {
+                    int_field(state, camel, snake)
+                        .and_then(|r| i16::try_from(r).ok())
+                        .unwrap_or(0)
+                };
+                let time = |camel, snake| int_field(state, camel, snake).unwrap_or(0);
+                let config = InterestBearingConfig {
+                    initialization_timestamp: time(
+                        "initializationTimestamp",
+                        "initialization_timestamp",
+                    ),
+                    pre_update_average_rate: rate(
+                        "preUpdateAverageRate",
+                        "pre_update_average_rate",
+                    ),
+                    last_update_timestamp: time("lastUpdateTimestamp", "last_update_timestamp"),
+                    current_rate: rate("currentRate", "current_rate"),
+                };
+                parsed.summary.interest_rate_bps = Some(config.current_rate);
+                parsed.interest = Some(config);
+            }
+            _ => {}
+        }
+        parsed.summary.extensions.push(name);
+    }
+    parsed
+}
+
+/// Transfer fees withheld in a token account, from its RPC extensions
+pub fn withheld_amount(account_extensions: &[Value]) -> Option<String> {
+    account_extensions
+        .iter()
+        .find(|e| e.get("extension").and_then(Value::as_str) == Some("transferFeeAmount"))
+        .and_then(|e| e.get("state"))
+        .and_then(|state| amount_field(state, "withheldAmount", "withheld_amount"))
+}
+
+/// Whole-token amount of a raw balance, with interest accrued to `now`
+pub fn ui_amount(
+    raw: &str,
+    decimals: u8,
+    interest: Option<&InterestBearingConfig>,
+    now: i64,
+) -> f64 {
+    let whole = raw.parse::<f64>().unwrap_or(0.0) / 10f64.powi(decimals as i32);
+    match interest {
+        Some(config) => whole * config.scale_at(now),
+        None => whole,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_rpc_extensions() {
+        let extensions = json!([
+            {
+                "extension": "transferFeeConfig",
+                "state": {
+                    "newerTransferFee": {
+                        "epoch": 600,
+                        "maximumFee": 5000000000u64,
+                        "transferFeeBasisPoints": 150
+                    },
+                    "olderTransferFee": {
+                        "epoch": 500,
+                        "maximumFee": 1000,
+                        "transferFeeBasisPoints": 100
+                    },
+                    "withheldAmount": 0
+                }
+            },
+            { "extension": "metadataPointer", "state": {} }
+        ]);
+
+        let parsed = parse_mint_extensions(&extensions);
+        assert_eq!(parsed.summary.program, "spl-token-2022");
+        assert_eq!(
+            parsed.summary.extensions,
+            vec!["transferFeeConfig", "metadataPointer"]
+        );
+        assert_eq!(parsed.summary.transfer_fee_basis_points, Some(150));
+        assert_eq!(
+            parsed.summary.maximum_transfer_fee.as_deref(),
+            Some("5000000000")
+        );
+        assert!(parsed.interest.is_none());
+    }
+
+    #[test]
+    fn test_parse_das_extensions() {
+        let extensions = json!({
+            "interest_bearing_config": {
+                "rate_authority": "Auth111111111111111111111111111111111111111",
+                "initialization_timestamp": 1_700_000_000,
+                "pre_update_average_rate": 500,
+                "last_update_timestamp": 1_700_000_000,
+                "current_rate": 500
+            }
+        });
+
+        let parsed = parse_mint_extensions(&extensions);
+        assert_eq!(parsed.summary.extensions, vec!["interestBearingConfig"]);
+        assert_eq!(parsed.summary.interest_rate_bps, Some(500));
+        assert_eq!(parsed.summary.transfer_fee_basis_points, None);
+        assert_eq!(
+            parsed.interest.map(|i| i.initialization_timestamp),
+            Some(1_700_000_000)
+        );
+    }
+
+    #[test]
+    fn test_interest_accrues_continuously() {
+        let config = InterestBearingConfig {
+            initialization_timestamp: 0,
+            pre_update_average_rate: 0,
+            last_update_timestamp: 0,
+            current_rate: 500,
+        };
+        let year = SECONDS_PER_YEAR as i64;
+
+        // 5% a year compounded continuously
+        let amount = ui_amount("1000000", 6, Some(&config), year);
+        assert!((amount - 0.05f64.exp()).abs() < 1e-6);
+        // Without the extension the raw balance reads as-is
+        assert_eq!(ui_amount("1000000", 6, None, year), 1.0);
+        // Nothing accrues before the rate took effect
+        assert_eq!(config.scale_at(-10), 1.0);
+    }
+
+    #[test]
+    fn test_withheld_amount() {
+        let account = vec![
+            json!({ "extension": "immutableOwner" }),
+            json!({ "extension": "transferFeeAmount", "state": { "withheldAmount": 1500 } }),
+        ];
+        assert_eq!(withheld_amount(&account).as_deref(), Some("1500"));
+        assert_eq!(withheld_amount(&[]), None);
+    }
+}
@@ -2,8 +2,11 @@
 //!
 //! Types for Solana transaction data from Helius API and standard Solana JSON-RPC.
 
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use crate::chains::TokenExtensions;
+
 // =============================================================================
 // WELL-KNOWN PROGRAM IDS
 // =============================================================================
@@ -18,12 +21,29 @@ pub const TOKEN_2022_PROGRAM: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuE
 pub const STAKE_PROGRAM: &str = "Stake11111111111111111111111111111111111111";
 /// Jupiter Aggregator v6
 pub const JUPITER_V6: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+/// Jupiter Aggregator v4
+pub const JUPITER_V4: &str = "JUP4Fb2cqiRUcaTHdrPC8h2gNsA2ETXiPDD33WcGuJB";
 /// Marinade Finance
 pub const MARINADE_FINANCE: &str = "MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD";
 /// Raydium AMM
 pub const RAYDIUM_AMM: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+/// Raydium Concentrated Liquidity
+pub const RAYDIUM_CLMM: &str = "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK";
+/// Raydium Constant Product (CPMM)
+pub const RAYDIUM_CPMM: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
 /// Orca Whirlpool
 pub const ORCA_WHIRLPOOL: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+/// Orca Token Swap v2
+pub const ORCA_TOKEN_SWAP_V2: &str = "9W959DqEETiGZocYWCQPaJ6sBmUzgfxXfqGeTEdp3aQP";
+
+// =============================================================================
+// WELL-KNOWN MINTS
+// =============================================================================
+
+/// Wrapped SOL
+pub const WRAPPED_SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+/// Marinade staked SOL (mSOL)
+pub const MSOL_MINT: &str = "mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So";
 
 // =============================================================================
 // SOLANA TRANSACTION TYPE CLASSIFICATION
@@ -43,6 +63,10 @@ pub enum SolanaTransactionType {
     Stake,
     /// Stake withdrawal / undelegation
     Unstake,
+    /// Liquidity added to a pool (e.g. Raydium, Orca)
+    AddLiquidity,
+    /// Liquidity withdrawn from a pool
+    RemoveLiquidity,
     /// Token mint
     Mint,
     /// Token burn
@@ -222,6 +246,9 @@ pub struct DasAsset {
     /// Content metadata
     #[serde(default)]
     pub content: Option<DasContent>,
+    /// Token-2022 mint extensions, keyed by extension name
+    #[serde(default)]
+    pub mint_extensions: Option<serde_json::Value>,
 }
 
 /// Token info from DAS
@@ -236,6 +263,9 @@ pub struct DasTokenInfo {
     /// Token balance (raw string)
     #[serde(default)]
     pub balance: String,
+    /// Token program owning the mint
+    #[serde(default)]
+    pub token_program: String,
     /// Price info
     #[serde(default)]
     pub price_info: Option<DasPriceInfo>,
@@ -246,10 +276,10 @@ pub struct DasTokenInfo {
 pub struct DasPriceInfo {
     /// Price per token in USD
     #[serde(default)]
-    pub price_per_token: f64,
+    pub price_per_token: Decimal,
     /// Total value in USD
     #[serde(default)]
-    pub total_price: f64,
+    pub total_price: Decimal,
     /// Currency
     #[serde(default)]
     pub currency: String,
@@ -357,6 +387,9 @@ pub struct RpcTokenInfo {
     /// Token amount information
     #[serde(rename = "tokenAmount")]
     pub token_amount: RpcTokenAmount,
+    /// Token-2022 account extensions (e.g. withheld transfer fees)
+    #[serde(default)]
+    pub extensions: Vec<serde_json::Value>,
 }
 
 /// Token amount from RPC
@@ -444,6 +477,10 @@ pub struct SolanaTokenTransfer {
     pub mint: String,
     /// Amount of tokens transferred.
     pub amount: f64,
+    /// Token-2022 transfer fee withheld from `amount`; the recipient gets
+    /// the difference.
+    #[serde(default)]
+    pub transfer_fee: f64,
     /// Token standard classification (e.g., fungible, non-fungible).
     pub token_standard: String,
 }
@@ -474,6 +511,9 @@ pub struct SolanaTokenAccount {
     pub decimals: u8,
     /// UI balance (human-readable)
     pub ui_balance: String,
+    /// Token-2022 extension metadata, when the mint has extensions
+    #[serde(default)]
+    pub extensions: Option<TokenExtensions>,
 }
 
 // =============================================================================
@@ -488,6 +528,8 @@ pub fn classify_transaction_type(tx_type: &str) -> SolanaTransactionType {
         "SWAP" => SolanaTransactionType::Swap,
         "STAKE" | "STAKE_SOL" => SolanaTransactionType::Stake,
         "UNSTAKE" | "UNSTAKE_SOL" | "DEACTIVATE_STAKE" => SolanaTransactionType::Unstake,
+        "ADD_LIQUIDITY" => SolanaTransactionType::AddLiquidity,
+        "WITHDRAW_LIQUIDITY" | "REMOVE_LIQUIDITY" => SolanaTransactionType::RemoveLiquidity,
         "TOKEN_MINT" | "MINT" => SolanaTransactionType::Mint,
         "BURN" | "TOKEN_BURN" => SolanaTransactionType::Burn,
         "NFT_SALE" | "NFT_LISTING" | "NFT_BID" => SolanaTransactionType::NftSale,
@@ -511,6 +553,19 @@ pub fn classify_transaction_source(source: &str) -> &str {
     }
 }
 
+/// Protocol behind a program ID, named as [`classify_transaction_source`]
+/// names Helius sources
+pub fn program_source(program_id: &str) -> Option<&'static str> {
+    match program_id {
+        JUPITER_V6 | JUPITER_V4 => Some("Jupiter"),
+        MARINADE_FINANCE => Some("Marinade"),
+        RAYDIUM_AMM | RAYDIUM_CLMM | RAYDIUM_CPMM => Some("Raydium"),
+        ORCA_WHIRLPOOL | ORCA_TOKEN_SWAP_V2 => Some("Orca"),
+        SYSTEM_PROGRAM => Some("System"),
+        _ => None,
+    }
+}
+
 impl HeliusTransaction {
     /// Convert a Helius enriched transaction to our normalized format
     pub fn to_solana_transaction(&self) -> SolanaTransaction {
@@ -540,6 +595,7 @@ impl HeliusTransaction {
                 to: t.to_user_account.clone(),
                 mint: t.mint.clone(),
                 amount: t.token_amount,
+                transfer_fee: 0.0,
                 token_standard: t.token_standard.clone(),
             })
             .collect();
@@ -652,6 +708,10 @@ mod tests {
             classify_transaction_type("STAKE_SOL"),
             SolanaTransactionType::Stake
         );
+        assert_eq!(
+            classify_transaction_type("WITHDRAW_LIQUIDITY"),
+            SolanaTransactionType::RemoveLiquidity
+        );
         assert_eq!(
             classify_transaction_type("UNKNOWN_TYPE"),
             SolanaTransactionType::Unknown
@@ -669,6 +729,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_program_source() {
+        assert_eq!(program_source(JUPITER_V6), Some("Jupiter"));
+        assert_eq!(program_source(RAYDIUM_CLMM), Some("Raydium"));
+        assert_eq!(program_source(ORCA_WHIRLPOOL), Some("Orca"));
+        assert_eq!(program_source(MARINADE_FINANCE), Some("Marinade"));
+        assert_eq!(program_source(TOKEN_PROGRAM), None);
+    }
+
     #[test]
     fn test_helius_to_solana_transaction() {
         let helius_tx = HeliusTransaction {
@@ -752,6 +821,9 @@ mod tests {
         let token_info = assets.items[0].token_info.as_ref().unwrap();
         assert_eq!(token_info.symbol, "USDC");
         assert_eq!(token_info.decimals, 6);
+        let price_info = token_info.price_info.as_ref().unwrap();
+        assert_eq!(price_info.price_per_token, Decimal::ONE);
+        assert_eq!(price_info.total_price, Decimal::ONE);
     }
 
     #[test]
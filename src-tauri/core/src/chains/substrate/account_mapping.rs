@@ -0,0 +1,344 @@
+//! EVM and Substrate account mapping
+//!
+//! Astar and Moonbeam run an EVM next to their Substrate runtime, so one key
+//! holder can own both an H160 and an SS58 account that are related:
+//!
+//! - On Astar every H160 has a default native account,
+//!   `blake2_256("evm:" ++ h160)`, and every native account a default H160,
+//!   `keccak_256(account)[12..]`. The unified accounts pallet lets a user
+//!   claim a different pair instead, which is read from chain storage.
+//! - On Moonbeam a relay chain account acts through XCM as a derived H160,
+//!   the address the XCM Utils precompile's `multilocationToAddress` returns
+//!   for it.
+
+use std::time::Duration;
+
+use serde_json::json;
+use sp_core::hashing::{blake2_128, blake2_256, keccak_256, twox_128};
+
+use super::{ss58, SubstrateConfig};
+use crate::chains::{ChainError, ChainResult};
+
+/// How a pair of accounts is related
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MappingMethod {
+    /// Astar's default derivation in either direction
+    AstarDefault,
+    /// A pair claimed in Astar's unified accounts pallet
+    AstarUnified,
+    /// Moonbeam's XCM derivation of a relay chain account
+    MoonbeamXcm,
+}
+
+impl MappingMethod {
+    /// Stable name for storage and the frontend
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MappingMethod::AstarDefault => "astar_default",
+            MappingMethod::AstarUnified => "astar_unified",
+            MappingMethod::MoonbeamXcm => "moonbeam_xcm",
+        }
+    }
+}
+
+/// The account paired with another on a dual-environment chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairedAccount {
+    /// Chain the paired account is tracked on
+    pub chain: String,
+    /// Paired address, H160 hex or SS58 for `chain`
+    pub address: String,
+    /// How the accounts are related
+    pub method: MappingMethod,
+}
+
+// =============================================================================
+// DERIVATION
+// =============================================================================
+
+/// Astar's default native account for an H160
+pub fn astar_default_native(evm: &[u8; 20]) -> [u8; 32] {
+    let mut data = [0u8; 24];
+    data[..4].copy_from_slice(b"evm:");
+    data[4..].copy_from_slice(evm);
+    blake2_256(&data)
+}
+
+/// Astar's default H160 for a native account
+pub fn astar_default_evm(account: &[u8; 32]) -> [u8; 20] {
+    let hash = keccak_256(account);
+    let mut evm = [0u8; 20];
+    evm.copy_from_slice(&hash[12..]);
+    evm
+}
+
+/// The H160 a relay chain account controls on Moonbeam through XCM
+///
+/// This is the `HashedDescription` of the location `{ parents: 1, interior:
+/// AccountId32 }`: `blake2_256(("ParentChain", ("AccountId32", id)))`,
+/// truncated to 20 bytes.
+pub fn moonbeam_relay_derivative(account: &[u8; 32]) -> [u8; 20] {
+    // ("AccountId32", id) encoded: 11 + 32 bytes, then length-prefixed as a
+    // Vec<u8> (compact 43 = 0xac) after the family tag.
+    let mut data = Vec::with_capacity(11 + 1 + 11 + 32);
+    data.extend_from_slice(b"ParentChain");
+    data.push(43 << 2);
+    data.extend_from_slice(b"AccountId32");
+    data.extend_from_slice(account);
+    let hash = blake2_256(&data);
+    let mut evm = [0u8; 20];
+    evm.copy_from_slice(&hash[..20]);
+    evm
+}
+
+/// Parse a 0x-prefixed H160
+pub fn parse_h160(address: &str) -> Option<[u8; 20]> {
+    let bytes = hex::decode(address.trim().strip_prefix("0x")?).ok()?;
+    bytes.try_into().ok()
+}
+
+/// Format an H160 as lowercase 0x hex
+pub fn format_h160(evm: &[u8; 20]) -> String {
+    format!("0x{}", hex::encode(evm))
+}
+
+/// Accounts derived from `address` on `chain` without asking the chain
+///
+/// Astar's EVM and Substrate sides map to each other by default; a Polkadot
+/// account maps to its Moonbeam derivative. Moonbeam H160s are hashes, so
+/// they map to nothing.
+pub fn derived_accounts(chain: &str, address: &str) -> Vec<PairedAccount> {
+    match chain {
+        "astar" => parse_h160(address)
+            .and_then(|evm| {
+                let prefix = ss58::prefix_for_chain("astar-substrate")?;
+                ss58::encode(&astar_default_native(&evm), prefix).ok()
+            })
+            .map(|native| PairedAccount {
+                chain: "astar-substrate".to_string(),
+                address: native,
+                method: MappingMethod::AstarDefault,
+            })
+            .into_iter()
+            .collect(),
+        "astar-substrate" => ss58::decode(address)
+            .map(|native| PairedAccount {
+                chain: "astar".to_string(),
+                address: format_h160(&astar_default_evm(&native.public_key)),
+                method: MappingMethod::AstarDefault,
+            })
+            .into_iter()
+            .collect(),
+        "polkadot" => ss58::decode(address)
+            .map(|relay| PairedAccount {
+                chain: "moonbeam".to_string(),
+                address: format_h160(&moonbeam_relay_derivative(&relay.public_key)),
+                method: MappingMethod::MoonbeamXcm,
+            })
+            .into_iter()
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// =============================================================================
+// UNIFIED ACCOUNTS
+// =============================================================================
+
+/// Storage key of a `Blake2_128Concat` map entry in the unified accounts
+/// pallet
+fn unified_accounts_key(storage: &str, key: &[u8]) -> String {
+    let mut full = Vec::with_capacity(32 + 16 + key.len());
+    full.extend_from_slice(&twox_128(b"UnifiedAccounts"));
+    full.extend_from_slice(&twox_128(storage.as_bytes()));
+    full.extend_from_slice(&blake2_128(key));
+    full.extend_from_slice(key);
+    format!("0x{}", hex::encode(full))
+}
+
+/// HTTP JSON-RPC URL for a chain's WebSocket endpoint
+fn http_rpc_url(rpc_url: &str) -> String {
+    if let Some(rest) = rpc_url.strip_prefix("wss://") {
+        format!("https://{}", rest)
+    } else if let Some(rest) = rpc_url.strip_prefix("ws://") {
+        format!("http://{}", rest)
+    } else {
+        rpc_url.to_string()
+    }
+}
+
+/// Read a raw storage value, `None` when the entry is empty
+async fn get_storage(rpc_url: &str, key: &str) -> ChainResult<Option<Vec<u8>>> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| ChainError::Internal(format!("Failed to create HTTP client: {}", e)))?;
+
+    let response = client
+        .post(http_rpc_url(rpc_url))
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "state_getStorage",
+            "params": [key],
+        }))
+        .send()
+        .await
+        .map_err(|e| ChainError::RpcError(format!("Storage query failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(ChainError::RpcError(format!(
+            "Storage query HTTP {}",
+            response.status()
+        )));
+    }
+
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| ChainError::ParseError(format!("Invalid storage response: {}", e)))?;
+    if let Some(error) = body.get("error") {
+        return Err(ChainError::RpcError(error.to_string()));
+    }
+
+    match body.get("result").and_then(|r| r.as_str()) {
+        Some(value) => hex::decode(value.trim_start_matches("0x"))
+            .map(Some)
+            .map_err(|e| ChainError::ParseError(format!("Invalid storage value: {}", e))),
+        None => Ok(None),
+    }
+}
+
+/// The pair claimed in Astar's unified accounts pallet for an address on
+/// `astar` or `astar-substrate`, if any
+pub async fn astar_unified_account(
+    config: &SubstrateConfig,
+    chain: &str,
+    address: &str,
+) -> ChainResult<Option<PairedAccount>> {
+    match chain {
+        "astar" => {
+            let evm = parse_h160(address)
+                .ok_or_else(|| ChainError::InvalidAddress(address.to_string()))?;
+            let value =
+                get_storage(&config.rpc_url, &unified_accounts_key("EvmToNative", &evm)).await?;
+            let Some(native) = value.and_then(|v| <[u8; 32]>::try_from(v).ok()) else {
+                return Ok(None);
+            };
+            let prefix = ss58::prefix_for_chain("astar-substrate").unwrap_or(5);
+            Ok(Some(PairedAccount {
+                chain: "astar-substrate".to_string(),
+                address: ss58::encode(&native, prefix)?,
+                method: MappingMethod::AstarUnified,
+            }))
+        }
+        "astar-substrate" => {
+            let native = ss58::decode(address)?.public_key;
+            let value = get_storage(
+                &config.rpc_url,
+                &unified_accounts_key("NativeToEvm", &native),
+            )
+            .await?;
+            Ok(value
+                .and_then(|v| <[u8; 20]>::try_from(v).ok())
+                .map(|evm| PairedAccount {
+                    chain: "astar".to_string(),
+                    address: format_h160(&evm),
+                    method: MappingMethod::AstarUnified,
+                }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The account paired with `address`: a claimed unified account on Astar
+/// when there is one, otherwise the derived account
+pub async fn paired_accounts(chain: &str, address: &str) -> ChainResult<Vec<PairedAccount>> {
+    if chain == "astar" || chain == "astar-substrate" {
+        let config = SubstrateConfig::astar_substrate();
+        if let Some(unified) = astar_unified_account(&config, chain, address).await? {
+            return Ok(vec![unified]);
+        }
+    }
+    Ok(derived_accounts(chain, address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::mock_http::{mount_rpc, MockServer};
+
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+    const EVM: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+    #[test]
+    fn test_astar_default_mapping() {
+        let native = derived_accounts("astar", EVM);
+        assert_eq!(
+            native,
+            vec![PairedAccount {
+                chain: "astar-substrate".to_string(),
+                address: "bVgxQGrTCdJSYxKcWn3GxXeBeLTojWGxKcbQ8iHkLa7DXqP".to_string(),
+                method: MappingMethod::AstarDefault,
+            }]
+        );
+
+        let evm = derived_accounts("astar-substrate", ALICE);
+        assert_eq!(evm[0].chain, "astar");
+        assert_eq!(evm[0].address, "0x9621dde636de098b43efb0fa9b61facfe328f99d");
+    }
+
+    #[test]
+    fn test_moonbeam_relay_derivative() {
+        let derived = derived_accounts("polkadot", ALICE);
+        assert_eq!(derived[0].chain, "moonbeam");
+        assert_eq!(
+            derived[0].address,
+            "0x7dcb1027ecb97011ebe79ca233def50d1f216eb0"
+        );
+        assert_eq!(derived[0].method, MappingMethod::MoonbeamXcm);
+
+        assert!(derived_accounts("moonbeam", EVM).is_empty());
+        assert!(derived_accounts("astar", "not-an-address").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unified_account_read_from_storage() {
+        let server = MockServer::start().await;
+        let mut config = SubstrateConfig::astar_substrate();
+        config.rpc_url = server.uri();
+
+        let claimed = "0x00000000000000000000000000000000000000aa";
+        mount_rpc(
+            &server,
+            "state_getStorage",
+            json!({ "jsonrpc": "2.0", "id": 1, "result": claimed }),
+        )
+        .await;
+
+        let paired = astar_unified_account(&config, "astar-substrate", ALICE)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(paired.address, claimed);
+        assert_eq!(paired.method, MappingMethod::AstarUnified);
+    }
+
+    #[tokio::test]
+    async fn test_unclaimed_unified_account_is_none() {
+        let server = MockServer::start().await;
+        let mut config = SubstrateConfig::astar_substrate();
+        config.rpc_url = server.uri();
+        mount_rpc(
+            &server,
+            "state_getStorage",
+            json!({ "jsonrpc": "2.0", "id": 1, "result": null }),
+        )
+        .await;
+
+        assert!(astar_unified_account(&config, "astar", EVM)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,635 @@
+//! Substrate Chain Adapter
+//!
+//! Provides access to Substrate-based chains (Polkadot, Kusama, etc.)
+//! Balances and transfers are read from Subscan, which also indexes the
+//! `assets` and `orml-tokens` pallets, so multi-currency chains like Acala
+//! report their full holdings rather than only the native token.
+
+/// Mapping between EVM and Substrate accounts on Astar and Moonbeam.
+pub mod account_mapping;
+/// SS58 address encoding, decoding, and network prefixes.
+pub mod ss58;
+/// Subscan API client for balances and transfers.
+pub mod subscan;
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::chains::units::{self, U256};
+use crate::chains::{
+    ChainAdapter, ChainError, ChainId, ChainResult, ChainTransaction, NativeBalance, TokenBalance,
+    TokenTransfer, TransactionStatus, TransactionType,
+};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use tokio::sync::OnceCell;
+
+use subscan::{SubscanAccountTokens, SubscanClient, SubscanToken, SubscanTransfer};
+
+/// Substrate chain configuration parameters.
+#[derive(Debug, Clone)]
+pub struct SubstrateConfig {
+    /// Internal chain name identifier.
+    pub name: String,
+    /// Human-readable display name.
+    pub display_name: String,
+    /// Native currency symbol (e.g., DOT).
+    pub native_symbol: String,
+    /// Native currency decimals.
+    pub native_decimals: u8,
+    /// WebSocket RPC endpoint URL.
+    pub rpc_url: String,
+    /// Subscan API URL for transaction indexing.
+    pub subscan_url: Option<String>,
+}
+
+impl SubstrateConfig {
+    /// Creates configuration for Polkadot mainnet.
+    pub fn polkadot() -> Self {
+        Self {
+            name: "polkadot".to_string(),
+            display_name: "Polkadot".to_string(),
+            native_symbol: "DOT".to_string(),
+            native_decimals: 10,
+            rpc_url: "wss://rpc.polkadot.io".to_string(),
+            subscan_url: Some("https://polkadot.api.subscan.io".to_string()),
+        }
+    }
+
+    /// Creates configuration for Kusama mainnet.
+    pub fn kusama() -> Self {
+        Self {
+            name: "kusama".to_string(),
+            display_name: "Kusama".to_string(),
+            native_symbol: "KSM".to_string(),
+            native_decimals: 12,
+            rpc_url: "wss://kusama-rpc.polkadot.io".to_string(),
+            subscan_url: Some("https://kusama.api.subscan.io".to_string()),
+        }
+    }
+
+    /// Creates configuration for Westend testnet.
+    pub fn westend() -> Self {
+        Self {
+            name: "westend".to_string(),
+            display_name: "Westend".to_string(),
+            native_symbol: "WND".to_string(),
+            native_decimals: 12,
+            rpc_url: "wss://westend-rpc.polkadot.io".to_string(),
+            subscan_url: Some("https://westend.api.subscan.io".to_string()),
+        }
+    }
+
+    /// Creates configuration for Acala mainnet.
+    pub fn acala() -> Self {
+        Self {
+            name: "acala".to_string(),
+            display_name: "Acala".to_string(),
+            native_symbol: "ACA".to_string(),
+            native_decimals: 12,
+            rpc_url: "wss://acala-rpc.aca-api.network".to_string(),
+            subscan_url: Some("https://acala.api.subscan.io".to_string()),
+        }
+    }
+
+    /// Creates configuration for Astar Substrate layer.
+    pub fn astar_substrate() -> Self {
+        Self {
+            name: "astar-substrate".to_string(),
+            display_name: "Astar (Substrate)".to_string(),
+            native_symbol: "ASTR".to_string(),
+            native_decimals: 18,
+            rpc_url: "wss://rpc.astar.network".to_string(),
+            subscan_url: Some("https://astar.api.subscan.io".to_string()),
+        }
+    }
+}
+
+/// Chains reachable over XCM, as (relay chain, para ID, chain name). Para
+/// ID 0 is the relay chain itself.
+pub const PARACHAINS: &[(&str, u32, &str)] = &[
+    ("polkadot", 0, "polkadot"),
+    ("polkadot", 2000, "acala"),
+    ("polkadot", 2004, "moonbeam"),
+    ("polkadot", 2006, "astar-substrate"),
+    ("kusama", 0, "kusama"),
+    ("kusama", 2023, "moonriver"),
+    ("westend", 0, "westend"),
+];
+
+/// Chain name of a parachain under `relay`
+pub fn chain_for_para(relay: &str, para_id: u32) -> Option<&'static str> {
+    PARACHAINS
+        .iter()
+        .find(|(r, id, _)| *r == relay && *id == para_id)
+        .map(|(_, _, chain)| *chain)
+}
+
+/// Relay chain a chain is connected to over XCM
+pub fn relay_for_chain(chain: &str) -> Option<&'static str> {
+    PARACHAINS
+        .iter()
+        .find(|(_, _, c)| *c == chain)
+        .map(|(relay, _, _)| *relay)
+}
+
+/// Parachain slot lease timing, as (relay chain, lease period in blocks,
+/// offset of the first period in blocks).
+pub const LEASE_PERIODS: &[(&str, u64, u64)] =
+    &[("polkadot", 1_209_600, 921_600), ("kusama", 604_800, 0)];
+
+/// Relay chain block at which a slot whose last lease period is
+/// `last_period` ends, releasing its crowdloan contributions
+pub fn lease_end_block(relay: &str, last_period: u32) -> Option<u64> {
+    LEASE_PERIODS
+        .iter()
+        .find(|(r, _, _)| *r == relay)
+        .map(|(_, period, offset)| (u64::from(last_period) + 1) * period + offset)
+}
+
+/// Whether an address is a pallet's account (a crowdloan fund, treasury,
+/// or rewards pot), whose keys start with `modl`
+pub fn is_pallet_account(address: &str) -> bool {
+    let bytes = match ss58::decode(address) {
+        Ok(decoded) => decoded.public_key.to_vec(),
+        Err(_) => account_mapping::parse_h160(address)
+            .map(|evm| evm.to_vec())
+            .unwrap_or_default(),
+    };
+    bytes.starts_with(b"modl")
+}
+
+/// Get Substrate config by chain name
+pub fn get_config_by_name(name: &str) -> Option<SubstrateConfig> {
+    match name.to_lowercase().as_str() {
+        "polkadot" | "dot" => Some(SubstrateConfig::polkadot()),
+        "kusama" | "ksm" => Some(SubstrateConfig::kusama()),
+        "westend" => Some(SubstrateConfig::westend()),
+        "acala" | "aca" => Some(SubstrateConfig::acala()),
+        "astar-substrate" => Some(SubstrateConfig::astar_substrate()),
+        _ => None,
+    }
+}
+
+/// Substrate Chain Adapter
+///
+/// Provides access to Substrate-based chains via RPC and Subscan API.
+pub struct SubstrateAdapter {
+    chain_id: ChainId,
+    config: SubstrateConfig,
+    connected: bool,
+    /// Subscan client, created on first use
+    subscan: OnceCell<Arc<SubscanClient>>,
+    /// Subscan API key (if configured)
+    subscan_api_key: Option<String>,
+}
+
+impl SubstrateAdapter {
+    /// Create a new Substrate adapter
+    pub fn new(config: SubstrateConfig) -> Self {
+        let chain_id = ChainId::substrate(&config.name);
+
+        Self {
+            chain_id,
+            config,
+            connected: false,
+            subscan: OnceCell::new(),
+            subscan_api_key: None,
+        }
+    }
+
+    /// Set the Subscan API key (builder pattern)
+    pub fn with_subscan_api_key(mut self, key: String) -> Self {
+        self.subscan_api_key = Some(key);
+        self
+    }
+
+    /// Create adapter for Polkadot
+    pub fn polkadot() -> Self {
+        Self::new(SubstrateConfig::polkadot())
+    }
+
+    /// Create adapter for Kusama
+    pub fn kusama() -> Self {
+        Self::new(SubstrateConfig::kusama())
+    }
+
+    /// Create adapter for Acala
+    pub fn acala() -> Self {
+        Self::new(SubstrateConfig::acala())
+    }
+
+    /// Get or initialize the Subscan client
+    async fn get_subscan_client(&self) -> ChainResult<Arc<SubscanClient>> {
+        let base_url = self.config.subscan_url.as_deref().ok_or_else(|| {
+            ChainError::ConfigError(format!("No Subscan API for {}", self.config.name))
+        })?;
+        self.subscan
+            .get_or_try_init(|| async {
+                SubscanClient::new(base_url, self.subscan_api_key.as_deref()).map(Arc::new)
+            })
+            .await
+            .cloned()
+    }
+
+    /// Whether a currency is the chain's native token
+    fn is_native(&self, unique_id: &str, symbol: &str) -> bool {
+        let native = &self.config.native_symbol;
+        unique_id.eq_ignore_ascii_case(native)
+            || (unique_id.is_empty() && symbol.eq_ignore_ascii_case(native))
+    }
+
+    /// Non-native currencies held in the `orml-tokens` and `assets` pallets
+    fn token_balances(&self, tokens: SubscanAccountTokens) -> Vec<TokenBalance> {
+        tokens
+            .builtin
+            .into_iter()
+            .chain(tokens.assets)
+            .filter(|t| !self.is_native(&t.unique_id, &t.symbol))
+            .filter(|t| !t.balance.trim_start_matches('0').is_empty())
+            .map(|t| TokenBalance {
+                token_address: if t.unique_id.is_empty() {
+                    t.symbol.clone()
+                } else {
+                    t.unique_id.clone()
+                },
+                balance_formatted: format_balance(&t.balance, t.decimals),
+                token_symbol: Some(t.symbol),
+                token_name: None,
+                token_decimals: t.decimals,
+                balance: t.balance,
+                extensions: None,
+            })
+            .collect()
+    }
+
+    /// Groups an account's transfers by extrinsic into transactions
+    ///
+    /// Native transfers become the transaction's value; `orml-tokens` and
+    /// `assets` transfers become token transfers, keyed by Subscan's
+    /// currency id. `decimals` gives each currency's decimals for transfers
+    /// that only report a whole-unit amount.
+    fn normalize_transfers(
+        &self,
+        address: &str,
+        transfers: Vec<SubscanTransfer>,
+        decimals: &HashMap<String, u8>,
+    ) -> Vec<ChainTransaction> {
+        let mut transactions: Vec<ChainTransaction> = Vec::new();
+        let mut by_hash: HashMap<String, usize> = HashMap::new();
+
+        for transfer in transfers {
+            let index = *by_hash.entry(transfer.hash.clone()).or_insert_with(|| {
+                transactions.push(ChainTransaction {
+                    hash: transfer.hash.clone(),
+                    chain_id: self.chain_id.clone(),
+                    block_number: transfer.block_num,
+                    timestamp: transfer.block_timestamp,
+                    from: transfer.from.clone(),
+                    to: Some(transfer.to.clone()),
+                    value: "0".to_string(),
+                    fee: "0".to_string(),
+                    status: if transfer.success {
+                        TransactionStatus::Success
+                    } else {
+                        TransactionStatus::Failed
+                    },
+                    tx_type: TransactionType::Transfer,
+                    token_transfers: Vec::new(),
+                    swaps: Vec::new(),
+                    fee_breakdown: None,
+                    user_operations: Vec::new(),
+                    inner_calls: Vec::new(),
+                    raw_data: Some(serde_json::Value::Array(Vec::new())),
+                });
+                transactions.len() - 1
+            });
+            let tx = &mut transactions[index];
+            // The signer pays the fee, so it's only ours when we sent something.
+            if same_account(&transfer.from, address) {
+                tx.fee = transfer.fee.clone();
+            }
+
+            if self.is_native(&transfer.asset_unique_id, &transfer.asset_symbol)
+                || transfer.module == "balances"
+            {
+                let value = raw_amount(&transfer, Some(self.config.native_decimals));
+                tx.value = value.map(|v| v.to_string()).unwrap_or_default();
+                tx.from = transfer.from.clone();
+                tx.to = Some(transfer.to.clone());
+            } else {
+                let currency = if transfer.asset_unique_id.is_empty() {
+                    transfer.asset_symbol.clone()
+                } else {
+                    transfer.asset_unique_id.clone()
+                };
+                let known = decimals.get(&currency).copied();
+                // Without known decimals, keep the whole-unit amount as is.
+                let (value, token_decimals) = match raw_amount(&transfer, known) {
+                    Some(raw) => (raw.to_string(), known),
+                    None => (transfer.amount.clone(), Some(0)),
+                };
+                tx.token_transfers.push(TokenTransfer {
+                    token_address: currency,
+                    token_symbol: Some(transfer.asset_symbol.clone()),
+                    token_decimals,
+                    from: transfer.from.clone(),
+                    to: transfer.to.clone(),
+                    value,
+                });
+            }
+
+            if let Some(serde_json::Value::Array(raw)) = tx.raw_data.as_mut() {
+                raw.push(serde_json::to_value(&transfer).unwrap_or_default());
+            }
+        }
+
+        transactions
+    }
+}
+
+/// Whether two SS58 addresses are the same account, whatever their network
+/// prefixes
+fn same_account(a: &str, b: &str) -> bool {
+    match (ss58::decode(a), ss58::decode(b)) {
+        (Ok(a), Ok(b)) => a.public_key == b.public_key,
+        _ => a == b,
+    }
+}
+
+/// A transfer's amount in smallest units, from Subscan's raw amount or by
+/// scaling the whole-unit amount when `decimals` is known
+fn raw_amount(transfer: &SubscanTransfer, decimals: Option<u8>) -> Option<U256> {
+    if let Some(raw) = transfer.amount_v2.as_deref().filter(|v| !v.is_empty()) {
+        return units::parse_decimal(raw).ok();
+    }
+    let amount = Decimal::from_str(transfer.amount.trim()).ok()?;
+    units::to_smallest_units(amount, decimals?)
+}
+
+/// Formats a smallest-unit balance string in whole units
+fn format_balance(balance: &str, decimals: u8) -> String {
+    units::parse_decimal(balance)
+        .map(|raw| units::format_units(raw, decimals))
+        .unwrap_or_else(|_| "0".to_string())
+}
+
+/// Decimals of each currency an account holds, keyed by Subscan currency id
+fn currency_decimals(tokens: &SubscanAccountTokens) -> HashMap<String, u8> {
+    let key = |t: &SubscanToken| {
+        if t.unique_id.is_empty() {
+            t.symbol.clone()
+        } else {
+            t.unique_id.clone()
+        }
+    };
+    tokens
+        .native
+        .iter()
+        .chain(&tokens.builtin)
+        .chain(&tokens.assets)
+        .map(|t| (key(t), t.decimals))
+        .collect()
+}
+
+#[async_trait]
+impl ChainAdapter for SubstrateAdapter {
+    fn chain_id(&self) -> &ChainId {
+        &self.chain_id
+    }
+
+    async fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    async fn connect(&mut self) -> ChainResult<()> {
+        // Placeholder: actual subxt connection not yet implemented
+        self.connected = true;
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> ChainResult<()> {
+        self.connected = false;
+        Ok(())
+    }
+
+    async fn get_block_number(&self) -> ChainResult<u64> {
+        self.get_subscan_client().await?.get_block_number().await
+    }
+
+    async fn get_native_balance(&self, address: &str) -> ChainResult<NativeBalance> {
+        let tokens = self
+            .get_subscan_client()
+            .await?
+            .get_account_tokens(address)
+            .await?;
+        let balance = tokens
+            .native
+            .into_iter()
+            .find(|t| self.is_native(&t.unique_id, &t.symbol))
+            .map(|t| t.balance)
+            .filter(|b| !b.is_empty())
+            .unwrap_or_else(|| "0".to_string());
+
+        Ok(NativeBalance {
+            symbol: self.config.native_symbol.clone(),
+            decimals: self.config.native_decimals,
+            balance_formatted: format_balance(&balance, self.config.native_decimals),
+            balance,
+        })
+    }
+
+    async fn get_token_balances(&self, address: &str) -> ChainResult<Vec<TokenBalance>> {
+        let tokens = self
+            .get_subscan_client()
+            .await?
+            .get_account_tokens(address)
+            .await?;
+        Ok(self.token_balances(tokens))
+    }
+
+    async fn get_transactions(
+        &self,
+        address: &str,
+        from_block: Option<u64>,
+        to_block: Option<u64>,
+    ) -> ChainResult<Vec<ChainTransaction>> {
+        let client = self.get_subscan_client().await?;
+        let decimals = currency_decimals(&client.get_account_tokens(address).await?);
+        let transfers = client
+            .get_all_transfers(address, from_block)
+            .await?
+            .into_iter()
+            .filter(|t| from_block.is_none_or(|from| t.block_num >= from))
+            .filter(|t| to_block.is_none_or(|to| t.block_num <= to))
+            .collect();
+
+        Ok(self.normalize_transfers(address, transfers, &decimals))
+    }
+
+    async fn get_transaction(&self, _hash: &str) -> ChainResult<ChainTransaction> {
+        // Placeholder: subxt integration pending
+        Err(ChainError::Internal(
+            "Substrate adapter not fully implemented".to_string(),
+        ))
+    }
+
+    fn validate_address(&self, address: &str) -> bool {
+        ss58::decode(address).is_ok()
+    }
+
+    fn format_address(&self, address: &str) -> ChainResult<String> {
+        if !self.validate_address(address) {
+            return Err(ChainError::InvalidAddress(address.to_string()));
+        }
+        Ok(address.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::mock_http::{fixture, mount_post, MockServer};
+
+    const ADDRESS: &str = "22bByhJxYURLGHguQJmygeCcuZaTtL9FPAyhTkmrsEBG94QJ";
+
+    async fn acala_adapter(server: &MockServer) -> SubstrateAdapter {
+        mount_post(
+            server,
+            "/api/scan/account/tokens",
+            serde_json::json!({}),
+            fixture("subscan/account_tokens.json"),
+        )
+        .await;
+        mount_post(
+            server,
+            "/api/v2/scan/transfers",
+            serde_json::json!({}),
+            fixture("subscan/transfers.json"),
+        )
+        .await;
+
+        let mut config = SubstrateConfig::acala();
+        config.subscan_url = Some(server.uri());
+        SubstrateAdapter::new(config)
+    }
+
+    #[test]
+    fn test_lease_end_block() {
+        // Acala's first slot, lease periods 6 to 13, ended at lease 14.
+        assert_eq!(lease_end_block("polkadot", 13), Some(17_856_000));
+        assert_eq!(lease_end_block("kusama", 2), Some(1_814_400));
+        assert_eq!(lease_end_block("acala", 13), None);
+    }
+
+    #[test]
+    fn test_is_pallet_account() {
+        let mut fund = [0u8; 32];
+        fund[..12].copy_from_slice(b"modlpy/cfund");
+        fund[12..16].copy_from_slice(&6u32.to_le_bytes());
+        assert!(is_pallet_account(&ss58::encode(&fund, 0).unwrap()));
+        assert!(is_pallet_account(
+            "0x6d6f646c70632f61736800000000000000000000"
+        ));
+        assert!(!is_pallet_account(ADDRESS));
+        assert!(!is_pallet_account(
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"
+        ));
+    }
+
+    #[test]
+    fn test_substrate_config() {
+        let polkadot = SubstrateConfig::polkadot();
+        assert_eq!(polkadot.name, "polkadot");
+        assert_eq!(polkadot.native_symbol, "DOT");
+        assert_eq!(polkadot.native_decimals, 10);
+    }
+
+    #[test]
+    fn test_validate_address() {
+        let adapter = SubstrateAdapter::polkadot();
+
+        // Valid Polkadot address (starts with 1)
+        assert!(adapter.validate_address("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5"));
+
+        // Invalid addresses
+        assert!(!adapter.validate_address(""));
+        assert!(!adapter.validate_address("0x123")); // EVM format
+    }
+
+    #[test]
+    fn test_same_account_across_prefixes() {
+        // The same key encoded for Polkadot and for Acala
+        assert!(same_account(
+            "12ibPmtvhZg4hheGLyyjudYNSi9XLVr3VmSPJvLrEgEPKiHJ",
+            ADDRESS
+        ));
+        assert!(!same_account(
+            "25qjcdgYUMBzobjSqgyWgXr8mkURyX6CH2RSUvvj4ZWDoL43",
+            ADDRESS
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_acala_balances_include_orml_tokens() {
+        let server = MockServer::start().await;
+        let adapter = acala_adapter(&server).await;
+
+        let native = adapter.get_native_balance(ADDRESS).await.unwrap();
+        assert_eq!(native.symbol, "ACA");
+        assert_eq!(native.balance, "152340000000000");
+
+        let tokens = adapter.get_token_balances(ADDRESS).await.unwrap();
+        let held: Vec<(&str, &str)> = tokens
+            .iter()
+            .map(|t| (t.token_address.as_str(), t.balance.as_str()))
+            .collect();
+        // Zero balances are left out.
+        assert_eq!(
+            held,
+            vec![
+                ("AUSD", "2500000000000000"),
+                ("LDOT", "845000000000"),
+                ("ForeignAsset/12", "100000000"),
+            ]
+        );
+        assert_eq!(tokens[1].token_decimals, 10);
+    }
+
+    #[tokio::test]
+    async fn test_acala_transfers_group_by_extrinsic() {
+        let server = MockServer::start().await;
+        let adapter = acala_adapter(&server).await;
+
+        let txs = adapter.get_transactions(ADDRESS, None, None).await.unwrap();
+        assert_eq!(txs.len(), 2);
+
+        // An incoming aUSD transfer: no native value, and the sender paid the fee.
+        let received = &txs[0];
+        assert_eq!(received.value, "0");
+        assert_eq!(received.fee, "0");
+        assert_eq!(received.token_transfers.len(), 1);
+        assert_eq!(received.token_transfers[0].token_address, "AUSD");
+        assert_eq!(received.token_transfers[0].value, "1000000000000000");
+
+        // ACA out and LDOT in within one extrinsic the wallet signed.
+        let exchanged = &txs[1];
+        assert_eq!(exchanged.value, "12500000000000");
+        assert_eq!(exchanged.from, ADDRESS);
+        assert_eq!(exchanged.fee, "2990000000");
+        assert_eq!(
+            exchanged.token_transfers[0].token_symbol.as_deref(),
+            Some("LDOT")
+        );
+        // Scaled from the whole-unit amount with LDOT's 10 decimals.
+        assert_eq!(exchanged.token_transfers[0].value, "32000000000");
+        assert_eq!(exchanged.token_transfers[0].token_decimals, Some(10));
+
+        let recent = adapter
+            .get_transactions(ADDRESS, Some(5_123_000), None)
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 1);
+    }
+}
@@ -0,0 +1,168 @@
+//! SS58 address encoding and decoding.
+//!
+//! An SS58 address is base58 over `prefix || public key || checksum`, where
+//! the prefix identifies the network and the checksum is the first two bytes
+//! of `blake2b-512("SS58PRE" || prefix || public key)`.
+
+use sp_core::hashing::blake2_512;
+
+use crate::chains::{ChainError, ChainResult};
+
+/// Bytes mixed into the checksum preimage.
+const CHECKSUM_PREFIX: &[u8] = b"SS58PRE";
+
+/// Length of the checksum for 32-byte account IDs.
+const CHECKSUM_LEN: usize = 2;
+
+/// Networks with a registered SS58 prefix, as (prefix, chain_id, name).
+pub const NETWORKS: &[(u16, &str, &str)] = &[
+    (0, "polkadot", "Polkadot"),
+    (2, "kusama", "Kusama"),
+    (5, "astar-substrate", "Astar"),
+    (10, "acala", "Acala"),
+    (42, "westend", "Substrate (generic)"),
+];
+
+/// A decoded SS58 address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ss58Address {
+    /// Network prefix.
+    pub prefix: u16,
+    /// 32-byte account public key.
+    pub public_key: [u8; 32],
+}
+
+/// Network registered for `prefix`, as (chain_id, name).
+pub fn network_for_prefix(prefix: u16) -> Option<(&'static str, &'static str)> {
+    NETWORKS
+        .iter()
+        .find(|(p, _, _)| *p == prefix)
+        .map(|(_, chain_id, name)| (*chain_id, *name))
+}
+
+/// Prefix registered for a supported chain.
+pub fn prefix_for_chain(chain_id: &str) -> Option<u16> {
+    NETWORKS
+        .iter()
+        .find(|(_, id, _)| id.eq_ignore_ascii_case(chain_id))
+        .map(|(prefix, _, _)| *prefix)
+}
+
+/// Computes the checksum over the prefix bytes and payload.
+fn checksum(data: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let mut preimage = Vec::with_capacity(CHECKSUM_PREFIX.len() + data.len());
+    preimage.extend_from_slice(CHECKSUM_PREFIX);
+    preimage.extend_from_slice(data);
+    let hash = blake2_512(&preimage);
+    [hash[0], hash[1]]
+}
+
+/// Decodes and verifies an SS58 address for a 32-byte account.
+pub fn decode(address: &str) -> ChainResult<Ss58Address> {
+    let data = bs58::decode(address.trim())
+        .into_vec()
+        .map_err(|_| ChainError::InvalidAddress("Invalid base58 encoding".to_string()))?;
+
+    let (prefix_len, prefix) = match data.first().copied() {
+        Some(b @ 0..=63) => (1, b as u16),
+        Some(b @ 64..=127) if data.len() > 1 => {
+            let lower = (b << 2) | (data[1] >> 6);
+            let upper = data[1] & 0b0011_1111;
+            (2, lower as u16 | ((upper as u16) << 8))
+        }
+        _ => {
+            return Err(ChainError::InvalidAddress(
+                "Invalid SS58 prefix".to_string(),
+            ))
+        }
+    };
+
+    if data.len() != prefix_len + 32 + CHECKSUM_LEN {
+        return Err(ChainError::InvalidAddress(format!(
+            "Invalid SS58 address: decoded to {} bytes",
+            data.len()
+        )));
+    }
+
+    let (body, check) = data.split_at(prefix_len + 32);
+    if checksum(body) != check {
+        return Err(ChainError::InvalidAddress(
+            "SS58 checksum mismatch".to_string(),
+        ));
+    }
+
+    let mut public_key = [0u8; 32];
+    public_key.copy_from_slice(&body[prefix_len..]);
+    Ok(Ss58Address { prefix, public_key })
+}
+
+/// Encodes a 32-byte account public key for the network with `prefix`.
+pub fn encode(public_key: &[u8; 32], prefix: u16) -> ChainResult<String> {
+    let mut data = match prefix {
+        0..=63 => vec![prefix as u8],
+        64..=16_383 => {
+            let first = ((prefix & 0b0000_0000_1111_1100) as u8) >> 2;
+            let second = ((prefix >> 8) as u8) | (((prefix & 0b0000_0000_0000_0011) as u8) << 6);
+            vec![first | 0b0100_0000, second]
+        }
+        _ => {
+            return Err(ChainError::InvalidAddress(format!(
+                "SS58 prefix {} is out of range",
+                prefix
+            )))
+        }
+    };
+    data.extend_from_slice(public_key);
+    let check = checksum(&data);
+    data.extend_from_slice(&check);
+    Ok(bs58::encode(data).into_string())
+}
+
+/// Re-encodes an SS58 address for the network with `prefix`.
+pub fn convert(address: &str, prefix: u16) -> ChainResult<String> {
+    let decoded = decode(address)?;
+    encode(&decoded.public_key, prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_known_addresses() {
+        let alice = decode("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY").unwrap();
+        assert_eq!(alice.prefix, 42);
+        assert_eq!(
+            hex::encode(alice.public_key),
+            "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+        );
+
+        let polkadot = decode("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5").unwrap();
+        assert_eq!(polkadot.prefix, 0);
+        assert_eq!(network_for_prefix(polkadot.prefix).unwrap().0, "polkadot");
+    }
+
+    #[test]
+    fn test_convert_between_networks() {
+        let generic = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+        let polkadot = convert(generic, 0).unwrap();
+        assert_eq!(polkadot, "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5");
+        assert_eq!(convert(&polkadot, 42).unwrap(), generic);
+
+        // Two-byte prefixes round-trip too.
+        let moonbeam = convert(generic, 1284).unwrap();
+        let decoded = decode(&moonbeam).unwrap();
+        assert_eq!(decoded.prefix, 1284);
+        assert_eq!(decoded.public_key, decode(generic).unwrap().public_key);
+
+        assert!(encode(&[0u8; 32], 16_384).is_err());
+        assert_eq!(prefix_for_chain("Kusama"), Some(2));
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_checksum() {
+        assert!(decode("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQZ").is_err());
+        assert!(decode("0x1234").is_err());
+        assert!(decode("").is_err());
+    }
+}
@@ -0,0 +1,593 @@
+//! Subscan API Client
+//!
+//! Client for the Subscan explorer API, which indexes balances and transfers
+//! for Substrate chains. Besides the native currency it covers assets held in
+//! the `assets` and `orml-tokens` pallets, such as aUSD and LDOT on Acala.
+//!
+//! API documentation: https://support.subscan.io
+
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+
+use governor::{Quota, RateLimiter};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::chains::{ChainError, ChainResult};
+use crate::fetchers::{ApiProvider, GovernorLimiter};
+
+/// Transfers per page (Subscan's maximum)
+pub const TRANSFERS_PER_PAGE: usize = 100;
+
+/// Upper bound on transfer pages fetched for one address
+const MAX_TRANSFER_PAGES: usize = 50;
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// Subscan response envelope
+#[derive(Debug, Deserialize)]
+struct SubscanResponse<T> {
+    code: i64,
+    #[serde(default)]
+    message: String,
+    data: Option<T>,
+}
+
+/// Chain metadata from `/api/scan/metadata`
+#[derive(Debug, Deserialize)]
+struct SubscanMetadata {
+    #[serde(rename = "blockNum")]
+    block_num: String,
+}
+
+/// An account's balance of one currency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscanToken {
+    /// Currency symbol (e.g. AUSD)
+    pub symbol: String,
+    /// Subscan's identifier for the currency (e.g. `AUSD`, `ForeignAsset/12`)
+    #[serde(default)]
+    pub unique_id: String,
+    /// Currency decimals
+    pub decimals: u8,
+    /// Free balance in smallest units
+    #[serde(default)]
+    pub balance: String,
+}
+
+/// An account's balances by pallet, from `/api/scan/account/tokens`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubscanAccountTokens {
+    /// The native currency (`balances` pallet)
+    #[serde(default)]
+    pub native: Vec<SubscanToken>,
+    /// Multi-currency balances (`orml-tokens` pallet)
+    #[serde(default)]
+    pub builtin: Vec<SubscanToken>,
+    /// Balances in the `assets` pallet
+    #[serde(default)]
+    pub assets: Vec<SubscanToken>,
+}
+
+/// A transfer event, from `/api/v2/scan/transfers`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscanTransfer {
+    /// Sender address
+    pub from: String,
+    /// Recipient address
+    pub to: String,
+    /// Extrinsic hash
+    pub hash: String,
+    /// Block number
+    pub block_num: u64,
+    /// Block timestamp (seconds)
+    pub block_timestamp: i64,
+    /// Whether the extrinsic succeeded
+    #[serde(default)]
+    pub success: bool,
+    /// Pallet that emitted the transfer (`balances`, `tokens`, `assets`, ...)
+    #[serde(default)]
+    pub module: String,
+    /// Amount in whole units
+    #[serde(default)]
+    pub amount: String,
+    /// Amount in smallest units, when Subscan provides it
+    #[serde(default)]
+    pub amount_v2: Option<String>,
+    /// Fee paid by the signer, in smallest units of the native currency
+    #[serde(default)]
+    pub fee: String,
+    /// Symbol of the transferred currency
+    #[serde(default)]
+    pub asset_symbol: String,
+    /// Subscan's identifier for the transferred currency
+    #[serde(default)]
+    pub asset_unique_id: String,
+}
+
+/// An asset moved by an XCM message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscanXcmAsset {
+    /// Asset symbol
+    #[serde(default)]
+    pub symbol: String,
+    /// Amount in whole units
+    #[serde(default)]
+    pub amount: String,
+    /// Asset decimals
+    #[serde(default)]
+    pub decimals: u8,
+}
+
+/// A cross-chain transfer, from the relay chain's `/api/scan/xcm/list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscanXcmTransfer {
+    /// Hash identifying the XCM message
+    pub message_hash: String,
+    /// Parachain the message was sent from (0 for the relay chain)
+    #[serde(default)]
+    pub origin_para_id: u32,
+    /// Parachain the message was delivered to (0 for the relay chain)
+    #[serde(default)]
+    pub dest_para_id: u32,
+    /// Sending account
+    #[serde(default)]
+    pub from_account_id: String,
+    /// Receiving account
+    #[serde(default)]
+    pub to_account_id: String,
+    /// Sending extrinsic, as `block-index`
+    #[serde(default)]
+    pub extrinsic_index: String,
+    /// Event that delivered the assets, as `block-index`
+    #[serde(default)]
+    pub dest_event_index: String,
+    /// When the message was sent (seconds)
+    #[serde(default)]
+    pub origin_block_timestamp: i64,
+    /// When the message was executed on the destination (seconds, 0 if not
+    /// yet)
+    #[serde(default)]
+    pub confirm_block_timestamp: i64,
+    /// `success`, `pending`, or `failed`
+    #[serde(default)]
+    pub status: String,
+    /// Assets moved
+    #[serde(default)]
+    pub assets: Vec<SubscanXcmAsset>,
+}
+
+/// One page of XCM transfers
+#[derive(Debug, Deserialize)]
+struct SubscanXcmPage {
+    #[serde(default)]
+    count: usize,
+    #[serde(default)]
+    list: Option<Vec<SubscanXcmTransfer>>,
+}
+
+/// A crowdloan contribution, from the relay chain's
+/// `/api/scan/parachain/contributes`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscanContribution {
+    /// Crowdloan fund, as `para_id-index`
+    pub fund_id: String,
+    /// Parachain the crowdloan was for
+    #[serde(default)]
+    pub para_id: u32,
+    /// Contributing account
+    #[serde(default)]
+    pub who: String,
+    /// Amount contributed, in smallest units
+    #[serde(default)]
+    pub contributed: String,
+    /// Block the contribution was made in
+    #[serde(default)]
+    pub block_num: u64,
+    /// Block timestamp (seconds)
+    #[serde(default)]
+    pub block_timestamp: i64,
+    /// Contributing extrinsic, as `block-index`
+    #[serde(default)]
+    pub extrinsic_index: String,
+}
+
+/// A crowdloan fund and the lease it bid for, from
+/// `/api/scan/parachain/funds`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscanFund {
+    /// Crowdloan fund, as `para_id-index`
+    pub fund_id: String,
+    /// Parachain the crowdloan was for
+    #[serde(default)]
+    pub para_id: u32,
+    /// First lease period of the slot
+    #[serde(default)]
+    pub first_period: u32,
+    /// Last lease period of the slot
+    #[serde(default)]
+    pub last_period: u32,
+}
+
+/// One page of crowdloan contributions
+#[derive(Debug, Deserialize)]
+struct SubscanContributionPage {
+    #[serde(default)]
+    count: usize,
+    #[serde(default)]
+    contributes: Option<Vec<SubscanContribution>>,
+}
+
+/// One page of crowdloan funds
+#[derive(Debug, Deserialize)]
+struct SubscanFundPage {
+    #[serde(default)]
+    funds: Option<Vec<SubscanFund>>,
+}
+
+/// One page of transfers
+#[derive(Debug, Deserialize)]
+struct SubscanTransferPage {
+    #[serde(default)]
+    count: usize,
+    #[serde(default)]
+    transfers: Option<Vec<SubscanTransfer>>,
+}
+
+// =============================================================================
+// CLIENT
+// =============================================================================
+
+/// Subscan API client for one Substrate chain
+pub struct SubscanClient {
+    /// HTTP client
+    client: Client,
+    /// Governor rate limiter
+    limiter: Arc<GovernorLimiter>,
+    /// API base URL, e.g. `https://acala.api.subscan.io`
+    base_url: String,
+    /// API key (raises the rate limit)
+    api_key: Option<String>,
+}
+
+impl SubscanClient {
+    /// Create a new Subscan client, rate limited for the key's tier
+    pub fn new(base_url: &str, api_key: Option<&str>) -> ChainResult<Self> {
+        let rate_limit = if api_key.is_some() {
+            ApiProvider::Subscan.turbo_rate_limit()
+        } else {
+            ApiProvider::Subscan.default_rate_limit()
+        };
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| ChainError::Internal(format!("Failed to create HTTP client: {}", e)))?;
+
+        let rps = NonZeroU32::new(rate_limit)
+            .ok_or_else(|| ChainError::ConfigError("Rate limit must be > 0".to_string()))?;
+
+        Ok(Self {
+            client,
+            limiter: Arc::new(RateLimiter::direct(Quota::per_second(rps))),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.map(str::to_string),
+        })
+    }
+
+    /// POST a JSON body to an API path and unwrap the response envelope
+    async fn post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: serde_json::Value,
+    ) -> ChainResult<Option<T>> {
+        self.limiter.until_ready().await;
+
+        let mut request = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.header("X-API-Key", key);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            if e.is_timeout() {
+                ChainError::ConnectionFailed("Subscan request timeout".to_string())
+            } else {
+                ChainError::ApiError(format!("Subscan request failed: {}", e))
+            }
+        })?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(ChainError::RateLimited);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(ChainError::ApiError(format!(
+                "Subscan HTTP {}: {}",
+                status, body
+            )));
+        }
+
+        let envelope: SubscanResponse<T> = response.json().await.map_err(|e| {
+            ChainError::ParseError(format!("Failed to parse Subscan response: {}", e))
+        })?;
+
+        if envelope.code != 0 {
+            return Err(ChainError::ApiError(format!(
+                "Subscan error {}: {}",
+                envelope.code, envelope.message
+            )));
+        }
+
+        Ok(envelope.data)
+    }
+
+    /// Get the latest block number
+    pub async fn get_block_number(&self) -> ChainResult<u64> {
+        let metadata: SubscanMetadata = self
+            .post("/api/scan/metadata", json!({}))
+            .await?
+            .ok_or_else(|| ChainError::ParseError("Subscan metadata missing".to_string()))?;
+
+        metadata
+            .block_num
+            .parse()
+            .map_err(|e| ChainError::ParseError(format!("Invalid block number: {}", e)))
+    }
+
+    /// Get an account's native, `orml-tokens`, and `assets` balances
+    pub async fn get_account_tokens(&self, address: &str) -> ChainResult<SubscanAccountTokens> {
+        Ok(self
+            .post("/api/scan/account/tokens", json!({ "address": address }))
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Get one page of an account's transfers, newest first
+    ///
+    /// Returns the page and the total number of transfers.
+    pub async fn get_transfers_page(
+        &self,
+        address: &str,
+        page: usize,
+    ) -> ChainResult<(Vec<SubscanTransfer>, usize)> {
+        let data: Option<SubscanTransferPage> = self
+            .post(
+                "/api/v2/scan/transfers",
+                json!({ "address": address, "page": page, "row": TRANSFERS_PER_PAGE }),
+            )
+            .await?;
+
+        Ok(data
+            .map(|d| (d.transfers.unwrap_or_default(), d.count))
+            .unwrap_or_default())
+    }
+
+    /// Get an account's transfers across all pallets, newest first
+    ///
+    /// # Arguments
+    /// * `address` - SS58 account address
+    /// * `min_block` - Stop paging once transfers are older than this block
+    pub async fn get_all_transfers(
+        &self,
+        address: &str,
+        min_block: Option<u64>,
+    ) -> ChainResult<Vec<SubscanTransfer>> {
+        let mut all = Vec::new();
+
+        for page in 0..MAX_TRANSFER_PAGES {
+            let (transfers, count) = self.get_transfers_page(address, page).await?;
+            let page_len = transfers.len();
+            let reached_min = min_block
+                .is_some_and(|min| transfers.last().is_some_and(|last| last.block_num < min));
+            all.extend(transfers);
+
+            if page_len < TRANSFERS_PER_PAGE || all.len() >= count || reached_min {
+                break;
+            }
+        }
+
+        Ok(all)
+    }
+
+    /// Get the XCM transfers an account sent or received, newest first
+    ///
+    /// Subscan indexes XCM on the relay chain, so this client must point at
+    /// the relay chain's API.
+    pub async fn get_xcm_transfers(&self, address: &str) -> ChainResult<Vec<SubscanXcmTransfer>> {
+        let mut all = Vec::new();
+
+        for page in 0..MAX_TRANSFER_PAGES {
+            let data: Option<SubscanXcmPage> = self
+                .post(
+                    "/api/scan/xcm/list",
+                    json!({ "address": address, "page": page, "row": TRANSFERS_PER_PAGE }),
+                )
+                .await?;
+            let (list, count) = data
+                .map(|d| (d.list.unwrap_or_default(), d.count))
+                .unwrap_or_default();
+            let page_len = list.len();
+            all.extend(list);
+
+            if page_len < TRANSFERS_PER_PAGE || all.len() >= count {
+                break;
+            }
+        }
+
+        Ok(all)
+    }
+    /// Get the crowdloan contributions an account made, newest first
+    ///
+    /// Crowdloans run on the relay chain, so this client must point at the
+    /// relay chain's API.
+    pub async fn get_crowdloan_contributions(
+        &self,
+        address: &str,
+    ) -> ChainResult<Vec<SubscanContribution>> {
+        let mut all = Vec::new();
+
+        for page in 0..MAX_TRANSFER_PAGES {
+            let data: Option<SubscanContributionPage> = self
+                .post(
+                    "/api/scan/parachain/contributes",
+                    json!({ "who": address, "page": page, "row": TRANSFERS_PER_PAGE }),
+                )
+                .await?;
+            let (list, count) = data
+                .map(|d| (d.contributes.unwrap_or_default(), d.count))
+                .unwrap_or_default();
+            let page_len = list.len();
+            all.extend(list);
+
+            if page_len < TRANSFERS_PER_PAGE || all.len() >= count {
+                break;
+            }
+        }
+
+        Ok(all)
+    }
+
+    /// Get a crowdloan fund by its `para_id-index` ID
+    pub async fn get_crowdloan_fund(&self, fund_id: &str) -> ChainResult<Option<SubscanFund>> {
+        let data: Option<SubscanFundPage> = self
+            .post(
+                "/api/scan/parachain/funds",
+                json!({ "fund_id": fund_id, "page": 0, "row": 1 }),
+            )
+            .await?;
+        Ok(data
+            .and_then(|d| d.funds)
+            .and_then(|funds| funds.into_iter().find(|f| f.fund_id == fund_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::mock_http::{fixture, mount_post, repeat_record, requests_to, MockServer};
+
+    const ADDRESS: &str = "22bByhJxYURLGHguQJmygeCcuZaTtL9FPAyhTkmrsEBG94QJ";
+
+    #[tokio::test]
+    async fn test_account_tokens_by_pallet() {
+        let server = MockServer::start().await;
+        mount_post(
+            &server,
+            "/api/scan/account/tokens",
+            json!({}),
+            fixture("subscan/account_tokens.json"),
+        )
+        .await;
+
+        let client = SubscanClient::new(&server.uri(), None).unwrap();
+        let tokens = client.get_account_tokens(ADDRESS).await.unwrap();
+
+        assert_eq!(tokens.native[0].symbol, "ACA");
+        let builtin: Vec<&str> = tokens.builtin.iter().map(|t| t.symbol.as_str()).collect();
+        assert_eq!(builtin, vec!["AUSD", "LDOT", "DOT"]);
+        assert_eq!(tokens.assets[0].unique_id, "ForeignAsset/12");
+    }
+
+    #[tokio::test]
+    async fn test_transfers_paginate() {
+        let server = MockServer::start().await;
+        let recorded = fixture("subscan/transfers.json");
+        let record = &recorded["data"]["transfers"][0];
+        let full_page = repeat_record(record, TRANSFERS_PER_PAGE, "hash");
+        mount_post(
+            &server,
+            "/api/v2/scan/transfers",
+            json!({ "page": 0 }),
+            json!({ "code": 0, "data": { "count": TRANSFERS_PER_PAGE + 1, "transfers": full_page } }),
+        )
+        .await;
+        mount_post(
+            &server,
+            "/api/v2/scan/transfers",
+            json!({ "page": 1 }),
+            json!({ "code": 0, "data": { "count": TRANSFERS_PER_PAGE + 1, "transfers": [record] } }),
+        )
+        .await;
+
+        let client = SubscanClient::new(&server.uri(), None).unwrap();
+        let transfers = client.get_all_transfers(ADDRESS, None).await.unwrap();
+
+        assert_eq!(transfers.len(), TRANSFERS_PER_PAGE + 1);
+        assert_eq!(requests_to(&server, "/api/v2/scan/transfers").await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_xcm_transfers() {
+        let server = MockServer::start().await;
+        mount_post(
+            &server,
+            "/api/scan/xcm/list",
+            json!({}),
+            fixture("subscan/xcm_list.json"),
+        )
+        .await;
+
+        let client = SubscanClient::new(&server.uri(), None).unwrap();
+        let transfers = client.get_xcm_transfers(ADDRESS).await.unwrap();
+
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].origin_para_id, 0);
+        assert_eq!(transfers[0].dest_para_id, 2000);
+        assert_eq!(transfers[0].assets[0].symbol, "DOT");
+        assert_eq!(transfers[0].assets[0].amount, "25");
+    }
+
+    #[tokio::test]
+    async fn test_crowdloan_contributions_and_fund() {
+        let server = MockServer::start().await;
+        mount_post(
+            &server,
+            "/api/scan/parachain/contributes",
+            json!({}),
+            fixture("subscan/contributes.json"),
+        )
+        .await;
+        mount_post(
+            &server,
+            "/api/scan/parachain/funds",
+            json!({ "fund_id": "2000-6" }),
+            fixture("subscan/funds.json"),
+        )
+        .await;
+
+        let client = SubscanClient::new(&server.uri(), None).unwrap();
+        let contributions = client.get_crowdloan_contributions(ADDRESS).await.unwrap();
+        assert_eq!(contributions.len(), 1);
+        assert_eq!(contributions[0].para_id, 2000);
+        assert_eq!(contributions[0].contributed, "1000000000000");
+
+        let fund = client.get_crowdloan_fund("2000-6").await.unwrap().unwrap();
+        assert_eq!((fund.first_period, fund.last_period), (6, 13));
+    }
+
+    #[tokio::test]
+    async fn test_error_code_is_an_error() {
+        let server = MockServer::start().await;
+        mount_post(
+            &server,
+            "/api/scan/account/tokens",
+            json!({}),
+            json!({ "code": 10004, "message": "Record Not Found", "data": null }),
+        )
+        .await;
+
+        let client = SubscanClient::new(&server.uri(), None).unwrap();
+        let err = client.get_account_tokens(ADDRESS).await.unwrap_err();
+        assert!(err.to_string().contains("Record Not Found"));
+    }
+}
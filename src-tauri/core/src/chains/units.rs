@@ -0,0 +1,275 @@
+//! Raw on-chain amounts.
+//!
+//! Balances, transfer values, and fees are integers in a chain's smallest
+//! unit. EVM amounts are 256-bit, so they are held as [`U256`] rather than
+//! `u128`, which truncates or overflows for tokens with very large supplies.
+//! Malformed amounts are reported as errors instead of being read as zero.
+
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+
+use super::{ChainError, ChainResult};
+
+pub use alloy_primitives::U256;
+
+/// Parses a `0x`-prefixed hex quantity. `"0x"` alone is zero, as returned
+/// by `eth_call` against an address with no code.
+pub fn parse_hex(hex: &str) -> ChainResult<U256> {
+    let digits = hex.trim().trim_start_matches("0x");
+    if digits.is_empty() {
+        return Ok(U256::ZERO);
+    }
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ChainError::ParseError(format!(
+            "Invalid hex amount: {}",
+            hex
+        )));
+    }
+    U256::from_str_radix(digits, 16)
+        .map_err(|e| ChainError::ParseError(format!("Invalid hex amount {}: {}", hex, e)))
+}
+
+/// Parses a base-10 amount as returned by block explorers.
+pub fn parse_decimal(value: &str) -> ChainResult<U256> {
+    let digits = value.trim();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(ChainError::ParseError(format!("Invalid amount: {}", value)));
+    }
+    U256::from_str_radix(digits, 10)
+        .map_err(|e| ChainError::ParseError(format!("Invalid amount {}: {}", value, e)))
+}
+
+/// Formats a raw amount as whole units, trimming trailing zeros.
+pub fn format_units(value: U256, decimals: u8) -> String {
+    let digits = value.to_string();
+    if decimals == 0 {
+        return digits;
+    }
+
+    let decimals = decimals as usize;
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, frac) = padded.split_at(padded.len() - decimals);
+    let frac = frac.trim_end_matches('0');
+    if frac.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, frac)
+    }
+}
+
+/// Converts a whole-unit amount to smallest units, dropping any precision
+/// finer than `decimals`. Negative amounts and overflow give `None`.
+pub fn to_smallest_units(amount: Decimal, decimals: u8) -> Option<U256> {
+    if amount.is_sign_negative() && !amount.is_zero() {
+        return None;
+    }
+    let mantissa = U256::from(amount.mantissa().unsigned_abs());
+    let scale = amount.scale();
+    let decimals = decimals as u32;
+    if decimals >= scale {
+        mantissa.checked_mul(pow10(decimals - scale)?)
+    } else {
+        Some(mantissa / pow10(scale - decimals)?)
+    }
+}
+
+/// Converts smallest units to whole units. Amounts beyond `Decimal`'s range
+/// give `None`.
+pub fn from_smallest_units(raw: U256, decimals: u8) -> Option<Decimal> {
+    Decimal::from_str(&format_units(raw, decimals)).ok()
+}
+
+/// `gas_used * gas_price`, saturating rather than wrapping.
+pub fn gas_fee(gas_used: U256, gas_price: U256) -> U256 {
+    gas_used.saturating_mul(gas_price)
+}
+
+fn pow10(exp: u32) -> Option<U256> {
+    U256::from(10u64).checked_pow(U256::from(exp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const MAX_DECIMAL: &str =
+        "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(parse_hex("0x0").unwrap(), U256::ZERO);
+        assert_eq!(parse_hex("0x").unwrap(), U256::ZERO);
+        assert_eq!(parse_hex("0xff").unwrap(), U256::from(255u64));
+        assert_eq!(
+            parse_hex("0xde0b6b3a7640000").unwrap(),
+            U256::from(1_000_000_000_000_000_000u128)
+        );
+        assert_eq!(
+            parse_hex("0xDE0B6B3A7640000").unwrap(),
+            parse_hex("de0b6b3a7640000").unwrap()
+        );
+        assert_eq!(
+            parse_hex(&format!("0x{}", "f".repeat(64))).unwrap(),
+            U256::MAX
+        );
+    }
+
+    #[test]
+    fn test_parse_hex_beyond_u128() {
+        // 2^128, one past u128::MAX
+        let value = parse_hex("0x100000000000000000000000000000000").unwrap();
+        assert_eq!(value.to_string(), "340282366920938463463374607431768211456");
+        assert_eq!(value, U256::from(u128::MAX) + U256::from(1u64));
+    }
+
+    #[test]
+    fn test_parse_hex_rejects_malformed() {
+        assert!(parse_hex("0xzz").is_err());
+        assert!(parse_hex("0x-1").is_err());
+        assert!(parse_hex("0x1.5").is_err());
+        // 257 bits
+        assert!(parse_hex(&format!("0x1{}", "0".repeat(64))).is_err());
+    }
+
+    #[test]
+    fn test_parse_decimal() {
+        assert_eq!(parse_decimal("0").unwrap(), U256::ZERO);
+        assert_eq!(parse_decimal(" 21000 ").unwrap(), U256::from(21_000u64));
+        assert_eq!(parse_decimal(MAX_DECIMAL).unwrap(), U256::MAX);
+        assert_eq!(parse_decimal(&U256::MAX.to_string()).unwrap(), U256::MAX);
+    }
+
+    #[test]
+    fn test_parse_decimal_rejects_malformed() {
+        assert!(parse_decimal("").is_err());
+        assert!(parse_decimal("-1").is_err());
+        assert!(parse_decimal("1.5").is_err());
+        assert!(parse_decimal("1e18").is_err());
+        assert!(parse_decimal("0x10").is_err());
+        // U256::MAX + 1
+        assert!(parse_decimal(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_format_units() {
+        let wei = |v: u128| U256::from(v);
+        assert_eq!(format_units(wei(1_000_000_000_000_000_000), 18), "1");
+        assert_eq!(format_units(wei(1_500_000_000_000_000_000), 18), "1.5");
+        assert_eq!(
+            format_units(wei(1_234_567_890_000_000_000), 18),
+            "1.23456789"
+        );
+        assert_eq!(format_units(wei(100_000), 6), "0.1");
+        assert_eq!(format_units(wei(1), 18), "0.000000000000000001");
+        assert_eq!(format_units(U256::ZERO, 18), "0");
+        assert_eq!(format_units(wei(12_345), 0), "12345");
+    }
+
+    #[test]
+    fn test_format_units_extremes() {
+        assert_eq!(
+            format_units(U256::MAX, 18),
+            "115792089237316195423570985008687907853269984665640564039457.584007913129639935"
+        );
+        assert_eq!(format_units(U256::MAX, 0), MAX_DECIMAL);
+        // More decimals than digits in U256::MAX
+        assert_eq!(format_units(U256::MAX, 80), format!("0.00{}", MAX_DECIMAL));
+        assert_eq!(format_units(U256::from(5u64), 255).len(), 257);
+    }
+
+    #[test]
+    fn test_to_smallest_units() {
+        let amount = Decimal::from_str("1.5").unwrap();
+        assert_eq!(
+            to_smallest_units(amount, 18),
+            Some(U256::from(1_500_000_000_000_000_000u128))
+        );
+        assert_eq!(to_smallest_units(amount, 6), Some(U256::from(1_500_000u64)));
+        assert_eq!(to_smallest_units(amount, 0), Some(U256::from(1u64)));
+        assert_eq!(
+            to_smallest_units(Decimal::from_str("0.1234567").unwrap(), 6),
+            Some(U256::from(123_456u64))
+        );
+        assert_eq!(to_smallest_units(Decimal::ZERO, 18), Some(U256::ZERO));
+        assert_eq!(to_smallest_units(Decimal::from(-1), 18), None);
+    }
+
+    #[test]
+    fn test_to_smallest_units_beyond_u128() {
+        // Decimal::MAX at 18 decimals is well past u128::MAX
+        let raw = to_smallest_units(Decimal::MAX, 18).unwrap();
+        assert_eq!(
+            raw.to_string(),
+            format!("{}{}", Decimal::MAX.mantissa(), "0".repeat(18))
+        );
+        assert!(raw > U256::from(u128::MAX));
+        // 10^255 does not fit in 256 bits
+        assert_eq!(to_smallest_units(Decimal::MAX, 255), None);
+    }
+
+    #[test]
+    fn test_from_smallest_units() {
+        assert_eq!(
+            from_smallest_units(U256::from(1_500_000u64), 6),
+            Some(Decimal::from_str("1.5").unwrap())
+        );
+        assert_eq!(from_smallest_units(U256::ZERO, 18), Some(Decimal::ZERO));
+        assert_eq!(from_smallest_units(U256::MAX, 0), None);
+    }
+
+    #[test]
+    fn test_gas_fee() {
+        assert_eq!(
+            gas_fee(U256::from(21_000u64), U256::from(30_000_000_000u64)),
+            U256::from(630_000_000_000_000u64)
+        );
+        // Would overflow u128
+        let price = U256::from(u128::MAX);
+        assert_eq!(
+            gas_fee(U256::from(2u64), price),
+            U256::from(u128::MAX) * U256::from(2u64)
+        );
+        assert_eq!(gas_fee(U256::MAX, U256::from(2u64)), U256::MAX);
+    }
+
+    fn any_u256() -> impl Strategy<Value = U256> {
+        any::<[u8; 32]>().prop_map(U256::from_be_bytes)
+    }
+
+    proptest! {
+        #[test]
+        fn prop_parse_round_trips(value in any_u256()) {
+            prop_assert_eq!(parse_decimal(&value.to_string()).unwrap(), value);
+            prop_assert_eq!(parse_hex(&format!("0x{:x}", value)).unwrap(), value);
+        }
+
+        #[test]
+        fn prop_format_units_round_trips(value in any_u256(), decimals in 0u8..=80) {
+            let formatted = format_units(value, decimals);
+            let (whole, frac) = formatted.split_once('.').unwrap_or((&formatted, ""));
+            prop_assert!(frac.len() <= decimals as usize);
+            prop_assert!(!frac.ends_with('0'));
+
+            let raw = format!("{}{:0<width$}", whole, frac, width = decimals as usize);
+            prop_assert_eq!(parse_decimal(&raw).unwrap(), value);
+        }
+
+        #[test]
+        fn prop_smallest_units_round_trip(raw in 0u128..(1u128 << 96), decimals in 0u8..=28) {
+            let raw = U256::from(raw);
+            let amount = from_smallest_units(raw, decimals).unwrap();
+            prop_assert_eq!(to_smallest_units(amount, decimals), Some(raw));
+        }
+
+        #[test]
+        fn prop_gas_fee_saturates(gas_used in any_u256(), gas_price in any_u256()) {
+            let fee = gas_fee(gas_used, gas_price);
+            prop_assert_eq!(fee, gas_used.checked_mul(gas_price).unwrap_or(U256::MAX));
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Hashing primitives for report attestations.
+//!
+//! Records are hashed over a canonical JSON encoding (object keys sorted, no
+//! whitespace) so the same content always produces the same hash. Leaves and
+//! interior nodes of the Merkle tree are domain-separated with a one-byte
+//! prefix, and an odd node at the end of a level is promoted unchanged rather
+//! than paired with itself.
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// A SHA-256 digest.
+pub type Hash = [u8; 32];
+
+/// Prefix for leaf hashes.
+const LEAF_PREFIX: u8 = 0x00;
+
+/// Prefix for interior node hashes.
+const NODE_PREFIX: u8 = 0x01;
+
+/// Serializes `value` with object keys sorted and no insignificant
+/// whitespace.
+pub fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|key| {
+                    format!(
+                        "{}:{}",
+                        Value::String(key.clone()),
+                        canonical_json(&map[key])
+                    )
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// SHA-256 of `data`.
+pub fn sha256(data: &[u8]) -> Hash {
+    Sha256::digest(data).into()
+}
+
+/// Hash of a record's canonical JSON.
+pub fn hash_value(value: &Value) -> Hash {
+    sha256(canonical_json(value).as_bytes())
+}
+
+/// Merkle leaf hash of a record.
+pub fn leaf_hash(value: &Value) -> Hash {
+    let mut data = vec![LEAF_PREFIX];
+    data.extend_from_slice(canonical_json(value).as_bytes());
+    sha256(&data)
+}
+
+/// Hash of two child nodes.
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut data = Vec::with_capacity(65);
+    data.push(NODE_PREFIX);
+    data.extend_from_slice(left);
+    data.extend_from_slice(right);
+    sha256(&data)
+}
+
+/// Merkle root over `leaves` in order. An empty tree hashes to SHA-256 of
+/// nothing.
+pub fn merkle_root(leaves: &[Hash]) -> Hash {
+    if leaves.is_empty() {
+        return sha256(&[]);
+    }
+
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => node_hash(left, right),
+                [single] => *single,
+                _ => unreachable!("chunks(2) yields one or two items"),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// Next link of a hash chain: SHA-256 of the previous link and a leaf.
+pub fn chain_link(previous: &Hash, leaf: &Hash) -> Hash {
+    let mut data = Vec::with_capacity(64);
+    data.extend_from_slice(previous);
+    data.extend_from_slice(leaf);
+    sha256(&data)
+}
+
+/// Parses a 64-character hex digest.
+pub fn parse_hash(hex_hash: &str) -> Option<Hash> {
+    hex::decode(hex_hash.trim()).ok()?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_canonical_json_sorts_keys() {
+        let a = json!({ "b": 1, "a": { "d": [1, "x"], "c": null } });
+        let b = json!({ "a": { "c": null, "d": [1, "x"] }, "b": 1 });
+        assert_eq!(canonical_json(&a), r#"{"a":{"c":null,"d":[1,"x"]},"b":1}"#);
+        assert_eq!(hash_value(&a), hash_value(&b));
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        let leaves: Vec<Hash> = (0..3).map(|i| leaf_hash(&json!(i))).collect();
+
+        assert_eq!(merkle_root(&leaves[..1]), leaves[0]);
+        let expected = node_hash(&node_hash(&leaves[0], &leaves[1]), &leaves[2]);
+        assert_eq!(merkle_root(&leaves), expected);
+
+        let mut reordered = leaves.clone();
+        reordered.swap(0, 1);
+        assert_ne!(merkle_root(&reordered), expected);
+        assert_eq!(merkle_root(&[]), sha256(&[]));
+    }
+
+    #[test]
+    fn test_parse_hash() {
+        let hash = sha256(b"pacioli");
+        assert_eq!(parse_hash(&hex::encode(hash)), Some(hash));
+        assert_eq!(parse_hash("abcd"), None);
+    }
+}
@@ -4,13 +4,16 @@
 
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
-    Argon2,
+    Algorithm, Argon2, Params, Version,
 };
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{decode, decode_header, encode, DecodingKey, EncodingKey, Header, Validation};
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+use super::jwt_keys::JwtKeyring;
 
 /// JWT claims structure
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -46,21 +49,134 @@ pub struct AuthTokens {
 // Password Hashing
 // ============================================================================
 
+/// Settings key holding the serialized [`Argon2Params`].
+pub const ARGON2_PARAMS_SETTING: &str = "auth.argon2_params";
+
+/// Argon2id work factors used for new password hashes.
+///
+/// Hashes are stored as PHC strings, which record the algorithm, version,
+/// and parameters they were created with; that encoding is the hash version.
+/// A hash whose recorded parameters differ from the configured ones is
+/// rehashed at the next successful login (see [`needs_rehash`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Argon2Params {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes
+    pub iterations: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    /// OWASP-recommended minimum: 19 MiB, 2 passes, 1 lane
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl Argon2Params {
+    /// Smallest memory cost accepted from settings (OWASP minimum)
+    pub const MIN_MEMORY_KIB: u32 = 19 * 1024;
+    /// Largest memory cost accepted from settings, to keep logins responsive
+    pub const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+
+    /// Check that the parameters are within accepted bounds
+    pub fn validate(&self) -> Result<(), String> {
+        if !(Self::MIN_MEMORY_KIB..=Self::MAX_MEMORY_KIB).contains(&self.memory_kib) {
+            return Err(format!(
+                "Memory cost must be between {} and {} KiB",
+                Self::MIN_MEMORY_KIB,
+                Self::MAX_MEMORY_KIB
+            ));
+        }
+        if !(1..=10).contains(&self.iterations) {
+            return Err("Iterations must be between 1 and 10".to_string());
+        }
+        if !(1..=16).contains(&self.parallelism) {
+            return Err("Parallelism must be between 1 and 16".to_string());
+        }
+        Ok(())
+    }
+
+    /// Short label such as `m=19456,t=2,p=1`
+    pub fn label(&self) -> String {
+        format!(
+            "m={},t={},p={}",
+            self.memory_kib, self.iterations, self.parallelism
+        )
+    }
+
+    fn hasher(&self) -> Result<Argon2<'static>, String> {
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, None)
+            .map_err(|e| format!("Invalid Argon2 parameters: {}", e))?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+}
+
+/// Parameters used for new hashes, loaded from settings at startup
+static ARGON2_PARAMS: RwLock<Option<Argon2Params>> = RwLock::new(None);
+
+/// Set the parameters used for new password hashes
+pub fn set_argon2_params(params: Argon2Params) {
+    *ARGON2_PARAMS.write().unwrap_or_else(|e| e.into_inner()) = Some(params);
+}
+
+/// Parameters currently used for new password hashes
+pub fn argon2_params() -> Argon2Params {
+    ARGON2_PARAMS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .unwrap_or_default()
+}
+
 /// Hash a password using Argon2id
 ///
 /// Uses secure defaults:
 /// - Argon2id variant (hybrid of Argon2i and Argon2d)
 /// - Random salt generated via OS RNG
+/// - Work factors from [`argon2_params`]
 pub fn hash_password(password: &str) -> Result<String, String> {
+    hash_password_with(password, &argon2_params())
+}
+
+/// Hash a password using Argon2id with explicit parameters
+pub fn hash_password_with(password: &str, params: &Argon2Params) -> Result<String, String> {
     let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
 
-    argon2
+    params
+        .hasher()?
         .hash_password(password.as_bytes(), &salt)
         .map(|hash| hash.to_string())
         .map_err(|e| format!("Failed to hash password: {}", e))
 }
 
+/// Parameters recorded in a stored hash, or `None` if it is not an
+/// Argon2id v1.3 hash
+pub fn hash_params(hash: &str) -> Option<Argon2Params> {
+    let parsed = PasswordHash::new(hash).ok()?;
+    if parsed.algorithm.as_str() != "argon2id" || parsed.version != Some(Version::V0x13.into()) {
+        return None;
+    }
+    let params = Params::try_from(&parsed).ok()?;
+    Some(Argon2Params {
+        memory_kib: params.m_cost(),
+        iterations: params.t_cost(),
+        parallelism: params.p_cost(),
+    })
+}
+
+/// Whether a stored hash was created with different parameters (or a
+/// different algorithm) than `current` and should be replaced
+pub fn needs_rehash(hash: &str, current: &Argon2Params) -> bool {
+    hash_params(hash).as_ref() != Some(current)
+}
+
 /// Verify a password against its hash
 pub fn verify_password(password: &str, hash: &str) -> Result<bool, String> {
     let parsed_hash =
@@ -81,7 +197,7 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, String> {
 pub fn generate_access_token(
     user_id: &str,
     email: &str,
-    secret: &[u8],
+    keys: &JwtKeyring,
     expiry_minutes: Option<i64>,
 ) -> Result<String, String> {
     let expiry = expiry_minutes.unwrap_or(15);
@@ -97,12 +213,7 @@ pub fn generate_access_token(
         session_id: None,
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret),
-    )
-    .map_err(|e| format!("Failed to generate access token: {}", e))
+    sign(&claims, keys).map_err(|e| format!("Failed to generate access token: {}", e))
 }
 
 /// Generate a refresh token (long-lived)
@@ -112,7 +223,7 @@ pub fn generate_refresh_token(
     user_id: &str,
     email: &str,
     session_id: &str,
-    secret: &[u8],
+    keys: &JwtKeyring,
     expiry_days: Option<i64>,
 ) -> Result<String, String> {
     let expiry = expiry_days.unwrap_or(7);
@@ -128,26 +239,41 @@ pub fn generate_refresh_token(
         session_id: Some(session_id.to_string()),
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret),
-    )
-    .map_err(|e| format!("Failed to generate refresh token: {}", e))
+    sign(&claims, keys).map_err(|e| format!("Failed to generate refresh token: {}", e))
+}
+
+/// Sign claims with the keyring's current key, naming it in the header
+fn sign(claims: &TokenClaims, keys: &JwtKeyring) -> jsonwebtoken::errors::Result<String> {
+    let key = keys.current();
+    let header = Header {
+        kid: Some(key.kid.clone()),
+        ..Header::default()
+    };
+    encode(&header, claims, &EncodingKey::from_secret(key.secret()))
 }
 
 /// Verify and decode a JWT token
-pub fn verify_token(token: &str, secret: &[u8]) -> Result<TokenClaims, String> {
+///
+/// The token must name a key in its header that is current or retired within
+/// the keyring's grace period.
+pub fn verify_token(token: &str, keys: &JwtKeyring) -> Result<TokenClaims, String> {
+    let header = decode_header(token).map_err(|e| format!("Invalid token: {}", e))?;
+    let kid = header
+        .kid
+        .ok_or_else(|| "Invalid token: missing key ID".to_string())?;
+    let key = keys
+        .verifying_key(&kid)
+        .ok_or_else(|| "Invalid token: unknown or expired signing key".to_string())?;
     let validation = Validation::default();
 
-    decode::<TokenClaims>(token, &DecodingKey::from_secret(secret), &validation)
+    decode::<TokenClaims>(token, &DecodingKey::from_secret(key.secret()), &validation)
         .map(|data| data.claims)
         .map_err(|e| format!("Invalid token: {}", e))
 }
 
 /// Verify an access token specifically (checks token_type)
-pub fn verify_access_token(token: &str, secret: &[u8]) -> Result<TokenClaims, String> {
-    let claims = verify_token(token, secret)?;
+pub fn verify_access_token(token: &str, keys: &JwtKeyring) -> Result<TokenClaims, String> {
+    let claims = verify_token(token, keys)?;
 
     if claims.token_type != "access" {
         return Err("Invalid token type: expected access token".to_string());
@@ -157,8 +283,8 @@ pub fn verify_access_token(token: &str, secret: &[u8]) -> Result<TokenClaims, St
 }
 
 /// Verify a refresh token specifically (checks token_type)
-pub fn verify_refresh_token(token: &str, secret: &[u8]) -> Result<TokenClaims, String> {
-    let claims = verify_token(token, secret)?;
+pub fn verify_refresh_token(token: &str, keys: &JwtKeyring) -> Result<TokenClaims, String> {
+    let claims = verify_token(token, keys)?;
 
     if claims.token_type != "refresh" {
         return Err("Invalid token type: expected refresh token".to_string());
@@ -191,7 +317,6 @@ pub fn generate_session_id() -> String {
 }
 
 /// Generate a password reset token (32 bytes)
-#[allow(dead_code)]
 pub fn generate_password_reset_token() -> String {
     generate_secure_token(32)
 }
@@ -281,14 +406,58 @@ mod tests {
         assert!(!verify_password("wrong_password", &hash).unwrap());
     }
 
+    fn fast_params() -> Argon2Params {
+        Argon2Params {
+            memory_kib: 1024,
+            iterations: 1,
+            parallelism: 1,
+        }
+    }
+
+    #[test]
+    fn test_hash_params_round_trip() {
+        let params = fast_params();
+        let hash = hash_password_with("SecureP@ssw0rd!", &params).unwrap();
+
+        assert!(hash.starts_with("$argon2id$v=19$m=1024,t=1,p=1$"));
+        assert_eq!(hash_params(&hash), Some(params));
+        assert!(verify_password("SecureP@ssw0rd!", &hash).unwrap());
+    }
+
+    #[test]
+    fn test_needs_rehash() {
+        let old = fast_params();
+        let hash = hash_password_with("SecureP@ssw0rd!", &old).unwrap();
+
+        assert!(!needs_rehash(&hash, &old));
+        let stronger = Argon2Params {
+            iterations: 2,
+            ..old
+        };
+        assert!(needs_rehash(&hash, &stronger));
+        assert!(needs_rehash("not-a-hash", &old));
+    }
+
+    #[test]
+    fn test_argon2_params_validation() {
+        assert!(Argon2Params::default().validate().is_ok());
+        assert!(fast_params().validate().is_err());
+        let too_many_passes = Argon2Params {
+            iterations: 50,
+            ..Argon2Params::default()
+        };
+        assert!(too_many_passes.validate().is_err());
+        assert_eq!(Argon2Params::default().label(), "m=19456,t=2,p=1");
+    }
+
     #[test]
     fn test_jwt_generation_and_verification() {
-        let secret = b"test_secret_key_32_bytes_long!!!";
+        let keys = JwtKeyring::from_secret("test", b"test_secret_key_32_bytes_long!!!");
         let user_id = "user_123";
         let email = "test@example.com";
 
-        let token = generate_access_token(user_id, email, secret, Some(15)).unwrap();
-        let claims = verify_access_token(&token, secret).unwrap();
+        let token = generate_access_token(user_id, email, &keys, Some(15)).unwrap();
+        let claims = verify_access_token(&token, &keys).unwrap();
 
         assert_eq!(claims.sub, user_id);
         assert_eq!(claims.email, email);
@@ -297,18 +466,36 @@ mod tests {
 
     #[test]
     fn test_refresh_token() {
-        let secret = b"test_secret_key_32_bytes_long!!!";
+        let keys = JwtKeyring::from_secret("test", b"test_secret_key_32_bytes_long!!!");
         let user_id = "user_123";
         let email = "test@example.com";
         let session_id = "session_456";
 
-        let token = generate_refresh_token(user_id, email, session_id, secret, Some(7)).unwrap();
-        let claims = verify_refresh_token(&token, secret).unwrap();
+        let token = generate_refresh_token(user_id, email, session_id, &keys, Some(7)).unwrap();
+        let claims = verify_refresh_token(&token, &keys).unwrap();
 
         assert_eq!(claims.sub, user_id);
         assert_eq!(claims.session_id, Some(session_id.to_string()));
     }
 
+    #[test]
+    fn test_tokens_verify_across_rotation() {
+        let mut keys = JwtKeyring::generate();
+        let token = generate_access_token("user_123", "test@example.com", &keys, None).unwrap();
+
+        keys.rotate(Utc::now());
+        assert!(verify_access_token(&token, &keys).is_ok());
+
+        // Once the signing key is past its grace period the token is rejected
+        keys.rotate(Utc::now() + crate::core::jwt_keys::GRACE_PERIOD + Duration::days(1));
+        assert!(verify_access_token(&token, &keys).is_err());
+
+        // A token from an unrelated keyring is rejected
+        let other = JwtKeyring::generate();
+        let forged = generate_access_token("user_123", "test@example.com", &other, None).unwrap();
+        assert!(verify_access_token(&forged, &keys).is_err());
+    }
+
     #[test]
     fn test_secure_token_generation() {
         let token1 = generate_secure_token(32);
@@ -1,11 +1,12 @@
 //! Authentication State Management
 //!
-//! Provides global authentication state including JWT secret and session caching.
+//! Provides global authentication state including the JWT keyring and session caching.
 
-use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
-use rand::RngCore;
+use super::jwt_keys::JwtKeyring;
+use super::rate_limit::AuthRateLimiter;
+use chrono::Utc;
 use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
 /// Cached session information for fast validation
@@ -25,33 +26,39 @@ pub struct CachedSession {
 /// This struct is registered with Tauri's state management system
 /// and provides thread-safe access to authentication-related state.
 pub struct AuthState {
-    /// JWT secret key (generated on startup)
-    jwt_secret: Vec<u8>,
+    /// JWT signing keys (generated on startup, replaced by the stored
+    /// keyring once the database is open)
+    jwt_keys: RwLock<Arc<JwtKeyring>>,
 
     /// In-memory session cache for fast validation
     /// Key: session_id, Value: cached session info
     session_cache: RwLock<HashMap<String, CachedSession>>,
 
+    /// User who most recently signed in on this device, recorded as the
+    /// actor on data changes
+    active_user: RwLock<Option<String>>,
+
+    /// Failed attempt counts and lockouts for auth commands
+    rate_limiter: AuthRateLimiter,
+
     /// Cache TTL (how long to cache session info)
     #[allow(dead_code)]
     cache_ttl: Duration,
 }
 
 impl AuthState {
-    /// Create a new AuthState with a randomly generated JWT secret
+    /// Create a new AuthState with a randomly generated JWT keyring
     pub fn new() -> Self {
         Self::with_cache_ttl(Duration::from_secs(300)) // 5 minute cache TTL
     }
 
     /// Create a new AuthState with a custom cache TTL
     pub fn with_cache_ttl(cache_ttl: Duration) -> Self {
-        // Generate a 32-byte (256-bit) random secret
-        let mut secret = vec![0u8; 32];
-        rand::thread_rng().fill_bytes(&mut secret);
-
         Self {
-            jwt_secret: secret,
+            jwt_keys: RwLock::new(Arc::new(JwtKeyring::generate())),
             session_cache: RwLock::new(HashMap::new()),
+            active_user: RwLock::new(None),
+            rate_limiter: AuthRateLimiter::new(),
             cache_ttl,
         }
     }
@@ -60,21 +67,39 @@ impl AuthState {
     #[cfg(test)]
     pub fn with_secret(secret: Vec<u8>) -> Self {
         Self {
-            jwt_secret: secret,
+            jwt_keys: RwLock::new(Arc::new(JwtKeyring::from_secret("test", &secret))),
             session_cache: RwLock::new(HashMap::new()),
+            active_user: RwLock::new(None),
+            rate_limiter: AuthRateLimiter::new(),
             cache_ttl: Duration::from_secs(300),
         }
     }
 
-    /// Get the JWT secret for token signing/verification
-    pub fn get_jwt_secret(&self) -> &[u8] {
-        &self.jwt_secret
+    /// Get the JWT keyring for token signing/verification
+    pub fn jwt_keys(&self) -> Arc<JwtKeyring> {
+        self.jwt_keys
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
     }
 
-    /// Get the JWT secret as a base64-encoded string (for display/debugging)
-    #[allow(dead_code)]
-    pub fn get_jwt_secret_base64(&self) -> String {
-        URL_SAFE_NO_PAD.encode(&self.jwt_secret)
+    /// Replace the JWT keyring (e.g. with the one loaded from storage)
+    pub fn set_jwt_keys(&self, keys: JwtKeyring) {
+        *self.jwt_keys.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(keys);
+    }
+
+    /// Rotate to a new JWT signing key, returning the updated keyring
+    pub fn rotate_jwt_keys(&self) -> Arc<JwtKeyring> {
+        let mut guard = self.jwt_keys.write().unwrap_or_else(|e| e.into_inner());
+        let mut keys = JwtKeyring::clone(&guard);
+        keys.rotate(Utc::now());
+        *guard = Arc::new(keys);
+        guard.clone()
+    }
+
+    /// Get the limiter that throttles failed auth attempts
+    pub fn rate_limiter(&self) -> &AuthRateLimiter {
+        &self.rate_limiter
     }
 
     /// Cache a session for fast lookup
@@ -89,6 +114,23 @@ impl AuthState {
                 },
             );
         }
+        if let Ok(mut active) = self.active_user.write() {
+            *active = Some(user_id.to_string());
+        }
+    }
+
+    /// Get the user who most recently signed in, if still signed in
+    pub fn active_user(&self) -> Option<String> {
+        self.active_user.read().ok().and_then(|user| user.clone())
+    }
+
+    /// Clear the active user if it is `user_id`
+    pub fn clear_active_user(&self, user_id: &str) {
+        if let Ok(mut active) = self.active_user.write() {
+            if active.as_deref() == Some(user_id) {
+                *active = None;
+            }
+        }
     }
 
     /// Get a cached session if it exists and hasn't expired
@@ -138,12 +180,11 @@ impl Default for AuthState {
     fn default() -> Self {
         // Inline implementation to avoid DeepSource RS-A1008 warning
         // about function calls returning Self in default()
-        let mut secret = vec![0u8; 32];
-        rand::thread_rng().fill_bytes(&mut secret);
-
         Self {
-            jwt_secret: secret,
+            jwt_keys: RwLock::new(Arc::new(JwtKeyring::generate())),
             session_cache: RwLock::new(HashMap::new()),
+            active_user: RwLock::new(None),
+            rate_limiter: AuthRateLimiter::new(),
             cache_ttl: Duration::from_secs(300), // 5 minute cache TTL
         }
     }
@@ -156,7 +197,17 @@ mod tests {
     #[test]
     fn test_auth_state_creation() {
         let state = AuthState::default();
-        assert_eq!(state.get_jwt_secret().len(), 32);
+        assert_eq!(state.jwt_keys().current().secret().len(), 32);
+    }
+
+    #[test]
+    fn test_jwt_key_rotation() {
+        let state = AuthState::default();
+        let old_kid = state.jwt_keys().current().kid.clone();
+
+        let keys = state.rotate_jwt_keys();
+        assert_ne!(keys.current().kid, old_kid);
+        assert!(state.jwt_keys().verifying_key(&old_kid).is_some());
     }
 
     #[test]
@@ -173,6 +224,10 @@ mod tests {
         let cached = cached.unwrap();
         assert_eq!(cached.user_id, "user_456");
         assert_eq!(cached.email, "test@example.com");
+        assert_eq!(state.active_user().as_deref(), Some("user_456"));
+
+        state.clear_active_user("user_456");
+        assert!(state.active_user().is_none());
     }
 
     #[test]
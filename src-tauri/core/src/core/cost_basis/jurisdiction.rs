@@ -0,0 +1,235 @@
+//! Tax jurisdiction rule sets for the cost-basis engine.
+//!
+//! Each jurisdiction decides how acquisitions are pooled, which lot selection
+//! methods are permitted, and how holding periods affect the taxable gain.
+
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::wash_sale::{LossDeferralKind, LossDeferralRule};
+
+/// Tax jurisdiction an accounting profile reports under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum Jurisdiction {
+    /// United States: specific lots with short/long-term split.
+    Us,
+    /// United Kingdom: same-day and 30-day matching, then a Section 104 pool.
+    Uk,
+    /// Canada: adjusted cost base averaged per asset, 50% inclusion rate.
+    Ca,
+    /// Germany: FIFO lots, gains exempt after a one-year holding period.
+    De,
+}
+
+impl Jurisdiction {
+    /// All supported jurisdictions.
+    pub const ALL: [Jurisdiction; 4] = [
+        Jurisdiction::Us,
+        Jurisdiction::Uk,
+        Jurisdiction::Ca,
+        Jurisdiction::De,
+    ];
+
+    /// Returns the rule set for this jurisdiction.
+    pub fn rules(&self) -> JurisdictionRules {
+        match self {
+            Jurisdiction::Us => JurisdictionRules {
+                jurisdiction: *self,
+                name: "United States".to_string(),
+                currency: "USD".to_string(),
+                pooling: PoolingMethod::Lots,
+                allowed_methods: vec![
+                    CostBasisMethod::Fifo,
+                    CostBasisMethod::Lifo,
+                    CostBasisMethod::Hifo,
+                ],
+                default_method: CostBasisMethod::Fifo,
+                long_term_months: Some(12),
+                exempt_after_months: None,
+                matching_window_days: None,
+                inclusion_rate: Decimal::ONE,
+                loss_deferral: Some(LossDeferralRule {
+                    kind: LossDeferralKind::WashSale,
+                    window_days: 30,
+                    requires_holding_at_window_end: false,
+                }),
+            },
+            Jurisdiction::Uk => JurisdictionRules {
+                jurisdiction: *self,
+                name: "United Kingdom".to_string(),
+                currency: "GBP".to_string(),
+                pooling: PoolingMethod::SharePool,
+                allowed_methods: Vec::new(),
+                default_method: CostBasisMethod::Fifo,
+                long_term_months: None,
+                exempt_after_months: None,
+                matching_window_days: Some(30),
+                inclusion_rate: Decimal::ONE,
+                loss_deferral: None,
+            },
+            Jurisdiction::Ca => JurisdictionRules {
+                jurisdiction: *self,
+                name: "Canada".to_string(),
+                currency: "CAD".to_string(),
+                pooling: PoolingMethod::AverageCost,
+                allowed_methods: Vec::new(),
+                default_method: CostBasisMethod::Fifo,
+                long_term_months: None,
+                exempt_after_months: None,
+                matching_window_days: None,
+                inclusion_rate: Decimal::new(5, 1),
+                loss_deferral: Some(LossDeferralRule {
+                    kind: LossDeferralKind::SuperficialLoss,
+                    window_days: 30,
+                    requires_holding_at_window_end: true,
+                }),
+            },
+            Jurisdiction::De => JurisdictionRules {
+                jurisdiction: *self,
+                name: "Germany".to_string(),
+                currency: "EUR".to_string(),
+                pooling: PoolingMethod::Lots,
+                allowed_methods: vec![CostBasisMethod::Fifo],
+                default_method: CostBasisMethod::Fifo,
+                long_term_months: None,
+                exempt_after_months: Some(12),
+                matching_window_days: None,
+                inclusion_rate: Decimal::ONE,
+                loss_deferral: None,
+            },
+        }
+    }
+}
+
+impl FromStr for Jurisdiction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "us" => Ok(Jurisdiction::Us),
+            "uk" | "gb" => Ok(Jurisdiction::Uk),
+            "ca" => Ok(Jurisdiction::Ca),
+            "de" => Ok(Jurisdiction::De),
+            other => Err(format!("Unsupported jurisdiction: {}", other)),
+        }
+    }
+}
+
+/// Order in which lots are consumed by a disposal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "UPPERCASE")]
+#[sqlx(rename_all = "UPPERCASE")]
+pub enum CostBasisMethod {
+    /// Oldest lots first.
+    Fifo,
+    /// Newest lots first.
+    Lifo,
+    /// Highest unit cost first.
+    Hifo,
+}
+
+impl CostBasisMethod {
+    /// Converts to database string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CostBasisMethod::Fifo => "FIFO",
+            CostBasisMethod::Lifo => "LIFO",
+            CostBasisMethod::Hifo => "HIFO",
+        }
+    }
+}
+
+impl FromStr for CostBasisMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "FIFO" => Ok(CostBasisMethod::Fifo),
+            "LIFO" => Ok(CostBasisMethod::Lifo),
+            "HIFO" => Ok(CostBasisMethod::Hifo),
+            other => Err(format!("Unsupported cost basis method: {}", other)),
+        }
+    }
+}
+
+/// How acquisitions of the same asset are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolingMethod {
+    /// Each acquisition is a separate lot, matched by the selected method.
+    Lots,
+    /// Same-day and bed-and-breakfast matching, then one averaged pool.
+    SharePool,
+    /// One averaged pool per asset.
+    AverageCost,
+}
+
+/// Rules the cost-basis engine applies for a jurisdiction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JurisdictionRules {
+    /// Jurisdiction these rules belong to.
+    pub jurisdiction: Jurisdiction,
+    /// Display name.
+    pub name: String,
+    /// Reporting currency code.
+    pub currency: String,
+    /// How acquisitions are pooled.
+    pub pooling: PoolingMethod,
+    /// Lot selection methods permitted; empty when acquisitions are pooled.
+    pub allowed_methods: Vec<CostBasisMethod>,
+    /// Lot selection method used when the profile hasn't chosen one.
+    pub default_method: CostBasisMethod,
+    /// Holding period after which gains are long-term, if the distinction exists.
+    pub long_term_months: Option<u32>,
+    /// Holding period after which gains are not taxable at all.
+    pub exempt_after_months: Option<u32>,
+    /// Days after a disposal in which a repurchase is matched to it.
+    pub matching_window_days: Option<i64>,
+    /// Fraction of a gain that is taxable.
+    pub inclusion_rate: Decimal,
+    /// Rule deferring losses when the asset is repurchased, if any.
+    pub loss_deferral: Option<LossDeferralRule>,
+}
+
+impl JurisdictionRules {
+    /// Whether `method` may be used. Pooled jurisdictions ignore the method,
+    /// so any value is accepted.
+    pub fn allows(&self, method: CostBasisMethod) -> bool {
+        self.pooling != PoolingMethod::Lots || self.allowed_methods.contains(&method)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for jurisdiction in Jurisdiction::ALL {
+            let json = serde_json::to_string(&jurisdiction).unwrap();
+            assert_eq!(
+                Jurisdiction::from_str(json.trim_matches('"')).unwrap(),
+                jurisdiction
+            );
+            assert_eq!(jurisdiction.rules().jurisdiction, jurisdiction);
+        }
+        assert_eq!(Jurisdiction::from_str("GB").unwrap(), Jurisdiction::Uk);
+        assert!(Jurisdiction::from_str("fr").is_err());
+        assert_eq!(
+            CostBasisMethod::from_str("hifo").unwrap(),
+            CostBasisMethod::Hifo
+        );
+    }
+
+    #[test]
+    fn test_allowed_methods() {
+        assert!(Jurisdiction::Us.rules().allows(CostBasisMethod::Hifo));
+        assert!(!Jurisdiction::De.rules().allows(CostBasisMethod::Lifo));
+        assert!(Jurisdiction::Uk.rules().allows(CostBasisMethod::Lifo));
+    }
+}
@@ -0,0 +1,543 @@
+//! Liquidity position lifecycle.
+//!
+//! Adding liquidity exchanges the contributed tokens for the pool's LP
+//! token, whose cost basis is the value of the tokens contributed. Removing
+//! liquidity disposes of LP tokens for the tokens returned, releasing basis
+//! pro rata. At removal the tokens returned are compared with what the
+//! contributed tokens would be worth had they been held, which is the
+//! position's impermanent loss. Where the protocol reports fees separately
+//! from principal, as Uniswap V3's `Collect` does beyond the amounts released
+//! by `DecreaseLiquidity`, the fees are income attributed to the position.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::{AssetEvent, AssetEventKind, IncomeSource};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// What a liquidity event did to the position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiquidityEventKind {
+    /// Tokens deposited for LP tokens.
+    Add,
+    /// LP tokens redeemed for the pool's tokens.
+    Remove,
+    /// Fees collected without changing the position.
+    Collect,
+}
+
+/// A quantity of one of the pool's tokens, valued in the reporting currency.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolAmount {
+    /// Token symbol or identifier.
+    pub asset: String,
+    /// Quantity of the token.
+    pub quantity: Decimal,
+    /// Fair market value at the event.
+    pub value: Decimal,
+}
+
+/// An add, removal, or fee collection on a liquidity position.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiquidityEvent {
+    /// Unique ID, usually the source transaction ID.
+    pub id: String,
+    /// What happened to the position.
+    pub kind: LiquidityEventKind,
+    /// When the event happened.
+    pub timestamp: DateTime<Utc>,
+    /// Pool address or identifier.
+    pub pool: String,
+    /// LP token or position identifier, e.g. a V3 position NFT.
+    pub lp_token: String,
+    /// LP tokens minted or burned; zero for a collection.
+    #[serde(default)]
+    pub lp_quantity: Decimal,
+    /// Tokens contributed on an add, or principal returned on a removal.
+    #[serde(default)]
+    pub amounts: Vec<PoolAmount>,
+    /// Fees collected, where the event data separates them from principal.
+    #[serde(default)]
+    pub fees_collected: Vec<PoolAmount>,
+    /// Network fee paid; added to cost or deducted from proceeds.
+    #[serde(default)]
+    pub fee: Decimal,
+}
+
+/// An open liquidity position.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiquidityPosition {
+    /// Pool the position is in.
+    pub pool: String,
+    /// LP token or position identifier.
+    pub lp_token: String,
+    /// LP tokens held.
+    pub lp_quantity: Decimal,
+    /// Remaining cost basis.
+    pub cost_basis: Decimal,
+    /// Tokens contributed that remain in the position, at their value when
+    /// contributed.
+    pub deposited: Vec<PoolAmount>,
+    /// Fee income collected so far.
+    pub fee_income: Decimal,
+}
+
+/// The result of removing liquidity from a position.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiquidityRemoval {
+    /// ID of the removal event.
+    pub event_id: String,
+    /// Pool the position is in.
+    pub pool: String,
+    /// LP token or position identifier.
+    pub lp_token: String,
+    /// When the liquidity was removed.
+    pub removed_at: DateTime<Utc>,
+    /// LP tokens burned.
+    pub lp_quantity: Decimal,
+    /// Value of the principal returned, net of the network fee.
+    pub proceeds: Decimal,
+    /// Basis released for the LP tokens burned.
+    pub cost_basis: Decimal,
+    /// `proceeds - cost_basis`.
+    pub gain: Decimal,
+    /// Value at removal of the tokens originally contributed for the LP
+    /// tokens burned; `None` when a contributed token wasn't returned, so
+    /// its price at removal is unknown.
+    pub hold_value: Option<Decimal>,
+    /// `hold_value` less the value of the principal returned; positive when
+    /// providing liquidity did worse than holding.
+    pub impermanent_loss: Option<Decimal>,
+    /// Fees collected with the removal.
+    pub fee_income: Decimal,
+}
+
+/// Result of [`track`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiquidityReport {
+    /// Positions still open.
+    pub positions: Vec<LiquidityPosition>,
+    /// Removals, in date order.
+    pub removals: Vec<LiquidityRemoval>,
+    /// Total fee income across all positions.
+    pub fee_income: Decimal,
+    /// Total impermanent loss across removals where it could be measured.
+    pub impermanent_loss: Decimal,
+    /// Acquisitions, disposals, and income for the cost-basis engine.
+    pub asset_events: Vec<AssetEvent>,
+    /// Problems found in the input, such as removing more than was added.
+    pub warnings: Vec<String>,
+}
+
+/// Running state of one position.
+#[derive(Default)]
+struct Position {
+    pool: String,
+    lp_quantity: Decimal,
+    cost_basis: Decimal,
+    deposited: BTreeMap<String, (Decimal, Decimal)>,
+    fee_income: Decimal,
+}
+
+// ============================================================================
+// Engine
+// ============================================================================
+
+/// Follows each position through `events`, which may be in any order.
+pub fn track(events: &[LiquidityEvent]) -> LiquidityReport {
+    let mut sorted: Vec<&LiquidityEvent> = events.iter().collect();
+    sorted.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.id.cmp(&b.id)));
+
+    let mut report = LiquidityReport::default();
+    let mut positions: BTreeMap<String, Position> = BTreeMap::new();
+
+    for event in sorted {
+        let position = positions
+            .entry(event.lp_token.clone())
+            .or_insert_with(|| Position {
+                pool: event.pool.clone(),
+                ..Position::default()
+            });
+
+        match event.kind {
+            LiquidityEventKind::Add => add(&mut report, position, event),
+            LiquidityEventKind::Remove => remove(&mut report, position, event),
+            LiquidityEventKind::Collect => {}
+        }
+        collect_fees(&mut report, position, event);
+    }
+
+    report.positions = positions
+        .into_iter()
+        .filter(|(_, p)| p.lp_quantity > Decimal::ZERO)
+        .map(|(lp_token, p)| LiquidityPosition {
+            pool: p.pool,
+            lp_token,
+            lp_quantity: p.lp_quantity,
+            cost_basis: p.cost_basis,
+            deposited: p
+                .deposited
+                .into_iter()
+                .map(|(asset, (quantity, value))| PoolAmount {
+                    asset,
+                    quantity,
+                    value,
+                })
+                .collect(),
+            fee_income: p.fee_income,
+        })
+        .collect();
+    report.impermanent_loss = report
+        .removals
+        .iter()
+        .filter_map(|r| r.impermanent_loss)
+        .sum();
+    report
+}
+
+/// Records the contributed tokens as disposed of for LP tokens.
+fn add(report: &mut LiquidityReport, position: &mut Position, event: &LiquidityEvent) {
+    let contributed: Decimal = event.amounts.iter().map(|a| a.value).sum();
+    for amount in &event.amounts {
+        report.asset_events.push(asset_event(
+            event,
+            &amount.asset,
+            AssetEventKind::Disposal,
+            amount,
+        ));
+        let deposited = position
+            .deposited
+            .entry(amount.asset.clone())
+            .or_insert((Decimal::ZERO, Decimal::ZERO));
+        deposited.0 += amount.quantity;
+        deposited.1 += amount.value;
+    }
+    report.asset_events.push(AssetEvent {
+        id: event.id.clone(),
+        asset: event.lp_token.clone(),
+        kind: AssetEventKind::Acquisition,
+        timestamp: event.timestamp,
+        quantity: event.lp_quantity,
+        value: contributed,
+        fee: event.fee,
+        income_source: None,
+        price_override: None,
+        lot_ids: Vec::new(),
+    });
+
+    position.lp_quantity += event.lp_quantity;
+    position.cost_basis += contributed + event.fee;
+}
+
+/// Records the LP tokens burned as disposed of for the principal returned,
+/// and measures impermanent loss against the contributed tokens.
+fn remove(report: &mut LiquidityReport, position: &mut Position, event: &LiquidityEvent) {
+    let mut burned = event.lp_quantity;
+    if burned > position.lp_quantity {
+        report.warnings.push(format!(
+            "{}: removes {} {} but only {} was added",
+            event.id, burned, event.lp_token, position.lp_quantity
+        ));
+        burned = position.lp_quantity;
+    }
+    let share = if position.lp_quantity > Decimal::ZERO {
+        burned / position.lp_quantity
+    } else {
+        Decimal::ZERO
+    };
+
+    let returned: Decimal = event.amounts.iter().map(|a| a.value).sum();
+    let cost_basis = position.cost_basis * share;
+
+    // Tokens that would have been held, valued at the removal prices
+    let prices: HashMap<&str, Decimal> = event
+        .amounts
+        .iter()
+        .filter(|a| a.quantity > Decimal::ZERO)
+        .map(|a| (a.asset.as_str(), a.value / a.quantity))
+        .collect();
+    let mut hold_value = Some(Decimal::ZERO);
+    for (asset, (quantity, value)) in position.deposited.iter_mut() {
+        let held = *quantity * share;
+        *quantity -= held;
+        *value -= *value * share;
+        hold_value = match (hold_value, prices.get(asset.as_str())) {
+            (Some(total), Some(price)) => Some(total + held * price),
+            (_, None) if held.is_zero() => hold_value,
+            _ => None,
+        };
+    }
+    if share.is_zero() {
+        hold_value = None;
+    }
+
+    for amount in &event.amounts {
+        report.asset_events.push(asset_event(
+            event,
+            &amount.asset,
+            AssetEventKind::Acquisition,
+            amount,
+        ));
+    }
+    report.asset_events.push(AssetEvent {
+        id: event.id.clone(),
+        asset: event.lp_token.clone(),
+        kind: AssetEventKind::Disposal,
+        timestamp: event.timestamp,
+        quantity: event.lp_quantity,
+        value: returned,
+        fee: event.fee,
+        income_source: None,
+        price_override: None,
+        lot_ids: Vec::new(),
+    });
+
+    position.lp_quantity -= burned;
+    position.cost_basis -= cost_basis;
+
+    let proceeds = returned - event.fee;
+    report.removals.push(LiquidityRemoval {
+        event_id: event.id.clone(),
+        pool: event.pool.clone(),
+        lp_token: event.lp_token.clone(),
+        removed_at: event.timestamp,
+        lp_quantity: burned,
+        proceeds,
+        cost_basis,
+        gain: proceeds - cost_basis,
+        hold_value,
+        impermanent_loss: hold_value.map(|hold| hold - returned),
+        fee_income: event.fees_collected.iter().map(|f| f.value).sum(),
+    });
+}
+
+/// Records fees collected as income attributed to the position.
+fn collect_fees(report: &mut LiquidityReport, position: &mut Position, event: &LiquidityEvent) {
+    for fee in event
+        .fees_collected
+        .iter()
+        .filter(|f| f.quantity > Decimal::ZERO)
+    {
+        report.asset_events.push(AssetEvent {
+            income_source: Some(IncomeSource::LiquidityFees),
+            ..asset_event(event, &fee.asset, AssetEventKind::Income, fee)
+        });
+        position.fee_income += fee.value;
+        report.fee_income += fee.value;
+    }
+}
+
+/// A cost-basis event for one of the pool's tokens. The network fee is
+/// carried by the LP token's event.
+fn asset_event(
+    event: &LiquidityEvent,
+    asset: &str,
+    kind: AssetEventKind,
+    amount: &PoolAmount,
+) -> AssetEvent {
+    AssetEvent {
+        id: event.id.clone(),
+        asset: asset.to_string(),
+        kind,
+        timestamp: event.timestamp,
+        quantity: amount.quantity,
+        value: amount.value,
+        fee: Decimal::ZERO,
+        income_source: None,
+        price_override: None,
+        lot_ids: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn amount(asset: &str, quantity: Decimal, value: Decimal) -> PoolAmount {
+        PoolAmount {
+            asset: asset.to_string(),
+            quantity,
+            value,
+        }
+    }
+
+    fn event(
+        id: &str,
+        kind: LiquidityEventKind,
+        day: u32,
+        lp_quantity: Decimal,
+        amounts: Vec<PoolAmount>,
+    ) -> LiquidityEvent {
+        LiquidityEvent {
+            id: id.to_string(),
+            kind,
+            timestamp: Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap(),
+            pool: "eth-usdc".to_string(),
+            lp_token: "UNI-V2".to_string(),
+            lp_quantity,
+            amounts,
+            fees_collected: Vec::new(),
+            fee: Decimal::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_add_sets_lp_basis_to_contributed_value() {
+        let mut add = event(
+            "add",
+            LiquidityEventKind::Add,
+            1,
+            dec("10"),
+            vec![
+                amount("ETH", dec("1"), dec("2000")),
+                amount("USDC", dec("2000"), dec("2000")),
+            ],
+        );
+        add.fee = dec("5");
+        let report = track(&[add]);
+
+        assert_eq!(report.positions.len(), 1);
+        assert_eq!(report.positions[0].lp_quantity, dec("10"));
+        assert_eq!(report.positions[0].cost_basis, dec("4005"));
+
+        let lp = report
+            .asset_events
+            .iter()
+            .find(|e| e.asset == "UNI-V2")
+            .unwrap();
+        assert_eq!(lp.kind, AssetEventKind::Acquisition);
+        assert_eq!((lp.value, lp.fee), (dec("4000"), dec("5")));
+        assert_eq!(
+            report
+                .asset_events
+                .iter()
+                .filter(|e| e.kind == AssetEventKind::Disposal)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_partial_removal_measures_impermanent_loss() {
+        let add = event(
+            "add",
+            LiquidityEventKind::Add,
+            1,
+            dec("10"),
+            vec![
+                amount("ETH", dec("1"), dec("2000")),
+                amount("USDC", dec("2000"), dec("2000")),
+            ],
+        );
+        // ETH doubled; half the position returns 0.3536 ETH and 1414.2 USDC
+        let remove = event(
+            "remove",
+            LiquidityEventKind::Remove,
+            20,
+            dec("5"),
+            vec![
+                amount("ETH", dec("0.3536"), dec("1414.4")),
+                amount("USDC", dec("1414.2"), dec("1414.2")),
+            ],
+        );
+        let report = track(&[remove, add]);
+
+        let removal = &report.removals[0];
+        assert_eq!(removal.cost_basis, dec("2000"));
+        assert_eq!(removal.proceeds, dec("2828.6"));
+        assert_eq!(removal.gain, dec("828.6"));
+        // Held: 0.5 ETH at 4000 + 1000 USDC
+        assert_eq!(removal.hold_value, Some(dec("3000")));
+        assert_eq!(removal.impermanent_loss, Some(dec("171.4")));
+        assert_eq!(report.impermanent_loss, dec("171.4"));
+
+        let position = &report.positions[0];
+        assert_eq!(position.lp_quantity, dec("5"));
+        assert_eq!(position.cost_basis, dec("2000"));
+        assert_eq!(position.deposited[0].quantity, dec("0.5"));
+        assert_eq!(position.deposited[0].value, dec("1000"));
+    }
+
+    #[test]
+    fn test_fees_collected_are_income() {
+        let add = event(
+            "add",
+            LiquidityEventKind::Add,
+            1,
+            dec("1"),
+            vec![amount("ETH", dec("1"), dec("2000"))],
+        );
+        let mut collect = event("collect", LiquidityEventKind::Collect, 10, dec("0"), vec![]);
+        collect.fees_collected = vec![amount("ETH", dec("0.01"), dec("25"))];
+        let mut remove = event(
+            "remove",
+            LiquidityEventKind::Remove,
+            20,
+            dec("1"),
+            vec![amount("ETH", dec("1"), dec("2500"))],
+        );
+        remove.fees_collected = vec![amount("ETH", dec("0.02"), dec("50"))];
+        let report = track(&[add, collect, remove]);
+
+        assert_eq!(report.fee_income, dec("75"));
+        assert_eq!(report.removals[0].fee_income, dec("50"));
+        assert_eq!(report.removals[0].impermanent_loss, Some(dec("0")));
+        assert!(report.positions.is_empty());
+
+        let income: Vec<&AssetEvent> = report
+            .asset_events
+            .iter()
+            .filter(|e| e.kind == AssetEventKind::Income)
+            .collect();
+        assert_eq!(income.len(), 2);
+        assert!(income
+            .iter()
+            .all(|e| e.income_source == Some(IncomeSource::LiquidityFees)));
+    }
+
+    #[test]
+    fn test_unknown_price_and_over_removal() {
+        let add = event(
+            "add",
+            LiquidityEventKind::Add,
+            1,
+            dec("1"),
+            vec![
+                amount("ETH", dec("1"), dec("2000")),
+                amount("USDC", dec("2000"), dec("2000")),
+            ],
+        );
+        // Only USDC is returned, so ETH's price at removal is unknown
+        let remove = event(
+            "remove",
+            LiquidityEventKind::Remove,
+            5,
+            dec("2"),
+            vec![amount("USDC", dec("3900"), dec("3900"))],
+        );
+        let report = track(&[add, remove]);
+
+        assert_eq!(report.removals[0].lp_quantity, dec("1"));
+        assert_eq!(report.removals[0].hold_value, None);
+        assert_eq!(report.removals[0].impermanent_loss, None);
+        assert_eq!(report.warnings.len(), 1);
+    }
+}
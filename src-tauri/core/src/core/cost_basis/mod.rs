@@ -0,0 +1,1231 @@
+//! Cost-basis engine.
+//!
+//! Matches disposals against earlier acquisitions of the same asset and
+//! computes realized gains under the rules of a tax jurisdiction. Values are
+//! in the profile's reporting currency; the engine does no price lookups.
+
+pub mod jurisdiction;
+pub mod liquidity;
+pub mod wash_sale;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, Duration, Months, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+pub use jurisdiction::{CostBasisMethod, Jurisdiction, JurisdictionRules, PoolingMethod};
+pub use wash_sale::LossDeferral;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Whether an event adds to or removes from holdings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetEventKind {
+    /// Purchase, income, or transfer in at a known cost.
+    Acquisition,
+    /// Sale, spend, or swap out.
+    Disposal,
+    /// Airdrop, mining payout, or staking reward. Recognized as ordinary
+    /// income at `value` and held as a lot with that cost basis.
+    Income,
+}
+
+impl AssetEventKind {
+    /// Whether the event creates a lot.
+    pub fn adds_holdings(&self) -> bool {
+        matches!(self, AssetEventKind::Acquisition | AssetEventKind::Income)
+    }
+}
+
+/// Where income was received from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncomeSource {
+    /// Tokens received in an airdrop.
+    Airdrop,
+    /// Block rewards from mining.
+    Mining,
+    /// Staking or validator rewards.
+    Staking,
+    /// Trading fees earned by a liquidity position.
+    LiquidityFees,
+    /// Any other income received in kind.
+    Other,
+}
+
+/// An acquisition or disposal of an asset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetEvent {
+    /// Unique ID, usually the source transaction ID.
+    pub id: String,
+    /// Asset symbol or identifier.
+    pub asset: String,
+    /// Acquisition or disposal.
+    pub kind: AssetEventKind,
+    /// When the event happened.
+    pub timestamp: DateTime<Utc>,
+    /// Quantity of the asset.
+    pub quantity: Decimal,
+    /// Cost for acquisitions, gross proceeds for disposals, or fair market
+    /// value on receipt for income.
+    pub value: Decimal,
+    /// Fee paid; added to cost or deducted from proceeds.
+    #[serde(default)]
+    pub fee: Decimal,
+    /// Kind of income, for income events.
+    #[serde(default)]
+    pub income_source: Option<IncomeSource>,
+    /// ID of the manual price override `value` was computed from, if any.
+    #[serde(default)]
+    pub price_override: Option<String>,
+    /// Acquisitions a disposal spends, when the asset identifies them, as
+    /// bitcoin's spent outputs do. Where lots are matched individually they
+    /// are consumed first, ahead of the lot selection method.
+    #[serde(default)]
+    pub lot_ids: Vec<String>,
+}
+
+/// How a disposal was matched to its cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchRule {
+    /// A specific acquisition lot.
+    Lot,
+    /// An acquisition on the same day.
+    SameDay,
+    /// A repurchase within the jurisdiction's matching window.
+    BedAndBreakfast,
+    /// The averaged pool for the asset.
+    Pool,
+    /// More was disposed than was held; matched at zero cost.
+    Unmatched,
+}
+
+/// Holding period classification of a gain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HoldingTerm {
+    /// Held up to the long-term threshold.
+    Short,
+    /// Held longer than the long-term threshold.
+    Long,
+    /// The jurisdiction doesn't distinguish, or the acquisition date is unknown.
+    NotApplicable,
+}
+
+/// A realized gain for part of a disposal matched to one cost source.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Disposal {
+    /// ID of the disposal event.
+    pub event_id: String,
+    /// Asset disposed.
+    pub asset: String,
+    /// When the disposal happened.
+    pub disposed_at: DateTime<Utc>,
+    /// ID of the matched acquisition; `None` for pools and unmatched quantity.
+    pub acquisition_id: Option<String>,
+    /// When the matched acquisition happened, if known.
+    pub acquired_at: Option<DateTime<Utc>>,
+    /// How the cost was determined.
+    pub rule: MatchRule,
+    /// Quantity disposed in this match.
+    pub quantity: Decimal,
+    /// Share of the net proceeds.
+    pub proceeds: Decimal,
+    /// Cost basis of the matched quantity.
+    pub cost_basis: Decimal,
+    /// `proceeds - cost_basis`.
+    pub gain: Decimal,
+    /// Days between acquisition and disposal, if known.
+    pub holding_days: Option<i64>,
+    /// Holding period classification.
+    pub term: HoldingTerm,
+    /// Whether the gain is exempt because of the holding period.
+    pub exempt: bool,
+    /// Part of a loss deferred to a replacement acquisition, as a positive
+    /// amount.
+    pub disallowed_loss: Decimal,
+    /// Portion of the gain that is taxable.
+    pub taxable_gain: Decimal,
+}
+
+/// Holdings remaining after all events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenLot {
+    /// Asset held.
+    pub asset: String,
+    /// Source acquisition; `None` for a pool.
+    pub acquisition_id: Option<String>,
+    /// When the lot was acquired; `None` for a pool.
+    pub acquired_at: Option<DateTime<Utc>>,
+    /// Remaining quantity.
+    pub quantity: Decimal,
+    /// Remaining cost basis.
+    pub cost_basis: Decimal,
+}
+
+/// Income received in kind.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomeItem {
+    /// ID of the income event.
+    pub event_id: String,
+    /// Asset received.
+    pub asset: String,
+    /// When it was received.
+    pub received_at: DateTime<Utc>,
+    /// Kind of income.
+    pub source: IncomeSource,
+    /// Quantity received.
+    pub quantity: Decimal,
+    /// Fair market value on receipt; also the lot's cost basis.
+    pub fair_value: Decimal,
+}
+
+/// Income total for one source.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomeSourceTotal {
+    /// Kind of income.
+    pub source: IncomeSource,
+    /// Number of receipts.
+    pub count: usize,
+    /// Total fair market value.
+    pub total: Decimal,
+}
+
+/// Ordinary income section of the report.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncomeSummary {
+    /// Total income at fair market value.
+    pub total: Decimal,
+    /// Totals per source.
+    pub by_source: Vec<IncomeSourceTotal>,
+    /// Individual receipts, in date order.
+    pub items: Vec<IncomeItem>,
+}
+
+impl IncomeSummary {
+    /// Collects the income events from `events`.
+    fn from_events(events: &[AssetEvent]) -> Self {
+        let mut items: Vec<IncomeItem> = events
+            .iter()
+            .filter(|e| e.kind == AssetEventKind::Income && e.quantity > Decimal::ZERO)
+            .map(|e| IncomeItem {
+                event_id: e.id.clone(),
+                asset: e.asset.clone(),
+                received_at: e.timestamp,
+                source: e.income_source.unwrap_or(IncomeSource::Other),
+                quantity: e.quantity,
+                fair_value: e.value,
+            })
+            .collect();
+        items.sort_by(|a, b| {
+            a.received_at
+                .cmp(&b.received_at)
+                .then(a.event_id.cmp(&b.event_id))
+        });
+
+        let mut by_source: BTreeMap<IncomeSource, IncomeSourceTotal> = BTreeMap::new();
+        for item in &items {
+            let entry = by_source
+                .entry(item.source)
+                .or_insert_with(|| IncomeSourceTotal {
+                    source: item.source,
+                    count: 0,
+                    total: Decimal::ZERO,
+                });
+            entry.count += 1;
+            entry.total += item.fair_value;
+        }
+
+        Self {
+            total: items.iter().map(|i| i.fair_value).sum(),
+            by_source: by_source.into_values().collect(),
+            items,
+        }
+    }
+}
+
+/// A section of a [`CostBasisReport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportSection {
+    /// Realized gains.
+    Disposals,
+    /// Remaining holdings.
+    OpenLots,
+    /// Ordinary income.
+    Income,
+}
+
+/// Audit note for a report section whose figures rest on manual prices.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceOverrideNote {
+    /// Section the note applies to.
+    pub section: ReportSection,
+    /// Asset valued with overridden prices.
+    pub asset: String,
+    /// Overrides used.
+    pub override_ids: Vec<String>,
+    /// Events in the section valued from an override, directly or through
+    /// the lot or pool they were matched against.
+    pub event_ids: Vec<String>,
+    /// Human-readable note for the report.
+    pub note: String,
+}
+
+/// Result of [`calculate`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostBasisReport {
+    /// Jurisdiction applied.
+    pub jurisdiction: Jurisdiction,
+    /// Lot selection method applied; `None` for pooled jurisdictions.
+    pub method: Option<CostBasisMethod>,
+    /// Realized gains, in disposal order.
+    pub disposals: Vec<Disposal>,
+    /// Remaining holdings.
+    pub open_lots: Vec<OpenLot>,
+    /// Sum of net proceeds.
+    pub total_proceeds: Decimal,
+    /// Sum of cost basis.
+    pub total_cost_basis: Decimal,
+    /// Sum of gains and losses.
+    pub total_gain: Decimal,
+    /// Gains classified short-term.
+    pub short_term_gain: Decimal,
+    /// Gains classified long-term.
+    pub long_term_gain: Decimal,
+    /// Gains exempt because of the holding period.
+    pub exempt_gain: Decimal,
+    /// Losses deferred by wash sale or superficial loss rules.
+    pub disallowed_loss: Decimal,
+    /// Taxable gain after exemptions, deferred losses, and the inclusion rate.
+    pub taxable_gain: Decimal,
+    /// Losses deferred to replacement acquisitions.
+    pub loss_deferrals: Vec<LossDeferral>,
+    /// Ordinary income received in kind.
+    pub income: IncomeSummary,
+    /// Problems found in the input, such as disposals exceeding holdings.
+    pub warnings: Vec<String>,
+    /// Sections that use manually overridden prices.
+    #[serde(default)]
+    pub price_overrides: Vec<PriceOverrideNote>,
+}
+
+/// An acquisition with its unconsumed quantity and cost.
+#[derive(Debug, Clone)]
+struct Lot {
+    id: String,
+    acquired_at: DateTime<Utc>,
+    quantity: Decimal,
+    cost: Decimal,
+}
+
+impl Lot {
+    fn from_event(event: &AssetEvent) -> Self {
+        Self {
+            id: event.id.clone(),
+            acquired_at: event.timestamp,
+            quantity: event.quantity,
+            cost: event.value + event.fee,
+        }
+    }
+
+    /// Removes up to `quantity` from the lot, returning the quantity and
+    /// cost taken.
+    fn take(&mut self, quantity: Decimal) -> (Decimal, Decimal) {
+        let taken = quantity.min(self.quantity);
+        if taken <= Decimal::ZERO {
+            return (Decimal::ZERO, Decimal::ZERO);
+        }
+        let cost = if taken == self.quantity {
+            self.cost
+        } else {
+            self.cost * taken / self.quantity
+        };
+        self.quantity -= taken;
+        self.cost -= cost;
+        (taken, cost)
+    }
+}
+
+/// Part of a disposal matched to a cost source, before proceeds are split.
+#[derive(Debug, Clone)]
+struct Match {
+    acquisition_id: Option<String>,
+    acquired_at: Option<DateTime<Utc>>,
+    rule: MatchRule,
+    quantity: Decimal,
+    cost_basis: Decimal,
+}
+
+impl Match {
+    fn from_lot(lot: &Lot, rule: MatchRule, quantity: Decimal, cost_basis: Decimal) -> Self {
+        Self {
+            acquisition_id: Some(lot.id.clone()),
+            acquired_at: Some(lot.acquired_at),
+            rule,
+            quantity,
+            cost_basis,
+        }
+    }
+
+    fn pooled(rule: MatchRule, quantity: Decimal, cost_basis: Decimal) -> Self {
+        Self {
+            acquisition_id: None,
+            acquired_at: None,
+            rule,
+            quantity,
+            cost_basis,
+        }
+    }
+}
+
+/// Output of the per-asset matchers.
+#[derive(Default)]
+struct AssetResult {
+    disposals: Vec<Disposal>,
+    open_lots: Vec<OpenLot>,
+    warnings: Vec<String>,
+}
+
+// ============================================================================
+// Engine
+// ============================================================================
+
+/// Computes realized gains for `events` under `rules`.
+///
+/// `method` selects lots in jurisdictions that match individual lots and is
+/// ignored where acquisitions are pooled. Events may be in any order. Where
+/// the jurisdiction defers losses on repurchases, disallowed losses are added
+/// to the replacement acquisitions' cost and gains are recomputed. Sections
+/// relying on events valued from a price override get an audit note.
+pub fn calculate(
+    events: &[AssetEvent],
+    rules: &JurisdictionRules,
+    method: CostBasisMethod,
+) -> CostBasisReport {
+    let mut report = calculate_gains(events, rules, method);
+    report.price_overrides = price_override_notes(events, &report);
+    report
+}
+
+/// Runs the matching and any loss deferral.
+fn calculate_gains(
+    events: &[AssetEvent],
+    rules: &JurisdictionRules,
+    method: CostBasisMethod,
+) -> CostBasisReport {
+    let report = match_events(events, rules, method);
+    let Some(rule) = &rules.loss_deferral else {
+        return report;
+    };
+
+    let deferrals = wash_sale::detect(events, &report.disposals, rule);
+    if deferrals.is_empty() {
+        return report;
+    }
+
+    let adjusted = wash_sale::adjust_replacement_costs(events, &deferrals);
+    let mut report = match_events(&adjusted, rules, method);
+    wash_sale::apply(&mut report, deferrals, rules);
+    // Income is reported at the value received, before any deferred loss was
+    // added to a replacement's cost.
+    report.income = IncomeSummary::from_events(events);
+    report.summarize();
+    report
+}
+
+/// Matches disposals to acquisitions without any loss deferral.
+fn match_events(
+    events: &[AssetEvent],
+    rules: &JurisdictionRules,
+    method: CostBasisMethod,
+) -> CostBasisReport {
+    let mut by_asset: BTreeMap<&str, Vec<&AssetEvent>> = BTreeMap::new();
+    let mut warnings = Vec::new();
+
+    for event in events {
+        if event.quantity <= Decimal::ZERO {
+            warnings.push(format!(
+                "Skipped event {} with non-positive quantity {}",
+                event.id, event.quantity
+            ));
+            continue;
+        }
+        by_asset
+            .entry(event.asset.as_str())
+            .or_default()
+            .push(event);
+    }
+
+    let mut disposals = Vec::new();
+    let mut open_lots = Vec::new();
+
+    for (_, mut asset_events) in by_asset {
+        // Acquisitions sort before disposals at the same instant so a buy and
+        // sell in one transaction can be matched.
+        asset_events.sort_by(|a, b| {
+            a.timestamp
+                .cmp(&b.timestamp)
+                .then(
+                    (a.kind == AssetEventKind::Disposal).cmp(&(b.kind == AssetEventKind::Disposal)),
+                )
+                .then(a.id.cmp(&b.id))
+        });
+
+        let result = match rules.pooling {
+            PoolingMethod::Lots => match_lots(&asset_events, rules, method),
+            PoolingMethod::AverageCost => match_average_cost(&asset_events, rules),
+            PoolingMethod::SharePool => match_share_pool(&asset_events, rules),
+        };
+        disposals.extend(result.disposals);
+        open_lots.extend(result.open_lots);
+        warnings.extend(result.warnings);
+    }
+
+    disposals.sort_by(|a, b| {
+        a.disposed_at
+            .cmp(&b.disposed_at)
+            .then(a.event_id.cmp(&b.event_id))
+    });
+
+    let mut report = CostBasisReport {
+        jurisdiction: rules.jurisdiction,
+        method: (rules.pooling == PoolingMethod::Lots).then_some(method),
+        disposals,
+        open_lots,
+        total_proceeds: Decimal::ZERO,
+        total_cost_basis: Decimal::ZERO,
+        total_gain: Decimal::ZERO,
+        short_term_gain: Decimal::ZERO,
+        long_term_gain: Decimal::ZERO,
+        exempt_gain: Decimal::ZERO,
+        disallowed_loss: Decimal::ZERO,
+        taxable_gain: Decimal::ZERO,
+        loss_deferrals: Vec::new(),
+        income: IncomeSummary::from_events(events),
+        warnings,
+        price_overrides: Vec::new(),
+    };
+    report.summarize();
+    report
+}
+
+/// Collects overrides and affected events for one section and asset.
+#[derive(Default)]
+struct NoteBuilder {
+    override_ids: BTreeSet<String>,
+    event_ids: BTreeSet<String>,
+}
+
+/// Finds the report rows that rest on overridden prices.
+///
+/// A row is affected when its own event was valued from an override, when it
+/// was matched against an overridden acquisition, or when it draws on a pool
+/// that an overridden acquisition went into.
+fn price_override_notes(events: &[AssetEvent], report: &CostBasisReport) -> Vec<PriceOverrideNote> {
+    let overridden: BTreeMap<&str, (&str, &str)> = events
+        .iter()
+        .filter_map(|e| {
+            let id = e.price_override.as_deref()?;
+            Some((e.id.as_str(), (e.asset.as_str(), id)))
+        })
+        .collect();
+    if overridden.is_empty() {
+        return Vec::new();
+    }
+
+    // Overrides feeding each asset's pool.
+    let mut pooled: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for event in events.iter().filter(|e| e.kind.adds_holdings()) {
+        if let Some(id) = event.price_override.as_deref() {
+            pooled.entry(event.asset.as_str()).or_default().insert(id);
+        }
+    }
+    let pool_overrides = |asset: &str, acquisition_id: Option<&str>| -> Vec<&str> {
+        match acquisition_id {
+            Some(id) => overridden
+                .get(id)
+                .map(|(_, o)| vec![*o])
+                .unwrap_or_default(),
+            None => pooled
+                .get(asset)
+                .map(|ids| ids.iter().copied().collect())
+                .unwrap_or_default(),
+        }
+    };
+
+    let mut notes: BTreeMap<(ReportSection, String), NoteBuilder> = BTreeMap::new();
+    let mut add = |section: ReportSection, asset: &str, event_id: &str, ids: Vec<&str>| {
+        if ids.is_empty() {
+            return;
+        }
+        let note = notes.entry((section, asset.to_string())).or_default();
+        note.override_ids
+            .extend(ids.into_iter().map(str::to_string));
+        note.event_ids.insert(event_id.to_string());
+    };
+
+    for d in &report.disposals {
+        let mut ids: Vec<&str> = overridden
+            .get(d.event_id.as_str())
+            .map(|(_, o)| *o)
+            .into_iter()
+            .collect();
+        if d.rule != MatchRule::Unmatched {
+            ids.extend(pool_overrides(&d.asset, d.acquisition_id.as_deref()));
+        }
+        add(ReportSection::Disposals, &d.asset, &d.event_id, ids);
+    }
+    for lot in &report.open_lots {
+        let ids = pool_overrides(&lot.asset, lot.acquisition_id.as_deref());
+        let event_id = lot.acquisition_id.as_deref().unwrap_or(&lot.asset);
+        add(ReportSection::OpenLots, &lot.asset, event_id, ids);
+    }
+    for item in &report.income.items {
+        let ids = overridden
+            .get(item.event_id.as_str())
+            .map(|(_, o)| vec![*o])
+            .unwrap_or_default();
+        add(ReportSection::Income, &item.asset, &item.event_id, ids);
+    }
+
+    notes
+        .into_iter()
+        .map(|((section, asset), built)| PriceOverrideNote {
+            note: format!(
+                "{} {} valued using manual price overrides ({} affected)",
+                asset,
+                match section {
+                    ReportSection::Disposals => "disposals",
+                    ReportSection::OpenLots => "holdings",
+                    ReportSection::Income => "income",
+                },
+                built.event_ids.len()
+            ),
+            section,
+            asset,
+            override_ids: built.override_ids.into_iter().collect(),
+            event_ids: built.event_ids.into_iter().collect(),
+        })
+        .collect()
+}
+
+impl CostBasisReport {
+    /// Recomputes the totals from the disposal rows.
+    ///
+    /// Short- and long-term totals use the recognized gain, i.e. with
+    /// deferred losses added back.
+    fn summarize(&mut self) {
+        self.total_proceeds = Decimal::ZERO;
+        self.total_cost_basis = Decimal::ZERO;
+        self.total_gain = Decimal::ZERO;
+        self.short_term_gain = Decimal::ZERO;
+        self.long_term_gain = Decimal::ZERO;
+        self.exempt_gain = Decimal::ZERO;
+        self.disallowed_loss = Decimal::ZERO;
+        self.taxable_gain = Decimal::ZERO;
+
+        for d in &self.disposals {
+            let recognized = d.gain + d.disallowed_loss;
+            self.total_proceeds += d.proceeds;
+            self.total_cost_basis += d.cost_basis;
+            self.total_gain += d.gain;
+            match d.term {
+                HoldingTerm::Short => self.short_term_gain += recognized,
+                HoldingTerm::Long => self.long_term_gain += recognized,
+                HoldingTerm::NotApplicable => {}
+            }
+            if d.exempt {
+                self.exempt_gain += d.gain;
+            }
+            self.disallowed_loss += d.disallowed_loss;
+            self.taxable_gain += d.taxable_gain;
+        }
+    }
+}
+
+/// Whether more than `months` elapsed between acquisition and disposal,
+/// compared by calendar date.
+fn held_longer_than(acquired: DateTime<Utc>, disposed: DateTime<Utc>, months: u32) -> bool {
+    acquired
+        .checked_add_months(Months::new(months))
+        .is_some_and(|threshold| disposed.date_naive() > threshold.date_naive())
+}
+
+/// Holding period classification of an asset acquired at `acquired_at` and
+/// disposed of, or valued, at `at`.
+pub fn holding_term(
+    rules: &JurisdictionRules,
+    acquired_at: Option<DateTime<Utc>>,
+    at: DateTime<Utc>,
+) -> HoldingTerm {
+    match (rules.long_term_months, acquired_at) {
+        (Some(months), Some(acquired)) if held_longer_than(acquired, at, months) => {
+            HoldingTerm::Long
+        }
+        (Some(_), Some(_)) => HoldingTerm::Short,
+        _ => HoldingTerm::NotApplicable,
+    }
+}
+
+/// Splits the disposal's net proceeds across `matches` and classifies each
+/// resulting gain.
+fn build_disposals(
+    event: &AssetEvent,
+    matches: Vec<Match>,
+    rules: &JurisdictionRules,
+) -> Vec<Disposal> {
+    let net_proceeds = event.value - event.fee;
+    let mut remaining_proceeds = net_proceeds;
+    let count = matches.len();
+
+    matches
+        .into_iter()
+        .enumerate()
+        .map(|(i, m)| {
+            // The last match takes the remainder so rounding never loses proceeds.
+            let proceeds = if i + 1 == count {
+                remaining_proceeds
+            } else {
+                net_proceeds * m.quantity / event.quantity
+            };
+            remaining_proceeds -= proceeds;
+
+            let gain = proceeds - m.cost_basis;
+            let holding_days = m
+                .acquired_at
+                .map(|acquired| (event.timestamp - acquired).num_days());
+            let term = holding_term(rules, m.acquired_at, event.timestamp);
+            let exempt = match (rules.exempt_after_months, m.acquired_at) {
+                (Some(months), Some(acquired)) => {
+                    held_longer_than(acquired, event.timestamp, months)
+                }
+                _ => false,
+            };
+            let taxable_gain = if exempt {
+                Decimal::ZERO
+            } else {
+                gain * rules.inclusion_rate
+            };
+
+            Disposal {
+                event_id: event.id.clone(),
+                asset: event.asset.clone(),
+                disposed_at: event.timestamp,
+                acquisition_id: m.acquisition_id,
+                acquired_at: m.acquired_at,
+                rule: m.rule,
+                quantity: m.quantity,
+                proceeds,
+                cost_basis: m.cost_basis,
+                gain,
+                holding_days,
+                term,
+                exempt,
+                disallowed_loss: Decimal::ZERO,
+                taxable_gain,
+            }
+        })
+        .collect()
+}
+
+/// Records quantity disposed beyond holdings as a zero-cost match.
+fn push_shortfall(
+    event: &AssetEvent,
+    shortfall: Decimal,
+    matches: &mut Vec<Match>,
+    warnings: &mut Vec<String>,
+) {
+    if shortfall <= Decimal::ZERO {
+        return;
+    }
+    warnings.push(format!(
+        "Disposal {} of {} {} exceeds holdings by {}; the shortfall has zero cost basis",
+        event.id, event.quantity, event.asset, shortfall
+    ));
+    matches.push(Match::pooled(
+        MatchRule::Unmatched,
+        shortfall,
+        Decimal::ZERO,
+    ));
+}
+
+/// Matches each disposal against individual lots in `method` order.
+fn match_lots(
+    events: &[&AssetEvent],
+    rules: &JurisdictionRules,
+    method: CostBasisMethod,
+) -> AssetResult {
+    let mut result = AssetResult::default();
+    let mut lots: Vec<Lot> = Vec::new();
+
+    for event in events {
+        match event.kind {
+            AssetEventKind::Acquisition | AssetEventKind::Income => {
+                lots.push(Lot::from_event(event))
+            }
+            AssetEventKind::Disposal => {
+                let mut order: Vec<usize> = (0..lots.len()).collect();
+                match method {
+                    CostBasisMethod::Fifo => {}
+                    CostBasisMethod::Lifo => order.reverse(),
+                    CostBasisMethod::Hifo => order.sort_by(|&a, &b| {
+                        let unit = |lot: &Lot| lot.cost / lot.quantity;
+                        unit(&lots[b]).cmp(&unit(&lots[a])).then(a.cmp(&b))
+                    }),
+                }
+                order.sort_by_key(|&index| !event.lot_ids.contains(&lots[index].id));
+
+                let mut needed = event.quantity;
+                let mut matches = Vec::new();
+                for index in order {
+                    if needed <= Decimal::ZERO {
+                        break;
+                    }
+                    let lot = &mut lots[index];
+                    let (quantity, cost) = lot.take(needed);
+                    if quantity > Decimal::ZERO {
+                        needed -= quantity;
+                        matches.push(Match::from_lot(lot, MatchRule::Lot, quantity, cost));
+                    }
+                }
+                lots.retain(|lot| lot.quantity > Decimal::ZERO);
+
+                push_shortfall(event, needed, &mut matches, &mut result.warnings);
+                result
+                    .disposals
+                    .extend(build_disposals(event, matches, rules));
+            }
+        }
+    }
+
+    result.open_lots = lots
+        .into_iter()
+        .map(|lot| OpenLot {
+            asset: events[0].asset.clone(),
+            acquisition_id: Some(lot.id),
+            acquired_at: Some(lot.acquired_at),
+            quantity: lot.quantity,
+            cost_basis: lot.cost,
+        })
+        .collect();
+    result
+}
+
+/// Removes up to `quantity` from an averaged pool, returning the quantity and
+/// cost taken.
+fn take_from_pool(pool: &mut (Decimal, Decimal), quantity: Decimal) -> (Decimal, Decimal) {
+    let (pool_quantity, pool_cost) = pool;
+    let taken = quantity.min(*pool_quantity);
+    if taken <= Decimal::ZERO {
+        return (Decimal::ZERO, Decimal::ZERO);
+    }
+    let cost = if taken == *pool_quantity {
+        *pool_cost
+    } else {
+        *pool_cost * taken / *pool_quantity
+    };
+    *pool_quantity -= taken;
+    *pool_cost -= cost;
+    (taken, cost)
+}
+
+/// Converts a remaining pool into an open lot.
+fn pool_open_lot(asset: &str, pool: (Decimal, Decimal)) -> Option<OpenLot> {
+    (pool.0 > Decimal::ZERO).then(|| OpenLot {
+        asset: asset.to_string(),
+        acquisition_id: None,
+        acquired_at: None,
+        quantity: pool.0,
+        cost_basis: pool.1,
+    })
+}
+
+/// Matches disposals against a single pool at average cost.
+fn match_average_cost(events: &[&AssetEvent], rules: &JurisdictionRules) -> AssetResult {
+    let mut result = AssetResult::default();
+    let mut pool = (Decimal::ZERO, Decimal::ZERO);
+
+    for event in events {
+        match event.kind {
+            AssetEventKind::Acquisition | AssetEventKind::Income => {
+                pool.0 += event.quantity;
+                pool.1 += event.value + event.fee;
+            }
+            AssetEventKind::Disposal => {
+                let mut matches = Vec::new();
+                let (quantity, cost) = take_from_pool(&mut pool, event.quantity);
+                if quantity > Decimal::ZERO {
+                    matches.push(Match::pooled(MatchRule::Pool, quantity, cost));
+                }
+                push_shortfall(
+                    event,
+                    event.quantity - quantity,
+                    &mut matches,
+                    &mut result.warnings,
+                );
+                result
+                    .disposals
+                    .extend(build_disposals(event, matches, rules));
+            }
+        }
+    }
+
+    result
+        .open_lots
+        .extend(pool_open_lot(&events[0].asset, pool));
+    result
+}
+
+/// Matches disposals first to same-day acquisitions, then to acquisitions in
+/// the following matching window, then to the averaged pool.
+fn match_share_pool(events: &[&AssetEvent], rules: &JurisdictionRules) -> AssetResult {
+    let mut result = AssetResult::default();
+    let window = Duration::days(rules.matching_window_days.unwrap_or(0));
+
+    // Acquisitions by event index, so later passes can consume what earlier
+    // passes left.
+    let mut lots: BTreeMap<usize, Lot> = events
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.kind.adds_holdings())
+        .map(|(i, e)| (i, Lot::from_event(e)))
+        .collect();
+    let disposal_indices: Vec<usize> = events
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.kind == AssetEventKind::Disposal)
+        .map(|(i, _)| i)
+        .collect();
+    let mut matches: BTreeMap<usize, Vec<Match>> = BTreeMap::new();
+    let mut needed: BTreeMap<usize, Decimal> = disposal_indices
+        .iter()
+        .map(|&i| (i, events[i].quantity))
+        .collect();
+
+    // Same-day rule.
+    for &d in &disposal_indices {
+        let day = events[d].timestamp.date_naive();
+        for lot in lots.values_mut() {
+            let remaining = needed[&d];
+            if remaining <= Decimal::ZERO {
+                break;
+            }
+            if lot.acquired_at.date_naive() != day {
+                continue;
+            }
+            let (quantity, cost) = lot.take(remaining);
+            if quantity > Decimal::ZERO {
+                needed.insert(d, remaining - quantity);
+                matches.entry(d).or_default().push(Match::from_lot(
+                    lot,
+                    MatchRule::SameDay,
+                    quantity,
+                    cost,
+                ));
+            }
+        }
+    }
+
+    // Bed-and-breakfast rule: acquisitions after the disposal day, within the
+    // window, earliest first.
+    if window > Duration::zero() {
+        for &d in &disposal_indices {
+            let disposed = events[d].timestamp;
+            let day = disposed.date_naive();
+            let last_day = (disposed + window).date_naive();
+            for lot in lots.values_mut() {
+                let remaining = needed[&d];
+                if remaining <= Decimal::ZERO {
+                    break;
+                }
+                let acquired_day = lot.acquired_at.date_naive();
+                if acquired_day <= day || acquired_day > last_day {
+                    continue;
+                }
+                let (quantity, cost) = lot.take(remaining);
+                if quantity > Decimal::ZERO {
+                    needed.insert(d, remaining - quantity);
+                    matches.entry(d).or_default().push(Match::from_lot(
+                        lot,
+                        MatchRule::BedAndBreakfast,
+                        quantity,
+                        cost,
+                    ));
+                }
+            }
+        }
+    }
+
+    // Section 104 pool for everything left, in chronological order.
+    let mut pool = (Decimal::ZERO, Decimal::ZERO);
+    for (i, event) in events.iter().enumerate() {
+        match event.kind {
+            AssetEventKind::Acquisition | AssetEventKind::Income => {
+                if let Some(lot) = lots.get(&i) {
+                    pool.0 += lot.quantity;
+                    pool.1 += lot.cost;
+                }
+            }
+            AssetEventKind::Disposal => {
+                let mut event_matches = matches.remove(&i).unwrap_or_default();
+                let remaining = needed[&i];
+                let (quantity, cost) = take_from_pool(&mut pool, remaining);
+                if quantity > Decimal::ZERO {
+                    event_matches.push(Match::pooled(MatchRule::Pool, quantity, cost));
+                }
+                push_shortfall(
+                    event,
+                    remaining - quantity,
+                    &mut event_matches,
+                    &mut result.warnings,
+                );
+                result
+                    .disposals
+                    .extend(build_disposals(event, event_matches, rules));
+            }
+        }
+    }
+
+    result
+        .open_lots
+        .extend(pool_open_lot(&events[0].asset, pool));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: i64) -> Decimal {
+        Decimal::from(value)
+    }
+
+    fn event(
+        id: &str,
+        kind: AssetEventKind,
+        date: &str,
+        quantity: Decimal,
+        value: Decimal,
+    ) -> AssetEvent {
+        AssetEvent {
+            id: id.to_string(),
+            asset: "BTC".to_string(),
+            kind,
+            timestamp: DateTime::parse_from_rfc3339(&format!("{}T12:00:00Z", date))
+                .unwrap()
+                .with_timezone(&Utc),
+            quantity,
+            value,
+            fee: Decimal::ZERO,
+            income_source: None,
+            price_override: None,
+            lot_ids: Vec::new(),
+        }
+    }
+
+    fn buy(id: &str, date: &str, quantity: Decimal, value: Decimal) -> AssetEvent {
+        event(id, AssetEventKind::Acquisition, date, quantity, value)
+    }
+
+    fn sell(id: &str, date: &str, quantity: Decimal, value: Decimal) -> AssetEvent {
+        event(id, AssetEventKind::Disposal, date, quantity, value)
+    }
+
+    #[test]
+    fn test_us_fifo_splits_terms() {
+        let events = vec![
+            buy("b1", "2023-01-10", dec(1), dec(100)),
+            buy("b2", "2024-06-01", dec(1), dec(300)),
+            sell("s1", "2024-07-01", dec(2), dec(800)),
+        ];
+        let report = calculate(&events, &Jurisdiction::Us.rules(), CostBasisMethod::Fifo);
+
+        assert_eq!(report.disposals.len(), 2);
+        assert_eq!(report.disposals[0].term, HoldingTerm::Long);
+        assert_eq!(report.disposals[1].term, HoldingTerm::Short);
+        assert_eq!(report.long_term_gain, dec(300));
+        assert_eq!(report.short_term_gain, dec(100));
+        assert_eq!(report.taxable_gain, dec(400));
+        assert!(report.open_lots.is_empty());
+    }
+
+    #[test]
+    fn test_us_hifo_uses_most_expensive_lot() {
+        let events = vec![
+            buy("b1", "2024-01-01", dec(1), dec(100)),
+            buy("b2", "2024-02-01", dec(1), dec(500)),
+            buy("b3", "2024-03-01", dec(1), dec(200)),
+            sell("s1", "2024-04-01", dec(1), dec(400)),
+        ];
+        let report = calculate(&events, &Jurisdiction::Us.rules(), CostBasisMethod::Hifo);
+
+        assert_eq!(report.disposals[0].acquisition_id.as_deref(), Some("b2"));
+        assert_eq!(report.total_gain, dec(-100));
+        assert_eq!(report.open_lots.len(), 2);
+    }
+
+    #[test]
+    fn test_named_lots_match_before_method() {
+        let mut spend = sell("s1", "2024-04-01", dec(2), dec(800));
+        spend.lot_ids = vec!["b3".to_string()];
+        let events = vec![
+            buy("b1", "2024-01-01", dec(1), dec(100)),
+            buy("b2", "2024-02-01", dec(1), dec(500)),
+            buy("b3", "2024-03-01", dec(1), dec(200)),
+            spend,
+        ];
+        let report = calculate(&events, &Jurisdiction::Us.rules(), CostBasisMethod::Fifo);
+
+        // The named lot first, then FIFO for the rest
+        let matched: Vec<_> = report
+            .disposals
+            .iter()
+            .map(|d| d.acquisition_id.as_deref().unwrap())
+            .collect();
+        assert_eq!(matched, vec!["b3", "b1"]);
+        assert_eq!(report.total_cost_basis, dec(300));
+    }
+
+    #[test]
+    fn test_germany_exempts_after_one_year() {
+        let events = vec![
+            buy("b1", "2023-01-10", dec(1), dec(100)),
+            buy("b2", "2024-01-01", dec(1), dec(200)),
+            sell("s1", "2024-01-11", dec(2), dec(1000)),
+        ];
+        let report = calculate(&events, &Jurisdiction::De.rules(), CostBasisMethod::Fifo);
+
+        assert!(report.disposals[0].exempt);
+        assert!(!report.disposals[1].exempt);
+        assert_eq!(report.exempt_gain, dec(400));
+        assert_eq!(report.taxable_gain, dec(300));
+    }
+
+    #[test]
+    fn test_one_year_boundary_is_exclusive() {
+        let acquired = buy("b1", "2023-03-15", dec(1), dec(0)).timestamp;
+        let anniversary = buy("x", "2024-03-15", dec(1), dec(0)).timestamp;
+        let day_after = buy("x", "2024-03-16", dec(1), dec(0)).timestamp;
+        assert!(!held_longer_than(acquired, anniversary, 12));
+        assert!(held_longer_than(acquired, day_after, 12));
+    }
+
+    #[test]
+    fn test_canada_average_cost_with_inclusion_rate() {
+        let events = vec![
+            buy("b1", "2024-01-01", dec(1), dec(100)),
+            buy("b2", "2024-02-01", dec(1), dec(300)),
+            sell("s1", "2024-03-01", dec(1), dec(500)),
+        ];
+        let report = calculate(&events, &Jurisdiction::Ca.rules(), CostBasisMethod::Fifo);
+
+        assert_eq!(report.method, None);
+        assert_eq!(report.disposals[0].rule, MatchRule::Pool);
+        assert_eq!(report.total_cost_basis, dec(200));
+        assert_eq!(report.total_gain, dec(300));
+        assert_eq!(report.taxable_gain, dec(150));
+        assert_eq!(report.open_lots[0].cost_basis, dec(200));
+    }
+
+    #[test]
+    fn test_uk_matching_rules() {
+        let events = vec![
+            buy("b1", "2024-01-01", dec(10), dec(1000)),
+            sell("s1", "2024-03-01", dec(6), dec(1200)),
+            buy("b2", "2024-03-01", dec(2), dec(500)),
+            buy("b3", "2024-03-20", dec(1), dec(150)),
+        ];
+        let report = calculate(&events, &Jurisdiction::Uk.rules(), CostBasisMethod::Fifo);
+
+        let rules: Vec<MatchRule> = report.disposals.iter().map(|d| d.rule).collect();
+        assert_eq!(
+            rules,
+            vec![
+                MatchRule::SameDay,
+                MatchRule::BedAndBreakfast,
+                MatchRule::Pool
+            ]
+        );
+        assert_eq!(report.disposals[0].cost_basis, dec(500));
+        assert_eq!(report.disposals[1].cost_basis, dec(150));
+        assert_eq!(report.disposals[2].cost_basis, dec(300));
+        assert_eq!(report.total_proceeds, dec(1200));
+        assert_eq!(report.open_lots[0].quantity, dec(7));
+        assert_eq!(report.open_lots[0].cost_basis, dec(700));
+    }
+
+    #[test]
+    fn test_shortfall_warns_and_uses_zero_cost() {
+        let events = vec![
+            buy("b1", "2024-01-01", dec(1), dec(100)),
+            sell("s1", "2024-02-01", dec(2), dec(400)),
+        ];
+        let report = calculate(&events, &Jurisdiction::Us.rules(), CostBasisMethod::Fifo);
+
+        assert_eq!(report.disposals.len(), 2);
+        assert_eq!(report.disposals[1].rule, MatchRule::Unmatched);
+        assert_eq!(report.total_gain, dec(300));
+        assert_eq!(report.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_income_creates_lot_at_fair_value() {
+        let mut reward = buy("r1", "2024-01-01", dec(10), dec(50));
+        reward.kind = AssetEventKind::Income;
+        reward.income_source = Some(IncomeSource::Staking);
+        let mut airdrop = buy("a1", "2024-01-05", dec(5), dec(20));
+        airdrop.kind = AssetEventKind::Income;
+        airdrop.income_source = Some(IncomeSource::Airdrop);
+        let events = vec![reward, airdrop, sell("s1", "2024-02-01", dec(10), dec(80))];
+        let report = calculate(&events, &Jurisdiction::Us.rules(), CostBasisMethod::Fifo);
+
+        assert_eq!(report.income.total, dec(70));
+        assert_eq!(report.income.by_source.len(), 2);
+        assert_eq!(report.income.by_source[0].source, IncomeSource::Airdrop);
+        assert_eq!(report.disposals[0].acquisition_id.as_deref(), Some("r1"));
+        assert_eq!(report.total_gain, dec(30));
+        assert_eq!(report.open_lots[0].cost_basis, dec(20));
+    }
+
+    #[test]
+    fn test_overridden_prices_are_noted_per_section() {
+        let mut lp = buy("b1", "2024-01-01", dec(10), dec(100));
+        lp.price_override = Some("o1".to_string());
+        let events = vec![
+            lp,
+            buy("b2", "2024-01-02", dec(10), dec(200)),
+            sell("s1", "2024-02-01", dec(5), dec(80)),
+        ];
+        let report = calculate(&events, &Jurisdiction::Us.rules(), CostBasisMethod::Fifo);
+
+        assert_eq!(report.price_overrides.len(), 2);
+        let disposals = &report.price_overrides[0];
+        assert_eq!(disposals.section, ReportSection::Disposals);
+        assert_eq!(disposals.override_ids, vec!["o1".to_string()]);
+        assert_eq!(disposals.event_ids, vec!["s1".to_string()]);
+        let holdings = &report.price_overrides[1];
+        assert_eq!(holdings.section, ReportSection::OpenLots);
+        assert_eq!(holdings.event_ids, vec!["b1".to_string()]);
+
+        let plain = calculate(
+            &[buy("b3", "2024-01-01", dec(1), dec(1))],
+            &Jurisdiction::Us.rules(),
+            CostBasisMethod::Fifo,
+        );
+        assert!(plain.price_overrides.is_empty());
+    }
+}
@@ -0,0 +1,309 @@
+//! Wash sale and superficial loss detection.
+//!
+//! A loss is deferred when the same asset is acquired within the
+//! jurisdiction's window before or after the disposal. The disallowed part of
+//! the loss is added to the cost of the replacement acquisition, so it is
+//! recognized later when the replacement is sold. The replacement's holding
+//! period is not extended.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::{AssetEvent, AssetEventKind, CostBasisReport, Disposal, JurisdictionRules};
+
+/// Which loss deferral rule applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LossDeferralKind {
+    /// US wash sale rule.
+    WashSale,
+    /// Canadian superficial loss rule.
+    SuperficialLoss,
+}
+
+/// How a jurisdiction defers losses on repurchases.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LossDeferralRule {
+    /// Rule name reported on deferrals.
+    pub kind: LossDeferralKind,
+    /// Days before and after the disposal in which an acquisition counts as a
+    /// replacement.
+    pub window_days: i64,
+    /// Whether the asset must still be held at the end of the window for the
+    /// loss to be deferred.
+    pub requires_holding_at_window_end: bool,
+}
+
+/// A loss, or part of one, deferred to a replacement acquisition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LossDeferral {
+    /// Rule that applied.
+    pub kind: LossDeferralKind,
+    /// ID of the disposal event that realized the loss.
+    pub event_id: String,
+    /// Asset disposed.
+    pub asset: String,
+    /// When the loss was realized.
+    pub disposed_at: DateTime<Utc>,
+    /// Lot sold at a loss; `None` when sold from a pool.
+    pub sold_acquisition_id: Option<String>,
+    /// Acquisition treated as the replacement.
+    pub replacement_acquisition_id: String,
+    /// When the replacement was acquired.
+    pub replacement_acquired_at: DateTime<Utc>,
+    /// Quantity of the replacement covering the loss.
+    pub quantity: Decimal,
+    /// Loss disallowed and added to the replacement's cost.
+    pub disallowed_loss: Decimal,
+}
+
+/// Net quantity of `asset` held at `at`.
+fn holding_at(events: &[AssetEvent], asset: &str, at: DateTime<Utc>) -> Decimal {
+    events
+        .iter()
+        .filter(|e| e.asset == asset && e.timestamp <= at && e.quantity > Decimal::ZERO)
+        .map(|e| match e.kind {
+            AssetEventKind::Acquisition | AssetEventKind::Income => e.quantity,
+            AssetEventKind::Disposal => -e.quantity,
+        })
+        .sum()
+}
+
+/// Finds losses in `disposals` with a replacement acquisition in the window.
+///
+/// Each acquisition can replace at most its own quantity across all losses,
+/// and acquisitions consumed by a disposal can't replace that same disposal.
+pub(super) fn detect(
+    events: &[AssetEvent],
+    disposals: &[Disposal],
+    rule: &LossDeferralRule,
+) -> Vec<LossDeferral> {
+    let window = Duration::days(rule.window_days);
+
+    let mut acquisitions: Vec<&AssetEvent> = events
+        .iter()
+        .filter(|e| e.kind.adds_holdings() && e.quantity > Decimal::ZERO)
+        .collect();
+    acquisitions.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.id.cmp(&b.id)));
+
+    let mut capacity: HashMap<&str, Decimal> = acquisitions
+        .iter()
+        .map(|a| (a.id.as_str(), a.quantity))
+        .collect();
+
+    let mut sold: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for d in disposals {
+        if let Some(id) = &d.acquisition_id {
+            sold.entry(d.event_id.as_str())
+                .or_default()
+                .insert(id.as_str());
+        }
+    }
+
+    let mut deferrals = Vec::new();
+    for row in disposals.iter().filter(|d| d.gain < Decimal::ZERO) {
+        let first_day = (row.disposed_at - window).date_naive();
+        let last_day = (row.disposed_at + window).date_naive();
+        let sold_here = sold.get(row.event_id.as_str());
+
+        let mut replaceable = row.quantity;
+        if rule.requires_holding_at_window_end {
+            let end_of_window = (row.disposed_at + window)
+                .date_naive()
+                .and_hms_opt(23, 59, 59)
+                .map(|t| t.and_utc())
+                .unwrap_or(row.disposed_at + window);
+            let held = holding_at(events, &row.asset, end_of_window);
+            replaceable = replaceable.min(held.max(Decimal::ZERO));
+        }
+
+        for acquisition in &acquisitions {
+            if replaceable <= Decimal::ZERO {
+                break;
+            }
+            let day = acquisition.timestamp.date_naive();
+            if acquisition.asset != row.asset
+                || day < first_day
+                || day > last_day
+                || sold_here.is_some_and(|ids| ids.contains(acquisition.id.as_str()))
+            {
+                continue;
+            }
+            let Some(available) = capacity.get_mut(acquisition.id.as_str()) else {
+                continue;
+            };
+            let quantity = replaceable.min(*available);
+            if quantity <= Decimal::ZERO {
+                continue;
+            }
+            *available -= quantity;
+            replaceable -= quantity;
+
+            deferrals.push(LossDeferral {
+                kind: rule.kind,
+                event_id: row.event_id.clone(),
+                asset: row.asset.clone(),
+                disposed_at: row.disposed_at,
+                sold_acquisition_id: row.acquisition_id.clone(),
+                replacement_acquisition_id: acquisition.id.clone(),
+                replacement_acquired_at: acquisition.timestamp,
+                quantity,
+                disallowed_loss: -row.gain * quantity / row.quantity,
+            });
+        }
+    }
+
+    deferrals
+}
+
+/// Returns `events` with each disallowed loss added to its replacement's cost.
+pub(super) fn adjust_replacement_costs(
+    events: &[AssetEvent],
+    deferrals: &[LossDeferral],
+) -> Vec<AssetEvent> {
+    let mut adjustments: HashMap<&str, Decimal> = HashMap::new();
+    for deferral in deferrals {
+        *adjustments
+            .entry(deferral.replacement_acquisition_id.as_str())
+            .or_default() += deferral.disallowed_loss;
+    }
+
+    events
+        .iter()
+        .map(|event| {
+            let mut event = event.clone();
+            if event.kind.adds_holdings() {
+                if let Some(adjustment) = adjustments.get(event.id.as_str()) {
+                    event.value += *adjustment;
+                }
+            }
+            event
+        })
+        .collect()
+}
+
+/// Marks the deferred losses on the recomputed disposal rows and records the
+/// deferrals on the report.
+pub(super) fn apply(
+    report: &mut CostBasisReport,
+    deferrals: Vec<LossDeferral>,
+    rules: &JurisdictionRules,
+) {
+    for deferral in &deferrals {
+        let row = report.disposals.iter_mut().find(|d| {
+            d.event_id == deferral.event_id
+                && d.acquisition_id == deferral.sold_acquisition_id
+                && d.gain + d.disallowed_loss < Decimal::ZERO
+        });
+        let Some(row) = row else {
+            report.warnings.push(format!(
+                "Could not apply deferred loss of {} on disposal {}",
+                deferral.disallowed_loss, deferral.event_id
+            ));
+            continue;
+        };
+
+        let remaining_loss = -(row.gain + row.disallowed_loss);
+        row.disallowed_loss += deferral.disallowed_loss.min(remaining_loss);
+        row.taxable_gain = if row.exempt {
+            Decimal::ZERO
+        } else {
+            (row.gain + row.disallowed_loss) * rules.inclusion_rate
+        };
+    }
+
+    report.loss_deferrals = deferrals;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{calculate, CostBasisMethod, Jurisdiction};
+    use super::*;
+
+    fn event(id: &str, kind: AssetEventKind, date: &str, quantity: i64, value: i64) -> AssetEvent {
+        AssetEvent {
+            id: id.to_string(),
+            asset: "ETH".to_string(),
+            kind,
+            timestamp: DateTime::parse_from_rfc3339(&format!("{}T12:00:00Z", date))
+                .unwrap()
+                .with_timezone(&Utc),
+            quantity: Decimal::from(quantity),
+            value: Decimal::from(value),
+            fee: Decimal::ZERO,
+            income_source: None,
+            price_override: None,
+            lot_ids: Vec::new(),
+        }
+    }
+
+    fn buy(id: &str, date: &str, quantity: i64, value: i64) -> AssetEvent {
+        event(id, AssetEventKind::Acquisition, date, quantity, value)
+    }
+
+    fn sell(id: &str, date: &str, quantity: i64, value: i64) -> AssetEvent {
+        event(id, AssetEventKind::Disposal, date, quantity, value)
+    }
+
+    #[test]
+    fn test_us_wash_sale_moves_loss_to_replacement() {
+        let events = vec![
+            buy("b1", "2024-01-01", 1, 1000),
+            sell("s1", "2024-02-01", 1, 600),
+            buy("b2", "2024-02-10", 1, 500),
+        ];
+        let report = calculate(&events, &Jurisdiction::Us.rules(), CostBasisMethod::Fifo);
+
+        assert_eq!(report.loss_deferrals.len(), 1);
+        assert_eq!(report.loss_deferrals[0].replacement_acquisition_id, "b2");
+        assert_eq!(report.disposals[0].gain, Decimal::from(-400));
+        assert_eq!(report.disposals[0].disallowed_loss, Decimal::from(400));
+        assert_eq!(report.taxable_gain, Decimal::ZERO);
+        assert_eq!(report.open_lots[0].cost_basis, Decimal::from(900));
+    }
+
+    #[test]
+    fn test_sold_lot_is_not_its_own_replacement() {
+        let events = vec![
+            buy("b1", "2024-01-01", 1, 1000),
+            sell("s1", "2024-01-15", 1, 600),
+            buy("b2", "2024-04-01", 1, 500),
+        ];
+        let report = calculate(&events, &Jurisdiction::Us.rules(), CostBasisMethod::Fifo);
+
+        assert!(report.loss_deferrals.is_empty());
+        assert_eq!(report.taxable_gain, Decimal::from(-400));
+    }
+
+    #[test]
+    fn test_canada_superficial_loss_limited_to_holding() {
+        let events = vec![
+            buy("b1", "2024-01-01", 2, 200),
+            sell("s1", "2024-03-01", 2, 100),
+            buy("b2", "2024-03-10", 1, 40),
+        ];
+        let report = calculate(&events, &Jurisdiction::Ca.rules(), CostBasisMethod::Fifo);
+
+        assert_eq!(report.loss_deferrals.len(), 1);
+        assert_eq!(report.loss_deferrals[0].quantity, Decimal::ONE);
+        assert_eq!(report.disallowed_loss, Decimal::from(50));
+        assert_eq!(report.taxable_gain, Decimal::from(-25));
+        assert_eq!(report.open_lots[0].cost_basis, Decimal::from(90));
+    }
+
+    #[test]
+    fn test_uk_has_no_separate_deferral() {
+        let events = vec![
+            buy("b1", "2024-01-01", 1, 1000),
+            sell("s1", "2024-02-01", 1, 600),
+            buy("b2", "2024-05-10", 1, 500),
+        ];
+        let report = calculate(&events, &Jurisdiction::Uk.rules(), CostBasisMethod::Fifo);
+        assert!(report.loss_deferrals.is_empty());
+    }
+}
@@ -1,8 +1,52 @@
 #![allow(dead_code)]
 
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+/// Fiat currencies whose minor unit is not cents (ISO 4217).
+const MINOR_UNIT_EXCEPTIONS: &[(&str, u32)] = &[
+    ("BHD", 3),
+    ("BIF", 0),
+    ("CLP", 0),
+    ("DJF", 0),
+    ("GNF", 0),
+    ("IQD", 3),
+    ("ISK", 0),
+    ("JOD", 3),
+    ("JPY", 0),
+    ("KMF", 0),
+    ("KRW", 0),
+    ("KWD", 3),
+    ("LYD", 3),
+    ("OMR", 3),
+    ("PYG", 0),
+    ("RWF", 0),
+    ("TND", 3),
+    ("UGX", 0),
+    ("VND", 0),
+    ("VUV", 0),
+    ("XAF", 0),
+    ("XOF", 0),
+    ("XPF", 0),
+];
+
+/// Number of decimal places in a fiat currency's minor unit, e.g. 2 for USD
+/// and 0 for JPY.
+pub fn fiat_decimals(code: &str) -> u32 {
+    MINOR_UNIT_EXCEPTIONS
+        .iter()
+        .find(|(c, _)| c.eq_ignore_ascii_case(code))
+        .map(|(_, decimals)| *decimals)
+        .unwrap_or(2)
+}
+
+/// Rounds a fiat amount to the currency's minor unit. Halves round away from
+/// zero, as on invoices and statements.
+pub fn round_fiat(amount: Decimal, code: &str) -> Decimal {
+    amount.round_dp_with_strategy(fiat_decimals(code), RoundingStrategy::MidpointAwayFromZero)
+}
+
 /// Currency type enumeration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
 #[sqlx(type_name = "TEXT")]
@@ -251,6 +295,8 @@ pub struct TransactionWithConversion {
     pub exchange_rate: Option<String>,
     /// Optional source of the exchange rate.
     pub exchange_rate_source: Option<String>,
+    /// Optional endpoint the exchange rate was read from.
+    pub exchange_rate_endpoint: Option<String>,
     /// Optional timestamp when exchange rate was applied.
     pub exchange_rate_timestamp: Option<String>,
     /// Timestamp when the transaction record was created.
@@ -305,4 +351,24 @@ mod tests {
         settings.set_reporting_currencies(vec!["CAD".to_string(), "AUD".to_string()]);
         assert_eq!(settings.reporting_currencies, Some("CAD,AUD".to_string()));
     }
+
+    #[test]
+    fn test_fiat_decimals() {
+        assert_eq!(fiat_decimals("USD"), 2);
+        assert_eq!(fiat_decimals("eur"), 2);
+        assert_eq!(fiat_decimals("JPY"), 0);
+        assert_eq!(fiat_decimals("KWD"), 3);
+    }
+
+    #[test]
+    fn test_round_fiat() {
+        let dec = |s: &str| s.parse::<Decimal>().unwrap();
+        assert_eq!(round_fiat(dec("10.005"), "USD"), dec("10.01"));
+        assert_eq!(round_fiat(dec("-10.005"), "USD"), dec("-10.01"));
+        assert_eq!(round_fiat(dec("10.004"), "USD"), dec("10.00"));
+        assert_eq!(round_fiat(dec("1234.5"), "JPY"), dec("1235"));
+        assert_eq!(round_fiat(dec("1.23456"), "BHD"), dec("1.235"));
+        // The classic f64 failure: 0.1 + 0.2 is exactly 0.3
+        assert_eq!(round_fiat(dec("0.1") + dec("0.2"), "USD"), dec("0.3"));
+    }
 }
@@ -6,8 +6,8 @@ use sqlx::{Pool, Sqlite};
 use std::str::FromStr;
 
 use super::currency::{
-    AccountSettings, ConversionMethod, Currency, CurrencyConversion, ExchangeRate,
-    TransactionWithConversion,
+    round_fiat, AccountSettings, ConversionMethod, Currency, CurrencyConversion, CurrencyType,
+    ExchangeRate, TransactionWithConversion,
 };
 
 /// Currency Service for database operations and conversions
@@ -213,7 +213,12 @@ impl CurrencyService {
         };
 
         let amount_decimal = Decimal::from_str(amount).context("Failed to parse amount")?;
-        let converted = amount_decimal * rate_value;
+        let mut converted = amount_decimal * rate_value;
+        if let Some(currency) = self.get_currency(to_currency).await? {
+            if currency.currency_type == CurrencyType::Fiat {
+                converted = round_fiat(converted, &currency.code);
+            }
+        }
 
         Ok(CurrencyConversion {
             from_currency: from_currency.to_string(),
@@ -233,6 +238,7 @@ impl CurrencyService {
         primary_currency: &str,
         exchange_rate: &str,
         exchange_rate_source: &str,
+        exchange_rate_endpoint: &str,
         exchange_rate_timestamp: &str,
     ) -> Result<()> {
         sqlx::query(
@@ -243,6 +249,7 @@ impl CurrencyService {
                 primary_currency = ?,
                 exchange_rate = ?,
                 exchange_rate_source = ?,
+                exchange_rate_endpoint = ?,
                 exchange_rate_timestamp = ?,
                 updated_at = datetime('now')
             WHERE id = ?
@@ -252,6 +259,7 @@ impl CurrencyService {
         .bind(primary_currency)
         .bind(exchange_rate)
         .bind(exchange_rate_source)
+        .bind(exchange_rate_endpoint)
         .bind(exchange_rate_timestamp)
         .bind(transaction_id)
         .execute(&self.pool)
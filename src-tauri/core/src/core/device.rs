@@ -0,0 +1,216 @@
+//! Device metadata captured at login.
+//!
+//! The desktop app has no HTTP request to read client details from, so the
+//! session metadata is collected from the host: hostname, operating system,
+//! app version, and the local network address. Comparing it with earlier
+//! sessions flags logins from unfamiliar devices or networks.
+
+use std::net::UdpSocket;
+
+/// Device and network details for a session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// Device name, the hostname unless the client supplied one.
+    pub device_name: String,
+    /// Device type (desktop, web, mobile).
+    pub device_type: String,
+    /// Operating system and architecture, e.g. "macos aarch64".
+    pub os: String,
+    /// Pacioli version.
+    pub app_version: String,
+    /// Local network address, if one could be determined.
+    pub ip_address: Option<String>,
+}
+
+impl DeviceInfo {
+    /// Collects details for the machine the app is running on.
+    pub fn current() -> Self {
+        let hostname = gethostname::gethostname().to_string_lossy().into_owned();
+        Self {
+            device_name: if hostname.is_empty() {
+                "Unknown device".to_string()
+            } else {
+                hostname
+            },
+            device_type: "desktop".to_string(),
+            os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            ip_address: local_ip_address(),
+        }
+    }
+
+    /// Replaces the name and type with values supplied by the client.
+    pub fn with_overrides(mut self, device_name: Option<&str>, device_type: Option<&str>) -> Self {
+        if let Some(name) = device_name.map(str::trim).filter(|n| !n.is_empty()) {
+            self.device_name = name.to_string();
+        }
+        if let Some(kind) = device_type.map(str::trim).filter(|t| !t.is_empty()) {
+            self.device_type = kind.to_string();
+        }
+        self
+    }
+
+    /// User agent string stored with the session, e.g.
+    /// `Pacioli/0.1.0 (linux x86_64)`.
+    pub fn user_agent(&self) -> String {
+        format!("Pacioli/{} ({})", self.app_version, self.os)
+    }
+
+    /// Short description for alerts, e.g. `work-laptop (macos aarch64)`.
+    pub fn describe(&self) -> String {
+        format!("{} ({})", self.device_name, self.os)
+    }
+}
+
+/// Returns the address of the interface used for outbound traffic.
+///
+/// Connecting a UDP socket only selects a route; no packets are sent.
+fn local_ip_address() -> Option<String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_unspecified()).then(|| ip.to_string())
+}
+
+/// Extracts the OS part from a user agent built by [`DeviceInfo::user_agent`].
+fn user_agent_os(user_agent: &str) -> Option<&str> {
+    let start = user_agent.find('(')? + 1;
+    let end = user_agent[start..].find(')')? + start;
+    Some(&user_agent[start..end])
+}
+
+/// Metadata recorded for an earlier session.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SessionFingerprint {
+    /// Device name of the session.
+    pub device_name: Option<String>,
+    /// User agent of the session.
+    pub user_agent: Option<String>,
+    /// Network address of the session.
+    pub ip_address: Option<String>,
+}
+
+/// Why a login looks unusual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginAnomaly {
+    /// No earlier session came from this device.
+    NewDevice,
+    /// No earlier session came from this network address.
+    NewNetwork,
+}
+
+impl LoginAnomaly {
+    /// Human-readable description for alerts and the audit log.
+    pub fn description(self) -> &'static str {
+        match self {
+            LoginAnomaly::NewDevice => "new device",
+            LoginAnomaly::NewNetwork => "new network location",
+        }
+    }
+}
+
+/// Compares a login with the user's earlier sessions.
+///
+/// A user's first login is never flagged. A device matches when both the
+/// name and operating system match, so app upgrades don't raise alerts.
+pub fn detect_anomalies(history: &[SessionFingerprint], device: &DeviceInfo) -> Vec<LoginAnomaly> {
+    if history.is_empty() {
+        return Vec::new();
+    }
+
+    let mut anomalies = Vec::new();
+
+    let known_device = history.iter().any(|s| {
+        s.device_name.as_deref() == Some(device.device_name.as_str())
+            && s.user_agent.as_deref().and_then(user_agent_os) == Some(device.os.as_str())
+    });
+    if !known_device {
+        anomalies.push(LoginAnomaly::NewDevice);
+    }
+
+    if let Some(ip) = device.ip_address.as_deref() {
+        let has_ip_history = history.iter().any(|s| s.ip_address.is_some());
+        let known_ip = history.iter().any(|s| s.ip_address.as_deref() == Some(ip));
+        if has_ip_history && !known_ip {
+            anomalies.push(LoginAnomaly::NewNetwork);
+        }
+    }
+
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device() -> DeviceInfo {
+        DeviceInfo {
+            device_name: "work-laptop".to_string(),
+            device_type: "desktop".to_string(),
+            os: "macos aarch64".to_string(),
+            app_version: "0.2.0".to_string(),
+            ip_address: Some("192.168.1.20".to_string()),
+        }
+    }
+
+    fn session(name: &str, user_agent: &str, ip: Option<&str>) -> SessionFingerprint {
+        SessionFingerprint {
+            device_name: Some(name.to_string()),
+            user_agent: Some(user_agent.to_string()),
+            ip_address: ip.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_user_agent_round_trip() {
+        let ua = device().user_agent();
+        assert_eq!(ua, "Pacioli/0.2.0 (macos aarch64)");
+        assert_eq!(user_agent_os(&ua), Some("macos aarch64"));
+        assert_eq!(user_agent_os("curl/8.0"), None);
+    }
+
+    #[test]
+    fn test_first_login_is_not_anomalous() {
+        assert!(detect_anomalies(&[], &device()).is_empty());
+    }
+
+    #[test]
+    fn test_known_device_after_upgrade() {
+        let history = vec![session(
+            "work-laptop",
+            "Pacioli/0.1.0 (macos aarch64)",
+            Some("192.168.1.20"),
+        )];
+        assert!(detect_anomalies(&history, &device()).is_empty());
+    }
+
+    #[test]
+    fn test_new_device_and_network() {
+        let history = vec![session(
+            "home-desktop",
+            "Pacioli/0.2.0 (windows x86_64)",
+            Some("10.0.0.5"),
+        )];
+        assert_eq!(
+            detect_anomalies(&history, &device()),
+            vec![LoginAnomaly::NewDevice, LoginAnomaly::NewNetwork]
+        );
+    }
+
+    #[test]
+    fn test_network_ignored_without_ip_history() {
+        let history = vec![SessionFingerprint {
+            device_name: Some("work-laptop".to_string()),
+            user_agent: Some("Pacioli/0.2.0 (macos aarch64)".to_string()),
+            ip_address: None,
+        }];
+        assert!(detect_anomalies(&history, &device()).is_empty());
+    }
+
+    #[test]
+    fn test_with_overrides() {
+        let d = device().with_overrides(Some(" Office Mac "), Some(""));
+        assert_eq!(d.device_name, "Office Mac");
+        assert_eq!(d.device_type, "desktop");
+    }
+}
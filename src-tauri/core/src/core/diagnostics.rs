@@ -0,0 +1,389 @@
+//! Local crash reports and diagnostics.
+//!
+//! Nothing here is sent anywhere. Errors and warnings logged through
+//! [`log_error!`](crate::log_error) and [`log_info!`](crate::log_info) are
+//! kept in a short in-memory buffer. When the app panics, the panic hook
+//! writes a crash report with the panic message, a backtrace, the app
+//! version, and the buffered lines to the crash report directory. The user
+//! can then export the reports as a diagnostics bundle to attach to a bug
+//! report.
+//!
+//! Lines are redacted as they are recorded: email addresses and wallet
+//! addresses are replaced and the home directory is shortened to `~`, so
+//! reports carry no personal data.
+
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Directory under the app data directory holding crash reports.
+pub const CRASH_REPORT_DIR: &str = "crash_reports";
+
+/// Number of recent log lines kept for crash reports.
+pub const MAX_LOG_LINES: usize = 200;
+
+/// Number of crash reports kept on disk; older ones are deleted.
+const MAX_STORED_REPORTS: usize = 20;
+
+/// Format version of exported diagnostics bundles.
+const BUNDLE_FORMAT_VERSION: &str = "pacioli-diagnostics-v1";
+
+/// Shortest run of letters and digits treated as an encoded address.
+const MIN_ENCODED_ADDRESS_LEN: usize = 26;
+
+static RECENT_LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+static CRASH_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Prints to stderr and keeps the line for crash reports.
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => {{
+        let line = format!($($arg)*);
+        eprintln!("{}", line);
+        $crate::core::diagnostics::record(&line);
+    }};
+}
+
+/// Prints to stdout and keeps the line for crash reports.
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => {{
+        let line = format!($($arg)*);
+        println!("{}", line);
+        $crate::core::diagnostics::record(&line);
+    }};
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A crash report written when the app panicked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    /// When the panic happened.
+    pub occurred_at: DateTime<Utc>,
+    /// App version.
+    pub app_version: String,
+    /// Operating system.
+    pub os: String,
+    /// CPU architecture.
+    pub arch: String,
+    /// Name of the thread that panicked, if it has one.
+    pub thread: Option<String>,
+    /// Panic message and location.
+    pub message: String,
+    /// Backtrace of the panicking thread.
+    pub backtrace: String,
+    /// Log lines leading up to the panic, oldest first.
+    pub recent_log: Vec<String>,
+}
+
+/// Diagnostics exported for a bug report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsBundle {
+    /// Bundle format version.
+    pub format_version: String,
+    /// When the bundle was made.
+    pub generated_at: DateTime<Utc>,
+    /// App version.
+    pub app_version: String,
+    /// Operating system.
+    pub os: String,
+    /// CPU architecture.
+    pub arch: String,
+    /// Log lines from the current session, oldest first.
+    pub recent_log: Vec<String>,
+    /// Stored crash reports, newest first.
+    pub crash_reports: Vec<CrashReport>,
+}
+
+// ============================================================================
+// Log buffer
+// ============================================================================
+
+/// Keeps a redacted, timestamped copy of `line`, dropping the oldest line
+/// once [`MAX_LOG_LINES`] are held.
+pub fn record(line: &str) {
+    let line = format!(
+        "{} {}",
+        Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        redact(line)
+    );
+    let mut lines = RECENT_LINES.lock().unwrap_or_else(|e| e.into_inner());
+    if lines.len() == MAX_LOG_LINES {
+        lines.pop_front();
+    }
+    lines.push_back(line);
+}
+
+/// The buffered log lines, oldest first.
+pub fn recent_lines() -> Vec<String> {
+    RECENT_LINES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .cloned()
+        .collect()
+}
+
+/// Replaces email addresses and wallet addresses in `text`, and shortens the
+/// home directory to `~`.
+pub fn redact(text: &str) -> String {
+    let text = match home_dir() {
+        Some(home) if home.len() > 1 => text.replace(&home, "~"),
+        _ => text.to_string(),
+    };
+
+    let mut redacted = String::with_capacity(text.len());
+    let mut word = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() || matches!(c, '@' | '.' | '_' | '-' | '+') {
+            word.push(c);
+        } else {
+            redacted.push_str(&redact_word(&word));
+            word.clear();
+            redacted.push(c);
+        }
+    }
+    redacted.push_str(&redact_word(&word));
+    redacted
+}
+
+fn redact_word(word: &str) -> String {
+    let trimmed = word.trim_end_matches('.');
+    let suffix = &word[trimmed.len()..];
+
+    let replacement = if is_email(trimmed) {
+        "<email>"
+    } else if is_hex_address(trimmed) || is_encoded_address(trimmed) {
+        "<address>"
+    } else {
+        return word.to_string();
+    };
+    format!("{}{}", replacement, suffix)
+}
+
+fn is_email(word: &str) -> bool {
+    match word.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty() && domain.contains('.') && !domain.starts_with('.')
+        }
+        None => false,
+    }
+}
+
+/// EVM addresses and transaction hashes.
+fn is_hex_address(word: &str) -> bool {
+    word.strip_prefix("0x")
+        .is_some_and(|hex| hex.len() >= 40 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Base58 and bech32 addresses (SS58, Solana, Bitcoin): long runs mixing
+/// letters and digits.
+fn is_encoded_address(word: &str) -> bool {
+    word.len() >= MIN_ENCODED_ADDRESS_LEN
+        && word.chars().all(|c| c.is_ascii_alphanumeric())
+        && word.chars().any(|c| c.is_ascii_digit())
+        && word.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+fn home_dir() -> Option<String> {
+    std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()
+}
+
+// ============================================================================
+// Crash reports
+// ============================================================================
+
+/// Installs a panic hook that writes a crash report to `dir` before running
+/// the previous hook.
+pub fn install_panic_hook(dir: PathBuf) {
+    *CRASH_DIR.write().unwrap_or_else(|e| e.into_inner()) = Some(dir);
+
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let dir = CRASH_DIR.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(dir) = dir {
+            // The panic may have happened while the buffer was locked
+            let recent_log = RECENT_LINES
+                .try_lock()
+                .map(|lines| lines.iter().cloned().collect())
+                .unwrap_or_default();
+            let report = CrashReport {
+                occurred_at: Utc::now(),
+                app_version: env!("CARGO_PKG_VERSION").to_string(),
+                os: std::env::consts::OS.to_string(),
+                arch: std::env::consts::ARCH.to_string(),
+                thread: std::thread::current().name().map(str::to_string),
+                message: redact(&info.to_string()),
+                backtrace: redact(&Backtrace::force_capture().to_string()),
+                recent_log,
+            };
+            if let Err(e) = write_crash_report(&dir, &report) {
+                eprintln!("Failed to write crash report: {}", e);
+            }
+        }
+        previous(info);
+    }));
+}
+
+/// The directory crash reports are written to, once the panic hook is
+/// installed.
+pub fn crash_report_dir() -> Option<PathBuf> {
+    CRASH_DIR.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Writes `report` to `dir` and deletes the oldest reports beyond
+/// [`MAX_STORED_REPORTS`].
+pub fn write_crash_report(dir: &Path, report: &CrashReport) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "crash-{}.json",
+        report.occurred_at.format("%Y%m%dT%H%M%S%.3fZ")
+    ));
+    let json = serde_json::to_vec_pretty(report).map_err(std::io::Error::other)?;
+    std::fs::write(&path, json)?;
+
+    let files = report_files(dir)?;
+    for old in files.iter().skip(MAX_STORED_REPORTS) {
+        std::fs::remove_file(old)?;
+    }
+    Ok(path)
+}
+
+/// Crash report files in `dir`, newest first.
+fn report_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let is_report = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("crash-") && name.ends_with(".json"));
+        if is_report {
+            files.push(path);
+        }
+    }
+    // Names sort by the time they were written
+    files.sort_by(|a, b| b.cmp(a));
+    Ok(files)
+}
+
+/// Reads the crash reports in `dir`, newest first. Files that can't be read
+/// are skipped.
+pub fn read_crash_reports(dir: &Path) -> std::io::Result<Vec<CrashReport>> {
+    Ok(report_files(dir)?
+        .into_iter()
+        .filter_map(|path| {
+            let json = std::fs::read(&path).ok()?;
+            serde_json::from_slice(&json).ok()
+        })
+        .collect())
+}
+
+/// Deletes the crash reports in `dir`, returning how many were removed.
+pub fn clear_crash_reports(dir: &Path) -> std::io::Result<usize> {
+    let files = report_files(dir)?;
+    for path in &files {
+        std::fs::remove_file(path)?;
+    }
+    Ok(files.len())
+}
+
+/// Collects the crash reports in `dir` and this session's log lines.
+pub fn diagnostics_bundle(dir: &Path) -> std::io::Result<DiagnosticsBundle> {
+    Ok(DiagnosticsBundle {
+        format_version: BUNDLE_FORMAT_VERSION.to_string(),
+        generated_at: Utc::now(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        recent_log: recent_lines(),
+        crash_reports: read_crash_reports(dir)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn report(occurred_at: DateTime<Utc>) -> CrashReport {
+        CrashReport {
+            occurred_at,
+            app_version: "0.1.0".to_string(),
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+            thread: Some("main".to_string()),
+            message: "panicked at src/lib.rs:1:1:\nboom".to_string(),
+            backtrace: String::new(),
+            recent_log: vec!["Warning: something".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_redact_removes_personal_data() {
+        assert_eq!(
+            redact("Failed to email alice@example.com."),
+            "Failed to email <email>."
+        );
+        assert_eq!(
+            redact("Failed to sync 0x742d35Cc6634C0532925a3b844Bc9e7595f8B123: timeout"),
+            "Failed to sync <address>: timeout"
+        );
+        assert_eq!(
+            redact("balance for 5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY failed"),
+            "balance for <address> failed"
+        );
+        assert_eq!(
+            redact("Chain not supported: ethereum_classic"),
+            "Chain not supported: ethereum_classic"
+        );
+    }
+
+    #[test]
+    fn test_record_keeps_recent_lines() {
+        for i in 0..MAX_LOG_LINES + 5 {
+            record(&format!("line {}", i));
+        }
+        let lines = recent_lines();
+        assert_eq!(lines.len(), MAX_LOG_LINES);
+        let last = format!(" line {}", MAX_LOG_LINES + 4);
+        assert!(lines.iter().any(|line| line.ends_with(&last)));
+    }
+
+    #[test]
+    fn test_crash_reports_round_trip_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let start = Utc.with_ymd_and_hms(2026, 5, 1, 12, 0, 0).unwrap();
+        for i in 0..MAX_STORED_REPORTS + 2 {
+            write_crash_report(dir.path(), &report(start + Duration::seconds(i as i64))).unwrap();
+        }
+
+        let reports = read_crash_reports(dir.path()).unwrap();
+        assert_eq!(reports.len(), MAX_STORED_REPORTS);
+        assert_eq!(
+            reports[0],
+            report(start + Duration::seconds(MAX_STORED_REPORTS as i64 + 1))
+        );
+
+        assert_eq!(clear_crash_reports(dir.path()).unwrap(), MAX_STORED_REPORTS);
+        assert!(read_crash_reports(dir.path()).unwrap().is_empty());
+    }
+}
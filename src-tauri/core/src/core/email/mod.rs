@@ -0,0 +1,787 @@
+//! Email Service
+//!
+//! Provides email sending functionality for transactional emails
+//! like verification, password reset, and security alerts.
+//!
+//! Delivery goes through pluggable [`EmailProvider`]s. Resend is used when an
+//! API key is configured; an SMTP server configured in settings lets
+//! self-hosted installs send email without Resend. When several providers are
+//! registered they are tried in order, so SMTP also acts as a fallback when
+//! Resend fails.
+
+#![allow(dead_code)]
+
+mod resend;
+pub mod smtp;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+
+pub use resend::ResendProvider;
+pub use smtp::{SmtpProvider, SmtpSecurity, SmtpSettings};
+
+/// Registered providers, in the order they are tried.
+static PROVIDERS: RwLock<Vec<Arc<dyn EmailProvider>>> = RwLock::new(Vec::new());
+
+/// Initialize the email service with a Resend API key
+///
+/// Resend is registered ahead of any other provider.
+pub fn init(api_key: String) {
+    let mut providers = PROVIDERS.write().unwrap_or_else(|e| e.into_inner());
+    providers.retain(|p| p.name() != resend::PROVIDER_NAME);
+    providers.insert(0, Arc::new(ResendProvider::new(api_key)));
+}
+
+/// Registers a provider, replacing any existing provider with the same name.
+pub fn register_provider(provider: Arc<dyn EmailProvider>) {
+    let mut providers = PROVIDERS.write().unwrap_or_else(|e| e.into_inner());
+    match providers.iter().position(|p| p.name() == provider.name()) {
+        Some(index) => providers[index] = provider,
+        None => providers.push(provider),
+    }
+}
+
+/// Removes the provider with the given name, if registered.
+pub fn remove_provider(name: &str) {
+    PROVIDERS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|p| p.name() != name);
+}
+
+/// Names of the registered providers, in the order they are tried.
+pub fn configured_providers() -> Vec<&'static str> {
+    PROVIDERS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|p| p.name())
+        .collect()
+}
+
+/// Whether any provider is available to send email.
+pub fn is_configured() -> bool {
+    !PROVIDERS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_empty()
+}
+
+/// Represents an email address with an optional display name.
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailAddress {
+    /// The email address.
+    pub email: String,
+    /// The optional display name of the email recipient.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl EmailAddress {
+    /// Creates a new `EmailAddress` with the given email.
+    pub fn new(email: impl Into<String>) -> Self {
+        Self {
+            email: email.into(),
+            name: None,
+        }
+    }
+
+    /// Creates a new `EmailAddress` with the given email and name.
+    pub fn with_name(email: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            email: email.into(),
+            name: Some(name.into()),
+        }
+    }
+}
+
+/// A rendered email ready for delivery.
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    /// Recipient address.
+    pub to: String,
+    /// Subject line.
+    pub subject: String,
+    /// HTML body.
+    pub html: String,
+    /// Optional plain-text alternative.
+    pub text: Option<String>,
+    /// Files attached to the message.
+    pub attachments: Vec<EmailAttachment>,
+}
+
+/// A file attached to an [`EmailMessage`].
+#[derive(Debug, Clone)]
+pub struct EmailAttachment {
+    /// File name shown to the recipient.
+    pub filename: String,
+    /// MIME type, e.g. `application/pdf`.
+    pub content_type: String,
+    /// File contents.
+    pub content: Vec<u8>,
+}
+
+/// A transport that can deliver an [`EmailMessage`].
+#[async_trait]
+pub trait EmailProvider: Send + Sync {
+    /// Short identifier used for registration and diagnostics.
+    fn name(&self) -> &'static str;
+
+    /// Delivers the message.
+    async fn send(&self, message: &EmailMessage) -> Result<(), String>;
+}
+
+/// Send an email through the registered providers
+///
+/// Providers are tried in order until one succeeds.
+pub async fn send_email(
+    to: &str,
+    subject: &str,
+    html_body: &str,
+    text_body: Option<&str>,
+) -> Result<(), String> {
+    send_email_with_attachments(to, subject, html_body, text_body, Vec::new()).await
+}
+
+/// Send an email with attached files through the registered providers
+pub async fn send_email_with_attachments(
+    to: &str,
+    subject: &str,
+    html_body: &str,
+    text_body: Option<&str>,
+    attachments: Vec<EmailAttachment>,
+) -> Result<(), String> {
+    let providers = PROVIDERS.read().unwrap_or_else(|e| e.into_inner()).clone();
+    if providers.is_empty() {
+        return Err("Email service not initialized".to_string());
+    }
+
+    let message = EmailMessage {
+        to: to.to_string(),
+        subject: subject.to_string(),
+        html: html_body.to_string(),
+        text: text_body.map(|s| s.to_string()),
+        attachments,
+    };
+
+    let mut errors = Vec::new();
+    for provider in providers {
+        match provider.send(&message).await {
+            Ok(()) => return Ok(()),
+            Err(e) => errors.push(format!("{}: {}", provider.name(), e)),
+        }
+    }
+
+    Err(errors.join("; "))
+}
+
+// =============================================================================
+// Email Templates
+// =============================================================================
+
+/// Send email verification for email change
+pub async fn send_email_change_verification(
+    to: &str,
+    verification_token: &str,
+    new_email: &str,
+) -> Result<(), String> {
+    let subject = "Verify your new email address - Pacioli";
+
+    // For now, just include the token. In production, this would be a clickable link.
+    let html_body = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+</head>
+<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, 'Helvetica Neue', Arial, sans-serif; line-height: 1.6; color: #333; max-width: 600px; margin: 0 auto; padding: 20px;">
+    <div style="background: linear-gradient(135deg, #283747 0%, #1a252f 100%); padding: 30px; border-radius: 10px 10px 0 0;">
+        <h1 style="color: #fff; margin: 0; font-size: 24px;">Pacioli</h1>
+        <p style="color: #94a3b8; margin: 5px 0 0 0; font-size: 14px;">Crypto-Inclusive Accounting Platform</p>
+    </div>
+
+    <div style="background: #fff; padding: 30px; border: 1px solid #e2e8f0; border-top: none; border-radius: 0 0 10px 10px;">
+        <h2 style="color: #283747; margin-top: 0;">Verify Your New Email Address</h2>
+
+        <p>You've requested to change your email address to:</p>
+        <p style="background: #f1f5f9; padding: 12px 16px; border-radius: 6px; font-family: monospace; font-size: 14px;">{}</p>
+
+        <p>To complete this change, use the verification code below:</p>
+
+        <div style="background: #283747; color: #fff; padding: 16px 24px; border-radius: 6px; text-align: center; margin: 24px 0;">
+            <code style="font-size: 18px; letter-spacing: 2px;">{}</code>
+        </div>
+
+        <p style="color: #64748b; font-size: 14px;">This code expires in 24 hours. If you didn't request this change, you can safely ignore this email or use the cancellation link sent to your current email.</p>
+
+        <hr style="border: none; border-top: 1px solid #e2e8f0; margin: 24px 0;">
+
+        <p style="color: #94a3b8; font-size: 12px; margin-bottom: 0;">
+            This email was sent by Pacioli. If you have questions, contact support@pacioli.io
+        </p>
+    </div>
+</body>
+</html>"#,
+        new_email, verification_token
+    );
+
+    let text_body = format!(
+        "Verify Your New Email Address\n\n\
+        You've requested to change your email address to: {}\n\n\
+        Verification code: {}\n\n\
+        This code expires in 24 hours. If you didn't request this change, you can safely ignore this email.\n\n\
+        - Pacioli Team",
+        new_email, verification_token
+    );
+
+    send_email(to, subject, &html_body, Some(&text_body)).await
+}
+
+/// Send security alert about email change to old email
+pub async fn send_email_change_alert(
+    to: &str,
+    cancellation_token: &str,
+    new_email: &str,
+) -> Result<(), String> {
+    let subject = "Security Alert: Email Change Requested - Pacioli";
+
+    let html_body = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+</head>
+<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, 'Helvetica Neue', Arial, sans-serif; line-height: 1.6; color: #333; max-width: 600px; margin: 0 auto; padding: 20px;">
+    <div style="background: linear-gradient(135deg, #283747 0%, #1a252f 100%); padding: 30px; border-radius: 10px 10px 0 0;">
+        <h1 style="color: #fff; margin: 0; font-size: 24px;">Pacioli</h1>
+        <p style="color: #94a3b8; margin: 5px 0 0 0; font-size: 14px;">Crypto-Inclusive Accounting Platform</p>
+    </div>
+
+    <div style="background: #fff; padding: 30px; border: 1px solid #e2e8f0; border-top: none; border-radius: 0 0 10px 10px;">
+        <div style="background: #fef3c7; border-left: 4px solid #f59e0b; padding: 16px; border-radius: 0 6px 6px 0; margin-bottom: 24px;">
+            <strong style="color: #92400e;">Security Alert</strong>
+        </div>
+
+        <h2 style="color: #283747; margin-top: 0;">Email Change Requested</h2>
+
+        <p>Someone has requested to change the email address associated with your Pacioli account to:</p>
+        <p style="background: #f1f5f9; padding: 12px 16px; border-radius: 6px; font-family: monospace; font-size: 14px;">{}</p>
+
+        <p><strong>If this was you:</strong> No action needed. The change will complete once the new email is verified and a short security waiting period has passed.</p>
+
+        <p><strong>If this wasn't you:</strong> Use the cancellation code below to stop this change immediately:</p>
+
+        <div style="background: #dc2626; color: #fff; padding: 16px 24px; border-radius: 6px; text-align: center; margin: 24px 0;">
+            <code style="font-size: 18px; letter-spacing: 2px;">{}</code>
+        </div>
+
+        <p style="color: #64748b; font-size: 14px;">The code works until the change takes effect. If the new email isn't verified within 48 hours, no changes will be made to your account.</p>
+
+        <hr style="border: none; border-top: 1px solid #e2e8f0; margin: 24px 0;">
+
+        <p style="color: #94a3b8; font-size: 12px; margin-bottom: 0;">
+            This email was sent by Pacioli. If you have questions, contact support@pacioli.io
+        </p>
+    </div>
+</body>
+</html>"#,
+        new_email, cancellation_token
+    );
+
+    let text_body = format!(
+        "SECURITY ALERT: Email Change Requested\n\n\
+        Someone has requested to change the email address associated with your Pacioli account to: {}\n\n\
+        If this was you: No action needed. The change will complete once the new email is verified and a short security waiting period has passed.\n\n\
+        If this wasn't you: Use this cancellation code to stop the change: {}\n\n\
+        The code works until the change takes effect. If the new email isn't verified within 48 hours, no changes will be made.\n\n\
+        - Pacioli Team",
+        new_email, cancellation_token
+    );
+
+    send_email(to, subject, &html_body, Some(&text_body)).await
+}
+
+/// Send security alert to old email once the new email is confirmed, with
+/// the time the change takes effect
+pub async fn send_email_change_confirmed(
+    to: &str,
+    cancellation_token: &str,
+    new_email: &str,
+    effective_at: &str,
+) -> Result<(), String> {
+    let subject = "Security Alert: Email Change Confirmed - Pacioli";
+
+    let html_body = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+</head>
+<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, 'Helvetica Neue', Arial, sans-serif; line-height: 1.6; color: #333; max-width: 600px; margin: 0 auto; padding: 20px;">
+    <div style="background: linear-gradient(135deg, #283747 0%, #1a252f 100%); padding: 30px; border-radius: 10px 10px 0 0;">
+        <h1 style="color: #fff; margin: 0; font-size: 24px;">Pacioli</h1>
+        <p style="color: #94a3b8; margin: 5px 0 0 0; font-size: 14px;">Crypto-Inclusive Accounting Platform</p>
+    </div>
+
+    <div style="background: #fff; padding: 30px; border: 1px solid #e2e8f0; border-top: none; border-radius: 0 0 10px 10px;">
+        <div style="background: #fef3c7; border-left: 4px solid #f59e0b; padding: 16px; border-radius: 0 6px 6px 0; margin-bottom: 24px;">
+            <strong style="color: #92400e;">Security Alert</strong>
+        </div>
+
+        <h2 style="color: #283747; margin-top: 0;">Email Change Confirmed</h2>
+
+        <p>The new email address for your Pacioli account has been verified:</p>
+        <p style="background: #f1f5f9; padding: 12px 16px; border-radius: 6px; font-family: monospace; font-size: 14px;">{}</p>
+
+        <p>The change takes effect at <strong>{}</strong>. All devices will then be signed out.</p>
+
+        <p><strong>If this wasn't you:</strong> Use the cancellation code below before then to stop the change:</p>
+
+        <div style="background: #dc2626; color: #fff; padding: 16px 24px; border-radius: 6px; text-align: center; margin: 24px 0;">
+            <code style="font-size: 18px; letter-spacing: 2px;">{}</code>
+        </div>
+
+        <hr style="border: none; border-top: 1px solid #e2e8f0; margin: 24px 0;">
+
+        <p style="color: #94a3b8; font-size: 12px; margin-bottom: 0;">
+            This email was sent by Pacioli. If you have questions, contact support@pacioli.io
+        </p>
+    </div>
+</body>
+</html>"#,
+        escape_html(new_email),
+        effective_at,
+        cancellation_token
+    );
+
+    let text_body = format!(
+        "SECURITY ALERT: Email Change Confirmed\n\n\
+        The new email address for your Pacioli account has been verified: {}\n\n\
+        The change takes effect at {}. All devices will then be signed out.\n\n\
+        If this wasn't you, use this cancellation code before then to stop the change: {}\n\n\
+        - Pacioli Team",
+        new_email, effective_at, cancellation_token
+    );
+
+    send_email(to, subject, &html_body, Some(&text_body)).await
+}
+
+/// Send password reset email
+pub async fn send_password_reset(to: &str, reset_token: &str) -> Result<(), String> {
+    let subject = "Reset your password - Pacioli";
+
+    let html_body = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+</head>
+<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, 'Helvetica Neue', Arial, sans-serif; line-height: 1.6; color: #333; max-width: 600px; margin: 0 auto; padding: 20px;">
+    <div style="background: linear-gradient(135deg, #283747 0%, #1a252f 100%); padding: 30px; border-radius: 10px 10px 0 0;">
+        <h1 style="color: #fff; margin: 0; font-size: 24px;">Pacioli</h1>
+        <p style="color: #94a3b8; margin: 5px 0 0 0; font-size: 14px;">Crypto-Inclusive Accounting Platform</p>
+    </div>
+
+    <div style="background: #fff; padding: 30px; border: 1px solid #e2e8f0; border-top: none; border-radius: 0 0 10px 10px;">
+        <h2 style="color: #283747; margin-top: 0;">Reset Your Password</h2>
+
+        <p>We received a request to reset your password. Use the code below to set a new password:</p>
+
+        <div style="background: #283747; color: #fff; padding: 16px 24px; border-radius: 6px; text-align: center; margin: 24px 0;">
+            <code style="font-size: 18px; letter-spacing: 2px;">{}</code>
+        </div>
+
+        <p style="color: #64748b; font-size: 14px;">This code expires in 1 hour. If you didn't request a password reset, you can safely ignore this email.</p>
+
+        <hr style="border: none; border-top: 1px solid #e2e8f0; margin: 24px 0;">
+
+        <p style="color: #94a3b8; font-size: 12px; margin-bottom: 0;">
+            This email was sent by Pacioli. If you have questions, contact support@pacioli.io
+        </p>
+    </div>
+</body>
+</html>"#,
+        reset_token
+    );
+
+    let text_body = format!(
+        "Reset Your Password\n\n\
+        We received a request to reset your password.\n\n\
+        Reset code: {}\n\n\
+        This code expires in 1 hour. If you didn't request a password reset, you can safely ignore this email.\n\n\
+        - Pacioli Team",
+        reset_token
+    );
+
+    send_email(to, subject, &html_body, Some(&text_body)).await
+}
+
+/// Send welcome email after registration
+pub async fn send_welcome_email(to: &str, display_name: &str) -> Result<(), String> {
+    let subject = "Welcome to Pacioli!";
+
+    let html_body = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+</head>
+<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, 'Helvetica Neue', Arial, sans-serif; line-height: 1.6; color: #333; max-width: 600px; margin: 0 auto; padding: 20px;">
+    <div style="background: linear-gradient(135deg, #283747 0%, #1a252f 100%); padding: 30px; border-radius: 10px 10px 0 0;">
+        <h1 style="color: #fff; margin: 0; font-size: 24px;">Pacioli</h1>
+        <p style="color: #94a3b8; margin: 5px 0 0 0; font-size: 14px;">Crypto-Inclusive Accounting Platform</p>
+    </div>
+
+    <div style="background: #fff; padding: 30px; border: 1px solid #e2e8f0; border-top: none; border-radius: 0 0 10px 10px;">
+        <h2 style="color: #283747; margin-top: 0;">Welcome, {}!</h2>
+
+        <p>Thanks for joining Pacioli. You're all set to start tracking and managing your crypto assets with professional-grade accounting tools.</p>
+
+        <h3 style="color: #283747;">Getting Started</h3>
+        <ul style="padding-left: 20px;">
+            <li>Connect your wallets to start tracking transactions</li>
+            <li>Set up your accounting profiles</li>
+            <li>Import historical transaction data</li>
+            <li>Generate reports for tax and compliance</li>
+        </ul>
+
+        <p>If you have any questions, don't hesitate to reach out to our support team.</p>
+
+        <hr style="border: none; border-top: 1px solid #e2e8f0; margin: 24px 0;">
+
+        <p style="color: #94a3b8; font-size: 12px; margin-bottom: 0;">
+            This email was sent by Pacioli. If you have questions, contact support@pacioli.io
+        </p>
+    </div>
+</body>
+</html>"#,
+        display_name
+    );
+
+    let text_body = format!(
+        "Welcome, {}!\n\n\
+        Thanks for joining Pacioli. You're all set to start tracking and managing your crypto assets with professional-grade accounting tools.\n\n\
+        Getting Started:\n\
+        - Connect your wallets to start tracking transactions\n\
+        - Set up your accounting profiles\n\
+        - Import historical transaction data\n\
+        - Generate reports for tax and compliance\n\n\
+        If you have any questions, don't hesitate to reach out to our support team.\n\n\
+        - Pacioli Team",
+        display_name
+    );
+
+    send_email(to, subject, &html_body, Some(&text_body)).await
+}
+
+/// Send an activity alert for a watched address
+pub async fn send_address_watch_alert(
+    to: &str,
+    watch_label: &str,
+    address: &str,
+    direction: &str,
+    amount: &str,
+    tx_hash: &str,
+) -> Result<(), String> {
+    let subject = format!("{} {} on {} - Pacioli", direction, amount, watch_label);
+
+    let html_body = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+</head>
+<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, 'Helvetica Neue', Arial, sans-serif; line-height: 1.6; color: #333; max-width: 600px; margin: 0 auto; padding: 20px;">
+    <div style="background: linear-gradient(135deg, #283747 0%, #1a252f 100%); padding: 30px; border-radius: 10px 10px 0 0;">
+        <h1 style="color: #fff; margin: 0; font-size: 24px;">Pacioli</h1>
+        <p style="color: #94a3b8; margin: 5px 0 0 0; font-size: 14px;">Crypto-Inclusive Accounting Platform</p>
+    </div>
+
+    <div style="background: #fff; padding: 30px; border: 1px solid #e2e8f0; border-top: none; border-radius: 0 0 10px 10px;">
+        <h2 style="color: #283747; margin-top: 0;">Watched Address Activity</h2>
+
+        <p>A new transaction above your alert threshold was detected on <strong>{}</strong>.</p>
+
+        <table style="width: 100%; border-collapse: collapse; margin: 24px 0; font-size: 14px;">
+            <tr><td style="color: #64748b; padding: 4px 0;">Address</td><td style="font-family: monospace;">{}</td></tr>
+            <tr><td style="color: #64748b; padding: 4px 0;">Direction</td><td>{}</td></tr>
+            <tr><td style="color: #64748b; padding: 4px 0;">Amount</td><td><strong>{}</strong></td></tr>
+            <tr><td style="color: #64748b; padding: 4px 0;">Transaction</td><td style="font-family: monospace; word-break: break-all;">{}</td></tr>
+        </table>
+
+        <p style="color: #64748b; font-size: 14px;">You can change the threshold or turn off email alerts for this address in Pacioli.</p>
+
+        <hr style="border: none; border-top: 1px solid #e2e8f0; margin: 24px 0;">
+
+        <p style="color: #94a3b8; font-size: 12px; margin-bottom: 0;">
+            This email was sent by Pacioli. If you have questions, contact support@pacioli.io
+        </p>
+    </div>
+</body>
+</html>"#,
+        watch_label, address, direction, amount, tx_hash
+    );
+
+    let text_body = format!(
+        "Watched Address Activity\n\n\
+        A new transaction above your alert threshold was detected on {}.\n\n\
+        Address: {}\n\
+        Direction: {}\n\
+        Amount: {}\n\
+        Transaction: {}\n\n\
+        You can change the threshold or turn off email alerts for this address in Pacioli.\n\n\
+        - Pacioli Team",
+        watch_label, address, direction, amount, tx_hash
+    );
+
+    send_email(to, &subject, &html_body, Some(&text_body)).await
+}
+
+/// Send an invitation to join a profile
+pub async fn send_invitation_email(
+    to: &str,
+    inviter_name: &str,
+    profile_name: &str,
+    role: &str,
+    message: Option<&str>,
+    invitation_link: &str,
+    invitation_token: &str,
+) -> Result<(), String> {
+    let subject = format!("{} invited you to {} - Pacioli", inviter_name, profile_name);
+
+    let message_html = message
+        .filter(|m| !m.trim().is_empty())
+        .map(|m| {
+            format!(
+                r#"<blockquote style="border-left: 3px solid #e2e8f0; margin: 16px 0; padding: 8px 16px; color: #475569;">{}</blockquote>"#,
+                escape_html(m)
+            )
+        })
+        .unwrap_or_default();
+
+    let html_body = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+</head>
+<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, 'Helvetica Neue', Arial, sans-serif; line-height: 1.6; color: #333; max-width: 600px; margin: 0 auto; padding: 20px;">
+    <div style="background: linear-gradient(135deg, #283747 0%, #1a252f 100%); padding: 30px; border-radius: 10px 10px 0 0;">
+        <h1 style="color: #fff; margin: 0; font-size: 24px;">Pacioli</h1>
+        <p style="color: #94a3b8; margin: 5px 0 0 0; font-size: 14px;">Crypto-Inclusive Accounting Platform</p>
+    </div>
+
+    <div style="background: #fff; padding: 30px; border: 1px solid #e2e8f0; border-top: none; border-radius: 0 0 10px 10px;">
+        <h2 style="color: #283747; margin-top: 0;">You're Invited</h2>
+
+        <p><strong>{}</strong> invited you to join <strong>{}</strong> on Pacioli as <strong>{}</strong>.</p>
+
+        {}
+
+        <div style="text-align: center; margin: 24px 0;">
+            <a href="{}" style="background: #283747; color: #fff; padding: 12px 24px; border-radius: 6px; text-decoration: none; display: inline-block;">Accept Invitation</a>
+        </div>
+
+        <p style="color: #64748b; font-size: 14px;">The button opens the Pacioli desktop app. If it doesn't, open Pacioli, choose "Accept invitation", and enter this code:</p>
+
+        <div style="background: #f1f5f9; padding: 12px 16px; border-radius: 6px; text-align: center; margin: 16px 0;">
+            <code style="font-size: 14px; word-break: break-all;">{}</code>
+        </div>
+
+        <p style="color: #64748b; font-size: 14px;">This invitation expires in 72 hours. If you weren't expecting it, you can safely ignore this email.</p>
+
+        <hr style="border: none; border-top: 1px solid #e2e8f0; margin: 24px 0;">
+
+        <p style="color: #94a3b8; font-size: 12px; margin-bottom: 0;">
+            This email was sent by Pacioli. If you have questions, contact support@pacioli.io
+        </p>
+    </div>
+</body>
+</html>"#,
+        escape_html(inviter_name),
+        escape_html(profile_name),
+        role,
+        message_html,
+        invitation_link,
+        invitation_token
+    );
+
+    let message_text = message
+        .filter(|m| !m.trim().is_empty())
+        .map(|m| format!("\"{}\"\n\n", m.trim()))
+        .unwrap_or_default();
+
+    let text_body = format!(
+        "You're Invited\n\n\
+        {} invited you to join {} on Pacioli as {}.\n\n\
+        {}\
+        Accept the invitation: {}\n\n\
+        If the link doesn't open Pacioli, open the app, choose \"Accept invitation\", and enter this code:\n\
+        {}\n\n\
+        This invitation expires in 72 hours. If you weren't expecting it, you can safely ignore this email.\n\n\
+        - Pacioli Team",
+        inviter_name, profile_name, role, message_text, invitation_link, invitation_token
+    );
+
+    send_email(to, &subject, &html_body, Some(&text_body)).await
+}
+
+/// Send an alert about a login from an unfamiliar device or network
+///
+/// `pending_email_change` is the new address and effective time of a
+/// confirmed email change, highlighted in the alert while it is pending.
+pub async fn send_login_alert(
+    to: &str,
+    reasons: &str,
+    device: &str,
+    ip_address: &str,
+    login_time: &str,
+    pending_email_change: Option<(&str, &str)>,
+) -> Result<(), String> {
+    let subject = "New sign-in to your account - Pacioli";
+
+    let (pending_html, pending_text) = match pending_email_change {
+        Some((new_email, effective_at)) => (
+            format!(
+                r#"<div style="background: #fef3c7; border-left: 4px solid #f59e0b; padding: 16px; border-radius: 0 6px 6px 0; margin-bottom: 24px;">
+            <strong style="color: #92400e;">Email change pending:</strong> this account's email will change to {} at {}. If you didn't request this, cancel it with the code in the email change alert sent to this address.
+        </div>
+"#,
+                escape_html(new_email),
+                effective_at
+            ),
+            format!(
+                "EMAIL CHANGE PENDING: this account's email will change to {} at {}. If you didn't request this, cancel it with the code in the email change alert sent to this address.\n\n",
+                new_email, effective_at
+            ),
+        ),
+        None => (String::new(), String::new()),
+    };
+
+    let html_body = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+</head>
+<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, 'Helvetica Neue', Arial, sans-serif; line-height: 1.6; color: #333; max-width: 600px; margin: 0 auto; padding: 20px;">
+    <div style="background: linear-gradient(135deg, #283747 0%, #1a252f 100%); padding: 30px; border-radius: 10px 10px 0 0;">
+        <h1 style="color: #fff; margin: 0; font-size: 24px;">Pacioli</h1>
+        <p style="color: #94a3b8; margin: 5px 0 0 0; font-size: 14px;">Crypto-Inclusive Accounting Platform</p>
+    </div>
+
+    <div style="background: #fff; padding: 30px; border: 1px solid #e2e8f0; border-top: none; border-radius: 0 0 10px 10px;">
+        <h2 style="color: #283747; margin-top: 0;">New Sign-In Detected</h2>
+
+        {}<p>Your account was just signed in to from a {}.</p>
+
+        <table style="width: 100%; border-collapse: collapse; margin: 24px 0; font-size: 14px;">
+            <tr><td style="color: #64748b; padding: 4px 0;">Device</td><td>{}</td></tr>
+            <tr><td style="color: #64748b; padding: 4px 0;">Network address</td><td>{}</td></tr>
+            <tr><td style="color: #64748b; padding: 4px 0;">Time</td><td>{}</td></tr>
+        </table>
+
+        <p>If this was you, no action is needed.</p>
+
+        <p style="color: #dc2626;">If you don't recognize this sign-in, change your password and revoke the session under Settings &rarr; Security.</p>
+
+        <hr style="border: none; border-top: 1px solid #e2e8f0; margin: 24px 0;">
+
+        <p style="color: #94a3b8; font-size: 12px; margin-bottom: 0;">
+            This email was sent by Pacioli. You can turn off login alerts in your notification settings.
+        </p>
+    </div>
+</body>
+</html>"#,
+        pending_html,
+        reasons,
+        escape_html(device),
+        ip_address,
+        login_time
+    );
+
+    let text_body = format!(
+        "New Sign-In Detected\n\n\
+        {}Your account was just signed in to from a {}.\n\n\
+        Device: {}\n\
+        Network address: {}\n\
+        Time: {}\n\n\
+        If this was you, no action is needed.\n\n\
+        If you don't recognize this sign-in, change your password and revoke the session under Settings > Security.\n\n\
+        - Pacioli Team",
+        pending_text, reasons, device, ip_address, login_time
+    );
+
+    send_email(to, subject, &html_body, Some(&text_body)).await
+}
+
+/// Send a scheduled report with the generated file attached
+pub async fn send_scheduled_report(
+    to: &str,
+    report_name: &str,
+    profile_name: &str,
+    period: &str,
+    attachment: EmailAttachment,
+) -> Result<(), String> {
+    let subject = format!("{} for {} - Pacioli", report_name, period);
+
+    let html_body = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+</head>
+<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, 'Helvetica Neue', Arial, sans-serif; line-height: 1.6; color: #333; max-width: 600px; margin: 0 auto; padding: 20px;">
+    <div style="background: linear-gradient(135deg, #283747 0%, #1a252f 100%); padding: 30px; border-radius: 10px 10px 0 0;">
+        <h1 style="color: #fff; margin: 0; font-size: 24px;">Pacioli</h1>
+        <p style="color: #94a3b8; margin: 5px 0 0 0; font-size: 14px;">Crypto-Inclusive Accounting Platform</p>
+    </div>
+
+    <div style="background: #fff; padding: 30px; border: 1px solid #e2e8f0; border-top: none; border-radius: 0 0 10px 10px;">
+        <h2 style="color: #283747; margin-top: 0;">{}</h2>
+
+        <p>The scheduled report for <strong>{}</strong> covering <strong>{}</strong> is attached.</p>
+
+        <p style="color: #64748b; font-size: 14px;">Earlier reports can be downloaded again from the report history in Pacioli.</p>
+
+        <hr style="border: none; border-top: 1px solid #e2e8f0; margin: 24px 0;">
+
+        <p style="color: #94a3b8; font-size: 12px; margin-bottom: 0;">
+            This email was sent by Pacioli. You can change the recipients or turn off this schedule in Pacioli.
+        </p>
+    </div>
+</body>
+</html>"#,
+        escape_html(report_name),
+        escape_html(profile_name),
+        period
+    );
+
+    let text_body = format!(
+        "{}\n\n\
+        The scheduled report for {} covering {} is attached.\n\n\
+        Earlier reports can be downloaded again from the report history in Pacioli.\n\n\
+        - Pacioli Team",
+        report_name, profile_name, period
+    );
+
+    send_email_with_attachments(to, &subject, &html_body, Some(&text_body), vec![attachment]).await
+}
+
+/// Escapes user-provided text for inclusion in an HTML template
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
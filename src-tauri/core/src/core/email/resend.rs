@@ -0,0 +1,106 @@
+//! Resend API provider.
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+
+use super::{EmailMessage, EmailProvider};
+
+/// Provider name used for registration.
+pub(super) const PROVIDER_NAME: &str = "resend";
+
+/// Sender used for all Resend email.
+const FROM_ADDRESS: &str = "Pacioli <noreply@pacioli.io>";
+
+/// Request body for Resend API
+#[derive(Debug, Serialize)]
+struct ResendRequest {
+    from: String,
+    to: Vec<String>,
+    subject: String,
+    html: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<ResendAttachment>,
+}
+
+/// Attachment in a Resend request, with base64 content
+#[derive(Debug, Serialize)]
+struct ResendAttachment {
+    filename: String,
+    content: String,
+}
+
+/// Response from Resend API
+#[derive(Debug, Deserialize)]
+struct ResendResponse {
+    #[allow(dead_code)]
+    id: String,
+}
+
+/// Error response from Resend API
+#[derive(Debug, Deserialize)]
+struct ResendError {
+    message: String,
+}
+
+/// Sends email through the Resend HTTP API.
+pub struct ResendProvider {
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl ResendProvider {
+    /// Creates a provider using the given API key.
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl EmailProvider for ResendProvider {
+    fn name(&self) -> &'static str {
+        PROVIDER_NAME
+    }
+
+    async fn send(&self, message: &EmailMessage) -> Result<(), String> {
+        let request = ResendRequest {
+            from: FROM_ADDRESS.to_string(),
+            to: vec![message.to.clone()],
+            subject: message.subject.clone(),
+            html: message.html.clone(),
+            text: message.text.clone(),
+            attachments: message
+                .attachments
+                .iter()
+                .map(|a| ResendAttachment {
+                    filename: a.filename.clone(),
+                    content: BASE64.encode(&a.content),
+                })
+                .collect(),
+        };
+
+        let response = self
+            .client
+            .post("https://api.resend.com/emails")
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to send email: {}", e))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let error: ResendError = response.json().await.unwrap_or_else(|_| ResendError {
+                message: "Unknown error".to_string(),
+            });
+            Err(format!("Email API error: {}", error.message))
+        }
+    }
+}
@@ -0,0 +1,325 @@
+//! SMTP provider for self-hosted installs.
+//!
+//! Server settings are stored as JSON in the `settings` table under
+//! [`SETTINGS_KEY`]; the password is kept in the OS keychain.
+
+use async_trait::async_trait;
+use keyring::Entry;
+use lettre::message::{header::ContentType, Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+
+use super::{EmailMessage, EmailProvider};
+
+/// Provider name used for registration.
+pub const PROVIDER_NAME: &str = "smtp";
+
+/// Settings key holding the serialized [`SmtpSettings`].
+pub const SETTINGS_KEY: &str = "email.smtp";
+
+/// Service name for keychain entries
+const KEYCHAIN_SERVICE: &str = "pacioli";
+
+/// Keychain entry holding the SMTP password.
+const KEYCHAIN_PASSWORD_KEY: &str = "smtp_password";
+
+/// Connection security for the SMTP server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Implicit TLS, usually port 465.
+    Tls,
+    /// Plain connection upgraded with STARTTLS, usually port 587.
+    StartTls,
+    /// Unencrypted connection. Only allowed for a relay on this machine.
+    None,
+}
+
+/// SMTP server settings. The password is stored separately in the keychain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmtpSettings {
+    /// Server hostname.
+    pub host: String,
+    /// Server port.
+    pub port: u16,
+    /// Connection security.
+    pub security: SmtpSecurity,
+    /// Login username, if the server requires authentication.
+    pub username: Option<String>,
+    /// Sender email address.
+    pub from_address: String,
+    /// Optional sender display name.
+    pub from_name: Option<String>,
+}
+
+impl SmtpSettings {
+    /// Checks that the settings are complete, the sender address parses, and
+    /// an unencrypted connection only goes to this machine.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.host.trim().is_empty() {
+            return Err("SMTP host is required".to_string());
+        }
+        if self.port == 0 {
+            return Err("SMTP port is required".to_string());
+        }
+        if self.security == SmtpSecurity::None && !is_loopback_host(self.host.trim()) {
+            return Err("Unencrypted SMTP is only allowed for a local relay".to_string());
+        }
+        self.sender()?;
+        Ok(())
+    }
+
+    /// The sender mailbox, including the display name when set.
+    pub fn sender(&self) -> Result<Mailbox, String> {
+        let address = self
+            .from_address
+            .trim()
+            .parse()
+            .map_err(|e| format!("Invalid sender address: {}", e))?;
+        let name = self
+            .from_name
+            .as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .map(str::to_string)
+            .or_else(|| Some("Pacioli".to_string()));
+        Ok(Mailbox::new(name, address))
+    }
+}
+
+/// Whether `host` names this machine, so credentials sent to it in the clear
+/// never cross the network.
+fn is_loopback_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Stores the SMTP password in the system keychain.
+pub fn save_password(password: &str) -> Result<(), String> {
+    Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_PASSWORD_KEY)
+        .and_then(|entry| entry.set_password(password))
+        .map_err(|e| format!("Keychain access failed: {}", e))
+}
+
+/// Retrieves the SMTP password from the system keychain.
+pub fn load_password() -> Result<Option<String>, String> {
+    let entry = Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_PASSWORD_KEY)
+        .map_err(|e| format!("Keychain access failed: {}", e))?;
+    match entry.get_password() {
+        Ok(password) => Ok(Some(password)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Keychain access failed: {}", e)),
+    }
+}
+
+/// Removes the SMTP password from the system keychain.
+pub fn delete_password() -> Result<(), String> {
+    let entry = Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_PASSWORD_KEY)
+        .map_err(|e| format!("Keychain access failed: {}", e))?;
+    match entry.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Keychain access failed: {}", e)),
+    }
+}
+
+/// Sends email through an SMTP server.
+pub struct SmtpProvider {
+    sender: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpProvider {
+    /// Creates a provider for the given server. `password` is used together
+    /// with `settings.username` when both are present.
+    pub fn new(settings: &SmtpSettings, password: Option<String>) -> Result<Self, String> {
+        settings.validate()?;
+        let host = settings.host.trim();
+
+        let builder = match settings.security {
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+            SmtpSecurity::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                host,
+            )),
+        }
+        .map_err(|e| format!("Invalid SMTP server: {}", e))?
+        .port(settings.port);
+
+        let builder = match (settings.username.as_deref(), password) {
+            (Some(username), Some(password)) if !username.is_empty() => {
+                builder.credentials(Credentials::new(username.to_string(), password))
+            }
+            _ => builder,
+        };
+
+        Ok(Self {
+            sender: settings.sender()?,
+            transport: builder.build(),
+        })
+    }
+}
+
+/// Builds a MIME message with HTML and optional plain-text parts, followed by
+/// any attachments.
+fn build_message(sender: &Mailbox, message: &EmailMessage) -> Result<Message, String> {
+    let to: Mailbox = message
+        .to
+        .parse()
+        .map_err(|e| format!("Invalid recipient address: {}", e))?;
+
+    let html = SinglePart::builder()
+        .header(ContentType::TEXT_HTML)
+        .body(message.html.clone());
+    let body = match &message.text {
+        Some(text) => MultiPart::alternative()
+            .singlepart(
+                SinglePart::builder()
+                    .header(ContentType::TEXT_PLAIN)
+                    .body(text.clone()),
+            )
+            .singlepart(html),
+        None => MultiPart::mixed().singlepart(html),
+    };
+    let body = if message.attachments.is_empty() {
+        body
+    } else {
+        let mut mixed = MultiPart::mixed().multipart(body);
+        for attachment in &message.attachments {
+            let content_type = ContentType::parse(&attachment.content_type)
+                .map_err(|e| format!("Invalid attachment type: {}", e))?;
+            mixed = mixed.singlepart(
+                Attachment::new(attachment.filename.clone())
+                    .body(attachment.content.clone(), content_type),
+            );
+        }
+        mixed
+    };
+
+    Message::builder()
+        .from(sender.clone())
+        .to(to)
+        .subject(message.subject.clone())
+        .multipart(body)
+        .map_err(|e| format!("Failed to build email: {}", e))
+}
+
+#[async_trait]
+impl EmailProvider for SmtpProvider {
+    fn name(&self) -> &'static str {
+        PROVIDER_NAME
+    }
+
+    async fn send(&self, message: &EmailMessage) -> Result<(), String> {
+        let email = build_message(&self.sender, message)?;
+        self.transport
+            .send(email)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to send email: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::email::EmailAttachment;
+
+    fn settings() -> SmtpSettings {
+        SmtpSettings {
+            host: "smtp.example.org".to_string(),
+            port: 587,
+            security: SmtpSecurity::StartTls,
+            username: Some("user".to_string()),
+            from_address: "books@example.org".to_string(),
+            from_name: None,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(settings().validate().is_ok());
+
+        let mut missing_host = settings();
+        missing_host.host = " ".to_string();
+        assert!(missing_host.validate().is_err());
+
+        let mut bad_sender = settings();
+        bad_sender.from_address = "not an address".to_string();
+        assert!(bad_sender.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_unencrypted_only_on_loopback() {
+        let mut plain = settings();
+        plain.security = SmtpSecurity::None;
+        assert!(plain.validate().is_err());
+
+        for host in ["localhost", "127.0.0.1", "[::1]"] {
+            plain.host = host.to_string();
+            assert!(plain.validate().is_ok(), "{host}");
+        }
+    }
+
+    #[test]
+    fn test_sender_defaults_display_name() {
+        let sender = settings().sender().unwrap();
+        assert_eq!(sender.to_string(), "Pacioli <books@example.org>");
+
+        let mut named = settings();
+        named.from_name = Some("Acme Foundation".to_string());
+        let sender = named.sender().unwrap();
+        assert_eq!(sender.name.as_deref(), Some("Acme Foundation"));
+        assert_eq!(sender.email.to_string(), "books@example.org");
+    }
+
+    #[test]
+    fn test_build_message() {
+        let sender = settings().sender().unwrap();
+        let message = EmailMessage {
+            to: "donor@example.com".to_string(),
+            subject: "Hello".to_string(),
+            html: "<p>Hi</p>".to_string(),
+            text: Some("Hi".to_string()),
+            attachments: Vec::new(),
+        };
+        let formatted =
+            String::from_utf8(build_message(&sender, &message).unwrap().formatted()).unwrap();
+        assert!(formatted.contains("To: donor@example.com"));
+        assert!(formatted.contains("Subject: Hello"));
+        assert!(formatted.contains("multipart/alternative"));
+        assert!(!formatted.contains("Content-Disposition: attachment"));
+
+        let with_report = EmailMessage {
+            attachments: vec![EmailAttachment {
+                filename: "report.csv".to_string(),
+                content_type: "text/csv".to_string(),
+                content: b"a,b\n1,2\n".to_vec(),
+            }],
+            ..message.clone()
+        };
+        let formatted =
+            String::from_utf8(build_message(&sender, &with_report).unwrap().formatted()).unwrap();
+        assert!(formatted.contains("multipart/mixed"));
+        assert!(formatted.contains("filename=\"report.csv\""));
+
+        let invalid = EmailMessage {
+            to: "nobody".to_string(),
+            ..message
+        };
+        assert!(build_message(&sender, &invalid).is_err());
+    }
+
+    #[test]
+    fn test_settings_serialization() {
+        let json = serde_json::to_string(&settings()).unwrap();
+        assert!(json.contains("\"fromAddress\":\"books@example.org\""));
+        assert!(json.contains("\"security\":\"starttls\""));
+        let parsed: SmtpSettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, settings());
+    }
+}
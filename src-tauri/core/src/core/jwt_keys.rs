@@ -0,0 +1,208 @@
+//! JWT signing keyring.
+//!
+//! Tokens are signed with the newest key and carry its key ID (`kid`) in
+//! their header. Rotating adds a new signing key and retires the old one,
+//! which keeps verifying tokens for [`GRACE_PERIOD`] so sessions survive the
+//! rotation. Keys retired longer ago than that are dropped.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Length of a generated signing secret, in bytes.
+const SECRET_LENGTH: usize = 32;
+
+/// How long a retired key still verifies tokens. Matches the refresh token
+/// lifetime, so no session outlives the key that signed it.
+pub const GRACE_PERIOD: Duration = Duration::days(7);
+
+/// How old the signing key gets before it's rotated on schedule.
+pub const ROTATION_INTERVAL: Duration = Duration::days(30);
+
+/// One signing key.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwtKey {
+    /// Key ID placed in token headers.
+    pub kid: String,
+    /// HMAC secret.
+    #[serde(serialize_with = "encode_secret", deserialize_with = "decode_secret")]
+    secret: Vec<u8>,
+    /// When the key was created.
+    pub created_at: DateTime<Utc>,
+    /// When the key stopped signing; `None` for the current key.
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+impl JwtKey {
+    fn generate(now: DateTime<Utc>) -> Self {
+        let mut secret = vec![0u8; SECRET_LENGTH];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let mut id = [0u8; 8];
+        rand::thread_rng().fill_bytes(&mut id);
+
+        Self {
+            kid: hex::encode(id),
+            secret,
+            created_at: now,
+            retired_at: None,
+        }
+    }
+
+    /// HMAC secret for signing and verifying.
+    pub fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+
+    /// Whether the key may still verify tokens at `now`.
+    fn accepts_at(&self, now: DateTime<Utc>) -> bool {
+        self.retired_at
+            .is_none_or(|retired| now < retired + GRACE_PERIOD)
+    }
+}
+
+impl std::fmt::Debug for JwtKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtKey")
+            .field("kid", &self.kid)
+            .field("created_at", &self.created_at)
+            .field("retired_at", &self.retired_at)
+            .finish_non_exhaustive()
+    }
+}
+
+fn encode_secret<S: Serializer>(secret: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&URL_SAFE_NO_PAD.encode(secret))
+}
+
+fn decode_secret<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let encoded = String::deserialize(deserializer)?;
+    URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(serde::de::Error::custom)
+}
+
+/// Key ID and dates of a key, without its secret, as shown in settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwtKeyInfo {
+    /// Key ID.
+    pub kid: String,
+    /// When the key was created.
+    pub created_at: DateTime<Utc>,
+    /// When the key stopped signing; `None` for the current key.
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+/// The current signing key and recently retired keys, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtKeyring {
+    keys: Vec<JwtKey>,
+}
+
+impl JwtKeyring {
+    /// Creates a keyring with one new signing key.
+    pub fn generate() -> Self {
+        Self {
+            keys: vec![JwtKey::generate(Utc::now())],
+        }
+    }
+
+    /// Creates a keyring with a single fixed key (for testing).
+    #[cfg(test)]
+    pub fn from_secret(kid: &str, secret: &[u8]) -> Self {
+        Self {
+            keys: vec![JwtKey {
+                kid: kid.to_string(),
+                secret: secret.to_vec(),
+                created_at: Utc::now(),
+                retired_at: None,
+            }],
+        }
+    }
+
+    /// The key new tokens are signed with.
+    pub fn current(&self) -> &JwtKey {
+        &self.keys[0]
+    }
+
+    /// The key with ID `kid`, if it may still verify tokens.
+    pub fn verifying_key(&self, kid: &str) -> Option<&JwtKey> {
+        let now = Utc::now();
+        self.keys
+            .iter()
+            .find(|key| key.kid == kid && key.accepts_at(now))
+    }
+
+    /// Whether the signing key is older than [`ROTATION_INTERVAL`].
+    pub fn rotation_due(&self, now: DateTime<Utc>) -> bool {
+        now - self.current().created_at >= ROTATION_INTERVAL
+    }
+
+    /// Starts signing with a new key, retiring the current one and dropping
+    /// keys past their grace period.
+    pub fn rotate(&mut self, now: DateTime<Utc>) {
+        if let Some(current) = self.keys.first_mut() {
+            current.retired_at = Some(now);
+        }
+        self.keys.insert(0, JwtKey::generate(now));
+        self.keys.retain(|key| key.accepts_at(now));
+    }
+
+    /// Key IDs and dates, newest first.
+    pub fn info(&self) -> Vec<JwtKeyInfo> {
+        self.keys
+            .iter()
+            .map(|key| JwtKeyInfo {
+                kid: key.kid.clone(),
+                created_at: key.created_at,
+                retired_at: key.retired_at,
+            })
+            .collect()
+    }
+
+    /// Checks a keyring loaded from storage has a signing key.
+    pub fn validate(&self) -> Result<(), String> {
+        match self.keys.first() {
+            Some(key) if key.retired_at.is_none() && !key.secret.is_empty() => Ok(()),
+            _ => Err("Stored JWT keyring has no signing key".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_keeps_old_key_during_grace_period() {
+        let mut keyring = JwtKeyring::generate();
+        let old_kid = keyring.current().kid.clone();
+
+        keyring.rotate(Utc::now());
+        assert_ne!(keyring.current().kid, old_kid);
+        assert!(keyring.verifying_key(&old_kid).is_some());
+
+        // A rotation after the grace period drops the old key
+        keyring.rotate(Utc::now() + GRACE_PERIOD + Duration::days(1));
+        assert!(keyring.info().iter().all(|key| key.kid != old_kid));
+        assert_eq!(keyring.info().len(), 2);
+    }
+
+    #[test]
+    fn test_rotation_due() {
+        let keyring = JwtKeyring::generate();
+        assert!(!keyring.rotation_due(Utc::now()));
+        assert!(keyring.rotation_due(Utc::now() + ROTATION_INTERVAL));
+    }
+
+    #[test]
+    fn test_keyring_round_trips_through_json() {
+        let keyring = JwtKeyring::generate();
+        let json = serde_json::to_string(&keyring).unwrap();
+        let restored: JwtKeyring = serde_json::from_str(&json).unwrap();
+        assert!(restored.validate().is_ok());
+        assert_eq!(restored.current().secret(), keyring.current().secret());
+    }
+}
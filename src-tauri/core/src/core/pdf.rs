@@ -0,0 +1,266 @@
+//! Minimal PDF writer for generated documents (receipts, statements).
+//!
+//! Produces PDF 1.4 files using the standard Helvetica fonts, so no font
+//! embedding or external dependency is needed. Only the subset required for
+//! simple text documents is supported: text runs, horizontal rules, and
+//! multiple pages. Text is encoded as WinAnsi; characters outside Latin-1
+//! are replaced with `?`.
+
+/// US Letter width in points.
+pub const PAGE_WIDTH: f32 = 612.0;
+/// US Letter height in points.
+pub const PAGE_HEIGHT: f32 = 792.0;
+
+/// Font face used for a text run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    /// Helvetica regular.
+    Regular,
+    /// Helvetica bold.
+    Bold,
+}
+
+impl Font {
+    fn resource_name(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+/// A PDF document under construction.
+#[derive(Debug)]
+pub struct PdfDocument {
+    pages: Vec<Vec<u8>>,
+    title: Option<String>,
+}
+
+impl Default for PdfDocument {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PdfDocument {
+    /// Creates an empty document with a single blank page.
+    pub fn new() -> Self {
+        Self {
+            pages: vec![Vec::new()],
+            title: None,
+        }
+    }
+
+    /// Sets the document title stored in the info dictionary.
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Starts a new page; subsequent drawing goes to it.
+    pub fn add_page(&mut self) {
+        self.pages.push(Vec::new());
+    }
+
+    fn current_page(&mut self) -> &mut Vec<u8> {
+        self.pages.last_mut().expect("document always has a page")
+    }
+
+    /// Draws a single line of text with its baseline at `(x, y)`, measured in
+    /// points from the bottom-left corner of the page.
+    pub fn text(&mut self, x: f32, y: f32, size: f32, font: Font, text: &str) {
+        let prefix = format!(
+            "BT /{} {} Tf {} {} Td (",
+            font.resource_name(),
+            fmt_num(size),
+            fmt_num(x),
+            fmt_num(y)
+        );
+        let escaped = escape_text(text);
+        let page = self.current_page();
+        page.extend_from_slice(prefix.as_bytes());
+        page.extend_from_slice(&escaped);
+        page.extend_from_slice(b") Tj ET\n");
+    }
+
+    /// Draws a horizontal rule from `x1` to `x2` at height `y`.
+    pub fn hline(&mut self, x1: f32, x2: f32, y: f32, width: f32) {
+        let op = format!(
+            "{} w {} {} m {} {} l S\n",
+            fmt_num(width),
+            fmt_num(x1),
+            fmt_num(y),
+            fmt_num(x2),
+            fmt_num(y)
+        );
+        self.current_page().extend_from_slice(op.as_bytes());
+    }
+
+    /// Serializes the document to PDF bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // Object layout:
+        // 1 catalog, 2 pages, 3 regular font, 4 bold font, 5 info,
+        // then a (page, content) pair per page.
+        let page_count = self.pages.len();
+        let first_page_obj = 6;
+        let mut objects: Vec<Vec<u8>> = Vec::new();
+
+        objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+
+        let kids: Vec<String> = (0..page_count)
+            .map(|i| format!("{} 0 R", first_page_obj + i * 2))
+            .collect();
+        objects.push(
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                page_count
+            )
+            .into_bytes(),
+        );
+
+        objects.push(
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+        );
+        objects.push(
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+        );
+
+        let mut info = b"<< /Producer (Pacioli)".to_vec();
+        if let Some(title) = &self.title {
+            info.extend_from_slice(b" /Title (");
+            info.extend_from_slice(&escape_text(title));
+            info.extend_from_slice(b")");
+        }
+        info.extend_from_slice(b" >>");
+        objects.push(info);
+
+        for (i, content) in self.pages.iter().enumerate() {
+            let content_obj = first_page_obj + i * 2 + 1;
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                     /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                    fmt_num(PAGE_WIDTH),
+                    fmt_num(PAGE_HEIGHT),
+                    content_obj
+                )
+                .into_bytes(),
+            );
+            let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+            stream.extend_from_slice(content);
+            stream.extend_from_slice(b"endstream");
+            objects.push(stream);
+        }
+
+        let mut out: Vec<u8> = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, body) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            out.extend_from_slice(body);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref_offset = out.len();
+        out.extend_from_slice(format!("xref\n0 {}\n", objects.len() + 1).as_bytes());
+        out.extend_from_slice(b"0000000000 65535 f \n");
+        for offset in offsets {
+            out.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        out.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref_offset
+            )
+            .as_bytes(),
+        );
+
+        out
+    }
+}
+
+/// Formats a coordinate without trailing zeros.
+fn fmt_num(n: f32) -> String {
+    let s = format!("{:.2}", n);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Escapes a string for a PDF literal string in WinAnsi encoding.
+fn escape_text(text: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push(b'\\');
+                out.push(c as u8);
+            }
+            '\n' | '\r' | '\t' => out.push(b' '),
+            c if (c as u32) < 0x20 => {}
+            c if (c as u32) <= 0xFF => out.push(c as u32 as u8),
+            _ => out.push(b'?'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(escape_text("a(b)c\\"), b"a\\(b\\)c\\\\".to_vec());
+        assert_eq!(escape_text("caf\u{e9}"), b"caf\xE9".to_vec());
+        assert_eq!(escape_text("\u{20ac}5"), b"?5".to_vec());
+    }
+
+    #[test]
+    fn test_fmt_num() {
+        assert_eq!(fmt_num(72.0), "72");
+        assert_eq!(fmt_num(10.5), "10.5");
+        assert_eq!(fmt_num(0.25), "0.25");
+    }
+
+    #[test]
+    fn test_document_structure_and_xref() {
+        let mut doc = PdfDocument::new().with_title("Receipt");
+        doc.text(72.0, 720.0, 18.0, Font::Bold, "Hello (world)");
+        doc.hline(72.0, 540.0, 700.0, 0.5);
+        doc.add_page();
+        doc.text(72.0, 720.0, 10.0, Font::Regular, "Page two");
+        let bytes = doc.to_bytes();
+
+        assert!(bytes.starts_with(b"%PDF-1.4\n"));
+        assert!(bytes.ends_with(b"%%EOF\n"));
+
+        let contains = |needle: &[u8]| bytes.windows(needle.len()).any(|w| w == needle);
+        assert!(contains(b"/Count 2"));
+        assert!(contains(b"(Hello \\(world\\)) Tj"));
+
+        // startxref must point at the xref table
+        let trailer = std::str::from_utf8(&bytes[bytes.len() - 32..]).unwrap();
+        let startxref = trailer.rfind("startxref\n").unwrap();
+        let offset: usize = trailer[startxref + 10..]
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(&bytes[offset..offset + 4], b"xref");
+
+        // Each xref entry must point at the matching object header
+        let xref = std::str::from_utf8(&bytes[offset..]).unwrap();
+        for (i, line) in xref.lines().skip(3).take(9).enumerate() {
+            let obj_offset: usize = line[..10].parse().unwrap();
+            let header = format!("{} 0 obj", i + 1);
+            assert_eq!(
+                &bytes[obj_offset..obj_offset + header.len()],
+                header.as_bytes()
+            );
+        }
+    }
+}
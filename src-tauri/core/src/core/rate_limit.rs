@@ -0,0 +1,297 @@
+//! In-process throttling of authentication attempts.
+//!
+//! Failed attempts are counted per action and identifier (an email, wallet
+//! address, or token hash). Once an identifier hits its limit it is locked
+//! out, and each further lockout of the same identifier doubles in length up
+//! to [`MAX_LOCKOUT`].
+//!
+//! Failures are also counted across all identifiers of an action, so that
+//! guessing many tokens is throttled as well as hammering one account. That
+//! count only slows every attempt down, by up to [`MAX_GLOBAL_DELAY`]: a
+//! lockout shared by everyone would let anyone lock every user out.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Failed attempts per identifier before it is locked out.
+const MAX_FAILURES: u32 = 5;
+
+/// Failed attempts across all identifiers of an action before attempts at
+/// the action are slowed down.
+const MAX_GLOBAL_FAILURES: u32 = 50;
+
+/// Delay added for each failure across an action beyond
+/// [`MAX_GLOBAL_FAILURES`].
+const GLOBAL_DELAY_STEP: Duration = Duration::from_millis(100);
+
+/// Longest delay added to an attempt.
+const MAX_GLOBAL_DELAY: Duration = Duration::from_secs(5);
+
+/// Window in which failed attempts are counted.
+const FAILURE_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// Length of the first lockout.
+const BASE_LOCKOUT: Duration = Duration::from_secs(30);
+
+/// Longest lockout.
+const MAX_LOCKOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Quiet period after which a key's lockout history is forgotten.
+const LOCKOUT_MEMORY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// An authentication action that is throttled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuthAction {
+    /// Email and password login, keyed by email.
+    Login,
+    /// Wallet signature login, keyed by wallet address.
+    WalletSignature,
+    /// Invitation acceptance, keyed by invitation token hash.
+    AcceptInvitation,
+    /// Access token refresh, keyed by refresh token hash.
+    RefreshToken,
+}
+
+impl AuthAction {
+    /// Name recorded in the audit log.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Login => "login",
+            Self::WalletSignature => "wallet_signature",
+            Self::AcceptInvitation => "accept_invitation",
+            Self::RefreshToken => "refresh_token",
+        }
+    }
+}
+
+/// A lockout started by a failed attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lockout {
+    /// Identifier locked out.
+    pub identifier: String,
+    /// Failed attempts that led to it.
+    pub failures: u32,
+    /// How long it lasts.
+    pub duration: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Attempts {
+    failures: u32,
+    window_start: Option<Instant>,
+    last_failure: Option<Instant>,
+    locked_until: Option<Instant>,
+    lockouts: u32,
+}
+
+impl Attempts {
+    fn remaining_lockout(&self, now: Instant) -> Option<Duration> {
+        self.locked_until
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+    }
+
+    /// Failures counted in the current window.
+    fn recent_failures(&self, now: Instant) -> u32 {
+        match self.window_start {
+            Some(start) if now.duration_since(start) <= FAILURE_WINDOW => self.failures,
+            _ => 0,
+        }
+    }
+
+    /// Counts a failure in the current window, starting a new one if it has
+    /// passed.
+    fn count(&mut self, now: Instant) {
+        if self
+            .window_start
+            .is_none_or(|start| now.duration_since(start) > FAILURE_WINDOW)
+        {
+            self.window_start = Some(now);
+            self.failures = 0;
+        }
+        self.failures += 1;
+        self.last_failure = Some(now);
+    }
+
+    /// Counts a failure, returning the lockout it starts, if any.
+    fn fail(&mut self, now: Instant, limit: u32) -> Option<(u32, Duration)> {
+        if self
+            .last_failure
+            .is_some_and(|last| now.duration_since(last) > LOCKOUT_MEMORY)
+        {
+            self.lockouts = 0;
+        }
+        self.count(now);
+
+        if self.failures < limit {
+            return None;
+        }
+        let failures = self.failures;
+        let duration = lockout_duration(self.lockouts);
+        self.lockouts += 1;
+        self.locked_until = Some(now + duration);
+        self.failures = 0;
+        self.window_start = None;
+        Some((failures, duration))
+    }
+}
+
+/// Length of a key's lockout after `previous` earlier lockouts.
+fn lockout_duration(previous: u32) -> Duration {
+    BASE_LOCKOUT
+        .checked_mul(2u32.saturating_pow(previous))
+        .map_or(MAX_LOCKOUT, |duration| duration.min(MAX_LOCKOUT))
+}
+
+/// Delay for an action after `failures` recent failures across all of its
+/// identifiers.
+fn global_delay(failures: u32) -> Duration {
+    let excess = failures.saturating_sub(MAX_GLOBAL_FAILURES - 1);
+    GLOBAL_DELAY_STEP
+        .checked_mul(excess)
+        .map_or(MAX_GLOBAL_DELAY, |delay| delay.min(MAX_GLOBAL_DELAY))
+}
+
+/// Counts failed authentication attempts and enforces lockouts.
+#[derive(Debug, Default)]
+pub struct AuthRateLimiter {
+    attempts: Mutex<HashMap<(AuthAction, String), Attempts>>,
+    global: Mutex<HashMap<AuthAction, Attempts>>,
+}
+
+impl AuthRateLimiter {
+    /// Creates an empty limiter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns an error if `identifier` is locked out.
+    pub fn check(&self, action: AuthAction, identifier: &str) -> Result<(), String> {
+        let now = Instant::now();
+        let attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+        let remaining = attempts
+            .get(&(action, identifier.to_string()))
+            .and_then(|entry| entry.remaining_lockout(now));
+
+        match remaining {
+            Some(remaining) => Err(format!(
+                "Too many failed attempts. Try again in {} seconds.",
+                remaining.as_secs().max(1)
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// How long to hold an attempt at `action` back, given how many
+    /// attempts at it have recently failed across every identifier.
+    pub fn delay(&self, action: AuthAction) -> Duration {
+        let global = self.global.lock().unwrap_or_else(|e| e.into_inner());
+        global.get(&action).map_or(Duration::ZERO, |entry| {
+            global_delay(entry.recent_failures(Instant::now()))
+        })
+    }
+
+    /// Records a failed attempt, returning the lockout it starts, if any.
+    pub fn record_failure(&self, action: AuthAction, identifier: &str) -> Option<Lockout> {
+        let now = Instant::now();
+        self.global
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(action)
+            .or_default()
+            .count(now);
+
+        let mut attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+        attempts
+            .entry((action, identifier.to_string()))
+            .or_default()
+            .fail(now, MAX_FAILURES)
+            .map(|(failures, duration)| Lockout {
+                identifier: identifier.to_string(),
+                failures,
+                duration,
+            })
+    }
+
+    /// Forgets an identifier's failed attempts and lockout history after a
+    /// successful attempt.
+    pub fn record_success(&self, action: AuthAction, identifier: &str) {
+        let mut attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+        attempts.remove(&(action, identifier.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locks_out_after_repeated_failures() {
+        let limiter = AuthRateLimiter::new();
+        for _ in 0..MAX_FAILURES - 1 {
+            assert!(limiter
+                .record_failure(AuthAction::Login, "a@example.org")
+                .is_none());
+        }
+        assert!(limiter.check(AuthAction::Login, "a@example.org").is_ok());
+
+        let lockout = limiter
+            .record_failure(AuthAction::Login, "a@example.org")
+            .unwrap();
+        assert_eq!(lockout.duration, BASE_LOCKOUT);
+        assert!(limiter.check(AuthAction::Login, "a@example.org").is_err());
+
+        // Other identifiers and actions are unaffected
+        assert!(limiter.check(AuthAction::Login, "b@example.org").is_ok());
+        assert!(limiter
+            .check(AuthAction::RefreshToken, "a@example.org")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_success_clears_failures() {
+        let limiter = AuthRateLimiter::new();
+        for _ in 0..MAX_FAILURES - 1 {
+            limiter.record_failure(AuthAction::WalletSignature, "0xabc");
+        }
+        limiter.record_success(AuthAction::WalletSignature, "0xabc");
+        assert!(limiter
+            .record_failure(AuthAction::WalletSignature, "0xabc")
+            .is_none());
+    }
+
+    #[test]
+    fn test_many_identifiers_slow_the_action_without_locking_it_out() {
+        let limiter = AuthRateLimiter::new();
+        for i in 0..MAX_GLOBAL_FAILURES - 1 {
+            limiter.record_failure(AuthAction::AcceptInvitation, &i.to_string());
+        }
+        assert_eq!(limiter.delay(AuthAction::AcceptInvitation), Duration::ZERO);
+
+        limiter.record_failure(AuthAction::AcceptInvitation, "one-more");
+        assert_eq!(
+            limiter.delay(AuthAction::AcceptInvitation),
+            GLOBAL_DELAY_STEP
+        );
+        assert!(limiter
+            .check(AuthAction::AcceptInvitation, "never-tried")
+            .is_ok());
+        assert_eq!(limiter.delay(AuthAction::Login), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_global_delay_is_capped() {
+        assert_eq!(global_delay(0), Duration::ZERO);
+        assert_eq!(global_delay(MAX_GLOBAL_FAILURES + 1), GLOBAL_DELAY_STEP * 2);
+        assert_eq!(global_delay(u32::MAX), MAX_GLOBAL_DELAY);
+    }
+
+    #[test]
+    fn test_lockouts_grow_exponentially() {
+        assert_eq!(lockout_duration(0), BASE_LOCKOUT);
+        assert_eq!(lockout_duration(1), BASE_LOCKOUT * 2);
+        assert_eq!(lockout_duration(3), BASE_LOCKOUT * 8);
+        assert_eq!(lockout_duration(40), MAX_LOCKOUT);
+    }
+}
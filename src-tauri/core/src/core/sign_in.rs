@@ -0,0 +1,415 @@
+//! Structured wallet sign-in messages.
+//!
+//! EVM wallets sign an EIP-4361 (Sign-In With Ethereum) message. Solana and
+//! Substrate wallets sign the same layout with their own account type, as
+//! described by CAIP-122 and used by Sign-In With Solana. The message binds
+//! the signature to this app's domain, a single-use nonce, and an expiry, so
+//! a signature captured here or made for another app can't be replayed.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+
+/// Domain requesting the sign-in.
+pub const DOMAIN: &str = "pacioli.app";
+
+/// URI of the app requesting the sign-in.
+pub const URI: &str = "tauri://localhost";
+
+/// Statement shown to the user in the wallet.
+pub const STATEMENT: &str = "Sign in to Pacioli";
+
+/// Message version.
+const VERSION: &str = "1";
+
+/// Length of a generated nonce. EIP-4361 requires at least 8 alphanumeric
+/// characters.
+const NONCE_LENGTH: usize = 17;
+
+/// Clock skew tolerated between the issue time and now.
+const MAX_CLOCK_SKEW_SECONDS: i64 = 60;
+
+/// Account type named in the message header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountKind {
+    /// EIP-4361.
+    Ethereum,
+    /// Sign-In With Solana.
+    Solana,
+    /// CAIP-122 for Substrate accounts.
+    Substrate,
+}
+
+impl AccountKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            AccountKind::Ethereum => "Ethereum",
+            AccountKind::Solana => "Solana",
+            AccountKind::Substrate => "Substrate",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "Ethereum" => Some(AccountKind::Ethereum),
+            "Solana" => Some(AccountKind::Solana),
+            "Substrate" => Some(AccountKind::Substrate),
+            _ => None,
+        }
+    }
+}
+
+/// A sign-in message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignInMessage {
+    /// Account type.
+    pub kind: AccountKind,
+    /// Domain requesting the sign-in.
+    pub domain: String,
+    /// Signing account address.
+    pub address: String,
+    /// Statement shown to the user.
+    pub statement: Option<String>,
+    /// URI of the app requesting the sign-in.
+    pub uri: String,
+    /// Message version.
+    pub version: String,
+    /// Chain the account is on; required for Ethereum.
+    pub chain_id: Option<String>,
+    /// Single-use nonce.
+    pub nonce: String,
+    /// When the message was issued.
+    pub issued_at: DateTime<Utc>,
+    /// When the message stops being valid.
+    pub expiration_time: Option<DateTime<Utc>>,
+}
+
+impl SignInMessage {
+    /// Creates a message for this app.
+    ///
+    /// `chain_id` comes from the client and is written into the message
+    /// verbatim, so it must be a numeric EVM chain ID or a CAIP-2 chain ID.
+    pub fn new(
+        kind: AccountKind,
+        address: &str,
+        chain_id: Option<String>,
+        nonce: &str,
+        issued_at: DateTime<Utc>,
+        expiration_time: DateTime<Utc>,
+    ) -> Result<Self, String> {
+        if let Some(chain_id) = &chain_id {
+            if !is_valid_chain_id(chain_id) {
+                return Err(format!("Invalid chain ID: {:?}", chain_id));
+            }
+        }
+        let chain_id = match kind {
+            AccountKind::Ethereum => chain_id.or_else(|| Some("1".to_string())),
+            AccountKind::Solana | AccountKind::Substrate => chain_id,
+        };
+
+        Ok(Self {
+            kind,
+            domain: DOMAIN.to_string(),
+            address: address.to_string(),
+            statement: Some(STATEMENT.to_string()),
+            uri: URI.to_string(),
+            version: VERSION.to_string(),
+            chain_id,
+            nonce: nonce.to_string(),
+            issued_at,
+            expiration_time: Some(expiration_time),
+        })
+    }
+
+    /// Parses a message in the EIP-4361 layout.
+    pub fn parse(message: &str) -> Result<Self, String> {
+        let mut lines = message.lines();
+
+        let header = lines.next().ok_or("Empty sign-in message")?;
+        let (domain, rest) = header
+            .split_once(" wants you to sign in with your ")
+            .ok_or("Invalid sign-in message header")?;
+        let kind = rest
+            .strip_suffix(" account:")
+            .and_then(AccountKind::parse)
+            .ok_or("Invalid sign-in message account type")?;
+
+        let address = lines
+            .next()
+            .filter(|line| !line.is_empty())
+            .ok_or("Sign-in message is missing the address")?;
+        if lines.next() != Some("") {
+            return Err("Invalid sign-in message layout".to_string());
+        }
+
+        let mut next = lines.next();
+        let mut statement = None;
+        if let Some(line) = next.filter(|line| !line.starts_with("URI: ")) {
+            statement = Some(line.to_string());
+            if lines.next() != Some("") {
+                return Err("Invalid sign-in message layout".to_string());
+            }
+            next = lines.next();
+        }
+
+        let mut uri = None;
+        let mut version = None;
+        let mut chain_id = None;
+        let mut nonce = None;
+        let mut issued_at = None;
+        let mut expiration_time = None;
+        while let Some(line) = next {
+            let (field, value) = line
+                .split_once(": ")
+                .ok_or_else(|| format!("Invalid sign-in message line: {}", line))?;
+            match field {
+                "URI" => uri = Some(value.to_string()),
+                "Version" => version = Some(value.to_string()),
+                "Chain ID" => chain_id = Some(value.to_string()),
+                "Nonce" => nonce = Some(value.to_string()),
+                "Issued At" => issued_at = Some(parse_time(value)?),
+                "Expiration Time" => expiration_time = Some(parse_time(value)?),
+                _ => return Err(format!("Unexpected sign-in message field: {}", field)),
+            }
+            next = lines.next();
+        }
+
+        Ok(Self {
+            kind,
+            domain: domain.to_string(),
+            address: address.to_string(),
+            statement,
+            uri: uri.ok_or("Sign-in message is missing the URI")?,
+            version: version.ok_or("Sign-in message is missing the version")?,
+            chain_id,
+            nonce: nonce.ok_or("Sign-in message is missing the nonce")?,
+            issued_at: issued_at.ok_or("Sign-in message is missing the issue time")?,
+            expiration_time,
+        })
+    }
+
+    /// Checks the message was issued by this app to `address` with `nonce`
+    /// and is valid at `now`.
+    pub fn validate(&self, address: &str, nonce: &str, now: DateTime<Utc>) -> Result<(), String> {
+        if self.domain != DOMAIN || self.uri != URI {
+            return Err("Sign-in message is for a different app".to_string());
+        }
+        if self.version != VERSION {
+            return Err(format!(
+                "Unsupported sign-in message version: {}",
+                self.version
+            ));
+        }
+        if self.kind == AccountKind::Ethereum && self.chain_id.is_none() {
+            return Err("Sign-in message is missing the chain ID".to_string());
+        }
+        if !self.address.eq_ignore_ascii_case(address) {
+            return Err("Sign-in message is for a different address".to_string());
+        }
+        if self.nonce != nonce {
+            return Err("Sign-in message nonce does not match".to_string());
+        }
+        if self.issued_at > now + chrono::Duration::seconds(MAX_CLOCK_SKEW_SECONDS) {
+            return Err("Sign-in message is issued in the future".to_string());
+        }
+        if self.expiration_time.is_some_and(|expiry| expiry <= now) {
+            return Err("Sign-in message has expired".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for SignInMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} wants you to sign in with your {} account:",
+            self.domain,
+            self.kind.as_str()
+        )?;
+        writeln!(f, "{}", self.address)?;
+        writeln!(f)?;
+        if let Some(statement) = &self.statement {
+            writeln!(f, "{}", statement)?;
+            writeln!(f)?;
+        }
+        writeln!(f, "URI: {}", self.uri)?;
+        writeln!(f, "Version: {}", self.version)?;
+        if let Some(chain_id) = &self.chain_id {
+            writeln!(f, "Chain ID: {}", chain_id)?;
+        }
+        writeln!(f, "Nonce: {}", self.nonce)?;
+        write!(f, "Issued At: {}", format_time(self.issued_at))?;
+        if let Some(expiry) = self.expiration_time {
+            write!(f, "\nExpiration Time: {}", format_time(expiry))?;
+        }
+        Ok(())
+    }
+}
+
+/// Generates a nonce in the form EIP-4361 requires.
+pub fn generate_nonce() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(NONCE_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+/// Whether `chain_id` is a numeric EVM chain ID or a CAIP-2 chain ID
+/// (`namespace:reference`).
+fn is_valid_chain_id(chain_id: &str) -> bool {
+    if !chain_id.is_empty() && chain_id.len() <= 20 && chain_id.bytes().all(|b| b.is_ascii_digit())
+    {
+        return true;
+    }
+    let Some((namespace, reference)) = chain_id.split_once(':') else {
+        return false;
+    };
+    (3..=8).contains(&namespace.len())
+        && namespace
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        && (1..=32).contains(&reference.len())
+        && reference
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| format!("Invalid sign-in message time: {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn sample(kind: AccountKind, address: &str) -> SignInMessage {
+        let issued_at = Utc.with_ymd_and_hms(2026, 5, 1, 12, 0, 0).unwrap();
+        SignInMessage::new(
+            kind,
+            address,
+            None,
+            "abcDEF123456789xy",
+            issued_at,
+            issued_at + Duration::minutes(5),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_ethereum_message_follows_eip_4361() {
+        let message = sample(
+            AccountKind::Ethereum,
+            "0x742d35Cc6634C0532925a3b844Bc9e7595f8B123",
+        );
+        assert_eq!(
+            message.to_string(),
+            "pacioli.app wants you to sign in with your Ethereum account:\n\
+             0x742d35Cc6634C0532925a3b844Bc9e7595f8B123\n\
+             \n\
+             Sign in to Pacioli\n\
+             \n\
+             URI: tauri://localhost\n\
+             Version: 1\n\
+             Chain ID: 1\n\
+             Nonce: abcDEF123456789xy\n\
+             Issued At: 2026-05-01T12:00:00Z\n\
+             Expiration Time: 2026-05-01T12:05:00Z"
+        );
+    }
+
+    #[test]
+    fn test_messages_round_trip() {
+        for (kind, address) in [
+            (
+                AccountKind::Ethereum,
+                "0x742d35Cc6634C0532925a3b844Bc9e7595f8B123",
+            ),
+            (
+                AccountKind::Solana,
+                "7EcDhSYGxXyscszYEp35KHN8vvw3svAuLKTzXwCFLtV",
+            ),
+            (
+                AccountKind::Substrate,
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            ),
+        ] {
+            let message = sample(kind, address);
+            assert_eq!(SignInMessage::parse(&message.to_string()).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_replay() {
+        let message = sample(AccountKind::Substrate, "5Grwva");
+        let now = message.issued_at + Duration::minutes(1);
+        assert!(message.validate("5Grwva", "abcDEF123456789xy", now).is_ok());
+
+        assert!(message
+            .validate("5Other", "abcDEF123456789xy", now)
+            .is_err());
+        assert!(message.validate("5Grwva", "another-nonce", now).is_err());
+        assert!(message
+            .validate(
+                "5Grwva",
+                "abcDEF123456789xy",
+                message.issued_at + Duration::minutes(6)
+            )
+            .is_err());
+
+        let foreign = SignInMessage {
+            domain: "evil.example".to_string(),
+            ..message
+        };
+        assert!(foreign
+            .validate("5Grwva", "abcDEF123456789xy", now)
+            .is_err());
+    }
+
+    #[test]
+    fn test_new_rejects_invalid_chain_id() {
+        let issued_at = Utc.with_ymd_and_hms(2026, 5, 1, 12, 0, 0).unwrap();
+        let message = |chain_id: &str| {
+            SignInMessage::new(
+                AccountKind::Ethereum,
+                "0x742d35Cc6634C0532925a3b844Bc9e7595f8B123",
+                Some(chain_id.to_string()),
+                "abcDEF123456789xy",
+                issued_at,
+                issued_at + Duration::minutes(5),
+            )
+        };
+
+        assert_eq!(message("137").unwrap().chain_id.as_deref(), Some("137"));
+        assert!(message("eip155:1").is_ok());
+        assert!(message("solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp").is_ok());
+
+        for chain_id in [
+            "",
+            "1\nURI: https://evil.example",
+            "eip155:1\nNonce: attacker",
+            "1 ",
+            "EIP155:1",
+            "eip155:",
+            ":1",
+            "ab:1",
+            "eip155:1:2",
+        ] {
+            assert!(message(chain_id).is_err(), "accepted {:?}", chain_id);
+        }
+    }
+
+    #[test]
+    fn test_generate_nonce() {
+        let nonce = generate_nonce();
+        assert_eq!(nonce.len(), NONCE_LENGTH);
+        assert!(nonce.chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+}
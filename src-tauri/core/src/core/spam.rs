@@ -0,0 +1,188 @@
+//! Heuristics for spotting spam and scam tokens.
+//!
+//! Wallets collect unsolicited tokens whose names advertise phishing sites,
+//! that have no market, or that were minted with absurd supplies. Each
+//! heuristic contributes a weighted signal; tokens at or above
+//! [`SPAM_THRESHOLD`] are treated as spam unless the user allows them.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Score at which a token is considered spam.
+pub const SPAM_THRESHOLD: u32 = 50;
+
+/// Supply, in whole tokens, above which a token is suspicious.
+const HUGE_SUPPLY: i64 = 1_000_000_000_000_000;
+
+/// Fragments of URLs and handles used to lure holders to phishing sites.
+const URL_MARKERS: &[&str] = &[
+    "http", "www.", ".com", ".io", ".xyz", ".org", ".net", ".app", ".site", ".top", ".gift",
+    "t.me/",
+];
+
+/// Words common in bait token names.
+const BAIT_WORDS: &[&str] = &["claim", "visit", "voucher", "bonus", "free ", "eligible"];
+
+/// Reason a token looks like spam.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpamSignal {
+    /// The contract is on a known spam blocklist.
+    Blocklisted,
+    /// The name or symbol contains a URL, bait wording, or lookalike characters.
+    SuspiciousName,
+    /// The token has no market or liquidity.
+    ZeroLiquidity,
+    /// The total supply is implausibly large.
+    HugeSupply,
+    /// The tokens arrived without the wallet initiating anything.
+    UnsolicitedAirdrop,
+}
+
+impl SpamSignal {
+    /// Contribution of the signal to the spam score.
+    pub fn weight(&self) -> u32 {
+        match self {
+            SpamSignal::Blocklisted => 100,
+            SpamSignal::SuspiciousName => 60,
+            SpamSignal::ZeroLiquidity => 30,
+            SpamSignal::HugeSupply => 25,
+            SpamSignal::UnsolicitedAirdrop => 25,
+        }
+    }
+}
+
+/// What is known about a token being assessed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenCandidate {
+    /// Chain the token lives on.
+    pub chain_id: String,
+    /// Contract or mint address, if any.
+    pub token_address: Option<String>,
+    /// Token symbol.
+    pub symbol: Option<String>,
+    /// Token name.
+    pub name: Option<String>,
+    /// Total supply in whole tokens, if known.
+    pub total_supply: Option<Decimal>,
+    /// Whether the token has a market; `None` if unknown.
+    pub has_liquidity: Option<bool>,
+    /// Whether the tokens were received without the wallet initiating it.
+    #[serde(default)]
+    pub unsolicited: bool,
+}
+
+/// Result of assessing a token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpamAssessment {
+    /// Chain the token lives on.
+    pub chain_id: String,
+    /// Contract or mint address, if any.
+    pub token_address: Option<String>,
+    /// Token symbol.
+    pub symbol: Option<String>,
+    /// Sum of the signal weights.
+    pub score: u32,
+    /// Signals that fired.
+    pub signals: Vec<SpamSignal>,
+    /// Whether the token is treated as spam.
+    pub is_spam: bool,
+}
+
+/// Whether a token name or symbol looks like bait.
+pub fn has_suspicious_name(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    !text.is_ascii()
+        || URL_MARKERS.iter().any(|m| lower.contains(m))
+        || BAIT_WORDS.iter().any(|w| lower.contains(w))
+}
+
+/// Scores a token. `blocklisted` reports whether it is on a known blocklist.
+pub fn assess(candidate: &TokenCandidate, blocklisted: bool) -> SpamAssessment {
+    let mut signals = Vec::new();
+
+    if blocklisted {
+        signals.push(SpamSignal::Blocklisted);
+    }
+    if [&candidate.name, &candidate.symbol]
+        .into_iter()
+        .flatten()
+        .any(|text| has_suspicious_name(text))
+    {
+        signals.push(SpamSignal::SuspiciousName);
+    }
+    if candidate.has_liquidity == Some(false) {
+        signals.push(SpamSignal::ZeroLiquidity);
+    }
+    if candidate
+        .total_supply
+        .is_some_and(|supply| supply > Decimal::from(HUGE_SUPPLY))
+    {
+        signals.push(SpamSignal::HugeSupply);
+    }
+    if candidate.unsolicited {
+        signals.push(SpamSignal::UnsolicitedAirdrop);
+    }
+
+    let score = signals.iter().map(SpamSignal::weight).sum();
+    SpamAssessment {
+        chain_id: candidate.chain_id.clone(),
+        token_address: candidate.token_address.clone(),
+        symbol: candidate.symbol.clone(),
+        score,
+        signals,
+        is_spam: score >= SPAM_THRESHOLD,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(symbol: &str) -> TokenCandidate {
+        TokenCandidate {
+            chain_id: "ethereum".to_string(),
+            token_address: Some("0xabc".to_string()),
+            symbol: Some(symbol.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_suspicious_names() {
+        assert!(has_suspicious_name("Visit uni-rewards.xyz to claim"));
+        assert!(has_suspicious_name("USDС")); // Cyrillic С
+        assert!(!has_suspicious_name("USDC"));
+        assert!(!has_suspicious_name("Wrapped Ether"));
+    }
+
+    #[test]
+    fn test_assess_combines_signals() {
+        let legit = assess(&candidate("USDC"), false);
+        assert!(!legit.is_spam);
+        assert!(legit.signals.is_empty());
+
+        let mut airdrop = candidate("MOON");
+        airdrop.has_liquidity = Some(false);
+        airdrop.unsolicited = true;
+        let assessment = assess(&airdrop, false);
+        assert_eq!(assessment.score, 55);
+        assert!(assessment.is_spam);
+
+        let bait = assess(&candidate("$ claim at dao.gift"), false);
+        assert!(bait.is_spam);
+
+        assert!(assess(&candidate("ANY"), true).is_spam);
+    }
+
+    #[test]
+    fn test_huge_supply_alone_is_not_spam() {
+        let mut token = candidate("BIG");
+        token.total_supply = Some(Decimal::from(HUGE_SUPPLY) * Decimal::from(10));
+        let assessment = assess(&token, false);
+        assert_eq!(assessment.signals, vec![SpamSignal::HugeSupply]);
+        assert!(!assessment.is_spam);
+    }
+}
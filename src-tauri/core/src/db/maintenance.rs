@@ -0,0 +1,179 @@
+//! Database maintenance: integrity checks, vacuuming, and size statistics.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+/// A row that references a missing parent, from `PRAGMA foreign_key_check`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ForeignKeyViolation {
+    /// Table containing the dangling reference.
+    pub table: String,
+    /// Rowid of the offending row, if the table has one.
+    pub rowid: Option<i64>,
+    /// Table the foreign key points to.
+    pub parent: String,
+    /// Index of the foreign key constraint on the table.
+    pub fkid: i64,
+}
+
+/// Result of [`integrity_check`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    /// Whether no problems were found.
+    pub ok: bool,
+    /// Problems reported by `PRAGMA integrity_check`.
+    pub integrity_errors: Vec<String>,
+    /// Dangling foreign key references.
+    pub foreign_key_violations: Vec<ForeignKeyViolation>,
+}
+
+/// Result of [`vacuum`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VacuumResult {
+    /// Database size before vacuuming, in bytes.
+    pub size_before: i64,
+    /// Database size after vacuuming, in bytes.
+    pub size_after: i64,
+    /// Space reclaimed, in bytes.
+    pub reclaimed: i64,
+}
+
+/// Row count for one table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableStats {
+    /// Table name.
+    pub name: String,
+    /// Number of rows.
+    pub row_count: i64,
+}
+
+/// Result of [`stats`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseStats {
+    /// Database size in bytes.
+    pub file_size: i64,
+    /// Unused space that a vacuum would reclaim, in bytes.
+    pub free_space: i64,
+    /// Page size in bytes.
+    pub page_size: i64,
+    /// Row counts per table, largest first.
+    pub tables: Vec<TableStats>,
+}
+
+/// Quotes an identifier for use in SQL.
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Turns `PRAGMA integrity_check` output into a list of problems.
+fn integrity_errors(rows: Vec<String>) -> Vec<String> {
+    if rows.len() == 1 && rows[0] == "ok" {
+        Vec::new()
+    } else {
+        rows
+    }
+}
+
+/// Returns `(page_size, page_count, freelist_count)`.
+async fn page_info(pool: &SqlitePool) -> Result<(i64, i64, i64)> {
+    let page_size: i64 = sqlx::query_scalar("PRAGMA page_size")
+        .fetch_one(pool)
+        .await?;
+    let page_count: i64 = sqlx::query_scalar("PRAGMA page_count")
+        .fetch_one(pool)
+        .await?;
+    let freelist_count: i64 = sqlx::query_scalar("PRAGMA freelist_count")
+        .fetch_one(pool)
+        .await?;
+    Ok((page_size, page_count, freelist_count))
+}
+
+/// Runs `PRAGMA integrity_check` and `PRAGMA foreign_key_check`.
+pub async fn integrity_check(pool: &SqlitePool) -> Result<IntegrityReport> {
+    let rows: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await?;
+    let integrity_errors = integrity_errors(rows);
+
+    let foreign_key_violations: Vec<ForeignKeyViolation> =
+        sqlx::query_as("PRAGMA foreign_key_check")
+            .fetch_all(pool)
+            .await?;
+
+    Ok(IntegrityReport {
+        ok: integrity_errors.is_empty() && foreign_key_violations.is_empty(),
+        integrity_errors,
+        foreign_key_violations,
+    })
+}
+
+/// Rebuilds the database file to reclaim free pages, then refreshes the
+/// query planner statistics.
+pub async fn vacuum(pool: &SqlitePool) -> Result<VacuumResult> {
+    let (page_size, before, _) = page_info(pool).await?;
+
+    sqlx::query("VACUUM").execute(pool).await?;
+    sqlx::query("PRAGMA optimize").execute(pool).await?;
+
+    let (_, after, _) = page_info(pool).await?;
+    let size_before = before * page_size;
+    let size_after = after * page_size;
+
+    Ok(VacuumResult {
+        size_before,
+        size_after,
+        reclaimed: (size_before - size_after).max(0),
+    })
+}
+
+/// Collects the database size and per-table row counts.
+pub async fn stats(pool: &SqlitePool) -> Result<DatabaseStats> {
+    let (page_size, page_count, freelist_count) = page_info(pool).await?;
+
+    let names: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut tables = Vec::with_capacity(names.len());
+    for name in names {
+        let row_count: i64 =
+            sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", quote_identifier(&name)))
+                .fetch_one(pool)
+                .await?;
+        tables.push(TableStats { name, row_count });
+    }
+    tables.sort_by(|a, b| b.row_count.cmp(&a.row_count).then(a.name.cmp(&b.name)));
+
+    Ok(DatabaseStats {
+        file_size: page_count * page_size,
+        free_space: freelist_count * page_size,
+        page_size,
+        tables,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("transactions"), "\"transactions\"");
+        assert_eq!(quote_identifier("we\"ird"), "\"we\"\"ird\"");
+    }
+
+    #[test]
+    fn test_integrity_errors() {
+        assert!(integrity_errors(vec!["ok".to_string()]).is_empty());
+        let problems = vec!["row 3 missing from index idx_x".to_string()];
+        assert_eq!(integrity_errors(problems.clone()), problems);
+    }
+}
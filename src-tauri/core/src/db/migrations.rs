@@ -0,0 +1,257 @@
+//! Versioned schema migrations.
+//!
+//! Migrations are the files in `migrations/`, embedded at compile time. Each
+//! pending migration is applied in its own transaction and recorded in
+//! `_sqlx_migrations`. Before an existing database is upgraded, a copy is
+//! written to a `backups/` directory next to it, so a failed upgrade can't
+//! leave the user's books unrecoverable.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::migrate::Migrator;
+use sqlx::SqlitePool;
+
+/// Embedded migrations from `migrations/`.
+pub static MIGRATOR: Migrator = sqlx::migrate!("../migrations");
+
+/// Number of pre-migration backups kept; older ones are deleted.
+const BACKUPS_TO_KEEP: usize = 5;
+
+/// Schema version information for the open database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaVersion {
+    /// Latest applied migration version, `None` for an empty database.
+    pub current_version: Option<i64>,
+    /// Latest migration shipped with this build.
+    pub latest_version: i64,
+    /// Number of applied migrations.
+    pub applied_count: usize,
+    /// Versions shipped with this build but not yet applied.
+    pub pending_versions: Vec<i64>,
+    /// When the latest migration was applied.
+    pub last_applied_at: Option<DateTime<Utc>>,
+}
+
+/// Result of running migrations at startup.
+#[derive(Debug, Clone)]
+pub struct MigrationOutcome {
+    /// Versions applied by this run.
+    pub applied: Vec<i64>,
+    /// Backup written before migrating, if any.
+    pub backup_path: Option<PathBuf>,
+}
+
+/// Latest migration version shipped with this build.
+pub fn latest_version() -> i64 {
+    MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0)
+}
+
+/// Versions recorded as applied, or empty if the database is new.
+async fn applied_versions(pool: &SqlitePool) -> Result<Vec<(i64, DateTime<Utc>)>> {
+    let table_exists: Option<(String,)> = sqlx::query_as(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if table_exists.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let rows: Vec<(i64, DateTime<Utc>)> = sqlx::query_as(
+        "SELECT version, installed_on FROM _sqlx_migrations WHERE success = 1 ORDER BY version",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Migration versions not yet in `applied`, in order.
+fn pending_versions(applied: &[i64]) -> Vec<i64> {
+    let applied: HashSet<i64> = applied.iter().copied().collect();
+    let mut pending: Vec<i64> = MIGRATOR
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration() && !applied.contains(&m.version))
+        .map(|m| m.version)
+        .collect();
+    pending.sort_unstable();
+    pending
+}
+
+/// Reads the schema version of the open database.
+pub async fn schema_version(pool: &SqlitePool) -> Result<SchemaVersion> {
+    let applied = applied_versions(pool).await?;
+    let versions: Vec<i64> = applied.iter().map(|(v, _)| *v).collect();
+
+    Ok(SchemaVersion {
+        current_version: versions.last().copied(),
+        latest_version: latest_version(),
+        applied_count: versions.len(),
+        pending_versions: pending_versions(&versions),
+        last_applied_at: applied.iter().map(|(_, at)| *at).max(),
+    })
+}
+
+/// Extracts the database file path from a `sqlite:` URL.
+///
+/// Returns `None` for in-memory databases.
+pub fn database_file(url: &str) -> Option<PathBuf> {
+    let path = url
+        .strip_prefix("sqlite://")
+        .or_else(|| url.strip_prefix("sqlite:"))
+        .unwrap_or(url);
+    let path = path.split('?').next().unwrap_or(path);
+    if path.is_empty() || path == ":memory:" {
+        return None;
+    }
+    Some(PathBuf::from(path))
+}
+
+/// Backup file name for a database at `version`, e.g.
+/// `pacioli-pre-migration-v20260401000001-20260501T120000Z.db`.
+fn backup_file_name(version: i64, now: DateTime<Utc>) -> String {
+    format!(
+        "pacioli-pre-migration-v{}-{}.db",
+        version,
+        now.format("%Y%m%dT%H%M%SZ")
+    )
+}
+
+/// Writes a consistent copy of the database with `VACUUM INTO`.
+async fn backup_database(pool: &SqlitePool, db_file: &Path, version: i64) -> Result<PathBuf> {
+    let dir = db_file
+        .parent()
+        .map(|p| p.join("backups"))
+        .unwrap_or_else(|| PathBuf::from("backups"));
+    std::fs::create_dir_all(&dir).context("Failed to create backup directory")?;
+
+    let backup_path = dir.join(backup_file_name(version, Utc::now()));
+    let target = backup_path.to_string_lossy().replace('\'', "''");
+    sqlx::query(&format!("VACUUM INTO '{}'", target))
+        .execute(pool)
+        .await
+        .context("Failed to back up database before migrating")?;
+
+    prune_backups(&dir);
+    Ok(backup_path)
+}
+
+/// Deletes all but the newest pre-migration backups.
+fn prune_backups(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut backups: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("pacioli-pre-migration-"))
+        })
+        .collect();
+    // Names embed the version and timestamp, so they sort chronologically.
+    backups.sort();
+    let excess = backups.len().saturating_sub(BACKUPS_TO_KEEP);
+    for old in backups.into_iter().take(excess) {
+        let _ = std::fs::remove_file(old);
+    }
+}
+
+/// Applies pending migrations, backing up an existing database first.
+///
+/// `database_url` locates the file to back up; fresh and in-memory databases
+/// are migrated without a backup.
+pub async fn run_migrations(pool: &SqlitePool, database_url: &str) -> Result<MigrationOutcome> {
+    let applied: Vec<i64> = applied_versions(pool)
+        .await?
+        .into_iter()
+        .map(|(v, _)| v)
+        .collect();
+    let pending = pending_versions(&applied);
+
+    if pending.is_empty() {
+        return Ok(MigrationOutcome {
+            applied: Vec::new(),
+            backup_path: None,
+        });
+    }
+
+    let backup_path = match (applied.last(), database_file(database_url)) {
+        (Some(&version), Some(file)) if file.exists() => {
+            Some(backup_database(pool, &file, version).await?)
+        }
+        _ => None,
+    };
+
+    if let Err(e) = MIGRATOR.run(pool).await {
+        return Err(match &backup_path {
+            Some(path) => anyhow::anyhow!(
+                "Database migration failed: {}. A backup was saved to {}",
+                e,
+                path.display()
+            ),
+            None => anyhow::anyhow!("Database migration failed: {}", e),
+        });
+    }
+
+    Ok(MigrationOutcome {
+        applied: pending,
+        backup_path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_file() {
+        assert_eq!(
+            database_file("sqlite:/data/pacioli.db?mode=rwc"),
+            Some(PathBuf::from("/data/pacioli.db"))
+        );
+        assert_eq!(
+            database_file("sqlite:///data/pacioli.db"),
+            Some(PathBuf::from("/data/pacioli.db"))
+        );
+        assert_eq!(database_file("sqlite::memory:"), None);
+    }
+
+    #[test]
+    fn test_pending_versions() {
+        let all: Vec<i64> = MIGRATOR.iter().map(|m| m.version).collect();
+        assert!(pending_versions(&all).is_empty());
+        assert_eq!(pending_versions(&[]).len(), all.len());
+        assert_eq!(
+            pending_versions(&all[..all.len() - 1]),
+            vec![latest_version()]
+        );
+    }
+
+    #[test]
+    fn test_backup_file_name_sorts_chronologically() {
+        let earlier = backup_file_name(
+            20260401000001,
+            DateTime::parse_from_rfc3339("2026-05-01T12:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        );
+        let later = backup_file_name(
+            20260402000001,
+            DateTime::parse_from_rfc3339("2026-06-01T08:30:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        );
+        assert_eq!(
+            earlier,
+            "pacioli-pre-migration-v20260401000001-20260501T120000Z.db"
+        );
+        assert!(earlier < later);
+    }
+}
@@ -1,7 +1,13 @@
 //! Database module for persistence operations.
 
+/// Integrity checks, vacuuming, and size statistics.
+pub mod maintenance;
+/// Versioned schema migrations with pre-migration backups.
+pub mod migrations;
 /// Multi-chain transaction storage for EVM, Substrate, Solana, and Bitcoin chains.
 pub mod multi_chain;
+/// Raw payload pruning with compressed archives.
+pub mod retention;
 /// Chain transaction repository for the legacy transaction storage system.
 pub mod transactions;
 
@@ -21,7 +27,7 @@ impl Database {
         let pool = SqlitePool::connect(database_url).await?;
 
         // Run migrations
-        sqlx::migrate!("./migrations").run(&pool).await?;
+        migrations::run_migrations(&pool, database_url).await?;
 
         Ok(Self { pool })
     }
@@ -6,7 +6,7 @@
 #![allow(dead_code)]
 
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Row, SqlitePool};
+use sqlx::{FromRow, QueryBuilder, Row, Sqlite, SqlitePool};
 
 // =============================================================================
 // MODELS
@@ -59,6 +59,7 @@ impl TxType {
     }
 
     /// Parses from database string representation.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s {
             "transfer" => TxType::Transfer,
@@ -99,6 +100,7 @@ impl TxStatus {
     }
 
     /// Parses from database string representation.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s {
             "success" => TxStatus::Success,
@@ -144,6 +146,7 @@ impl TokenType {
     }
 
     /// Parses from database string representation.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s {
             "erc20" => TokenType::Erc20,
@@ -183,6 +186,7 @@ impl WalletType {
     }
 
     /// Parses from database string representation.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s {
             "evm" => WalletType::Evm,
@@ -448,6 +452,71 @@ impl From<WalletRow> for Wallet {
     }
 }
 
+/// Rows per multi-row INSERT. Each row binds 12 parameters, which keeps
+/// statements under SQLite's historical 999-parameter limit.
+const INSERT_CHUNK_SIZE: usize = 80;
+
+/// A row that could not be written during a batch insert.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowError {
+    /// Position of the row in the input slice.
+    pub index: usize,
+    /// ID of the row.
+    pub id: String,
+    /// Database error message.
+    pub error: String,
+}
+
+/// Outcome of a batch insert.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchInsertResult {
+    /// Number of rows inserted or updated.
+    pub inserted: usize,
+    /// Rows that failed, in input order.
+    pub errors: Vec<RowError>,
+}
+
+/// Builds a multi-row upsert into `multi_chain_transactions`.
+fn upsert_transactions_query(txs: &[Transaction]) -> QueryBuilder<'_, Sqlite> {
+    let mut builder = QueryBuilder::new(
+        r#"
+        INSERT INTO multi_chain_transactions (
+            id, chain_id, hash, from_address, to_address,
+            value, fee, timestamp, block_number, tx_type,
+            status, raw_data
+        ) "#,
+    );
+    builder.push_values(txs, |mut row, tx| {
+        row.push_bind(&tx.id)
+            .push_bind(&tx.chain_id)
+            .push_bind(&tx.hash)
+            .push_bind(&tx.from_address)
+            .push_bind(&tx.to_address)
+            .push_bind(&tx.value)
+            .push_bind(&tx.fee)
+            .push_bind(tx.timestamp)
+            .push_bind(tx.block_number)
+            .push_bind(tx.tx_type.as_str())
+            .push_bind(tx.status.as_str())
+            .push_bind(&tx.raw_data);
+    });
+    builder.push(
+        r#"
+        ON CONFLICT(chain_id, hash) DO UPDATE SET
+            from_address = excluded.from_address,
+            to_address = excluded.to_address,
+            value = excluded.value,
+            fee = excluded.fee,
+            timestamp = excluded.timestamp,
+            block_number = excluded.block_number,
+            tx_type = excluded.tx_type,
+            status = excluded.status,
+            raw_data = excluded.raw_data
+        "#,
+    );
+    builder
+}
+
 // =============================================================================
 // REPOSITORY
 // =============================================================================
@@ -469,51 +538,69 @@ impl MultiChainRepository {
 
     /// Inserts multiple transactions with upsert semantics.
     ///
-    /// Returns the number of successfully inserted/updated transactions.
-    pub async fn insert_transactions(&self, txs: &[Transaction]) -> Result<usize, sqlx::Error> {
-        let mut count = 0;
+    /// The whole batch runs inside one SQLite transaction using multi-row
+    /// inserts of [`INSERT_CHUNK_SIZE`] rows. If a chunk fails, its rows are
+    /// retried one at a time so a single bad row doesn't discard its
+    /// neighbours; rows that still fail are reported in
+    /// [`BatchInsertResult::errors`]. Database errors outside individual rows
+    /// (e.g. the commit failing) roll back the entire batch.
+    pub async fn insert_transactions(
+        &self,
+        txs: &[Transaction],
+    ) -> Result<BatchInsertResult, sqlx::Error> {
+        let mut result = BatchInsertResult::default();
+        if txs.is_empty() {
+            return Ok(result);
+        }
 
-        for tx in txs {
-            let result = sqlx::query(
-                r#"
-                INSERT INTO multi_chain_transactions (
-                    id, chain_id, hash, from_address, to_address,
-                    value, fee, timestamp, block_number, tx_type,
-                    status, raw_data
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-                ON CONFLICT(chain_id, hash) DO UPDATE SET
-                    from_address = excluded.from_address,
-                    to_address = excluded.to_address,
-                    value = excluded.value,
-                    fee = excluded.fee,
-                    timestamp = excluded.timestamp,
-                    block_number = excluded.block_number,
-                    tx_type = excluded.tx_type,
-                    status = excluded.status,
-                    raw_data = excluded.raw_data
-                "#,
-            )
-            .bind(&tx.id)
-            .bind(&tx.chain_id)
-            .bind(&tx.hash)
-            .bind(&tx.from_address)
-            .bind(&tx.to_address)
-            .bind(&tx.value)
-            .bind(&tx.fee)
-            .bind(tx.timestamp)
-            .bind(tx.block_number)
-            .bind(tx.tx_type.as_str())
-            .bind(tx.status.as_str())
-            .bind(&tx.raw_data)
-            .execute(&self.pool)
-            .await;
+        let mut db_tx = self.pool.begin().await?;
 
-            if result.is_ok() {
-                count += 1;
+        for (chunk_index, chunk) in txs.chunks(INSERT_CHUNK_SIZE).enumerate() {
+            let offset = chunk_index * INSERT_CHUNK_SIZE;
+
+            sqlx::query("SAVEPOINT insert_chunk")
+                .execute(&mut *db_tx)
+                .await?;
+            let chunk_result = upsert_transactions_query(chunk)
+                .build()
+                .execute(&mut *db_tx)
+                .await;
+
+            match chunk_result {
+                Ok(_) => {
+                    sqlx::query("RELEASE insert_chunk")
+                        .execute(&mut *db_tx)
+                        .await?;
+                    result.inserted += chunk.len();
+                }
+                Err(_) => {
+                    sqlx::query("ROLLBACK TO insert_chunk")
+                        .execute(&mut *db_tx)
+                        .await?;
+                    sqlx::query("RELEASE insert_chunk")
+                        .execute(&mut *db_tx)
+                        .await?;
+
+                    for (row_index, tx) in chunk.iter().enumerate() {
+                        let row_result = upsert_transactions_query(std::slice::from_ref(tx))
+                            .build()
+                            .execute(&mut *db_tx)
+                            .await;
+                        match row_result {
+                            Ok(_) => result.inserted += 1,
+                            Err(e) => result.errors.push(RowError {
+                                index: offset + row_index,
+                                id: tx.id.clone(),
+                                error: e.to_string(),
+                            }),
+                        }
+                    }
+                }
             }
         }
 
-        Ok(count)
+        db_tx.commit().await?;
+        Ok(result)
     }
 
     /// Retrieves transactions for an address on a chain within a time range.
@@ -801,6 +888,30 @@ impl MultiChainRepository {
         Ok(())
     }
 
+    /// Records the last fully synced block of a sync still in progress, so an
+    /// interrupted sync resumes after it.
+    pub async fn set_sync_progress(
+        &self,
+        chain_id: &str,
+        address: &str,
+        last_block: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE address_sync_status
+            SET last_block_synced = ?
+            WHERE chain_id = ? AND address = ?
+            "#,
+        )
+        .bind(last_block)
+        .bind(chain_id)
+        .bind(address)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Sets sync status to error state.
     pub async fn set_sync_error(
         &self,
@@ -947,6 +1058,54 @@ impl MultiChainRepository {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    proptest! {
+        #[test]
+        fn prop_composite_ids_are_unique(
+            keys in prop::collection::hash_set(
+                (
+                    prop_oneof![
+                        Just("ethereum"),
+                        Just("polygon"),
+                        Just("arbitrum_one"),
+                        Just("1"),
+                        Just("137"),
+                        Just("solana"),
+                        Just("polkadot"),
+                    ],
+                    prop_oneof![
+                        "0x[0-9a-f]{64}",
+                        "[1-9A-HJ-NP-Za-km-z]{87,88}",
+                        "[0-9a-f]{64}",
+                    ],
+                ),
+                0..32,
+            )
+        ) {
+            let ids: std::collections::HashSet<String> = keys
+                .iter()
+                .map(|(chain, hash)| {
+                    Transaction::new(
+                        chain.to_string(),
+                        hash.clone(),
+                        String::new(),
+                        None,
+                        "0".to_string(),
+                        None,
+                        0,
+                        None,
+                        TxType::Transfer,
+                        TxStatus::Success,
+                        None,
+                    )
+                    .id
+                })
+                .collect();
+            prop_assert_eq!(ids.len(), keys.len());
+        }
+    }
 
     #[test]
     fn test_tx_type_conversion() {
@@ -986,4 +1145,91 @@ mod tests {
 
         assert_eq!(tx.id, "ethereum_0x123");
     }
+
+    #[test]
+    fn test_upsert_query_is_multi_row() {
+        let tx = |hash: &str| {
+            Transaction::new(
+                "ethereum".to_string(),
+                hash.to_string(),
+                "0xfrom".to_string(),
+                None,
+                "0".to_string(),
+                None,
+                1234567890,
+                None,
+                TxType::Transfer,
+                TxStatus::Success,
+                None,
+            )
+        };
+        let txs = vec![tx("0x1"), tx("0x2"), tx("0x3")];
+        let builder = upsert_transactions_query(&txs);
+        let sql = builder.sql();
+
+        assert_eq!(sql.matches('?').count(), 36);
+        assert_eq!(sql.matches("), (").count(), 2);
+        assert!(sql.contains("ON CONFLICT(chain_id, hash)"));
+    }
+
+    #[tokio::test]
+    async fn test_insert_transactions_keeps_rows_around_a_bad_row() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(include_str!(
+            "../../../migrations/20260118000001_multi_chain_transactions.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        let repo = MultiChainRepository::new(pool.clone());
+
+        let mut txs: Vec<Transaction> = (0..INSERT_CHUNK_SIZE + 20)
+            .map(|i| {
+                Transaction::new(
+                    "ethereum".to_string(),
+                    format!("0x{:x}", i),
+                    "0xfrom".to_string(),
+                    None,
+                    "0".to_string(),
+                    None,
+                    1234567890 + i as i64,
+                    None,
+                    TxType::Transfer,
+                    TxStatus::Success,
+                    None,
+                )
+            })
+            .collect();
+        // A different hash under an existing ID violates the primary key,
+        // which the (chain_id, hash) upsert doesn't cover.
+        let bad_index = INSERT_CHUNK_SIZE + 10;
+        txs[bad_index].id = txs[0].id.clone();
+
+        let result = repo.insert_transactions(&txs).await.unwrap();
+
+        assert_eq!(result.inserted, txs.len() - 1);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].index, bad_index);
+        assert_eq!(result.errors[0].id, txs[0].id);
+
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM multi_chain_transactions")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, (txs.len() - 1) as i64);
+        let hashes: Vec<String> =
+            sqlx::query_scalar("SELECT hash FROM multi_chain_transactions WHERE hash IN (?, ?, ?)")
+                .bind(&txs[bad_index - 1].hash)
+                .bind(&txs[bad_index].hash)
+                .bind(&txs[bad_index + 1].hash)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(hashes.len(), 2);
+        assert!(!hashes.contains(&txs[bad_index].hash));
+    }
 }
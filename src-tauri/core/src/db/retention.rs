@@ -0,0 +1,356 @@
+//! Raw payload retention: strips the raw API responses stored alongside old
+//! transactions, archiving them to a gzip sidecar file first.
+//!
+//! Only the raw payload is removed. Normalized fields (amounts, addresses,
+//! timestamps, classification) stay in place, so reports are unaffected.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Months, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use super::maintenance::{self, VacuumResult};
+
+/// Directory under the app data directory holding pruned payload archives.
+pub const ARCHIVE_DIR: &str = "archive";
+
+/// Rows read per query while archiving.
+const BATCH_SIZE: i64 = 500;
+
+/// A table whose raw payloads can be pruned.
+struct RawDataTable {
+    name: &'static str,
+    /// SQL expression giving the row's time in unix seconds.
+    unix_time: &'static str,
+}
+
+const RAW_DATA_TABLES: &[RawDataTable] = &[
+    RawDataTable {
+        name: "transactions",
+        unix_time: "CAST(strftime('%s', timestamp) AS INTEGER)",
+    },
+    RawDataTable {
+        name: "multi_chain_transactions",
+        unix_time: "timestamp",
+    },
+];
+
+/// Gzip writer for an archive file.
+type Archive = GzEncoder<BufWriter<File>>;
+
+/// Payloads that a prune would remove, from [`preview`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrunePreview {
+    /// Rows older than this have their payload removed.
+    pub cutoff: DateTime<Utc>,
+    /// Rows with a payload older than the cutoff.
+    pub rows: i64,
+    /// Total size of those payloads, in bytes.
+    pub payload_bytes: i64,
+}
+
+/// Result of [`prune`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneReport {
+    /// Rows older than this had their payload removed.
+    pub cutoff: DateTime<Utc>,
+    /// Rows whose payload was removed.
+    pub rows_pruned: i64,
+    /// Total size of the removed payloads, in bytes.
+    pub payload_bytes: i64,
+    /// Archive the payloads were written to, if archiving was on and
+    /// anything was pruned.
+    pub archive_path: Option<String>,
+    /// Size of the compressed archive, in bytes.
+    pub archive_bytes: u64,
+    /// Database compaction after pruning; `None` when nothing was pruned.
+    pub vacuum: Option<VacuumResult>,
+    /// When the prune ran.
+    pub pruned_at: DateTime<Utc>,
+}
+
+/// One archived payload, written as a line of the archive.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchivedPayload<'a> {
+    table: &'a str,
+    id: &'a str,
+    hash: &'a str,
+    raw_data: &'a str,
+}
+
+/// Returns the time before which payloads are pruned.
+pub fn cutoff(now: DateTime<Utc>, months: u32) -> DateTime<Utc> {
+    now.checked_sub_months(Months::new(months)).unwrap_or(now)
+}
+
+/// Returns the tables in this database that store raw payloads.
+async fn present_tables(pool: &SqlitePool) -> Result<Vec<&'static RawDataTable>> {
+    let mut present = Vec::new();
+    for table in RAW_DATA_TABLES {
+        let has_column: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM pragma_table_info(?) WHERE name = 'raw_data')",
+        )
+        .bind(table.name)
+        .fetch_one(pool)
+        .await?;
+        if has_column {
+            present.push(table);
+        }
+    }
+    Ok(present)
+}
+
+/// Counts the payloads older than `months` months.
+pub async fn preview(pool: &SqlitePool, months: u32) -> Result<PrunePreview> {
+    let cutoff = cutoff(Utc::now(), months);
+    let mut rows = 0;
+    let mut payload_bytes = 0;
+
+    for table in present_tables(pool).await? {
+        let (count, bytes): (i64, i64) = sqlx::query_as(&format!(
+            "SELECT COUNT(*), COALESCE(SUM(length(raw_data)), 0) FROM {} \
+             WHERE raw_data IS NOT NULL AND {} < ?",
+            table.name, table.unix_time
+        ))
+        .bind(cutoff.timestamp())
+        .fetch_one(pool)
+        .await?;
+        rows += count;
+        payload_bytes += bytes;
+    }
+
+    Ok(PrunePreview {
+        cutoff,
+        rows,
+        payload_bytes,
+    })
+}
+
+/// Removes payloads older than `months` months, then compacts the database.
+///
+/// When `archive_dir` is given, every payload is first written to a gzip
+/// JSON Lines file there, and nothing is removed unless the archive was
+/// written completely.
+pub async fn prune(
+    pool: &SqlitePool,
+    months: u32,
+    archive_dir: Option<&Path>,
+) -> Result<PruneReport> {
+    let now = Utc::now();
+    let cutoff = cutoff(now, months);
+    let tables = present_tables(pool).await?;
+
+    let mut archive: Option<(PathBuf, Archive)> = None;
+
+    // Collect the rows to prune, archiving each payload as it's read
+    let mut pruned: Vec<(&'static str, Vec<String>)> = Vec::new();
+    let mut payload_bytes = 0;
+    for table in &tables {
+        let mut ids = Vec::new();
+        let mut last_id = String::new();
+        loop {
+            let rows: Vec<(String, String, String)> = sqlx::query_as(&format!(
+                "SELECT id, hash, raw_data FROM {} \
+                 WHERE raw_data IS NOT NULL AND {} < ? AND id > ? ORDER BY id LIMIT ?",
+                table.name, table.unix_time
+            ))
+            .bind(cutoff.timestamp())
+            .bind(&last_id)
+            .bind(BATCH_SIZE)
+            .fetch_all(pool)
+            .await?;
+
+            let Some((id, _, _)) = rows.last() else {
+                break;
+            };
+            last_id = id.clone();
+
+            for (id, hash, raw_data) in rows {
+                if let Some(dir) = archive_dir {
+                    if archive.is_none() {
+                        archive = Some(create_archive(dir, now)?);
+                    }
+                    let (_, writer) = archive.as_mut().expect("archive was just created");
+                    serde_json::to_writer(
+                        &mut *writer,
+                        &ArchivedPayload {
+                            table: table.name,
+                            id: &id,
+                            hash: &hash,
+                            raw_data: &raw_data,
+                        },
+                    )?;
+                    writer.write_all(b"\n")?;
+                }
+                payload_bytes += raw_data.len() as i64;
+                ids.push(id);
+            }
+        }
+        if !ids.is_empty() {
+            pruned.push((table.name, ids));
+        }
+    }
+
+    let rows_pruned: i64 = pruned.iter().map(|(_, ids)| ids.len() as i64).sum();
+
+    let (archive_path, archive_bytes) = match archive {
+        Some((path, writer)) => {
+            let mut inner = writer.finish()?;
+            inner.flush()?;
+            inner.get_ref().sync_all()?;
+            drop(inner);
+            let bytes = fs::metadata(&path)?.len();
+            (Some(path.to_string_lossy().into_owned()), bytes)
+        }
+        None => (None, 0),
+    };
+
+    if rows_pruned == 0 {
+        return Ok(PruneReport {
+            cutoff,
+            rows_pruned,
+            payload_bytes,
+            archive_path,
+            archive_bytes,
+            vacuum: None,
+            pruned_at: now,
+        });
+    }
+
+    // Clear by id rather than re-applying the cutoff, so a payload written
+    // after archiving is never removed without being archived
+    let mut tx = pool.begin().await?;
+    for (table, ids) in &pruned {
+        let sql = format!("UPDATE {} SET raw_data = NULL WHERE id = ?", table);
+        for id in ids {
+            sqlx::query(&sql).bind(id).execute(&mut *tx).await?;
+        }
+    }
+    tx.commit().await?;
+
+    let vacuum = maintenance::vacuum(pool).await?;
+
+    Ok(PruneReport {
+        cutoff,
+        rows_pruned,
+        payload_bytes,
+        archive_path,
+        archive_bytes,
+        vacuum: Some(vacuum),
+        pruned_at: now,
+    })
+}
+
+/// Creates a new archive file in `dir`, never overwriting an earlier one.
+fn create_archive(dir: &Path, now: DateTime<Utc>) -> Result<(PathBuf, Archive)> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let stem = format!("raw_data_{}", now.format("%Y%m%d_%H%M%S"));
+    for attempt in 0.. {
+        let name = match attempt {
+            0 => format!("{}.jsonl.gz", stem),
+            n => format!("{}_{}.jsonl.gz", stem, n),
+        };
+        let path = dir.join(name);
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => {
+                let writer = GzEncoder::new(BufWriter::new(file), Compression::best());
+                return Ok((path, writer));
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to create {}", path.display()))
+            }
+        }
+    }
+    unreachable!("archive name attempts are unbounded")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::io::Read;
+
+    async fn setup() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(include_str!(
+            "../../../migrations/20260118000001_multi_chain_transactions.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    async fn insert(pool: &SqlitePool, hash: &str, timestamp: i64) {
+        sqlx::query(
+            "INSERT INTO multi_chain_transactions \
+             (id, chain_id, hash, from_address, value, timestamp, tx_type, status, raw_data) \
+             VALUES (?, 'ethereum', ?, '0xabc', '1', ?, 'transfer', 'success', ?)",
+        )
+        .bind(format!("ethereum_{}", hash))
+        .bind(hash)
+        .bind(timestamp)
+        .bind(format!("{{\"hash\":\"{}\"}}", hash))
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_prune_archives_and_strips_old_payloads() {
+        let pool = setup().await;
+        let now = Utc::now();
+        insert(&pool, "0xold", cutoff(now, 24).timestamp()).await;
+        insert(&pool, "0xnew", now.timestamp()).await;
+
+        let preview = preview(&pool, 12).await.unwrap();
+        assert_eq!(preview.rows, 1);
+
+        let dir = tempfile::tempdir().unwrap();
+        let report = prune(&pool, 12, Some(dir.path())).await.unwrap();
+        assert_eq!(report.rows_pruned, 1);
+        assert!(report.vacuum.is_some());
+
+        let remaining: Vec<(String, Option<String>)> =
+            sqlx::query_as("SELECT hash, raw_data FROM multi_chain_transactions ORDER BY hash")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            remaining[0],
+            (
+                "0xnew".to_string(),
+                Some("{\"hash\":\"0xnew\"}".to_string())
+            )
+        );
+        assert_eq!(remaining[1], ("0xold".to_string(), None));
+
+        let mut archived = String::new();
+        GzDecoder::new(File::open(report.archive_path.unwrap()).unwrap())
+            .read_to_string(&mut archived)
+            .unwrap();
+        let line: serde_json::Value = serde_json::from_str(archived.trim()).unwrap();
+        assert_eq!(line["hash"], "0xold");
+        assert_eq!(line["rawData"], "{\"hash\":\"0xold\"}");
+
+        // Nothing left to prune, so no archive is written
+        let again = prune(&pool, 12, Some(dir.path())).await.unwrap();
+        assert_eq!(again.rows_pruned, 0);
+        assert!(again.archive_path.is_none());
+    }
+}
@@ -502,6 +502,10 @@ impl StoredTransaction {
             status,
             tx_type,
             token_transfers,
+            swaps: Vec::new(),
+            fee_breakdown: None,
+            user_operations: Vec::new(),
+            inner_calls: Vec::new(),
             raw_data,
         }
     }
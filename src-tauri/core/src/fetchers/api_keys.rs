@@ -53,6 +53,12 @@ pub enum ApiProvider {
     Alchemy,
     /// Helius (Solana enhanced RPC + DAS).
     Helius,
+    /// CoinGecko (prices; a key selects the Pro API).
+    CoinGecko,
+    /// CryptoCompare (prices).
+    CryptoCompare,
+    /// DefiLlama (prices; a key selects the Pro API).
+    DefiLlama,
 }
 
 impl ApiProvider {
@@ -68,6 +74,9 @@ impl ApiProvider {
             ApiProvider::Covalent => "covalent_api_key",
             ApiProvider::Alchemy => "alchemy_api_key",
             ApiProvider::Helius => "helius_api_key",
+            ApiProvider::CoinGecko => "coingecko_api_key",
+            ApiProvider::CryptoCompare => "cryptocompare_api_key",
+            ApiProvider::DefiLlama => "defillama_api_key",
         }
     }
 
@@ -83,6 +92,9 @@ impl ApiProvider {
             ApiProvider::Covalent => "Covalent",
             ApiProvider::Alchemy => "Alchemy",
             ApiProvider::Helius => "Helius",
+            ApiProvider::CoinGecko => "CoinGecko",
+            ApiProvider::CryptoCompare => "CryptoCompare",
+            ApiProvider::DefiLlama => "DefiLlama",
         }
     }
 
@@ -103,6 +115,12 @@ impl ApiProvider {
             ApiProvider::Alchemy => 2,
             // Helius: 5 req/sec on free tier
             ApiProvider::Helius => 5,
+            // CoinGecko: roughly 10-30 calls/min on the public API
+            ApiProvider::CoinGecko => 1,
+            // CryptoCompare: heavily limited without key
+            ApiProvider::CryptoCompare => 1,
+            // DefiLlama: generous public limits
+            ApiProvider::DefiLlama => 5,
         }
     }
 
@@ -123,10 +141,17 @@ impl ApiProvider {
             ApiProvider::Alchemy => 10,
             // Helius: 30 req/sec with paid key
             ApiProvider::Helius => 30,
+            // CoinGecko Pro: 500 calls/min on the entry plan
+            ApiProvider::CoinGecko => 8,
+            // CryptoCompare: 20 req/sec with key
+            ApiProvider::CryptoCompare => 20,
+            // DefiLlama Pro: 1000 calls/min
+            ApiProvider::DefiLlama => 15,
         }
     }
 
     /// Parse provider from string.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "etherscan" => Some(ApiProvider::Etherscan),
@@ -138,6 +163,9 @@ impl ApiProvider {
             "covalent" => Some(ApiProvider::Covalent),
             "alchemy" => Some(ApiProvider::Alchemy),
             "helius" => Some(ApiProvider::Helius),
+            "coingecko" => Some(ApiProvider::CoinGecko),
+            "cryptocompare" => Some(ApiProvider::CryptoCompare),
+            "defillama" | "llama" => Some(ApiProvider::DefiLlama),
             _ => None,
         }
     }
@@ -154,6 +182,9 @@ impl ApiProvider {
             ApiProvider::Covalent,
             ApiProvider::Alchemy,
             ApiProvider::Helius,
+            ApiProvider::CoinGecko,
+            ApiProvider::CryptoCompare,
+            ApiProvider::DefiLlama,
         ]
     }
 }
@@ -253,9 +284,10 @@ mod tests {
     #[test]
     fn test_all_providers() {
         let all = ApiProvider::all();
-        assert_eq!(all.len(), 9);
+        assert_eq!(all.len(), 12);
         assert!(all.contains(&ApiProvider::Etherscan));
         assert!(all.contains(&ApiProvider::Subscan));
         assert!(all.contains(&ApiProvider::Helius));
+        assert!(all.contains(&ApiProvider::CoinGecko));
     }
 }
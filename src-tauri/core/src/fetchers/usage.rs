@@ -0,0 +1,360 @@
+//! API Usage Metering
+//!
+//! Counts outbound calls to each metered provider per UTC day. Calls are
+//! tallied in memory as they are made and added to the `api_usage` table by
+//! a background flush, so a request never waits on the database.
+//!
+//! Usage is compared with the provider's free-tier quota. Once a provider
+//! without an API key passes [`WARNING_PERCENT`] of its quota, the settings
+//! screen shows a warning, and the app emits an event, so the user knows to
+//! add a key (Turbo Mode) before requests start failing.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use super::api_keys::{ApiKeyManager, ApiProvider};
+
+/// Share of a free-tier quota, in percent, at which usage is flagged.
+pub const WARNING_PERCENT: f64 = 80.0;
+
+/// Days of history returned when none is asked for.
+const DEFAULT_HISTORY_DAYS: u32 = 30;
+
+/// Calls made since the last flush, by provider and day.
+static PENDING: Mutex<Option<HashMap<(ApiProvider, NaiveDate), u64>>> = Mutex::new(None);
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// Window a free-tier quota applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaPeriod {
+    /// Resets each UTC day.
+    Day,
+    /// Resets each calendar month.
+    Month,
+}
+
+/// A provider's free-tier call allowance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FreeTierQuota {
+    /// Calls allowed per period.
+    pub calls: u64,
+    /// Period the allowance resets over.
+    pub period: QuotaPeriod,
+}
+
+/// Calls to a provider on one day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyUsage {
+    /// UTC day.
+    pub day: NaiveDate,
+    /// Calls made.
+    pub calls: u64,
+}
+
+/// A provider's usage for the settings screen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiUsage {
+    /// Provider identifier.
+    pub provider: String,
+    /// Display name.
+    pub name: String,
+    /// Whether an API key is configured.
+    pub has_api_key: bool,
+    /// Calls made today.
+    pub today: u64,
+    /// Calls made this calendar month.
+    pub month_to_date: u64,
+    /// Free-tier allowance, if the provider has one.
+    pub quota: Option<FreeTierQuota>,
+    /// Share of the allowance used in its current period, in percent.
+    pub quota_used_percent: Option<f64>,
+    /// Whether usage without a key is near the allowance.
+    pub near_limit: bool,
+    /// Calls per day, oldest first; days without calls are left out.
+    pub daily: Vec<DailyUsage>,
+}
+
+/// Usage of every provider, with warnings for those near their quota.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiUsageReport {
+    /// One entry per provider.
+    pub providers: Vec<ApiUsage>,
+    /// Providers near their free-tier quota.
+    pub warnings: Vec<String>,
+}
+
+// =============================================================================
+// METERING
+// =============================================================================
+
+/// Free-tier allowance of a provider's public plan, if it has one.
+///
+/// Alchemy and Helius meter compute units and credits rather than calls;
+/// their allowances are given as calls at a typical cost per call.
+pub fn free_tier_quota(provider: ApiProvider) -> Option<FreeTierQuota> {
+    let (calls, period) = match provider {
+        // Etherscan-family: 100k calls a day on the free plan
+        ApiProvider::Etherscan
+        | ApiProvider::Polygonscan
+        | ApiProvider::Arbiscan
+        | ApiProvider::Basescan
+        | ApiProvider::Optimism => (100_000, QuotaPeriod::Day),
+        // Alchemy: 30M compute units a month, about 25 per call
+        ApiProvider::Alchemy => (1_200_000, QuotaPeriod::Month),
+        // Helius: 1M credits a month, one per standard call
+        ApiProvider::Helius => (1_000_000, QuotaPeriod::Month),
+        // CoinGecko Demo: 10k calls a month
+        ApiProvider::CoinGecko => (10_000, QuotaPeriod::Month),
+        ApiProvider::Subscan
+        | ApiProvider::Covalent
+        | ApiProvider::CryptoCompare
+        | ApiProvider::DefiLlama => return None,
+    };
+    Some(FreeTierQuota { calls, period })
+}
+
+/// Provider identifier used in storage and commands.
+fn provider_id(provider: ApiProvider) -> String {
+    provider.keychain_key().replace("_api_key", "")
+}
+
+/// Counts one outbound call to `provider`.
+pub fn record_call(provider: ApiProvider) {
+    let today = Utc::now().date_naive();
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    *pending
+        .get_or_insert_with(HashMap::new)
+        .entry((provider, today))
+        .or_default() += 1;
+}
+
+/// Adds the calls counted since the last flush to `api_usage`. Counts that
+/// couldn't be saved are kept for the next flush.
+pub async fn flush(pool: &SqlitePool) -> Result<(), String> {
+    let counts = PENDING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+        .unwrap_or_default();
+
+    let mut unsaved = HashMap::new();
+    let mut error = None;
+    for ((provider, day), calls) in counts {
+        let saved = sqlx::query(
+            r#"
+            INSERT INTO api_usage (provider, day, calls) VALUES (?, ?, ?)
+            ON CONFLICT(provider, day) DO UPDATE SET calls = calls + excluded.calls
+            "#,
+        )
+        .bind(provider_id(provider))
+        .bind(day)
+        .bind(calls as i64)
+        .execute(pool)
+        .await;
+        if let Err(e) = saved {
+            unsaved.insert((provider, day), calls);
+            error = Some(e.to_string());
+        }
+    }
+
+    if !unsaved.is_empty() {
+        let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+        let pending = pending.get_or_insert_with(HashMap::new);
+        for (key, calls) in unsaved {
+            *pending.entry(key).or_default() += calls;
+        }
+    }
+    error.map_or(Ok(()), Err)
+}
+
+// =============================================================================
+// REPORTING
+// =============================================================================
+
+/// Summarizes a provider's usage from its calls per day.
+pub fn summarize(
+    provider: ApiProvider,
+    has_api_key: bool,
+    mut daily: Vec<DailyUsage>,
+    today: NaiveDate,
+) -> ApiUsage {
+    daily.sort_by_key(|d| d.day);
+    let month_start = today.with_day(1).unwrap_or(today);
+    let today_calls: u64 = daily
+        .iter()
+        .filter(|d| d.day == today)
+        .map(|d| d.calls)
+        .sum();
+    let month_to_date: u64 = daily
+        .iter()
+        .filter(|d| d.day >= month_start && d.day <= today)
+        .map(|d| d.calls)
+        .sum();
+
+    let quota = free_tier_quota(provider);
+    let quota_used_percent = quota.filter(|q| q.calls > 0).map(|q| {
+        let used = match q.period {
+            QuotaPeriod::Day => today_calls,
+            QuotaPeriod::Month => month_to_date,
+        };
+        used as f64 * 100.0 / q.calls as f64
+    });
+
+    ApiUsage {
+        provider: provider_id(provider),
+        name: provider.display_name().to_string(),
+        has_api_key,
+        today: today_calls,
+        month_to_date,
+        quota,
+        quota_used_percent,
+        near_limit: !has_api_key && quota_used_percent.is_some_and(|p| p >= WARNING_PERCENT),
+        daily,
+    }
+}
+
+fn warning(usage: &ApiUsage) -> String {
+    let period = match usage.quota.map(|q| q.period) {
+        Some(QuotaPeriod::Day) => "daily",
+        _ => "monthly",
+    };
+    format!(
+        "{} has used {:.0}% of its free {} quota; add an API key to keep syncing",
+        usage.name,
+        usage.quota_used_percent.unwrap_or_default(),
+        period
+    )
+}
+
+/// Loads every provider's usage over the last `days` days, and the month to
+/// date.
+pub async fn load_usage(pool: &SqlitePool, days: u32) -> Result<ApiUsageReport, String> {
+    let today = Utc::now().date_naive();
+    let history_start = today - chrono::Duration::days(i64::from(days.max(1)) - 1);
+    let since = history_start.min(today.with_day(1).unwrap_or(today));
+
+    let rows: Vec<(String, NaiveDate, i64)> =
+        sqlx::query_as("SELECT provider, day, calls FROM api_usage WHERE day >= ? ORDER BY day")
+            .bind(since)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    let mut by_provider: HashMap<ApiProvider, Vec<DailyUsage>> = HashMap::new();
+    for (provider, day, calls) in rows {
+        if let Some(provider) = ApiProvider::from_str(&provider) {
+            by_provider.entry(provider).or_default().push(DailyUsage {
+                day,
+                calls: calls.max(0) as u64,
+            });
+        }
+    }
+
+    let providers: Vec<ApiUsage> = ApiProvider::all()
+        .iter()
+        .map(|p| {
+            let mut usage = summarize(
+                *p,
+                ApiKeyManager::has_api_key(*p),
+                by_provider.remove(p).unwrap_or_default(),
+                today,
+            );
+            usage.daily.retain(|d| d.day >= history_start);
+            usage
+        })
+        .collect();
+    let warnings = providers
+        .iter()
+        .filter(|u| u.near_limit)
+        .map(warning)
+        .collect();
+
+    Ok(ApiUsageReport {
+        providers,
+        warnings,
+    })
+}
+
+/// Loads usage with the default history, flushing the latest counts first.
+pub async fn current_usage(pool: &SqlitePool, days: Option<u32>) -> Result<ApiUsageReport, String> {
+    flush(pool).await?;
+    load_usage(pool, days.unwrap_or(DEFAULT_HISTORY_DAYS)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    #[test]
+    fn test_summarize_against_quota() {
+        let daily = vec![
+            DailyUsage {
+                day: day(2),
+                calls: 3_000,
+            },
+            DailyUsage {
+                day: day(1),
+                calls: 5_500,
+            },
+        ];
+        let usage = summarize(ApiProvider::CoinGecko, false, daily.clone(), day(2));
+        assert_eq!(usage.provider, "coingecko");
+        assert_eq!(usage.today, 3_000);
+        assert_eq!(usage.month_to_date, 8_500);
+        assert_eq!(usage.quota_used_percent, Some(85.0));
+        assert!(usage.near_limit);
+        assert_eq!(usage.daily[0].day, day(1));
+        assert!(warning(&usage).contains("85% of its free monthly quota"));
+
+        // A key lifts the free-tier limit
+        assert!(!summarize(ApiProvider::CoinGecko, true, daily.clone(), day(2)).near_limit);
+
+        // Etherscan's quota is per day
+        let usage = summarize(ApiProvider::Etherscan, false, daily, day(2));
+        assert_eq!(usage.quota_used_percent, Some(3.0));
+        assert!(!usage.near_limit);
+
+        let usage = summarize(ApiProvider::DefiLlama, false, Vec::new(), day(2));
+        assert_eq!(usage.quota, None);
+        assert_eq!(usage.quota_used_percent, None);
+    }
+
+    #[tokio::test]
+    async fn test_flush_accumulates_counts() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(include_str!(
+            "../../../migrations/20260504000001_create_api_usage.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // Nothing else calls Covalent, so no other test adds to its count
+        record_call(ApiProvider::Covalent);
+        record_call(ApiProvider::Covalent);
+        flush(&pool).await.unwrap();
+        record_call(ApiProvider::Covalent);
+        flush(&pool).await.unwrap();
+
+        let report = load_usage(&pool, 7).await.unwrap();
+        let covalent = report
+            .providers
+            .iter()
+            .find(|u| u.provider == "covalent")
+            .unwrap();
+        assert_eq!(covalent.today, 3);
+        assert_eq!(covalent.daily.len(), 1);
+    }
+}
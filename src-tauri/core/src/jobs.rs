@@ -0,0 +1,70 @@
+//! Cooperative cancellation for long-running work.
+//!
+//! Work that can take a while, such as a wallet sync or an export, takes a
+//! [`CancelToken`] that it checks between steps and races its network calls
+//! against. Work already saved stays saved, so a later run picks up where
+//! the cancelled one stopped.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+
+/// Error returned by work that stopped because its job was cancelled.
+pub const CANCELLED: &str = "Cancelled";
+
+/// Cooperative cancellation signal shared between a job and `cancel_job`.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl CancelToken {
+    /// Creates a token that hasn't been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signals cancellation to everything holding the token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    /// Whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Fails with [`CANCELLED`] if the token has been cancelled.
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Completes once the token is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Runs `future` unless the token is cancelled first, in which case the
+    /// future is dropped and [`CANCELLED`] returned.
+    pub async fn run<F: Future>(&self, future: F) -> Result<F::Output, String> {
+        self.check()?;
+        tokio::select! {
+            output = future => Ok(output),
+            _ = self.cancelled() => Err(CANCELLED.to_string()),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//! Backend shared by the desktop app and `pacioli-cli`.
+//!
+//! Holds everything that doesn't need a Tauri runtime: chain adapters,
+//! fetchers, the database and its migrations, and the services that sync,
+//! store, value, and export a profile's transactions. The app wraps these in
+//! Tauri commands; the CLI calls them directly.
+
+/// Chain adapters and the chain manager.
+pub mod chains;
+/// Core types and services: authentication, currencies, email, PDFs, spam
+/// heuristics, and diagnostics.
+pub mod core;
+/// Database module for persistence operations.
+pub mod db;
+/// Resilient fetchers, API key storage, and usage metering.
+pub mod fetchers;
+/// Cooperative cancellation for long-running work.
+pub mod jobs;
+/// Profile, wallet, and transaction services behind the app's commands and
+/// the CLI.
+pub mod services;
+
+/// Database file in the app data directory.
+pub const DATABASE_FILE: &str = "pacioli.db";
@@ -0,0 +1,235 @@
+//! Ledger reports per profile.
+//!
+//! The ledger views sum every profile's posted entries together, so the
+//! balances here are computed from one profile's lines and rounded to the
+//! reporting currency.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::price_overrides::reporting_currency;
+use crate::core::currency::round_fiat;
+
+/// Account balance from the v_account_balances view.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountBalance {
+    /// GL account ID.
+    pub account_id: i64,
+    /// Account number.
+    pub account_number: String,
+    /// Account name.
+    pub account_name: String,
+    /// Account type (Asset/Liability/Equity/Income/Expense).
+    pub account_type: String,
+    /// Digital asset type, if applicable.
+    pub digital_asset_type: Option<String>,
+    /// Normal balance direction (debit/credit).
+    pub normal_balance: Option<String>,
+    /// Total debits posted.
+    #[sqlx(try_from = "f64")]
+    pub total_debits: Decimal,
+    /// Total credits posted.
+    #[sqlx(try_from = "f64")]
+    pub total_credits: Decimal,
+    /// Balance in natural direction.
+    #[sqlx(try_from = "f64")]
+    pub balance: Decimal,
+    /// Signed balance for reporting.
+    #[sqlx(try_from = "f64")]
+    pub balance_signed: Decimal,
+}
+
+/// Trial balance row from the v_trial_balance view.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TrialBalanceRow {
+    /// Account number.
+    pub account_number: String,
+    /// Account name.
+    pub account_name: String,
+    /// Account type.
+    pub account_type: String,
+    /// Debit balance (0 if credit side).
+    #[sqlx(try_from = "f64")]
+    pub debit_balance: Decimal,
+    /// Credit balance (0 if debit side).
+    #[sqlx(try_from = "f64")]
+    pub credit_balance: Decimal,
+}
+
+/// Posted journal lines of one profile, joined to every active account.
+///
+/// The ledger views sum every profile's entries together, so the reports
+/// here filter the lines by profile before aggregating.
+const PROFILE_LEDGER: &str = r#"
+    FROM gl_accounts ga
+    LEFT JOIN (
+        SELECT jel.gl_account_id, jel.debit_amount, jel.credit_amount
+        FROM journal_entry_lines jel
+        INNER JOIN journal_entries je ON je.id = jel.journal_entry_id
+        WHERE je.is_posted = 1 AND je.profile_id = ?
+    ) l ON l.gl_account_id = ga.id
+    WHERE ga.is_active = 1
+    GROUP BY ga.id, ga.account_number, ga.account_name, ga.account_type, ga.normal_balance
+"#;
+
+/// The currency ledger amounts are kept in.
+pub async fn ledger_currency(pool: &sqlx::SqlitePool) -> Result<String, String> {
+    reporting_currency(pool).await.map_err(|e| e.to_string())
+}
+
+/// A profile's account balances, rounded to the reporting currency.
+pub async fn account_balances(
+    pool: &sqlx::SqlitePool,
+    profile_id: &str,
+) -> Result<Vec<AccountBalance>, String> {
+    let query = format!(
+        r#"
+        SELECT
+            ga.id AS account_id, ga.account_number, ga.account_name, ga.account_type,
+            ga.digital_asset_type, ga.normal_balance,
+            TOTAL(l.debit_amount) AS total_debits,
+            TOTAL(l.credit_amount) AS total_credits,
+            CASE WHEN ga.normal_balance = 'debit'
+                THEN TOTAL(l.debit_amount) - TOTAL(l.credit_amount)
+                ELSE TOTAL(l.credit_amount) - TOTAL(l.debit_amount)
+            END AS balance,
+            CASE WHEN ga.account_type IN ('Asset', 'Expense')
+                THEN TOTAL(l.debit_amount) - TOTAL(l.credit_amount)
+                ELSE TOTAL(l.credit_amount) - TOTAL(l.debit_amount)
+            END AS balance_signed
+        {PROFILE_LEDGER}
+        ORDER BY ga.account_number
+        "#
+    );
+    let mut balances = sqlx::query_as::<_, AccountBalance>(&query)
+        .bind(profile_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let currency = ledger_currency(pool).await?;
+    for row in &mut balances {
+        row.total_debits = round_fiat(row.total_debits, &currency);
+        row.total_credits = round_fiat(row.total_credits, &currency);
+        row.balance = round_fiat(row.balance, &currency);
+        row.balance_signed = round_fiat(row.balance_signed, &currency);
+    }
+    Ok(balances)
+}
+
+/// A profile's trial balance rows, rounded to the reporting currency.
+pub async fn trial_balance(
+    pool: &sqlx::SqlitePool,
+    profile_id: &str,
+) -> Result<Vec<TrialBalanceRow>, String> {
+    let query = format!(
+        r#"
+        SELECT
+            ga.account_number, ga.account_name, ga.account_type,
+            MAX(TOTAL(l.debit_amount) - TOTAL(l.credit_amount), 0.0) AS debit_balance,
+            MAX(TOTAL(l.credit_amount) - TOTAL(l.debit_amount), 0.0) AS credit_balance
+        {PROFILE_LEDGER}
+        HAVING ABS(TOTAL(l.debit_amount) - TOTAL(l.credit_amount)) > 0.01
+        ORDER BY ga.account_number
+        "#
+    );
+    let mut rows = sqlx::query_as::<_, TrialBalanceRow>(&query)
+        .bind(profile_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let currency = ledger_currency(pool).await?;
+    for row in &mut rows {
+        row.debit_balance = round_fiat(row.debit_balance, &currency);
+        row.credit_balance = round_fiat(row.credit_balance, &currency);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for ddl in [
+            r#"
+            CREATE TABLE gl_accounts (
+                id INTEGER PRIMARY KEY,
+                account_number TEXT NOT NULL,
+                account_name TEXT NOT NULL,
+                account_type TEXT NOT NULL,
+                digital_asset_type TEXT,
+                normal_balance TEXT,
+                is_active INTEGER NOT NULL DEFAULT 1
+            )
+            "#,
+            r#"
+            CREATE TABLE journal_entries (
+                id INTEGER PRIMARY KEY,
+                profile_id TEXT,
+                is_posted INTEGER NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE journal_entry_lines (
+                id INTEGER PRIMARY KEY,
+                journal_entry_id INTEGER NOT NULL,
+                gl_account_id INTEGER NOT NULL,
+                debit_amount TEXT NOT NULL,
+                credit_amount TEXT NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE cost_basis_preferences (
+                preference_key TEXT PRIMARY KEY,
+                preference_value TEXT
+            )
+            "#,
+            "INSERT INTO gl_accounts VALUES (1, '1200', 'Crypto Assets', 'Asset', NULL, 'debit', 1)",
+            "INSERT INTO gl_accounts VALUES (2, '4000', 'Income', 'Income', NULL, 'credit', 1)",
+            // Alpha has a posted entry and a draft; Beta has a larger posted entry.
+            "INSERT INTO journal_entries VALUES (1, 'alpha', 1), (2, 'alpha', 0), (3, 'beta', 1)",
+            r#"
+            INSERT INTO journal_entry_lines VALUES
+                (1, 1, 1, '100', '0'), (2, 1, 2, '0', '100'),
+                (3, 2, 1, '7', '0'), (4, 2, 2, '0', '7'),
+                (5, 3, 1, '900', '0'), (6, 3, 2, '0', '900')
+            "#,
+        ] {
+            sqlx::query(ddl).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_ledger_reports_only_sum_the_profiles_posted_entries() {
+        let pool = setup_test_db().await;
+
+        let balances = account_balances(&pool, "alpha").await.unwrap();
+        assert_eq!(balances.len(), 2);
+        assert_eq!(balances[0].balance, Decimal::from(100));
+        assert_eq!(balances[1].balance, Decimal::from(100));
+
+        let rows = trial_balance(&pool, "beta").await.unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].debit_balance, Decimal::from(900));
+        assert_eq!(rows[1].credit_balance, Decimal::from(900));
+
+        let empty = trial_balance(&pool, "gamma").await.unwrap();
+        assert!(empty.is_empty());
+        let untouched = account_balances(&pool, "gamma").await.unwrap();
+        assert!(untouched.iter().all(|b| b.balance.is_zero()));
+    }
+}
@@ -0,0 +1,519 @@
+//! Economic events within wallet transactions.
+//!
+//! Expands a stored transaction into its events, from its swaps, token
+//! transfers, native value and fee, and saves them without losing the tags
+//! or bookings of events already stored.
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use super::address_watch::native_currency;
+use super::balance_history::load_transaction_token_transfers;
+use super::persistence::{StoredTransaction, Wallet};
+use super::swaps::load_transaction_swaps;
+use crate::chains::{NetSwap, SwapDetail, TokenTransfer};
+
+/// Asset name of a chain's native currency.
+const NATIVE_ASSET: &str = "native";
+
+/// Source of mints and destination of burns on EVM chains.
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// What an accounting event is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountingEventKind {
+    /// One asset traded for another.
+    Swap,
+    /// An asset received from another address.
+    TransferIn,
+    /// An asset sent to another address.
+    TransferOut,
+    /// A token minted to the wallet.
+    Mint,
+    /// A token burned from the wallet.
+    Burn,
+    /// The network fee the wallet paid.
+    Fee,
+}
+
+impl AccountingEventKind {
+    /// Value stored in the `kind` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Swap => "swap",
+            Self::TransferIn => "transfer_in",
+            Self::TransferOut => "transfer_out",
+            Self::Mint => "mint",
+            Self::Burn => "burn",
+            Self::Fee => "fee",
+        }
+    }
+
+    /// Parses a value stored in the `kind` column.
+    pub fn parse(value: &str) -> Option<Self> {
+        [
+            Self::Swap,
+            Self::TransferIn,
+            Self::TransferOut,
+            Self::Mint,
+            Self::Burn,
+            Self::Fee,
+        ]
+        .into_iter()
+        .find(|kind| kind.as_str() == value)
+    }
+}
+
+/// An amount of one asset moved by an event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventAmount {
+    /// Token contract, or `native` for the chain's currency.
+    pub asset: String,
+    /// Asset symbol, if known.
+    pub symbol: Option<String>,
+    /// Asset decimals, if known.
+    pub decimals: Option<u8>,
+    /// Amount in the asset's smallest units.
+    pub amount: String,
+}
+
+/// An event a transaction expands into, before it is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandedEvent {
+    /// What the event is.
+    pub kind: AccountingEventKind,
+    /// Asset leaving the wallet.
+    pub sent: Option<EventAmount>,
+    /// Asset entering the wallet.
+    pub received: Option<EventAmount>,
+    /// The other side of a transfer.
+    pub counterparty: Option<String>,
+}
+
+/// What expansion reads from a stored transaction.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionFacts {
+    /// Sender.
+    pub from: Option<String>,
+    /// Recipient.
+    pub to: Option<String>,
+    /// Native value, in smallest units.
+    pub value: Option<String>,
+    /// Fee paid by the sender, in smallest units.
+    pub fee: Option<String>,
+    /// Symbol of the chain's currency.
+    pub native_symbol: Option<String>,
+    /// Decimals of the chain's currency.
+    pub native_decimals: Option<u8>,
+    /// Decoded pool swaps.
+    pub swaps: Vec<SwapDetail>,
+    /// Token transfers.
+    pub transfers: Vec<TokenTransfer>,
+}
+
+/// Whether a stored amount is above zero.
+fn is_nonzero(amount: &str) -> bool {
+    !amount.trim().trim_start_matches(['0', '.']).is_empty()
+}
+
+/// Expands a transaction of the wallet at `address` into its economic
+/// events: its swaps, with a routed swap netted to one trade; the token
+/// transfers those trades don't account for, with transfers from and to the
+/// zero address as mints and burns; its native value, unless it paid into a
+/// swap; and the fee, if the wallet sent the transaction.
+pub fn expand(address: &str, facts: &TransactionFacts) -> Vec<ExpandedEvent> {
+    let is_wallet = |other: &str| other.eq_ignore_ascii_case(address);
+    let native = |amount: &str| EventAmount {
+        asset: NATIVE_ASSET.to_string(),
+        symbol: facts.native_symbol.clone(),
+        decimals: facts.native_decimals,
+        amount: amount.to_string(),
+    };
+
+    let trades: Vec<(EventAmount, EventAmount)> = match NetSwap::from_swaps(&facts.swaps) {
+        Some(net) => vec![(
+            EventAmount {
+                asset: net.token_in,
+                symbol: net.token_in_symbol,
+                decimals: net.token_in_decimals,
+                amount: net.amount_in,
+            },
+            EventAmount {
+                asset: net.token_out,
+                symbol: net.token_out_symbol,
+                decimals: net.token_out_decimals,
+                amount: net.amount_out,
+            },
+        )],
+        // Independent swaps, as a multicall makes, stay separate trades
+        None => facts
+            .swaps
+            .iter()
+            .map(|leg| {
+                (
+                    EventAmount {
+                        asset: leg.token_in.clone(),
+                        symbol: leg.token_in_symbol.clone(),
+                        decimals: leg.token_in_decimals,
+                        amount: leg.amount_in.clone(),
+                    },
+                    EventAmount {
+                        asset: leg.token_out.clone(),
+                        symbol: leg.token_out_symbol.clone(),
+                        decimals: leg.token_out_decimals,
+                        amount: leg.amount_out.clone(),
+                    },
+                )
+            })
+            .collect(),
+    };
+
+    let mut events: Vec<ExpandedEvent> = trades
+        .iter()
+        .map(|(sent, received)| ExpandedEvent {
+            kind: AccountingEventKind::Swap,
+            sent: Some(sent.clone()),
+            received: Some(received.clone()),
+            counterparty: None,
+        })
+        .collect();
+
+    for transfer in &facts.transfers {
+        let incoming = is_wallet(&transfer.to);
+        // Transfers between other addresses, or to the wallet itself
+        if incoming == is_wallet(&transfer.from) {
+            continue;
+        }
+        let traded = trades.iter().any(|(sent, received)| {
+            let side = if incoming { received } else { sent };
+            side.asset.eq_ignore_ascii_case(&transfer.token_address)
+        });
+        if traded {
+            continue;
+        }
+
+        let moved = EventAmount {
+            asset: transfer.token_address.clone(),
+            symbol: transfer.token_symbol.clone(),
+            decimals: transfer.token_decimals,
+            amount: transfer.value.clone(),
+        };
+        let counterparty = if incoming {
+            &transfer.from
+        } else {
+            &transfer.to
+        };
+        let (kind, counterparty) = match (incoming, counterparty.eq_ignore_ascii_case(ZERO_ADDRESS))
+        {
+            (true, true) => (AccountingEventKind::Mint, None),
+            (true, false) => (AccountingEventKind::TransferIn, Some(counterparty.clone())),
+            (false, true) => (AccountingEventKind::Burn, None),
+            (false, false) => (AccountingEventKind::TransferOut, Some(counterparty.clone())),
+        };
+        events.push(ExpandedEvent {
+            kind,
+            sent: (!incoming).then(|| moved.clone()),
+            received: incoming.then_some(moved),
+            counterparty,
+        });
+    }
+
+    let from = facts.from.as_deref().unwrap_or_default();
+    let to = facts.to.as_deref().unwrap_or_default();
+    // Native value paid into a swap is already part of the trade
+    let value = facts
+        .value
+        .as_deref()
+        .filter(|value| is_nonzero(value) && trades.is_empty());
+    if let Some(value) = value {
+        if is_wallet(from) && !is_wallet(to) {
+            events.push(ExpandedEvent {
+                kind: AccountingEventKind::TransferOut,
+                sent: Some(native(value)),
+                received: None,
+                counterparty: facts.to.clone(),
+            });
+        } else if is_wallet(to) && !is_wallet(from) {
+            events.push(ExpandedEvent {
+                kind: AccountingEventKind::TransferIn,
+                sent: None,
+                received: Some(native(value)),
+                counterparty: facts.from.clone(),
+            });
+        }
+    }
+
+    if let Some(fee) = facts.fee.as_deref().filter(|fee| is_nonzero(fee)) {
+        if is_wallet(from) {
+            events.push(ExpandedEvent {
+                kind: AccountingEventKind::Fee,
+                sent: Some(native(fee)),
+                received: None,
+                counterparty: None,
+            });
+        }
+    }
+
+    events
+}
+
+/// Replaces the stored events of a wallet transaction. An event whose kind
+/// is unchanged at its position keeps its ID and tag.
+pub async fn save_events(
+    pool: &SqlitePool,
+    wallet_id: &str,
+    hash: &str,
+    events: &[ExpandedEvent],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    for (event_index, event) in events.iter().enumerate() {
+        let sent = event.sent.as_ref();
+        let received = event.received.as_ref();
+        sqlx::query(
+            r#"
+            INSERT INTO accounting_events (
+                id, wallet_id, hash, event_index, kind,
+                sent_asset, sent_symbol, sent_decimals, sent_amount,
+                received_asset, received_symbol, received_decimals, received_amount,
+                counterparty
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(wallet_id, hash, event_index) DO UPDATE SET
+                category = CASE WHEN kind = excluded.kind THEN category END,
+                entity_id = CASE WHEN kind = excluded.kind THEN entity_id END,
+                kind = excluded.kind,
+                sent_asset = excluded.sent_asset,
+                sent_symbol = excluded.sent_symbol,
+                sent_decimals = excluded.sent_decimals,
+                sent_amount = excluded.sent_amount,
+                received_asset = excluded.received_asset,
+                received_symbol = excluded.received_symbol,
+                received_decimals = excluded.received_decimals,
+                received_amount = excluded.received_amount,
+                counterparty = excluded.counterparty
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(wallet_id)
+        .bind(hash)
+        .bind(event_index as i64)
+        .bind(event.kind.as_str())
+        .bind(sent.map(|a| &a.asset))
+        .bind(sent.and_then(|a| a.symbol.as_ref()))
+        .bind(sent.and_then(|a| a.decimals).map(i64::from))
+        .bind(sent.map(|a| &a.amount))
+        .bind(received.map(|a| &a.asset))
+        .bind(received.and_then(|a| a.symbol.as_ref()))
+        .bind(received.and_then(|a| a.decimals).map(i64::from))
+        .bind(received.map(|a| &a.amount))
+        .bind(&event.counterparty)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query(
+        "DELETE FROM accounting_events WHERE wallet_id = ? AND hash = ? AND event_index >= ?",
+    )
+    .bind(wallet_id)
+    .bind(hash)
+    .bind(events.len() as i64)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+/// Whether any event of a wallet transaction is booked to the ledger.
+pub async fn has_booked_events(
+    pool: &SqlitePool,
+    wallet_id: &str,
+    hash: &str,
+) -> Result<bool, sqlx::Error> {
+    let (booked,): (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM accounting_events
+        WHERE wallet_id = ? AND hash = ? AND journal_entry_id IS NOT NULL
+        "#,
+    )
+    .bind(wallet_id)
+    .bind(hash)
+    .fetch_one(pool)
+    .await?;
+    Ok(booked > 0)
+}
+
+/// Expands a stored transaction of `wallet` into its accounting events,
+/// replacing earlier ones. Once any of its events is booked the events are
+/// left as they are and `false` is returned.
+pub async fn expand_transaction(
+    pool: &SqlitePool,
+    wallet: &Wallet,
+    stored: &StoredTransaction,
+) -> Result<bool, sqlx::Error> {
+    if has_booked_events(pool, &wallet.id, &stored.hash).await? {
+        return Ok(false);
+    }
+
+    let (native_symbol, native_decimals) = native_currency(&stored.chain);
+    let facts = TransactionFacts {
+        from: stored.from_address.clone(),
+        to: stored.to_address.clone(),
+        value: stored.value.clone(),
+        fee: stored.fee.clone(),
+        native_symbol: Some(stored.token_symbol.clone().unwrap_or(native_symbol)),
+        native_decimals: u8::try_from(stored.token_decimals.unwrap_or(native_decimals)).ok(),
+        swaps: load_transaction_swaps(pool, &wallet.id, &stored.hash).await?,
+        transfers: load_transaction_token_transfers(pool, &wallet.id, &stored.hash).await?,
+    };
+    save_events(
+        pool,
+        &wallet.id,
+        &stored.hash,
+        &expand(&wallet.address, &facts),
+    )
+    .await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "0xAb5801a7D398351b8bE11C439e05C5B3259aeC9B";
+    const NFT: &str = "0x60e4d786628fea6478f785a6d7e704777c86a7c6";
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    const WETH: &str = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+
+    fn transfer(token: &str, from: &str, to: &str, value: &str) -> TokenTransfer {
+        TokenTransfer {
+            token_address: token.to_string(),
+            token_symbol: None,
+            token_decimals: None,
+            from: from.to_string(),
+            to: to.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn leg(token_in: &str, amount_in: &str, token_out: &str, amount_out: &str) -> SwapDetail {
+        SwapDetail {
+            protocol: "uniswap_v3".to_string(),
+            pool: format!("{}-{}", token_in, token_out),
+            token_in: token_in.to_string(),
+            token_in_symbol: None,
+            token_in_decimals: None,
+            amount_in: amount_in.to_string(),
+            token_out: token_out.to_string(),
+            token_out_symbol: None,
+            token_out_decimals: None,
+            amount_out: amount_out.to_string(),
+        }
+    }
+
+    fn sent_by_wallet() -> TransactionFacts {
+        TransactionFacts {
+            from: Some(WALLET.to_lowercase()),
+            to: Some("0xd152f549545093347a162dce210e7293f1452150".to_string()),
+            value: Some("0".to_string()),
+            fee: Some("21000000000000".to_string()),
+            native_symbol: Some("ETH".to_string()),
+            native_decimals: Some(18),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_expand_disperse_payout() {
+        let facts = TransactionFacts {
+            transfers: vec![
+                transfer(
+                    USDC,
+                    WALLET,
+                    "0x1111111111111111111111111111111111111111",
+                    "100",
+                ),
+                transfer(
+                    USDC,
+                    WALLET,
+                    "0x2222222222222222222222222222222222222222",
+                    "250",
+                ),
+            ],
+            ..sent_by_wallet()
+        };
+        let events = expand(WALLET, &facts);
+
+        let kinds: Vec<_> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                AccountingEventKind::TransferOut,
+                AccountingEventKind::TransferOut,
+                AccountingEventKind::Fee
+            ]
+        );
+        assert_eq!(events[1].sent.as_ref().unwrap().amount, "250");
+        assert_eq!(
+            events[1].counterparty.as_deref(),
+            Some("0x2222222222222222222222222222222222222222")
+        );
+        assert_eq!(events[2].sent.as_ref().unwrap().asset, NATIVE_ASSET);
+    }
+
+    #[test]
+    fn test_expand_batch_mint_and_multicall_swaps() {
+        // Three NFTs minted for 0.3 ETH
+        let mint = TransactionFacts {
+            value: Some("300000000000000000".to_string()),
+            transfers: (1..=3)
+                .map(|id| transfer(NFT, ZERO_ADDRESS, WALLET, &id.to_string()))
+                .collect(),
+            ..sent_by_wallet()
+        };
+        let events = expand(WALLET, &mint);
+        assert_eq!(events.len(), 5);
+        assert!(events[..3]
+            .iter()
+            .all(|e| e.kind == AccountingEventKind::Mint && e.counterparty.is_none()));
+        assert_eq!(events[3].kind, AccountingEventKind::TransferOut);
+        assert_eq!(events[4].kind, AccountingEventKind::Fee);
+
+        // Two independent swaps, whose transfers are part of the trades
+        let multicall = TransactionFacts {
+            swaps: vec![leg(USDC, "1000", WETH, "5"), leg(NFT, "7", USDC, "40")],
+            transfers: vec![
+                transfer(USDC, WALLET, "0xpool1", "1000"),
+                transfer(WETH, "0xpool1", WALLET, "5"),
+                transfer(NFT, WALLET, "0xpool2", "7"),
+                transfer(USDC, "0xpool2", WALLET, "40"),
+            ],
+            ..sent_by_wallet()
+        };
+        let events = expand(WALLET, &multicall);
+        let kinds: Vec<_> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                AccountingEventKind::Swap,
+                AccountingEventKind::Swap,
+                AccountingEventKind::Fee
+            ]
+        );
+        assert_eq!(events[1].received.as_ref().unwrap().amount, "40");
+
+        // Someone else's transaction paying the wallet costs it no fee
+        let incoming = TransactionFacts {
+            from: Some("0x3333333333333333333333333333333333333333".to_string()),
+            to: Some(WALLET.to_string()),
+            value: Some("5".to_string()),
+            ..sent_by_wallet()
+        };
+        let events = expand(WALLET, &incoming);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, AccountingEventKind::TransferIn);
+    }
+}
@@ -0,0 +1,31 @@
+//! Chain currency lookups used by address watches and reports.
+
+use crate::chains::ChainManager;
+
+/// Looks up the native symbol and decimals for a chain.
+///
+/// Accepts either the chain name or its numeric id. Unknown chains fall back
+/// to 18 decimals, which matches every EVM chain.
+pub fn native_currency(chain_id: &str) -> (String, i32) {
+    ChainManager::get_supported_chains()
+        .into_iter()
+        .find(|c| {
+            c.chain_id.eq_ignore_ascii_case(chain_id)
+                || c.numeric_chain_id.map(|n| n.to_string()).as_deref() == Some(chain_id)
+        })
+        .map(|c| (c.symbol, c.decimals as i32))
+        .unwrap_or_else(|| (chain_id.to_uppercase(), 18))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_currency() {
+        assert_eq!(
+            native_currency("unknown-chain"),
+            ("UNKNOWN-CHAIN".to_string(), 18)
+        );
+    }
+}
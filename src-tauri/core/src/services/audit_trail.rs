@@ -0,0 +1,196 @@
+//! Recording changes to the audit trail.
+//!
+//! Each change is appended to `data_audit_log` with snapshots of the record
+//! before and after and the fields that differ.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::SqliteExecutor;
+use uuid::Uuid;
+
+/// Actor recorded when no user is signed in.
+const SYSTEM_ACTOR: &str = "system";
+
+/// Kind of record an audit entry describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordType {
+    /// A row in `wallets`.
+    Wallet,
+    /// A row in `transactions`.
+    Transaction,
+    /// A row in `multi_chain_transactions`.
+    RawTransaction,
+    /// A row in `transaction_tags`.
+    TransactionTag,
+    /// A row in `entities`.
+    Entity,
+    /// A row in `entity_addresses`.
+    EntityAddress,
+    /// A journal entry with its lines.
+    JournalEntry,
+    /// A row in `recurring_series`, for its auto-tag rule.
+    RecurringSeries,
+    /// A row in `transaction_merges`.
+    TransactionMerge,
+    /// A row in `account_links`.
+    AccountLink,
+    /// A row in `accounting_events`.
+    AccountingEvent,
+}
+
+impl RecordType {
+    /// Value stored in the `record_type` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Wallet => "wallet",
+            Self::Transaction => "transaction",
+            Self::RawTransaction => "raw_transaction",
+            Self::TransactionTag => "transaction_tag",
+            Self::Entity => "entity",
+            Self::EntityAddress => "entity_address",
+            Self::JournalEntry => "journal_entry",
+            Self::RecurringSeries => "recurring_series",
+            Self::TransactionMerge => "transaction_merge",
+            Self::AccountLink => "account_link",
+            Self::AccountingEvent => "accounting_event",
+        }
+    }
+
+    /// Parses a value stored in the `record_type` column.
+    pub fn parse(value: &str) -> Option<Self> {
+        [
+            Self::Wallet,
+            Self::Transaction,
+            Self::RawTransaction,
+            Self::TransactionTag,
+            Self::Entity,
+            Self::EntityAddress,
+            Self::JournalEntry,
+            Self::RecurringSeries,
+            Self::TransactionMerge,
+            Self::AccountLink,
+            Self::AccountingEvent,
+        ]
+        .into_iter()
+        .find(|record_type| record_type.as_str() == value)
+    }
+}
+
+/// Top-level fields that differ between two snapshots, each as
+/// `{"before": .., "after": ..}`. A missing side is treated as null.
+pub fn diff_snapshots(before: &Value, after: &Value) -> Map<String, Value> {
+    let empty = Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+
+    let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .filter_map(|key| {
+            let old = before.get(key).unwrap_or(&Value::Null);
+            let new = after.get(key).unwrap_or(&Value::Null);
+            (old != new).then(|| {
+                (
+                    key.clone(),
+                    serde_json::json!({ "before": old, "after": new }),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Appends a change to the audit log.
+///
+/// The action follows from which snapshots are given: only `after` is a
+/// create, only `before` a delete, both an update. Updates that change
+/// nothing are not recorded. Pass an open transaction as `executor` to make
+/// the record part of it.
+pub async fn record_change<'e, T: Serialize>(
+    executor: impl SqliteExecutor<'e>,
+    actor: Option<&str>,
+    record_type: RecordType,
+    record_id: &str,
+    profile_id: Option<&str>,
+    before: Option<&T>,
+    after: Option<&T>,
+) -> Result<(), String> {
+    let action = match (before.is_some(), after.is_some()) {
+        (false, true) => "create",
+        (true, true) => "update",
+        (true, false) => "delete",
+        (false, false) => return Ok(()),
+    };
+
+    let snapshot = |record: Option<&T>| -> Result<Value, String> {
+        record
+            .map(serde_json::to_value)
+            .transpose()
+            .map(|value| value.unwrap_or(Value::Null))
+            .map_err(|e| e.to_string())
+    };
+    let before = snapshot(before)?;
+    let after = snapshot(after)?;
+    let changes = diff_snapshots(&before, &after);
+    if action == "update" && changes.is_empty() {
+        return Ok(());
+    }
+
+    let to_text = |value: &Value| (!value.is_null()).then(|| value.to_string());
+    sqlx::query(
+        r#"
+        INSERT INTO data_audit_log (
+            id, record_type, record_id, action, actor, profile_id,
+            before_data, after_data, changes, created_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(record_type.as_str())
+    .bind(record_id)
+    .bind(action)
+    .bind(actor.unwrap_or(SYSTEM_ACTOR))
+    .bind(profile_id)
+    .bind(to_text(&before))
+    .bind(to_text(&after))
+    .bind(Value::Object(changes).to_string())
+    .bind(Utc::now())
+    .execute(executor)
+    .await
+    .map_err(|e| format!("Failed to record audit trail: {}", e))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_snapshots() {
+        let before = json!({ "name": "Treasury", "chain": "ethereum", "notes": "old" });
+        let after = json!({ "name": "Ops", "chain": "ethereum", "label": "hot" });
+        let changes = diff_snapshots(&before, &after);
+
+        assert_eq!(changes.len(), 3);
+        assert_eq!(
+            changes["name"],
+            json!({ "before": "Treasury", "after": "Ops" })
+        );
+        assert_eq!(changes["notes"], json!({ "before": "old", "after": null }));
+        assert_eq!(changes["label"], json!({ "before": null, "after": "hot" }));
+    }
+
+    #[test]
+    fn test_diff_against_nothing_lists_every_field() {
+        let created = json!({ "id": "w1", "chain": "polkadot" });
+        let changes = diff_snapshots(&Value::Null, &created);
+        assert_eq!(changes.len(), 2);
+        assert!(diff_snapshots(&created, &created).is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+//! Profile access checks and the authentication audit log.
+
+use super::permissions::{role_has, Permission, AUDITOR_ROLE};
+use chrono::Utc;
+use uuid::Uuid;
+
+/// Verifies the user holds an active role on the profile that grants
+/// `permission`. Checks made for an auditor are logged, allowed or not.
+pub async fn verify_profile_access(
+    pool: &sqlx::SqlitePool,
+    user_id: &str,
+    profile_id: &str,
+    permission: Permission,
+) -> Result<(), String> {
+    let role: Option<(String, String)> = sqlx::query_as(
+        "SELECT role, status FROM user_profile_roles WHERE user_id = ? AND profile_id = ?",
+    )
+    .bind(user_id)
+    .bind(profile_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    match role {
+        Some((role, status)) => {
+            if status != "active" {
+                return Err("Your access to this profile has been suspended".to_string());
+            }
+            let allowed = role_has(&role, permission);
+            if role == AUDITOR_ROLE {
+                let details = serde_json::json!({ "permission": permission }).to_string();
+                let outcome = if allowed { "success" } else { "failure" };
+                log_audit_event(
+                    pool,
+                    Some(user_id),
+                    "auditor_access",
+                    outcome,
+                    Some(&details),
+                    None,
+                    Some(profile_id),
+                )
+                .await;
+            }
+            if !allowed {
+                return Err(format!(
+                    "Insufficient permissions: the {} role cannot {}",
+                    role,
+                    permission.description()
+                ));
+            }
+            Ok(())
+        }
+        None => Err("You don't have access to this profile".to_string()),
+    }
+}
+
+/// Records an authentication or access event in the audit log. Failures
+/// to write are ignored so they never block the action being logged.
+pub async fn log_audit_event(
+    pool: &sqlx::SqlitePool,
+    user_id: Option<&str>,
+    event_type: &str,
+    event_status: &str,
+    event_details: Option<&str>,
+    target_user_id: Option<&str>,
+    target_profile_id: Option<&str>,
+) {
+    let id = Uuid::new_v4().to_string();
+    sqlx::query(
+        r#"
+        INSERT INTO auth_audit_log (id, user_id, event_type, event_status, event_details, target_user_id, target_profile_id, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(event_type)
+    .bind(event_status)
+    .bind(event_details)
+    .bind(target_user_id)
+    .bind(target_profile_id)
+    .bind(Utc::now())
+    .execute(pool)
+    .await
+    .ok();
+}
@@ -0,0 +1,193 @@
+//! Storage of the token transfers wallet sync decodes from each
+//! transaction, which balance history replays.
+
+use std::collections::HashMap;
+
+use sqlx::{FromRow, SqliteExecutor, SqlitePool};
+
+use crate::chains::TokenTransfer;
+
+/// A stored token transfer.
+#[derive(Debug, Clone, FromRow)]
+struct TransferRow {
+    hash: String,
+    token_address: String,
+    token_symbol: Option<String>,
+    token_decimals: Option<i64>,
+    from_address: String,
+    to_address: String,
+    value: String,
+}
+
+impl From<TransferRow> for TokenTransfer {
+    fn from(row: TransferRow) -> Self {
+        Self {
+            token_address: row.token_address,
+            token_symbol: row.token_symbol,
+            token_decimals: row.token_decimals.and_then(|d| u8::try_from(d).ok()),
+            from: row.from_address,
+            to: row.to_address,
+            value: row.value,
+        }
+    }
+}
+
+/// Replaces the stored token transfers of a wallet transaction.
+pub(crate) async fn save_token_transfers(
+    pool: &SqlitePool,
+    wallet_id: &str,
+    hash: &str,
+    transfers: &[TokenTransfer],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM transaction_token_transfers WHERE wallet_id = ? AND hash = ?")
+        .bind(wallet_id)
+        .bind(hash)
+        .execute(&mut *tx)
+        .await?;
+
+    for (transfer_index, transfer) in transfers.iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO transaction_token_transfers (
+                wallet_id, hash, transfer_index, token_address, token_symbol,
+                token_decimals, from_address, to_address, value
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(wallet_id)
+        .bind(hash)
+        .bind(transfer_index as i64)
+        .bind(&transfer.token_address)
+        .bind(&transfer.token_symbol)
+        .bind(transfer.token_decimals.map(i64::from))
+        .bind(&transfer.from)
+        .bind(&transfer.to)
+        .bind(&transfer.value)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await
+}
+
+/// Token transfers of one wallet transaction, in stored order.
+pub(crate) async fn load_transaction_token_transfers(
+    pool: &SqlitePool,
+    wallet_id: &str,
+    hash: &str,
+) -> Result<Vec<TokenTransfer>, sqlx::Error> {
+    let rows: Vec<TransferRow> = sqlx::query_as(
+        r#"
+        SELECT hash, token_address, token_symbol, token_decimals,
+               from_address, to_address, value
+        FROM transaction_token_transfers
+        WHERE wallet_id = ? AND hash = ?
+        ORDER BY transfer_index
+        "#,
+    )
+    .bind(wallet_id)
+    .bind(hash)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(TokenTransfer::from).collect())
+}
+
+/// Deletes the stored token transfers of every transaction of a wallet.
+pub async fn delete_wallet_token_transfers<'e>(
+    executor: impl SqliteExecutor<'e>,
+    wallet_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM transaction_token_transfers WHERE wallet_id = ?")
+        .bind(wallet_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Token transfers of a wallet's transactions, by hash, in stored order.
+pub async fn load_wallet_token_transfers(
+    pool: &SqlitePool,
+    wallet_id: &str,
+) -> Result<HashMap<String, Vec<TokenTransfer>>, sqlx::Error> {
+    let rows: Vec<TransferRow> = sqlx::query_as(
+        r#"
+        SELECT hash, token_address, token_symbol, token_decimals,
+               from_address, to_address, value
+        FROM transaction_token_transfers
+        WHERE wallet_id = ?
+        ORDER BY hash, transfer_index
+        "#,
+    )
+    .bind(wallet_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut transfers: HashMap<String, Vec<TokenTransfer>> = HashMap::new();
+    for row in rows {
+        transfers
+            .entry(row.hash.clone())
+            .or_default()
+            .push(row.into());
+    }
+    Ok(transfers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    const WALLET: &str = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045";
+    const OTHER: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+
+    fn usdc(from: &str, to: &str, value: &str) -> TokenTransfer {
+        TokenTransfer {
+            token_address: USDC.to_string(),
+            token_symbol: Some("USDC".to_string()),
+            token_decimals: Some(6),
+            from: from.to_string(),
+            to: to.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_token_transfers() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(include_str!(
+            "../../../migrations/20260417000001_create_transaction_token_transfers.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let first = vec![usdc(OTHER, WALLET, "1")];
+        let second = vec![usdc(OTHER, WALLET, "2"), usdc(WALLET, OTHER, "3")];
+        save_token_transfers(&pool, "w1", "0xaa", &first)
+            .await
+            .unwrap();
+        save_token_transfers(&pool, "w1", "0xaa", &second)
+            .await
+            .unwrap();
+
+        let transfers = load_wallet_token_transfers(&pool, "w1").await.unwrap();
+        assert_eq!(transfers["0xaa"].len(), 2);
+        assert_eq!(transfers["0xaa"][1].value, "3");
+        assert_eq!(transfers["0xaa"][0].token_decimals, Some(6));
+
+        delete_wallet_token_transfers(&pool, "w1").await.unwrap();
+        assert!(load_wallet_token_transfers(&pool, "w1")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+//! Confirmation thresholds for synced transactions.
+//!
+//! Explorers return a transaction as soon as it is mined, but its block can
+//! still be reorganized away until enough blocks are built on top. Each
+//! chain has a default threshold: 12 confirmations on Ethereum, Polygon,
+//! and BSC, 6 on Bitcoin, and 1 elsewhere, including Layer 2s. A profile
+//! may override it per chain. A successful transaction short of the
+//! threshold is stored as pending, and is marked successful by a later
+//! wallet sync or the background pending-transaction check once it has
+//! enough confirmations.
+
+use std::collections::HashMap;
+
+use sqlx::SqlitePool;
+
+use super::audit_trail::{record_change, RecordType};
+use super::mempool_watch::confirmations;
+use super::persistence::StoredTransaction;
+use crate::chains::{bitcoin, evm, ChainManagerState, TransactionStatus};
+use crate::log_error;
+
+/// Confirmations Bitcoin transactions need unless a profile chooses.
+pub const DEFAULT_BITCOIN_CONFIRMATIONS: u32 = 6;
+
+/// Confirmations a chain's transactions need unless a profile overrides it.
+pub fn default_confirmations(chain: &str) -> u32 {
+    if let Some(config) = evm::config::get_chain_by_name(chain) {
+        return config.confirmations;
+    }
+    if bitcoin::get_config_by_name(chain).is_some() {
+        return DEFAULT_BITCOIN_CONFIRMATIONS;
+    }
+    1
+}
+
+/// Whether a transaction mined at `block_number` has `required`
+/// confirmations with the chain at `tip`. One confirmation only takes being
+/// mined.
+pub fn is_confirmed(block_number: u64, tip: u64, required: u32) -> bool {
+    required <= 1 || confirmations(block_number, tip) >= u64::from(required)
+}
+
+/// Status to store for a fetched transaction: a success is held as pending
+/// until it has `required` confirmations. Without a `tip` the status is
+/// kept, which is only right when one confirmation is required.
+pub fn status_at_depth(
+    status: TransactionStatus,
+    block_number: u64,
+    tip: Option<u64>,
+    required: u32,
+) -> TransactionStatus {
+    match tip {
+        Some(tip)
+            if status == TransactionStatus::Success
+                && !is_confirmed(block_number, tip, required) =>
+        {
+            TransactionStatus::Pending
+        }
+        _ => status,
+    }
+}
+
+/// Confirmations a profile requires on `chain`, falling back to the chain's
+/// default.
+pub async fn required_confirmations(
+    pool: &SqlitePool,
+    profile_id: &str,
+    chain: &str,
+) -> Result<u32, String> {
+    let configured: Option<u32> = sqlx::query_scalar(
+        "SELECT confirmations FROM profile_chain_confirmations WHERE profile_id = ? AND chain = ?",
+    )
+    .bind(profile_id)
+    .bind(chain)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(configured.unwrap_or_else(|| default_confirmations(chain)))
+}
+
+/// Marks stored pending transactions successful once they have the
+/// confirmations their profile requires on their chain. Transactions not
+/// yet mined are left to their chain's own pending check. Limited to one
+/// wallet when `wallet_id` is given. Returns how many were marked.
+pub async fn run_confirmation_checks(
+    pool: &SqlitePool,
+    chains: &ChainManagerState,
+    wallet_id: Option<&str>,
+) -> Result<usize, String> {
+    let pending: Vec<StoredTransaction> = sqlx::query_as(
+        r#"
+        SELECT * FROM transactions
+        WHERE status = 'pending' AND block_number > 0 AND (? IS NULL OR wallet_id = ?)
+        ORDER BY chain, block_number
+        "#,
+    )
+    .bind(wallet_id)
+    .bind(wallet_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut profiles: HashMap<String, String> = HashMap::new();
+    let mut tips: HashMap<String, Option<u64>> = HashMap::new();
+    let mut confirmed = 0;
+    for tx in pending {
+        let profile_id = match profiles.get(&tx.wallet_id) {
+            Some(profile_id) => profile_id.clone(),
+            None => {
+                let profile_id: String =
+                    sqlx::query_scalar("SELECT profile_id FROM wallets WHERE id = ?")
+                        .bind(&tx.wallet_id)
+                        .fetch_one(pool)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                profiles.insert(tx.wallet_id.clone(), profile_id.clone());
+                profile_id
+            }
+        };
+        let required = required_confirmations(pool, &profile_id, &tx.chain).await?;
+
+        if !tips.contains_key(&tx.chain) {
+            let tip = match chains.read().await.get_block_number(&tx.chain).await {
+                Ok(tip) => Some(tip),
+                Err(e) => {
+                    log_error!("Failed to get the {} block number: {}", tx.chain, e);
+                    None
+                }
+            };
+            tips.insert(tx.chain.clone(), tip);
+        }
+        let Some(tip) = tips[&tx.chain] else {
+            continue;
+        };
+
+        let block_number = tx.block_number.unwrap_or_default() as u64;
+        if is_confirmed(block_number, tip, required) {
+            mark_confirmed(pool, &profile_id, &tx).await?;
+            confirmed += 1;
+        }
+    }
+    Ok(confirmed)
+}
+
+/// Marks a stored pending transaction successful, recording the change in
+/// the audit trail.
+async fn mark_confirmed(
+    pool: &SqlitePool,
+    profile_id: &str,
+    tx: &StoredTransaction,
+) -> Result<(), String> {
+    sqlx::query("UPDATE transactions SET status = 'success' WHERE id = ? AND status = 'pending'")
+        .bind(&tx.id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let after = StoredTransaction {
+        status: Some("success".to_string()),
+        ..tx.clone()
+    };
+    record_change(
+        pool,
+        None,
+        RecordType::Transaction,
+        &tx.id,
+        Some(profile_id),
+        Some(tx),
+        Some(&after),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_confirmations() {
+        assert_eq!(default_confirmations("ethereum"), 12);
+        assert_eq!(default_confirmations("arbitrum"), 1);
+        assert_eq!(default_confirmations("base"), 1);
+        assert_eq!(default_confirmations("bitcoin"), 6);
+        assert_eq!(default_confirmations("polkadot"), 1);
+    }
+
+    #[test]
+    fn test_status_at_depth_holds_shallow_successes() {
+        let success = TransactionStatus::Success;
+        // Block 100 with the tip at 110 has 11 confirmations
+        assert_eq!(
+            status_at_depth(success, 100, Some(110), 12),
+            TransactionStatus::Pending
+        );
+        assert_eq!(status_at_depth(success, 100, Some(111), 12), success);
+        assert_eq!(status_at_depth(success, 100, Some(100), 1), success);
+        assert_eq!(status_at_depth(success, 100, None, 12), success);
+        assert_eq!(
+            status_at_depth(TransactionStatus::Failed, 100, Some(100), 12),
+            TransactionStatus::Failed
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Statement lines and their valuation.
+//!
+//! [`Pricer`] values lines in the reporting currency, preferring a manual
+//! price override, and keeps the quote behind each value for the price
+//! source appendix. Transaction exports value their rows the same way.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use super::persistence::StoredTransaction;
+use super::price_feeds::{PriceQuote, PriceService};
+use super::price_ids::symbol_coin_ids;
+use super::price_overrides::{load_overrides, reporting_currency, select_override, PriceOverride};
+use super::price_sources::{price_sources, PriceSource};
+use super::token_spam::SpamFilter;
+use crate::core::currency::round_fiat;
+
+/// Which way a transfer went, from the profile's side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementDirection {
+    /// The profile paid the entity.
+    Paid,
+    /// The entity paid the profile.
+    Received,
+}
+
+impl StatementDirection {
+    /// Label shown in statement exports.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Paid => "Paid",
+            Self::Received => "Received",
+        }
+    }
+}
+
+/// One transfer with the entity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityStatementLine {
+    /// Stored transaction ID.
+    pub transaction_id: String,
+    /// Transaction hash.
+    pub hash: String,
+    /// Chain the transfer was on.
+    pub chain: String,
+    /// When the transfer happened.
+    pub date: DateTime<Utc>,
+    /// Whether the profile paid or was paid.
+    pub direction: StatementDirection,
+    /// The entity's address.
+    pub counterparty: String,
+    /// Asset symbol.
+    pub asset: String,
+    /// Amount in whole units.
+    pub amount: Decimal,
+    /// Unit price in the reporting currency, if one was found.
+    pub fiat_price: Option<Decimal>,
+    /// Value in the reporting currency, if the asset was priced.
+    pub fiat_value: Option<Decimal>,
+    /// Where the price came from.
+    pub price_source: Option<String>,
+    /// Endpoint the price was read from.
+    pub price_endpoint: Option<String>,
+    /// When the price was retrieved.
+    pub price_retrieved_at: Option<DateTime<Utc>>,
+}
+
+/// Values transfers at the time they happened: an override on the asset or
+/// its coin ID first, then the price feeds' daily price. Coin IDs passed in
+/// win over the profile's coin ID table. Prices are looked
+/// up once per asset and day, and the quote behind each value is kept for
+/// the price source appendix.
+pub struct Pricer {
+    overrides: Vec<PriceOverride>,
+    /// Reporting currency prices are in.
+    pub currency: String,
+    coin_ids: HashMap<String, String>,
+    cache: HashMap<(String, NaiveDate), Option<(Decimal, PriceQuote)>>,
+    used: Vec<(String, PriceQuote)>,
+    warnings: Vec<String>,
+}
+
+impl Pricer {
+    /// Loads a profile's price overrides, reporting currency, and coin IDs,
+    /// with `coin_ids` taking precedence over the profile's table.
+    pub async fn load(
+        pool: &SqlitePool,
+        profile_id: &str,
+        coin_ids: HashMap<String, String>,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            overrides: load_overrides(pool, profile_id)
+                .await
+                .map_err(|e| e.to_string())?,
+            currency: reporting_currency(pool).await.map_err(|e| e.to_string())?,
+            coin_ids: symbol_coin_ids(pool, profile_id, coin_ids).await?,
+            cache: HashMap::new(),
+            used: Vec::new(),
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Price of one unit of `asset` at `at`, and the quote it came from.
+    pub(crate) async fn price(
+        &mut self,
+        asset: &str,
+        at: DateTime<Utc>,
+    ) -> Option<(Decimal, PriceQuote)> {
+        let asset = asset.to_uppercase();
+        let coin_id = self.coin_ids.get(&asset).cloned();
+        let overridden = select_override(&self.overrides, &asset, &self.currency, at)
+            .or_else(|| {
+                coin_id
+                    .as_deref()
+                    .and_then(|id| select_override(&self.overrides, id, &self.currency, at))
+            })
+            .and_then(|o| Some((Decimal::from_str(&o.price).ok()?, o.quote())));
+        if overridden.is_some() {
+            return overridden;
+        }
+
+        let key = (asset.clone(), at.date_naive());
+        if let Some(cached) = self.cache.get(&key) {
+            return cached.clone();
+        }
+        let found = match coin_id {
+            Some(id) => {
+                let quote = match PriceService::shared() {
+                    Ok(service) => {
+                        service
+                            .historical_price(&id, key.1, &self.currency.to_lowercase())
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+                match quote.map(|q| (Decimal::from_str(&q.price), q)) {
+                    Ok((Ok(price), quote)) => Some((price, quote)),
+                    Ok((Err(_), _)) => {
+                        self.warnings
+                            .push(format!("Invalid price for {} on {}", asset, key.1));
+                        None
+                    }
+                    Err(e) => {
+                        self.warnings
+                            .push(format!("No price for {} on {}: {}", asset, key.1, e));
+                        None
+                    }
+                }
+            }
+            None => {
+                self.warnings
+                    .push(format!("No coin ID for {}; not valued", asset));
+                None
+            }
+        };
+        self.cache.insert(key, found.clone());
+        found
+    }
+
+    /// Fills in the fiat price, value, and price source of each line it can
+    /// price.
+    pub async fn value(&mut self, lines: &mut [EntityStatementLine]) {
+        for line in lines.iter_mut() {
+            if let Some((price, quote)) = self.price(&line.asset, line.date).await {
+                line.fiat_price = Some(price);
+                line.fiat_value = Some(round_fiat(price * line.amount, &self.currency));
+                line.price_source = Some(quote.provider.clone());
+                line.price_endpoint = Some(quote.endpoint.clone());
+                line.price_retrieved_at = Some(quote.retrieved_at);
+                self.used.push((line.asset.to_uppercase(), quote));
+            }
+        }
+    }
+
+    /// Sources of the prices used so far, for the price source appendix.
+    pub fn price_sources(&self) -> Vec<PriceSource> {
+        price_sources(
+            self.used
+                .iter()
+                .map(|(asset, quote)| (asset.as_str(), quote)),
+        )
+    }
+
+    /// Warnings about assets that couldn't be priced, sorted and without
+    /// duplicates.
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        let mut warnings = std::mem::take(&mut self.warnings);
+        warnings.sort();
+        warnings.dedup();
+        warnings
+    }
+}
+
+/// Whether `filter` hides a stored transaction as spam.
+pub fn hides_stored(filter: &SpamFilter, tx: &StoredTransaction) -> bool {
+    let raw = tx
+        .raw_data
+        .as_deref()
+        .and_then(|raw| serde_json::from_str(raw).ok());
+    filter.hides_transaction(&tx.chain, tx.token_symbol.as_deref(), raw.as_ref())
+}
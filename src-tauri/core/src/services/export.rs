@@ -0,0 +1,113 @@
+//! Writing a profile's transactions to CSV, optionally gzip-compressed.
+
+use super::token_spam::SpamFilter;
+use crate::db::Database;
+use anyhow::Result;
+use csv::Writer;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// File an export is written to, gzip-compressed when asked for. Rows go
+/// through a buffer to disk as they're written rather than being collected
+/// first.
+pub enum ExportFile {
+    /// Uncompressed output.
+    Plain(BufWriter<File>),
+    /// Gzip-compressed output.
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl ExportFile {
+    /// Creates or truncates the file at `path`.
+    pub fn create(path: &str, gzip: bool) -> Result<Self, String> {
+        let file = BufWriter::new(File::create(path).map_err(|e| e.to_string())?);
+        Ok(if gzip {
+            Self::Gzip(GzEncoder::new(file, Compression::default()))
+        } else {
+            Self::Plain(file)
+        })
+    }
+
+    /// Flushes what's buffered and, for gzip, writes the trailer. A gzip
+    /// file isn't readable until this is called.
+    pub fn finish(self) -> Result<(), String> {
+        let mut file = match self {
+            Self::Plain(file) => file,
+            Self::Gzip(encoder) => encoder.finish().map_err(|e| e.to_string())?,
+        };
+        file.flush().map_err(|e| e.to_string())
+    }
+}
+
+impl Write for ExportFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(file) => file.write(buf),
+            Self::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(file) => file.flush(),
+            Self::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Writes a profile's transactions to a CSV file at `path`, leaving out
+/// spam tokens, gzip-compressed if `gzip` is set. Returns the number of
+/// transactions written.
+pub async fn write_transactions_csv(
+    db: &Database,
+    path: &str,
+    profile_id: &str,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    gzip: bool,
+) -> Result<usize, String> {
+    let transactions = db
+        .get_transactions(profile_id, start_date, end_date)
+        .await
+        .map_err(|e| e.to_string())?;
+    let filter = SpamFilter::load(&db.pool, Some(profile_id))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut writer = Writer::from_writer(ExportFile::create(path, gzip)?);
+
+    // Write headers
+    writer
+        .write_record([
+            "Date", "Chain", "Hash", "From", "To", "Value", "Token", "Type", "Fee", "Status",
+        ])
+        .map_err(|e| e.to_string())?;
+
+    // Write transactions
+    let mut written = 0;
+    for tx in transactions {
+        if filter.hides_transaction(&tx.chain, Some(&tx.token_symbol), Some(&tx.metadata)) {
+            continue;
+        }
+        writer
+            .write_record(&[
+                tx.timestamp.to_string(),
+                tx.chain,
+                tx.hash,
+                tx.from_address,
+                tx.to_address.unwrap_or_default(),
+                tx.value.to_string(),
+                tx.token_symbol,
+                tx.transaction_type,
+                tx.fee.map(|f| f.to_string()).unwrap_or_default(),
+                tx.status,
+            ])
+            .map_err(|e| e.to_string())?;
+        written += 1;
+    }
+
+    writer.into_inner().map_err(|e| e.to_string())?.finish()?;
+    Ok(written)
+}
@@ -0,0 +1,1056 @@
+//! Export templates for transaction CSVs.
+//!
+//! A template picks which columns a transaction export has and in what
+//! order, including fiat values, budget tags, counterparty entities, and
+//! tax lot details, along with how dates and decimals are written and the
+//! field delimiter. Templates are saved per profile so a recurring export,
+//! whether from the app or `pacioli-cli export --template`, comes out the
+//! same way each time.
+
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::str::FromStr;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+use csv::{Writer, WriterBuilder};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, SqlitePool};
+
+use super::address_watch::native_currency;
+use super::entity_statements::{hides_stored, Pricer};
+use super::persistence::{StoredTransaction, Wallet};
+use super::profile_scope::profile_wallets;
+use super::statement_export::{parse_amount, parse_period_bound};
+use super::token_spam::SpamFilter;
+use crate::core::currency::round_fiat;
+use crate::jobs::CancelToken;
+
+/// Date format used when a template doesn't set one.
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Transactions read from the database at a time while exporting.
+pub(crate) const EXPORT_PAGE_SIZE: i64 = 1000;
+
+/// Columns of an export without a template, matching the fixed layout of
+/// `export_transactions_csv`.
+pub const DEFAULT_COLUMNS: &[ExportColumn] = &[
+    ExportColumn::Date,
+    ExportColumn::Chain,
+    ExportColumn::Hash,
+    ExportColumn::From,
+    ExportColumn::To,
+    ExportColumn::Amount,
+    ExportColumn::Asset,
+    ExportColumn::Type,
+    ExportColumn::Fee,
+    ExportColumn::Status,
+];
+
+/// A column an export can include.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportColumn {
+    /// When the transaction happened.
+    Date,
+    /// Chain identifier.
+    Chain,
+    /// Wallet name, or its address when unnamed.
+    Wallet,
+    /// Transaction hash.
+    Hash,
+    /// Block number.
+    Block,
+    /// Sender address.
+    From,
+    /// Recipient address.
+    To,
+    /// `in`, `out`, or `self`, from the wallet's side.
+    Direction,
+    /// Amount in whole units of the asset.
+    Amount,
+    /// Asset symbol.
+    Asset,
+    /// Transaction type.
+    Type,
+    /// Status.
+    Status,
+    /// Network fee in whole units of the chain's currency.
+    Fee,
+    /// Unit price in the reporting currency.
+    FiatPrice,
+    /// Amount valued in the reporting currency.
+    FiatValue,
+    /// Budget tag categories.
+    Tags,
+    /// Entity on the other side of the transfer.
+    Entity,
+    /// Cost basis of the lots the transaction disposed of, or acquired.
+    CostBasis,
+    /// Gain or loss realized on the lots disposed of.
+    RealizedGain,
+    /// `short`, `long`, or `mixed`, for the lots disposed of.
+    HoldingPeriod,
+}
+
+impl ExportColumn {
+    /// Header cell for the column. Fiat columns name the currency.
+    fn header(self, currency: &str) -> String {
+        match self {
+            Self::Date => "Date".to_string(),
+            Self::Chain => "Chain".to_string(),
+            Self::Wallet => "Wallet".to_string(),
+            Self::Hash => "Hash".to_string(),
+            Self::Block => "Block".to_string(),
+            Self::From => "From".to_string(),
+            Self::To => "To".to_string(),
+            Self::Direction => "Direction".to_string(),
+            Self::Amount => "Amount".to_string(),
+            Self::Asset => "Asset".to_string(),
+            Self::Type => "Type".to_string(),
+            Self::Status => "Status".to_string(),
+            Self::Fee => "Fee".to_string(),
+            Self::FiatPrice => format!("Price ({})", currency),
+            Self::FiatValue => format!("Value ({})", currency),
+            Self::Tags => "Tags".to_string(),
+            Self::Entity => "Entity".to_string(),
+            Self::CostBasis => format!("Cost Basis ({})", currency),
+            Self::RealizedGain => format!("Realized Gain ({})", currency),
+            Self::HoldingPeriod => "Holding Period".to_string(),
+        }
+    }
+}
+
+/// Character between the whole and fractional parts of a number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecimalSeparator {
+    /// `1234.5`
+    Period,
+    /// `1234,5`
+    Comma,
+}
+
+impl DecimalSeparator {
+    /// Name the separator is stored under.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Period => "period",
+            Self::Comma => "comma",
+        }
+    }
+}
+
+impl FromStr for DecimalSeparator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "period" => Ok(Self::Period),
+            "comma" => Ok(Self::Comma),
+            _ => Err(format!("Unsupported decimal separator: {}", s)),
+        }
+    }
+}
+
+/// Character between fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CsvDelimiter {
+    /// `,`
+    Comma,
+    /// `;`, usual where the comma is the decimal separator.
+    Semicolon,
+    /// A tab.
+    Tab,
+}
+
+impl CsvDelimiter {
+    /// Name the delimiter is stored under.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Comma => "comma",
+            Self::Semicolon => "semicolon",
+            Self::Tab => "tab",
+        }
+    }
+
+    fn byte(self) -> u8 {
+        match self {
+            Self::Comma => b',',
+            Self::Semicolon => b';',
+            Self::Tab => b'\t',
+        }
+    }
+}
+
+impl FromStr for CsvDelimiter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "comma" => Ok(Self::Comma),
+            "semicolon" => Ok(Self::Semicolon),
+            "tab" => Ok(Self::Tab),
+            _ => Err(format!("Unsupported delimiter: {}", s)),
+        }
+    }
+}
+
+/// A saved export template.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTemplate {
+    /// Unique identifier.
+    pub id: String,
+    /// Profile the template belongs to.
+    pub profile_id: String,
+    /// Display name, unique within the profile.
+    pub name: String,
+    /// Columns in output order.
+    pub columns: Json<Vec<ExportColumn>>,
+    /// strftime pattern dates are written with.
+    pub date_format: String,
+    /// One of: period, comma.
+    pub decimal_separator: String,
+    /// One of: comma, semicolon, tab.
+    pub delimiter: String,
+    /// User who created the template.
+    pub created_by: Option<String>,
+    /// Timestamp when the template was created.
+    pub created_at: DateTime<Utc>,
+    /// Timestamp when the template was last updated.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Input for creating or updating a template.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTemplateInput {
+    /// Template to update; a new one is created when unset.
+    pub id: Option<String>,
+    /// Profile the template belongs to.
+    pub profile_id: String,
+    /// Display name.
+    pub name: String,
+    /// Columns in output order.
+    pub columns: Vec<ExportColumn>,
+    /// strftime pattern; defaults to [`DEFAULT_DATE_FORMAT`].
+    pub date_format: Option<String>,
+    /// One of: period, comma. Defaults to period.
+    pub decimal_separator: Option<String>,
+    /// One of: comma, semicolon, tab. Defaults to semicolon when the
+    /// decimal separator is a comma, and comma otherwise.
+    pub delimiter: Option<String>,
+}
+
+/// How an export is laid out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportLayout {
+    /// Columns in output order.
+    pub columns: Vec<ExportColumn>,
+    /// strftime pattern for dates.
+    pub date_format: String,
+    /// Decimal separator for numbers.
+    pub decimal_separator: DecimalSeparator,
+    /// Field delimiter.
+    pub delimiter: CsvDelimiter,
+}
+
+impl Default for ExportLayout {
+    fn default() -> Self {
+        Self {
+            columns: DEFAULT_COLUMNS.to_vec(),
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            decimal_separator: DecimalSeparator::Period,
+            delimiter: CsvDelimiter::Comma,
+        }
+    }
+}
+
+impl ExportLayout {
+    /// Checks and normalizes a template's settings.
+    pub fn from_input(input: &ExportTemplateInput) -> Result<Self, String> {
+        if input.name.trim().is_empty() {
+            return Err("A template name is required".to_string());
+        }
+        if input.columns.is_empty() {
+            return Err("A template needs at least one column".to_string());
+        }
+        let mut seen = HashSet::new();
+        if let Some(column) = input.columns.iter().find(|c| !seen.insert(**c)) {
+            return Err(format!("Column {:?} is listed twice", column));
+        }
+
+        let date_format = input
+            .date_format
+            .as_deref()
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .unwrap_or(DEFAULT_DATE_FORMAT)
+            .to_string();
+        if StrftimeItems::new(&date_format).any(|item| matches!(item, Item::Error)) {
+            return Err(format!("Invalid date format: {}", date_format));
+        }
+
+        let decimal_separator = match &input.decimal_separator {
+            Some(separator) => separator.parse()?,
+            None => DecimalSeparator::Period,
+        };
+        let delimiter = match &input.delimiter {
+            Some(delimiter) => delimiter.parse()?,
+            None if decimal_separator == DecimalSeparator::Comma => CsvDelimiter::Semicolon,
+            None => CsvDelimiter::Comma,
+        };
+        if decimal_separator == DecimalSeparator::Comma && delimiter == CsvDelimiter::Comma {
+            return Err("The delimiter can't be a comma when the decimal separator is".to_string());
+        }
+
+        Ok(Self {
+            columns: input.columns.clone(),
+            date_format,
+            decimal_separator,
+            delimiter,
+        })
+    }
+
+    /// The layout a saved template describes.
+    pub fn from_template(template: &ExportTemplate) -> Result<Self, String> {
+        Ok(Self {
+            columns: template.columns.0.clone(),
+            date_format: template.date_format.clone(),
+            decimal_separator: template.decimal_separator.parse()?,
+            delimiter: template.delimiter.parse()?,
+        })
+    }
+
+    fn includes(&self, columns: &[ExportColumn]) -> bool {
+        self.columns.iter().any(|c| columns.contains(c))
+    }
+
+    fn number(&self, value: Option<Decimal>) -> String {
+        let Some(value) = value else {
+            return String::new();
+        };
+        let text = value.normalize().to_string();
+        match self.decimal_separator {
+            DecimalSeparator::Period => text,
+            DecimalSeparator::Comma => text.replace('.', ","),
+        }
+    }
+}
+
+/// Progress of an export, emitted as [`EXPORT_PROGRESS_EVENT`].
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportProgress {
+    /// Job the export was registered under, if any.
+    pub job_id: Option<String>,
+    /// File being written.
+    pub path: String,
+    /// Transactions read so far, including spam left out.
+    pub processed: usize,
+    /// Transactions in the period.
+    pub total: usize,
+    /// Transactions written so far.
+    pub written: usize,
+}
+
+/// Tax lot details of a transaction.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LotDetails {
+    /// Cost basis of the lots the transaction acquired.
+    pub acquired_cost: Option<Decimal>,
+    /// Cost basis of the lots the transaction disposed of.
+    pub disposed_cost: Option<Decimal>,
+    /// Gain or loss realized on the lots disposed of.
+    pub realized_gain: Option<Decimal>,
+    /// Whether each disposal was held long term.
+    pub long_term: Vec<bool>,
+}
+
+impl LotDetails {
+    fn holding_period(&self) -> &'static str {
+        match (
+            self.long_term.iter().any(|l| *l),
+            self.long_term.iter().any(|l| !*l),
+        ) {
+            (true, true) => "mixed",
+            (true, false) => "long",
+            (false, true) => "short",
+            (false, false) => "",
+        }
+    }
+}
+
+/// One exported transaction.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportRow {
+    /// When the transaction happened.
+    pub date: Option<DateTime<Utc>>,
+    /// Chain identifier.
+    pub chain: String,
+    /// Wallet name or address.
+    pub wallet: String,
+    /// Transaction hash.
+    pub hash: String,
+    /// Block number.
+    pub block: Option<i64>,
+    /// Sender address.
+    pub from: String,
+    /// Recipient address.
+    pub to: String,
+    /// `in`, `out`, or `self`, if the wallet is either side.
+    pub direction: Option<&'static str>,
+    /// Amount in whole units.
+    pub amount: Option<Decimal>,
+    /// Asset symbol.
+    pub asset: String,
+    /// Transaction type.
+    pub tx_type: String,
+    /// Status.
+    pub status: String,
+    /// Fee in whole units of the chain's currency.
+    pub fee: Option<Decimal>,
+    /// Unit price in the reporting currency.
+    pub fiat_price: Option<Decimal>,
+    /// Value in the reporting currency.
+    pub fiat_value: Option<Decimal>,
+    /// Budget tag categories.
+    pub tags: Vec<String>,
+    /// Counterparty entity name.
+    pub entity: Option<String>,
+    /// Tax lot details.
+    pub lots: LotDetails,
+}
+
+/// Direction of a transaction from `wallet`'s side, both lowercase.
+pub(crate) fn direction(wallet: &str, from: &str, to: &str) -> Option<&'static str> {
+    match (from == wallet, to == wallet) {
+        (true, true) => Some("self"),
+        (true, false) => Some("out"),
+        (false, true) => Some("in"),
+        (false, false) => None,
+    }
+}
+
+/// Builds a transaction's row from the stored transaction and what's known
+/// about it. Fiat values are left for the caller to fill in.
+fn export_row(
+    tx: &StoredTransaction,
+    wallet: Option<&Wallet>,
+    tags: &HashMap<String, Vec<String>>,
+    entities: &HashMap<String, String>,
+    lots: &HashMap<(String, String), LotDetails>,
+) -> ExportRow {
+    let from = tx.from_address.clone().unwrap_or_default();
+    let to = tx.to_address.clone().unwrap_or_default();
+    let direction = wallet.and_then(|w| {
+        direction(
+            &w.address.to_lowercase(),
+            &from.to_lowercase(),
+            &to.to_lowercase(),
+        )
+    });
+    let counterparty = match direction {
+        Some("out") => Some(&to),
+        Some("in") => Some(&from),
+        _ => None,
+    };
+
+    let (native_symbol, native_decimals) = native_currency(&tx.chain);
+    let (asset, decimals) = match &tx.token_symbol {
+        Some(symbol) => (symbol.clone(), tx.token_decimals),
+        None => (native_symbol, Some(native_decimals)),
+    };
+
+    ExportRow {
+        date: tx.timestamp,
+        chain: tx.chain.clone(),
+        wallet: wallet
+            .map(|w| w.name.clone().unwrap_or_else(|| w.address.clone()))
+            .unwrap_or_default(),
+        hash: tx.hash.clone(),
+        block: tx.block_number,
+        from: from.clone(),
+        to: to.clone(),
+        direction,
+        amount: tx
+            .value
+            .as_deref()
+            .and_then(|value| parse_amount(value, decimals)),
+        asset,
+        tx_type: tx.tx_type.clone().unwrap_or_default(),
+        status: tx.status.clone().unwrap_or_default(),
+        fee: tx
+            .fee
+            .as_deref()
+            .and_then(|fee| parse_amount(fee, Some(native_decimals))),
+        fiat_price: None,
+        fiat_value: None,
+        tags: tags.get(&tx.id).cloned().unwrap_or_default(),
+        entity: counterparty.and_then(|address| entities.get(&address.to_lowercase()).cloned()),
+        lots: lots
+            .get(&(tx.chain.clone(), tx.hash.to_lowercase()))
+            .cloned()
+            .unwrap_or_default(),
+    }
+}
+
+fn cell(layout: &ExportLayout, column: ExportColumn, row: &ExportRow) -> String {
+    match column {
+        ExportColumn::Date => row
+            .date
+            .map(|date| date.format(&layout.date_format).to_string())
+            .unwrap_or_default(),
+        ExportColumn::Chain => row.chain.clone(),
+        ExportColumn::Wallet => row.wallet.clone(),
+        ExportColumn::Hash => row.hash.clone(),
+        ExportColumn::Block => row.block.map(|b| b.to_string()).unwrap_or_default(),
+        ExportColumn::From => row.from.clone(),
+        ExportColumn::To => row.to.clone(),
+        ExportColumn::Direction => row.direction.unwrap_or_default().to_string(),
+        ExportColumn::Amount => layout.number(row.amount),
+        ExportColumn::Asset => row.asset.clone(),
+        ExportColumn::Type => row.tx_type.clone(),
+        ExportColumn::Status => row.status.clone(),
+        ExportColumn::Fee => layout.number(row.fee),
+        ExportColumn::FiatPrice => layout.number(row.fiat_price),
+        ExportColumn::FiatValue => layout.number(row.fiat_value),
+        ExportColumn::Tags => row.tags.join("; "),
+        ExportColumn::Entity => row.entity.clone().unwrap_or_default(),
+        ExportColumn::CostBasis => layout.number(row.lots.disposed_cost.or(row.lots.acquired_cost)),
+        ExportColumn::RealizedGain => layout.number(row.lots.realized_gain),
+        ExportColumn::HoldingPeriod => row.lots.holding_period().to_string(),
+    }
+}
+
+fn write_header<W: Write>(
+    writer: &mut Writer<W>,
+    layout: &ExportLayout,
+    currency: &str,
+) -> Result<(), String> {
+    writer
+        .write_record(layout.columns.iter().map(|c| c.header(currency)))
+        .map_err(|e| e.to_string())
+}
+
+fn write_row<W: Write>(
+    writer: &mut Writer<W>,
+    layout: &ExportLayout,
+    row: &ExportRow,
+) -> Result<(), String> {
+    writer
+        .write_record(layout.columns.iter().map(|c| cell(layout, *c, row)))
+        .map_err(|e| e.to_string())
+}
+
+/// Budget tag categories by transaction ID.
+async fn transaction_tags(
+    pool: &SqlitePool,
+    profile_id: &str,
+) -> Result<HashMap<String, Vec<String>>, String> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT transaction_id, category FROM transaction_tags WHERE profile_id = ? ORDER BY category",
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for (transaction_id, category) in rows {
+        tags.entry(transaction_id).or_default().push(category);
+    }
+    Ok(tags)
+}
+
+/// Entity names by lowercase address.
+async fn entity_names(
+    pool: &SqlitePool,
+    profile_id: &str,
+) -> Result<HashMap<String, String>, String> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT ea.address, e.name FROM entity_addresses ea
+        INNER JOIN entities e ON ea.entity_id = e.id
+        WHERE e.profile_id = ?
+        "#,
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(rows
+        .into_iter()
+        .map(|(address, name)| (address.to_lowercase(), name))
+        .collect())
+}
+
+/// Tax lot details by chain and lowercase transaction hash, for the
+/// ledger transactions that record an on-chain hash.
+async fn lot_details(pool: &SqlitePool) -> Result<HashMap<(String, String), LotDetails>, String> {
+    let acquired: Vec<(String, String, String)> = sqlx::query_as(
+        r#"
+        SELECT a.chain_id, a.txn_hash, CAST(l.cost_basis AS TEXT)
+        FROM transaction_lots l
+        INNER JOIN accounting_transactions a ON l.accounting_transaction_id = a.id
+        WHERE a.txn_hash IS NOT NULL
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let disposed: Vec<(String, String, String, String, Option<bool>)> = sqlx::query_as(
+        r#"
+        SELECT a.chain_id, a.txn_hash, CAST(d.cost_basis AS TEXT), CAST(d.gain_loss AS TEXT),
+               d.is_long_term
+        FROM lot_disposals d
+        INNER JOIN accounting_transactions a ON d.disposal_transaction_id = a.id
+        WHERE a.txn_hash IS NOT NULL
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let add = |total: &mut Option<Decimal>, value: &str| {
+        if let Ok(value) = Decimal::from_str(value) {
+            *total = Some(total.unwrap_or_default() + value);
+        }
+    };
+    let mut lots: HashMap<(String, String), LotDetails> = HashMap::new();
+    for (chain, hash, cost) in acquired {
+        let details = lots.entry((chain, hash.to_lowercase())).or_default();
+        add(&mut details.acquired_cost, &cost);
+    }
+    for (chain, hash, cost, gain, long_term) in disposed {
+        let details = lots.entry((chain, hash.to_lowercase())).or_default();
+        add(&mut details.disposed_cost, &cost);
+        add(&mut details.realized_gain, &gain);
+        if let Some(long_term) = long_term {
+            details.long_term.push(long_term);
+        }
+    }
+    Ok(lots)
+}
+
+/// Where the next page of an export starts. Undated transactions sort
+/// first, so the cursor moves through them by ID before the dated ones.
+#[derive(Debug, Default)]
+pub(crate) struct PageCursor {
+    timestamp: Option<DateTime<Utc>>,
+    id: String,
+}
+
+impl PageCursor {
+    pub(crate) fn after(tx: &StoredTransaction) -> Self {
+        Self {
+            timestamp: tx.timestamp,
+            id: tx.id.clone(),
+        }
+    }
+}
+
+/// The profile's transactions in the period, less merged duplicates.
+pub(crate) async fn count_transactions(
+    pool: &SqlitePool,
+    profile_id: &str,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> Result<usize, String> {
+    let count: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM transactions t
+        INNER JOIN wallets w ON t.wallet_id = w.id
+        WHERE w.profile_id = ?
+          AND (? IS NULL OR t.timestamp >= ?) AND (? IS NULL OR t.timestamp <= ?)
+          AND t.id NOT IN (SELECT duplicate_id FROM transaction_merges)
+        "#,
+    )
+    .bind(profile_id)
+    .bind(start)
+    .bind(start)
+    .bind(end)
+    .bind(end)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(count as usize)
+}
+
+/// Up to `limit` of the profile's transactions in the period following
+/// `cursor`, oldest first, less merged duplicates.
+pub(crate) async fn transaction_page(
+    pool: &SqlitePool,
+    profile_id: &str,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    cursor: &PageCursor,
+    limit: i64,
+) -> Result<Vec<StoredTransaction>, String> {
+    sqlx::query_as::<_, StoredTransaction>(
+        r#"
+        SELECT t.* FROM transactions t
+        INNER JOIN wallets w ON t.wallet_id = w.id
+        WHERE w.profile_id = ?
+          AND (? IS NULL OR t.timestamp >= ?) AND (? IS NULL OR t.timestamp <= ?)
+          AND t.id NOT IN (SELECT duplicate_id FROM transaction_merges)
+          AND (
+            (? IS NULL AND (t.timestamp IS NOT NULL OR t.id > ?))
+            OR t.timestamp > ? OR (t.timestamp = ? AND t.id > ?)
+          )
+        ORDER BY t.timestamp ASC, t.id ASC
+        LIMIT ?
+        "#,
+    )
+    .bind(profile_id)
+    .bind(start)
+    .bind(start)
+    .bind(end)
+    .bind(end)
+    .bind(cursor.timestamp)
+    .bind(&cursor.id)
+    .bind(cursor.timestamp)
+    .bind(cursor.timestamp)
+    .bind(&cursor.id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Writes a profile's transactions between `start_date` and `end_date` to
+/// `out` as CSV in `layout`, leaving out merged duplicates and spam
+/// tokens. Assets are priced with `coin_ids` when the layout has fiat
+/// columns.
+///
+/// Transactions are read and written [`EXPORT_PAGE_SIZE`] at a time, so
+/// memory stays flat however many there are. `on_progress` is called with
+/// `progress` updated after each page, and the export stops between pages
+/// once `cancel` is cancelled. Returns `out` and the number of transactions
+/// written.
+#[allow(clippy::too_many_arguments)]
+pub async fn write_export<W: Write>(
+    pool: &SqlitePool,
+    profile_id: &str,
+    layout: &ExportLayout,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+    coin_ids: HashMap<String, String>,
+    out: W,
+    cancel: &CancelToken,
+    mut progress: ExportProgress,
+    mut on_progress: impl FnMut(&ExportProgress),
+) -> Result<(W, usize), String> {
+    let start = parse_period_bound(start_date, false)?;
+    let end = parse_period_bound(end_date, true)?;
+    let filter = SpamFilter::load(pool, Some(profile_id))
+        .await
+        .map_err(|e| e.to_string())?;
+    let wallets: HashMap<String, Wallet> = profile_wallets(pool, profile_id)
+        .await?
+        .into_iter()
+        .map(|w| (w.id.clone(), w))
+        .collect();
+
+    let tags = if layout.includes(&[ExportColumn::Tags]) {
+        transaction_tags(pool, profile_id).await?
+    } else {
+        HashMap::new()
+    };
+    let entities = if layout.includes(&[ExportColumn::Entity]) {
+        entity_names(pool, profile_id).await?
+    } else {
+        HashMap::new()
+    };
+    let lots = if layout.includes(&[
+        ExportColumn::CostBasis,
+        ExportColumn::RealizedGain,
+        ExportColumn::HoldingPeriod,
+    ]) {
+        lot_details(pool).await?
+    } else {
+        HashMap::new()
+    };
+    let priced = layout.includes(&[ExportColumn::FiatPrice, ExportColumn::FiatValue]);
+    let mut pricer = Pricer::load(pool, profile_id, coin_ids).await?;
+
+    let mut writer = WriterBuilder::new()
+        .delimiter(layout.delimiter.byte())
+        .from_writer(out);
+    write_header(&mut writer, layout, &pricer.currency)?;
+
+    progress.total = count_transactions(pool, profile_id, start, end).await?;
+    let mut cursor = PageCursor::default();
+    loop {
+        cancel.check()?;
+        let page =
+            transaction_page(pool, profile_id, start, end, &cursor, EXPORT_PAGE_SIZE).await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        cursor = PageCursor::after(last);
+
+        for tx in page.iter().filter(|tx| !hides_stored(&filter, tx)) {
+            let mut row = export_row(tx, wallets.get(&tx.wallet_id), &tags, &entities, &lots);
+            if let (true, Some(date), Some(amount)) = (priced, row.date, row.amount) {
+                if let Some((price, _)) = pricer.price(&row.asset, date).await {
+                    row.fiat_price = Some(price);
+                    row.fiat_value = Some(round_fiat(price * amount, &pricer.currency));
+                }
+            }
+            write_row(&mut writer, layout, &row)?;
+            progress.written += 1;
+        }
+        writer.flush().map_err(|e| e.to_string())?;
+
+        progress.processed += page.len();
+        on_progress(&progress);
+    }
+
+    let out = writer.into_inner().map_err(|e| e.to_string())?;
+    Ok((out, progress.written))
+}
+
+/// A profile's template by name.
+pub async fn find_template(
+    pool: &SqlitePool,
+    profile_id: &str,
+    name: &str,
+) -> Result<ExportTemplate, String> {
+    sqlx::query_as::<_, ExportTemplate>(
+        "SELECT * FROM export_templates WHERE profile_id = ? AND name = ?",
+    )
+    .bind(profile_id)
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Export template not found: {}", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    const WALLET: &str = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045";
+    const VENDOR: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+
+    fn input(columns: Vec<ExportColumn>) -> ExportTemplateInput {
+        ExportTemplateInput {
+            id: None,
+            profile_id: "p1".to_string(),
+            name: "Accountant".to_string(),
+            columns,
+            date_format: None,
+            decimal_separator: None,
+            delimiter: None,
+        }
+    }
+
+    fn wallet() -> Wallet {
+        Wallet {
+            id: "w1".to_string(),
+            profile_id: "p1".to_string(),
+            address: WALLET.to_string(),
+            chain: "ethereum".to_string(),
+            name: Some("Treasury".to_string()),
+            wallet_type: "software".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+        }
+    }
+
+    fn tx() -> StoredTransaction {
+        let at = Utc.with_ymd_and_hms(2025, 3, 4, 12, 30, 0).unwrap();
+        StoredTransaction {
+            id: "t1".to_string(),
+            wallet_id: "w1".to_string(),
+            hash: "0xABC".to_string(),
+            block_number: Some(100),
+            timestamp: Some(at),
+            from_address: Some(WALLET.to_string()),
+            to_address: Some(VENDOR.to_uppercase().replace("0X", "0x")),
+            value: Some("1500000".to_string()),
+            fee: Some("21000000000000".to_string()),
+            status: Some("success".to_string()),
+            tx_type: Some("transfer".to_string()),
+            token_symbol: Some("USDC".to_string()),
+            token_decimals: Some(6),
+            chain: "ethereum".to_string(),
+            raw_data: None,
+            created_at: at,
+        }
+    }
+
+    #[test]
+    fn test_validates_templates() {
+        let layout = ExportLayout::from_input(&input(vec![ExportColumn::Date])).unwrap();
+        assert_eq!(layout.date_format, DEFAULT_DATE_FORMAT);
+        assert_eq!(layout.delimiter, CsvDelimiter::Comma);
+
+        let mut comma = input(vec![ExportColumn::Amount]);
+        comma.decimal_separator = Some("comma".to_string());
+        let layout = ExportLayout::from_input(&comma).unwrap();
+        assert_eq!(layout.delimiter, CsvDelimiter::Semicolon);
+        comma.delimiter = Some("comma".to_string());
+        assert!(ExportLayout::from_input(&comma).is_err());
+
+        assert!(ExportLayout::from_input(&input(vec![])).is_err());
+        assert!(
+            ExportLayout::from_input(&input(vec![ExportColumn::Hash, ExportColumn::Hash])).is_err()
+        );
+        let mut bad_date = input(vec![ExportColumn::Date]);
+        bad_date.date_format = Some("%Q".to_string());
+        assert!(ExportLayout::from_input(&bad_date).is_err());
+    }
+
+    #[test]
+    fn test_builds_rows_with_tags_entities_and_lots() {
+        let tags = HashMap::from([(
+            "t1".to_string(),
+            vec!["grants".to_string(), "ops".to_string()],
+        )]);
+        let entities = HashMap::from([(VENDOR.to_string(), "Acme".to_string())]);
+        let lots = HashMap::from([(
+            ("ethereum".to_string(), "0xabc".to_string()),
+            LotDetails {
+                disposed_cost: Some(Decimal::new(120, 2)),
+                realized_gain: Some(Decimal::new(30, 2)),
+                long_term: vec![true, false],
+                ..LotDetails::default()
+            },
+        )]);
+
+        let row = export_row(&tx(), Some(&wallet()), &tags, &entities, &lots);
+        assert_eq!(row.wallet, "Treasury");
+        assert_eq!(row.direction, Some("out"));
+        assert_eq!(row.amount, Some(Decimal::new(15, 1)));
+        assert_eq!(row.fee, Some(Decimal::new(21, 6)));
+        assert_eq!(row.entity.as_deref(), Some("Acme"));
+        assert_eq!(row.lots.holding_period(), "mixed");
+    }
+
+    #[test]
+    fn test_renders_in_template_order_and_format() {
+        let mut template = input(vec![
+            ExportColumn::Amount,
+            ExportColumn::Date,
+            ExportColumn::FiatValue,
+            ExportColumn::Tags,
+        ]);
+        template.date_format = Some("%d.%m.%Y".to_string());
+        template.decimal_separator = Some("comma".to_string());
+        let layout = ExportLayout::from_input(&template).unwrap();
+
+        let mut row = export_row(
+            &tx(),
+            Some(&wallet()),
+            &HashMap::from([("t1".to_string(), vec!["grants".to_string()])]),
+            &HashMap::new(),
+            &HashMap::new(),
+        );
+        row.fiat_value = Some(Decimal::new(150, 2));
+
+        let mut writer = WriterBuilder::new()
+            .delimiter(layout.delimiter.byte())
+            .from_writer(Vec::new());
+        write_header(&mut writer, &layout, "EUR").unwrap();
+        write_row(&mut writer, &layout, &row).unwrap();
+        let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            csv,
+            "Amount;Date;Value (EUR);Tags\n1,5;04.03.2025;1,5;grants\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pages_through_undated_then_dated_transactions() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        for ddl in [
+            "CREATE TABLE wallets (id TEXT PRIMARY KEY, profile_id TEXT NOT NULL)",
+            r#"
+            CREATE TABLE transactions (
+                id TEXT PRIMARY KEY,
+                wallet_id TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                block_number INTEGER,
+                timestamp DATETIME,
+                from_address TEXT,
+                to_address TEXT,
+                value TEXT,
+                fee TEXT,
+                status TEXT,
+                tx_type TEXT,
+                token_symbol TEXT,
+                token_decimals INTEGER,
+                chain TEXT NOT NULL,
+                raw_data TEXT,
+                created_at DATETIME NOT NULL
+            )
+            "#,
+            "CREATE TABLE transaction_merges (duplicate_id TEXT PRIMARY KEY)",
+            "INSERT INTO wallets VALUES ('w1', 'p1')",
+            "INSERT INTO transaction_merges VALUES ('t4')",
+        ] {
+            sqlx::query(ddl).execute(&pool).await.unwrap();
+        }
+
+        // Two undated transactions, two sharing a timestamp, a later one,
+        // and a merged duplicate
+        let first = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let later = Utc.with_ymd_and_hms(2025, 2, 1, 0, 0, 0).unwrap();
+        for (id, at) in [
+            ("t6", Some(later)),
+            ("t3", Some(first)),
+            ("t2", None),
+            ("t5", Some(first)),
+            ("t1", None),
+            ("t4", Some(first)),
+        ] {
+            sqlx::query(
+                "INSERT INTO transactions (id, wallet_id, hash, timestamp, chain, created_at) VALUES (?, 'w1', ?, ?, 'ethereum', ?)",
+            )
+            .bind(id)
+            .bind(id)
+            .bind(at)
+            .bind(first)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let mut cursor = PageCursor::default();
+        let mut ids = Vec::new();
+        loop {
+            let page = transaction_page(&pool, "p1", None, None, &cursor, 2)
+                .await
+                .unwrap();
+            let Some(last) = page.last() else {
+                break;
+            };
+            cursor = PageCursor::after(last);
+            ids.extend(page.iter().map(|tx| tx.id.clone()));
+        }
+        assert_eq!(ids, ["t1", "t2", "t3", "t5", "t6"]);
+        assert_eq!(
+            count_transactions(&pool, "p1", None, None).await.unwrap(),
+            5
+        );
+
+        // A bounded period leaves undated transactions out
+        let page = transaction_page(&pool, "p1", Some(later), None, &PageCursor::default(), 10)
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 1);
+    }
+}
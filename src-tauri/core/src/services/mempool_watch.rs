@@ -0,0 +1,20 @@
+//! Confirmation counting for watched Bitcoin transactions.
+
+/// Confirmations of a transaction mined at `block_height` with the chain at
+/// `tip`.
+pub fn confirmations(block_height: u64, tip: u64) -> u64 {
+    tip.saturating_sub(block_height) + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirmations() {
+        assert_eq!(confirmations(840_000, 840_000), 1);
+        assert_eq!(confirmations(840_000, 840_005), 6);
+        // A lagging tip never reports zero for a mined transaction
+        assert_eq!(confirmations(840_001, 840_000), 1);
+    }
+}
@@ -0,0 +1,48 @@
+/// Per-profile ledger balances and trial balance.
+pub mod accounting;
+/// Economic events each wallet transaction expands into, and their storage.
+pub mod accounting_events;
+/// Native currency symbol and decimals of each supported chain.
+pub mod address_watch;
+/// Append-only history of changes to wallets, transactions, tags, entities, and journal entries.
+pub mod audit_trail;
+/// Profile access checks and the authentication audit log.
+pub mod auth;
+/// Stored token transfers of wallet transactions.
+pub mod balance_history;
+/// Confirmation depths per chain and the checks that settle pending transactions.
+pub mod confirmations;
+/// Statement lines and the pricing shared by entity statements and exports.
+pub mod entity_statements;
+/// Transaction CSV exports, plain or gzip-compressed.
+pub mod export;
+/// Transaction exports laid out by a saved template.
+pub mod export_templates;
+/// Confirmation counts of Bitcoin transactions.
+pub mod mempool_watch;
+/// Roles and the permissions each one grants.
+pub mod permissions;
+/// Profiles, wallets, and stored transactions, and the database state.
+pub mod persistence;
+/// Module for fetching and managing price feeds from various data providers.
+pub mod price_feeds;
+/// Price feed coin IDs for chain-native assets and token contracts, with per-profile overrides.
+pub mod price_ids;
+/// Manual token prices that take precedence over price provider data.
+pub mod price_overrides;
+/// Price source appendix listing the provider, endpoint, and retrieval time behind report values.
+pub mod price_sources;
+/// Profile-scoped authorization and queries for profiles, wallets, and transactions.
+pub mod profile_scope;
+/// Parsing of stored amounts and statement periods.
+pub mod statement_export;
+/// Stored DEX swaps of wallet transactions.
+pub mod swaps;
+/// Transaction exports in the CSV formats tax tools import.
+pub mod tax_tool_export;
+/// Spam token filtering from user marks and the bundled blocklist.
+pub mod token_spam;
+/// Stored EIP-1559 fee breakdowns of wallet transactions.
+pub mod transaction_fees;
+/// Fetching and storing wallet transactions, with progress reporting.
+pub mod wallet_sync;
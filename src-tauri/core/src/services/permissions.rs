@@ -0,0 +1,172 @@
+//! Role-based permissions.
+//!
+//! Each profile role grants a fixed set of permissions, and commands name
+//! the permission they need rather than a list of roles.
+
+use serde::{Deserialize, Serialize};
+
+/// Something a profile member may be allowed to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// Read wallets, transactions, balances, and reports.
+    ViewTransactions,
+    /// Read the history of changes to a profile's records.
+    ViewAuditTrail,
+    /// Add, import, merge, or delete transactions and related records.
+    EditTransactions,
+    /// Tag transactions, events, and UTXOs.
+    EditTags,
+    /// Book accounting events and create journal entries.
+    CreateJournalEntries,
+    /// Approve and close accounting periods.
+    Approve,
+    /// Export reports and statements, and schedule emailed reports.
+    Export,
+    /// Add, remove, sync, and configure wallets.
+    ManageWallets,
+    /// Store or export node credentials, API keys, and other secrets.
+    ManageKeys,
+    /// Invite members, change their roles, and remove them.
+    ManageMembers,
+    /// Rename a profile, import configuration, and reopen closed periods.
+    ManageProfile,
+    /// Delete a profile.
+    DeleteProfile,
+    /// Combine profiles into a consolidated report.
+    ConsolidateProfiles,
+    /// Export the read-only bundle handed to auditors.
+    ExportAuditorBundle,
+}
+
+impl Permission {
+    /// Every permission, in the order shown in the UI.
+    pub const ALL: &'static [Permission] = &[
+        Permission::ViewTransactions,
+        Permission::ViewAuditTrail,
+        Permission::EditTransactions,
+        Permission::EditTags,
+        Permission::CreateJournalEntries,
+        Permission::Approve,
+        Permission::Export,
+        Permission::ManageWallets,
+        Permission::ManageKeys,
+        Permission::ManageMembers,
+        Permission::ManageProfile,
+        Permission::DeleteProfile,
+        Permission::ConsolidateProfiles,
+        Permission::ExportAuditorBundle,
+    ];
+
+    /// Describes the permission for error messages.
+    pub fn description(self) -> &'static str {
+        match self {
+            Permission::ViewTransactions => "view transactions",
+            Permission::ViewAuditTrail => "view the audit trail",
+            Permission::EditTransactions => "edit transactions",
+            Permission::EditTags => "edit tags",
+            Permission::CreateJournalEntries => "create journal entries",
+            Permission::Approve => "approve",
+            Permission::Export => "export",
+            Permission::ManageWallets => "manage wallets",
+            Permission::ManageKeys => "manage keys",
+            Permission::ManageMembers => "manage members",
+            Permission::ManageProfile => "manage the profile",
+            Permission::DeleteProfile => "delete the profile",
+            Permission::ConsolidateProfiles => "consolidate profiles",
+            Permission::ExportAuditorBundle => "export auditor bundles",
+        }
+    }
+}
+
+/// Read-only role for outside auditors. Every access check made for an
+/// auditor is recorded in the security audit log.
+pub const AUDITOR_ROLE: &str = "auditor";
+
+/// Permissions granted by each role.
+pub const ROLE_PERMISSIONS: &[(&str, &[Permission])] = &[
+    ("owner", Permission::ALL),
+    (
+        "admin",
+        &[
+            Permission::ViewTransactions,
+            Permission::ViewAuditTrail,
+            Permission::EditTransactions,
+            Permission::EditTags,
+            Permission::CreateJournalEntries,
+            Permission::Approve,
+            Permission::Export,
+            Permission::ManageWallets,
+            Permission::ManageKeys,
+            Permission::ManageMembers,
+            Permission::ManageProfile,
+            Permission::ExportAuditorBundle,
+        ],
+    ),
+    (
+        "approver",
+        &[
+            Permission::ViewTransactions,
+            Permission::ViewAuditTrail,
+            Permission::Approve,
+            Permission::Export,
+        ],
+    ),
+    (
+        "preparer",
+        &[
+            Permission::ViewTransactions,
+            Permission::EditTransactions,
+            Permission::EditTags,
+            Permission::CreateJournalEntries,
+            Permission::Export,
+            Permission::ManageWallets,
+        ],
+    ),
+    ("user", &[Permission::ViewTransactions]),
+    (
+        AUDITOR_ROLE,
+        &[
+            Permission::ViewTransactions,
+            Permission::ViewAuditTrail,
+            Permission::ExportAuditorBundle,
+        ],
+    ),
+];
+
+/// Permissions granted by `role`; none for an unknown role.
+pub fn role_permissions(role: &str) -> &'static [Permission] {
+    ROLE_PERMISSIONS
+        .iter()
+        .find(|(name, _)| *name == role)
+        .map(|(_, permissions)| *permissions)
+        .unwrap_or(&[])
+}
+
+/// Whether `role` grants `permission`.
+pub fn role_has(role: &str, permission: Permission) -> bool {
+    role_permissions(role).contains(&permission)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auditor_is_read_only() {
+        let granted = role_permissions(AUDITOR_ROLE);
+        assert!(granted.contains(&Permission::ViewAuditTrail));
+        assert!(granted.contains(&Permission::ExportAuditorBundle));
+        for permission in [
+            Permission::EditTransactions,
+            Permission::EditTags,
+            Permission::CreateJournalEntries,
+            Permission::Approve,
+            Permission::ManageWallets,
+            Permission::ManageKeys,
+            Permission::ManageMembers,
+        ] {
+            assert!(!granted.contains(&permission), "{:?}", permission);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! Profiles, wallets, and stored transactions.
+//!
+//! Holds the database state shared by the app and the CLI, and the write
+//! path that stores synced transactions with their decoded transfers, swaps,
+//! fees, and accounting events.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use super::accounting_events::expand_transaction;
+use super::audit_trail::{record_change, RecordType};
+use super::balance_history::save_token_transfers;
+use super::swaps::save_swaps;
+use super::transaction_fees::save_fee_breakdown;
+use crate::chains::{FeeBreakdown, SwapDetail, TokenTransfer};
+use crate::db::migrations;
+use crate::log_info;
+
+/// Represents a user profile with unique ID, name, optional avatar URL, and timestamps.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Profile {
+    /// The unique identifier of the profile.
+    pub id: String,
+    /// The display name of the user.
+    pub name: String,
+    /// The optional URL of the user's avatar image.
+    pub avatar_url: Option<String>,
+    /// The timestamp when the profile was created.
+    pub created_at: DateTime<Utc>,
+    /// The timestamp when the profile was last updated.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Represents a cryptocurrency wallet associated with a user profile.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Wallet {
+    /// The unique identifier of the wallet.
+    pub id: String,
+    /// The identifier of the associated user profile.
+    pub profile_id: String,
+    /// The blockchain address of the wallet.
+    pub address: String,
+    /// The blockchain network identifier (e.g., Ethereum, Bitcoin).
+    pub chain: String,
+    /// An optional display name for the wallet.
+    pub name: Option<String>,
+    /// The type of the wallet (e.g., hardware, software).
+    pub wallet_type: String,
+    /// The timestamp when the wallet was created.
+    pub created_at: DateTime<Utc>,
+    /// The optional timestamp when the wallet was last updated.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Represents a stored transaction record with metadata and blockchain details.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct StoredTransaction {
+    /// The unique identifier of the stored transaction.
+    pub id: String,
+    /// The identifier of the wallet associated with the transaction.
+    pub wallet_id: String,
+    /// The blockchain transaction hash.
+    pub hash: String,
+    /// The optional block number where the transaction was recorded.
+    pub block_number: Option<i64>,
+    /// The optional timestamp of when the transaction occurred.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// The optional sender address of the transaction.
+    pub from_address: Option<String>,
+    /// The optional recipient address of the transaction.
+    pub to_address: Option<String>,
+    /// The optional value transferred in the transaction.
+    pub value: Option<String>,
+    /// The optional transaction fee paid.
+    pub fee: Option<String>,
+    /// The optional status of the transaction (e.g., pending, confirmed).
+    pub status: Option<String>,
+    /// The optional type of the transaction.
+    pub tx_type: Option<String>,
+    /// The optional symbol of the token involved.
+    pub token_symbol: Option<String>,
+    /// The optional decimal precision of the token.
+    pub token_decimals: Option<i32>,
+    /// The blockchain network identifier for the transaction.
+    pub chain: String,
+    /// The optional raw data of the transaction.
+    pub raw_data: Option<String>,
+    /// The timestamp when the transaction was stored.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Input data for saving or updating transactions in the system.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionInput {
+    /// The blockchain transaction hash.
+    pub hash: String,
+    /// The optional block number where the transaction was recorded.
+    pub block_number: Option<i64>,
+    /// The optional timestamp of when the transaction occurred, as an ISO 8601 string.
+    pub timestamp: Option<String>,
+    /// The optional sender address of the transaction.
+    pub from_address: Option<String>,
+    /// The optional recipient address of the transaction.
+    pub to_address: Option<String>,
+    /// The optional value transferred in the transaction.
+    pub value: Option<String>,
+    /// The optional transaction fee paid.
+    pub fee: Option<String>,
+    /// The optional status of the transaction (e.g., pending, confirmed).
+    pub status: Option<String>,
+    /// The optional type of the transaction.
+    pub tx_type: Option<String>,
+    /// The optional symbol of the token involved.
+    pub token_symbol: Option<String>,
+    /// The optional decimal precision of the token.
+    pub token_decimals: Option<i32>,
+    /// The blockchain network identifier for the transaction.
+    pub chain: String,
+    /// The optional raw data of the transaction.
+    pub raw_data: Option<String>,
+    /// Swaps decoded from the transaction's swap events, if any.
+    #[serde(default)]
+    pub swaps: Vec<SwapDetail>,
+    /// Token transfers within the transaction, if any.
+    #[serde(default)]
+    pub token_transfers: Vec<TokenTransfer>,
+    /// Base fee and tip split of the fee, for EVM transactions.
+    #[serde(default)]
+    pub fee_breakdown: Option<FeeBreakdown>,
+}
+
+/// Maintains the SQLite connection pool for database operations.
+pub struct DatabaseState {
+    /// The SQLite database connection pool for executing queries.
+    pub pool: SqlitePool,
+}
+
+impl DatabaseState {
+    /// Creates a new DatabaseState by connecting to the specified SQLite database path and running migrations.
+    ///
+    /// An existing database is backed up before pending migrations are applied.
+    pub async fn new(database_path: &str) -> anyhow::Result<Self> {
+        let pool = SqlitePool::connect(database_path).await?;
+
+        // Run migrations
+        let outcome = migrations::run_migrations(&pool, database_path).await?;
+        if !outcome.applied.is_empty() {
+            log_info!("Applied {} database migration(s)", outcome.applied.len());
+        }
+        if let Some(backup) = outcome.backup_path {
+            log_info!("Pre-migration backup saved to {}", backup.display());
+        }
+
+        Ok(Self { pool })
+    }
+}
+
+/// Upserts transactions for `wallet` by hash, recording each change in the
+/// audit trail. Returns the number saved.
+pub async fn store_transactions(
+    pool: &SqlitePool,
+    user_id: &str,
+    wallet: &Wallet,
+    transactions: Vec<TransactionInput>,
+) -> Result<usize, String> {
+    let wallet_id = &wallet.id;
+    let now = Utc::now();
+    let mut saved_count = 0;
+
+    for tx in transactions {
+        let id = Uuid::new_v4().to_string();
+        let timestamp = tx
+            .timestamp
+            .as_ref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc));
+
+        let existing = sqlx::query_as::<_, StoredTransaction>(
+            "SELECT * FROM transactions WHERE wallet_id = ? AND hash = ?",
+        )
+        .bind(wallet_id)
+        .bind(&tx.hash)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO transactions (
+                id, wallet_id, hash, block_number, timestamp, from_address, to_address,
+                value, fee, status, tx_type, token_symbol, token_decimals, chain, raw_data, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(wallet_id, hash) DO UPDATE SET
+                block_number = excluded.block_number,
+                timestamp = excluded.timestamp,
+                status = excluded.status,
+                raw_data = excluded.raw_data
+            "#,
+        )
+        .bind(&id)
+        .bind(wallet_id)
+        .bind(&tx.hash)
+        .bind(tx.block_number)
+        .bind(timestamp)
+        .bind(&tx.from_address)
+        .bind(&tx.to_address)
+        .bind(&tx.value)
+        .bind(&tx.fee)
+        .bind(&tx.status)
+        .bind(&tx.tx_type)
+        .bind(&tx.token_symbol)
+        .bind(tx.token_decimals)
+        .bind(&tx.chain)
+        .bind(&tx.raw_data)
+        .bind(now)
+        .execute(pool)
+        .await;
+
+        if result.is_ok() {
+            saved_count += 1;
+
+            // Keep earlier swaps and transfers if this sync has none
+            if !tx.swaps.is_empty() {
+                save_swaps(pool, wallet_id, &tx.hash, &tx.swaps)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            if !tx.token_transfers.is_empty() {
+                save_token_transfers(pool, wallet_id, &tx.hash, &tx.token_transfers)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            if let Some(breakdown) = &tx.fee_breakdown {
+                save_fee_breakdown(pool, wallet_id, &tx.hash, breakdown)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+
+            let saved = sqlx::query_as::<_, StoredTransaction>(
+                "SELECT * FROM transactions WHERE wallet_id = ? AND hash = ?",
+            )
+            .bind(wallet_id)
+            .bind(&tx.hash)
+            .fetch_one(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+            // Events already booked to the ledger stay as they are
+            expand_transaction(pool, wallet, &saved)
+                .await
+                .map_err(|e| e.to_string())?;
+            record_change(
+                pool,
+                Some(user_id),
+                RecordType::Transaction,
+                &saved.id,
+                Some(&wallet.profile_id),
+                existing.as_ref(),
+                Some(&saved),
+            )
+            .await?;
+        }
+    }
+
+    Ok(saved_count)
+}
@@ -0,0 +1,230 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::provider::{format_price, parse_price, PriceProvider};
+use crate::fetchers::{ApiProvider, FetchError, FetchResult, FetcherConfig, ResilientFetcher};
+
+/// Public API base URL.
+const PUBLIC_BASE_URL: &str = "https://api.coingecko.com/api/v3";
+
+/// Pro API base URL, used when a key is configured.
+const PRO_BASE_URL: &str = "https://pro-api.coingecko.com/api/v3";
+
+/// CoinGecko API client for cryptocurrency price feeds
+pub struct CoinGeckoClient {
+    fetcher: ResilientFetcher,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CoinGeckoPriceResponse {
+    #[serde(flatten)]
+    prices: HashMap<String, HashMap<String, Value>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CoinGeckoHistoricalResponse {
+    market_data: Option<MarketData>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MarketData {
+    current_price: HashMap<String, Value>,
+}
+
+/// Picks the prices of `coin_ids` in `vs_currency` out of a
+/// `/simple/price` response.
+fn parse_simple_prices(
+    body: &str,
+    coin_ids: &[&str],
+    vs_currency: &str,
+) -> FetchResult<HashMap<String, String>> {
+    let data: CoinGeckoPriceResponse =
+        serde_json::from_str(body).map_err(|e| FetchError::ParseError(e.to_string()))?;
+    let currency = vs_currency.to_lowercase();
+
+    Ok(coin_ids
+        .iter()
+        .filter_map(|coin_id| {
+            let price = parse_price(data.prices.get(*coin_id)?.get(&currency)?)?;
+            Some((coin_id.to_string(), format_price(price)))
+        })
+        .collect())
+}
+
+/// Reads the price in `vs_currency` from a `/coins/{id}/history` response.
+fn parse_historical_price(body: &str, vs_currency: &str) -> FetchResult<String> {
+    let data: CoinGeckoHistoricalResponse =
+        serde_json::from_str(body).map_err(|e| FetchError::ParseError(e.to_string()))?;
+    data.market_data
+        .and_then(|market| {
+            market
+                .current_price
+                .get(&vs_currency.to_lowercase())
+                .and_then(parse_price)
+        })
+        .map(format_price)
+        .ok_or_else(|| FetchError::ApiError(format!("No {} price for that date", vs_currency)))
+}
+
+impl CoinGeckoClient {
+    /// Create a new CoinGecko client. A key selects the Pro API and its
+    /// higher rate limit.
+    pub fn new(api_key: Option<String>) -> FetchResult<Self> {
+        let provider = ApiProvider::CoinGecko;
+        let (base_url, requests_per_second) = match api_key {
+            Some(_) => (PRO_BASE_URL, provider.turbo_rate_limit()),
+            None => (PUBLIC_BASE_URL, provider.default_rate_limit()),
+        };
+
+        let fetcher = ResilientFetcher::new(FetcherConfig {
+            base_url: base_url.to_string(),
+            api_key,
+            requests_per_second,
+            timeout_secs: 30,
+            max_retries: 3,
+            provider: Some(ApiProvider::CoinGecko),
+        })?;
+        Ok(Self { fetcher })
+    }
+
+    /// Builds a URL, appending the Pro key when there is one.
+    fn url(&self, path: &str, query: &str) -> String {
+        let mut url = format!("{}?{}", self.fetcher.build_url(path), query);
+        if let Some(key) = self.fetcher.api_key() {
+            url.push_str("&x_cg_pro_api_key=");
+            url.push_str(key);
+        }
+        url
+    }
+
+    /// Get prices for multiple cryptocurrencies at once
+    ///
+    /// # Arguments
+    /// * `coin_ids` - Vec of CoinGecko coin IDs
+    /// * `vs_currency` - Target currency
+    pub async fn get_multiple_prices(
+        &self,
+        coin_ids: &[&str],
+        vs_currency: &str,
+    ) -> FetchResult<HashMap<String, String>> {
+        let url = self.url(
+            "/simple/price",
+            &format!(
+                "ids={}&vs_currencies={}",
+                coin_ids.join(","),
+                vs_currency.to_lowercase()
+            ),
+        );
+        let body = self.fetcher.get(&url).await?;
+        parse_simple_prices(&body, coin_ids, vs_currency)
+    }
+
+    /// Get historical price for a specific date
+    ///
+    /// # Arguments
+    /// * `coin_id` - CoinGecko coin ID
+    /// * `date` - Date in format "DD-MM-YYYY"
+    /// * `vs_currency` - Target currency
+    pub async fn get_historical_price(
+        &self,
+        coin_id: &str,
+        date: &str,
+        vs_currency: &str,
+    ) -> FetchResult<String> {
+        let url = self.url(
+            &format!("/coins/{}/history", coin_id),
+            &format!("date={}&localization=false", date),
+        );
+        let body = self.fetcher.get(&url).await?;
+        parse_historical_price(&body, vs_currency)
+    }
+
+    /// Get supported vs currencies
+    #[allow(dead_code)]
+    pub async fn get_supported_currencies(&self) -> FetchResult<Vec<String>> {
+        let url = self.url("/simple/supported_vs_currencies", "");
+        self.fetcher.get_json(&url).await
+    }
+}
+
+#[async_trait]
+impl PriceProvider for CoinGeckoClient {
+    fn name(&self) -> &'static str {
+        "coingecko"
+    }
+
+    fn current_endpoint(&self) -> String {
+        self.fetcher.build_url("/simple/price")
+    }
+
+    fn historical_endpoint(&self) -> String {
+        self.fetcher.build_url("/coins/{id}/history")
+    }
+
+    async fn current_prices(
+        &self,
+        coin_ids: &[&str],
+        vs_currency: &str,
+    ) -> FetchResult<HashMap<String, String>> {
+        self.get_multiple_prices(coin_ids, vs_currency).await
+    }
+
+    async fn historical_price(
+        &self,
+        coin_id: &str,
+        date: NaiveDate,
+        vs_currency: &str,
+    ) -> FetchResult<String> {
+        self.get_historical_price(coin_id, &date.format("%d-%m-%Y").to_string(), vs_currency)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simple_prices() {
+        let body = r#"{"polkadot":{"usd":4.25},"kusama":{"eur":20.0}}"#;
+        let prices = parse_simple_prices(body, &["polkadot", "kusama", "moonbeam"], "USD").unwrap();
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices["polkadot"], "4.25");
+    }
+
+    #[test]
+    fn test_parse_historical_price() {
+        let body = r#"{"id":"polkadot","market_data":{"current_price":{"usd":5.5}}}"#;
+        assert!(parse_historical_price(body, "usd")
+            .unwrap()
+            .starts_with("5.5"));
+        // Dates before a coin was listed come back without market data.
+        assert!(parse_historical_price(r#"{"id":"polkadot"}"#, "usd").is_err());
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires internet connection
+    async fn test_get_price() {
+        let client = CoinGeckoClient::new(None).unwrap();
+        let prices = client.current_prices(&["polkadot"], "usd").await;
+        assert!(prices.is_ok());
+        println!("DOT price: ${}", prices.unwrap()["polkadot"]);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires internet connection
+    async fn test_get_multiple_prices() {
+        let client = CoinGeckoClient::new(None).unwrap();
+        let prices = client
+            .get_multiple_prices(&["polkadot", "kusama"], "usd")
+            .await;
+        assert!(prices.is_ok());
+        let prices = prices.unwrap();
+        println!("Prices: {:?}", prices);
+        assert!(prices.contains_key("polkadot"));
+        assert!(prices.contains_key("kusama"));
+    }
+}
@@ -0,0 +1,205 @@
+use async_trait::async_trait;
+use chrono::{NaiveDate, NaiveTime};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::provider::{format_price, parse_price, PriceProvider};
+use crate::fetchers::{ApiProvider, FetchError, FetchResult, FetcherConfig, ResilientFetcher};
+
+/// API base URL.
+const BASE_URL: &str = "https://min-api.cryptocompare.com/data";
+
+/// CryptoCompare API client, used as a price fallback.
+///
+/// CryptoCompare keys prices by ticker symbol, so only coins with a known
+/// symbol can be priced.
+pub struct CryptoCompareClient {
+    fetcher: ResilientFetcher,
+}
+
+/// Ticker symbol CryptoCompare uses for a CoinGecko coin ID.
+pub fn symbol_for(coin_id: &str) -> Option<&'static str> {
+    Some(match coin_id {
+        "bitcoin" => "BTC",
+        "ethereum" => "ETH",
+        "polkadot" => "DOT",
+        "kusama" => "KSM",
+        "moonbeam" => "GLMR",
+        "moonriver" => "MOVR",
+        "astar" => "ASTR",
+        "acala" => "ACA",
+        "solana" => "SOL",
+        "matic-network" => "MATIC",
+        "polygon-ecosystem-token" => "POL",
+        "binancecoin" => "BNB",
+        "avalanche-2" => "AVAX",
+        "arbitrum" => "ARB",
+        "optimism" => "OP",
+        "tether" => "USDT",
+        "usd-coin" => "USDC",
+        "dai" => "DAI",
+        "weth" => "WETH",
+        "wrapped-bitcoin" => "WBTC",
+        "chainlink" => "LINK",
+        "uniswap" => "UNI",
+        _ => return None,
+    })
+}
+
+/// Fails on CryptoCompare's in-band errors, which arrive with status 200.
+fn check_error(data: &Value) -> FetchResult<()> {
+    if data.get("Response").and_then(Value::as_str) == Some("Error") {
+        let message = data
+            .get("Message")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        if message.to_lowercase().contains("rate limit") {
+            return Err(FetchError::RateLimited);
+        }
+        return Err(FetchError::ApiError(message.to_string()));
+    }
+    Ok(())
+}
+
+/// Picks prices out of a `pricemulti` or `pricehistorical` response, keyed
+/// by the coin IDs they were requested for.
+fn parse_prices(
+    body: &str,
+    coins: &[(&str, &'static str)],
+    vs_currency: &str,
+) -> FetchResult<HashMap<String, String>> {
+    let data: Value =
+        serde_json::from_str(body).map_err(|e| FetchError::ParseError(e.to_string()))?;
+    check_error(&data)?;
+    let currency = vs_currency.to_uppercase();
+
+    Ok(coins
+        .iter()
+        .filter_map(|(coin_id, symbol)| {
+            let price = parse_price(data.get(*symbol)?.get(&currency)?)?;
+            (price > Decimal::ZERO).then(|| (coin_id.to_string(), format_price(price)))
+        })
+        .collect())
+}
+
+impl CryptoCompareClient {
+    /// Create a new CryptoCompare client.
+    pub fn new(api_key: Option<String>) -> FetchResult<Self> {
+        let provider = ApiProvider::CryptoCompare;
+        let requests_per_second = match api_key {
+            Some(_) => provider.turbo_rate_limit(),
+            None => provider.default_rate_limit(),
+        };
+
+        let fetcher = ResilientFetcher::new(FetcherConfig {
+            base_url: BASE_URL.to_string(),
+            api_key,
+            requests_per_second,
+            timeout_secs: 30,
+            max_retries: 3,
+            provider: Some(ApiProvider::CryptoCompare),
+        })?;
+        Ok(Self { fetcher })
+    }
+
+    /// Builds a URL, appending the key when there is one.
+    fn url(&self, path: &str, query: &str) -> String {
+        let mut url = format!("{}?{}", self.fetcher.build_url(path), query);
+        if let Some(key) = self.fetcher.api_key() {
+            url.push_str("&api_key=");
+            url.push_str(key);
+        }
+        url
+    }
+}
+
+#[async_trait]
+impl PriceProvider for CryptoCompareClient {
+    fn name(&self) -> &'static str {
+        "cryptocompare"
+    }
+
+    fn current_endpoint(&self) -> String {
+        self.fetcher.build_url("/pricemulti")
+    }
+
+    fn historical_endpoint(&self) -> String {
+        self.fetcher.build_url("/pricehistorical")
+    }
+
+    async fn current_prices(
+        &self,
+        coin_ids: &[&str],
+        vs_currency: &str,
+    ) -> FetchResult<HashMap<String, String>> {
+        let coins: Vec<(&str, &'static str)> = coin_ids
+            .iter()
+            .filter_map(|id| symbol_for(id).map(|symbol| (*id, symbol)))
+            .collect();
+        if coins.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let symbols: Vec<&str> = coins.iter().map(|(_, symbol)| *symbol).collect();
+        let url = self.url(
+            "/pricemulti",
+            &format!(
+                "fsyms={}&tsyms={}",
+                symbols.join(","),
+                vs_currency.to_uppercase()
+            ),
+        );
+        let body = self.fetcher.get(&url).await?;
+        parse_prices(&body, &coins, vs_currency)
+    }
+
+    async fn historical_price(
+        &self,
+        coin_id: &str,
+        date: NaiveDate,
+        vs_currency: &str,
+    ) -> FetchResult<String> {
+        let symbol = symbol_for(coin_id)
+            .ok_or_else(|| FetchError::ApiError(format!("No symbol known for {}", coin_id)))?;
+        let timestamp = date.and_time(NaiveTime::MIN).and_utc().timestamp();
+        let url = self.url(
+            "/pricehistorical",
+            &format!(
+                "fsym={}&tsyms={}&ts={}",
+                symbol,
+                vs_currency.to_uppercase(),
+                timestamp
+            ),
+        );
+        let body = self.fetcher.get(&url).await?;
+        parse_prices(&body, &[(coin_id, symbol)], vs_currency)?
+            .remove(coin_id)
+            .ok_or_else(|| FetchError::ApiError(format!("No {} price for that date", vs_currency)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_prices() {
+        let body = r#"{"DOT":{"USD":4.25},"KSM":{"USD":0}}"#;
+        let prices = parse_prices(body, &[("polkadot", "DOT"), ("kusama", "KSM")], "usd").unwrap();
+        assert!(prices["polkadot"].starts_with("4.25"));
+        // CryptoCompare reports unknown history as zero.
+        assert!(!prices.contains_key("kusama"));
+    }
+
+    #[test]
+    fn test_in_band_errors() {
+        let limited = r#"{"Response":"Error","Message":"You are over your rate limit please upgrade your account!"}"#;
+        assert!(matches!(
+            parse_prices(limited, &[("polkadot", "DOT")], "usd"),
+            Err(FetchError::RateLimited)
+        ));
+        assert_eq!(symbol_for("polkadot"), Some("DOT"));
+        assert_eq!(symbol_for("some-lp-token"), None);
+    }
+}
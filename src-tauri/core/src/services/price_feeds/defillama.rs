@@ -0,0 +1,159 @@
+use async_trait::async_trait;
+use chrono::{NaiveDate, NaiveTime};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::provider::{format_price, parse_price, PriceProvider};
+use crate::fetchers::{ApiProvider, FetchError, FetchResult, FetcherConfig, ResilientFetcher};
+
+/// Public coins API base URL.
+const PUBLIC_BASE_URL: &str = "https://coins.llama.fi";
+
+/// Pro API base URL; the key is part of the path.
+const PRO_BASE_URL: &str = "https://pro-api.llama.fi";
+
+/// DefiLlama coins API client, used as a price fallback.
+///
+/// DefiLlama accepts CoinGecko IDs directly but only quotes in USD.
+pub struct DefiLlamaClient {
+    fetcher: ResilientFetcher,
+    /// Base URL recorded as the source of prices; the Pro base URL carries
+    /// the key, which is left out.
+    endpoint_base: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlamaPricesResponse {
+    coins: HashMap<String, LlamaPrice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LlamaPrice {
+    price: Value,
+}
+
+/// DefiLlama's key for a CoinGecko coin ID.
+fn llama_key(coin_id: &str) -> String {
+    format!("coingecko:{}", coin_id)
+}
+
+/// Picks prices out of a `/prices/...` response, keyed by coin ID.
+fn parse_prices(body: &str, coin_ids: &[&str]) -> FetchResult<HashMap<String, String>> {
+    let data: LlamaPricesResponse =
+        serde_json::from_str(body).map_err(|e| FetchError::ParseError(e.to_string()))?;
+    Ok(coin_ids
+        .iter()
+        .filter_map(|coin_id| {
+            let price = parse_price(&data.coins.get(&llama_key(coin_id))?.price)?;
+            Some((coin_id.to_string(), format_price(price)))
+        })
+        .collect())
+}
+
+/// Fails for any quote currency but USD.
+fn require_usd(vs_currency: &str) -> FetchResult<()> {
+    if vs_currency.eq_ignore_ascii_case("usd") {
+        Ok(())
+    } else {
+        Err(FetchError::ApiError(format!(
+            "DefiLlama doesn't quote in {}",
+            vs_currency
+        )))
+    }
+}
+
+impl DefiLlamaClient {
+    /// Create a new DefiLlama client. A key selects the Pro API.
+    pub fn new(api_key: Option<String>) -> FetchResult<Self> {
+        let provider = ApiProvider::DefiLlama;
+        let (base_url, endpoint_base, requests_per_second) = match &api_key {
+            Some(key) => (
+                format!("{}/{}/coins", PRO_BASE_URL, key),
+                format!("{}/coins", PRO_BASE_URL),
+                provider.turbo_rate_limit(),
+            ),
+            None => (
+                PUBLIC_BASE_URL.to_string(),
+                PUBLIC_BASE_URL.to_string(),
+                provider.default_rate_limit(),
+            ),
+        };
+
+        let fetcher = ResilientFetcher::new(FetcherConfig {
+            base_url,
+            api_key,
+            requests_per_second,
+            timeout_secs: 30,
+            max_retries: 3,
+            provider: Some(ApiProvider::DefiLlama),
+        })?;
+        Ok(Self {
+            fetcher,
+            endpoint_base,
+        })
+    }
+}
+
+#[async_trait]
+impl PriceProvider for DefiLlamaClient {
+    fn name(&self) -> &'static str {
+        "defillama"
+    }
+
+    fn current_endpoint(&self) -> String {
+        format!("{}/prices/current", self.endpoint_base)
+    }
+
+    fn historical_endpoint(&self) -> String {
+        format!("{}/prices/historical", self.endpoint_base)
+    }
+
+    async fn current_prices(
+        &self,
+        coin_ids: &[&str],
+        vs_currency: &str,
+    ) -> FetchResult<HashMap<String, String>> {
+        require_usd(vs_currency)?;
+        let keys: Vec<String> = coin_ids.iter().map(|id| llama_key(id)).collect();
+        let url = self
+            .fetcher
+            .build_url(&format!("/prices/current/{}", keys.join(",")));
+        let body = self.fetcher.get(&url).await?;
+        parse_prices(&body, coin_ids)
+    }
+
+    async fn historical_price(
+        &self,
+        coin_id: &str,
+        date: NaiveDate,
+        vs_currency: &str,
+    ) -> FetchResult<String> {
+        require_usd(vs_currency)?;
+        let timestamp = date.and_time(NaiveTime::MIN).and_utc().timestamp();
+        let url = self.fetcher.build_url(&format!(
+            "/prices/historical/{}/{}",
+            timestamp,
+            llama_key(coin_id)
+        ));
+        let body = self.fetcher.get(&url).await?;
+        parse_prices(&body, &[coin_id])?
+            .remove(coin_id)
+            .ok_or_else(|| FetchError::ApiError("No price for that date".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_prices() {
+        let body = r#"{"coins":{"coingecko:polkadot":{"price":4.25,"symbol":"DOT","timestamp":1760000000,"confidence":0.99}}}"#;
+        let prices = parse_prices(body, &["polkadot", "kusama"]).unwrap();
+        assert!(prices["polkadot"].starts_with("4.25"));
+        assert!(!prices.contains_key("kusama"));
+        assert!(require_usd("USD").is_ok());
+        assert!(require_usd("eur").is_err());
+    }
+}
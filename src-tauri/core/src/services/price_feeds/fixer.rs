@@ -1,7 +1,11 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 
+use super::provider::{format_price, parse_price};
+use crate::core::currency::round_fiat;
+
 /// Fixer.io API client for fiat currency exchange rates
 pub struct FixerClient {
     api_key: String,
@@ -14,7 +18,7 @@ struct FixerResponse {
     timestamp: Option<i64>,
     base: String,
     date: Option<String>,
-    rates: HashMap<String, f64>,
+    rates: HashMap<String, Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,7 +28,7 @@ struct FixerHistoricalResponse {
     date: String,
     timestamp: i64,
     base: String,
-    rates: HashMap<String, f64>,
+    rates: HashMap<String, Value>,
 }
 
 impl FixerClient {
@@ -84,13 +88,14 @@ impl FixerClient {
         let rate = data
             .rates
             .get(to_currency.to_uppercase().as_str())
+            .and_then(parse_price)
             .context(format!(
                 "Exchange rate not found for {} to {}",
                 from_currency, to_currency
             ))?;
 
         // Return as string to preserve precision
-        Ok(format!("{:.18}", rate))
+        Ok(format_price(rate))
     }
 
     /// Get multiple exchange rates at once
@@ -141,7 +146,9 @@ impl FixerClient {
 
         let mut result = HashMap::new();
         for (currency, rate) in data.rates {
-            result.insert(currency, format!("{:.18}", rate));
+            if let Some(rate) = parse_price(&rate) {
+                result.insert(currency, format_price(rate));
+            }
         }
 
         Ok(result)
@@ -198,12 +205,13 @@ impl FixerClient {
         let rate = data
             .rates
             .get(to_currency.to_uppercase().as_str())
+            .and_then(parse_price)
             .context(format!(
                 "Historical rate not found for {} to {} on {}",
                 from_currency, to_currency, date
             ))?;
 
-        Ok(format!("{:.18}", rate))
+        Ok(format_price(rate))
     }
 
     /// Get all available currencies
@@ -235,7 +243,8 @@ impl FixerClient {
         Ok(data.symbols.keys().cloned().collect())
     }
 
-    /// Convert amount from one currency to another
+    /// Convert amount from one currency to another, rounded to the target
+    /// currency's minor unit
     ///
     /// # Arguments
     /// * `from_currency` - Base currency
@@ -256,7 +265,7 @@ impl FixerClient {
         let amount_decimal = Decimal::from_str(amount).context("Failed to parse amount")?;
         let rate_decimal = Decimal::from_str(&rate).context("Failed to parse rate")?;
 
-        let result = amount_decimal * rate_decimal;
+        let result = round_fiat(amount_decimal * rate_decimal, to_currency);
         Ok(result.to_string())
     }
 }
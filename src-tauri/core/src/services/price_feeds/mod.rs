@@ -0,0 +1,13 @@
+/// CoinGecko API client for cryptocurrency price data.
+pub mod coingecko;
+/// CryptoCompare API client, a fallback price source.
+pub mod cryptocompare;
+/// DefiLlama coins API client, a fallback price source.
+pub mod defillama;
+/// Fixer.io API client for fiat currency exchange rates.
+#[allow(dead_code)]
+pub mod fixer;
+/// Price provider abstraction and failover between providers.
+pub mod provider;
+
+pub use provider::{PriceQuote, PriceService};
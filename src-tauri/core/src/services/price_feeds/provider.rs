@@ -0,0 +1,393 @@
+//! Price provider abstraction with automatic failover.
+//!
+//! Coins are identified by their CoinGecko IDs throughout; providers that
+//! key prices differently translate them. [`PriceService`] asks providers in
+//! order until one answers: those with an API key configured in Settings
+//! first, then the public tiers. Every provider goes through a
+//! [`ResilientFetcher`](crate::fetchers::ResilientFetcher), so its rate limit
+//! applies across all callers.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::coingecko::CoinGeckoClient;
+use super::cryptocompare::CryptoCompareClient;
+use super::defillama::DefiLlamaClient;
+use crate::fetchers::{ApiKeyManager, ApiProvider, FetchResult};
+
+/// Environment variable still honored for the CoinGecko key when none is
+/// stored in the keychain.
+const ENV_COINGECKO_API_KEY: &str = "COINGECKO_API_KEY";
+
+/// Price providers in order of preference.
+const PRICE_PROVIDERS: [ApiProvider; 3] = [
+    ApiProvider::CoinGecko,
+    ApiProvider::CryptoCompare,
+    ApiProvider::DefiLlama,
+];
+
+/// A source of cryptocurrency prices.
+#[async_trait]
+pub trait PriceProvider: Send + Sync {
+    /// Short name recorded as the source of a price.
+    fn name(&self) -> &'static str;
+
+    /// Endpoint current prices are read from, without any API key.
+    fn current_endpoint(&self) -> String;
+
+    /// Endpoint historical prices are read from, without any API key.
+    fn historical_endpoint(&self) -> String;
+
+    /// Current prices of several coins. Coins the provider doesn't know are
+    /// left out of the result.
+    async fn current_prices(
+        &self,
+        coin_ids: &[&str],
+        vs_currency: &str,
+    ) -> FetchResult<HashMap<String, String>>;
+
+    /// Price of a coin at the start of `date` (UTC).
+    async fn historical_price(
+        &self,
+        coin_id: &str,
+        date: NaiveDate,
+        vs_currency: &str,
+    ) -> FetchResult<String>;
+}
+
+/// A price and where it came from, kept so valuations can be audited.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceQuote {
+    /// Price as a decimal string.
+    pub price: String,
+    /// Name of the provider that answered.
+    pub provider: String,
+    /// Endpoint the price was read from.
+    pub endpoint: String,
+    /// When the price was retrieved.
+    pub retrieved_at: DateTime<Utc>,
+}
+
+/// Reads a price from a provider's JSON, which may be a number or a string.
+///
+/// Numbers are taken from their shortest decimal form, so `0.1` stays
+/// `0.1` instead of picking up binary floating-point noise. Prices too
+/// small for `Decimal` give `None`.
+pub fn parse_price(value: &Value) -> Option<Decimal> {
+    let text = match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.trim().to_string(),
+        _ => return None,
+    };
+    Decimal::from_str(&text)
+        .or_else(|_| Decimal::from_scientific(&text))
+        .ok()
+}
+
+/// Formats a price as a decimal string without trailing zeros.
+pub fn format_price(price: Decimal) -> String {
+    price.normalize().to_string()
+}
+
+/// Order to try providers in: those with a key first, then the rest, each
+/// group in the order of [`PRICE_PROVIDERS`].
+pub fn provider_order(has_key: impl Fn(ApiProvider) -> bool) -> Vec<ApiProvider> {
+    let (keyed, public): (Vec<ApiProvider>, Vec<ApiProvider>) =
+        PRICE_PROVIDERS.into_iter().partition(|p| has_key(*p));
+    keyed.into_iter().chain(public).collect()
+}
+
+/// Looks up the stored key for a price provider.
+fn configured_key(provider: ApiProvider) -> Option<String> {
+    let stored = ApiKeyManager::get_api_key(provider).ok().flatten();
+    match provider {
+        ApiProvider::CoinGecko => stored.or_else(|| std::env::var(ENV_COINGECKO_API_KEY).ok()),
+        _ => stored,
+    }
+}
+
+/// Price providers tried in order until one answers.
+pub struct PriceService {
+    providers: Vec<Box<dyn PriceProvider>>,
+}
+
+/// The shared service and the keys it was built with.
+type SharedService = (Vec<Option<String>>, Arc<PriceService>);
+
+static SHARED: Mutex<Option<SharedService>> = Mutex::new(None);
+
+impl PriceService {
+    /// Creates a service over the given providers, tried in order.
+    pub fn new(providers: Vec<Box<dyn PriceProvider>>) -> Self {
+        Self { providers }
+    }
+
+    /// Returns the shared service, rebuilding it when API keys have been
+    /// added or removed so rate limits carry over between calls.
+    pub fn shared() -> Result<Arc<Self>, String> {
+        let keys: Vec<Option<String>> = PRICE_PROVIDERS.into_iter().map(configured_key).collect();
+        let mut shared = SHARED.lock().map_err(|e| e.to_string())?;
+        if let Some((built_with, service)) = shared.as_ref() {
+            if *built_with == keys {
+                return Ok(service.clone());
+            }
+        }
+
+        let key_for = |provider: ApiProvider| {
+            PRICE_PROVIDERS
+                .iter()
+                .position(|p| *p == provider)
+                .and_then(|i| keys[i].clone())
+        };
+        let mut providers: Vec<Box<dyn PriceProvider>> = Vec::new();
+        for provider in provider_order(|p| key_for(p).is_some()) {
+            let key = key_for(provider);
+            let client: Box<dyn PriceProvider> = match provider {
+                ApiProvider::CoinGecko => {
+                    Box::new(CoinGeckoClient::new(key).map_err(|e| e.to_string())?)
+                }
+                ApiProvider::CryptoCompare => {
+                    Box::new(CryptoCompareClient::new(key).map_err(|e| e.to_string())?)
+                }
+                _ => Box::new(DefiLlamaClient::new(key).map_err(|e| e.to_string())?),
+            };
+            providers.push(client);
+        }
+
+        let service = Arc::new(Self::new(providers));
+        *shared = Some((keys, service.clone()));
+        Ok(service)
+    }
+
+    /// Current price of one coin.
+    pub async fn current_price(
+        &self,
+        coin_id: &str,
+        vs_currency: &str,
+    ) -> Result<PriceQuote, String> {
+        let mut quotes = self.current_prices(&[coin_id], vs_currency).await?;
+        quotes
+            .remove(coin_id)
+            .ok_or_else(|| format!("Price not found for {} in {}", coin_id, vs_currency))
+    }
+
+    /// Current prices of several coins. Coins a provider doesn't answer for
+    /// are asked of the next one; coins no provider knows are left out.
+    pub async fn current_prices(
+        &self,
+        coin_ids: &[&str],
+        vs_currency: &str,
+    ) -> Result<HashMap<String, PriceQuote>, String> {
+        let mut quotes = HashMap::new();
+        let mut errors = Vec::new();
+
+        for provider in &self.providers {
+            let missing: Vec<&str> = coin_ids
+                .iter()
+                .copied()
+                .filter(|id| !quotes.contains_key(*id))
+                .collect();
+            if missing.is_empty() {
+                break;
+            }
+            match provider.current_prices(&missing, vs_currency).await {
+                Ok(prices) => {
+                    let retrieved_at = Utc::now();
+                    for (coin_id, price) in prices {
+                        quotes.insert(
+                            coin_id,
+                            PriceQuote {
+                                price,
+                                provider: provider.name().to_string(),
+                                endpoint: provider.current_endpoint(),
+                                retrieved_at,
+                            },
+                        );
+                    }
+                }
+                Err(e) => errors.push(format!("{}: {}", provider.name(), e)),
+            }
+        }
+
+        if quotes.is_empty() && !errors.is_empty() {
+            return Err(format!(
+                "All price providers failed ({})",
+                errors.join("; ")
+            ));
+        }
+        Ok(quotes)
+    }
+
+    /// Price of a coin at the start of `date` (UTC), from the first provider
+    /// that has it.
+    pub async fn historical_price(
+        &self,
+        coin_id: &str,
+        date: NaiveDate,
+        vs_currency: &str,
+    ) -> Result<PriceQuote, String> {
+        let mut errors = Vec::new();
+        for provider in &self.providers {
+            match provider.historical_price(coin_id, date, vs_currency).await {
+                Ok(price) => {
+                    return Ok(PriceQuote {
+                        price,
+                        provider: provider.name().to_string(),
+                        endpoint: provider.historical_endpoint(),
+                        retrieved_at: Utc::now(),
+                    })
+                }
+                Err(e) => errors.push(format!("{}: {}", provider.name(), e)),
+            }
+        }
+        Err(format!(
+            "No price for {} in {} on {} ({})",
+            coin_id,
+            vs_currency,
+            date,
+            errors.join("; ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetchers::FetchError;
+
+    /// A provider with fixed answers.
+    struct FixedProvider {
+        name: &'static str,
+        prices: HashMap<String, String>,
+    }
+
+    #[async_trait]
+    impl PriceProvider for FixedProvider {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn current_endpoint(&self) -> String {
+            format!("https://{}.test/current", self.name)
+        }
+
+        fn historical_endpoint(&self) -> String {
+            format!("https://{}.test/historical", self.name)
+        }
+
+        async fn current_prices(
+            &self,
+            coin_ids: &[&str],
+            _vs_currency: &str,
+        ) -> FetchResult<HashMap<String, String>> {
+            if self.prices.is_empty() {
+                return Err(FetchError::RateLimited);
+            }
+            Ok(coin_ids
+                .iter()
+                .filter_map(|id| self.prices.get(*id).map(|p| (id.to_string(), p.clone())))
+                .collect())
+        }
+
+        async fn historical_price(
+            &self,
+            coin_id: &str,
+            _date: NaiveDate,
+            _vs_currency: &str,
+        ) -> FetchResult<String> {
+            self.prices
+                .get(coin_id)
+                .cloned()
+                .ok_or_else(|| FetchError::ApiError("unknown coin".to_string()))
+        }
+    }
+
+    fn fixed(name: &'static str, prices: &[(&str, &str)]) -> Box<dyn PriceProvider> {
+        Box::new(FixedProvider {
+            name,
+            prices: prices
+                .iter()
+                .map(|(id, price)| (id.to_string(), price.to_string()))
+                .collect(),
+        })
+    }
+
+    #[test]
+    fn test_provider_order_prefers_configured_keys() {
+        assert_eq!(
+            provider_order(|_| false),
+            vec![
+                ApiProvider::CoinGecko,
+                ApiProvider::CryptoCompare,
+                ApiProvider::DefiLlama
+            ]
+        );
+        assert_eq!(
+            provider_order(|p| p == ApiProvider::CryptoCompare),
+            vec![
+                ApiProvider::CryptoCompare,
+                ApiProvider::CoinGecko,
+                ApiProvider::DefiLlama
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_to_next_provider() {
+        let service = PriceService::new(vec![
+            fixed("limited", &[]),
+            fixed("partial", &[("polkadot", "4.2")]),
+            fixed("fallback", &[("polkadot", "9"), ("kusama", "21.5")]),
+        ]);
+
+        let quotes = service
+            .current_prices(&["polkadot", "kusama", "unknown"], "usd")
+            .await
+            .unwrap();
+        assert_eq!(quotes["polkadot"].provider, "partial");
+        assert_eq!(quotes["kusama"].price, "21.5");
+        assert_eq!(quotes["kusama"].provider, "fallback");
+        assert_eq!(quotes["kusama"].endpoint, "https://fallback.test/current");
+        assert!(!quotes.contains_key("unknown"));
+
+        let date = NaiveDate::from_ymd_opt(2026, 1, 15).unwrap();
+        let quote = service
+            .historical_price("kusama", date, "usd")
+            .await
+            .unwrap();
+        assert_eq!(quote.provider, "fallback");
+        assert_eq!(quote.endpoint, "https://fallback.test/historical");
+        assert!(service
+            .historical_price("unknown", date, "usd")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_all_providers_failing_is_an_error() {
+        let service = PriceService::new(vec![fixed("a", &[]), fixed("b", &[])]);
+        let err = service.current_price("polkadot", "usd").await.unwrap_err();
+        assert!(err.contains("a: Rate limited"));
+        assert!(err.contains("b: Rate limited"));
+    }
+
+    #[test]
+    fn test_parse_price() {
+        let price =
+            |json: &str| parse_price(&serde_json::from_str(json).unwrap()).map(format_price);
+        assert_eq!(price("0.1").as_deref(), Some("0.1"));
+        assert_eq!(price("4.25").as_deref(), Some("4.25"));
+        assert_eq!(price("65000").as_deref(), Some("65000"));
+        assert_eq!(price("1.2e-7").as_deref(), Some("0.00000012"));
+        assert_eq!(price(r#""1.10""#).as_deref(), Some("1.1"));
+        assert_eq!(price("null"), None);
+        assert_eq!(price(r#""n/a""#), None);
+    }
+}
@@ -0,0 +1,418 @@
+//! Price feed coin IDs for chain-native assets and token contracts.
+//!
+//! Price feeds key coins by CoinGecko ID, while balances and transactions
+//! name an asset by its chain and contract, or only by its symbol. A
+//! built-in table maps each supported chain's native currency and a few
+//! widely held tokens to their coin IDs. A profile can override any entry
+//! or add its own, e.g. for a token the table doesn't know. Valuations fall
+//! back to the table for assets the caller didn't pass a coin ID for.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+use super::address_watch::native_currency;
+
+/// Asset name of a chain's own currency, as opposed to a token contract.
+pub const NATIVE_ASSET: &str = "native";
+
+/// Symbols and coin IDs of the supported chains' native currencies.
+const NATIVE_COIN_IDS: &[(&str, &str, &str)] = &[
+    ("ethereum", "ETH", "ethereum"),
+    ("arbitrum", "ETH", "ethereum"),
+    ("base", "ETH", "ethereum"),
+    ("optimism", "ETH", "ethereum"),
+    ("polygon", "POL", "polygon-ecosystem-token"),
+    ("bsc", "BNB", "binancecoin"),
+    ("moonbeam", "GLMR", "moonbeam"),
+    ("moonriver", "MOVR", "moonriver"),
+    ("astar", "ASTR", "astar"),
+    ("astar-substrate", "ASTR", "astar"),
+    ("polkadot", "DOT", "polkadot"),
+    ("kusama", "KSM", "kusama"),
+    ("acala", "ACA", "acala"),
+    ("bitcoin", "BTC", "bitcoin"),
+    ("solana", "SOL", "solana"),
+];
+
+/// Coin IDs of widely held tokens: chain, lowercase contract, symbol, and
+/// coin ID.
+const TOKEN_COIN_IDS: &[(&str, &str, &str, &str)] = &[
+    (
+        "ethereum",
+        "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+        "USDC",
+        "usd-coin",
+    ),
+    (
+        "ethereum",
+        "0xdac17f958d2ee523a2206206994597c13d831ec7",
+        "USDT",
+        "tether",
+    ),
+    (
+        "ethereum",
+        "0x6b175474e89094c44da98b954eedeac495271d0f",
+        "DAI",
+        "dai",
+    ),
+    (
+        "ethereum",
+        "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+        "WETH",
+        "weth",
+    ),
+    (
+        "ethereum",
+        "0x2260fac5e5542a773aa44fbcfedf7c193bc2c599",
+        "WBTC",
+        "wrapped-bitcoin",
+    ),
+    (
+        "ethereum",
+        "0x514910771af9ca656af840dff83e8264ecf986ca",
+        "LINK",
+        "chainlink",
+    ),
+    (
+        "ethereum",
+        "0x1f9840a85d5af5bf1d1762f925bdaddc4201f984",
+        "UNI",
+        "uniswap",
+    ),
+    (
+        "arbitrum",
+        "0xaf88d065e77c8cc2239327c5edb3a432268e5831",
+        "USDC",
+        "usd-coin",
+    ),
+    (
+        "base",
+        "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913",
+        "USDC",
+        "usd-coin",
+    ),
+    (
+        "polygon",
+        "0x3c499c542cef5e3811e1192ce70d8cc03d5c3359",
+        "USDC",
+        "usd-coin",
+    ),
+];
+
+#[derive(Debug, Clone, FromRow)]
+struct MappingRow {
+    chain: String,
+    asset: String,
+    symbol: Option<String>,
+    coin_id: String,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+/// How an asset is keyed in the table: [`NATIVE_ASSET`] for a chain's own
+/// currency, otherwise the contract, lowercased when it's hex. Base58
+/// addresses are case-sensitive and kept as given.
+pub fn mapping_asset(contract: Option<&str>) -> String {
+    match contract.map(str::trim) {
+        None | Some("") => NATIVE_ASSET.to_string(),
+        Some(asset) if asset.eq_ignore_ascii_case(NATIVE_ASSET) => NATIVE_ASSET.to_string(),
+        Some(asset) if asset.starts_with("0x") => asset.to_lowercase(),
+        Some(asset) => asset.to_string(),
+    }
+}
+
+/// Symbol of a chain's native currency, from the table or else the chain's
+/// configuration.
+fn native_symbol(chain: &str) -> String {
+    NATIVE_COIN_IDS
+        .iter()
+        .find(|(c, _, _)| c.eq_ignore_ascii_case(chain))
+        .map(|(_, symbol, _)| symbol.to_string())
+        .unwrap_or_else(|| native_currency(chain).0)
+}
+
+/// A profile's coin ID table: the built-in entries with its own on top.
+#[derive(Debug, Clone, Default)]
+pub struct PriceIds {
+    by_asset: HashMap<(String, String), String>,
+    by_symbol: HashMap<String, String>,
+}
+
+impl PriceIds {
+    fn from_rows(rows: Vec<MappingRow>) -> Self {
+        let mut ids = Self::default();
+        for (chain, symbol, coin_id) in NATIVE_COIN_IDS {
+            ids.insert(chain, NATIVE_ASSET, Some(symbol), coin_id);
+        }
+        for (chain, contract, symbol, coin_id) in TOKEN_COIN_IDS {
+            ids.insert(chain, contract, Some(symbol), coin_id);
+        }
+        for row in rows {
+            // A native override reprices the chain's currency by symbol too
+            let symbol = row
+                .symbol
+                .or_else(|| (row.asset == NATIVE_ASSET).then(|| native_symbol(&row.chain)));
+            ids.insert(&row.chain, &row.asset, symbol.as_deref(), &row.coin_id);
+        }
+        ids
+    }
+
+    fn insert(&mut self, chain: &str, asset: &str, symbol: Option<&str>, coin_id: &str) {
+        self.by_asset.insert(
+            (chain.to_lowercase(), asset.to_string()),
+            coin_id.to_string(),
+        );
+        if let Some(symbol) = symbol {
+            self.by_symbol
+                .insert(symbol.trim().to_uppercase(), coin_id.to_string());
+        }
+    }
+
+    /// Loads a profile's table.
+    pub async fn load(pool: &SqlitePool, profile_id: &str) -> Result<Self, sqlx::Error> {
+        Ok(Self::from_rows(load_rows(pool, profile_id).await?))
+    }
+
+    /// Coin ID of a token on `chain`, or of the chain's native currency
+    /// when `contract` is `None`.
+    pub fn coin_id(&self, chain: &str, contract: Option<&str>) -> Option<&str> {
+        self.by_asset
+            .get(&(chain.to_lowercase(), mapping_asset(contract)))
+            .map(String::as_str)
+    }
+
+    /// Coin IDs by uppercase symbol, for valuations keyed by symbol.
+    pub fn by_symbol(&self) -> &HashMap<String, String> {
+        &self.by_symbol
+    }
+}
+
+async fn load_rows(pool: &SqlitePool, profile_id: &str) -> Result<Vec<MappingRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT chain, asset, symbol, coin_id, updated_at FROM price_id_mappings
+        WHERE profile_id = ?
+        "#,
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Coin IDs by uppercase symbol from a profile's table, overridden by
+/// `coin_ids` passed in by the caller.
+pub(crate) async fn symbol_coin_ids(
+    pool: &SqlitePool,
+    profile_id: &str,
+    coin_ids: HashMap<String, String>,
+) -> Result<HashMap<String, String>, String> {
+    let mut ids = PriceIds::load(pool, profile_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .by_symbol()
+        .clone();
+    ids.extend(
+        coin_ids
+            .into_iter()
+            .map(|(asset, id)| (asset.trim().to_uppercase(), id)),
+    );
+    Ok(ids)
+}
+
+/// An asset's coin ID for a profile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceIdMapping {
+    /// Chain name.
+    pub chain: String,
+    /// `native`, or the token's contract address.
+    pub asset: String,
+    /// Symbol lookups by symbol alone resolve through, if any.
+    pub symbol: Option<String>,
+    /// Coin ID prices are fetched under.
+    pub coin_id: String,
+    /// The built-in coin ID, if the table has one.
+    pub default_coin_id: Option<String>,
+    /// When the profile overrode or added the entry; `None` if it hasn't.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// The built-in coin ID of an asset keyed by [`mapping_asset`].
+pub fn default_coin_id(chain: &str, asset: &str) -> Option<&'static str> {
+    if asset == NATIVE_ASSET {
+        return NATIVE_COIN_IDS
+            .iter()
+            .find(|(c, _, _)| c.eq_ignore_ascii_case(chain))
+            .map(|(_, _, id)| *id);
+    }
+    TOKEN_COIN_IDS
+        .iter()
+        .find(|(c, contract, _, _)| c.eq_ignore_ascii_case(chain) && *contract == asset)
+        .map(|(_, _, _, id)| *id)
+}
+
+/// Whether `id` looks like a CoinGecko coin ID: lowercase letters, digits,
+/// and hyphens.
+pub fn is_coin_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// The built-in entries merged with a profile's own, ordered by chain and
+/// asset with each chain's native currency first.
+fn merge_mappings(rows: Vec<MappingRow>) -> Vec<PriceIdMapping> {
+    let mut mappings: HashMap<(String, String), PriceIdMapping> = HashMap::new();
+    for (chain, symbol, coin_id) in NATIVE_COIN_IDS {
+        mappings.insert(
+            (chain.to_string(), NATIVE_ASSET.to_string()),
+            PriceIdMapping {
+                chain: chain.to_string(),
+                asset: NATIVE_ASSET.to_string(),
+                symbol: Some(symbol.to_string()),
+                coin_id: coin_id.to_string(),
+                default_coin_id: Some(coin_id.to_string()),
+                updated_at: None,
+            },
+        );
+    }
+    for (chain, contract, symbol, coin_id) in TOKEN_COIN_IDS {
+        mappings.insert(
+            (chain.to_string(), contract.to_string()),
+            PriceIdMapping {
+                chain: chain.to_string(),
+                asset: contract.to_string(),
+                symbol: Some(symbol.to_string()),
+                coin_id: coin_id.to_string(),
+                default_coin_id: Some(coin_id.to_string()),
+                updated_at: None,
+            },
+        );
+    }
+    for row in rows {
+        let default = default_coin_id(&row.chain, &row.asset).map(str::to_string);
+        let entry = mappings
+            .entry((row.chain.clone(), row.asset.clone()))
+            .or_insert_with(|| PriceIdMapping {
+                chain: row.chain.clone(),
+                asset: row.asset.clone(),
+                symbol: (row.asset == NATIVE_ASSET).then(|| native_symbol(&row.chain)),
+                coin_id: String::new(),
+                default_coin_id: default,
+                updated_at: None,
+            });
+        if row.symbol.is_some() {
+            entry.symbol = row.symbol;
+        }
+        entry.coin_id = row.coin_id;
+        entry.updated_at = row.updated_at;
+    }
+
+    let mut mappings: Vec<PriceIdMapping> = mappings.into_values().collect();
+    mappings.sort_by(|a, b| {
+        (&a.chain, a.asset != NATIVE_ASSET, &a.asset).cmp(&(
+            &b.chain,
+            b.asset != NATIVE_ASSET,
+            &b.asset,
+        ))
+    });
+    mappings
+}
+
+/// A profile's coin ID table: the built-in entries, with the profile's
+/// overrides and additions applied.
+pub async fn profile_mappings(
+    pool: &SqlitePool,
+    profile_id: &str,
+) -> Result<Vec<PriceIdMapping>, sqlx::Error> {
+    let rows = load_rows(pool, profile_id).await?;
+    Ok(merge_mappings(rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(chain: &str, asset: &str, symbol: Option<&str>, coin_id: &str) -> MappingRow {
+        MappingRow {
+            chain: chain.to_string(),
+            asset: asset.to_string(),
+            symbol: symbol.map(str::to_string),
+            coin_id: coin_id.to_string(),
+            updated_at: Some(Utc::now()),
+        }
+    }
+
+    #[test]
+    fn test_resolves_defaults_and_overrides() {
+        let ids = PriceIds::from_rows(vec![
+            row("moonbeam", NATIVE_ASSET, None, "moonbeam-glmr"),
+            row(
+                "ethereum",
+                "0x6982508145454ce325ddbe47a25d4ec3d2311933",
+                Some("PEPE"),
+                "pepe",
+            ),
+        ]);
+
+        assert_eq!(ids.coin_id("arbitrum", None), Some("ethereum"));
+        assert_eq!(ids.coin_id("Moonriver", None), Some("moonriver"));
+        assert_eq!(
+            ids.coin_id(
+                "ethereum",
+                Some("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48")
+            ),
+            Some("usd-coin")
+        );
+        assert_eq!(ids.coin_id("moonbeam", None), Some("moonbeam-glmr"));
+        assert_eq!(
+            ids.by_symbol().get("GLMR").map(String::as_str),
+            Some("moonbeam-glmr")
+        );
+        assert_eq!(
+            ids.by_symbol().get("PEPE").map(String::as_str),
+            Some("pepe")
+        );
+        assert_eq!(
+            ids.by_symbol().get("DOT").map(String::as_str),
+            Some("polkadot")
+        );
+        assert_eq!(ids.coin_id("ethereum", Some("0xdeadbeef")), None);
+    }
+
+    #[test]
+    fn test_keys_assets() {
+        assert_eq!(mapping_asset(None), NATIVE_ASSET);
+        assert_eq!(mapping_asset(Some("Native")), NATIVE_ASSET);
+        assert_eq!(
+            mapping_asset(Some("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48")),
+            "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+        );
+        assert_eq!(
+            mapping_asset(Some("So11111111111111111111111111111111111111112")),
+            "So11111111111111111111111111111111111111112"
+        );
+        assert!(is_coin_id("avalanche-2"));
+        assert!(!is_coin_id("Ethereum"));
+    }
+
+    #[test]
+    fn test_lists_overrides_against_defaults() {
+        let mappings = merge_mappings(vec![row("bsc", NATIVE_ASSET, None, "bnb")]);
+        let bsc: Vec<&PriceIdMapping> = mappings.iter().filter(|m| m.chain == "bsc").collect();
+        assert_eq!(bsc.len(), 1);
+        assert_eq!(bsc[0].coin_id, "bnb");
+        assert_eq!(bsc[0].default_coin_id.as_deref(), Some("binancecoin"));
+        assert!(bsc[0].updated_at.is_some());
+
+        let ethereum: Vec<&str> = mappings
+            .iter()
+            .filter(|m| m.chain == "ethereum")
+            .map(|m| m.asset.as_str())
+            .collect();
+        assert_eq!(ethereum[0], NATIVE_ASSET);
+    }
+}
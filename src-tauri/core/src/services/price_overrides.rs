@@ -0,0 +1,141 @@
+//! Manual token price overrides: loading them and picking the one that
+//! applies to an asset, currency, and date.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+use super::price_feeds::PriceQuote;
+
+/// Price source recorded for prices taken from an override.
+pub(crate) const OVERRIDE_SOURCE: &str = "override";
+
+/// A manual price for a token.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceOverride {
+    /// Unique identifier for the override.
+    pub id: String,
+    /// Profile the override applies to.
+    pub profile_id: String,
+    /// CoinGecko coin ID or asset symbol, matched case-insensitively.
+    pub token: String,
+    /// Currency the price is in, e.g. "USD".
+    pub currency: String,
+    /// Price of one token as a decimal string.
+    pub price: String,
+    /// Start of the range, inclusive; `None` for no lower bound.
+    pub valid_from: Option<DateTime<Utc>>,
+    /// End of the range, exclusive; `None` for no upper bound.
+    pub valid_to: Option<DateTime<Utc>>,
+    /// Why the price was overridden.
+    pub note: Option<String>,
+    /// When the override was created.
+    pub created_at: DateTime<Utc>,
+    /// When the override was last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PriceOverride {
+    /// Whether the override has a date range rather than a fixed price.
+    fn is_ranged(&self) -> bool {
+        self.valid_from.is_some() || self.valid_to.is_some()
+    }
+
+    /// Whether the override applies to `token` in `currency` at `at`.
+    fn covers(&self, token: &str, currency: &str, at: DateTime<Utc>) -> bool {
+        self.token.eq_ignore_ascii_case(token)
+            && self.currency.eq_ignore_ascii_case(currency)
+            && self.valid_from.is_none_or(|from| at >= from)
+            && self.valid_to.is_none_or(|to| at < to)
+    }
+
+    /// The override's price as a quote, traced to the override record and
+    /// dated by its last change.
+    pub fn quote(&self) -> PriceQuote {
+        PriceQuote {
+            price: self.price.clone(),
+            provider: OVERRIDE_SOURCE.to_string(),
+            endpoint: format!("price_overrides/{}", self.id),
+            retrieved_at: self.updated_at,
+        }
+    }
+}
+
+/// Picks the override for `token` in `currency` at `at`. A ranged override
+/// beats a fixed one, and among ranged ones the latest start wins.
+pub fn select_override<'a>(
+    overrides: &'a [PriceOverride],
+    token: &str,
+    currency: &str,
+    at: DateTime<Utc>,
+) -> Option<&'a PriceOverride> {
+    overrides
+        .iter()
+        .filter(|o| o.covers(token, currency, at))
+        .max_by_key(|o| (o.is_ranged(), o.valid_from, o.updated_at))
+}
+
+/// Loads a profile's overrides.
+pub async fn load_overrides(
+    pool: &SqlitePool,
+    profile_id: &str,
+) -> Result<Vec<PriceOverride>, sqlx::Error> {
+    sqlx::query_as("SELECT * FROM price_overrides WHERE profile_id = ? ORDER BY token, valid_from")
+        .bind(profile_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// The currency reports are prepared in.
+pub async fn reporting_currency(pool: &SqlitePool) -> Result<String, sqlx::Error> {
+    let currency: Option<String> = sqlx::query_scalar(
+        "SELECT preference_value FROM cost_basis_preferences WHERE preference_key = 'reporting_currency'",
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(currency.unwrap_or_else(|| "USD".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+    }
+
+    fn price_override(
+        id: &str,
+        price: &str,
+        range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    ) -> PriceOverride {
+        PriceOverride {
+            id: id.to_string(),
+            profile_id: "p1".to_string(),
+            token: "UNI-V2".to_string(),
+            currency: "USD".to_string(),
+            price: price.to_string(),
+            valid_from: range.map(|(from, _)| from),
+            valid_to: range.map(|(_, to)| to),
+            note: None,
+            created_at: at(2024, 1, 1),
+            updated_at: at(2024, 1, 1),
+        }
+    }
+
+    #[test]
+    fn test_ranged_override_beats_fixed() {
+        let overrides = vec![
+            price_override("fixed", "1.5", None),
+            price_override("q1", "2", Some((at(2024, 1, 1), at(2024, 4, 1)))),
+        ];
+
+        let pick = |day| select_override(&overrides, "uni-v2", "usd", day).map(|o| o.id.as_str());
+        assert_eq!(pick(at(2024, 2, 1)), Some("q1"));
+        assert_eq!(pick(at(2024, 4, 1)), Some("fixed"));
+        assert_eq!(pick(at(2023, 12, 31)), Some("fixed"));
+        assert!(select_override(&overrides, "uni-v2", "eur", at(2024, 2, 1)).is_none());
+    }
+}
@@ -0,0 +1,206 @@
+//! Price source appendix for reports.
+//!
+//! Auditors ask where every fiat value came from. Each price keeps the
+//! provider and endpoint that produced it and when it was retrieved (see
+//! [`PriceQuote`]), and reports list the sources behind their values in an
+//! appendix: one entry per provider endpoint, with the assets it priced and
+//! the span of its retrieval times.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, Utc};
+use csv::Writer;
+use serde::{Deserialize, Serialize};
+
+use super::price_feeds::PriceQuote;
+use crate::core::pdf::{Font, PdfDocument, PAGE_WIDTH};
+
+/// One provider endpoint behind a report's values.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceSource {
+    /// Provider name, or `override` for a manual price.
+    pub provider: String,
+    /// Endpoint the prices were read from; an override's record for
+    /// manual prices.
+    pub endpoint: String,
+    /// Assets priced from it, sorted.
+    pub assets: Vec<String>,
+    /// Number of values priced from it.
+    pub valuations: usize,
+    /// Earliest retrieval of a price used.
+    pub first_retrieved_at: DateTime<Utc>,
+    /// Latest retrieval of a price used.
+    pub last_retrieved_at: DateTime<Utc>,
+}
+
+/// Groups the quotes behind a report's values, each with the asset it
+/// priced, by provider and endpoint.
+pub fn price_sources<'a>(
+    valuations: impl IntoIterator<Item = (&'a str, &'a PriceQuote)>,
+) -> Vec<PriceSource> {
+    type Span = (BTreeSet<String>, usize, DateTime<Utc>, DateTime<Utc>);
+    let mut grouped: BTreeMap<(String, String), Span> = BTreeMap::new();
+    for (asset, quote) in valuations {
+        let key = (quote.provider.clone(), quote.endpoint.clone());
+        let (assets, count, first, last) = grouped
+            .entry(key)
+            .or_insert_with(|| (BTreeSet::new(), 0, quote.retrieved_at, quote.retrieved_at));
+        assets.insert(asset.to_string());
+        *count += 1;
+        *first = (*first).min(quote.retrieved_at);
+        *last = (*last).max(quote.retrieved_at);
+    }
+
+    grouped
+        .into_iter()
+        .map(
+            |((provider, endpoint), (assets, valuations, first, last))| PriceSource {
+                provider,
+                endpoint,
+                assets: assets.into_iter().collect(),
+                valuations,
+                first_retrieved_at: first,
+                last_retrieved_at: last,
+            },
+        )
+        .collect()
+}
+
+fn format_time(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+/// Writes the appendix below a CSV report's rows: a blank row, a heading,
+/// and one row per source. The writer must be flexible, since the rows are
+/// narrower than the report's.
+pub fn write_csv_appendix(
+    writer: &mut Writer<Vec<u8>>,
+    sources: &[PriceSource],
+) -> Result<(), String> {
+    if sources.is_empty() {
+        return Ok(());
+    }
+    let heading: [&[&str]; 3] = [
+        &[""],
+        &["Price Sources"],
+        &[
+            "Provider",
+            "Endpoint",
+            "Assets",
+            "Values",
+            "First Retrieved",
+            "Last Retrieved",
+        ],
+    ];
+    for record in heading {
+        writer.write_record(record).map_err(|e| e.to_string())?;
+    }
+    for source in sources {
+        writer
+            .write_record([
+                source.provider.clone(),
+                source.endpoint.clone(),
+                source.assets.join(" "),
+                source.valuations.to_string(),
+                format_time(source.first_retrieved_at),
+                format_time(source.last_retrieved_at),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Renders the appendix on a new page of a PDF report.
+pub fn render_pdf_appendix(doc: &mut PdfDocument, sources: &[PriceSource]) {
+    if sources.is_empty() {
+        return;
+    }
+    let left = 54.0;
+    let right = PAGE_WIDTH - 54.0;
+    doc.add_page();
+    let mut y = 740.0;
+    doc.text(left, y, 13.0, Font::Bold, "Appendix: Price Sources");
+    y -= 12.0;
+    doc.hline(left, right, y, 0.75);
+    y -= 20.0;
+
+    for source in sources {
+        if y < 90.0 {
+            doc.add_page();
+            y = 740.0;
+        }
+        doc.text(left, y, 10.0, Font::Bold, &source.provider);
+        doc.text(left + 90.0, y, 9.0, Font::Regular, &source.endpoint);
+        y -= 13.0;
+        doc.text(
+            left + 90.0,
+            y,
+            9.0,
+            Font::Regular,
+            &format!(
+                "{} value(s) of {}",
+                source.valuations,
+                source.assets.join(", ")
+            ),
+        );
+        y -= 13.0;
+        doc.text(
+            left + 90.0,
+            y,
+            9.0,
+            Font::Regular,
+            &format!(
+                "Retrieved {} to {}",
+                format_time(source.first_retrieved_at),
+                format_time(source.last_retrieved_at)
+            ),
+        );
+        y -= 20.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use csv::WriterBuilder;
+
+    fn quote(provider: &str, endpoint: &str, hour: u32) -> PriceQuote {
+        PriceQuote {
+            price: "1".to_string(),
+            provider: provider.to_string(),
+            endpoint: endpoint.to_string(),
+            retrieved_at: Utc.with_ymd_and_hms(2026, 3, 1, hour, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_price_sources_group_by_endpoint() {
+        let history = "https://api.coingecko.com/api/v3/coins/{id}/history";
+        let quotes = [
+            ("ETH", quote("coingecko", history, 12)),
+            ("DOT", quote("coingecko", history, 9)),
+            ("ETH", quote("coingecko", history, 10)),
+            ("GIVE", quote("override", "price_overrides/o1", 8)),
+        ];
+
+        let sources = price_sources(quotes.iter().map(|(asset, q)| (*asset, q)));
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].provider, "coingecko");
+        assert_eq!(sources[0].assets, ["DOT", "ETH"]);
+        assert_eq!(sources[0].valuations, 3);
+        assert_eq!(sources[0].first_retrieved_at, quotes[1].1.retrieved_at);
+        assert_eq!(sources[0].last_retrieved_at, quotes[0].1.retrieved_at);
+        assert_eq!(sources[1].endpoint, "price_overrides/o1");
+
+        let mut writer = WriterBuilder::new().flexible(true).from_writer(Vec::new());
+        writer
+            .write_record(["a", "b", "c", "d", "e", "f", "g"])
+            .unwrap();
+        write_csv_appendix(&mut writer, &sources).unwrap();
+        let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert!(csv.contains("\nPrice Sources\n"));
+        assert!(csv.contains("override,price_overrides/o1,GIVE,1,2026-03-01 08:00:00 UTC"));
+    }
+}
@@ -0,0 +1,422 @@
+//! Per-profile data scoping.
+//!
+//! Every profile, wallet, and transaction query goes through this module:
+//! the caller's access token is verified, their role on the profile is
+//! checked for the permission the command needs, and the query itself is filtered by that profile so a record
+//! belonging to another profile is never returned, even when its ID is
+//! known.
+
+use sqlx::SqlitePool;
+
+use super::auth::verify_profile_access;
+use super::permissions::Permission;
+use super::persistence::{Profile, StoredTransaction, Wallet};
+use crate::core::auth_helpers::verify_access_token;
+use crate::core::auth_state::AuthState;
+
+// ============================================================================
+// Authorization
+// ============================================================================
+
+/// Verifies `token` and returns the user ID it was issued to.
+pub fn authenticate(auth: &AuthState, token: &str) -> Result<String, String> {
+    Ok(verify_access_token(token, &auth.jwt_keys())?.sub)
+}
+
+/// Verifies `token` and that its user's role on `profile_id` grants
+/// `permission`. Returns the user ID.
+pub async fn authorize_profile(
+    pool: &SqlitePool,
+    auth: &AuthState,
+    token: &str,
+    profile_id: &str,
+    permission: Permission,
+) -> Result<String, String> {
+    let user_id = authenticate(auth, token)?;
+    verify_profile_access(pool, &user_id, profile_id, permission).await?;
+    Ok(user_id)
+}
+
+/// Verifies `token` and that its user's role on the profile owning
+/// `wallet_id` grants `permission`. Returns the user ID and the wallet.
+///
+/// A wallet the user can't see is reported the same way as one that doesn't
+/// exist, so wallet IDs can't be probed across profiles.
+pub async fn authorize_wallet(
+    pool: &SqlitePool,
+    auth: &AuthState,
+    token: &str,
+    wallet_id: &str,
+    permission: Permission,
+) -> Result<(String, Wallet), String> {
+    let user_id = authenticate(auth, token)?;
+    let not_found = format!("Wallet not found: {}", wallet_id);
+    let wallet = sqlx::query_as::<_, Wallet>("SELECT * FROM wallets WHERE id = ?")
+        .bind(wallet_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| not_found.clone())?;
+
+    authorize_record(pool, &user_id, &wallet.profile_id, permission, &not_found).await?;
+    Ok((user_id, wallet))
+}
+
+/// Verifies that `user_id`'s role on `profile_id`, the profile owning some
+/// record, grants `permission`.
+///
+/// A user who can't see the profile gets `not_found`, the error for a record
+/// that doesn't exist, so record IDs can't be probed across profiles.
+pub async fn authorize_record(
+    pool: &SqlitePool,
+    user_id: &str,
+    profile_id: &str,
+    permission: Permission,
+    not_found: &str,
+) -> Result<(), String> {
+    verify_profile_access(pool, user_id, profile_id, Permission::ViewTransactions)
+        .await
+        .map_err(|_| not_found.to_string())?;
+    verify_profile_access(pool, user_id, profile_id, permission).await
+}
+
+// ============================================================================
+// Scoped Queries
+// ============================================================================
+
+/// Profiles the user holds an active role on, newest first.
+pub async fn profiles_for_user(pool: &SqlitePool, user_id: &str) -> Result<Vec<Profile>, String> {
+    sqlx::query_as::<_, Profile>(
+        r#"
+        SELECT p.* FROM profiles p
+        INNER JOIN user_profile_roles upr ON upr.profile_id = p.id
+        WHERE upr.user_id = ? AND upr.status = 'active'
+        ORDER BY p.created_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// A profile's wallets, newest first.
+pub async fn profile_wallets(pool: &SqlitePool, profile_id: &str) -> Result<Vec<Wallet>, String> {
+    sqlx::query_as::<_, Wallet>(
+        "SELECT * FROM wallets WHERE profile_id = ? ORDER BY created_at DESC",
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// One wallet's transactions, newest first, leaving out records merged
+/// into another. Returns nothing if the wallet doesn't belong to
+/// `profile_id`.
+pub async fn wallet_transactions(
+    pool: &SqlitePool,
+    profile_id: &str,
+    wallet_id: &str,
+    limit: i32,
+    offset: i32,
+) -> Result<Vec<StoredTransaction>, String> {
+    sqlx::query_as::<_, StoredTransaction>(
+        r#"
+        SELECT t.* FROM transactions t
+        INNER JOIN wallets w ON t.wallet_id = w.id
+        WHERE w.profile_id = ? AND t.wallet_id = ?
+          AND t.id NOT IN (SELECT duplicate_id FROM transaction_merges)
+        ORDER BY t.timestamp DESC
+        LIMIT ? OFFSET ?
+        "#,
+    )
+    .bind(profile_id)
+    .bind(wallet_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Transactions across all of a profile's wallets, newest first, leaving
+/// out records merged into another.
+pub async fn profile_transactions(
+    pool: &SqlitePool,
+    profile_id: &str,
+    limit: i32,
+    offset: i32,
+) -> Result<Vec<StoredTransaction>, String> {
+    sqlx::query_as::<_, StoredTransaction>(
+        r#"
+        SELECT t.* FROM transactions t
+        INNER JOIN wallets w ON t.wallet_id = w.id
+        WHERE w.profile_id = ?
+          AND t.id NOT IN (SELECT duplicate_id FROM transaction_merges)
+        ORDER BY t.timestamp DESC
+        LIMIT ? OFFSET ?
+        "#,
+    )
+    .bind(profile_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::auth_helpers::generate_access_token;
+    use chrono::Utc;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for ddl in [
+            r#"
+            CREATE TABLE profiles (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                avatar_url TEXT,
+                created_at DATETIME NOT NULL,
+                updated_at DATETIME NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE user_profile_roles (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                profile_id TEXT NOT NULL,
+                role TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'active',
+                UNIQUE(user_id, profile_id)
+            )
+            "#,
+            r#"
+            CREATE TABLE wallets (
+                id TEXT PRIMARY KEY,
+                profile_id TEXT NOT NULL,
+                address TEXT NOT NULL,
+                chain TEXT NOT NULL,
+                name TEXT,
+                wallet_type TEXT NOT NULL,
+                created_at DATETIME NOT NULL,
+                updated_at DATETIME
+            )
+            "#,
+            r#"
+            CREATE TABLE transactions (
+                id TEXT PRIMARY KEY,
+                wallet_id TEXT NOT NULL,
+                hash TEXT NOT NULL,
+                block_number INTEGER,
+                timestamp DATETIME,
+                from_address TEXT,
+                to_address TEXT,
+                value TEXT,
+                fee TEXT,
+                status TEXT,
+                tx_type TEXT,
+                token_symbol TEXT,
+                token_decimals INTEGER,
+                chain TEXT NOT NULL,
+                raw_data TEXT,
+                created_at DATETIME NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE transaction_merges (
+                duplicate_id TEXT PRIMARY KEY,
+                kept_id TEXT NOT NULL,
+                profile_id TEXT NOT NULL,
+                merged_by TEXT,
+                created_at DATETIME NOT NULL
+            )
+            "#,
+        ] {
+            sqlx::query(ddl).execute(&pool).await.unwrap();
+        }
+
+        // Two organizations, each with an owner, a wallet, and a transaction.
+        // Carol's role on Alpha is suspended.
+        let now = Utc::now();
+        for (profile, wallet, tx) in [
+            ("alpha", "w-alpha", "t-alpha"),
+            ("beta", "w-beta", "t-beta"),
+        ] {
+            sqlx::query("INSERT INTO profiles VALUES (?, ?, NULL, ?, ?)")
+                .bind(profile)
+                .bind(profile)
+                .bind(now)
+                .bind(now)
+                .execute(&pool)
+                .await
+                .unwrap();
+            sqlx::query(
+                "INSERT INTO wallets VALUES (?, ?, ?, 'ethereum', NULL, 'software', ?, NULL)",
+            )
+            .bind(wallet)
+            .bind(profile)
+            .bind(format!("0x{}", profile))
+            .bind(now)
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                "INSERT INTO transactions (id, wallet_id, hash, chain, created_at) VALUES (?, ?, ?, 'ethereum', ?)",
+            )
+            .bind(tx)
+            .bind(wallet)
+            .bind(format!("0x{}", tx))
+            .bind(now)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        for (id, user, profile, role, status) in [
+            ("r1", "alice", "alpha", "owner", "active"),
+            ("r2", "bob", "beta", "owner", "active"),
+            ("r3", "carol", "alpha", "preparer", "suspended"),
+        ] {
+            sqlx::query("INSERT INTO user_profile_roles VALUES (?, ?, ?, ?, ?)")
+                .bind(id)
+                .bind(user)
+                .bind(profile)
+                .bind(role)
+                .bind(status)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        pool
+    }
+
+    fn token_for(auth: &AuthState, user_id: &str) -> String {
+        generate_access_token(
+            user_id,
+            &format!("{}@example.org", user_id),
+            &auth.jwt_keys(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_profile_access_is_limited_to_assigned_profiles() {
+        let pool = setup_test_db().await;
+        let auth = AuthState::new();
+        let alice = token_for(&auth, "alice");
+        let carol = token_for(&auth, "carol");
+
+        let user = authorize_profile(&pool, &auth, &alice, "alpha", Permission::ViewTransactions)
+            .await
+            .unwrap();
+        assert_eq!(user, "alice");
+        assert!(
+            authorize_profile(&pool, &auth, &alice, "beta", Permission::ViewTransactions)
+                .await
+                .is_err()
+        );
+        assert!(
+            authorize_profile(&pool, &auth, &carol, "alpha", Permission::ViewTransactions)
+                .await
+                .is_err()
+        );
+        assert!(authorize_profile(
+            &pool,
+            &auth,
+            "not-a-token",
+            "alpha",
+            Permission::ViewTransactions
+        )
+        .await
+        .is_err());
+
+        let profiles = profiles_for_user(&pool, "alice").await.unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].id, "alpha");
+        assert!(profiles_for_user(&pool, "carol").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_wallet_of_another_profile_is_not_found() {
+        let pool = setup_test_db().await;
+        let auth = AuthState::new();
+        let alice = token_for(&auth, "alice");
+
+        let (_, wallet) =
+            authorize_wallet(&pool, &auth, &alice, "w-alpha", Permission::ManageWallets)
+                .await
+                .unwrap();
+        assert_eq!(wallet.profile_id, "alpha");
+
+        let foreign =
+            authorize_wallet(&pool, &auth, &alice, "w-beta", Permission::ViewTransactions).await;
+        let missing =
+            authorize_wallet(&pool, &auth, &alice, "w-none", Permission::ViewTransactions).await;
+        assert_eq!(foreign.unwrap_err(), "Wallet not found: w-beta");
+        assert_eq!(missing.unwrap_err(), "Wallet not found: w-none");
+    }
+
+    #[tokio::test]
+    async fn test_scoped_queries_do_not_leak_across_profiles() {
+        let pool = setup_test_db().await;
+
+        let wallets = profile_wallets(&pool, "alpha").await.unwrap();
+        assert_eq!(wallets.len(), 1);
+        assert_eq!(wallets[0].id, "w-alpha");
+
+        let txs = profile_transactions(&pool, "alpha", 100, 0).await.unwrap();
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].id, "t-alpha");
+
+        // A wallet ID from another profile yields nothing under this one.
+        assert!(wallet_transactions(&pool, "alpha", "w-beta", 100, 0)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            wallet_transactions(&pool, "beta", "w-beta", 100, 0)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merged_duplicates_are_hidden() {
+        let pool = setup_test_db().await;
+        sqlx::query(
+            "INSERT INTO transactions (id, wallet_id, hash, chain, created_at) VALUES ('t-copy', 'w-alpha', '0xcopy', 'ethereum', ?)",
+        )
+        .bind(Utc::now())
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO transaction_merges VALUES ('t-copy', 't-alpha', 'alpha', 'alice', ?)",
+        )
+        .bind(Utc::now())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let txs = profile_transactions(&pool, "alpha", 100, 0).await.unwrap();
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].id, "t-alpha");
+        let txs = wallet_transactions(&pool, "alpha", "w-alpha", 100, 0)
+            .await
+            .unwrap();
+        assert_eq!(txs.len(), 1);
+    }
+}
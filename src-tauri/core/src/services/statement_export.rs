@@ -0,0 +1,88 @@
+//! Parsing of the stored amounts and period bounds statements and exports
+//! are built from.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+
+/// Parses a stored amount string into a `Decimal`.
+///
+/// Values that already contain a decimal point are taken as-is. Integer
+/// strings are treated as base units and scaled by `decimals` when known.
+pub fn parse_amount(value: &str, decimals: Option<i32>) -> Option<Decimal> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    if value.contains('.') || value.contains('e') || value.contains('E') {
+        return Decimal::from_str(value)
+            .or_else(|_| Decimal::from_scientific(value))
+            .ok();
+    }
+
+    match decimals {
+        Some(d) if d > 0 => {
+            let d = d as usize;
+            let (negative, digits) = match value.strip_prefix('-') {
+                Some(rest) => (true, rest),
+                None => (false, value),
+            };
+            let padded = format!("{:0>width$}", digits, width = d + 1);
+            let split = padded.len() - d;
+            let formatted = format!(
+                "{}{}.{}",
+                if negative { "-" } else { "" },
+                &padded[..split],
+                &padded[split..]
+            );
+            Decimal::from_str(&formatted).ok().map(|d| d.normalize())
+        }
+        _ => Decimal::from_str(value).ok(),
+    }
+}
+
+/// Parses an optional `YYYY-MM-DD` or RFC 3339 period bound. A bare date
+/// is taken as the start or, with `end_of_day`, the end of that day.
+pub fn parse_period_bound(
+    value: Option<&str>,
+    end_of_day: bool,
+) -> Result<Option<DateTime<Utc>>, String> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(Some(dt.with_timezone(&Utc)));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date: {}", value))?;
+    let time = if end_of_day {
+        date.and_hms_opt(23, 59, 59)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+    Ok(time.map(|t| t.and_utc()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_amount_scales_base_units() {
+        assert_eq!(
+            parse_amount("1500000000000000000", Some(18)),
+            Some(Decimal::from_str("1.5").unwrap())
+        );
+        assert_eq!(
+            parse_amount("42", Some(6)),
+            Some(Decimal::from_str("0.000042").unwrap())
+        );
+        assert_eq!(
+            parse_amount("2.25", Some(18)),
+            Some(Decimal::from_str("2.25").unwrap())
+        );
+        assert_eq!(parse_amount("", Some(18)), None);
+    }
+}
@@ -0,0 +1,257 @@
+//! Exact swap amounts decoded from DEX swap events.
+//!
+//! Wallet sync stores each swap's pool legs alongside the transaction, so
+//! cost basis can use the amounts the pools executed rather than amounts
+//! inferred from the transaction's token transfers. Routed swaps are
+//! reported both leg by leg and netted to the one token sold and bought.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteExecutor, SqlitePool};
+
+use crate::chains::{NetSwap, SwapDetail};
+
+/// A stored swap leg.
+#[derive(Debug, Clone, FromRow)]
+struct SwapRow {
+    wallet_id: String,
+    hash: String,
+    protocol: String,
+    pool: String,
+    token_in: String,
+    token_in_symbol: Option<String>,
+    token_in_decimals: Option<i64>,
+    amount_in: String,
+    token_out: String,
+    token_out_symbol: Option<String>,
+    token_out_decimals: Option<i64>,
+    amount_out: String,
+}
+
+impl From<SwapRow> for SwapDetail {
+    fn from(row: SwapRow) -> Self {
+        Self {
+            protocol: row.protocol,
+            pool: row.pool,
+            token_in: row.token_in,
+            token_in_symbol: row.token_in_symbol,
+            token_in_decimals: row.token_in_decimals.and_then(|d| u8::try_from(d).ok()),
+            amount_in: row.amount_in,
+            token_out: row.token_out,
+            token_out_symbol: row.token_out_symbol,
+            token_out_decimals: row.token_out_decimals.and_then(|d| u8::try_from(d).ok()),
+            amount_out: row.amount_out,
+        }
+    }
+}
+
+/// The swaps executed by one wallet transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionSwaps {
+    /// Wallet the transaction belongs to.
+    pub wallet_id: String,
+    /// Transaction hash.
+    pub hash: String,
+    /// Pool swaps in execution order.
+    pub swaps: Vec<SwapDetail>,
+    /// Token sold and bought overall; `None` when the swaps don't reduce to
+    /// one of each.
+    pub net: Option<NetSwap>,
+}
+
+/// Replaces the stored swaps of a wallet transaction.
+pub(crate) async fn save_swaps(
+    pool: &SqlitePool,
+    wallet_id: &str,
+    hash: &str,
+    swaps: &[SwapDetail],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM transaction_swaps WHERE wallet_id = ? AND hash = ?")
+        .bind(wallet_id)
+        .bind(hash)
+        .execute(&mut *tx)
+        .await?;
+
+    for (leg_index, swap) in swaps.iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO transaction_swaps (
+                wallet_id, hash, leg_index, protocol, pool,
+                token_in, token_in_symbol, token_in_decimals, amount_in,
+                token_out, token_out_symbol, token_out_decimals, amount_out
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(wallet_id)
+        .bind(hash)
+        .bind(leg_index as i64)
+        .bind(&swap.protocol)
+        .bind(&swap.pool)
+        .bind(&swap.token_in)
+        .bind(&swap.token_in_symbol)
+        .bind(swap.token_in_decimals.map(i64::from))
+        .bind(&swap.amount_in)
+        .bind(&swap.token_out)
+        .bind(&swap.token_out_symbol)
+        .bind(swap.token_out_decimals.map(i64::from))
+        .bind(&swap.amount_out)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await
+}
+
+/// Swaps of one wallet transaction, in execution order.
+pub(crate) async fn load_transaction_swaps(
+    pool: &SqlitePool,
+    wallet_id: &str,
+    hash: &str,
+) -> Result<Vec<SwapDetail>, sqlx::Error> {
+    let rows: Vec<SwapRow> = sqlx::query_as(
+        r#"
+        SELECT wallet_id, hash, protocol, pool,
+               token_in, token_in_symbol, token_in_decimals, amount_in,
+               token_out, token_out_symbol, token_out_decimals, amount_out
+        FROM transaction_swaps
+        WHERE wallet_id = ? AND hash = ?
+        ORDER BY leg_index
+        "#,
+    )
+    .bind(wallet_id)
+    .bind(hash)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(SwapDetail::from).collect())
+}
+
+/// Swaps of every transaction in a profile's wallets, grouped by
+/// transaction in the order stored.
+pub async fn load_profile_swaps(
+    pool: &SqlitePool,
+    profile_id: &str,
+) -> Result<Vec<TransactionSwaps>, sqlx::Error> {
+    let rows: Vec<SwapRow> = sqlx::query_as(
+        r#"
+        SELECT s.wallet_id, s.hash, s.protocol, s.pool,
+               s.token_in, s.token_in_symbol, s.token_in_decimals, s.amount_in,
+               s.token_out, s.token_out_symbol, s.token_out_decimals, s.amount_out
+        FROM transaction_swaps s
+        JOIN wallets w ON w.id = s.wallet_id
+        WHERE w.profile_id = ?
+        ORDER BY s.wallet_id, s.hash, s.leg_index
+        "#,
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut grouped: Vec<TransactionSwaps> = Vec::new();
+    for row in rows {
+        match grouped.last_mut() {
+            Some(last) if last.wallet_id == row.wallet_id && last.hash == row.hash => {
+                last.swaps.push(row.into());
+            }
+            _ => grouped.push(TransactionSwaps {
+                wallet_id: row.wallet_id.clone(),
+                hash: row.hash.clone(),
+                swaps: vec![row.into()],
+                net: None,
+            }),
+        }
+    }
+    for transaction in &mut grouped {
+        transaction.net = NetSwap::from_swaps(&transaction.swaps);
+    }
+
+    Ok(grouped)
+}
+
+/// Deletes the stored swaps of every transaction of a wallet.
+pub async fn delete_wallet_swaps<'e>(
+    executor: impl SqliteExecutor<'e>,
+    wallet_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM transaction_swaps WHERE wallet_id = ?")
+        .bind(wallet_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn leg(token_in: &str, amount_in: &str, token_out: &str, amount_out: &str) -> SwapDetail {
+        SwapDetail {
+            protocol: "uniswap_v3".to_string(),
+            pool: format!("{}-{}", token_in, token_out),
+            token_in: token_in.to_string(),
+            token_in_symbol: Some(token_in.to_uppercase()),
+            token_in_decimals: Some(18),
+            amount_in: amount_in.to_string(),
+            token_out: token_out.to_string(),
+            token_out_symbol: None,
+            token_out_decimals: None,
+            amount_out: amount_out.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_profile_swaps() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(include_str!(
+            "../../../migrations/20260415000001_create_transaction_swaps.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE wallets (id TEXT PRIMARY KEY, profile_id TEXT);
+            INSERT INTO wallets VALUES ('w1', 'p1'), ('w2', 'p2');
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // USDC -> WETH -> UNI, saved twice to check the replace
+        let route = vec![
+            leg("usdc", "1000", "weth", "5"),
+            leg("weth", "5", "uni", "70"),
+        ];
+        save_swaps(&pool, "w1", "0xaa", &route[..1]).await.unwrap();
+        save_swaps(&pool, "w1", "0xaa", &route).await.unwrap();
+        save_swaps(&pool, "w2", "0xbb", &route).await.unwrap();
+
+        let swaps = load_profile_swaps(&pool, "p1").await.unwrap();
+        assert_eq!(swaps.len(), 1);
+        assert_eq!(swaps[0].hash, "0xaa");
+        assert_eq!(swaps[0].swaps, route);
+
+        let net = swaps[0].net.as_ref().unwrap();
+        assert_eq!(
+            (net.token_in.as_str(), net.amount_in.as_str()),
+            ("usdc", "1000")
+        );
+        assert_eq!(
+            (net.token_out.as_str(), net.amount_out.as_str()),
+            ("uni", "70")
+        );
+        assert_eq!(net.token_in_symbol.as_deref(), Some("USDC"));
+
+        delete_wallet_swaps(&pool, "w1").await.unwrap();
+        assert!(load_profile_swaps(&pool, "p1").await.unwrap().is_empty());
+    }
+}
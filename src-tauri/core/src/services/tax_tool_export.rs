@@ -0,0 +1,619 @@
+//! Transaction history in the import formats of other crypto tax tools.
+//!
+//! Users moving over often keep Koinly, CoinTracking, or Accointing running
+//! alongside for a while. These exports write a profile's transactions as
+//! each tool's CSV import expects, with our transaction types mapped to the
+//! tool's labels. Decoded swaps become single trades, fees are only
+//! charged on the side that paid them, and transfers between the
+//! profile's own wallets are marked internal where the tool has a label
+//! for it.
+
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use csv::Writer;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use super::address_watch::native_currency;
+use super::entity_statements::hides_stored;
+use super::export_templates::{
+    count_transactions, direction, transaction_page, ExportProgress, PageCursor, EXPORT_PAGE_SIZE,
+};
+use super::persistence::{StoredTransaction, Wallet};
+use super::profile_scope::profile_wallets;
+use super::statement_export::{parse_amount, parse_period_bound};
+use super::swaps::load_profile_swaps;
+use super::token_spam::SpamFilter;
+use crate::chains::NetSwap;
+use crate::jobs::CancelToken;
+
+/// A tax tool whose import format can be written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaxToolFormat {
+    /// Koinly's universal CSV template.
+    Koinly,
+    /// CoinTracking's CSV import.
+    CoinTracking,
+    /// Accointing's XLSX template, saved as CSV.
+    Accointing,
+}
+
+impl FromStr for TaxToolFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "koinly" => Ok(Self::Koinly),
+            "cointracking" => Ok(Self::CoinTracking),
+            "accointing" => Ok(Self::Accointing),
+            _ => Err(format!("Unsupported export format: {}", s)),
+        }
+    }
+}
+
+impl TaxToolFormat {
+    fn headers(self) -> &'static [&'static str] {
+        match self {
+            Self::Koinly => &[
+                "Date",
+                "Sent Amount",
+                "Sent Currency",
+                "Received Amount",
+                "Received Currency",
+                "Fee Amount",
+                "Fee Currency",
+                "Net Worth Amount",
+                "Net Worth Currency",
+                "Label",
+                "Description",
+                "TxHash",
+            ],
+            Self::CoinTracking => &[
+                "Type",
+                "Buy Amount",
+                "Buy Currency",
+                "Sell Amount",
+                "Sell Currency",
+                "Fee",
+                "Fee Currency",
+                "Exchange",
+                "Trade-Group",
+                "Comment",
+                "Date",
+                "Tx-ID",
+            ],
+            Self::Accointing => &[
+                "transactionType",
+                "date",
+                "inBuyAmount",
+                "inBuyAsset",
+                "outSellAmount",
+                "outSellAsset",
+                "feeAmount (optional)",
+                "feeAsset (optional)",
+                "classification (optional)",
+                "operationId (optional)",
+                "comments (optional)",
+            ],
+        }
+    }
+}
+
+/// What a record is, before it's named in a tool's terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Activity {
+    /// Funds received.
+    Deposit,
+    /// Funds sent.
+    Withdrawal,
+    /// One asset swapped for another.
+    Trade,
+    /// Funds added to a liquidity pool.
+    LiquidityIn,
+    /// Funds taken out of a liquidity pool.
+    LiquidityOut,
+    /// Funds staked.
+    Stake,
+    /// Funds unstaked.
+    Unstake,
+    /// Profit realized on, or funding received for, a derivatives position.
+    MarginGain,
+    /// Loss realized on a derivatives position.
+    MarginLoss,
+    /// Funding paid for a derivatives position.
+    MarginFee,
+    /// A network fee paid without moving any value.
+    Fee,
+}
+
+/// One transaction in the terms every format shares.
+#[derive(Debug, Clone, PartialEq)]
+struct ToolRecord {
+    date: Option<DateTime<Utc>>,
+    activity: Activity,
+    /// Moved between two of the profile's own wallets.
+    internal: bool,
+    /// Amount and asset that left the wallet.
+    sent: Option<(Decimal, String)>,
+    /// Amount and asset that came in.
+    received: Option<(Decimal, String)>,
+    /// Fee the wallet paid, and its asset.
+    fee: Option<(Decimal, String)>,
+    /// Wallet name, or its address when unnamed.
+    wallet: String,
+    /// Our transaction type, kept as a note.
+    tx_type: String,
+    hash: String,
+}
+
+/// Turns a stored transaction into a record, or `None` when it moved
+/// nothing the wallet paid or received. `swap` is the transaction's netted
+/// swap, if one was decoded; `own` holds the profile's wallet addresses,
+/// lowercased.
+fn tool_record(
+    tx: &StoredTransaction,
+    wallet: &Wallet,
+    swap: Option<&NetSwap>,
+    own: &HashSet<String>,
+) -> Option<ToolRecord> {
+    let from = tx
+        .from_address
+        .as_deref()
+        .unwrap_or_default()
+        .to_lowercase();
+    let to = tx.to_address.as_deref().unwrap_or_default().to_lowercase();
+    let direction = direction(&wallet.address.to_lowercase(), &from, &to);
+    let tx_type = tx.tx_type.clone().unwrap_or_default();
+
+    let (native_symbol, native_decimals) = native_currency(&tx.chain);
+    let fee = match direction {
+        Some("out") | Some("self") => tx
+            .fee
+            .as_deref()
+            .and_then(|fee| parse_amount(fee, Some(native_decimals)))
+            .filter(|fee| !fee.is_zero())
+            .map(|fee| (fee, native_symbol.clone())),
+        _ => None,
+    };
+    let (asset, decimals) = match &tx.token_symbol {
+        Some(symbol) => (symbol.clone(), tx.token_decimals),
+        None => (native_symbol, Some(native_decimals)),
+    };
+    let amount = tx
+        .value
+        .as_deref()
+        .and_then(|value| parse_amount(value, decimals))
+        .filter(|amount| !amount.is_zero())
+        .map(|amount| (amount, asset));
+
+    let mut record = ToolRecord {
+        date: tx.timestamp,
+        activity: Activity::Fee,
+        internal: false,
+        sent: None,
+        received: None,
+        fee,
+        wallet: wallet
+            .name
+            .clone()
+            .unwrap_or_else(|| wallet.address.clone()),
+        tx_type: tx_type.clone(),
+        hash: tx.hash.clone(),
+    };
+
+    if let Some(swap) = swap.filter(|_| tx_type == "swap") {
+        let side = |amount: &str, symbol: &Option<String>, token: &str, decimals: Option<u8>| {
+            parse_amount(amount, decimals.map(i32::from))
+                .map(|amount| (amount, symbol.clone().unwrap_or_else(|| token.to_string())))
+        };
+        record.activity = Activity::Trade;
+        record.sent = side(
+            &swap.amount_in,
+            &swap.token_in_symbol,
+            &swap.token_in,
+            swap.token_in_decimals,
+        );
+        record.received = side(
+            &swap.amount_out,
+            &swap.token_out_symbol,
+            &swap.token_out,
+            swap.token_out_decimals,
+        );
+        return Some(record);
+    }
+
+    match (direction, amount) {
+        (Some("out"), Some(amount)) => {
+            record.internal = own.contains(&to);
+            record.activity = match tx_type.as_str() {
+                "add_liquidity" => Activity::LiquidityIn,
+                "stake" => Activity::Stake,
+                "funding_payment" => Activity::MarginFee,
+                "realized_pnl" => Activity::MarginLoss,
+                _ => Activity::Withdrawal,
+            };
+            record.sent = Some(amount);
+        }
+        (Some("in"), Some(amount)) => {
+            record.internal = own.contains(&from);
+            record.activity = match tx_type.as_str() {
+                "remove_liquidity" => Activity::LiquidityOut,
+                "unstake" => Activity::Unstake,
+                "funding_payment" | "realized_pnl" => Activity::MarginGain,
+                _ => Activity::Deposit,
+            };
+            record.received = Some(amount);
+        }
+        _ if record.fee.is_some() => {}
+        _ => return None,
+    }
+    Some(record)
+}
+
+fn number(value: &Option<(Decimal, String)>) -> (String, String) {
+    match value {
+        Some((amount, asset)) => (amount.normalize().to_string(), asset.clone()),
+        None => (String::new(), String::new()),
+    }
+}
+
+/// A record as a row of `format`.
+fn format_row(format: TaxToolFormat, record: &ToolRecord) -> Vec<String> {
+    let (sent, sent_asset) = number(&record.sent);
+    let (received, received_asset) = number(&record.received);
+    let (fee, fee_asset) = number(&record.fee);
+    let date = |pattern: &str| {
+        record
+            .date
+            .map(|date| date.format(pattern).to_string())
+            .unwrap_or_default()
+    };
+    // A fee on its own is recorded as the amount spent
+    let (sent, sent_asset, fee, fee_asset) = match record.activity {
+        Activity::Fee => (fee, fee_asset, String::new(), String::new()),
+        _ => (sent, sent_asset, fee, fee_asset),
+    };
+
+    match format {
+        TaxToolFormat::Koinly => {
+            let label = match record.activity {
+                Activity::LiquidityIn => "liquidity in",
+                Activity::LiquidityOut => "liquidity out",
+                Activity::MarginGain | Activity::MarginLoss => "realized gain",
+                Activity::MarginFee => "margin fee",
+                Activity::Fee => "cost",
+                _ => "",
+            };
+            vec![
+                date("%Y-%m-%d %H:%M:%S UTC"),
+                sent,
+                sent_asset,
+                received,
+                received_asset,
+                fee,
+                fee_asset,
+                String::new(),
+                String::new(),
+                label.to_string(),
+                record.tx_type.clone(),
+                record.hash.clone(),
+            ]
+        }
+        TaxToolFormat::CoinTracking => {
+            let kind = match record.activity {
+                Activity::Trade => "Trade",
+                Activity::Deposit | Activity::LiquidityOut | Activity::Unstake => "Deposit",
+                Activity::Withdrawal | Activity::LiquidityIn | Activity::Stake => "Withdrawal",
+                Activity::MarginGain => "Margin Profit",
+                Activity::MarginLoss => "Margin Loss",
+                Activity::MarginFee => "Margin Fee",
+                Activity::Fee => "Other Fee",
+            };
+            vec![
+                kind.to_string(),
+                received,
+                received_asset,
+                sent,
+                sent_asset,
+                fee,
+                fee_asset,
+                record.wallet.clone(),
+                String::new(),
+                record.tx_type.clone(),
+                date("%Y-%m-%d %H:%M:%S"),
+                record.hash.clone(),
+            ]
+        }
+        TaxToolFormat::Accointing => {
+            let (kind, classification) = match record.activity {
+                Activity::Trade => ("order", ""),
+                _ if record.internal && record.received.is_some() => ("deposit", "internal"),
+                _ if record.internal => ("withdraw", "internal"),
+                Activity::Deposit | Activity::Unstake => ("deposit", ""),
+                Activity::Withdrawal | Activity::Stake => ("withdraw", ""),
+                Activity::LiquidityOut => ("deposit", "liquidity_pool"),
+                Activity::LiquidityIn => ("withdraw", "liquidity_pool"),
+                Activity::MarginGain => ("deposit", "margin_gain"),
+                Activity::MarginLoss => ("withdraw", "margin_loss"),
+                Activity::MarginFee => ("withdraw", "margin_fee"),
+                Activity::Fee => ("withdraw", "fee"),
+            };
+            vec![
+                kind.to_string(),
+                date("%m/%d/%Y %H:%M:%S"),
+                received,
+                received_asset,
+                sent,
+                sent_asset,
+                fee,
+                fee_asset,
+                classification.to_string(),
+                record.hash.clone(),
+                record.tx_type.clone(),
+            ]
+        }
+    }
+}
+
+/// Writes a profile's transactions between `start_date` and `end_date` to
+/// `out` in `format`, leaving out merged duplicates and spam tokens.
+/// Transactions are read a page at a time like other exports, calling
+/// `on_progress` after each page and stopping between pages once `cancel`
+/// is cancelled. Returns `out` and the number of rows written.
+#[allow(clippy::too_many_arguments)]
+pub async fn write_tax_tool_export<W: Write>(
+    pool: &SqlitePool,
+    profile_id: &str,
+    format: TaxToolFormat,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+    out: W,
+    cancel: &CancelToken,
+    mut progress: ExportProgress,
+    mut on_progress: impl FnMut(&ExportProgress),
+) -> Result<(W, usize), String> {
+    let start = parse_period_bound(start_date, false)?;
+    let end = parse_period_bound(end_date, true)?;
+    let filter = SpamFilter::load(pool, Some(profile_id))
+        .await
+        .map_err(|e| e.to_string())?;
+    let wallets: HashMap<String, Wallet> = profile_wallets(pool, profile_id)
+        .await?
+        .into_iter()
+        .map(|w| (w.id.clone(), w))
+        .collect();
+    let own: HashSet<String> = wallets.values().map(|w| w.address.to_lowercase()).collect();
+    let swaps: HashMap<(String, String), NetSwap> = load_profile_swaps(pool, profile_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter_map(|t| Some(((t.wallet_id, t.hash.to_lowercase()), t.net?)))
+        .collect();
+    // A swap is one trade however many of its transfers were stored
+    let mut traded = HashSet::new();
+
+    let mut writer = Writer::from_writer(out);
+    writer
+        .write_record(format.headers())
+        .map_err(|e| e.to_string())?;
+
+    progress.total = count_transactions(pool, profile_id, start, end).await?;
+    let mut cursor = PageCursor::default();
+    loop {
+        cancel.check()?;
+        let page =
+            transaction_page(pool, profile_id, start, end, &cursor, EXPORT_PAGE_SIZE).await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        cursor = PageCursor::after(last);
+
+        for tx in page.iter().filter(|tx| !hides_stored(&filter, tx)) {
+            let Some(wallet) = wallets.get(&tx.wallet_id) else {
+                continue;
+            };
+            let key = (tx.wallet_id.clone(), tx.hash.to_lowercase());
+            let swap = swaps.get(&key);
+            if swap.is_some() && traded.contains(&key) {
+                continue;
+            }
+            let Some(record) = tool_record(tx, wallet, swap, &own) else {
+                continue;
+            };
+            if record.activity == Activity::Trade {
+                traded.insert(key);
+            }
+            writer
+                .write_record(format_row(format, &record))
+                .map_err(|e| e.to_string())?;
+            progress.written += 1;
+        }
+        writer.flush().map_err(|e| e.to_string())?;
+
+        progress.processed += page.len();
+        on_progress(&progress);
+    }
+
+    let out = writer.into_inner().map_err(|e| e.to_string())?;
+    Ok((out, progress.written))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const WALLET: &str = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045";
+    const COLD: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+    const OTHER: &str = "0x1111111254eeb25477b68fb85ed929f73a960582";
+
+    fn wallet() -> Wallet {
+        Wallet {
+            id: "w1".to_string(),
+            profile_id: "p1".to_string(),
+            address: WALLET.to_string(),
+            chain: "ethereum".to_string(),
+            name: Some("Treasury".to_string()),
+            wallet_type: "software".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+        }
+    }
+
+    fn tx(tx_type: &str, from: &str, to: &str, value: &str) -> StoredTransaction {
+        let at = Utc.with_ymd_and_hms(2025, 3, 4, 12, 30, 0).unwrap();
+        StoredTransaction {
+            id: "t1".to_string(),
+            wallet_id: "w1".to_string(),
+            hash: "0xabc".to_string(),
+            block_number: Some(100),
+            timestamp: Some(at),
+            from_address: Some(from.to_string()),
+            to_address: Some(to.to_string()),
+            value: Some(value.to_string()),
+            fee: Some("21000000000000".to_string()),
+            status: Some("success".to_string()),
+            tx_type: Some(tx_type.to_string()),
+            token_symbol: None,
+            token_decimals: None,
+            chain: "ethereum".to_string(),
+            raw_data: None,
+            created_at: at,
+        }
+    }
+
+    fn own() -> HashSet<String> {
+        HashSet::from([WALLET.to_string(), COLD.to_string()])
+    }
+
+    #[test]
+    fn test_parses_formats() {
+        assert_eq!(
+            "CoinTracking".parse::<TaxToolFormat>().unwrap(),
+            TaxToolFormat::CoinTracking
+        );
+        assert!("cointracker".parse::<TaxToolFormat>().is_err());
+    }
+
+    #[test]
+    fn test_maps_transfers_and_fees() {
+        // Fees are only charged on the paying side
+        let received = tool_record(
+            &tx("transfer", OTHER, WALLET, "2000000000000000000"),
+            &wallet(),
+            None,
+            &own(),
+        )
+        .unwrap();
+        assert_eq!(received.activity, Activity::Deposit);
+        assert_eq!(received.fee, None);
+
+        let moved = tool_record(
+            &tx("transfer", WALLET, COLD, "1000000000000000000"),
+            &wallet(),
+            None,
+            &own(),
+        )
+        .unwrap();
+        assert!(moved.internal);
+        assert_eq!(
+            format_row(TaxToolFormat::Accointing, &moved)[..6],
+            ["withdraw", "03/04/2025 12:30:00", "", "", "1", "ETH"]
+        );
+        assert_eq!(format_row(TaxToolFormat::Accointing, &moved)[8], "internal");
+
+        let approval =
+            tool_record(&tx("approval", WALLET, OTHER, "0"), &wallet(), None, &own()).unwrap();
+        assert_eq!(
+            format_row(TaxToolFormat::Koinly, &approval),
+            [
+                "2025-03-04 12:30:00 UTC",
+                "0.000021",
+                "ETH",
+                "",
+                "",
+                "",
+                "",
+                "",
+                "",
+                "cost",
+                "approval",
+                "0xabc"
+            ]
+        );
+
+        assert_eq!(
+            tool_record(&tx("transfer", OTHER, WALLET, "0"), &wallet(), None, &own()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_maps_swaps_to_trades() {
+        let swap = NetSwap {
+            token_in: "0xa0b8".to_string(),
+            token_in_symbol: Some("USDC".to_string()),
+            token_in_decimals: Some(6),
+            amount_in: "2500000000".to_string(),
+            token_out: "0xc02a".to_string(),
+            token_out_symbol: Some("WETH".to_string()),
+            token_out_decimals: Some(18),
+            amount_out: "1000000000000000000".to_string(),
+        };
+        let record = tool_record(
+            &tx("swap", WALLET, OTHER, "0"),
+            &wallet(),
+            Some(&swap),
+            &own(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            format_row(TaxToolFormat::CoinTracking, &record),
+            [
+                "Trade",
+                "1",
+                "WETH",
+                "2500",
+                "USDC",
+                "0.000021",
+                "ETH",
+                "Treasury",
+                "",
+                "swap",
+                "2025-03-04 12:30:00",
+                "0xabc"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_maps_derivatives() {
+        let funding = tool_record(
+            &tx("funding_payment", WALLET, OTHER, "5000000000000000"),
+            &wallet(),
+            None,
+            &own(),
+        )
+        .unwrap();
+        assert_eq!(funding.activity, Activity::MarginFee);
+        assert_eq!(format_row(TaxToolFormat::Koinly, &funding)[9], "margin fee");
+
+        let profit = tool_record(
+            &tx("realized_pnl", OTHER, WALLET, "5000000000000000"),
+            &wallet(),
+            None,
+            &own(),
+        )
+        .unwrap();
+        assert_eq!(
+            format_row(TaxToolFormat::CoinTracking, &profit)[0],
+            "Margin Profit"
+        );
+    }
+}
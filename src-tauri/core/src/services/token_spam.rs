@@ -0,0 +1,222 @@
+//! Spam token filtering.
+//!
+//! Tokens can be marked spam or allowed per profile, and known spam
+//! contracts can be imported into a shared blocklist. Together with the
+//! heuristics in [`crate::core::spam`], these decide which tokens are hidden
+//! from balances and exports. An explicit allow always wins.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::chains::WalletBalances;
+use crate::core::spam;
+
+/// Whether a marked token is hidden or kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum TokenMarkStatus {
+    /// Hidden from balances and exports.
+    Spam,
+    /// Always shown, even if heuristics flag it.
+    Allowed,
+}
+
+/// Lookup key for a token: its address when known, otherwise its symbol.
+pub fn token_key(address: Option<&str>, symbol: Option<&str>) -> Option<String> {
+    let address = address.map(str::trim).filter(|a| !a.is_empty());
+    let symbol = symbol.map(str::trim).filter(|s| !s.is_empty());
+    match (address, symbol) {
+        (Some(address), _) => Some(address.to_lowercase()),
+        (None, Some(symbol)) => Some(format!("symbol:{}", symbol.to_uppercase())),
+        (None, None) => None,
+    }
+}
+
+/// Finds a token contract address in a transaction's raw data or metadata.
+fn contract_address_in(data: &serde_json::Value) -> Option<String> {
+    [
+        "contract_address",
+        "contractAddress",
+        "token_address",
+        "tokenAddress",
+    ]
+    .iter()
+    .find_map(|key| data.get(key).and_then(|v| v.as_str()))
+    .map(str::to_string)
+}
+
+/// Decides whether tokens are hidden for a profile.
+#[derive(Debug, Default)]
+pub struct SpamFilter {
+    spam: HashSet<(String, String)>,
+    allowed: HashSet<(String, String)>,
+    blocklist: HashSet<(String, String)>,
+}
+
+impl SpamFilter {
+    /// Loads the profile's marks and the shared blocklist.
+    pub async fn load(pool: &SqlitePool, profile_id: Option<&str>) -> Result<Self, sqlx::Error> {
+        let mut filter = SpamFilter::default();
+
+        if let Some(profile_id) = profile_id {
+            let marks: Vec<(String, String, TokenMarkStatus)> = sqlx::query_as(
+                "SELECT chain_id, token_key, status FROM token_marks WHERE profile_id = ?",
+            )
+            .bind(profile_id)
+            .fetch_all(pool)
+            .await?;
+            for (chain_id, key, status) in marks {
+                let entry = (chain_id.to_lowercase(), key);
+                match status {
+                    TokenMarkStatus::Spam => filter.spam.insert(entry),
+                    TokenMarkStatus::Allowed => filter.allowed.insert(entry),
+                };
+            }
+        }
+
+        let blocked: Vec<(String, String)> =
+            sqlx::query_as("SELECT chain_id, token_address FROM token_blocklist")
+                .fetch_all(pool)
+                .await?;
+        filter.blocklist = blocked
+            .into_iter()
+            .map(|(chain_id, address)| (chain_id.to_lowercase(), address.to_lowercase()))
+            .collect();
+
+        Ok(filter)
+    }
+
+    /// Whether the token is on the shared blocklist.
+    pub fn is_blocklisted(&self, chain_id: &str, address: Option<&str>) -> bool {
+        address.is_some_and(|a| {
+            self.blocklist
+                .contains(&(chain_id.to_lowercase(), a.to_lowercase()))
+        })
+    }
+
+    /// Keys a token may be marked under: its address and its symbol.
+    fn keys(chain_id: &str, address: Option<&str>, symbol: Option<&str>) -> Vec<(String, String)> {
+        let chain_id = chain_id.to_lowercase();
+        [token_key(address, None), token_key(None, symbol)]
+            .into_iter()
+            .flatten()
+            .map(|key| (chain_id.clone(), key))
+            .collect()
+    }
+
+    /// Whether the user has explicitly allowed the token.
+    pub fn is_allowed(&self, chain_id: &str, address: Option<&str>, symbol: Option<&str>) -> bool {
+        Self::keys(chain_id, address, symbol)
+            .iter()
+            .any(|k| self.allowed.contains(k))
+    }
+
+    /// Whether a token should be hidden.
+    pub fn is_hidden(
+        &self,
+        chain_id: &str,
+        address: Option<&str>,
+        symbol: Option<&str>,
+        name: Option<&str>,
+    ) -> bool {
+        if self.is_allowed(chain_id, address, symbol) {
+            return false;
+        }
+        let marked = Self::keys(chain_id, address, symbol)
+            .iter()
+            .any(|k| self.spam.contains(k));
+        if marked || self.is_blocklisted(chain_id, address) {
+            return true;
+        }
+        [name, symbol]
+            .into_iter()
+            .flatten()
+            .any(spam::has_suspicious_name)
+    }
+
+    /// Whether a transaction moves a hidden token, judging by its symbol and
+    /// any contract address in its raw data.
+    pub fn hides_transaction(
+        &self,
+        chain_id: &str,
+        symbol: Option<&str>,
+        data: Option<&serde_json::Value>,
+    ) -> bool {
+        let address = data.and_then(contract_address_in);
+        // Without an address, only explicit marks apply; a bare symbol is too
+        // weak to hide on heuristics alone.
+        if address.is_none() {
+            let key = token_key(None, symbol).map(|k| (chain_id.to_lowercase(), k));
+            return key.is_some_and(|k| self.spam.contains(&k) && !self.allowed.contains(&k));
+        }
+        self.is_hidden(chain_id, address.as_deref(), symbol, None)
+    }
+
+    /// Removes hidden tokens from a balance snapshot.
+    pub fn filter_balances(&self, balances: &mut WalletBalances) {
+        let chain_id = balances.chain_id.clone();
+        balances.token_balances.retain(|token| {
+            !self.is_hidden(
+                &chain_id,
+                Some(&token.token_address),
+                token.token_symbol.as_deref(),
+                token.token_name.as_deref(),
+            )
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter() -> SpamFilter {
+        let mut filter = SpamFilter::default();
+        filter
+            .spam
+            .insert(("ethereum".to_string(), "0xspam".to_string()));
+        filter
+            .allowed
+            .insert(("ethereum".to_string(), "symbol:CLAIMR".to_string()));
+        filter
+            .blocklist
+            .insert(("ethereum".to_string(), "0xblocked".to_string()));
+        filter
+    }
+
+    #[test]
+    fn test_token_key() {
+        assert_eq!(
+            token_key(Some(" 0xABC "), Some("usdc")),
+            Some("0xabc".to_string())
+        );
+        assert_eq!(
+            token_key(None, Some("usdc")),
+            Some("symbol:USDC".to_string())
+        );
+        assert_eq!(token_key(Some(""), None), None);
+    }
+
+    #[test]
+    fn test_is_hidden() {
+        let filter = filter();
+        assert!(filter.is_hidden("Ethereum", Some("0xSPAM"), Some("OK"), None));
+        assert!(filter.is_hidden("ethereum", Some("0xblocked"), None, None));
+        assert!(filter.is_hidden("ethereum", Some("0x1"), Some("visit x.xyz"), None));
+        // An explicit allow overrides the heuristics.
+        assert!(!filter.is_hidden("ethereum", Some("0x2"), Some("CLAIMR"), None));
+        assert!(!filter.is_hidden("ethereum", Some("0x3"), Some("USDC"), None));
+    }
+
+    #[test]
+    fn test_hides_transaction() {
+        let filter = filter();
+        let data = serde_json::json!({ "contractAddress": "0xSpam" });
+        assert!(filter.hides_transaction("ethereum", Some("USDC"), Some(&data)));
+        // A bare symbol isn't enough for the heuristics.
+        assert!(!filter.hides_transaction("ethereum", Some("visit x.xyz"), None));
+    }
+}
@@ -0,0 +1,210 @@
+//! EIP-1559 fee breakdowns of wallet transactions.
+//!
+//! Wallet sync stores how each EVM transaction's fee divides into the base
+//! fee burned and the tip paid to the block producer, along with the part
+//! of a type-2 transaction's max fee that was refunded, the blob fee of
+//! an EIP-4844 transaction and, on rollups, the L1 data fee. The fee
+//! posted as an expense is always the total actually charged.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteExecutor, SqlitePool};
+
+use crate::chains::FeeBreakdown;
+
+/// Replaces the stored fee breakdown of a wallet transaction.
+pub(crate) async fn save_fee_breakdown(
+    pool: &SqlitePool,
+    wallet_id: &str,
+    hash: &str,
+    breakdown: &FeeBreakdown,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO transaction_fees (
+            wallet_id, hash, gas_used, effective_gas_price,
+            base_fee, priority_fee, refunded, l1_data_fee, blob_fee, total
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(wallet_id, hash) DO UPDATE SET
+            gas_used = excluded.gas_used,
+            effective_gas_price = excluded.effective_gas_price,
+            base_fee = excluded.base_fee,
+            priority_fee = excluded.priority_fee,
+            refunded = excluded.refunded,
+            l1_data_fee = excluded.l1_data_fee,
+            blob_fee = excluded.blob_fee,
+            total = excluded.total
+        "#,
+    )
+    .bind(wallet_id)
+    .bind(hash)
+    .bind(&breakdown.gas_used)
+    .bind(&breakdown.effective_gas_price)
+    .bind(&breakdown.base_fee)
+    .bind(&breakdown.priority_fee)
+    .bind(&breakdown.refunded)
+    .bind(&breakdown.l1_data_fee)
+    .bind(&breakdown.blob_fee)
+    .bind(&breakdown.total)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// A stored fee breakdown.
+#[derive(Debug, Clone, FromRow)]
+struct FeeRow {
+    wallet_id: String,
+    hash: String,
+    gas_used: String,
+    effective_gas_price: String,
+    base_fee: Option<String>,
+    priority_fee: Option<String>,
+    refunded: Option<String>,
+    l1_data_fee: Option<String>,
+    blob_fee: Option<String>,
+    total: String,
+}
+
+/// The fee breakdown of one wallet transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionFee {
+    /// Wallet the transaction belongs to.
+    pub wallet_id: String,
+    /// Transaction hash.
+    pub hash: String,
+    /// How the fee divides.
+    pub breakdown: FeeBreakdown,
+}
+
+impl From<FeeRow> for TransactionFee {
+    fn from(row: FeeRow) -> Self {
+        Self {
+            wallet_id: row.wallet_id,
+            hash: row.hash,
+            breakdown: FeeBreakdown {
+                gas_used: row.gas_used,
+                effective_gas_price: row.effective_gas_price,
+                base_fee: row.base_fee,
+                priority_fee: row.priority_fee,
+                refunded: row.refunded,
+                l1_data_fee: row.l1_data_fee,
+                blob_fee: row.blob_fee,
+                total: row.total,
+            },
+        }
+    }
+}
+
+/// Deletes the stored fee breakdowns of every transaction of a wallet.
+pub async fn delete_wallet_fee_breakdowns<'e>(
+    executor: impl SqliteExecutor<'e>,
+    wallet_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM transaction_fees WHERE wallet_id = ?")
+        .bind(wallet_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Fee breakdowns of every transaction in a profile's wallets.
+pub async fn load_profile_fee_breakdowns(
+    pool: &SqlitePool,
+    profile_id: &str,
+) -> Result<Vec<TransactionFee>, sqlx::Error> {
+    let rows: Vec<FeeRow> = sqlx::query_as(
+        r#"
+        SELECT f.wallet_id, f.hash, f.gas_used, f.effective_gas_price,
+               f.base_fee, f.priority_fee, f.refunded, f.l1_data_fee,
+               f.blob_fee, f.total
+        FROM transaction_fees f
+        JOIN wallets w ON w.id = f.wallet_id
+        WHERE w.profile_id = ?
+        ORDER BY f.wallet_id, f.hash
+        "#,
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(TransactionFee::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn breakdown(total: &str) -> FeeBreakdown {
+        FeeBreakdown {
+            gas_used: "21000".to_string(),
+            effective_gas_price: "12000000000".to_string(),
+            base_fee: Some("210000000000000".to_string()),
+            priority_fee: Some("42000000000000".to_string()),
+            refunded: None,
+            l1_data_fee: Some("9000000000000".to_string()),
+            blob_fee: None,
+            total: total.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_profile_fee_breakdowns() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(include_str!(
+            "../../../migrations/20260427000001_create_transaction_fees.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(include_str!(
+            "../../../migrations/20260428000001_add_transaction_l1_data_fee.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(include_str!(
+            "../../../migrations/20260429000001_add_transaction_blob_fee.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE wallets (id TEXT PRIMARY KEY, profile_id TEXT);
+            INSERT INTO wallets VALUES ('w1', 'p1'), ('w2', 'p2');
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // Saved twice to check the replace
+        save_fee_breakdown(&pool, "w1", "0xaa", &breakdown("1"))
+            .await
+            .unwrap();
+        save_fee_breakdown(&pool, "w1", "0xaa", &breakdown("252000000000000"))
+            .await
+            .unwrap();
+        save_fee_breakdown(&pool, "w2", "0xbb", &breakdown("1"))
+            .await
+            .unwrap();
+
+        let fees = load_profile_fee_breakdowns(&pool, "p1").await.unwrap();
+        assert_eq!(fees.len(), 1);
+        assert_eq!(fees[0].hash, "0xaa");
+        assert_eq!(fees[0].breakdown, breakdown("252000000000000"));
+
+        delete_wallet_fee_breakdowns(&pool, "w1").await.unwrap();
+        assert!(load_profile_fee_breakdowns(&pool, "p1")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,490 @@
+//! Wallet transaction sync with per-wallet status and progress events.
+//!
+//! Syncing a wallet fetches its transactions since the last synced block
+//! through the chain manager and saves them in pages. Progress is kept in
+//! `address_sync_status` and reported through [`SyncEvents`]; in the app
+//! these are Tauri events, so each wallet can show its own progress bar:
+//!
+//! - `sync:started` when a wallet begins syncing,
+//! - `sync:page` after each page of transactions is saved,
+//! - `sync:completed` when the wallet is up to date,
+//! - `sync:error` when fetching or saving fails, or the sync is cancelled.
+//!
+//! In the app each wallet syncs as its own queued job, so syncs are limited
+//! per chain and retried when they fail. The last complete
+//! block is saved after every page, so a cancelled or retried sync resumes
+//! where it stopped.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+use super::address_watch::native_currency;
+use super::confirmations::{required_confirmations, run_confirmation_checks, status_at_depth};
+use super::persistence::{store_transactions, TransactionInput, Wallet};
+use super::profile_scope::profile_wallets;
+use crate::chains::{ChainManagerState, ChainTransaction};
+use crate::db::multi_chain::MultiChainRepository;
+use crate::jobs::CancelToken;
+
+/// Event emitted when a wallet starts syncing.
+pub const SYNC_STARTED_EVENT: &str = "sync:started";
+
+/// Event emitted after each page of transactions is saved.
+pub const SYNC_PAGE_EVENT: &str = "sync:page";
+
+/// Event emitted when a wallet finishes syncing.
+pub const SYNC_COMPLETED_EVENT: &str = "sync:completed";
+
+/// Event emitted when a wallet fails to sync.
+pub const SYNC_ERROR_EVENT: &str = "sync:error";
+
+/// Transactions saved per page.
+const SYNC_PAGE_SIZE: usize = 50;
+
+/// Sync status of one wallet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletSyncStatus {
+    /// Wallet the status belongs to.
+    pub wallet_id: String,
+    /// Wallet display name, if set.
+    pub wallet_name: Option<String>,
+    /// Chain the wallet is on.
+    pub chain: String,
+    /// Wallet address.
+    pub address: String,
+    /// `idle`, `syncing`, `error`, or `never` for wallets not yet synced.
+    pub state: String,
+    /// When the wallet last finished syncing.
+    pub last_sync_at: Option<DateTime<Utc>>,
+    /// Highest block synced.
+    pub last_block: Option<i64>,
+    /// Why the last sync failed, if it did.
+    pub error_message: Option<String>,
+}
+
+/// A wallet joined with its `address_sync_status` row.
+#[derive(Debug, FromRow)]
+struct SyncStatusRow {
+    wallet_id: String,
+    wallet_name: Option<String>,
+    chain: String,
+    address: String,
+    sync_state: Option<String>,
+    last_sync_timestamp: Option<i64>,
+    last_block_synced: Option<i64>,
+    error_message: Option<String>,
+}
+
+impl From<SyncStatusRow> for WalletSyncStatus {
+    fn from(row: SyncStatusRow) -> Self {
+        Self {
+            wallet_id: row.wallet_id,
+            wallet_name: row.wallet_name,
+            chain: row.chain,
+            address: row.address,
+            state: row.sync_state.unwrap_or_else(|| "never".to_string()),
+            last_sync_at: row
+                .last_sync_timestamp
+                .and_then(|t| Utc.timestamp_opt(t, 0).single()),
+            last_block: row.last_block_synced.filter(|b| *b > 0),
+            error_message: row.error_message,
+        }
+    }
+}
+
+/// Payload of the `sync:*` events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncProgress {
+    /// Profile the wallet belongs to.
+    pub profile_id: String,
+    /// Wallet being synced.
+    pub wallet_id: String,
+    /// Chain the wallet is on.
+    pub chain: String,
+    /// Wallet address.
+    pub address: String,
+    /// Pages saved so far.
+    pub page: usize,
+    /// Transactions saved so far.
+    pub processed: usize,
+    /// Transactions fetched in this sync; zero until the fetch returns.
+    pub total: usize,
+    /// Last block whose transactions are all saved.
+    pub last_block: Option<i64>,
+    /// Why the sync failed, for `sync:error`.
+    pub error: Option<String>,
+}
+
+impl SyncProgress {
+    fn new(wallet: &Wallet, last_block: Option<i64>) -> Self {
+        Self {
+            profile_id: wallet.profile_id.clone(),
+            wallet_id: wallet.id.clone(),
+            chain: wallet.chain.clone(),
+            address: wallet.address.clone(),
+            page: 0,
+            processed: 0,
+            total: 0,
+            last_block,
+            error: None,
+        }
+    }
+}
+
+/// Receives the `sync:*` events of a sync run.
+pub trait SyncEvents: Send + Sync {
+    /// Reports `event` with the wallet's progress so far.
+    fn emit(&self, event: &str, progress: &SyncProgress);
+}
+
+/// Serialized name of a chain enum such as a status or transaction type.
+pub fn enum_name<T: Serialize>(value: &T) -> Option<String> {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+}
+
+/// Converts a fetched transaction into the form stored for a wallet. The
+/// token is the first token transferred, or the chain's native currency. A
+/// success short of `required` confirmations with the chain at `tip` is
+/// stored as pending.
+fn to_transaction_input(
+    tx: &ChainTransaction,
+    chain: &str,
+    tip: Option<u64>,
+    required: u32,
+) -> TransactionInput {
+    let (token_symbol, token_decimals) = match tx.token_transfers.first() {
+        Some(transfer) => (
+            transfer.token_symbol.clone(),
+            transfer.token_decimals.map(i32::from),
+        ),
+        None => {
+            let (symbol, decimals) = native_currency(chain);
+            (Some(symbol), Some(decimals))
+        }
+    };
+
+    TransactionInput {
+        hash: tx.hash.clone(),
+        block_number: Some(tx.block_number as i64),
+        timestamp: Utc
+            .timestamp_opt(tx.timestamp, 0)
+            .single()
+            .map(|t| t.to_rfc3339()),
+        from_address: Some(tx.from.clone()),
+        to_address: tx.to.clone(),
+        value: Some(tx.value.clone()),
+        fee: Some(tx.fee.clone()),
+        status: enum_name(&status_at_depth(tx.status, tx.block_number, tip, required)),
+        tx_type: enum_name(&tx.tx_type),
+        token_symbol,
+        token_decimals,
+        chain: chain.to_string(),
+        raw_data: tx.raw_data.as_ref().map(|data| data.to_string()),
+        swaps: tx.swaps.clone(),
+        token_transfers: tx.token_transfers.clone(),
+        fee_breakdown: tx.fee_breakdown.clone(),
+    }
+}
+
+/// Sync status of each of a profile's wallets, oldest wallet first.
+pub async fn load_sync_statuses(
+    pool: &SqlitePool,
+    profile_id: &str,
+) -> Result<Vec<WalletSyncStatus>, sqlx::Error> {
+    let rows: Vec<SyncStatusRow> = sqlx::query_as(
+        r#"
+        SELECT
+            w.id AS wallet_id, w.name AS wallet_name, w.chain, w.address,
+            s.sync_state, s.last_sync_timestamp, s.last_block_synced, s.error_message
+        FROM wallets w
+        LEFT JOIN address_sync_status s
+            ON s.chain_id = w.chain AND LOWER(s.address) = LOWER(w.address)
+        WHERE w.profile_id = ?
+        ORDER BY w.created_at
+        "#,
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(WalletSyncStatus::from).collect())
+}
+
+/// Last block whose transactions are all saved once `saved` of the sorted
+/// `transactions` are. A block split across pages isn't complete until its
+/// last transaction is saved.
+fn completed_block(transactions: &[ChainTransaction], saved: usize) -> Option<i64> {
+    let last = transactions.get(saved.checked_sub(1)?)?.block_number as i64;
+    match transactions.get(saved) {
+        Some(next) if next.block_number as i64 == last => Some(last - 1),
+        _ => Some(last),
+    }
+}
+
+/// Fetches a wallet's new transactions and saves them page by page,
+/// emitting `sync:page` after each. Successes short of the profile's
+/// confirmation threshold are saved as pending. The last complete block is
+/// recorded after every page, so a cancelled or failed sync resumes from
+/// there. Returns the last block synced.
+async fn fetch_and_store(
+    events: &dyn SyncEvents,
+    pool: &SqlitePool,
+    chains: &ChainManagerState,
+    user_id: &str,
+    wallet: &Wallet,
+    progress: &mut SyncProgress,
+    cancel: &CancelToken,
+) -> Result<Option<i64>, String> {
+    let repository = MultiChainRepository::new(pool.clone());
+    let from_block = progress.last_block.map(|b| b.max(0) as u64 + 1);
+    let required = required_confirmations(pool, &wallet.profile_id, &wallet.chain).await?;
+    let (mut transactions, tip) = {
+        let manager = chains.read().await;
+        let transactions = cancel
+            .run(manager.get_transactions(&wallet.chain, &wallet.address, from_block))
+            .await?
+            .map_err(|e| e.to_string())?;
+        // Any mined transaction has one confirmation, so the tip is only
+        // needed for deeper thresholds
+        let tip = if required > 1 {
+            let tip = cancel.run(manager.get_block_number(&wallet.chain)).await?;
+            Some(tip.map_err(|e| e.to_string())?)
+        } else {
+            None
+        };
+        (transactions, tip)
+    };
+    transactions.sort_by_key(|tx| tx.block_number);
+    progress.total = transactions.len();
+
+    for page in transactions.chunks(SYNC_PAGE_SIZE) {
+        cancel.check()?;
+        let inputs = page
+            .iter()
+            .map(|tx| to_transaction_input(tx, &wallet.chain, tip, required))
+            .collect();
+        store_transactions(pool, user_id, wallet, inputs).await?;
+
+        progress.page += 1;
+        progress.processed += page.len();
+        progress.last_block =
+            completed_block(&transactions, progress.processed).max(progress.last_block);
+        if let Some(block) = progress.last_block {
+            repository
+                .set_sync_progress(&wallet.chain, &wallet.address, block)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        events.emit(SYNC_PAGE_EVENT, progress);
+    }
+
+    // Everything fetched is saved, so the last block fetched is complete.
+    let fetched_last = transactions.last().map(|tx| tx.block_number as i64);
+    Ok(fetched_last.max(progress.last_block))
+}
+
+/// Syncs one wallet, recording its state and emitting its events. Failures
+/// are recorded on the wallet as well as returned.
+async fn sync_wallet(
+    events: &dyn SyncEvents,
+    pool: &SqlitePool,
+    chains: &ChainManagerState,
+    user_id: &str,
+    wallet: &Wallet,
+    cancel: &CancelToken,
+) -> Result<(), String> {
+    cancel.check()?;
+    let repository = MultiChainRepository::new(pool.clone());
+    let previous = repository
+        .get_sync_status(&wallet.chain, &wallet.address)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut progress = SyncProgress::new(
+        wallet,
+        previous.map(|s| s.last_block_synced).filter(|b| *b > 0),
+    );
+
+    repository
+        .set_sync_started(&wallet.chain, &wallet.address)
+        .await
+        .map_err(|e| e.to_string())?;
+    events.emit(SYNC_STARTED_EVENT, &progress);
+
+    let synced = async {
+        let last_block =
+            fetch_and_store(events, pool, chains, user_id, wallet, &mut progress, cancel).await?;
+        // Transactions held as pending by earlier syncs aren't fetched again
+        cancel
+            .run(run_confirmation_checks(pool, chains, Some(&wallet.id)))
+            .await??;
+        Ok::<_, String>(last_block)
+    }
+    .await;
+    match synced {
+        Ok(last_block) => {
+            progress.last_block = last_block;
+            repository
+                .update_sync_status(&wallet.chain, &wallet.address, last_block.unwrap_or(0))
+                .await
+                .map_err(|e| e.to_string())?;
+            // New transactions mean the cached balances are stale.
+            chains
+                .read()
+                .await
+                .invalidate_balances(&wallet.chain, &wallet.address);
+            events.emit(SYNC_COMPLETED_EVENT, &progress);
+            Ok(())
+        }
+        Err(e) => {
+            repository
+                .set_sync_error(&wallet.chain, &wallet.address, &e)
+                .await
+                .map_err(|e| e.to_string())?;
+            progress.error = Some(e.clone());
+            events.emit(SYNC_ERROR_EVENT, &progress);
+            Err(e)
+        }
+    }
+}
+
+/// Runs a queued wallet sync job.
+pub async fn run_wallet_sync(
+    events: &dyn SyncEvents,
+    pool: &SqlitePool,
+    chains: &ChainManagerState,
+    profile_id: &str,
+    user_id: &str,
+    wallet_id: &str,
+    cancel: &CancelToken,
+) -> Result<(), String> {
+    let wallet = profile_wallets(pool, profile_id)
+        .await?
+        .into_iter()
+        .find(|w| w.id == wallet_id)
+        .ok_or_else(|| {
+            format!(
+                "Wallet {} is no longer in profile {}",
+                wallet_id, profile_id
+            )
+        })?;
+    sync_wallet(events, pool, chains, user_id, &wallet, cancel).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::{ChainId, TokenTransfer, TransactionStatus, TransactionType};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn tx(token_transfers: Vec<TokenTransfer>) -> ChainTransaction {
+        ChainTransaction {
+            hash: "0xabc".to_string(),
+            chain_id: ChainId::evm("ethereum", 1),
+            block_number: 42,
+            timestamp: 1_700_000_000,
+            from: "0x1".to_string(),
+            to: Some("0x2".to_string()),
+            value: "1000".to_string(),
+            fee: "21".to_string(),
+            status: TransactionStatus::Success,
+            tx_type: TransactionType::ContractCall,
+            token_transfers,
+            swaps: Vec::new(),
+            fee_breakdown: None,
+            user_operations: Vec::new(),
+            inner_calls: Vec::new(),
+            raw_data: None,
+        }
+    }
+
+    #[test]
+    fn test_to_transaction_input() {
+        let native = to_transaction_input(&tx(Vec::new()), "ethereum", None, 12);
+        assert_eq!(native.block_number, Some(42));
+        assert_eq!(native.status.as_deref(), Some("success"));
+        assert_eq!(native.tx_type.as_deref(), Some("contract_call"));
+        assert_eq!(native.token_symbol.as_deref(), Some("ETH"));
+        assert!(native.timestamp.unwrap().starts_with("2023-11-14"));
+
+        let token = to_transaction_input(
+            &tx(vec![TokenTransfer {
+                token_address: "0xusdc".to_string(),
+                token_symbol: Some("USDC".to_string()),
+                token_decimals: Some(6),
+                from: "0x1".to_string(),
+                to: "0x2".to_string(),
+                value: "5".to_string(),
+            }]),
+            "ethereum",
+            Some(60),
+            12,
+        );
+        assert_eq!(token.token_symbol.as_deref(), Some("USDC"));
+        // Block 42 has 19 confirmations with the tip at 60
+        assert_eq!(token.status.as_deref(), Some("success"));
+
+        let shallow = to_transaction_input(&tx(Vec::new()), "ethereum", Some(50), 12);
+        assert_eq!(shallow.status.as_deref(), Some("pending"));
+        assert_eq!(token.token_decimals, Some(6));
+    }
+
+    #[test]
+    fn test_completed_block_waits_for_split_blocks() {
+        let at = |block| ChainTransaction {
+            block_number: block,
+            ..tx(Vec::new())
+        };
+        let txs = vec![at(10), at(11), at(11), at(12)];
+        assert_eq!(completed_block(&txs, 0), None);
+        assert_eq!(completed_block(&txs, 1), Some(10));
+        assert_eq!(completed_block(&txs, 2), Some(10));
+        assert_eq!(completed_block(&txs, 3), Some(11));
+        assert_eq!(completed_block(&txs, 4), Some(12));
+    }
+
+    #[tokio::test]
+    async fn test_load_sync_statuses() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE wallets (
+                id TEXT PRIMARY KEY, profile_id TEXT, address TEXT, chain TEXT,
+                name TEXT, created_at TEXT
+            );
+            CREATE TABLE address_sync_status (
+                chain_id TEXT, address TEXT, last_block_synced INTEGER DEFAULT 0,
+                last_sync_timestamp INTEGER, sync_state TEXT DEFAULT 'idle',
+                error_message TEXT
+            );
+            INSERT INTO wallets VALUES
+                ('w1', 'p1', '0xAbC', 'ethereum', 'Main', '2026-01-01'),
+                ('w2', 'p1', '0xdef', 'ethereum', NULL, '2026-01-02'),
+                ('w3', 'p2', '0x999', 'ethereum', NULL, '2026-01-03');
+            INSERT INTO address_sync_status
+                (chain_id, address, last_block_synced, last_sync_timestamp, sync_state, error_message)
+            VALUES ('ethereum', '0xabc', 1200, 1760000000, 'error', 'rate limited');
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let statuses = load_sync_statuses(&pool, "p1").await.unwrap();
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].state, "error");
+        assert_eq!(statuses[0].last_block, Some(1200));
+        assert_eq!(statuses[0].error_message.as_deref(), Some("rate limited"));
+        assert!(statuses[0].last_sync_at.is_some());
+        assert_eq!(statuses[1].state, "never");
+        assert_eq!(statuses[1].last_block, None);
+    }
+}
@@ -0,0 +1,51 @@
+-- =============================================================================
+-- BUDGETS
+-- Grant and program budgets with actuals tracked from tagged transactions
+-- =============================================================================
+
+-- Budgets scoped to a profile. A budget targets a category, an entity, or both,
+-- over an inclusive date range. Amounts are stored as decimal strings.
+CREATE TABLE IF NOT EXISTS budgets (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    category TEXT,
+    entity_id TEXT,
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    amount TEXT NOT NULL,
+    currency TEXT NOT NULL DEFAULT 'USD',
+    notes TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (entity_id) REFERENCES entities(id) ON DELETE SET NULL,
+    CHECK (category IS NOT NULL OR entity_id IS NOT NULL),
+    CHECK (period_end >= period_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_budgets_profile ON budgets(profile_id);
+CREATE INDEX IF NOT EXISTS idx_budgets_period ON budgets(profile_id, period_start, period_end);
+
+-- Budget tags on transactions. transaction_id is the id of a stored wallet
+-- transaction or a multi-chain transaction. The tagged amount is the value allocated
+-- to the category/entity in the budget currency, which may differ from the raw
+-- on-chain value (partial allocations, fiat conversion).
+CREATE TABLE IF NOT EXISTS transaction_tags (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    transaction_id TEXT NOT NULL,
+    category TEXT NOT NULL,
+    entity_id TEXT,
+    amount TEXT NOT NULL,
+    occurred_at DATETIME NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (entity_id) REFERENCES entities(id) ON DELETE SET NULL,
+    UNIQUE(transaction_id, category)
+);
+
+CREATE INDEX IF NOT EXISTS idx_transaction_tags_profile_date
+    ON transaction_tags(profile_id, occurred_at);
+CREATE INDEX IF NOT EXISTS idx_transaction_tags_transaction
+    ON transaction_tags(transaction_id);
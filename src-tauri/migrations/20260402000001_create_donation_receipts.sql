@@ -0,0 +1,38 @@
+-- =============================================================================
+-- DONATION RECEIPTS
+-- Receipts issued by charities for crypto donations, with fiat valuation
+-- =============================================================================
+
+-- Receipt numbers are sequential per profile per calendar year of receipt.
+-- Amounts and prices are stored as decimal strings.
+CREATE TABLE IF NOT EXISTS donation_receipts (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    receipt_number TEXT NOT NULL,
+    receipt_year INTEGER NOT NULL,
+    sequence INTEGER NOT NULL,
+    donor_entity_id TEXT,
+    donor_name TEXT NOT NULL,
+    asset_symbol TEXT NOT NULL,
+    coin_id TEXT,
+    amount TEXT NOT NULL,
+    fiat_currency TEXT NOT NULL,
+    fiat_price TEXT NOT NULL,
+    fiat_value TEXT NOT NULL,
+    price_source TEXT NOT NULL,
+    chain TEXT,
+    tx_hash TEXT,
+    received_at DATETIME NOT NULL,
+    notes TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (donor_entity_id) REFERENCES entities(id) ON DELETE SET NULL,
+    UNIQUE(profile_id, receipt_year, sequence)
+);
+
+CREATE INDEX IF NOT EXISTS idx_donation_receipts_profile_year
+    ON donation_receipts(profile_id, receipt_year);
+CREATE INDEX IF NOT EXISTS idx_donation_receipts_donor
+    ON donation_receipts(donor_entity_id);
+CREATE INDEX IF NOT EXISTS idx_donation_receipts_tx_hash
+    ON donation_receipts(tx_hash);
@@ -0,0 +1,49 @@
+-- =============================================================================
+-- RECURRING TRANSACTION SERIES
+-- Detected recurring patterns (payroll, subscriptions, vesting claims)
+-- =============================================================================
+
+-- A series groups transactions with the same counterparty, direction, and
+-- asset that recur at a regular cadence with similar amounts.
+CREATE TABLE IF NOT EXISTS recurring_series (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    wallet_id TEXT NOT NULL,
+    counterparty TEXT NOT NULL,
+    direction TEXT NOT NULL CHECK (direction IN ('incoming', 'outgoing')),
+    token_symbol TEXT NOT NULL DEFAULT '',
+    cadence TEXT NOT NULL CHECK (cadence IN ('weekly', 'biweekly', 'monthly', 'quarterly', 'yearly')),
+    interval_days INTEGER NOT NULL,
+    typical_amount TEXT NOT NULL,
+    amount_tolerance TEXT NOT NULL,
+    occurrence_count INTEGER NOT NULL DEFAULT 0,
+    confidence REAL NOT NULL DEFAULT 0,
+    first_seen_at DATETIME NOT NULL,
+    last_seen_at DATETIME NOT NULL,
+    next_expected_at DATETIME NOT NULL,
+    label TEXT,
+    -- When set, future occurrences are tagged with this budget category
+    auto_tag_category TEXT,
+    auto_tag_entity_id TEXT,
+    is_active INTEGER NOT NULL DEFAULT 1,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (auto_tag_entity_id) REFERENCES entities(id) ON DELETE SET NULL,
+    UNIQUE(wallet_id, counterparty, direction, token_symbol)
+);
+
+CREATE INDEX IF NOT EXISTS idx_recurring_series_profile ON recurring_series(profile_id);
+CREATE INDEX IF NOT EXISTS idx_recurring_series_wallet ON recurring_series(wallet_id);
+
+-- Transactions that belong to a series
+CREATE TABLE IF NOT EXISTS recurring_series_members (
+    series_id TEXT NOT NULL,
+    transaction_id TEXT NOT NULL,
+    occurred_at DATETIME NOT NULL,
+    PRIMARY KEY (series_id, transaction_id),
+    FOREIGN KEY (series_id) REFERENCES recurring_series(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_recurring_members_transaction
+    ON recurring_series_members(transaction_id);
@@ -0,0 +1,48 @@
+-- =============================================================================
+-- ADDRESS WATCHES
+-- Monitored addresses (own or third-party) with activity alerts
+-- =============================================================================
+
+-- A watched address. Transactions moving at least `threshold` (in native
+-- units, decimal string) raise an alert. last_block is NULL until the first
+-- check establishes a baseline, so existing history does not trigger alerts.
+CREATE TABLE IF NOT EXISTS address_watches (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    chain_id TEXT NOT NULL,
+    address TEXT NOT NULL,
+    label TEXT,
+    is_own INTEGER NOT NULL DEFAULT 0,
+    threshold TEXT NOT NULL DEFAULT '0',
+    notify_email TEXT,
+    is_active INTEGER NOT NULL DEFAULT 1,
+    last_block INTEGER,
+    last_checked_at DATETIME,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    UNIQUE(profile_id, chain_id, address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_address_watches_active ON address_watches(is_active);
+
+-- Alerts raised for watched addresses
+CREATE TABLE IF NOT EXISTS address_watch_alerts (
+    id TEXT PRIMARY KEY,
+    watch_id TEXT NOT NULL,
+    tx_hash TEXT NOT NULL,
+    direction TEXT NOT NULL CHECK (direction IN ('incoming', 'outgoing')),
+    amount TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    counterparty TEXT,
+    block_number INTEGER NOT NULL,
+    occurred_at DATETIME NOT NULL,
+    email_sent INTEGER NOT NULL DEFAULT 0,
+    is_read INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (watch_id) REFERENCES address_watches(id) ON DELETE CASCADE,
+    UNIQUE(watch_id, tx_hash)
+);
+
+CREATE INDEX IF NOT EXISTS idx_address_watch_alerts_watch
+    ON address_watch_alerts(watch_id, occurred_at DESC);
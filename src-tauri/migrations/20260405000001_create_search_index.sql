@@ -0,0 +1,249 @@
+-- =============================================================================
+-- FULL-TEXT SEARCH
+-- FTS5 index over transactions, entities, addresses, tags, and notes
+-- =============================================================================
+
+-- One row per searchable record. kind + record_id identify the source row;
+-- parent_id points at the record a result should open (the transaction for a
+-- tag, the entity for an address). profile_id is NULL for records that are
+-- not profile-scoped (multi-chain transactions).
+CREATE TABLE IF NOT EXISTS search_documents (
+    id INTEGER PRIMARY KEY,
+    kind TEXT NOT NULL,
+    record_id TEXT NOT NULL,
+    profile_id TEXT,
+    parent_id TEXT,
+    title TEXT NOT NULL,
+    body TEXT NOT NULL,
+    UNIQUE(kind, record_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_documents_profile ON search_documents(profile_id);
+
+-- External-content FTS5 index over search_documents. The unicode61 tokenizer
+-- keeps hashes and addresses as single tokens, so prefix queries match them.
+CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+    title,
+    body,
+    content = 'search_documents',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER IF NOT EXISTS search_documents_ai AFTER INSERT ON search_documents BEGIN
+    INSERT INTO search_index(rowid, title, body) VALUES (new.id, new.title, new.body);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_documents_ad AFTER DELETE ON search_documents BEGIN
+    INSERT INTO search_index(search_index, rowid, title, body)
+        VALUES ('delete', old.id, old.title, old.body);
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_documents_au AFTER UPDATE ON search_documents BEGIN
+    INSERT INTO search_index(search_index, rowid, title, body)
+        VALUES ('delete', old.id, old.title, old.body);
+    INSERT INTO search_index(rowid, title, body) VALUES (new.id, new.title, new.body);
+END;
+
+-- transactions
+CREATE TRIGGER IF NOT EXISTS search_transactions_ai AFTER INSERT ON transactions BEGIN
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('transaction', new.id, new.profile_id, NULL, new.hash,
+        new.from_address || ' ' || COALESCE(new.to_address, '') || ' ' || new.chain || ' ' || new.token_symbol || ' ' || COALESCE(new.metadata, ''))
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_transactions_au AFTER UPDATE ON transactions BEGIN
+    DELETE FROM search_documents WHERE kind = 'transaction' AND record_id = old.id AND old.id <> new.id;
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('transaction', new.id, new.profile_id, NULL, new.hash,
+        new.from_address || ' ' || COALESCE(new.to_address, '') || ' ' || new.chain || ' ' || new.token_symbol || ' ' || COALESCE(new.metadata, ''))
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_transactions_ad AFTER DELETE ON transactions BEGIN
+    DELETE FROM search_documents WHERE kind = 'transaction' AND record_id = old.id;
+END;
+
+INSERT OR IGNORE INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+SELECT 'transaction', t.id, t.profile_id, NULL, t.hash,
+    t.from_address || ' ' || COALESCE(t.to_address, '') || ' ' || t.chain || ' ' || t.token_symbol || ' ' || COALESCE(t.metadata, '')
+FROM transactions t;
+
+-- multi_chain_transactions
+CREATE TRIGGER IF NOT EXISTS search_multi_chain_transactions_ai AFTER INSERT ON multi_chain_transactions BEGIN
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('chain_transaction', new.id, NULL, NULL, new.hash,
+        new.from_address || ' ' || COALESCE(new.to_address, '') || ' ' || new.chain_id || ' ' || new.tx_type)
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_multi_chain_transactions_au AFTER UPDATE ON multi_chain_transactions BEGIN
+    DELETE FROM search_documents WHERE kind = 'chain_transaction' AND record_id = old.id AND old.id <> new.id;
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('chain_transaction', new.id, NULL, NULL, new.hash,
+        new.from_address || ' ' || COALESCE(new.to_address, '') || ' ' || new.chain_id || ' ' || new.tx_type)
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_multi_chain_transactions_ad AFTER DELETE ON multi_chain_transactions BEGIN
+    DELETE FROM search_documents WHERE kind = 'chain_transaction' AND record_id = old.id;
+END;
+
+INSERT OR IGNORE INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+SELECT 'chain_transaction', t.id, NULL, NULL, t.hash,
+    t.from_address || ' ' || COALESCE(t.to_address, '') || ' ' || t.chain_id || ' ' || t.tx_type
+FROM multi_chain_transactions t;
+
+-- entities
+CREATE TRIGGER IF NOT EXISTS search_entities_ai AFTER INSERT ON entities BEGIN
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('entity', new.id, new.profile_id, NULL, COALESCE(new.display_name, new.name),
+        new.name || ' ' || COALESCE(new.email, '') || ' ' || COALESCE(new.category, '') || ' ' || COALESCE(new.tags, '') || ' ' || COALESCE(new.default_wallet_address, '') || ' ' || COALESCE(new.notes, ''))
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_entities_au AFTER UPDATE ON entities BEGIN
+    DELETE FROM search_documents WHERE kind = 'entity' AND record_id = old.id AND old.id <> new.id;
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('entity', new.id, new.profile_id, NULL, COALESCE(new.display_name, new.name),
+        new.name || ' ' || COALESCE(new.email, '') || ' ' || COALESCE(new.category, '') || ' ' || COALESCE(new.tags, '') || ' ' || COALESCE(new.default_wallet_address, '') || ' ' || COALESCE(new.notes, ''))
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_entities_ad AFTER DELETE ON entities BEGIN
+    DELETE FROM search_documents WHERE kind = 'entity' AND record_id = old.id;
+END;
+
+INSERT OR IGNORE INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+SELECT 'entity', t.id, t.profile_id, NULL, COALESCE(t.display_name, t.name),
+    t.name || ' ' || COALESCE(t.email, '') || ' ' || COALESCE(t.category, '') || ' ' || COALESCE(t.tags, '') || ' ' || COALESCE(t.default_wallet_address, '') || ' ' || COALESCE(t.notes, '')
+FROM entities t;
+
+-- entity_addresses
+CREATE TRIGGER IF NOT EXISTS search_entity_addresses_ai AFTER INSERT ON entity_addresses BEGIN
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('entity_address', new.id, (SELECT profile_id FROM entities WHERE id = new.entity_id), new.entity_id, new.address,
+        COALESCE(new.label, '') || ' ' || new.chain || ' ' || COALESCE(new.address_type, ''))
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_entity_addresses_au AFTER UPDATE ON entity_addresses BEGIN
+    DELETE FROM search_documents WHERE kind = 'entity_address' AND record_id = old.id AND old.id <> new.id;
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('entity_address', new.id, (SELECT profile_id FROM entities WHERE id = new.entity_id), new.entity_id, new.address,
+        COALESCE(new.label, '') || ' ' || new.chain || ' ' || COALESCE(new.address_type, ''))
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_entity_addresses_ad AFTER DELETE ON entity_addresses BEGIN
+    DELETE FROM search_documents WHERE kind = 'entity_address' AND record_id = old.id;
+END;
+
+INSERT OR IGNORE INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+SELECT 'entity_address', t.id, (SELECT profile_id FROM entities WHERE id = t.entity_id), t.entity_id, t.address,
+    COALESCE(t.label, '') || ' ' || t.chain || ' ' || COALESCE(t.address_type, '')
+FROM entity_addresses t;
+
+-- transaction_tags
+CREATE TRIGGER IF NOT EXISTS search_transaction_tags_ai AFTER INSERT ON transaction_tags BEGIN
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('tag', new.id, new.profile_id, new.transaction_id, new.category,
+        new.transaction_id)
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_transaction_tags_au AFTER UPDATE ON transaction_tags BEGIN
+    DELETE FROM search_documents WHERE kind = 'tag' AND record_id = old.id AND old.id <> new.id;
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('tag', new.id, new.profile_id, new.transaction_id, new.category,
+        new.transaction_id)
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_transaction_tags_ad AFTER DELETE ON transaction_tags BEGIN
+    DELETE FROM search_documents WHERE kind = 'tag' AND record_id = old.id;
+END;
+
+INSERT OR IGNORE INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+SELECT 'tag', t.id, t.profile_id, t.transaction_id, t.category,
+    t.transaction_id
+FROM transaction_tags t;
+
+-- budgets
+CREATE TRIGGER IF NOT EXISTS search_budgets_ai AFTER INSERT ON budgets BEGIN
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('budget', new.id, new.profile_id, NULL, new.name,
+        COALESCE(new.category, '') || ' ' || COALESCE(new.notes, ''))
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_budgets_au AFTER UPDATE ON budgets BEGIN
+    DELETE FROM search_documents WHERE kind = 'budget' AND record_id = old.id AND old.id <> new.id;
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('budget', new.id, new.profile_id, NULL, new.name,
+        COALESCE(new.category, '') || ' ' || COALESCE(new.notes, ''))
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_budgets_ad AFTER DELETE ON budgets BEGIN
+    DELETE FROM search_documents WHERE kind = 'budget' AND record_id = old.id;
+END;
+
+INSERT OR IGNORE INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+SELECT 'budget', t.id, t.profile_id, NULL, t.name,
+    COALESCE(t.category, '') || ' ' || COALESCE(t.notes, '')
+FROM budgets t;
+
+-- donation_receipts
+CREATE TRIGGER IF NOT EXISTS search_donation_receipts_ai AFTER INSERT ON donation_receipts BEGIN
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('donation_receipt', new.id, new.profile_id, NULL, new.receipt_number || ' ' || new.donor_name,
+        new.asset_symbol || ' ' || COALESCE(new.tx_hash, '') || ' ' || COALESCE(new.notes, ''))
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_donation_receipts_au AFTER UPDATE ON donation_receipts BEGIN
+    DELETE FROM search_documents WHERE kind = 'donation_receipt' AND record_id = old.id AND old.id <> new.id;
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('donation_receipt', new.id, new.profile_id, NULL, new.receipt_number || ' ' || new.donor_name,
+        new.asset_symbol || ' ' || COALESCE(new.tx_hash, '') || ' ' || COALESCE(new.notes, ''))
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_donation_receipts_ad AFTER DELETE ON donation_receipts BEGIN
+    DELETE FROM search_documents WHERE kind = 'donation_receipt' AND record_id = old.id;
+END;
+
+INSERT OR IGNORE INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+SELECT 'donation_receipt', t.id, t.profile_id, NULL, t.receipt_number || ' ' || t.donor_name,
+    t.asset_symbol || ' ' || COALESCE(t.tx_hash, '') || ' ' || COALESCE(t.notes, '')
+FROM donation_receipts t;
@@ -0,0 +1,15 @@
+-- =============================================================================
+-- PROFILE TAX SETTINGS
+-- Tax jurisdiction and lot selection method elected per accounting profile
+-- =============================================================================
+
+-- The jurisdiction decides pooling and holding-period rules in the
+-- cost-basis engine; the method only applies where lots are matched
+-- individually (US, Germany).
+CREATE TABLE IF NOT EXISTS profile_tax_settings (
+    profile_id TEXT PRIMARY KEY,
+    jurisdiction TEXT NOT NULL DEFAULT 'us' CHECK (jurisdiction IN ('us', 'uk', 'ca', 'de')),
+    cost_basis_method TEXT NOT NULL DEFAULT 'FIFO' CHECK (cost_basis_method IN ('FIFO', 'LIFO', 'HIFO')),
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);
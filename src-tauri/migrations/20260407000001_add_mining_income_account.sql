@@ -0,0 +1,7 @@
+-- =============================================================================
+-- MINING INCOME ACCOUNT
+-- Income account for block rewards, used when classifying mining payouts
+-- =============================================================================
+
+INSERT OR IGNORE INTO gl_accounts (account_number, account_name, account_type, normal_balance, is_editable, description) VALUES
+    ('4600', 'Mining Income',             'Income',    'credit', 1, 'Block rewards from mining');
@@ -0,0 +1,32 @@
+-- =============================================================================
+-- TOKEN SPAM LISTS
+-- Per-profile spam/allow marks and a shared blocklist of spam contracts
+-- =============================================================================
+
+-- token_key is the lowercased contract address, or 'symbol:<SYMBOL>' for
+-- tokens identified only by symbol. An 'allowed' mark overrides both the
+-- blocklist and the heuristics.
+CREATE TABLE IF NOT EXISTS token_marks (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    chain_id TEXT NOT NULL,
+    token_key TEXT NOT NULL,
+    token_address TEXT,
+    token_symbol TEXT,
+    status TEXT NOT NULL CHECK (status IN ('spam', 'allowed')),
+    reason TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    UNIQUE (profile_id, chain_id, token_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_token_marks_profile ON token_marks(profile_id);
+
+-- Known spam contracts imported from public lists; addresses are lowercased.
+CREATE TABLE IF NOT EXISTS token_blocklist (
+    chain_id TEXT NOT NULL,
+    token_address TEXT NOT NULL,
+    source TEXT,
+    added_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (chain_id, token_address)
+);
@@ -0,0 +1,32 @@
+-- =============================================================================
+-- PERIOD CLOSES
+-- Closed accounting periods per profile, and profile ownership of journal entries
+-- =============================================================================
+
+-- A closed period rejects edits, deletes, and re-tags of anything dated inside
+-- it. Reopening keeps the row with who reopened it and why; the event is also
+-- written to auth_audit_log.
+CREATE TABLE IF NOT EXISTS period_closes (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    period_type TEXT NOT NULL CHECK (period_type IN ('month', 'quarter', 'year')),
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    status TEXT NOT NULL DEFAULT 'closed' CHECK (status IN ('closed', 'reopened')),
+    closed_by TEXT NOT NULL,
+    closed_at DATETIME NOT NULL,
+    reopened_by TEXT,
+    reopened_at DATETIME,
+    reopen_reason TEXT,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    UNIQUE (profile_id, period_start, period_end)
+);
+
+CREATE INDEX IF NOT EXISTS idx_period_closes_lookup
+    ON period_closes(status, period_start, period_end);
+
+-- Entries created before this migration have no profile and are checked
+-- against every profile's closed periods.
+ALTER TABLE journal_entries ADD COLUMN profile_id TEXT REFERENCES profiles(id);
+
+CREATE INDEX IF NOT EXISTS idx_journal_entries_profile ON journal_entries(profile_id);
@@ -0,0 +1,40 @@
+-- =============================================================================
+-- DATA AUDIT LOG
+-- Append-only history of creates, updates, and deletes of financial records
+-- =============================================================================
+
+-- before_data and after_data are JSON snapshots of the record; changes holds
+-- only the top-level fields that differ, as {"field": {"before": .., "after": ..}}.
+CREATE TABLE IF NOT EXISTS data_audit_log (
+    id TEXT PRIMARY KEY,
+    record_type TEXT NOT NULL CHECK (record_type IN (
+        'wallet', 'transaction', 'raw_transaction', 'transaction_tag',
+        'entity', 'entity_address', 'journal_entry'
+    )),
+    record_id TEXT NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('create', 'update', 'delete')),
+    actor TEXT NOT NULL,
+    profile_id TEXT,
+    before_data TEXT,
+    after_data TEXT,
+    changes TEXT NOT NULL,
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_audit_log_record
+    ON data_audit_log(record_type, record_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_data_audit_log_profile
+    ON data_audit_log(profile_id, created_at);
+
+-- History can be appended to but never rewritten.
+CREATE TRIGGER IF NOT EXISTS data_audit_log_no_update
+BEFORE UPDATE ON data_audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'data_audit_log is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS data_audit_log_no_delete
+BEFORE DELETE ON data_audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'data_audit_log is append-only');
+END;
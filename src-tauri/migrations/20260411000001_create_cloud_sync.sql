@@ -0,0 +1,33 @@
+-- =============================================================================
+-- CLOUD SYNC
+-- Device state and merge bookkeeping for end-to-end encrypted sync
+-- =============================================================================
+
+-- This device's sync identity and progress. Only one row exists.
+CREATE TABLE IF NOT EXISTS cloud_sync_state (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    device_id TEXT NOT NULL,
+    -- rowid of the last data_audit_log entry pushed
+    last_pushed_rowid INTEGER NOT NULL DEFAULT 0,
+    last_push_at DATETIME,
+    last_pull_at DATETIME,
+    last_error TEXT,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Change-sets from other devices that have been merged
+CREATE TABLE IF NOT EXISTS cloud_sync_applied (
+    object_key TEXT PRIMARY KEY,
+    device_id TEXT NOT NULL,
+    change_count INTEGER NOT NULL,
+    applied_at DATETIME NOT NULL
+);
+
+-- Latest known change per record, for last-writer-wins merging
+CREATE TABLE IF NOT EXISTS cloud_sync_versions (
+    record_type TEXT NOT NULL,
+    record_id TEXT NOT NULL,
+    changed_at DATETIME NOT NULL,
+    device_id TEXT NOT NULL,
+    PRIMARY KEY (record_type, record_id)
+);
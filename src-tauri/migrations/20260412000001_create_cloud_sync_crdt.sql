@@ -0,0 +1,49 @@
+-- =============================================================================
+-- CLOUD SYNC MERGE STATE
+-- Per-record vector clocks, per-field write stamps, and merge conflicts
+-- =============================================================================
+
+-- Replaced by the vector clocks below
+DROP TABLE IF EXISTS cloud_sync_versions;
+
+-- Vector clock of every synced record: JSON object of device ID to the
+-- highest change counter from that device this device has seen
+CREATE TABLE IF NOT EXISTS cloud_sync_clocks (
+    record_type TEXT NOT NULL,
+    record_id TEXT NOT NULL,
+    clock TEXT NOT NULL,
+    PRIMARY KEY (record_type, record_id)
+);
+
+-- The change that last wrote each field of a record
+CREATE TABLE IF NOT EXISTS cloud_sync_field_stamps (
+    record_type TEXT NOT NULL,
+    record_id TEXT NOT NULL,
+    field TEXT NOT NULL,
+    device_id TEXT NOT NULL,
+    counter INTEGER NOT NULL,
+    changed_at DATETIME NOT NULL,
+    PRIMARY KEY (record_type, record_id, field)
+);
+
+-- Concurrent edits that couldn't both be kept
+CREATE TABLE IF NOT EXISTS cloud_sync_conflicts (
+    id TEXT PRIMARY KEY,
+    record_type TEXT NOT NULL,
+    record_id TEXT NOT NULL,
+    profile_id TEXT,
+    -- NULL when one side deleted the record
+    field TEXT,
+    kept TEXT NOT NULL CHECK (kept IN ('local', 'remote')),
+    -- JSON values; NULL stands for a deleted record
+    local_value TEXT,
+    remote_value TEXT,
+    local_changed_at DATETIME NOT NULL,
+    remote_changed_at DATETIME NOT NULL,
+    remote_device TEXT NOT NULL,
+    detected_at DATETIME NOT NULL,
+    resolved_at DATETIME
+);
+
+CREATE INDEX IF NOT EXISTS idx_cloud_sync_conflicts_open
+    ON cloud_sync_conflicts(resolved_at, detected_at);
@@ -0,0 +1,25 @@
+-- =============================================================================
+-- PRICE OVERRIDES
+-- Manual token prices that take precedence over price provider data
+-- =============================================================================
+
+-- token is a CoinGecko coin ID or an asset symbol, matched case-insensitively.
+-- An override without valid_from/valid_to applies at all times; a ranged one
+-- applies from valid_from (inclusive) to valid_to (exclusive) and beats a
+-- fixed one for the same token.
+CREATE TABLE IF NOT EXISTS price_overrides (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    token TEXT NOT NULL COLLATE NOCASE,
+    currency TEXT NOT NULL,
+    price TEXT NOT NULL,
+    valid_from DATETIME,
+    valid_to DATETIME,
+    note TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_price_overrides_lookup
+    ON price_overrides(profile_id, token, currency);
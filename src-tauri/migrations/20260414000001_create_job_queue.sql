@@ -0,0 +1,16 @@
+-- =============================================================================
+-- JOB QUEUE
+-- Background jobs waiting to run, kept so they survive a restart
+-- =============================================================================
+
+-- Rows are removed once a job finishes, fails for good, or is cancelled.
+-- Jobs that were running when the app closed are queued again on start;
+-- syncs save their progress as they go, so they resume rather than restart.
+CREATE TABLE IF NOT EXISTS job_queue (
+    id TEXT PRIMARY KEY,
+    task TEXT NOT NULL,              -- JSON-encoded task
+    priority TEXT NOT NULL CHECK (priority IN ('user', 'background')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    run_after DATETIME NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,23 @@
+-- =============================================================================
+-- TRANSACTION SWAPS
+-- Exact swap amounts decoded from DEX swap events in transaction receipts
+-- =============================================================================
+
+-- One row per pool swap, in execution order; a routed swap has a leg per
+-- pool. Amounts are integers in the token's smallest units.
+CREATE TABLE IF NOT EXISTS transaction_swaps (
+    wallet_id TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    leg_index INTEGER NOT NULL,
+    protocol TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    token_in TEXT NOT NULL,
+    token_in_symbol TEXT,
+    token_in_decimals INTEGER,
+    amount_in TEXT NOT NULL,
+    token_out TEXT NOT NULL,
+    token_out_symbol TEXT,
+    token_out_decimals INTEGER,
+    amount_out TEXT NOT NULL,
+    PRIMARY KEY (wallet_id, hash, leg_index)
+);
@@ -0,0 +1,7 @@
+-- =============================================================================
+-- LIQUIDITY FEE INCOME ACCOUNT
+-- Income account for trading fees earned by liquidity positions
+-- =============================================================================
+
+INSERT OR IGNORE INTO gl_accounts (account_number, account_name, account_type, normal_balance, is_editable, description) VALUES
+    ('4700', 'Liquidity Fee Income',      'Income',    'credit', 1, 'Trading fees earned from providing liquidity');
@@ -0,0 +1,19 @@
+-- =============================================================================
+-- TRANSACTION TOKEN TRANSFERS
+-- Token movements within each wallet transaction, for balance reconstruction
+-- =============================================================================
+
+-- One row per token transfer, in the order the chain reported them. Values
+-- are integers in the token's smallest units.
+CREATE TABLE IF NOT EXISTS transaction_token_transfers (
+    wallet_id TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    transfer_index INTEGER NOT NULL,
+    token_address TEXT NOT NULL,
+    token_symbol TEXT,
+    token_decimals INTEGER,
+    from_address TEXT NOT NULL,
+    to_address TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (wallet_id, hash, transfer_index)
+);
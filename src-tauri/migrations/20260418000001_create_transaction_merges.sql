@@ -0,0 +1,20 @@
+-- =============================================================================
+-- TRANSACTION MERGES
+-- Records of the same movement imported twice, e.g. an exchange withdrawal
+-- and the on-chain deposit it produced
+-- =============================================================================
+
+-- A merged duplicate stays in `transactions` so the merge can be undone, but
+-- is left out of profile transaction listings and reports.
+CREATE TABLE IF NOT EXISTS transaction_merges (
+    duplicate_id TEXT PRIMARY KEY,
+    kept_id TEXT NOT NULL,
+    profile_id TEXT NOT NULL,
+    merged_by TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (duplicate_id) REFERENCES transactions(id) ON DELETE CASCADE,
+    FOREIGN KEY (kept_id) REFERENCES transactions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_transaction_merges_kept ON transaction_merges(kept_id);
+CREATE INDEX IF NOT EXISTS idx_transaction_merges_profile ON transaction_merges(profile_id);
@@ -0,0 +1,42 @@
+-- =============================================================================
+-- INVOICES
+-- Payment requests to a profile address, marked paid when the chain shows a
+-- matching incoming payment
+-- =============================================================================
+
+-- An invoice for `amount` (whole units, decimal string) of a chain's native
+-- currency, or of the token at token_address, paid to payee_address.
+-- last_block is the highest block checked so far. Once paid, the payment's
+-- hash is kept, and settlement_transaction_id links the stored transaction
+-- when the payee wallet has synced it.
+CREATE TABLE IF NOT EXISTS invoices (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    invoice_number TEXT NOT NULL,
+    sequence INTEGER NOT NULL,
+    payer_entity_id TEXT,
+    payer_name TEXT,
+    payee_address TEXT NOT NULL,
+    chain TEXT NOT NULL,
+    token_address TEXT,
+    token_symbol TEXT NOT NULL,
+    token_decimals INTEGER NOT NULL,
+    amount TEXT NOT NULL,
+    due_date DATE,
+    memo TEXT,
+    status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'paid', 'cancelled')),
+    last_block INTEGER,
+    paid_tx_hash TEXT,
+    paid_amount TEXT,
+    paid_from TEXT,
+    paid_at DATETIME,
+    settlement_transaction_id TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (payer_entity_id) REFERENCES entities(id) ON DELETE SET NULL,
+    UNIQUE(profile_id, sequence)
+);
+
+CREATE INDEX IF NOT EXISTS idx_invoices_profile ON invoices(profile_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_invoices_status ON invoices(status);
@@ -0,0 +1,30 @@
+-- =============================================================================
+-- VESTING SCHEDULES
+-- Token grants that vest over time to one of a profile's wallets
+-- =============================================================================
+
+-- A grant of total_amount (whole units, decimal string) vesting linearly
+-- from start_at to end_at. Nothing vests before cliff_at, when the amount
+-- accrued since start_at unlocks at once. Claims are detected as transfers
+-- from contract_address to the wallet when the grant is held on chain.
+CREATE TABLE IF NOT EXISTS vesting_schedules (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    wallet_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    token_symbol TEXT NOT NULL,
+    token_address TEXT,
+    token_decimals INTEGER NOT NULL,
+    total_amount TEXT NOT NULL,
+    start_at DATETIME NOT NULL,
+    cliff_at DATETIME,
+    end_at DATETIME NOT NULL,
+    contract_address TEXT,
+    notes TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (wallet_id) REFERENCES wallets(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_vesting_schedules_profile ON vesting_schedules(profile_id);
@@ -0,0 +1,25 @@
+-- =============================================================================
+-- ACCOUNT LINKS
+-- EVM and Substrate accounts of one key holder on dual-environment chains
+-- =============================================================================
+
+-- Links a wallet to its paired account, e.g. an Astar H160 and its native
+-- SS58 account, or a Polkadot account and the address it controls on
+-- Moonbeam through XCM. Linked wallets are treated as one identity.
+-- `method` is how the pair was established: astar_default, astar_unified,
+-- or moonbeam_xcm.
+CREATE TABLE IF NOT EXISTS account_links (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    wallet_id TEXT NOT NULL,
+    linked_wallet_id TEXT NOT NULL,
+    method TEXT NOT NULL,
+    created_by TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (wallet_id) REFERENCES wallets(id) ON DELETE CASCADE,
+    FOREIGN KEY (linked_wallet_id) REFERENCES wallets(id) ON DELETE CASCADE,
+    UNIQUE (wallet_id, linked_wallet_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_links_profile ON account_links(profile_id);
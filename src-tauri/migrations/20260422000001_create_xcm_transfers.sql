@@ -0,0 +1,31 @@
+-- =============================================================================
+-- XCM TRANSFERS
+-- Cross-chain messages moving assets between the relay chain and parachains
+-- =============================================================================
+
+-- One row per XCM message a profile's accounts sent or received, linking the
+-- outgoing leg on the origin chain to the incoming leg on the destination.
+-- Linked legs are typed `bridge` so the hop isn't booked as a disposal.
+CREATE TABLE IF NOT EXISTS xcm_transfers (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    message_hash TEXT NOT NULL,
+    origin_chain TEXT NOT NULL,
+    dest_chain TEXT NOT NULL,
+    from_address TEXT NOT NULL,
+    to_address TEXT NOT NULL,
+    asset_symbol TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    status TEXT NOT NULL,
+    send_transaction_id TEXT,
+    receive_transaction_id TEXT,
+    sent_at DATETIME NOT NULL,
+    received_at DATETIME,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (send_transaction_id) REFERENCES transactions(id) ON DELETE SET NULL,
+    FOREIGN KEY (receive_transaction_id) REFERENCES transactions(id) ON DELETE SET NULL,
+    UNIQUE (profile_id, message_hash)
+);
+
+CREATE INDEX IF NOT EXISTS idx_xcm_transfers_profile ON xcm_transfers(profile_id);
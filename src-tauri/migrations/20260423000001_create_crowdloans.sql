@@ -0,0 +1,54 @@
+-- =============================================================================
+-- CROWDLOANS
+-- DOT and KSM locked in parachain crowdloans, and the rewards they earned
+-- =============================================================================
+
+-- One row per contribution. The contributed amount stays the contributor's
+-- asset while locked: the contribution and its release are typed
+-- `crowdloan_contribution` and `crowdloan_release` rather than booked as a
+-- disposal and fresh income. `status` is locked, withdrawable (the lease
+-- has ended but the funds haven't come back yet), or released.
+CREATE TABLE IF NOT EXISTS crowdloan_contributions (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    wallet_id TEXT NOT NULL,
+    relay_chain TEXT NOT NULL,
+    para_id INTEGER NOT NULL,
+    fund_id TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    extrinsic_index TEXT NOT NULL,
+    contributed_at DATETIME NOT NULL,
+    lease_end_block INTEGER,
+    lease_end_at DATETIME,
+    status TEXT NOT NULL DEFAULT 'locked',
+    contribution_transaction_id TEXT,
+    release_transaction_id TEXT,
+    released_at DATETIME,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (wallet_id) REFERENCES wallets(id) ON DELETE CASCADE,
+    FOREIGN KEY (contribution_transaction_id) REFERENCES transactions(id) ON DELETE SET NULL,
+    FOREIGN KEY (release_transaction_id) REFERENCES transactions(id) ON DELETE SET NULL,
+    UNIQUE (wallet_id, extrinsic_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_crowdloan_contributions_profile ON crowdloan_contributions(profile_id);
+
+-- Reward tokens a parachain paid out to its contributors, typed
+-- `crowdloan_reward` and booked as income.
+CREATE TABLE IF NOT EXISTS crowdloan_rewards (
+    transaction_id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    relay_chain TEXT NOT NULL,
+    para_id INTEGER NOT NULL,
+    chain TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    received_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (transaction_id) REFERENCES transactions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_crowdloan_rewards_profile ON crowdloan_rewards(profile_id);
@@ -0,0 +1,17 @@
+-- =============================================================================
+-- UTXO LABELS
+-- Coin-control labels on a Bitcoin wallet's unspent outputs
+-- =============================================================================
+
+-- One label per output, keyed by outpoint. Labels outlive the output being
+-- spent so a disposal can still be traced to the coins it used.
+CREATE TABLE IF NOT EXISTS utxo_labels (
+    wallet_id TEXT NOT NULL,
+    txid TEXT NOT NULL,
+    vout INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    updated_by TEXT,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (wallet_id, txid, vout),
+    FOREIGN KEY (wallet_id) REFERENCES wallets(id) ON DELETE CASCADE
+);
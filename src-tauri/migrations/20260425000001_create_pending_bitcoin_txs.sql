@@ -0,0 +1,26 @@
+-- =============================================================================
+-- PENDING BITCOIN TRANSACTIONS
+-- Unconfirmed Bitcoin transactions followed until they confirm, are replaced,
+-- or drop out of the mempool
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS pending_bitcoin_txs (
+    wallet_id TEXT NOT NULL,
+    txid TEXT NOT NULL,
+    chain TEXT NOT NULL,
+    -- JSON array of the outpoints the transaction spends
+    inputs TEXT NOT NULL,
+    -- Whether the transaction signals opt-in replace-by-fee (BIP 125)
+    replaceable BOOLEAN NOT NULL DEFAULT 0,
+    -- pending, confirmed, replaced, or dropped
+    state TEXT NOT NULL DEFAULT 'pending',
+    replaced_by TEXT,
+    -- JSON array of unconfirmed children bumping the fee (CPFP)
+    bumped_by TEXT NOT NULL DEFAULT '[]',
+    first_seen_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    resolved_at DATETIME,
+    PRIMARY KEY (wallet_id, txid),
+    FOREIGN KEY (wallet_id) REFERENCES wallets(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_pending_bitcoin_txs_state ON pending_bitcoin_txs(state);
@@ -0,0 +1,37 @@
+-- =============================================================================
+-- MEMPOOL PAYMENTS
+-- Incoming Bitcoin payments to watched addresses, seen before they confirm
+-- =============================================================================
+
+-- Confirmations a profile requires before an incoming payment counts as
+-- received
+CREATE TABLE IF NOT EXISTS profile_confirmation_settings (
+    profile_id TEXT PRIMARY KEY,
+    bitcoin_confirmations INTEGER NOT NULL DEFAULT 1 CHECK (bitcoin_confirmations BETWEEN 0 AND 100),
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);
+
+-- A payment is pending from the moment it reaches the mempool until it has
+-- the profile's confirmations, or is replaced or dropped
+CREATE TABLE IF NOT EXISTS mempool_payments (
+    id TEXT PRIMARY KEY,
+    watch_id TEXT NOT NULL,
+    txid TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    counterparty TEXT,
+    -- JSON array of the outpoints the transaction spends
+    inputs TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'success', 'replaced', 'dropped')),
+    confirmations INTEGER NOT NULL DEFAULT 0,
+    block_height INTEGER,
+    replaced_by TEXT,
+    first_seen_at DATETIME NOT NULL,
+    confirmed_at DATETIME,
+    updated_at DATETIME NOT NULL,
+    FOREIGN KEY (watch_id) REFERENCES address_watches(id) ON DELETE CASCADE,
+    UNIQUE(watch_id, txid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_mempool_payments_status ON mempool_payments(status);
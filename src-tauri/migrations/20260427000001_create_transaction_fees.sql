@@ -0,0 +1,19 @@
+-- =============================================================================
+-- TRANSACTION FEES
+-- EIP-1559 split of EVM transaction fees into burned base fee and tip
+-- =============================================================================
+
+-- One row per transaction that has a breakdown. Amounts are integers in wei;
+-- the base fee and tip are NULL when the block's base fee is unknown, and
+-- the refund is NULL for legacy transactions.
+CREATE TABLE IF NOT EXISTS transaction_fees (
+    wallet_id TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    gas_used TEXT NOT NULL,
+    effective_gas_price TEXT NOT NULL,
+    base_fee TEXT,
+    priority_fee TEXT,
+    refunded TEXT,
+    total TEXT NOT NULL,
+    PRIMARY KEY (wallet_id, hash)
+);
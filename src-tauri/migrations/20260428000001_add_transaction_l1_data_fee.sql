@@ -0,0 +1,2 @@
+-- L1 data fee of rollup transactions, in wei. NULL off rollups.
+ALTER TABLE transaction_fees ADD COLUMN l1_data_fee TEXT;
@@ -0,0 +1,2 @@
+-- Blob gas fee of EIP-4844 transactions, in wei. NULL without blobs.
+ALTER TABLE transaction_fees ADD COLUMN blob_fee TEXT;
@@ -0,0 +1,36 @@
+-- =============================================================================
+-- ACCOUNTING EVENTS
+-- Economic events a wallet transaction expands into, each tagged and booked
+-- to the ledger on its own
+-- =============================================================================
+
+-- One row per event, in the order the expansion rules produce them: swaps,
+-- then token movements, then native value, then the fee. Amounts are
+-- integers in the asset's smallest units; assets are token contracts, or
+-- `native` for the chain's currency. Re-expanding a transaction keeps each
+-- event's tag, and is refused once any of its events is booked.
+CREATE TABLE IF NOT EXISTS accounting_events (
+    id TEXT PRIMARY KEY,
+    wallet_id TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    kind TEXT NOT NULL
+        CHECK (kind IN ('swap', 'transfer_in', 'transfer_out', 'mint', 'burn', 'fee')),
+    sent_asset TEXT,
+    sent_symbol TEXT,
+    sent_decimals INTEGER,
+    sent_amount TEXT,
+    received_asset TEXT,
+    received_symbol TEXT,
+    received_decimals INTEGER,
+    received_amount TEXT,
+    counterparty TEXT,
+    category TEXT,
+    entity_id TEXT,
+    journal_entry_id INTEGER,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (wallet_id, hash, event_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_accounting_events_journal_entry
+    ON accounting_events(journal_entry_id);
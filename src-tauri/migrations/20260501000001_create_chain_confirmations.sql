@@ -0,0 +1,15 @@
+-- =============================================================================
+-- CHAIN CONFIRMATIONS
+-- Per-profile confirmation thresholds that override each chain's default
+-- =============================================================================
+
+-- Confirmations a synced transaction needs on a chain before it counts as
+-- successful rather than pending
+CREATE TABLE IF NOT EXISTS profile_chain_confirmations (
+    profile_id TEXT NOT NULL,
+    chain TEXT NOT NULL,
+    confirmations INTEGER NOT NULL CHECK (confirmations BETWEEN 0 AND 256),
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (profile_id, chain),
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);
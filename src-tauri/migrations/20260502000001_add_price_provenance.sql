@@ -0,0 +1,14 @@
+-- =============================================================================
+-- PRICE PROVENANCE
+-- Where each stored fiat valuation was read from, for audit
+-- =============================================================================
+
+-- Endpoint the receipt's price was read from (an override's record for
+-- overrides, `manual` for prices entered by hand) and when it was retrieved.
+-- Receipts issued before this migration have neither.
+ALTER TABLE donation_receipts ADD COLUMN price_endpoint TEXT;
+ALTER TABLE donation_receipts ADD COLUMN price_retrieved_at DATETIME;
+
+-- Endpoint the exchange rate was read from, alongside exchange_rate_source
+-- and exchange_rate_timestamp.
+ALTER TABLE transactions ADD COLUMN exchange_rate_endpoint TEXT;
@@ -0,0 +1,60 @@
+-- =============================================================================
+-- REPORT SCHEDULES
+-- Reports generated each month or quarter and emailed to recipients, with a
+-- history of every report generated
+-- =============================================================================
+
+-- A report generated for each completed period. entity_id is the entity an
+-- entity statement covers; coin_ids maps asset symbols to price feed coin
+-- IDs (JSON object) and recipients is a JSON array of email addresses.
+-- next_run_at is when the next completed period's report is due.
+CREATE TABLE IF NOT EXISTS report_schedules (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    report_type TEXT NOT NULL CHECK (report_type IN ('entity_statement', 'payee_report')),
+    format TEXT NOT NULL CHECK (format IN ('csv', 'pdf')),
+    frequency TEXT NOT NULL CHECK (frequency IN ('monthly', 'quarterly')),
+    entity_id TEXT,
+    coin_ids TEXT NOT NULL DEFAULT '{}',
+    recipients TEXT NOT NULL DEFAULT '[]',
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    next_run_at DATETIME NOT NULL,
+    last_run_at DATETIME,
+    created_by TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (entity_id) REFERENCES entities(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_report_schedules_profile ON report_schedules(profile_id);
+CREATE INDEX IF NOT EXISTS idx_report_schedules_due ON report_schedules(is_active, next_run_at);
+
+-- Each report generated for a schedule, kept so it can be downloaded again.
+-- status is 'sent' once every recipient was emailed, 'generated' when there
+-- was no one to send to, and 'failed' when generation or delivery failed,
+-- with the reason in error. content is empty when generation failed.
+CREATE TABLE IF NOT EXISTS generated_reports (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    schedule_id TEXT,
+    name TEXT NOT NULL,
+    report_type TEXT NOT NULL,
+    format TEXT NOT NULL,
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    file_name TEXT NOT NULL,
+    content BLOB NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    recipients TEXT NOT NULL DEFAULT '[]',
+    status TEXT NOT NULL CHECK (status IN ('sent', 'generated', 'failed')),
+    error TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (schedule_id) REFERENCES report_schedules(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_generated_reports_profile
+    ON generated_reports(profile_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_generated_reports_schedule ON generated_reports(schedule_id);
@@ -0,0 +1,11 @@
+-- =============================================================================
+-- API USAGE
+-- Outbound calls per provider per UTC day, for free-tier quota warnings
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS api_usage (
+    provider TEXT NOT NULL,
+    day DATE NOT NULL,
+    calls INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (provider, day)
+);
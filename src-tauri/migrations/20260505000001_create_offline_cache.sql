@@ -0,0 +1,12 @@
+-- =============================================================================
+-- OFFLINE CACHE
+-- Last successful result of each fetch command, served while offline
+-- =============================================================================
+
+-- cache_key names the command and its arguments (e.g.
+-- 'balances:ethereum:0xabc...'); payload is the result as JSON.
+CREATE TABLE IF NOT EXISTS offline_cache (
+    cache_key TEXT PRIMARY KEY,
+    payload TEXT NOT NULL,
+    fetched_at DATETIME NOT NULL
+);
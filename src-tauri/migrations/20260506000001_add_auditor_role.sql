@@ -0,0 +1,76 @@
+-- =============================================================================
+-- ADD AUDITOR ROLE
+-- Extends the profile role CHECK constraints to include the read-only
+-- 'auditor' role. Uses SQLite table recreation since ALTER TABLE cannot
+-- modify CHECK constraints
+-- =============================================================================
+
+-- Step 1: Recreate user_profile_roles with the updated CHECK constraint
+CREATE TABLE IF NOT EXISTS user_profile_roles_new (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    profile_id TEXT NOT NULL,
+    role TEXT NOT NULL DEFAULT 'user'
+        CHECK (role IN ('user', 'preparer', 'approver', 'admin', 'owner', 'auditor')),
+    invited_by_user_id TEXT,
+    invited_at DATETIME,
+    accepted_at DATETIME,
+    status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('pending', 'active', 'suspended', 'revoked')),
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (invited_by_user_id) REFERENCES users(id) ON DELETE SET NULL,
+    UNIQUE(user_id, profile_id)
+);
+
+INSERT OR IGNORE INTO user_profile_roles_new
+    SELECT id, user_id, profile_id, role, invited_by_user_id, invited_at,
+           accepted_at, status, created_at, updated_at
+    FROM user_profile_roles;
+
+DROP TABLE IF EXISTS user_profile_roles;
+ALTER TABLE user_profile_roles_new RENAME TO user_profile_roles;
+
+CREATE INDEX IF NOT EXISTS idx_user_profile_roles_user ON user_profile_roles(user_id);
+CREATE INDEX IF NOT EXISTS idx_user_profile_roles_profile ON user_profile_roles(profile_id);
+CREATE INDEX IF NOT EXISTS idx_user_profile_roles_role ON user_profile_roles(role);
+CREATE INDEX IF NOT EXISTS idx_user_profile_roles_status ON user_profile_roles(status);
+
+CREATE TRIGGER IF NOT EXISTS user_profile_roles_update_timestamp
+AFTER UPDATE ON user_profile_roles
+BEGIN
+    UPDATE user_profile_roles SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+-- Step 2: Recreate invitations with the updated CHECK constraint
+CREATE TABLE IF NOT EXISTS invitations_new (
+    id TEXT PRIMARY KEY,
+    email TEXT NOT NULL COLLATE NOCASE,
+    profile_id TEXT NOT NULL,
+    role TEXT NOT NULL DEFAULT 'user'
+        CHECK (role IN ('user', 'preparer', 'approver', 'admin', 'auditor')),
+    token TEXT NOT NULL UNIQUE,
+    token_expires_at DATETIME NOT NULL,
+    invited_by_user_id TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'accepted', 'declined', 'expired', 'revoked')),
+    accepted_at DATETIME,
+    message TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (invited_by_user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+INSERT OR IGNORE INTO invitations_new
+    SELECT id, email, profile_id, role, token, token_expires_at,
+           invited_by_user_id, status, accepted_at, message, created_at
+    FROM invitations;
+
+DROP TABLE IF EXISTS invitations;
+ALTER TABLE invitations_new RENAME TO invitations;
+
+CREATE INDEX IF NOT EXISTS idx_invitations_email ON invitations(email);
+CREATE INDEX IF NOT EXISTS idx_invitations_token ON invitations(token);
+CREATE INDEX IF NOT EXISTS idx_invitations_profile ON invitations(profile_id);
+CREATE INDEX IF NOT EXISTS idx_invitations_status ON invitations(status);
@@ -0,0 +1,9 @@
+-- =============================================================================
+-- EMAIL CHANGE COOL-DOWN
+-- A confirmed email change takes effect after a cool-down, during which the
+-- old address can still cancel it
+-- =============================================================================
+
+ALTER TABLE email_change_requests ADD COLUMN effective_at DATETIME;
+
+CREATE INDEX IF NOT EXISTS idx_email_change_effective ON email_change_requests(effective_at);
@@ -0,0 +1,24 @@
+-- =============================================================================
+-- EXPORT TEMPLATES
+-- Saved column layouts and formatting for transaction CSV exports
+-- =============================================================================
+
+-- columns is a JSON array of column names in output order. date_format is a
+-- strftime pattern; decimal_separator is 'period' or 'comma' and delimiter
+-- is 'comma', 'semicolon', or 'tab'.
+CREATE TABLE IF NOT EXISTS export_templates (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    columns TEXT NOT NULL DEFAULT '[]',
+    date_format TEXT NOT NULL,
+    decimal_separator TEXT NOT NULL CHECK (decimal_separator IN ('period', 'comma')),
+    delimiter TEXT NOT NULL CHECK (delimiter IN ('comma', 'semicolon', 'tab')),
+    created_by TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    UNIQUE (profile_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_export_templates_profile ON export_templates(profile_id);
@@ -0,0 +1,19 @@
+-- =============================================================================
+-- PRICE ID MAPPINGS
+-- Per-profile overrides of the price feed coin ID for a chain's native asset
+-- or a token contract
+-- =============================================================================
+
+-- asset is 'native' for the chain's own currency, or the token's contract
+-- address (lowercased when hex). symbol, when set, also maps lookups made by
+-- symbol alone to coin_id.
+CREATE TABLE IF NOT EXISTS price_id_mappings (
+    profile_id TEXT NOT NULL,
+    chain TEXT NOT NULL,
+    asset TEXT NOT NULL,
+    symbol TEXT,
+    coin_id TEXT NOT NULL,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (profile_id, chain, asset),
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);
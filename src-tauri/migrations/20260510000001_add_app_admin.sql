@@ -0,0 +1,18 @@
+-- =============================================================================
+-- APP ADMINISTRATOR
+-- App-wide settings (password hashing, email change, JWT signing keys) are
+-- managed by the app administrator, not by whoever administers a profile
+-- =============================================================================
+
+ALTER TABLE users ADD COLUMN is_app_admin INTEGER NOT NULL DEFAULT 0;
+
+-- The first account created on this install is its administrator
+UPDATE users SET is_app_admin = 1
+WHERE id = (SELECT id FROM users ORDER BY created_at, id LIMIT 1);
+
+CREATE TRIGGER IF NOT EXISTS users_first_app_admin
+AFTER INSERT ON users
+WHEN NOT EXISTS (SELECT 1 FROM users WHERE is_app_admin = 1)
+BEGIN
+    UPDATE users SET is_app_admin = 1 WHERE id = NEW.id;
+END;
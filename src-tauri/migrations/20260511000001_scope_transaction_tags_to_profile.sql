@@ -0,0 +1,58 @@
+-- =============================================================================
+-- SCOPE TRANSACTION TAGS TO THEIR PROFILE
+-- A tag was unique per transaction and category across every profile, so one
+-- profile re-tagging a transaction overwrote another profile's allocation.
+-- Uses SQLite table recreation since ALTER TABLE cannot change a UNIQUE
+-- constraint
+-- =============================================================================
+
+CREATE TABLE IF NOT EXISTS transaction_tags_new (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    transaction_id TEXT NOT NULL,
+    category TEXT NOT NULL,
+    entity_id TEXT,
+    amount TEXT NOT NULL,
+    occurred_at DATETIME NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE,
+    FOREIGN KEY (entity_id) REFERENCES entities(id) ON DELETE SET NULL,
+    UNIQUE(profile_id, transaction_id, category)
+);
+
+INSERT OR IGNORE INTO transaction_tags_new
+    SELECT id, profile_id, transaction_id, category, entity_id, amount,
+           occurred_at, created_at
+    FROM transaction_tags;
+
+DROP TABLE IF EXISTS transaction_tags;
+ALTER TABLE transaction_tags_new RENAME TO transaction_tags;
+
+CREATE INDEX IF NOT EXISTS idx_transaction_tags_profile_date
+    ON transaction_tags(profile_id, occurred_at);
+CREATE INDEX IF NOT EXISTS idx_transaction_tags_transaction
+    ON transaction_tags(transaction_id);
+
+-- Dropping the table dropped its search triggers
+CREATE TRIGGER IF NOT EXISTS search_transaction_tags_ai AFTER INSERT ON transaction_tags BEGIN
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('tag', new.id, new.profile_id, new.transaction_id, new.category,
+        new.transaction_id)
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_transaction_tags_au AFTER UPDATE ON transaction_tags BEGIN
+    DELETE FROM search_documents WHERE kind = 'tag' AND record_id = old.id AND old.id <> new.id;
+    INSERT INTO search_documents (kind, record_id, profile_id, parent_id, title, body)
+    VALUES ('tag', new.id, new.profile_id, new.transaction_id, new.category,
+        new.transaction_id)
+    ON CONFLICT(kind, record_id) DO UPDATE SET
+        profile_id = excluded.profile_id, parent_id = excluded.parent_id,
+        title = excluded.title, body = excluded.body;
+END;
+
+CREATE TRIGGER IF NOT EXISTS search_transaction_tags_ad AFTER DELETE ON transaction_tags BEGIN
+    DELETE FROM search_documents WHERE kind = 'tag' AND record_id = old.id;
+END;
@@ -0,0 +1,483 @@
+//! Linked EVM and Substrate accounts on dual-environment chains.
+//!
+//! Astar and Moonbeam users hold an H160 and an SS58 account that belong
+//! together. For each of a profile's wallets the paired account is worked
+//! out (a claimed unified account or the default mapping on Astar, the XCM
+//! derivative of a Polkadot account on Moonbeam) and offered as a
+//! suggestion. Accepting one tracks the paired account if it isn't tracked
+//! yet and links the two, so they count as one identity.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::audit_trail::{record_change, RecordType};
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, Wallet};
+use super::profile_scope::{authorize_profile, authorize_wallet, profile_wallets};
+use super::wallet_identity::canonical_address;
+use crate::chains::address::identity_key;
+use crate::chains::substrate::account_mapping::{
+    derived_accounts, paired_accounts, MappingMethod, PairedAccount,
+};
+use crate::core::auth_state::AuthState;
+use crate::log_error;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Two wallets of one key holder, linked into one identity.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountLink {
+    /// Unique identifier.
+    pub id: String,
+    /// Profile the wallets belong to.
+    pub profile_id: String,
+    /// Wallet the link was made from.
+    pub wallet_id: String,
+    /// Its paired wallet.
+    pub linked_wallet_id: String,
+    /// How the accounts are related: `astar_default`, `astar_unified`, or
+    /// `moonbeam_xcm`.
+    pub method: String,
+    /// User who made the link.
+    pub created_by: Option<String>,
+    /// When the link was made.
+    pub created_at: DateTime<Utc>,
+}
+
+/// A paired account the app can offer to track and link.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountLinkSuggestion {
+    /// Wallet the pair was worked out from.
+    pub wallet_id: String,
+    /// Its chain.
+    pub chain: String,
+    /// Its address.
+    pub address: String,
+    /// Chain of the paired account.
+    pub paired_chain: String,
+    /// Address of the paired account.
+    pub paired_address: String,
+    /// How the accounts are related.
+    pub method: String,
+    /// The paired account's wallet, if it's tracked already.
+    pub paired_wallet_id: Option<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// The wallet on `chain` whose address is the same account as `address`.
+fn find_wallet<'a>(wallets: &'a [Wallet], chain: &str, address: &str) -> Option<&'a Wallet> {
+    let key = identity_key(address);
+    wallets
+        .iter()
+        .find(|w| w.chain == chain && identity_key(&w.address) == key)
+}
+
+/// Suggestions for each wallet's paired accounts, leaving out pairs that
+/// are already linked.
+pub fn build_suggestions(
+    wallets: &[Wallet],
+    links: &[(String, String)],
+    pairs: &HashMap<String, Vec<PairedAccount>>,
+) -> Vec<AccountLinkSuggestion> {
+    let linked = |a: &str, b: &str| {
+        links
+            .iter()
+            .any(|(x, y)| (x == a && y == b) || (x == b && y == a))
+    };
+
+    let mut suggestions = Vec::new();
+    for wallet in wallets {
+        for pair in pairs.get(&wallet.id).into_iter().flatten() {
+            let paired_wallet = find_wallet(wallets, &pair.chain, &pair.address);
+            if paired_wallet.is_some_and(|p| linked(&wallet.id, &p.id)) {
+                continue;
+            }
+            // Suggest a pair of tracked wallets once, from the first of them.
+            if let Some(paired) = paired_wallet {
+                let seen = suggestions.iter().any(|s: &AccountLinkSuggestion| {
+                    s.wallet_id == paired.id && s.paired_wallet_id.as_deref() == Some(&wallet.id)
+                });
+                if seen {
+                    continue;
+                }
+            }
+            suggestions.push(AccountLinkSuggestion {
+                wallet_id: wallet.id.clone(),
+                chain: wallet.chain.clone(),
+                address: wallet.address.clone(),
+                paired_chain: pair.chain.clone(),
+                paired_address: pair.address.clone(),
+                method: pair.method.as_str().to_string(),
+                paired_wallet_id: paired_wallet.map(|p| p.id.clone()),
+            });
+        }
+    }
+    suggestions
+}
+
+/// A wallet's paired accounts, falling back to the derived ones when the
+/// chain can't be asked for a claimed unified account.
+async fn wallet_pairs(wallet: &Wallet) -> Vec<PairedAccount> {
+    match paired_accounts(&wallet.chain, &wallet.address).await {
+        Ok(pairs) => pairs,
+        Err(e) => {
+            log_error!(
+                "Failed to look up unified account for {}: {e}",
+                wallet.address
+            );
+            derived_accounts(&wallet.chain, &wallet.address)
+        }
+    }
+}
+
+async fn load_links(pool: &SqlitePool, profile_id: &str) -> Result<Vec<AccountLink>, String> {
+    sqlx::query_as::<_, AccountLink>(
+        "SELECT * FROM account_links WHERE profile_id = ? ORDER BY created_at ASC",
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Returns the wallet for `address` on `chain`, adding it to the profile
+/// with the source wallet's type if it isn't tracked yet.
+async fn track_paired_wallet(
+    pool: &SqlitePool,
+    user_id: &str,
+    source: &Wallet,
+    chain: &str,
+    address: &str,
+) -> Result<Wallet, String> {
+    let address = canonical_address(chain, address);
+    let wallets = profile_wallets(pool, &source.profile_id).await?;
+    if let Some(existing) = find_wallet(&wallets, chain, &address) {
+        return Ok(existing.clone());
+    }
+
+    let now = Utc::now();
+    let wallet = Wallet {
+        id: Uuid::new_v4().to_string(),
+        profile_id: source.profile_id.clone(),
+        address,
+        chain: chain.to_string(),
+        name: source.name.clone(),
+        wallet_type: source.wallet_type.clone(),
+        created_at: now,
+        updated_at: Some(now),
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO wallets (id, profile_id, address, chain, name, wallet_type, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&wallet.id)
+    .bind(&wallet.profile_id)
+    .bind(&wallet.address)
+    .bind(&wallet.chain)
+    .bind(&wallet.name)
+    .bind(&wallet.wallet_type)
+    .bind(wallet.created_at)
+    .bind(wallet.updated_at)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    record_change(
+        pool,
+        Some(user_id),
+        RecordType::Wallet,
+        &wallet.id,
+        Some(&wallet.profile_id),
+        None,
+        Some(&wallet),
+    )
+    .await?;
+
+    Ok(wallet)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Lists a profile's account links.
+#[tauri::command]
+pub async fn get_account_links(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<AccountLink>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    load_links(&state.pool, &profile_id).await
+}
+
+/// Suggests the paired EVM or Substrate accounts of a profile's Astar and
+/// Polkadot wallets that aren't linked yet.
+#[tauri::command]
+pub async fn get_account_link_suggestions(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<AccountLinkSuggestion>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let mut wallets = profile_wallets(&state.pool, &profile_id).await?;
+    wallets.reverse();
+    let links: Vec<(String, String)> = load_links(&state.pool, &profile_id)
+        .await?
+        .into_iter()
+        .map(|l| (l.wallet_id, l.linked_wallet_id))
+        .collect();
+
+    let mut pairs = HashMap::new();
+    for wallet in &wallets {
+        let found = wallet_pairs(wallet).await;
+        if !found.is_empty() {
+            pairs.insert(wallet.id.clone(), found);
+        }
+    }
+
+    Ok(build_suggestions(&wallets, &links, &pairs))
+}
+
+/// Links a wallet to its paired account, tracking the paired account first
+/// if needed. The pair is worked out again, so only a real pairing links.
+#[tauri::command]
+pub async fn link_paired_account(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    wallet_id: String,
+    paired_chain: String,
+    paired_address: String,
+) -> Result<AccountLink, String> {
+    let (user_id, wallet) = authorize_wallet(
+        &state.pool,
+        &auth,
+        &token,
+        &wallet_id,
+        Permission::ManageWallets,
+    )
+    .await?;
+
+    let key = identity_key(&paired_address);
+    let pair = wallet_pairs(&wallet)
+        .await
+        .into_iter()
+        .find(|p| p.chain == paired_chain && identity_key(&p.address) == key)
+        .ok_or_else(|| {
+            format!(
+                "{} on {} is not paired with this wallet",
+                paired_address, paired_chain
+            )
+        })?;
+
+    let paired =
+        track_paired_wallet(&state.pool, &user_id, &wallet, &pair.chain, &pair.address).await?;
+
+    let (existing,): (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM account_links
+        WHERE (wallet_id = ? AND linked_wallet_id = ?) OR (wallet_id = ? AND linked_wallet_id = ?)
+        "#,
+    )
+    .bind(&wallet.id)
+    .bind(&paired.id)
+    .bind(&paired.id)
+    .bind(&wallet.id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    if existing > 0 {
+        return Err("These accounts are already linked".to_string());
+    }
+
+    let link = AccountLink {
+        id: Uuid::new_v4().to_string(),
+        profile_id: wallet.profile_id.clone(),
+        wallet_id: wallet.id.clone(),
+        linked_wallet_id: paired.id,
+        method: pair.method.as_str().to_string(),
+        created_by: Some(user_id.clone()),
+        created_at: Utc::now(),
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO account_links (id, profile_id, wallet_id, linked_wallet_id, method, created_by, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&link.id)
+    .bind(&link.profile_id)
+    .bind(&link.wallet_id)
+    .bind(&link.linked_wallet_id)
+    .bind(&link.method)
+    .bind(&link.created_by)
+    .bind(link.created_at)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    record_change(
+        &state.pool,
+        Some(&user_id),
+        RecordType::AccountLink,
+        &link.id,
+        Some(&link.profile_id),
+        None,
+        Some(&link),
+    )
+    .await?;
+
+    Ok(link)
+}
+
+/// Removes an account link. Both wallets stay tracked.
+#[tauri::command]
+pub async fn unlink_account(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    id: String,
+) -> Result<(), String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ManageWallets,
+    )
+    .await?;
+    let link = sqlx::query_as::<_, AccountLink>(
+        "SELECT * FROM account_links WHERE profile_id = ? AND id = ?",
+    )
+    .bind(&profile_id)
+    .bind(&id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Account link not found: {}", id))?;
+
+    sqlx::query("DELETE FROM account_links WHERE id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    record_change(
+        &state.pool,
+        Some(&user_id),
+        RecordType::AccountLink,
+        &link.id,
+        Some(&link.profile_id),
+        Some(&link),
+        None,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASTAR_EVM: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    const ASTAR_NATIVE: &str = "bVgxQGrTCdJSYxKcWn3GxXeBeLTojWGxKcbQ8iHkLa7DXqP";
+    const ALICE: &str = "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5";
+
+    fn wallet(id: &str, chain: &str, address: &str) -> Wallet {
+        Wallet {
+            id: id.to_string(),
+            profile_id: "p1".to_string(),
+            address: address.to_string(),
+            chain: chain.to_string(),
+            name: None,
+            wallet_type: "software".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+        }
+    }
+
+    fn pairs_for(wallets: &[Wallet]) -> HashMap<String, Vec<PairedAccount>> {
+        wallets
+            .iter()
+            .map(|w| (w.id.clone(), derived_accounts(&w.chain, &w.address)))
+            .filter(|(_, pairs)| !pairs.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn test_suggests_untracked_pairs() {
+        let wallets = vec![
+            wallet("w1", "astar", ASTAR_EVM),
+            wallet("w2", "polkadot", ALICE),
+            wallet("w3", "ethereum", ASTAR_EVM),
+        ];
+        let suggestions = build_suggestions(&wallets, &[], &pairs_for(&wallets));
+
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].paired_chain, "astar-substrate");
+        assert_eq!(suggestions[0].paired_address, ASTAR_NATIVE);
+        assert_eq!(suggestions[0].method, "astar_default");
+        assert!(suggestions[0].paired_wallet_id.is_none());
+        assert_eq!(suggestions[1].paired_chain, "moonbeam");
+        assert_eq!(suggestions[1].method, MappingMethod::MoonbeamXcm.as_str());
+    }
+
+    #[test]
+    fn test_tracked_pairs_suggested_once_until_linked() {
+        let wallets = vec![
+            wallet("w1", "astar", ASTAR_EVM),
+            wallet("w2", "astar-substrate", ASTAR_NATIVE),
+        ];
+        let mut pairs = pairs_for(&wallets);
+        // w2's default EVM address isn't w1, but a claimed unified account is.
+        pairs.insert(
+            "w2".to_string(),
+            vec![PairedAccount {
+                chain: "astar".to_string(),
+                address: ASTAR_EVM.to_lowercase(),
+                method: MappingMethod::AstarUnified,
+            }],
+        );
+
+        let suggestions = build_suggestions(&wallets, &[], &pairs);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].wallet_id, "w1");
+        assert_eq!(suggestions[0].paired_wallet_id.as_deref(), Some("w2"));
+
+        let linked = build_suggestions(&wallets, &[("w2".to_string(), "w1".to_string())], &pairs);
+        assert!(linked.is_empty());
+    }
+}
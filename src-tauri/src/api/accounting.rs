@@ -1,9 +1,22 @@
+use std::str::FromStr;
+
 use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use tauri::State;
 
+use super::audit_trail::{record_change, RecordType};
+use super::auth::verify_admin;
+use super::period_close::ensure_period_open;
+use super::permissions::Permission;
 use super::persistence::DatabaseState;
+use super::profile_scope::{authenticate, authorize_profile, authorize_record};
+use crate::core::auth_state::AuthState;
+use crate::core::cost_basis::IncomeSource;
+use crate::core::currency::round_fiat;
+
+pub use pacioli_core::services::accounting::*;
 
 // ============================================================================
 // Types — Chart of Accounts
@@ -89,6 +102,8 @@ pub struct UpdateGlAccountInput {
 pub struct JournalEntry {
     /// Auto-incremented primary key.
     pub id: i64,
+    /// Profile the entry belongs to; older entries have none.
+    pub profile_id: Option<String>,
     /// Date of the accounting event.
     pub entry_date: NaiveDateTime,
     /// Unique sequential entry number.
@@ -124,9 +139,11 @@ pub struct JournalEntryLine {
     /// Optional FK to tokens table.
     pub token_id: Option<i64>,
     /// Debit amount (0 if this is a credit line).
-    pub debit_amount: f64,
+    #[sqlx(try_from = "f64")]
+    pub debit_amount: Decimal,
     /// Credit amount (0 if this is a debit line).
-    pub credit_amount: f64,
+    #[sqlx(try_from = "f64")]
+    pub credit_amount: Decimal,
     /// Optional line-level description.
     pub description: Option<String>,
     /// Ordering within the entry.
@@ -155,9 +172,9 @@ pub struct JournalEntryLineInput {
     /// Optional FK to tokens table.
     pub token_id: Option<i64>,
     /// Debit amount (0 if credit line).
-    pub debit_amount: f64,
+    pub debit_amount: Decimal,
     /// Credit amount (0 if debit line).
-    pub credit_amount: f64,
+    pub credit_amount: Decimal,
     /// Optional memo for this line.
     pub description: Option<String>,
 }
@@ -166,6 +183,9 @@ pub struct JournalEntryLineInput {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NewJournalEntryInput {
+    /// Profile the entry belongs to.
+    #[serde(default)]
+    pub profile_id: Option<String>,
     /// Date of the accounting event (ISO 8601).
     pub entry_date: String,
     /// Description of the entry.
@@ -178,52 +198,6 @@ pub struct NewJournalEntryInput {
     pub lines: Vec<JournalEntryLineInput>,
 }
 
-// ============================================================================
-// Types — Account Balance
-// ============================================================================
-
-/// Account balance from the v_account_balances view.
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-#[serde(rename_all = "camelCase")]
-pub struct AccountBalance {
-    /// GL account ID.
-    pub account_id: i64,
-    /// Account number.
-    pub account_number: String,
-    /// Account name.
-    pub account_name: String,
-    /// Account type (Asset/Liability/Equity/Income/Expense).
-    pub account_type: String,
-    /// Digital asset type, if applicable.
-    pub digital_asset_type: Option<String>,
-    /// Normal balance direction (debit/credit).
-    pub normal_balance: Option<String>,
-    /// Total debits posted.
-    pub total_debits: f64,
-    /// Total credits posted.
-    pub total_credits: f64,
-    /// Balance in natural direction.
-    pub balance: f64,
-    /// Signed balance for reporting.
-    pub balance_signed: f64,
-}
-
-/// Trial balance row from the v_trial_balance view.
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-#[serde(rename_all = "camelCase")]
-pub struct TrialBalanceRow {
-    /// Account number.
-    pub account_number: String,
-    /// Account name.
-    pub account_name: String,
-    /// Account type.
-    pub account_type: String,
-    /// Debit balance (0 if credit side).
-    pub debit_balance: f64,
-    /// Credit balance (0 if debit side).
-    pub credit_balance: f64,
-}
-
 // ============================================================================
 // Chart of Accounts Commands
 // ============================================================================
@@ -232,7 +206,10 @@ pub struct TrialBalanceRow {
 #[tauri::command]
 pub async fn get_chart_of_accounts(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
 ) -> Result<Vec<GlAccount>, String> {
+    authenticate(&auth, &token)?;
     sqlx::query_as::<_, GlAccount>(
         "SELECT * FROM gl_accounts WHERE is_active = 1 ORDER BY account_number",
     )
@@ -241,12 +218,27 @@ pub async fn get_chart_of_accounts(
     .map_err(|e| e.to_string())
 }
 
-/// Creates a new GL account and returns it.
+/// Verifies `token` belongs to the app administrator. The chart of accounts
+/// is shared by every profile, so no profile role is enough to change it.
+async fn authorize_chart_change(
+    pool: &sqlx::SqlitePool,
+    auth: &AuthState,
+    token: &str,
+) -> Result<(), String> {
+    let user_id = authenticate(auth, token)?;
+    verify_admin(pool, &user_id).await
+}
+
+/// Creates a new GL account and returns it (app administrator only).
 #[tauri::command]
 pub async fn create_gl_account(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     input: NewGlAccountInput,
 ) -> Result<GlAccount, String> {
+    authorize_chart_change(&state.pool, &auth, &token).await?;
+
     // Default normal_balance based on account_type
     let normal_balance =
         input
@@ -287,13 +279,18 @@ pub async fn create_gl_account(
         .map_err(|e| e.to_string())
 }
 
-/// Updates an existing GL account. Only editable accounts can be modified.
+/// Updates an existing GL account (app administrator only). Only editable
+/// accounts can be modified.
 #[tauri::command]
 pub async fn update_gl_account(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     id: i64,
     input: UpdateGlAccountInput,
 ) -> Result<GlAccount, String> {
+    authorize_chart_change(&state.pool, &auth, &token).await?;
+
     // Verify the account is editable
     let account = sqlx::query_as::<_, GlAccount>("SELECT * FROM gl_accounts WHERE id = ?")
         .bind(id)
@@ -334,9 +331,17 @@ pub async fn update_gl_account(
         .map_err(|e| e.to_string())
 }
 
-/// Deactivates a GL account (soft delete). Only editable accounts can be deactivated.
+/// Deactivates a GL account (soft delete, app administrator only). Only
+/// editable accounts can be deactivated.
 #[tauri::command]
-pub async fn deactivate_gl_account(state: State<'_, DatabaseState>, id: i64) -> Result<(), String> {
+pub async fn deactivate_gl_account(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    id: i64,
+) -> Result<(), String> {
+    authorize_chart_change(&state.pool, &auth, &token).await?;
+
     let account = sqlx::query_as::<_, GlAccount>("SELECT * FROM gl_accounts WHERE id = ?")
         .bind(id)
         .fetch_optional(&state.pool)
@@ -361,111 +366,236 @@ pub async fn deactivate_gl_account(state: State<'_, DatabaseState>, id: i64) ->
 // Journal Entry Commands
 // ============================================================================
 
-/// Returns journal entries matching the given status filter.
+/// Returns a profile's journal entries matching the given status filter.
 #[tauri::command]
 pub async fn get_journal_entries(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
     status_filter: Option<String>,
     limit: Option<i32>,
     offset: Option<i32>,
 ) -> Result<Vec<JournalEntryWithLines>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
     let limit = limit.unwrap_or(100);
     let offset = offset.unwrap_or(0);
 
-    let entries = match status_filter.as_deref() {
-        Some("posted") => {
-            sqlx::query_as::<_, JournalEntry>(
-                "SELECT * FROM journal_entries WHERE is_posted = 1 AND is_reversed = 0 ORDER BY entry_date DESC LIMIT ? OFFSET ?",
-            )
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&state.pool)
-            .await
-        }
-        Some("draft") => {
-            sqlx::query_as::<_, JournalEntry>(
-                "SELECT * FROM journal_entries WHERE is_posted = 0 AND is_reversed = 0 ORDER BY entry_date DESC LIMIT ? OFFSET ?",
-            )
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&state.pool)
-            .await
-        }
-        Some("void") => {
-            sqlx::query_as::<_, JournalEntry>(
-                "SELECT * FROM journal_entries WHERE is_reversed = 1 ORDER BY entry_date DESC LIMIT ? OFFSET ?",
-            )
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&state.pool)
-            .await
-        }
-        _ => {
-            sqlx::query_as::<_, JournalEntry>(
-                "SELECT * FROM journal_entries ORDER BY entry_date DESC LIMIT ? OFFSET ?",
-            )
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&state.pool)
-            .await
-        }
-    }
-    .map_err(|e| e.to_string())?;
-
-    let mut result = Vec::with_capacity(entries.len());
-    for entry in entries {
-        let lines = sqlx::query_as::<_, JournalEntryLine>(
-            "SELECT * FROM journal_entry_lines WHERE journal_entry_id = ? ORDER BY line_number",
-        )
-        .bind(entry.id)
+    let status = match status_filter.as_deref() {
+        Some("posted") => "AND is_posted = 1 AND is_reversed = 0",
+        Some("draft") => "AND is_posted = 0 AND is_reversed = 0",
+        Some("void") => "AND is_reversed = 1",
+        _ => "",
+    };
+    let query = format!(
+        "SELECT * FROM journal_entries WHERE profile_id = ? {} ORDER BY entry_date DESC LIMIT ? OFFSET ?",
+        status
+    );
+    let entries = sqlx::query_as::<_, JournalEntry>(&query)
+        .bind(&profile_id)
+        .bind(limit)
+        .bind(offset)
         .fetch_all(&state.pool)
         .await
         .map_err(|e| e.to_string())?;
 
+    let currency = ledger_currency(&state.pool).await?;
+    let mut result = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let lines = load_lines(&state.pool, entry.id, &currency).await?;
         result.push(JournalEntryWithLines { entry, lines });
     }
 
     Ok(result)
 }
 
-/// Returns a single journal entry with its lines.
-#[tauri::command]
-pub async fn get_journal_entry(
-    state: State<'_, DatabaseState>,
+/// Loads a journal entry with its lines.
+async fn load_journal_entry(
+    pool: &sqlx::SqlitePool,
     id: i64,
 ) -> Result<JournalEntryWithLines, String> {
     let entry = sqlx::query_as::<_, JournalEntry>("SELECT * FROM journal_entries WHERE id = ?")
         .bind(id)
-        .fetch_optional(&state.pool)
+        .fetch_optional(pool)
         .await
         .map_err(|e| e.to_string())?
         .ok_or_else(|| "Journal entry not found".to_string())?;
 
-    let lines = sqlx::query_as::<_, JournalEntryLine>(
+    let currency = ledger_currency(pool).await?;
+    let lines = load_lines(pool, entry.id, &currency).await?;
+
+    Ok(JournalEntryWithLines { entry, lines })
+}
+
+/// Loads the lines of a journal entry.
+///
+/// SQLite stores the amounts as floats, so they are rounded back to the
+/// currency's minor unit on the way out.
+async fn load_lines(
+    pool: &sqlx::SqlitePool,
+    entry_id: i64,
+    currency: &str,
+) -> Result<Vec<JournalEntryLine>, String> {
+    let mut lines = sqlx::query_as::<_, JournalEntryLine>(
         "SELECT * FROM journal_entry_lines WHERE journal_entry_id = ? ORDER BY line_number",
     )
-    .bind(entry.id)
-    .fetch_all(&state.pool)
+    .bind(entry_id)
+    .fetch_all(pool)
     .await
     .map_err(|e| e.to_string())?;
 
-    Ok(JournalEntryWithLines { entry, lines })
+    for line in &mut lines {
+        line.debit_amount = round_fiat(line.debit_amount, currency);
+        line.credit_amount = round_fiat(line.credit_amount, currency);
+    }
+    Ok(lines)
+}
+
+/// Verifies `token` and loads journal entry `id`, checking the caller's role
+/// on the entry's profile grants `permission`. Entries from before entries
+/// were tied to a profile are left to the app administrator.
+async fn authorize_entry(
+    pool: &sqlx::SqlitePool,
+    auth: &AuthState,
+    token: &str,
+    id: i64,
+    permission: Permission,
+) -> Result<(String, JournalEntryWithLines), String> {
+    let user_id = authenticate(auth, token)?;
+    let not_found = "Journal entry not found";
+    let entry = load_journal_entry(pool, id)
+        .await
+        .map_err(|_| not_found.to_string())?;
+    match entry.entry.profile_id.as_deref() {
+        Some(profile_id) => {
+            authorize_record(pool, &user_id, profile_id, permission, not_found).await?
+        }
+        None => verify_admin(pool, &user_id)
+            .await
+            .map_err(|_| not_found.to_string())?,
+    }
+    Ok((user_id, entry))
+}
+
+/// A raw transaction that involves one of `profile_id`'s wallets, as a
+/// sender or recipient. Raw transactions aren't tied to a profile, so this
+/// is what lets a profile see one.
+async fn profile_raw_transaction(
+    pool: &sqlx::SqlitePool,
+    profile_id: &str,
+    transaction_id: &str,
+) -> Result<MultiChainTx, String> {
+    let query = format!(
+        "SELECT id, chain_id, hash, from_address, to_address, value, fee, timestamp, tx_type, status FROM multi_chain_transactions mct WHERE id = ? AND {}",
+        PROFILE_RAW_TRANSACTION
+    );
+    sqlx::query_as::<_, MultiChainTx>(&query)
+        .bind(transaction_id)
+        .bind(profile_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Transaction not found".to_string())
+}
+
+/// Matches raw transactions (aliased `mct`) sent from or to a wallet of the
+/// profile bound to its placeholder.
+const PROFILE_RAW_TRANSACTION: &str = "EXISTS (
+    SELECT 1 FROM wallets w
+    WHERE w.profile_id = ?
+      AND (LOWER(w.address) = LOWER(mct.from_address) OR LOWER(w.address) = LOWER(mct.to_address))
+)";
+
+/// Records a change to a journal entry in the audit trail.
+async fn record_journal_change(
+    pool: &sqlx::SqlitePool,
+    actor: &str,
+    before: Option<&JournalEntryWithLines>,
+    after: &JournalEntryWithLines,
+) -> Result<(), String> {
+    record_change(
+        pool,
+        Some(actor),
+        RecordType::JournalEntry,
+        &after.entry.id.to_string(),
+        after.entry.profile_id.as_deref(),
+        before,
+        Some(after),
+    )
+    .await
+}
+
+/// Returns a single journal entry with its lines.
+#[tauri::command]
+pub async fn get_journal_entry(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    id: i64,
+) -> Result<JournalEntryWithLines, String> {
+    let (_, entry) =
+        authorize_entry(&state.pool, &auth, &token, id, Permission::ViewTransactions).await?;
+    Ok(entry)
 }
 
 /// Creates a new journal entry as a draft with the given lines.
 #[tauri::command]
 pub async fn create_journal_entry(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     input: NewJournalEntryInput,
+) -> Result<JournalEntryWithLines, String> {
+    let profile_id = input
+        .profile_id
+        .clone()
+        .ok_or_else(|| "A journal entry needs a profile".to_string())?;
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::CreateJournalEntries,
+    )
+    .await?;
+    if let Some(tx_id) = &input.raw_transaction_id {
+        profile_raw_transaction(&state.pool, &profile_id, tx_id).await?;
+    }
+    insert_journal_entry(&state.pool, &user_id, input).await
+}
+
+/// Inserts a draft journal entry for `input.profile_id`, which `user_id` has
+/// already been authorized to create entries in.
+pub(crate) async fn insert_journal_entry(
+    pool: &sqlx::SqlitePool,
+    user_id: &str,
+    mut input: NewJournalEntryInput,
 ) -> Result<JournalEntryWithLines, String> {
     if input.lines.is_empty() {
         return Err("Journal entry must have at least one line".to_string());
     }
 
+    // Amounts are kept to the reporting currency's minor unit
+    let currency = ledger_currency(pool).await?;
+    for line in &mut input.lines {
+        line.debit_amount = round_fiat(line.debit_amount, &currency);
+        line.credit_amount = round_fiat(line.credit_amount, &currency);
+    }
+
     // Validate each line has exactly one of debit or credit > 0
     for line in &input.lines {
-        if (line.debit_amount > 0.0 && line.credit_amount > 0.0)
-            || (line.debit_amount == 0.0 && line.credit_amount == 0.0)
+        if line.debit_amount < Decimal::ZERO || line.credit_amount < Decimal::ZERO {
+            return Err("Line amounts cannot be negative".to_string());
+        }
+        if (line.debit_amount > Decimal::ZERO && line.credit_amount > Decimal::ZERO)
+            || (line.debit_amount.is_zero() && line.credit_amount.is_zero())
         {
             return Err("Each line must have exactly one of debit or credit amount".to_string());
         }
@@ -479,25 +609,27 @@ pub async fn create_journal_entry(
             )
         })
         .map_err(|e| format!("Invalid date format: {e}"))?;
+    ensure_period_open(pool, input.profile_id.as_deref(), entry_date.date()).await?;
 
     // Generate entry number
     let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM journal_entries")
-        .fetch_one(&state.pool)
+        .fetch_one(pool)
         .await
         .map_err(|e| e.to_string())?;
     let entry_number = format!("JE-{:06}", count.0 + 1);
 
     let result = sqlx::query(
         r#"
-        INSERT INTO journal_entries (entry_date, entry_number, description, reference_number, is_posted, created_by)
-        VALUES (?, ?, ?, ?, 0, 'system')
+        INSERT INTO journal_entries (profile_id, entry_date, entry_number, description, reference_number, is_posted, created_by)
+        VALUES (?, ?, ?, ?, ?, 0, 'system')
         "#,
     )
+    .bind(&input.profile_id)
     .bind(entry_date)
     .bind(&entry_number)
     .bind(&input.description)
     .bind(&input.reference_number)
-    .execute(&state.pool)
+    .execute(pool)
     .await
     .map_err(|e| e.to_string())?;
 
@@ -514,11 +646,11 @@ pub async fn create_journal_entry(
         .bind(entry_id)
         .bind(line.gl_account_id)
         .bind(line.token_id)
-        .bind(line.debit_amount)
-        .bind(line.credit_amount)
+        .bind(line.debit_amount.to_string())
+        .bind(line.credit_amount.to_string())
         .bind(&line.description)
         .bind(i as i64 + 1)
-        .execute(&state.pool)
+        .execute(pool)
         .await
         .map_err(|e| e.to_string())?;
     }
@@ -529,26 +661,34 @@ pub async fn create_journal_entry(
             "UPDATE multi_chain_transactions SET classification_status = 'classified' WHERE id = ?",
         )
         .bind(tx_id)
-        .execute(&state.pool)
+        .execute(pool)
         .await
         .map_err(|e| e.to_string())?;
     }
 
-    get_journal_entry(state, entry_id).await
+    let created = load_journal_entry(pool, entry_id).await?;
+    record_journal_change(pool, user_id, None, &created).await?;
+
+    Ok(created)
 }
 
 /// Posts a draft journal entry (validates debits = credits via DB trigger).
 #[tauri::command]
 pub async fn post_journal_entry(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     id: i64,
 ) -> Result<JournalEntryWithLines, String> {
-    let entry = sqlx::query_as::<_, JournalEntry>("SELECT * FROM journal_entries WHERE id = ?")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "Journal entry not found".to_string())?;
+    let (user_id, before) = authorize_entry(
+        &state.pool,
+        &auth,
+        &token,
+        id,
+        Permission::CreateJournalEntries,
+    )
+    .await?;
+    let entry = &before.entry;
 
     if entry.is_posted {
         return Err("Journal entry is already posted".to_string());
@@ -558,19 +698,20 @@ pub async fn post_journal_entry(
         return Err("Cannot post a reversed entry".to_string());
     }
 
-    // Validate balance before posting (the DB trigger also enforces this)
-    let balance: (f64,) = sqlx::query_as(
-        "SELECT ABS(SUM(debit_amount) - SUM(credit_amount)) FROM journal_entry_lines WHERE journal_entry_id = ?",
+    ensure_period_open(
+        &state.pool,
+        entry.profile_id.as_deref(),
+        entry.entry_date.date(),
     )
-    .bind(id)
-    .fetch_one(&state.pool)
-    .await
-    .map_err(|e| e.to_string())?;
+    .await?;
 
-    if balance.0 > 0.01 {
+    // Validate balance before posting (the DB trigger also enforces this)
+    let debits: Decimal = before.lines.iter().map(|l| l.debit_amount).sum();
+    let credits: Decimal = before.lines.iter().map(|l| l.credit_amount).sum();
+    if debits != credits {
         return Err(format!(
-            "Journal entry is not balanced. Difference: {:.2}",
-            balance.0
+            "Journal entry is not balanced. Difference: {}",
+            (debits - credits).abs()
         ));
     }
 
@@ -581,103 +722,149 @@ pub async fn post_journal_entry(
         .await
         .map_err(|e| e.to_string())?;
 
-    get_journal_entry(state, id).await
+    let after = load_journal_entry(&state.pool, id).await?;
+    record_journal_change(&state.pool, &user_id, Some(&before), &after).await?;
+
+    Ok(after)
 }
 
 /// Voids a posted journal entry by marking it as reversed.
 #[tauri::command]
 pub async fn void_journal_entry(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     id: i64,
 ) -> Result<JournalEntryWithLines, String> {
-    let entry = sqlx::query_as::<_, JournalEntry>("SELECT * FROM journal_entries WHERE id = ?")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "Journal entry not found".to_string())?;
+    let (user_id, before) = authorize_entry(
+        &state.pool,
+        &auth,
+        &token,
+        id,
+        Permission::CreateJournalEntries,
+    )
+    .await?;
+    let entry = &before.entry;
 
     if entry.is_reversed {
         return Err("Journal entry is already voided".to_string());
     }
 
+    ensure_period_open(
+        &state.pool,
+        entry.profile_id.as_deref(),
+        entry.entry_date.date(),
+    )
+    .await?;
+
     sqlx::query("UPDATE journal_entries SET is_reversed = 1 WHERE id = ?")
         .bind(id)
         .execute(&state.pool)
         .await
         .map_err(|e| e.to_string())?;
 
-    get_journal_entry(state, id).await
+    let after = load_journal_entry(&state.pool, id).await?;
+    record_journal_change(&state.pool, &user_id, Some(&before), &after).await?;
+
+    Ok(after)
 }
 
 // ============================================================================
 // Auto-Classify Command
 // ============================================================================
 
+/// GL account number and line label for income of the given source.
+fn income_account(source: IncomeSource) -> (&'static str, &'static str) {
+    match source {
+        IncomeSource::Staking => ("4100", "Staking reward"),
+        IncomeSource::Airdrop => ("4400", "Airdrop"),
+        IncomeSource::Mining => ("4600", "Mining reward"),
+        IncomeSource::LiquidityFees => ("4700", "Liquidity fees"),
+        IncomeSource::Other => ("4000", "Reward"),
+    }
+}
+
 /// Auto-classifies a raw multi_chain_transaction into a draft journal entry
 /// using basic heuristics based on the transaction type.
+///
+/// Claims and stakes are treated as staking rewards unless `income_source`
+/// says otherwise. Income is booked at `fair_market_value` when given,
+/// otherwise at the raw transaction value.
 #[tauri::command]
 pub async fn auto_classify_transaction(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
     transaction_id: String,
+    income_source: Option<IncomeSource>,
+    fair_market_value: Option<Decimal>,
 ) -> Result<JournalEntryWithLines, String> {
-    // Fetch the raw transaction
-    let tx = sqlx::query_as::<_, MultiChainTx>(
-        "SELECT id, chain_id, hash, from_address, to_address, value, fee, timestamp, tx_type, status FROM multi_chain_transactions WHERE id = ?",
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::CreateJournalEntries,
     )
-    .bind(&transaction_id)
-    .fetch_optional(&state.pool)
-    .await
-    .map_err(|e| e.to_string())?
-    .ok_or_else(|| "Transaction not found".to_string())?;
+    .await?;
+    let tx = profile_raw_transaction(&state.pool, &profile_id, &transaction_id).await?;
 
     // Resolve GL account IDs
     let crypto_assets_id = get_account_id_by_number(&state.pool, "1200").await?;
-    let staking_income_id = get_account_id_by_number(&state.pool, "4100").await?;
     let network_fees_id = get_account_id_by_number(&state.pool, "5100").await?;
     let income_id = get_account_id_by_number(&state.pool, "4000").await?;
 
     // Parse amount
-    let amount: f64 = tx.value.parse().unwrap_or(0.0);
-    let fee_amount: f64 = tx.fee.as_deref().unwrap_or("0").parse().unwrap_or(0.0);
+    let amount = Decimal::from_str(&tx.value).unwrap_or(Decimal::ZERO);
+    let fee_amount = Decimal::from_str(tx.fee.as_deref().unwrap_or("0")).unwrap_or(Decimal::ZERO);
+
+    let income_source = income_source.or(match tx.tx_type.as_str() {
+        "claim" | "stake" => Some(IncomeSource::Staking),
+        "crowdloan_reward" => Some(IncomeSource::Other),
+        _ => None,
+    });
 
     // Build lines based on tx_type heuristics
     let mut lines = Vec::new();
-    let description = match tx.tx_type.as_str() {
-        "claim" | "stake" => {
-            // Staking reward: DR Crypto Assets / CR Staking Income
-            if amount > 0.0 {
+    let description = match (income_source, tx.tx_type.as_str()) {
+        (Some(source), _) => {
+            // Income in kind: DR Crypto Assets / CR income account at fair value
+            let (account_number, label) = income_account(source);
+            let income_account_id = get_account_id_by_number(&state.pool, account_number).await?;
+            let income_amount = fair_market_value.unwrap_or(amount);
+            if income_amount > Decimal::ZERO {
                 lines.push(JournalEntryLineInput {
                     gl_account_id: crypto_assets_id,
                     token_id: None,
-                    debit_amount: amount,
-                    credit_amount: 0.0,
-                    description: Some("Staking reward received".to_string()),
+                    debit_amount: income_amount,
+                    credit_amount: Decimal::ZERO,
+                    description: Some(format!("{} received", label)),
                 });
                 lines.push(JournalEntryLineInput {
-                    gl_account_id: staking_income_id,
+                    gl_account_id: income_account_id,
                     token_id: None,
-                    debit_amount: 0.0,
-                    credit_amount: amount,
-                    description: Some("Staking reward income".to_string()),
+                    debit_amount: Decimal::ZERO,
+                    credit_amount: income_amount,
+                    description: Some(format!("{} income", label)),
                 });
             }
-            format!("Staking reward on {}", tx.chain_id)
+            format!("{} on {}", label, tx.chain_id)
         }
-        "transfer" => {
+        (None, "transfer") => {
             // Incoming transfer: DR Crypto Assets / CR Income (uncategorized)
-            if amount > 0.0 {
+            if amount > Decimal::ZERO {
                 lines.push(JournalEntryLineInput {
                     gl_account_id: crypto_assets_id,
                     token_id: None,
                     debit_amount: amount,
-                    credit_amount: 0.0,
+                    credit_amount: Decimal::ZERO,
                     description: Some("Transfer received".to_string()),
                 });
                 lines.push(JournalEntryLineInput {
                     gl_account_id: income_id,
                     token_id: None,
-                    debit_amount: 0.0,
+                    debit_amount: Decimal::ZERO,
                     credit_amount: amount,
                     description: Some("Uncategorized income — review and reclassify".to_string()),
                 });
@@ -688,20 +875,20 @@ pub async fn auto_classify_transaction(
                 &tx.hash[..8.min(tx.hash.len())]
             )
         }
-        _ => {
+        (None, _) => {
             // Default: if there's a fee, record it as an expense
-            if fee_amount > 0.0 {
+            if fee_amount > Decimal::ZERO {
                 lines.push(JournalEntryLineInput {
                     gl_account_id: network_fees_id,
                     token_id: None,
                     debit_amount: fee_amount,
-                    credit_amount: 0.0,
+                    credit_amount: Decimal::ZERO,
                     description: Some("Network/gas fee".to_string()),
                 });
                 lines.push(JournalEntryLineInput {
                     gl_account_id: crypto_assets_id,
                     token_id: None,
-                    debit_amount: 0.0,
+                    debit_amount: Decimal::ZERO,
                     credit_amount: fee_amount,
                     description: Some("Fee paid from crypto assets".to_string()),
                 });
@@ -720,15 +907,15 @@ pub async fn auto_classify_transaction(
         lines.push(JournalEntryLineInput {
             gl_account_id: crypto_assets_id,
             token_id: None,
-            debit_amount: 0.01,
-            credit_amount: 0.0,
+            debit_amount: Decimal::new(1, 2),
+            credit_amount: Decimal::ZERO,
             description: Some("Placeholder — update amounts".to_string()),
         });
         lines.push(JournalEntryLineInput {
             gl_account_id: income_id,
             token_id: None,
-            debit_amount: 0.0,
-            credit_amount: 0.01,
+            debit_amount: Decimal::ZERO,
+            credit_amount: Decimal::new(1, 2),
             description: Some("Placeholder — update amounts".to_string()),
         });
     }
@@ -739,6 +926,7 @@ pub async fn auto_classify_transaction(
         .unwrap_or_else(|| chrono::Utc::now().format("%Y-%m-%d").to_string());
 
     let input = NewJournalEntryInput {
+        profile_id: Some(profile_id),
         entry_date,
         description,
         reference_number: Some(tx.hash.clone()),
@@ -746,7 +934,7 @@ pub async fn auto_classify_transaction(
         lines,
     };
 
-    create_journal_entry(state, input).await
+    insert_journal_entry(&state.pool, &user_id, input).await
 }
 
 /// Lightweight row for reading multi_chain_transactions during auto-classify.
@@ -779,7 +967,10 @@ struct MultiChainTx {
 }
 
 /// Resolves a GL account number to its database ID.
-async fn get_account_id_by_number(pool: &sqlx::SqlitePool, number: &str) -> Result<i64, String> {
+pub(crate) async fn get_account_id_by_number(
+    pool: &sqlx::SqlitePool,
+    number: &str,
+) -> Result<i64, String> {
     let row: (i64,) =
         sqlx::query_as("SELECT id FROM gl_accounts WHERE account_number = ? AND is_active = 1")
             .bind(number)
@@ -798,9 +989,22 @@ async fn get_account_id_by_number(pool: &sqlx::SqlitePool, number: &str) -> Resu
 #[tauri::command]
 pub async fn update_transaction_classification(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
     transaction_id: String,
     classification_status: String,
 ) -> Result<(), String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    profile_raw_transaction(&state.pool, &profile_id, &transaction_id).await?;
+
     let valid = ["unclassified", "classified", "ignored", "split"];
     if !valid.contains(&classification_status.as_str()) {
         return Err(format!(
@@ -808,6 +1012,21 @@ pub async fn update_transaction_classification(
         ));
     }
 
+    // Raw transactions aren't tied to a profile, so any profile's close applies.
+    let current: Option<(i64, String)> = sqlx::query_as(
+        "SELECT timestamp, classification_status FROM multi_chain_transactions WHERE id = ?",
+    )
+    .bind(&transaction_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    if let Some(date) = current
+        .as_ref()
+        .and_then(|(ts, _)| chrono::DateTime::from_timestamp(*ts, 0))
+    {
+        ensure_period_open(&state.pool, None, date.date_naive()).await?;
+    }
+
     sqlx::query("UPDATE multi_chain_transactions SET classification_status = ? WHERE id = ?")
         .bind(&classification_status)
         .bind(&transaction_id)
@@ -815,6 +1034,19 @@ pub async fn update_transaction_classification(
         .await
         .map_err(|e| e.to_string())?;
 
+    if let Some((_, previous)) = current {
+        record_change(
+            &state.pool,
+            Some(&user_id),
+            RecordType::RawTransaction,
+            &transaction_id,
+            None,
+            Some(&serde_json::json!({ "classificationStatus": previous })),
+            Some(&serde_json::json!({ "classificationStatus": classification_status })),
+        )
+        .await?;
+    }
+
     Ok(())
 }
 
@@ -822,49 +1054,95 @@ pub async fn update_transaction_classification(
 // Ledger Query Commands
 // ============================================================================
 
-/// Returns account balances from the v_account_balances view.
+/// Returns a profile's account balances from its posted journal entries.
 #[tauri::command]
 pub async fn get_account_balances(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
 ) -> Result<Vec<AccountBalance>, String> {
-    sqlx::query_as::<_, AccountBalance>("SELECT * FROM v_account_balances ORDER BY account_number")
-        .fetch_all(&state.pool)
-        .await
-        .map_err(|e| e.to_string())
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    account_balances(&state.pool, &profile_id).await
 }
 
-/// Returns the trial balance from the v_trial_balance view.
+/// Returns a profile's trial balance from its posted journal entries.
 #[tauri::command]
 pub async fn get_trial_balance(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
 ) -> Result<Vec<TrialBalanceRow>, String> {
-    sqlx::query_as::<_, TrialBalanceRow>("SELECT * FROM v_trial_balance ORDER BY account_number")
-        .fetch_all(&state.pool)
-        .await
-        .map_err(|e| e.to_string())
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    trial_balance(&state.pool, &profile_id).await
 }
 
-/// Returns the count of unclassified multi-chain transactions.
+/// Returns the count of unclassified multi-chain transactions involving a
+/// profile's wallets.
 #[tauri::command]
 pub async fn get_unclassified_transaction_count(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
 ) -> Result<i64, String> {
-    let row: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM multi_chain_transactions WHERE classification_status = 'unclassified'",
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
     )
-    .fetch_one(&state.pool)
-    .await
-    .map_err(|e| e.to_string())?;
+    .await?;
+    let query = format!(
+        "SELECT COUNT(*) FROM multi_chain_transactions mct WHERE classification_status = 'unclassified' AND {}",
+        PROFILE_RAW_TRANSACTION
+    );
+    let row: (i64,) = sqlx::query_as(&query)
+        .bind(&profile_id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
 
     Ok(row.0)
 }
 
-/// Returns the count of draft (unposted, non-reversed) journal entries.
+/// Returns the count of a profile's draft (unposted, non-reversed) journal
+/// entries.
 #[tauri::command]
-pub async fn get_draft_journal_entry_count(state: State<'_, DatabaseState>) -> Result<i64, String> {
+pub async fn get_draft_journal_entry_count(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<i64, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
     let row: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM journal_entries WHERE is_posted = 0 AND is_reversed = 0",
+        "SELECT COUNT(*) FROM journal_entries WHERE profile_id = ? AND is_posted = 0 AND is_reversed = 0",
     )
+    .bind(&profile_id)
     .fetch_one(&state.pool)
     .await
     .map_err(|e| e.to_string())?;
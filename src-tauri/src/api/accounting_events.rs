@@ -0,0 +1,578 @@
+//! Economic events within wallet transactions.
+//!
+//! One on-chain transaction can carry many economic events: a multicall of
+//! swaps, a batch of NFT mints, a disperse contract paying out to dozens of
+//! addresses. Wallet sync expands each transaction into accounting events
+//! from what was decoded from it — its swaps, its token transfers, its
+//! native value and its fee — so each event can be tagged and booked to the
+//! ledger on its own.
+
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteExecutor, SqlitePool};
+use tauri::State;
+
+use super::accounting::{
+    get_account_id_by_number, insert_journal_entry, JournalEntryLineInput, JournalEntryWithLines,
+    NewJournalEntryInput,
+};
+use super::audit_trail::{record_change, RecordType};
+use super::period_close::ensure_period_open;
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction};
+use super::profile_scope::{authorize_profile, authorize_wallet};
+use crate::chains::TokenTransfer;
+use crate::core::auth_state::AuthState;
+
+pub use pacioli_core::services::accounting_events::*;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A stored accounting event.
+#[derive(Debug, Clone, FromRow)]
+struct EventRow {
+    id: String,
+    wallet_id: String,
+    hash: String,
+    event_index: i64,
+    kind: String,
+    sent_asset: Option<String>,
+    sent_symbol: Option<String>,
+    sent_decimals: Option<i64>,
+    sent_amount: Option<String>,
+    received_asset: Option<String>,
+    received_symbol: Option<String>,
+    received_decimals: Option<i64>,
+    received_amount: Option<String>,
+    counterparty: Option<String>,
+    category: Option<String>,
+    entity_id: Option<String>,
+    journal_entry_id: Option<i64>,
+}
+
+/// One economic event of a wallet transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountingEvent {
+    /// Unique identifier.
+    pub id: String,
+    /// Wallet the transaction belongs to.
+    pub wallet_id: String,
+    /// Transaction hash.
+    pub hash: String,
+    /// Position among the transaction's events.
+    pub event_index: i64,
+    /// What the event is.
+    pub kind: AccountingEventKind,
+    /// Asset leaving the wallet.
+    pub sent: Option<EventAmount>,
+    /// Asset entering the wallet.
+    pub received: Option<EventAmount>,
+    /// The other side of a transfer.
+    pub counterparty: Option<String>,
+    /// Budget category tagged on the event.
+    pub category: Option<String>,
+    /// Entity tagged on the event.
+    pub entity_id: Option<String>,
+    /// Journal entry the event is booked in, once booked.
+    pub journal_entry_id: Option<i64>,
+}
+
+/// An amount from its stored columns; `None` unless both the asset and the
+/// amount are set.
+fn stored_amount(
+    asset: Option<String>,
+    symbol: Option<String>,
+    decimals: Option<i64>,
+    amount: Option<String>,
+) -> Option<EventAmount> {
+    Some(EventAmount {
+        asset: asset?,
+        symbol,
+        decimals: decimals.and_then(|d| u8::try_from(d).ok()),
+        amount: amount?,
+    })
+}
+
+impl TryFrom<EventRow> for AccountingEvent {
+    type Error = String;
+
+    fn try_from(row: EventRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            kind: AccountingEventKind::parse(&row.kind)
+                .ok_or_else(|| format!("Unknown accounting event kind: {}", row.kind))?,
+            sent: stored_amount(
+                row.sent_asset,
+                row.sent_symbol,
+                row.sent_decimals,
+                row.sent_amount,
+            ),
+            received: stored_amount(
+                row.received_asset,
+                row.received_symbol,
+                row.received_decimals,
+                row.received_amount,
+            ),
+            id: row.id,
+            wallet_id: row.wallet_id,
+            hash: row.hash,
+            event_index: row.event_index,
+            counterparty: row.counterparty,
+            category: row.category,
+            entity_id: row.entity_id,
+            journal_entry_id: row.journal_entry_id,
+        })
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Deletes the stored events of every transaction of a wallet.
+pub(crate) async fn delete_wallet_accounting_events<'e>(
+    executor: impl SqliteExecutor<'e>,
+    wallet_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM accounting_events WHERE wallet_id = ?")
+        .bind(wallet_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Events of every transaction in a profile's wallets, in transaction and
+/// event order.
+pub(crate) async fn load_profile_accounting_events(
+    pool: &SqlitePool,
+    profile_id: &str,
+) -> Result<Vec<AccountingEvent>, String> {
+    let rows: Vec<EventRow> = sqlx::query_as(
+        r#"
+        SELECT e.id, e.wallet_id, e.hash, e.event_index, e.kind,
+               e.sent_asset, e.sent_symbol, e.sent_decimals, e.sent_amount,
+               e.received_asset, e.received_symbol, e.received_decimals, e.received_amount,
+               e.counterparty, e.category, e.entity_id, e.journal_entry_id
+        FROM accounting_events e
+        JOIN wallets w ON w.id = e.wallet_id
+        WHERE w.profile_id = ?
+        ORDER BY e.wallet_id, e.hash, e.event_index
+        "#,
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    rows.into_iter().map(AccountingEvent::try_from).collect()
+}
+
+/// An event of a profile, with its transaction.
+async fn load_profile_event(
+    pool: &SqlitePool,
+    profile_id: &str,
+    id: &str,
+) -> Result<(AccountingEvent, StoredTransaction), String> {
+    let row: EventRow = sqlx::query_as(
+        r#"
+        SELECT e.id, e.wallet_id, e.hash, e.event_index, e.kind,
+               e.sent_asset, e.sent_symbol, e.sent_decimals, e.sent_amount,
+               e.received_asset, e.received_symbol, e.received_decimals, e.received_amount,
+               e.counterparty, e.category, e.entity_id, e.journal_entry_id
+        FROM accounting_events e
+        JOIN wallets w ON w.id = e.wallet_id
+        WHERE e.id = ? AND w.profile_id = ?
+        "#,
+    )
+    .bind(id)
+    .bind(profile_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| "Accounting event not found".to_string())?;
+
+    let transaction = sqlx::query_as::<_, StoredTransaction>(
+        "SELECT * FROM transactions WHERE wallet_id = ? AND hash = ?",
+    )
+    .bind(&row.wallet_id)
+    .bind(&row.hash)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok((AccountingEvent::try_from(row)?, transaction))
+}
+
+/// Ledger lines booking an event worth `value` in the reporting currency.
+async fn journal_lines(
+    pool: &SqlitePool,
+    event: &AccountingEvent,
+    value: Decimal,
+) -> Result<Vec<JournalEntryLineInput>, String> {
+    let symbol = |amount: &Option<EventAmount>| {
+        amount
+            .as_ref()
+            .and_then(|a| a.symbol.clone())
+            .unwrap_or_else(|| "Asset".to_string())
+    };
+    let (debit_account, debit_label, credit_account, credit_label) = match event.kind {
+        AccountingEventKind::Swap => (
+            "1200",
+            format!("{} received", symbol(&event.received)),
+            "1200",
+            format!("{} sold", symbol(&event.sent)),
+        ),
+        AccountingEventKind::TransferIn | AccountingEventKind::Mint => (
+            "1200",
+            format!("{} received", symbol(&event.received)),
+            "4000",
+            "Uncategorized income — review and reclassify".to_string(),
+        ),
+        AccountingEventKind::TransferOut | AccountingEventKind::Burn => (
+            "5000",
+            "Uncategorized expense — review and reclassify".to_string(),
+            "1200",
+            format!("{} sent", symbol(&event.sent)),
+        ),
+        AccountingEventKind::Fee => (
+            "5100",
+            "Network/gas fee".to_string(),
+            "1200",
+            "Fee paid from crypto assets".to_string(),
+        ),
+    };
+
+    Ok(vec![
+        JournalEntryLineInput {
+            gl_account_id: get_account_id_by_number(pool, debit_account).await?,
+            token_id: None,
+            debit_amount: value,
+            credit_amount: Decimal::ZERO,
+            description: Some(debit_label),
+        },
+        JournalEntryLineInput {
+            gl_account_id: get_account_id_by_number(pool, credit_account).await?,
+            token_id: None,
+            debit_amount: Decimal::ZERO,
+            credit_amount: value,
+            description: Some(credit_label),
+        },
+    ])
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Expands a wallet transaction into its accounting events again, as after
+/// its swaps or transfers were re-synced, and returns the profile's events.
+/// Refused once any of the transaction's events is booked.
+#[tauri::command]
+pub async fn expand_transaction_events(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    wallet_id: String,
+    hash: String,
+) -> Result<Vec<AccountingEvent>, String> {
+    let (_, wallet) = authorize_wallet(
+        &state.pool,
+        &auth,
+        &token,
+        &wallet_id,
+        Permission::CreateJournalEntries,
+    )
+    .await?;
+    let stored = sqlx::query_as::<_, StoredTransaction>(
+        "SELECT * FROM transactions WHERE wallet_id = ? AND hash = ?",
+    )
+    .bind(&wallet_id)
+    .bind(&hash)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| "Transaction not found".to_string())?;
+    if let Some(timestamp) = stored.timestamp {
+        ensure_period_open(
+            &state.pool,
+            Some(&wallet.profile_id),
+            timestamp.date_naive(),
+        )
+        .await?;
+    }
+
+    let expanded = expand_transaction(&state.pool, &wallet, &stored)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !expanded {
+        return Err(
+            "Events of this transaction are booked; void their journal entries first".to_string(),
+        );
+    }
+    load_profile_accounting_events(&state.pool, &wallet.profile_id).await
+}
+
+/// Returns the accounting events of every transaction in a profile's
+/// wallets.
+#[tauri::command]
+pub async fn get_accounting_events(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<AccountingEvent>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    load_profile_accounting_events(&state.pool, &profile_id).await
+}
+
+/// Tags an accounting event with a budget category and entity, or clears
+/// its tag when both are `None`.
+#[tauri::command]
+pub async fn tag_accounting_event(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    id: String,
+    category: Option<String>,
+    entity_id: Option<String>,
+) -> Result<AccountingEvent, String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::EditTags,
+    )
+    .await?;
+    let (before, transaction) = load_profile_event(&state.pool, &profile_id, &id).await?;
+    if let Some(timestamp) = transaction.timestamp {
+        ensure_period_open(&state.pool, Some(&profile_id), timestamp.date_naive()).await?;
+    }
+
+    sqlx::query("UPDATE accounting_events SET category = ?, entity_id = ? WHERE id = ?")
+        .bind(&category)
+        .bind(&entity_id)
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let after = AccountingEvent {
+        category,
+        entity_id,
+        ..before.clone()
+    };
+    record_change(
+        &state.pool,
+        Some(&user_id),
+        RecordType::AccountingEvent,
+        &id,
+        Some(&profile_id),
+        Some(&before),
+        Some(&after),
+    )
+    .await?;
+
+    Ok(after)
+}
+
+/// Books an accounting event to the ledger as a draft journal entry, at
+/// `value` in the reporting currency, and links the entry to the event.
+#[tauri::command]
+pub async fn book_accounting_event(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    id: String,
+    value: Decimal,
+) -> Result<JournalEntryWithLines, String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::CreateJournalEntries,
+    )
+    .await?;
+    if value <= Decimal::ZERO {
+        return Err("Event value must be positive".to_string());
+    }
+    let pool = state.pool.clone();
+    let (event, transaction) = load_profile_event(&pool, &profile_id, &id).await?;
+    if event.journal_entry_id.is_some() {
+        return Err("Accounting event is already booked".to_string());
+    }
+
+    let input = NewJournalEntryInput {
+        profile_id: Some(profile_id.clone()),
+        entry_date: transaction
+            .timestamp
+            .unwrap_or_else(Utc::now)
+            .format("%Y-%m-%dT%H:%M:%S")
+            .to_string(),
+        description: format!(
+            "{} {} of {} ({})",
+            event.kind.as_str(),
+            event.event_index + 1,
+            transaction.chain,
+            &event.hash[..8.min(event.hash.len())]
+        ),
+        reference_number: Some(event.hash.clone()),
+        raw_transaction_id: None,
+        lines: journal_lines(&pool, &event, value).await?,
+    };
+    let entry = insert_journal_entry(&pool, &user_id, input).await?;
+
+    sqlx::query("UPDATE accounting_events SET journal_entry_id = ? WHERE id = ?")
+        .bind(entry.entry.id)
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let after = AccountingEvent {
+        journal_entry_id: Some(entry.entry.id),
+        ..event.clone()
+    };
+    record_change(
+        &pool,
+        Some(&user_id),
+        RecordType::AccountingEvent,
+        &id,
+        Some(&profile_id),
+        Some(&event),
+        Some(&after),
+    )
+    .await?;
+
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    const WALLET: &str = "0xAb5801a7D398351b8bE11C439e05C5B3259aeC9B";
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+
+    fn transfer(token: &str, from: &str, to: &str, value: &str) -> TokenTransfer {
+        TokenTransfer {
+            token_address: token.to_string(),
+            token_symbol: None,
+            token_decimals: None,
+            from: from.to_string(),
+            to: to.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn sent_by_wallet() -> TransactionFacts {
+        TransactionFacts {
+            from: Some(WALLET.to_lowercase()),
+            to: Some("0xd152f549545093347a162dce210e7293f1452150".to_string()),
+            value: Some("0".to_string()),
+            fee: Some("21000000000000".to_string()),
+            native_symbol: Some("ETH".to_string()),
+            native_decimals: Some(18),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_events_keeps_tags() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(include_str!(
+            "../../migrations/20260430000001_create_accounting_events.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE wallets (id TEXT PRIMARY KEY, profile_id TEXT);
+            INSERT INTO wallets VALUES ('w1', 'p1'), ('w2', 'p2');
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let payout = TransactionFacts {
+            transfers: vec![
+                transfer(
+                    USDC,
+                    WALLET,
+                    "0x1111111111111111111111111111111111111111",
+                    "100",
+                ),
+                transfer(
+                    USDC,
+                    WALLET,
+                    "0x2222222222222222222222222222222222222222",
+                    "250",
+                ),
+            ],
+            ..sent_by_wallet()
+        };
+        save_events(&pool, "w1", "0xaa", &expand(WALLET, &payout))
+            .await
+            .unwrap();
+        save_events(&pool, "w2", "0xbb", &expand(WALLET, &payout))
+            .await
+            .unwrap();
+
+        let events = load_profile_accounting_events(&pool, "p1").await.unwrap();
+        assert_eq!(events.len(), 3);
+        sqlx::query("UPDATE accounting_events SET category = 'grants' WHERE id = ?")
+            .bind(&events[0].id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(!has_booked_events(&pool, "w1", "0xaa").await.unwrap());
+
+        // Re-expanded with one payout fewer: the first payout keeps its tag,
+        // and the fee takes the second's place untagged
+        let fewer = TransactionFacts {
+            transfers: payout.transfers[..1].to_vec(),
+            ..payout
+        };
+        save_events(&pool, "w1", "0xaa", &expand(WALLET, &fewer))
+            .await
+            .unwrap();
+        let reexpanded = load_profile_accounting_events(&pool, "p1").await.unwrap();
+        assert_eq!(reexpanded.len(), 2);
+        assert_eq!(reexpanded[0].id, events[0].id);
+        assert_eq!(reexpanded[0].category.as_deref(), Some("grants"));
+        assert_eq!(reexpanded[1].kind, AccountingEventKind::Fee);
+        assert_eq!(reexpanded[1].category, None);
+
+        sqlx::query("UPDATE accounting_events SET journal_entry_id = 1 WHERE id = ?")
+            .bind(&events[0].id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(has_booked_events(&pool, "w1", "0xaa").await.unwrap());
+
+        delete_wallet_accounting_events(&pool, "w1").await.unwrap();
+        assert!(load_profile_accounting_events(&pool, "p1")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
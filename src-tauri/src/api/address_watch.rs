@@ -0,0 +1,749 @@
+//! Address watches.
+//!
+//! Users mark addresses (their own wallets or third-party addresses such as
+//! an exchange deposit or a grantee) for monitoring. A background task polls
+//! each active watch through the chain manager, records an alert for every
+//! new transaction that moves at least the watch's threshold, and surfaces it
+//! as a Tauri event, a native notification, and optionally an email.
+
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+use uuid::Uuid;
+
+use super::auth::verify_admin;
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::{authenticate, authorize_profile, authorize_record};
+use super::statement_export::parse_amount;
+use crate::chains::{ChainManagerState, ChainTransaction, TransactionStatus};
+use crate::core::auth_state::AuthState;
+use crate::core::email;
+use crate::jobs::queue::{JobQueueState, JobTask};
+use crate::jobs::JobPriority;
+use crate::log_error;
+
+pub use pacioli_core::services::address_watch::*;
+
+/// Event emitted to the frontend for each new alert.
+pub const WATCH_ALERT_EVENT: &str = "address-watch-alert";
+
+/// Interval between background checks.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Delay before the first background check, so startup is not slowed down.
+const WATCH_INITIAL_DELAY: Duration = Duration::from_secs(30);
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A monitored address.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AddressWatch {
+    /// Unique identifier for the watch.
+    pub id: String,
+    /// The profile that owns the watch.
+    pub profile_id: String,
+    /// Chain the address lives on (e.g. "ethereum", "polkadot").
+    pub chain_id: String,
+    /// The watched address.
+    pub address: String,
+    /// Optional display label.
+    pub label: Option<String>,
+    /// Whether the address belongs to the user (vs. a third party).
+    pub is_own: bool,
+    /// Minimum amount, in native units, that raises an alert.
+    pub threshold: String,
+    /// Email address for alerts; no email is sent when unset.
+    pub notify_email: Option<String>,
+    /// Whether the watch is checked by the background task.
+    pub is_active: bool,
+    /// Highest block seen so far; `None` until the first check.
+    pub last_block: Option<i64>,
+    /// When the watch was last checked.
+    pub last_checked_at: Option<DateTime<Utc>>,
+    /// Timestamp when the watch was created.
+    pub created_at: DateTime<Utc>,
+    /// Timestamp when the watch was last updated.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Input for creating or updating a watch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressWatchInput {
+    /// The profile that owns the watch.
+    pub profile_id: String,
+    /// Chain the address lives on.
+    pub chain_id: String,
+    /// The watched address.
+    pub address: String,
+    /// Optional display label.
+    pub label: Option<String>,
+    /// Whether the address belongs to the user.
+    pub is_own: Option<bool>,
+    /// Minimum amount, in native units, that raises an alert. Defaults to 0.
+    pub threshold: Option<String>,
+    /// Email address for alerts.
+    pub notify_email: Option<String>,
+    /// Whether the watch is active. Defaults to true.
+    pub is_active: Option<bool>,
+}
+
+/// An alert raised for a watched address.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WatchAlert {
+    /// Unique identifier for the alert.
+    pub id: String,
+    /// The watch that raised the alert.
+    pub watch_id: String,
+    /// Transaction hash.
+    pub tx_hash: String,
+    /// `incoming` or `outgoing` relative to the watched address.
+    pub direction: String,
+    /// Amount moved, in native units.
+    pub amount: String,
+    /// Native currency symbol.
+    pub symbol: String,
+    /// The other side of the transaction.
+    pub counterparty: Option<String>,
+    /// Block containing the transaction.
+    pub block_number: i64,
+    /// When the transaction occurred.
+    pub occurred_at: DateTime<Utc>,
+    /// Whether an email alert was delivered.
+    pub email_sent: bool,
+    /// Whether the user has seen the alert.
+    pub is_read: bool,
+    /// Timestamp when the alert was recorded.
+    pub created_at: DateTime<Utc>,
+}
+
+/// A transaction that crossed a watch's threshold, before it is stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchActivity {
+    /// Transaction hash.
+    pub tx_hash: String,
+    /// `incoming` or `outgoing`.
+    pub direction: &'static str,
+    /// Amount moved, in native units.
+    pub amount: Decimal,
+    /// The other side of the transaction.
+    pub counterparty: Option<String>,
+    /// Block containing the transaction.
+    pub block_number: u64,
+    /// Unix timestamp in seconds.
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// Detection
+// ============================================================================
+
+/// Selects the transactions that should raise an alert for a watch.
+///
+/// Only successful transactions above `after_block` that touch `address` and
+/// move at least `threshold` (in native units) are returned, oldest first.
+pub fn find_watch_activity(
+    address: &str,
+    transactions: &[ChainTransaction],
+    threshold: Decimal,
+    decimals: i32,
+    after_block: Option<u64>,
+) -> Vec<WatchActivity> {
+    let address = address.to_lowercase();
+    let mut activity: Vec<WatchActivity> = transactions
+        .iter()
+        .filter(|tx| after_block.is_none_or(|b| tx.block_number > b))
+        .filter(|tx| tx.status != TransactionStatus::Failed)
+        .filter_map(|tx| {
+            let from = tx.from.to_lowercase();
+            let to = tx.to.as_deref().map(str::to_lowercase);
+            let (direction, counterparty) = if to.as_deref() == Some(address.as_str()) {
+                ("incoming", Some(tx.from.clone()))
+            } else if from == address {
+                ("outgoing", tx.to.clone())
+            } else {
+                return None;
+            };
+
+            let amount = parse_amount(&tx.value, Some(decimals))?.abs();
+            if amount.is_zero() || amount < threshold {
+                return None;
+            }
+
+            Some(WatchActivity {
+                tx_hash: tx.hash.clone(),
+                direction,
+                amount: amount.normalize(),
+                counterparty,
+                block_number: tx.block_number,
+                timestamp: tx.timestamp,
+            })
+        })
+        .collect();
+
+    activity.sort_by_key(|a| (a.block_number, a.timestamp));
+    activity
+}
+
+/// Checks every active watch and records new alerts.
+///
+/// The first check of a watch only establishes the starting block, so
+/// pre-existing history does not flood the user with alerts.
+pub async fn run_watch_checks(
+    pool: &SqlitePool,
+    manager: &ChainManagerState,
+    app: Option<&AppHandle>,
+) -> Result<Vec<WatchAlert>, String> {
+    let watches: Vec<AddressWatch> =
+        sqlx::query_as("SELECT * FROM address_watches WHERE is_active = 1")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    let mut alerts = Vec::new();
+    for watch in watches {
+        match check_watch(pool, manager, &watch).await {
+            Ok(mut new_alerts) => {
+                for alert in &mut new_alerts {
+                    deliver_alert(pool, app, &watch, alert).await;
+                }
+                alerts.extend(new_alerts);
+            }
+            Err(e) => log_error!("Address watch {} check failed: {}", watch.id, e),
+        }
+    }
+
+    Ok(alerts)
+}
+
+async fn check_watch(
+    pool: &SqlitePool,
+    manager: &ChainManagerState,
+    watch: &AddressWatch,
+) -> Result<Vec<WatchAlert>, String> {
+    let after_block = watch.last_block.map(|b| b.max(0) as u64);
+    let transactions = {
+        let manager = manager.read().await;
+        manager
+            .get_transactions(&watch.chain_id, &watch.address, after_block.map(|b| b + 1))
+            .await
+            .map_err(|e| e.to_string())?
+    };
+
+    let highest_block = transactions
+        .iter()
+        .map(|tx| tx.block_number as i64)
+        .max()
+        .into_iter()
+        .chain(watch.last_block)
+        .max();
+    let now = Utc::now();
+
+    let mut alerts = Vec::new();
+    if watch.last_block.is_some() {
+        let (symbol, decimals) = native_currency(&watch.chain_id);
+        let threshold = parse_amount(&watch.threshold, None).unwrap_or(Decimal::ZERO);
+
+        for activity in find_watch_activity(
+            &watch.address,
+            &transactions,
+            threshold,
+            decimals,
+            after_block,
+        ) {
+            let alert = WatchAlert {
+                id: Uuid::new_v4().to_string(),
+                watch_id: watch.id.clone(),
+                tx_hash: activity.tx_hash,
+                direction: activity.direction.to_string(),
+                amount: activity.amount.to_string(),
+                symbol: symbol.clone(),
+                counterparty: activity.counterparty,
+                block_number: activity.block_number as i64,
+                occurred_at: Utc
+                    .timestamp_opt(activity.timestamp, 0)
+                    .single()
+                    .unwrap_or(now),
+                email_sent: false,
+                is_read: false,
+                created_at: now,
+            };
+
+            let inserted = sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO address_watch_alerts (
+                    id, watch_id, tx_hash, direction, amount, symbol, counterparty,
+                    block_number, occurred_at, email_sent, is_read, created_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 0, 0, ?)
+                "#,
+            )
+            .bind(&alert.id)
+            .bind(&alert.watch_id)
+            .bind(&alert.tx_hash)
+            .bind(&alert.direction)
+            .bind(&alert.amount)
+            .bind(&alert.symbol)
+            .bind(&alert.counterparty)
+            .bind(alert.block_number)
+            .bind(alert.occurred_at)
+            .bind(alert.created_at)
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+            if inserted.rows_affected() > 0 {
+                alerts.push(alert);
+            }
+        }
+    }
+
+    sqlx::query(
+        "UPDATE address_watches SET last_block = ?, last_checked_at = ?, updated_at = ? WHERE id = ?",
+    )
+    .bind(highest_block.unwrap_or(0))
+    .bind(now)
+    .bind(now)
+    .bind(&watch.id)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(alerts)
+}
+
+/// Emits the frontend event and native notification, and sends the email
+/// alert if the watch has one configured.
+async fn deliver_alert(
+    pool: &SqlitePool,
+    app: Option<&AppHandle>,
+    watch: &AddressWatch,
+    alert: &mut WatchAlert,
+) {
+    let label = watch
+        .label
+        .clone()
+        .unwrap_or_else(|| short_address(&watch.address));
+    let amount = format!("{} {}", alert.amount, alert.symbol);
+    let direction = if alert.direction == "incoming" {
+        "Received"
+    } else {
+        "Sent"
+    };
+
+    if let Some(app) = app {
+        let _ = app.emit(WATCH_ALERT_EVENT, &*alert);
+        if let Err(e) = app
+            .notification()
+            .builder()
+            .title(format!("{}: {}", label, direction))
+            .body(amount.clone())
+            .show()
+        {
+            log_error!("Failed to show watch notification: {}", e);
+        }
+    }
+
+    if let Some(to) = watch.notify_email.as_deref().filter(|s| !s.is_empty()) {
+        match email::send_address_watch_alert(
+            to,
+            &label,
+            &watch.address,
+            direction,
+            &amount,
+            &alert.tx_hash,
+        )
+        .await
+        {
+            Ok(()) => {
+                alert.email_sent = true;
+                let _ = sqlx::query("UPDATE address_watch_alerts SET email_sent = 1 WHERE id = ?")
+                    .bind(&alert.id)
+                    .execute(pool)
+                    .await;
+            }
+            Err(e) => log_error!("Failed to send watch alert email: {}", e),
+        }
+    }
+}
+
+fn short_address(address: &str) -> String {
+    if address.len() <= 12 {
+        return address.to_string();
+    }
+    format!("{}...{}", &address[..6], &address[address.len() - 4..])
+}
+
+/// Starts the background task that periodically queues a check of all
+/// active watches.
+pub fn spawn_watch_loop(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(WATCH_INITIAL_DELAY).await;
+        loop {
+            let queue = app.state::<JobQueueState>().inner().clone();
+            if let Err(e) = queue
+                .enqueue(JobTask::AddressWatch, JobPriority::Background)
+                .await
+            {
+                log_error!("Failed to queue address watch check: {}", e);
+            }
+            tokio::time::sleep(WATCH_POLL_INTERVAL).await;
+        }
+    });
+}
+
+fn validate_threshold(threshold: &str) -> Result<(), String> {
+    match parse_amount(threshold, None) {
+        Some(t) if !t.is_sign_negative() => Ok(()),
+        _ => Err(format!("Invalid threshold: {}", threshold)),
+    }
+}
+
+/// Verifies `token` and that its user's role on the profile owning watch
+/// `id` grants `permission`. Returns the user ID.
+async fn authorize_watch(
+    pool: &SqlitePool,
+    auth: &AuthState,
+    token: &str,
+    id: &str,
+    permission: Permission,
+) -> Result<String, String> {
+    let user_id = authenticate(auth, token)?;
+    let not_found = "Address watch not found";
+    let profile_id: String =
+        sqlx::query_scalar("SELECT profile_id FROM address_watches WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| not_found.to_string())?;
+    authorize_record(pool, &user_id, &profile_id, permission, not_found).await?;
+    Ok(user_id)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Adds an address to the watch list. Requires the owner, admin, or
+/// preparer role on the profile.
+#[tauri::command]
+pub async fn add_address_watch(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    input: AddressWatchInput,
+) -> Result<AddressWatch, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &input.profile_id,
+        Permission::ManageWallets,
+    )
+    .await?;
+    let threshold = input.threshold.unwrap_or_else(|| "0".to_string());
+    validate_threshold(&threshold)?;
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now();
+
+    sqlx::query(
+        r#"
+        INSERT INTO address_watches (
+            id, profile_id, chain_id, address, label, is_own, threshold,
+            notify_email, is_active, created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(&input.profile_id)
+    .bind(&input.chain_id)
+    .bind(input.address.trim())
+    .bind(&input.label)
+    .bind(input.is_own.unwrap_or(false))
+    .bind(&threshold)
+    .bind(&input.notify_email)
+    .bind(input.is_active.unwrap_or(true))
+    .bind(now)
+    .bind(now)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    sqlx::query_as::<_, AddressWatch>("SELECT * FROM address_watches WHERE id = ?")
+        .bind(&id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Lists watches for a profile. Requires any role on the profile.
+#[tauri::command]
+pub async fn get_address_watches(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<AddressWatch>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as::<_, AddressWatch>(
+        "SELECT * FROM address_watches WHERE profile_id = ? ORDER BY created_at",
+    )
+    .bind(&profile_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Updates a watch's label, threshold, email, or active flag. Requires the
+/// owner, admin, or preparer role on the watch's profile.
+#[tauri::command]
+pub async fn update_address_watch(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    id: String,
+    input: AddressWatchInput,
+) -> Result<AddressWatch, String> {
+    authorize_watch(&state.pool, &auth, &token, &id, Permission::ManageWallets).await?;
+    let threshold = input.threshold.unwrap_or_else(|| "0".to_string());
+    validate_threshold(&threshold)?;
+
+    sqlx::query(
+        r#"
+        UPDATE address_watches
+        SET label = ?, is_own = ?, threshold = ?, notify_email = ?, is_active = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&input.label)
+    .bind(input.is_own.unwrap_or(false))
+    .bind(&threshold)
+    .bind(&input.notify_email)
+    .bind(input.is_active.unwrap_or(true))
+    .bind(Utc::now())
+    .bind(&id)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    sqlx::query_as::<_, AddressWatch>("SELECT * FROM address_watches WHERE id = ?")
+        .bind(&id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Removes a watch, its alerts, and its mempool payments. Requires the
+/// owner, admin, or preparer role on the watch's profile.
+#[tauri::command]
+pub async fn remove_address_watch(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    id: String,
+) -> Result<(), String> {
+    authorize_watch(&state.pool, &auth, &token, &id, Permission::ManageWallets).await?;
+    sqlx::query("DELETE FROM address_watch_alerts WHERE watch_id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    sqlx::query("DELETE FROM mempool_payments WHERE watch_id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    sqlx::query("DELETE FROM address_watches WHERE id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Lists alerts for a profile's watches, newest first. Requires any role on
+/// the profile.
+#[tauri::command]
+pub async fn get_watch_alerts(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    unread_only: Option<bool>,
+) -> Result<Vec<WatchAlert>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as::<_, WatchAlert>(
+        r#"
+        SELECT a.* FROM address_watch_alerts a
+        JOIN address_watches w ON w.id = a.watch_id
+        WHERE w.profile_id = ? AND (? = 0 OR a.is_read = 0)
+        ORDER BY a.occurred_at DESC
+        "#,
+    )
+    .bind(&profile_id)
+    .bind(unread_only.unwrap_or(false))
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Marks alerts as read. Requires any role on each alert's profile.
+#[tauri::command]
+pub async fn mark_watch_alerts_read(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    alert_ids: Vec<String>,
+) -> Result<(), String> {
+    let user_id = authenticate(&auth, &token)?;
+    for id in alert_ids {
+        let not_found = "Watch alert not found";
+        let profile_id: String = sqlx::query_scalar(
+            r#"
+            SELECT w.profile_id FROM address_watch_alerts a
+            JOIN address_watches w ON w.id = a.watch_id
+            WHERE a.id = ?
+            "#,
+        )
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| not_found.to_string())?;
+        authorize_record(
+            &state.pool,
+            &user_id,
+            &profile_id,
+            Permission::ViewTransactions,
+            not_found,
+        )
+        .await?;
+
+        sqlx::query("UPDATE address_watch_alerts SET is_read = 1 WHERE id = ?")
+            .bind(&id)
+            .execute(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Checks all active watches immediately instead of waiting for the
+/// background task. The check covers every profile's watches, so it
+/// requires the app admin.
+#[tauri::command]
+pub async fn check_address_watches(
+    app: AppHandle,
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    chain_manager: State<'_, ChainManagerState>,
+) -> Result<Vec<WatchAlert>, String> {
+    let user_id = authenticate(&auth, &token)?;
+    verify_admin(&state.pool, &user_id).await?;
+    run_watch_checks(&state.pool, chain_manager.inner(), Some(&app)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::{ChainId, TransactionType};
+    use std::str::FromStr;
+
+    const WATCHED: &str = "0xAbC0000000000000000000000000000000000001";
+    const OTHER: &str = "0x0000000000000000000000000000000000000002";
+
+    fn tx(hash: &str, block: u64, from: &str, to: &str, wei: &str) -> ChainTransaction {
+        ChainTransaction {
+            hash: hash.to_string(),
+            chain_id: ChainId::evm("ethereum", 1),
+            block_number: block,
+            timestamp: 1_700_000_000 + block as i64,
+            from: from.to_string(),
+            to: Some(to.to_string()),
+            value: wei.to_string(),
+            fee: "0".to_string(),
+            status: TransactionStatus::Success,
+            tx_type: TransactionType::Transfer,
+            token_transfers: Vec::new(),
+            swaps: Vec::new(),
+            fee_breakdown: None,
+            user_operations: Vec::new(),
+            inner_calls: Vec::new(),
+            raw_data: None,
+        }
+    }
+
+    #[test]
+    fn test_find_watch_activity_threshold_and_direction() {
+        let txs = vec![
+            tx("0x3", 12, WATCHED, OTHER, "2000000000000000000"),
+            tx(
+                "0x1",
+                10,
+                OTHER,
+                &WATCHED.to_lowercase(),
+                "1500000000000000000",
+            ),
+            tx("0x2", 11, OTHER, WATCHED, "100000000000000000"),
+        ];
+        let threshold = Decimal::from_str("1").unwrap();
+        let activity = find_watch_activity(WATCHED, &txs, threshold, 18, None);
+
+        assert_eq!(activity.len(), 2);
+        assert_eq!(activity[0].tx_hash, "0x1");
+        assert_eq!(activity[0].direction, "incoming");
+        assert_eq!(activity[0].amount, Decimal::from_str("1.5").unwrap());
+        assert_eq!(activity[0].counterparty.as_deref(), Some(OTHER));
+        assert_eq!(activity[1].tx_hash, "0x3");
+        assert_eq!(activity[1].direction, "outgoing");
+    }
+
+    #[test]
+    fn test_find_watch_activity_skips_old_failed_and_unrelated() {
+        let mut failed = tx("0x2", 11, OTHER, WATCHED, "5000000000000000000");
+        failed.status = TransactionStatus::Failed;
+        let txs = vec![
+            tx("0x1", 10, OTHER, WATCHED, "5000000000000000000"),
+            failed,
+            tx("0x3", 12, OTHER, OTHER, "5000000000000000000"),
+            tx("0x4", 13, OTHER, WATCHED, "0"),
+            tx("0x5", 14, OTHER, WATCHED, "5000000000000000000"),
+        ];
+        let activity = find_watch_activity(WATCHED, &txs, Decimal::ZERO, 18, Some(10));
+
+        assert_eq!(activity.len(), 1);
+        assert_eq!(activity[0].tx_hash, "0x5");
+    }
+
+    #[test]
+    fn test_short_address() {
+        assert_eq!(short_address(WATCHED), "0xAbC0...0001");
+        assert_eq!(short_address("short"), "short");
+    }
+}
@@ -0,0 +1,304 @@
+//! Token approval exposure per wallet.
+//!
+//! Lists the ERC-20 allowances and NFT operator approvals each of a
+//! profile's EVM wallets has granted, with the current allowance read from
+//! the token and the block each was revoked in. Spenders are named from the
+//! profile's entities and the known address list; unlimited approvals
+//! still in force to spenders with neither are flagged.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::{authorize_profile, profile_wallets};
+use crate::chains::evm::config::get_chain_by_name;
+use crate::chains::{ApprovalKind, ChainManagerState, TokenApproval};
+use crate::core::auth_state::AuthState;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// One approval granted by a wallet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalExposure {
+    /// Token or collection contract.
+    pub token: String,
+    /// Address allowed to move the wallet's tokens.
+    pub spender: String,
+    /// Entity or known protocol the spender belongs to, if recognized.
+    pub spender_name: Option<String>,
+    /// Allowance or operator approval.
+    pub kind: ApprovalKind,
+    /// Current allowance in smallest units.
+    pub allowance: String,
+    /// Whether the approval is effectively unlimited.
+    pub unlimited: bool,
+    /// Whether the spender can still move tokens.
+    pub active: bool,
+    /// Whether the approval is unlimited, in force, and to an unrecognized
+    /// spender.
+    pub flagged: bool,
+    /// Block of the latest grant.
+    pub approved_block: u64,
+    /// Transaction of the latest grant.
+    pub approved_tx: Option<String>,
+    /// Block the approval was revoked in, if it was.
+    pub revoked_block: Option<u64>,
+    /// Transaction that revoked the approval, if it was.
+    pub revoked_tx: Option<String>,
+}
+
+/// Approvals granted by one wallet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletApprovals {
+    /// Wallet the approvals were granted from.
+    pub wallet_id: String,
+    /// Chain the wallet is on.
+    pub chain: String,
+    /// Wallet address.
+    pub address: String,
+    /// Approvals in force first, then revoked or spent ones.
+    pub approvals: Vec<ApprovalExposure>,
+    /// Number of flagged approvals.
+    pub flagged_count: usize,
+    /// Why the wallet's approvals couldn't be read, if they couldn't.
+    pub error: Option<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Name of a spender from the profile's entities or the known address
+/// list, matching addresses case-insensitively.
+async fn spender_name(
+    pool: &SqlitePool,
+    profile_id: &str,
+    chain: &str,
+    spender: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let entity: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT e.name
+        FROM entities e
+        INNER JOIN entity_addresses ea ON e.id = ea.entity_id
+        WHERE e.profile_id = ? AND LOWER(ea.address) = LOWER(?)
+        LIMIT 1
+        "#,
+    )
+    .bind(profile_id)
+    .bind(spender)
+    .fetch_optional(pool)
+    .await?;
+    if let Some((name,)) = entity {
+        return Ok(Some(name));
+    }
+
+    let known: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT entity_name
+        FROM known_addresses
+        WHERE LOWER(address) = LOWER(?) AND chain = ? AND is_active = 1
+        LIMIT 1
+        "#,
+    )
+    .bind(spender)
+    .bind(chain)
+    .fetch_optional(pool)
+    .await?;
+    Ok(known.map(|(name,)| name))
+}
+
+/// Names approvals' spenders and flags unlimited approvals in force to
+/// unrecognized ones. Approvals in force come first.
+fn to_exposures(
+    approvals: Vec<TokenApproval>,
+    names: &HashMap<String, String>,
+) -> Vec<ApprovalExposure> {
+    let mut exposures: Vec<ApprovalExposure> = approvals
+        .into_iter()
+        .map(|a| {
+            let spender_name = names.get(&a.spender.to_lowercase()).cloned();
+            ApprovalExposure {
+                flagged: a.active && a.unlimited && spender_name.is_none(),
+                token: a.token,
+                spender: a.spender,
+                spender_name,
+                kind: a.kind,
+                allowance: a.allowance,
+                unlimited: a.unlimited,
+                active: a.active,
+                approved_block: a.approved_block,
+                approved_tx: a.approved_tx,
+                revoked_block: a.revoked_block,
+                revoked_tx: a.revoked_tx,
+            }
+        })
+        .collect();
+    exposures.sort_by_key(|e| (!e.active, !e.flagged));
+    exposures
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns the approvals each of a profile's EVM wallets has granted,
+/// flagging unlimited approvals to unrecognized spenders. A wallet whose
+/// approvals can't be read is returned with the error.
+#[tauri::command]
+pub async fn get_approval_exposure(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    chains: State<'_, ChainManagerState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<WalletApprovals>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+
+    let wallets = profile_wallets(&state.pool, &profile_id).await?;
+    let manager = chains.read().await;
+    let mut report = Vec::new();
+
+    for wallet in wallets
+        .into_iter()
+        .filter(|w| get_chain_by_name(&w.chain).is_some())
+    {
+        let (approvals, error) = match manager
+            .get_token_approvals(&wallet.chain, &wallet.address)
+            .await
+        {
+            Ok(approvals) => (approvals, None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+
+        let mut names = HashMap::new();
+        for approval in &approvals {
+            let spender = approval.spender.to_lowercase();
+            if names.contains_key(&spender) {
+                continue;
+            }
+            if let Some(name) = spender_name(&state.pool, &profile_id, &wallet.chain, &spender)
+                .await
+                .map_err(|e| e.to_string())?
+            {
+                names.insert(spender, name);
+            }
+        }
+
+        let approvals = to_exposures(approvals, &names);
+        report.push(WalletApprovals {
+            wallet_id: wallet.id,
+            chain: wallet.chain,
+            address: wallet.address,
+            flagged_count: approvals.iter().filter(|a| a.flagged).count(),
+            approvals,
+            error,
+        });
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn approval(spender: &str, unlimited: bool, active: bool) -> TokenApproval {
+        TokenApproval {
+            token: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string(),
+            spender: spender.to_string(),
+            kind: ApprovalKind::Erc20,
+            allowance: if active { "1" } else { "0" }.to_string(),
+            unlimited,
+            active,
+            approved_block: 100,
+            approved_tx: None,
+            revoked_block: (!active).then_some(200),
+            revoked_tx: None,
+        }
+    }
+
+    #[test]
+    fn test_flags_unlimited_approvals_to_unknown_spenders() {
+        let names = HashMap::from([("0xknown".to_string(), "Uniswap".to_string())]);
+        let exposures = to_exposures(
+            vec![
+                approval("0xrevoked", true, false),
+                approval("0xKnown", true, true),
+                approval("0xlimited", false, true),
+                approval("0xunknown", true, true),
+            ],
+            &names,
+        );
+
+        let spenders: Vec<&str> = exposures.iter().map(|e| e.spender.as_str()).collect();
+        assert_eq!(
+            spenders,
+            vec!["0xunknown", "0xKnown", "0xlimited", "0xrevoked"]
+        );
+        assert!(exposures[0].flagged);
+        assert_eq!(exposures[1].spender_name.as_deref(), Some("Uniswap"));
+        assert!(exposures.iter().skip(1).all(|e| !e.flagged));
+    }
+
+    #[tokio::test]
+    async fn test_spender_name_ignores_case() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE entities (id TEXT PRIMARY KEY, profile_id TEXT, name TEXT);
+            CREATE TABLE entity_addresses (entity_id TEXT, address TEXT, chain TEXT);
+            CREATE TABLE known_addresses (
+                address TEXT, chain TEXT, entity_name TEXT, is_active INTEGER
+            );
+            INSERT INTO entities VALUES ('e1', 'p1', 'Payroll Multisig');
+            INSERT INTO entity_addresses VALUES ('e1', '0xAbCd', 'ethereum');
+            INSERT INTO known_addresses VALUES
+                ('0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D', 'ethereum', 'Uniswap', 1);
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let name = |spender: &'static str, profile: &'static str| {
+            let pool = pool.clone();
+            async move {
+                spender_name(&pool, profile, "ethereum", spender)
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(
+            name("0xabcd", "p1").await.as_deref(),
+            Some("Payroll Multisig")
+        );
+        assert_eq!(name("0xabcd", "p2").await, None);
+        assert_eq!(
+            name("0x7a250d5630b4cf539739df2c5dacb4c659f2488d", "p2")
+                .await
+                .as_deref(),
+            Some("Uniswap")
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Audit trail of data changes.
+//!
+//! Every create, update, and delete of wallets, transactions, tags, entities,
+//! and journal entries is appended to `data_audit_log` with JSON snapshots of
+//! the record before and after, the fields that changed, and who made the
+//! change. The table rejects updates and deletes, so a figure in a report can
+//! always be traced back through its history.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::authorize_profile;
+use crate::core::auth_state::AuthState;
+
+pub use pacioli_core::services::audit_trail::*;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// One recorded change to a record.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditTrailEntry {
+    /// Unique identifier.
+    pub id: String,
+    /// Kind of record changed.
+    pub record_type: String,
+    /// ID of the record changed.
+    pub record_id: String,
+    /// One of: create, update, delete.
+    pub action: String,
+    /// User who made the change, or "system".
+    pub actor: String,
+    /// Profile the record belongs to, when known.
+    pub profile_id: Option<String>,
+    /// JSON snapshot before the change; absent for creates.
+    pub before_data: Option<String>,
+    /// JSON snapshot after the change; absent for deletes.
+    pub after_data: Option<String>,
+    /// JSON object of changed fields, each as `{"before": .., "after": ..}`.
+    pub changes: String,
+    /// When the change was made.
+    pub created_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// A profile's recorded changes, newest first, optionally limited to one
+/// kind of record. A negative `limit` returns every change.
+pub(crate) async fn profile_audit_trail(
+    pool: &SqlitePool,
+    profile_id: &str,
+    record_type: Option<RecordType>,
+    limit: i32,
+    offset: i32,
+) -> Result<Vec<AuditTrailEntry>, String> {
+    sqlx::query_as::<_, AuditTrailEntry>(
+        r#"
+        SELECT * FROM data_audit_log
+        WHERE profile_id = ? AND (? IS NULL OR record_type = ?)
+        ORDER BY created_at DESC
+        LIMIT ? OFFSET ?
+        "#,
+    )
+    .bind(profile_id)
+    .bind(record_type.map(RecordType::as_str))
+    .bind(record_type.map(RecordType::as_str))
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns the full history of one of a profile's records, oldest change
+/// first. Requires the view audit trail permission.
+#[tauri::command]
+pub async fn get_record_history(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    record_type: RecordType,
+    record_id: String,
+) -> Result<Vec<AuditTrailEntry>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewAuditTrail,
+    )
+    .await?;
+
+    sqlx::query_as::<_, AuditTrailEntry>(
+        r#"
+        SELECT * FROM data_audit_log
+        WHERE profile_id = ? AND record_type = ? AND record_id = ?
+        ORDER BY created_at ASC
+        "#,
+    )
+    .bind(&profile_id)
+    .bind(record_type.as_str())
+    .bind(&record_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Returns a profile's recorded changes, newest first, optionally limited to
+/// one kind of record. Requires the view audit trail permission.
+#[tauri::command]
+pub async fn get_audit_trail(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    record_type: Option<RecordType>,
+    limit: Option<i32>,
+    offset: Option<i32>,
+) -> Result<Vec<AuditTrailEntry>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewAuditTrail,
+    )
+    .await?;
+
+    profile_audit_trail(
+        &state.pool,
+        &profile_id,
+        record_type,
+        limit.unwrap_or(100),
+        offset.unwrap_or(0),
+    )
+    .await
+}
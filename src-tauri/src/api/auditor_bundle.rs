@@ -0,0 +1,147 @@
+//! Auditor bundles.
+//!
+//! A single JSON file handed to an auditor for one profile and period: the
+//! attested transaction report, the profile's change history, and its
+//! period closes. Members, sessions, API keys, and other secrets are never
+//! included, and the attestation hash lets the auditor confirm the
+//! transactions are unaltered.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use super::audit_trail::{profile_audit_trail, AuditTrailEntry};
+use super::auth::log_audit_event;
+use super::period_close::PeriodClose;
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::authorize_profile;
+use super::report_attestation::{attested_report, AttestedReport};
+use crate::core::auth_state::AuthState;
+
+/// Version of the bundle format.
+const FORMAT_VERSION: &str = "pacioli-auditor-bundle-v1";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Everything an auditor receives for one profile and period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditorBundle {
+    /// Bundle format version.
+    pub format_version: String,
+    /// When the bundle was generated.
+    pub generated_at: DateTime<Utc>,
+    /// User who generated it.
+    pub generated_by: String,
+    /// Profile the bundle covers.
+    pub profile_id: String,
+    /// Profile name.
+    pub profile_name: String,
+    /// Attested report of the period's transactions.
+    pub report: AttestedReport,
+    /// Every recorded change to the profile's records, newest first.
+    pub audit_trail: Vec<AuditTrailEntry>,
+    /// The profile's closed and reopened periods, most recent first.
+    pub period_closes: Vec<PeriodClose>,
+}
+
+/// Summary returned to the frontend after an export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditorBundleResult {
+    /// Path the bundle was written to.
+    pub path: String,
+    /// Number of transactions in the report.
+    pub transaction_count: usize,
+    /// Number of audit trail entries.
+    pub audit_entry_count: usize,
+    /// Attestation hash of the report, hex.
+    pub attestation_hash: String,
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Exports an auditor bundle for a profile and period to `path`. Requires
+/// the export auditor bundle permission.
+///
+/// # Arguments
+/// * `profile_id` - The profile to export.
+/// * `period` - `YYYY`, `YYYY-Qn`, `YYYY-MM`, or `YYYY-MM-DD..YYYY-MM-DD`.
+/// * `path` - Destination file path.
+#[tauri::command]
+pub async fn export_auditor_bundle(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    period: String,
+    path: String,
+) -> Result<AuditorBundleResult, String> {
+    let pool = &state.pool;
+    let user_id = authorize_profile(
+        pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ExportAuditorBundle,
+    )
+    .await?;
+
+    let profile_name: String = sqlx::query_scalar("SELECT name FROM profiles WHERE id = ?")
+        .bind(&profile_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let report = attested_report(pool, &profile_id, &period).await?;
+    let audit_trail = profile_audit_trail(pool, &profile_id, None, -1, 0).await?;
+    let period_closes = sqlx::query_as::<_, PeriodClose>(
+        "SELECT * FROM period_closes WHERE profile_id = ? ORDER BY period_start DESC",
+    )
+    .bind(&profile_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let bundle = AuditorBundle {
+        format_version: FORMAT_VERSION.to_string(),
+        generated_at: Utc::now(),
+        generated_by: user_id.clone(),
+        profile_id: profile_id.clone(),
+        profile_name,
+        report,
+        audit_trail,
+        period_closes,
+    };
+
+    let contents = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    let details = serde_json::json!({
+        "period": period.trim(),
+        "attestationHash": bundle.report.attestation_hash,
+    })
+    .to_string();
+    log_audit_event(
+        pool,
+        Some(&user_id),
+        "auditor_bundle_exported",
+        "success",
+        Some(&details),
+        None,
+        Some(&profile_id),
+    )
+    .await;
+
+    Ok(AuditorBundleResult {
+        path,
+        transaction_count: bundle.report.records.len(),
+        audit_entry_count: bundle.audit_trail.len(),
+        attestation_hash: bundle.report.attestation_hash,
+    })
+}
@@ -3,20 +3,30 @@
 //! Provides Tauri commands for user authentication, session management,
 //! profile role management, and invitation system.
 
+use crate::api::permissions::{Permission, ASSIGNABLE_ROLES};
 use crate::api::persistence::DatabaseState;
 use crate::core::auth_helpers::{
-    generate_access_token, generate_invitation_token, generate_session_id, hash_password,
-    hash_token, validate_email, validate_password_strength, verify_access_token, verify_password,
-    verify_refresh_token,
+    argon2_params, generate_access_token, generate_invitation_token, generate_password_reset_token,
+    generate_session_id, hash_params, hash_password, hash_password_with, hash_token, needs_rehash,
+    set_argon2_params, validate_email, validate_password_strength, verify_access_token,
+    verify_password, verify_refresh_token, Argon2Params, ARGON2_PARAMS_SETTING,
 };
 use crate::core::auth_state::AuthState;
+use crate::core::deep_link;
+use crate::core::device::{self, DeviceInfo, SessionFingerprint};
 use crate::core::email;
+use crate::core::rate_limit::AuthAction;
+use crate::log_error;
+use crate::storage::settings_store;
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::future::Future;
 use tauri::State;
 use uuid::Uuid;
 
+pub use pacioli_core::services::auth::*;
+
 // ============================================================================
 // Types
 // ============================================================================
@@ -311,6 +321,37 @@ pub struct InvitationWithProfile {
     pub created_at: DateTime<Utc>,
 }
 
+// ============================================================================
+// Password Hash Types
+// ============================================================================
+
+/// Number of password hashes created with a given set of parameters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordHashParamsCount {
+    /// Parameter label (e.g. `m=19456,t=2,p=1`), or `legacy` for hashes that
+    /// are not Argon2id v1.3
+    pub params: String,
+    /// Number of users with hashes using these parameters
+    pub count: i64,
+}
+
+/// Progress of migrating stored password hashes to the current parameters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordHashReport {
+    /// Parameters used for new hashes
+    pub current_params: Argon2Params,
+    /// Users with a password set
+    pub users_with_password: i64,
+    /// Users whose hash already uses the current parameters
+    pub up_to_date: i64,
+    /// Users whose hash will be upgraded at next login
+    pub pending_rehash: i64,
+    /// Breakdown by hash parameters
+    pub by_params: Vec<PasswordHashParamsCount>,
+}
+
 // ============================================================================
 // Email Change Types
 // ============================================================================
@@ -344,8 +385,48 @@ pub struct EmailChangeStatus {
     pub expires_at: Option<DateTime<Utc>>,
     /// When the pending request was created
     pub created_at: Option<DateTime<Utc>>,
+    /// Whether the new address has been confirmed
+    pub confirmed: bool,
+    /// When a confirmed change takes effect
+    pub effective_at: Option<DateTime<Utc>>,
+}
+
+/// Settings for the email change flow.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailChangeSettings {
+    /// Hours between confirming the new address and the change taking
+    /// effect, during which the old address can still cancel it
+    pub cooldown_hours: u32,
+}
+
+impl Default for EmailChangeSettings {
+    fn default() -> Self {
+        Self { cooldown_hours: 24 }
+    }
+}
+
+impl EmailChangeSettings {
+    /// Longest allowed cool-down (one week)
+    const MAX_COOLDOWN_HOURS: u32 = 168;
+
+    fn validate(&self) -> Result<(), String> {
+        if self.cooldown_hours > Self::MAX_COOLDOWN_HOURS {
+            return Err(format!(
+                "Email change cool-down cannot exceed {} hours",
+                Self::MAX_COOLDOWN_HOURS
+            ));
+        }
+        Ok(())
+    }
 }
 
+/// Settings key for [`EmailChangeSettings`]
+const EMAIL_CHANGE_SETTINGS: &str = "email_change_settings";
+
+/// How often confirmed email changes past their cool-down are applied
+const EMAIL_CHANGE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
 // ============================================================================
 // Authentication Commands
 // ============================================================================
@@ -440,12 +521,12 @@ pub async fn register(
     let display_name = input.display_name.clone();
     tokio::spawn(async move {
         if let Err(e) = email::send_welcome_email(&email_addr, &display_name).await {
-            eprintln!("Failed to send welcome email: {}", e);
+            log_error!("Failed to send welcome email: {}", e);
         }
     });
 
     // Create session and return tokens
-    create_session_and_tokens(&db, &auth, &user_id, &input.email, None, None).await
+    create_session_and_tokens(&db, &auth, &user_id, &input.email, &DeviceInfo::current()).await
 }
 
 /// Provision a local-only session without email/password credentials.
@@ -553,7 +634,7 @@ pub async fn provision_local_session(
     )
     .await;
 
-    create_session_and_tokens(&db, &auth, &user_id, local_email, None, None).await
+    create_session_and_tokens(&db, &auth, &user_id, local_email, &DeviceInfo::current()).await
 }
 
 /// Login with email and password
@@ -566,47 +647,52 @@ pub async fn login(
     let pool = &db.pool;
     let email = credentials.email.to_lowercase();
 
-    // Find user
-    let user_row: Option<(String, String, String, i32, Option<DateTime<Utc>>, i32)> =
-        sqlx::query_as(
-            r#"
+    throttled(pool, &auth, AuthAction::Login, &email, async {
+        // Find user
+        let user_row: Option<(String, String, String, i32, Option<DateTime<Utc>>, i32)> =
+            sqlx::query_as(
+                r#"
         SELECT id, password_hash, status, failed_login_attempts, lockout_until, two_factor_enabled
         FROM users WHERE email = ?
         "#,
-        )
-        .bind(&email)
-        .fetch_optional(pool)
-        .await
-        .map_err(|e| format!("Database error: {}", e))?;
+            )
+            .bind(&email)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
 
-    let (user_id, password_hash, status, failed_attempts, lockout_until, _two_factor) =
-        user_row.ok_or("Invalid email or password")?;
+        let (user_id, password_hash, status, failed_attempts, lockout_until, _two_factor) =
+            user_row.ok_or_else(|| AttemptError::rejected("Invalid email or password"))?;
 
-    // Check lockout
-    if let Some(lockout) = lockout_until {
-        if lockout > Utc::now() {
-            return Err("Account is temporarily locked. Please try again later.".to_string());
+        // Check lockout
+        if let Some(lockout) = lockout_until {
+            if lockout > Utc::now() {
+                return Err("Account is temporarily locked. Please try again later."
+                    .to_string()
+                    .into());
+            }
         }
-    }
 
-    // Check account status
-    if status != "active" {
-        return Err(format!("Account is {}", status));
-    }
+        // Check account status
+        if status != "active" {
+            return Err(format!("Account is {}", status).into());
+        }
 
-    // Verify password
-    let is_valid = verify_password(&credentials.password, &password_hash)?;
+        // Verify password
+        let is_valid = verify_password(&credentials.password, &password_hash)?;
 
-    if !is_valid {
-        // Increment failed attempts
-        let new_attempts = failed_attempts + 1;
-        let lockout_time = if new_attempts >= 5 {
-            Some(Utc::now() + Duration::minutes(15))
-        } else {
-            None
-        };
+        if !is_valid {
+            // Increment failed attempts
+            let new_attempts = failed_attempts + 1;
+            let lockout_time = if new_attempts >= 5 {
+                Some(Utc::now() + Duration::minutes(15))
+            } else {
+                None
+            };
 
-        sqlx::query("UPDATE users SET failed_login_attempts = ?, lockout_until = ? WHERE id = ?")
+            sqlx::query(
+                "UPDATE users SET failed_login_attempts = ?, lockout_until = ? WHERE id = ?",
+            )
             .bind(new_attempts)
             .bind(lockout_time)
             .bind(&user_id)
@@ -614,47 +700,75 @@ pub async fn login(
             .await
             .ok();
 
-        // Log failed attempt
-        log_audit_event(
-            pool,
-            Some(&user_id),
-            "login",
-            "failure",
-            Some("Invalid password"),
-            None,
-            None,
-        )
-        .await;
+            // Log failed attempt
+            log_audit_event(
+                pool,
+                Some(&user_id),
+                "login",
+                "failure",
+                Some("Invalid password"),
+                None,
+                None,
+            )
+            .await;
 
-        return Err("Invalid email or password".to_string());
-    }
+            return Err(AttemptError::rejected("Invalid email or password"));
+        }
 
-    // Reset failed attempts and update last login
-    sqlx::query(
-        r#"
+        // Transparently upgrade hashes created with older parameters
+        let params = argon2_params();
+        if needs_rehash(&password_hash, &params) {
+            match hash_password_with(&credentials.password, &params) {
+                Ok(new_hash) => {
+                    let updated = sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+                        .bind(&new_hash)
+                        .bind(&user_id)
+                        .execute(pool)
+                        .await;
+                    if updated.is_ok() {
+                        log_audit_event(
+                            pool,
+                            Some(&user_id),
+                            "password_rehash",
+                            "success",
+                            Some(&params.label()),
+                            None,
+                            None,
+                        )
+                        .await;
+                    }
+                }
+                Err(e) => log_error!("Failed to rehash password: {}", e),
+            }
+        }
+
+        // Reset failed attempts and update last login
+        sqlx::query(
+            r#"
         UPDATE users
         SET failed_login_attempts = 0, lockout_until = NULL, last_login_at = ?
         WHERE id = ?
         "#,
-    )
-    .bind(Utc::now())
-    .bind(&user_id)
-    .execute(pool)
-    .await
-    .ok();
+        )
+        .bind(Utc::now())
+        .bind(&user_id)
+        .execute(pool)
+        .await
+        .ok();
 
-    // Log successful login
-    log_audit_event(pool, Some(&user_id), "login", "success", None, None, None).await;
+        // Log successful login
+        log_audit_event(pool, Some(&user_id), "login", "success", None, None, None).await;
 
-    // Create session and return tokens
-    create_session_and_tokens(
-        &db,
-        &auth,
-        &user_id,
-        &email,
-        credentials.device_name.as_deref(),
-        credentials.device_type.as_deref(),
-    )
+        // Capture device metadata and alert on unfamiliar devices or networks
+        let device = DeviceInfo::current().with_overrides(
+            credentials.device_name.as_deref(),
+            credentials.device_type.as_deref(),
+        );
+        check_login_anomaly(pool, &user_id, &device).await;
+
+        // Create session and return tokens
+        Ok(create_session_and_tokens(&db, &auth, &user_id, &email, &device).await?)
+    })
     .await
 }
 
@@ -665,7 +779,7 @@ pub async fn logout(
     auth: State<'_, AuthState>,
     token: String,
 ) -> Result<(), String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     // Revoke all sessions for this user from this token
@@ -679,6 +793,7 @@ pub async fn logout(
 
     // Invalidate cache
     auth.invalidate_user_sessions(&claims.sub);
+    auth.clear_active_user(&claims.sub);
 
     // Log logout
     log_audit_event(
@@ -702,55 +817,62 @@ pub async fn refresh_token(
     auth: State<'_, AuthState>,
     refresh_token: String,
 ) -> Result<AuthResponse, String> {
-    let claims = verify_refresh_token(&refresh_token, auth.get_jwt_secret())?;
     let pool = &db.pool;
+    let identifier = hash_token(&refresh_token);
 
-    let session_id = claims
-        .session_id
-        .ok_or("Invalid refresh token: no session ID")?;
+    throttled(pool, &auth, AuthAction::RefreshToken, &identifier, async {
+        let claims = verify_refresh_token(&refresh_token, &auth.jwt_keys())
+            .map_err(AttemptError::Rejected)?;
 
-    // Verify session is still valid
-    let session: Option<(String, i32)> = sqlx::query_as(
-        "SELECT user_id, revoked FROM sessions WHERE id = ? AND refresh_token_hash = ?",
-    )
-    .bind(&session_id)
-    .bind(hash_token(&refresh_token))
-    .fetch_optional(pool)
-    .await
-    .map_err(|e| format!("Database error: {}", e))?;
+        let session_id = claims
+            .session_id
+            .ok_or_else(|| AttemptError::rejected("Invalid refresh token: no session ID"))?;
 
-    let (user_id, revoked) = session.ok_or("Session not found or expired")?;
+        // Verify session is still valid
+        let session: Option<(String, i32)> = sqlx::query_as(
+            "SELECT user_id, revoked FROM sessions WHERE id = ? AND refresh_token_hash = ?",
+        )
+        .bind(&session_id)
+        .bind(hash_token(&refresh_token))
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
 
-    if revoked != 0 {
-        return Err("Session has been revoked".to_string());
-    }
+        let (user_id, revoked) =
+            session.ok_or_else(|| AttemptError::rejected("Session not found or expired"))?;
 
-    // Verify user ID matches
-    if user_id != claims.sub {
-        return Err("Invalid refresh token".to_string());
-    }
+        if revoked != 0 {
+            return Err(AttemptError::rejected("Session has been revoked"));
+        }
 
-    // Update session activity
-    sqlx::query("UPDATE sessions SET last_activity_at = ? WHERE id = ?")
-        .bind(Utc::now())
-        .bind(&session_id)
-        .execute(pool)
-        .await
-        .ok();
+        // Verify user ID matches
+        if user_id != claims.sub {
+            return Err(AttemptError::rejected("Invalid refresh token"));
+        }
 
-    // Get user
-    let user = get_user_by_id(pool, &user_id).await?;
+        // Update session activity
+        sqlx::query("UPDATE sessions SET last_activity_at = ? WHERE id = ?")
+            .bind(Utc::now())
+            .bind(&session_id)
+            .execute(pool)
+            .await
+            .ok();
 
-    // Generate new access token (keep same refresh token)
-    let access_token =
-        generate_access_token(&user.id, &user.email, auth.get_jwt_secret(), Some(15))?;
+        // Get user
+        let user = get_user_by_id(pool, &user_id).await?;
 
-    Ok(AuthResponse {
-        access_token,
-        refresh_token, // Return the same refresh token
-        user,
-        expires_in: 15 * 60, // 15 minutes in seconds
+        // Generate new access token (keep same refresh token)
+        let access_token =
+            generate_access_token(&user.id, &user.email, &auth.jwt_keys(), Some(15))?;
+
+        Ok(AuthResponse {
+            access_token,
+            refresh_token, // Return the same refresh token
+            user,
+            expires_in: 15 * 60, // 15 minutes in seconds
+        })
     })
+    .await
 }
 
 /// Verify and decode an access token
@@ -759,7 +881,7 @@ pub async fn verify_token(
     auth: State<'_, AuthState>,
     token: String,
 ) -> Result<crate::core::auth_helpers::TokenClaims, String> {
-    verify_access_token(&token, auth.get_jwt_secret())
+    verify_access_token(&token, &auth.jwt_keys())
 }
 
 // ============================================================================
@@ -773,7 +895,7 @@ pub async fn get_current_user(
     auth: State<'_, AuthState>,
     token: String,
 ) -> Result<User, String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     get_user_by_id(&db.pool, &claims.sub).await
 }
 
@@ -785,7 +907,7 @@ pub async fn update_user(
     token: String,
     update: UserUpdate,
 ) -> Result<User, String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     // Validate display_name if provided
@@ -855,7 +977,7 @@ pub async fn change_password(
     old_password: String,
     new_password: String,
 ) -> Result<(), String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     // Validate new password
@@ -914,163 +1036,427 @@ pub async fn change_password(
 // Email Change Commands
 // ============================================================================
 
-/// Request an email change - sends verification to new email
+/// Get the Argon2id parameters used for new password hashes (app administrator only)
 #[tauri::command]
-pub async fn request_email_change(
+pub async fn get_password_hash_settings(
     db: State<'_, DatabaseState>,
     auth: State<'_, AuthState>,
     token: String,
-    request: EmailChangeRequest,
-) -> Result<EmailChangeResponse, String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+) -> Result<Argon2Params, String> {
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
+    verify_admin(&db.pool, &claims.sub).await?;
+
+    Ok(argon2_params())
+}
+
+/// Update the Argon2id parameters used for new password hashes (app administrator only)
+///
+/// Existing hashes are upgraded as each user next signs in.
+#[tauri::command]
+pub async fn update_password_hash_settings(
+    db: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    params: Argon2Params,
+) -> Result<Argon2Params, String> {
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
+    verify_admin(pool, &claims.sub).await?;
 
-    // Validate new email format
-    validate_email(&request.new_email)?;
-    let new_email = request.new_email.to_lowercase();
+    params.validate()?;
 
-    // Get current user info and password hash
-    let user_row: Option<(String, String, String)> =
-        sqlx::query_as("SELECT id, email, password_hash FROM users WHERE id = ?")
-            .bind(&claims.sub)
-            .fetch_optional(pool)
+    settings_store::set_setting_json(pool, ARGON2_PARAMS_SETTING, &params)
+        .await
+        .map_err(|e| format!("Failed to save settings: {}", e))?;
+    set_argon2_params(params);
+
+    log_audit_event(
+        pool,
+        Some(&claims.sub),
+        "password_hash_settings_changed",
+        "success",
+        Some(&params.label()),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(params)
+}
+
+/// Report how many stored password hashes still use outdated parameters
+/// (app administrator only)
+#[tauri::command]
+pub async fn get_password_hash_report(
+    db: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+) -> Result<PasswordHashReport, String> {
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
+    let pool = &db.pool;
+    verify_admin(pool, &claims.sub).await?;
+
+    let hashes: Vec<(String,)> =
+        sqlx::query_as("SELECT password_hash FROM users WHERE password_hash IS NOT NULL")
+            .fetch_all(pool)
             .await
             .map_err(|e| format!("Database error: {}", e))?;
 
-    let (user_id, old_email, password_hash) = user_row.ok_or("User not found")?;
+    let current = argon2_params();
+    let mut by_params: Vec<PasswordHashParamsCount> = Vec::new();
+    let mut up_to_date = 0;
 
-    // Verify current password
-    if !verify_password(&request.current_password, &password_hash)? {
-        log_audit_event(
-            pool,
-            Some(&user_id),
-            "email_change_request",
-            "failure",
-            Some("Invalid password"),
-            None,
-            None,
-        )
-        .await;
-        return Err("Current password is incorrect".to_string());
+    for (hash,) in &hashes {
+        let params = hash_params(hash);
+        if params == Some(current) {
+            up_to_date += 1;
+        }
+        let label = params
+            .map(|p| p.label())
+            .unwrap_or_else(|| "legacy".to_string());
+        match by_params.iter_mut().find(|c| c.params == label) {
+            Some(entry) => entry.count += 1,
+            None => by_params.push(PasswordHashParamsCount {
+                params: label,
+                count: 1,
+            }),
+        }
     }
+    by_params.sort_by(|a, b| b.count.cmp(&a.count).then(a.params.cmp(&b.params)));
+
+    Ok(PasswordHashReport {
+        current_params: current,
+        users_with_password: hashes.len() as i64,
+        up_to_date,
+        pending_rehash: hashes.len() as i64 - up_to_date,
+        by_params,
+    })
+}
 
-    // Check if new email is same as current
-    if new_email == old_email.to_lowercase() {
-        return Err("New email must be different from current email".to_string());
+/// Load the configured Argon2id parameters from settings
+///
+/// Called at startup; falls back to the defaults when unset or invalid.
+pub async fn load_password_hash_settings(pool: &sqlx::SqlitePool) -> Result<(), String> {
+    let params: Option<Argon2Params> =
+        settings_store::get_setting_json(pool, ARGON2_PARAMS_SETTING)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    if let Some(params) = params {
+        params.validate()?;
+        set_argon2_params(params);
     }
 
-    // Check if new email is already in use
-    let existing: Option<(String,)> =
-        sqlx::query_as("SELECT id FROM users WHERE LOWER(email) = ? AND id != ?")
-            .bind(&new_email)
-            .bind(&user_id)
+    Ok(())
+}
+
+/// Request a password reset - emails a one-hour reset code
+///
+/// Always succeeds so the response does not reveal whether an account exists
+/// for the email address.
+#[tauri::command]
+pub async fn request_password_reset(
+    db: State<'_, DatabaseState>,
+    email: String,
+) -> Result<(), String> {
+    let pool = &db.pool;
+    let email_addr = email.trim().to_lowercase();
+    validate_email(&email_addr)?;
+
+    let user: Option<(String, String, Option<String>)> =
+        sqlx::query_as("SELECT id, status, password_hash FROM users WHERE email = ?")
+            .bind(&email_addr)
             .fetch_optional(pool)
             .await
             .map_err(|e| format!("Database error: {}", e))?;
 
-    if existing.is_some() {
-        return Err("This email is already in use by another account".to_string());
-    }
-
-    // Cancel any existing pending requests
-    sqlx::query(
-        "UPDATE email_change_requests SET cancelled_at = ? WHERE user_id = ? AND verified_at IS NULL AND cancelled_at IS NULL AND completed_at IS NULL",
-    )
-    .bind(Utc::now())
-    .bind(&user_id)
-    .execute(pool)
-    .await
-    .ok();
+    // Only active accounts with a password can be reset
+    let user_id = match user {
+        Some((id, status, Some(_))) if status == "active" => id,
+        _ => return Ok(()),
+    };
 
-    // Generate tokens
-    let verification_token = generate_invitation_token();
-    let cancellation_token = generate_invitation_token();
-    let expires_at = Utc::now() + Duration::hours(48);
-    let request_id = Uuid::new_v4().to_string();
+    let reset_token = generate_password_reset_token();
+    let expires_at = Utc::now() + Duration::hours(1);
 
-    // Create the email change request
     sqlx::query(
-        r#"
-        INSERT INTO email_change_requests
-        (id, user_id, old_email, new_email, verification_token, cancellation_token, expires_at, created_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
-        "#,
+        "UPDATE users SET password_reset_token = ?, password_reset_expires_at = ?, updated_at = ? WHERE id = ?",
     )
-    .bind(&request_id)
-    .bind(&user_id)
-    .bind(&old_email)
-    .bind(&new_email)
-    .bind(&verification_token)
-    .bind(&cancellation_token)
+    .bind(hash_token(&reset_token))
     .bind(expires_at)
     .bind(Utc::now())
+    .bind(&user_id)
     .execute(pool)
     .await
-    .map_err(|e| format!("Failed to create email change request: {}", e))?;
+    .map_err(|e| format!("Failed to store reset token: {}", e))?;
+
+    let status = match email::send_password_reset(&email_addr, &reset_token).await {
+        Ok(()) => "success",
+        Err(e) => {
+            log_error!("Failed to send password reset email: {}", e);
+            "failure"
+        }
+    };
 
-    // Log the request
     log_audit_event(
         pool,
         Some(&user_id),
-        "email_change_request",
-        "success",
-        Some(&format!(
-            "Requested change from {} to {}",
-            old_email, new_email
-        )),
+        "password_reset_requested",
+        status,
+        None,
         None,
         None,
     )
     .await;
 
-    // Send verification email to new email address
-    if let Err(e) =
-        email::send_email_change_verification(&new_email, &verification_token, &new_email).await
-    {
-        eprintln!("Failed to send verification email: {}", e);
-        // Continue anyway - user can request again
-    }
-
-    // Send security alert to old email address
-    if let Err(e) =
-        email::send_email_change_alert(&old_email, &cancellation_token, &new_email).await
-    {
-        eprintln!("Failed to send security alert email: {}", e);
-        // Continue anyway
-    }
-
-    // Also log tokens for debugging (can be removed in production)
-    println!("Email Change Request Created:");
-    println!(
-        "  Verification token (for new email): {}",
-        verification_token
-    );
-    println!(
-        "  Cancellation token (for old email): {}",
-        cancellation_token
-    );
-
-    Ok(EmailChangeResponse {
-        message: format!(
-            "A verification email has been sent to {}. Please check your inbox and click the verification link within 48 hours.",
-            new_email
-        ),
-        expires_at,
-    })
+    Ok(())
 }
 
-/// Verify email change with token from new email
+/// Complete a password reset with the emailed code
+///
+/// Sets the new password, clears any lockout, and revokes all sessions.
 #[tauri::command]
-pub async fn verify_email_change(
+pub async fn reset_password(
     db: State<'_, DatabaseState>,
-    verification_token: String,
-) -> Result<String, String> {
-    let pool = &db.pool;
-
+    auth: State<'_, AuthState>,
+    reset_token: String,
+    new_password: String,
+) -> Result<(), String> {
+    let pool = &db.pool;
+
+    let user: Option<(String, Option<DateTime<Utc>>)> = sqlx::query_as(
+        "SELECT id, password_reset_expires_at FROM users WHERE password_reset_token = ?",
+    )
+    .bind(hash_token(reset_token.trim()))
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let (user_id, expires_at) = user.ok_or("Invalid or expired reset code")?;
+
+    if expires_at.is_none_or(|e| e < Utc::now()) {
+        sqlx::query(
+            "UPDATE users SET password_reset_token = NULL, password_reset_expires_at = NULL WHERE id = ?",
+        )
+        .bind(&user_id)
+        .execute(pool)
+        .await
+        .ok();
+
+        log_audit_event(
+            pool,
+            Some(&user_id),
+            "password_reset",
+            "failure",
+            Some("Reset code expired"),
+            None,
+            None,
+        )
+        .await;
+
+        return Err("Invalid or expired reset code".to_string());
+    }
+
+    validate_password_strength(&new_password, 8)?;
+    let new_hash = hash_password(&new_password)?;
+    let now = Utc::now();
+
+    // Update password and consume the reset token
+    sqlx::query(
+        r#"
+        UPDATE users
+        SET password_hash = ?, password_reset_token = NULL, password_reset_expires_at = NULL,
+            failed_login_attempts = 0, lockout_until = NULL, updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&new_hash)
+    .bind(now)
+    .bind(&user_id)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to update password: {}", e))?;
+
+    // Invalidate all sessions (force re-login everywhere)
+    sqlx::query("UPDATE sessions SET revoked = 1, revoked_at = ?, revoked_reason = 'password_reset' WHERE user_id = ? AND revoked = 0")
+        .bind(now)
+        .bind(&user_id)
+        .execute(pool)
+        .await
+        .ok();
+
+    auth.invalidate_user_sessions(&user_id);
+
+    log_audit_event(
+        pool,
+        Some(&user_id),
+        "password_reset",
+        "success",
+        None,
+        None,
+        None,
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Request an email change - sends verification to new email
+///
+/// The old address is alerted with a cancellation code, which stays valid
+/// until the change takes effect.
+#[tauri::command]
+pub async fn request_email_change(
+    db: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    request: EmailChangeRequest,
+) -> Result<EmailChangeResponse, String> {
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
+    let pool = &db.pool;
+
+    // Validate new email format
+    validate_email(&request.new_email)?;
+    let new_email = request.new_email.to_lowercase();
+
+    // Get current user info and password hash
+    let user_row: Option<(String, String, String)> =
+        sqlx::query_as("SELECT id, email, password_hash FROM users WHERE id = ?")
+            .bind(&claims.sub)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+    let (user_id, old_email, password_hash) = user_row.ok_or("User not found")?;
+
+    // Verify current password
+    if !verify_password(&request.current_password, &password_hash)? {
+        log_audit_event(
+            pool,
+            Some(&user_id),
+            "email_change_request",
+            "failure",
+            Some("Invalid password"),
+            None,
+            None,
+        )
+        .await;
+        return Err("Current password is incorrect".to_string());
+    }
+
+    // Check if new email is same as current
+    if new_email == old_email.to_lowercase() {
+        return Err("New email must be different from current email".to_string());
+    }
+
+    // Check if new email is already in use
+    let existing: Option<(String,)> =
+        sqlx::query_as("SELECT id FROM users WHERE LOWER(email) = ? AND id != ?")
+            .bind(&new_email)
+            .bind(&user_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+    if existing.is_some() {
+        return Err("This email is already in use by another account".to_string());
+    }
+
+    // Cancel any existing pending requests, including confirmed ones still
+    // in their cool-down
+    sqlx::query(
+        "UPDATE email_change_requests SET cancelled_at = ? WHERE user_id = ? AND cancelled_at IS NULL AND completed_at IS NULL",
+    )
+    .bind(Utc::now())
+    .bind(&user_id)
+    .execute(pool)
+    .await
+    .ok();
+
+    // Generate tokens
+    let verification_token = generate_invitation_token();
+    let cancellation_token = generate_invitation_token();
+    let expires_at = Utc::now() + Duration::hours(48);
+    let request_id = Uuid::new_v4().to_string();
+
+    // Create the email change request
+    sqlx::query(
+        r#"
+        INSERT INTO email_change_requests
+        (id, user_id, old_email, new_email, verification_token, cancellation_token, expires_at, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&request_id)
+    .bind(&user_id)
+    .bind(&old_email)
+    .bind(&new_email)
+    .bind(&verification_token)
+    .bind(&cancellation_token)
+    .bind(expires_at)
+    .bind(Utc::now())
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to create email change request: {}", e))?;
+
+    // Log the request
+    log_audit_event(
+        pool,
+        Some(&user_id),
+        "email_change_request",
+        "success",
+        Some(&format!(
+            "Requested change from {} to {}",
+            old_email, new_email
+        )),
+        None,
+        None,
+    )
+    .await;
+
+    // Send verification email to new email address
+    if let Err(e) =
+        email::send_email_change_verification(&new_email, &verification_token, &new_email).await
+    {
+        log_error!("Failed to send verification email: {}", e);
+        // Continue anyway - user can request again
+    }
+
+    // Send security alert to old email address
+    if let Err(e) =
+        email::send_email_change_alert(&old_email, &cancellation_token, &new_email).await
+    {
+        log_error!("Failed to send security alert email: {}", e);
+        // Continue anyway
+    }
+
+    Ok(EmailChangeResponse {
+        message: format!(
+            "A verification email has been sent to {}. Please check your inbox and click the verification link within 48 hours.",
+            new_email
+        ),
+        expires_at,
+    })
+}
+
+/// Verify email change with token from new email
+///
+/// Confirms the new address. The change takes effect after the configured
+/// cool-down, during which the old address is alerted and can still cancel
+/// it; with no cool-down it takes effect immediately. Completing the change
+/// revokes all sessions.
+#[tauri::command]
+pub async fn verify_email_change(
+    db: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    verification_token: String,
+) -> Result<String, String> {
+    let pool = &db.pool;
+
     // Find the pending request
-    let request: Option<(String, String, String, String, DateTime<Utc>)> = sqlx::query_as(
+    let request: Option<(String, String, String, String, String, DateTime<Utc>)> = sqlx::query_as(
         r#"
-        SELECT id, user_id, old_email, new_email, expires_at
+        SELECT id, user_id, old_email, new_email, cancellation_token, expires_at
         FROM email_change_requests
         WHERE verification_token = ?
           AND verified_at IS NULL
@@ -1083,7 +1469,7 @@ pub async fn verify_email_change(
     .await
     .map_err(|e| format!("Database error: {}", e))?;
 
-    let (request_id, user_id, old_email, new_email, expires_at) =
+    let (request_id, user_id, old_email, new_email, cancellation_token, expires_at) =
         request.ok_or("Invalid or expired verification token")?;
 
     // Check if expired
@@ -1094,56 +1480,57 @@ pub async fn verify_email_change(
     }
 
     // Check if new email was taken in the meantime
-    let existing: Option<(String,)> =
-        sqlx::query_as("SELECT id FROM users WHERE LOWER(email) = ? AND id != ?")
-            .bind(new_email.to_lowercase())
-            .bind(&user_id)
-            .fetch_optional(pool)
-            .await
-            .map_err(|e| format!("Database error: {}", e))?;
-
-    if existing.is_some() {
+    if email_in_use(pool, &new_email, &user_id).await? {
         return Err("This email is already in use by another account".to_string());
     }
 
+    let settings = load_email_change_settings(pool).await?;
     let now = Utc::now();
+    let effective_at = now + Duration::hours(i64::from(settings.cooldown_hours));
 
-    // Update the user's email
-    sqlx::query("UPDATE users SET email = ?, email_verified = 1, updated_at = ? WHERE id = ?")
-        .bind(&new_email)
-        .bind(now)
-        .bind(&user_id)
-        .execute(pool)
-        .await
-        .map_err(|e| format!("Failed to update email: {}", e))?;
-
-    // Mark the request as verified and completed
-    sqlx::query("UPDATE email_change_requests SET verified_at = ?, completed_at = ? WHERE id = ?")
-        .bind(now)
+    sqlx::query("UPDATE email_change_requests SET verified_at = ?, effective_at = ? WHERE id = ?")
         .bind(now)
+        .bind(effective_at)
         .bind(&request_id)
         .execute(pool)
         .await
-        .ok();
+        .map_err(|e| format!("Failed to confirm email change: {}", e))?;
 
-    // Log the change
     log_audit_event(
         pool,
         Some(&user_id),
-        "email_change_complete",
+        "email_change_confirmed",
         "success",
         Some(&format!(
-            "Changed email from {} to {}",
-            old_email, new_email
+            "Confirmed change from {} to {}, effective {}",
+            old_email,
+            new_email,
+            effective_at.to_rfc3339()
         )),
         None,
         None,
     )
     .await;
 
+    if settings.cooldown_hours == 0 {
+        complete_email_change(pool, &auth, &request_id, &user_id, &old_email, &new_email).await?;
+        return Ok(format!(
+            "Your email has been successfully changed to {}. Please sign in again.",
+            new_email
+        ));
+    }
+
+    let effective = effective_at.format("%Y-%m-%d %H:%M UTC").to_string();
+    if let Err(e) =
+        email::send_email_change_confirmed(&old_email, &cancellation_token, &new_email, &effective)
+            .await
+    {
+        log_error!("Failed to send email change confirmation alert: {}", e);
+    }
+
     Ok(format!(
-        "Your email has been successfully changed to {}",
-        new_email
+        "Your new email {} is confirmed. The change takes effect at {}.",
+        new_email, effective
     ))
 }
 
@@ -1155,13 +1542,13 @@ pub async fn cancel_email_change(
 ) -> Result<String, String> {
     let pool = &db.pool;
 
-    // Find the pending request
+    // Find the pending request; confirmed requests can be cancelled until
+    // they take effect
     let request: Option<(String, String, String, String)> = sqlx::query_as(
         r#"
         SELECT id, user_id, old_email, new_email
         FROM email_change_requests
         WHERE cancellation_token = ?
-          AND verified_at IS NULL
           AND cancelled_at IS NULL
           AND completed_at IS NULL
         "#,
@@ -1207,18 +1594,26 @@ pub async fn get_email_change_status(
     auth: State<'_, AuthState>,
     token: String,
 ) -> Result<EmailChangeStatus, String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
-    let pending: Option<(String, DateTime<Utc>, DateTime<Utc>)> = sqlx::query_as(
+    // Apply any change whose cool-down has ended before reporting
+    complete_due_email_changes(pool, &auth).await?;
+
+    let pending: Option<(
+        String,
+        DateTime<Utc>,
+        DateTime<Utc>,
+        Option<DateTime<Utc>>,
+        Option<DateTime<Utc>>,
+    )> = sqlx::query_as(
         r#"
-        SELECT new_email, expires_at, created_at
+        SELECT new_email, expires_at, created_at, verified_at, effective_at
         FROM email_change_requests
         WHERE user_id = ?
-          AND verified_at IS NULL
           AND cancelled_at IS NULL
           AND completed_at IS NULL
-          AND expires_at > ?
+          AND (verified_at IS NOT NULL OR expires_at > ?)
         ORDER BY created_at DESC
         LIMIT 1
         "#,
@@ -1230,21 +1625,223 @@ pub async fn get_email_change_status(
     .map_err(|e| format!("Database error: {}", e))?;
 
     match pending {
-        Some((new_email, expires_at, created_at)) => Ok(EmailChangeStatus {
-            pending: true,
-            new_email: Some(new_email),
-            expires_at: Some(expires_at),
-            created_at: Some(created_at),
-        }),
+        Some((new_email, expires_at, created_at, verified_at, effective_at)) => {
+            Ok(EmailChangeStatus {
+                pending: true,
+                new_email: Some(new_email),
+                expires_at: Some(expires_at),
+                created_at: Some(created_at),
+                confirmed: verified_at.is_some(),
+                effective_at,
+            })
+        }
         None => Ok(EmailChangeStatus {
             pending: false,
             new_email: None,
             expires_at: None,
             created_at: None,
+            confirmed: false,
+            effective_at: None,
         }),
     }
 }
 
+/// Get the email change settings (app administrator only)
+#[tauri::command]
+pub async fn get_email_change_settings(
+    db: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+) -> Result<EmailChangeSettings, String> {
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
+    verify_admin(&db.pool, &claims.sub).await?;
+
+    load_email_change_settings(&db.pool).await
+}
+
+/// Update the email change settings (app administrator only)
+///
+/// A new cool-down applies to changes confirmed from now on.
+#[tauri::command]
+pub async fn update_email_change_settings(
+    db: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    settings: EmailChangeSettings,
+) -> Result<EmailChangeSettings, String> {
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
+    let pool = &db.pool;
+    verify_admin(pool, &claims.sub).await?;
+
+    settings.validate()?;
+
+    settings_store::set_setting_json(pool, EMAIL_CHANGE_SETTINGS, &settings)
+        .await
+        .map_err(|e| format!("Failed to save settings: {}", e))?;
+
+    log_audit_event(
+        pool,
+        Some(&claims.sub),
+        "email_change_settings_changed",
+        "success",
+        Some(&format!("cooldown_hours={}", settings.cooldown_hours)),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(settings)
+}
+
+/// Load the email change settings, or the defaults if none are saved
+async fn load_email_change_settings(
+    pool: &sqlx::SqlitePool,
+) -> Result<EmailChangeSettings, String> {
+    settings_store::get_setting_json(pool, EMAIL_CHANGE_SETTINGS)
+        .await
+        .map(Option::unwrap_or_default)
+        .map_err(|e| e.to_string())
+}
+
+/// Whether another account already uses `email`
+async fn email_in_use(pool: &sqlx::SqlitePool, email: &str, user_id: &str) -> Result<bool, String> {
+    let existing: Option<(String,)> =
+        sqlx::query_as("SELECT id FROM users WHERE LOWER(email) = ? AND id != ?")
+            .bind(email.to_lowercase())
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(existing.is_some())
+}
+
+/// Apply a confirmed email change and revoke all of the user's sessions
+async fn complete_email_change(
+    pool: &sqlx::SqlitePool,
+    auth: &AuthState,
+    request_id: &str,
+    user_id: &str,
+    old_email: &str,
+    new_email: &str,
+) -> Result<(), String> {
+    let now = Utc::now();
+
+    // The new address may have been taken during the cool-down
+    if email_in_use(pool, new_email, user_id).await? {
+        sqlx::query("UPDATE email_change_requests SET cancelled_at = ? WHERE id = ?")
+            .bind(now)
+            .bind(request_id)
+            .execute(pool)
+            .await
+            .ok();
+
+        log_audit_event(
+            pool,
+            Some(user_id),
+            "email_change_complete",
+            "failure",
+            Some(&format!("{} is already in use", new_email)),
+            None,
+            None,
+        )
+        .await;
+
+        return Err("This email is already in use by another account".to_string());
+    }
+
+    // Update the user's email
+    sqlx::query("UPDATE users SET email = ?, email_verified = 1, updated_at = ? WHERE id = ?")
+        .bind(new_email)
+        .bind(now)
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to update email: {}", e))?;
+
+    sqlx::query("UPDATE email_change_requests SET completed_at = ? WHERE id = ?")
+        .bind(now)
+        .bind(request_id)
+        .execute(pool)
+        .await
+        .ok();
+
+    // Invalidate all sessions (force re-login with the new address)
+    sqlx::query("UPDATE sessions SET revoked = 1, revoked_at = ?, revoked_reason = 'email_change' WHERE user_id = ? AND revoked = 0")
+        .bind(now)
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .ok();
+
+    auth.invalidate_user_sessions(user_id);
+    auth.clear_active_user(user_id);
+
+    log_audit_event(
+        pool,
+        Some(user_id),
+        "email_change_complete",
+        "success",
+        Some(&format!(
+            "Changed email from {} to {}",
+            old_email, new_email
+        )),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Apply confirmed email changes whose cool-down has ended
+pub(crate) async fn complete_due_email_changes(
+    pool: &sqlx::SqlitePool,
+    auth: &AuthState,
+) -> Result<usize, String> {
+    let due: Vec<(String, String, String, String)> = sqlx::query_as(
+        r#"
+        SELECT id, user_id, old_email, new_email
+        FROM email_change_requests
+        WHERE verified_at IS NOT NULL
+          AND cancelled_at IS NULL
+          AND completed_at IS NULL
+          AND effective_at <= ?
+        "#,
+    )
+    .bind(Utc::now())
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut completed = 0;
+    for (request_id, user_id, old_email, new_email) in due {
+        match complete_email_change(pool, auth, &request_id, &user_id, &old_email, &new_email).await
+        {
+            Ok(()) => completed += 1,
+            Err(e) => log_error!("Failed to complete email change {}: {}", request_id, e),
+        }
+    }
+    Ok(completed)
+}
+
+/// Starts the background task that applies confirmed email changes once
+/// their cool-down ends.
+pub fn spawn_email_change_loop(app: tauri::AppHandle) {
+    use tauri::Manager;
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let pool = app.state::<DatabaseState>().pool.clone();
+            let auth = app.state::<AuthState>();
+            if let Err(e) = complete_due_email_changes(&pool, &auth).await {
+                log_error!("Failed to apply pending email changes: {}", e);
+            }
+            tokio::time::sleep(EMAIL_CHANGE_CHECK_INTERVAL).await;
+        }
+    });
+}
+
 // ============================================================================
 // Session Management Commands
 // ============================================================================
@@ -1256,7 +1853,7 @@ pub async fn get_user_sessions(
     auth: State<'_, AuthState>,
     token: String,
 ) -> Result<Vec<SessionInfo>, String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     let sessions: Vec<Session> = sqlx::query_as(
@@ -1298,7 +1895,7 @@ pub async fn revoke_session(
     token: String,
     session_id: String,
 ) -> Result<(), String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     // Verify session belongs to user
@@ -1335,7 +1932,7 @@ pub async fn revoke_all_sessions(
     auth: State<'_, AuthState>,
     token: String,
 ) -> Result<(), String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     sqlx::query(
@@ -1363,7 +1960,7 @@ pub async fn get_user_profiles(
     auth: State<'_, AuthState>,
     token: String,
 ) -> Result<Vec<ProfileWithRole>, String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     let profiles: Vec<ProfileWithRole> = sqlx::query_as(
@@ -1391,11 +1988,11 @@ pub async fn get_profile_users(
     token: String,
     profile_id: String,
 ) -> Result<Vec<UserWithRole>, String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     // Verify user has admin access to this profile
-    verify_profile_access(pool, &claims.sub, &profile_id, &["owner", "admin"]).await?;
+    verify_profile_access(pool, &claims.sub, &profile_id, Permission::ManageMembers).await?;
 
     let users: Vec<UserWithRole> = sqlx::query_as(
         r#"
@@ -1433,20 +2030,19 @@ pub async fn update_user_role(
     profile_id: String,
     role: String,
 ) -> Result<UserProfileRole, String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     // Validate role
-    let valid_roles = ["user", "preparer", "approver", "admin"];
-    if !valid_roles.contains(&role.as_str()) {
+    if !ASSIGNABLE_ROLES.contains(&role.as_str()) {
         return Err(format!(
             "Invalid role: {}. Valid roles are: {:?}",
-            role, valid_roles
+            role, ASSIGNABLE_ROLES
         ));
     }
 
     // Verify user has admin access
-    verify_profile_access(pool, &claims.sub, &profile_id, &["owner", "admin"]).await?;
+    verify_profile_access(pool, &claims.sub, &profile_id, Permission::ManageMembers).await?;
 
     // Cannot change owner's role
     let target_role: Option<(String,)> =
@@ -1507,11 +2103,11 @@ pub async fn remove_user_from_profile(
     user_id: String,
     profile_id: String,
 ) -> Result<(), String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     // Verify user has admin access
-    verify_profile_access(pool, &claims.sub, &profile_id, &["owner", "admin"]).await?;
+    verify_profile_access(pool, &claims.sub, &profile_id, Permission::ManageMembers).await?;
 
     // Cannot remove owner
     let target_role: Option<(String,)> =
@@ -1569,15 +2165,14 @@ pub async fn create_invitation(
     token: String,
     invitation: InvitationInput,
 ) -> Result<Invitation, String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     // Validate email
     validate_email(&invitation.email)?;
 
     // Validate role
-    let valid_roles = ["user", "preparer", "approver", "admin"];
-    if !valid_roles.contains(&invitation.role.as_str()) {
+    if !ASSIGNABLE_ROLES.contains(&invitation.role.as_str()) {
         return Err(format!("Invalid role: {}", invitation.role));
     }
 
@@ -1586,7 +2181,7 @@ pub async fn create_invitation(
         pool,
         &claims.sub,
         &invitation.profile_id,
-        &["owner", "admin"],
+        Permission::ManageMembers,
     )
     .await?;
 
@@ -1655,6 +2250,100 @@ pub async fn create_invitation(
     )
     .await;
 
+    // Email the invitation link (non-blocking, the invitation can be resent)
+    let email_pool = pool.clone();
+    let inviter_id = claims.sub.clone();
+    let invitee = invitation.clone();
+    tokio::spawn(async move {
+        if let Err(e) = send_invitation(
+            &email_pool,
+            &inviter_id,
+            &invitee.email,
+            &invitee.profile_id,
+            &invitee.role,
+            invitee.message.as_deref(),
+            &invite_token,
+        )
+        .await
+        {
+            log_error!("Failed to send invitation email: {}", e);
+        }
+    });
+
+    sqlx::query_as("SELECT id, email, profile_id, role, status, message, token_expires_at, created_at FROM invitations WHERE id = ?")
+        .bind(&invitation_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Resend a pending or expired invitation
+///
+/// Issues a fresh token (invalidating the previous link), extends the
+/// expiration, and emails the new link.
+#[tauri::command]
+pub async fn resend_invitation(
+    db: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    invitation_id: String,
+) -> Result<Invitation, String> {
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
+    let pool = &db.pool;
+
+    let invitation: Option<(String, String, String, Option<String>, String)> = sqlx::query_as(
+        "SELECT email, profile_id, role, message, status FROM invitations WHERE id = ?",
+    )
+    .bind(&invitation_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let (inv_email, profile_id, role, message, status) =
+        invitation.ok_or("Invitation not found")?;
+
+    // Verify user has admin access before revealing the invitation's status
+    verify_profile_access(pool, &claims.sub, &profile_id, Permission::ManageMembers).await?;
+
+    if status != "pending" && status != "expired" {
+        return Err(format!("Invitation has already been {}", status));
+    }
+
+    let invite_token = generate_invitation_token();
+    let expires_at = Utc::now() + Duration::hours(72);
+
+    sqlx::query(
+        "UPDATE invitations SET token = ?, token_expires_at = ?, status = 'pending' WHERE id = ?",
+    )
+    .bind(&invite_token)
+    .bind(expires_at)
+    .bind(&invitation_id)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to update invitation: {}", e))?;
+
+    send_invitation(
+        pool,
+        &claims.sub,
+        &inv_email,
+        &profile_id,
+        &role,
+        message.as_deref(),
+        &invite_token,
+    )
+    .await?;
+
+    log_audit_event(
+        pool,
+        Some(&claims.sub),
+        "invitation_resent",
+        "success",
+        Some(&inv_email),
+        None,
+        Some(&profile_id),
+    )
+    .await;
+
     sqlx::query_as("SELECT id, email, profile_id, role, status, message, token_expires_at, created_at FROM invitations WHERE id = ?")
         .bind(&invitation_id)
         .fetch_one(pool)
@@ -1670,11 +2359,11 @@ pub async fn get_profile_invitations(
     token: String,
     profile_id: String,
 ) -> Result<Vec<Invitation>, String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     // Verify user has admin access
-    verify_profile_access(pool, &claims.sub, &profile_id, &["owner", "admin"]).await?;
+    verify_profile_access(pool, &claims.sub, &profile_id, Permission::ManageMembers).await?;
 
     let invitations: Vec<Invitation> = sqlx::query_as(
         r#"
@@ -1702,7 +2391,9 @@ pub async fn accept_invitation(
     new_user: Option<RegisterInput>,
 ) -> Result<AuthResponse, String> {
     let pool = &db.pool;
+    let identifier = hash_token(&invitation_token);
 
+    throttled(pool, &auth, AuthAction::AcceptInvitation, &identifier, async {
     // Find invitation by token
     let invitation: Option<(String, String, String, String, String, DateTime<Utc>)> =
         sqlx::query_as(
@@ -1717,11 +2408,11 @@ pub async fn accept_invitation(
         .map_err(|e| format!("Database error: {}", e))?;
 
     let (inv_id, inv_email, profile_id, role, status, expires_at) =
-        invitation.ok_or("Invalid invitation token")?;
+        invitation.ok_or_else(|| AttemptError::rejected("Invalid invitation token"))?;
 
     // Check invitation status
     if status != "pending" {
-        return Err(format!("Invitation has already been {}", status));
+        return Err(format!("Invitation has already been {}", status).into());
     }
 
     // Check expiration
@@ -1731,7 +2422,7 @@ pub async fn accept_invitation(
             .execute(pool)
             .await
             .ok();
-        return Err("Invitation has expired".to_string());
+        return Err("Invitation has expired".to_string().into());
     }
 
     let user_id: String;
@@ -1740,11 +2431,15 @@ pub async fn accept_invitation(
     // Handle existing user login or new user registration
     if let Some(token) = user_token {
         // Existing user accepting invitation
-        let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+        let claims = verify_access_token(&token, &auth.jwt_keys())?;
 
         // Verify email matches
         if claims.email.to_lowercase() != inv_email.to_lowercase() {
-            return Err("This invitation was sent to a different email address".to_string());
+            return Err(
+                "This invitation was sent to a different email address"
+                    .to_string()
+                    .into(),
+            );
         }
 
         user_id = claims.sub;
@@ -1752,7 +2447,7 @@ pub async fn accept_invitation(
     } else if let Some(input) = new_user {
         // New user registration
         if input.email.to_lowercase() != inv_email.to_lowercase() {
-            return Err("Email must match the invitation email".to_string());
+            return Err("Email must match the invitation email".to_string().into());
         }
 
         // Register the new user (but don't create default profile)
@@ -1783,7 +2478,9 @@ pub async fn accept_invitation(
         user_email = input.email.to_lowercase();
     } else {
         return Err(
-            "Must provide either user_token (existing user) or new_user (registration)".to_string(),
+            "Must provide either user_token (existing user) or new_user (registration)"
+                .to_string()
+                .into(),
         );
     }
 
@@ -1818,7 +2515,9 @@ pub async fn accept_invitation(
         .ok();
 
     // Create session and return tokens
-    create_session_and_tokens(&db, &auth, &user_id, &user_email, None, None).await
+    Ok(create_session_and_tokens(&db, &auth, &user_id, &user_email, &DeviceInfo::current()).await?)
+    })
+    .await
 }
 
 /// Revoke an invitation
@@ -1829,7 +2528,7 @@ pub async fn revoke_invitation(
     token: String,
     invitation_id: String,
 ) -> Result<(), String> {
-    let claims = verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     // Get invitation to verify profile access
@@ -1843,7 +2542,7 @@ pub async fn revoke_invitation(
     let (profile_id,) = invitation.ok_or("Invitation not found or not pending")?;
 
     // Verify user has admin access
-    verify_profile_access(pool, &claims.sub, &profile_id, &["owner", "admin"]).await?;
+    verify_profile_access(pool, &claims.sub, &profile_id, Permission::ManageMembers).await?;
 
     sqlx::query("UPDATE invitations SET status = 'revoked' WHERE id = ?")
         .bind(&invitation_id)
@@ -1875,13 +2574,163 @@ async fn get_user_by_id(pool: &sqlx::SqlitePool, user_id: &str) -> Result<User,
     .map_err(|e| format!("User not found: {}", e))
 }
 
+/// Email an invitation link, looking up the inviter and profile names
+async fn send_invitation(
+    pool: &sqlx::SqlitePool,
+    inviter_id: &str,
+    to: &str,
+    profile_id: &str,
+    role: &str,
+    message: Option<&str>,
+    invite_token: &str,
+) -> Result<(), String> {
+    let inviter = get_user_by_id(pool, inviter_id).await?;
+    let profile_name: Option<(String,)> = sqlx::query_as("SELECT name FROM profiles WHERE id = ?")
+        .bind(profile_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let profile_name = profile_name
+        .map(|(name,)| name)
+        .unwrap_or_else(|| "a profile".to_string());
+
+    email::send_invitation_email(
+        to,
+        &inviter.display_name,
+        &profile_name,
+        role,
+        message,
+        &deep_link::invitation_url(invite_token),
+        invite_token,
+    )
+    .await
+}
+
+/// Compare a login with earlier sessions and send a login alert if it comes
+/// from an unfamiliar device or network. Must run before the new session is
+/// stored.
+pub(crate) async fn check_login_anomaly(
+    pool: &sqlx::SqlitePool,
+    user_id: &str,
+    device: &DeviceInfo,
+) {
+    let history: Vec<SessionFingerprint> = match sqlx::query_as(
+        "SELECT device_name, user_agent, ip_address FROM sessions WHERE user_id = ?",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    {
+        Ok(history) => history,
+        Err(e) => {
+            log_error!("Failed to load session history: {}", e);
+            return;
+        }
+    };
+
+    let anomalies = device::detect_anomalies(&history, device);
+
+    // While a confirmed email change is in its cool-down, every login is
+    // reported so the owner of the old address can cancel a hijack
+    let pending_change: Option<(String, DateTime<Utc>)> = sqlx::query_as(
+        r#"
+        SELECT new_email, effective_at
+        FROM email_change_requests
+        WHERE user_id = ?
+          AND verified_at IS NOT NULL
+          AND cancelled_at IS NULL
+          AND completed_at IS NULL
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten();
+
+    if anomalies.is_empty() && pending_change.is_none() {
+        return;
+    }
+
+    let ip_address = device
+        .ip_address
+        .clone()
+        .unwrap_or_else(|| "unknown".to_string());
+    let reasons = if anomalies.is_empty() {
+        "familiar device".to_string()
+    } else {
+        let reasons = anomalies
+            .iter()
+            .map(|a| a.description())
+            .collect::<Vec<_>>()
+            .join(" and ");
+
+        log_audit_event(
+            pool,
+            Some(user_id),
+            "login_anomaly",
+            "success",
+            Some(&format!(
+                "{}: {}, {}",
+                reasons,
+                device.describe(),
+                ip_address
+            )),
+            None,
+            None,
+        )
+        .await;
+
+        reasons
+    };
+
+    let user = match get_user_by_id(pool, user_id).await {
+        Ok(user) => user,
+        Err(_) => return,
+    };
+    if user.login_alerts == Some(false) && pending_change.is_none() {
+        return;
+    }
+
+    // Send alert (non-blocking, don't fail login if email fails)
+    let to = user
+        .notification_email
+        .filter(|e| !e.is_empty())
+        .unwrap_or(user.email);
+    let device_description = device.describe();
+    let login_time = Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
+    let pending_change = pending_change.map(|(new_email, effective_at)| {
+        (
+            new_email,
+            effective_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+        )
+    });
+    tokio::spawn(async move {
+        if let Err(e) = email::send_login_alert(
+            &to,
+            &reasons,
+            &device_description,
+            &ip_address,
+            &login_time,
+            pending_change
+                .as_ref()
+                .map(|(new_email, effective)| (new_email.as_str(), effective.as_str())),
+        )
+        .await
+        {
+            log_error!("Failed to send login alert: {}", e);
+        }
+    });
+}
+
 async fn create_session_and_tokens(
     db: &State<'_, DatabaseState>,
     auth: &State<'_, AuthState>,
     user_id: &str,
     email: &str,
-    device_name: Option<&str>,
-    device_type: Option<&str>,
+    device: &DeviceInfo,
 ) -> Result<AuthResponse, String> {
     let pool = &db.pool;
     let session_id = generate_session_id();
@@ -1889,28 +2738,30 @@ async fn create_session_and_tokens(
     let expires_at = now + Duration::days(7);
 
     // Generate tokens
-    let access_token = generate_access_token(user_id, email, auth.get_jwt_secret(), Some(15))?;
+    let access_token = generate_access_token(user_id, email, &auth.jwt_keys(), Some(15))?;
 
     let refresh_token = crate::core::auth_helpers::generate_refresh_token(
         user_id,
         email,
         &session_id,
-        auth.get_jwt_secret(),
+        &auth.jwt_keys(),
         Some(7),
     )?;
 
     // Store session
     sqlx::query(
         r#"
-        INSERT INTO sessions (id, user_id, refresh_token_hash, device_name, device_type, expires_at, created_at, last_activity_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        INSERT INTO sessions (id, user_id, refresh_token_hash, device_name, device_type, ip_address, user_agent, expires_at, created_at, last_activity_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
     )
     .bind(&session_id)
     .bind(user_id)
     .bind(hash_token(&refresh_token))
-    .bind(device_name)
-    .bind(device_type)
+    .bind(&device.device_name)
+    .bind(&device.device_type)
+    .bind(&device.ip_address)
+    .bind(device.user_agent())
     .bind(expires_at)
     .bind(now)
     .bind(now)
@@ -1932,63 +2783,95 @@ async fn create_session_and_tokens(
     })
 }
 
-async fn verify_profile_access(
-    pool: &sqlx::SqlitePool,
-    user_id: &str,
-    profile_id: &str,
-    required_roles: &[&str],
-) -> Result<(), String> {
-    let role: Option<(String, String)> = sqlx::query_as(
-        "SELECT role, status FROM user_profile_roles WHERE user_id = ? AND profile_id = ?",
+/// Verify the user is the app administrator
+///
+/// App-wide settings affect every user, so administering a profile is not
+/// enough: anyone can create a profile and own it.
+pub(crate) async fn verify_admin(pool: &sqlx::SqlitePool, user_id: &str) -> Result<(), String> {
+    let admin: Option<(i64,)> = sqlx::query_as(
+        "SELECT 1 FROM users WHERE id = ? AND status = 'active' AND is_app_admin = 1",
     )
     .bind(user_id)
-    .bind(profile_id)
     .fetch_optional(pool)
     .await
     .map_err(|e| format!("Database error: {}", e))?;
 
-    match role {
-        Some((role, status)) => {
-            if status != "active" {
-                return Err("Your access to this profile has been suspended".to_string());
-            }
-            if !required_roles.contains(&role.as_str()) {
-                return Err(format!(
-                    "Insufficient permissions. Required: {:?}, you have: {}",
-                    required_roles, role
-                ));
-            }
-            Ok(())
-        }
-        None => Err("You don't have access to this profile".to_string()),
+    admin
+        .map(|_| ())
+        .ok_or_else(|| "Administrator access required".to_string())
+}
+
+/// Why a throttled auth attempt failed.
+#[derive(Debug)]
+pub(crate) enum AttemptError {
+    /// The credential presented was wrong, unknown, expired, or already
+    /// used. Only these count toward a lockout.
+    Rejected(String),
+    /// Anything else, such as a database error or invalid input.
+    Failed(String),
+}
+
+impl AttemptError {
+    /// A rejected credential.
+    pub(crate) fn rejected(message: impl Into<String>) -> Self {
+        Self::Rejected(message.into())
+    }
+}
+
+impl From<String> for AttemptError {
+    fn from(message: String) -> Self {
+        Self::Failed(message)
     }
 }
 
-async fn log_audit_event(
+/// Runs an auth attempt under the rate limiter.
+///
+/// The attempt is rejected without running while `identifier` is locked out,
+/// and held back while many attempts at the action have recently failed. An
+/// attempt whose credential is rejected counts toward a lockout, and a
+/// lockout it starts is recorded in the audit log; a successful one clears
+/// the identifier's failures. Other errors don't count either way.
+pub(crate) async fn throttled<T>(
     pool: &sqlx::SqlitePool,
-    user_id: Option<&str>,
-    event_type: &str,
-    event_status: &str,
-    event_details: Option<&str>,
-    target_user_id: Option<&str>,
-    target_profile_id: Option<&str>,
-) {
-    let id = Uuid::new_v4().to_string();
-    sqlx::query(
-        r#"
-        INSERT INTO auth_audit_log (id, user_id, event_type, event_status, event_details, target_user_id, target_profile_id, created_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
-        "#,
-    )
-    .bind(&id)
-    .bind(user_id)
-    .bind(event_type)
-    .bind(event_status)
-    .bind(event_details)
-    .bind(target_user_id)
-    .bind(target_profile_id)
-    .bind(Utc::now())
-    .execute(pool)
-    .await
-    .ok();
+    auth: &AuthState,
+    action: AuthAction,
+    identifier: &str,
+    attempt: impl Future<Output = Result<T, AttemptError>>,
+) -> Result<T, String> {
+    let limiter = auth.rate_limiter();
+    limiter.check(action, identifier)?;
+    let delay = limiter.delay(action);
+    if !delay.is_zero() {
+        tokio::time::sleep(delay).await;
+    }
+
+    match attempt.await {
+        Ok(value) => {
+            limiter.record_success(action, identifier);
+            Ok(value)
+        }
+        Err(AttemptError::Failed(message)) => Err(message),
+        Err(AttemptError::Rejected(message)) => {
+            if let Some(lockout) = limiter.record_failure(action, identifier) {
+                let details = serde_json::json!({
+                    "action": action.as_str(),
+                    "identifier": lockout.identifier,
+                    "failures": lockout.failures,
+                    "lockoutSeconds": lockout.duration.as_secs(),
+                })
+                .to_string();
+                log_audit_event(
+                    pool,
+                    None,
+                    "rate_limit_lockout",
+                    "failure",
+                    Some(&details),
+                    None,
+                    None,
+                )
+                .await;
+            }
+            Err(message)
+        }
+    }
 }
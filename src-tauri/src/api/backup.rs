@@ -1,5 +1,21 @@
 use anyhow::Result;
-use tauri::Manager;
+use tauri::{Manager, State};
+
+use super::auth::verify_admin;
+use super::persistence::DatabaseState;
+use super::profile_scope::authenticate;
+use crate::core::auth_state::AuthState;
+
+/// Verifies `token` belongs to the app administrator. A backup holds every
+/// profile's data, so no profile role is enough to take or restore one.
+async fn authorize_backup(
+    state: &DatabaseState,
+    auth: &AuthState,
+    token: &str,
+) -> Result<(), String> {
+    let user_id = authenticate(auth, token)?;
+    verify_admin(&state.pool, &user_id).await
+}
 
 #[tauri::command]
 /// Creates a backup of the application's data directory.
@@ -7,7 +23,14 @@ use tauri::Manager;
 /// This asynchronous command retrieves the application data directory from the provided
 /// `AppHandle`, generates a timestamped ZIP filename, and performs the backup process.
 /// Returns the name of the created backup archive on success, or an error message on failure.
-pub async fn create_backup(app_handle: tauri::AppHandle) -> Result<String, String> {
+/// Only the app administrator may take a backup.
+pub async fn create_backup(
+    app_handle: tauri::AppHandle,
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+) -> Result<String, String> {
+    authorize_backup(&state, &auth, &token).await?;
     let _data_dir = app_handle
         .path()
         .app_data_dir()
@@ -30,10 +53,15 @@ pub async fn create_backup(app_handle: tauri::AppHandle) -> Result<String, Strin
 /// This asynchronous command accepts an `AppHandle` and the path to a backup ZIP file.
 /// It extracts the archive contents and restores the application's database and settings.
 /// Returns `()` on success, or an error message if the restore operation fails.
+/// Only the app administrator may restore a backup.
 pub async fn restore_backup(
     _app_handle: tauri::AppHandle,
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     _backup_path: String,
 ) -> Result<(), String> {
+    authorize_backup(&state, &auth, &token).await?;
     // Implementation would extract the backup and restore database
     Ok(())
 }
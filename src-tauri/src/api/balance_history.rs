@@ -0,0 +1,561 @@
+//! Wallet balances at past dates, rebuilt from stored history.
+//!
+//! Each wallet's stored transactions are replayed in order up to the
+//! requested time: native value in and out, the fees the wallet paid, and
+//! the token transfers wallet sync stores alongside each transaction.
+//! Replaying up to now and comparing with the balances on chain shows
+//! where history is missing. Non-fungible transfers are not counted.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::address_watch::native_currency;
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction, Wallet};
+use super::profile_scope::{authorize_profile, profile_wallets};
+use super::token_spam::SpamFilter;
+use crate::chains::units::{from_smallest_units, parse_decimal};
+use crate::chains::{ChainManagerState, TokenTransfer, WalletBalances};
+use crate::core::auth_state::AuthState;
+
+pub use pacioli_core::services::balance_history::*;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Balance of one asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetBalance {
+    /// Asset symbol.
+    pub symbol: String,
+    /// Token contract; `None` for the native currency and for assets known
+    /// only by symbol.
+    pub token_address: Option<String>,
+    /// Balance in whole units.
+    pub balance: Decimal,
+}
+
+/// A wallet's balances at a point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoricalBalances {
+    /// Wallet the balances belong to.
+    pub wallet_id: String,
+    /// Chain the wallet is on.
+    pub chain: String,
+    /// Wallet address.
+    pub address: String,
+    /// Time the balances are as of.
+    pub at: DateTime<Utc>,
+    /// Non-zero balances, assets known by symbol first, then tokens.
+    pub balances: Vec<AssetBalance>,
+    /// Number of transactions replayed.
+    pub replayed: usize,
+    /// Number of transactions without a timestamp or with amounts that
+    /// couldn't be read.
+    pub skipped: usize,
+}
+
+/// An asset whose rebuilt balance differs from the balance on chain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceDiscrepancy {
+    /// Asset symbol.
+    pub symbol: String,
+    /// Token contract; `None` for the native currency.
+    pub token_address: Option<String>,
+    /// Balance rebuilt from stored history.
+    pub reconstructed: Decimal,
+    /// Balance reported by the chain.
+    pub on_chain: Decimal,
+    /// `on_chain - reconstructed`; positive when history is missing inflows.
+    pub difference: Decimal,
+}
+
+/// Comparison of a wallet's rebuilt and on-chain balances.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceReconciliation {
+    /// Wallet compared.
+    pub wallet_id: String,
+    /// Chain the wallet is on.
+    pub chain: String,
+    /// Wallet address.
+    pub address: String,
+    /// Assets whose balances differ.
+    pub discrepancies: Vec<BalanceDiscrepancy>,
+    /// Whether every asset matched.
+    pub reconciled: bool,
+    /// Why the on-chain balances couldn't be read, if they couldn't.
+    pub error: Option<String>,
+}
+
+// ============================================================================
+// Replay
+// ============================================================================
+
+/// Converts a raw amount to whole units.
+fn whole_units(raw: &str, decimals: Option<i64>) -> Option<Decimal> {
+    let decimals = u8::try_from(decimals?).ok()?;
+    from_smallest_units(parse_decimal(raw).ok()?, decimals)
+}
+
+/// Running balances, keyed so assets known by symbol sort before tokens.
+#[derive(Default)]
+struct Ledger {
+    balances: BTreeMap<(u8, String), AssetBalance>,
+}
+
+impl Ledger {
+    fn add(&mut self, symbol: &str, token_address: Option<&str>, amount: Decimal) {
+        let key = match token_address {
+            None if symbol.is_empty() => return,
+            None => (0, symbol.to_uppercase()),
+            Some(address) => (1, address.to_lowercase()),
+        };
+        self.balances
+            .entry(key)
+            .or_insert_with(|| AssetBalance {
+                symbol: symbol.to_string(),
+                token_address: token_address.map(str::to_lowercase),
+                balance: Decimal::ZERO,
+            })
+            .balance += amount;
+    }
+}
+
+/// Rebuilds the balances of `address` as of `at` from its transactions
+/// and their token transfers.
+///
+/// Failed transactions only cost their fee. A transaction with no stored
+/// transfers but a token symbol other than the native currency's, such as
+/// imported venue activity, moves that token by its value.
+fn replay(
+    address: &str,
+    chain: &str,
+    transactions: &[StoredTransaction],
+    transfers: &HashMap<String, Vec<TokenTransfer>>,
+    at: DateTime<Utc>,
+) -> (Vec<AssetBalance>, usize, usize) {
+    let (native_symbol, native_decimals) = native_currency(chain);
+    let is_wallet = |other: Option<&str>| other.is_some_and(|a| a.eq_ignore_ascii_case(address));
+
+    let mut ledger = Ledger::default();
+    let (mut replayed, mut skipped) = (0, 0);
+
+    for tx in transactions {
+        match tx.timestamp {
+            None => {
+                skipped += 1;
+                continue;
+            }
+            Some(timestamp) if timestamp > at => continue,
+            Some(_) => {}
+        }
+        let outgoing = is_wallet(tx.from_address.as_deref());
+        let incoming = is_wallet(tx.to_address.as_deref());
+        let failed = tx.status.as_deref() == Some("failed");
+        let tx_transfers = transfers.get(&tx.hash).map(Vec::as_slice).unwrap_or(&[]);
+
+        // The value is native unless the row is a bare token movement.
+        let (value_symbol, value_decimals) = match tx.token_symbol.as_deref() {
+            Some(symbol)
+                if tx_transfers.is_empty() && !symbol.eq_ignore_ascii_case(&native_symbol) =>
+            {
+                (symbol, tx.token_decimals.map(i64::from))
+            }
+            _ => (native_symbol.as_str(), Some(i64::from(native_decimals))),
+        };
+        let value = match tx.value.as_deref() {
+            None | Some("") => Some(Decimal::ZERO),
+            Some(raw) => whole_units(raw, value_decimals),
+        };
+        let fee = match tx.fee.as_deref() {
+            None | Some("") => Some(Decimal::ZERO),
+            Some(raw) => whole_units(raw, Some(i64::from(native_decimals))),
+        };
+        let (Some(value), Some(fee)) = (value, fee) else {
+            skipped += 1;
+            continue;
+        };
+        replayed += 1;
+
+        if outgoing {
+            ledger.add(&native_symbol, None, -fee);
+        }
+        if failed {
+            continue;
+        }
+        let net_value = match (outgoing, incoming) {
+            (true, false) => -value,
+            (false, true) => value,
+            _ => Decimal::ZERO,
+        };
+        ledger.add(value_symbol, None, net_value);
+
+        for transfer in tx_transfers {
+            // Non-fungible transfers carry a token ID rather than an amount
+            if transfer.token_decimals == Some(0) {
+                continue;
+            }
+            let Some(amount) = whole_units(&transfer.value, transfer.token_decimals.map(i64::from))
+            else {
+                continue;
+            };
+            let symbol = transfer
+                .token_symbol
+                .as_deref()
+                .unwrap_or(&transfer.token_address);
+            if is_wallet(Some(transfer.from.as_str())) {
+                ledger.add(symbol, Some(&transfer.token_address), -amount);
+            }
+            if is_wallet(Some(transfer.to.as_str())) {
+                ledger.add(symbol, Some(&transfer.token_address), amount);
+            }
+        }
+    }
+
+    let balances = ledger
+        .balances
+        .into_values()
+        .filter(|b| !b.balance.is_zero())
+        .collect();
+    (balances, replayed, skipped)
+}
+
+/// Rebuilds one wallet's balances as of `at`.
+pub(crate) async fn wallet_balances_at(
+    pool: &SqlitePool,
+    wallet: &Wallet,
+    at: DateTime<Utc>,
+) -> Result<HistoricalBalances, String> {
+    let transactions = sqlx::query_as::<_, StoredTransaction>(
+        "SELECT * FROM transactions WHERE wallet_id = ? ORDER BY timestamp ASC, block_number ASC",
+    )
+    .bind(&wallet.id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let transfers = load_wallet_token_transfers(pool, &wallet.id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let (balances, replayed, skipped) = replay(
+        &wallet.address,
+        &wallet.chain,
+        &transactions,
+        &transfers,
+        at,
+    );
+    Ok(HistoricalBalances {
+        wallet_id: wallet.id.clone(),
+        chain: wallet.chain.clone(),
+        address: wallet.address.clone(),
+        at,
+        balances,
+        replayed,
+        skipped,
+    })
+}
+
+/// On-chain balances in the same form as rebuilt ones.
+fn on_chain_balances(balances: &WalletBalances) -> Vec<AssetBalance> {
+    let native = &balances.native_balance;
+    let mut assets: Vec<AssetBalance> =
+        whole_units(&native.balance, Some(i64::from(native.decimals)))
+            .map(|balance| AssetBalance {
+                symbol: native.symbol.clone(),
+                token_address: None,
+                balance,
+            })
+            .into_iter()
+            .collect();
+    assets.extend(balances.token_balances.iter().filter_map(|t| {
+        Some(AssetBalance {
+            symbol: t
+                .token_symbol
+                .clone()
+                .unwrap_or_else(|| t.token_address.clone()),
+            token_address: Some(t.token_address.to_lowercase()),
+            balance: whole_units(&t.balance, Some(i64::from(t.token_decimals)))?,
+        })
+    }));
+    assets
+}
+
+/// Assets whose rebuilt and on-chain balances differ. Native balances are
+/// matched by symbol, tokens by contract.
+pub(crate) fn find_discrepancies(
+    reconstructed: &[AssetBalance],
+    on_chain: &[AssetBalance],
+) -> Vec<BalanceDiscrepancy> {
+    let key = |b: &AssetBalance| match &b.token_address {
+        Some(address) => (1, address.to_lowercase()),
+        None => (0, b.symbol.to_uppercase()),
+    };
+    let mut pairs: BTreeMap<(u8, String), (Option<&AssetBalance>, Option<&AssetBalance>)> =
+        BTreeMap::new();
+    for balance in reconstructed {
+        pairs.entry(key(balance)).or_default().0 = Some(balance);
+    }
+    for balance in on_chain {
+        pairs.entry(key(balance)).or_default().1 = Some(balance);
+    }
+
+    pairs
+        .into_values()
+        .filter_map(|(rebuilt, chain)| {
+            let reconstructed = rebuilt.map(|b| b.balance).unwrap_or_default();
+            let on_chain = chain.map(|b| b.balance).unwrap_or_default();
+            let difference = on_chain - reconstructed;
+            let named = chain.or(rebuilt)?;
+            (!difference.is_zero()).then(|| BalanceDiscrepancy {
+                symbol: named.symbol.clone(),
+                token_address: named.token_address.clone(),
+                reconstructed,
+                on_chain,
+                difference,
+            })
+        })
+        .collect()
+}
+
+/// Compares each of a profile's wallets' rebuilt balances with the chain.
+pub(crate) async fn reconcile_profile_balances(
+    pool: &SqlitePool,
+    chains: &ChainManagerState,
+    profile_id: &str,
+) -> Result<Vec<BalanceReconciliation>, String> {
+    let wallets = profile_wallets(pool, profile_id).await?;
+    let filter = SpamFilter::load(pool, Some(profile_id))
+        .await
+        .map_err(|e| e.to_string())?;
+    let now = Utc::now();
+    let manager = chains.read().await;
+
+    let mut reconciliations = Vec::with_capacity(wallets.len());
+    for wallet in &wallets {
+        let rebuilt = wallet_balances_at(pool, wallet, now).await?;
+        let (discrepancies, error) =
+            match manager.get_balances(&wallet.chain, &wallet.address).await {
+                Ok(mut balances) => {
+                    filter.filter_balances(&mut balances);
+                    (
+                        find_discrepancies(&rebuilt.balances, &on_chain_balances(&balances)),
+                        None,
+                    )
+                }
+                Err(e) => (Vec::new(), Some(e.to_string())),
+            };
+        reconciliations.push(BalanceReconciliation {
+            wallet_id: wallet.id.clone(),
+            chain: wallet.chain.clone(),
+            address: wallet.address.clone(),
+            reconciled: error.is_none() && discrepancies.is_empty(),
+            discrepancies,
+            error,
+        });
+    }
+    Ok(reconciliations)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns the balances each of a profile's wallets held at `at`, rebuilt
+/// from stored transactions.
+#[tauri::command]
+pub async fn get_historical_balances(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    at: DateTime<Utc>,
+) -> Result<Vec<HistoricalBalances>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+
+    let wallets = profile_wallets(&state.pool, &profile_id).await?;
+    let mut history = Vec::with_capacity(wallets.len());
+    for wallet in &wallets {
+        history.push(wallet_balances_at(&state.pool, wallet, at).await?);
+    }
+    Ok(history)
+}
+
+/// Compares each of a profile's wallets' balances rebuilt from stored
+/// transactions with its current balances on chain. A difference means
+/// history is missing or was recorded wrong.
+#[tauri::command]
+pub async fn reconcile_wallet_balances(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    chains: State<'_, ChainManagerState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<BalanceReconciliation>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    reconcile_profile_balances(&state.pool, &chains, &profile_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::str::FromStr;
+
+    const WALLET: &str = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045";
+    const OTHER: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 12, d, 12, 0, 0).unwrap()
+    }
+
+    fn tx(hash: &str, d: u32, from: &str, to: &str, value: &str, fee: &str) -> StoredTransaction {
+        StoredTransaction {
+            id: hash.to_string(),
+            wallet_id: "w1".to_string(),
+            hash: hash.to_string(),
+            block_number: Some(i64::from(d)),
+            timestamp: Some(day(d)),
+            from_address: Some(from.to_string()),
+            to_address: Some(to.to_string()),
+            value: Some(value.to_string()),
+            fee: Some(fee.to_string()),
+            status: Some("success".to_string()),
+            tx_type: Some("transfer".to_string()),
+            token_symbol: Some("ETH".to_string()),
+            token_decimals: Some(18),
+            chain: "ethereum".to_string(),
+            raw_data: None,
+            created_at: day(d),
+        }
+    }
+
+    fn usdc(from: &str, to: &str, value: &str) -> TokenTransfer {
+        TokenTransfer {
+            token_address: USDC.to_string(),
+            token_symbol: Some("USDC".to_string()),
+            token_decimals: Some(6),
+            from: from.to_string(),
+            to: to.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_replay_native_tokens_and_fees() {
+        let mut failed = tx("0x04", 20, WALLET, OTHER, "500000000000000000", "1000");
+        failed.status = Some("failed".to_string());
+        let mut undated = tx("0x05", 21, OTHER, WALLET, "1", "0");
+        undated.timestamp = None;
+        let transactions = vec![
+            tx(
+                "0x01",
+                1,
+                OTHER,
+                &WALLET.to_uppercase(),
+                "2000000000000000000",
+                "0",
+            ),
+            tx(
+                "0x02",
+                10,
+                WALLET,
+                OTHER,
+                "250000000000000000",
+                "50000000000000000",
+            ),
+            tx("0x03", 15, OTHER, WALLET, "0", "0"),
+            failed,
+            undated,
+            tx("0x06", 31, WALLET, OTHER, "1000000000000000000", "0"),
+        ];
+        let transfers = HashMap::from([(
+            "0x03".to_string(),
+            vec![
+                usdc(OTHER, WALLET, "1500000000"),
+                usdc(WALLET, OTHER, "250000000"),
+            ],
+        )]);
+
+        let (balances, replayed, skipped) =
+            replay(WALLET, "ethereum", &transactions, &transfers, day(30));
+        assert_eq!(replayed, 4);
+        assert_eq!(skipped, 1);
+        assert_eq!(
+            balances,
+            vec![
+                AssetBalance {
+                    symbol: "ETH".to_string(),
+                    token_address: None,
+                    balance: dec("1.699999999999999"),
+                },
+                AssetBalance {
+                    symbol: "USDC".to_string(),
+                    token_address: Some(USDC.to_string()),
+                    balance: dec("1250"),
+                },
+            ]
+        );
+
+        let (before_swap, _, _) = replay(WALLET, "ethereum", &transactions, &transfers, day(12));
+        assert_eq!(before_swap.len(), 1);
+        assert_eq!(before_swap[0].balance, dec("1.7"));
+    }
+
+    #[test]
+    fn test_find_discrepancies() {
+        let balance = |symbol: &str, token: Option<&str>, value: &str| AssetBalance {
+            symbol: symbol.to_string(),
+            token_address: token.map(str::to_string),
+            balance: dec(value),
+        };
+        let rebuilt = vec![
+            balance("ETH", None, "1.7"),
+            balance("USDC", Some(USDC), "1250"),
+            balance("DAI", Some("0x6b17"), "10"),
+        ];
+        let on_chain = vec![
+            balance("eth", None, "1.7"),
+            balance("USDC", Some(&USDC.to_uppercase()), "1300"),
+            balance("UNI", Some("0x1f98"), "5"),
+        ];
+
+        let discrepancies = find_discrepancies(&rebuilt, &on_chain);
+        let diffs: Vec<(&str, Decimal)> = discrepancies
+            .iter()
+            .map(|d| (d.symbol.as_str(), d.difference))
+            .collect();
+        assert_eq!(
+            diffs,
+            vec![("UNI", dec("5")), ("DAI", dec("-10")), ("USDC", dec("50"))]
+        );
+    }
+}
@@ -0,0 +1,847 @@
+//! Budgets for grant and program tracking.
+//!
+//! Budgets are defined per category and/or entity over a date range. Actuals
+//! come from transaction tags, which allocate an amount of a transaction to a
+//! category (and optionally an entity). `get_budget_report` rolls both up for
+//! a reporting period and computes variance and remaining balances.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::audit_trail::{record_change, RecordType};
+use super::period_close::ensure_period_open;
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::{authenticate, authorize_profile, authorize_record};
+use crate::core::auth_state::AuthState;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A budget for a category, entity, or both over a date range.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Budget {
+    /// Unique identifier for the budget.
+    pub id: String,
+    /// Profile that owns this budget.
+    pub profile_id: String,
+    /// Display name (e.g. grant or program name).
+    pub name: String,
+    /// Category the budget applies to, if any.
+    pub category: Option<String>,
+    /// Entity the budget applies to, if any.
+    pub entity_id: Option<String>,
+    /// First day of the budget period (inclusive).
+    pub period_start: NaiveDate,
+    /// Last day of the budget period (inclusive).
+    pub period_end: NaiveDate,
+    /// Budgeted amount as a decimal string.
+    pub amount: String,
+    /// Currency of the budgeted amount.
+    pub currency: String,
+    /// Optional notes.
+    pub notes: Option<String>,
+    /// Timestamp when the budget was created.
+    pub created_at: Option<DateTime<Utc>>,
+    /// Timestamp when the budget was last updated.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Input for creating or replacing a budget.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetInput {
+    /// Profile that owns this budget.
+    pub profile_id: String,
+    /// Display name.
+    pub name: String,
+    /// Category the budget applies to.
+    pub category: Option<String>,
+    /// Entity the budget applies to.
+    pub entity_id: Option<String>,
+    /// First day of the budget period (`YYYY-MM-DD`).
+    pub period_start: String,
+    /// Last day of the budget period (`YYYY-MM-DD`).
+    pub period_end: String,
+    /// Budgeted amount as a decimal string.
+    pub amount: String,
+    /// Currency code, defaults to USD.
+    pub currency: Option<String>,
+    /// Optional notes.
+    pub notes: Option<String>,
+}
+
+/// An allocation of (part of) a transaction to a budget category.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TransactionTag {
+    /// Unique identifier for the tag.
+    pub id: String,
+    /// Profile that owns this tag.
+    pub profile_id: String,
+    /// The tagged transaction.
+    pub transaction_id: String,
+    /// Category the amount is allocated to.
+    pub category: String,
+    /// Entity the amount is attributed to, if any.
+    pub entity_id: Option<String>,
+    /// Allocated amount as a decimal string, in the budget currency.
+    pub amount: String,
+    /// When the underlying transaction occurred.
+    pub occurred_at: DateTime<Utc>,
+    /// Timestamp when the tag was created.
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Input for tagging a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionTagInput {
+    /// Profile that owns the transaction.
+    pub profile_id: String,
+    /// The transaction to tag.
+    pub transaction_id: String,
+    /// Category to allocate to.
+    pub category: String,
+    /// Optional entity to attribute to.
+    pub entity_id: Option<String>,
+    /// Allocated amount as a decimal string.
+    pub amount: String,
+    /// When the transaction occurred (RFC 3339).
+    pub occurred_at: String,
+}
+
+/// One budget line in a budget report.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BudgetReportLine {
+    /// Budget identifier.
+    pub budget_id: String,
+    /// Budget name.
+    pub name: String,
+    /// Budget category, if any.
+    pub category: Option<String>,
+    /// Budget entity, if any.
+    pub entity_id: Option<String>,
+    /// Budget currency.
+    pub currency: String,
+    /// Budgeted amount.
+    pub budgeted: String,
+    /// Sum of matching tagged amounts within the report period.
+    pub actual: String,
+    /// Actual minus budgeted (positive means over budget).
+    pub variance: String,
+    /// Variance as a percentage of the budgeted amount, if budgeted is non-zero.
+    pub variance_percent: Option<String>,
+    /// Budgeted minus actual, floored at zero.
+    pub remaining: String,
+    /// Whether actuals exceed the budget.
+    pub over_budget: bool,
+}
+
+/// Budget vs. actuals for a profile over a reporting period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetReport {
+    /// Profile the report is for.
+    pub profile_id: String,
+    /// The requested period label.
+    pub period: String,
+    /// First day of the period (inclusive).
+    pub period_start: NaiveDate,
+    /// Last day of the period (inclusive).
+    pub period_end: NaiveDate,
+    /// One line per budget overlapping the period.
+    pub lines: Vec<BudgetReportLine>,
+}
+
+// ============================================================================
+// Calculation
+// ============================================================================
+
+/// Resolves a period label to an inclusive date range.
+///
+/// Accepts `YYYY`, `YYYY-Qn`, `YYYY-MM`, or an explicit
+/// `YYYY-MM-DD..YYYY-MM-DD` range.
+pub fn parse_period(period: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let period = period.trim();
+    let invalid = || format!("Invalid period: {}", period);
+
+    if let Some((start, end)) = period.split_once("..") {
+        let start = NaiveDate::parse_from_str(start, "%Y-%m-%d").map_err(|_| invalid())?;
+        let end = NaiveDate::parse_from_str(end, "%Y-%m-%d").map_err(|_| invalid())?;
+        if end < start {
+            return Err(invalid());
+        }
+        return Ok((start, end));
+    }
+
+    let (year, rest) = match period.split_once('-') {
+        Some((y, r)) => (y, Some(r)),
+        None => (period, None),
+    };
+    let year: i32 = year.parse().map_err(|_| invalid())?;
+
+    let (start_month, months) = match rest {
+        None => (1, 12),
+        Some(q) if q.starts_with('Q') || q.starts_with('q') => {
+            let quarter: u32 = q[1..].parse().map_err(|_| invalid())?;
+            if !(1..=4).contains(&quarter) {
+                return Err(invalid());
+            }
+            ((quarter - 1) * 3 + 1, 3)
+        }
+        Some(m) => {
+            let month: u32 = m.parse().map_err(|_| invalid())?;
+            if !(1..=12).contains(&month) {
+                return Err(invalid());
+            }
+            (month, 1)
+        }
+    };
+
+    let start = NaiveDate::from_ymd_opt(year, start_month, 1).ok_or_else(invalid)?;
+    let end_month = start_month + months;
+    let next = if end_month > 12 {
+        NaiveDate::from_ymd_opt(year + 1, end_month - 12, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, end_month, 1)
+    }
+    .ok_or_else(invalid)?;
+    let end = next.pred_opt().ok_or_else(invalid)?;
+
+    Ok((start, end))
+}
+
+/// Whether a tag counts towards a budget's actuals.
+///
+/// A budget with a category matches tags in that category (case-insensitive);
+/// a budget with an entity matches tags for that entity. When both are set,
+/// both must match.
+pub fn tag_matches_budget(budget: &Budget, tag: &TransactionTag) -> bool {
+    if let Some(category) = &budget.category {
+        if !category.eq_ignore_ascii_case(&tag.category) {
+            return false;
+        }
+    }
+    if let Some(entity_id) = &budget.entity_id {
+        if tag.entity_id.as_deref() != Some(entity_id.as_str()) {
+            return false;
+        }
+    }
+    budget.category.is_some() || budget.entity_id.is_some()
+}
+
+/// Builds a report line for one budget from the tags that fall in the
+/// intersection of the budget period and the report period.
+pub fn build_report_line(
+    budget: &Budget,
+    tags: &[TransactionTag],
+    period_start: NaiveDate,
+    period_end: NaiveDate,
+) -> BudgetReportLine {
+    let window_start = budget.period_start.max(period_start);
+    let window_end = budget.period_end.min(period_end);
+
+    let budgeted = Decimal::from_str(&budget.amount).unwrap_or(Decimal::ZERO);
+    let actual: Decimal = tags
+        .iter()
+        .filter(|t| {
+            let day = t.occurred_at.date_naive();
+            day >= window_start && day <= window_end && tag_matches_budget(budget, t)
+        })
+        .filter_map(|t| Decimal::from_str(&t.amount).ok())
+        .sum();
+
+    let variance = actual - budgeted;
+    let variance_percent = if budgeted.is_zero() {
+        None
+    } else {
+        Some(
+            (variance / budgeted * Decimal::ONE_HUNDRED)
+                .round_dp(2)
+                .to_string(),
+        )
+    };
+    let remaining = (budgeted - actual).max(Decimal::ZERO);
+
+    BudgetReportLine {
+        budget_id: budget.id.clone(),
+        name: budget.name.clone(),
+        category: budget.category.clone(),
+        entity_id: budget.entity_id.clone(),
+        currency: budget.currency.clone(),
+        budgeted: budgeted.to_string(),
+        actual: actual.to_string(),
+        variance: variance.to_string(),
+        variance_percent,
+        remaining: remaining.to_string(),
+        over_budget: actual > budgeted,
+    }
+}
+
+fn validate_budget_input(input: &BudgetInput) -> Result<(NaiveDate, NaiveDate), String> {
+    if input.category.is_none() && input.entity_id.is_none() {
+        return Err("A budget needs a category, an entity, or both".to_string());
+    }
+    Decimal::from_str(&input.amount).map_err(|_| format!("Invalid amount: {}", input.amount))?;
+    let start = NaiveDate::parse_from_str(&input.period_start, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid period start: {}", input.period_start))?;
+    let end = NaiveDate::parse_from_str(&input.period_end, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid period end: {}", input.period_end))?;
+    if end < start {
+        return Err("Budget period end is before its start".to_string());
+    }
+    Ok((start, end))
+}
+
+async fn get_budget_by_id(pool: &SqlitePool, id: &str) -> Result<Budget, String> {
+    sqlx::query_as::<_, Budget>("SELECT * FROM budgets WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Budget not found".to_string())
+}
+
+/// Verifies `token` and loads budget `id`, checking the caller's role on the
+/// budget's profile grants `permission`.
+async fn authorize_budget(
+    pool: &SqlitePool,
+    auth: &AuthState,
+    token: &str,
+    id: &str,
+    permission: Permission,
+) -> Result<Budget, String> {
+    let user_id = authenticate(auth, token)?;
+    let budget = get_budget_by_id(pool, id).await?;
+    authorize_record(
+        pool,
+        &user_id,
+        &budget.profile_id,
+        permission,
+        "Budget not found",
+    )
+    .await?;
+    Ok(budget)
+}
+
+// ============================================================================
+// Budget Commands
+// ============================================================================
+
+/// Creates a budget and returns it.
+#[tauri::command]
+pub async fn create_budget(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    input: BudgetInput,
+) -> Result<Budget, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &input.profile_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    let (start, end) = validate_budget_input(&input)?;
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now();
+
+    sqlx::query(
+        r#"
+        INSERT INTO budgets (
+            id, profile_id, name, category, entity_id, period_start, period_end,
+            amount, currency, notes, created_at, updated_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(&input.profile_id)
+    .bind(&input.name)
+    .bind(&input.category)
+    .bind(&input.entity_id)
+    .bind(start)
+    .bind(end)
+    .bind(&input.amount)
+    .bind(input.currency.as_deref().unwrap_or("USD"))
+    .bind(&input.notes)
+    .bind(now)
+    .bind(now)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    get_budget_by_id(&state.pool, &id).await
+}
+
+/// Lists all budgets for a profile, ordered by period start.
+#[tauri::command]
+pub async fn get_budgets(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<Budget>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as::<_, Budget>(
+        "SELECT * FROM budgets WHERE profile_id = ? ORDER BY period_start ASC, name ASC",
+    )
+    .bind(&profile_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Replaces a budget's definition and returns the updated budget.
+#[tauri::command]
+pub async fn update_budget(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    id: String,
+    input: BudgetInput,
+) -> Result<Budget, String> {
+    authorize_budget(
+        &state.pool,
+        &auth,
+        &token,
+        &id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    let (start, end) = validate_budget_input(&input)?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE budgets SET
+            name = ?, category = ?, entity_id = ?, period_start = ?, period_end = ?,
+            amount = ?, currency = ?, notes = ?, updated_at = ?
+        WHERE id = ? AND profile_id = ?
+        "#,
+    )
+    .bind(&input.name)
+    .bind(&input.category)
+    .bind(&input.entity_id)
+    .bind(start)
+    .bind(end)
+    .bind(&input.amount)
+    .bind(input.currency.as_deref().unwrap_or("USD"))
+    .bind(&input.notes)
+    .bind(Utc::now())
+    .bind(&id)
+    .bind(&input.profile_id)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if result.rows_affected() == 0 {
+        return Err("Budget not found".to_string());
+    }
+
+    get_budget_by_id(&state.pool, &id).await
+}
+
+/// Deletes a budget.
+#[tauri::command]
+pub async fn delete_budget(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    id: String,
+) -> Result<(), String> {
+    authorize_budget(
+        &state.pool,
+        &auth,
+        &token,
+        &id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    sqlx::query("DELETE FROM budgets WHERE id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+// ============================================================================
+// Tag Commands
+// ============================================================================
+
+/// Tags a transaction with a budget category, replacing any existing tag the
+/// profile has for the same transaction and category.
+#[tauri::command]
+pub async fn tag_transaction(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    input: TransactionTagInput,
+) -> Result<TransactionTag, String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &input.profile_id,
+        Permission::EditTags,
+    )
+    .await?;
+    Decimal::from_str(&input.amount).map_err(|_| format!("Invalid amount: {}", input.amount))?;
+    let occurred_at = DateTime::parse_from_rfc3339(&input.occurred_at)
+        .map_err(|_| format!("Invalid timestamp: {}", input.occurred_at))?
+        .with_timezone(&Utc);
+    ensure_period_open(
+        &state.pool,
+        Some(&input.profile_id),
+        occurred_at.date_naive(),
+    )
+    .await?;
+
+    // Re-tagging also changes the existing tag, which may sit in a closed period.
+    let existing = sqlx::query_as::<_, TransactionTag>(
+        "SELECT * FROM transaction_tags WHERE profile_id = ? AND transaction_id = ? AND category = ?",
+    )
+    .bind(&input.profile_id)
+    .bind(&input.transaction_id)
+    .bind(&input.category)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    if let Some(tag) = &existing {
+        ensure_period_open(
+            &state.pool,
+            Some(&tag.profile_id),
+            tag.occurred_at.date_naive(),
+        )
+        .await?;
+    }
+
+    let id = Uuid::new_v4().to_string();
+
+    sqlx::query(
+        r#"
+        INSERT INTO transaction_tags (
+            id, profile_id, transaction_id, category, entity_id, amount, occurred_at, created_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(profile_id, transaction_id, category) DO UPDATE SET
+            entity_id = excluded.entity_id,
+            amount = excluded.amount,
+            occurred_at = excluded.occurred_at
+        "#,
+    )
+    .bind(&id)
+    .bind(&input.profile_id)
+    .bind(&input.transaction_id)
+    .bind(&input.category)
+    .bind(&input.entity_id)
+    .bind(&input.amount)
+    .bind(occurred_at)
+    .bind(Utc::now())
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let tag = sqlx::query_as::<_, TransactionTag>(
+        "SELECT * FROM transaction_tags WHERE profile_id = ? AND transaction_id = ? AND category = ?",
+    )
+    .bind(&input.profile_id)
+    .bind(&input.transaction_id)
+    .bind(&input.category)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    record_change(
+        &state.pool,
+        Some(&user_id),
+        RecordType::TransactionTag,
+        &tag.id,
+        Some(&tag.profile_id),
+        existing.as_ref(),
+        Some(&tag),
+    )
+    .await?;
+
+    Ok(tag)
+}
+
+/// Removes a transaction tag.
+#[tauri::command]
+pub async fn untag_transaction(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    id: String,
+) -> Result<(), String> {
+    let user_id = authenticate(&auth, &token)?;
+    let not_found = "Transaction tag not found";
+    let tag = sqlx::query_as::<_, TransactionTag>("SELECT * FROM transaction_tags WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| not_found.to_string())?;
+    authorize_record(
+        &state.pool,
+        &user_id,
+        &tag.profile_id,
+        Permission::EditTags,
+        not_found,
+    )
+    .await?;
+    ensure_period_open(
+        &state.pool,
+        Some(&tag.profile_id),
+        tag.occurred_at.date_naive(),
+    )
+    .await?;
+
+    sqlx::query("DELETE FROM transaction_tags WHERE id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    record_change(
+        &state.pool,
+        Some(&user_id),
+        RecordType::TransactionTag,
+        &tag.id,
+        Some(&tag.profile_id),
+        Some(&tag),
+        None,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Lists a profile's tags on a transaction.
+#[tauri::command]
+pub async fn get_transaction_tags(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    transaction_id: String,
+) -> Result<Vec<TransactionTag>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as::<_, TransactionTag>(
+        "SELECT * FROM transaction_tags WHERE profile_id = ? AND transaction_id = ? ORDER BY category ASC",
+    )
+    .bind(&profile_id)
+    .bind(&transaction_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Report Commands
+// ============================================================================
+
+/// Returns budget vs. actuals for every budget overlapping `period`.
+///
+/// # Arguments
+/// * `profile_id` - The profile to report on.
+/// * `period` - `YYYY`, `YYYY-Qn`, `YYYY-MM`, or `YYYY-MM-DD..YYYY-MM-DD`.
+#[tauri::command]
+pub async fn get_budget_report(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    period: String,
+) -> Result<BudgetReport, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let (period_start, period_end) = parse_period(&period)?;
+
+    let budgets = sqlx::query_as::<_, Budget>(
+        r#"
+        SELECT * FROM budgets
+        WHERE profile_id = ? AND period_start <= ? AND period_end >= ?
+        ORDER BY period_start ASC, name ASC
+        "#,
+    )
+    .bind(&profile_id)
+    .bind(period_end)
+    .bind(period_start)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Widen by a day on each side so timezone-suffixed timestamps on the
+    // boundary dates are not lost to string comparison; the exact window is
+    // applied per budget in build_report_line.
+    let from = (period_start - chrono::Duration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .map(|t| t.and_utc());
+    let to = (period_end + chrono::Duration::days(1))
+        .and_hms_opt(23, 59, 59)
+        .map(|t| t.and_utc());
+
+    let tags = sqlx::query_as::<_, TransactionTag>(
+        r#"
+        SELECT * FROM transaction_tags
+        WHERE profile_id = ? AND occurred_at >= ? AND occurred_at <= ?
+        "#,
+    )
+    .bind(&profile_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let lines = budgets
+        .iter()
+        .map(|b| build_report_line(b, &tags, period_start, period_end))
+        .collect();
+
+    Ok(BudgetReport {
+        profile_id,
+        period,
+        period_start,
+        period_end,
+        lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
+    }
+
+    fn budget(category: Option<&str>, entity_id: Option<&str>, amount: &str) -> Budget {
+        Budget {
+            id: "b1".to_string(),
+            profile_id: "p1".to_string(),
+            name: "Grant A".to_string(),
+            category: category.map(String::from),
+            entity_id: entity_id.map(String::from),
+            period_start: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            period_end: NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+            amount: amount.to_string(),
+            currency: "USD".to_string(),
+            notes: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn tag(category: &str, entity_id: Option<&str>, amount: &str, month: u32) -> TransactionTag {
+        TransactionTag {
+            id: format!("t-{}-{}", category, month),
+            profile_id: "p1".to_string(),
+            transaction_id: format!("tx-{}", month),
+            category: category.to_string(),
+            entity_id: entity_id.map(String::from),
+            amount: amount.to_string(),
+            occurred_at: Utc.with_ymd_and_hms(2025, month, 15, 0, 0, 0).unwrap(),
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_period() {
+        let d = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            parse_period("2025").unwrap(),
+            (d(2025, 1, 1), d(2025, 12, 31))
+        );
+        assert_eq!(
+            parse_period("2025-Q4").unwrap(),
+            (d(2025, 10, 1), d(2025, 12, 31))
+        );
+        assert_eq!(
+            parse_period("2024-02").unwrap(),
+            (d(2024, 2, 1), d(2024, 2, 29))
+        );
+        assert_eq!(
+            parse_period("2025-03-10..2025-04-10").unwrap(),
+            (d(2025, 3, 10), d(2025, 4, 10))
+        );
+        assert!(parse_period("2025-Q5").is_err());
+        assert!(parse_period("2025-13").is_err());
+        assert!(parse_period("soon").is_err());
+    }
+
+    #[test]
+    fn test_report_line_variance_and_remaining() {
+        let b = budget(Some("Programs"), None, "1000");
+        let tags = vec![
+            tag("programs", None, "250.50", 2),
+            tag("Programs", None, "149.50", 5),
+            tag("Operations", None, "999", 5),
+        ];
+        let (start, end) = parse_period("2025").unwrap();
+        let line = build_report_line(&b, &tags, start, end);
+
+        assert_eq!(dec(&line.actual), dec("400"));
+        assert_eq!(dec(&line.variance), dec("-600"));
+        assert_eq!(dec(&line.remaining), dec("600"));
+        assert_eq!(dec(line.variance_percent.as_deref().unwrap()), dec("-60"));
+        assert!(!line.over_budget);
+    }
+
+    #[test]
+    fn test_report_line_respects_period_and_entity() {
+        let b = budget(Some("Programs"), Some("e1"), "100");
+        let tags = vec![
+            tag("Programs", Some("e1"), "80", 1),
+            tag("Programs", Some("e1"), "50", 2),
+            tag("Programs", Some("e2"), "500", 2),
+        ];
+        let (start, end) = parse_period("2025-02").unwrap();
+        let line = build_report_line(&b, &tags, start, end);
+
+        assert_eq!(dec(&line.actual), dec("50"));
+        assert_eq!(dec(&line.remaining), dec("50"));
+
+        let (start, end) = parse_period("2025-Q1").unwrap();
+        let line = build_report_line(&b, &tags, start, end);
+        assert_eq!(dec(&line.actual), dec("130"));
+        assert_eq!(dec(&line.remaining), Decimal::ZERO);
+        assert!(line.over_budget);
+    }
+
+    #[test]
+    fn test_entity_only_budget_matches_any_category() {
+        let b = budget(None, Some("e1"), "100");
+        assert!(tag_matches_budget(&b, &tag("Travel", Some("e1"), "1", 1)));
+        assert!(!tag_matches_budget(&b, &tag("Travel", None, "1", 1)));
+    }
+}
@@ -0,0 +1,712 @@
+//! Portable configuration bundles.
+//!
+//! A config bundle carries a profile's setup — preferences, tax settings, the
+//! chart of accounts, token spam and allow lists, and auto-tagging rules —
+//! without any financial data, so a setup can be replicated on another
+//! machine without restoring a full backup. API keys and the SMTP password are
+//! left out unless a passphrase is given, in which case they travel encrypted
+//! with AES-256-GCM.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::auth::{log_audit_event, verify_profile_access};
+use super::email_settings::load_smtp_provider;
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::authorize_profile;
+use crate::core::auth_helpers::ARGON2_PARAMS_SETTING;
+use crate::core::auth_state::AuthState;
+use crate::core::email::smtp;
+use crate::fetchers::{ApiKeyManager, ApiProvider};
+use crate::storage::encryption::{decrypt, encrypt, EncryptedData};
+
+/// Format identifier written into every bundle.
+const FORMAT_VERSION: &str = "pacioli-config-v1";
+
+/// Settings that describe this installation rather than the user's setup.
+const LOCAL_SETTINGS: &[&str] = &[
+    "app_initialized",
+    "password_set",
+    "password_hash",
+    "recovery_phrase_hash",
+    ARGON2_PARAMS_SETTING,
+];
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A portable bundle of a profile's configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigBundle {
+    /// Bundle format identifier.
+    pub format_version: String,
+    /// When the bundle was exported.
+    pub exported_at: DateTime<Utc>,
+    /// Application settings and preferences.
+    pub settings: Vec<BundleSetting>,
+    /// The profile's tax jurisdiction and cost basis method.
+    pub tax_settings: Option<BundleTaxSettings>,
+    /// Chart of accounts.
+    pub chart_of_accounts: Vec<BundleAccount>,
+    /// Tokens marked as spam or allowed.
+    pub token_marks: Vec<BundleTokenMark>,
+    /// Recurring series that auto-tag new occurrences.
+    pub auto_tag_rules: Vec<BundleAutoTagRule>,
+    /// API keys and passwords, present only when exported with a passphrase.
+    pub secrets: Option<EncryptedSecrets>,
+}
+
+/// One application setting.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSetting {
+    /// Setting key.
+    pub key: String,
+    /// Setting value.
+    pub value: String,
+}
+
+/// Per-profile tax settings.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleTaxSettings {
+    /// Tax jurisdiction code.
+    pub jurisdiction: String,
+    /// Cost basis method.
+    pub cost_basis_method: String,
+}
+
+/// A general ledger account, with its parent referenced by account number.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleAccount {
+    /// Account number.
+    pub account_number: String,
+    /// Account name.
+    pub account_name: String,
+    /// One of: Asset, Liability, Equity, Income, Expense.
+    pub account_type: String,
+    /// Account number of the parent account.
+    pub parent_account_number: Option<String>,
+    /// Digital asset classification.
+    pub digital_asset_type: Option<String>,
+    /// Subcategory.
+    pub subcategory: Option<String>,
+    /// Description.
+    pub description: Option<String>,
+    /// Either debit or credit.
+    pub normal_balance: Option<String>,
+    /// Whether the account is active.
+    pub is_active: bool,
+}
+
+/// A token marked as spam or allowed.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleTokenMark {
+    /// Chain the token lives on.
+    pub chain_id: String,
+    /// Normalized token key.
+    pub token_key: String,
+    /// Token contract address.
+    pub token_address: Option<String>,
+    /// Token symbol.
+    pub token_symbol: Option<String>,
+    /// Either spam or allowed.
+    pub status: String,
+    /// Why the token was marked.
+    pub reason: Option<String>,
+}
+
+/// A recurring series with an auto-tag category, with its wallet referenced
+/// by chain and address.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleAutoTagRule {
+    /// Chain of the wallet the series belongs to.
+    pub wallet_chain: String,
+    /// Address of the wallet the series belongs to.
+    pub wallet_address: String,
+    /// Counterparty address.
+    pub counterparty: String,
+    /// Either incoming or outgoing.
+    pub direction: String,
+    /// Token symbol.
+    pub token_symbol: String,
+    /// Detected cadence.
+    pub cadence: String,
+    /// Days between occurrences.
+    pub interval_days: i64,
+    /// Typical amount.
+    pub typical_amount: String,
+    /// Allowed deviation from the typical amount.
+    pub amount_tolerance: String,
+    /// User label.
+    pub label: Option<String>,
+    /// Budget category applied to new occurrences.
+    pub auto_tag_category: String,
+}
+
+/// Secrets encrypted with a passphrase.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedSecrets {
+    /// Argon2 salt (base64).
+    pub salt: String,
+    /// AES-GCM nonce (base64).
+    pub nonce: String,
+    /// Encrypted secrets (base64).
+    pub ciphertext: String,
+}
+
+/// Secrets carried in a bundle, before encryption.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSecrets {
+    /// API keys by provider name.
+    pub api_keys: BTreeMap<String, String>,
+    /// SMTP password.
+    pub smtp_password: Option<String>,
+}
+
+/// What an import applied.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigImportResult {
+    /// Settings written.
+    pub settings: usize,
+    /// Whether tax settings were applied.
+    pub tax_settings: bool,
+    /// Accounts added; existing account numbers are left unchanged.
+    pub accounts: usize,
+    /// Token marks written.
+    pub token_marks: usize,
+    /// Auto-tag rules written.
+    pub auto_tag_rules: usize,
+    /// Secrets restored to the keychain.
+    pub secrets: usize,
+    /// Items that couldn't be applied, with the reason.
+    pub skipped: Vec<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Whether a setting belongs in a bundle. Installation state and anything
+/// that looks like a credential stays behind.
+pub fn is_portable_setting(key: &str) -> bool {
+    let lower = key.to_lowercase();
+    !LOCAL_SETTINGS.contains(&key)
+        && !["password", "secret", "api_key", "token"]
+            .iter()
+            .any(|word| lower.contains(word))
+}
+
+/// Encrypts secrets with `passphrase`.
+pub fn seal_secrets(secrets: &BundleSecrets, passphrase: &str) -> Result<EncryptedSecrets, String> {
+    let json = serde_json::to_vec(secrets).map_err(|e| e.to_string())?;
+    let encrypted = encrypt(&json, passphrase).map_err(|e| e.to_string())?;
+    Ok(EncryptedSecrets {
+        salt: encrypted.salt,
+        nonce: encrypted.nonce,
+        ciphertext: encrypted.ciphertext,
+    })
+}
+
+/// Decrypts secrets sealed with `passphrase`.
+pub fn open_secrets(sealed: &EncryptedSecrets, passphrase: &str) -> Result<BundleSecrets, String> {
+    let encrypted = EncryptedData {
+        salt: sealed.salt.clone(),
+        nonce: sealed.nonce.clone(),
+        ciphertext: sealed.ciphertext.clone(),
+    };
+    let json = decrypt(&encrypted, passphrase)
+        .map_err(|_| "Wrong passphrase or corrupted secrets".to_string())?;
+    serde_json::from_slice(&json).map_err(|e| e.to_string())
+}
+
+/// Parses a bundle, rejecting other formats.
+pub fn parse_bundle(content: &str) -> Result<ConfigBundle, String> {
+    let bundle: ConfigBundle =
+        serde_json::from_str(content).map_err(|e| format!("Invalid config bundle: {}", e))?;
+    if bundle.format_version != FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported config bundle format: {}",
+            bundle.format_version
+        ));
+    }
+    Ok(bundle)
+}
+
+/// API keys and the SMTP password stored in the keychain.
+fn collect_secrets() -> Result<BundleSecrets, String> {
+    let mut secrets = BundleSecrets::default();
+    for provider in ApiProvider::all() {
+        if let Some(key) = ApiKeyManager::get_api_key(*provider).map_err(|e| e.to_string())? {
+            let name = provider.keychain_key().replace("_api_key", "");
+            secrets.api_keys.insert(name, key);
+        }
+    }
+    secrets.smtp_password = smtp::load_password()?;
+    Ok(secrets)
+}
+
+/// Gathers a profile's configuration.
+async fn build_bundle(pool: &SqlitePool, profile_id: &str) -> Result<ConfigBundle, String> {
+    let settings: Vec<BundleSetting> =
+        sqlx::query_as("SELECT key, value FROM settings ORDER BY key")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    let settings = settings
+        .into_iter()
+        .filter(|setting| is_portable_setting(&setting.key))
+        .collect();
+
+    let tax_settings = sqlx::query_as(
+        "SELECT jurisdiction, cost_basis_method FROM profile_tax_settings WHERE profile_id = ?",
+    )
+    .bind(profile_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let chart_of_accounts = sqlx::query_as(
+        r#"
+        SELECT a.account_number, a.account_name, a.account_type,
+               p.account_number AS parent_account_number, a.digital_asset_type,
+               a.subcategory, a.description, a.normal_balance,
+               COALESCE(a.is_active, 1) AS is_active
+        FROM gl_accounts a
+        LEFT JOIN gl_accounts p ON p.id = a.parent_account_id
+        ORDER BY a.account_number
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let token_marks = sqlx::query_as(
+        r#"
+        SELECT chain_id, token_key, token_address, token_symbol, status, reason
+        FROM token_marks WHERE profile_id = ?
+        ORDER BY chain_id, token_key
+        "#,
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let auto_tag_rules = sqlx::query_as(
+        r#"
+        SELECT w.chain AS wallet_chain, w.address AS wallet_address, s.counterparty,
+               s.direction, s.token_symbol, s.cadence, s.interval_days, s.typical_amount,
+               s.amount_tolerance, s.label, s.auto_tag_category
+        FROM recurring_series s
+        INNER JOIN wallets w ON w.id = s.wallet_id
+        WHERE s.profile_id = ? AND s.auto_tag_category IS NOT NULL
+        ORDER BY w.chain, w.address, s.counterparty
+        "#,
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(ConfigBundle {
+        format_version: FORMAT_VERSION.to_string(),
+        exported_at: Utc::now(),
+        settings,
+        tax_settings,
+        chart_of_accounts,
+        token_marks,
+        auto_tag_rules,
+        secrets: None,
+    })
+}
+
+/// Applies a bundle to a profile. Existing settings, marks, and rules are
+/// overwritten; existing accounts are kept.
+async fn apply_bundle(
+    pool: &SqlitePool,
+    profile_id: &str,
+    bundle: &ConfigBundle,
+) -> Result<ConfigImportResult, String> {
+    let mut result = ConfigImportResult::default();
+    let now = Utc::now();
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+    for setting in &bundle.settings {
+        if !is_portable_setting(&setting.key) {
+            result
+                .skipped
+                .push(format!("setting {}: not portable", setting.key));
+            continue;
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO settings (key, value, updated_at) VALUES (?, ?, ?)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&setting.key)
+        .bind(&setting.value)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+        result.settings += 1;
+    }
+
+    if let Some(tax) = &bundle.tax_settings {
+        sqlx::query(
+            r#"
+            INSERT INTO profile_tax_settings (profile_id, jurisdiction, cost_basis_method, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(profile_id) DO UPDATE SET
+                jurisdiction = excluded.jurisdiction,
+                cost_basis_method = excluded.cost_basis_method,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(profile_id)
+        .bind(&tax.jurisdiction)
+        .bind(&tax.cost_basis_method)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Invalid tax settings: {}", e))?;
+        result.tax_settings = true;
+    }
+
+    // Accounts are inserted in number order; a parent that sorts after its
+    // child is linked in a second pass.
+    for account in &bundle.chart_of_accounts {
+        let inserted = sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO gl_accounts (
+                account_number, account_name, account_type, digital_asset_type,
+                subcategory, description, normal_balance, is_active, is_editable
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, 1)
+            "#,
+        )
+        .bind(&account.account_number)
+        .bind(&account.account_name)
+        .bind(&account.account_type)
+        .bind(&account.digital_asset_type)
+        .bind(&account.subcategory)
+        .bind(&account.description)
+        .bind(&account.normal_balance)
+        .bind(account.is_active)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Invalid account {}: {}", account.account_number, e))?;
+        if inserted.rows_affected() > 0 {
+            result.accounts += 1;
+        }
+    }
+    for account in &bundle.chart_of_accounts {
+        let Some(parent) = &account.parent_account_number else {
+            continue;
+        };
+        sqlx::query(
+            r#"
+            UPDATE gl_accounts
+            SET parent_account_id = (SELECT id FROM gl_accounts WHERE account_number = ?)
+            WHERE account_number = ? AND parent_account_id IS NULL
+            "#,
+        )
+        .bind(parent)
+        .bind(&account.account_number)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    for mark in &bundle.token_marks {
+        sqlx::query(
+            r#"
+            INSERT INTO token_marks (
+                id, profile_id, chain_id, token_key, token_address, token_symbol, status, reason, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(profile_id, chain_id, token_key) DO UPDATE SET
+                status = excluded.status,
+                reason = excluded.reason
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(profile_id)
+        .bind(&mark.chain_id)
+        .bind(&mark.token_key)
+        .bind(&mark.token_address)
+        .bind(&mark.token_symbol)
+        .bind(&mark.status)
+        .bind(&mark.reason)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Invalid token mark {}: {}", mark.token_key, e))?;
+        result.token_marks += 1;
+    }
+
+    for rule in &bundle.auto_tag_rules {
+        let wallet_id: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM wallets WHERE profile_id = ? AND chain = ? AND address = ?",
+        )
+        .bind(profile_id)
+        .bind(&rule.wallet_chain)
+        .bind(&rule.wallet_address)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+        let Some(wallet_id) = wallet_id else {
+            result.skipped.push(format!(
+                "auto-tag rule for {}: wallet {} on {} not in this profile",
+                rule.counterparty, rule.wallet_address, rule.wallet_chain
+            ));
+            continue;
+        };
+
+        // Occurrence statistics are filled in the next time series are
+        // detected for the wallet.
+        sqlx::query(
+            r#"
+            INSERT INTO recurring_series (
+                id, profile_id, wallet_id, counterparty, direction, token_symbol, cadence,
+                interval_days, typical_amount, amount_tolerance, occurrence_count, confidence,
+                first_seen_at, last_seen_at, next_expected_at, label, auto_tag_category,
+                is_active, created_at, updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 0, 0, ?, ?, ?, ?, ?, 1, ?, ?)
+            ON CONFLICT(wallet_id, counterparty, direction, token_symbol) DO UPDATE SET
+                label = excluded.label,
+                auto_tag_category = excluded.auto_tag_category,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(profile_id)
+        .bind(&wallet_id)
+        .bind(&rule.counterparty)
+        .bind(&rule.direction)
+        .bind(&rule.token_symbol)
+        .bind(&rule.cadence)
+        .bind(rule.interval_days)
+        .bind(&rule.typical_amount)
+        .bind(&rule.amount_tolerance)
+        .bind(now)
+        .bind(now)
+        .bind(now)
+        .bind(&rule.label)
+        .bind(&rule.auto_tag_category)
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Invalid auto-tag rule for {}: {}", rule.counterparty, e))?;
+        result.auto_tag_rules += 1;
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(result)
+}
+
+/// Writes secrets to the keychain, recording any that can't be restored.
+fn restore_secrets(secrets: &BundleSecrets, result: &mut ConfigImportResult) {
+    for (name, key) in &secrets.api_keys {
+        let restored = ApiProvider::from_str(name)
+            .ok_or_else(|| "unknown provider".to_string())
+            .and_then(|provider| {
+                ApiKeyManager::save_api_key(provider, key).map_err(|e| e.to_string())
+            });
+        match restored {
+            Ok(()) => result.secrets += 1,
+            Err(e) => result.skipped.push(format!("API key {}: {}", name, e)),
+        }
+    }
+    if let Some(password) = &secrets.smtp_password {
+        match smtp::save_password(password) {
+            Ok(()) => result.secrets += 1,
+            Err(e) => result.skipped.push(format!("SMTP password: {}", e)),
+        }
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Exports a profile's configuration to `path`. Secrets are included,
+/// encrypted, only when a passphrase is given, which also requires the
+/// manage keys permission.
+#[tauri::command]
+pub async fn export_config_bundle(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    path: String,
+    passphrase: Option<String>,
+) -> Result<ConfigBundle, String> {
+    let pool = &state.pool;
+    let user_id = authorize_profile(pool, &auth, &token, &profile_id, Permission::Export).await?;
+
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    if passphrase.is_some() {
+        verify_profile_access(pool, &user_id, &profile_id, Permission::ManageKeys).await?;
+    }
+
+    let mut bundle = build_bundle(pool, &profile_id).await?;
+    if let Some(passphrase) = passphrase.as_deref() {
+        bundle.secrets = Some(seal_secrets(&collect_secrets()?, passphrase)?);
+    }
+
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    let details = serde_json::json!({ "withSecrets": bundle.secrets.is_some() }).to_string();
+    log_audit_event(
+        pool,
+        Some(&user_id),
+        "config_bundle_exported",
+        "success",
+        Some(&details),
+        None,
+        Some(&profile_id),
+    )
+    .await;
+
+    Ok(bundle)
+}
+
+/// Imports a configuration bundle from `path` into a profile. Requires the
+/// owner or admin role. Encrypted secrets are restored only when the
+/// passphrase is given, which also requires the manage keys permission;
+/// otherwise they are skipped.
+#[tauri::command]
+pub async fn import_config_bundle(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    path: String,
+    passphrase: Option<String>,
+) -> Result<ConfigImportResult, String> {
+    let pool = &state.pool;
+    let user_id =
+        authorize_profile(pool, &auth, &token, &profile_id, Permission::ManageProfile).await?;
+
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let bundle = parse_bundle(&content)?;
+    if bundle.secrets.is_some() && passphrase.is_some() {
+        verify_profile_access(pool, &user_id, &profile_id, Permission::ManageKeys).await?;
+    }
+
+    // Decrypt before writing anything so a wrong passphrase changes nothing.
+    let secrets = match (&bundle.secrets, passphrase.as_deref()) {
+        (Some(sealed), Some(passphrase)) => Some(open_secrets(sealed, passphrase)?),
+        _ => None,
+    };
+
+    let mut result = apply_bundle(pool, &profile_id, &bundle).await?;
+    match &secrets {
+        Some(secrets) => restore_secrets(secrets, &mut result),
+        None if bundle.secrets.is_some() => result
+            .skipped
+            .push("secrets: no passphrase given".to_string()),
+        None => {}
+    }
+
+    if bundle.settings.iter().any(|s| s.key == smtp::SETTINGS_KEY) {
+        load_smtp_provider(pool).await?;
+    }
+
+    let details = serde_json::json!({
+        "settings": result.settings,
+        "accounts": result.accounts,
+        "tokenMarks": result.token_marks,
+        "autoTagRules": result.auto_tag_rules,
+        "secrets": result.secrets,
+    })
+    .to_string();
+    log_audit_event(
+        pool,
+        Some(&user_id),
+        "config_bundle_imported",
+        "success",
+        Some(&details),
+        None,
+        Some(&profile_id),
+    )
+    .await;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_portable_setting() {
+        assert!(is_portable_setting("theme"));
+        assert!(is_portable_setting(smtp::SETTINGS_KEY));
+        assert!(!is_portable_setting("app_initialized"));
+        assert!(!is_portable_setting(ARGON2_PARAMS_SETTING));
+        assert!(!is_portable_setting("recovery_phrase_hash"));
+        assert!(!is_portable_setting("coingecko_api_key"));
+    }
+
+    #[test]
+    fn test_secrets_round_trip() {
+        let mut secrets = BundleSecrets::default();
+        secrets
+            .api_keys
+            .insert("etherscan".to_string(), "ABC123".to_string());
+        secrets.smtp_password = Some("hunter2".to_string());
+
+        let sealed = seal_secrets(&secrets, "correct horse").unwrap();
+        assert!(!sealed.ciphertext.contains("ABC123"));
+
+        let opened = open_secrets(&sealed, "correct horse").unwrap();
+        assert_eq!(opened.api_keys["etherscan"], "ABC123");
+        assert_eq!(opened.smtp_password.as_deref(), Some("hunter2"));
+        assert!(open_secrets(&sealed, "wrong").is_err());
+    }
+
+    #[test]
+    fn test_parse_bundle_checks_format() {
+        let bundle = ConfigBundle {
+            format_version: FORMAT_VERSION.to_string(),
+            exported_at: Utc::now(),
+            settings: vec![BundleSetting {
+                key: "theme".to_string(),
+                value: "dark".to_string(),
+            }],
+            tax_settings: None,
+            chart_of_accounts: Vec::new(),
+            token_marks: Vec::new(),
+            auto_tag_rules: Vec::new(),
+            secrets: None,
+        };
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert_eq!(parse_bundle(&json).unwrap().settings.len(), 1);
+
+        let other = json.replace(FORMAT_VERSION, "pacioli-config-v0");
+        assert!(parse_bundle(&other).is_err());
+        assert!(parse_bundle("{}").is_err());
+    }
+}
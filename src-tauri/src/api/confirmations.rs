@@ -0,0 +1,239 @@
+//! Tauri commands for a profile's per-chain confirmation thresholds.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+
+use super::mempool_watch::confirmations;
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::authorize_profile;
+use crate::core::auth_state::AuthState;
+
+pub use pacioli_core::services::confirmations::*;
+
+/// Most confirmations a profile may require.
+const MAX_CONFIRMATIONS: u32 = 256;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A chain's confirmation threshold for a profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainConfirmations {
+    /// Chain name.
+    pub chain: String,
+    /// Confirmations the profile requires.
+    pub confirmations: u32,
+    /// The chain's default threshold.
+    pub default_confirmations: u32,
+    /// When the profile overrode the default; `None` if it hasn't.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(FromRow)]
+struct OverrideRow {
+    chain: String,
+    confirmations: u32,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+// ============================================================================
+// Thresholds
+// ============================================================================
+
+/// Thresholds on each chain a profile has wallets on or has overridden,
+/// ordered by chain.
+async fn load_profile_confirmations(
+    pool: &SqlitePool,
+    profile_id: &str,
+) -> Result<Vec<ChainConfirmations>, String> {
+    let overrides: Vec<OverrideRow> = sqlx::query_as(
+        "SELECT chain, confirmations, updated_at FROM profile_chain_confirmations WHERE profile_id = ?",
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let chains: Vec<String> =
+        sqlx::query_scalar("SELECT DISTINCT chain FROM wallets WHERE profile_id = ?")
+            .bind(profile_id)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    let mut thresholds: HashMap<String, ChainConfirmations> = chains
+        .into_iter()
+        .map(|chain| {
+            let default = default_confirmations(&chain);
+            let threshold = ChainConfirmations {
+                chain: chain.clone(),
+                confirmations: default,
+                default_confirmations: default,
+                updated_at: None,
+            };
+            (chain, threshold)
+        })
+        .collect();
+    for row in overrides {
+        thresholds.insert(
+            row.chain.clone(),
+            ChainConfirmations {
+                default_confirmations: default_confirmations(&row.chain),
+                chain: row.chain,
+                confirmations: row.confirmations,
+                updated_at: row.updated_at,
+            },
+        );
+    }
+
+    let mut thresholds: Vec<ChainConfirmations> = thresholds.into_values().collect();
+    thresholds.sort_by(|a, b| a.chain.cmp(&b.chain));
+    Ok(thresholds)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns a profile's confirmation threshold on each chain it has wallets
+/// on or has overridden.
+///
+/// Requires any role on the profile.
+#[tauri::command]
+pub async fn get_chain_confirmations(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<ChainConfirmations>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    load_profile_confirmations(&state.pool, &profile_id).await
+}
+
+/// Sets the confirmations a profile requires on `chain` before a synced
+/// transaction counts as successful, or restores the chain's default when
+/// `confirmations` is `None`. Returns the profile's thresholds.
+///
+/// Requires the owner, admin, or preparer role on the profile.
+#[tauri::command]
+pub async fn set_chain_confirmations(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    chain: String,
+    confirmations: Option<u32>,
+) -> Result<Vec<ChainConfirmations>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ManageWallets,
+    )
+    .await?;
+
+    match confirmations {
+        Some(confirmations) if confirmations > MAX_CONFIRMATIONS => {
+            return Err(format!(
+                "At most {} confirmations may be required",
+                MAX_CONFIRMATIONS
+            ));
+        }
+        Some(confirmations) => {
+            sqlx::query(
+                r#"
+                INSERT INTO profile_chain_confirmations (profile_id, chain, confirmations, updated_at)
+                VALUES (?, ?, ?, ?)
+                ON CONFLICT(profile_id, chain) DO UPDATE SET
+                    confirmations = excluded.confirmations,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(&profile_id)
+            .bind(&chain)
+            .bind(confirmations)
+            .bind(Utc::now())
+            .execute(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+        None => {
+            sqlx::query(
+                "DELETE FROM profile_chain_confirmations WHERE profile_id = ? AND chain = ?",
+            )
+            .bind(&profile_id)
+            .bind(&chain)
+            .execute(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+    }
+
+    load_profile_confirmations(&state.pool, &profile_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn test_required_confirmations_prefers_profile_override() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(include_str!(
+            "../../migrations/20260501000001_create_chain_confirmations.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE profiles (id TEXT PRIMARY KEY);
+            CREATE TABLE wallets (id TEXT PRIMARY KEY, profile_id TEXT, chain TEXT);
+            INSERT INTO profiles VALUES ('p1');
+            INSERT INTO wallets VALUES ('w1', 'p1', 'ethereum'), ('w2', 'p1', 'base');
+            INSERT INTO profile_chain_confirmations (profile_id, chain, confirmations)
+            VALUES ('p1', 'ethereum', 32), ('p1', 'bitcoin', 3);
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(
+            required_confirmations(&pool, "p1", "ethereum").await,
+            Ok(32)
+        );
+        assert_eq!(
+            required_confirmations(&pool, "p2", "ethereum").await,
+            Ok(12)
+        );
+
+        let thresholds = load_profile_confirmations(&pool, "p1").await.unwrap();
+        let chains: Vec<_> = thresholds.iter().map(|t| t.chain.as_str()).collect();
+        assert_eq!(chains, ["base", "bitcoin", "ethereum"]);
+        assert_eq!(thresholds[0].confirmations, 1);
+        assert!(thresholds[0].updated_at.is_none());
+        assert_eq!(thresholds[1].confirmations, 3);
+        assert_eq!(thresholds[1].default_confirmations, 6);
+        assert_eq!(thresholds[2].confirmations, 32);
+    }
+}
@@ -0,0 +1,519 @@
+//! Consolidated reporting across profiles.
+//!
+//! Organizations that keep each legal entity in its own profile can report
+//! on a group of profiles together. Token flows and posted ledger figures
+//! are summed across the group, and transfers from one profile's wallet to
+//! another's are eliminated, along with the journal entries that reference
+//! them, so money moving inside the group isn't reported as income or
+//! expense. Only owners of every selected profile can run a consolidation.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{NaiveDate, NaiveTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tauri::State;
+
+use super::auth::verify_profile_access;
+use super::budgets::parse_period;
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction, Wallet};
+use super::price_overrides::reporting_currency;
+use super::statement_export::parse_amount;
+use crate::chains::address::identity_key;
+use crate::core::auth_helpers::verify_access_token;
+use crate::core::auth_state::AuthState;
+use crate::core::currency::round_fiat;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Token flows for one token on one chain across the group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsolidatedTokenTotal {
+    /// Chain the token is on.
+    pub chain: String,
+    /// Token symbol.
+    pub token_symbol: String,
+    /// Received from outside the group, as a decimal string.
+    pub inflow: String,
+    /// Sent outside the group, as a decimal string.
+    pub outflow: String,
+    /// Fees paid by the group's wallets, as a decimal string.
+    pub fees: String,
+    /// Amount moved between profiles and left out of the flows.
+    pub eliminated: String,
+    /// Inflow less outflow and fees.
+    pub net: String,
+}
+
+/// A transfer between two profiles of the group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Elimination {
+    /// Chain the transfer is on.
+    pub chain: String,
+    /// Transaction hash.
+    pub hash: String,
+    /// Sending profile.
+    pub from_profile_id: String,
+    /// Receiving profile.
+    pub to_profile_id: String,
+    /// Token symbol.
+    pub token_symbol: String,
+    /// Amount transferred, as a decimal string.
+    pub amount: String,
+}
+
+/// Posted ledger totals for one GL account across the group.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsolidatedLedgerLine {
+    /// GL account number.
+    pub account_number: String,
+    /// GL account name.
+    pub account_name: String,
+    /// One of: Asset, Liability, Equity, Income, Expense.
+    pub account_type: String,
+    /// Debits after eliminations.
+    pub debit: Decimal,
+    /// Credits after eliminations.
+    pub credit: Decimal,
+    /// Debits from entries for eliminated transfers.
+    pub eliminated_debit: Decimal,
+    /// Credits from entries for eliminated transfers.
+    pub eliminated_credit: Decimal,
+}
+
+/// Consolidated report for a group of profiles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsolidatedReport {
+    /// Profiles included.
+    pub profile_ids: Vec<String>,
+    /// First day of the period.
+    pub period_start: NaiveDate,
+    /// Last day of the period, inclusive.
+    pub period_end: NaiveDate,
+    /// Distinct transactions in the period, counting each transfer between
+    /// profiles once.
+    pub transaction_count: usize,
+    /// Token flows per chain and token.
+    pub token_totals: Vec<ConsolidatedTokenTotal>,
+    /// Posted ledger totals per GL account.
+    pub ledger: Vec<ConsolidatedLedgerLine>,
+    /// Transfers between profiles that were eliminated.
+    pub eliminations: Vec<Elimination>,
+}
+
+/// A posted journal line with its account and the entry's reference.
+#[derive(Debug, Clone, FromRow)]
+pub struct LedgerRow {
+    /// GL account number.
+    pub account_number: String,
+    /// GL account name.
+    pub account_name: String,
+    /// GL account type.
+    pub account_type: String,
+    /// Debit amount.
+    #[sqlx(try_from = "f64")]
+    pub debit_amount: Decimal,
+    /// Credit amount.
+    #[sqlx(try_from = "f64")]
+    pub credit_amount: Decimal,
+    /// Entry reference, the transaction hash for classified transactions.
+    pub reference_number: Option<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Sums token flows across the group's transactions and finds transfers
+/// between its profiles.
+///
+/// Each stored transaction is seen from the side of the wallet it was
+/// recorded for. A transfer whose counterparty is a wallet of a different
+/// selected profile is eliminated; it usually appears twice, once per side,
+/// and is reported once.
+pub fn consolidate_flows(
+    wallets: &[Wallet],
+    transactions: &[StoredTransaction],
+) -> (Vec<ConsolidatedTokenTotal>, Vec<Elimination>, usize) {
+    #[derive(Default)]
+    struct Sums {
+        inflow: Decimal,
+        outflow: Decimal,
+        fees: Decimal,
+        eliminated: Decimal,
+    }
+
+    let wallets_by_id: HashMap<&str, &Wallet> =
+        wallets.iter().map(|w| (w.id.as_str(), w)).collect();
+    let profile_by_identity: HashMap<String, &str> = wallets
+        .iter()
+        .map(|w| (identity_key(&w.address), w.profile_id.as_str()))
+        .collect();
+
+    let mut sums: BTreeMap<(String, String), Sums> = BTreeMap::new();
+    let mut eliminations: Vec<Elimination> = Vec::new();
+    let mut seen: HashSet<(&str, &str)> = HashSet::new();
+
+    for tx in transactions {
+        let Some(wallet) = wallets_by_id.get(tx.wallet_id.as_str()) else {
+            continue;
+        };
+        seen.insert((tx.chain.as_str(), tx.hash.as_str()));
+
+        let own = identity_key(&wallet.address);
+        let from = tx.from_address.as_deref().map(identity_key);
+        let to = tx.to_address.as_deref().map(identity_key);
+        let outgoing = from.as_deref() == Some(own.as_str());
+        let incoming = to.as_deref() == Some(own.as_str());
+
+        let symbol = tx.token_symbol.clone().unwrap_or_default();
+        let entry = sums.entry((tx.chain.clone(), symbol.clone())).or_default();
+        let amount = tx
+            .value
+            .as_deref()
+            .and_then(|v| parse_amount(v, tx.token_decimals))
+            .unwrap_or(Decimal::ZERO);
+
+        if outgoing {
+            entry.fees += tx
+                .fee
+                .as_deref()
+                .and_then(|f| parse_amount(f, tx.token_decimals))
+                .unwrap_or(Decimal::ZERO);
+        }
+        if tx.status.as_deref() == Some("failed") || outgoing == incoming {
+            continue;
+        }
+
+        let counterparty = if outgoing {
+            to.as_deref()
+        } else {
+            from.as_deref()
+        };
+        let other_profile = counterparty.and_then(|key| profile_by_identity.get(key).copied());
+        match other_profile {
+            Some(other) if other != wallet.profile_id => {
+                let (from_profile, to_profile) = if outgoing {
+                    (wallet.profile_id.as_str(), other)
+                } else {
+                    (other, wallet.profile_id.as_str())
+                };
+                let recorded = eliminations
+                    .iter()
+                    .any(|e| e.chain == tx.chain && e.hash == tx.hash);
+                if !recorded {
+                    entry.eliminated += amount;
+                    eliminations.push(Elimination {
+                        chain: tx.chain.clone(),
+                        hash: tx.hash.clone(),
+                        from_profile_id: from_profile.to_string(),
+                        to_profile_id: to_profile.to_string(),
+                        token_symbol: symbol,
+                        amount: amount.normalize().to_string(),
+                    });
+                }
+            }
+            _ if outgoing => entry.outflow += amount,
+            _ => entry.inflow += amount,
+        }
+    }
+
+    let totals = sums
+        .into_iter()
+        .map(|((chain, token_symbol), s)| ConsolidatedTokenTotal {
+            chain,
+            token_symbol,
+            inflow: s.inflow.normalize().to_string(),
+            outflow: s.outflow.normalize().to_string(),
+            fees: s.fees.normalize().to_string(),
+            eliminated: s.eliminated.normalize().to_string(),
+            net: (s.inflow - s.outflow - s.fees).normalize().to_string(),
+        })
+        .collect();
+
+    (totals, eliminations, seen.len())
+}
+
+/// Sums posted journal lines per GL account, setting aside lines of entries
+/// that reference an eliminated transfer.
+pub fn consolidate_ledger(
+    rows: &[LedgerRow],
+    eliminations: &[Elimination],
+) -> Vec<ConsolidatedLedgerLine> {
+    let eliminated: HashSet<String> = eliminations.iter().map(|e| e.hash.to_lowercase()).collect();
+    let mut lines: BTreeMap<&str, ConsolidatedLedgerLine> = BTreeMap::new();
+
+    for row in rows {
+        let line =
+            lines
+                .entry(row.account_number.as_str())
+                .or_insert_with(|| ConsolidatedLedgerLine {
+                    account_number: row.account_number.clone(),
+                    account_name: row.account_name.clone(),
+                    account_type: row.account_type.clone(),
+                    debit: Decimal::ZERO,
+                    credit: Decimal::ZERO,
+                    eliminated_debit: Decimal::ZERO,
+                    eliminated_credit: Decimal::ZERO,
+                });
+        let is_eliminated = row
+            .reference_number
+            .as_deref()
+            .is_some_and(|r| eliminated.contains(&r.to_lowercase()));
+        if is_eliminated {
+            line.eliminated_debit += row.debit_amount;
+            line.eliminated_credit += row.credit_amount;
+        } else {
+            line.debit += row.debit_amount;
+            line.credit += row.credit_amount;
+        }
+    }
+
+    lines.into_values().collect()
+}
+
+/// `?, ?, ...` with `count` placeholders.
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Builds a consolidated report over `profile_ids` for a period.
+///
+/// # Arguments
+/// * `token` - Access token; the user must own every profile.
+/// * `profile_ids` - Profiles to consolidate.
+/// * `period` - `YYYY`, `YYYY-Qn`, `YYYY-MM`, or `YYYY-MM-DD..YYYY-MM-DD`.
+#[tauri::command]
+pub async fn get_consolidated_report(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_ids: Vec<String>,
+    period: String,
+) -> Result<ConsolidatedReport, String> {
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
+    let pool = &state.pool;
+
+    let mut profile_ids = profile_ids;
+    profile_ids.sort();
+    profile_ids.dedup();
+    if profile_ids.is_empty() {
+        return Err("Select at least one profile to consolidate".to_string());
+    }
+    for profile_id in &profile_ids {
+        verify_profile_access(
+            pool,
+            &claims.sub,
+            profile_id,
+            Permission::ConsolidateProfiles,
+        )
+        .await?;
+    }
+
+    let (period_start, period_end) = parse_period(&period)?;
+    let start = period_start.and_time(NaiveTime::MIN);
+    let end = period_end
+        .succ_opt()
+        .ok_or_else(|| format!("Invalid period: {}", period))?
+        .and_time(NaiveTime::MIN);
+    let in_profiles = placeholders(profile_ids.len());
+
+    let wallet_query = format!(
+        "SELECT * FROM wallets WHERE profile_id IN ({})",
+        in_profiles
+    );
+    let mut q = sqlx::query_as::<_, Wallet>(&wallet_query);
+    for profile_id in &profile_ids {
+        q = q.bind(profile_id);
+    }
+    let wallets = q.fetch_all(pool).await.map_err(|e| e.to_string())?;
+
+    let tx_query = format!(
+        r#"
+        SELECT t.* FROM transactions t
+        INNER JOIN wallets w ON t.wallet_id = w.id
+        WHERE w.profile_id IN ({}) AND t.timestamp >= ? AND t.timestamp < ?
+          AND t.id NOT IN (SELECT duplicate_id FROM transaction_merges)
+        ORDER BY t.timestamp ASC
+        "#,
+        in_profiles
+    );
+    let mut q = sqlx::query_as::<_, StoredTransaction>(&tx_query);
+    for profile_id in &profile_ids {
+        q = q.bind(profile_id);
+    }
+    let transactions = q
+        .bind(start.and_utc())
+        .bind(end.and_utc())
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let ledger_query = format!(
+        r#"
+        SELECT ga.account_number, ga.account_name, ga.account_type,
+               jel.debit_amount, jel.credit_amount, je.reference_number
+        FROM journal_entry_lines jel
+        INNER JOIN journal_entries je ON jel.journal_entry_id = je.id
+        INNER JOIN gl_accounts ga ON jel.gl_account_id = ga.id
+        WHERE je.profile_id IN ({})
+          AND je.is_posted = 1 AND je.is_reversed = 0
+          AND je.entry_date >= ? AND je.entry_date < ?
+        "#,
+        in_profiles
+    );
+    let mut q = sqlx::query_as::<_, LedgerRow>(&ledger_query);
+    for profile_id in &profile_ids {
+        q = q.bind(profile_id);
+    }
+    let mut ledger_rows = q
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // SQLite stores ledger amounts as floats; round them back to the
+    // reporting currency before summing.
+    let currency = reporting_currency(pool).await.map_err(|e| e.to_string())?;
+    for row in &mut ledger_rows {
+        row.debit_amount = round_fiat(row.debit_amount, &currency);
+        row.credit_amount = round_fiat(row.credit_amount, &currency);
+    }
+
+    let (token_totals, eliminations, transaction_count) =
+        consolidate_flows(&wallets, &transactions);
+    let ledger = consolidate_ledger(&ledger_rows, &eliminations);
+
+    Ok(ConsolidatedReport {
+        profile_ids,
+        period_start,
+        period_end,
+        transaction_count,
+        token_totals,
+        ledger,
+        eliminations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    const PARENT: &str = "0x1000000000000000000000000000000000000001";
+    const SUBSIDIARY: &str = "0x2000000000000000000000000000000000000002";
+    const VENDOR: &str = "0x3000000000000000000000000000000000000003";
+
+    fn wallet(id: &str, profile_id: &str, address: &str) -> Wallet {
+        Wallet {
+            id: id.to_string(),
+            profile_id: profile_id.to_string(),
+            address: address.to_string(),
+            chain: "ethereum".to_string(),
+            name: None,
+            wallet_type: "external".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+        }
+    }
+
+    fn tx(wallet_id: &str, hash: &str, from: &str, to: &str, value: &str) -> StoredTransaction {
+        StoredTransaction {
+            id: format!("{}-{}", wallet_id, hash),
+            wallet_id: wallet_id.to_string(),
+            hash: hash.to_string(),
+            block_number: None,
+            timestamp: Some(Utc.with_ymd_and_hms(2025, 2, 1, 0, 0, 0).unwrap()),
+            from_address: Some(from.to_string()),
+            to_address: Some(to.to_string()),
+            value: Some(value.to_string()),
+            fee: None,
+            status: Some("success".to_string()),
+            tx_type: None,
+            token_symbol: Some("USDC".to_string()),
+            token_decimals: Some(6),
+            chain: "ethereum".to_string(),
+            raw_data: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_transfers_between_profiles_are_eliminated() {
+        let wallets = vec![
+            wallet("w1", "parent", PARENT),
+            wallet("w2", "sub", SUBSIDIARY),
+        ];
+        let transactions = vec![
+            // Parent funds the subsidiary; stored once per side.
+            tx("w1", "0xa", PARENT, SUBSIDIARY, "100.0"),
+            tx("w2", "0xa", PARENT, SUBSIDIARY, "100.0"),
+            // Subsidiary pays a vendor.
+            tx("w2", "0xb", SUBSIDIARY, VENDOR, "40.0"),
+            // Parent receives a grant.
+            tx("w1", "0xc", VENDOR, PARENT, "250.0"),
+        ];
+
+        let (totals, eliminations, count) = consolidate_flows(&wallets, &transactions);
+        assert_eq!(count, 3);
+        assert_eq!(eliminations.len(), 1);
+        assert_eq!(eliminations[0].from_profile_id, "parent");
+        assert_eq!(eliminations[0].to_profile_id, "sub");
+
+        assert_eq!(totals.len(), 1);
+        assert_eq!(totals[0].inflow, "250");
+        assert_eq!(totals[0].outflow, "40");
+        assert_eq!(totals[0].eliminated, "100");
+        assert_eq!(totals[0].net, "210");
+    }
+
+    #[test]
+    fn test_ledger_sets_aside_eliminated_entries() {
+        let dec = |s: &str| s.parse::<Decimal>().unwrap();
+        let row = |account: &str, debit: &str, credit: &str, reference: &str| LedgerRow {
+            account_number: account.to_string(),
+            account_name: account.to_string(),
+            account_type: "Asset".to_string(),
+            debit_amount: dec(debit),
+            credit_amount: dec(credit),
+            reference_number: Some(reference.to_string()),
+        };
+        let eliminations = vec![Elimination {
+            chain: "ethereum".to_string(),
+            hash: "0xA".to_string(),
+            from_profile_id: "parent".to_string(),
+            to_profile_id: "sub".to_string(),
+            token_symbol: "USDC".to_string(),
+            amount: "100".to_string(),
+        }];
+
+        let ledger = consolidate_ledger(
+            &[
+                row("1200", "100", "0", "0xa"),
+                row("1200", "250.1", "0", "0xc"),
+                row("1200", "0.2", "0", "0xd"),
+                row("4000", "0", "250.3", "0xc"),
+            ],
+            &eliminations,
+        );
+        assert_eq!(ledger.len(), 2);
+        assert_eq!(ledger[0].debit, dec("250.3"));
+        assert_eq!(ledger[0].eliminated_debit, dec("100"));
+        assert_eq!(ledger[1].credit, dec("250.3"));
+        // Debits and credits balance exactly, with no float residue
+        assert_eq!(ledger[0].debit, ledger[1].credit);
+    }
+}
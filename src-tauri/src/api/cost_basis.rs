@@ -0,0 +1,212 @@
+//! Realized gains per tax jurisdiction.
+//!
+//! Each profile elects a jurisdiction and, where lots are matched
+//! individually, a lot selection method. Reports run the cost-basis engine
+//! over the supplied acquisitions and disposals using that election, after
+//! revaluing any event covered by a manual price override.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::price_overrides::{apply_to_events, load_overrides, reporting_currency};
+use super::profile_scope::authorize_profile;
+use crate::core::auth_state::AuthState;
+use crate::core::cost_basis::liquidity::{self, LiquidityEvent, LiquidityReport};
+use crate::core::cost_basis::{
+    self, AssetEvent, CostBasisMethod, CostBasisReport, Jurisdiction, JurisdictionRules,
+};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Tax settings elected for a profile.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileTaxSettings {
+    /// Profile the settings belong to.
+    pub profile_id: String,
+    /// Tax jurisdiction.
+    pub jurisdiction: Jurisdiction,
+    /// Lot selection method, used where lots are matched individually.
+    pub cost_basis_method: CostBasisMethod,
+    /// When the settings were last changed; `None` for defaults.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl ProfileTaxSettings {
+    /// Settings for a profile that hasn't made an election.
+    fn default_for(profile_id: &str) -> Self {
+        let rules = Jurisdiction::Us.rules();
+        Self {
+            profile_id: profile_id.to_string(),
+            jurisdiction: rules.jurisdiction,
+            cost_basis_method: rules.default_method,
+            updated_at: None,
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Loads a profile's tax settings, falling back to the defaults.
+pub(crate) async fn load_tax_settings(
+    pool: &SqlitePool,
+    profile_id: &str,
+) -> Result<ProfileTaxSettings, sqlx::Error> {
+    let settings: Option<ProfileTaxSettings> =
+        sqlx::query_as("SELECT * FROM profile_tax_settings WHERE profile_id = ?")
+            .bind(profile_id)
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(settings.unwrap_or_else(|| ProfileTaxSettings::default_for(profile_id)))
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Lists the supported jurisdictions and their rules.
+#[tauri::command]
+pub async fn get_tax_jurisdictions() -> Result<Vec<JurisdictionRules>, String> {
+    Ok(Jurisdiction::ALL.iter().map(|j| j.rules()).collect())
+}
+
+/// Returns the tax settings for a profile. Requires any role on the profile.
+#[tauri::command]
+pub async fn get_profile_tax_settings(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<ProfileTaxSettings, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    load_tax_settings(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Elects a jurisdiction and lot selection method for a profile.
+///
+/// When `cost_basis_method` is omitted, the jurisdiction's default is used.
+/// Requires the owner or admin role on the profile.
+#[tauri::command]
+pub async fn update_profile_tax_settings(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    jurisdiction: Jurisdiction,
+    cost_basis_method: Option<CostBasisMethod>,
+) -> Result<ProfileTaxSettings, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ManageProfile,
+    )
+    .await?;
+    let rules = jurisdiction.rules();
+    let method = cost_basis_method.unwrap_or(rules.default_method);
+    if !rules.allows(method) {
+        return Err(format!(
+            "{} is not permitted in {}",
+            method.as_str(),
+            rules.name
+        ));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO profile_tax_settings (profile_id, jurisdiction, cost_basis_method, updated_at)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT(profile_id) DO UPDATE SET
+            jurisdiction = excluded.jurisdiction,
+            cost_basis_method = excluded.cost_basis_method,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(&profile_id)
+    .bind(jurisdiction)
+    .bind(method)
+    .bind(Utc::now())
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    load_tax_settings(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Computes realized gains for `events` under the profile's elected
+/// jurisdiction and method.
+///
+/// Events covered by one of the profile's price overrides in the reporting
+/// currency are valued at the override, and the report notes each section
+/// that relied on one. Requires any role on the profile.
+#[tauri::command]
+pub async fn calculate_cost_basis(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    mut events: Vec<AssetEvent>,
+) -> Result<CostBasisReport, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let settings = load_tax_settings(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let rules = settings.jurisdiction.rules();
+
+    let overrides = load_overrides(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !overrides.is_empty() {
+        let currency = reporting_currency(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        apply_to_events(&overrides, &currency, &mut events);
+    }
+
+    Ok(cost_basis::calculate(
+        &events,
+        &rules,
+        settings.cost_basis_method,
+    ))
+}
+
+/// Follows liquidity positions through their adds, removals, and fee
+/// collections.
+///
+/// The report's asset events record each add and removal as an exchange
+/// between the pool's tokens and the LP token, and fees as income, for
+/// passing to [`calculate_cost_basis`] with the profile's other events.
+#[tauri::command]
+pub async fn track_liquidity_positions(
+    events: Vec<LiquidityEvent>,
+) -> Result<LiquidityReport, String> {
+    Ok(liquidity::track(&events))
+}
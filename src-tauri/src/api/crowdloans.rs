@@ -0,0 +1,872 @@
+//! Parachain crowdloan contributions.
+//!
+//! Contributing DOT or KSM to a crowdloan moves it to the fund's pallet
+//! account, where it stays locked until the parachain's slot lease ends (or
+//! the auction is lost) and it is returned. The contributor still owns it
+//! throughout, so the contribution and its return are typed as
+//! `crowdloan_contribution` and `crowdloan_release` instead of a disposal
+//! followed by fresh income. Reward tokens the parachain pays contributors
+//! are typed `crowdloan_reward` and booked as income.
+//!
+//! Contributions and slot leases come from the relay chain's Subscan API;
+//! the matching legs, releases, and rewards from stored transactions.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction, Wallet};
+use super::profile_scope::{authorize_profile, profile_transactions, profile_wallets};
+use super::statement_export::parse_amount;
+use super::wallet_identity::{find_identity, load_identities};
+use super::xcm_transfers::retype_leg;
+use crate::chains::address::identity_key;
+use crate::chains::substrate::subscan::{SubscanClient, SubscanContribution, SubscanFund};
+use crate::chains::substrate::{
+    chain_for_para, get_config_by_name, is_pallet_account, lease_end_block,
+};
+use crate::core::auth_state::AuthState;
+use crate::fetchers::{ApiKeyManager, ApiProvider};
+use crate::log_error;
+
+/// Relay chain block time, used to date future lease ends.
+const RELAY_BLOCK_SECS: i64 = 6;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// DOT or KSM contributed to a parachain crowdloan.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CrowdloanContribution {
+    /// Unique identifier.
+    pub id: String,
+    /// Profile the contributing wallet belongs to.
+    pub profile_id: String,
+    /// Contributing wallet.
+    pub wallet_id: String,
+    /// Relay chain the crowdloan ran on.
+    pub relay_chain: String,
+    /// Parachain the crowdloan was for.
+    pub para_id: i64,
+    /// Crowdloan fund, as `para_id-index`.
+    pub fund_id: String,
+    /// Amount contributed, in whole units.
+    pub amount: String,
+    /// Symbol of the relay chain's token.
+    pub symbol: String,
+    /// Contributing extrinsic, as `block-index`.
+    pub extrinsic_index: String,
+    /// When the contribution was made.
+    pub contributed_at: DateTime<Utc>,
+    /// Relay chain block at which the slot lease ends.
+    pub lease_end_block: Option<i64>,
+    /// When the lease ends, estimated from the block while still ahead.
+    pub lease_end_at: Option<DateTime<Utc>>,
+    /// `locked`, `withdrawable`, or `released`.
+    pub status: String,
+    /// Stored transaction of the contribution, if synced.
+    pub contribution_transaction_id: Option<String>,
+    /// Stored transaction returning the contribution, if synced.
+    pub release_transaction_id: Option<String>,
+    /// When the contribution was returned.
+    pub released_at: Option<DateTime<Utc>>,
+    /// When the contribution was first recorded.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Reward tokens a parachain paid for a crowdloan contribution.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CrowdloanReward {
+    /// Stored transaction of the payout.
+    pub transaction_id: String,
+    /// Profile the receiving wallet belongs to.
+    pub profile_id: String,
+    /// Relay chain the crowdloan ran on.
+    pub relay_chain: String,
+    /// Parachain that paid the reward.
+    pub para_id: i64,
+    /// Chain the reward was paid on.
+    pub chain: String,
+    /// Reward token symbol.
+    pub symbol: String,
+    /// Amount received, in whole units.
+    pub amount: String,
+    /// When the reward was received.
+    pub received_at: DateTime<Utc>,
+    /// When the reward was first recorded.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Crowdloan balances of one relay chain token.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrowdloanBalance {
+    /// Relay chain token symbol.
+    pub symbol: String,
+    /// Locked until a lease ends.
+    pub locked: Decimal,
+    /// Lease ended but not yet returned.
+    pub withdrawable: Decimal,
+    /// Returned and liquid again.
+    pub released: Decimal,
+}
+
+/// A profile's crowdloan position.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrowdloanStatus {
+    /// When the figures were computed.
+    pub as_of: DateTime<Utc>,
+    /// Balances per relay chain token.
+    pub balances: Vec<CrowdloanBalance>,
+    /// Contributions, newest first.
+    pub contributions: Vec<CrowdloanContribution>,
+    /// Rewards received, newest first.
+    pub rewards: Vec<CrowdloanReward>,
+    /// Locked contributions by lease end, soonest first.
+    pub upcoming_releases: Vec<CrowdloanContribution>,
+}
+
+/// Outcome of syncing a profile's crowdloans.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrowdloanSyncResult {
+    /// Contributions found.
+    pub contributions: usize,
+    /// Contributions returned to their wallet.
+    pub releases: usize,
+    /// Reward payouts found.
+    pub rewards: usize,
+    /// Legs left alone because their period is closed.
+    pub legs_in_closed_periods: usize,
+    /// Accounts whose crowdloans couldn't be fetched.
+    pub failed_accounts: Vec<String>,
+}
+
+// ============================================================================
+// Crowdloans
+// ============================================================================
+
+/// Where a contribution stands at `now`.
+pub fn contribution_status(
+    released: bool,
+    lease_end_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> &'static str {
+    if released {
+        "released"
+    } else if lease_end_at.is_some_and(|end| end <= now) {
+        "withdrawable"
+    } else {
+        "locked"
+    }
+}
+
+/// When relay chain `block` is (or was) produced, counting from the current
+/// block at `now`.
+pub fn block_time(block: u64, current_block: u64, now: DateTime<Utc>) -> DateTime<Utc> {
+    let blocks = block as i64 - current_block as i64;
+    now + Duration::seconds(blocks * RELAY_BLOCK_SECS)
+}
+
+fn same_account(a: Option<&str>, b: &str) -> bool {
+    a.is_some_and(|a| identity_key(a) == identity_key(b))
+}
+
+fn tx_amount(tx: &StoredTransaction, decimals: u8) -> Option<Decimal> {
+    parse_amount(
+        tx.value.as_deref()?,
+        tx.token_decimals.or(Some(i32::from(decimals))),
+    )
+}
+
+/// The stored transaction of a contribution: one from the wallet to a
+/// pallet account in the contributing block.
+pub fn find_contribution_leg<'a>(
+    contribution: &SubscanContribution,
+    wallet: &Wallet,
+    transactions: &'a [StoredTransaction],
+) -> Option<&'a StoredTransaction> {
+    let block = i64::try_from(contribution.block_num).ok()?;
+    transactions.iter().find(|tx| {
+        tx.wallet_id == wallet.id
+            && tx.block_number == Some(block)
+            && same_account(tx.from_address.as_deref(), &wallet.address)
+            && tx.to_address.as_deref().is_some_and(is_pallet_account)
+    })
+}
+
+/// The stored transaction returning a fund's contributions: the first one
+/// after `after` from a pallet account to the wallet of exactly `amount`,
+/// the total contributed, that isn't already taken.
+pub fn find_release_leg<'a>(
+    wallet: &Wallet,
+    amount: Decimal,
+    decimals: u8,
+    after: DateTime<Utc>,
+    taken: &[String],
+    transactions: &'a [StoredTransaction],
+) -> Option<&'a StoredTransaction> {
+    transactions
+        .iter()
+        .filter(|tx| {
+            tx.wallet_id == wallet.id
+                && tx.status.as_deref() != Some("failed")
+                && !taken.contains(&tx.id)
+                && tx.timestamp.is_some_and(|at| at > after)
+                && same_account(tx.to_address.as_deref(), &wallet.address)
+                && tx.from_address.as_deref().is_some_and(is_pallet_account)
+                && tx_amount(tx, decimals) == Some(amount)
+        })
+        .min_by_key(|tx| tx.timestamp)
+}
+
+/// Reward payouts among `wallets`' transactions: tokens other than the
+/// relay chain's own, paid from a pallet account after `after`. Relay chain
+/// tokens arriving on a parachain are XCM moves, not rewards.
+pub fn find_rewards<'a>(
+    wallets: &[&Wallet],
+    relay_symbol: &str,
+    after: DateTime<Utc>,
+    transactions: &'a [StoredTransaction],
+) -> Vec<&'a StoredTransaction> {
+    transactions
+        .iter()
+        .filter(|tx| {
+            let Some(wallet) = wallets.iter().find(|w| w.id == tx.wallet_id) else {
+                return false;
+            };
+            tx.status.as_deref() != Some("failed")
+                && tx.tx_type.as_deref() != Some("bridge")
+                && tx.timestamp.is_some_and(|at| at > after)
+                && same_account(tx.to_address.as_deref(), &wallet.address)
+                && tx.from_address.as_deref().is_some_and(is_pallet_account)
+                && !tx
+                    .token_symbol
+                    .as_deref()
+                    .is_some_and(|s| s.eq_ignore_ascii_case(relay_symbol))
+        })
+        .collect()
+}
+
+/// Totals per relay chain token, in the order each token first appears.
+pub fn summarize(
+    contributions: &[CrowdloanContribution],
+    now: DateTime<Utc>,
+) -> Vec<CrowdloanBalance> {
+    let mut balances: Vec<CrowdloanBalance> = Vec::new();
+    for contribution in contributions {
+        let amount = parse_amount(&contribution.amount, None).unwrap_or_default();
+        let index = match balances
+            .iter()
+            .position(|b| b.symbol == contribution.symbol)
+        {
+            Some(index) => index,
+            None => {
+                balances.push(CrowdloanBalance {
+                    symbol: contribution.symbol.clone(),
+                    locked: Decimal::ZERO,
+                    withdrawable: Decimal::ZERO,
+                    released: Decimal::ZERO,
+                });
+                balances.len() - 1
+            }
+        };
+        let balance = &mut balances[index];
+        let released = contribution.release_transaction_id.is_some();
+        match contribution_status(released, contribution.lease_end_at, now) {
+            "released" => balance.released += amount,
+            "withdrawable" => balance.withdrawable += amount,
+            _ => balance.locked += amount,
+        }
+    }
+    balances
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn timestamp(secs: i64) -> DateTime<Utc> {
+    Utc.timestamp_opt(secs, 0).single().unwrap_or_else(Utc::now)
+}
+
+/// Sets a leg's type, counting it when its period is closed.
+async fn retype(
+    pool: &SqlitePool,
+    user_id: &str,
+    profile_id: &str,
+    tx: &StoredTransaction,
+    tx_type: &str,
+    result: &mut CrowdloanSyncResult,
+) -> Result<(), String> {
+    if retype_leg(pool, user_id, profile_id, tx, tx_type)
+        .await?
+        .is_none()
+    {
+        result.legs_in_closed_periods += 1;
+    }
+    Ok(())
+}
+
+async fn save_contribution(pool: &SqlitePool, row: &CrowdloanContribution) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        INSERT INTO crowdloan_contributions (
+            id, profile_id, wallet_id, relay_chain, para_id, fund_id, amount, symbol,
+            extrinsic_index, contributed_at, lease_end_block, lease_end_at, status,
+            contribution_transaction_id, release_transaction_id, released_at, created_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(wallet_id, extrinsic_index) DO UPDATE SET
+            lease_end_block = excluded.lease_end_block,
+            lease_end_at = excluded.lease_end_at,
+            status = excluded.status,
+            contribution_transaction_id = COALESCE(excluded.contribution_transaction_id, contribution_transaction_id),
+            release_transaction_id = COALESCE(excluded.release_transaction_id, release_transaction_id),
+            released_at = COALESCE(excluded.released_at, released_at)
+        "#,
+    )
+    .bind(&row.id)
+    .bind(&row.profile_id)
+    .bind(&row.wallet_id)
+    .bind(&row.relay_chain)
+    .bind(row.para_id)
+    .bind(&row.fund_id)
+    .bind(&row.amount)
+    .bind(&row.symbol)
+    .bind(&row.extrinsic_index)
+    .bind(row.contributed_at)
+    .bind(row.lease_end_block)
+    .bind(row.lease_end_at)
+    .bind(&row.status)
+    .bind(&row.contribution_transaction_id)
+    .bind(&row.release_transaction_id)
+    .bind(row.released_at)
+    .bind(row.created_at)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Fetches a wallet's contributions and the funds they went to.
+async fn fetch_contributions(
+    client: &SubscanClient,
+    address: &str,
+) -> Result<(u64, Vec<SubscanContribution>, HashMap<String, SubscanFund>), String> {
+    let current_block = client.get_block_number().await.map_err(|e| e.to_string())?;
+    let contributions = client
+        .get_crowdloan_contributions(address)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut funds = HashMap::new();
+    for contribution in &contributions {
+        if funds.contains_key(&contribution.fund_id) {
+            continue;
+        }
+        if let Some(fund) = client
+            .get_crowdloan_fund(&contribution.fund_id)
+            .await
+            .map_err(|e| e.to_string())?
+        {
+            funds.insert(contribution.fund_id.clone(), fund);
+        }
+    }
+
+    Ok((current_block, contributions, funds))
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Fetches the crowdloan contributions of the profile's Polkadot and Kusama
+/// wallets, matches their stored legs, returns, and reward payouts, and
+/// types those so locked contributions stay on the books.
+#[tauri::command]
+pub async fn sync_crowdloans(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<CrowdloanSyncResult, String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ManageWallets,
+    )
+    .await?;
+    let wallets = profile_wallets(&state.pool, &profile_id).await?;
+    let transactions = profile_transactions(&state.pool, &profile_id, i32::MAX, 0).await?;
+    let identities = load_identities(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let api_key = ApiKeyManager::get_api_key(ApiProvider::Subscan)
+        .ok()
+        .flatten();
+
+    let mut result = CrowdloanSyncResult::default();
+    let mut taken_releases = Vec::new();
+    for wallet in &wallets {
+        // Crowdloans only run on relay chains with slot leases.
+        let relay = wallet.chain.as_str();
+        if lease_end_block(relay, 0).is_none() {
+            continue;
+        }
+        let Some(config) = get_config_by_name(relay) else {
+            continue;
+        };
+        let Some(base_url) = config.subscan_url.as_deref() else {
+            continue;
+        };
+        let fetched = match SubscanClient::new(base_url, api_key.as_deref()) {
+            Ok(client) => fetch_contributions(&client, &wallet.address).await,
+            Err(e) => Err(e.to_string()),
+        };
+        let (current_block, contributions, funds) = match fetched {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                log_error!("Failed to fetch crowdloans for {}: {}", wallet.address, e);
+                result.failed_accounts.push(wallet.address.clone());
+                continue;
+            }
+        };
+
+        let now = Utc::now();
+        let decimals = config.native_decimals;
+        let amount_of =
+            |c: &SubscanContribution| parse_amount(&c.contributed, Some(i32::from(decimals)));
+
+        // A fund returns everything the wallet put in at once, after the
+        // last contribution.
+        let mut fund_totals: HashMap<&str, (Decimal, DateTime<Utc>)> = HashMap::new();
+        for contribution in &contributions {
+            let entry = fund_totals
+                .entry(contribution.fund_id.as_str())
+                .or_insert((Decimal::ZERO, DateTime::<Utc>::MIN_UTC));
+            entry.0 += amount_of(contribution).unwrap_or_default();
+            entry.1 = entry.1.max(timestamp(contribution.block_timestamp));
+        }
+        let mut releases = HashMap::new();
+        for (fund_id, (total, last)) in &fund_totals {
+            if let Some(tx) = find_release_leg(
+                wallet,
+                *total,
+                decimals,
+                *last,
+                &taken_releases,
+                &transactions,
+            ) {
+                taken_releases.push(tx.id.clone());
+                releases.insert(*fund_id, tx);
+                result.releases += 1;
+            }
+        }
+
+        for contribution in &contributions {
+            let Some(amount) = amount_of(contribution) else {
+                continue;
+            };
+            let lease_end = funds
+                .get(&contribution.fund_id)
+                .and_then(|f| lease_end_block(relay, f.last_period));
+            let lease_end_at = lease_end.map(|block| block_time(block, current_block, now));
+            let leg = find_contribution_leg(contribution, wallet, &transactions);
+            let release = releases.get(contribution.fund_id.as_str()).copied();
+
+            save_contribution(
+                &state.pool,
+                &CrowdloanContribution {
+                    id: Uuid::new_v4().to_string(),
+                    profile_id: profile_id.clone(),
+                    wallet_id: wallet.id.clone(),
+                    relay_chain: relay.to_string(),
+                    para_id: i64::from(contribution.para_id),
+                    fund_id: contribution.fund_id.clone(),
+                    amount: amount.normalize().to_string(),
+                    symbol: config.native_symbol.clone(),
+                    extrinsic_index: contribution.extrinsic_index.clone(),
+                    contributed_at: timestamp(contribution.block_timestamp),
+                    lease_end_block: lease_end.and_then(|b| i64::try_from(b).ok()),
+                    lease_end_at,
+                    status: contribution_status(release.is_some(), lease_end_at, now).to_string(),
+                    contribution_transaction_id: leg.map(|tx| tx.id.clone()),
+                    release_transaction_id: release.map(|tx| tx.id.clone()),
+                    released_at: release.and_then(|tx| tx.timestamp),
+                    created_at: now,
+                },
+            )
+            .await?;
+            result.contributions += 1;
+
+            if let Some(tx) = leg {
+                retype(
+                    &state.pool,
+                    &user_id,
+                    &profile_id,
+                    tx,
+                    "crowdloan_contribution",
+                    &mut result,
+                )
+                .await?;
+            }
+        }
+        for tx in releases.values() {
+            retype(
+                &state.pool,
+                &user_id,
+                &profile_id,
+                tx,
+                "crowdloan_release",
+                &mut result,
+            )
+            .await?;
+        }
+
+        // Rewards arrive on the parachain, in any wallet of the same account.
+        let Some(identity) = find_identity(&identities, &wallet.address) else {
+            continue;
+        };
+        let mut first_by_para: HashMap<u32, DateTime<Utc>> = HashMap::new();
+        for contribution in &contributions {
+            let at = timestamp(contribution.block_timestamp);
+            let first = first_by_para.entry(contribution.para_id).or_insert(at);
+            *first = (*first).min(at);
+        }
+        for (para_id, first) in first_by_para {
+            let Some(chain) = chain_for_para(relay, para_id) else {
+                continue;
+            };
+            let para_wallets: Vec<&Wallet> = identity
+                .wallets
+                .iter()
+                .filter(|w| w.chain == chain)
+                .collect();
+            let chain_decimals = get_config_by_name(chain).map_or(18, |c| c.native_decimals);
+
+            for tx in find_rewards(&para_wallets, &config.native_symbol, first, &transactions) {
+                let inserted = sqlx::query(
+                    r#"
+                    INSERT INTO crowdloan_rewards (
+                        transaction_id, profile_id, relay_chain, para_id, chain, symbol,
+                        amount, received_at, created_at
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    ON CONFLICT(transaction_id) DO NOTHING
+                    "#,
+                )
+                .bind(&tx.id)
+                .bind(&profile_id)
+                .bind(relay)
+                .bind(i64::from(para_id))
+                .bind(chain)
+                .bind(tx.token_symbol.as_deref().unwrap_or_default())
+                .bind(
+                    tx_amount(tx, chain_decimals)
+                        .unwrap_or_default()
+                        .normalize()
+                        .to_string(),
+                )
+                .bind(tx.timestamp.unwrap_or(now))
+                .bind(now)
+                .execute(&state.pool)
+                .await
+                .map_err(|e| e.to_string())?;
+                if inserted.rows_affected() > 0 {
+                    result.rewards += 1;
+                }
+                retype(
+                    &state.pool,
+                    &user_id,
+                    &profile_id,
+                    tx,
+                    "crowdloan_reward",
+                    &mut result,
+                )
+                .await?;
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Returns a profile's crowdloan contributions and rewards, with locked,
+/// withdrawable, and released totals and the leases ending next.
+#[tauri::command]
+pub async fn get_crowdloan_status(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<CrowdloanStatus, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let mut contributions = sqlx::query_as::<_, CrowdloanContribution>(
+        "SELECT * FROM crowdloan_contributions WHERE profile_id = ? ORDER BY contributed_at DESC",
+    )
+    .bind(&profile_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let rewards = sqlx::query_as::<_, CrowdloanReward>(
+        "SELECT * FROM crowdloan_rewards WHERE profile_id = ? ORDER BY received_at DESC",
+    )
+    .bind(&profile_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Leases may have ended since the last sync.
+    let now = Utc::now();
+    for contribution in &mut contributions {
+        contribution.status = contribution_status(
+            contribution.release_transaction_id.is_some(),
+            contribution.lease_end_at,
+            now,
+        )
+        .to_string();
+    }
+
+    let mut upcoming_releases: Vec<CrowdloanContribution> = contributions
+        .iter()
+        .filter(|c| c.status == "locked" && c.lease_end_at.is_some())
+        .cloned()
+        .collect();
+    upcoming_releases.sort_by_key(|c| c.lease_end_at);
+
+    Ok(CrowdloanStatus {
+        as_of: now,
+        balances: summarize(&contributions, now),
+        contributions,
+        rewards,
+        upcoming_releases,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::substrate::ss58;
+    use std::str::FromStr;
+
+    const WALLET: &str = "12ibPmtvhZg4hheGLyyjudYNSi9XLVr3VmSPJvLrEgEPKiHJ";
+    const ACALA: &str = "22bByhJxYURLGHguQJmygeCcuZaTtL9FPAyhTkmrsEBG94QJ";
+    const OTHER: &str = "13UVJyLnbVp9x5XDyJv8rGmA8ZtsNkzhuFiPH8VxZ1LUmjkT";
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn fund_account() -> String {
+        let mut key = [0u8; 32];
+        key[..12].copy_from_slice(b"modlpy/cfund");
+        key[12..16].copy_from_slice(&6u32.to_le_bytes());
+        ss58::encode(&key, 0).unwrap()
+    }
+
+    fn wallet(id: &str, chain: &str, address: &str) -> Wallet {
+        Wallet {
+            id: id.to_string(),
+            profile_id: "p1".to_string(),
+            address: address.to_string(),
+            chain: chain.to_string(),
+            name: None,
+            wallet_type: "software".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+        }
+    }
+
+    fn tx(id: &str, block: i64, secs: i64, from: &str, to: &str, value: &str) -> StoredTransaction {
+        StoredTransaction {
+            id: id.to_string(),
+            wallet_id: "w1".to_string(),
+            hash: format!("0x{}", id),
+            block_number: Some(block),
+            timestamp: Utc.timestamp_opt(secs, 0).single(),
+            from_address: Some(from.to_string()),
+            to_address: Some(to.to_string()),
+            value: Some(value.to_string()),
+            fee: None,
+            status: Some("success".to_string()),
+            tx_type: Some("transfer".to_string()),
+            token_symbol: Some("DOT".to_string()),
+            token_decimals: None,
+            chain: "polkadot".to_string(),
+            raw_data: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn contribution(block: u64, contributed: &str) -> SubscanContribution {
+        SubscanContribution {
+            fund_id: "2000-6".to_string(),
+            para_id: 2000,
+            who: WALLET.to_string(),
+            contributed: contributed.to_string(),
+            block_num: block,
+            block_timestamp: 1_636_934_400,
+            extrinsic_index: format!("{}-3", block),
+        }
+    }
+
+    #[test]
+    fn test_contribution_and_release_legs() {
+        let fund = fund_account();
+        let wallet = wallet("w1", "polkadot", WALLET);
+        let transactions = vec![
+            tx(
+                "t1",
+                7_701_233,
+                1_636_934_394,
+                WALLET,
+                OTHER,
+                "1000000000000",
+            ),
+            tx(
+                "t2",
+                7_701_234,
+                1_636_934_400,
+                WALLET,
+                &fund,
+                "1000000000000",
+            ),
+            tx(
+                "t3",
+                17_860_000,
+                1_698_000_000,
+                &fund,
+                WALLET,
+                "999000000000",
+            ),
+            tx(
+                "t4",
+                17_860_100,
+                1_698_000_600,
+                &fund,
+                WALLET,
+                "1000000000000",
+            ),
+        ];
+
+        let leg = find_contribution_leg(
+            &contribution(7_701_234, "1000000000000"),
+            &wallet,
+            &transactions,
+        );
+        assert_eq!(leg.unwrap().id, "t2");
+
+        let after = Utc.timestamp_opt(1_636_934_400, 0).unwrap();
+        let release = find_release_leg(&wallet, dec("100"), 10, after, &[], &transactions);
+        assert_eq!(release.unwrap().id, "t4");
+        let taken = vec!["t4".to_string()];
+        assert!(find_release_leg(&wallet, dec("100"), 10, after, &taken, &transactions).is_none());
+    }
+
+    #[test]
+    fn test_rewards_exclude_relay_token() {
+        let pot = ss58::encode(
+            &{
+                let mut key = [0u8; 32];
+                key[..8].copy_from_slice(b"modlaca/");
+                key
+            },
+            10,
+        )
+        .unwrap();
+        let acala = wallet("w2", "acala", ACALA);
+        let mut reward = tx("t5", 100, 1_640_000_000, &pot, ACALA, "3000000000000");
+        reward.wallet_id = "w2".to_string();
+        reward.token_symbol = Some("ACA".to_string());
+        let mut xcm = reward.clone();
+        xcm.id = "t6".to_string();
+        xcm.token_symbol = Some("DOT".to_string());
+        let mut early = reward.clone();
+        early.id = "t7".to_string();
+        early.timestamp = Utc.timestamp_opt(1_600_000_000, 0).single();
+
+        let transactions = vec![reward, xcm, early];
+        let after = Utc.timestamp_opt(1_636_934_400, 0).unwrap();
+        let rewards = find_rewards(&[&acala], "DOT", after, &transactions);
+        let ids: Vec<&str> = rewards.iter().map(|tx| tx.id.as_str()).collect();
+        assert_eq!(ids, vec!["t5"]);
+    }
+
+    #[test]
+    fn test_status_and_balances() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let ended = Utc.timestamp_opt(1_698_000_000, 0).single();
+        let ahead = Utc.timestamp_opt(1_800_000_000, 0).single();
+        assert_eq!(contribution_status(false, ahead, now), "locked");
+        assert_eq!(contribution_status(false, ended, now), "withdrawable");
+        assert_eq!(contribution_status(true, ended, now), "released");
+        assert_eq!(contribution_status(false, None, now), "locked");
+
+        let row = |amount: &str, lease_end_at, released: bool| CrowdloanContribution {
+            id: "c".to_string(),
+            profile_id: "p1".to_string(),
+            wallet_id: "w1".to_string(),
+            relay_chain: "polkadot".to_string(),
+            para_id: 2000,
+            fund_id: "2000-6".to_string(),
+            amount: amount.to_string(),
+            symbol: "DOT".to_string(),
+            extrinsic_index: "1-1".to_string(),
+            contributed_at: now,
+            lease_end_block: None,
+            lease_end_at,
+            status: "locked".to_string(),
+            contribution_transaction_id: None,
+            release_transaction_id: released.then(|| "t".to_string()),
+            released_at: None,
+            created_at: now,
+        };
+        let balances = summarize(
+            &[
+                row("100", ahead, false),
+                row("25.5", ahead, false),
+                row("40", ended, false),
+                row("10", ended, true),
+            ],
+            now,
+        );
+        assert_eq!(
+            balances,
+            vec![CrowdloanBalance {
+                symbol: "DOT".to_string(),
+                locked: dec("125.5"),
+                withdrawable: dec("40"),
+                released: dec("10"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_block_time() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        assert_eq!(
+            block_time(17_856_100, 17_856_000, now),
+            Utc.timestamp_opt(1_700_000_600, 0).unwrap()
+        );
+        assert!(block_time(17_000_000, 17_856_000, now) < now);
+    }
+}
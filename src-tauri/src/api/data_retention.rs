@@ -0,0 +1,218 @@
+//! Data retention policy for raw transaction payloads.
+//!
+//! Raw API responses are the bulk of a long-running database. When a
+//! retention period is set, payloads older than it are archived to a gzip
+//! file under the app data directory and removed from the database, keeping
+//! every normalized field.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager, State};
+
+use super::persistence::DatabaseState;
+use crate::db::retention::{self, PrunePreview, PruneReport, ARCHIVE_DIR};
+use crate::log_error;
+use crate::storage::settings_store;
+
+/// Settings key for the retention policy.
+pub const SETTINGS_KEY: &str = "data_retention";
+
+/// Settings key for the most recent prune report.
+const LAST_PRUNE_KEY: &str = "data_retention_last_prune";
+
+/// Shortest retention period accepted, in months.
+const MIN_RETENTION_MONTHS: u32 = 1;
+
+/// Delay before the first scheduled prune after startup.
+const PRUNE_INITIAL_DELAY: Duration = Duration::from_secs(600);
+
+/// How often the retention policy is applied.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// How long raw transaction payloads are kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionSettings {
+    /// Months after which raw payloads are removed; `None` keeps them forever.
+    pub strip_raw_after_months: Option<u32>,
+    /// Whether removed payloads are archived to a compressed file first.
+    #[serde(default = "default_archive")]
+    pub archive: bool,
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            strip_raw_after_months: None,
+            archive: true,
+        }
+    }
+}
+
+fn default_archive() -> bool {
+    true
+}
+
+/// Retention settings with the most recent prune, as shown in settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionStatus {
+    /// Current policy.
+    pub settings: RetentionSettings,
+    /// Result of the most recent prune, if any has run.
+    pub last_prune: Option<PruneReport>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Loads the retention policy, or the default if none is saved.
+pub async fn load_settings(pool: &SqlitePool) -> Result<RetentionSettings, String> {
+    settings_store::get_setting_json(pool, SETTINGS_KEY)
+        .await
+        .map(Option::unwrap_or_default)
+        .map_err(|e| e.to_string())
+}
+
+fn validate_months(months: u32) -> Result<(), String> {
+    if months < MIN_RETENTION_MONTHS {
+        return Err(format!(
+            "Retention period must be at least {} month",
+            MIN_RETENTION_MONTHS
+        ));
+    }
+    Ok(())
+}
+
+fn archive_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(ARCHIVE_DIR))
+        .map_err(|e| e.to_string())
+}
+
+/// Prunes payloads older than `months` months and records the report.
+async fn run_prune(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    months: u32,
+    archive: bool,
+) -> Result<PruneReport, String> {
+    let dir = if archive {
+        Some(archive_dir(app)?)
+    } else {
+        None
+    };
+
+    let report = retention::prune(pool, months, dir.as_deref())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    settings_store::set_setting_json(pool, LAST_PRUNE_KEY, &report)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(report)
+}
+
+/// Starts the background task that applies the retention policy once a day.
+pub fn spawn_retention_loop(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(PRUNE_INITIAL_DELAY).await;
+        loop {
+            let pool = app.state::<DatabaseState>().pool.clone();
+            match load_settings(&pool).await {
+                Ok(RetentionSettings {
+                    strip_raw_after_months: Some(months),
+                    archive,
+                }) => {
+                    if let Err(e) = run_prune(&app, &pool, months, archive).await {
+                        log_error!("Failed to apply data retention policy: {}", e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => log_error!("Failed to load data retention settings: {}", e),
+            }
+            tokio::time::sleep(PRUNE_INTERVAL).await;
+        }
+    });
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns the retention policy and the most recent prune.
+#[tauri::command]
+pub async fn get_data_retention(
+    state: State<'_, DatabaseState>,
+) -> Result<RetentionStatus, String> {
+    let settings = load_settings(&state.pool).await?;
+    let last_prune = settings_store::get_setting_json(&state.pool, LAST_PRUNE_KEY)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(RetentionStatus {
+        settings,
+        last_prune,
+    })
+}
+
+/// Saves the retention policy. It is applied by the daily background task,
+/// or immediately with [`prune_raw_data`].
+#[tauri::command]
+pub async fn save_data_retention(
+    state: State<'_, DatabaseState>,
+    settings: RetentionSettings,
+) -> Result<(), String> {
+    if let Some(months) = settings.strip_raw_after_months {
+        validate_months(months)?;
+    }
+
+    settings_store::set_setting_json(&state.pool, SETTINGS_KEY, &settings)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Reports how many payloads a prune would remove and their size.
+///
+/// Uses the saved retention period unless `months` is given.
+#[tauri::command]
+pub async fn preview_raw_data_prune(
+    state: State<'_, DatabaseState>,
+    months: Option<u32>,
+) -> Result<PrunePreview, String> {
+    let months = months
+        .or(load_settings(&state.pool).await?.strip_raw_after_months)
+        .ok_or("No retention period is set")?;
+    validate_months(months)?;
+
+    retention::preview(&state.pool, months)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Applies the retention policy now and reports the space reclaimed.
+///
+/// Uses the saved retention period unless `months` is given.
+#[tauri::command]
+pub async fn prune_raw_data(
+    app: AppHandle,
+    state: State<'_, DatabaseState>,
+    months: Option<u32>,
+) -> Result<PruneReport, String> {
+    let settings = load_settings(&state.pool).await?;
+    let months = months
+        .or(settings.strip_raw_after_months)
+        .ok_or("No retention period is set")?;
+    validate_months(months)?;
+
+    run_prune(&app, &state.pool, months, settings.archive).await
+}
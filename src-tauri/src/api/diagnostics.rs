@@ -0,0 +1,68 @@
+//! Crash reports and diagnostics bundles.
+//!
+//! Commands for reviewing the crash reports written by the panic hook and
+//! exporting them, with this session's recent log lines, as a single JSON
+//! file the user can attach to a bug report. Nothing is uploaded.
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::diagnostics::{self, CrashReport};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// What an exported diagnostics bundle contains.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsExport {
+    /// File the bundle was written to.
+    pub path: String,
+    /// Number of crash reports included.
+    pub crash_report_count: usize,
+    /// Number of log lines included.
+    pub log_line_count: usize,
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// List stored crash reports, newest first
+#[tauri::command]
+pub async fn get_crash_reports() -> Result<Vec<CrashReport>, String> {
+    match diagnostics::crash_report_dir() {
+        Some(dir) => diagnostics::read_crash_reports(&dir).map_err(|e| e.to_string()),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Export crash reports and recent log lines to `path`
+///
+/// # Arguments
+/// * `path` - Destination file path.
+#[tauri::command]
+pub async fn export_diagnostics(path: String) -> Result<DiagnosticsExport, String> {
+    let dir = diagnostics::crash_report_dir().ok_or("Crash reporting is not set up")?;
+    let bundle = diagnostics::diagnostics_bundle(&dir).map_err(|e| e.to_string())?;
+
+    let contents = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    Ok(DiagnosticsExport {
+        path,
+        crash_report_count: bundle.crash_reports.len(),
+        log_line_count: bundle.recent_log.len(),
+    })
+}
+
+/// Delete stored crash reports, e.g. once they have been sent
+///
+/// Returns the number of reports deleted.
+#[tauri::command]
+pub async fn clear_crash_reports() -> Result<usize, String> {
+    match diagnostics::crash_report_dir() {
+        Some(dir) => diagnostics::clear_crash_reports(&dir).map_err(|e| e.to_string()),
+        None => Ok(0),
+    }
+}
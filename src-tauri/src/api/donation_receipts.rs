@@ -0,0 +1,518 @@
+//! Donation receipts for charities receiving crypto.
+//!
+//! A receipt records the donor, asset, amount, and fiat value at the time of
+//! receipt (taken from a price override or looked up through the price
+//! service unless a price is supplied), along with the endpoint the price was
+//! read from and when, and is numbered sequentially per profile per calendar
+//! year.
+//! Receipts can be rendered to PDF for sending to donors.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::price_feeds::{PriceQuote, PriceService};
+use super::price_overrides::find_override;
+use super::profile_scope::{authenticate, authorize_profile, authorize_record};
+use crate::core::auth_state::AuthState;
+use crate::core::pdf::{Font, PdfDocument, PAGE_WIDTH};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A stored donation receipt.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DonationReceipt {
+    /// Unique identifier for the receipt.
+    pub id: String,
+    /// Profile (organization) that issued the receipt.
+    pub profile_id: String,
+    /// Human-readable receipt number, e.g. `2025-00042`.
+    pub receipt_number: String,
+    /// Calendar year the donation was received.
+    pub receipt_year: i32,
+    /// Sequence within the profile and year, starting at 1.
+    pub sequence: i64,
+    /// Donor entity, if the donor is on file.
+    pub donor_entity_id: Option<String>,
+    /// Donor name as printed on the receipt.
+    pub donor_name: String,
+    /// Asset symbol (e.g. ETH, DOT).
+    pub asset_symbol: String,
+    /// CoinGecko coin ID used for valuation, if any.
+    pub coin_id: Option<String>,
+    /// Amount donated, in asset units.
+    pub amount: String,
+    /// Fiat currency of the valuation.
+    pub fiat_currency: String,
+    /// Unit price of the asset at receipt time.
+    pub fiat_price: String,
+    /// Total fiat value of the donation.
+    pub fiat_value: String,
+    /// Where the price came from (a price provider name, `override`, or `manual`).
+    pub price_source: String,
+    /// Endpoint the price was read from, if recorded.
+    pub price_endpoint: Option<String>,
+    /// When the price was retrieved, if recorded.
+    pub price_retrieved_at: Option<DateTime<Utc>>,
+    /// Chain the donation was received on.
+    pub chain: Option<String>,
+    /// Transaction hash of the donation.
+    pub tx_hash: Option<String>,
+    /// When the donation was received.
+    pub received_at: DateTime<Utc>,
+    /// Optional notes printed on the receipt.
+    pub notes: Option<String>,
+    /// Timestamp when the receipt was created.
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Input for generating a donation receipt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DonationReceiptInput {
+    /// Profile issuing the receipt.
+    pub profile_id: String,
+    /// Donor entity ID. Either this or `donor_name` is required.
+    pub donor_entity_id: Option<String>,
+    /// Donor name, used when no entity is on file or to override it.
+    pub donor_name: Option<String>,
+    /// Asset symbol.
+    pub asset_symbol: String,
+    /// CoinGecko coin ID for valuation. Required unless `fiat_price` is given.
+    pub coin_id: Option<String>,
+    /// Amount donated, in asset units.
+    pub amount: String,
+    /// Fiat currency for the valuation. Defaults to USD.
+    pub fiat_currency: Option<String>,
+    /// Unit price override, skipping the price service.
+    pub fiat_price: Option<String>,
+    /// Chain the donation was received on.
+    pub chain: Option<String>,
+    /// Transaction hash of the donation.
+    pub tx_hash: Option<String>,
+    /// When the donation was received (RFC 3339).
+    pub received_at: String,
+    /// Optional notes printed on the receipt.
+    pub notes: Option<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Formats a receipt number from its year and sequence.
+pub fn format_receipt_number(year: i32, sequence: i64) -> String {
+    format!("{}-{:05}", year, sequence)
+}
+
+/// Computes the fiat value of a donation, rounded to cents.
+pub fn compute_fiat_value(amount: &str, price: &str) -> Result<Decimal, String> {
+    let amount = Decimal::from_str(amount).map_err(|_| format!("Invalid amount: {}", amount))?;
+    let price = Decimal::from_str(price).map_err(|_| format!("Invalid price: {}", price))?;
+    Ok((amount * price).round_dp(2))
+}
+
+async fn resolve_donor_name(
+    pool: &SqlitePool,
+    input: &DonationReceiptInput,
+) -> Result<String, String> {
+    if let Some(name) = input.donor_name.as_ref().filter(|n| !n.trim().is_empty()) {
+        return Ok(name.clone());
+    }
+    let Some(entity_id) = &input.donor_entity_id else {
+        return Err("A donor entity or donor name is required".to_string());
+    };
+
+    let row: Option<(String, Option<String>)> =
+        sqlx::query_as("SELECT name, display_name FROM entities WHERE id = ? AND profile_id = ?")
+            .bind(entity_id)
+            .bind(&input.profile_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    row.map(|(name, display_name)| display_name.unwrap_or(name))
+        .ok_or_else(|| "Donor entity not found".to_string())
+}
+
+/// Price source recorded for prices entered by hand.
+const MANUAL_SOURCE: &str = "manual";
+
+async fn fetch_receipt_price(
+    pool: &SqlitePool,
+    input: &DonationReceiptInput,
+    received_at: DateTime<Utc>,
+    currency: &str,
+) -> Result<PriceQuote, String> {
+    if let Some(price) = &input.fiat_price {
+        Decimal::from_str(price).map_err(|_| format!("Invalid price: {}", price))?;
+        return Ok(PriceQuote {
+            price: price.clone(),
+            provider: MANUAL_SOURCE.to_string(),
+            endpoint: MANUAL_SOURCE.to_string(),
+            retrieved_at: Utc::now(),
+        });
+    }
+
+    // A price override on the coin or the symbol beats provider data.
+    let tokens = input
+        .coin_id
+        .iter()
+        .chain(std::iter::once(&input.asset_symbol));
+    for token in tokens {
+        let found = find_override(pool, &input.profile_id, token, currency, received_at)
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Some(found) = found {
+            return Ok(found.quote());
+        }
+    }
+
+    let coin_id = input
+        .coin_id
+        .as_ref()
+        .ok_or_else(|| "A coin ID or a manual fiat price is required".to_string())?;
+
+    PriceService::shared()?
+        .historical_price(coin_id, received_at.date_naive(), &currency.to_lowercase())
+        .await
+}
+
+/// Renders a receipt to PDF bytes.
+pub fn render_receipt_pdf(receipt: &DonationReceipt, organization: &str) -> Vec<u8> {
+    let mut doc =
+        PdfDocument::new().with_title(&format!("Donation Receipt {}", receipt.receipt_number));
+    let left = 72.0;
+    let right = PAGE_WIDTH - 72.0;
+    let mut y = 720.0;
+
+    doc.text(left, y, 20.0, Font::Bold, organization);
+    y -= 28.0;
+    doc.text(left, y, 14.0, Font::Bold, "Donation Receipt");
+    doc.text(
+        right - 150.0,
+        y,
+        11.0,
+        Font::Regular,
+        &format!("No. {}", receipt.receipt_number),
+    );
+    y -= 12.0;
+    doc.hline(left, right, y, 0.75);
+    y -= 28.0;
+
+    let value = format!(
+        "{} {}",
+        receipt.fiat_value,
+        receipt.fiat_currency.to_uppercase()
+    );
+    let price = format!(
+        "{} {} per {} ({})",
+        receipt.fiat_price,
+        receipt.fiat_currency.to_uppercase(),
+        receipt.asset_symbol,
+        receipt.price_source
+    );
+    let amount = format!("{} {}", receipt.amount, receipt.asset_symbol);
+    let received = receipt.received_at.format("%Y-%m-%d %H:%M UTC").to_string();
+    let mut rows: Vec<(&str, &str)> = vec![
+        ("Donor", receipt.donor_name.as_str()),
+        ("Date received", received.as_str()),
+        ("Asset donated", amount.as_str()),
+        ("Unit price", price.as_str()),
+        ("Fair market value", value.as_str()),
+    ];
+    if let Some(chain) = &receipt.chain {
+        rows.push(("Network", chain.as_str()));
+    }
+    if let Some(hash) = &receipt.tx_hash {
+        rows.push(("Transaction", hash.as_str()));
+    }
+    let retrieved = receipt
+        .price_retrieved_at
+        .map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string());
+    if let Some(endpoint) = &receipt.price_endpoint {
+        rows.push(("Price source", endpoint.as_str()));
+    }
+    if let Some(retrieved) = &retrieved {
+        rows.push(("Price retrieved", retrieved.as_str()));
+    }
+
+    for (label, value) in rows {
+        doc.text(left, y, 10.0, Font::Bold, label);
+        doc.text(left + 130.0, y, 10.0, Font::Regular, value);
+        y -= 20.0;
+    }
+
+    y -= 16.0;
+    doc.text(
+        left,
+        y,
+        10.0,
+        Font::Regular,
+        "No goods or services were provided in exchange for this contribution.",
+    );
+    if let Some(notes) = &receipt.notes {
+        y -= 20.0;
+        doc.text(left, y, 10.0, Font::Regular, notes);
+    }
+
+    y -= 40.0;
+    doc.hline(left, right, y, 0.5);
+    doc.text(
+        left,
+        y - 14.0,
+        8.0,
+        Font::Regular,
+        &format!("Issued {}", Utc::now().format("%Y-%m-%d")),
+    );
+
+    doc.to_bytes()
+}
+
+async fn write_receipt_pdf(
+    pool: &SqlitePool,
+    receipt: &DonationReceipt,
+    path: &str,
+) -> Result<(), String> {
+    let organization: Option<String> = sqlx::query_scalar("SELECT name FROM profiles WHERE id = ?")
+        .bind(&receipt.profile_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let bytes = render_receipt_pdf(receipt, organization.as_deref().unwrap_or_default());
+    std::fs::write(path, bytes).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Generates a donation receipt, valuing the donation in fiat at the time of
+/// receipt, and optionally renders it to a PDF file.
+///
+/// # Arguments
+/// * `input` - Donation details.
+/// * `output_path` - Optional path to write the receipt PDF to.
+///
+/// Requires the owner, admin, or preparer role on the profile.
+#[tauri::command]
+pub async fn generate_donation_receipt(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    input: DonationReceiptInput,
+    output_path: Option<String>,
+) -> Result<DonationReceipt, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &input.profile_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    let received_at = DateTime::parse_from_rfc3339(&input.received_at)
+        .map_err(|_| format!("Invalid timestamp: {}", input.received_at))?
+        .with_timezone(&Utc);
+    let currency = input
+        .fiat_currency
+        .clone()
+        .unwrap_or_else(|| "USD".to_string())
+        .to_uppercase();
+
+    let donor_name = resolve_donor_name(&state.pool, &input).await?;
+    let quote = fetch_receipt_price(&state.pool, &input, received_at, &currency).await?;
+    let fiat_value = compute_fiat_value(&input.amount, &quote.price)?;
+
+    let id = Uuid::new_v4().to_string();
+    let year = received_at.year();
+
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+
+    let sequence: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(MAX(sequence), 0) + 1 FROM donation_receipts WHERE profile_id = ? AND receipt_year = ?",
+    )
+    .bind(&input.profile_id)
+    .bind(year)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO donation_receipts (
+            id, profile_id, receipt_number, receipt_year, sequence, donor_entity_id,
+            donor_name, asset_symbol, coin_id, amount, fiat_currency, fiat_price,
+            fiat_value, price_source, price_endpoint, price_retrieved_at, chain, tx_hash,
+            received_at, notes, created_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(&input.profile_id)
+    .bind(format_receipt_number(year, sequence))
+    .bind(year)
+    .bind(sequence)
+    .bind(&input.donor_entity_id)
+    .bind(&donor_name)
+    .bind(&input.asset_symbol)
+    .bind(&input.coin_id)
+    .bind(&input.amount)
+    .bind(&currency)
+    .bind(&quote.price)
+    .bind(fiat_value.to_string())
+    .bind(&quote.provider)
+    .bind(&quote.endpoint)
+    .bind(quote.retrieved_at)
+    .bind(&input.chain)
+    .bind(&input.tx_hash)
+    .bind(received_at)
+    .bind(&input.notes)
+    .bind(Utc::now())
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    let receipt =
+        sqlx::query_as::<_, DonationReceipt>("SELECT * FROM donation_receipts WHERE id = ?")
+            .bind(&id)
+            .fetch_one(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    if let Some(path) = output_path {
+        write_receipt_pdf(&state.pool, &receipt, &path).await?;
+    }
+
+    Ok(receipt)
+}
+
+/// Lists donation receipts for a profile, optionally limited to one year.
+/// Requires any role on the profile.
+#[tauri::command]
+pub async fn get_donation_receipts(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    year: Option<i32>,
+) -> Result<Vec<DonationReceipt>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as::<_, DonationReceipt>(
+        r#"
+        SELECT * FROM donation_receipts
+        WHERE profile_id = ? AND (? IS NULL OR receipt_year = ?)
+        ORDER BY receipt_year DESC, sequence DESC
+        "#,
+    )
+    .bind(&profile_id)
+    .bind(year)
+    .bind(year)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Renders an existing donation receipt to a PDF file. Requires a role on
+/// the receipt's profile that may export.
+#[tauri::command]
+pub async fn export_donation_receipt_pdf(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    id: String,
+    path: String,
+) -> Result<(), String> {
+    let user_id = authenticate(&auth, &token)?;
+    let not_found = "Donation receipt not found";
+    let receipt =
+        sqlx::query_as::<_, DonationReceipt>("SELECT * FROM donation_receipts WHERE id = ?")
+            .bind(&id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| not_found.to_string())?;
+    authorize_record(
+        &state.pool,
+        &user_id,
+        &receipt.profile_id,
+        Permission::Export,
+        not_found,
+    )
+    .await?;
+
+    write_receipt_pdf(&state.pool, &receipt, &path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_format_receipt_number() {
+        assert_eq!(format_receipt_number(2025, 1), "2025-00001");
+        assert_eq!(format_receipt_number(2025, 123456), "2025-123456");
+    }
+
+    #[test]
+    fn test_compute_fiat_value_rounds_to_cents() {
+        let value = compute_fiat_value("1.5", "2345.678").unwrap();
+        assert_eq!(value, Decimal::from_str("3518.52").unwrap());
+        assert!(compute_fiat_value("abc", "1").is_err());
+    }
+
+    #[test]
+    fn test_render_receipt_pdf() {
+        let receipt = DonationReceipt {
+            id: "r1".to_string(),
+            profile_id: "p1".to_string(),
+            receipt_number: "2025-00007".to_string(),
+            receipt_year: 2025,
+            sequence: 7,
+            donor_entity_id: None,
+            donor_name: "Ada Lovelace".to_string(),
+            asset_symbol: "ETH".to_string(),
+            coin_id: Some("ethereum".to_string()),
+            amount: "1.5".to_string(),
+            fiat_currency: "USD".to_string(),
+            fiat_price: "2345.678".to_string(),
+            fiat_value: "3518.52".to_string(),
+            price_source: "coingecko".to_string(),
+            price_endpoint: Some("https://api.coingecko.com/api/v3/coins/{id}/history".to_string()),
+            price_retrieved_at: Some(Utc.with_ymd_and_hms(2025, 6, 2, 9, 0, 0).unwrap()),
+            chain: Some("ethereum".to_string()),
+            tx_hash: Some("0xabc".to_string()),
+            received_at: Utc.with_ymd_and_hms(2025, 6, 1, 10, 30, 0).unwrap(),
+            notes: None,
+            created_at: None,
+        };
+        let bytes = render_receipt_pdf(&receipt, "Give Foundation");
+        let contains = |needle: &[u8]| bytes.windows(needle.len()).any(|w| w == needle);
+
+        assert!(bytes.starts_with(b"%PDF-1.4"));
+        assert!(contains(b"(No. 2025-00007) Tj"));
+        assert!(contains(b"(3518.52 USD) Tj"));
+        assert!(contains(b"(Ada Lovelace) Tj"));
+        assert!(contains(b"(2025-06-02 09:00 UTC) Tj"));
+    }
+}
@@ -0,0 +1,683 @@
+//! Duplicate records of the same transfer.
+//!
+//! A withdrawal imported from an exchange and the deposit a chain sync
+//! stores for it are one movement recorded twice. Candidates are paired by
+//! asset, amount, and time: an outgoing record in one wallet and an incoming
+//! record in another, with the deposit no larger than the withdrawal and at
+//! most a small fee short of it. Merging a pair keeps one record and hides
+//! the other from listings and reports; the merge can be undone.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+
+use super::address_watch::native_currency;
+use super::audit_trail::{record_change, RecordType};
+use super::period_close::ensure_period_open;
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction, Wallet};
+use super::profile_scope::{authorize_profile, profile_wallets};
+use crate::chains::units::{from_smallest_units, parse_decimal};
+use crate::core::auth_state::AuthState;
+
+/// Largest gap between a withdrawal and its deposit, in seconds.
+const MATCH_WINDOW_SECS: i64 = 6 * 60 * 60;
+
+/// Largest share of a withdrawal that may be lost to fees before the deposit
+/// arrives, in basis points.
+const FEE_TOLERANCE_BPS: i64 = 200;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A withdrawal and a deposit that look like the same transfer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateCandidate {
+    /// Stored ID of the outgoing record.
+    pub withdrawal_id: String,
+    /// Wallet the outgoing record belongs to.
+    pub withdrawal_wallet_id: String,
+    /// Stored ID of the incoming record.
+    pub deposit_id: String,
+    /// Wallet the incoming record belongs to.
+    pub deposit_wallet_id: String,
+    /// Asset symbol, as recorded on the withdrawal.
+    pub asset: String,
+    /// Amount withdrawn, in whole units.
+    pub withdrawal_amount: Decimal,
+    /// Amount deposited, in whole units.
+    pub deposit_amount: Decimal,
+    /// Seconds between the two records.
+    pub seconds_apart: i64,
+    /// Whether both records carry the same transaction hash.
+    pub same_hash: bool,
+}
+
+/// A record hidden as a duplicate of another.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionMerge {
+    /// The hidden record.
+    pub duplicate_id: String,
+    /// The record that stands for the transfer.
+    pub kept_id: String,
+    /// Profile both records belong to.
+    pub profile_id: String,
+    /// User who merged the records.
+    pub merged_by: Option<String>,
+    /// When the records were merged.
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Out,
+    In,
+}
+
+/// One record's side of a transfer.
+struct Movement<'a> {
+    tx: &'a StoredTransaction,
+    direction: Direction,
+    asset: String,
+    amount: Decimal,
+    timestamp: DateTime<Utc>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Whether a record moves funds out of or into its wallet. Records whose
+/// addresses don't name the wallet, as exchange exports often don't, fall
+/// back to their type.
+fn direction(wallet_address: &str, tx: &StoredTransaction) -> Option<Direction> {
+    let is_wallet = |address: &Option<String>| {
+        address
+            .as_deref()
+            .is_some_and(|a| a.eq_ignore_ascii_case(wallet_address))
+    };
+    match (is_wallet(&tx.from_address), is_wallet(&tx.to_address)) {
+        (true, false) => Some(Direction::Out),
+        (false, true) => Some(Direction::In),
+        (true, true) => None,
+        (false, false) => match tx.tx_type.as_deref()?.to_lowercase().as_str() {
+            "withdrawal" | "withdraw" | "send" => Some(Direction::Out),
+            "deposit" | "receive" => Some(Direction::In),
+            _ => None,
+        },
+    }
+}
+
+/// Asset symbol and whole-unit amount of a record. Records without a token
+/// symbol move the chain's native currency.
+fn asset_amount(tx: &StoredTransaction) -> Option<(String, Decimal)> {
+    let (native_symbol, native_decimals) = native_currency(&tx.chain);
+    let symbol = tx.token_symbol.clone().unwrap_or(native_symbol);
+    let decimals = u8::try_from(tx.token_decimals.unwrap_or(native_decimals)).ok()?;
+    let amount = from_smallest_units(parse_decimal(tx.value.as_deref()?).ok()?, decimals)?;
+    (!amount.is_zero()).then_some((symbol, amount))
+}
+
+fn movements<'a>(wallets: &[Wallet], transactions: &'a [StoredTransaction]) -> Vec<Movement<'a>> {
+    let addresses: HashMap<&str, &str> = wallets
+        .iter()
+        .map(|w| (w.id.as_str(), w.address.as_str()))
+        .collect();
+
+    transactions
+        .iter()
+        .filter(|tx| tx.status.as_deref() != Some("failed"))
+        .filter_map(|tx| {
+            let direction = direction(addresses.get(tx.wallet_id.as_str())?, tx)?;
+            let (asset, amount) = asset_amount(tx)?;
+            Some(Movement {
+                tx,
+                direction,
+                asset,
+                amount,
+                timestamp: tx.timestamp?,
+            })
+        })
+        .collect()
+}
+
+/// Pairs withdrawals with deposits in other wallets that look like the same
+/// transfer. Each record is used in at most one pair; pairs sharing a hash
+/// are taken first, then the closest in amount and then in time.
+///
+/// Both ends of one on-chain transfer between the profile's own wallets are
+/// an internal transfer, not a duplicate, and are never paired.
+pub fn find_duplicate_candidates(
+    wallets: &[Wallet],
+    transactions: &[StoredTransaction],
+) -> Vec<DuplicateCandidate> {
+    let movements = movements(wallets, transactions);
+    let (withdrawals, deposits): (Vec<&Movement>, Vec<&Movement>) = movements
+        .iter()
+        .partition(|m| m.direction == Direction::Out);
+
+    let tolerance = Decimal::new(FEE_TOLERANCE_BPS, 4);
+    let mut pairs = Vec::new();
+    for out in &withdrawals {
+        for deposit in &deposits {
+            if out.tx.wallet_id == deposit.tx.wallet_id
+                || !out.asset.eq_ignore_ascii_case(&deposit.asset)
+            {
+                continue;
+            }
+            let same_hash = out.tx.hash.eq_ignore_ascii_case(&deposit.tx.hash);
+            if same_hash && out.tx.chain == deposit.tx.chain {
+                continue;
+            }
+            if deposit.amount > out.amount
+                || deposit.amount < out.amount * (Decimal::ONE - tolerance)
+            {
+                continue;
+            }
+            let seconds_apart = (deposit.timestamp - out.timestamp).num_seconds().abs();
+            if seconds_apart > MATCH_WINDOW_SECS {
+                continue;
+            }
+            pairs.push((out, deposit, same_hash, seconds_apart));
+        }
+    }
+    pairs.sort_by_key(|(out, deposit, same_hash, seconds_apart)| {
+        (!*same_hash, out.amount - deposit.amount, *seconds_apart)
+    });
+
+    let mut used = HashSet::new();
+    let mut candidates = Vec::new();
+    for (out, deposit, same_hash, seconds_apart) in pairs {
+        if used.contains(&out.tx.id) || used.contains(&deposit.tx.id) {
+            continue;
+        }
+        used.insert(&out.tx.id);
+        used.insert(&deposit.tx.id);
+        candidates.push(DuplicateCandidate {
+            withdrawal_id: out.tx.id.clone(),
+            withdrawal_wallet_id: out.tx.wallet_id.clone(),
+            deposit_id: deposit.tx.id.clone(),
+            deposit_wallet_id: deposit.tx.wallet_id.clone(),
+            asset: out.asset.clone(),
+            withdrawal_amount: out.amount,
+            deposit_amount: deposit.amount,
+            seconds_apart,
+            same_hash,
+        });
+    }
+    candidates
+}
+
+/// A profile's transactions that haven't been merged away, oldest first.
+/// Records already kept for a merge are left out too, so they aren't
+/// offered again.
+async fn unmerged_transactions(
+    pool: &SqlitePool,
+    profile_id: &str,
+) -> Result<Vec<StoredTransaction>, sqlx::Error> {
+    sqlx::query_as::<_, StoredTransaction>(
+        r#"
+        SELECT t.* FROM transactions t
+        INNER JOIN wallets w ON t.wallet_id = w.id
+        WHERE w.profile_id = ?
+          AND t.id NOT IN (SELECT duplicate_id FROM transaction_merges)
+          AND t.id NOT IN (SELECT kept_id FROM transaction_merges)
+        ORDER BY t.timestamp ASC
+        "#,
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// One of a profile's transactions, or an error if it isn't the profile's.
+async fn profile_transaction(
+    pool: &SqlitePool,
+    profile_id: &str,
+    transaction_id: &str,
+) -> Result<StoredTransaction, String> {
+    sqlx::query_as::<_, StoredTransaction>(
+        r#"
+        SELECT t.* FROM transactions t
+        INNER JOIN wallets w ON t.wallet_id = w.id
+        WHERE w.profile_id = ? AND t.id = ?
+        "#,
+    )
+    .bind(profile_id)
+    .bind(transaction_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Transaction not found: {}", transaction_id))
+}
+
+async fn ensure_open(
+    pool: &SqlitePool,
+    profile_id: &str,
+    tx: &StoredTransaction,
+) -> Result<(), String> {
+    match tx.timestamp {
+        Some(timestamp) => ensure_period_open(pool, Some(profile_id), timestamp.date_naive()).await,
+        None => Ok(()),
+    }
+}
+
+async fn load_merge(
+    pool: &SqlitePool,
+    profile_id: &str,
+    duplicate_id: &str,
+) -> Result<Option<TransactionMerge>, sqlx::Error> {
+    sqlx::query_as::<_, TransactionMerge>(
+        "SELECT * FROM transaction_merges WHERE profile_id = ? AND duplicate_id = ?",
+    )
+    .bind(profile_id)
+    .bind(duplicate_id)
+    .fetch_optional(pool)
+    .await
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns withdrawals and deposits across a profile's wallets that look
+/// like the same transfer recorded twice.
+#[tauri::command]
+pub async fn find_duplicate_transfers(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<DuplicateCandidate>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+
+    let wallets = profile_wallets(&state.pool, &profile_id).await?;
+    let transactions = unmerged_transactions(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(find_duplicate_candidates(&wallets, &transactions))
+}
+
+/// Returns the profile's merged records.
+#[tauri::command]
+pub async fn get_transaction_merges(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<TransactionMerge>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+
+    sqlx::query_as::<_, TransactionMerge>(
+        "SELECT * FROM transaction_merges WHERE profile_id = ? ORDER BY created_at DESC",
+    )
+    .bind(&profile_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Hides `duplicate_id` as a second record of the transfer `kept_id`
+/// records. Neither record may fall in a closed period or already be part
+/// of another merge.
+#[tauri::command]
+pub async fn merge_transactions(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    kept_id: String,
+    duplicate_id: String,
+) -> Result<TransactionMerge, String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    if kept_id == duplicate_id {
+        return Err("A transaction can't be merged into itself".to_string());
+    }
+
+    let kept = profile_transaction(&state.pool, &profile_id, &kept_id).await?;
+    let duplicate = profile_transaction(&state.pool, &profile_id, &duplicate_id).await?;
+    ensure_open(&state.pool, &profile_id, &kept).await?;
+    ensure_open(&state.pool, &profile_id, &duplicate).await?;
+
+    let (already_merged,): (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM transaction_merges
+        WHERE duplicate_id IN (?, ?) OR kept_id IN (?, ?)
+        "#,
+    )
+    .bind(&kept_id)
+    .bind(&duplicate_id)
+    .bind(&kept_id)
+    .bind(&duplicate_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    if already_merged > 0 {
+        return Err("One of the transactions is already part of a merge".to_string());
+    }
+
+    let merge = TransactionMerge {
+        duplicate_id,
+        kept_id,
+        profile_id,
+        merged_by: Some(user_id.clone()),
+        created_at: Utc::now(),
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO transaction_merges (duplicate_id, kept_id, profile_id, merged_by, created_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&merge.duplicate_id)
+    .bind(&merge.kept_id)
+    .bind(&merge.profile_id)
+    .bind(&merge.merged_by)
+    .bind(merge.created_at)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    record_change(
+        &state.pool,
+        Some(&user_id),
+        RecordType::TransactionMerge,
+        &merge.duplicate_id,
+        Some(&merge.profile_id),
+        None,
+        Some(&merge),
+    )
+    .await?;
+
+    Ok(merge)
+}
+
+/// Undoes a merge, so the hidden record counts again.
+#[tauri::command]
+pub async fn unmerge_transaction(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    duplicate_id: String,
+) -> Result<(), String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+
+    let merge = load_merge(&state.pool, &profile_id, &duplicate_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Transaction is not merged: {}", duplicate_id))?;
+    let duplicate = profile_transaction(&state.pool, &profile_id, &duplicate_id).await?;
+    ensure_open(&state.pool, &profile_id, &duplicate).await?;
+
+    sqlx::query("DELETE FROM transaction_merges WHERE duplicate_id = ?")
+        .bind(&duplicate_id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    record_change(
+        &state.pool,
+        Some(&user_id),
+        RecordType::TransactionMerge,
+        &duplicate_id,
+        Some(&profile_id),
+        Some(&merge),
+        None,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use std::str::FromStr;
+
+    const ONCHAIN: &str = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045";
+    const OTHER: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn wallet(id: &str, chain: &str, address: &str) -> Wallet {
+        Wallet {
+            id: id.to_string(),
+            profile_id: "p1".to_string(),
+            address: address.to_string(),
+            chain: chain.to_string(),
+            name: None,
+            wallet_type: "software".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+        }
+    }
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 12, 1, 12, 0, 0).unwrap() + Duration::minutes(minutes)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn tx(
+        id: &str,
+        wallet_id: &str,
+        chain: &str,
+        from: &str,
+        to: &str,
+        value: &str,
+        tx_type: &str,
+        minutes: i64,
+    ) -> StoredTransaction {
+        StoredTransaction {
+            id: id.to_string(),
+            wallet_id: wallet_id.to_string(),
+            hash: id.to_string(),
+            block_number: None,
+            timestamp: Some(at(minutes)),
+            from_address: Some(from.to_string()),
+            to_address: Some(to.to_string()),
+            value: Some(value.to_string()),
+            fee: None,
+            status: Some("success".to_string()),
+            tx_type: Some(tx_type.to_string()),
+            token_symbol: None,
+            token_decimals: None,
+            chain: chain.to_string(),
+            raw_data: None,
+            created_at: at(minutes),
+        }
+    }
+
+    fn wallets() -> Vec<Wallet> {
+        vec![
+            wallet("exchange", "coinbase", "coinbase-account-1"),
+            wallet("onchain", "ethereum", ONCHAIN),
+        ]
+    }
+
+    #[test]
+    fn test_pairs_exchange_withdrawal_with_onchain_deposit() {
+        // 1 ETH leaves the exchange; 0.999 ETH arrives 20 minutes later.
+        // A decoy deposit of the same size is too late to match.
+        let mut withdrawal = tx(
+            "w1",
+            "exchange",
+            "coinbase",
+            "",
+            ONCHAIN,
+            "1000000000000000000",
+            "withdrawal",
+            0,
+        );
+        withdrawal.token_symbol = Some("ETH".to_string());
+        withdrawal.token_decimals = Some(18);
+        let transactions = vec![
+            withdrawal,
+            tx(
+                "d1",
+                "onchain",
+                "ethereum",
+                OTHER,
+                ONCHAIN,
+                "999000000000000000",
+                "transfer",
+                20,
+            ),
+            tx(
+                "d2",
+                "onchain",
+                "ethereum",
+                OTHER,
+                ONCHAIN,
+                "999000000000000000",
+                "transfer",
+                60 * 24,
+            ),
+        ];
+
+        let candidates = find_duplicate_candidates(&wallets(), &transactions);
+        assert_eq!(candidates.len(), 1);
+        let candidate = &candidates[0];
+        assert_eq!(candidate.withdrawal_id, "w1");
+        assert_eq!(candidate.deposit_id, "d1");
+        assert_eq!(candidate.asset, "ETH");
+        assert_eq!(candidate.withdrawal_amount, dec("1"));
+        assert_eq!(candidate.deposit_amount, dec("0.999"));
+        assert_eq!(candidate.seconds_apart, 20 * 60);
+        assert!(!candidate.same_hash);
+    }
+
+    #[test]
+    fn test_skips_mismatched_amounts_and_internal_transfers() {
+        let second = wallet("second", "ethereum", OTHER);
+        let mut wallets = wallets();
+        wallets.push(second);
+
+        // The deposit is larger than the withdrawal, and the other pair is
+        // one on-chain transfer seen from both of the profile's wallets.
+        let mut out = tx(
+            "shared",
+            "onchain",
+            "ethereum",
+            ONCHAIN,
+            OTHER,
+            "500000000000000000",
+            "transfer",
+            0,
+        );
+        out.id = "out".to_string();
+        let mut into = tx(
+            "shared",
+            "second",
+            "ethereum",
+            ONCHAIN,
+            OTHER,
+            "500000000000000000",
+            "transfer",
+            0,
+        );
+        into.id = "in".to_string();
+        let transactions = vec![
+            tx(
+                "w1",
+                "exchange",
+                "ethereum",
+                "",
+                "",
+                "1000000000000000000",
+                "withdrawal",
+                0,
+            ),
+            tx(
+                "d1",
+                "onchain",
+                "ethereum",
+                OTHER,
+                ONCHAIN,
+                "1100000000000000000",
+                "transfer",
+                5,
+            ),
+            out,
+            into,
+        ];
+
+        assert!(find_duplicate_candidates(&wallets, &transactions).is_empty());
+    }
+
+    #[test]
+    fn test_each_record_is_paired_once() {
+        let transactions = vec![
+            tx(
+                "w1",
+                "exchange",
+                "ethereum",
+                "",
+                "",
+                "1000000000000000000",
+                "withdrawal",
+                0,
+            ),
+            tx(
+                "d1",
+                "onchain",
+                "ethereum",
+                OTHER,
+                ONCHAIN,
+                "990000000000000000",
+                "transfer",
+                10,
+            ),
+            tx(
+                "d2",
+                "onchain",
+                "ethereum",
+                OTHER,
+                ONCHAIN,
+                "1000000000000000000",
+                "transfer",
+                30,
+            ),
+        ];
+
+        let candidates = find_duplicate_candidates(&wallets(), &transactions);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].deposit_id, "d2");
+    }
+}
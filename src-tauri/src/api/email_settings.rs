@@ -0,0 +1,169 @@
+//! Email delivery settings.
+//!
+//! Lets self-hosted installs configure an SMTP server so invitations and
+//! alerts can be emailed without a Resend API key.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::auth::verify_admin;
+use super::persistence::DatabaseState;
+use super::profile_scope::authenticate;
+use crate::core::auth_state::AuthState;
+use crate::core::email::{self, smtp, SmtpProvider, SmtpSettings};
+use crate::log_error;
+use crate::storage::settings_store;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Current email configuration, as shown in settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailSettings {
+    /// Stored SMTP server settings, if any.
+    pub smtp: Option<SmtpSettings>,
+    /// Whether an SMTP password is stored in the keychain.
+    pub has_smtp_password: bool,
+    /// Registered providers, in the order they are tried.
+    pub active_providers: Vec<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Registers the SMTP provider from stored settings, if configured.
+///
+/// Returns whether a provider was registered. Called at startup and after the
+/// settings change.
+pub async fn load_smtp_provider(pool: &SqlitePool) -> Result<bool, String> {
+    let settings: Option<SmtpSettings> = settings_store::get_setting_json(pool, smtp::SETTINGS_KEY)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let Some(settings) = settings else {
+        email::remove_provider(smtp::PROVIDER_NAME);
+        return Ok(false);
+    };
+
+    let password = smtp::load_password().unwrap_or_else(|e| {
+        log_error!("Warning: could not read SMTP password: {}", e);
+        None
+    });
+    let provider = SmtpProvider::new(&settings, password)?;
+    email::register_provider(Arc::new(provider));
+    Ok(true)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns the current email configuration. Requires the app admin.
+#[tauri::command]
+pub async fn get_email_settings(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+) -> Result<EmailSettings, String> {
+    let user_id = authenticate(&auth, &token)?;
+    verify_admin(&state.pool, &user_id).await?;
+
+    let smtp_settings: Option<SmtpSettings> =
+        settings_store::get_setting_json(&state.pool, smtp::SETTINGS_KEY)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    Ok(EmailSettings {
+        smtp: smtp_settings,
+        has_smtp_password: matches!(smtp::load_password(), Ok(Some(_))),
+        active_providers: email::configured_providers()
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+    })
+}
+
+/// Saves SMTP settings and activates the SMTP provider.
+///
+/// `password` replaces the stored password when provided; pass `None` to keep
+/// the existing one. The existing password is dropped instead when the host or
+/// username changes, so it is never sent to another server or account.
+/// Requires the app admin.
+#[tauri::command]
+pub async fn save_smtp_settings(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    settings: SmtpSettings,
+    password: Option<String>,
+) -> Result<(), String> {
+    let user_id = authenticate(&auth, &token)?;
+    verify_admin(&state.pool, &user_id).await?;
+    settings.validate()?;
+
+    let previous: Option<SmtpSettings> =
+        settings_store::get_setting_json(&state.pool, smtp::SETTINGS_KEY)
+            .await
+            .map_err(|e| e.to_string())?;
+    let same_account = previous.is_some_and(|p| {
+        p.host.trim().eq_ignore_ascii_case(settings.host.trim()) && p.username == settings.username
+    });
+
+    match password.as_deref() {
+        Some("") => smtp::delete_password()?,
+        Some(password) => smtp::save_password(password)?,
+        None if !same_account => smtp::delete_password()?,
+        None => {}
+    }
+
+    settings_store::set_setting_json(&state.pool, smtp::SETTINGS_KEY, &settings)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    load_smtp_provider(&state.pool).await.map(|_| ())
+}
+
+/// Removes SMTP settings and the stored password. Requires the app admin.
+#[tauri::command]
+pub async fn remove_smtp_settings(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+) -> Result<(), String> {
+    let user_id = authenticate(&auth, &token)?;
+    verify_admin(&state.pool, &user_id).await?;
+
+    settings_store::delete_setting(&state.pool, smtp::SETTINGS_KEY)
+        .await
+        .map_err(|e| e.to_string())?;
+    smtp::delete_password()?;
+    email::remove_provider(smtp::PROVIDER_NAME);
+    Ok(())
+}
+
+/// Sends a test email through the configured providers. Requires the app
+/// admin.
+#[tauri::command]
+pub async fn send_test_email(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    to: String,
+) -> Result<(), String> {
+    let user_id = authenticate(&auth, &token)?;
+    verify_admin(&state.pool, &user_id).await?;
+
+    email::send_email(
+        &to,
+        "Test email - Pacioli",
+        "<p>Email delivery from Pacioli is working.</p>",
+        Some("Email delivery from Pacioli is working.\n\n- Pacioli Team"),
+    )
+    .await
+}
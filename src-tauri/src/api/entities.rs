@@ -4,7 +4,12 @@ use sqlx::FromRow;
 use tauri::State;
 use uuid::Uuid;
 
+use super::audit_trail::{record_change, RecordType};
+use super::permissions::Permission;
 use super::persistence::DatabaseState;
+use super::profile_scope::{authenticate, authorize_profile, authorize_record};
+use crate::chains::address::is_evm_address;
+use crate::core::auth_state::AuthState;
 
 // ============================================================================
 // Types
@@ -261,9 +266,31 @@ pub struct AddressMatch {
 // Entity Commands
 // ============================================================================
 
+// Verifies `token` and loads entity `id`, checking the caller's role on the
+// entity's profile grants `permission`
+async fn authorize_entity(
+    pool: &sqlx::SqlitePool,
+    auth: &AuthState,
+    token: &str,
+    id: &str,
+    permission: Permission,
+) -> Result<(String, Entity), String> {
+    let user_id = authenticate(auth, token)?;
+    let not_found = "Entity not found";
+    let entity = sqlx::query_as::<_, Entity>("SELECT * FROM entities WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| not_found.to_string())?;
+    authorize_record(pool, &user_id, &entity.profile_id, permission, not_found).await?;
+    Ok((user_id, entity))
+}
+
 // Internal helper function for entity creation
 async fn create_entity_internal(
     pool: &sqlx::SqlitePool,
+    actor: Option<&str>,
     entity: EntityInput,
 ) -> Result<Entity, String> {
     let id = Uuid::new_v4().to_string();
@@ -320,6 +347,17 @@ async fn create_entity_internal(
         .await
         .map_err(|e| e.to_string())?;
 
+    record_change(
+        pool,
+        actor,
+        RecordType::Entity,
+        &created.id,
+        Some(&created.profile_id),
+        None,
+        Some(&created),
+    )
+    .await?;
+
     Ok(created)
 }
 
@@ -327,19 +365,39 @@ async fn create_entity_internal(
 #[tauri::command]
 pub async fn create_entity(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     entity: EntityInput,
 ) -> Result<Entity, String> {
-    create_entity_internal(&state.pool, entity).await
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &entity.profile_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    create_entity_internal(&state.pool, Some(&user_id), entity).await
 }
 
 /// Retrieve a list of entities for the specified profile, optionally filtering by entity type and active status.
 #[tauri::command]
 pub async fn get_entities(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     profile_id: String,
     entity_type: Option<String>,
     is_active: Option<bool>,
 ) -> Result<Vec<Entity>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
     let mut query = String::from("SELECT * FROM entities WHERE profile_id = ?");
 
     if entity_type.is_some() {
@@ -363,28 +421,48 @@ pub async fn get_entities(
     Ok(entities)
 }
 
-/// Fetch an entity by its unique identifier, returning `None` if not found.
+/// Fetch an entity by its unique identifier, returning `None` if not found
+/// or not visible to the caller.
 #[tauri::command]
 pub async fn get_entity_by_id(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     id: String,
 ) -> Result<Option<Entity>, String> {
-    let entity = sqlx::query_as::<_, Entity>("SELECT * FROM entities WHERE id = ?")
-        .bind(&id)
-        .fetch_optional(&state.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(entity)
+    authenticate(&auth, &token)?;
+    match authorize_entity(
+        &state.pool,
+        &auth,
+        &token,
+        &id,
+        Permission::ViewTransactions,
+    )
+    .await
+    {
+        Ok((_, entity)) => Ok(Some(entity)),
+        Err(_) => Ok(None),
+    }
 }
 
 /// Update an existing entity with the provided fields
 #[tauri::command]
 pub async fn update_entity(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     id: String,
     update: EntityUpdate,
 ) -> Result<Entity, String> {
+    let (user_id, before) = authorize_entity(
+        &state.pool,
+        &auth,
+        &token,
+        &id,
+        Permission::EditTransactions,
+    )
+    .await?;
+
     // Collect string field updates using a table-driven approach
     let string_fields: &[(&str, &Option<String>)] = &[
         ("entity_type", &update.entity_type),
@@ -422,9 +500,7 @@ pub async fn update_entity(
         || update.is_active.is_some();
 
     if updates.is_empty() && !has_non_string_updates {
-        return get_entity_by_id(state, id)
-            .await?
-            .ok_or_else(|| "Entity not found".to_string());
+        return Ok(before);
     }
 
     // Apply string field updates in a single query
@@ -466,9 +542,24 @@ pub async fn update_entity(
             .map_err(|e| e.to_string())?;
     }
 
-    get_entity_by_id(state, id)
-        .await?
-        .ok_or_else(|| "Entity not found".to_string())
+    let after = sqlx::query_as::<_, Entity>("SELECT * FROM entities WHERE id = ?")
+        .bind(&id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    record_change(
+        &state.pool,
+        Some(&user_id),
+        RecordType::Entity,
+        &id,
+        Some(&after.profile_id),
+        Some(&before),
+        Some(&after),
+    )
+    .await?;
+
+    Ok(after)
 }
 
 /// Deletes the entity with the specified ID from the database.
@@ -476,6 +567,8 @@ pub async fn update_entity(
 /// # Arguments
 ///
 /// * `state` - The application state containing the database connection pool.
+/// * `token` - Session token of the caller, who must be able to edit the
+///   entity's profile.
 /// * `id` - The unique identifier of the entity to delete.
 ///
 /// # Returns
@@ -483,13 +576,38 @@ pub async fn update_entity(
 /// * `Ok(())` if the deletion succeeds.
 /// * `Err(String)` if an error occurs during deletion.
 #[tauri::command]
-pub async fn delete_entity(state: State<'_, DatabaseState>, id: String) -> Result<(), String> {
+pub async fn delete_entity(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    id: String,
+) -> Result<(), String> {
+    let (user_id, entity) = authorize_entity(
+        &state.pool,
+        &auth,
+        &token,
+        &id,
+        Permission::EditTransactions,
+    )
+    .await?;
+
     sqlx::query("DELETE FROM entities WHERE id = ?")
         .bind(&id)
         .execute(&state.pool)
         .await
         .map_err(|e| e.to_string())?;
 
+    record_change(
+        &state.pool,
+        Some(&user_id),
+        RecordType::Entity,
+        &entity.id,
+        Some(&entity.profile_id),
+        Some(&entity),
+        None,
+    )
+    .await?;
+
     Ok(())
 }
 
@@ -500,8 +618,19 @@ pub async fn delete_entity(state: State<'_, DatabaseState>, id: String) -> Resul
 // Internal helper function for adding entity address
 async fn add_entity_address_internal(
     pool: &sqlx::SqlitePool,
+    actor: Option<&str>,
     address_input: EntityAddressInput,
 ) -> Result<EntityAddress, String> {
+    let existing = sqlx::query_as::<_, EntityAddress>(
+        "SELECT * FROM entity_addresses WHERE entity_id = ? AND address = ? AND chain = ?",
+    )
+    .bind(&address_input.entity_id)
+    .bind(&address_input.address)
+    .bind(&address_input.chain)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
     let id = Uuid::new_v4().to_string();
     let now = Utc::now();
     let is_verified = address_input.is_verified.unwrap_or(false);
@@ -550,24 +679,70 @@ async fn add_entity_address_internal(
     .await
     .map_err(|e| e.to_string())?;
 
+    let profile_id = entity_profile_id(pool, &saved.entity_id).await?;
+    record_change(
+        pool,
+        actor,
+        RecordType::EntityAddress,
+        &saved.id,
+        profile_id.as_deref(),
+        existing.as_ref(),
+        Some(&saved),
+    )
+    .await?;
+
     Ok(saved)
 }
 
+// Profile that owns an entity, for recording changes to its addresses
+async fn entity_profile_id(
+    pool: &sqlx::SqlitePool,
+    entity_id: &str,
+) -> Result<Option<String>, String> {
+    let row: Option<(String,)> = sqlx::query_as("SELECT profile_id FROM entities WHERE id = ?")
+        .bind(entity_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(row.map(|(profile_id,)| profile_id))
+}
+
 /// Add a blockchain address to an entity
 #[tauri::command]
 pub async fn add_entity_address(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     address_input: EntityAddressInput,
 ) -> Result<EntityAddress, String> {
-    add_entity_address_internal(&state.pool, address_input).await
+    let (user_id, _) = authorize_entity(
+        &state.pool,
+        &auth,
+        &token,
+        &address_input.entity_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    add_entity_address_internal(&state.pool, Some(&user_id), address_input).await
 }
 
 /// Retrieve all blockchain addresses associated with the specified entity, ordered by creation time descending
 #[tauri::command]
 pub async fn get_entity_addresses(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     entity_id: String,
 ) -> Result<Vec<EntityAddress>, String> {
+    authorize_entity(
+        &state.pool,
+        &auth,
+        &token,
+        &entity_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
     let addresses = sqlx::query_as::<_, EntityAddress>(
         "SELECT * FROM entity_addresses WHERE entity_id = ? ORDER BY created_at DESC",
     )
@@ -583,14 +758,47 @@ pub async fn get_entity_addresses(
 #[tauri::command]
 pub async fn delete_entity_address(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     id: String,
 ) -> Result<(), String> {
+    let user_id = authenticate(&auth, &token)?;
+    let not_found = "Entity address not found";
+    let address = sqlx::query_as::<_, EntityAddress>("SELECT * FROM entity_addresses WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| not_found.to_string())?;
+    let profile_id = entity_profile_id(&state.pool, &address.entity_id)
+        .await?
+        .ok_or_else(|| not_found.to_string())?;
+    authorize_record(
+        &state.pool,
+        &user_id,
+        &profile_id,
+        Permission::EditTransactions,
+        not_found,
+    )
+    .await?;
+
     sqlx::query("DELETE FROM entity_addresses WHERE id = ?")
         .bind(&id)
         .execute(&state.pool)
         .await
         .map_err(|e| e.to_string())?;
 
+    record_change(
+        &state.pool,
+        Some(&user_id),
+        RecordType::EntityAddress,
+        &address.id,
+        Some(&profile_id),
+        Some(&address),
+        None,
+    )
+    .await?;
+
     Ok(())
 }
 
@@ -599,7 +807,7 @@ pub async fn delete_entity_address(
 // ============================================================================
 
 // Internal helper function for address lookup
-async fn lookup_address_internal(
+pub(crate) async fn lookup_address_internal(
     pool: &sqlx::SqlitePool,
     profile_id: &str,
     address: &str,
@@ -635,6 +843,38 @@ async fn lookup_address_internal(
         }));
     }
 
+    // An EVM address is the same account on every EVM chain, so an entity
+    // address saved for another chain still identifies the counterparty.
+    if is_evm_address(address) {
+        let cross_chain_match = sqlx::query_as::<_, (String, String, String, Option<String>)>(
+            r#"
+            SELECT e.id, e.name, e.entity_type, e.category
+            FROM entities e
+            INNER JOIN entity_addresses ea ON e.id = ea.entity_id
+            WHERE e.profile_id = ? AND LOWER(ea.address) = LOWER(?)
+            LIMIT 1
+            "#,
+        )
+        .bind(profile_id)
+        .bind(address)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        if let Some((entity_id, name, entity_type, category)) = cross_chain_match {
+            return Ok(Some(AddressMatch {
+                address: address.to_string(),
+                chain: chain.to_string(),
+                match_type: "entity".to_string(),
+                entity_id: Some(entity_id),
+                entity_name: name,
+                entity_type: Some(entity_type),
+                category,
+                confidence: "medium".to_string(),
+            }));
+        }
+    }
+
     // Then, check known_addresses (global reference data)
     let known_match = sqlx::query_as::<_, KnownAddress>(
         "SELECT * FROM known_addresses WHERE address = ? AND chain = ? AND is_active = 1",
@@ -665,10 +905,20 @@ async fn lookup_address_internal(
 #[tauri::command]
 pub async fn lookup_address(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     profile_id: String,
     address: String,
     chain: String,
 ) -> Result<Option<AddressMatch>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
     lookup_address_internal(&state.pool, &profile_id, &address, &chain).await
 }
 
@@ -676,9 +926,19 @@ pub async fn lookup_address(
 #[tauri::command]
 pub async fn batch_lookup_addresses(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     profile_id: String,
     addresses: Vec<(String, String)>, // Vec of (address, chain)
 ) -> Result<Vec<AddressMatch>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
     let mut matches = Vec::new();
 
     for (address, chain) in addresses {
@@ -695,9 +955,12 @@ pub async fn batch_lookup_addresses(
 #[tauri::command]
 pub async fn get_known_addresses(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     chain: Option<String>,
     entity_type: Option<String>,
 ) -> Result<Vec<KnownAddress>, String> {
+    authenticate(&auth, &token)?;
     let mut query = String::from("SELECT * FROM known_addresses WHERE is_active = 1");
 
     if chain.is_some() {
@@ -725,10 +988,21 @@ pub async fn get_known_addresses(
 #[tauri::command]
 pub async fn create_entity_from_known(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     profile_id: String,
     address: String,
     chain: String,
 ) -> Result<Entity, String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+
     // Look up the known address
     let known = sqlx::query_as::<_, KnownAddress>(
         "SELECT * FROM known_addresses WHERE address = ? AND chain = ? AND is_active = 1",
@@ -768,7 +1042,7 @@ pub async fn create_entity_from_known(
         )),
     };
 
-    let entity = create_entity_internal(&state.pool, entity_input).await?;
+    let entity = create_entity_internal(&state.pool, Some(&user_id), entity_input).await?;
 
     // Add the address to entity_addresses
     let address_input = EntityAddressInput {
@@ -781,7 +1055,7 @@ pub async fn create_entity_from_known(
         verification_method: Some("known_address_database".to_string()),
     };
 
-    add_entity_address_internal(&state.pool, address_input).await?;
+    add_entity_address_internal(&state.pool, Some(&user_id), address_input).await?;
 
     Ok(entity)
 }
@@ -795,6 +1069,7 @@ pub async fn create_entity_from_known(
 /// # Arguments
 ///
 /// * `state` - Application state containing the database connection pool.
+/// * `token` - Session token of the caller, who must be able to view the profile.
 /// * `profile_id` - The profile identifier to scope the search.
 /// * `query` - The search term to filter entity fields.
 /// * `limit` - Optional maximum number of results; defaults to 20.
@@ -805,10 +1080,20 @@ pub async fn create_entity_from_known(
 #[tauri::command]
 pub async fn search_entities(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     profile_id: String,
     query: String,
     limit: Option<i32>,
 ) -> Result<Vec<Entity>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
     let limit = limit.unwrap_or(20);
     let search_term = format!("%{}%", query);
 
@@ -846,6 +1131,7 @@ pub async fn search_entities(
 /// # Arguments
 ///
 /// * `state` - Application state containing the database connection pool.
+/// * `token` - Session token of the caller, who must be able to view the profile.
 /// * `profile_id` - The profile identifier to scope the search.
 /// * `address` - The address to look up.
 /// * `chain` - Optional blockchain chain identifier.
@@ -856,10 +1142,20 @@ pub async fn search_entities(
 #[tauri::command]
 pub async fn find_entity_by_address(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     profile_id: String,
     address: String,
     chain: Option<String>,
 ) -> Result<Option<Entity>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
     let entity = if let Some(ref c) = chain {
         sqlx::query_as::<_, Entity>(
             r#"
@@ -0,0 +1,970 @@
+//! Per-entity statements and payee reports.
+//!
+//! A statement lists every transfer between a profile's wallets and one
+//! entity's addresses over a period, each valued in the reporting currency
+//! at the time it happened, with totals paid and received. The payee report
+//! sums payments to the profile's reportable payees for a year, as the
+//! basis for 1099-style filings or grantee disbursement summaries. Both can
+//! be written to CSV or PDF.
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use csv::WriterBuilder;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::address_watch::native_currency;
+use super::entities::Entity;
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction, Wallet};
+use super::price_sources::{price_sources, render_pdf_appendix, write_csv_appendix, PriceSource};
+use super::profile_scope::{authorize_profile, profile_wallets};
+use super::statement_export::{parse_amount, parse_period_bound, StatementExportResult};
+use super::token_spam::SpamFilter;
+use crate::core::auth_state::AuthState;
+use crate::core::pdf::{Font, PdfDocument, PAGE_WIDTH};
+
+pub use pacioli_core::services::entity_statements::*;
+
+/// Payments at or above this much in a year make a payee reportable on a
+/// 1099-NEC or 1099-MISC.
+const DEFAULT_REPORTING_THRESHOLD: i64 = 600;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Output formats for entity statements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityReportFormat {
+    /// Comma-separated values, one row per transfer.
+    Csv,
+    /// A printable statement.
+    Pdf,
+}
+
+impl FromStr for EntityReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "pdf" => Ok(Self::Pdf),
+            _ => Err(format!("Unsupported report format: {}", s)),
+        }
+    }
+}
+
+/// All transfers with one entity over a period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityStatement {
+    /// Entity the statement is for.
+    pub entity_id: String,
+    /// Entity name.
+    pub entity_name: String,
+    /// Entity type, e.g. donor, grantee, or vendor.
+    pub entity_type: String,
+    /// Tax identifier on file, if any.
+    pub tax_identifier: Option<String>,
+    /// First day covered.
+    pub period_start: DateTime<Utc>,
+    /// Last moment covered.
+    pub period_end: DateTime<Utc>,
+    /// Reporting currency of the fiat values.
+    pub currency: String,
+    /// Transfers, oldest first.
+    pub lines: Vec<EntityStatementLine>,
+    /// Value of the priced transfers the profile paid.
+    pub total_paid: Decimal,
+    /// Value of the priced transfers the profile received.
+    pub total_received: Decimal,
+    /// Transfers left out of the totals for want of a price.
+    pub unpriced_count: usize,
+    /// Where the prices came from.
+    pub price_sources: Vec<PriceSource>,
+    /// Assets that couldn't be priced, and why.
+    pub warnings: Vec<String>,
+}
+
+/// Payments to one reportable payee in a year.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayeeTotal {
+    /// Entity paid.
+    pub entity_id: String,
+    /// Entity name.
+    pub name: String,
+    /// Entity type.
+    pub entity_type: String,
+    /// Tax identifier on file, if any.
+    pub tax_identifier: Option<String>,
+    /// Kind of tax identifier, e.g. EIN or SSN.
+    pub tax_identifier_type: Option<String>,
+    /// State of the payee's tax documentation, e.g. a W-9.
+    pub tax_documentation_status: String,
+    /// Value of the priced payments.
+    pub total_paid: Decimal,
+    /// Number of payments.
+    pub payment_count: usize,
+    /// Payments left out of the total for want of a price.
+    pub unpriced_count: usize,
+    /// Whether the total reaches the reporting threshold.
+    pub meets_threshold: bool,
+}
+
+/// Payments to a profile's reportable payees for a year.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayeeReport {
+    /// Calendar year covered.
+    pub year: i32,
+    /// Reporting currency of the totals.
+    pub currency: String,
+    /// Total at which a payee must be reported.
+    pub threshold: Decimal,
+    /// Payees paid in the year, largest total first.
+    pub payees: Vec<PayeeTotal>,
+    /// Where the prices came from.
+    pub price_sources: Vec<PriceSource>,
+    /// Assets that couldn't be priced, and why.
+    pub warnings: Vec<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Transfers between the profile's wallets and `counterparties`, which must
+/// be lowercase. Failed transactions, and ones without a timestamp or
+/// amount, are skipped.
+pub fn entity_lines(
+    wallets: &[Wallet],
+    counterparties: &HashSet<String>,
+    transactions: &[StoredTransaction],
+) -> Vec<EntityStatementLine> {
+    let addresses: HashMap<&str, String> = wallets
+        .iter()
+        .map(|w| (w.id.as_str(), w.address.to_lowercase()))
+        .collect();
+
+    let mut lines: Vec<EntityStatementLine> = transactions
+        .iter()
+        .filter(|tx| tx.status.as_deref() != Some("failed"))
+        .filter_map(|tx| {
+            let wallet = addresses.get(tx.wallet_id.as_str())?;
+            let from = tx.from_address.as_deref()?.to_lowercase();
+            let to = tx.to_address.as_deref()?.to_lowercase();
+            let (direction, counterparty) = if &from == wallet && counterparties.contains(&to) {
+                (StatementDirection::Paid, tx.to_address.clone()?)
+            } else if &to == wallet && counterparties.contains(&from) {
+                (StatementDirection::Received, tx.from_address.clone()?)
+            } else {
+                return None;
+            };
+
+            let (native_symbol, native_decimals) = native_currency(&tx.chain);
+            let (asset, decimals) = match &tx.token_symbol {
+                Some(symbol) => (symbol.clone(), tx.token_decimals),
+                None => (native_symbol, Some(native_decimals)),
+            };
+            let amount = parse_amount(tx.value.as_deref()?, decimals)?;
+            if amount.is_zero() {
+                return None;
+            }
+
+            Some(EntityStatementLine {
+                transaction_id: tx.id.clone(),
+                hash: tx.hash.clone(),
+                chain: tx.chain.clone(),
+                date: tx.timestamp?,
+                direction,
+                counterparty,
+                asset,
+                amount,
+                fiat_price: None,
+                fiat_value: None,
+                price_source: None,
+                price_endpoint: None,
+                price_retrieved_at: None,
+            })
+        })
+        .collect();
+    lines.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.hash.cmp(&b.hash)));
+    lines
+}
+
+/// Totals paid and received over the priced lines, and the number of lines
+/// without a price.
+fn totals(lines: &[EntityStatementLine]) -> (Decimal, Decimal, usize) {
+    let mut paid = Decimal::ZERO;
+    let mut received = Decimal::ZERO;
+    let mut unpriced = 0;
+    for line in lines {
+        match (line.fiat_value, line.direction) {
+            (Some(value), StatementDirection::Paid) => paid += value,
+            (Some(value), StatementDirection::Received) => received += value,
+            (None, _) => unpriced += 1,
+        }
+    }
+    (paid, received, unpriced)
+}
+
+/// A profile's transactions between `start` and `end`, either of which may
+/// be open, leaving out merged duplicates and spam tokens.
+pub(crate) async fn period_transactions(
+    pool: &SqlitePool,
+    profile_id: &str,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> Result<Vec<StoredTransaction>, String> {
+    let transactions = sqlx::query_as::<_, StoredTransaction>(
+        r#"
+        SELECT t.* FROM transactions t
+        INNER JOIN wallets w ON t.wallet_id = w.id
+        WHERE w.profile_id = ?
+          AND (? IS NULL OR t.timestamp >= ?) AND (? IS NULL OR t.timestamp <= ?)
+          AND t.id NOT IN (SELECT duplicate_id FROM transaction_merges)
+        ORDER BY t.timestamp ASC
+        "#,
+    )
+    .bind(profile_id)
+    .bind(start)
+    .bind(start)
+    .bind(end)
+    .bind(end)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let filter = SpamFilter::load(pool, Some(profile_id))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(transactions
+        .into_iter()
+        .filter(|tx| !hides_stored(&filter, tx))
+        .collect())
+}
+
+/// An entity's addresses, lowercased.
+async fn entity_addresses(pool: &SqlitePool, entity_id: &str) -> Result<HashSet<String>, String> {
+    let addresses: Vec<(String,)> =
+        sqlx::query_as("SELECT address FROM entity_addresses WHERE entity_id = ?")
+            .bind(entity_id)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    Ok(addresses
+        .into_iter()
+        .map(|(address,)| address.to_lowercase())
+        .collect())
+}
+
+async fn profile_entity(
+    pool: &SqlitePool,
+    profile_id: &str,
+    entity_id: &str,
+) -> Result<Entity, String> {
+    sqlx::query_as::<_, Entity>("SELECT * FROM entities WHERE profile_id = ? AND id = ?")
+        .bind(profile_id)
+        .bind(entity_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Entity not found: {}", entity_id))
+}
+
+fn required_bound(value: &str, end_of_day: bool) -> Result<DateTime<Utc>, String> {
+    parse_period_bound(Some(value), end_of_day)?.ok_or_else(|| format!("Invalid date: {}", value))
+}
+
+pub(crate) async fn build_statement(
+    pool: &SqlitePool,
+    profile_id: &str,
+    entity_id: &str,
+    start_date: &str,
+    end_date: &str,
+    coin_ids: HashMap<String, String>,
+) -> Result<EntityStatement, String> {
+    let start = required_bound(start_date, false)?;
+    let end = required_bound(end_date, true)?;
+    let entity = profile_entity(pool, profile_id, entity_id).await?;
+
+    let wallets = profile_wallets(pool, profile_id).await?;
+    let counterparties = entity_addresses(pool, entity_id).await?;
+    let transactions = period_transactions(pool, profile_id, Some(start), Some(end)).await?;
+    let mut lines = entity_lines(&wallets, &counterparties, &transactions);
+
+    let mut pricer = Pricer::load(pool, profile_id, coin_ids).await?;
+    pricer.value(&mut lines).await;
+    let (total_paid, total_received, unpriced_count) = totals(&lines);
+
+    Ok(EntityStatement {
+        entity_id: entity.id,
+        entity_name: entity.display_name.unwrap_or(entity.name),
+        entity_type: entity.entity_type,
+        tax_identifier: entity.tax_identifier,
+        period_start: start,
+        period_end: end,
+        currency: pricer.currency.clone(),
+        lines,
+        total_paid,
+        total_received,
+        unpriced_count,
+        price_sources: pricer.price_sources(),
+        warnings: pricer.take_warnings(),
+    })
+}
+
+fn decimal_cell(value: Option<Decimal>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// Renders a statement as CSV, one row per transfer, followed by the price
+/// source appendix.
+pub fn statement_csv(statement: &EntityStatement) -> Result<Vec<u8>, String> {
+    let mut writer = WriterBuilder::new().flexible(true).from_writer(Vec::new());
+    let value_header = format!("Value ({})", statement.currency);
+    writer
+        .write_record([
+            "Date",
+            "Direction",
+            "Chain",
+            "Hash",
+            "Counterparty",
+            "Asset",
+            "Amount",
+            "Price",
+            value_header.as_str(),
+            "Price Source",
+            "Price Endpoint",
+            "Price Retrieved",
+        ])
+        .map_err(|e| e.to_string())?;
+    for line in &statement.lines {
+        writer
+            .write_record([
+                line.date.format("%Y-%m-%d %H:%M:%S").to_string(),
+                line.direction.label().to_string(),
+                line.chain.clone(),
+                line.hash.clone(),
+                line.counterparty.clone(),
+                line.asset.clone(),
+                line.amount.to_string(),
+                decimal_cell(line.fiat_price),
+                decimal_cell(line.fiat_value),
+                line.price_source.clone().unwrap_or_default(),
+                line.price_endpoint.clone().unwrap_or_default(),
+                line.price_retrieved_at
+                    .map(|at| at.to_rfc3339())
+                    .unwrap_or_default(),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    write_csv_appendix(&mut writer, &statement.price_sources)?;
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+/// Renders a statement to PDF bytes, continuing onto new pages as needed,
+/// with the price source appendix on its own page.
+pub fn render_statement_pdf(statement: &EntityStatement, organization: &str) -> Vec<u8> {
+    let mut doc = PdfDocument::new().with_title(&format!(
+        "Statement for {} {} to {}",
+        statement.entity_name,
+        statement.period_start.format("%Y-%m-%d"),
+        statement.period_end.format("%Y-%m-%d")
+    ));
+    let left = 54.0;
+    let right = PAGE_WIDTH - 54.0;
+    let columns = [left, left + 70.0, left + 135.0, left + 290.0, left + 400.0];
+    let mut y = 720.0;
+
+    doc.text(left, y, 18.0, Font::Bold, organization);
+    y -= 26.0;
+    doc.text(
+        left,
+        y,
+        13.0,
+        Font::Bold,
+        &format!("Statement for {}", statement.entity_name),
+    );
+    y -= 16.0;
+    doc.text(
+        left,
+        y,
+        10.0,
+        Font::Regular,
+        &format!(
+            "{} to {}",
+            statement.period_start.format("%Y-%m-%d"),
+            statement.period_end.format("%Y-%m-%d")
+        ),
+    );
+    if let Some(tin) = &statement.tax_identifier {
+        y -= 14.0;
+        doc.text(left, y, 10.0, Font::Regular, &format!("Tax ID: {}", tin));
+    }
+    y -= 12.0;
+    doc.hline(left, right, y, 0.75);
+    y -= 20.0;
+
+    let value_header = format!("Value ({})", statement.currency);
+    let headers = ["Date", "Direction", "Amount", "Hash", value_header.as_str()];
+    for (x, header) in columns.iter().zip(headers) {
+        doc.text(*x, y, 9.0, Font::Bold, header);
+    }
+    y -= 16.0;
+
+    for line in &statement.lines {
+        if y < 90.0 {
+            doc.add_page();
+            y = 740.0;
+        }
+        let hash: String = line.hash.chars().take(20).collect();
+        let cells = [
+            line.date.format("%Y-%m-%d").to_string(),
+            line.direction.label().to_string(),
+            format!("{} {}", line.amount, line.asset),
+            hash,
+            line.fiat_value
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "not priced".to_string()),
+        ];
+        for (x, cell) in columns.iter().zip(cells) {
+            doc.text(*x, y, 9.0, Font::Regular, &cell);
+        }
+        y -= 14.0;
+    }
+
+    if y < 120.0 {
+        doc.add_page();
+        y = 740.0;
+    }
+    y -= 8.0;
+    doc.hline(left, right, y, 0.5);
+    y -= 18.0;
+    for (label, total) in [
+        ("Total paid", statement.total_paid),
+        ("Total received", statement.total_received),
+    ] {
+        doc.text(left, y, 10.0, Font::Bold, label);
+        doc.text(
+            columns[4],
+            y,
+            10.0,
+            Font::Regular,
+            &format!("{} {}", total, statement.currency),
+        );
+        y -= 16.0;
+    }
+    if statement.unpriced_count > 0 {
+        doc.text(
+            left,
+            y,
+            9.0,
+            Font::Regular,
+            &format!(
+                "{} transfer(s) could not be priced and are not included in the totals.",
+                statement.unpriced_count
+            ),
+        );
+        y -= 16.0;
+    }
+
+    y -= 20.0;
+    doc.text(
+        left,
+        y,
+        8.0,
+        Font::Regular,
+        &format!("Issued {}", Utc::now().format("%Y-%m-%d")),
+    );
+    render_pdf_appendix(&mut doc, &statement.price_sources);
+
+    doc.to_bytes()
+}
+
+/// Totals each payee's lines, marking those that reach `threshold`, and
+/// orders them largest first.
+fn payee_totals(
+    payees: Vec<(Entity, Vec<EntityStatementLine>)>,
+    threshold: Decimal,
+) -> Vec<PayeeTotal> {
+    let mut totals: Vec<PayeeTotal> = payees
+        .into_iter()
+        .filter_map(|(entity, lines)| {
+            let lines: Vec<EntityStatementLine> = lines
+                .into_iter()
+                .filter(|l| l.direction == StatementDirection::Paid)
+                .collect();
+            if lines.is_empty() {
+                return None;
+            }
+            let (total_paid, _, unpriced_count) = totals(&lines);
+            Some(PayeeTotal {
+                entity_id: entity.id,
+                name: entity.name,
+                entity_type: entity.entity_type,
+                tax_identifier: entity.tax_identifier,
+                tax_identifier_type: entity.tax_identifier_type,
+                tax_documentation_status: entity.tax_documentation_status,
+                total_paid,
+                payment_count: lines.len(),
+                unpriced_count,
+                meets_threshold: total_paid >= threshold,
+            })
+        })
+        .collect();
+    totals.sort_by(|a, b| {
+        b.total_paid
+            .cmp(&a.total_paid)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    totals
+}
+
+pub(crate) async fn build_payee_report(
+    pool: &SqlitePool,
+    profile_id: &str,
+    year: i32,
+    threshold: Option<String>,
+    coin_ids: HashMap<String, String>,
+) -> Result<PayeeReport, String> {
+    let threshold = match threshold {
+        Some(value) => {
+            Decimal::from_str(&value).map_err(|_| format!("Invalid threshold: {}", value))?
+        }
+        None => Decimal::from(DEFAULT_REPORTING_THRESHOLD),
+    };
+    let start = required_bound(&format!("{}-01-01", year), false)?;
+    let end = required_bound(&format!("{}-12-31", year), true)?;
+
+    let entities = sqlx::query_as::<_, Entity>(
+        "SELECT * FROM entities WHERE profile_id = ? AND reportable_payee = 1 ORDER BY name",
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let wallets = profile_wallets(pool, profile_id).await?;
+    let transactions = period_transactions(pool, profile_id, Some(start), Some(end)).await?;
+
+    let mut pricer = Pricer::load(pool, profile_id, coin_ids).await?;
+    let mut payees = Vec::new();
+    for entity in entities {
+        let counterparties = entity_addresses(pool, &entity.id).await?;
+        let mut lines = entity_lines(&wallets, &counterparties, &transactions);
+        lines.retain(|l| l.direction == StatementDirection::Paid);
+        pricer.value(&mut lines).await;
+        payees.push((entity, lines));
+    }
+
+    Ok(PayeeReport {
+        year,
+        currency: pricer.currency.clone(),
+        threshold,
+        payees: payee_totals(payees, threshold),
+        price_sources: pricer.price_sources(),
+        warnings: pricer.take_warnings(),
+    })
+}
+
+/// Renders a payee report as CSV, one row per payee, followed by the price
+/// source appendix.
+pub fn payee_report_csv(report: &PayeeReport) -> Result<Vec<u8>, String> {
+    let mut writer = WriterBuilder::new().flexible(true).from_writer(Vec::new());
+    let total_header = format!("Total Paid ({})", report.currency);
+    writer
+        .write_record([
+            "Payee",
+            "Type",
+            "Tax ID",
+            "Tax ID Type",
+            "Documentation",
+            total_header.as_str(),
+            "Payments",
+            "Unpriced Payments",
+            "Reportable",
+        ])
+        .map_err(|e| e.to_string())?;
+    for payee in &report.payees {
+        writer
+            .write_record([
+                payee.name.clone(),
+                payee.entity_type.clone(),
+                payee.tax_identifier.clone().unwrap_or_default(),
+                payee.tax_identifier_type.clone().unwrap_or_default(),
+                payee.tax_documentation_status.clone(),
+                payee.total_paid.to_string(),
+                payee.payment_count.to_string(),
+                payee.unpriced_count.to_string(),
+                if payee.meets_threshold { "yes" } else { "no" }.to_string(),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    write_csv_appendix(&mut writer, &report.price_sources)?;
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns every transfer with an entity between `start_date` and
+/// `end_date` (`YYYY-MM-DD`, inclusive), valued in the reporting currency.
+///
+/// `coin_ids` maps asset symbols to price feed coin IDs, e.g. `ETH` to
+/// `ethereum`. Assets without one are priced only from overrides.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_entity_statement(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    entity_id: String,
+    start_date: String,
+    end_date: String,
+    coin_ids: HashMap<String, String>,
+) -> Result<EntityStatement, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    build_statement(
+        &state.pool,
+        &profile_id,
+        &entity_id,
+        &start_date,
+        &end_date,
+        coin_ids,
+    )
+    .await
+}
+
+/// Writes an entity statement to `path` as `"csv"` or `"pdf"`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_entity_statement(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    entity_id: String,
+    start_date: String,
+    end_date: String,
+    coin_ids: HashMap<String, String>,
+    format: String,
+    path: String,
+) -> Result<StatementExportResult, String> {
+    authorize_profile(&state.pool, &auth, &token, &profile_id, Permission::Export).await?;
+    let format: EntityReportFormat = format.parse()?;
+    let statement = build_statement(
+        &state.pool,
+        &profile_id,
+        &entity_id,
+        &start_date,
+        &end_date,
+        coin_ids,
+    )
+    .await?;
+
+    let contents = match format {
+        EntityReportFormat::Csv => statement_csv(&statement)?,
+        EntityReportFormat::Pdf => {
+            let organization: Option<String> =
+                sqlx::query_scalar("SELECT name FROM profiles WHERE id = ?")
+                    .bind(&profile_id)
+                    .fetch_optional(&state.pool)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            render_statement_pdf(&statement, organization.as_deref().unwrap_or_default())
+        }
+    };
+    std::fs::write(&path, contents).map_err(|e| e.to_string())?;
+
+    Ok(StatementExportResult {
+        path,
+        line_count: statement.lines.len(),
+    })
+}
+
+/// Returns the year's payments to each of the profile's reportable payees,
+/// marking those at or above `threshold` (600 in the reporting currency by
+/// default).
+#[tauri::command]
+pub async fn get_payee_report(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    year: i32,
+    threshold: Option<String>,
+    coin_ids: HashMap<String, String>,
+) -> Result<PayeeReport, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    build_payee_report(&state.pool, &profile_id, year, threshold, coin_ids).await
+}
+
+/// Writes the payee report for a year to `path` as CSV.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_payee_report(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    year: i32,
+    threshold: Option<String>,
+    coin_ids: HashMap<String, String>,
+    path: String,
+) -> Result<StatementExportResult, String> {
+    authorize_profile(&state.pool, &auth, &token, &profile_id, Permission::Export).await?;
+    let report = build_payee_report(&state.pool, &profile_id, year, threshold, coin_ids).await?;
+    std::fs::write(&path, payee_report_csv(&report)?).map_err(|e| e.to_string())?;
+
+    Ok(StatementExportResult {
+        path,
+        line_count: report.payees.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const WALLET: &str = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045";
+    const GRANTEE: &str = "0x7A250D5630B4CF539739DF2C5DACB4C659F2488D";
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn wallet() -> Wallet {
+        Wallet {
+            id: "w1".to_string(),
+            profile_id: "p1".to_string(),
+            address: WALLET.to_string(),
+            chain: "ethereum".to_string(),
+            name: None,
+            wallet_type: "software".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+        }
+    }
+
+    fn tx(hash: &str, day: u32, from: &str, to: &str, value: &str) -> StoredTransaction {
+        let at = Utc.with_ymd_and_hms(2025, 3, day, 12, 0, 0).unwrap();
+        StoredTransaction {
+            id: hash.to_string(),
+            wallet_id: "w1".to_string(),
+            hash: hash.to_string(),
+            block_number: None,
+            timestamp: Some(at),
+            from_address: Some(from.to_string()),
+            to_address: Some(to.to_string()),
+            value: Some(value.to_string()),
+            fee: None,
+            status: Some("success".to_string()),
+            tx_type: Some("transfer".to_string()),
+            token_symbol: None,
+            token_decimals: None,
+            chain: "ethereum".to_string(),
+            raw_data: None,
+            created_at: at,
+        }
+    }
+
+    fn entity(id: &str, name: &str) -> Entity {
+        Entity {
+            id: id.to_string(),
+            profile_id: "p1".to_string(),
+            entity_type: "grantee".to_string(),
+            name: name.to_string(),
+            display_name: None,
+            email: None,
+            phone: None,
+            website: None,
+            address: None,
+            country_code: None,
+            tax_identifier: Some("12-3456789".to_string()),
+            tax_identifier_type: Some("EIN".to_string()),
+            default_wallet_address: None,
+            category: None,
+            tags: None,
+            default_payment_terms: None,
+            default_currency: None,
+            reportable_payee: true,
+            tax_documentation_status: "received".to_string(),
+            tax_documentation_date: None,
+            tax_compliance: None,
+            notes: None,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn priced(mut line: EntityStatementLine, value: Option<&str>) -> EntityStatementLine {
+        line.fiat_value = value.map(dec);
+        line
+    }
+
+    #[test]
+    fn test_entity_lines_keep_only_transfers_with_the_entity() {
+        let counterparties = HashSet::from([GRANTEE.to_lowercase()]);
+        let mut failed = tx("0x4", 4, WALLET, GRANTEE, "1000000000000000000");
+        failed.status = Some("failed".to_string());
+        let transactions = vec![
+            tx("0x2", 2, GRANTEE, WALLET, "250000000000000000"),
+            tx("0x1", 1, WALLET, GRANTEE, "1500000000000000000"),
+            tx("0x3", 3, WALLET, "0xsomeoneelse", "1000000000000000000"),
+            failed,
+        ];
+
+        let lines = entity_lines(&[wallet()], &counterparties, &transactions);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].hash, "0x1");
+        assert_eq!(lines[0].direction, StatementDirection::Paid);
+        assert_eq!(lines[0].counterparty, GRANTEE);
+        assert_eq!(lines[0].asset, "ETH");
+        assert_eq!(lines[0].amount, dec("1.5"));
+        assert_eq!(lines[1].direction, StatementDirection::Received);
+        assert_eq!(lines[1].amount, dec("0.25"));
+    }
+
+    #[test]
+    fn test_totals_skip_unpriced_lines() {
+        let counterparties = HashSet::from([GRANTEE.to_lowercase()]);
+        let transactions = vec![
+            tx("0x1", 1, WALLET, GRANTEE, "1000000000000000000"),
+            tx("0x2", 2, WALLET, GRANTEE, "1000000000000000000"),
+            tx("0x3", 3, GRANTEE, WALLET, "1000000000000000000"),
+        ];
+        let mut lines = entity_lines(&[wallet()], &counterparties, &transactions).into_iter();
+        let lines = vec![
+            priced(lines.next().unwrap(), Some("2000.00")),
+            priced(lines.next().unwrap(), None),
+            priced(lines.next().unwrap(), Some("150.25")),
+        ];
+
+        assert_eq!(totals(&lines), (dec("2000.00"), dec("150.25"), 1));
+    }
+
+    #[test]
+    fn test_payee_totals_mark_threshold() {
+        let counterparties = HashSet::from([GRANTEE.to_lowercase()]);
+        let transactions = vec![tx("0x1", 1, WALLET, GRANTEE, "1000000000000000000")];
+        let line = entity_lines(&[wallet()], &counterparties, &transactions).remove(0);
+
+        let payees = payee_totals(
+            vec![
+                (
+                    entity("e1", "Small Grant"),
+                    vec![priced(line.clone(), Some("250"))],
+                ),
+                (entity("e2", "Big Grant"), vec![priced(line, Some("5000"))]),
+                (entity("e3", "Unpaid"), Vec::new()),
+            ],
+            dec("600"),
+        );
+
+        assert_eq!(payees.len(), 2);
+        assert_eq!(payees[0].name, "Big Grant");
+        assert!(payees[0].meets_threshold);
+        assert_eq!(payees[1].total_paid, dec("250"));
+        assert!(!payees[1].meets_threshold);
+
+        let report = PayeeReport {
+            year: 2025,
+            currency: "USD".to_string(),
+            threshold: dec("600"),
+            payees,
+            price_sources: Vec::new(),
+            warnings: Vec::new(),
+        };
+        let csv = String::from_utf8(payee_report_csv(&report).unwrap()).unwrap();
+        assert!(csv.starts_with("Payee,Type,Tax ID,"));
+        assert!(csv.contains("Big Grant,grantee,12-3456789,EIN,received,5000,1,0,yes\n"));
+    }
+
+    #[test]
+    fn test_render_statement_pdf_paginates() {
+        let counterparties = HashSet::from([GRANTEE.to_lowercase()]);
+        let transactions: Vec<StoredTransaction> = (1..=28)
+            .flat_map(|day| {
+                (0..2).map(move |n| {
+                    tx(
+                        &format!("0x{}{}", day, n),
+                        day,
+                        WALLET,
+                        GRANTEE,
+                        "1000000000000000000",
+                    )
+                })
+            })
+            .collect();
+        let lines: Vec<EntityStatementLine> =
+            entity_lines(&[wallet()], &counterparties, &transactions)
+                .into_iter()
+                .map(|l| priced(l, Some("2000")))
+                .collect();
+        let (total_paid, total_received, unpriced_count) = totals(&lines);
+        let statement = EntityStatement {
+            entity_id: "e1".to_string(),
+            entity_name: "Clean Water Fund".to_string(),
+            entity_type: "grantee".to_string(),
+            tax_identifier: None,
+            period_start: Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap(),
+            period_end: Utc.with_ymd_and_hms(2025, 3, 31, 23, 59, 59).unwrap(),
+            currency: "USD".to_string(),
+            lines,
+            total_paid,
+            total_received,
+            unpriced_count,
+            price_sources: vec![PriceSource {
+                provider: "coingecko".to_string(),
+                endpoint: "https://api.coingecko.com/api/v3/coins/{id}/history".to_string(),
+                assets: vec!["ETH".to_string()],
+                valuations: 56,
+                first_retrieved_at: Utc.with_ymd_and_hms(2025, 4, 2, 9, 0, 0).unwrap(),
+                last_retrieved_at: Utc.with_ymd_and_hms(2025, 4, 2, 9, 1, 0).unwrap(),
+            }],
+            warnings: Vec::new(),
+        };
+
+        let bytes = render_statement_pdf(&statement, "Give Foundation");
+        let contains = |needle: &[u8]| bytes.windows(needle.len()).any(|w| w == needle);
+        assert!(bytes.starts_with(b"%PDF-1.4"));
+        assert!(contains(b"(Statement for Clean Water Fund) Tj"));
+        assert!(contains(b"(112000 USD) Tj"));
+        assert!(contains(b"(Appendix: Price Sources) Tj"));
+        assert!(contains(b"(56 value\\(s\\) of ETH) Tj"));
+        assert!(contains(b"/Count 3"));
+
+        let csv = String::from_utf8(statement_csv(&statement).unwrap()).unwrap();
+        assert_eq!(csv.lines().count(), 61);
+        assert!(csv
+            .starts_with("Date,Direction,Chain,Hash,Counterparty,Asset,Amount,Price,Value (USD),"));
+        assert!(
+            csv.contains("\ncoingecko,https://api.coingecko.com/api/v3/coins/{id}/history,ETH,56,")
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Structured errors returned across the command boundary.
+//!
+//! Commands that return [`ApiError`] give the frontend a stable error code,
+//! a message for display, whether retrying may help, and the provider or
+//! chain involved, so it can react without matching on message text (for
+//! example, offering to add an API key when a provider is rate limiting).
+//! Commands still returning `String` convert with `?`, as `ApiError`
+//! converts to and from `String`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::chains::ChainError;
+use crate::fetchers::api_keys::ApiKeyError;
+use crate::fetchers::FetchError;
+use crate::jobs::CANCELLED;
+
+/// What kind of failure an error is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// A provider refused the request for exceeding its rate limit.
+    RateLimited,
+    /// The network or a provider couldn't be reached.
+    Network,
+    /// A request took too long.
+    Timeout,
+    /// The app is offline and has nothing cached for the request.
+    Offline,
+    /// A provider answered with an error or a response that couldn't be read.
+    Provider,
+    /// The request named a chain that isn't supported.
+    UnsupportedChain,
+    /// The input was rejected, e.g. a malformed address.
+    InvalidInput,
+    /// The requested record doesn't exist.
+    NotFound,
+    /// Configuration, such as an RPC URL or API key, is missing or invalid.
+    Config,
+    /// The caller isn't signed in.
+    Unauthorized,
+    /// The caller's role doesn't allow the action.
+    Forbidden,
+    /// The user cancelled the operation.
+    Cancelled,
+    /// A database operation failed.
+    Database,
+    /// Anything else.
+    Internal,
+}
+
+impl ErrorCode {
+    /// Whether retrying the same request later may succeed.
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            ErrorCode::RateLimited | ErrorCode::Network | ErrorCode::Timeout | ErrorCode::Offline
+        )
+    }
+}
+
+/// An error returned by a command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[serde(rename_all = "camelCase")]
+#[error("{message}")]
+pub struct ApiError {
+    /// Kind of failure.
+    pub code: ErrorCode,
+    /// Message for display.
+    pub message: String,
+    /// Whether retrying the same request later may succeed.
+    pub retryable: bool,
+    /// API provider or chain the error came from, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+}
+
+impl ApiError {
+    /// Creates an error with the code's default retry hint.
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retryable: code.is_retryable(),
+            provider: None,
+        }
+    }
+
+    /// An invalid input error.
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidInput, message)
+    }
+
+    /// An offline error for a request with nothing cached.
+    pub fn offline(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Offline, message)
+    }
+
+    /// Names the provider or chain the error came from, unless one is
+    /// already set.
+    pub fn with_provider(mut self, provider: impl Into<String>) -> Self {
+        self.provider.get_or_insert_with(|| provider.into());
+        self
+    }
+}
+
+impl From<ChainError> for ApiError {
+    fn from(error: ChainError) -> Self {
+        let code = match &error {
+            ChainError::UnsupportedChain(_) => ErrorCode::UnsupportedChain,
+            ChainError::ConnectionFailed(_) => ErrorCode::Network,
+            ChainError::RpcError(_) | ChainError::ApiError(_) | ChainError::ParseError(_) => {
+                ErrorCode::Provider
+            }
+            ChainError::RateLimited => ErrorCode::RateLimited,
+            ChainError::InvalidAddress(_) => ErrorCode::InvalidInput,
+            ChainError::TransactionNotFound(_) | ChainError::BlockNotFound(_) => {
+                ErrorCode::NotFound
+            }
+            ChainError::ConfigError(_) => ErrorCode::Config,
+            ChainError::Internal(_) => ErrorCode::Internal,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+impl From<FetchError> for ApiError {
+    fn from(error: FetchError) -> Self {
+        let code = match &error {
+            FetchError::HttpError(_) => ErrorCode::Network,
+            FetchError::RateLimited => ErrorCode::RateLimited,
+            FetchError::ParseError(_) | FetchError::ApiError(_) => ErrorCode::Provider,
+            FetchError::ConfigError(_) => ErrorCode::Config,
+            FetchError::Timeout => ErrorCode::Timeout,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+impl From<ApiKeyError> for ApiError {
+    fn from(error: ApiKeyError) -> Self {
+        let code = match &error {
+            ApiKeyError::KeychainError(_) => ErrorCode::Config,
+            ApiKeyError::NotFound(_) => ErrorCode::NotFound,
+            ApiKeyError::InvalidProvider(_) => ErrorCode::InvalidInput,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(error: sqlx::Error) -> Self {
+        let code = match &error {
+            sqlx::Error::RowNotFound => ErrorCode::NotFound,
+            _ => ErrorCode::Database,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+impl From<std::io::Error> for ApiError {
+    fn from(error: std::io::Error) -> Self {
+        Self::new(ErrorCode::Internal, error.to_string())
+    }
+}
+
+/// Errors from code that still reports failures as text. A cancelled job is
+/// recognised; anything else is internal.
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        let code = if message == CANCELLED {
+            ErrorCode::Cancelled
+        } else {
+            ErrorCode::Internal
+        };
+        Self::new(code, message)
+    }
+}
+
+impl From<&str> for ApiError {
+    fn from(message: &str) -> Self {
+        Self::from(message.to_string())
+    }
+}
+
+/// Lets commands that still return `String` use `?` on an `ApiError`.
+impl From<ApiError> for String {
+    fn from(error: ApiError) -> Self {
+        error.message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limits_are_retryable() {
+        let error = ApiError::from(ChainError::RateLimited).with_provider("ethereum");
+        assert_eq!(error.code, ErrorCode::RateLimited);
+        assert!(error.retryable);
+        assert_eq!(error.provider.as_deref(), Some("ethereum"));
+
+        let error = ApiError::from(FetchError::ConfigError("no key".to_string()));
+        assert_eq!(error.code, ErrorCode::Config);
+        assert!(!error.retryable);
+    }
+
+    #[test]
+    fn test_serializes_for_the_frontend() {
+        let error = ApiError::from(FetchError::RateLimited).with_provider("etherscan");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": "rate_limited",
+                "message": "Rate limited",
+                "retryable": true,
+                "provider": "etherscan",
+            })
+        );
+    }
+
+    #[test]
+    fn test_string_conversions() {
+        assert_eq!(ApiError::from(CANCELLED).code, ErrorCode::Cancelled);
+        assert_eq!(ApiError::from("boom").code, ErrorCode::Internal);
+        assert_eq!(String::from(ApiError::invalid_input("bad")), "bad");
+        assert_eq!(
+            ApiError::from(sqlx::Error::RowNotFound).code,
+            ErrorCode::NotFound
+        );
+    }
+}
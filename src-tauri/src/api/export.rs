@@ -1,67 +1,61 @@
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::authorize_profile;
+use crate::core::auth_state::AuthState;
 use crate::db::Database;
 use anyhow::Result;
-use csv::Writer;
 use serde_json;
 
+pub use pacioli_core::services::export::*;
+
 /// Exports transactions to a CSV file at the specified path.
 ///
+/// Transactions in tokens the profile has marked as spam are left out.
+///
 /// # Arguments
-/// * `db` - Tauri state containing the database connection.
+/// * `state` - Tauri state containing the database connection.
+/// * `token` - Session token of a user allowed to export the profile.
 /// * `path` - The file system path where the CSV will be saved.
 /// * `profile_id` - Identifier for the user profile to export.
 /// * `start_date` - Optional start date filter.
 /// * `end_date` - Optional end date filter.
+/// * `gzip` - Compress the file with gzip.
 ///
 /// # Errors
 /// Returns a `String` error if database retrieval or file operations fail.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn export_transactions_csv(
-    db: tauri::State<'_, Database>,
+    state: tauri::State<'_, DatabaseState>,
+    auth: tauri::State<'_, AuthState>,
+    token: String,
     path: String,
     profile_id: String,
     start_date: Option<String>,
     end_date: Option<String>,
+    gzip: Option<bool>,
 ) -> Result<(), String> {
-    let transactions = db
-        .get_transactions(&profile_id, start_date, end_date)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let mut writer = Writer::from_path(path).map_err(|e| e.to_string())?;
-
-    // Write headers
-    writer
-        .write_record([
-            "Date", "Chain", "Hash", "From", "To", "Value", "Token", "Type", "Fee", "Status",
-        ])
-        .map_err(|e| e.to_string())?;
-
-    // Write transactions
-    for tx in transactions {
-        writer
-            .write_record(&[
-                tx.timestamp.to_string(),
-                tx.chain,
-                tx.hash,
-                tx.from_address,
-                tx.to_address.unwrap_or_default(),
-                tx.value.to_string(),
-                tx.token_symbol,
-                tx.transaction_type,
-                tx.fee.map(|f| f.to_string()).unwrap_or_default(),
-                tx.status,
-            ])
-            .map_err(|e| e.to_string())?;
-    }
-
-    writer.flush().map_err(|e| e.to_string())?;
-    Ok(())
+    authorize_profile(&state.pool, &auth, &token, &profile_id, Permission::Export).await?;
+    let db = Database {
+        pool: state.pool.clone(),
+    };
+    write_transactions_csv(
+        &db,
+        &path,
+        &profile_id,
+        start_date,
+        end_date,
+        gzip.unwrap_or(false),
+    )
+    .await
+    .map(|_| ())
 }
 
 /// Generates and returns a tax report for the specified year as JSON.
 ///
 /// # Arguments
-/// * `db` - Tauri state containing the database connection.
+/// * `state` - Tauri state containing the database connection.
+/// * `token` - Session token of a user allowed to export the profile.
 /// * `profile_id` - Identifier for the user profile.
 /// * `year` - The year for which the tax report is generated.
 ///
@@ -72,10 +66,16 @@ pub async fn export_transactions_csv(
 /// Returns a `String` error if report generation fails.
 #[tauri::command]
 pub async fn export_tax_report(
-    db: tauri::State<'_, Database>,
+    state: tauri::State<'_, DatabaseState>,
+    auth: tauri::State<'_, AuthState>,
+    token: String,
     profile_id: String,
     year: i32,
 ) -> Result<serde_json::Value, String> {
+    authorize_profile(&state.pool, &auth, &token, &profile_id, Permission::Export).await?;
+    let db = Database {
+        pool: state.pool.clone(),
+    };
     // Generate tax report data
     let report = generate_tax_report(&db, &profile_id, year)
         .await
@@ -0,0 +1,253 @@
+//! Tauri commands for managing export templates and exporting with one.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sqlx::types::Json;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, State};
+use uuid::Uuid;
+
+use super::export::ExportFile;
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::authorize_profile;
+use super::statement_export::StatementExportResult;
+use crate::core::auth_state::AuthState;
+use crate::jobs::{CancelToken, JobKind, JobRegistryState};
+
+pub use pacioli_core::services::export_templates::*;
+
+/// Event emitted after each page of an export is written.
+pub const EXPORT_PROGRESS_EVENT: &str = "export:progress";
+
+// ============================================================================
+// Loading
+// ============================================================================
+
+async fn load_template(
+    pool: &SqlitePool,
+    profile_id: &str,
+    id: &str,
+) -> Result<ExportTemplate, String> {
+    sqlx::query_as::<_, ExportTemplate>(
+        "SELECT * FROM export_templates WHERE profile_id = ? AND id = ?",
+    )
+    .bind(profile_id)
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Export template not found: {}", id))
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns a profile's export templates.
+#[tauri::command]
+pub async fn get_export_templates(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<ExportTemplate>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as::<_, ExportTemplate>(
+        "SELECT * FROM export_templates WHERE profile_id = ? ORDER BY name",
+    )
+    .bind(&profile_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Creates an export template, or updates the one with `input.id`.
+#[tauri::command]
+pub async fn save_export_template(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    input: ExportTemplateInput,
+) -> Result<ExportTemplate, String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &input.profile_id,
+        Permission::Export,
+    )
+    .await?;
+    let layout = ExportLayout::from_input(&input)?;
+
+    let now = Utc::now();
+    let id = match &input.id {
+        Some(id) => {
+            let result = sqlx::query(
+                r#"
+                UPDATE export_templates
+                SET name = ?, columns = ?, date_format = ?, decimal_separator = ?,
+                    delimiter = ?, updated_at = ?
+                WHERE profile_id = ? AND id = ?
+                "#,
+            )
+            .bind(input.name.trim())
+            .bind(Json(&layout.columns))
+            .bind(&layout.date_format)
+            .bind(layout.decimal_separator.as_str())
+            .bind(layout.delimiter.as_str())
+            .bind(now)
+            .bind(&input.profile_id)
+            .bind(id)
+            .execute(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+            if result.rows_affected() == 0 {
+                return Err(format!("Export template not found: {}", id));
+            }
+            id.clone()
+        }
+        None => {
+            let id = Uuid::new_v4().to_string();
+            sqlx::query(
+                r#"
+                INSERT INTO export_templates (
+                    id, profile_id, name, columns, date_format, decimal_separator, delimiter,
+                    created_by, created_at, updated_at
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&id)
+            .bind(&input.profile_id)
+            .bind(input.name.trim())
+            .bind(Json(&layout.columns))
+            .bind(&layout.date_format)
+            .bind(layout.decimal_separator.as_str())
+            .bind(layout.delimiter.as_str())
+            .bind(&user_id)
+            .bind(now)
+            .bind(now)
+            .execute(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+            id
+        }
+    };
+
+    load_template(&state.pool, &input.profile_id, &id).await
+}
+
+/// Deletes an export template.
+#[tauri::command]
+pub async fn delete_export_template(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    id: String,
+) -> Result<(), String> {
+    authorize_profile(&state.pool, &auth, &token, &profile_id, Permission::Export).await?;
+    let result = sqlx::query("DELETE FROM export_templates WHERE profile_id = ? AND id = ?")
+        .bind(&profile_id)
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if result.rows_affected() == 0 {
+        return Err(format!("Export template not found: {}", id));
+    }
+    Ok(())
+}
+
+/// Writes a profile's transactions to `path` as CSV laid out by a saved
+/// template, or in the default layout without one. Progress is emitted as
+/// [`EXPORT_PROGRESS_EVENT`] after each page. When `job_id` is given the
+/// export is registered as a job that `cancel_job` can stop; a stopped or
+/// failed export leaves no file behind.
+///
+/// # Arguments
+/// * `template_id` - Template to lay the export out with.
+/// * `start_date` / `end_date` - Optional period bounds, `YYYY-MM-DD` or RFC 3339.
+/// * `coin_ids` - Asset symbols mapped to price feed coin IDs, for fiat columns.
+/// * `gzip` - Compress the file with gzip.
+/// * `job_id` - ID to register the export under, for cancelling it.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_transactions_with_template(
+    app: AppHandle,
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    jobs: State<'_, JobRegistryState>,
+    token: String,
+    profile_id: String,
+    template_id: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    coin_ids: HashMap<String, String>,
+    path: String,
+    gzip: Option<bool>,
+    job_id: Option<String>,
+) -> Result<StatementExportResult, String> {
+    authorize_profile(&state.pool, &auth, &token, &profile_id, Permission::Export).await?;
+    let layout = match &template_id {
+        Some(id) => {
+            ExportLayout::from_template(&load_template(&state.pool, &profile_id, id).await?)?
+        }
+        None => ExportLayout::default(),
+    };
+
+    let (job_id, cancel) = match job_id {
+        Some(id) => {
+            let (id, token) = jobs.start(JobKind::Export, &path, Some(id));
+            (Some(id), token)
+        }
+        None => (None, CancelToken::new()),
+    };
+    let progress = ExportProgress {
+        job_id: job_id.clone(),
+        path: path.clone(),
+        ..ExportProgress::default()
+    };
+
+    let result = async {
+        let file = ExportFile::create(&path, gzip.unwrap_or(false))?;
+        let (file, written) = write_export(
+            &state.pool,
+            &profile_id,
+            &layout,
+            start_date.as_deref(),
+            end_date.as_deref(),
+            coin_ids,
+            file,
+            &cancel,
+            progress,
+            |progress| {
+                let _ = app.emit(EXPORT_PROGRESS_EVENT, progress);
+            },
+        )
+        .await?;
+        file.finish()?;
+        Ok::<_, String>(written)
+    }
+    .await;
+
+    if let Some(id) = &job_id {
+        jobs.finish(id, &result.as_ref().map(|_| ()).map_err(Clone::clone));
+    }
+    if result.is_err() {
+        let _ = std::fs::remove_file(&path);
+    }
+    Ok(StatementExportResult {
+        path,
+        line_count: result?,
+    })
+}
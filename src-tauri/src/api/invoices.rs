@@ -0,0 +1,853 @@
+//! Invoices: payment requests settled on chain.
+//!
+//! An invoice asks for an amount of a chain's native currency or of one
+//! token, paid to one of the profile's addresses. A background task checks
+//! each open invoice's payee address through the chain manager and marks the
+//! invoice paid when a single incoming payment of at least the amount
+//! arrives after the invoice was created, linking the stored transaction
+//! once the payee wallet has synced it. Invoices can be exported as CSV.
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use csv::Writer;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+use uuid::Uuid;
+
+use super::address_watch::native_currency;
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::authorize_profile;
+use super::statement_export::parse_amount;
+use crate::chains::{ChainManagerState, ChainTransaction, TransactionStatus};
+use crate::core::auth_state::AuthState;
+use crate::jobs::queue::{JobQueueState, JobTask};
+use crate::jobs::JobPriority;
+use crate::log_error;
+
+/// Event emitted to the frontend when an invoice is paid.
+pub const INVOICE_PAID_EVENT: &str = "invoice-paid";
+
+/// Interval between background checks.
+const INVOICE_POLL_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Delay before the first background check, so startup is not slowed down.
+const INVOICE_INITIAL_DELAY: Duration = Duration::from_secs(45);
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A stored invoice.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Invoice {
+    /// Unique identifier for the invoice.
+    pub id: String,
+    /// Profile that issued the invoice.
+    pub profile_id: String,
+    /// Human-readable invoice number, e.g. `INV-00042`.
+    pub invoice_number: String,
+    /// Sequence within the profile, starting at 1.
+    pub sequence: i64,
+    /// Payer entity, if the payer is on file.
+    pub payer_entity_id: Option<String>,
+    /// Payer name as shown on the invoice.
+    pub payer_name: Option<String>,
+    /// Address the payment is due to.
+    pub payee_address: String,
+    /// Chain the payment is due on.
+    pub chain: String,
+    /// Token contract, or `None` for the chain's native currency.
+    pub token_address: Option<String>,
+    /// Symbol of the asset due.
+    pub token_symbol: String,
+    /// Decimals of the asset due.
+    pub token_decimals: i32,
+    /// Amount due, in whole units.
+    pub amount: String,
+    /// Date payment is due.
+    pub due_date: Option<NaiveDate>,
+    /// Free-form memo.
+    pub memo: Option<String>,
+    /// `open`, `paid`, or `cancelled`.
+    pub status: String,
+    /// Highest block checked for a payment.
+    pub last_block: Option<i64>,
+    /// Hash of the payment.
+    pub paid_tx_hash: Option<String>,
+    /// Amount paid, in whole units.
+    pub paid_amount: Option<String>,
+    /// Address the payment came from.
+    pub paid_from: Option<String>,
+    /// When the payment was made.
+    pub paid_at: Option<DateTime<Utc>>,
+    /// Stored transaction of the payment, once the payee wallet has it.
+    pub settlement_transaction_id: Option<String>,
+    /// Timestamp when the invoice was created.
+    pub created_at: DateTime<Utc>,
+    /// Timestamp when the invoice was last updated.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Input for creating an invoice.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceInput {
+    /// Profile issuing the invoice.
+    pub profile_id: String,
+    /// Payer entity, if on file.
+    pub payer_entity_id: Option<String>,
+    /// Payer name, used when no entity is on file or to override it.
+    pub payer_name: Option<String>,
+    /// Address the payment is due to.
+    pub payee_address: String,
+    /// Chain the payment is due on.
+    pub chain: String,
+    /// Token contract; omit for the native currency.
+    pub token_address: Option<String>,
+    /// Token symbol. Required with `token_address`.
+    pub token_symbol: Option<String>,
+    /// Token decimals. Required with `token_address`.
+    pub token_decimals: Option<i32>,
+    /// Amount due, in whole units.
+    pub amount: String,
+    /// Date payment is due (`YYYY-MM-DD`).
+    pub due_date: Option<String>,
+    /// Free-form memo.
+    pub memo: Option<String>,
+}
+
+/// An incoming payment that settles an invoice, before it is stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvoicePayment {
+    /// Transaction hash.
+    pub tx_hash: String,
+    /// Amount paid, in whole units.
+    pub amount: Decimal,
+    /// Address the payment came from.
+    pub from: String,
+    /// Block containing the transaction.
+    pub block_number: u64,
+    /// Unix timestamp in seconds.
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// Detection
+// ============================================================================
+
+/// Formats an invoice number from its sequence.
+pub fn format_invoice_number(sequence: i64) -> String {
+    format!("INV-{:05}", sequence)
+}
+
+/// Finds the earliest successful payment to the invoice's payee address,
+/// made after the invoice was created, of at least the amount due in the
+/// invoice's asset. Partial payments don't settle an invoice.
+pub fn find_invoice_payment(
+    invoice: &Invoice,
+    transactions: &[ChainTransaction],
+) -> Option<InvoicePayment> {
+    let due = Decimal::from_str(&invoice.amount).ok()?;
+    let payee = invoice.payee_address.to_lowercase();
+    let created = invoice.created_at.timestamp();
+
+    let mut payments: Vec<InvoicePayment> = transactions
+        .iter()
+        .filter(|tx| tx.status == TransactionStatus::Success && tx.timestamp >= created)
+        .flat_map(|tx| {
+            let received: Vec<(Decimal, String)> = match &invoice.token_address {
+                None => tx
+                    .to
+                    .as_deref()
+                    .filter(|to| to.eq_ignore_ascii_case(&payee))
+                    .and_then(|_| parse_amount(&tx.value, Some(invoice.token_decimals)))
+                    .map(|amount| (amount, tx.from.clone()))
+                    .into_iter()
+                    .collect(),
+                Some(token) => tx
+                    .token_transfers
+                    .iter()
+                    .filter(|t| {
+                        t.token_address.eq_ignore_ascii_case(token)
+                            && t.to.eq_ignore_ascii_case(&payee)
+                    })
+                    .filter_map(|t| {
+                        let decimals = t
+                            .token_decimals
+                            .map(i32::from)
+                            .unwrap_or(invoice.token_decimals);
+                        Some((parse_amount(&t.value, Some(decimals))?, t.from.clone()))
+                    })
+                    .collect(),
+            };
+            received
+                .into_iter()
+                .filter(move |(amount, _)| *amount >= due)
+                .map(move |(amount, from)| InvoicePayment {
+                    tx_hash: tx.hash.clone(),
+                    amount: amount.normalize(),
+                    from,
+                    block_number: tx.block_number,
+                    timestamp: tx.timestamp,
+                })
+        })
+        .collect();
+
+    payments.sort_by_key(|p| (p.block_number, p.timestamp));
+    payments.into_iter().next()
+}
+
+/// Links an invoice to the stored transaction with its payment hash in the
+/// payee's wallet, if that wallet has synced it. Returns whether it linked.
+async fn link_settlement(pool: &SqlitePool, invoice: &Invoice) -> Result<bool, String> {
+    let Some(hash) = &invoice.paid_tx_hash else {
+        return Ok(false);
+    };
+    let linked = sqlx::query(
+        r#"
+        UPDATE invoices SET settlement_transaction_id = (
+            SELECT t.id FROM transactions t
+            INNER JOIN wallets w ON t.wallet_id = w.id
+            WHERE w.profile_id = ? AND LOWER(w.address) = LOWER(?)
+              AND LOWER(t.hash) = LOWER(?)
+            LIMIT 1
+        ), updated_at = ?
+        WHERE id = ? AND settlement_transaction_id IS NULL
+          AND EXISTS (
+              SELECT 1 FROM transactions t
+              INNER JOIN wallets w ON t.wallet_id = w.id
+              WHERE w.profile_id = ? AND LOWER(w.address) = LOWER(?)
+                AND LOWER(t.hash) = LOWER(?)
+          )
+        "#,
+    )
+    .bind(&invoice.profile_id)
+    .bind(&invoice.payee_address)
+    .bind(hash)
+    .bind(Utc::now())
+    .bind(&invoice.id)
+    .bind(&invoice.profile_id)
+    .bind(&invoice.payee_address)
+    .bind(hash)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(linked.rows_affected() > 0)
+}
+
+/// Checks every open invoice for a payment and links paid invoices to their
+/// stored transactions. Returns the invoices paid in this check.
+pub async fn run_invoice_checks(
+    pool: &SqlitePool,
+    manager: &ChainManagerState,
+    app: Option<&AppHandle>,
+) -> Result<Vec<Invoice>, String> {
+    let invoices: Vec<Invoice> =
+        sqlx::query_as("SELECT * FROM invoices WHERE status = 'open' ORDER BY created_at")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    let mut paid = Vec::new();
+    for invoice in invoices {
+        match check_invoice(pool, manager, &invoice).await {
+            Ok(Some(invoice)) => {
+                notify_paid(app, &invoice);
+                paid.push(invoice);
+            }
+            Ok(None) => {}
+            Err(e) => log_error!("Invoice {} check failed: {}", invoice.id, e),
+        }
+    }
+
+    let unlinked: Vec<Invoice> = sqlx::query_as(
+        "SELECT * FROM invoices WHERE status = 'paid' AND settlement_transaction_id IS NULL",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    for invoice in &unlinked {
+        link_settlement(pool, invoice).await?;
+    }
+
+    Ok(paid)
+}
+
+async fn check_invoice(
+    pool: &SqlitePool,
+    manager: &ChainManagerState,
+    invoice: &Invoice,
+) -> Result<Option<Invoice>, String> {
+    let from_block = invoice.last_block.map(|b| b.max(0) as u64 + 1);
+    let transactions = {
+        let manager = manager.read().await;
+        manager
+            .get_transactions(&invoice.chain, &invoice.payee_address, from_block)
+            .await
+            .map_err(|e| e.to_string())?
+    };
+    let highest_block = transactions
+        .iter()
+        .map(|tx| tx.block_number as i64)
+        .max()
+        .into_iter()
+        .chain(invoice.last_block)
+        .max();
+    let now = Utc::now();
+
+    let Some(payment) = find_invoice_payment(invoice, &transactions) else {
+        sqlx::query("UPDATE invoices SET last_block = ?, updated_at = ? WHERE id = ?")
+            .bind(highest_block)
+            .bind(now)
+            .bind(&invoice.id)
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(None);
+    };
+
+    let paid_at = Utc
+        .timestamp_opt(payment.timestamp, 0)
+        .single()
+        .unwrap_or(now);
+    sqlx::query(
+        r#"
+        UPDATE invoices
+        SET status = 'paid', last_block = ?, paid_tx_hash = ?, paid_amount = ?,
+            paid_from = ?, paid_at = ?, updated_at = ?
+        WHERE id = ? AND status = 'open'
+        "#,
+    )
+    .bind(highest_block)
+    .bind(&payment.tx_hash)
+    .bind(payment.amount.to_string())
+    .bind(&payment.from)
+    .bind(paid_at)
+    .bind(now)
+    .bind(&invoice.id)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let paid = load_invoice(pool, &invoice.profile_id, &invoice.id).await?;
+    link_settlement(pool, &paid).await?;
+    load_invoice(pool, &invoice.profile_id, &invoice.id)
+        .await
+        .map(Some)
+}
+
+/// Emits the frontend event and a native notification for a paid invoice.
+fn notify_paid(app: Option<&AppHandle>, invoice: &Invoice) {
+    let Some(app) = app else {
+        return;
+    };
+    let _ = app.emit(INVOICE_PAID_EVENT, invoice);
+    let amount = format!(
+        "{} {}",
+        invoice.paid_amount.as_deref().unwrap_or(&invoice.amount),
+        invoice.token_symbol
+    );
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title(format!("Invoice {} paid", invoice.invoice_number))
+        .body(amount)
+        .show()
+    {
+        log_error!("Failed to show invoice notification: {}", e);
+    }
+}
+
+/// Starts the background task that periodically queues a check of all open
+/// invoices.
+pub fn spawn_invoice_loop(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(INVOICE_INITIAL_DELAY).await;
+        loop {
+            let queue = app.state::<JobQueueState>().inner().clone();
+            if let Err(e) = queue
+                .enqueue(JobTask::InvoiceCheck, JobPriority::Background)
+                .await
+            {
+                log_error!("Failed to queue invoice check: {}", e);
+            }
+            tokio::time::sleep(INVOICE_POLL_INTERVAL).await;
+        }
+    });
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+async fn load_invoice(pool: &SqlitePool, profile_id: &str, id: &str) -> Result<Invoice, String> {
+    sqlx::query_as::<_, Invoice>("SELECT * FROM invoices WHERE profile_id = ? AND id = ?")
+        .bind(profile_id)
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Invoice not found: {}", id))
+}
+
+/// Checks an invoice's amount and asset, returning the asset's symbol and
+/// decimals.
+fn validate_input(input: &InvoiceInput) -> Result<(String, i32), String> {
+    match parse_amount(&input.amount, None) {
+        Some(amount) if amount > Decimal::ZERO => {}
+        _ => return Err(format!("Invalid amount: {}", input.amount)),
+    }
+    if input.payee_address.trim().is_empty() {
+        return Err("A payee address is required".to_string());
+    }
+
+    match &input.token_address {
+        None => Ok(native_currency(&input.chain)),
+        Some(_) => {
+            let symbol = input
+                .token_symbol
+                .clone()
+                .filter(|s| !s.trim().is_empty())
+                .ok_or_else(|| "A token symbol is required".to_string())?;
+            let decimals = input
+                .token_decimals
+                .filter(|d| (0..=36).contains(d))
+                .ok_or_else(|| "Token decimals are required".to_string())?;
+            Ok((symbol, decimals))
+        }
+    }
+}
+
+/// Renders invoices as CSV, one row per invoice.
+pub fn invoices_csv(invoices: &[Invoice]) -> Result<Vec<u8>, String> {
+    let mut writer = Writer::from_writer(Vec::new());
+    writer
+        .write_record([
+            "Invoice",
+            "Payer",
+            "Chain",
+            "Payee Address",
+            "Asset",
+            "Token Address",
+            "Amount",
+            "Due Date",
+            "Status",
+            "Paid Amount",
+            "Paid At",
+            "Payment Hash",
+            "Memo",
+        ])
+        .map_err(|e| e.to_string())?;
+    for invoice in invoices {
+        writer
+            .write_record([
+                invoice.invoice_number.clone(),
+                invoice.payer_name.clone().unwrap_or_default(),
+                invoice.chain.clone(),
+                invoice.payee_address.clone(),
+                invoice.token_symbol.clone(),
+                invoice.token_address.clone().unwrap_or_default(),
+                invoice.amount.clone(),
+                invoice.due_date.map(|d| d.to_string()).unwrap_or_default(),
+                invoice.status.clone(),
+                invoice.paid_amount.clone().unwrap_or_default(),
+                invoice
+                    .paid_at
+                    .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_default(),
+                invoice.paid_tx_hash.clone().unwrap_or_default(),
+                invoice.memo.clone().unwrap_or_default(),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+async fn profile_invoices(
+    pool: &SqlitePool,
+    profile_id: &str,
+    status: Option<&str>,
+) -> Result<Vec<Invoice>, String> {
+    sqlx::query_as::<_, Invoice>(
+        r#"
+        SELECT * FROM invoices
+        WHERE profile_id = ? AND (? IS NULL OR status = ?)
+        ORDER BY sequence DESC
+        "#,
+    )
+    .bind(profile_id)
+    .bind(status)
+    .bind(status)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Creates an open invoice, numbered sequentially within the profile.
+#[tauri::command]
+pub async fn create_invoice(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    input: InvoiceInput,
+) -> Result<Invoice, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &input.profile_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    let (symbol, decimals) = validate_input(&input)?;
+    let due_date = input
+        .due_date
+        .as_deref()
+        .map(|d| {
+            NaiveDate::parse_from_str(d, "%Y-%m-%d").map_err(|_| format!("Invalid date: {}", d))
+        })
+        .transpose()?;
+
+    let payer_name = match (&input.payer_name, &input.payer_entity_id) {
+        (Some(name), _) if !name.trim().is_empty() => Some(name.clone()),
+        (_, Some(entity_id)) => {
+            let row: Option<(String, Option<String>)> = sqlx::query_as(
+                "SELECT name, display_name FROM entities WHERE id = ? AND profile_id = ?",
+            )
+            .bind(entity_id)
+            .bind(&input.profile_id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+            let (name, display_name) = row.ok_or_else(|| "Payer entity not found".to_string())?;
+            Some(display_name.unwrap_or(name))
+        }
+        _ => None,
+    };
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now();
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+
+    let sequence: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(MAX(sequence), 0) + 1 FROM invoices WHERE profile_id = ?",
+    )
+    .bind(&input.profile_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO invoices (
+            id, profile_id, invoice_number, sequence, payer_entity_id, payer_name,
+            payee_address, chain, token_address, token_symbol, token_decimals, amount,
+            due_date, memo, status, created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'open', ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(&input.profile_id)
+    .bind(format_invoice_number(sequence))
+    .bind(sequence)
+    .bind(&input.payer_entity_id)
+    .bind(&payer_name)
+    .bind(input.payee_address.trim())
+    .bind(&input.chain)
+    .bind(input.token_address.as_deref().map(str::trim))
+    .bind(&symbol)
+    .bind(decimals)
+    .bind(input.amount.trim())
+    .bind(due_date)
+    .bind(&input.memo)
+    .bind(now)
+    .bind(now)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    load_invoice(&state.pool, &input.profile_id, &id).await
+}
+
+/// Lists a profile's invoices, newest first, optionally only those with
+/// one status.
+#[tauri::command]
+pub async fn get_invoices(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    status: Option<String>,
+) -> Result<Vec<Invoice>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    profile_invoices(&state.pool, &profile_id, status.as_deref()).await
+}
+
+/// Cancels an open invoice, so it's no longer checked for payment.
+#[tauri::command]
+pub async fn cancel_invoice(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    id: String,
+) -> Result<Invoice, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    let result = sqlx::query(
+        "UPDATE invoices SET status = 'cancelled', updated_at = ? WHERE profile_id = ? AND id = ? AND status = 'open'",
+    )
+    .bind(Utc::now())
+    .bind(&profile_id)
+    .bind(&id)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    if result.rows_affected() == 0 {
+        return Err(format!("No open invoice: {}", id));
+    }
+    load_invoice(&state.pool, &profile_id, &id).await
+}
+
+/// Marks an open invoice paid by `tx_hash`, for payments the check can't
+/// see, and links the stored transaction if the payee wallet has it.
+#[tauri::command]
+pub async fn mark_invoice_paid(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    id: String,
+    tx_hash: String,
+) -> Result<Invoice, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    let now = Utc::now();
+    let result = sqlx::query(
+        r#"
+        UPDATE invoices
+        SET status = 'paid', paid_tx_hash = ?, paid_amount = amount, paid_at = ?, updated_at = ?
+        WHERE profile_id = ? AND id = ? AND status = 'open'
+        "#,
+    )
+    .bind(tx_hash.trim())
+    .bind(now)
+    .bind(now)
+    .bind(&profile_id)
+    .bind(&id)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    if result.rows_affected() == 0 {
+        return Err(format!("No open invoice: {}", id));
+    }
+
+    let invoice = load_invoice(&state.pool, &profile_id, &id).await?;
+    link_settlement(&state.pool, &invoice).await?;
+    load_invoice(&state.pool, &profile_id, &id).await
+}
+
+/// Checks all open invoices immediately instead of waiting for the
+/// background task. Returns the invoices found paid.
+#[tauri::command]
+pub async fn check_invoices(
+    app: AppHandle,
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    chain_manager: State<'_, ChainManagerState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<Invoice>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let paid = run_invoice_checks(&state.pool, chain_manager.inner(), Some(&app)).await?;
+    Ok(paid
+        .into_iter()
+        .filter(|invoice| invoice.profile_id == profile_id)
+        .collect())
+}
+
+/// Writes a profile's invoices to `path` as CSV. Returns the number written.
+#[tauri::command]
+pub async fn export_invoices_csv(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    path: String,
+) -> Result<usize, String> {
+    authorize_profile(&state.pool, &auth, &token, &profile_id, Permission::Export).await?;
+    let invoices = profile_invoices(&state.pool, &profile_id, None).await?;
+    std::fs::write(&path, invoices_csv(&invoices)?).map_err(|e| e.to_string())?;
+    Ok(invoices.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::{ChainId, TokenTransfer, TransactionType};
+
+    const PAYEE: &str = "0xAbC0000000000000000000000000000000000001";
+    const PAYER: &str = "0x0000000000000000000000000000000000000002";
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    const CREATED: i64 = 1_700_000_000;
+
+    fn invoice(token_address: Option<&str>, amount: &str) -> Invoice {
+        let created_at = Utc.timestamp_opt(CREATED, 0).unwrap();
+        Invoice {
+            id: "i1".to_string(),
+            profile_id: "p1".to_string(),
+            invoice_number: format_invoice_number(1),
+            sequence: 1,
+            payer_entity_id: None,
+            payer_name: Some("Acme".to_string()),
+            payee_address: PAYEE.to_string(),
+            chain: "ethereum".to_string(),
+            token_address: token_address.map(str::to_string),
+            token_symbol: if token_address.is_some() {
+                "USDC"
+            } else {
+                "ETH"
+            }
+            .to_string(),
+            token_decimals: if token_address.is_some() { 6 } else { 18 },
+            amount: amount.to_string(),
+            due_date: None,
+            memo: None,
+            status: "open".to_string(),
+            last_block: None,
+            paid_tx_hash: None,
+            paid_amount: None,
+            paid_from: None,
+            paid_at: None,
+            settlement_transaction_id: None,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    fn tx(hash: &str, block: u64, offset: i64, to: &str, wei: &str) -> ChainTransaction {
+        ChainTransaction {
+            hash: hash.to_string(),
+            chain_id: ChainId::evm("ethereum", 1),
+            block_number: block,
+            timestamp: CREATED + offset,
+            from: PAYER.to_string(),
+            to: Some(to.to_string()),
+            value: wei.to_string(),
+            fee: "0".to_string(),
+            status: TransactionStatus::Success,
+            tx_type: TransactionType::Transfer,
+            token_transfers: Vec::new(),
+            swaps: Vec::new(),
+            fee_breakdown: None,
+            user_operations: Vec::new(),
+            inner_calls: Vec::new(),
+            raw_data: None,
+        }
+    }
+
+    #[test]
+    fn test_format_invoice_number() {
+        assert_eq!(format_invoice_number(42), "INV-00042");
+    }
+
+    #[test]
+    fn test_finds_native_payment_after_creation() {
+        let mut failed = tx("0x4", 13, 30, PAYEE, "2000000000000000000");
+        failed.status = TransactionStatus::Failed;
+        let txs = vec![
+            tx("0x1", 10, -60, PAYEE, "2000000000000000000"),
+            tx("0x2", 11, 60, PAYEE, "500000000000000000"),
+            failed,
+            tx("0x5", 15, 120, &PAYEE.to_lowercase(), "1500000000000000000"),
+            tx("0x3", 12, 90, PAYER, "2000000000000000000"),
+        ];
+
+        let payment = find_invoice_payment(&invoice(None, "1.5"), &txs).unwrap();
+        assert_eq!(payment.tx_hash, "0x5");
+        assert_eq!(payment.amount, Decimal::from_str("1.5").unwrap());
+        assert_eq!(payment.from, PAYER);
+        assert!(find_invoice_payment(&invoice(None, "3"), &txs).is_none());
+    }
+
+    #[test]
+    fn test_finds_token_payment() {
+        let mut payment_tx = tx("0x9", 20, 10, USDC, "0");
+        payment_tx.token_transfers = vec![
+            TokenTransfer {
+                token_address: "0xdac17f958d2ee523a2206206994597c13d831ec7".to_string(),
+                token_symbol: Some("USDT".to_string()),
+                token_decimals: Some(6),
+                from: PAYER.to_string(),
+                to: PAYEE.to_string(),
+                value: "250000000".to_string(),
+            },
+            TokenTransfer {
+                token_address: USDC.to_uppercase().replace("0X", "0x"),
+                token_symbol: Some("USDC".to_string()),
+                token_decimals: Some(6),
+                from: PAYER.to_string(),
+                to: PAYEE.to_lowercase(),
+                value: "250000000".to_string(),
+            },
+        ];
+
+        let payment = find_invoice_payment(&invoice(Some(USDC), "250"), &[payment_tx]).unwrap();
+        assert_eq!(payment.tx_hash, "0x9");
+        assert_eq!(payment.amount, Decimal::from(250));
+    }
+
+    #[test]
+    fn test_invoices_csv() {
+        let mut paid = invoice(None, "1.5");
+        paid.status = "paid".to_string();
+        paid.paid_amount = Some("1.5".to_string());
+        paid.paid_tx_hash = Some("0x5".to_string());
+        let csv = String::from_utf8(invoices_csv(&[paid]).unwrap()).unwrap();
+
+        assert!(csv.starts_with("Invoice,Payer,Chain,"));
+        assert!(csv.contains(&format!(
+            "INV-00001,Acme,ethereum,{},ETH,,1.5,,paid,1.5,,0x5,\n",
+            PAYEE
+        )));
+    }
+}
@@ -0,0 +1,210 @@
+//! Persistence and rotation of the JWT signing keyring.
+//!
+//! The keyring is stored in settings, encrypted with a random passphrase kept
+//! in the OS keychain, so sessions survive a restart while the database alone
+//! is not enough to forge tokens. The signing key is rotated on a schedule or
+//! on demand by the app administrator; tokens signed by the previous key stay
+//! valid for the keyring's grace period.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use keyring::Entry;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager, State};
+
+use super::auth::{log_audit_event, verify_admin};
+use super::persistence::DatabaseState;
+use crate::core::auth_helpers::{generate_secure_token, verify_access_token};
+use crate::core::auth_state::AuthState;
+use crate::core::jwt_keys::{JwtKeyInfo, JwtKeyring};
+use crate::log_error;
+use crate::storage::encryption::{decrypt, encrypt, EncryptedData};
+use crate::storage::settings_store;
+
+/// Settings key for the encrypted keyring.
+const SETTINGS_KEY: &str = "jwt_keyring";
+
+/// Keychain service holding the keyring passphrase.
+const KEYCHAIN_SERVICE: &str = "pacioli";
+
+/// Keychain entry holding the keyring passphrase.
+const KEYCHAIN_PASSPHRASE_KEY: &str = "jwt_keyring_key";
+
+/// How often the scheduled rotation checks the signing key's age.
+const ROTATION_CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// The keyring as stored in settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredKeyring {
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Returns the keyring passphrase from the keychain, creating one if none
+/// is stored.
+fn keychain_passphrase() -> Result<String, String> {
+    let entry = Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_PASSPHRASE_KEY)
+        .map_err(|e| format!("Keychain access failed: {}", e))?;
+    match entry.get_password() {
+        Ok(passphrase) => Ok(passphrase),
+        Err(keyring::Error::NoEntry) => {
+            let passphrase = generate_secure_token(32);
+            entry
+                .set_password(&passphrase)
+                .map_err(|e| format!("Keychain access failed: {}", e))?;
+            Ok(passphrase)
+        }
+        Err(e) => Err(format!("Keychain access failed: {}", e)),
+    }
+}
+
+/// Encrypts and saves the keyring.
+async fn save_keyring(pool: &SqlitePool, keys: &JwtKeyring) -> Result<(), String> {
+    let passphrase = keychain_passphrase()?;
+    let json = serde_json::to_vec(keys).map_err(|e| e.to_string())?;
+    let encrypted = encrypt(&json, &passphrase).map_err(|e| e.to_string())?;
+    let stored = StoredKeyring {
+        salt: encrypted.salt,
+        nonce: encrypted.nonce,
+        ciphertext: encrypted.ciphertext,
+    };
+
+    settings_store::set_setting_json(pool, SETTINGS_KEY, &stored)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Loads and decrypts the saved keyring, if there is one.
+async fn load_keyring(pool: &SqlitePool) -> Result<Option<JwtKeyring>, String> {
+    let stored: Option<StoredKeyring> = settings_store::get_setting_json(pool, SETTINGS_KEY)
+        .await
+        .map_err(|e| e.to_string())?;
+    let Some(stored) = stored else {
+        return Ok(None);
+    };
+
+    let passphrase = keychain_passphrase()?;
+    let encrypted = EncryptedData {
+        salt: stored.salt,
+        nonce: stored.nonce,
+        ciphertext: stored.ciphertext,
+    };
+    let json = decrypt(&encrypted, &passphrase)
+        .map_err(|_| "Stored JWT keyring could not be decrypted".to_string())?;
+    let keys: JwtKeyring = serde_json::from_slice(&json).map_err(|e| e.to_string())?;
+    keys.validate()?;
+    Ok(Some(keys))
+}
+
+/// Installs the saved keyring, or saves the one generated at startup if
+/// there is none or it can't be read.
+///
+/// If the keychain is unavailable the generated keyring is kept in memory
+/// only, and sessions end when the app closes.
+pub async fn init_jwt_keys(pool: &SqlitePool, auth: &AuthState) -> Result<(), String> {
+    match load_keyring(pool).await {
+        Ok(Some(keys)) => {
+            auth.set_jwt_keys(keys);
+            Ok(())
+        }
+        Ok(None) => save_keyring(pool, &auth.jwt_keys()).await,
+        Err(e) => {
+            log_error!("Warning: {}, generating new JWT keys", e);
+            save_keyring(pool, &auth.jwt_keys()).await
+        }
+    }
+}
+
+/// Rotates the signing key and saves the keyring.
+async fn rotate_and_save(pool: &SqlitePool, auth: &AuthState) -> Result<Vec<JwtKeyInfo>, String> {
+    let keys = auth.rotate_jwt_keys();
+    save_keyring(pool, &keys).await?;
+    Ok(keys.info())
+}
+
+/// Starts the background task that rotates the signing key once it is older
+/// than the rotation interval.
+pub fn spawn_key_rotation_loop(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let auth = app.state::<AuthState>();
+            if auth.jwt_keys().rotation_due(Utc::now()) {
+                let pool = app.state::<DatabaseState>().pool.clone();
+                match rotate_and_save(&pool, &auth).await {
+                    Ok(_) => {
+                        log_audit_event(
+                            &pool,
+                            None,
+                            "jwt_key_rotated",
+                            "success",
+                            Some("scheduled"),
+                            None,
+                            None,
+                        )
+                        .await;
+                    }
+                    Err(e) => log_error!("Failed to save rotated JWT keys: {}", e),
+                }
+            }
+            tokio::time::sleep(ROTATION_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// List the JWT signing keys, without their secrets (app administrator only)
+#[tauri::command]
+pub async fn get_jwt_keys(
+    db: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+) -> Result<Vec<JwtKeyInfo>, String> {
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
+    verify_admin(&db.pool, &claims.sub).await?;
+
+    Ok(auth.jwt_keys().info())
+}
+
+/// Rotate the JWT signing key now (app administrator only)
+///
+/// Existing tokens stay valid until they expire; new tokens are signed with
+/// the new key.
+#[tauri::command]
+pub async fn rotate_jwt_key(
+    db: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+) -> Result<Vec<JwtKeyInfo>, String> {
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
+    let pool = &db.pool;
+    verify_admin(pool, &claims.sub).await?;
+
+    let info = rotate_and_save(pool, &auth).await?;
+
+    log_audit_event(
+        pool,
+        Some(&claims.sub),
+        "jwt_key_rotated",
+        "success",
+        Some(&info[0].kid),
+        None,
+        None,
+    )
+    .await;
+
+    Ok(info)
+}
@@ -0,0 +1,214 @@
+//! Import of Lightning Network payments into a wallet.
+//!
+//! A wallet whose address is a Lightning node's public key can be connected
+//! to that node. The connection (endpoint, macaroon or rune, and TLS
+//! certificate) is kept in the system keychain, never in the database, and
+//! importing reads the node's completed payments and settled invoices into
+//! the wallet's transactions. Each item is keyed by direction and payment
+//! hash, so importing again updates rather than duplicates.
+//!
+//! Amounts and fees are stored in millisatoshis, as BTC with 11 decimals,
+//! since Lightning settles below a satoshi.
+
+use chrono::{TimeZone, Utc};
+use keyring::Entry;
+use tauri::State;
+
+use super::permissions::Permission;
+use super::persistence::{store_transactions, DatabaseState, TransactionInput};
+use super::profile_scope::authorize_wallet;
+use super::wallet_sync::enum_name;
+use crate::chains::lightning::{self, LightningNodeConfig, LightningPayment, PaymentDirection};
+use crate::chains::{TransactionStatus, TransactionType};
+use crate::core::auth_state::AuthState;
+
+/// Keychain service name, shared with the app's other secrets.
+const KEYCHAIN_SERVICE: &str = "pacioli";
+
+/// Symbol amounts are recorded in.
+const LIGHTNING_SYMBOL: &str = "BTC";
+
+/// Decimals of a millisatoshi amount of BTC.
+const MSAT_DECIMALS: i32 = 11;
+
+/// Counterparty recorded when the other side of a payment is unknown.
+const LIGHTNING_COUNTERPARTY: &str = "lightning";
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn keychain_entry(wallet_id: &str) -> Result<Entry, String> {
+    Entry::new(KEYCHAIN_SERVICE, &format!("lightning_node_{}", wallet_id))
+        .map_err(|e| format!("Keychain access failed: {}", e))
+}
+
+fn load_node(wallet_id: &str) -> Result<Option<LightningNodeConfig>, String> {
+    match keychain_entry(wallet_id)?.get_password() {
+        Ok(json) => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Stored Lightning node is unreadable: {}", e)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Keychain access failed: {}", e)),
+    }
+}
+
+/// Converts a node payment into the form stored for a wallet.
+fn to_transaction_input(payment: &LightningPayment, node: &str, chain: &str) -> TransactionInput {
+    let counterparty = payment
+        .counterparty
+        .clone()
+        .unwrap_or_else(|| LIGHTNING_COUNTERPARTY.to_string());
+    let (from_address, to_address) = match payment.direction {
+        PaymentDirection::Sent => (node.to_string(), counterparty),
+        PaymentDirection::Received => (counterparty, node.to_string()),
+    };
+
+    TransactionInput {
+        hash: payment.id.clone(),
+        block_number: None,
+        timestamp: Utc
+            .timestamp_opt(payment.timestamp, 0)
+            .single()
+            .map(|t| t.to_rfc3339()),
+        from_address: Some(from_address),
+        to_address: Some(to_address),
+        value: Some(payment.amount_msat.to_string()),
+        fee: Some(payment.fee_msat.to_string()),
+        status: enum_name(&TransactionStatus::Success),
+        tx_type: enum_name(&TransactionType::Transfer),
+        token_symbol: Some(LIGHTNING_SYMBOL.to_string()),
+        token_decimals: Some(MSAT_DECIMALS),
+        chain: chain.to_string(),
+        raw_data: serde_json::to_string(payment).ok(),
+        swaps: Vec::new(),
+        token_transfers: Vec::new(),
+        fee_breakdown: None,
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Connects a wallet to its Lightning node, replacing any earlier
+/// connection. Requires the manage keys permission on the wallet's profile.
+#[tauri::command]
+pub async fn save_lightning_node(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    wallet_id: String,
+    node: LightningNodeConfig,
+) -> Result<(), String> {
+    authorize_wallet(
+        &state.pool,
+        &auth,
+        &token,
+        &wallet_id,
+        Permission::ManageKeys,
+    )
+    .await?;
+    if node.url.trim().is_empty() || node.credential.trim().is_empty() {
+        return Err("Node URL and credential are required".to_string());
+    }
+    let json = serde_json::to_string(&node).map_err(|e| e.to_string())?;
+    keychain_entry(&wallet_id)?
+        .set_password(&json)
+        .map_err(|e| format!("Keychain access failed: {}", e))
+}
+
+/// Disconnects a wallet from its Lightning node. Imported payments stay.
+/// Requires the manage keys permission on the wallet's profile.
+#[tauri::command]
+pub async fn delete_lightning_node(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    wallet_id: String,
+) -> Result<(), String> {
+    authorize_wallet(
+        &state.pool,
+        &auth,
+        &token,
+        &wallet_id,
+        Permission::ManageKeys,
+    )
+    .await?;
+    match keychain_entry(&wallet_id)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Keychain access failed: {}", e)),
+    }
+}
+
+/// Imports a wallet's completed Lightning payments and settled invoices from
+/// its connected node, returning the number of transactions saved.
+///
+/// Requires the owner, admin, or preparer role on the wallet's profile.
+#[tauri::command]
+pub async fn import_lightning_payments(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    wallet_id: String,
+) -> Result<usize, String> {
+    let (user_id, wallet) = authorize_wallet(
+        &state.pool,
+        &auth,
+        &token,
+        &wallet_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    let node = load_node(&wallet_id)?
+        .ok_or_else(|| "Wallet is not connected to a Lightning node".to_string())?;
+
+    let payments = lightning::fetch_payments(&node)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let inputs = payments
+        .iter()
+        .map(|p| to_transaction_input(p, &wallet.address, &wallet.chain))
+        .collect();
+    store_transactions(&state.pool, &user_id, &wallet, inputs).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NODE: &str = "02b1f3c5d7e9a1b3c5d7e9f1a3b5c7d9e1f3a5b7c9d1e3f5a7b9c1d3e5f7a9b1c3";
+
+    #[test]
+    fn test_to_transaction_input_directions() {
+        let sent = LightningPayment {
+            amount_msat: 150_000_500,
+            fee_msat: 2_150,
+            timestamp: 1_713_571_603,
+            counterparty: Some("02df5ffe".to_string()),
+            ..LightningPayment::new("c3a5", PaymentDirection::Sent)
+        };
+        let input = to_transaction_input(&sent, NODE, "lightning");
+        assert_eq!(input.hash, "lightning:sent:c3a5");
+        assert_eq!(input.from_address.as_deref(), Some(NODE));
+        assert_eq!(input.to_address.as_deref(), Some("02df5ffe"));
+        assert_eq!(input.value.as_deref(), Some("150000500"));
+        assert_eq!(input.fee.as_deref(), Some("2150"));
+        assert_eq!(input.token_decimals, Some(11));
+        assert_eq!(input.tx_type.as_deref(), Some("transfer"));
+        assert_eq!(
+            input.timestamp.as_deref(),
+            Some("2024-04-20T00:06:43+00:00")
+        );
+
+        let received = LightningPayment {
+            amount_msat: 21_000_000,
+            ..LightningPayment::new("e5c7", PaymentDirection::Received)
+        };
+        let input = to_transaction_input(&received, NODE, "lightning");
+        assert_eq!(input.from_address.as_deref(), Some("lightning"));
+        assert_eq!(input.to_address.as_deref(), Some(NODE));
+        assert_eq!(input.fee.as_deref(), Some("0"));
+    }
+}
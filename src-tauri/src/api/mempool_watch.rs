@@ -0,0 +1,545 @@
+//! Zero-confirmation monitoring of watched Bitcoin addresses.
+//!
+//! The address watch check only sees mined transactions. This check polls
+//! mempool.space for each active Bitcoin watch's unconfirmed transactions so
+//! an incoming payment shows as pending as soon as it is broadcast. The
+//! payment is then followed until it has the number of confirmations the
+//! profile requires, when it becomes a success, or until it is replaced or
+//! dropped from the mempool.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, SqlitePool};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+use uuid::Uuid;
+
+use super::address_watch::{native_currency, AddressWatch};
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::authorize_profile;
+use super::statement_export::parse_amount;
+use crate::chains::bitcoin::{BitcoinAdapter, BitcoinTransaction, PendingTxState, TxOutpoint};
+use crate::core::auth_state::AuthState;
+use crate::jobs::queue::{JobQueueState, JobTask};
+use crate::jobs::JobPriority;
+use crate::log_error;
+
+pub use pacioli_core::services::mempool_watch::*;
+
+/// Event emitted to the frontend when a payment is seen or changes status.
+pub const MEMPOOL_PAYMENT_EVENT: &str = "mempool-payment";
+
+/// Confirmations required by a profile that hasn't chosen.
+pub const DEFAULT_BITCOIN_CONFIRMATIONS: u32 = 1;
+
+/// Most confirmations a profile may require.
+const MAX_BITCOIN_CONFIRMATIONS: u32 = 100;
+
+/// Interval between background checks; shorter than the address watch so
+/// payments appear soon after broadcast.
+const MEMPOOL_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Delay before the first background check, so startup is not slowed down.
+const MEMPOOL_INITIAL_DELAY: Duration = Duration::from_secs(40);
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Confirmations a profile requires before a payment counts as received.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmationSettings {
+    /// Profile the settings belong to.
+    pub profile_id: String,
+    /// Confirmations required for Bitcoin; 0 accepts payments on broadcast.
+    pub bitcoin_confirmations: u32,
+    /// When the settings were last changed; `None` for defaults.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// An incoming payment to a watched address, seen in the mempool.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolPayment {
+    /// Unique identifier for the payment.
+    pub id: String,
+    /// The watch whose address is paid.
+    pub watch_id: String,
+    /// Transaction ID.
+    pub txid: String,
+    /// Amount received, in native units.
+    pub amount: String,
+    /// Native currency symbol.
+    pub symbol: String,
+    /// Address of the first input.
+    pub counterparty: Option<String>,
+    /// Outputs the transaction spends.
+    pub inputs: Json<Vec<TxOutpoint>>,
+    /// `pending`, `success`, `replaced`, or `dropped`.
+    pub status: String,
+    /// Confirmations when last checked.
+    pub confirmations: i64,
+    /// Block the transaction confirmed in.
+    pub block_height: Option<i64>,
+    /// The transaction that replaced it.
+    pub replaced_by: Option<String>,
+    /// When the payment was first seen.
+    pub first_seen_at: DateTime<Utc>,
+    /// When the payment reached the required confirmations.
+    pub confirmed_at: Option<DateTime<Utc>>,
+    /// When the payment was last checked.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An unconfirmed transaction paying a watched address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncomingPayment {
+    /// Satoshis paid to the address.
+    pub value: u64,
+    /// Address of the first input.
+    pub counterparty: Option<String>,
+}
+
+// ============================================================================
+// Detection
+// ============================================================================
+
+/// What `tx` pays `address`, if it pays it anything and doesn't spend from
+/// it; a transaction spending from the address only returns change.
+pub fn incoming_payment(tx: &BitcoinTransaction, address: &str) -> Option<IncomingPayment> {
+    if tx
+        .inputs
+        .iter()
+        .any(|i| i.address.as_deref() == Some(address))
+    {
+        return None;
+    }
+    let value: u64 = tx
+        .outputs
+        .iter()
+        .filter(|o| o.address.as_deref() == Some(address))
+        .map(|o| o.value)
+        .sum();
+    (value > 0).then(|| IncomingPayment {
+        value,
+        counterparty: tx.inputs.first().and_then(|i| i.address.clone()),
+    })
+}
+
+/// Loads a profile's confirmation settings, falling back to the defaults.
+pub(crate) async fn load_confirmation_settings(
+    pool: &SqlitePool,
+    profile_id: &str,
+) -> Result<ConfirmationSettings, String> {
+    let settings: Option<ConfirmationSettings> =
+        sqlx::query_as("SELECT * FROM profile_confirmation_settings WHERE profile_id = ?")
+            .bind(profile_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    Ok(settings.unwrap_or_else(|| ConfirmationSettings {
+        profile_id: profile_id.to_string(),
+        bitcoin_confirmations: DEFAULT_BITCOIN_CONFIRMATIONS,
+        updated_at: None,
+    }))
+}
+
+// ============================================================================
+// Checks
+// ============================================================================
+
+/// Records new mempool payments to every active Bitcoin watch and follows
+/// pending ones to confirmation. Returns the payments seen or changed in
+/// this check.
+pub async fn run_mempool_checks(
+    pool: &SqlitePool,
+    app: Option<&AppHandle>,
+) -> Result<Vec<MempoolPayment>, String> {
+    let watches: Vec<AddressWatch> =
+        sqlx::query_as("SELECT * FROM address_watches WHERE is_active = 1")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    let mut changed = Vec::new();
+    for watch in watches {
+        let Ok(adapter) = BitcoinAdapter::from_network(&watch.chain_id) else {
+            continue;
+        };
+        match check_watch(pool, &adapter, &watch).await {
+            Ok(payments) => {
+                for payment in &payments {
+                    notify(app, &watch, payment);
+                }
+                changed.extend(payments);
+            }
+            Err(e) => log_error!("Mempool check of watch {} failed: {}", watch.id, e),
+        }
+    }
+
+    Ok(changed)
+}
+
+async fn check_watch(
+    pool: &SqlitePool,
+    adapter: &BitcoinAdapter,
+    watch: &AddressWatch,
+) -> Result<Vec<MempoolPayment>, String> {
+    let (symbol, _) = native_currency(&watch.chain_id);
+    let threshold = parse_amount(&watch.threshold, None).unwrap_or(Decimal::ZERO);
+    let now = Utc::now();
+    let mut changed = Vec::new();
+
+    let unconfirmed = adapter
+        .fetch_mempool_transactions(&watch.address)
+        .await
+        .map_err(|e| e.to_string())?;
+    for tx in &unconfirmed {
+        let Some(payment) = incoming_payment(tx, &watch.address) else {
+            continue;
+        };
+        let amount = Decimal::new(payment.value as i64, 8).normalize();
+        if amount < threshold {
+            continue;
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let inserted = sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO mempool_payments (
+                id, watch_id, txid, amount, symbol, counterparty, inputs, status,
+                confirmations, first_seen_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, 'pending', 0, ?, ?)
+            "#,
+        )
+        .bind(&id)
+        .bind(&watch.id)
+        .bind(&tx.txid)
+        .bind(amount.to_string())
+        .bind(&symbol)
+        .bind(&payment.counterparty)
+        .bind(Json(TxOutpoint::spent_by(tx)))
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        if inserted.rows_affected() > 0 {
+            changed.push(load_payment(pool, &id).await?);
+        }
+    }
+
+    let pending: Vec<MempoolPayment> = sqlx::query_as(
+        "SELECT * FROM mempool_payments WHERE watch_id = ? AND status = 'pending' ORDER BY first_seen_at",
+    )
+    .bind(&watch.id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    if pending.is_empty() {
+        return Ok(changed);
+    }
+
+    let required = load_confirmation_settings(pool, &watch.profile_id)
+        .await?
+        .bitcoin_confirmations as u64;
+    let tip = adapter
+        .get_block_number()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for payment in pending {
+        let state = adapter
+            .check_pending(&payment.txid, &payment.inputs)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let (status, count, block_height, replaced_by) = match state {
+            PendingTxState::Pending { .. } if required == 0 => ("success", 0, None, None),
+            PendingTxState::Pending { .. } => continue,
+            PendingTxState::Confirmed { block_height, .. } => {
+                let count = block_height.map_or(1, |h| confirmations(h, tip));
+                let status = if count >= required {
+                    "success"
+                } else {
+                    "pending"
+                };
+                (status, count, block_height, None)
+            }
+            PendingTxState::Replaced { by, .. } => ("replaced", 0, None, Some(by)),
+            PendingTxState::Dropped => ("dropped", 0, None, None),
+        };
+        if status == payment.status && count as i64 == payment.confirmations {
+            continue;
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE mempool_payments
+            SET status = ?, confirmations = ?, block_height = ?, replaced_by = ?,
+                confirmed_at = CASE WHEN ? = 'success' THEN ? ELSE confirmed_at END,
+                updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(status)
+        .bind(count as i64)
+        .bind(block_height.map(|h| h as i64))
+        .bind(&replaced_by)
+        .bind(status)
+        .bind(now)
+        .bind(now)
+        .bind(&payment.id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        // Confirmations ticking up aren't worth a notification
+        if status != payment.status {
+            changed.push(load_payment(pool, &payment.id).await?);
+        }
+    }
+
+    Ok(changed)
+}
+
+async fn load_payment(pool: &SqlitePool, id: &str) -> Result<MempoolPayment, String> {
+    sqlx::query_as("SELECT * FROM mempool_payments WHERE id = ?")
+        .bind(id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Emits the frontend event and a native notification for a payment.
+fn notify(app: Option<&AppHandle>, watch: &AddressWatch, payment: &MempoolPayment) {
+    let Some(app) = app else {
+        return;
+    };
+    let _ = app.emit(MEMPOOL_PAYMENT_EVENT, payment);
+
+    let label = watch.label.clone().unwrap_or_else(|| watch.address.clone());
+    let title = match payment.status.as_str() {
+        "pending" => format!("{}: Incoming payment pending", label),
+        "success" => format!("{}: Payment received", label),
+        "replaced" => format!("{}: Pending payment replaced", label),
+        _ => format!("{}: Pending payment dropped", label),
+    };
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title(title)
+        .body(format!("{} {}", payment.amount, payment.symbol))
+        .show()
+    {
+        log_error!("Failed to show payment notification: {}", e);
+    }
+}
+
+/// Starts the background task that periodically queues a mempool check of
+/// the watched Bitcoin addresses.
+pub fn spawn_mempool_loop(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(MEMPOOL_INITIAL_DELAY).await;
+        loop {
+            let queue = app.state::<JobQueueState>().inner().clone();
+            if let Err(e) = queue
+                .enqueue(JobTask::MempoolWatch, JobPriority::Background)
+                .await
+            {
+                log_error!("Failed to queue mempool check: {}", e);
+            }
+            tokio::time::sleep(MEMPOOL_POLL_INTERVAL).await;
+        }
+    });
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns a profile's confirmation settings.
+///
+/// Requires any role on the profile.
+#[tauri::command]
+pub async fn get_confirmation_settings(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<ConfirmationSettings, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    load_confirmation_settings(&state.pool, &profile_id).await
+}
+
+/// Sets the confirmations a profile requires before an incoming Bitcoin
+/// payment counts as received.
+///
+/// Requires the owner, admin, or preparer role on the profile.
+#[tauri::command]
+pub async fn update_confirmation_settings(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    bitcoin_confirmations: u32,
+) -> Result<ConfirmationSettings, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ManageWallets,
+    )
+    .await?;
+    if bitcoin_confirmations > MAX_BITCOIN_CONFIRMATIONS {
+        return Err(format!(
+            "At most {} confirmations may be required",
+            MAX_BITCOIN_CONFIRMATIONS
+        ));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO profile_confirmation_settings (profile_id, bitcoin_confirmations, updated_at)
+        VALUES (?, ?, ?)
+        ON CONFLICT(profile_id) DO UPDATE SET
+            bitcoin_confirmations = excluded.bitcoin_confirmations,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(&profile_id)
+    .bind(bitcoin_confirmations)
+    .bind(Utc::now())
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    load_confirmation_settings(&state.pool, &profile_id).await
+}
+
+/// Lists mempool payments to a profile's watched addresses, newest first.
+///
+/// Requires any role on the profile.
+#[tauri::command]
+pub async fn get_mempool_payments(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    pending_only: Option<bool>,
+) -> Result<Vec<MempoolPayment>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as(
+        r#"
+        SELECT p.* FROM mempool_payments p
+        JOIN address_watches w ON w.id = p.watch_id
+        WHERE w.profile_id = ? AND (? = 0 OR p.status = 'pending')
+        ORDER BY p.first_seen_at DESC
+        "#,
+    )
+    .bind(&profile_id)
+    .bind(pending_only.unwrap_or(false))
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Checks the watched Bitcoin addresses' mempools immediately instead of
+/// waiting for the background task.
+#[tauri::command]
+pub async fn check_mempool_payments(
+    app: AppHandle,
+    state: State<'_, DatabaseState>,
+) -> Result<Vec<MempoolPayment>, String> {
+    run_mempool_checks(&state.pool, Some(&app)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::bitcoin::types::{BitcoinTxInput, BitcoinTxOutput};
+
+    const WATCHED: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+    const PAYER: &str = "bc1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh";
+
+    fn tx(from: &str, outputs: &[(&str, u64)]) -> BitcoinTransaction {
+        BitcoinTransaction {
+            txid: "9dbf".to_string(),
+            block_height: None,
+            timestamp: None,
+            inputs: vec![BitcoinTxInput {
+                address: Some(from.to_string()),
+                value: 1_250_000,
+                prev_txid: "48aa".to_string(),
+                prev_vout: 1,
+            }],
+            outputs: outputs
+                .iter()
+                .enumerate()
+                .map(|(i, (address, value))| BitcoinTxOutput {
+                    address: Some(address.to_string()),
+                    value: *value,
+                    index: i as u32,
+                    script_type: "v0_p2wpkh".to_string(),
+                })
+                .collect(),
+            fee: 2_820,
+            confirmations: 0,
+            is_coinbase: false,
+            total_input: 1_250_000,
+            total_output: 1_247_180,
+            rbf_signaled: true,
+        }
+    }
+
+    #[test]
+    fn test_incoming_payment() {
+        let payment = incoming_payment(
+            &tx(PAYER, &[(WATCHED, 1_000_000), (PAYER, 247_180)]),
+            WATCHED,
+        );
+        assert_eq!(
+            payment,
+            Some(IncomingPayment {
+                value: 1_000_000,
+                counterparty: Some(PAYER.to_string()),
+            })
+        );
+
+        // Change back to the watched address is not a payment
+        assert_eq!(
+            incoming_payment(
+                &tx(WATCHED, &[(PAYER, 1_000_000), (WATCHED, 247_180)]),
+                WATCHED
+            ),
+            None
+        );
+        assert_eq!(
+            incoming_payment(&tx(PAYER, &[(PAYER, 1_247_180)]), WATCHED),
+            None
+        );
+    }
+}
@@ -1,21 +1,125 @@
+/// Linked EVM and Substrate accounts on Astar and Moonbeam.
+pub mod account_links;
 /// Accounting module for chart of accounts, journal entries, ledger queries, and transaction classification.
 pub mod accounting;
+/// Economic events each wallet transaction expands into, tagged and booked one by one.
+pub mod accounting_events;
+/// Address watches with threshold alerts via notifications and email.
+pub mod address_watch;
+/// Outstanding token approvals per wallet, flagging unlimited approvals to unknown spenders.
+pub mod approvals;
+/// Append-only history of changes to wallets, transactions, tags, entities, and journal entries.
+pub mod audit_trail;
+/// Read-only bundles of a profile's report and change history for auditors.
+pub mod auditor_bundle;
 /// Authentication module containing functionality and types for user authentication and authorization.
 pub mod auth;
 /// Provides functionality for creating and restoring
 /// backups of application data, including serialization
 /// and storage management.
 pub mod backup;
+/// Wallet balances at past dates rebuilt from stored history, reconciled against the chain.
+pub mod balance_history;
+/// Budgets for grant and program tracking with actuals from tagged transactions.
+pub mod budgets;
+/// Portable configuration bundles with optionally encrypted secrets.
+pub mod config_bundle;
+/// Per-chain confirmation thresholds, holding synced transactions as pending until reached.
+pub mod confirmations;
+/// Consolidated reports across profiles with eliminations for transfers between them.
+pub mod consolidation;
+/// Realized gains per tax jurisdiction and per-profile tax settings.
+pub mod cost_basis;
+/// Parachain crowdloan contributions, lease releases, and rewards.
+pub mod crowdloans;
+/// Retention policy that archives and strips old raw transaction payloads.
+pub mod data_retention;
+/// Crash reports and exportable diagnostics bundles.
+pub mod diagnostics;
+/// Donation receipts with fiat valuation and PDF rendering.
+pub mod donation_receipts;
+/// Finding the same transfer recorded twice and merging the records.
+pub mod duplicate_records;
+/// Email delivery settings, including SMTP for self-hosted installs.
+pub mod email_settings;
 /// The `entities` module contains definitions for the core data entities used by the API.
 pub mod entities;
+/// Per-entity statements and reportable payee totals, exported as CSV or PDF.
+pub mod entity_statements;
+/// Typed errors returned across the command boundary.
+pub mod error;
 /// Module responsible for handling export operations, including data serialization and file output.
 pub mod export;
+/// Saved column layouts and number formats for transaction CSV exports.
+pub mod export_templates;
+/// Invoices paid on chain, detected by a background check of the payee address.
+pub mod invoices;
+/// JWT signing key persistence and rotation.
+pub mod jwt_keys;
+/// Import of payments and invoices from the user's own Lightning node.
+pub mod lightning_import;
+/// Zero-confirmation monitoring of watched Bitcoin addresses and per-profile confirmation depth.
+pub mod mempool_watch;
+/// Tracking of pending Bitcoin transactions through confirmation, RBF replacement, and CPFP bumps.
+pub mod pending_bitcoin;
+/// Closing accounting periods per profile and locking the records dated inside them.
+pub mod period_close;
+/// Permission matrix of profile roles, with capability queries for the UI.
+pub mod permissions;
+/// Import of funding, realized PnL, and collateral history from perpetual futures venues.
+pub mod perp_import;
 /// Module for handling data persistence, including storing, retrieving, and managing application data.
 pub mod persistence;
-/// Module for fetching and managing price feeds from various data providers.
-pub mod price_feeds;
+/// Encrypted, watch-only portfolio snapshots that another instance can open read-only.
+pub mod portfolio_snapshot;
+/// Price feed coin IDs for chain-native assets and token contracts, with per-profile overrides.
+pub mod price_ids;
+/// Manual token prices that take precedence over price provider data.
+pub mod price_overrides;
 /// The `prices` module provides functionality for retrieving and managing price data.
 pub mod prices;
+/// Detection of recurring transaction series and auto-tagging of new occurrences.
+pub mod recurring;
+/// Hash-chained, Merkle-rooted report exports that third parties can verify.
+pub mod report_attestation;
+/// Monthly and quarterly reports generated in the background and emailed to recipients.
+pub mod report_schedules;
+/// Full-text search across transactions, entities, addresses, tags, and notes.
+pub mod search;
+/// OFX and QIF statement export of wallet transaction history for legacy finance tools.
+pub mod statement_export;
+/// Exact swap amounts decoded from DEX swap events, per wallet transaction.
+pub mod swaps;
+/// Missing-history checks across wallets, with re-sync of the affected block ranges.
+pub mod sync_gaps;
+/// Transaction history in the CSV import formats of Koinly, CoinTracking, and Accointing.
+pub mod tax_tool_export;
+/// Per-profile spam and allow lists for tokens, plus a shared blocklist.
+pub mod token_spam;
+/// EIP-1559 fee breakdowns of wallet transactions into burned base fee and tip.
+pub mod transaction_fees;
+/// Paginated, server-side filtered transaction queries.
+pub mod transaction_query;
+/// Dry-run fee, balance, counterparty, and accounting projection for pending transfers.
+pub mod transfer_simulation;
+/// Cost basis, market value, and unrealized gain of current holdings.
+pub mod unrealized_gains;
+/// Bitcoin UTXO listing, coin-control labels, and per-output lot linkage.
+pub mod utxos;
+/// Token vesting schedules, claims, and projected unlocks.
+pub mod vesting;
 /// Provides functionality for wallet-based authentication, including
 /// signing in users through their wallets and verifying credentials.
 pub mod wallet_auth;
+/// Heuristic suggestions for untracked addresses that likely belong to a profile.
+pub mod wallet_clusters;
+/// Logical accounts grouping wallets that share an address or public key across chains.
+pub mod wallet_identity;
+/// Wallet transaction sync with per-wallet status and progress events.
+pub mod wallet_sync;
+/// Cross-chain XCM transfers linking their sending and receiving legs.
+pub mod xcm_transfers;
+
+// Price feeds, the price source appendix, and profile scoping have no
+// commands of their own; they live in the core crate the CLI shares.
+pub use pacioli_core::services::{price_feeds, price_sources, profile_scope};
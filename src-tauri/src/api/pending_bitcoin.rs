@@ -0,0 +1,509 @@
+//! Pending Bitcoin transactions: confirmation, replacement, and fee bumps.
+//!
+//! Every stored Bitcoin transaction still pending is tracked by the outputs
+//! it spends, along with whether it signals replace-by-fee. A background
+//! task checks each one through mempool.space:
+//!
+//! - Confirmed: the stored transaction is marked successful, or keeps
+//!   waiting for the profile's confirmation threshold if it needs more
+//!   than one.
+//! - Replaced (an RBF bump or a double-spend): the stored transaction is
+//!   superseded by the replacement, rewritten in place, rather than left
+//!   beside it as a duplicate that can never confirm.
+//! - Dropped from the mempool: the stored transaction is marked failed.
+//!
+//! Unconfirmed children paying for the parent (CPFP) are recorded while it
+//! waits. Each outcome is surfaced as a Tauri event and a native
+//! notification.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, SqlitePool};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use super::audit_trail::{record_change, RecordType};
+use super::confirmations::required_confirmations;
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction, Wallet};
+use super::profile_scope::authorize_wallet;
+use super::wallet_sync::enum_name;
+use crate::chains::bitcoin::{self, BitcoinAdapter, PendingTxState, TxOutpoint};
+use crate::core::auth_state::AuthState;
+use crate::jobs::queue::{JobQueueState, JobTask};
+use crate::jobs::JobPriority;
+use crate::log_error;
+
+/// Event emitted to the frontend when a pending transaction is resolved.
+pub const PENDING_TX_EVENT: &str = "bitcoin-tx-update";
+
+/// Interval between background checks; a block takes about ten minutes.
+const PENDING_POLL_INTERVAL: Duration = Duration::from_secs(120);
+
+/// Delay before the first background check, so startup is not slowed down.
+const PENDING_INITIAL_DELAY: Duration = Duration::from_secs(60);
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A tracked unconfirmed Bitcoin transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingBitcoinTx {
+    /// Wallet the transaction is stored for.
+    pub wallet_id: String,
+    /// Transaction ID.
+    pub txid: String,
+    /// Bitcoin network.
+    pub chain: String,
+    /// Outputs the transaction spends.
+    pub inputs: Json<Vec<TxOutpoint>>,
+    /// Whether the transaction signals replace-by-fee.
+    pub replaceable: bool,
+    /// `pending`, `confirmed`, `replaced`, or `dropped`.
+    pub state: String,
+    /// The transaction that replaced it.
+    pub replaced_by: Option<String>,
+    /// Unconfirmed children bumping its fee (CPFP).
+    pub bumped_by: Json<Vec<String>>,
+    /// When tracking started.
+    pub first_seen_at: DateTime<Utc>,
+    /// When the transaction confirmed, was replaced, or dropped.
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(FromRow)]
+struct UntrackedTx {
+    wallet_id: String,
+    hash: String,
+    chain: String,
+}
+
+// ============================================================================
+// Checks
+// ============================================================================
+
+/// Starts tracking a wallet's pending Bitcoin transactions, then checks
+/// every tracked one. Returns those resolved in this check. Limited to one
+/// wallet when `wallet_id` is given.
+pub async fn run_pending_checks(
+    pool: &SqlitePool,
+    app: Option<&AppHandle>,
+    wallet_id: Option<&str>,
+) -> Result<Vec<PendingBitcoinTx>, String> {
+    let mut adapters: HashMap<String, BitcoinAdapter> = HashMap::new();
+
+    let untracked: Vec<UntrackedTx> = sqlx::query_as(
+        r#"
+        SELECT t.wallet_id, t.hash, t.chain FROM transactions t
+        WHERE t.status = 'pending' AND (? IS NULL OR t.wallet_id = ?)
+          AND NOT EXISTS (
+              SELECT 1 FROM pending_bitcoin_txs p
+              WHERE p.wallet_id = t.wallet_id AND p.txid = t.hash
+          )
+        "#,
+    )
+    .bind(wallet_id)
+    .bind(wallet_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for tx in untracked {
+        let Some(adapter) = adapter_for(&mut adapters, &tx.chain) else {
+            continue;
+        };
+        if let Err(e) = track(pool, adapter, &tx).await {
+            log_error!("Failed to track pending transaction {}: {}", tx.hash, e);
+        }
+    }
+
+    let tracked: Vec<PendingBitcoinTx> = sqlx::query_as(
+        r#"
+        SELECT * FROM pending_bitcoin_txs
+        WHERE state = 'pending' AND (? IS NULL OR wallet_id = ?)
+        ORDER BY first_seen_at
+        "#,
+    )
+    .bind(wallet_id)
+    .bind(wallet_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut resolved = Vec::new();
+    for pending in tracked {
+        let Some(adapter) = adapter_for(&mut adapters, &pending.chain) else {
+            continue;
+        };
+        match check(pool, adapter, &pending).await {
+            Ok(Some(update)) => {
+                notify(app, &update);
+                resolved.push(update);
+            }
+            Ok(None) => {}
+            Err(e) => log_error!("Pending transaction {} check failed: {}", pending.txid, e),
+        }
+    }
+
+    Ok(resolved)
+}
+
+fn adapter_for<'a>(
+    adapters: &'a mut HashMap<String, BitcoinAdapter>,
+    chain: &str,
+) -> Option<&'a BitcoinAdapter> {
+    if !adapters.contains_key(chain) {
+        let adapter = BitcoinAdapter::from_network(chain).ok()?;
+        adapters.insert(chain.to_string(), adapter);
+    }
+    adapters.get(chain)
+}
+
+/// Records the inputs and RBF signal of a pending transaction, read while it
+/// is still in the mempool.
+async fn track(
+    pool: &SqlitePool,
+    adapter: &BitcoinAdapter,
+    tx: &UntrackedTx,
+) -> Result<(), String> {
+    let fetched = adapter
+        .fetch_transaction(&tx.hash)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO pending_bitcoin_txs (
+            wallet_id, txid, chain, inputs, replaceable, state, bumped_by, first_seen_at
+        ) VALUES (?, ?, ?, ?, ?, 'pending', '[]', ?)
+        "#,
+    )
+    .bind(&tx.wallet_id)
+    .bind(&tx.hash)
+    .bind(&tx.chain)
+    .bind(Json(TxOutpoint::spent_by(&fetched)))
+    .bind(fetched.rbf_signaled)
+    .bind(Utc::now())
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Checks one tracked transaction, applying the outcome to the stored
+/// transaction. Returns the tracked record if it was resolved.
+async fn check(
+    pool: &SqlitePool,
+    adapter: &BitcoinAdapter,
+    pending: &PendingBitcoinTx,
+) -> Result<Option<PendingBitcoinTx>, String> {
+    let state = adapter
+        .check_pending(&pending.txid, &pending.inputs)
+        .await
+        .map_err(|e| e.to_string())?;
+    let now = Utc::now();
+
+    let replaced_by = match &state {
+        PendingTxState::Pending { bumped_by } => {
+            if *bumped_by != pending.bumped_by.0 {
+                sqlx::query(
+                    "UPDATE pending_bitcoin_txs SET bumped_by = ? WHERE wallet_id = ? AND txid = ?",
+                )
+                .bind(Json(bumped_by))
+                .bind(&pending.wallet_id)
+                .bind(&pending.txid)
+                .execute(pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            }
+            return Ok(None);
+        }
+        PendingTxState::Confirmed {
+            block_height,
+            block_time,
+        } => {
+            let timestamp = block_time.and_then(|t| Utc.timestamp_opt(t, 0).single());
+            let wallet = load_wallet(pool, &pending.wallet_id).await?;
+            let required = required_confirmations(pool, &wallet.profile_id, &pending.chain).await?;
+            update_stored(pool, pending, |tx| {
+                // Deeper thresholds are reached in the confirmation checks
+                if required <= 1 {
+                    tx.status = Some("success".to_string());
+                }
+                tx.block_number = block_height.map(|h| h as i64);
+                tx.timestamp = timestamp.or(tx.timestamp);
+            })
+            .await?;
+            None
+        }
+        PendingTxState::Replaced { by, .. } => {
+            supersede(pool, adapter, pending, by).await?;
+            Some(by.clone())
+        }
+        PendingTxState::Dropped => {
+            update_stored(pool, pending, |tx| tx.status = Some("failed".to_string())).await?;
+            None
+        }
+    };
+
+    let state_name = match state {
+        PendingTxState::Confirmed { .. } => "confirmed",
+        PendingTxState::Replaced { .. } => "replaced",
+        _ => "dropped",
+    };
+    sqlx::query(
+        r#"
+        UPDATE pending_bitcoin_txs SET state = ?, replaced_by = ?, resolved_at = ?
+        WHERE wallet_id = ? AND txid = ?
+        "#,
+    )
+    .bind(state_name)
+    .bind(&replaced_by)
+    .bind(now)
+    .bind(&pending.wallet_id)
+    .bind(&pending.txid)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    sqlx::query_as("SELECT * FROM pending_bitcoin_txs WHERE wallet_id = ? AND txid = ?")
+        .bind(&pending.wallet_id)
+        .bind(&pending.txid)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn load_stored(
+    pool: &SqlitePool,
+    wallet_id: &str,
+    hash: &str,
+) -> Result<Option<StoredTransaction>, String> {
+    sqlx::query_as("SELECT * FROM transactions WHERE wallet_id = ? AND hash = ?")
+        .bind(wallet_id)
+        .bind(hash)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn load_wallet(pool: &SqlitePool, wallet_id: &str) -> Result<Wallet, String> {
+    sqlx::query_as("SELECT * FROM wallets WHERE id = ?")
+        .bind(wallet_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Saves the stored transaction after `change`, recording it in the audit
+/// trail. Does nothing if the transaction is no longer stored.
+async fn update_stored(
+    pool: &SqlitePool,
+    pending: &PendingBitcoinTx,
+    change: impl FnOnce(&mut StoredTransaction),
+) -> Result<(), String> {
+    let Some(before) = load_stored(pool, &pending.wallet_id, &pending.txid).await? else {
+        return Ok(());
+    };
+    let mut after = before.clone();
+    change(&mut after);
+
+    sqlx::query(
+        r#"
+        UPDATE transactions
+        SET hash = ?, block_number = ?, timestamp = ?, from_address = ?, to_address = ?,
+            value = ?, fee = ?, status = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&after.hash)
+    .bind(after.block_number)
+    .bind(after.timestamp)
+    .bind(&after.from_address)
+    .bind(&after.to_address)
+    .bind(&after.value)
+    .bind(&after.fee)
+    .bind(&after.status)
+    .bind(&before.id)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let wallet = load_wallet(pool, &pending.wallet_id).await?;
+    record_change(
+        pool,
+        None,
+        RecordType::Transaction,
+        &before.id,
+        Some(&wallet.profile_id),
+        Some(&before),
+        Some(&after),
+    )
+    .await
+}
+
+/// Replaces the stored transaction with `replacement`. If the wallet has
+/// already synced the replacement, the original is removed instead.
+async fn supersede(
+    pool: &SqlitePool,
+    adapter: &BitcoinAdapter,
+    pending: &PendingBitcoinTx,
+    replacement: &str,
+) -> Result<(), String> {
+    let Some(original) = load_stored(pool, &pending.wallet_id, &pending.txid).await? else {
+        return Ok(());
+    };
+    let wallet = load_wallet(pool, &pending.wallet_id).await?;
+
+    if load_stored(pool, &pending.wallet_id, replacement)
+        .await?
+        .is_some()
+    {
+        sqlx::query("DELETE FROM transactions WHERE id = ?")
+            .bind(&original.id)
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        return record_change(
+            pool,
+            None,
+            RecordType::Transaction,
+            &original.id,
+            Some(&wallet.profile_id),
+            Some(&original),
+            None,
+        )
+        .await;
+    }
+
+    let fetched = adapter
+        .fetch_transaction(replacement)
+        .await
+        .map_err(|e| e.to_string())?;
+    let normalized = adapter.normalize_transaction(&fetched, &wallet.address);
+    let timestamp = Utc.timestamp_opt(normalized.timestamp, 0).single();
+
+    update_stored(pool, pending, |tx| {
+        tx.hash = normalized.hash.clone();
+        tx.block_number = fetched.block_height.map(|h| h as i64);
+        tx.timestamp = timestamp
+            .filter(|_| normalized.timestamp > 0)
+            .or(tx.timestamp);
+        tx.from_address = Some(normalized.from.clone());
+        tx.to_address = normalized.to.clone();
+        tx.value = Some(normalized.value.clone());
+        tx.fee = Some(normalized.fee.clone());
+        tx.status = enum_name(&normalized.status);
+    })
+    .await
+}
+
+/// Emits the frontend event and a native notification for a resolved
+/// transaction.
+fn notify(app: Option<&AppHandle>, pending: &PendingBitcoinTx) {
+    let Some(app) = app else {
+        return;
+    };
+    let _ = app.emit(PENDING_TX_EVENT, pending);
+
+    let short = |txid: &str| txid.chars().take(10).collect::<String>();
+    let (title, body) = match pending.state.as_str() {
+        "confirmed" => ("Bitcoin payment confirmed", short(&pending.txid)),
+        "replaced" => (
+            "Bitcoin payment replaced",
+            format!(
+                "{} replaced by {}",
+                short(&pending.txid),
+                short(pending.replaced_by.as_deref().unwrap_or_default())
+            ),
+        ),
+        _ => ("Bitcoin payment dropped", short(&pending.txid)),
+    };
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        log_error!("Failed to show transaction notification: {}", e);
+    }
+}
+
+/// Starts the background task that periodically queues a check of pending
+/// Bitcoin transactions.
+pub fn spawn_pending_loop(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(PENDING_INITIAL_DELAY).await;
+        loop {
+            let queue = app.state::<JobQueueState>().inner().clone();
+            if let Err(e) = queue
+                .enqueue(JobTask::PendingTxCheck, JobPriority::Background)
+                .await
+            {
+                log_error!("Failed to queue pending transaction check: {}", e);
+            }
+            tokio::time::sleep(PENDING_POLL_INTERVAL).await;
+        }
+    });
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Lists a wallet's tracked Bitcoin transactions, newest first.
+///
+/// Requires any role on the wallet's profile.
+#[tauri::command]
+pub async fn get_pending_bitcoin_txs(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    wallet_id: String,
+) -> Result<Vec<PendingBitcoinTx>, String> {
+    authorize_wallet(
+        &state.pool,
+        &auth,
+        &token,
+        &wallet_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as(
+        "SELECT * FROM pending_bitcoin_txs WHERE wallet_id = ? ORDER BY first_seen_at DESC",
+    )
+    .bind(&wallet_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Checks a wallet's pending Bitcoin transactions immediately instead of
+/// waiting for the background task. Returns those resolved.
+///
+/// Requires the owner, admin, or preparer role on the wallet's profile.
+#[tauri::command]
+pub async fn check_pending_bitcoin_txs(
+    app: AppHandle,
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    wallet_id: String,
+) -> Result<Vec<PendingBitcoinTx>, String> {
+    let (_, wallet) = authorize_wallet(
+        &state.pool,
+        &auth,
+        &token,
+        &wallet_id,
+        Permission::ManageWallets,
+    )
+    .await?;
+    if bitcoin::get_config_by_name(&wallet.chain).is_none() {
+        return Err(format!(
+            "Wallet is not on a Bitcoin network: {}",
+            wallet.chain
+        ));
+    }
+    run_pending_checks(&state.pool, Some(&app), Some(&wallet_id)).await
+}
@@ -0,0 +1,310 @@
+//! Period close and locking.
+//!
+//! Once a month, quarter, or year is closed for a profile, transactions and
+//! journal entries dated inside it can no longer be edited, deleted, or
+//! re-tagged. An owner or admin can reopen the period with a reason, which is
+//! recorded on the close and in the audit log.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::auth::{log_audit_event, verify_profile_access};
+use super::budgets::parse_period;
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use crate::core::auth_helpers::verify_access_token;
+use crate::core::auth_state::AuthState;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A closed (or since reopened) accounting period.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodClose {
+    /// Unique identifier.
+    pub id: String,
+    /// Profile the period belongs to.
+    pub profile_id: String,
+    /// One of: month, quarter, year.
+    pub period_type: String,
+    /// First day of the period.
+    pub period_start: NaiveDate,
+    /// Last day of the period, inclusive.
+    pub period_end: NaiveDate,
+    /// One of: closed, reopened.
+    pub status: String,
+    /// User who closed the period.
+    pub closed_by: String,
+    /// When the period was closed.
+    pub closed_at: DateTime<Utc>,
+    /// User who last reopened the period.
+    pub reopened_by: Option<String>,
+    /// When the period was last reopened.
+    pub reopened_at: Option<DateTime<Utc>>,
+    /// Reason given for the last reopen.
+    pub reopen_reason: Option<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Period type of a `YYYY`, `YYYY-Qn`, or `YYYY-MM` period string. Explicit
+/// date ranges can't be closed.
+pub fn period_type(period: &str) -> Result<&'static str, String> {
+    let period = period.trim();
+    if period.contains("..") {
+        return Err("Only a month, quarter, or year can be closed".to_string());
+    }
+    Ok(match period.split_once('-') {
+        None => "year",
+        Some((_, rest)) if rest.starts_with('Q') || rest.starts_with('q') => "quarter",
+        Some(_) => "month",
+    })
+}
+
+/// Rejects a change dated `date` if it falls in a closed period.
+///
+/// With no `profile_id` every profile's closes apply, for records such as
+/// older journal entries that aren't tied to a profile.
+pub(crate) async fn ensure_period_open(
+    pool: &SqlitePool,
+    profile_id: Option<&str>,
+    date: NaiveDate,
+) -> Result<(), String> {
+    let closed = sqlx::query_as::<_, PeriodClose>(
+        r#"
+        SELECT * FROM period_closes
+        WHERE status = 'closed'
+          AND (? IS NULL OR profile_id = ?)
+          AND period_start <= ? AND period_end >= ?
+        ORDER BY period_start ASC
+        LIMIT 1
+        "#,
+    )
+    .bind(profile_id)
+    .bind(profile_id)
+    .bind(date)
+    .bind(date)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    match closed {
+        Some(close) => Err(format!(
+            "The {} {} to {} is closed; an admin must reopen it before changes dated {} can be made",
+            close.period_type, close.period_start, close.period_end, date
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Rejects deleting a wallet's transactions if any of them fall in a period
+/// closed for the wallet's profile.
+pub(crate) async fn ensure_wallet_periods_open(
+    pool: &SqlitePool,
+    wallet_id: &str,
+) -> Result<(), String> {
+    let closed = sqlx::query_as::<_, PeriodClose>(
+        r#"
+        SELECT pc.* FROM period_closes pc
+        INNER JOIN wallets w ON w.profile_id = pc.profile_id
+        WHERE w.id = ? AND pc.status = 'closed'
+          AND EXISTS (
+              SELECT 1 FROM transactions t
+              WHERE t.wallet_id = w.id
+                AND date(t.timestamp) BETWEEN pc.period_start AND pc.period_end
+          )
+        ORDER BY pc.period_start ASC
+        LIMIT 1
+        "#,
+    )
+    .bind(wallet_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    match closed {
+        Some(close) => Err(format!(
+            "The wallet has transactions in the closed {} {} to {}; an admin must reopen it first",
+            close.period_type, close.period_start, close.period_end
+        )),
+        None => Ok(()),
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Closes a month (`YYYY-MM`), quarter (`YYYY-Qn`), or year (`YYYY`) for a
+/// profile. Requires the owner, admin, or approver role.
+#[tauri::command]
+pub async fn close_period(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    period: String,
+) -> Result<PeriodClose, String> {
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
+    let pool = &state.pool;
+    verify_profile_access(pool, &claims.sub, &profile_id, Permission::Approve).await?;
+
+    let period_type = period_type(&period)?;
+    let (period_start, period_end) = parse_period(&period)?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO period_closes (
+            id, profile_id, period_type, period_start, period_end, status, closed_by, closed_at
+        )
+        VALUES (?, ?, ?, ?, ?, 'closed', ?, ?)
+        ON CONFLICT(profile_id, period_start, period_end) DO UPDATE SET
+            status = 'closed',
+            closed_by = excluded.closed_by,
+            closed_at = excluded.closed_at
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(&profile_id)
+    .bind(period_type)
+    .bind(period_start)
+    .bind(period_end)
+    .bind(&claims.sub)
+    .bind(Utc::now())
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let details = serde_json::json!({ "period": period.trim() }).to_string();
+    log_audit_event(
+        pool,
+        Some(&claims.sub),
+        "period_closed",
+        "success",
+        Some(&details),
+        None,
+        Some(&profile_id),
+    )
+    .await;
+
+    sqlx::query_as::<_, PeriodClose>(
+        "SELECT * FROM period_closes WHERE profile_id = ? AND period_start = ? AND period_end = ?",
+    )
+    .bind(&profile_id)
+    .bind(period_start)
+    .bind(period_end)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Reopens a closed period. Requires the owner or admin role and a reason,
+/// which is kept on the period and written to the audit log.
+#[tauri::command]
+pub async fn reopen_period(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    period: String,
+    reason: String,
+) -> Result<PeriodClose, String> {
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
+    let pool = &state.pool;
+    verify_profile_access(pool, &claims.sub, &profile_id, Permission::ManageProfile).await?;
+
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err("A reason is required to reopen a closed period".to_string());
+    }
+
+    let (period_start, period_end) = parse_period(&period)?;
+    let result = sqlx::query(
+        r#"
+        UPDATE period_closes
+        SET status = 'reopened', reopened_by = ?, reopened_at = ?, reopen_reason = ?
+        WHERE profile_id = ? AND period_start = ? AND period_end = ? AND status = 'closed'
+        "#,
+    )
+    .bind(&claims.sub)
+    .bind(Utc::now())
+    .bind(reason)
+    .bind(&profile_id)
+    .bind(period_start)
+    .bind(period_end)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if result.rows_affected() == 0 {
+        return Err(format!("Period {} is not closed", period.trim()));
+    }
+
+    let details = serde_json::json!({ "period": period.trim(), "reason": reason }).to_string();
+    log_audit_event(
+        pool,
+        Some(&claims.sub),
+        "period_reopened",
+        "success",
+        Some(&details),
+        None,
+        Some(&profile_id),
+    )
+    .await;
+
+    sqlx::query_as::<_, PeriodClose>(
+        "SELECT * FROM period_closes WHERE profile_id = ? AND period_start = ? AND period_end = ?",
+    )
+    .bind(&profile_id)
+    .bind(period_start)
+    .bind(period_end)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Lists a profile's closed and reopened periods, most recent first.
+/// Requires any role on the profile.
+#[tauri::command]
+pub async fn get_period_closes(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<PeriodClose>, String> {
+    let claims = verify_access_token(&token, &auth.jwt_keys())?;
+    verify_profile_access(
+        &state.pool,
+        &claims.sub,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as::<_, PeriodClose>(
+        "SELECT * FROM period_closes WHERE profile_id = ? ORDER BY period_start DESC",
+    )
+    .bind(&profile_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_period_type() {
+        assert_eq!(period_type("2026").unwrap(), "year");
+        assert_eq!(period_type("2026-Q2").unwrap(), "quarter");
+        assert_eq!(period_type("2026-03").unwrap(), "month");
+        assert!(period_type("2026-01-01..2026-02-15").is_err());
+    }
+}
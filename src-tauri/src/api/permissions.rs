@@ -0,0 +1,119 @@
+//! Role-based permissions.
+//!
+//! Each profile role grants a fixed set of permissions. Profile-scoped
+//! commands name the permission they need rather than a list of roles, and
+//! [`super::profile_scope`] checks it against the caller's role before the
+//! command runs. The UI queries the same matrix to decide what to show.
+
+use tauri::State;
+
+use super::auth::verify_profile_access;
+use super::persistence::DatabaseState;
+use super::profile_scope::authenticate;
+use crate::core::auth_state::AuthState;
+
+pub use pacioli_core::services::permissions::*;
+
+/// Profile roles.
+pub const ROLES: &[&str] = &["owner", "admin", "approver", "preparer", "user", "auditor"];
+
+/// Roles that can be given to an invited or existing member. Ownership is
+/// never assigned this way.
+pub const ASSIGNABLE_ROLES: &[&str] = &["user", "preparer", "approver", "admin", "auditor"];
+
+/// Roles that grant `permission`.
+pub fn roles_with(permission: Permission) -> Vec<&'static str> {
+    ROLE_PERMISSIONS
+        .iter()
+        .filter(|(_, permissions)| permissions.contains(&permission))
+        .map(|(role, _)| *role)
+        .collect()
+}
+
+/// One role's permissions, as shown in the UI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RolePermissions {
+    /// Role name.
+    pub role: String,
+    /// Permissions the role grants.
+    pub permissions: Vec<Permission>,
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns the permission matrix: every role with the permissions it grants.
+#[tauri::command]
+pub fn get_role_permissions() -> Vec<RolePermissions> {
+    ROLE_PERMISSIONS
+        .iter()
+        .map(|(role, permissions)| RolePermissions {
+            role: role.to_string(),
+            permissions: permissions.to_vec(),
+        })
+        .collect()
+}
+
+/// Returns the caller's role on a profile and the permissions it grants.
+#[tauri::command]
+pub async fn get_my_permissions(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<RolePermissions, String> {
+    let user_id = authenticate(&auth, &token)?;
+    verify_profile_access(
+        &state.pool,
+        &user_id,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+
+    let role: String = sqlx::query_scalar(
+        "SELECT role FROM user_profile_roles WHERE user_id = ? AND profile_id = ?",
+    )
+    .bind(&user_id)
+    .bind(&profile_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(RolePermissions {
+        permissions: role_permissions(&role).to_vec(),
+        role,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_role_has_permissions() {
+        for role in ROLES {
+            assert!(
+                role_has(role, Permission::ViewTransactions),
+                "{} cannot view",
+                role
+            );
+        }
+        assert!(role_permissions("guest").is_empty());
+    }
+
+    #[test]
+    fn test_permission_matrix() {
+        assert_eq!(roles_with(Permission::DeleteProfile), vec!["owner"]);
+        assert_eq!(
+            roles_with(Permission::Approve),
+            vec!["owner", "admin", "approver"]
+        );
+        assert!(role_has("preparer", Permission::CreateJournalEntries));
+        assert!(!role_has("preparer", Permission::ManageKeys));
+        assert!(!role_has("approver", Permission::EditTags));
+        assert!(!role_has("user", Permission::Export));
+    }
+}
@@ -0,0 +1,161 @@
+//! Import of perpetual futures history into a wallet.
+//!
+//! Funding payments, realized PnL, and collateral movements on dYdX and GMX
+//! never appear as token transfers of the trading wallet, so they are read
+//! from each venue's indexer and stored as the wallet's transactions. Each
+//! item is keyed by a stable venue ID, so importing again updates rather
+//! than duplicates. Amounts credited to the account come from the venue;
+//! amounts debited go to it.
+
+use chrono::{TimeZone, Utc};
+use tauri::State;
+
+use super::permissions::Permission;
+use super::persistence::{store_transactions, DatabaseState, TransactionInput};
+use super::profile_scope::authorize_wallet;
+use super::wallet_sync::enum_name;
+use crate::chains::derivatives::dydx::DydxClient;
+use crate::chains::derivatives::gmx::GmxClient;
+use crate::chains::derivatives::{PerpActivity, PerpVenue};
+use crate::chains::units::to_smallest_units;
+use crate::chains::TransactionStatus;
+use crate::core::auth_state::AuthState;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Converts venue activity into the form stored for a wallet.
+fn to_transaction_input(activity: &PerpActivity, account: &str, chain: &str) -> TransactionInput {
+    let venue = activity.venue.as_str().to_string();
+    let (from_address, to_address) = if activity.amount.is_sign_negative() {
+        (account.to_string(), venue)
+    } else {
+        (venue, account.to_string())
+    };
+    let value = to_smallest_units(activity.amount.abs(), activity.decimals)
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "0".to_string());
+
+    TransactionInput {
+        hash: activity.id.clone(),
+        block_number: Some(activity.block_number as i64),
+        timestamp: Utc
+            .timestamp_opt(activity.timestamp, 0)
+            .single()
+            .map(|t| t.to_rfc3339()),
+        from_address: Some(from_address),
+        to_address: Some(to_address),
+        value: Some(value),
+        fee: Some("0".to_string()),
+        status: enum_name(&TransactionStatus::Success),
+        tx_type: enum_name(&activity.tx_type),
+        token_symbol: Some(activity.asset.clone()),
+        token_decimals: Some(i32::from(activity.decimals)),
+        chain: chain.to_string(),
+        raw_data: serde_json::to_string(activity).ok(),
+        swaps: Vec::new(),
+        token_transfers: Vec::new(),
+        fee_breakdown: None,
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Imports a wallet's funding payments, realized PnL, and collateral
+/// movements from `venue`, returning the number of transactions saved.
+///
+/// dYdX reads the wallet's `subaccount_number`, 0 by default. GMX reads the
+/// subgraph of the wallet's chain, Arbitrum or Avalanche. Requires the
+/// owner, admin, or preparer role on the wallet's profile.
+#[tauri::command]
+pub async fn import_perp_history(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    wallet_id: String,
+    venue: PerpVenue,
+    subaccount_number: Option<u32>,
+) -> Result<usize, String> {
+    let (user_id, wallet) = authorize_wallet(
+        &state.pool,
+        &auth,
+        &token,
+        &wallet_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+
+    let activity = match venue {
+        PerpVenue::Dydx => {
+            DydxClient::new()
+                .map_err(|e| e.to_string())?
+                .fetch_activity(&wallet.address, subaccount_number.unwrap_or(0))
+                .await
+        }
+        PerpVenue::Gmx => {
+            GmxClient::for_chain(&wallet.chain)
+                .map_err(|e| e.to_string())?
+                .fetch_activity(&wallet.address)
+                .await
+        }
+    }
+    .map_err(|e| e.to_string())?;
+
+    let inputs = activity
+        .iter()
+        .map(|a| to_transaction_input(a, &wallet.address, &wallet.chain))
+        .collect();
+    store_transactions(&state.pool, &user_id, &wallet, inputs).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::TransactionType;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn activity(tx_type: TransactionType, amount: &str) -> PerpActivity {
+        PerpActivity {
+            id: "dydx:funding:ETH-USD:1150000".to_string(),
+            venue: PerpVenue::Dydx,
+            tx_type,
+            market: Some("ETH-USD".to_string()),
+            asset: "USDC".to_string(),
+            decimals: 6,
+            amount: Decimal::from_str(amount).unwrap(),
+            timestamp: 1_704_441_600,
+            block_number: 1_150_000,
+            tx_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_to_transaction_input_directions() {
+        let paid = to_transaction_input(
+            &activity(TransactionType::FundingPayment, "-1.25"),
+            "dydx1abc",
+            "dydx",
+        );
+        assert_eq!(paid.hash, "dydx:funding:ETH-USD:1150000");
+        assert_eq!(paid.from_address.as_deref(), Some("dydx1abc"));
+        assert_eq!(paid.to_address.as_deref(), Some("dydx"));
+        assert_eq!(paid.value.as_deref(), Some("1250000"));
+        assert_eq!(paid.tx_type.as_deref(), Some("funding_payment"));
+        assert_eq!(paid.token_symbol.as_deref(), Some("USDC"));
+        assert_eq!(paid.timestamp.as_deref(), Some("2024-01-05T08:00:00+00:00"));
+
+        let pnl = to_transaction_input(
+            &activity(TransactionType::RealizedPnl, "411.75"),
+            "dydx1abc",
+            "dydx",
+        );
+        assert_eq!(pnl.from_address.as_deref(), Some("dydx"));
+        assert_eq!(pnl.to_address.as_deref(), Some("dydx1abc"));
+        assert_eq!(pnl.value.as_deref(), Some("411750000"));
+        assert_eq!(pnl.tx_type.as_deref(), Some("realized_pnl"));
+    }
+}
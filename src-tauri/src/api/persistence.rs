@@ -1,86 +1,31 @@
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
 use tauri::State;
 use uuid::Uuid;
 
+use super::accounting_events::delete_wallet_accounting_events;
+use super::audit_trail::{record_change, RecordType};
+use super::auth::verify_admin;
+use super::balance_history::delete_wallet_token_transfers;
+use super::period_close::ensure_wallet_periods_open;
+use super::permissions::Permission;
+use super::profile_scope::{
+    authenticate, authorize_profile, authorize_wallet, profile_transactions, profile_wallets,
+    profiles_for_user, wallet_transactions,
+};
+use super::swaps::delete_wallet_swaps;
+use super::transaction_fees::delete_wallet_fee_breakdowns;
+use super::wallet_identity::canonical_address;
+use crate::core::auth_state::AuthState;
+use crate::db::{maintenance, migrations};
+use crate::storage::settings_store::is_reserved_setting;
+
+pub use pacioli_core::services::persistence::*;
+
 // ============================================================================
 // Types
 // ============================================================================
 
-/// Represents a user profile with unique ID, name, optional avatar URL, and timestamps.
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-pub struct Profile {
-    /// The unique identifier of the profile.
-    pub id: String,
-    /// The display name of the user.
-    pub name: String,
-    /// The optional URL of the user's avatar image.
-    pub avatar_url: Option<String>,
-    /// The timestamp when the profile was created.
-    pub created_at: DateTime<Utc>,
-    /// The timestamp when the profile was last updated.
-    pub updated_at: DateTime<Utc>,
-}
-
-/// Represents a cryptocurrency wallet associated with a user profile.
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-pub struct Wallet {
-    /// The unique identifier of the wallet.
-    pub id: String,
-    /// The identifier of the associated user profile.
-    pub profile_id: String,
-    /// The blockchain address of the wallet.
-    pub address: String,
-    /// The blockchain network identifier (e.g., Ethereum, Bitcoin).
-    pub chain: String,
-    /// An optional display name for the wallet.
-    pub name: Option<String>,
-    /// The type of the wallet (e.g., hardware, software).
-    pub wallet_type: String,
-    /// The timestamp when the wallet was created.
-    pub created_at: DateTime<Utc>,
-    /// The optional timestamp when the wallet was last updated.
-    pub updated_at: Option<DateTime<Utc>>,
-}
-
-/// Represents a stored transaction record with metadata and blockchain details.
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-pub struct StoredTransaction {
-    /// The unique identifier of the stored transaction.
-    pub id: String,
-    /// The identifier of the wallet associated with the transaction.
-    pub wallet_id: String,
-    /// The blockchain transaction hash.
-    pub hash: String,
-    /// The optional block number where the transaction was recorded.
-    pub block_number: Option<i64>,
-    /// The optional timestamp of when the transaction occurred.
-    pub timestamp: Option<DateTime<Utc>>,
-    /// The optional sender address of the transaction.
-    pub from_address: Option<String>,
-    /// The optional recipient address of the transaction.
-    pub to_address: Option<String>,
-    /// The optional value transferred in the transaction.
-    pub value: Option<String>,
-    /// The optional transaction fee paid.
-    pub fee: Option<String>,
-    /// The optional status of the transaction (e.g., pending, confirmed).
-    pub status: Option<String>,
-    /// The optional type of the transaction.
-    pub tx_type: Option<String>,
-    /// The optional symbol of the token involved.
-    pub token_symbol: Option<String>,
-    /// The optional decimal precision of the token.
-    pub token_decimals: Option<i32>,
-    /// The blockchain network identifier for the transaction.
-    pub chain: String,
-    /// The optional raw data of the transaction.
-    pub raw_data: Option<String>,
-    /// The timestamp when the transaction was stored.
-    pub created_at: DateTime<Utc>,
-}
-
 /// Input data for creating or updating a wallet in the system.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletInput {
@@ -92,76 +37,30 @@ pub struct WalletInput {
     pub chain: String,
     /// An optional display name for the wallet.
     pub name: Option<String>,
-    /// The type of the wallet (e.g., hardware, software).
+    /// The type of the wallet (e.g., hardware, software). ERC-4337 smart
+    /// accounts use `smart_account`; their user operations are attributed
+    /// to the account when it syncs.
     pub wallet_type: String,
 }
 
-/// Input data for saving or updating transactions in the system.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TransactionInput {
-    /// The blockchain transaction hash.
-    pub hash: String,
-    /// The optional block number where the transaction was recorded.
-    pub block_number: Option<i64>,
-    /// The optional timestamp of when the transaction occurred, as an ISO 8601 string.
-    pub timestamp: Option<String>,
-    /// The optional sender address of the transaction.
-    pub from_address: Option<String>,
-    /// The optional recipient address of the transaction.
-    pub to_address: Option<String>,
-    /// The optional value transferred in the transaction.
-    pub value: Option<String>,
-    /// The optional transaction fee paid.
-    pub fee: Option<String>,
-    /// The optional status of the transaction (e.g., pending, confirmed).
-    pub status: Option<String>,
-    /// The optional type of the transaction.
-    pub tx_type: Option<String>,
-    /// The optional symbol of the token involved.
-    pub token_symbol: Option<String>,
-    /// The optional decimal precision of the token.
-    pub token_decimals: Option<i32>,
-    /// The blockchain network identifier for the transaction.
-    pub chain: String,
-    /// The optional raw data of the transaction.
-    pub raw_data: Option<String>,
-}
-
-// ============================================================================
-// Database State
-// ============================================================================
-
-/// Maintains the SQLite connection pool for database operations.
-pub struct DatabaseState {
-    /// The SQLite database connection pool for executing queries.
-    pub pool: SqlitePool,
-}
-
-impl DatabaseState {
-    /// Creates a new DatabaseState by connecting to the specified SQLite database path and running migrations.
-    pub async fn new(database_path: &str) -> Result<Self, sqlx::Error> {
-        let pool = SqlitePool::connect(database_path).await?;
-
-        // Run migrations
-        sqlx::migrate!("./migrations").run(&pool).await?;
-
-        Ok(Self { pool })
-    }
-}
-
 // ============================================================================
 // Profile Commands
 // ============================================================================
 
-/// Creates a new user profile with the given name and returns the created Profile.
+/// Creates a new user profile with the given name, owned by the calling user,
+/// and returns the created Profile.
 #[tauri::command]
 pub async fn create_profile(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     name: String,
 ) -> Result<Profile, String> {
+    let user_id = authenticate(&auth, &token)?;
     let id = Uuid::new_v4().to_string();
     let now = Utc::now();
 
+    let mut db_tx = state.pool.begin().await.map_err(|e| e.to_string())?;
     sqlx::query(
         r#"
         INSERT INTO profiles (id, name, created_at, updated_at)
@@ -172,10 +71,27 @@ pub async fn create_profile(
     .bind(&name)
     .bind(now)
     .bind(now)
-    .execute(&state.pool)
+    .execute(&mut *db_tx)
     .await
     .map_err(|e| e.to_string())?;
 
+    sqlx::query(
+        r#"
+        INSERT INTO user_profile_roles (id, user_id, profile_id, role, status, accepted_at, created_at, updated_at)
+        VALUES (?, ?, ?, 'owner', 'active', ?, ?, ?)
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(&user_id)
+    .bind(&id)
+    .bind(now)
+    .bind(now)
+    .bind(now)
+    .execute(&mut *db_tx)
+    .await
+    .map_err(|e| format!("Failed to assign profile role: {}", e))?;
+    db_tx.commit().await.map_err(|e| e.to_string())?;
+
     Ok(Profile {
         id,
         name,
@@ -185,24 +101,28 @@ pub async fn create_profile(
     })
 }
 
-/// Retrieves all user profiles ordered by creation time, newest first.
+/// Retrieves the profiles the calling user has access to, newest first.
 #[tauri::command]
-pub async fn get_profiles(state: State<'_, DatabaseState>) -> Result<Vec<Profile>, String> {
-    let profiles = sqlx::query_as::<_, Profile>("SELECT * FROM profiles ORDER BY created_at DESC")
-        .fetch_all(&state.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(profiles)
+pub async fn get_profiles(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+) -> Result<Vec<Profile>, String> {
+    let user_id = authenticate(&auth, &token)?;
+    profiles_for_user(&state.pool, &user_id).await
 }
 
 /// Updates the name of an existing profile by ID and returns the updated Profile.
+/// Requires the owner or admin role.
 #[tauri::command]
 pub async fn update_profile(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     id: String,
     name: String,
 ) -> Result<Profile, String> {
+    authorize_profile(&state.pool, &auth, &token, &id, Permission::ManageProfile).await?;
     let now = Utc::now();
 
     sqlx::query("UPDATE profiles SET name = ?, updated_at = ? WHERE id = ?")
@@ -222,9 +142,15 @@ pub async fn update_profile(
     Ok(profile)
 }
 
-/// Deletes a user profile by ID from the database.
+/// Deletes a user profile by ID from the database. Requires the owner role.
 #[tauri::command]
-pub async fn delete_profile(state: State<'_, DatabaseState>, id: String) -> Result<(), String> {
+pub async fn delete_profile(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    id: String,
+) -> Result<(), String> {
+    authorize_profile(&state.pool, &auth, &token, &id, Permission::DeleteProfile).await?;
     sqlx::query("DELETE FROM profiles WHERE id = ?")
         .bind(&id)
         .execute(&state.pool)
@@ -239,13 +165,37 @@ pub async fn delete_profile(state: State<'_, DatabaseState>, id: String) -> Resu
 // ============================================================================
 
 /// Saves a new wallet or updates an existing one for a profile and returns the Wallet.
+/// Requires the owner, admin, or preparer role on the profile.
 #[tauri::command]
 pub async fn save_wallet(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     wallet: WalletInput,
 ) -> Result<Wallet, String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &wallet.profile_id,
+        Permission::ManageWallets,
+    )
+    .await?;
     let id = Uuid::new_v4().to_string();
     let now = Utc::now();
+    // Store Substrate addresses in the chain's own SS58 encoding so the same
+    // account entered in another encoding updates the existing wallet.
+    let address = canonical_address(&wallet.chain, &wallet.address);
+
+    let existing = sqlx::query_as::<_, Wallet>(
+        "SELECT * FROM wallets WHERE profile_id = ? AND address = ? AND chain = ?",
+    )
+    .bind(&wallet.profile_id)
+    .bind(&address)
+    .bind(&wallet.chain)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
 
     sqlx::query(
         r#"
@@ -259,7 +209,7 @@ pub async fn save_wallet(
     )
     .bind(&id)
     .bind(&wallet.profile_id)
-    .bind(&wallet.address)
+    .bind(&address)
     .bind(&wallet.chain)
     .bind(&wallet.name)
     .bind(&wallet.wallet_type)
@@ -274,12 +224,23 @@ pub async fn save_wallet(
         "SELECT * FROM wallets WHERE profile_id = ? AND address = ? AND chain = ?",
     )
     .bind(&wallet.profile_id)
-    .bind(&wallet.address)
+    .bind(&address)
     .bind(&wallet.chain)
     .fetch_one(&state.pool)
     .await
     .map_err(|e| e.to_string())?;
 
+    record_change(
+        &state.pool,
+        Some(&user_id),
+        RecordType::Wallet,
+        &saved_wallet.id,
+        Some(&saved_wallet.profile_id),
+        existing.as_ref(),
+        Some(&saved_wallet),
+    )
+    .await?;
+
     Ok(saved_wallet)
 }
 
@@ -287,43 +248,112 @@ pub async fn save_wallet(
 #[tauri::command]
 pub async fn get_wallets(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     profile_id: String,
 ) -> Result<Vec<Wallet>, String> {
-    let wallets = sqlx::query_as::<_, Wallet>(
-        "SELECT * FROM wallets WHERE profile_id = ? ORDER BY created_at DESC",
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
     )
-    .bind(&profile_id)
-    .fetch_all(&state.pool)
-    .await
-    .map_err(|e| e.to_string())?;
-
-    Ok(wallets)
+    .await?;
+    profile_wallets(&state.pool, &profile_id).await
 }
 
-/// Retrieves a wallet by its unique ID, or None if not found.
+/// Retrieves a wallet by its unique ID, or None if not found or it belongs to
+/// a profile the caller can't access.
 #[tauri::command]
 pub async fn get_wallet_by_id(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     id: String,
 ) -> Result<Option<Wallet>, String> {
-    let wallet = sqlx::query_as::<_, Wallet>("SELECT * FROM wallets WHERE id = ?")
-        .bind(&id)
-        .fetch_optional(&state.pool)
-        .await
-        .map_err(|e| e.to_string())?;
+    let user_id = authenticate(&auth, &token)?;
+    let wallet = sqlx::query_as::<_, Wallet>(
+        r#"
+        SELECT w.* FROM wallets w
+        INNER JOIN user_profile_roles upr ON upr.profile_id = w.profile_id
+        WHERE w.id = ? AND upr.user_id = ? AND upr.status = 'active'
+        "#,
+    )
+    .bind(&id)
+    .bind(&user_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
 
     Ok(wallet)
 }
 
-/// Deletes a wallet by its unique ID from the database.
+/// Deletes a wallet by its unique ID from the database. Requires the owner,
+/// admin, or preparer role on the wallet's profile, and refuses if any of the
+/// wallet's transactions fall in a closed period.
 #[tauri::command]
-pub async fn delete_wallet(state: State<'_, DatabaseState>, id: String) -> Result<(), String> {
+pub async fn delete_wallet(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    id: String,
+) -> Result<(), String> {
+    let (user_id, wallet) =
+        authorize_wallet(&state.pool, &auth, &token, &id, Permission::ManageWallets).await?;
+    ensure_wallet_periods_open(&state.pool, &id).await?;
+
+    let mut db_tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    // The wallet's transactions are deleted with it.
+    let transactions =
+        sqlx::query_as::<_, StoredTransaction>("SELECT * FROM transactions WHERE wallet_id = ?")
+            .bind(&id)
+            .fetch_all(&mut *db_tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
     sqlx::query("DELETE FROM wallets WHERE id = ?")
         .bind(&id)
-        .execute(&state.pool)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    delete_wallet_swaps(&mut *db_tx, &id)
         .await
         .map_err(|e| e.to_string())?;
+    delete_wallet_token_transfers(&mut *db_tx, &id)
+        .await
+        .map_err(|e| e.to_string())?;
+    delete_wallet_fee_breakdowns(&mut *db_tx, &id)
+        .await
+        .map_err(|e| e.to_string())?;
+    delete_wallet_accounting_events(&mut *db_tx, &id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for tx in &transactions {
+        record_change(
+            &mut *db_tx,
+            Some(&user_id),
+            RecordType::Transaction,
+            &tx.id,
+            Some(&wallet.profile_id),
+            Some(tx),
+            None,
+        )
+        .await?;
+    }
+    record_change(
+        &mut *db_tx,
+        Some(&user_id),
+        RecordType::Wallet,
+        &wallet.id,
+        Some(&wallet.profile_id),
+        Some(&wallet),
+        None,
+    )
+    .await?;
 
+    db_tx.commit().await.map_err(|e| e.to_string())?;
     Ok(())
 }
 
@@ -332,62 +362,24 @@ pub async fn delete_wallet(state: State<'_, DatabaseState>, id: String) -> Resul
 // ============================================================================
 
 /// Saves or updates a batch of transactions for the specified wallet and returns the number of saved records.
+/// Requires the owner, admin, or preparer role on the wallet's profile.
 #[tauri::command]
 pub async fn save_transactions(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     wallet_id: String,
     transactions: Vec<TransactionInput>,
 ) -> Result<usize, String> {
-    let now = Utc::now();
-    let mut saved_count = 0;
-
-    for tx in transactions {
-        let id = Uuid::new_v4().to_string();
-        let timestamp = tx
-            .timestamp
-            .as_ref()
-            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
-            .map(|t| t.with_timezone(&Utc));
-
-        let result = sqlx::query(
-            r#"
-            INSERT INTO transactions (
-                id, wallet_id, hash, block_number, timestamp, from_address, to_address,
-                value, fee, status, tx_type, token_symbol, token_decimals, chain, raw_data, created_at
-            )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            ON CONFLICT(wallet_id, hash) DO UPDATE SET
-                block_number = excluded.block_number,
-                timestamp = excluded.timestamp,
-                status = excluded.status,
-                raw_data = excluded.raw_data
-            "#,
-        )
-        .bind(&id)
-        .bind(&wallet_id)
-        .bind(&tx.hash)
-        .bind(tx.block_number)
-        .bind(timestamp)
-        .bind(&tx.from_address)
-        .bind(&tx.to_address)
-        .bind(&tx.value)
-        .bind(&tx.fee)
-        .bind(&tx.status)
-        .bind(&tx.tx_type)
-        .bind(&tx.token_symbol)
-        .bind(tx.token_decimals)
-        .bind(&tx.chain)
-        .bind(&tx.raw_data)
-        .bind(now)
-        .execute(&state.pool)
-        .await;
-
-        if result.is_ok() {
-            saved_count += 1;
-        }
-    }
-
-    Ok(saved_count)
+    let (user_id, wallet) = authorize_wallet(
+        &state.pool,
+        &auth,
+        &token,
+        &wallet_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    store_transactions(&state.pool, &user_id, &wallet, transactions).await
 }
 
 /// Retrieves a list of stored transactions for the specified wallet ID.
@@ -395,29 +387,29 @@ pub async fn save_transactions(
 #[tauri::command]
 pub async fn get_transactions(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
     wallet_id: String,
     limit: Option<i32>,
     offset: Option<i32>,
 ) -> Result<Vec<StoredTransaction>, String> {
-    let limit = limit.unwrap_or(100);
-    let offset = offset.unwrap_or(0);
-
-    let transactions = sqlx::query_as::<_, StoredTransaction>(
-        r#"
-        SELECT * FROM transactions
-        WHERE wallet_id = ?
-        ORDER BY timestamp DESC
-        LIMIT ? OFFSET ?
-        "#,
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    wallet_transactions(
+        &state.pool,
+        &profile_id,
+        &wallet_id,
+        limit.unwrap_or(100),
+        offset.unwrap_or(0),
     )
-    .bind(&wallet_id)
-    .bind(limit)
-    .bind(offset)
-    .fetch_all(&state.pool)
     .await
-    .map_err(|e| e.to_string())?;
-
-    Ok(transactions)
 }
 
 /// Retrieves all stored transactions for wallets associated with the given profile ID.
@@ -425,43 +417,85 @@ pub async fn get_transactions(
 #[tauri::command]
 pub async fn get_all_transactions(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     profile_id: String,
     limit: Option<i32>,
     offset: Option<i32>,
 ) -> Result<Vec<StoredTransaction>, String> {
-    let limit = limit.unwrap_or(100);
-    let offset = offset.unwrap_or(0);
-
-    let transactions = sqlx::query_as::<_, StoredTransaction>(
-        r#"
-        SELECT t.* FROM transactions t
-        INNER JOIN wallets w ON t.wallet_id = w.id
-        WHERE w.profile_id = ?
-        ORDER BY t.timestamp DESC
-        LIMIT ? OFFSET ?
-        "#,
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    profile_transactions(
+        &state.pool,
+        &profile_id,
+        limit.unwrap_or(100),
+        offset.unwrap_or(0),
     )
-    .bind(&profile_id)
-    .bind(limit)
-    .bind(offset)
-    .fetch_all(&state.pool)
     .await
-    .map_err(|e| e.to_string())?;
-
-    Ok(transactions)
 }
 
 /// Deletes all transactions for the specified wallet ID and returns the number of rows deleted.
+/// Requires the owner, admin, or preparer role on the wallet's profile.
 #[tauri::command]
 pub async fn delete_transactions(
     state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
     wallet_id: String,
 ) -> Result<u64, String> {
+    let (user_id, wallet) = authorize_wallet(
+        &state.pool,
+        &auth,
+        &token,
+        &wallet_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    ensure_wallet_periods_open(&state.pool, &wallet_id).await?;
+
+    let deleted =
+        sqlx::query_as::<_, StoredTransaction>("SELECT * FROM transactions WHERE wallet_id = ?")
+            .bind(&wallet_id)
+            .fetch_all(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
     let result = sqlx::query("DELETE FROM transactions WHERE wallet_id = ?")
         .bind(&wallet_id)
         .execute(&state.pool)
         .await
         .map_err(|e| e.to_string())?;
+    delete_wallet_swaps(&state.pool, &wallet_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    delete_wallet_token_transfers(&state.pool, &wallet_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    delete_wallet_fee_breakdowns(&state.pool, &wallet_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    delete_wallet_accounting_events(&state.pool, &wallet_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for tx in &deleted {
+        record_change(
+            &state.pool,
+            Some(&user_id),
+            RecordType::Transaction,
+            &tx.id,
+            Some(&wallet.profile_id),
+            Some(tx),
+            None,
+        )
+        .await?;
+    }
 
     Ok(result.rows_affected())
 }
@@ -470,13 +504,22 @@ pub async fn delete_transactions(
 // Settings Commands
 // ============================================================================
 
+/// Refuses keys owned by a gated command.
+fn ensure_not_reserved(key: &str) -> Result<(), String> {
+    if is_reserved_setting(key) {
+        return Err(format!("Setting {} can't be accessed directly", key));
+    }
+    Ok(())
+}
+
 /// Retrieves the value associated with the given key from settings.
-/// Returns `None` if the key does not exist.
+/// Returns `None` if the key does not exist. Reserved keys are refused.
 #[tauri::command]
 pub async fn get_setting(
     state: State<'_, DatabaseState>,
     key: String,
 ) -> Result<Option<String>, String> {
+    ensure_not_reserved(&key)?;
     let result = sqlx::query_scalar::<_, String>("SELECT value FROM settings WHERE key = ?")
         .bind(&key)
         .fetch_optional(&state.pool)
@@ -487,12 +530,14 @@ pub async fn get_setting(
 }
 
 /// Inserts or updates a setting value for the given key with the current timestamp.
+/// Reserved keys are refused.
 #[tauri::command]
 pub async fn set_setting(
     state: State<'_, DatabaseState>,
     key: String,
     value: String,
 ) -> Result<(), String> {
+    ensure_not_reserved(&key)?;
     let now = Utc::now();
 
     sqlx::query(
@@ -514,9 +559,10 @@ pub async fn set_setting(
     Ok(())
 }
 
-/// Deletes the setting associated with the given key.
+/// Deletes the setting associated with the given key. Reserved keys are refused.
 #[tauri::command]
 pub async fn delete_setting(state: State<'_, DatabaseState>, key: String) -> Result<(), String> {
+    ensure_not_reserved(&key)?;
     sqlx::query("DELETE FROM settings WHERE key = ?")
         .bind(&key)
         .execute(&state.pool)
@@ -526,7 +572,7 @@ pub async fn delete_setting(state: State<'_, DatabaseState>, key: String) -> Res
     Ok(())
 }
 
-/// Retrieves all settings as a list of key-value pairs.
+/// Retrieves all settings except reserved ones as a list of key-value pairs.
 #[tauri::command]
 pub async fn get_all_settings(
     state: State<'_, DatabaseState>,
@@ -536,5 +582,68 @@ pub async fn get_all_settings(
         .await
         .map_err(|e| e.to_string())?;
 
-    Ok(settings)
+    Ok(settings
+        .into_iter()
+        .filter(|(key, _)| !is_reserved_setting(key))
+        .collect())
+}
+
+// ============================================================================
+// Schema Commands
+// ============================================================================
+
+/// Returns the applied and latest schema versions.
+#[tauri::command]
+pub async fn get_schema_version(
+    state: State<'_, DatabaseState>,
+) -> Result<migrations::SchemaVersion, String> {
+    migrations::schema_version(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Maintenance Commands
+// ============================================================================
+
+/// Runs SQLite integrity and foreign key checks. Requires the app admin.
+#[tauri::command]
+pub async fn db_integrity_check(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+) -> Result<maintenance::IntegrityReport, String> {
+    let user_id = authenticate(&auth, &token)?;
+    verify_admin(&state.pool, &user_id).await?;
+    maintenance::integrity_check(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Compacts the database file and reports the space reclaimed. Requires the app admin.
+#[tauri::command]
+pub async fn db_vacuum(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+) -> Result<maintenance::VacuumResult, String> {
+    let user_id = authenticate(&auth, &token)?;
+    verify_admin(&state.pool, &user_id).await?;
+    maintenance::vacuum(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Returns the database size and per-table row counts. Requires the app admin.
+#[tauri::command]
+pub async fn db_stats(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+) -> Result<maintenance::DatabaseStats, String> {
+    let user_id = authenticate(&auth, &token)?;
+    verify_admin(&state.pool, &user_id).await?;
+    maintenance::stats(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
 }
@@ -0,0 +1,360 @@
+//! Watch-only portfolio snapshots.
+//!
+//! A snapshot is a passphrase-encrypted file a profile can hand to someone
+//! who only needs to look at the books — a board member, say — without
+//! giving them a login or setting up sync. It carries each wallet's
+//! balances, rebuilt from stored history, and optionally the attested
+//! transaction report for a period. Keys, secrets, members, notes, and
+//! settings are never included. Opening a snapshot decrypts it and returns
+//! it as is; nothing is written to the opening instance's database.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::auth::log_audit_event;
+use super::balance_history::{wallet_balances_at, AssetBalance};
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::{authenticate, authorize_profile, profile_wallets};
+use super::report_attestation::{attested_report, AttestedReport};
+use crate::core::auth_state::AuthState;
+use crate::storage::encryption::{decrypt, encrypt, EncryptedData};
+
+/// Format identifier written into every snapshot file.
+const FORMAT_VERSION: &str = "pacioli-snapshot-v1";
+
+/// Shortest passphrase accepted for a snapshot.
+const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A report that can be included in a snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotReport {
+    /// Per-asset inflow, outflow, and fee totals for the period.
+    PeriodTotals,
+    /// The period's transactions, with the attestation hashes.
+    Transactions,
+}
+
+impl FromStr for SnapshotReport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "period_totals" | "totals" => Ok(Self::PeriodTotals),
+            "transactions" => Ok(Self::Transactions),
+            other => Err(format!("Unknown snapshot report: {}", other)),
+        }
+    }
+}
+
+/// Balances of one wallet in a snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotWallet {
+    /// Chain the wallet is on.
+    pub chain: String,
+    /// Wallet address.
+    pub address: String,
+    /// Display name of the wallet.
+    pub name: Option<String>,
+    /// Non-zero balances as of the snapshot time.
+    pub balances: Vec<AssetBalance>,
+}
+
+/// The decrypted contents of a snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioSnapshot {
+    /// Snapshot format version.
+    pub format_version: String,
+    /// When the snapshot was created.
+    pub created_at: DateTime<Utc>,
+    /// Name of the profile the snapshot was taken from.
+    pub profile_name: String,
+    /// Time the balances are as of.
+    pub as_of: DateTime<Utc>,
+    /// One entry per wallet.
+    pub wallets: Vec<SnapshotWallet>,
+    /// Attested report for the selected period, if one was requested.
+    /// Records are left out unless transactions were selected.
+    pub report: Option<AttestedReport>,
+}
+
+/// A snapshot as written to disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotFile {
+    /// Snapshot format version.
+    pub format_version: String,
+    /// Argon2 salt (base64).
+    pub salt: String,
+    /// AES-GCM nonce (base64).
+    pub nonce: String,
+    /// Encrypted snapshot (base64).
+    pub ciphertext: String,
+}
+
+/// Summary returned to the frontend after a snapshot is written.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotResult {
+    /// Path the snapshot was written to.
+    pub path: String,
+    /// Number of wallets included.
+    pub wallet_count: usize,
+    /// Number of transactions included.
+    pub transaction_count: usize,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Encrypts a snapshot with `passphrase`.
+pub fn seal_snapshot(
+    snapshot: &PortfolioSnapshot,
+    passphrase: &str,
+) -> Result<SnapshotFile, String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+        return Err(format!(
+            "Snapshot passphrase must be at least {} characters",
+            MIN_PASSPHRASE_LENGTH
+        ));
+    }
+    let json = serde_json::to_vec(snapshot).map_err(|e| e.to_string())?;
+    let encrypted = encrypt(&json, passphrase).map_err(|e| e.to_string())?;
+    Ok(SnapshotFile {
+        format_version: FORMAT_VERSION.to_string(),
+        salt: encrypted.salt,
+        nonce: encrypted.nonce,
+        ciphertext: encrypted.ciphertext,
+    })
+}
+
+/// Parses and decrypts a snapshot file, rejecting other formats.
+pub fn open_snapshot(content: &str, passphrase: &str) -> Result<PortfolioSnapshot, String> {
+    let file: SnapshotFile =
+        serde_json::from_str(content).map_err(|e| format!("Invalid snapshot file: {}", e))?;
+    if file.format_version != FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported snapshot format: {}",
+            file.format_version
+        ));
+    }
+    let encrypted = EncryptedData {
+        salt: file.salt,
+        nonce: file.nonce,
+        ciphertext: file.ciphertext,
+    };
+    let json = decrypt(&encrypted, passphrase)
+        .map_err(|_| "Wrong passphrase or corrupted snapshot".to_string())?;
+    serde_json::from_slice(&json).map_err(|e| format!("Invalid snapshot contents: {}", e))
+}
+
+/// Builds a profile's snapshot as of `as_of`, with the attested report for
+/// `period` when any reports are selected.
+async fn build_snapshot(
+    pool: &SqlitePool,
+    profile_id: &str,
+    as_of: DateTime<Utc>,
+    period: Option<&str>,
+    reports: &[SnapshotReport],
+) -> Result<PortfolioSnapshot, String> {
+    let profile_name: String = sqlx::query_scalar("SELECT name FROM profiles WHERE id = ?")
+        .bind(profile_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut wallets = Vec::new();
+    for wallet in profile_wallets(pool, profile_id).await? {
+        let rebuilt = wallet_balances_at(pool, &wallet, as_of).await?;
+        wallets.push(SnapshotWallet {
+            chain: wallet.chain,
+            address: wallet.address,
+            name: wallet.name,
+            balances: rebuilt.balances,
+        });
+    }
+
+    let report = match (period, reports.is_empty()) {
+        (_, true) => None,
+        (None, false) => return Err("Select a period to include reports".to_string()),
+        (Some(period), false) => {
+            let mut report = attested_report(pool, profile_id, period).await?;
+            if !reports.contains(&SnapshotReport::Transactions) {
+                report.records.clear();
+            }
+            Some(report)
+        }
+    };
+
+    Ok(PortfolioSnapshot {
+        format_version: FORMAT_VERSION.to_string(),
+        created_at: Utc::now(),
+        profile_name,
+        as_of,
+        wallets,
+        report,
+    })
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Writes an encrypted, watch-only snapshot of a profile to `path`.
+///
+/// # Arguments
+/// * `as_of` - Time the balances are taken at; defaults to now.
+/// * `period` - `YYYY`, `YYYY-Qn`, `YYYY-MM`, or `YYYY-MM-DD..YYYY-MM-DD`;
+///   required when `reports` is not empty.
+/// * `reports` - Any of `period_totals` and `transactions`.
+/// * `passphrase` - At least eight characters; the recipient needs it to
+///   open the file.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn export_portfolio_snapshot(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    path: String,
+    passphrase: String,
+    as_of: Option<DateTime<Utc>>,
+    period: Option<String>,
+    reports: Option<Vec<String>>,
+) -> Result<SnapshotResult, String> {
+    let pool = &state.pool;
+    let user_id = authorize_profile(pool, &auth, &token, &profile_id, Permission::Export).await?;
+
+    let selected = reports
+        .unwrap_or_default()
+        .iter()
+        .map(|r| r.parse::<SnapshotReport>())
+        .collect::<Result<Vec<_>, _>>()?;
+    let period = period.filter(|p| !p.trim().is_empty());
+
+    let snapshot = build_snapshot(
+        pool,
+        &profile_id,
+        as_of.unwrap_or_else(Utc::now),
+        period.as_deref(),
+        &selected,
+    )
+    .await?;
+    let file = seal_snapshot(&snapshot, &passphrase)?;
+    let json = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    let transaction_count = snapshot.report.as_ref().map_or(0, |r| r.records.len());
+    let details = serde_json::json!({
+        "wallets": snapshot.wallets.len(),
+        "period": period,
+        "transactions": transaction_count,
+    })
+    .to_string();
+    log_audit_event(
+        pool,
+        Some(&user_id),
+        "portfolio_snapshot_exported",
+        "success",
+        Some(&details),
+        None,
+        Some(&profile_id),
+    )
+    .await;
+
+    Ok(SnapshotResult {
+        path,
+        wallet_count: snapshot.wallets.len(),
+        transaction_count,
+    })
+}
+
+/// Opens a snapshot file written by any Pacioli instance for read-only
+/// viewing. Nothing from the snapshot is stored.
+#[tauri::command]
+pub async fn open_portfolio_snapshot(
+    auth: State<'_, AuthState>,
+    token: String,
+    path: String,
+    passphrase: String,
+) -> Result<PortfolioSnapshot, String> {
+    authenticate(&auth, &token)?;
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    open_snapshot(&content, &passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn snapshot() -> PortfolioSnapshot {
+        PortfolioSnapshot {
+            format_version: FORMAT_VERSION.to_string(),
+            created_at: Utc::now(),
+            profile_name: "Treasury".to_string(),
+            as_of: Utc::now(),
+            wallets: vec![SnapshotWallet {
+                chain: "ethereum".to_string(),
+                address: "0xabc".to_string(),
+                name: Some("Operations".to_string()),
+                balances: vec![AssetBalance {
+                    symbol: "ETH".to_string(),
+                    token_address: None,
+                    balance: Decimal::new(15, 1),
+                }],
+            }],
+            report: None,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let file = seal_snapshot(&snapshot(), "correct horse").unwrap();
+        assert!(!file.ciphertext.contains("Treasury"));
+
+        let json = serde_json::to_string(&file).unwrap();
+        let opened = open_snapshot(&json, "correct horse").unwrap();
+        assert_eq!(opened.profile_name, "Treasury");
+        assert_eq!(opened.wallets[0].balances[0].balance, Decimal::new(15, 1));
+        assert!(open_snapshot(&json, "wrong passphrase").is_err());
+    }
+
+    #[test]
+    fn test_snapshot_checks_format_and_passphrase() {
+        assert!(seal_snapshot(&snapshot(), "short").is_err());
+
+        let file = seal_snapshot(&snapshot(), "correct horse").unwrap();
+        let json = serde_json::to_string(&file)
+            .unwrap()
+            .replace(FORMAT_VERSION, "pacioli-snapshot-v0");
+        assert!(open_snapshot(&json, "correct horse").is_err());
+        assert!(open_snapshot("{}", "correct horse").is_err());
+    }
+
+    #[test]
+    fn test_parse_snapshot_report() {
+        assert_eq!(
+            "totals".parse::<SnapshotReport>().unwrap(),
+            SnapshotReport::PeriodTotals
+        );
+        assert_eq!(
+            " Transactions ".parse::<SnapshotReport>().unwrap(),
+            SnapshotReport::Transactions
+        );
+        assert!("balances".parse::<SnapshotReport>().is_err());
+    }
+}
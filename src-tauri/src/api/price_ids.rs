@@ -0,0 +1,117 @@
+//! Tauri commands for viewing and editing a profile's coin ID table.
+
+use chrono::Utc;
+use tauri::State;
+
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::authorize_profile;
+use crate::core::auth_state::AuthState;
+
+pub use pacioli_core::services::price_ids::*;
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns the coin ID table for a profile: the built-in entries, with the
+/// profile's overrides and additions applied.
+///
+/// Requires any role on the profile.
+#[tauri::command]
+pub async fn get_price_id_mappings(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<PriceIdMapping>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    profile_mappings(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Sets the coin ID a profile prices an asset under, or restores the
+/// built-in one when `coin_id` is `None`. `asset` is `native` or a token
+/// contract; `symbol` also routes lookups by that symbol to the coin ID.
+/// Returns the profile's table.
+///
+/// Requires the owner, admin, or preparer role on the profile.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn set_price_id_mapping(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    chain: String,
+    asset: String,
+    symbol: Option<String>,
+    coin_id: Option<String>,
+) -> Result<Vec<PriceIdMapping>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ManageWallets,
+    )
+    .await?;
+
+    let chain = chain.trim().to_lowercase();
+    if chain.is_empty() {
+        return Err("A chain is required".to_string());
+    }
+    let asset = mapping_asset(Some(&asset));
+    match coin_id.map(|id| id.trim().to_string()) {
+        Some(coin_id) if !is_coin_id(&coin_id) => {
+            return Err(format!("Invalid coin ID: {}", coin_id));
+        }
+        Some(coin_id) => {
+            let symbol = symbol
+                .map(|s| s.trim().to_uppercase())
+                .filter(|s| !s.is_empty());
+            sqlx::query(
+                r#"
+                INSERT INTO price_id_mappings (profile_id, chain, asset, symbol, coin_id, updated_at)
+                VALUES (?, ?, ?, ?, ?, ?)
+                ON CONFLICT(profile_id, chain, asset) DO UPDATE SET
+                    symbol = excluded.symbol,
+                    coin_id = excluded.coin_id,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(&profile_id)
+            .bind(&chain)
+            .bind(&asset)
+            .bind(symbol)
+            .bind(coin_id)
+            .bind(Utc::now())
+            .execute(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+        None => {
+            sqlx::query(
+                "DELETE FROM price_id_mappings WHERE profile_id = ? AND chain = ? AND asset = ?",
+            )
+            .bind(&profile_id)
+            .bind(&chain)
+            .bind(&asset)
+            .execute(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+    }
+
+    profile_mappings(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,284 @@
+//! Manual token price overrides.
+//!
+//! Illiquid and LP tokens often have no reliable provider price, so users
+//! can set their own per token and currency: either fixed, or for a date
+//! range. Price lookups and the cost-basis engine use an override whenever
+//! one covers the date in question, and reports note the sections that
+//! relied on one.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::State;
+use uuid::Uuid;
+
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::{authenticate, authorize_profile, authorize_record};
+use crate::core::auth_state::AuthState;
+use crate::core::cost_basis::AssetEvent;
+
+pub use pacioli_core::services::price_overrides::*;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Fields for creating or replacing an override.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceOverrideInput {
+    /// Existing override to replace; `None` to create one.
+    pub id: Option<String>,
+    /// Profile the override applies to.
+    pub profile_id: String,
+    /// CoinGecko coin ID or asset symbol.
+    pub token: String,
+    /// Currency the price is in.
+    pub currency: String,
+    /// Price of one token.
+    pub price: String,
+    /// Start of the range, inclusive.
+    pub valid_from: Option<DateTime<Utc>>,
+    /// End of the range, exclusive.
+    pub valid_to: Option<DateTime<Utc>>,
+    /// Why the price was overridden.
+    pub note: Option<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Finds the override for one token at one time.
+pub(crate) async fn find_override(
+    pool: &SqlitePool,
+    profile_id: &str,
+    token: &str,
+    currency: &str,
+    at: DateTime<Utc>,
+) -> Result<Option<PriceOverride>, sqlx::Error> {
+    let overrides: Vec<PriceOverride> =
+        sqlx::query_as("SELECT * FROM price_overrides WHERE profile_id = ? AND token = ?")
+            .bind(profile_id)
+            .bind(token)
+            .fetch_all(pool)
+            .await?;
+    Ok(select_override(&overrides, token, currency, at).cloned())
+}
+
+/// Revalues events at overridden prices. Each event whose asset has an
+/// override in `currency` at its timestamp gets `quantity × price` as its
+/// value and records the override it used.
+pub(crate) fn apply_to_events(
+    overrides: &[PriceOverride],
+    currency: &str,
+    events: &mut [AssetEvent],
+) {
+    for event in events.iter_mut() {
+        let Some(found) = select_override(overrides, &event.asset, currency, event.timestamp)
+        else {
+            continue;
+        };
+        let Ok(price) = Decimal::from_str(&found.price) else {
+            continue;
+        };
+        event.value = price * event.quantity;
+        event.price_override = Some(found.id.clone());
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Lists a profile's price overrides. Requires any role on the profile.
+#[tauri::command]
+pub async fn get_price_overrides(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<PriceOverride>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    load_overrides(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Creates or replaces a price override. Requires the owner, admin, or
+/// preparer role on the profile.
+#[tauri::command]
+pub async fn save_price_override(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    input: PriceOverrideInput,
+) -> Result<PriceOverride, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &input.profile_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    let token = input.token.trim();
+    if token.is_empty() {
+        return Err("A token is required".to_string());
+    }
+    let price = Decimal::from_str(input.price.trim())
+        .map_err(|_| format!("Invalid price: {}", input.price))?;
+    if price < Decimal::ZERO {
+        return Err("Price cannot be negative".to_string());
+    }
+    if let (Some(from), Some(to)) = (input.valid_from, input.valid_to) {
+        if from >= to {
+            return Err("The range must end after it starts".to_string());
+        }
+    }
+
+    let id = input.id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    let now = Utc::now();
+    sqlx::query(
+        r#"
+        INSERT INTO price_overrides (
+            id, profile_id, token, currency, price, valid_from, valid_to, note,
+            created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(id) DO UPDATE SET
+            token = excluded.token,
+            currency = excluded.currency,
+            price = excluded.price,
+            valid_from = excluded.valid_from,
+            valid_to = excluded.valid_to,
+            note = excluded.note,
+            updated_at = excluded.updated_at
+        WHERE price_overrides.profile_id = excluded.profile_id
+        "#,
+    )
+    .bind(&id)
+    .bind(&input.profile_id)
+    .bind(token)
+    .bind(input.currency.trim().to_uppercase())
+    .bind(price.to_string())
+    .bind(input.valid_from)
+    .bind(input.valid_to)
+    .bind(&input.note)
+    .bind(now)
+    .bind(now)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    sqlx::query_as("SELECT * FROM price_overrides WHERE id = ? AND profile_id = ?")
+        .bind(&id)
+        .bind(&input.profile_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Price override belongs to another profile".to_string())
+}
+
+/// Deletes a price override. Requires the owner, admin, or preparer role on
+/// the override's profile.
+#[tauri::command]
+pub async fn delete_price_override(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    id: String,
+) -> Result<(), String> {
+    let user_id = authenticate(&auth, &token)?;
+    let not_found = "Price override not found";
+    let profile_id: String =
+        sqlx::query_scalar("SELECT profile_id FROM price_overrides WHERE id = ?")
+            .bind(&id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| not_found.to_string())?;
+    authorize_record(
+        &state.pool,
+        &user_id,
+        &profile_id,
+        Permission::EditTransactions,
+        not_found,
+    )
+    .await?;
+
+    sqlx::query("DELETE FROM price_overrides WHERE id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cost_basis::AssetEventKind;
+    use chrono::TimeZone;
+
+    fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+    }
+
+    fn price_override(
+        id: &str,
+        price: &str,
+        range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    ) -> PriceOverride {
+        PriceOverride {
+            id: id.to_string(),
+            profile_id: "p1".to_string(),
+            token: "UNI-V2".to_string(),
+            currency: "USD".to_string(),
+            price: price.to_string(),
+            valid_from: range.map(|(from, _)| from),
+            valid_to: range.map(|(_, to)| to),
+            note: None,
+            created_at: at(2024, 1, 1),
+            updated_at: at(2024, 1, 1),
+        }
+    }
+
+    #[test]
+    fn test_apply_to_events_revalues_and_tags() {
+        let overrides = vec![price_override(
+            "q1",
+            "2.5",
+            Some((at(2024, 1, 1), at(2024, 4, 1))),
+        )];
+        let event = |id: &str, timestamp| AssetEvent {
+            id: id.to_string(),
+            asset: "UNI-V2".to_string(),
+            kind: AssetEventKind::Acquisition,
+            timestamp,
+            quantity: Decimal::from(4),
+            value: Decimal::from(7),
+            fee: Decimal::ZERO,
+            income_source: None,
+            price_override: None,
+            lot_ids: Vec::new(),
+        };
+        let mut events = vec![event("in", at(2024, 2, 1)), event("out", at(2024, 5, 1))];
+        apply_to_events(&overrides, "USD", &mut events);
+
+        assert_eq!(events[0].value, Decimal::from(10));
+        assert_eq!(events[0].price_override.as_deref(), Some("q1"));
+        assert_eq!(events[1].value, Decimal::from(7));
+        assert!(events[1].price_override.is_none());
+    }
+}
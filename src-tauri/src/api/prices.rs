@@ -1,14 +1,64 @@
 //! Price Feed Commands
 //!
-//! Tauri commands for fetching cryptocurrency prices from CoinGecko.
-//! Used to add USD values to imported transactions.
+//! Tauri commands for fetching cryptocurrency prices. Prices come from
+//! CoinGecko, falling back to CryptoCompare and DefiLlama (see
+//! [`PriceService`]), unless the profile has a manual price override for the
+//! coin. Used to add USD values to imported transactions. While offline the
+//! last prices fetched are returned (see [`crate::fetchers::offline`]).
 
-use super::price_feeds::CoinGeckoClient;
+use super::persistence::DatabaseState;
+use super::price_feeds::{PriceQuote, PriceService};
+use super::price_overrides::{load_overrides, select_override, PriceOverride};
+use crate::fetchers::offline::{fetch_or_cached, Fetched};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
 use std::collections::HashMap;
+use tauri::State;
 
-/// Environment variable name for the CoinGecko API key.
-static ENV_COINGECKO_API_KEY: &str = "COINGECKO_API_KEY";
+/// Parses a date in CoinGecko's DD-MM-YYYY format.
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%d-%m-%Y")
+        .map_err(|_| format!("Invalid date (expected DD-MM-YYYY): {}", date))
+}
+
+/// Start of `date` in UTC, the time historical prices are quoted at.
+fn start_of(date: NaiveDate) -> DateTime<Utc> {
+    date.and_time(NaiveTime::MIN).and_utc()
+}
+
+/// Loads the price overrides of a profile, if one is given.
+async fn profile_overrides(
+    pool: &SqlitePool,
+    profile_id: Option<&str>,
+) -> Result<Vec<PriceOverride>, String> {
+    match profile_id {
+        Some(profile_id) => load_overrides(pool, profile_id)
+            .await
+            .map_err(|e| e.to_string()),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Quote from the override covering `coin_id` at `at`, if any.
+fn override_quote(
+    overrides: &[PriceOverride],
+    coin_id: &str,
+    currency: &str,
+    at: DateTime<Utc>,
+) -> Option<PriceQuote> {
+    select_override(overrides, coin_id, currency, at).map(PriceOverride::quote)
+}
+
+/// Offline cache key for a current price.
+fn current_key(coin_id: &str, currency: &str) -> String {
+    format!("price:{}:{}", coin_id, currency)
+}
+
+/// Offline cache key for a historical price.
+fn historical_key(coin_id: &str, day: NaiveDate, currency: &str) -> String {
+    format!("historical_price:{}:{}:{}", coin_id, day, currency)
+}
 
 /// Response for a single price lookup.
 #[derive(Debug, Serialize, Deserialize)]
@@ -19,6 +69,12 @@ pub struct PriceResponse {
     pub price: String,
     /// The currency the price is denominated in.
     pub currency: String,
+    /// The provider that supplied the price.
+    pub provider: String,
+    /// The endpoint the price was read from.
+    pub endpoint: String,
+    /// When the price was retrieved.
+    pub retrieved_at: DateTime<Utc>,
 }
 
 /// Response for a historical price lookup.
@@ -32,6 +88,12 @@ pub struct HistoricalPriceResponse {
     pub currency: String,
     /// The date of the price (DD-MM-YYYY format).
     pub date: String,
+    /// The provider that supplied the price.
+    pub provider: String,
+    /// The endpoint the price was read from.
+    pub endpoint: String,
+    /// When the price was retrieved.
+    pub retrieved_at: DateTime<Utc>,
 }
 
 /// Response for batch historical price lookups.
@@ -50,27 +112,37 @@ pub struct BatchHistoricalPriceResponse {
 /// # Arguments
 /// * `coin_id` - CoinGecko coin ID (e.g., "polkadot", "kusama", "ethereum")
 /// * `vs_currency` - Target currency (e.g., "usd", "eur"). Defaults to "usd".
+/// * `profile_id` - Profile whose price overrides apply, if any.
 #[tauri::command]
 pub async fn get_crypto_price(
+    state: State<'_, DatabaseState>,
     coin_id: String,
     vs_currency: Option<String>,
-) -> Result<PriceResponse, String> {
+    profile_id: Option<String>,
+) -> Result<Fetched<PriceResponse>, String> {
     let currency = vs_currency.unwrap_or_else(|| "usd".to_string());
 
-    // Load API key from environment if available
-    let api_key = std::env::var(ENV_COINGECKO_API_KEY).ok();
-    let client = CoinGeckoClient::new(api_key);
-
-    let price = client
-        .get_price(&coin_id, &currency)
-        .await
-        .map_err(|e| e.to_string())?;
+    let overrides = profile_overrides(&state.pool, profile_id.as_deref()).await?;
+    let quote = match override_quote(&overrides, &coin_id, &currency, Utc::now()) {
+        Some(quote) => Fetched::Live { data: quote },
+        None => {
+            fetch_or_cached(&state.pool, &current_key(&coin_id, &currency), async {
+                PriceService::shared()?
+                    .current_price(&coin_id, &currency)
+                    .await
+            })
+            .await?
+        }
+    };
 
-    Ok(PriceResponse {
+    Ok(quote.map(|quote| PriceResponse {
         coin_id,
-        price,
+        price: quote.price,
         currency,
-    })
+        provider: quote.provider,
+        endpoint: quote.endpoint,
+        retrieved_at: quote.retrieved_at,
+    }))
 }
 
 /// Get current prices for multiple cryptocurrencies.
@@ -78,23 +150,51 @@ pub async fn get_crypto_price(
 /// # Arguments
 /// * `coin_ids` - List of CoinGecko coin IDs
 /// * `vs_currency` - Target currency. Defaults to "usd".
+/// * `profile_id` - Profile whose price overrides apply, if any.
+///
+/// While offline, the prices last fetched for the same coins are returned.
 #[tauri::command]
 pub async fn get_crypto_prices(
+    state: State<'_, DatabaseState>,
     coin_ids: Vec<String>,
     vs_currency: Option<String>,
-) -> Result<HashMap<String, String>, String> {
+    profile_id: Option<String>,
+) -> Result<Fetched<HashMap<String, String>>, String> {
     let currency = vs_currency.unwrap_or_else(|| "usd".to_string());
 
-    let api_key = std::env::var(ENV_COINGECKO_API_KEY).ok();
-    let client = CoinGeckoClient::new(api_key);
+    let overrides = profile_overrides(&state.pool, profile_id.as_deref()).await?;
+    let now = Utc::now();
+    let mut prices: HashMap<String, String> = coin_ids
+        .iter()
+        .filter_map(|id| {
+            override_quote(&overrides, id, &currency, now).map(|quote| (id.clone(), quote.price))
+        })
+        .collect();
 
-    let ids: Vec<&str> = coin_ids.iter().map(|s| s.as_str()).collect();
-    let prices = client
-        .get_multiple_prices(&ids, &currency)
-        .await
-        .map_err(|e| e.to_string())?;
+    let ids: Vec<&str> = coin_ids
+        .iter()
+        .map(|s| s.as_str())
+        .filter(|id| !prices.contains_key(*id))
+        .collect();
+    if ids.is_empty() {
+        return Ok(Fetched::Live { data: prices });
+    }
 
-    Ok(prices)
+    let key = current_key(&ids.join(","), &currency);
+    let quotes = fetch_or_cached(&state.pool, &key, async {
+        PriceService::shared()?
+            .current_prices(&ids, &currency)
+            .await
+    })
+    .await?;
+    Ok(quotes.map(|quotes| {
+        prices.extend(
+            quotes
+                .into_iter()
+                .map(|(coin_id, quote)| (coin_id, quote.price)),
+        );
+        prices
+    }))
 }
 
 /// Get historical price for a cryptocurrency on a specific date.
@@ -103,28 +203,44 @@ pub async fn get_crypto_prices(
 /// * `coin_id` - CoinGecko coin ID (e.g., "polkadot", "kusama")
 /// * `date` - Date in DD-MM-YYYY format (CoinGecko's required format)
 /// * `vs_currency` - Target currency. Defaults to "usd".
+/// * `profile_id` - Profile whose price overrides apply, if any.
 #[tauri::command]
 pub async fn get_historical_crypto_price(
+    state: State<'_, DatabaseState>,
     coin_id: String,
     date: String,
     vs_currency: Option<String>,
-) -> Result<HistoricalPriceResponse, String> {
+    profile_id: Option<String>,
+) -> Result<Fetched<HistoricalPriceResponse>, String> {
     let currency = vs_currency.unwrap_or_else(|| "usd".to_string());
+    let day = parse_date(&date)?;
 
-    let api_key = std::env::var(ENV_COINGECKO_API_KEY).ok();
-    let client = CoinGeckoClient::new(api_key);
-
-    let price = client
-        .get_historical_price(&coin_id, &date, &currency)
-        .await
-        .map_err(|e| e.to_string())?;
+    let overrides = profile_overrides(&state.pool, profile_id.as_deref()).await?;
+    let quote = match override_quote(&overrides, &coin_id, &currency, start_of(day)) {
+        Some(quote) => Fetched::Live { data: quote },
+        None => {
+            fetch_or_cached(
+                &state.pool,
+                &historical_key(&coin_id, day, &currency),
+                async {
+                    PriceService::shared()?
+                        .historical_price(&coin_id, day, &currency)
+                        .await
+                },
+            )
+            .await?
+        }
+    };
 
-    Ok(HistoricalPriceResponse {
+    Ok(quote.map(|quote| HistoricalPriceResponse {
         coin_id,
-        price,
+        price: quote.price,
         currency,
         date,
-    })
+        provider: quote.provider,
+        endpoint: quote.endpoint,
+        retrieved_at: quote.retrieved_at,
+    }))
 }
 
 /// Get historical prices for multiple cryptocurrencies on a specific date.
@@ -134,42 +250,53 @@ pub async fn get_historical_crypto_price(
 /// * `coin_ids` - List of CoinGecko coin IDs
 /// * `date` - Date in DD-MM-YYYY format
 /// * `vs_currency` - Target currency. Defaults to "usd".
+/// * `profile_id` - Profile whose price overrides apply, if any.
 ///
-/// Note: CoinGecko's free API has rate limits. This function makes sequential
-/// requests with a small delay to avoid hitting rate limits.
+/// Requests are made one at a time; each provider's rate limiter spaces them
+/// out.
 #[tauri::command]
 pub async fn get_batch_historical_prices(
+    state: State<'_, DatabaseState>,
     coin_ids: Vec<String>,
     date: String,
     vs_currency: Option<String>,
-) -> Result<BatchHistoricalPriceResponse, String> {
+    profile_id: Option<String>,
+) -> Result<Fetched<BatchHistoricalPriceResponse>, String> {
     let currency = vs_currency.unwrap_or_else(|| "usd".to_string());
 
-    let api_key = std::env::var(ENV_COINGECKO_API_KEY).ok();
-    let client = CoinGeckoClient::new(api_key);
-
+    let service = PriceService::shared()?;
+    let day = parse_date(&date)?;
+    let overrides = profile_overrides(&state.pool, profile_id.as_deref()).await?;
     let mut prices: HashMap<String, Result<String, String>> = HashMap::new();
+    let mut cached_at = None;
 
     for coin_id in &coin_ids {
-        // Add delay between requests to respect rate limits (10-30 calls/min for free tier)
-        if !prices.is_empty() {
-            tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
+        if let Some(quote) = override_quote(&overrides, coin_id, &currency, start_of(day)) {
+            prices.insert(coin_id.clone(), Ok(quote.price));
+            continue;
         }
-
-        match client.get_historical_price(coin_id, &date, &currency).await {
-            Ok(price) => {
-                prices.insert(coin_id.clone(), Ok(price));
-            }
-            Err(e) => {
-                prices.insert(coin_id.clone(), Err(e.to_string()));
+        let key = historical_key(coin_id, day, &currency);
+        let price = fetch_or_cached(&state.pool, &key, async {
+            service.historical_price(coin_id, day, &currency).await
+        })
+        .await
+        .map(|quote| {
+            if let Some(at) = quote.cached_at() {
+                cached_at = Some(cached_at.map_or(at, |oldest: DateTime<Utc>| oldest.min(at)));
             }
-        }
+            quote.data().price.clone()
+        });
+        prices.insert(coin_id.clone(), price);
     }
 
-    Ok(BatchHistoricalPriceResponse {
+    let response = BatchHistoricalPriceResponse {
         prices,
         currency,
         date: date.clone(),
+    };
+    Ok(match cached_at {
+        Some(cached_at) => Fetched::offline(response, cached_at),
+        None => Fetched::Live { data: response },
     })
 }
 
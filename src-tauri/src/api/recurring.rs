@@ -0,0 +1,956 @@
+//! Recurring transaction detection.
+//!
+//! Groups a wallet's transactions by counterparty, direction, and asset, and
+//! looks for groups with similar amounts at a regular cadence (payroll,
+//! subscriptions, vesting claims). Detected series are stored so users can
+//! label them and have future occurrences tagged automatically with a budget
+//! category.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::audit_trail::{record_change, RecordType};
+use super::budgets::TransactionTag;
+use super::period_close::ensure_period_open;
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction, Wallet};
+use super::profile_scope::{authenticate, authorize_profile, authorize_record, authorize_wallet};
+use super::statement_export::parse_amount;
+use crate::core::auth_state::AuthState;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// How often a series recurs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Cadence {
+    /// Every 7 days.
+    Weekly,
+    /// Every 14 days.
+    Biweekly,
+    /// Roughly every calendar month.
+    Monthly,
+    /// Roughly every three months.
+    Quarterly,
+    /// Roughly every year.
+    Yearly,
+}
+
+impl Cadence {
+    const ALL: [Cadence; 5] = [
+        Cadence::Weekly,
+        Cadence::Biweekly,
+        Cadence::Monthly,
+        Cadence::Quarterly,
+        Cadence::Yearly,
+    ];
+
+    /// Nominal interval in days.
+    pub fn days(self) -> i64 {
+        match self {
+            Cadence::Weekly => 7,
+            Cadence::Biweekly => 14,
+            Cadence::Monthly => 30,
+            Cadence::Quarterly => 91,
+            Cadence::Yearly => 365,
+        }
+    }
+
+    /// Allowed deviation from the nominal interval, in days.
+    fn tolerance_days(self) -> f64 {
+        match self {
+            Cadence::Weekly => 1.0,
+            Cadence::Biweekly => 2.0,
+            Cadence::Monthly => 3.5,
+            Cadence::Quarterly => 8.0,
+            Cadence::Yearly => 12.0,
+        }
+    }
+
+    /// Returns the cadence an interval (in days) falls within, if any.
+    pub fn classify(interval_days: f64) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|c| (interval_days - c.days() as f64).abs() <= c.tolerance_days())
+    }
+
+    /// Database string representation.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Cadence::Weekly => "weekly",
+            Cadence::Biweekly => "biweekly",
+            Cadence::Monthly => "monthly",
+            Cadence::Quarterly => "quarterly",
+            Cadence::Yearly => "yearly",
+        }
+    }
+}
+
+/// Tuning parameters for detection.
+#[derive(Debug, Clone)]
+pub struct DetectionConfig {
+    /// Minimum number of occurrences before a group counts as recurring.
+    pub min_occurrences: usize,
+    /// Maximum relative deviation from the typical amount (0.10 = 10%).
+    pub amount_tolerance: Decimal,
+    /// Minimum share of intervals that must match the cadence.
+    pub min_confidence: f64,
+}
+
+impl Default for DetectionConfig {
+    fn default() -> Self {
+        Self {
+            min_occurrences: 3,
+            amount_tolerance: Decimal::new(10, 2),
+            min_confidence: 0.75,
+        }
+    }
+}
+
+/// A recurring series found by [`detect_series`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedSeries {
+    /// Counterparty address (lowercased).
+    pub counterparty: String,
+    /// `incoming` or `outgoing`.
+    pub direction: String,
+    /// Asset symbol, empty if unknown.
+    pub token_symbol: String,
+    /// Detected cadence.
+    pub cadence: Cadence,
+    /// Median observed interval in whole days.
+    pub interval_days: i64,
+    /// Median amount of the occurrences.
+    pub typical_amount: Decimal,
+    /// Absolute amount tolerance used when matching new occurrences.
+    pub amount_tolerance: Decimal,
+    /// Share of intervals that matched the cadence.
+    pub confidence: f64,
+    /// Member transaction IDs with their timestamps, oldest first.
+    pub occurrences: Vec<(String, DateTime<Utc>)>,
+}
+
+impl DetectedSeries {
+    /// When the next occurrence is expected.
+    pub fn next_expected_at(&self) -> DateTime<Utc> {
+        let last = self
+            .occurrences
+            .last()
+            .map(|(_, t)| *t)
+            .unwrap_or_else(Utc::now);
+        last + Duration::days(self.cadence.days())
+    }
+}
+
+/// A stored recurring series.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RecurringSeries {
+    /// Unique identifier for the series.
+    pub id: String,
+    /// Profile that owns the wallet.
+    pub profile_id: String,
+    /// Wallet the series was detected on.
+    pub wallet_id: String,
+    /// Counterparty address.
+    pub counterparty: String,
+    /// `incoming` or `outgoing`.
+    pub direction: String,
+    /// Asset symbol.
+    pub token_symbol: String,
+    /// Cadence name.
+    pub cadence: String,
+    /// Median observed interval in days.
+    pub interval_days: i64,
+    /// Typical amount as a decimal string.
+    pub typical_amount: String,
+    /// Absolute amount tolerance as a decimal string.
+    pub amount_tolerance: String,
+    /// Number of member transactions.
+    pub occurrence_count: i64,
+    /// Share of intervals that matched the cadence.
+    pub confidence: f64,
+    /// First occurrence.
+    pub first_seen_at: DateTime<Utc>,
+    /// Most recent occurrence.
+    pub last_seen_at: DateTime<Utc>,
+    /// When the next occurrence is expected.
+    pub next_expected_at: DateTime<Utc>,
+    /// User-facing label (e.g. "Payroll").
+    pub label: Option<String>,
+    /// Budget category applied to future occurrences.
+    pub auto_tag_category: Option<String>,
+    /// Entity applied to future occurrences.
+    pub auto_tag_entity_id: Option<String>,
+    /// Whether the series is still tracked.
+    pub is_active: bool,
+    /// Timestamp when the series was created.
+    pub created_at: Option<DateTime<Utc>>,
+    /// Timestamp when the series was last updated.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl RecurringSeries {
+    /// Whether a transaction amount falls within this series' tolerance.
+    pub fn amount_matches(&self, amount: Decimal) -> bool {
+        let typical = Decimal::from_str(&self.typical_amount).unwrap_or(Decimal::ZERO);
+        let tolerance = Decimal::from_str(&self.amount_tolerance).unwrap_or(Decimal::ZERO);
+        (amount - typical).abs() <= tolerance
+    }
+}
+
+/// Result of applying auto-tags to a wallet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoTagResult {
+    /// New transactions added to a series.
+    pub matched: usize,
+    /// Budget tags created for those transactions.
+    pub tagged: usize,
+}
+
+// ============================================================================
+// Detection
+// ============================================================================
+
+struct Occurrence {
+    id: String,
+    at: DateTime<Utc>,
+    amount: Decimal,
+}
+
+/// Classifies a transaction relative to a wallet, returning
+/// `(direction, counterparty, token_symbol, amount)`.
+fn classify_transaction(
+    wallet_address: &str,
+    tx: &StoredTransaction,
+) -> Option<(&'static str, String, String, Decimal)> {
+    if tx.status.as_deref() == Some("failed") {
+        return None;
+    }
+    let from = tx.from_address.as_deref().unwrap_or_default();
+    let to = tx.to_address.as_deref().unwrap_or_default();
+    let (direction, counterparty) = if from.eq_ignore_ascii_case(wallet_address) {
+        ("outgoing", to)
+    } else if to.eq_ignore_ascii_case(wallet_address) {
+        ("incoming", from)
+    } else {
+        return None;
+    };
+    if counterparty.is_empty() || counterparty.eq_ignore_ascii_case(wallet_address) {
+        return None;
+    }
+    let amount = parse_amount(tx.value.as_deref()?, tx.token_decimals)?;
+    if amount.is_zero() {
+        return None;
+    }
+    Some((
+        direction,
+        counterparty.to_lowercase(),
+        tx.token_symbol.clone().unwrap_or_default(),
+        amount,
+    ))
+}
+
+fn median_decimal(values: &mut [Decimal]) -> Decimal {
+    values.sort();
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / Decimal::TWO
+    } else {
+        values[mid]
+    }
+}
+
+fn median_f64(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Detects recurring series among a wallet's transactions.
+pub fn detect_series(
+    wallet_address: &str,
+    transactions: &[StoredTransaction],
+    config: &DetectionConfig,
+) -> Vec<DetectedSeries> {
+    let mut groups: HashMap<(&'static str, String, String), Vec<Occurrence>> = HashMap::new();
+
+    for tx in transactions {
+        let Some(at) = tx.timestamp else {
+            continue;
+        };
+        let Some((direction, counterparty, symbol, amount)) =
+            classify_transaction(wallet_address, tx)
+        else {
+            continue;
+        };
+        groups
+            .entry((direction, counterparty, symbol))
+            .or_default()
+            .push(Occurrence {
+                id: tx.id.clone(),
+                at,
+                amount,
+            });
+    }
+
+    let mut series = Vec::new();
+
+    for ((direction, counterparty, symbol), mut occurrences) in groups {
+        if occurrences.len() < config.min_occurrences {
+            continue;
+        }
+
+        let mut amounts: Vec<Decimal> = occurrences.iter().map(|o| o.amount).collect();
+        let typical = median_decimal(&mut amounts);
+        let tolerance = (typical * config.amount_tolerance).abs();
+        occurrences.retain(|o| (o.amount - typical).abs() <= tolerance);
+        if occurrences.len() < config.min_occurrences {
+            continue;
+        }
+
+        occurrences.sort_by_key(|o| o.at);
+        let mut intervals: Vec<f64> = occurrences
+            .windows(2)
+            .map(|w| (w[1].at - w[0].at).num_seconds() as f64 / 86_400.0)
+            .collect();
+        let median_interval = median_f64(&mut intervals);
+        let Some(cadence) = Cadence::classify(median_interval) else {
+            continue;
+        };
+
+        let matching = intervals
+            .iter()
+            .filter(|i| (**i - cadence.days() as f64).abs() <= cadence.tolerance_days())
+            .count();
+        let confidence = matching as f64 / intervals.len() as f64;
+        if confidence < config.min_confidence {
+            continue;
+        }
+
+        series.push(DetectedSeries {
+            counterparty,
+            direction: direction.to_string(),
+            token_symbol: symbol,
+            cadence,
+            interval_days: median_interval.round() as i64,
+            typical_amount: typical,
+            amount_tolerance: tolerance,
+            confidence,
+            occurrences: occurrences.into_iter().map(|o| (o.id, o.at)).collect(),
+        });
+    }
+
+    series.sort_by(|a, b| {
+        a.counterparty
+            .cmp(&b.counterparty)
+            .then_with(|| a.direction.cmp(&b.direction))
+            .then_with(|| a.token_symbol.cmp(&b.token_symbol))
+    });
+    series
+}
+
+// ============================================================================
+// Persistence helpers
+// ============================================================================
+
+async fn get_wallet_transactions(
+    pool: &SqlitePool,
+    wallet_id: &str,
+) -> Result<Vec<StoredTransaction>, String> {
+    sqlx::query_as::<_, StoredTransaction>(
+        "SELECT * FROM transactions WHERE wallet_id = ? ORDER BY timestamp ASC",
+    )
+    .bind(wallet_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+async fn fetch_series(
+    conn: &mut SqliteConnection,
+    series_id: &str,
+) -> Result<Option<RecurringSeries>, String> {
+    sqlx::query_as::<_, RecurringSeries>("SELECT * FROM recurring_series WHERE id = ?")
+        .bind(series_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Verifies `token` and that its user's role on the profile owning
+/// `series_id` grants `permission`. Returns the user ID and the series.
+async fn authorize_series(
+    pool: &SqlitePool,
+    auth: &AuthState,
+    token: &str,
+    series_id: &str,
+    permission: Permission,
+) -> Result<(String, RecurringSeries), String> {
+    let user_id = authenticate(auth, token)?;
+    let not_found = "Recurring series not found";
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let series = fetch_series(&mut conn, series_id)
+        .await?
+        .ok_or_else(|| not_found.to_string())?;
+    authorize_record(pool, &user_id, &series.profile_id, permission, not_found).await?;
+    Ok((user_id, series))
+}
+
+async fn save_detected_series(
+    pool: &SqlitePool,
+    wallet: &Wallet,
+    detected: &DetectedSeries,
+) -> Result<(), String> {
+    let now = Utc::now();
+    let first_seen = detected.occurrences.first().map(|(_, t)| *t).unwrap_or(now);
+    let last_seen = detected.occurrences.last().map(|(_, t)| *t).unwrap_or(now);
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+    // Upsert keeps user-set label and auto-tag settings on re-detection
+    sqlx::query(
+        r#"
+        INSERT INTO recurring_series (
+            id, profile_id, wallet_id, counterparty, direction, token_symbol, cadence,
+            interval_days, typical_amount, amount_tolerance, occurrence_count, confidence,
+            first_seen_at, last_seen_at, next_expected_at, is_active, created_at, updated_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 1, ?, ?)
+        ON CONFLICT(wallet_id, counterparty, direction, token_symbol) DO UPDATE SET
+            cadence = excluded.cadence,
+            interval_days = excluded.interval_days,
+            typical_amount = excluded.typical_amount,
+            amount_tolerance = excluded.amount_tolerance,
+            occurrence_count = excluded.occurrence_count,
+            confidence = excluded.confidence,
+            first_seen_at = excluded.first_seen_at,
+            last_seen_at = excluded.last_seen_at,
+            next_expected_at = excluded.next_expected_at,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(&wallet.profile_id)
+    .bind(&wallet.id)
+    .bind(&detected.counterparty)
+    .bind(&detected.direction)
+    .bind(&detected.token_symbol)
+    .bind(detected.cadence.as_str())
+    .bind(detected.interval_days)
+    .bind(detected.typical_amount.to_string())
+    .bind(detected.amount_tolerance.to_string())
+    .bind(detected.occurrences.len() as i64)
+    .bind(detected.confidence)
+    .bind(first_seen)
+    .bind(last_seen)
+    .bind(detected.next_expected_at())
+    .bind(now)
+    .bind(now)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let series_id: String = sqlx::query_scalar(
+        r#"
+        SELECT id FROM recurring_series
+        WHERE wallet_id = ? AND counterparty = ? AND direction = ? AND token_symbol = ?
+        "#,
+    )
+    .bind(&wallet.id)
+    .bind(&detected.counterparty)
+    .bind(&detected.direction)
+    .bind(&detected.token_symbol)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for (transaction_id, occurred_at) in &detected.occurrences {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO recurring_series_members (series_id, transaction_id, occurred_at)
+            VALUES (?, ?, ?)
+            "#,
+        )
+        .bind(&series_id)
+        .bind(transaction_id)
+        .bind(occurred_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Runs recurring-pattern detection over a wallet's transactions, stores the
+/// series found, and returns all series for the wallet.
+///
+/// # Arguments
+/// * `wallet_id` - The wallet to analyse.
+/// * `amount_tolerance` - Optional relative amount tolerance (default `0.10`).
+/// * `min_occurrences` - Optional minimum occurrences (default 3).
+///
+/// Requires the owner, admin, or preparer role on the wallet's profile.
+#[tauri::command]
+pub async fn detect_recurring_transactions(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    wallet_id: String,
+    amount_tolerance: Option<String>,
+    min_occurrences: Option<usize>,
+) -> Result<Vec<RecurringSeries>, String> {
+    let (_, wallet) =
+        authorize_wallet(&state.pool, &auth, &token, &wallet_id, Permission::EditTags).await?;
+    let mut config = DetectionConfig::default();
+    if let Some(tolerance) = amount_tolerance {
+        config.amount_tolerance = Decimal::from_str(&tolerance)
+            .map_err(|_| format!("Invalid amount tolerance: {}", tolerance))?;
+    }
+    if let Some(min) = min_occurrences {
+        config.min_occurrences = min.max(2);
+    }
+
+    let transactions = get_wallet_transactions(&state.pool, &wallet_id).await?;
+
+    for detected in detect_series(&wallet.address, &transactions, &config) {
+        save_detected_series(&state.pool, &wallet, &detected).await?;
+    }
+
+    sqlx::query_as::<_, RecurringSeries>(
+        "SELECT * FROM recurring_series WHERE wallet_id = ? ORDER BY next_expected_at ASC",
+    )
+    .bind(&wallet_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Lists recurring series for a profile. Requires any role on the profile.
+#[tauri::command]
+pub async fn get_recurring_series(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<RecurringSeries>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as::<_, RecurringSeries>(
+        "SELECT * FROM recurring_series WHERE profile_id = ? ORDER BY next_expected_at ASC",
+    )
+    .bind(&profile_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Returns the transaction IDs belonging to a series, oldest first.
+/// Requires any role on the series' profile.
+#[tauri::command]
+pub async fn get_recurring_series_members(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    series_id: String,
+) -> Result<Vec<String>, String> {
+    authorize_series(
+        &state.pool,
+        &auth,
+        &token,
+        &series_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_scalar(
+        "SELECT transaction_id FROM recurring_series_members WHERE series_id = ? ORDER BY occurred_at ASC",
+    )
+    .bind(&series_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Updates a series' label and auto-tag settings. Passing no category turns
+/// auto-tagging off. Requires the owner, admin, or preparer role on
+/// the series' profile.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn update_recurring_series(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    series_id: String,
+    label: Option<String>,
+    auto_tag_category: Option<String>,
+    auto_tag_entity_id: Option<String>,
+    is_active: Option<bool>,
+) -> Result<RecurringSeries, String> {
+    let (user_id, _) =
+        authorize_series(&state.pool, &auth, &token, &series_id, Permission::EditTags).await?;
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    let before = fetch_series(&mut tx, &series_id)
+        .await?
+        .ok_or_else(|| "Recurring series not found".to_string())?;
+
+    sqlx::query(
+        r#"
+        UPDATE recurring_series SET
+            label = ?, auto_tag_category = ?, auto_tag_entity_id = ?,
+            is_active = COALESCE(?, is_active), updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&label)
+    .bind(&auto_tag_category)
+    .bind(&auto_tag_entity_id)
+    .bind(is_active)
+    .bind(Utc::now())
+    .bind(&series_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let after = fetch_series(&mut tx, &series_id)
+        .await?
+        .ok_or_else(|| "Recurring series not found".to_string())?;
+    record_change(
+        &mut *tx,
+        Some(&user_id),
+        RecordType::RecurringSeries,
+        &series_id,
+        Some(&after.profile_id),
+        Some(&before),
+        Some(&after),
+    )
+    .await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(after)
+}
+
+/// Deletes a recurring series and its membership records. Requires the
+/// owner, admin, or preparer role on the series' profile.
+#[tauri::command]
+pub async fn delete_recurring_series(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    series_id: String,
+) -> Result<(), String> {
+    let (user_id, _) =
+        authorize_series(&state.pool, &auth, &token, &series_id, Permission::EditTags).await?;
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    let Some(before) = fetch_series(&mut tx, &series_id).await? else {
+        return Ok(());
+    };
+
+    sqlx::query("DELETE FROM recurring_series WHERE id = ?")
+        .bind(&series_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    record_change(
+        &mut *tx,
+        Some(&user_id),
+        RecordType::RecurringSeries,
+        &series_id,
+        Some(&before.profile_id),
+        Some(&before),
+        None,
+    )
+    .await?;
+
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+/// Matches transactions newer than each active series' last occurrence
+/// against the series and, for series with an auto-tag category, tags them.
+/// Transactions in a closed period join the series but are not tagged.
+///
+/// Intended to run after a wallet sync. Requires the owner, admin, or
+/// preparer role on the wallet's profile.
+#[tauri::command]
+pub async fn apply_recurring_auto_tags(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    wallet_id: String,
+) -> Result<AutoTagResult, String> {
+    let (user_id, wallet) =
+        authorize_wallet(&state.pool, &auth, &token, &wallet_id, Permission::EditTags).await?;
+    let series_list = sqlx::query_as::<_, RecurringSeries>(
+        "SELECT * FROM recurring_series WHERE wallet_id = ? AND is_active = 1",
+    )
+    .bind(&wallet_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let transactions = get_wallet_transactions(&state.pool, &wallet_id).await?;
+
+    let mut result = AutoTagResult {
+        matched: 0,
+        tagged: 0,
+    };
+
+    for series in series_list {
+        let mut last_seen = series.last_seen_at;
+        let mut added: i64 = 0;
+        let mut db_tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+
+        for tx in &transactions {
+            let Some(at) = tx.timestamp else {
+                continue;
+            };
+            if at <= series.last_seen_at {
+                continue;
+            }
+            let Some((direction, counterparty, symbol, amount)) =
+                classify_transaction(&wallet.address, tx)
+            else {
+                continue;
+            };
+            if direction != series.direction
+                || counterparty != series.counterparty
+                || symbol != series.token_symbol
+                || !series.amount_matches(amount)
+            {
+                continue;
+            }
+
+            let inserted = sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO recurring_series_members (series_id, transaction_id, occurred_at)
+                VALUES (?, ?, ?)
+                "#,
+            )
+            .bind(&series.id)
+            .bind(&tx.id)
+            .bind(at)
+            .execute(&mut *db_tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            if inserted.rows_affected() == 0 {
+                continue;
+            }
+            added += 1;
+            result.matched += 1;
+            last_seen = last_seen.max(at);
+
+            if let Some(category) = &series.auto_tag_category {
+                // Tags in a closed period would change its reports.
+                if ensure_period_open(&state.pool, Some(&series.profile_id), at.date_naive())
+                    .await
+                    .is_err()
+                {
+                    continue;
+                }
+                let tag = TransactionTag {
+                    id: Uuid::new_v4().to_string(),
+                    profile_id: series.profile_id.clone(),
+                    transaction_id: tx.id.clone(),
+                    category: category.clone(),
+                    entity_id: series.auto_tag_entity_id.clone(),
+                    amount: amount.to_string(),
+                    occurred_at: at,
+                    created_at: Some(Utc::now()),
+                };
+                let tagged = sqlx::query(
+                    r#"
+                    INSERT OR IGNORE INTO transaction_tags (
+                        id, profile_id, transaction_id, category, entity_id, amount, occurred_at, created_at
+                    )
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                    "#,
+                )
+                .bind(&tag.id)
+                .bind(&tag.profile_id)
+                .bind(&tag.transaction_id)
+                .bind(&tag.category)
+                .bind(&tag.entity_id)
+                .bind(&tag.amount)
+                .bind(tag.occurred_at)
+                .bind(tag.created_at)
+                .execute(&mut *db_tx)
+                .await
+                .map_err(|e| e.to_string())?;
+                if tagged.rows_affected() > 0 {
+                    result.tagged += 1;
+                    record_change(
+                        &mut *db_tx,
+                        Some(&user_id),
+                        RecordType::TransactionTag,
+                        &tag.id,
+                        Some(&tag.profile_id),
+                        None,
+                        Some(&tag),
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        if added > 0 {
+            let interval = Cadence::ALL
+                .into_iter()
+                .find(|c| c.as_str() == series.cadence)
+                .map(|c| c.days())
+                .unwrap_or(series.interval_days);
+            sqlx::query(
+                r#"
+                UPDATE recurring_series SET
+                    occurrence_count = occurrence_count + ?,
+                    last_seen_at = ?, next_expected_at = ?, updated_at = ?
+                WHERE id = ?
+                "#,
+            )
+            .bind(added)
+            .bind(last_seen)
+            .bind(last_seen + Duration::days(interval))
+            .bind(Utc::now())
+            .bind(&series.id)
+            .execute(&mut *db_tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+
+        db_tx.commit().await.map_err(|e| e.to_string())?;
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const WALLET: &str = "0xwallet";
+
+    fn tx(id: &str, from: &str, to: &str, value: &str, at: DateTime<Utc>) -> StoredTransaction {
+        StoredTransaction {
+            id: id.to_string(),
+            wallet_id: "w1".to_string(),
+            hash: id.to_string(),
+            block_number: None,
+            timestamp: Some(at),
+            from_address: Some(from.to_string()),
+            to_address: Some(to.to_string()),
+            value: Some(value.to_string()),
+            fee: None,
+            status: Some("success".to_string()),
+            tx_type: Some("transfer".to_string()),
+            token_symbol: Some("USDC".to_string()),
+            token_decimals: Some(6),
+            chain: "ethereum".to_string(),
+            raw_data: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn day(n: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap() + Duration::days(n)
+    }
+
+    #[test]
+    fn test_cadence_classify() {
+        assert_eq!(Cadence::classify(7.2), Some(Cadence::Weekly));
+        assert_eq!(Cadence::classify(13.0), Some(Cadence::Biweekly));
+        assert_eq!(Cadence::classify(28.0), Some(Cadence::Monthly));
+        assert_eq!(Cadence::classify(31.0), Some(Cadence::Monthly));
+        assert_eq!(Cadence::classify(92.0), Some(Cadence::Quarterly));
+        assert_eq!(Cadence::classify(366.0), Some(Cadence::Yearly));
+        assert_eq!(Cadence::classify(45.0), None);
+    }
+
+    #[test]
+    fn test_detects_monthly_payroll() {
+        let txs = vec![
+            tx("1", "0xEmployer", WALLET, "5000", day(0)),
+            tx("2", "0xemployer", WALLET, "5000", day(31)),
+            tx("3", "0xemployer", WALLET, "5100", day(59)),
+            tx("4", "0xemployer", WALLET, "4950", day(90)),
+            // One-off bonus from the same payer is excluded by amount
+            tx("5", "0xemployer", WALLET, "20000", day(95)),
+        ];
+        let series = detect_series(WALLET, &txs, &DetectionConfig::default());
+
+        assert_eq!(series.len(), 1);
+        let s = &series[0];
+        assert_eq!(s.cadence, Cadence::Monthly);
+        assert_eq!(s.direction, "incoming");
+        assert_eq!(s.counterparty, "0xemployer");
+        assert_eq!(s.occurrences.len(), 4);
+        assert!(s.occurrences.iter().all(|(id, _)| id != "5"));
+        assert_eq!(s.next_expected_at(), day(120));
+    }
+
+    #[test]
+    fn test_irregular_or_sparse_groups_are_ignored() {
+        let irregular = vec![
+            tx("1", WALLET, "0xshop", "10", day(0)),
+            tx("2", WALLET, "0xshop", "10", day(3)),
+            tx("3", WALLET, "0xshop", "10", day(40)),
+            tx("4", WALLET, "0xshop", "10", day(41)),
+        ];
+        assert!(detect_series(WALLET, &irregular, &DetectionConfig::default()).is_empty());
+
+        let sparse = vec![
+            tx("1", WALLET, "0xsaas", "20", day(0)),
+            tx("2", WALLET, "0xsaas", "20", day(30)),
+        ];
+        assert!(detect_series(WALLET, &sparse, &DetectionConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_amount_matches_tolerance() {
+        let series = RecurringSeries {
+            id: "s1".to_string(),
+            profile_id: "p1".to_string(),
+            wallet_id: "w1".to_string(),
+            counterparty: "0xsaas".to_string(),
+            direction: "outgoing".to_string(),
+            token_symbol: "USDC".to_string(),
+            cadence: "monthly".to_string(),
+            interval_days: 30,
+            typical_amount: "20".to_string(),
+            amount_tolerance: "2".to_string(),
+            occurrence_count: 3,
+            confidence: 1.0,
+            first_seen_at: day(0),
+            last_seen_at: day(60),
+            next_expected_at: day(90),
+            label: None,
+            auto_tag_category: Some("Software".to_string()),
+            auto_tag_entity_id: None,
+            is_active: true,
+            created_at: None,
+            updated_at: None,
+        };
+        assert!(series.amount_matches(Decimal::from(21)));
+        assert!(!series.amount_matches(Decimal::from(23)));
+    }
+}
@@ -0,0 +1,599 @@
+//! Attested report exports for grant compliance.
+//!
+//! Exports a profile's transactions and per-token totals for a period as a
+//! deterministic JSON document. Every transaction is hashed into both a
+//! Merkle tree and a hash chain, and the parameters, totals, Merkle root, and
+//! chain head are combined into a single attestation hash. Publishing that
+//! hash lets a third party later confirm the exported report is unaltered by
+//! recomputing it from the file.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::budgets::parse_period;
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction, Wallet};
+use super::profile_scope::authorize_profile;
+use super::statement_export::parse_amount;
+use super::token_spam::SpamFilter;
+use crate::core::attestation::{self, Hash};
+use crate::core::auth_state::AuthState;
+
+/// Version of the attestation format, part of the hashed parameters.
+const FORMAT_VERSION: &str = "pacioli-attestation-v1";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Parameters the report was generated with. Hashed, so two exports of the
+/// same data with the same parameters attest to the same hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationParameters {
+    /// Attestation format version.
+    pub format_version: String,
+    /// Profile the report covers.
+    pub profile_id: String,
+    /// Period as requested, e.g. `2025` or `2025-Q1`.
+    pub period: String,
+    /// First day of the period.
+    pub period_start: NaiveDate,
+    /// Last day of the period, inclusive.
+    pub period_end: NaiveDate,
+}
+
+/// A transaction as included in the report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestedTransaction {
+    /// Stored transaction ID.
+    pub id: String,
+    /// Wallet the transaction was recorded for.
+    pub wallet_id: String,
+    /// Chain the transaction is on.
+    pub chain: String,
+    /// Transaction hash.
+    pub hash: String,
+    /// Block number, when known.
+    pub block_number: Option<i64>,
+    /// When the transaction occurred.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Sender address.
+    pub from_address: Option<String>,
+    /// Recipient address.
+    pub to_address: Option<String>,
+    /// Value as stored.
+    pub value: Option<String>,
+    /// Fee as stored.
+    pub fee: Option<String>,
+    /// Token symbol.
+    pub token_symbol: Option<String>,
+    /// Token decimals.
+    pub token_decimals: Option<i32>,
+    /// Transaction status.
+    pub status: Option<String>,
+    /// Transaction type.
+    pub tx_type: Option<String>,
+}
+
+/// A transaction with its leaf hash and position in the hash chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestedRecord {
+    /// The transaction.
+    pub transaction: AttestedTransaction,
+    /// Merkle leaf hash of the transaction, hex.
+    pub leaf_hash: String,
+    /// Hash chain link after this transaction, hex.
+    pub chain_hash: String,
+}
+
+/// Totals for one token on one chain, from the point of view of the
+/// profile's wallets.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestedTotal {
+    /// Chain the token is on.
+    pub chain: String,
+    /// Token symbol.
+    pub token_symbol: String,
+    /// Number of transactions.
+    pub transaction_count: usize,
+    /// Amount received, as a decimal string.
+    pub inflow: String,
+    /// Amount sent, as a decimal string.
+    pub outflow: String,
+    /// Fees paid, as a decimal string.
+    pub fees: String,
+}
+
+/// The exported, attested report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestedReport {
+    /// When the file was generated. Informational only; not hashed.
+    pub generated_at: DateTime<Utc>,
+    /// Generation parameters.
+    pub parameters: AttestationParameters,
+    /// Per-token totals.
+    pub totals: Vec<AttestedTotal>,
+    /// Transactions in report order.
+    pub records: Vec<AttestedRecord>,
+    /// Hash of the parameters, hex. Also the first link of the hash chain.
+    pub parameters_hash: String,
+    /// Hash of the totals, hex.
+    pub totals_hash: String,
+    /// Merkle root over the transaction leaf hashes, hex.
+    pub merkle_root: String,
+    /// Last link of the hash chain, hex.
+    pub chain_head: String,
+    /// Hash committing to all of the above, hex.
+    pub attestation_hash: String,
+}
+
+/// Summary returned to the frontend after an export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationExportResult {
+    /// Path the report was written to.
+    pub path: String,
+    /// Number of transactions in the report.
+    pub transaction_count: usize,
+    /// Merkle root over the transactions, hex.
+    pub merkle_root: String,
+    /// Attestation hash to publish or share, hex.
+    pub attestation_hash: String,
+}
+
+/// Outcome of checking an attested report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationVerification {
+    /// Whether every hash in the report checks out, and matches the expected
+    /// attestation hash when one was given.
+    pub valid: bool,
+    /// Attestation hash recomputed from the report contents, hex.
+    pub computed_hash: String,
+    /// Problems found.
+    pub errors: Vec<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Serializes a value for hashing.
+fn to_json<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// Per-token totals over `transactions`, keyed by wallet address so
+/// transfers are signed from the wallet's side.
+pub fn compute_totals(
+    wallets: &[Wallet],
+    transactions: &[StoredTransaction],
+) -> Vec<AttestedTotal> {
+    #[derive(Default)]
+    struct Sums {
+        count: usize,
+        inflow: Decimal,
+        outflow: Decimal,
+        fees: Decimal,
+    }
+
+    let mut sums: BTreeMap<(String, String), Sums> = BTreeMap::new();
+    for tx in transactions {
+        let Some(wallet) = wallets.iter().find(|w| w.id == tx.wallet_id) else {
+            continue;
+        };
+        let symbol = tx.token_symbol.clone().unwrap_or_default();
+        let entry = sums.entry((tx.chain.clone(), symbol)).or_default();
+        entry.count += 1;
+
+        let outgoing = tx
+            .from_address
+            .as_deref()
+            .is_some_and(|a| a.eq_ignore_ascii_case(&wallet.address));
+        let incoming = tx
+            .to_address
+            .as_deref()
+            .is_some_and(|a| a.eq_ignore_ascii_case(&wallet.address));
+        let amount = tx
+            .value
+            .as_deref()
+            .and_then(|v| parse_amount(v, tx.token_decimals))
+            .unwrap_or(Decimal::ZERO);
+
+        if tx.status.as_deref() != Some("failed") && outgoing != incoming {
+            if outgoing {
+                entry.outflow += amount;
+            } else {
+                entry.inflow += amount;
+            }
+        }
+        if outgoing {
+            entry.fees += tx
+                .fee
+                .as_deref()
+                .and_then(|f| parse_amount(f, tx.token_decimals))
+                .unwrap_or(Decimal::ZERO);
+        }
+    }
+
+    sums.into_iter()
+        .map(|((chain, token_symbol), s)| AttestedTotal {
+            chain,
+            token_symbol,
+            transaction_count: s.count,
+            inflow: s.inflow.normalize().to_string(),
+            outflow: s.outflow.normalize().to_string(),
+            fees: s.fees.normalize().to_string(),
+        })
+        .collect()
+}
+
+/// Hash committing to the parameters, totals, Merkle root, and chain head.
+fn attestation_hash(
+    parameters_hash: &Hash,
+    totals_hash: &Hash,
+    merkle_root: &Hash,
+    chain_head: &Hash,
+) -> Hash {
+    attestation::hash_value(&serde_json::json!({
+        "formatVersion": FORMAT_VERSION,
+        "parametersHash": hex::encode(parameters_hash),
+        "totalsHash": hex::encode(totals_hash),
+        "merkleRoot": hex::encode(merkle_root),
+        "chainHead": hex::encode(chain_head),
+    }))
+}
+
+/// Builds the attested report. Transactions must already be in report
+/// order.
+pub fn build_report(
+    parameters: AttestationParameters,
+    totals: Vec<AttestedTotal>,
+    transactions: Vec<AttestedTransaction>,
+    generated_at: DateTime<Utc>,
+) -> AttestedReport {
+    let parameters_hash = attestation::hash_value(&to_json(&parameters));
+    let totals_hash = attestation::hash_value(&to_json(&totals));
+
+    let mut chain = parameters_hash;
+    let mut leaves = Vec::with_capacity(transactions.len());
+    let records = transactions
+        .into_iter()
+        .map(|transaction| {
+            let leaf = attestation::leaf_hash(&to_json(&transaction));
+            chain = attestation::chain_link(&chain, &leaf);
+            leaves.push(leaf);
+            AttestedRecord {
+                transaction,
+                leaf_hash: hex::encode(leaf),
+                chain_hash: hex::encode(chain),
+            }
+        })
+        .collect();
+    let merkle_root = attestation::merkle_root(&leaves);
+
+    AttestedReport {
+        generated_at,
+        parameters,
+        totals,
+        records,
+        parameters_hash: hex::encode(parameters_hash),
+        totals_hash: hex::encode(totals_hash),
+        merkle_root: hex::encode(merkle_root),
+        chain_head: hex::encode(chain),
+        attestation_hash: hex::encode(attestation_hash(
+            &parameters_hash,
+            &totals_hash,
+            &merkle_root,
+            &chain,
+        )),
+    }
+}
+
+/// Recomputes every hash in `report` from its contents and compares.
+pub fn verify_report(report: &AttestedReport, expected: Option<&str>) -> AttestationVerification {
+    let mut errors = Vec::new();
+    let mut check = |label: &str, stated: &str, computed: &Hash| {
+        if attestation::parse_hash(stated) != Some(*computed) {
+            errors.push(format!("{} does not match the report contents", label));
+        }
+    };
+
+    let parameters_hash = attestation::hash_value(&to_json(&report.parameters));
+    check("Parameters hash", &report.parameters_hash, &parameters_hash);
+    let totals_hash = attestation::hash_value(&to_json(&report.totals));
+    check("Totals hash", &report.totals_hash, &totals_hash);
+
+    let mut chain = parameters_hash;
+    let mut leaves = Vec::with_capacity(report.records.len());
+    for (index, record) in report.records.iter().enumerate() {
+        let leaf = attestation::leaf_hash(&to_json(&record.transaction));
+        chain = attestation::chain_link(&chain, &leaf);
+        check(
+            &format!("Leaf hash of record {}", index + 1),
+            &record.leaf_hash,
+            &leaf,
+        );
+        check(
+            &format!("Chain hash of record {}", index + 1),
+            &record.chain_hash,
+            &chain,
+        );
+        leaves.push(leaf);
+    }
+
+    let merkle_root = attestation::merkle_root(&leaves);
+    check("Merkle root", &report.merkle_root, &merkle_root);
+    check("Chain head", &report.chain_head, &chain);
+
+    let computed = attestation_hash(&parameters_hash, &totals_hash, &merkle_root, &chain);
+    check("Attestation hash", &report.attestation_hash, &computed);
+    if let Some(expected) = expected {
+        if attestation::parse_hash(expected) != Some(computed) {
+            errors.push("Report does not match the expected attestation hash".to_string());
+        }
+    }
+
+    AttestationVerification {
+        valid: errors.is_empty(),
+        computed_hash: hex::encode(computed),
+        errors,
+    }
+}
+
+impl From<StoredTransaction> for AttestedTransaction {
+    fn from(tx: StoredTransaction) -> Self {
+        Self {
+            id: tx.id,
+            wallet_id: tx.wallet_id,
+            chain: tx.chain,
+            hash: tx.hash,
+            block_number: tx.block_number,
+            timestamp: tx.timestamp,
+            from_address: tx.from_address,
+            to_address: tx.to_address,
+            value: tx.value,
+            fee: tx.fee,
+            token_symbol: tx.token_symbol,
+            token_decimals: tx.token_decimals,
+            status: tx.status,
+            tx_type: tx.tx_type,
+        }
+    }
+}
+
+/// Builds the attested report of a profile's transactions for a period.
+/// Tokens marked as spam are left out.
+pub(crate) async fn attested_report(
+    pool: &SqlitePool,
+    profile_id: &str,
+    period: &str,
+) -> Result<AttestedReport, String> {
+    let (period_start, period_end) = parse_period(period)?;
+    let start = period_start.and_time(NaiveTime::MIN).and_utc();
+    let end = period_end
+        .succ_opt()
+        .ok_or_else(|| format!("Invalid period: {}", period))?
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+
+    let wallets = sqlx::query_as::<_, Wallet>("SELECT * FROM wallets WHERE profile_id = ?")
+        .bind(profile_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let transactions = sqlx::query_as::<_, StoredTransaction>(
+        r#"
+        SELECT t.* FROM transactions t
+        INNER JOIN wallets w ON t.wallet_id = w.id
+        WHERE w.profile_id = ? AND t.timestamp >= ? AND t.timestamp < ?
+        ORDER BY t.timestamp ASC, t.hash ASC, t.id ASC
+        "#,
+    )
+    .bind(profile_id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let filter = SpamFilter::load(pool, Some(profile_id))
+        .await
+        .map_err(|e| e.to_string())?;
+    let transactions: Vec<StoredTransaction> = transactions
+        .into_iter()
+        .filter(|tx| {
+            let raw = tx
+                .raw_data
+                .as_deref()
+                .and_then(|raw| serde_json::from_str(raw).ok());
+            !filter.hides_transaction(&tx.chain, tx.token_symbol.as_deref(), raw.as_ref())
+        })
+        .collect();
+
+    let totals = compute_totals(&wallets, &transactions);
+    let parameters = AttestationParameters {
+        format_version: FORMAT_VERSION.to_string(),
+        profile_id: profile_id.to_string(),
+        period: period.trim().to_string(),
+        period_start,
+        period_end,
+    };
+    Ok(build_report(
+        parameters,
+        totals,
+        transactions.into_iter().map(Into::into).collect(),
+        Utc::now(),
+    ))
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Exports an attested report of a profile's transactions for a period and
+/// writes it to `path` as JSON. Tokens marked as spam are left out.
+/// Requires the export permission on the profile.
+///
+/// # Arguments
+/// * `token` - Access token of the caller.
+/// * `profile_id` - The profile to report on.
+/// * `period` - `YYYY`, `YYYY-Qn`, `YYYY-MM`, or `YYYY-MM-DD..YYYY-MM-DD`.
+/// * `path` - Destination file path.
+#[tauri::command]
+pub async fn export_report_attestation(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    period: String,
+    path: String,
+) -> Result<AttestationExportResult, String> {
+    authorize_profile(&state.pool, &auth, &token, &profile_id, Permission::Export).await?;
+    let report = attested_report(&state.pool, &profile_id, &period).await?;
+
+    let contents = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    std::fs::write(&path, contents).map_err(|e| e.to_string())?;
+
+    Ok(AttestationExportResult {
+        path,
+        transaction_count: report.records.len(),
+        merkle_root: report.merkle_root,
+        attestation_hash: report.attestation_hash,
+    })
+}
+
+/// Verifies an attested report file, optionally against a previously
+/// published attestation hash.
+#[tauri::command]
+pub async fn verify_report_attestation(
+    path: String,
+    expected_hash: Option<String>,
+) -> Result<AttestationVerification, String> {
+    let contents = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let report: AttestedReport =
+        serde_json::from_str(&contents).map_err(|e| format!("Invalid attested report: {}", e))?;
+
+    Ok(verify_report(&report, expected_hash.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const WALLET: &str = "0xAbC0000000000000000000000000000000000001";
+
+    fn wallet() -> Wallet {
+        Wallet {
+            id: "w1".to_string(),
+            profile_id: "p1".to_string(),
+            address: WALLET.to_string(),
+            chain: "ethereum".to_string(),
+            name: None,
+            wallet_type: "external".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+        }
+    }
+
+    fn tx(hash: &str, from: &str, to: &str, value: &str, fee: Option<&str>) -> StoredTransaction {
+        StoredTransaction {
+            id: hash.to_string(),
+            wallet_id: "w1".to_string(),
+            hash: hash.to_string(),
+            block_number: Some(1),
+            timestamp: Some(Utc.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap()),
+            from_address: Some(from.to_string()),
+            to_address: Some(to.to_string()),
+            value: Some(value.to_string()),
+            fee: fee.map(String::from),
+            status: Some("success".to_string()),
+            tx_type: Some("transfer".to_string()),
+            token_symbol: Some("ETH".to_string()),
+            token_decimals: Some(18),
+            chain: "ethereum".to_string(),
+            raw_data: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn report() -> AttestedReport {
+        let transactions = vec![
+            tx("0x1", "0xother", WALLET, "2.5", None),
+            tx("0x2", WALLET, "0xother", "1", Some("0.01")),
+        ];
+        let parameters = AttestationParameters {
+            format_version: FORMAT_VERSION.to_string(),
+            profile_id: "p1".to_string(),
+            period: "2025-Q1".to_string(),
+            period_start: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            period_end: NaiveDate::from_ymd_opt(2025, 3, 31).unwrap(),
+        };
+        let totals = compute_totals(&[wallet()], &transactions);
+        build_report(
+            parameters,
+            totals,
+            transactions.into_iter().map(Into::into).collect(),
+            Utc::now(),
+        )
+    }
+
+    #[test]
+    fn test_totals() {
+        let totals = report().totals;
+        assert_eq!(totals.len(), 1);
+        assert_eq!(totals[0].transaction_count, 2);
+        assert_eq!(totals[0].inflow, "2.5");
+        assert_eq!(totals[0].outflow, "1");
+        assert_eq!(totals[0].fees, "0.01");
+    }
+
+    #[test]
+    fn test_report_is_deterministic_and_verifies() {
+        let first = report();
+        let second = report();
+        assert_eq!(first.attestation_hash, second.attestation_hash);
+
+        let verification = verify_report(&first, Some(&first.attestation_hash));
+        assert!(verification.valid, "{:?}", verification.errors);
+        assert_eq!(verification.computed_hash, first.attestation_hash);
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let original = report();
+
+        let mut altered = original.clone();
+        altered.records[1].transaction.value = Some("10".to_string());
+        let verification = verify_report(&altered, None);
+        assert!(!verification.valid);
+        assert!(verification.errors[0].contains("record 2"));
+
+        // Rehashing everything still changes the attestation hash.
+        let rebuilt = build_report(
+            altered.parameters.clone(),
+            altered.totals.clone(),
+            altered
+                .records
+                .iter()
+                .map(|r| r.transaction.clone())
+                .collect(),
+            Utc::now(),
+        );
+        assert!(verify_report(&rebuilt, None).valid);
+        assert!(!verify_report(&rebuilt, Some(&original.attestation_hash)).valid);
+    }
+}
@@ -0,0 +1,815 @@
+//! Scheduled reports.
+//!
+//! A schedule generates a report for each completed month or quarter: an
+//! entity statement as PDF or CSV, or the payee report for the year to date
+//! as CSV. A background task generates each report once its period has
+//! ended, emails it to the schedule's recipients through the email service,
+//! and keeps the file in the report history so it can be downloaded again.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, SqlitePool};
+use tauri::{AppHandle, Manager, State};
+use uuid::Uuid;
+
+use super::entity_statements::{
+    build_payee_report, build_statement, payee_report_csv, render_statement_pdf, statement_csv,
+    EntityReportFormat,
+};
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::authorize_profile;
+use crate::core::auth_helpers::validate_email;
+use crate::core::auth_state::AuthState;
+use crate::core::email::{self, EmailAttachment};
+use crate::jobs::queue::{JobQueueState, JobTask};
+use crate::jobs::JobPriority;
+use crate::log_error;
+
+/// Report type for an entity statement.
+const ENTITY_STATEMENT: &str = "entity_statement";
+
+/// Report type for the year-to-date payee report.
+const PAYEE_REPORT: &str = "payee_report";
+
+/// Hour (UTC) on the first day of a period at which the last period's
+/// report is generated, leaving wallets time to sync its final day.
+const REPORT_RUN_HOUR: u32 = 6;
+
+/// Interval between checks for due reports.
+const REPORT_POLL_INTERVAL: Duration = Duration::from_secs(900);
+
+/// Delay before the first check, so startup is not slowed down.
+const REPORT_INITIAL_DELAY: Duration = Duration::from_secs(60);
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// How often a scheduled report is generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFrequency {
+    /// After each calendar month.
+    Monthly,
+    /// After each calendar quarter.
+    Quarterly,
+}
+
+impl ReportFrequency {
+    fn months(self) -> u32 {
+        match self {
+            Self::Monthly => 1,
+            Self::Quarterly => 3,
+        }
+    }
+}
+
+impl FromStr for ReportFrequency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "monthly" => Ok(Self::Monthly),
+            "quarterly" => Ok(Self::Quarterly),
+            _ => Err(format!("Unsupported report frequency: {}", s)),
+        }
+    }
+}
+
+/// A stored report schedule.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportSchedule {
+    /// Unique identifier.
+    pub id: String,
+    /// Profile the reports cover.
+    pub profile_id: String,
+    /// Display name, used as the email subject.
+    pub name: String,
+    /// One of: entity_statement, payee_report.
+    pub report_type: String,
+    /// One of: csv, pdf.
+    pub format: String,
+    /// One of: monthly, quarterly.
+    pub frequency: String,
+    /// Entity an entity statement covers.
+    pub entity_id: Option<String>,
+    /// Asset symbols mapped to price feed coin IDs.
+    pub coin_ids: Json<HashMap<String, String>>,
+    /// Email addresses the reports are sent to.
+    pub recipients: Json<Vec<String>>,
+    /// Whether reports are generated.
+    pub is_active: bool,
+    /// When the next report is due.
+    pub next_run_at: DateTime<Utc>,
+    /// When a report was last generated.
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// User who created the schedule.
+    pub created_by: Option<String>,
+    /// Timestamp when the schedule was created.
+    pub created_at: DateTime<Utc>,
+    /// Timestamp when the schedule was last updated.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Input for creating or updating a schedule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportScheduleInput {
+    /// Schedule to update; a new one is created when unset.
+    pub id: Option<String>,
+    /// Profile the reports cover.
+    pub profile_id: String,
+    /// Display name.
+    pub name: String,
+    /// One of: entity_statement, payee_report.
+    pub report_type: String,
+    /// One of: csv, pdf. Payee reports are CSV only.
+    pub format: String,
+    /// One of: monthly, quarterly.
+    pub frequency: String,
+    /// Entity to report on; required for entity statements.
+    pub entity_id: Option<String>,
+    /// Asset symbols mapped to price feed coin IDs.
+    #[serde(default)]
+    pub coin_ids: HashMap<String, String>,
+    /// Email addresses to send the reports to.
+    #[serde(default)]
+    pub recipients: Vec<String>,
+    /// Whether reports are generated. Defaults to true.
+    pub is_active: Option<bool>,
+}
+
+/// A report generated for a schedule. The file itself is fetched with
+/// `download_generated_report`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedReport {
+    /// Unique identifier.
+    pub id: String,
+    /// Profile the report covers.
+    pub profile_id: String,
+    /// Schedule that generated it; unset once the schedule is deleted.
+    pub schedule_id: Option<String>,
+    /// Schedule name at the time.
+    pub name: String,
+    /// One of: entity_statement, payee_report.
+    pub report_type: String,
+    /// One of: csv, pdf.
+    pub format: String,
+    /// First day covered.
+    pub period_start: NaiveDate,
+    /// Last day covered.
+    pub period_end: NaiveDate,
+    /// File name the report is sent and downloaded as.
+    pub file_name: String,
+    /// Size of the file in bytes.
+    pub size_bytes: i64,
+    /// Email addresses it was sent to.
+    pub recipients: Json<Vec<String>>,
+    /// One of: sent, generated (no recipients), failed.
+    pub status: String,
+    /// Why generation or delivery failed.
+    pub error: Option<String>,
+    /// When the report was generated.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Columns of `generated_reports` other than the file contents.
+const GENERATED_REPORT_COLUMNS: &str = "id, profile_id, schedule_id, name, report_type, format, \
+     period_start, period_end, file_name, size_bytes, recipients, status, error, created_at";
+
+// ============================================================================
+// Periods
+// ============================================================================
+
+/// First day of the month or quarter containing `date`.
+fn period_start(frequency: ReportFrequency, date: NaiveDate) -> NaiveDate {
+    let month = match frequency {
+        ReportFrequency::Monthly => date.month(),
+        ReportFrequency::Quarterly => (date.month() - 1) / 3 * 3 + 1,
+    };
+    NaiveDate::from_ymd_opt(date.year(), month, 1).unwrap_or(date)
+}
+
+/// The last month or quarter to end before `date`, as its first and last
+/// days.
+pub fn completed_period(frequency: ReportFrequency, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let current = period_start(frequency, date);
+    let start = current
+        .checked_sub_months(Months::new(frequency.months()))
+        .unwrap_or(current);
+    (start, current.pred_opt().unwrap_or(current))
+}
+
+/// When the next report is due after `now`: the first day of a period, at
+/// [`REPORT_RUN_HOUR`].
+pub fn next_run_at(frequency: ReportFrequency, now: DateTime<Utc>) -> DateTime<Utc> {
+    let start = period_start(frequency, now.date_naive());
+    let run_at = |date: NaiveDate| {
+        date.and_hms_opt(REPORT_RUN_HOUR, 0, 0)
+            .unwrap_or_default()
+            .and_utc()
+    };
+    if run_at(start) > now {
+        return run_at(start);
+    }
+    run_at(
+        start
+            .checked_add_months(Months::new(frequency.months()))
+            .unwrap_or(start),
+    )
+}
+
+/// Label for the period starting `start`, e.g. `2026-03` or `2026-Q1`.
+pub fn period_label(frequency: ReportFrequency, start: NaiveDate) -> String {
+    match frequency {
+        ReportFrequency::Monthly => start.format("%Y-%m").to_string(),
+        ReportFrequency::Quarterly => format!("{}-Q{}", start.year(), (start.month() - 1) / 3 + 1),
+    }
+}
+
+// ============================================================================
+// Generation
+// ============================================================================
+
+/// Checks a schedule's settings, returning its frequency.
+fn validate_input(input: &ReportScheduleInput) -> Result<ReportFrequency, String> {
+    if input.name.trim().is_empty() {
+        return Err("A schedule name is required".to_string());
+    }
+    let format: EntityReportFormat = input.format.parse()?;
+    match input.report_type.as_str() {
+        ENTITY_STATEMENT => {
+            if input.entity_id.as_deref().unwrap_or_default().is_empty() {
+                return Err("An entity statement needs an entity".to_string());
+            }
+        }
+        PAYEE_REPORT => {
+            if format != EntityReportFormat::Csv {
+                return Err("Payee reports are only available as CSV".to_string());
+            }
+        }
+        other => return Err(format!("Unsupported report type: {}", other)),
+    }
+    for recipient in &input.recipients {
+        validate_email(recipient).map_err(|e| format!("{}: {}", recipient, e))?;
+    }
+    input.frequency.parse()
+}
+
+async fn organization_name(pool: &SqlitePool, profile_id: &str) -> Result<String, String> {
+    let name: Option<String> = sqlx::query_scalar("SELECT name FROM profiles WHERE id = ?")
+        .bind(profile_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(name.unwrap_or_default())
+}
+
+/// Renders a schedule's report for the period from `start` to `end`.
+async fn render_report(
+    pool: &SqlitePool,
+    schedule: &ReportSchedule,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<u8>, String> {
+    let format: EntityReportFormat = schedule.format.parse()?;
+    match schedule.report_type.as_str() {
+        ENTITY_STATEMENT => {
+            let entity_id = schedule
+                .entity_id
+                .as_deref()
+                .ok_or_else(|| "The schedule's entity was removed".to_string())?;
+            let statement = build_statement(
+                pool,
+                &schedule.profile_id,
+                entity_id,
+                &start.to_string(),
+                &end.to_string(),
+                schedule.coin_ids.0.clone(),
+            )
+            .await?;
+            match format {
+                EntityReportFormat::Csv => statement_csv(&statement),
+                EntityReportFormat::Pdf => {
+                    let organization = organization_name(pool, &schedule.profile_id).await?;
+                    Ok(render_statement_pdf(&statement, &organization))
+                }
+            }
+        }
+        PAYEE_REPORT => {
+            let report = build_payee_report(
+                pool,
+                &schedule.profile_id,
+                end.year(),
+                None,
+                schedule.coin_ids.0.clone(),
+            )
+            .await?;
+            payee_report_csv(&report)
+        }
+        other => Err(format!("Unsupported report type: {}", other)),
+    }
+}
+
+fn content_type(format: &str) -> &'static str {
+    match format {
+        "pdf" => "application/pdf",
+        _ => "text/csv",
+    }
+}
+
+/// Generates a schedule's report for `period`, emails it to the schedule's
+/// recipients, and stores it in the report history. Failures to render or
+/// send are recorded on the stored report rather than returned.
+pub(crate) async fn generate_report(
+    pool: &SqlitePool,
+    schedule: &ReportSchedule,
+    frequency: ReportFrequency,
+    period: (NaiveDate, NaiveDate),
+) -> Result<GeneratedReport, String> {
+    let (start, end) = period;
+    let label = period_label(frequency, start);
+    let file_name = format!(
+        "{}-{}.{}",
+        schedule.report_type.replace('_', "-"),
+        label,
+        schedule.format
+    );
+    let recipients = &schedule.recipients.0;
+
+    let mut errors = Vec::new();
+    let content = match render_report(pool, schedule, start, end).await {
+        Ok(content) => content,
+        Err(e) => {
+            errors.push(e);
+            Vec::new()
+        }
+    };
+    if errors.is_empty() && !recipients.is_empty() {
+        let organization = organization_name(pool, &schedule.profile_id).await?;
+        for to in recipients {
+            let attachment = EmailAttachment {
+                filename: file_name.clone(),
+                content_type: content_type(&schedule.format).to_string(),
+                content: content.clone(),
+            };
+            if let Err(e) =
+                email::send_scheduled_report(to, &schedule.name, &organization, &label, attachment)
+                    .await
+            {
+                errors.push(format!("{}: {}", to, e));
+            }
+        }
+    }
+    let status = if !errors.is_empty() {
+        "failed"
+    } else if recipients.is_empty() {
+        "generated"
+    } else {
+        "sent"
+    };
+
+    let id = Uuid::new_v4().to_string();
+    sqlx::query(
+        r#"
+        INSERT INTO generated_reports (
+            id, profile_id, schedule_id, name, report_type, format, period_start, period_end,
+            file_name, content, size_bytes, recipients, status, error, created_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(&schedule.profile_id)
+    .bind(&schedule.id)
+    .bind(&schedule.name)
+    .bind(&schedule.report_type)
+    .bind(&schedule.format)
+    .bind(start)
+    .bind(end)
+    .bind(&file_name)
+    .bind(&content)
+    .bind(content.len() as i64)
+    .bind(Json(recipients))
+    .bind(status)
+    .bind((!errors.is_empty()).then(|| errors.join("; ")))
+    .bind(Utc::now())
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    load_generated_report(pool, &schedule.profile_id, &id).await
+}
+
+/// Generates every active schedule's report that is due, for the period
+/// that most recently ended, and moves each schedule on to its next period.
+/// Returns the number of reports generated.
+pub async fn run_due_reports(pool: &SqlitePool) -> Result<usize, String> {
+    let now = Utc::now();
+    let due = sqlx::query_as::<_, ReportSchedule>(
+        "SELECT * FROM report_schedules WHERE is_active = 1 AND next_run_at <= ? ORDER BY next_run_at",
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for schedule in &due {
+        let frequency: ReportFrequency = schedule.frequency.parse()?;
+        let period = completed_period(frequency, now.date_naive());
+        let report = generate_report(pool, schedule, frequency, period).await?;
+        if let Some(error) = &report.error {
+            log_error!("Scheduled report {} failed: {}", schedule.id, error);
+        }
+
+        sqlx::query(
+            "UPDATE report_schedules SET last_run_at = ?, next_run_at = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(now)
+        .bind(next_run_at(frequency, now))
+        .bind(now)
+        .bind(&schedule.id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(due.len())
+}
+
+/// Starts the background task that periodically queues a run of the
+/// reports that are due.
+pub fn spawn_report_loop(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(REPORT_INITIAL_DELAY).await;
+        loop {
+            let queue = app.state::<JobQueueState>().inner().clone();
+            if let Err(e) = queue
+                .enqueue(JobTask::ScheduledReports, JobPriority::Background)
+                .await
+            {
+                log_error!("Failed to queue scheduled reports: {}", e);
+            }
+            tokio::time::sleep(REPORT_POLL_INTERVAL).await;
+        }
+    });
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+async fn load_schedule(
+    pool: &SqlitePool,
+    profile_id: &str,
+    id: &str,
+) -> Result<ReportSchedule, String> {
+    sqlx::query_as::<_, ReportSchedule>(
+        "SELECT * FROM report_schedules WHERE profile_id = ? AND id = ?",
+    )
+    .bind(profile_id)
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Report schedule not found: {}", id))
+}
+
+async fn load_generated_report(
+    pool: &SqlitePool,
+    profile_id: &str,
+    id: &str,
+) -> Result<GeneratedReport, String> {
+    sqlx::query_as::<_, GeneratedReport>(&format!(
+        "SELECT {} FROM generated_reports WHERE profile_id = ? AND id = ?",
+        GENERATED_REPORT_COLUMNS
+    ))
+    .bind(profile_id)
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Generated report not found: {}", id))
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns a profile's report schedules.
+#[tauri::command]
+pub async fn get_report_schedules(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<ReportSchedule>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as::<_, ReportSchedule>(
+        "SELECT * FROM report_schedules WHERE profile_id = ? ORDER BY name",
+    )
+    .bind(&profile_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Creates a report schedule, or updates the one with `input.id`. The first
+/// report is due once the current period ends; changing a schedule's
+/// frequency moves its next report to the end of the new period.
+#[tauri::command]
+pub async fn save_report_schedule(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    input: ReportScheduleInput,
+) -> Result<ReportSchedule, String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &input.profile_id,
+        Permission::Export,
+    )
+    .await?;
+    let frequency = validate_input(&input)?;
+    let entity_id = match input.report_type.as_str() {
+        ENTITY_STATEMENT => input.entity_id.clone(),
+        _ => None,
+    };
+    if let Some(entity_id) = &entity_id {
+        let found: Option<String> =
+            sqlx::query_scalar("SELECT id FROM entities WHERE profile_id = ? AND id = ?")
+                .bind(&input.profile_id)
+                .bind(entity_id)
+                .fetch_optional(&state.pool)
+                .await
+                .map_err(|e| e.to_string())?;
+        found.ok_or_else(|| format!("Entity not found: {}", entity_id))?;
+    }
+
+    let now = Utc::now();
+    let next_run = next_run_at(frequency, now);
+    let frequency_name = input.frequency.to_lowercase();
+    let id = match &input.id {
+        Some(id) => {
+            let result = sqlx::query(
+                r#"
+                UPDATE report_schedules
+                SET name = ?, report_type = ?, format = ?, entity_id = ?, coin_ids = ?,
+                    recipients = ?, is_active = ?,
+                    next_run_at = CASE WHEN frequency = ? THEN next_run_at ELSE ? END,
+                    frequency = ?, updated_at = ?
+                WHERE profile_id = ? AND id = ?
+                "#,
+            )
+            .bind(input.name.trim())
+            .bind(&input.report_type)
+            .bind(input.format.to_lowercase())
+            .bind(&entity_id)
+            .bind(Json(&input.coin_ids))
+            .bind(Json(&input.recipients))
+            .bind(input.is_active.unwrap_or(true))
+            .bind(&frequency_name)
+            .bind(next_run)
+            .bind(&frequency_name)
+            .bind(now)
+            .bind(&input.profile_id)
+            .bind(id)
+            .execute(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+            if result.rows_affected() == 0 {
+                return Err(format!("Report schedule not found: {}", id));
+            }
+            id.clone()
+        }
+        None => {
+            let id = Uuid::new_v4().to_string();
+            sqlx::query(
+                r#"
+                INSERT INTO report_schedules (
+                    id, profile_id, name, report_type, format, frequency, entity_id, coin_ids,
+                    recipients, is_active, next_run_at, created_by, created_at, updated_at
+                )
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&id)
+            .bind(&input.profile_id)
+            .bind(input.name.trim())
+            .bind(&input.report_type)
+            .bind(input.format.to_lowercase())
+            .bind(&frequency_name)
+            .bind(&entity_id)
+            .bind(Json(&input.coin_ids))
+            .bind(Json(&input.recipients))
+            .bind(input.is_active.unwrap_or(true))
+            .bind(next_run)
+            .bind(&user_id)
+            .bind(now)
+            .bind(now)
+            .execute(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+            id
+        }
+    };
+
+    load_schedule(&state.pool, &input.profile_id, &id).await
+}
+
+/// Deletes a report schedule. Reports it generated stay in the history.
+#[tauri::command]
+pub async fn delete_report_schedule(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    id: String,
+) -> Result<(), String> {
+    authorize_profile(&state.pool, &auth, &token, &profile_id, Permission::Export).await?;
+    let result = sqlx::query("DELETE FROM report_schedules WHERE profile_id = ? AND id = ?")
+        .bind(&profile_id)
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if result.rows_affected() == 0 {
+        return Err(format!("Report schedule not found: {}", id));
+    }
+    Ok(())
+}
+
+/// Generates and sends a schedule's report for the period that most
+/// recently ended, without waiting for it to come due. The schedule's next
+/// report is unaffected.
+#[tauri::command]
+pub async fn run_report_schedule(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    id: String,
+) -> Result<GeneratedReport, String> {
+    authorize_profile(&state.pool, &auth, &token, &profile_id, Permission::Export).await?;
+    let schedule = load_schedule(&state.pool, &profile_id, &id).await?;
+    let frequency: ReportFrequency = schedule.frequency.parse()?;
+    let period = completed_period(frequency, Utc::now().date_naive());
+    generate_report(&state.pool, &schedule, frequency, period).await
+}
+
+/// Returns a profile's generated reports, newest first, optionally only
+/// those of one schedule.
+#[tauri::command]
+pub async fn get_generated_reports(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    schedule_id: Option<String>,
+) -> Result<Vec<GeneratedReport>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as::<_, GeneratedReport>(&format!(
+        r#"
+        SELECT {} FROM generated_reports
+        WHERE profile_id = ? AND (? IS NULL OR schedule_id = ?)
+        ORDER BY created_at DESC
+        "#,
+        GENERATED_REPORT_COLUMNS
+    ))
+    .bind(&profile_id)
+    .bind(&schedule_id)
+    .bind(&schedule_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Writes a generated report's file to `path`.
+#[tauri::command]
+pub async fn download_generated_report(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    id: String,
+    path: String,
+) -> Result<(), String> {
+    authorize_profile(&state.pool, &auth, &token, &profile_id, Permission::Export).await?;
+    let content: Option<Vec<u8>> = sqlx::query_scalar(
+        "SELECT content FROM generated_reports WHERE profile_id = ? AND id = ? AND status != 'failed'",
+    )
+    .bind(&profile_id)
+    .bind(&id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let content = content.ok_or_else(|| format!("No report file for: {}", id))?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_periods() {
+        use ReportFrequency::*;
+
+        assert_eq!(
+            completed_period(Monthly, date(2026, 3, 1)),
+            (date(2026, 2, 1), date(2026, 2, 28))
+        );
+        assert_eq!(
+            completed_period(Quarterly, date(2026, 2, 14)),
+            (date(2025, 10, 1), date(2025, 12, 31))
+        );
+        assert_eq!(period_label(Monthly, date(2026, 2, 1)), "2026-02");
+        assert_eq!(period_label(Quarterly, date(2025, 10, 1)), "2025-Q4");
+
+        // Due on the first day of the next period, unless today's run hasn't
+        // passed yet
+        let early = Utc.with_ymd_and_hms(2026, 4, 1, 3, 0, 0).unwrap();
+        let later = Utc.with_ymd_and_hms(2026, 4, 1, 9, 0, 0).unwrap();
+        assert_eq!(
+            next_run_at(Monthly, early),
+            Utc.with_ymd_and_hms(2026, 4, 1, 6, 0, 0).unwrap()
+        );
+        assert_eq!(
+            next_run_at(Monthly, later),
+            Utc.with_ymd_and_hms(2026, 5, 1, 6, 0, 0).unwrap()
+        );
+        assert_eq!(
+            next_run_at(Quarterly, later),
+            Utc.with_ymd_and_hms(2026, 7, 1, 6, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_validate_input() {
+        let input = ReportScheduleInput {
+            id: None,
+            profile_id: "p1".to_string(),
+            name: "Grantee statement".to_string(),
+            report_type: ENTITY_STATEMENT.to_string(),
+            format: "pdf".to_string(),
+            frequency: "quarterly".to_string(),
+            entity_id: Some("e1".to_string()),
+            coin_ids: HashMap::new(),
+            recipients: vec!["board@example.org".to_string()],
+            is_active: None,
+        };
+        assert_eq!(validate_input(&input), Ok(ReportFrequency::Quarterly));
+
+        let no_entity = ReportScheduleInput {
+            entity_id: None,
+            ..input.clone()
+        };
+        assert!(validate_input(&no_entity).is_err());
+
+        let payee_pdf = ReportScheduleInput {
+            report_type: PAYEE_REPORT.to_string(),
+            ..input.clone()
+        };
+        assert!(validate_input(&payee_pdf).is_err());
+
+        let bad_recipient = ReportScheduleInput {
+            recipients: vec!["board".to_string()],
+            ..input.clone()
+        };
+        assert!(validate_input(&bad_recipient).is_err());
+
+        let weekly = ReportScheduleInput {
+            frequency: "weekly".to_string(),
+            ..input
+        };
+        assert!(validate_input(&weekly).is_err());
+    }
+}
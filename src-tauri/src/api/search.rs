@@ -0,0 +1,195 @@
+//! Full-text search across transactions, entities, addresses, tags, and notes.
+//!
+//! Backed by the `search_index` FTS5 table. Triggers on each source table keep
+//! `search_documents` (and through it the index) up to date as records change,
+//! so no explicit reindexing is needed.
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use tauri::State;
+
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::authorize_profile;
+use crate::core::auth_state::AuthState;
+
+/// Default number of results returned by [`search_everything`].
+const DEFAULT_LIMIT: i64 = 50;
+
+/// Upper bound on the number of results per query.
+const MAX_LIMIT: i64 = 500;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// The kind of record a search result refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum SearchResultKind {
+    /// A profile transaction (`transactions`).
+    Transaction,
+    /// A synced multi-chain transaction (`multi_chain_transactions`).
+    ChainTransaction,
+    /// An entity (vendor, customer, donor).
+    Entity,
+    /// An address belonging to an entity; `parent_id` is the entity.
+    EntityAddress,
+    /// A budget tag on a transaction; `parent_id` is the transaction.
+    Tag,
+    /// A budget.
+    Budget,
+    /// A donation receipt.
+    DonationReceipt,
+}
+
+/// A single search hit.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    /// Kind of record.
+    pub kind: SearchResultKind,
+    /// ID of the matching record.
+    pub record_id: String,
+    /// Record to open for this result, when it differs from `record_id`.
+    pub parent_id: Option<String>,
+    /// Owning profile, if the record is profile-scoped.
+    pub profile_id: Option<String>,
+    /// Primary text (hash, name, category).
+    pub title: String,
+    /// Excerpt of the matching text with matches wrapped in `[` and `]`.
+    pub snippet: String,
+    /// Relevance score; lower is more relevant.
+    pub rank: f64,
+}
+
+// ============================================================================
+// Query Building
+// ============================================================================
+
+/// Converts free-form user input into an FTS5 query.
+///
+/// Each whitespace-separated term becomes a quoted prefix query and all terms
+/// must match, so partial hashes and addresses work and FTS5 operators in the
+/// input are treated as plain text. Returns `None` when there is nothing to
+/// search for.
+pub fn build_fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| term.chars().any(|c| c.is_alphanumeric()))
+        .map(|term| format!("\"{}\"*", term))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" AND "))
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Searches the indexed records of a profile plus records that are not
+/// profile-scoped. Requires any role on the profile.
+#[tauri::command]
+pub async fn search_everything(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    query: String,
+    profile_id: String,
+    kinds: Option<Vec<SearchResultKind>>,
+    limit: Option<i64>,
+) -> Result<Vec<SearchResult>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let Some(fts_query) = build_fts_query(&query) else {
+        return Ok(Vec::new());
+    };
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let kinds = kinds
+        .filter(|k| !k.is_empty())
+        .map(|k| serde_json::to_string(&k))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+
+    let results: Vec<SearchResult> = sqlx::query_as(
+        r#"
+        SELECT d.kind, d.record_id, d.parent_id, d.profile_id, d.title,
+               snippet(search_index, -1, '[', ']', '...', 12) AS snippet,
+               bm25(search_index, 4.0, 1.0) AS rank
+        FROM search_index
+        JOIN search_documents d ON d.id = search_index.rowid
+        WHERE search_index MATCH ?
+          AND (d.profile_id IS NULL OR d.profile_id = ?)
+          AND (? IS NULL OR d.kind IN (SELECT value FROM json_each(?)))
+        ORDER BY rank
+        LIMIT ?
+        "#,
+    )
+    .bind(&fts_query)
+    .bind(&profile_id)
+    .bind(&kinds)
+    .bind(&kinds)
+    .bind(limit)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(results)
+}
+
+/// Rebuilds the full-text index from `search_documents`.
+///
+/// Only needed to recover from corruption; the index is otherwise kept up to
+/// date by triggers.
+#[tauri::command]
+pub async fn rebuild_search_index(state: State<'_, DatabaseState>) -> Result<(), String> {
+    sqlx::query("INSERT INTO search_index(search_index) VALUES ('rebuild')")
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_fts_query() {
+        assert_eq!(build_fts_query("0xabc"), Some("\"0xabc\"*".to_string()));
+        assert_eq!(
+            build_fts_query("  acme   payroll "),
+            Some("\"acme\"* AND \"payroll\"*".to_string())
+        );
+    }
+
+    #[test]
+    fn test_build_fts_query_neutralizes_operators() {
+        assert_eq!(
+            build_fts_query("\"grant\" OR -"),
+            Some("\"grant\"* AND \"OR\"*".to_string())
+        );
+        assert_eq!(build_fts_query("  "), None);
+        assert_eq!(build_fts_query("* - \""), None);
+    }
+
+    #[test]
+    fn test_kind_serialization() {
+        assert_eq!(
+            serde_json::to_string(&SearchResultKind::DonationReceipt).unwrap(),
+            "\"donation_receipt\""
+        );
+    }
+}
@@ -0,0 +1,552 @@
+//! OFX and QIF statement export for wallet transaction history.
+//!
+//! Renders a single wallet's transactions for a period as a bank-style
+//! statement so that legacy personal-finance and reconciliation tools
+//! (Quicken, GnuCash, Moneydance, bank rec software) can ingest them
+//! without a custom CSV mapping. A statement is kept in one asset, the
+//! chain's native currency unless another is asked for, so its amounts
+//! and balance share a unit.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use super::address_watch::native_currency;
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction, Wallet};
+use super::profile_scope::authorize_wallet;
+use super::token_spam::SpamFilter;
+use crate::core::auth_state::AuthState;
+
+pub use pacioli_core::services::statement_export::*;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Supported statement formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    /// Open Financial Exchange 1.02 (SGML), accepted by most desktop finance tools.
+    Ofx,
+    /// Quicken Interchange Format, bank account register.
+    Qif,
+}
+
+impl FromStr for StatementFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ofx" => Ok(Self::Ofx),
+            "qif" => Ok(Self::Qif),
+            _ => Err(format!("Unsupported statement format: {}", s)),
+        }
+    }
+}
+
+/// The asset a statement is kept in, and the chain's native currency that
+/// fees are paid in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementAsset {
+    /// Symbol of the statement's asset.
+    pub symbol: String,
+    /// Symbol of the chain's native currency.
+    pub native_symbol: String,
+    /// Decimals of the chain's native currency.
+    pub native_decimals: i32,
+}
+
+impl StatementAsset {
+    /// A statement in `chain`'s native currency, or in `symbol` if given.
+    pub fn for_chain(chain: &str, symbol: Option<&str>) -> Self {
+        let (native_symbol, native_decimals) = native_currency(chain);
+        Self {
+            symbol: symbol.unwrap_or(&native_symbol).to_string(),
+            native_symbol,
+            native_decimals,
+        }
+    }
+
+    /// Whether the statement is in the chain's native currency, and so
+    /// carries fees.
+    fn is_native(&self) -> bool {
+        self.symbol.eq_ignore_ascii_case(&self.native_symbol)
+    }
+}
+
+/// A transaction line normalised for statement output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementLine {
+    /// Unique id within the statement (the transaction hash).
+    pub id: String,
+    /// Posting date.
+    pub date: DateTime<Utc>,
+    /// Signed amount: positive for inflows, negative for outflows.
+    pub amount: Decimal,
+    /// Counterparty address, if known.
+    pub payee: Option<String>,
+    /// Free-form memo (token symbol and transaction type).
+    pub memo: String,
+    /// Whether this line is a network fee rather than a transfer.
+    pub is_fee: bool,
+}
+
+/// Summary returned to the frontend after an export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementExportResult {
+    /// Path the statement was written to.
+    pub path: String,
+    /// Number of statement lines written (fees count as separate lines).
+    pub line_count: usize,
+}
+
+// ============================================================================
+// Normalisation
+// ============================================================================
+
+/// Converts stored transactions into signed statement lines in `asset` from
+/// the point of view of `wallet_address`.
+///
+/// Outgoing transactions produce a negative line, incoming ones a positive
+/// line; transactions in other assets are left out. Network fees paid by
+/// the wallet are emitted as a separate negative line, on statements in the
+/// native currency, so that the running balance reconciles. Transactions
+/// without a timestamp are skipped since statements require a posting date.
+pub fn build_statement_lines(
+    wallet_address: &str,
+    asset: &StatementAsset,
+    transactions: &[StoredTransaction],
+) -> Vec<StatementLine> {
+    let mut lines = Vec::new();
+
+    for tx in transactions {
+        let Some(date) = tx.timestamp else {
+            continue;
+        };
+        if tx.status.as_deref() == Some("failed") && tx.fee.is_none() {
+            continue;
+        }
+
+        let from = tx.from_address.as_deref().unwrap_or_default();
+        let to = tx.to_address.as_deref().unwrap_or_default();
+        let outgoing = from.eq_ignore_ascii_case(wallet_address);
+        let incoming = to.eq_ignore_ascii_case(wallet_address);
+        let symbol = tx.token_symbol.as_deref().unwrap_or(&asset.native_symbol);
+        let in_asset = symbol.eq_ignore_ascii_case(&asset.symbol);
+        let tx_type = tx.tx_type.clone().unwrap_or_else(|| "transfer".to_string());
+
+        let decimals = tx.token_decimals.or(symbol
+            .eq_ignore_ascii_case(&asset.native_symbol)
+            .then_some(asset.native_decimals));
+        let amount = tx
+            .value
+            .as_deref()
+            .and_then(|v| parse_amount(v, decimals))
+            .unwrap_or(Decimal::ZERO);
+
+        // A failed transaction moves no value but still burns the fee.
+        let moved = tx.status.as_deref() != Some("failed");
+
+        if in_asset && moved && !amount.is_zero() && outgoing != incoming {
+            let (signed, payee) = if outgoing {
+                (-amount, tx.to_address.clone())
+            } else {
+                (amount, tx.from_address.clone())
+            };
+            lines.push(StatementLine {
+                id: tx.hash.clone(),
+                date,
+                amount: signed,
+                payee,
+                memo: format!("{} {}", tx_type, asset.symbol),
+                is_fee: false,
+            });
+        }
+
+        // Fees are paid in the native currency, whatever the transaction moved
+        if outgoing && asset.is_native() {
+            let fee = tx
+                .fee
+                .as_deref()
+                .and_then(|f| parse_amount(f, Some(asset.native_decimals)))
+                .unwrap_or(Decimal::ZERO);
+            if !fee.is_zero() {
+                lines.push(StatementLine {
+                    id: format!("{}-fee", tx.hash),
+                    date,
+                    amount: -fee,
+                    payee: None,
+                    memo: format!("Network fee {}", asset.native_symbol),
+                    is_fee: true,
+                });
+            }
+        }
+    }
+
+    lines.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.id.cmp(&b.id)));
+    lines
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+fn escape_sgml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn ofx_date(date: &DateTime<Utc>) -> String {
+    date.format("%Y%m%d%H%M%S").to_string()
+}
+
+/// Renders statement lines as an OFX 1.02 SGML bank statement.
+///
+/// The wallet address is used as the account id and the chain as the bank
+/// id. `currency` becomes `CURDEF` and must be the asset every line is in;
+/// finance tools generally accept any three-to-five letter code, so the
+/// asset symbol is passed through.
+pub fn render_ofx(
+    wallet: &Wallet,
+    currency: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    lines: &[StatementLine],
+    generated_at: DateTime<Utc>,
+) -> String {
+    let balance: Decimal = lines.iter().map(|l| l.amount).sum();
+    let mut out = String::new();
+
+    out.push_str("OFXHEADER:100\nDATA:OFXSGML\nVERSION:102\nSECURITY:NONE\n");
+    out.push_str("ENCODING:USASCII\nCHARSET:1252\nCOMPRESSION:NONE\n");
+    out.push_str("OLDFILEUID:NONE\nNEWFILEUID:NONE\n\n");
+    out.push_str("<OFX>\n<SIGNONMSGSRSV1>\n<SONRS>\n");
+    out.push_str("<STATUS>\n<CODE>0\n<SEVERITY>INFO\n</STATUS>\n");
+    out.push_str(&format!("<DTSERVER>{}\n", ofx_date(&generated_at)));
+    out.push_str("<LANGUAGE>ENG\n</SONRS>\n</SIGNONMSGSRSV1>\n");
+    out.push_str("<BANKMSGSRSV1>\n<STMTTRNRS>\n<TRNUID>1\n");
+    out.push_str("<STATUS>\n<CODE>0\n<SEVERITY>INFO\n</STATUS>\n");
+    out.push_str("<STMTRS>\n");
+    out.push_str(&format!(
+        "<CURDEF>{}\n",
+        escape_sgml(&currency.to_uppercase())
+    ));
+    out.push_str("<BANKACCTFROM>\n");
+    out.push_str(&format!("<BANKID>{}\n", escape_sgml(&wallet.chain)));
+    out.push_str(&format!("<ACCTID>{}\n", escape_sgml(&wallet.address)));
+    out.push_str("<ACCTTYPE>CHECKING\n</BANKACCTFROM>\n");
+    out.push_str("<BANKTRANLIST>\n");
+    out.push_str(&format!("<DTSTART>{}\n", ofx_date(&start)));
+    out.push_str(&format!("<DTEND>{}\n", ofx_date(&end)));
+
+    for line in lines {
+        let trn_type = if line.is_fee {
+            "FEE"
+        } else if line.amount.is_sign_negative() {
+            "DEBIT"
+        } else {
+            "CREDIT"
+        };
+        out.push_str("<STMTTRN>\n");
+        out.push_str(&format!("<TRNTYPE>{}\n", trn_type));
+        out.push_str(&format!("<DTPOSTED>{}\n", ofx_date(&line.date)));
+        out.push_str(&format!("<TRNAMT>{}\n", line.amount));
+        out.push_str(&format!("<FITID>{}\n", escape_sgml(&line.id)));
+        // NAME is limited to 32 characters in OFX 1.x
+        let name: String = line
+            .payee
+            .as_deref()
+            .unwrap_or(if line.is_fee {
+                "Network fee"
+            } else {
+                "Unknown"
+            })
+            .chars()
+            .take(32)
+            .collect();
+        out.push_str(&format!("<NAME>{}\n", escape_sgml(&name)));
+        if !line.memo.is_empty() {
+            out.push_str(&format!("<MEMO>{}\n", escape_sgml(&line.memo)));
+        }
+        out.push_str("</STMTTRN>\n");
+    }
+
+    out.push_str("</BANKTRANLIST>\n");
+    out.push_str("<LEDGERBAL>\n");
+    out.push_str(&format!("<BALAMT>{}\n", balance));
+    out.push_str(&format!("<DTASOF>{}\n", ofx_date(&end)));
+    out.push_str("</LEDGERBAL>\n</STMTRS>\n</STMTTRNRS>\n</BANKMSGSRSV1>\n</OFX>\n");
+
+    out
+}
+
+/// Renders statement lines as a QIF bank register.
+pub fn render_qif(lines: &[StatementLine]) -> String {
+    let mut out = String::from("!Type:Bank\n");
+
+    for line in lines {
+        out.push_str(&format!("D{}\n", line.date.format("%m/%d/%Y")));
+        out.push_str(&format!("T{}\n", line.amount));
+        out.push_str(&format!("N{}\n", line.id));
+        if let Some(payee) = &line.payee {
+            out.push_str(&format!("P{}\n", payee));
+        } else if line.is_fee {
+            out.push_str("PNetwork fee\n");
+        }
+        if !line.memo.is_empty() {
+            out.push_str(&format!("M{}\n", line.memo));
+        }
+        if line.is_fee {
+            out.push_str("LFees\n");
+        }
+        out.push_str("^\n");
+    }
+
+    out
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Exports a wallet's transaction history for a period as an OFX or QIF file.
+///
+/// # Arguments
+/// * `token` - Session token of a user allowed to export the wallet's profile.
+/// * `wallet_id` - The persisted wallet to export.
+/// * `format` - `"ofx"` or `"qif"`.
+/// * `path` - Destination file path.
+/// * `start_date` - Optional period start (`YYYY-MM-DD` or RFC 3339).
+/// * `end_date` - Optional period end (`YYYY-MM-DD` or RFC 3339, inclusive).
+/// * `asset` - Symbol of the asset to export; the chain's native currency
+///   if not given.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_wallet_statement(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    wallet_id: String,
+    format: String,
+    path: String,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    asset: Option<String>,
+) -> Result<StatementExportResult, String> {
+    let format: StatementFormat = format.parse()?;
+    let start = parse_period_bound(start_date.as_deref(), false)?;
+    let end = parse_period_bound(end_date.as_deref(), true)?;
+
+    let (_, wallet) =
+        authorize_wallet(&state.pool, &auth, &token, &wallet_id, Permission::Export).await?;
+    let asset = StatementAsset::for_chain(&wallet.chain, asset.as_deref());
+
+    let transactions = sqlx::query_as::<_, StoredTransaction>(
+        r#"
+        SELECT * FROM transactions
+        WHERE wallet_id = ?
+          AND (? IS NULL OR timestamp >= ?)
+          AND (? IS NULL OR timestamp <= ?)
+        ORDER BY timestamp ASC
+        "#,
+    )
+    .bind(&wallet_id)
+    .bind(start)
+    .bind(start)
+    .bind(end)
+    .bind(end)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let filter = SpamFilter::load(&state.pool, Some(&wallet.profile_id))
+        .await
+        .map_err(|e| e.to_string())?;
+    let transactions: Vec<StoredTransaction> = transactions
+        .into_iter()
+        .filter(|tx| {
+            let raw = tx
+                .raw_data
+                .as_deref()
+                .and_then(|raw| serde_json::from_str(raw).ok());
+            !filter.hides_transaction(&tx.chain, tx.token_symbol.as_deref(), raw.as_ref())
+        })
+        .collect();
+
+    let lines = build_statement_lines(&wallet.address, &asset, &transactions);
+
+    let contents = match format {
+        StatementFormat::Ofx => {
+            let now = Utc::now();
+            let period_start = start
+                .or_else(|| lines.first().map(|l| l.date))
+                .unwrap_or(now);
+            let period_end = end.or_else(|| lines.last().map(|l| l.date)).unwrap_or(now);
+            render_ofx(
+                &wallet,
+                &asset.symbol,
+                period_start,
+                period_end,
+                &lines,
+                now,
+            )
+        }
+        StatementFormat::Qif => render_qif(&lines),
+    };
+
+    std::fs::write(&path, contents).map_err(|e| e.to_string())?;
+
+    Ok(StatementExportResult {
+        path,
+        line_count: lines.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const WALLET: &str = "0xAbC0000000000000000000000000000000000001";
+
+    fn wallet() -> Wallet {
+        Wallet {
+            id: "w1".to_string(),
+            profile_id: "p1".to_string(),
+            address: WALLET.to_string(),
+            chain: "ethereum".to_string(),
+            name: Some("Main".to_string()),
+            wallet_type: "external".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+        }
+    }
+
+    fn eth() -> StatementAsset {
+        StatementAsset::for_chain("ethereum", None)
+    }
+
+    fn usdc(hash: &str, from: &str, to: &str, value: &str, fee: &str) -> StoredTransaction {
+        StoredTransaction {
+            token_symbol: Some("USDC".to_string()),
+            token_decimals: Some(6),
+            ..tx(hash, from, to, value, Some(fee))
+        }
+    }
+
+    fn tx(hash: &str, from: &str, to: &str, value: &str, fee: Option<&str>) -> StoredTransaction {
+        StoredTransaction {
+            id: hash.to_string(),
+            wallet_id: "w1".to_string(),
+            hash: hash.to_string(),
+            block_number: Some(1),
+            timestamp: Some(Utc.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap()),
+            from_address: Some(from.to_string()),
+            to_address: Some(to.to_string()),
+            value: Some(value.to_string()),
+            fee: fee.map(|f| f.to_string()),
+            status: Some("success".to_string()),
+            tx_type: Some("transfer".to_string()),
+            token_symbol: Some("ETH".to_string()),
+            token_decimals: Some(18),
+            chain: "ethereum".to_string(),
+            raw_data: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_build_lines_signs_and_fees() {
+        let txs = vec![
+            tx("0x1", "0xother", &WALLET.to_lowercase(), "1.0", None),
+            tx("0x2", WALLET, "0xother", "0.5", Some("0.01")),
+        ];
+        let lines = build_statement_lines(WALLET, &eth(), &txs);
+
+        assert_eq!(lines.len(), 3);
+        let incoming = lines.iter().find(|l| l.id == "0x1").unwrap();
+        assert_eq!(incoming.amount, Decimal::from_str("1.0").unwrap());
+        let outgoing = lines.iter().find(|l| l.id == "0x2").unwrap();
+        assert_eq!(outgoing.amount, Decimal::from_str("-0.5").unwrap());
+        let fee = lines.iter().find(|l| l.is_fee).unwrap();
+        assert_eq!(fee.amount, Decimal::from_str("-0.01").unwrap());
+    }
+
+    #[test]
+    fn test_failed_transaction_only_records_fee() {
+        let mut failed = tx("0x3", WALLET, "0xother", "5", Some("0.02"));
+        failed.status = Some("failed".to_string());
+        let lines = build_statement_lines(WALLET, &eth(), &[failed]);
+
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].is_fee);
+    }
+
+    #[test]
+    fn test_render_qif() {
+        let txs = vec![tx("0x1", "0xother", WALLET, "1.0", None)];
+        let qif = render_qif(&build_statement_lines(WALLET, &eth(), &txs));
+
+        assert!(qif.starts_with("!Type:Bank\n"));
+        assert!(qif.contains("D03/01/2025\n"));
+        assert!(qif.contains("T1.0\n"));
+        assert!(qif.contains("P0xother\n"));
+        assert!(qif.ends_with("^\n"));
+    }
+
+    #[test]
+    fn test_render_ofx() {
+        let txs = vec![tx("0x2", WALLET, "0xother", "0.5", Some("0.01"))];
+        let lines = build_statement_lines(WALLET, &eth(), &txs);
+        let at = Utc.with_ymd_and_hms(2025, 4, 1, 0, 0, 0).unwrap();
+        let ofx = render_ofx(&wallet(), "eth", at, at, &lines, at);
+
+        assert!(ofx.starts_with("OFXHEADER:100"));
+        assert!(ofx.contains("<CURDEF>ETH\n"));
+        assert!(ofx.contains(&format!("<ACCTID>{}\n", WALLET)));
+        assert!(ofx.contains("<TRNTYPE>DEBIT\n"));
+        assert!(ofx.contains("<TRNTYPE>FEE\n"));
+        assert!(ofx.contains("<BALAMT>-0.51\n"));
+    }
+
+    #[test]
+    fn test_native_statement_leaves_out_tokens_but_keeps_their_fees() {
+        let txs = vec![
+            tx("0x1", "0xother", WALLET, "1.0", None),
+            usdc("0x2", WALLET, "0xother", "2500000", "210000000000000"),
+        ];
+        let lines = build_statement_lines(WALLET, &eth(), &txs);
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| l.id != "0x2"));
+        // The fee is in wei, not in USDC's six decimals
+        let fee = lines.iter().find(|l| l.is_fee).unwrap();
+        assert_eq!(fee.amount, Decimal::from_str("-0.00021").unwrap());
+        assert_eq!(fee.memo, "Network fee ETH");
+    }
+
+    #[test]
+    fn test_token_statement_has_only_that_token() {
+        let txs = vec![
+            tx("0x1", "0xother", WALLET, "1.0", None),
+            usdc("0x2", WALLET, "0xother", "2500000", "210000000000000"),
+        ];
+        let asset = StatementAsset::for_chain("ethereum", Some("USDC"));
+        let lines = build_statement_lines(WALLET, &asset, &txs);
+
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].amount, Decimal::from_str("-2.5").unwrap());
+
+        let at = Utc.with_ymd_and_hms(2025, 4, 1, 0, 0, 0).unwrap();
+        let ofx = render_ofx(&wallet(), &asset.symbol, at, at, &lines, at);
+        assert!(ofx.contains("<CURDEF>USDC\n"));
+        assert!(ofx.contains("<BALAMT>-2.5\n"));
+    }
+}
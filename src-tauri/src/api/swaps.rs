@@ -0,0 +1,36 @@
+//! Tauri command listing the decoded swaps of a profile's transactions.
+
+use tauri::State;
+
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::authorize_profile;
+use crate::core::auth_state::AuthState;
+
+pub use pacioli_core::services::swaps::*;
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns the decoded swaps of every swap transaction in a profile's
+/// wallets, for valuing disposals and acquisitions at executed amounts.
+#[tauri::command]
+pub async fn get_transaction_swaps(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<TransactionSwaps>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    load_profile_swaps(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,381 @@
+//! Detection and re-sync of missing wallet history.
+//!
+//! Three checks find transactions that were never stored: gaps in the
+//! nonces of an EVM wallet's outgoing transactions, assets whose balance
+//! rebuilt from stored history differs from the chain, and block ranges
+//! never synced because a wallet was never synced or its last sync failed.
+//! Each gap carries the block range to fetch again, and re-syncing rewinds
+//! the wallet's sync cursor to the start of that range and queues a sync.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use super::balance_history::reconcile_profile_balances;
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction};
+use super::profile_scope::{authorize_profile, profile_wallets};
+use super::wallet_sync::load_sync_statuses;
+use crate::chains::evm::config::get_chain_by_name;
+use crate::chains::ChainManagerState;
+use crate::core::auth_state::AuthState;
+use crate::db::multi_chain::MultiChainRepository;
+use crate::jobs::queue::{JobQueueState, JobTask};
+use crate::jobs::JobPriority;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// What revealed a gap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GapKind {
+    /// Outgoing transactions with these nonces were never stored.
+    NonceGap,
+    /// An asset's rebuilt balance differs from the chain.
+    BalanceMismatch,
+    /// The wallet has never been synced.
+    NeverSynced,
+    /// The wallet's last sync failed, leaving later blocks unsynced.
+    SyncFailed,
+}
+
+/// A stretch of history likely missing from a wallet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncGap {
+    /// Wallet the history is missing from.
+    pub wallet_id: String,
+    /// Chain the wallet is on.
+    pub chain: String,
+    /// Wallet address.
+    pub address: String,
+    /// What revealed the gap.
+    pub kind: GapKind,
+    /// First block to fetch again; `None` for the wallet's whole history.
+    pub from_block: Option<i64>,
+    /// Last block to fetch again; `None` for up to the latest block.
+    pub to_block: Option<i64>,
+    /// What is missing, for display.
+    pub description: String,
+}
+
+/// Gaps found across a profile's wallets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncGapReport {
+    /// Number of wallets checked.
+    pub wallets_checked: usize,
+    /// Gaps found, by wallet.
+    pub gaps: Vec<SyncGap>,
+    /// Checks that couldn't run, such as balances that couldn't be fetched.
+    pub warnings: Vec<String>,
+}
+
+/// A block range to fetch again for a wallet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResyncRange {
+    /// Wallet to re-sync.
+    pub wallet_id: String,
+    /// First block to fetch again; `None` for the wallet's whole history.
+    pub from_block: Option<i64>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Nonce of a stored EVM transaction, from the explorer record kept as its
+/// raw data.
+fn stored_nonce(tx: &StoredTransaction) -> Option<u64> {
+    let raw: serde_json::Value = serde_json::from_str(tx.raw_data.as_deref()?).ok()?;
+    match raw.get("nonce")? {
+        serde_json::Value::String(nonce) => nonce.parse().ok(),
+        serde_json::Value::Number(nonce) => nonce.as_u64(),
+        _ => None,
+    }
+}
+
+/// Missing nonces among the outgoing transactions of `address`, as
+/// `(first missing, last missing, block before, block after)`. Nonces below
+/// the lowest stored one count as missing from the start of history.
+pub(crate) fn find_nonce_gaps(
+    address: &str,
+    transactions: &[StoredTransaction],
+) -> Vec<(u64, u64, Option<i64>, Option<i64>)> {
+    let mut sent: BTreeMap<u64, Option<i64>> = BTreeMap::new();
+    for tx in transactions {
+        let outgoing = tx
+            .from_address
+            .as_deref()
+            .is_some_and(|from| from.eq_ignore_ascii_case(address));
+        if let (true, Some(nonce)) = (outgoing, stored_nonce(tx)) {
+            sent.insert(nonce, tx.block_number);
+        }
+    }
+
+    let mut gaps = Vec::new();
+    let mut expected = 0u64;
+    let mut previous_block = None;
+    for (nonce, block) in sent {
+        if nonce > expected {
+            gaps.push((expected, nonce - 1, previous_block, block));
+        }
+        expected = nonce + 1;
+        previous_block = block;
+    }
+    gaps
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Checks each of a profile's wallets for missing history: nonce gaps on
+/// EVM wallets, balances that don't reconcile with the chain, and block
+/// ranges never synced.
+#[tauri::command]
+pub async fn detect_sync_gaps(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    chains: State<'_, ChainManagerState>,
+    token: String,
+    profile_id: String,
+) -> Result<SyncGapReport, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let wallets = profile_wallets(&state.pool, &profile_id).await?;
+    let mut gaps = Vec::new();
+    let mut warnings = Vec::new();
+
+    // Block ranges never synced.
+    let statuses = load_sync_statuses(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    for status in statuses {
+        let (kind, from_block, description) = match status.state.as_str() {
+            "never" => (
+                GapKind::NeverSynced,
+                None,
+                "Wallet has never been synced".to_string(),
+            ),
+            "error" => (
+                GapKind::SyncFailed,
+                status.last_block.map(|b| b + 1),
+                format!(
+                    "Last sync failed{}",
+                    status
+                        .error_message
+                        .map(|e| format!(": {}", e))
+                        .unwrap_or_default()
+                ),
+            ),
+            _ => continue,
+        };
+        gaps.push(SyncGap {
+            wallet_id: status.wallet_id,
+            chain: status.chain,
+            address: status.address,
+            kind,
+            from_block,
+            to_block: None,
+            description,
+        });
+    }
+
+    // Nonce gaps on EVM wallets.
+    for wallet in wallets
+        .iter()
+        .filter(|w| get_chain_by_name(&w.chain).is_some())
+    {
+        let transactions = sqlx::query_as::<_, StoredTransaction>(
+            "SELECT * FROM transactions WHERE wallet_id = ?",
+        )
+        .bind(&wallet.id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        for (first, last, before, after) in find_nonce_gaps(&wallet.address, &transactions) {
+            let nonces = if first == last {
+                format!("nonce {}", first)
+            } else {
+                format!("nonces {} to {}", first, last)
+            };
+            gaps.push(SyncGap {
+                wallet_id: wallet.id.clone(),
+                chain: wallet.chain.clone(),
+                address: wallet.address.clone(),
+                kind: GapKind::NonceGap,
+                from_block: before.map(|b| b + 1),
+                to_block: after,
+                description: format!("Outgoing transactions with {} are missing", nonces),
+            });
+        }
+    }
+
+    // Balances that don't reconcile.
+    for reconciliation in reconcile_profile_balances(&state.pool, &chains, &profile_id).await? {
+        if let Some(error) = &reconciliation.error {
+            warnings.push(format!(
+                "Could not check balances of {} on {}: {}",
+                reconciliation.address, reconciliation.chain, error
+            ));
+        }
+        for discrepancy in &reconciliation.discrepancies {
+            gaps.push(SyncGap {
+                wallet_id: reconciliation.wallet_id.clone(),
+                chain: reconciliation.chain.clone(),
+                address: reconciliation.address.clone(),
+                kind: GapKind::BalanceMismatch,
+                from_block: None,
+                to_block: None,
+                description: format!(
+                    "{} balance is {} on chain but {} from stored history",
+                    discrepancy.symbol, discrepancy.on_chain, discrepancy.reconstructed
+                ),
+            });
+        }
+    }
+
+    gaps.sort_by(|a, b| a.wallet_id.cmp(&b.wallet_id));
+    Ok(SyncGapReport {
+        wallets_checked: wallets.len(),
+        gaps,
+        warnings,
+    })
+}
+
+/// Fetches the given ranges again. Each wallet's sync cursor is rewound to
+/// the start of its earliest range and a sync is queued, which fetches
+/// from there to the latest block; transactions already stored are
+/// updated rather than duplicated. Returns one job ID per wallet. Requires
+/// the owner, admin, or preparer role on the profile.
+#[tauri::command]
+pub async fn resync_gaps(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    queue: State<'_, JobQueueState>,
+    token: String,
+    profile_id: String,
+    ranges: Vec<ResyncRange>,
+) -> Result<Vec<String>, String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ManageWallets,
+    )
+    .await?;
+    let wallets = profile_wallets(&state.pool, &profile_id).await?;
+    let repository = MultiChainRepository::new(state.pool.clone());
+
+    // Earliest block to fetch per wallet; zero fetches the whole history.
+    let mut starts: BTreeMap<&str, i64> = BTreeMap::new();
+    for range in &ranges {
+        let start = range.from_block.unwrap_or(0).max(0);
+        starts
+            .entry(range.wallet_id.as_str())
+            .and_modify(|s| *s = (*s).min(start))
+            .or_insert(start);
+    }
+
+    let mut job_ids = Vec::with_capacity(starts.len());
+    for (wallet_id, start) in starts {
+        let wallet = wallets
+            .iter()
+            .find(|w| w.id == wallet_id)
+            .ok_or_else(|| format!("Wallet {} is not in profile {}", wallet_id, profile_id))?;
+
+        let synced = repository
+            .get_sync_status(&wallet.chain, &wallet.address)
+            .await
+            .map_err(|e| e.to_string())?
+            .map(|s| s.last_block_synced)
+            .unwrap_or(0);
+        let rewound = (start - 1).max(0);
+        if rewound < synced {
+            repository
+                .set_sync_progress(&wallet.chain, &wallet.address, rewound)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+
+        let task = JobTask::WalletSync {
+            profile_id: profile_id.clone(),
+            user_id: user_id.clone(),
+            wallet_id: wallet.id.clone(),
+            chain: wallet.chain.clone(),
+        };
+        job_ids.push(queue.enqueue(task, JobPriority::User).await?);
+    }
+    Ok(job_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    const WALLET: &str = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045";
+    const OTHER: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+
+    fn tx(from: &str, nonce: Option<u64>, block: i64) -> StoredTransaction {
+        StoredTransaction {
+            id: format!("t{}", block),
+            wallet_id: "w1".to_string(),
+            hash: format!("0x{:064x}", block),
+            block_number: Some(block),
+            timestamp: None,
+            from_address: Some(from.to_string()),
+            to_address: Some(OTHER.to_string()),
+            value: Some("0".to_string()),
+            fee: Some("0".to_string()),
+            status: Some("success".to_string()),
+            tx_type: Some("transfer".to_string()),
+            token_symbol: None,
+            token_decimals: None,
+            chain: "ethereum".to_string(),
+            raw_data: nonce.map(|n| format!(r#"{{"hash":"0x","nonce":"{}"}}"#, n)),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_find_nonce_gaps() {
+        let transactions = vec![
+            tx(WALLET, Some(2), 100),
+            tx(&WALLET.to_uppercase(), Some(3), 110),
+            tx(WALLET, Some(7), 150),
+            tx(WALLET, Some(8), 160),
+            // Incoming and token-only records carry no nonce of ours
+            tx(OTHER, Some(5), 120),
+            tx(WALLET, None, 130),
+        ];
+
+        assert_eq!(
+            find_nonce_gaps(WALLET, &transactions),
+            vec![(0, 1, None, Some(100)), (4, 6, Some(110), Some(150))]
+        );
+        assert!(find_nonce_gaps(OTHER, &[tx(OTHER, Some(0), 1)]).is_empty());
+    }
+
+    #[test]
+    fn test_stored_nonce_formats() {
+        let mut numeric = tx(WALLET, None, 1);
+        numeric.raw_data = Some(r#"{"nonce":12}"#.to_string());
+        assert_eq!(stored_nonce(&numeric), Some(12));
+        assert_eq!(stored_nonce(&tx(WALLET, Some(4), 1)), Some(4));
+        assert_eq!(stored_nonce(&tx(WALLET, None, 1)), None);
+    }
+}
@@ -0,0 +1,92 @@
+//! Tauri command for exporting a profile's history for another tax tool.
+
+use tauri::{AppHandle, Emitter, State};
+
+use super::export::ExportFile;
+use super::export_templates::{ExportProgress, EXPORT_PROGRESS_EVENT};
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::authorize_profile;
+use super::statement_export::StatementExportResult;
+use crate::core::auth_state::AuthState;
+use crate::jobs::{CancelToken, JobKind, JobRegistryState};
+
+pub use pacioli_core::services::tax_tool_export::*;
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Writes a profile's transactions to `path` in a tax tool's import format.
+/// Progress is emitted as [`EXPORT_PROGRESS_EVENT`]; when `job_id` is given
+/// the export can be stopped with `cancel_job`, leaving no file behind.
+///
+/// # Arguments
+/// * `format` - One of: koinly, cointracking, accointing.
+/// * `start_date` / `end_date` - Optional period bounds, `YYYY-MM-DD` or RFC 3339.
+/// * `gzip` - Compress the file with gzip.
+/// * `job_id` - ID to register the export under, for cancelling it.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_tax_tool_csv(
+    app: AppHandle,
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    jobs: State<'_, JobRegistryState>,
+    token: String,
+    profile_id: String,
+    format: String,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    path: String,
+    gzip: Option<bool>,
+    job_id: Option<String>,
+) -> Result<StatementExportResult, String> {
+    authorize_profile(&state.pool, &auth, &token, &profile_id, Permission::Export).await?;
+    let format: TaxToolFormat = format.parse()?;
+
+    let (job_id, cancel) = match job_id {
+        Some(id) => {
+            let (id, token) = jobs.start(JobKind::Export, &path, Some(id));
+            (Some(id), token)
+        }
+        None => (None, CancelToken::new()),
+    };
+    let progress = ExportProgress {
+        job_id: job_id.clone(),
+        path: path.clone(),
+        ..ExportProgress::default()
+    };
+
+    let result = async {
+        let file = ExportFile::create(&path, gzip.unwrap_or(false))?;
+        let (file, written) = write_tax_tool_export(
+            &state.pool,
+            &profile_id,
+            format,
+            start_date.as_deref(),
+            end_date.as_deref(),
+            file,
+            &cancel,
+            progress,
+            |progress| {
+                let _ = app.emit(EXPORT_PROGRESS_EVENT, progress);
+            },
+        )
+        .await?;
+        file.finish()?;
+        Ok::<_, String>(written)
+    }
+    .await;
+
+    if let Some(id) = &job_id {
+        jobs.finish(id, &result.as_ref().map(|_| ()).map_err(Clone::clone));
+    }
+    if result.is_err() {
+        let _ = std::fs::remove_file(&path);
+    }
+    Ok(StatementExportResult {
+        path,
+        line_count: result?,
+    })
+}
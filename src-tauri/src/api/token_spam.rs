@@ -0,0 +1,302 @@
+//! Tauri commands for marking tokens spam or allowed and importing
+//! blocklists.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::auth::verify_admin;
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::{authenticate, authorize_profile, authorize_record};
+use crate::core::auth_state::AuthState;
+use crate::core::spam::{self, SpamAssessment, TokenCandidate};
+
+pub use pacioli_core::services::token_spam::*;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A token marked by the user for a profile.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenMark {
+    /// Unique identifier for the mark.
+    pub id: String,
+    /// Profile the mark applies to.
+    pub profile_id: String,
+    /// Chain the token lives on.
+    pub chain_id: String,
+    /// Lowercased contract address, or `symbol:<SYMBOL>` for tokens without one.
+    pub token_key: String,
+    /// Contract or mint address, if known.
+    pub token_address: Option<String>,
+    /// Token symbol, if known.
+    pub token_symbol: Option<String>,
+    /// Spam or allowed.
+    pub status: TokenMarkStatus,
+    /// Optional reason given by the user.
+    pub reason: Option<String>,
+    /// When the token was marked.
+    pub created_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Inserts or replaces a profile's mark on a token.
+async fn upsert_mark(
+    pool: &SqlitePool,
+    profile_id: &str,
+    chain_id: &str,
+    token_address: Option<String>,
+    token_symbol: Option<String>,
+    status: TokenMarkStatus,
+    reason: Option<String>,
+) -> Result<TokenMark, String> {
+    let key = token_key(token_address.as_deref(), token_symbol.as_deref())
+        .ok_or_else(|| "A token address or symbol is required".to_string())?;
+    let chain_id = chain_id.to_lowercase();
+
+    sqlx::query(
+        r#"
+        INSERT INTO token_marks (
+            id, profile_id, chain_id, token_key, token_address, token_symbol,
+            status, reason, created_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(profile_id, chain_id, token_key) DO UPDATE SET
+            token_address = COALESCE(excluded.token_address, token_address),
+            token_symbol = COALESCE(excluded.token_symbol, token_symbol),
+            status = excluded.status,
+            reason = excluded.reason
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(profile_id)
+    .bind(&chain_id)
+    .bind(&key)
+    .bind(&token_address)
+    .bind(&token_symbol)
+    .bind(status)
+    .bind(&reason)
+    .bind(Utc::now())
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    sqlx::query_as(
+        "SELECT * FROM token_marks WHERE profile_id = ? AND chain_id = ? AND token_key = ?",
+    )
+    .bind(profile_id)
+    .bind(&chain_id)
+    .bind(&key)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Marks a token as spam for a profile, hiding it from balances and exports.
+/// Requires the owner, admin, or preparer role on the profile.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn mark_token_spam(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    chain_id: String,
+    token_address: Option<String>,
+    token_symbol: Option<String>,
+    reason: Option<String>,
+) -> Result<TokenMark, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::EditTags,
+    )
+    .await?;
+    upsert_mark(
+        &state.pool,
+        &profile_id,
+        &chain_id,
+        token_address,
+        token_symbol,
+        TokenMarkStatus::Spam,
+        reason,
+    )
+    .await
+}
+
+/// Marks a token as allowed for a profile, overriding the blocklist and
+/// heuristics. Requires the owner, admin, or preparer role on the profile.
+#[tauri::command]
+pub async fn mark_token_allowed(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    chain_id: String,
+    token_address: Option<String>,
+    token_symbol: Option<String>,
+) -> Result<TokenMark, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::EditTags,
+    )
+    .await?;
+    upsert_mark(
+        &state.pool,
+        &profile_id,
+        &chain_id,
+        token_address,
+        token_symbol,
+        TokenMarkStatus::Allowed,
+        None,
+    )
+    .await
+}
+
+/// Removes a spam or allowed mark. Requires the owner, admin, or preparer
+/// role on the mark's profile.
+#[tauri::command]
+pub async fn remove_token_mark(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    id: String,
+) -> Result<(), String> {
+    let user_id = authenticate(&auth, &token)?;
+    let not_found = "Token mark not found";
+    let profile_id: String = sqlx::query_scalar("SELECT profile_id FROM token_marks WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| not_found.to_string())?;
+    authorize_record(
+        &state.pool,
+        &user_id,
+        &profile_id,
+        Permission::EditTags,
+        not_found,
+    )
+    .await?;
+
+    sqlx::query("DELETE FROM token_marks WHERE id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Lists a profile's token marks. Requires any role on the profile.
+#[tauri::command]
+pub async fn get_token_marks(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<TokenMark>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as("SELECT * FROM token_marks WHERE profile_id = ? ORDER BY created_at DESC")
+        .bind(&profile_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Adds contract addresses to the shared spam blocklist.
+///
+/// Returns the number of addresses that were not already listed. The
+/// blocklist is shared by every profile, so it requires the app admin.
+#[tauri::command]
+pub async fn import_token_blocklist(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    chain_id: String,
+    addresses: Vec<String>,
+    source: Option<String>,
+) -> Result<u64, String> {
+    let user_id = authenticate(&auth, &token)?;
+    verify_admin(&state.pool, &user_id).await?;
+    let chain_id = chain_id.to_lowercase();
+    let now = Utc::now();
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    let mut added = 0;
+
+    for address in addresses.iter().map(|a| a.trim()).filter(|a| !a.is_empty()) {
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO token_blocklist (chain_id, token_address, source, added_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(&chain_id)
+        .bind(address.to_lowercase())
+        .bind(&source)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+        added += result.rows_affected();
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(added)
+}
+
+/// Scores tokens with the spam heuristics, the blocklist, and the profile's
+/// marks. Requires any role on the profile.
+#[tauri::command]
+pub async fn assess_tokens(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    tokens: Vec<TokenCandidate>,
+) -> Result<Vec<SpamAssessment>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let filter = SpamFilter::load(&state.pool, Some(&profile_id))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(tokens
+        .iter()
+        .map(|token| {
+            let address = token.token_address.as_deref();
+            let symbol = token.symbol.as_deref();
+            let mut assessment =
+                spam::assess(token, filter.is_blocklisted(&token.chain_id, address));
+            assessment.is_spam = !filter.is_allowed(&token.chain_id, address, symbol)
+                && (assessment.is_spam
+                    || filter.is_hidden(&token.chain_id, address, symbol, token.name.as_deref()));
+            assessment
+        })
+        .collect())
+}
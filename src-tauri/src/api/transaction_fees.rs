@@ -0,0 +1,36 @@
+//! Tauri command listing the fee breakdowns of a profile's transactions.
+
+use tauri::State;
+
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::profile_scope::authorize_profile;
+use crate::core::auth_state::AuthState;
+
+pub use pacioli_core::services::transaction_fees::*;
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns the fee breakdown of every EVM transaction in a profile's
+/// wallets, separating burned base fees, tips, and L1 data fees.
+#[tauri::command]
+pub async fn get_transaction_fees(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<TransactionFee>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    load_profile_fee_breakdowns(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())
+}
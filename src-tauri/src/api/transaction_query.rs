@@ -0,0 +1,352 @@
+//! Paginated, server-side filtered transaction queries.
+//!
+//! Transaction histories can run to hundreds of thousands of rows, so the
+//! frontend pages through them with an opaque keyset cursor instead of
+//! `OFFSET`, which gets slower the deeper the page. Filters are applied in
+//! SQL and the same filters produce the total count.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, Sqlite};
+use tauri::State;
+
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction};
+use super::profile_scope::authorize_profile;
+use crate::core::auth_state::AuthState;
+
+/// Default page size for [`query_transactions`].
+const DEFAULT_PAGE_SIZE: i64 = 100;
+
+/// Upper bound on the page size.
+const MAX_PAGE_SIZE: i64 = 1000;
+
+/// Expression transactions are ordered by. Transactions without an on-chain
+/// timestamp fall back to when they were stored.
+const SORT_KEY: &str = "COALESCE(t.timestamp, t.created_at)";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Filters for [`query_transactions`]. Unset fields don't restrict results;
+/// list fields match any of the given values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionFilter {
+    /// Chain identifiers (e.g., `ethereum`, `polkadot`).
+    pub chains: Option<Vec<String>>,
+    /// Wallet IDs.
+    pub wallet_ids: Option<Vec<String>>,
+    /// Transaction types.
+    pub tx_types: Option<Vec<String>>,
+    /// Transaction statuses.
+    pub statuses: Option<Vec<String>>,
+    /// Earliest timestamp, inclusive.
+    pub start_date: Option<DateTime<Utc>>,
+    /// Latest timestamp, inclusive.
+    pub end_date: Option<DateTime<Utc>>,
+    /// Minimum value, in the units stored on the transaction.
+    pub min_amount: Option<f64>,
+    /// Maximum value, in the units stored on the transaction.
+    pub max_amount: Option<f64>,
+    /// Budget tag categories; matches transactions with any of these tags.
+    pub tags: Option<Vec<String>>,
+}
+
+/// Position after the last row of a page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct TransactionCursor {
+    /// Sort timestamp of the last row.
+    timestamp: DateTime<Utc>,
+    /// ID of the last row, to break timestamp ties.
+    id: String,
+}
+
+impl TransactionCursor {
+    /// Encodes the cursor as an opaque URL-safe string.
+    fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    /// Decodes a cursor returned by [`TransactionCursor::encode`].
+    fn decode(cursor: &str) -> Result<Self, String> {
+        URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| "Invalid pagination cursor".to_string())
+    }
+}
+
+/// One page of transactions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionPage {
+    /// Transactions on this page, newest first.
+    pub transactions: Vec<StoredTransaction>,
+    /// Number of transactions matching the filter across all pages.
+    pub total_count: i64,
+    /// Cursor for the next page, or `None` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+// ============================================================================
+// Query Building
+// ============================================================================
+
+/// Treats an empty list the same as no filter.
+fn non_empty(values: &Option<Vec<String>>) -> Option<&[String]> {
+    values.as_deref().filter(|v| !v.is_empty())
+}
+
+/// Appends `AND column IN (?, ...)`.
+fn push_in(builder: &mut QueryBuilder<'_, Sqlite>, column: &str, values: &[String]) {
+    builder.push(" AND ").push(column).push(" IN (");
+    let mut separated = builder.separated(", ");
+    for value in values {
+        separated.push_bind(value.clone());
+    }
+    separated.push_unseparated(")");
+}
+
+/// Appends the profile constraint and all filter conditions.
+fn push_filters(
+    builder: &mut QueryBuilder<'_, Sqlite>,
+    profile_id: &str,
+    filter: &TransactionFilter,
+) {
+    builder
+        .push(" WHERE w.profile_id = ")
+        .push_bind(profile_id.to_string())
+        .push(" AND t.id NOT IN (SELECT duplicate_id FROM transaction_merges)");
+
+    if let Some(chains) = non_empty(&filter.chains) {
+        push_in(builder, "t.chain", chains);
+    }
+    if let Some(wallet_ids) = non_empty(&filter.wallet_ids) {
+        push_in(builder, "t.wallet_id", wallet_ids);
+    }
+    if let Some(tx_types) = non_empty(&filter.tx_types) {
+        push_in(builder, "t.tx_type", tx_types);
+    }
+    if let Some(statuses) = non_empty(&filter.statuses) {
+        push_in(builder, "t.status", statuses);
+    }
+    if let Some(start) = filter.start_date {
+        builder
+            .push(" AND ")
+            .push(SORT_KEY)
+            .push(" >= ")
+            .push_bind(start);
+    }
+    if let Some(end) = filter.end_date {
+        builder
+            .push(" AND ")
+            .push(SORT_KEY)
+            .push(" <= ")
+            .push_bind(end);
+    }
+    if let Some(min) = filter.min_amount {
+        builder
+            .push(" AND CAST(t.value AS REAL) >= ")
+            .push_bind(min);
+    }
+    if let Some(max) = filter.max_amount {
+        builder
+            .push(" AND CAST(t.value AS REAL) <= ")
+            .push_bind(max);
+    }
+    if let Some(tags) = non_empty(&filter.tags) {
+        builder
+            .push(" AND EXISTS (SELECT 1 FROM transaction_tags tt WHERE tt.transaction_id = t.id");
+        push_in(builder, "tt.category", tags);
+        builder.push(")");
+    }
+}
+
+/// Appends the keyset condition for rows after `cursor`.
+fn push_cursor(builder: &mut QueryBuilder<'_, Sqlite>, cursor: &TransactionCursor) {
+    builder
+        .push(" AND (")
+        .push(SORT_KEY)
+        .push(" < ")
+        .push_bind(cursor.timestamp)
+        .push(" OR (")
+        .push(SORT_KEY)
+        .push(" = ")
+        .push_bind(cursor.timestamp)
+        .push(" AND t.id < ")
+        .push_bind(cursor.id.clone())
+        .push("))");
+}
+
+/// Splits off the look-ahead row and returns the cursor for the next page.
+fn finish_page(
+    mut rows: Vec<StoredTransaction>,
+    page_size: usize,
+) -> (Vec<StoredTransaction>, Option<String>) {
+    if rows.len() <= page_size {
+        return (rows, None);
+    }
+    rows.truncate(page_size);
+    let next_cursor = rows.last().map(|last| {
+        TransactionCursor {
+            timestamp: last.timestamp.unwrap_or(last.created_at),
+            id: last.id.clone(),
+        }
+        .encode()
+    });
+    (rows, next_cursor)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns one page of a profile's transactions matching `filter`, newest
+/// first.
+///
+/// Pass the returned `next_cursor` back as `cursor` to fetch the next page.
+/// `total_count` ignores the cursor, so it stays the same across pages.
+/// Requires any role on the profile.
+#[tauri::command]
+pub async fn query_transactions(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    filter: Option<TransactionFilter>,
+    cursor: Option<String>,
+    page_size: Option<i64>,
+) -> Result<TransactionPage, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let filter = filter.unwrap_or_default();
+    let page_size = page_size
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let cursor = cursor
+        .as_deref()
+        .map(TransactionCursor::decode)
+        .transpose()?;
+
+    let mut count_query = QueryBuilder::<Sqlite>::new(
+        "SELECT COUNT(*) FROM transactions t INNER JOIN wallets w ON t.wallet_id = w.id",
+    );
+    push_filters(&mut count_query, &profile_id, &filter);
+    let total_count: i64 = count_query
+        .build_query_scalar()
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut page_query = QueryBuilder::<Sqlite>::new(
+        "SELECT t.* FROM transactions t INNER JOIN wallets w ON t.wallet_id = w.id",
+    );
+    push_filters(&mut page_query, &profile_id, &filter);
+    if let Some(cursor) = &cursor {
+        push_cursor(&mut page_query, cursor);
+    }
+    page_query
+        .push(" ORDER BY ")
+        .push(SORT_KEY)
+        .push(" DESC, t.id DESC LIMIT ")
+        .push_bind(page_size + 1);
+
+    let rows: Vec<StoredTransaction> = page_query
+        .build_query_as()
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let (transactions, next_cursor) = finish_page(rows, page_size as usize);
+
+    Ok(TransactionPage {
+        transactions,
+        total_count,
+        next_cursor,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(id: &str, timestamp: &str) -> StoredTransaction {
+        let timestamp = DateTime::parse_from_rfc3339(timestamp)
+            .unwrap()
+            .with_timezone(&Utc);
+        StoredTransaction {
+            id: id.to_string(),
+            wallet_id: "wallet".to_string(),
+            hash: format!("0x{}", id),
+            block_number: None,
+            timestamp: Some(timestamp),
+            from_address: None,
+            to_address: None,
+            value: Some("1".to_string()),
+            fee: None,
+            status: None,
+            tx_type: None,
+            token_symbol: None,
+            token_decimals: None,
+            chain: "ethereum".to_string(),
+            raw_data: None,
+            created_at: timestamp,
+        }
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = TransactionCursor {
+            timestamp: Utc::now(),
+            id: "abc".to_string(),
+        };
+        assert_eq!(TransactionCursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(TransactionCursor::decode("not-a-cursor").is_err());
+    }
+
+    #[test]
+    fn test_finish_page() {
+        let rows = vec![
+            transaction("c", "2026-03-03T00:00:00Z"),
+            transaction("b", "2026-03-02T00:00:00Z"),
+            transaction("a", "2026-03-01T00:00:00Z"),
+        ];
+
+        let (page, next) = finish_page(rows.clone(), 2);
+        assert_eq!(page.len(), 2);
+        let cursor = TransactionCursor::decode(&next.unwrap()).unwrap();
+        assert_eq!(cursor.id, "b");
+
+        let (page, next) = finish_page(rows, 3);
+        assert_eq!(page.len(), 3);
+        assert!(next.is_none());
+    }
+
+    #[test]
+    fn test_push_filters_skips_empty_lists() {
+        let filter = TransactionFilter {
+            chains: Some(vec![]),
+            statuses: Some(vec!["confirmed".to_string(), "pending".to_string()]),
+            tags: Some(vec!["grants".to_string()]),
+            ..Default::default()
+        };
+        let mut builder = QueryBuilder::<Sqlite>::new("SELECT 1");
+        push_filters(&mut builder, "profile", &filter);
+        let sql = builder.sql();
+
+        assert!(!sql.contains("t.chain"));
+        assert!(sql.contains("t.status IN (?, ?)"));
+        assert!(sql.contains("tt.category IN (?)"));
+    }
+}
@@ -0,0 +1,459 @@
+//! Dry-run valuation of transfers before they are sent.
+//!
+//! Transfers are signed in the user's external wallet, so Pacioli only sees
+//! them after the fact. Simulating first shows the network fee, whether the
+//! balance covers the transfer, who the counterparty is, and the journal
+//! entry and lots the transfer would produce.
+
+use std::str::FromStr;
+
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use super::cost_basis::load_tax_settings;
+use super::entities::{lookup_address_internal, AddressMatch};
+use super::persistence::DatabaseState;
+use super::price_overrides::{
+    apply_to_events, load_overrides, reporting_currency, select_override,
+};
+use super::wallet_identity::{find_identity, load_identities};
+use crate::chains::units::{self, from_smallest_units, to_smallest_units, U256};
+use crate::chains::{ChainManagerState, FeeEstimate, WalletBalances};
+use crate::core::cost_basis::{self, AssetEvent, AssetEventKind, Disposal, MatchRule};
+
+/// ID given to the simulated disposal in the cost-basis run.
+const SIMULATED_EVENT_ID: &str = "simulated-transfer";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A transfer to simulate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferRequest {
+    /// Profile the sending wallet belongs to.
+    pub profile_id: String,
+    /// Chain the transfer is sent on.
+    pub chain_id: String,
+    /// Sending address.
+    pub from: String,
+    /// Receiving address.
+    pub to: String,
+    /// Token contract; `None` for the native currency.
+    pub token_address: Option<String>,
+    /// Amount in whole tokens, e.g. "1.5".
+    pub amount: String,
+    /// Asset symbol used to match lots in `asset_events`.
+    pub asset: Option<String>,
+    /// Fair value of one token in the reporting currency.
+    pub unit_price: Option<Decimal>,
+    /// Fair value of one unit of the native currency, for valuing the fee.
+    pub native_unit_price: Option<Decimal>,
+    /// Acquisition and disposal history of the asset, for lot matching.
+    #[serde(default)]
+    pub asset_events: Vec<AssetEvent>,
+}
+
+/// Whether the sending wallet can cover the transfer and its fee.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceCheck {
+    /// Balance of the transferred asset, in smallest units.
+    pub available: String,
+    /// Amount to send, in smallest units; includes the fee for native transfers.
+    pub required: String,
+    /// Native balance left for the fee, in smallest units.
+    pub native_available: String,
+    /// Whether both the amount and the fee are covered.
+    pub sufficient: bool,
+}
+
+/// A journal line the transfer would produce.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectedLine {
+    /// GL account number.
+    pub account_number: String,
+    /// GL account name.
+    pub account_name: String,
+    /// Debit amount in the reporting currency.
+    pub debit: Decimal,
+    /// Credit amount in the reporting currency.
+    pub credit: Decimal,
+    /// Line memo.
+    pub description: String,
+}
+
+/// Result of simulating a transfer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferSimulation {
+    /// Estimated network fee, if the chain supports estimation.
+    pub fee: Option<FeeEstimate>,
+    /// Balance sufficiency, if balances could be fetched.
+    pub balance: Option<BalanceCheck>,
+    /// Entity or known address the recipient resolves to.
+    pub counterparty: Option<AddressMatch>,
+    /// Whether the recipient is one of the profile's own accounts.
+    pub internal: bool,
+    /// Journal entry the transfer would produce.
+    pub ledger_entries: Vec<ProjectedLine>,
+    /// Lots the transfer would consume under the profile's tax settings.
+    pub lot_consumption: Vec<Disposal>,
+    /// Anything that could not be projected.
+    pub warnings: Vec<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Builds a projected journal line.
+fn line(number: &str, name: &str, debit: Decimal, credit: Decimal, memo: String) -> ProjectedLine {
+    ProjectedLine {
+        account_number: number.to_string(),
+        account_name: name.to_string(),
+        debit,
+        credit,
+        description: memo,
+    }
+}
+
+/// Decimals and raw balance of the transferred asset.
+fn asset_balance(balances: &WalletBalances, token_address: Option<&str>) -> Option<(u8, U256)> {
+    match token_address {
+        None => Some((
+            balances.native_balance.decimals,
+            raw_amount(&balances.native_balance.balance),
+        )),
+        Some(token) => balances
+            .token_balances
+            .iter()
+            .find(|t| t.token_address.eq_ignore_ascii_case(token))
+            .map(|t| (t.token_decimals, raw_amount(&t.balance))),
+    }
+}
+
+/// Parses a raw balance or fee, treating a malformed one as zero.
+fn raw_amount(value: &str) -> U256 {
+    units::parse_decimal(value).unwrap_or(U256::ZERO)
+}
+
+/// Journal lines for sending `cost_basis` worth of crypto valued at
+/// `fair_value` to an outside party.
+fn transfer_lines(
+    counterparty: Option<&AddressMatch>,
+    fair_value: Decimal,
+    cost_basis: Decimal,
+) -> Vec<ProjectedLine> {
+    let payee = counterparty
+        .map(|c| c.entity_name.clone())
+        .unwrap_or_else(|| "unknown recipient".to_string());
+    let mut lines = vec![
+        line(
+            "5000",
+            "Expenses",
+            fair_value,
+            Decimal::ZERO,
+            format!("Payment to {}", payee),
+        ),
+        line(
+            "1200",
+            "Crypto Assets",
+            Decimal::ZERO,
+            cost_basis,
+            "Crypto assets sent".to_string(),
+        ),
+    ];
+
+    let gain = fair_value - cost_basis;
+    if gain > Decimal::ZERO {
+        lines.push(line(
+            "4200",
+            "Trading Gains",
+            Decimal::ZERO,
+            gain,
+            "Gain on disposal".to_string(),
+        ));
+    } else if gain < Decimal::ZERO {
+        lines.push(line(
+            "5200",
+            "Trading Losses",
+            -gain,
+            Decimal::ZERO,
+            "Loss on disposal".to_string(),
+        ));
+    }
+    lines
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Estimates the fee, checks the balance, resolves the recipient, and
+/// projects the journal entry and lot consumption of a transfer without
+/// sending it.
+#[tauri::command]
+pub async fn simulate_transfer(
+    state: State<'_, DatabaseState>,
+    chains: State<'_, ChainManagerState>,
+    request: TransferRequest,
+) -> Result<TransferSimulation, String> {
+    let amount = Decimal::from_str(request.amount.trim())
+        .map_err(|_| format!("Invalid amount: {}", request.amount))?;
+    if amount <= Decimal::ZERO {
+        return Err("Amount must be positive".to_string());
+    }
+    let token = request.token_address.as_deref();
+    let mut warnings = Vec::new();
+
+    // Balances give the asset's decimals as well as what is available.
+    let manager = chains.read().await;
+    let balances = match manager.get_balances(&request.chain_id, &request.from).await {
+        Ok(balances) => Some(balances),
+        Err(e) => {
+            warnings.push(format!("Could not fetch balances: {}", e));
+            None
+        }
+    };
+    let held = balances.as_ref().and_then(|b| asset_balance(b, token));
+    if balances.is_some() && held.is_none() {
+        warnings.push("The sending wallet holds none of this token".to_string());
+    }
+    let decimals = held.map(|(decimals, _)| decimals).unwrap_or(18);
+    let raw_amount =
+        to_smallest_units(amount, decimals).ok_or_else(|| "Amount is too large".to_string())?;
+
+    let fee = match manager
+        .estimate_transfer_fee(
+            &request.chain_id,
+            &request.from,
+            &request.to,
+            token,
+            raw_amount,
+        )
+        .await
+    {
+        Ok(fee) => Some(fee),
+        Err(e) => {
+            warnings.push(format!("Could not estimate fee: {}", e));
+            None
+        }
+    };
+    drop(manager);
+    let raw_fee = fee
+        .as_ref()
+        .map(|f| raw_amount(&f.fee))
+        .unwrap_or(U256::ZERO);
+
+    let balance = balances.as_ref().map(|b| {
+        let native_available = raw_amount(&b.native_balance.balance);
+        let available = held.map(|(_, raw)| raw).unwrap_or(U256::ZERO);
+        let (required, sufficient) = match token {
+            None => {
+                let required = raw_amount.saturating_add(raw_fee);
+                (required, available >= required)
+            }
+            Some(_) => (
+                raw_amount,
+                available >= raw_amount && native_available >= raw_fee,
+            ),
+        };
+        BalanceCheck {
+            available: available.to_string(),
+            required: required.to_string(),
+            native_available: native_available.to_string(),
+            sufficient,
+        }
+    });
+
+    // Counterparty and internal transfer detection.
+    let counterparty = lookup_address_internal(
+        &state.pool,
+        &request.profile_id,
+        &request.to,
+        &request.chain_id,
+    )
+    .await?;
+    let identities = load_identities(&state.pool, &request.profile_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let internal = find_identity(&identities, &request.to).is_some();
+
+    // Manual price overrides take precedence over the supplied prices.
+    let overrides = load_overrides(&state.pool, &request.profile_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let currency = reporting_currency(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut asset_events = request.asset_events.clone();
+    apply_to_events(&overrides, &currency, &mut asset_events);
+    let override_price = request
+        .asset
+        .as_deref()
+        .and_then(|asset| select_override(&overrides, asset, &currency, Utc::now()))
+        .and_then(|o| Decimal::from_str(&o.price).ok());
+    if override_price.is_some() {
+        warnings.push("Valued at a manual price override".to_string());
+    }
+    let unit_price = override_price.or(request.unit_price);
+
+    // Lots consumed under the profile's tax settings.
+    let mut lot_consumption = Vec::new();
+    let fair_value = unit_price.map(|price| price * amount);
+    if !internal && !asset_events.is_empty() {
+        let settings = load_tax_settings(&state.pool, &request.profile_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let asset = request
+            .asset
+            .clone()
+            .or_else(|| asset_events.first().map(|e| e.asset.clone()))
+            .unwrap_or_default();
+
+        let mut events = asset_events;
+        events.push(AssetEvent {
+            id: SIMULATED_EVENT_ID.to_string(),
+            asset,
+            kind: AssetEventKind::Disposal,
+            timestamp: Utc::now(),
+            quantity: amount,
+            value: fair_value.unwrap_or_default(),
+            fee: Decimal::ZERO,
+            income_source: None,
+            price_override: None,
+            lot_ids: Vec::new(),
+        });
+        let report = cost_basis::calculate(
+            &events,
+            &settings.jurisdiction.rules(),
+            settings.cost_basis_method,
+        );
+        warnings.extend(report.price_overrides.iter().map(|n| n.note.clone()));
+        lot_consumption = report
+            .disposals
+            .into_iter()
+            .filter(|d| d.event_id == SIMULATED_EVENT_ID)
+            .collect();
+        if lot_consumption
+            .iter()
+            .any(|d| d.rule == MatchRule::Unmatched)
+        {
+            warnings.push("Not enough lots to cover the transfer".to_string());
+        }
+    }
+
+    // Journal entry.
+    let mut ledger_entries = Vec::new();
+    if !internal {
+        let cost_basis: Option<Decimal> = (!lot_consumption.is_empty())
+            .then(|| lot_consumption.iter().map(|d| d.cost_basis).sum());
+        match (fair_value, cost_basis) {
+            (Some(value), Some(cost)) => {
+                ledger_entries.extend(transfer_lines(counterparty.as_ref(), value, cost))
+            }
+            (Some(value), None) | (None, Some(value)) => {
+                if fair_value.is_none() {
+                    warnings.push("No unit price given; valued at cost basis".to_string());
+                }
+                ledger_entries.extend(transfer_lines(counterparty.as_ref(), value, value));
+            }
+            (None, None) => {
+                warnings.push("No unit price or lots given; transfer not valued".to_string())
+            }
+        }
+    }
+    if !raw_fee.is_zero() {
+        let native_decimals = balances
+            .as_ref()
+            .map(|b| b.native_balance.decimals)
+            .unwrap_or(18);
+        let fee_value = request
+            .native_unit_price
+            .zip(from_smallest_units(raw_fee, native_decimals))
+            .map(|(price, fee)| price * fee);
+        match fee_value {
+            Some(value) => {
+                ledger_entries.push(line(
+                    "5100",
+                    "Network Fees",
+                    value,
+                    Decimal::ZERO,
+                    "Network/gas fee".to_string(),
+                ));
+                ledger_entries.push(line(
+                    "1200",
+                    "Crypto Assets",
+                    Decimal::ZERO,
+                    value,
+                    "Fee paid from crypto assets".to_string(),
+                ));
+            }
+            None => warnings.push("No native price given; fee not valued".to_string()),
+        }
+    }
+
+    Ok(TransferSimulation {
+        fee,
+        balance,
+        counterparty,
+        internal,
+        ledger_entries,
+        lot_consumption,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::{NativeBalance, TokenBalance};
+
+    #[test]
+    fn test_asset_balance_beyond_u128() {
+        let huge = U256::from(u128::MAX) * U256::from(1_000u64);
+        let balances = WalletBalances {
+            chain_id: "ethereum".to_string(),
+            address: "0xabc".to_string(),
+            native_balance: NativeBalance {
+                symbol: "ETH".to_string(),
+                decimals: 18,
+                balance: "not-a-number".to_string(),
+                balance_formatted: "0".to_string(),
+            },
+            token_balances: vec![TokenBalance {
+                token_address: "0xToken".to_string(),
+                token_symbol: Some("BIG".to_string()),
+                token_name: None,
+                token_decimals: 18,
+                balance: huge.to_string(),
+                balance_formatted: String::new(),
+                extensions: None,
+            }],
+            total_value_usd: None,
+            fetched_at: 0,
+        };
+
+        assert_eq!(asset_balance(&balances, None), Some((18, U256::ZERO)));
+        assert_eq!(asset_balance(&balances, Some("0xtoken")), Some((18, huge)));
+        assert_eq!(asset_balance(&balances, Some("0xother")), None);
+    }
+
+    #[test]
+    fn test_transfer_lines_balance() {
+        let lines = transfer_lines(None, Decimal::from(150), Decimal::from(100));
+        let debits: Decimal = lines.iter().map(|l| l.debit).sum();
+        let credits: Decimal = lines.iter().map(|l| l.credit).sum();
+        assert_eq!(debits, credits);
+        assert_eq!(lines[2].account_number, "4200");
+
+        let loss = transfer_lines(None, Decimal::from(80), Decimal::from(100));
+        assert_eq!(loss[2].account_number, "5200");
+        assert_eq!(loss[2].debit, Decimal::from(20));
+    }
+}
@@ -0,0 +1,535 @@
+//! Unrealized gains on current holdings.
+//!
+//! Holdings are the on-chain balances of a profile's wallets, summed by
+//! asset. Each asset's open lots, from running the cost-basis engine over
+//! the supplied events under the profile's tax settings, are valued at the
+//! current price to give its unrealized gain, split by holding term as if
+//! the lots were sold today. Quantities held without a matching lot, and
+//! lots for quantities no longer held, are reported rather than valued.
+
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use super::cost_basis::load_tax_settings;
+use super::permissions::Permission;
+use super::persistence::DatabaseState;
+use super::price_feeds::{PriceQuote, PriceService};
+use super::price_ids::PriceIds;
+use super::price_overrides::{
+    apply_to_events, load_overrides, reporting_currency, select_override, PriceOverride,
+};
+use super::price_sources::{price_sources, PriceSource};
+use super::profile_scope::{authorize_profile, profile_wallets};
+use super::token_spam::SpamFilter;
+use crate::chains::units::{from_smallest_units, parse_decimal};
+use crate::chains::{ChainManagerState, WalletBalances};
+use crate::core::auth_state::AuthState;
+use crate::core::cost_basis::{
+    self, holding_term, AssetEvent, HoldingTerm, JurisdictionRules, OpenLot,
+};
+use crate::core::currency::round_fiat;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Current price of an asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetPrice {
+    /// Price of one unit in the reporting currency.
+    pub price: Decimal,
+    /// Provider that supplied the price, or the override source.
+    pub source: String,
+    /// Endpoint the price was read from, or the override's record.
+    pub endpoint: String,
+    /// When the price was retrieved.
+    pub retrieved_at: DateTime<Utc>,
+}
+
+impl AssetPrice {
+    /// The price of a quote, if it is a valid decimal.
+    fn from_quote(quote: &PriceQuote) -> Option<Self> {
+        Some(Self {
+            price: Decimal::from_str(&quote.price).ok()?,
+            source: quote.provider.clone(),
+            endpoint: quote.endpoint.clone(),
+            retrieved_at: quote.retrieved_at,
+        })
+    }
+}
+
+/// Unrealized gain on one asset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnrealizedPosition {
+    /// Asset symbol.
+    pub asset: String,
+    /// Quantity held across the profile's wallets.
+    pub quantity: Decimal,
+    /// Quantity covered by open lots.
+    pub lot_quantity: Decimal,
+    /// Cost basis of the open lots.
+    pub cost_basis: Decimal,
+    /// Current price, if one was found.
+    pub price: Option<AssetPrice>,
+    /// Value of the quantity held at the current price.
+    pub market_value: Option<Decimal>,
+    /// Value of the open lots less their cost basis.
+    pub unrealized_gain: Option<Decimal>,
+    /// Part of the unrealized gain on lots held up to the long-term threshold.
+    pub short_term_gain: Option<Decimal>,
+    /// Part of the unrealized gain on lots held longer than the threshold.
+    pub long_term_gain: Option<Decimal>,
+    /// Quantity held beyond the open lots; negative when the lots cover
+    /// more than is held.
+    pub untracked_quantity: Decimal,
+}
+
+/// Unrealized gains across a profile's holdings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnrealizedGainsReport {
+    /// Currency values are reported in.
+    pub currency: String,
+    /// One entry per asset held or with open lots, by asset.
+    pub positions: Vec<UnrealizedPosition>,
+    /// Total cost basis of the open lots.
+    pub total_cost_basis: Decimal,
+    /// Total market value of priced holdings.
+    pub total_market_value: Decimal,
+    /// Total unrealized gain of priced lots.
+    pub total_unrealized_gain: Decimal,
+    /// Total short-term unrealized gain.
+    pub short_term_gain: Decimal,
+    /// Total long-term unrealized gain.
+    pub long_term_gain: Decimal,
+    /// Where the prices came from.
+    pub price_sources: Vec<PriceSource>,
+    /// Wallets whose balances could not be fetched, as `chain:address`.
+    pub failed_wallets: Vec<String>,
+    /// Anything that could not be valued or matched.
+    pub warnings: Vec<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Key assets are matched on across balances, lots, and prices.
+fn asset_key(symbol: &str) -> String {
+    symbol.trim().to_uppercase()
+}
+
+/// Adds the native and non-spam token balances of a wallet to `holdings`,
+/// in whole units.
+fn add_balances(holdings: &mut BTreeMap<String, Decimal>, balances: &WalletBalances) {
+    let native = &balances.native_balance;
+    let tokens = balances
+        .token_balances
+        .iter()
+        .filter_map(|t| Some((t.token_symbol.as_deref()?, t.token_decimals, &t.balance)));
+
+    for (symbol, decimals, raw) in
+        std::iter::once((native.symbol.as_str(), native.decimals, &native.balance)).chain(tokens)
+    {
+        let Some(quantity) = parse_decimal(raw)
+            .ok()
+            .and_then(|raw| from_smallest_units(raw, decimals))
+        else {
+            continue;
+        };
+        if !quantity.is_zero() {
+            *holdings.entry(asset_key(symbol)).or_default() += quantity;
+        }
+    }
+}
+
+/// Records the coin IDs the profile's table has for a wallet's native
+/// currency and tokens, keyed by symbol.
+fn add_coin_ids(
+    coin_ids: &mut HashMap<String, String>,
+    price_ids: &PriceIds,
+    chain: &str,
+    balances: &WalletBalances,
+) {
+    if let Some(id) = price_ids.coin_id(chain, None) {
+        coin_ids.insert(asset_key(&balances.native_balance.symbol), id.to_string());
+    }
+    for token in &balances.token_balances {
+        let (Some(symbol), Some(id)) = (
+            token.token_symbol.as_deref(),
+            price_ids.coin_id(chain, Some(&token.token_address)),
+        ) else {
+            continue;
+        };
+        coin_ids.insert(asset_key(symbol), id.to_string());
+    }
+}
+
+/// Current price of `asset` from an override on the asset or its coin ID,
+/// skipping overrides whose price isn't a valid decimal.
+fn override_quote(
+    overrides: &[PriceOverride],
+    asset: &str,
+    coin_id: Option<&str>,
+    currency: &str,
+    at: DateTime<Utc>,
+) -> Option<PriceQuote> {
+    select_override(overrides, asset, currency, at)
+        .or_else(|| coin_id.and_then(|id| select_override(overrides, id, currency, at)))
+        .filter(|o| Decimal::from_str(&o.price).is_ok())
+        .map(PriceOverride::quote)
+}
+
+/// Values each asset's holdings and open lots at its price, as of `at`.
+fn value_positions(
+    holdings: &BTreeMap<String, Decimal>,
+    lots: &[OpenLot],
+    prices: &HashMap<String, AssetPrice>,
+    rules: &JurisdictionRules,
+    currency: &str,
+    at: DateTime<Utc>,
+) -> Vec<UnrealizedPosition> {
+    let mut lots_by_asset: BTreeMap<String, Vec<&OpenLot>> = BTreeMap::new();
+    for lot in lots.iter().filter(|l| l.quantity > Decimal::ZERO) {
+        lots_by_asset
+            .entry(asset_key(&lot.asset))
+            .or_default()
+            .push(lot);
+    }
+    let mut assets: Vec<&String> = holdings.keys().chain(lots_by_asset.keys()).collect();
+    assets.sort();
+    assets.dedup();
+
+    assets
+        .into_iter()
+        .map(|asset| {
+            let quantity = holdings.get(asset).copied().unwrap_or_default();
+            let asset_lots = lots_by_asset.get(asset).map(Vec::as_slice).unwrap_or(&[]);
+            let lot_quantity: Decimal = asset_lots.iter().map(|l| l.quantity).sum();
+            let cost_basis: Decimal = asset_lots.iter().map(|l| l.cost_basis).sum();
+            let price = prices.get(asset).cloned();
+
+            let gains = price.as_ref().map(|p| {
+                let (mut short, mut long) = (Decimal::ZERO, Decimal::ZERO);
+                let mut total = Decimal::ZERO;
+                for lot in asset_lots {
+                    let gain = lot.quantity * p.price - lot.cost_basis;
+                    total += gain;
+                    match holding_term(rules, lot.acquired_at, at) {
+                        HoldingTerm::Short => short += gain,
+                        HoldingTerm::Long => long += gain,
+                        HoldingTerm::NotApplicable => {}
+                    }
+                }
+                (
+                    round_fiat(total, currency),
+                    round_fiat(short, currency),
+                    round_fiat(long, currency),
+                )
+            });
+
+            UnrealizedPosition {
+                asset: asset.clone(),
+                quantity,
+                lot_quantity,
+                cost_basis: round_fiat(cost_basis, currency),
+                market_value: price
+                    .as_ref()
+                    .map(|p| round_fiat(quantity * p.price, currency)),
+                unrealized_gain: gains.map(|g| g.0),
+                short_term_gain: gains.map(|g| g.1),
+                long_term_gain: gains.map(|g| g.2),
+                untracked_quantity: quantity - lot_quantity,
+                price,
+            }
+        })
+        .collect()
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Matches a profile's current holdings to the open lots left by `events`
+/// and to current prices, returning each asset's cost basis, market value,
+/// and unrealized gain split by holding term.
+///
+/// `coin_ids` maps asset symbols to price feed coin IDs, e.g. `ETH` to
+/// `ethereum`. Assets not in it are looked up in the profile's coin ID
+/// table, by contract and then by symbol; assets in neither are priced only
+/// from overrides.
+#[tauri::command]
+pub async fn get_unrealized_gains(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    chains: State<'_, ChainManagerState>,
+    token: String,
+    profile_id: String,
+    mut events: Vec<AssetEvent>,
+    coin_ids: HashMap<String, String>,
+) -> Result<UnrealizedGainsReport, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let now = Utc::now();
+    let mut warnings = Vec::new();
+
+    // Holdings across the profile's wallets.
+    let wallets = profile_wallets(&state.pool, &profile_id).await?;
+    let filter = SpamFilter::load(&state.pool, Some(&profile_id))
+        .await
+        .map_err(|e| e.to_string())?;
+    let price_ids = PriceIds::load(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut holdings = BTreeMap::new();
+    let mut contract_ids = HashMap::new();
+    let mut failed_wallets = Vec::new();
+    let manager = chains.read().await;
+    for wallet in &wallets {
+        match manager.get_balances(&wallet.chain, &wallet.address).await {
+            Ok(mut balances) => {
+                filter.filter_balances(&mut balances);
+                add_balances(&mut holdings, &balances);
+                add_coin_ids(&mut contract_ids, &price_ids, &wallet.chain, &balances);
+            }
+            Err(_) => failed_wallets.push(format!("{}:{}", wallet.chain, wallet.address)),
+        }
+    }
+    drop(manager);
+
+    // Open lots under the profile's tax settings.
+    let overrides = load_overrides(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let currency = reporting_currency(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    apply_to_events(&overrides, &currency, &mut events);
+    let settings = load_tax_settings(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let rules = settings.jurisdiction.rules();
+    let report = cost_basis::calculate(&events, &rules, settings.cost_basis_method);
+    warnings.extend(report.warnings);
+
+    // Current prices, overrides first.
+    let mut ids = price_ids.by_symbol().clone();
+    ids.extend(contract_ids);
+    ids.extend(
+        coin_ids
+            .into_iter()
+            .map(|(asset, id)| (asset_key(&asset), id)),
+    );
+    let coin_ids = ids;
+    let mut assets: Vec<String> = holdings.keys().cloned().collect();
+    assets.extend(report.open_lots.iter().map(|l| asset_key(&l.asset)));
+    assets.sort();
+    assets.dedup();
+
+    let mut quotes_used = HashMap::new();
+    let mut to_fetch = Vec::new();
+    for asset in &assets {
+        let coin_id = coin_ids.get(asset).map(String::as_str);
+        match override_quote(&overrides, asset, coin_id, &currency, now) {
+            Some(quote) => {
+                quotes_used.insert(asset.clone(), quote);
+            }
+            None => match coin_id {
+                Some(id) => to_fetch.push((asset.clone(), id)),
+                None => warnings.push(format!("No coin ID for {}; not valued", asset)),
+            },
+        }
+    }
+    if !to_fetch.is_empty() {
+        let ids: Vec<&str> = to_fetch.iter().map(|(_, id)| *id).collect();
+        let quotes = PriceService::shared()?
+            .current_prices(&ids, &currency.to_lowercase())
+            .await;
+        match quotes {
+            Ok(quotes) => {
+                for (asset, id) in &to_fetch {
+                    match quotes
+                        .get(*id)
+                        .filter(|q| Decimal::from_str(&q.price).is_ok())
+                    {
+                        Some(quote) => {
+                            quotes_used.insert(asset.clone(), quote.clone());
+                        }
+                        None => warnings.push(format!("No price found for {}", asset)),
+                    }
+                }
+            }
+            Err(e) => warnings.push(format!("Could not fetch prices: {}", e)),
+        }
+    }
+    let prices: HashMap<String, AssetPrice> = quotes_used
+        .iter()
+        .filter_map(|(asset, quote)| Some((asset.clone(), AssetPrice::from_quote(quote)?)))
+        .collect();
+
+    let positions = value_positions(
+        &holdings,
+        &report.open_lots,
+        &prices,
+        &rules,
+        &currency,
+        now,
+    );
+    for position in positions.iter().filter(|p| !p.untracked_quantity.is_zero()) {
+        warnings.push(if position.untracked_quantity > Decimal::ZERO {
+            format!(
+                "{} {} held without a matching lot",
+                position.untracked_quantity, position.asset
+            )
+        } else {
+            format!(
+                "Lots cover {} {} more than is held",
+                -position.untracked_quantity, position.asset
+            )
+        });
+    }
+
+    Ok(UnrealizedGainsReport {
+        total_cost_basis: positions.iter().map(|p| p.cost_basis).sum(),
+        total_market_value: positions.iter().filter_map(|p| p.market_value).sum(),
+        total_unrealized_gain: positions.iter().filter_map(|p| p.unrealized_gain).sum(),
+        short_term_gain: positions.iter().filter_map(|p| p.short_term_gain).sum(),
+        long_term_gain: positions.iter().filter_map(|p| p.long_term_gain).sum(),
+        price_sources: price_sources(
+            quotes_used
+                .iter()
+                .map(|(asset, quote)| (asset.as_str(), quote)),
+        ),
+        currency,
+        positions,
+        failed_wallets,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::{NativeBalance, TokenBalance};
+    use crate::core::cost_basis::Jurisdiction;
+    use chrono::TimeZone;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn lot(asset: &str, acquired: DateTime<Utc>, quantity: &str, cost: &str) -> OpenLot {
+        OpenLot {
+            asset: asset.to_string(),
+            acquisition_id: Some(format!("{}-{}", asset, acquired.timestamp())),
+            acquired_at: Some(acquired),
+            quantity: dec(quantity),
+            cost_basis: dec(cost),
+        }
+    }
+
+    fn price(value: &str) -> AssetPrice {
+        AssetPrice {
+            price: dec(value),
+            source: "CoinGecko".to_string(),
+            endpoint: "https://api.coingecko.com/api/v3/simple/price".to_string(),
+            retrieved_at: Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_add_balances_sums_by_symbol() {
+        let balances = WalletBalances {
+            chain_id: "ethereum".to_string(),
+            address: "0xabc".to_string(),
+            native_balance: NativeBalance {
+                symbol: "ETH".to_string(),
+                decimals: 18,
+                balance: "1500000000000000000".to_string(),
+                balance_formatted: "1.5".to_string(),
+            },
+            token_balances: vec![
+                TokenBalance {
+                    token_address: "0xa0b8".to_string(),
+                    token_symbol: Some("usdc".to_string()),
+                    token_name: None,
+                    token_decimals: 6,
+                    balance: "2500000".to_string(),
+                    balance_formatted: "2.5".to_string(),
+                    extensions: None,
+                },
+                TokenBalance {
+                    token_address: "0xdead".to_string(),
+                    token_symbol: None,
+                    token_name: None,
+                    token_decimals: 18,
+                    balance: "1".to_string(),
+                    balance_formatted: String::new(),
+                    extensions: None,
+                },
+            ],
+            total_value_usd: None,
+            fetched_at: 0,
+        };
+
+        let mut holdings = BTreeMap::new();
+        add_balances(&mut holdings, &balances);
+        add_balances(&mut holdings, &balances);
+        assert_eq!(holdings.len(), 2);
+        assert_eq!(holdings["ETH"], dec("3"));
+        assert_eq!(holdings["USDC"], dec("5"));
+    }
+
+    #[test]
+    fn test_value_positions_splits_terms() {
+        let now = Utc.with_ymd_and_hms(2026, 6, 30, 0, 0, 0).unwrap();
+        let old = Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap();
+        let recent = Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap();
+        let holdings = BTreeMap::from([
+            ("ETH".to_string(), dec("3")),
+            ("DOT".to_string(), dec("100")),
+        ]);
+        let lots = vec![
+            lot("eth", old, "2", "3000"),
+            lot("ETH", recent, "1", "3500"),
+            lot("SOL", recent, "4", "600"),
+        ];
+        let prices = HashMap::from([("ETH".to_string(), price("3000"))]);
+        let rules = Jurisdiction::Us.rules();
+
+        let positions = value_positions(&holdings, &lots, &prices, &rules, "USD", now);
+        let assets: Vec<&str> = positions.iter().map(|p| p.asset.as_str()).collect();
+        assert_eq!(assets, vec!["DOT", "ETH", "SOL"]);
+
+        let eth = &positions[1];
+        assert_eq!(eth.lot_quantity, dec("3"));
+        assert_eq!(eth.cost_basis, dec("6500"));
+        assert_eq!(eth.market_value, Some(dec("9000")));
+        assert_eq!(eth.unrealized_gain, Some(dec("2500")));
+        assert_eq!(eth.long_term_gain, Some(dec("3000")));
+        assert_eq!(eth.short_term_gain, Some(dec("-500")));
+        assert!(eth.untracked_quantity.is_zero());
+
+        let dot = &positions[0];
+        assert_eq!(dot.untracked_quantity, dec("100"));
+        assert_eq!(dot.market_value, None);
+
+        let sol = &positions[2];
+        assert_eq!(sol.quantity, Decimal::ZERO);
+        assert_eq!(sol.untracked_quantity, dec("-4"));
+        assert_eq!(sol.unrealized_gain, None);
+    }
+}
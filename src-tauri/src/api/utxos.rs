@@ -0,0 +1,441 @@
+//! Coin control for Bitcoin wallets.
+//!
+//! Lists a wallet's unspent outputs with their age, value, and the address
+//! holding them, derived from the wallet's xPub where it has one, and keeps
+//! a label per output (e.g. "donation batch March").
+//!
+//! Each output is linked to the acquisition lot it came from, the funding
+//! transaction. A spending transaction is linked to the lots of the outputs
+//! it consumed, which disposals pass as `lot_ids` so the cost-basis engine
+//! matches the coins actually spent instead of applying the lot selection
+//! method.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, Wallet};
+use super::profile_scope::authorize_wallet;
+use crate::chains::bitcoin::{self, BitcoinAdapter, BitcoinTransaction, BitcoinUtxo};
+use crate::core::auth_state::AuthState;
+
+/// Receiving and change addresses derived from an xPub wallet, the
+/// standard gap limit.
+const XPUB_GAP_LIMIT: u32 = 20;
+
+/// Longest label accepted.
+const MAX_LABEL_LEN: usize = 200;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// An unspent output of a wallet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletUtxo {
+    /// Funding transaction.
+    pub txid: String,
+    /// Output index in the funding transaction.
+    pub vout: u32,
+    /// Value in satoshis.
+    pub value: u64,
+    /// Address holding the output.
+    pub address: String,
+    /// Path of the address relative to the wallet's xPub, e.g. `0/3`.
+    pub derivation_path: Option<String>,
+    /// Whether the funding transaction is confirmed.
+    pub confirmed: bool,
+    /// Block the funding transaction confirmed in.
+    pub block_height: Option<u64>,
+    /// When the output was received; `None` while unconfirmed.
+    pub received_at: Option<DateTime<Utc>>,
+    /// Whole days since the output was received.
+    pub age_days: Option<i64>,
+    /// Coin-control label.
+    pub label: Option<String>,
+    /// Acquisition lot the output belongs to: the funding transaction.
+    pub lot_id: String,
+}
+
+/// A label on one output.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct UtxoLabel {
+    /// Wallet holding the output.
+    pub wallet_id: String,
+    /// Funding transaction.
+    pub txid: String,
+    /// Output index.
+    pub vout: i64,
+    /// Label text.
+    pub label: String,
+    /// User who set the label.
+    pub updated_by: Option<String>,
+    /// When the label was set.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The lots a spending transaction consumed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpentLots {
+    /// Spending transaction; the disposal's event ID.
+    pub txid: String,
+    /// Funding transactions of the wallet's outputs it spent.
+    pub lot_ids: Vec<String>,
+    /// Satoshis of the wallet's outputs it spent.
+    pub spent: u64,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// The wallet's addresses with their xPub derivation paths; a plain address
+/// wallet has just its own.
+pub(crate) fn wallet_addresses(wallet: &Wallet) -> Result<Vec<(String, Option<String>)>, String> {
+    if !bitcoin::is_xpub(&wallet.address) {
+        return Ok(vec![(wallet.address.clone(), None)]);
+    }
+    let portfolio = bitcoin::derive_addresses(&wallet.address, XPUB_GAP_LIMIT, XPUB_GAP_LIMIT)
+        .map_err(|e| e.to_string())?;
+    Ok(portfolio
+        .receiving_addresses
+        .into_iter()
+        .chain(portfolio.change_addresses)
+        .map(|a| (a.address, Some(a.derivation_path)))
+        .collect())
+}
+
+fn adapter_for(wallet: &Wallet) -> Result<BitcoinAdapter, String> {
+    BitcoinAdapter::from_network(&wallet.chain)
+        .map_err(|_| format!("Wallet is not on a Bitcoin network: {}", wallet.chain))
+}
+
+async fn load_labels(
+    pool: &SqlitePool,
+    wallet_id: &str,
+) -> Result<HashMap<(String, u32), String>, String> {
+    let labels: Vec<UtxoLabel> = sqlx::query_as("SELECT * FROM utxo_labels WHERE wallet_id = ?")
+        .bind(wallet_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(labels
+        .into_iter()
+        .map(|l| ((l.txid, l.vout as u32), l.label))
+        .collect())
+}
+
+/// Describes an output of `address` as of `now`.
+fn to_wallet_utxo(
+    utxo: &BitcoinUtxo,
+    address: &str,
+    derivation_path: Option<&str>,
+    labels: &HashMap<(String, u32), String>,
+    now: DateTime<Utc>,
+) -> WalletUtxo {
+    let received_at = utxo
+        .status
+        .block_time
+        .and_then(|t| Utc.timestamp_opt(t, 0).single());
+    WalletUtxo {
+        txid: utxo.txid.clone(),
+        vout: utxo.vout,
+        value: utxo.value,
+        address: address.to_string(),
+        derivation_path: derivation_path.map(str::to_string),
+        confirmed: utxo.status.confirmed,
+        block_height: utxo.status.block_height,
+        received_at,
+        age_days: received_at.map(|t| (now - t).num_days()),
+        label: labels.get(&(utxo.txid.clone(), utxo.vout)).cloned(),
+        lot_id: utxo.txid.clone(),
+    }
+}
+
+/// The lots each transaction spent from `addresses`, for transactions that
+/// spent any, oldest first.
+fn spent_lots(transactions: &[BitcoinTransaction], addresses: &HashSet<String>) -> Vec<SpentLots> {
+    let mut seen = HashSet::new();
+    let mut spends: Vec<(Option<i64>, SpentLots)> = Vec::new();
+    for tx in transactions {
+        // The same transaction is returned for each address it touches
+        if !seen.insert(tx.txid.as_str()) {
+            continue;
+        }
+        let ours: Vec<_> = tx
+            .inputs
+            .iter()
+            .filter(|i| i.address.as_ref().is_some_and(|a| addresses.contains(a)))
+            .collect();
+        if ours.is_empty() {
+            continue;
+        }
+        let lot_ids: BTreeSet<String> = ours.iter().map(|i| i.prev_txid.clone()).collect();
+        spends.push((
+            tx.timestamp,
+            SpentLots {
+                txid: tx.txid.clone(),
+                lot_ids: lot_ids.into_iter().collect(),
+                spent: ours.iter().map(|i| i.value).sum(),
+            },
+        ));
+    }
+    // Unconfirmed spends sort last
+    spends.sort_by_key(|(timestamp, s)| (timestamp.unwrap_or(i64::MAX), s.txid.clone()));
+    spends.into_iter().map(|(_, s)| s).collect()
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Lists a Bitcoin wallet's unspent outputs, oldest first, with their labels.
+///
+/// Requires any role on the wallet's profile.
+#[tauri::command]
+pub async fn list_wallet_utxos(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    wallet_id: String,
+) -> Result<Vec<WalletUtxo>, String> {
+    let (_, wallet) = authorize_wallet(
+        &state.pool,
+        &auth,
+        &token,
+        &wallet_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let adapter = adapter_for(&wallet)?;
+    let labels = load_labels(&state.pool, &wallet.id).await?;
+    let now = Utc::now();
+
+    let mut utxos = Vec::new();
+    for (address, path) in wallet_addresses(&wallet)? {
+        let outputs = adapter
+            .fetch_utxos(&address)
+            .await
+            .map_err(|e| e.to_string())?;
+        utxos.extend(
+            outputs
+                .iter()
+                .map(|u| to_wallet_utxo(u, &address, path.as_deref(), &labels, now)),
+        );
+    }
+    utxos.sort_by(|a, b| {
+        a.block_height
+            .unwrap_or(u64::MAX)
+            .cmp(&b.block_height.unwrap_or(u64::MAX))
+            .then(a.txid.cmp(&b.txid))
+            .then(a.vout.cmp(&b.vout))
+    });
+    Ok(utxos)
+}
+
+/// Sets or, with an empty or missing label, clears the label on an output.
+///
+/// Requires the owner, admin, or preparer role on the wallet's profile.
+#[tauri::command]
+pub async fn set_utxo_label(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    wallet_id: String,
+    txid: String,
+    vout: u32,
+    label: Option<String>,
+) -> Result<Option<UtxoLabel>, String> {
+    let (user_id, _) =
+        authorize_wallet(&state.pool, &auth, &token, &wallet_id, Permission::EditTags).await?;
+    let label = label
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty());
+
+    let Some(label) = label else {
+        sqlx::query("DELETE FROM utxo_labels WHERE wallet_id = ? AND txid = ? AND vout = ?")
+            .bind(&wallet_id)
+            .bind(&txid)
+            .bind(vout as i64)
+            .execute(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(None);
+    };
+    if label.chars().count() > MAX_LABEL_LEN {
+        return Err(format!(
+            "Label must be at most {} characters",
+            MAX_LABEL_LEN
+        ));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO utxo_labels (wallet_id, txid, vout, label, updated_by, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT(wallet_id, txid, vout) DO UPDATE SET
+            label = excluded.label,
+            updated_by = excluded.updated_by,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(&wallet_id)
+    .bind(&txid)
+    .bind(vout as i64)
+    .bind(&label)
+    .bind(&user_id)
+    .bind(Utc::now())
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    sqlx::query_as("SELECT * FROM utxo_labels WHERE wallet_id = ? AND txid = ? AND vout = ?")
+        .bind(&wallet_id)
+        .bind(&txid)
+        .bind(vout as i64)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Links each of a Bitcoin wallet's spending transactions to the lots of the
+/// outputs it consumed, for passing as the disposals' `lot_ids`.
+///
+/// Requires any role on the wallet's profile.
+#[tauri::command]
+pub async fn get_utxo_spent_lots(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    wallet_id: String,
+) -> Result<Vec<SpentLots>, String> {
+    let (_, wallet) = authorize_wallet(
+        &state.pool,
+        &auth,
+        &token,
+        &wallet_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let adapter = adapter_for(&wallet)?;
+    let addresses = wallet_addresses(&wallet)?;
+
+    let mut transactions = Vec::new();
+    for (address, _) in &addresses {
+        transactions.extend(
+            adapter
+                .fetch_transactions(address, None)
+                .await
+                .map_err(|e| e.to_string())?,
+        );
+    }
+    let addresses: HashSet<String> = addresses.into_iter().map(|(a, _)| a).collect();
+    Ok(spent_lots(&transactions, &addresses))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::bitcoin::types::{BitcoinTxInput, BitcoinTxStatus};
+
+    const ADDRESS: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+    const OTHER: &str = "bc1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh";
+
+    fn input(address: &str, prev_txid: &str, value: u64) -> BitcoinTxInput {
+        BitcoinTxInput {
+            address: Some(address.to_string()),
+            value,
+            prev_txid: prev_txid.to_string(),
+            prev_vout: 0,
+        }
+    }
+
+    fn transaction(
+        txid: &str,
+        timestamp: Option<i64>,
+        inputs: Vec<BitcoinTxInput>,
+    ) -> BitcoinTransaction {
+        BitcoinTransaction {
+            txid: txid.to_string(),
+            block_height: timestamp.map(|_| 840_000),
+            timestamp,
+            inputs,
+            outputs: Vec::new(),
+            fee: 500,
+            confirmations: 1,
+            is_coinbase: false,
+            total_input: 0,
+            total_output: 0,
+            rbf_signaled: false,
+        }
+    }
+
+    #[test]
+    fn test_to_wallet_utxo_age_and_label() {
+        let utxo = BitcoinUtxo {
+            txid: "f1".to_string(),
+            vout: 1,
+            value: 150_000,
+            status: BitcoinTxStatus {
+                confirmed: true,
+                block_height: Some(830_000),
+                block_hash: None,
+                block_time: Some(1_709_251_200), // 2024-03-01
+            },
+        };
+        let labels = HashMap::from([(("f1".to_string(), 1), "donation batch March".to_string())]);
+        let now = Utc.with_ymd_and_hms(2024, 3, 31, 12, 0, 0).unwrap();
+
+        let described = to_wallet_utxo(&utxo, ADDRESS, Some("0/3"), &labels, now);
+        assert_eq!(described.age_days, Some(30));
+        assert_eq!(described.label.as_deref(), Some("donation batch March"));
+        assert_eq!(described.derivation_path.as_deref(), Some("0/3"));
+        assert_eq!(described.lot_id, "f1");
+
+        let unconfirmed = BitcoinUtxo {
+            status: BitcoinTxStatus {
+                confirmed: false,
+                block_height: None,
+                block_hash: None,
+                block_time: None,
+            },
+            vout: 0,
+            ..utxo
+        };
+        let described = to_wallet_utxo(&unconfirmed, ADDRESS, None, &labels, now);
+        assert_eq!(described.age_days, None);
+        assert_eq!(described.label, None);
+    }
+
+    #[test]
+    fn test_spent_lots_follow_inputs() {
+        let addresses = HashSet::from([ADDRESS.to_string()]);
+        let spend = transaction(
+            "s1",
+            Some(1_710_000_000),
+            vec![
+                input(ADDRESS, "f1", 100_000),
+                input(ADDRESS, "f2", 50_000),
+                input(ADDRESS, "f1", 25_000),
+                input(OTHER, "x9", 10_000),
+            ],
+        );
+        let receive = transaction("r1", Some(1_700_000_000), vec![input(OTHER, "x1", 5_000)]);
+        let pending = transaction("s2", None, vec![input(ADDRESS, "f3", 1_000)]);
+
+        // Each transaction appears once per address it touches
+        let lots = spent_lots(&[pending, spend.clone(), receive, spend], &addresses);
+        assert_eq!(lots.len(), 2);
+        assert_eq!(lots[0].txid, "s1");
+        assert_eq!(lots[0].lot_ids, vec!["f1", "f2"]);
+        assert_eq!(lots[0].spent, 175_000);
+        assert_eq!(lots[1].txid, "s2");
+    }
+}
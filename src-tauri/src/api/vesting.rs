@@ -0,0 +1,634 @@
+//! Vesting schedules for token grants.
+//!
+//! A schedule records a grant vesting linearly to one of a profile's wallets
+//! between a start and end date, with an optional cliff before which nothing
+//! vests. When the grant is held by a vesting contract, claims are the
+//! wallet's stored transfers from that contract. The tracker reports vested,
+//! unvested, claimed, and claimable amounts, and projects the unlocks to
+//! come month by month.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Months, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::balance_history::load_wallet_token_transfers;
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction};
+use super::profile_scope::{authorize_profile, authorize_wallet};
+use super::statement_export::{parse_amount, parse_period_bound};
+use crate::chains::TokenTransfer;
+use crate::core::auth_state::AuthState;
+
+/// Months of unlocks projected when none is given.
+const DEFAULT_PROJECTION_MONTHS: u32 = 12;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A stored vesting schedule.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct VestingSchedule {
+    /// Unique identifier for the schedule.
+    pub id: String,
+    /// Profile the grant belongs to.
+    pub profile_id: String,
+    /// Wallet the grant vests to.
+    pub wallet_id: String,
+    /// Display name, e.g. the grant or the team member.
+    pub name: String,
+    /// Symbol of the granted token.
+    pub token_symbol: String,
+    /// Token contract, or `None` for the chain's native currency.
+    pub token_address: Option<String>,
+    /// Decimals of the granted token.
+    pub token_decimals: i32,
+    /// Total grant, in whole units.
+    pub total_amount: String,
+    /// When vesting starts.
+    pub start_at: DateTime<Utc>,
+    /// Before this, nothing vests.
+    pub cliff_at: Option<DateTime<Utc>>,
+    /// When the whole grant has vested.
+    pub end_at: DateTime<Utc>,
+    /// Vesting contract the grant is claimed from, if on chain.
+    pub contract_address: Option<String>,
+    /// Free-form notes.
+    pub notes: Option<String>,
+    /// Timestamp when the schedule was created.
+    pub created_at: DateTime<Utc>,
+    /// Timestamp when the schedule was last updated.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Input for creating a vesting schedule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VestingScheduleInput {
+    /// Wallet the grant vests to.
+    pub wallet_id: String,
+    /// Display name.
+    pub name: String,
+    /// Symbol of the granted token.
+    pub token_symbol: String,
+    /// Token contract; omit for the native currency.
+    pub token_address: Option<String>,
+    /// Decimals of the granted token.
+    pub token_decimals: i32,
+    /// Total grant, in whole units.
+    pub total_amount: String,
+    /// When vesting starts (`YYYY-MM-DD` or RFC 3339).
+    pub start_at: String,
+    /// End of the cliff, if any (`YYYY-MM-DD` or RFC 3339).
+    pub cliff_at: Option<String>,
+    /// When the whole grant has vested (`YYYY-MM-DD` or RFC 3339).
+    pub end_at: String,
+    /// Vesting contract the grant is claimed from, if on chain.
+    pub contract_address: Option<String>,
+    /// Free-form notes.
+    pub notes: Option<String>,
+}
+
+/// A claim of vested tokens.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VestingClaim {
+    /// Claim transaction hash.
+    pub hash: String,
+    /// When the claim was made.
+    pub claimed_at: DateTime<Utc>,
+    /// Amount claimed, in whole units.
+    pub amount: Decimal,
+}
+
+/// Tokens unlocking on a date.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VestingUnlock {
+    /// When the tokens unlock.
+    pub date: DateTime<Utc>,
+    /// Tokens unlocking since the previous date.
+    pub amount: Decimal,
+    /// Total vested as of the date.
+    pub vested_total: Decimal,
+}
+
+/// Where a grant stands now.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VestingStatus {
+    /// The schedule.
+    pub schedule: VestingSchedule,
+    /// When the figures were computed.
+    pub as_of: DateTime<Utc>,
+    /// Vested so far.
+    pub vested: Decimal,
+    /// Still to vest.
+    pub unvested: Decimal,
+    /// Claimed from the vesting contract so far.
+    pub claimed: Decimal,
+    /// Vested but not yet claimed.
+    pub claimable: Decimal,
+    /// Claims found, oldest first.
+    pub claims: Vec<VestingClaim>,
+    /// Projected unlocks, soonest first.
+    pub upcoming_unlocks: Vec<VestingUnlock>,
+}
+
+// ============================================================================
+// Vesting
+// ============================================================================
+
+/// Amount of a grant of `total` vested at `at`: nothing before the cliff,
+/// then linear from `start` to `end`.
+pub fn vested_amount(
+    total: Decimal,
+    start: DateTime<Utc>,
+    cliff: Option<DateTime<Utc>>,
+    end: DateTime<Utc>,
+    at: DateTime<Utc>,
+) -> Decimal {
+    if at < start || cliff.is_some_and(|c| at < c) {
+        return Decimal::ZERO;
+    }
+    if at >= end {
+        return total;
+    }
+    let elapsed = Decimal::from((at - start).num_seconds());
+    let period = Decimal::from((end - start).num_seconds());
+    (total * elapsed / period).round_dp(18)
+}
+
+/// Unlocks at the cliff, the end, and each month from `now` for `months`
+/// months, leaving out dates where nothing unlocks.
+pub fn project_unlocks(
+    total: Decimal,
+    start: DateTime<Utc>,
+    cliff: Option<DateTime<Utc>>,
+    end: DateTime<Utc>,
+    now: DateTime<Utc>,
+    months: u32,
+) -> Vec<VestingUnlock> {
+    let horizon = now.checked_add_months(Months::new(months)).unwrap_or(now);
+    let mut dates: Vec<DateTime<Utc>> = (1..=months)
+        .filter_map(|m| now.checked_add_months(Months::new(m)))
+        .collect();
+    dates.extend(
+        cliff
+            .into_iter()
+            .chain([end])
+            .filter(|d| *d > now && *d <= horizon),
+    );
+    dates.sort();
+    dates.dedup();
+
+    let mut previous = vested_amount(total, start, cliff, end, now);
+    let mut unlocks = Vec::new();
+    for date in dates {
+        let vested_total = vested_amount(total, start, cliff, end, date);
+        if vested_total > previous {
+            unlocks.push(VestingUnlock {
+                date,
+                amount: vested_total - previous,
+                vested_total,
+            });
+        }
+        previous = vested_total;
+    }
+    unlocks
+}
+
+/// Claims of a schedule's grant among a wallet's transactions: native
+/// transfers from the vesting contract to the wallet, or transfers of the
+/// granted token from the contract to the wallet. Without a contract no
+/// claims can be told apart, so none are found.
+pub fn find_claims(
+    schedule: &VestingSchedule,
+    wallet_address: &str,
+    transactions: &[StoredTransaction],
+    token_transfers: &HashMap<String, Vec<TokenTransfer>>,
+) -> Vec<VestingClaim> {
+    let Some(contract) = &schedule.contract_address else {
+        return Vec::new();
+    };
+    let is = |address: Option<&str>, expected: &str| {
+        address.is_some_and(|a| a.eq_ignore_ascii_case(expected))
+    };
+
+    let mut claims: Vec<VestingClaim> = transactions
+        .iter()
+        .filter(|tx| tx.status.as_deref() != Some("failed"))
+        .filter_map(|tx| {
+            let claimed_at = tx.timestamp?;
+            let amount = match &schedule.token_address {
+                None => {
+                    if !is(tx.from_address.as_deref(), contract)
+                        || !is(tx.to_address.as_deref(), wallet_address)
+                    {
+                        return None;
+                    }
+                    parse_amount(tx.value.as_deref()?, Some(schedule.token_decimals))?
+                }
+                Some(token) => token_transfers
+                    .get(&tx.hash)?
+                    .iter()
+                    .filter(|t| {
+                        t.token_address.eq_ignore_ascii_case(token)
+                            && t.from.eq_ignore_ascii_case(contract)
+                            && t.to.eq_ignore_ascii_case(wallet_address)
+                    })
+                    .filter_map(|t| {
+                        let decimals = t
+                            .token_decimals
+                            .map(i32::from)
+                            .unwrap_or(schedule.token_decimals);
+                        parse_amount(&t.value, Some(decimals))
+                    })
+                    .sum(),
+            };
+            (!amount.is_zero()).then(|| VestingClaim {
+                hash: tx.hash.clone(),
+                claimed_at,
+                amount,
+            })
+        })
+        .collect();
+    claims.sort_by_key(|c| c.claimed_at);
+    claims
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn parse_date(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_period_bound(Some(value), false)?.ok_or_else(|| format!("Invalid date: {}", value))
+}
+
+async fn load_schedule(
+    pool: &SqlitePool,
+    profile_id: &str,
+    id: &str,
+) -> Result<VestingSchedule, String> {
+    sqlx::query_as::<_, VestingSchedule>(
+        "SELECT * FROM vesting_schedules WHERE profile_id = ? AND id = ?",
+    )
+    .bind(profile_id)
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Vesting schedule not found: {}", id))
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Records a vesting schedule for a grant to one of the profile's wallets.
+#[tauri::command]
+pub async fn create_vesting_schedule(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    input: VestingScheduleInput,
+) -> Result<VestingSchedule, String> {
+    let (_, wallet) = authorize_wallet(
+        &state.pool,
+        &auth,
+        &token,
+        &input.wallet_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+
+    match parse_amount(&input.total_amount, None) {
+        Some(total) if total > Decimal::ZERO => {}
+        _ => return Err(format!("Invalid amount: {}", input.total_amount)),
+    }
+    if !(0..=36).contains(&input.token_decimals) {
+        return Err(format!("Invalid token decimals: {}", input.token_decimals));
+    }
+    let start_at = parse_date(&input.start_at)?;
+    let end_at = parse_date(&input.end_at)?;
+    let cliff_at = input.cliff_at.as_deref().map(parse_date).transpose()?;
+    if end_at < start_at {
+        return Err("Vesting can't end before it starts".to_string());
+    }
+    if cliff_at.is_some_and(|c| c < start_at || c > end_at) {
+        return Err("The cliff must fall between the start and end".to_string());
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now();
+    sqlx::query(
+        r#"
+        INSERT INTO vesting_schedules (
+            id, profile_id, wallet_id, name, token_symbol, token_address, token_decimals,
+            total_amount, start_at, cliff_at, end_at, contract_address, notes,
+            created_at, updated_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(&wallet.profile_id)
+    .bind(&wallet.id)
+    .bind(input.name.trim())
+    .bind(input.token_symbol.trim())
+    .bind(input.token_address.as_deref().map(str::trim))
+    .bind(input.token_decimals)
+    .bind(input.total_amount.trim())
+    .bind(start_at)
+    .bind(cliff_at)
+    .bind(end_at)
+    .bind(input.contract_address.as_deref().map(str::trim))
+    .bind(&input.notes)
+    .bind(now)
+    .bind(now)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    load_schedule(&state.pool, &wallet.profile_id, &id).await
+}
+
+/// Lists a profile's vesting schedules, soonest start first.
+#[tauri::command]
+pub async fn get_vesting_schedules(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<VestingSchedule>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as::<_, VestingSchedule>(
+        "SELECT * FROM vesting_schedules WHERE profile_id = ? ORDER BY start_at, name",
+    )
+    .bind(&profile_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Deletes a vesting schedule.
+#[tauri::command]
+pub async fn delete_vesting_schedule(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    id: String,
+) -> Result<(), String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::EditTransactions,
+    )
+    .await?;
+    let result = sqlx::query("DELETE FROM vesting_schedules WHERE profile_id = ? AND id = ?")
+        .bind(&profile_id)
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if result.rows_affected() == 0 {
+        return Err(format!("Vesting schedule not found: {}", id));
+    }
+    Ok(())
+}
+
+/// Returns a grant's vested, unvested, claimed, and claimable amounts now,
+/// the claims found in the wallet's stored history, and the unlocks over
+/// the next `months` months (12 by default).
+#[tauri::command]
+pub async fn get_vesting_status(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+    id: String,
+    months: Option<u32>,
+) -> Result<VestingStatus, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let schedule = load_schedule(&state.pool, &profile_id, &id).await?;
+    let total = parse_amount(&schedule.total_amount, None)
+        .ok_or_else(|| format!("Invalid amount: {}", schedule.total_amount))?;
+
+    let (wallet_address,): (String,) = sqlx::query_as("SELECT address FROM wallets WHERE id = ?")
+        .bind(&schedule.wallet_id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let transactions = sqlx::query_as::<_, StoredTransaction>(
+        "SELECT * FROM transactions WHERE wallet_id = ? ORDER BY timestamp ASC",
+    )
+    .bind(&schedule.wallet_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let token_transfers = load_wallet_token_transfers(&state.pool, &schedule.wallet_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let claims = find_claims(&schedule, &wallet_address, &transactions, &token_transfers);
+
+    let now = Utc::now();
+    let vested = vested_amount(
+        total,
+        schedule.start_at,
+        schedule.cliff_at,
+        schedule.end_at,
+        now,
+    );
+    let claimed: Decimal = claims.iter().map(|c| c.amount).sum();
+    let upcoming_unlocks = project_unlocks(
+        total,
+        schedule.start_at,
+        schedule.cliff_at,
+        schedule.end_at,
+        now,
+        months.unwrap_or(DEFAULT_PROJECTION_MONTHS),
+    );
+
+    Ok(VestingStatus {
+        schedule,
+        as_of: now,
+        vested,
+        unvested: total - vested,
+        claimed,
+        claimable: (vested - claimed).max(Decimal::ZERO),
+        claims,
+        upcoming_unlocks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::str::FromStr;
+
+    const WALLET: &str = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045";
+    const CONTRACT: &str = "0x7a250d5630b4cf539739df2c5dacb4c659f2488d";
+    const TOKEN: &str = "0x1f9840a85d5af5bf1d1762f925bdaddc4201f984";
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap()
+    }
+
+    fn schedule(contract: Option<&str>) -> VestingSchedule {
+        VestingSchedule {
+            id: "v1".to_string(),
+            profile_id: "p1".to_string(),
+            wallet_id: "w1".to_string(),
+            name: "Core team".to_string(),
+            token_symbol: "UNI".to_string(),
+            token_address: Some(TOKEN.to_string()),
+            token_decimals: 18,
+            total_amount: "1200".to_string(),
+            start_at: date(2025, 1, 1),
+            cliff_at: Some(date(2025, 7, 1)),
+            end_at: date(2026, 1, 1),
+            contract_address: contract.map(str::to_string),
+            notes: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn tx(hash: &str, at: DateTime<Utc>) -> StoredTransaction {
+        StoredTransaction {
+            id: hash.to_string(),
+            wallet_id: "w1".to_string(),
+            hash: hash.to_string(),
+            block_number: None,
+            timestamp: Some(at),
+            from_address: Some(WALLET.to_string()),
+            to_address: Some(CONTRACT.to_string()),
+            value: Some("0".to_string()),
+            fee: None,
+            status: Some("success".to_string()),
+            tx_type: Some("contract_call".to_string()),
+            token_symbol: None,
+            token_decimals: None,
+            chain: "ethereum".to_string(),
+            raw_data: None,
+            created_at: at,
+        }
+    }
+
+    fn transfer(token: &str, from: &str, value: &str) -> TokenTransfer {
+        TokenTransfer {
+            token_address: token.to_string(),
+            token_symbol: Some("UNI".to_string()),
+            token_decimals: Some(18),
+            from: from.to_string(),
+            to: WALLET.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_vested_amount_respects_cliff() {
+        let s = schedule(None);
+        let vested = |at| vested_amount(dec("1200"), s.start_at, s.cliff_at, s.end_at, at);
+
+        assert_eq!(vested(date(2024, 12, 1)), Decimal::ZERO);
+        assert_eq!(vested(date(2025, 6, 30)), Decimal::ZERO);
+        // 181 of 365 days have passed at the cliff.
+        assert_eq!(
+            vested(date(2025, 7, 1)),
+            (dec("1200") * dec("181") / dec("365")).round_dp(18)
+        );
+        assert_eq!(vested(date(2026, 1, 1)), dec("1200"));
+        assert_eq!(vested(date(2027, 1, 1)), dec("1200"));
+    }
+
+    #[test]
+    fn test_project_unlocks_includes_cliff_and_end() {
+        let s = schedule(None);
+        let unlocks = project_unlocks(
+            dec("1200"),
+            s.start_at,
+            s.cliff_at,
+            s.end_at,
+            date(2025, 5, 15),
+            12,
+        );
+
+        // Nothing before the cliff, then the accrued amount at the cliff,
+        // monthly unlocks, and the remainder at the end.
+        assert_eq!(unlocks[0].date, date(2025, 7, 1));
+        assert!(unlocks[0].amount > dec("590"));
+        assert_eq!(unlocks.last().unwrap().date, date(2026, 1, 1));
+        assert_eq!(unlocks.last().unwrap().vested_total, dec("1200"));
+        let total: Decimal = unlocks.iter().map(|u| u.amount).sum();
+        assert_eq!(total, dec("1200"));
+    }
+
+    #[test]
+    fn test_find_claims_from_contract() {
+        let transactions = vec![
+            tx("0x2", date(2025, 9, 1)),
+            tx("0x1", date(2025, 8, 1)),
+            tx("0x3", date(2025, 10, 1)),
+        ];
+        let token_transfers = HashMap::from([
+            (
+                "0x1".to_string(),
+                vec![transfer(TOKEN, CONTRACT, "100000000000000000000")],
+            ),
+            (
+                "0x2".to_string(),
+                vec![transfer(
+                    &TOKEN.to_uppercase(),
+                    CONTRACT,
+                    "50500000000000000000",
+                )],
+            ),
+            // A transfer of the token from someone else isn't a claim.
+            (
+                "0x3".to_string(),
+                vec![transfer(TOKEN, WALLET, "1000000000000000000")],
+            ),
+        ]);
+
+        let claims = find_claims(
+            &schedule(Some(CONTRACT)),
+            WALLET,
+            &transactions,
+            &token_transfers,
+        );
+        assert_eq!(claims.len(), 2);
+        assert_eq!(claims[0].hash, "0x1");
+        assert_eq!(claims[0].amount, dec("100"));
+        assert_eq!(claims[1].amount, dec("50.5"));
+
+        assert!(find_claims(&schedule(None), WALLET, &transactions, &token_transfers).is_empty());
+    }
+}
@@ -3,12 +3,13 @@
 //! Provides Tauri commands for Web3 wallet-based authentication,
 //! supporting both Substrate (sr25519) and EVM (secp256k1) wallets.
 
-use crate::api::auth::{AuthResponse, User};
+use crate::api::auth::{check_login_anomaly, throttled, AttemptError, AuthResponse, User};
 use crate::api::persistence::DatabaseState;
-use crate::core::auth_helpers::{
-    generate_access_token, generate_secure_token, generate_session_id, hash_token,
-};
+use crate::core::auth_helpers::{generate_access_token, generate_session_id, hash_token};
 use crate::core::auth_state::AuthState;
+use crate::core::device::DeviceInfo;
+use crate::core::rate_limit::AuthAction;
+use crate::core::sign_in::{generate_nonce, AccountKind, SignInMessage};
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sp_core::Pair;
@@ -100,6 +101,10 @@ pub struct ChallengeRequest {
     pub wallet_address: String,
     /// The type of wallet (e.g., "substrate" or "evm").
     pub wallet_type: String,
+    /// Optional chain the wallet is connected to, named in the message
+    /// (an EIP-155 chain ID for EVM wallets; defaults to Ethereum mainnet).
+    #[serde(default)]
+    pub chain_id: Option<String>,
 }
 
 /// Input for verifying a wallet signature
@@ -150,8 +155,9 @@ const CHALLENGE_EXPIRY_SECONDS: i64 = 300; // 5 minutes
 
 /// Generate a challenge for wallet sign-in
 ///
-/// Creates a unique nonce-based message that must be signed by the wallet.
-/// The challenge expires after 5 minutes.
+/// Creates a structured sign-in message (EIP-4361 for EVM wallets, the same
+/// layout for Solana and Substrate) with a unique nonce that must be signed
+/// by the wallet. The challenge expires after 5 minutes.
 #[tauri::command]
 pub async fn generate_wallet_challenge(
     db: State<'_, DatabaseState>,
@@ -165,12 +171,19 @@ pub async fn generate_wallet_challenge(
 
     // Generate unique nonce and challenge ID
     let challenge_id = Uuid::new_v4().to_string();
-    let nonce = generate_secure_token(16);
+    let nonce = generate_nonce();
     let now = Utc::now();
     let expires_at = now + Duration::seconds(CHALLENGE_EXPIRY_SECONDS);
 
     // Create the message to be signed
-    let message = create_sign_message(&request.wallet_address, &nonce, &wallet_type);
+    let message = create_sign_message(
+        &request.wallet_address,
+        &nonce,
+        &wallet_type,
+        request.chain_id,
+        now,
+        expires_at,
+    )?;
 
     // Store challenge in database
     sqlx::query(
@@ -210,7 +223,9 @@ pub async fn verify_wallet_signature(
     request: VerifySignatureRequest,
 ) -> Result<AuthResponse, String> {
     let pool = &db.pool;
+    let identifier = request.wallet_address.to_lowercase();
 
+    throttled(pool, &auth, AuthAction::WalletSignature, &identifier, async {
     // Fetch and validate challenge
     let challenge: Option<(String, String, String, String, Option<DateTime<Utc>>)> =
         sqlx::query_as(
@@ -226,28 +241,33 @@ pub async fn verify_wallet_signature(
         .await
         .map_err(|e| format!("Database error: {}", e))?;
 
-    let (_nonce, stored_address, wallet_type_str, message, used_at) =
-        challenge.ok_or("Challenge not found or expired")?;
+    let (nonce, stored_address, wallet_type_str, message, used_at) =
+        challenge.ok_or_else(|| AttemptError::rejected("Challenge not found or expired"))?;
 
     // Check if challenge was already used
     if used_at.is_some() {
-        return Err("Challenge has already been used".to_string());
+        return Err(AttemptError::rejected("Challenge has already been used"));
     }
 
     // Verify address matches
     if !addresses_match(&stored_address, &request.wallet_address) {
-        return Err("Wallet address mismatch".to_string());
+        return Err(AttemptError::rejected("Wallet address mismatch"));
     }
 
     let wallet_type: WalletType = wallet_type_str.parse()?;
 
+    // Check the message binds this app, address, and nonce
+    check_sign_message(&message, &request.wallet_address, &nonce, &wallet_type)
+        .map_err(AttemptError::Rejected)?;
+
     // Verify the signature
     verify_signature(
         &request.wallet_address,
         &message,
         &request.signature,
         &wallet_type,
-    )?;
+    )
+    .map_err(AttemptError::Rejected)?;
 
     // Mark challenge as used
     sqlx::query("UPDATE auth_challenges SET used_at = ? WHERE id = ?")
@@ -311,7 +331,9 @@ pub async fn verify_wallet_signature(
     log_wallet_audit(pool, Some(&user_id), "wallet_login", "success", None).await;
 
     // Create session and return tokens
-    create_wallet_session(&db, &auth, &user_id, &user_email.0).await
+    Ok(create_wallet_session(&db, &auth, &user_id, &user_email.0).await?)
+    })
+    .await
 }
 
 /// Link a wallet to an existing authenticated user
@@ -324,10 +346,8 @@ pub async fn link_wallet_to_account(
     let pool = &db.pool;
 
     // Verify access token
-    let claims = crate::core::auth_helpers::verify_access_token(
-        &request.access_token,
-        auth.get_jwt_secret(),
-    )?;
+    let claims =
+        crate::core::auth_helpers::verify_access_token(&request.access_token, &auth.jwt_keys())?;
 
     let wallet_type: WalletType = request.wallet_type.parse()?;
 
@@ -335,9 +355,9 @@ pub async fn link_wallet_to_account(
     validate_wallet_address(&request.wallet_address, &wallet_type)?;
 
     // Fetch and validate challenge
-    let challenge: Option<(String, String, Option<DateTime<Utc>>)> = sqlx::query_as(
+    let challenge: Option<(String, String, String, Option<DateTime<Utc>>)> = sqlx::query_as(
         r#"
-        SELECT wallet_address, message, used_at
+        SELECT nonce, wallet_address, message, used_at
         FROM auth_challenges
         WHERE id = ? AND expires_at > ?
         "#,
@@ -348,7 +368,8 @@ pub async fn link_wallet_to_account(
     .await
     .map_err(|e| format!("Database error: {}", e))?;
 
-    let (stored_address, message, used_at) = challenge.ok_or("Challenge not found or expired")?;
+    let (nonce, stored_address, message, used_at) =
+        challenge.ok_or("Challenge not found or expired")?;
 
     if used_at.is_some() {
         return Err("Challenge has already been used".to_string());
@@ -358,6 +379,8 @@ pub async fn link_wallet_to_account(
         return Err("Wallet address mismatch".to_string());
     }
 
+    check_sign_message(&message, &request.wallet_address, &nonce, &wallet_type)?;
+
     // Verify signature
     verify_signature(
         &request.wallet_address,
@@ -459,7 +482,7 @@ pub async fn get_user_wallets(
     auth: State<'_, AuthState>,
     token: String,
 ) -> Result<Vec<UserWallet>, String> {
-    let claims = crate::core::auth_helpers::verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = crate::core::auth_helpers::verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     let wallets: Vec<UserWallet> = sqlx::query_as(
@@ -487,7 +510,7 @@ pub async fn unlink_wallet(
     token: String,
     wallet_id: String,
 ) -> Result<(), String> {
-    let claims = crate::core::auth_helpers::verify_access_token(&token, auth.get_jwt_secret())?;
+    let claims = crate::core::auth_helpers::verify_access_token(&token, &auth.jwt_keys())?;
     let pool = &db.pool;
 
     // Verify wallet belongs to user
@@ -561,35 +584,48 @@ pub async fn cleanup_expired_challenges(db: State<'_, DatabaseState>) -> Result<
 // Helper Functions
 // ============================================================================
 
-/// Create the message to be signed by the wallet
-fn create_sign_message(wallet_address: &str, nonce: &str, wallet_type: &WalletType) -> String {
+/// Sign-in message account type for a wallet type
+fn account_kind(wallet_type: &WalletType) -> AccountKind {
     match wallet_type {
-        WalletType::Substrate => {
-            format!(
-                "Sign this message to authenticate with Pacioli.\n\nWallet: {}\nNonce: {}\nTimestamp: {}",
-                wallet_address,
-                nonce,
-                Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
-            )
-        }
-        WalletType::Evm => {
-            // EIP-4361 SIWE format
-            format!(
-                "pacioli.app wants you to sign in with your Ethereum account:\n{}\n\nSign in to Pacioli\n\nURI: tauri://localhost\nVersion: 1\nNonce: {}\nIssued At: {}",
-                wallet_address,
-                nonce,
-                Utc::now().to_rfc3339()
-            )
-        }
-        WalletType::Solana => {
-            format!(
-                "Sign this message to authenticate with Pacioli.\n\nWallet: {}\nNonce: {}\nTimestamp: {}",
-                wallet_address,
-                nonce,
-                Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
-            )
-        }
+        WalletType::Substrate => AccountKind::Substrate,
+        WalletType::Evm => AccountKind::Ethereum,
+        WalletType::Solana => AccountKind::Solana,
+    }
+}
+
+/// Create the message to be signed by the wallet
+fn create_sign_message(
+    wallet_address: &str,
+    nonce: &str,
+    wallet_type: &WalletType,
+    chain_id: Option<String>,
+    issued_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+) -> Result<String, String> {
+    SignInMessage::new(
+        account_kind(wallet_type),
+        wallet_address,
+        chain_id,
+        nonce,
+        issued_at,
+        expires_at,
+    )
+    .map(|message| message.to_string())
+}
+
+/// Check a challenge message names this app, the wallet, and the nonce, and
+/// hasn't expired
+fn check_sign_message(
+    message: &str,
+    wallet_address: &str,
+    nonce: &str,
+    wallet_type: &WalletType,
+) -> Result<(), String> {
+    let message = SignInMessage::parse(message)?;
+    if message.kind != account_kind(wallet_type) {
+        return Err("Sign-in message is for a different wallet type".to_string());
     }
+    message.validate(wallet_address, nonce, Utc::now())
 }
 
 /// Validate wallet address format
@@ -649,41 +685,72 @@ fn verify_signature(
     }
 }
 
-/// Verify a Substrate sr25519 signature
+/// Signature schemes used by Substrate accounts whose public key is the
+/// account ID
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SubstrateScheme {
+    Sr25519,
+    Ed25519,
+}
+
+/// Verify a Substrate sr25519 or ed25519 signature
+///
+/// Accepts a plain 64-byte signature, tried as sr25519 then ed25519, or a
+/// 65-byte `MultiSignature` whose first byte names the scheme. Messages are
+/// checked both wrapped in `<Bytes>` (as Polkadot.js `signRaw` signs them)
+/// and unwrapped.
 fn verify_substrate_signature(address: &str, message: &str, signature: &str) -> Result<(), String> {
-    use sp_core::{crypto::Ss58Codec, sr25519};
+    use sp_core::{crypto::AccountId32, crypto::Ss58Codec, ed25519, sr25519};
 
     // Decode the SS58 address to get the public key
-    let public_key = sr25519::Public::from_ss58check(address)
+    let account = AccountId32::from_ss58check(address)
         .map_err(|e| format!("Invalid Substrate address: {:?}", e))?;
+    let public_key: [u8; 32] = account.into();
 
     // Decode the hex signature
     let sig_bytes = hex::decode(signature.trim_start_matches("0x"))
         .map_err(|e| format!("Invalid signature hex: {}", e))?;
 
-    if sig_bytes.len() != 64 {
-        return Err("Invalid signature length for sr25519".to_string());
-    }
-
-    let signature = sr25519::Signature::from_raw(
-        sig_bytes
-            .try_into()
-            .map_err(|_| "Failed to convert signature bytes")?,
-    );
+    let (schemes, raw): (&[SubstrateScheme], &[u8]) = match sig_bytes.len() {
+        64 => (
+            &[SubstrateScheme::Sr25519, SubstrateScheme::Ed25519],
+            &sig_bytes[..],
+        ),
+        65 => match sig_bytes[0] {
+            0 => (&[SubstrateScheme::Ed25519], &sig_bytes[1..]),
+            1 => (&[SubstrateScheme::Sr25519], &sig_bytes[1..]),
+            2 => return Err("ECDSA Substrate accounts are not supported".to_string()),
+            _ => return Err("Invalid signature type for Substrate".to_string()),
+        },
+        _ => return Err("Invalid signature length for sr25519/ed25519".to_string()),
+    };
+    let raw: [u8; 64] = raw
+        .try_into()
+        .map_err(|_| "Failed to convert signature bytes")?;
 
-    // Substrate wraps messages with a prefix
+    // Substrate wraps messages with a prefix; some wallets don't wrap
     let wrapped_message = format!("<Bytes>{}</Bytes>", message);
-
-    // Verify signature
-    if sp_core::sr25519::Pair::verify(&signature, wrapped_message.as_bytes(), &public_key) {
+    let payloads = [wrapped_message.as_bytes(), message.as_bytes()];
+
+    let verified = schemes.iter().any(|scheme| {
+        payloads.iter().any(|payload| match scheme {
+            SubstrateScheme::Sr25519 => sr25519::Pair::verify(
+                &sr25519::Signature::from_raw(raw),
+                payload,
+                &sr25519::Public::from_raw(public_key),
+            ),
+            SubstrateScheme::Ed25519 => ed25519::Pair::verify(
+                &ed25519::Signature::from_raw(raw),
+                payload,
+                &ed25519::Public::from_raw(public_key),
+            ),
+        })
+    });
+
+    if verified {
         Ok(())
     } else {
-        // Also try without wrapping (some wallets don't wrap)
-        if sp_core::sr25519::Pair::verify(&signature, message.as_bytes(), &public_key) {
-            Ok(())
-        } else {
-            Err("Invalid signature".to_string())
-        }
+        Err("Invalid signature".to_string())
     }
 }
 
@@ -764,10 +831,16 @@ fn verify_solana_signature(address: &str, message: &str, signature: &str) -> Res
     let verifying_key = VerifyingKey::from_bytes(&pubkey_array)
         .map_err(|e| format!("Invalid ed25519 public key: {}", e))?;
 
-    // Decode the base58 signature (Phantom wallet encodes signatures as base58)
-    let sig_bytes = bs58::decode(signature)
-        .into_vec()
-        .map_err(|e| format!("Invalid signature encoding: {}", e))?;
+    // Phantom and most Solana tooling encode signatures as base58; accept
+    // 0x-prefixed hex as well
+    let sig_bytes = match signature.strip_prefix("0x") {
+        Some(hex_sig) => {
+            hex::decode(hex_sig).map_err(|e| format!("Invalid signature hex: {}", e))?
+        }
+        None => bs58::decode(signature)
+            .into_vec()
+            .map_err(|e| format!("Invalid signature encoding: {}", e))?,
+    };
 
     if sig_bytes.len() != 64 {
         return Err(format!(
@@ -907,29 +980,35 @@ async fn create_wallet_session(
     let now = Utc::now();
     let expires_at = now + Duration::days(7);
 
+    // Capture device metadata and alert on unfamiliar devices or networks
+    let device = DeviceInfo::current();
+    check_login_anomaly(pool, user_id, &device).await;
+
     // Generate tokens
-    let access_token = generate_access_token(user_id, email, auth.get_jwt_secret(), Some(15))?;
+    let access_token = generate_access_token(user_id, email, &auth.jwt_keys(), Some(15))?;
 
     let refresh_token = crate::core::auth_helpers::generate_refresh_token(
         user_id,
         email,
         &session_id,
-        auth.get_jwt_secret(),
+        &auth.jwt_keys(),
         Some(7),
     )?;
 
     // Store session
     sqlx::query(
         r#"
-        INSERT INTO sessions (id, user_id, refresh_token_hash, device_name, device_type, expires_at, created_at, last_activity_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        INSERT INTO sessions (id, user_id, refresh_token_hash, device_name, device_type, ip_address, user_agent, expires_at, created_at, last_activity_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
     )
     .bind(&session_id)
     .bind(user_id)
     .bind(hash_token(&refresh_token))
-    .bind("Wallet")
-    .bind("desktop")
+    .bind(&device.device_name)
+    .bind(&device.device_type)
+    .bind(&device.ip_address)
+    .bind(device.user_agent())
     .bind(expires_at)
     .bind(now)
     .bind(now)
@@ -999,26 +1078,58 @@ async fn log_wallet_audit(
 mod tests {
     use super::*;
 
+    fn sign_message(address: &str, nonce: &str, wallet_type: &WalletType) -> String {
+        let now = Utc::now();
+        create_sign_message(
+            address,
+            nonce,
+            wallet_type,
+            None,
+            now,
+            now + Duration::seconds(CHALLENGE_EXPIRY_SECONDS),
+        )
+        .unwrap()
+    }
+
     #[test]
     fn test_create_sign_message_substrate() {
         let address = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
-        let nonce = "abc123";
-        let message = create_sign_message(address, nonce, &WalletType::Substrate);
+        let nonce = "abc123def";
+        let message = sign_message(address, nonce, &WalletType::Substrate);
 
         assert!(message.contains(address));
         assert!(message.contains(nonce));
         assert!(message.contains("Pacioli"));
+        assert!(message.contains("with your Substrate account"));
     }
 
     #[test]
     fn test_create_sign_message_evm() {
         let address = "0x742d35Cc6634C0532925a3b844Bc9e7595f8B123";
-        let nonce = "xyz789";
-        let message = create_sign_message(address, nonce, &WalletType::Evm);
+        let nonce = "xyz789abc";
+        let message = sign_message(address, nonce, &WalletType::Evm);
 
         assert!(message.contains(address));
         assert!(message.contains(nonce));
         assert!(message.contains("Pacioli"));
+        assert!(message.contains("Chain ID: 1"));
+    }
+
+    #[test]
+    fn test_check_sign_message() {
+        let address = "0x742d35Cc6634C0532925a3b844Bc9e7595f8B123";
+        let message = sign_message(address, "xyz789abc", &WalletType::Evm);
+
+        assert!(check_sign_message(&message, address, "xyz789abc", &WalletType::Evm).is_ok());
+        assert!(check_sign_message(&message, address, "other1234", &WalletType::Evm).is_err());
+        assert!(check_sign_message(&message, address, "xyz789abc", &WalletType::Solana).is_err());
+        assert!(check_sign_message(
+            "Sign this message to authenticate with Pacioli.",
+            address,
+            "xyz789abc",
+            &WalletType::Evm
+        )
+        .is_err());
     }
 
     #[test]
@@ -1109,6 +1220,63 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_verify_substrate_sr25519_signature() {
+        use sp_core::crypto::Ss58Codec;
+
+        let pair = sp_core::sr25519::Pair::from_string("//Alice", None).unwrap();
+        let address = pair.public().to_ss58check();
+        let message = sign_message(&address, "abc123def", &WalletType::Substrate);
+
+        // Polkadot.js signRaw signs the message wrapped in <Bytes>
+        let wrapped = format!("<Bytes>{}</Bytes>", message);
+        let signature = hex::encode(pair.sign(wrapped.as_bytes()).0);
+        assert!(verify_signature(&address, &message, &signature, &WalletType::Substrate).is_ok());
+
+        // MultiSignature form with the sr25519 type byte
+        let typed = format!("0x01{}", signature);
+        assert!(verify_substrate_signature(&address, &message, &typed).is_ok());
+        // ...but not under the ed25519 type byte
+        let mistyped = format!("0x00{}", signature);
+        assert!(verify_substrate_signature(&address, &message, &mistyped).is_err());
+
+        assert!(verify_substrate_signature(&address, "other message", &signature).is_err());
+    }
+
+    #[test]
+    fn test_verify_substrate_ed25519_signature() {
+        use sp_core::crypto::Ss58Codec;
+
+        let pair = sp_core::ed25519::Pair::from_seed(&[3u8; 32]);
+        let address = pair.public().to_ss58check();
+        let message = sign_message(&address, "abc123def", &WalletType::Substrate);
+
+        let signature = format!("0x{}", hex::encode(pair.sign(message.as_bytes()).0));
+        assert!(verify_signature(&address, &message, &signature, &WalletType::Substrate).is_ok());
+
+        // Signed by a different key
+        let other = sp_core::ed25519::Pair::from_seed(&[4u8; 32]);
+        let forged = hex::encode(other.sign(message.as_bytes()).0);
+        assert!(verify_substrate_signature(&address, &message, &forged).is_err());
+    }
+
+    #[test]
+    fn test_verify_solana_signature() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let address = bs58::encode(key.verifying_key().as_bytes()).into_string();
+        assert!(validate_wallet_address(&address, &WalletType::Solana).is_ok());
+        let message = sign_message(&address, "abc123def", &WalletType::Solana);
+
+        let sig = key.sign(message.as_bytes()).to_bytes();
+        let base58 = bs58::encode(sig).into_string();
+        let hex_sig = format!("0x{}", hex::encode(sig));
+        assert!(verify_signature(&address, &message, &base58, &WalletType::Solana).is_ok());
+        assert!(verify_solana_signature(&address, &message, &hex_sig).is_ok());
+        assert!(verify_solana_signature(&address, "other message", &base58).is_err());
+    }
+
     #[test]
     fn test_verify_substrate_signature_invalid_format() {
         // Test with invalid signature format
@@ -0,0 +1,717 @@
+//! Suggestions for addresses that likely belong to a profile.
+//!
+//! Three heuristics look past the wallets a profile already tracks:
+//! addresses spent together with a tracked Bitcoin address, and the change
+//! output of a payment made only from tracked addresses; addresses whose
+//! every incoming transfer came from the profile's wallets; and the owners
+//! of a tracked Safe. None of them is proof, so each suggestion carries the
+//! evidence behind it for the user to judge before adding the address.
+//! CoinJoin transactions are skipped, since their inputs belong to many
+//! people.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use super::balance_history::load_wallet_token_transfers;
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction, Wallet};
+use super::profile_scope::{authorize_profile, profile_wallets};
+use super::utxos::wallet_addresses;
+use crate::chains::address::identity_key;
+use crate::chains::bitcoin::types::BitcoinTxOutput;
+use crate::chains::bitcoin::{self, BitcoinAdapter, BitcoinTransaction};
+use crate::chains::evm::config::get_chain_by_name;
+use crate::chains::{ChainManagerState, ChainTransaction, TokenTransfer};
+use crate::core::auth_state::AuthState;
+
+/// Payments are usually round amounts; change rarely is. In satoshis.
+const ROUND_AMOUNT_SATS: u64 = 100_000;
+
+/// Outputs of one value that mark a transaction as a CoinJoin.
+const COINJOIN_EQUAL_OUTPUTS: usize = 3;
+
+/// Most funded addresses whose history is fetched to check who funded them.
+const MAX_FUNDING_CANDIDATES: usize = 20;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Why an address is thought to belong to the profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClusterHeuristic {
+    /// Spent as an input alongside a tracked Bitcoin address.
+    CoSpentInput,
+    /// The change output of a payment made only from tracked addresses.
+    ChangeOutput,
+    /// Every transfer into the address came from the profile's wallets.
+    FundedByProfile,
+    /// An owner of a tracked Safe.
+    SafeOwner,
+}
+
+/// One observation behind a suggestion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterEvidence {
+    /// Heuristic the observation supports.
+    pub heuristic: ClusterHeuristic,
+    /// Tracked wallet the observation ties the address to.
+    pub wallet_id: String,
+    /// Transaction observed, if the evidence is a transaction.
+    pub transaction_hash: Option<String>,
+    /// What was observed.
+    pub detail: String,
+}
+
+/// An untracked address offered for adding to the profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterSuggestion {
+    /// Chain the address is on.
+    pub chain: String,
+    /// The address.
+    pub address: String,
+    /// Heuristics that point to it.
+    pub heuristics: Vec<ClusterHeuristic>,
+    /// Every observation behind the suggestion.
+    pub evidence: Vec<ClusterEvidence>,
+}
+
+/// Suggestions for a profile, with the wallets that couldn't be checked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterReport {
+    /// Suggestions with the most evidence first.
+    pub suggestions: Vec<ClusterSuggestion>,
+    /// Wallets whose history or Safe owners couldn't be fetched, as
+    /// `chain:address`.
+    pub failed_wallets: Vec<String>,
+}
+
+/// An address found by a heuristic, before suggestions are merged.
+type Finding = (String, String, ClusterEvidence);
+
+// ============================================================================
+// Bitcoin
+// ============================================================================
+
+/// Kind of output script an address pays to, from its encoding.
+fn address_kind(address: &str) -> &'static str {
+    let lower = address.to_lowercase();
+    match lower.get(..4) {
+        Some("bc1p" | "tb1p") => "p2tr",
+        Some("bc1q" | "tb1q") if lower.len() > 50 => "p2wsh",
+        Some("bc1q" | "tb1q") => "p2wpkh",
+        _ if lower.starts_with('3') || lower.starts_with('2') => "p2sh",
+        _ => "p2pkh",
+    }
+}
+
+/// Whether a transaction has several outputs of one value, as a CoinJoin
+/// does.
+fn is_coinjoin(tx: &BitcoinTransaction) -> bool {
+    let mut counts: HashMap<u64, usize> = HashMap::new();
+    for output in &tx.outputs {
+        *counts.entry(output.value).or_default() += 1;
+    }
+    counts
+        .values()
+        .any(|&count| count >= COINJOIN_EQUAL_OUTPUTS)
+}
+
+/// The change output of a payment spending only `owned` addresses, when one
+/// of its two outputs stands out: the only one paying the inputs' script
+/// type, or failing that the only one that isn't a round amount.
+fn change_output<'a>(
+    tx: &'a BitcoinTransaction,
+    owned: &HashSet<String>,
+) -> Option<&'a BitcoinTxOutput> {
+    let inputs: Vec<&str> = tx
+        .inputs
+        .iter()
+        .map(|i| i.address.as_deref())
+        .collect::<Option<_>>()?;
+    if inputs.is_empty() || !inputs.iter().all(|a| owned.contains(*a)) {
+        return None;
+    }
+    // Change to a tracked address needs no suggestion.
+    let is_owned = |o: &BitcoinTxOutput| o.address.as_ref().is_some_and(|a| owned.contains(a));
+    let [first, second] = tx.outputs.as_slice() else {
+        return None;
+    };
+    if is_owned(first) || is_owned(second) || first.address.is_none() || second.address.is_none() {
+        return None;
+    }
+
+    let input_kind = address_kind(inputs[0]);
+    let same_kind =
+        |o: &BitcoinTxOutput| o.address.as_deref().map(address_kind) == Some(input_kind);
+    match (same_kind(first), same_kind(second)) {
+        (true, false) => return Some(first),
+        (false, true) => return Some(second),
+        _ => {}
+    }
+    let round = |o: &BitcoinTxOutput| o.value % ROUND_AMOUNT_SATS == 0;
+    match (round(first), round(second)) {
+        (false, true) => Some(first),
+        (true, false) => Some(second),
+        _ => None,
+    }
+}
+
+/// Untracked addresses a Bitcoin wallet's transactions tie to `owned`:
+/// inputs spent alongside an owned address, and change outputs.
+fn bitcoin_findings(
+    wallet: &Wallet,
+    owned: &HashSet<String>,
+    transactions: &[BitcoinTransaction],
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut seen = HashSet::new();
+    for tx in transactions {
+        if !seen.insert(&tx.txid) || tx.is_coinbase || is_coinjoin(tx) {
+            continue;
+        }
+        let addresses: Vec<&str> = tx
+            .inputs
+            .iter()
+            .filter_map(|i| i.address.as_deref())
+            .collect();
+        let Some(spender) = addresses.iter().find(|a| owned.contains(**a)) else {
+            continue;
+        };
+
+        let mut co_spent: Vec<&str> = addresses
+            .iter()
+            .copied()
+            .filter(|a| !owned.contains(*a))
+            .collect();
+        co_spent.sort_unstable();
+        co_spent.dedup();
+        for address in co_spent {
+            findings.push((
+                wallet.chain.clone(),
+                address.to_string(),
+                ClusterEvidence {
+                    heuristic: ClusterHeuristic::CoSpentInput,
+                    wallet_id: wallet.id.clone(),
+                    transaction_hash: Some(tx.txid.clone()),
+                    detail: format!("Spent together with {} in one transaction", spender),
+                },
+            ));
+        }
+
+        if let Some(address) = change_output(tx, owned).and_then(|o| o.address.clone()) {
+            findings.push((
+                wallet.chain.clone(),
+                address,
+                ClusterEvidence {
+                    heuristic: ClusterHeuristic::ChangeOutput,
+                    wallet_id: wallet.id.clone(),
+                    transaction_hash: Some(tx.txid.clone()),
+                    detail: format!("Likely change of a payment from {}", spender),
+                },
+            ));
+        }
+    }
+    findings
+}
+
+// ============================================================================
+// Funding
+// ============================================================================
+
+/// Whether a raw amount, decimal or `0x` hex, is more than zero.
+fn is_nonzero(value: &str) -> bool {
+    let digits = value.trim();
+    let digits = digits.strip_prefix("0x").unwrap_or(digits);
+    digits
+        .chars()
+        .any(|c| c.is_ascii_alphanumeric() && c != '0')
+}
+
+/// Untracked addresses a wallet sent value to, with the hashes of the
+/// transactions that sent it.
+fn sent_to(
+    wallet: &Wallet,
+    transactions: &[StoredTransaction],
+    transfers: &HashMap<String, Vec<TokenTransfer>>,
+    owned: &HashSet<String>,
+) -> BTreeMap<String, (String, Vec<String>)> {
+    let own = identity_key(&wallet.address);
+    let mut recipients: BTreeMap<String, (String, Vec<String>)> = BTreeMap::new();
+    let mut add = |to: &str, hash: &str| {
+        let key = identity_key(to);
+        if owned.contains(&key) {
+            return;
+        }
+        let (_, hashes) = recipients
+            .entry(key)
+            .or_insert_with(|| (to.to_string(), Vec::new()));
+        if !hashes.iter().any(|h| h == hash) {
+            hashes.push(hash.to_string());
+        }
+    };
+
+    for tx in transactions {
+        let sent_native = tx.from_address.as_deref().map(identity_key).as_ref() == Some(&own)
+            && tx.value.as_deref().is_some_and(is_nonzero);
+        if let (true, Some(to)) = (sent_native, tx.to_address.as_deref()) {
+            add(to, &tx.hash);
+        }
+        for transfer in transfers.get(&tx.hash).into_iter().flatten() {
+            if identity_key(&transfer.from) == own && is_nonzero(&transfer.value) {
+                add(&transfer.to, &tx.hash);
+            }
+        }
+    }
+    recipients
+}
+
+/// Identity keys of every sender of value into `address` in its history.
+fn funders(address: &str, history: &[ChainTransaction]) -> HashSet<String> {
+    let key = identity_key(address);
+    let mut senders = HashSet::new();
+    for tx in history {
+        if tx.to.as_deref().map(identity_key).as_ref() == Some(&key) && is_nonzero(&tx.value) {
+            senders.insert(identity_key(&tx.from));
+        }
+        for transfer in &tx.token_transfers {
+            if identity_key(&transfer.to) == key && is_nonzero(&transfer.value) {
+                senders.insert(identity_key(&transfer.from));
+            }
+        }
+    }
+    senders
+}
+
+// ============================================================================
+// Merging
+// ============================================================================
+
+/// Merges findings by address, leaving out addresses that are tracked on
+/// their chain and repeated observations. Most evidence first.
+fn merge_findings(
+    findings: Vec<Finding>,
+    owned: &HashSet<(String, String)>,
+) -> Vec<ClusterSuggestion> {
+    let mut merged: BTreeMap<(String, String), ClusterSuggestion> = BTreeMap::new();
+    for (chain, address, evidence) in findings {
+        let key = (chain.clone(), identity_key(&address));
+        if owned.contains(&key) {
+            continue;
+        }
+        let suggestion = merged.entry(key).or_insert_with(|| ClusterSuggestion {
+            chain,
+            address,
+            heuristics: Vec::new(),
+            evidence: Vec::new(),
+        });
+        if !suggestion.heuristics.contains(&evidence.heuristic) {
+            suggestion.heuristics.push(evidence.heuristic);
+            suggestion.heuristics.sort();
+        }
+        if !suggestion.evidence.contains(&evidence) {
+            suggestion.evidence.push(evidence);
+        }
+    }
+
+    let mut suggestions: Vec<ClusterSuggestion> = merged.into_values().collect();
+    suggestions.sort_by(|a, b| b.evidence.len().cmp(&a.evidence.len()));
+    suggestions
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Suggests untracked addresses that likely belong to a profile, each with
+/// the evidence behind it: Bitcoin addresses spent alongside or receiving
+/// change from the profile's, addresses funded only by the profile's
+/// wallets, and owners of the profile's Safes. Accepting a suggestion is
+/// adding the wallet as usual.
+#[tauri::command]
+pub async fn get_wallet_cluster_suggestions(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    chains: State<'_, ChainManagerState>,
+    token: String,
+    profile_id: String,
+) -> Result<ClusterReport, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let wallets = profile_wallets(&state.pool, &profile_id).await?;
+    let mut owned: HashSet<(String, String)> = wallets
+        .iter()
+        .map(|w| (w.chain.clone(), identity_key(&w.address)))
+        .collect();
+    let mut findings = Vec::new();
+    let mut failed_wallets = Vec::new();
+
+    // Bitcoin: co-spent inputs and change, across every derived address.
+    let mut wallet_owned: Vec<(&Wallet, Vec<String>)> = Vec::new();
+    for wallet in wallets
+        .iter()
+        .filter(|w| bitcoin::get_config_by_name(&w.chain).is_some())
+    {
+        let addresses: Vec<String> = wallet_addresses(wallet)?
+            .into_iter()
+            .map(|(address, _)| address)
+            .collect();
+        owned.extend(
+            addresses
+                .iter()
+                .map(|a| (wallet.chain.clone(), identity_key(a))),
+        );
+        wallet_owned.push((wallet, addresses));
+    }
+    for (wallet, addresses) in &wallet_owned {
+        let chain_owned: HashSet<String> = wallet_owned
+            .iter()
+            .filter(|(w, _)| w.chain == wallet.chain)
+            .flat_map(|(_, a)| a.iter().cloned())
+            .collect();
+        let adapter = BitcoinAdapter::from_network(&wallet.chain).map_err(|e| e.to_string())?;
+        let mut transactions = Vec::new();
+        let mut failed = false;
+        for address in addresses {
+            match adapter.fetch_transactions(address, None).await {
+                Ok(fetched) => transactions.extend(fetched),
+                Err(_) => failed = true,
+            }
+        }
+        if failed {
+            failed_wallets.push(format!("{}:{}", wallet.chain, wallet.address));
+        }
+        findings.extend(bitcoin_findings(wallet, &chain_owned, &transactions));
+    }
+
+    // Account chains: addresses the profile funded, checked against their
+    // own history.
+    let mut recipients: BTreeMap<(String, String), (String, Vec<(String, String)>)> =
+        BTreeMap::new();
+    for wallet in wallets
+        .iter()
+        .filter(|w| bitcoin::get_config_by_name(&w.chain).is_none())
+    {
+        let transactions = sqlx::query_as::<_, StoredTransaction>(
+            "SELECT * FROM transactions WHERE wallet_id = ?",
+        )
+        .bind(&wallet.id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+        let transfers = load_wallet_token_transfers(&state.pool, &wallet.id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let chain_owned: HashSet<String> = owned
+            .iter()
+            .filter(|(chain, _)| *chain == wallet.chain)
+            .map(|(_, key)| key.clone())
+            .collect();
+        for (key, (address, hashes)) in sent_to(wallet, &transactions, &transfers, &chain_owned) {
+            let (_, sources) = recipients
+                .entry((wallet.chain.clone(), key))
+                .or_insert_with(|| (address, Vec::new()));
+            sources.extend(hashes.into_iter().map(|h| (wallet.id.clone(), h)));
+        }
+    }
+    let mut candidates: Vec<_> = recipients.into_iter().collect();
+    candidates.sort_by_key(|(_, (_, sources))| std::cmp::Reverse(sources.len()));
+    candidates.truncate(MAX_FUNDING_CANDIDATES);
+
+    let manager = chains.read().await;
+    for ((chain, _), (address, sources)) in candidates {
+        let Ok(history) = manager.get_transactions(&chain, &address, None).await else {
+            continue;
+        };
+        let senders = funders(&address, &history);
+        let exclusive = !senders.is_empty()
+            && senders
+                .iter()
+                .all(|s| owned.contains(&(chain.clone(), s.clone())));
+        if !exclusive {
+            continue;
+        }
+        for (wallet_id, hash) in sources {
+            findings.push((
+                chain.clone(),
+                address.clone(),
+                ClusterEvidence {
+                    heuristic: ClusterHeuristic::FundedByProfile,
+                    wallet_id,
+                    transaction_hash: Some(hash),
+                    detail: format!(
+                        "Funded from the profile; all {} funding sources are tracked wallets",
+                        senders.len()
+                    ),
+                },
+            ));
+        }
+    }
+
+    // Safe owners.
+    for wallet in wallets
+        .iter()
+        .filter(|w| get_chain_by_name(&w.chain).is_some())
+    {
+        match manager
+            .get_safe_owners(&wallet.chain, &wallet.address)
+            .await
+        {
+            Ok(owners) => findings.extend(owners.into_iter().map(|owner| {
+                (
+                    wallet.chain.clone(),
+                    owner,
+                    ClusterEvidence {
+                        heuristic: ClusterHeuristic::SafeOwner,
+                        wallet_id: wallet.id.clone(),
+                        transaction_hash: None,
+                        detail: format!("Owner of the Safe {}", wallet.address),
+                    },
+                )
+            })),
+            Err(_) => failed_wallets.push(format!("{}:{}", wallet.chain, wallet.address)),
+        }
+    }
+    drop(manager);
+
+    failed_wallets.dedup();
+    Ok(ClusterReport {
+        suggestions: merge_findings(findings, &owned),
+        failed_wallets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::bitcoin::types::BitcoinTxInput;
+    use crate::chains::{ChainId, TransactionStatus, TransactionType};
+    use chrono::Utc;
+
+    const OURS: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+    const CO_SPENT: &str = "bc1qxy2kgdygjrsqtzq2n0yrf2493p83kkfjhx0wlh";
+    const CHANGE: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+    const PAYEE: &str = "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy";
+
+    fn wallet(chain: &str, address: &str) -> Wallet {
+        Wallet {
+            id: "w1".to_string(),
+            profile_id: "p1".to_string(),
+            address: address.to_string(),
+            chain: chain.to_string(),
+            name: None,
+            wallet_type: "external".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+        }
+    }
+
+    fn output(address: &str, value: u64, index: u32) -> BitcoinTxOutput {
+        BitcoinTxOutput {
+            address: Some(address.to_string()),
+            value,
+            index,
+            script_type: address_kind(address).to_string(),
+        }
+    }
+
+    fn payment(txid: &str, inputs: &[&str], outputs: Vec<BitcoinTxOutput>) -> BitcoinTransaction {
+        BitcoinTransaction {
+            txid: txid.to_string(),
+            block_height: Some(840_000),
+            timestamp: Some(1_713_571_540),
+            inputs: inputs
+                .iter()
+                .map(|address| BitcoinTxInput {
+                    address: Some(address.to_string()),
+                    value: 100_000_000,
+                    prev_txid: "prev".to_string(),
+                    prev_vout: 0,
+                })
+                .collect(),
+            outputs,
+            fee: 500,
+            confirmations: 1,
+            is_coinbase: false,
+            total_input: 0,
+            total_output: 0,
+            rbf_signaled: false,
+        }
+    }
+
+    #[test]
+    fn test_change_output_by_script_type_then_amount() {
+        let owned: HashSet<String> = [OURS.to_string()].into();
+
+        // Only the change pays the inputs' script type.
+        let tx = payment(
+            "t1",
+            &[OURS],
+            vec![output(PAYEE, 50_000_000, 0), output(CHANGE, 49_999_500, 1)],
+        );
+        assert_eq!(change_output(&tx, &owned).unwrap().index, 1);
+
+        // Same script type: the payment is the round amount.
+        let tx = payment(
+            "t2",
+            &[OURS],
+            vec![
+                output(CO_SPENT, 20_000_000, 0),
+                output(CHANGE, 79_999_500, 1),
+            ],
+        );
+        assert_eq!(change_output(&tx, &owned).unwrap().index, 1);
+
+        // Nothing stands out, or an input isn't ours.
+        let tx = payment(
+            "t3",
+            &[OURS],
+            vec![
+                output(CO_SPENT, 20_000_000, 0),
+                output(CHANGE, 70_000_000, 1),
+            ],
+        );
+        assert!(change_output(&tx, &owned).is_none());
+        let tx = payment(
+            "t4",
+            &[OURS, CO_SPENT],
+            vec![output(PAYEE, 50_000_000, 0), output(CHANGE, 49_999_500, 1)],
+        );
+        assert!(change_output(&tx, &owned).is_none());
+    }
+
+    #[test]
+    fn test_bitcoin_findings_skip_coinjoins() {
+        let wallet = wallet("bitcoin", OURS);
+        let owned: HashSet<String> = [OURS.to_string()].into();
+        let spend = payment(
+            "t1",
+            &[OURS, CO_SPENT],
+            vec![output(PAYEE, 50_000_000, 0), output(CHANGE, 49_999_500, 1)],
+        );
+        let coinjoin = payment(
+            "t2",
+            &[OURS, CHANGE],
+            vec![
+                output(PAYEE, 10_000_000, 0),
+                output(PAYEE, 10_000_000, 1),
+                output(PAYEE, 10_000_000, 2),
+            ],
+        );
+
+        let findings = bitcoin_findings(&wallet, &owned, &[spend.clone(), spend, coinjoin]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].1, CO_SPENT);
+        assert_eq!(findings[0].2.heuristic, ClusterHeuristic::CoSpentInput);
+        assert_eq!(findings[0].2.transaction_hash.as_deref(), Some("t1"));
+    }
+
+    fn chain_tx(from: &str, to: &str, wei: &str) -> ChainTransaction {
+        ChainTransaction {
+            hash: "0xabc".to_string(),
+            chain_id: ChainId::evm("ethereum", 1),
+            block_number: 1,
+            timestamp: 1_700_000_000,
+            from: from.to_string(),
+            to: Some(to.to_string()),
+            value: wei.to_string(),
+            fee: "0".to_string(),
+            status: TransactionStatus::Success,
+            tx_type: TransactionType::Transfer,
+            token_transfers: Vec::new(),
+            swaps: Vec::new(),
+            fee_breakdown: None,
+            user_operations: Vec::new(),
+            inner_calls: Vec::new(),
+            raw_data: None,
+        }
+    }
+
+    #[test]
+    fn test_funders_count_native_and_token_inflows() {
+        let address = "0x1111111111111111111111111111111111111111";
+        let native_funder = "0x2222222222222222222222222222222222222222";
+        let token_funder = "0x3333333333333333333333333333333333333333";
+
+        let native = chain_tx(native_funder, address, "0xa");
+        let mut token = chain_tx(token_funder, address, "0");
+        token.token_transfers.push(TokenTransfer {
+            token_address: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string(),
+            token_symbol: Some("USDC".to_string()),
+            token_decimals: Some(6),
+            from: token_funder.to_string(),
+            to: address.to_uppercase().replace("0X", "0x"),
+            value: "5".to_string(),
+        });
+        let outgoing = chain_tx(address, native_funder, "1000");
+        let empty = chain_tx("0x4444444444444444444444444444444444444444", address, "0");
+
+        let senders = funders(address, &[native, token, outgoing, empty]);
+        assert_eq!(senders.len(), 2);
+        assert!(senders.contains(&identity_key(native_funder)));
+        assert!(senders.contains(&identity_key(token_funder)));
+    }
+
+    #[test]
+    fn test_merge_findings_skips_tracked_and_repeats() {
+        let evidence = |heuristic, hash: &str| ClusterEvidence {
+            heuristic,
+            wallet_id: "w1".to_string(),
+            transaction_hash: Some(hash.to_string()),
+            detail: String::new(),
+        };
+        let findings = vec![
+            (
+                "bitcoin".to_string(),
+                CHANGE.to_string(),
+                evidence(ClusterHeuristic::ChangeOutput, "t1"),
+            ),
+            (
+                "bitcoin".to_string(),
+                CHANGE.to_string(),
+                evidence(ClusterHeuristic::ChangeOutput, "t1"),
+            ),
+            (
+                "bitcoin".to_string(),
+                CHANGE.to_string(),
+                evidence(ClusterHeuristic::CoSpentInput, "t2"),
+            ),
+            (
+                "bitcoin".to_string(),
+                CO_SPENT.to_string(),
+                evidence(ClusterHeuristic::CoSpentInput, "t3"),
+            ),
+            (
+                "bitcoin".to_string(),
+                OURS.to_string(),
+                evidence(ClusterHeuristic::CoSpentInput, "t4"),
+            ),
+        ];
+        let owned: HashSet<(String, String)> = [("bitcoin".to_string(), identity_key(OURS))].into();
+
+        let suggestions = merge_findings(findings, &owned);
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].address, CHANGE);
+        assert_eq!(
+            suggestions[0].heuristics,
+            vec![
+                ClusterHeuristic::CoSpentInput,
+                ClusterHeuristic::ChangeOutput
+            ]
+        );
+        assert_eq!(suggestions[0].evidence.len(), 2);
+        assert_eq!(suggestions[1].address, CO_SPENT);
+    }
+}
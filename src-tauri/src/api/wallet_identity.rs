@@ -0,0 +1,480 @@
+//! Wallet identities across address encodings.
+//!
+//! A Polkadot account appears under a different SS58 string on every
+//! parachain, and EVM addresses may be stored in either case. Wallets whose
+//! addresses resolve to the same account are grouped into one identity so
+//! aggregated views don't count the account twice. The same 0x address on
+//! Ethereum, Polygon, and Arbitrum is one logical account: its balances are
+//! summed together and transfers between its chains are internal. Accounts
+//! linked explicitly, like the EVM and Substrate sides of an Astar account,
+//! are merged into one identity too.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction, Wallet};
+use super::profile_scope::authorize_profile;
+use super::token_spam::SpamFilter;
+use crate::chains::address::identity_key;
+use crate::chains::substrate::ss58;
+use crate::chains::{ChainManagerState, WalletBalances};
+use crate::core::auth_state::AuthState;
+use crate::core::currency::round_fiat;
+use crate::log_error;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Wallets that belong to the same account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletIdentity {
+    /// Encoding-independent key for the account.
+    pub identity_key: String,
+    /// Hex public key, for Substrate accounts.
+    pub public_key: Option<String>,
+    /// Chains the account has wallets on.
+    pub chains: Vec<String>,
+    /// Keys of linked accounts merged into this identity.
+    pub linked_keys: Vec<String>,
+    /// The wallets, in the order they were given.
+    pub wallets: Vec<Wallet>,
+}
+
+/// A transfer between two addresses that belong to the same profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InternalTransfer {
+    /// Stored transaction ID.
+    pub transaction_id: String,
+    /// Transaction hash.
+    pub hash: String,
+    /// Chain the transaction is on.
+    pub chain: String,
+    /// Identity of the sending account.
+    pub from_identity: String,
+    /// Identity of the receiving account.
+    pub to_identity: String,
+    /// Whether both sides are the same logical account, e.g. one 0x address
+    /// moving funds to itself on another chain.
+    pub same_account: bool,
+}
+
+/// Balances of one logical account across all its chains.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountBalances {
+    /// Encoding-independent key for the account.
+    pub identity_key: String,
+    /// Balances per chain the account has a wallet on.
+    pub balances: Vec<WalletBalances>,
+    /// Sum of the per-chain USD values that were available.
+    pub total_value_usd: Option<Decimal>,
+    /// Chains whose balances could not be fetched.
+    pub failed_chains: Vec<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Groups wallets by the account behind their addresses, keeping the order
+/// in which each identity first appears.
+pub fn group_wallets(wallets: Vec<Wallet>) -> Vec<WalletIdentity> {
+    let mut identities: Vec<WalletIdentity> = Vec::new();
+
+    for wallet in wallets {
+        let key = identity_key(&wallet.address);
+        let identity = match identities.iter().position(|i| i.identity_key == key) {
+            Some(index) => &mut identities[index],
+            None => {
+                identities.push(WalletIdentity {
+                    public_key: key.strip_prefix("substrate:").map(str::to_string),
+                    identity_key: key,
+                    chains: Vec::new(),
+                    linked_keys: Vec::new(),
+                    wallets: Vec::new(),
+                });
+                identities.last_mut().expect("identity was just pushed")
+            }
+        };
+        if !identity.chains.contains(&wallet.chain) {
+            identity.chains.push(wallet.chain.clone());
+        }
+        identity.wallets.push(wallet);
+    }
+
+    identities
+}
+
+/// Merges identities whose wallets are linked, given as pairs of wallet
+/// IDs. The identity that appears first absorbs the other.
+pub fn merge_linked(
+    mut identities: Vec<WalletIdentity>,
+    links: &[(String, String)],
+) -> Vec<WalletIdentity> {
+    let position = |identities: &[WalletIdentity], wallet_id: &str| {
+        identities
+            .iter()
+            .position(|i| i.wallets.iter().any(|w| w.id == wallet_id))
+    };
+
+    for (a, b) in links {
+        let (Some(a), Some(b)) = (position(&identities, a), position(&identities, b)) else {
+            continue;
+        };
+        if a == b {
+            continue;
+        }
+        let (keep, absorb) = (a.min(b), a.max(b));
+        let absorbed = identities.remove(absorb);
+        let identity = &mut identities[keep];
+        identity.linked_keys.push(absorbed.identity_key);
+        identity.linked_keys.extend(absorbed.linked_keys);
+        for chain in absorbed.chains {
+            if !identity.chains.contains(&chain) {
+                identity.chains.push(chain);
+            }
+        }
+        identity.wallets.extend(absorbed.wallets);
+    }
+
+    identities
+}
+
+/// Finds the identity an address belongs to, on any chain.
+pub fn find_identity<'a>(
+    identities: &'a [WalletIdentity],
+    address: &str,
+) -> Option<&'a WalletIdentity> {
+    let key = identity_key(address);
+    identities
+        .iter()
+        .find(|i| i.identity_key == key || i.linked_keys.contains(&key))
+}
+
+/// Loads and groups a profile's wallets, merging linked accounts.
+pub async fn load_identities(
+    pool: &SqlitePool,
+    profile_id: &str,
+) -> Result<Vec<WalletIdentity>, sqlx::Error> {
+    let wallets = sqlx::query_as::<_, Wallet>(
+        "SELECT * FROM wallets WHERE profile_id = ? ORDER BY created_at ASC",
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await?;
+    let links: Vec<(String, String)> = sqlx::query_as(
+        "SELECT wallet_id, linked_wallet_id FROM account_links WHERE profile_id = ?",
+    )
+    .bind(profile_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(merge_linked(group_wallets(wallets), &links))
+}
+
+/// Transactions whose sender and recipient both belong to `identities`.
+pub fn find_internal_transfers(
+    identities: &[WalletIdentity],
+    transactions: &[StoredTransaction],
+) -> Vec<InternalTransfer> {
+    transactions
+        .iter()
+        .filter_map(|tx| {
+            let from = find_identity(identities, tx.from_address.as_deref()?)?;
+            let to = find_identity(identities, tx.to_address.as_deref()?)?;
+            Some(InternalTransfer {
+                transaction_id: tx.id.clone(),
+                hash: tx.hash.clone(),
+                chain: tx.chain.clone(),
+                from_identity: from.identity_key.clone(),
+                to_identity: to.identity_key.clone(),
+                same_account: from.identity_key == to.identity_key,
+            })
+        })
+        .collect()
+}
+
+/// Encodes a Substrate address with the prefix of the chain it is saved on,
+/// so the same account is always stored in the same form per chain.
+/// Addresses for other chains, or that don't decode, are returned unchanged.
+pub fn canonical_address(chain: &str, address: &str) -> String {
+    ss58::prefix_for_chain(chain)
+        .and_then(|prefix| ss58::convert(address, prefix).ok())
+        .unwrap_or_else(|| address.to_string())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns a profile's wallets grouped by underlying account. Requires any
+/// role on the profile.
+#[tauri::command]
+pub async fn get_wallet_identities(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<WalletIdentity>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    load_identities(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Finds a profile's transactions that move funds between its own accounts,
+/// including the same address on different chains. Requires any role on the
+/// profile.
+#[tauri::command]
+pub async fn detect_internal_transfers(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<InternalTransfer>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let identities = load_identities(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let transactions = sqlx::query_as::<_, StoredTransaction>(
+        r#"
+        SELECT t.* FROM transactions t
+        INNER JOIN wallets w ON t.wallet_id = w.id
+        WHERE w.profile_id = ?
+        ORDER BY t.timestamp ASC
+        "#,
+    )
+    .bind(&profile_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(find_internal_transfers(&identities, &transactions))
+}
+
+/// Fetches balances for every wallet of a profile and groups them by
+/// logical account. Tokens marked as spam are hidden. Requires any role on
+/// the profile.
+#[tauri::command]
+pub async fn get_account_balances(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    chains: State<'_, ChainManagerState>,
+    profile_id: String,
+) -> Result<Vec<AccountBalances>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    let identities = load_identities(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let filter = SpamFilter::load(&state.pool, Some(&profile_id))
+        .await
+        .map_err(|e| e.to_string())?;
+    let manager = chains.read().await;
+
+    let mut accounts = Vec::with_capacity(identities.len());
+    for identity in identities {
+        let mut balances = Vec::new();
+        let mut failed_chains = Vec::new();
+        for wallet in &identity.wallets {
+            match manager.get_balances(&wallet.chain, &wallet.address).await {
+                Ok(mut balance) => {
+                    filter.filter_balances(&mut balance);
+                    balances.push(balance);
+                }
+                Err(e) => {
+                    log_error!("Failed to fetch balance for {}: {e}", wallet.chain);
+                    failed_chains.push(wallet.chain.clone());
+                }
+            }
+        }
+
+        let values: Vec<Decimal> = balances.iter().filter_map(|b| b.total_value_usd).collect();
+        accounts.push(AccountBalances {
+            identity_key: identity.identity_key,
+            total_value_usd: (!values.is_empty()).then(|| round_fiat(values.iter().sum(), "USD")),
+            balances,
+            failed_chains,
+        });
+    }
+
+    Ok(accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn wallet(id: &str, chain: &str, address: &str) -> Wallet {
+        Wallet {
+            id: id.to_string(),
+            profile_id: "p1".to_string(),
+            address: address.to_string(),
+            chain: chain.to_string(),
+            name: None,
+            wallet_type: "substrate".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_groups_same_key_across_networks() {
+        let identities = group_wallets(vec![
+            wallet(
+                "w1",
+                "polkadot",
+                "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5",
+            ),
+            wallet(
+                "w2",
+                "ethereum",
+                "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            ),
+            wallet(
+                "w3",
+                "westend",
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            ),
+        ]);
+
+        assert_eq!(identities.len(), 2);
+        assert_eq!(identities[0].chains, vec!["polkadot", "westend"]);
+        assert_eq!(
+            identities[0].public_key.as_deref(),
+            Some("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d")
+        );
+        assert!(identities[1].public_key.is_none());
+    }
+
+    #[test]
+    fn test_same_evm_address_is_one_account() {
+        let identities = group_wallets(vec![
+            wallet(
+                "w1",
+                "ethereum",
+                "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            ),
+            wallet(
+                "w2",
+                "polygon",
+                "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+            ),
+            wallet(
+                "w3",
+                "arbitrum",
+                "0x00000000000000000000000000000000000000aa",
+            ),
+        ]);
+        assert_eq!(identities.len(), 2);
+        assert_eq!(identities[0].chains, vec!["ethereum", "polygon"]);
+
+        let tx = |id: &str, from: &str, to: &str| StoredTransaction {
+            id: id.to_string(),
+            wallet_id: "w1".to_string(),
+            hash: format!("0x{}", id),
+            block_number: None,
+            timestamp: None,
+            from_address: Some(from.to_string()),
+            to_address: Some(to.to_string()),
+            value: None,
+            fee: None,
+            status: None,
+            tx_type: None,
+            token_symbol: None,
+            token_decimals: None,
+            chain: "ethereum".to_string(),
+            raw_data: None,
+            created_at: Utc::now(),
+        };
+        let transfers = find_internal_transfers(
+            &identities,
+            &[
+                tx(
+                    "t1",
+                    "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+                    "0x00000000000000000000000000000000000000AA",
+                ),
+                tx(
+                    "t2",
+                    "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+                    "0x00000000000000000000000000000000000000bb",
+                ),
+            ],
+        );
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].transaction_id, "t1");
+        assert!(!transfers[0].same_account);
+    }
+
+    #[test]
+    fn test_linked_accounts_merge_into_one_identity() {
+        let identities = group_wallets(vec![
+            wallet("w1", "astar", "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+            wallet(
+                "w2",
+                "ethereum",
+                "0x00000000000000000000000000000000000000aa",
+            ),
+            wallet(
+                "w3",
+                "astar-substrate",
+                "bVgxQGrTCdJSYxKcWn3GxXeBeLTojWGxKcbQ8iHkLa7DXqP",
+            ),
+        ]);
+        assert_eq!(identities.len(), 3);
+
+        let merged = merge_linked(identities, &[("w3".to_string(), "w1".to_string())]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].chains, vec!["astar", "astar-substrate"]);
+        assert_eq!(merged[0].wallets.len(), 2);
+
+        let found =
+            find_identity(&merged, "bVgxQGrTCdJSYxKcWn3GxXeBeLTojWGxKcbQ8iHkLa7DXqP").unwrap();
+        assert_eq!(
+            found.identity_key,
+            "evm:0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+        );
+    }
+
+    #[test]
+    fn test_canonical_address_uses_chain_prefix() {
+        assert_eq!(
+            canonical_address(
+                "polkadot",
+                "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+            ),
+            "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5"
+        );
+        assert_eq!(canonical_address("ethereum", "0xabc"), "0xabc");
+    }
+}
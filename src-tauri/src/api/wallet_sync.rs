@@ -0,0 +1,93 @@
+//! Tauri commands for wallet sync, and [`AppSyncEvents`], which turns its
+//! progress into Tauri events.
+
+use tauri::{AppHandle, Emitter, State};
+
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, Wallet};
+use super::profile_scope::{authorize_profile, profile_wallets};
+use crate::core::auth_state::AuthState;
+use crate::jobs::queue::{JobQueueState, JobTask};
+use crate::jobs::JobPriority;
+
+pub use pacioli_core::services::wallet_sync::*;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Reports a sync's progress as Tauri events on the app.
+pub(crate) struct AppSyncEvents<'a>(pub &'a AppHandle);
+
+impl SyncEvents for AppSyncEvents<'_> {
+    fn emit(&self, event: &str, progress: &SyncProgress) {
+        let _ = self.0.emit(event, progress);
+    }
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Returns each of a profile's wallets with its last sync time, last block,
+/// sync state, and error message.
+#[tauri::command]
+pub async fn get_sync_status(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<WalletSyncStatus>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    load_sync_statuses(&state.pool, &profile_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Queues a sync of each of a profile's wallets, or only `wallet_ids` when
+/// given, ahead of background work. Returns one job ID per wallet, which
+/// `cancel_job` accepts; progress is reported with `sync:*` events and the
+/// end of each job with `job:finished`. Requires the owner, admin, or
+/// preparer role on the profile.
+#[tauri::command]
+pub async fn sync_wallets(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    queue: State<'_, JobQueueState>,
+    token: String,
+    profile_id: String,
+    wallet_ids: Option<Vec<String>>,
+) -> Result<Vec<String>, String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ManageWallets,
+    )
+    .await?;
+    let wallets: Vec<Wallet> = profile_wallets(&state.pool, &profile_id)
+        .await?
+        .into_iter()
+        .filter(|w| wallet_ids.as_ref().is_none_or(|ids| ids.contains(&w.id)))
+        .collect();
+
+    let mut job_ids = Vec::with_capacity(wallets.len());
+    for wallet in wallets {
+        let task = JobTask::WalletSync {
+            profile_id: profile_id.clone(),
+            user_id: user_id.clone(),
+            wallet_id: wallet.id,
+            chain: wallet.chain,
+        };
+        job_ids.push(queue.enqueue(task, JobPriority::User).await?);
+    }
+    Ok(job_ids)
+}
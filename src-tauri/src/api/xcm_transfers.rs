@@ -0,0 +1,539 @@
+//! XCM transfers between parachains.
+//!
+//! Moving DOT from Polkadot to Acala, or GLMR from Moonbeam back to the
+//! relay chain, leaves one record on each chain: an outgoing transfer where
+//! the message was sent and an incoming one where it was executed. Subscan
+//! indexes XCM messages on the relay chain; each message the profile's
+//! accounts sent or received is matched to its stored legs, the pair is
+//! recorded, and both legs are typed as `bridge` so the hop books as a move
+//! between the owner's own wallets rather than a disposal and fresh income.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::State;
+use uuid::Uuid;
+
+use super::audit_trail::{record_change, RecordType};
+use super::period_close::ensure_period_open;
+use super::permissions::Permission;
+use super::persistence::{DatabaseState, StoredTransaction, Wallet};
+use super::profile_scope::{authorize_profile, profile_transactions, profile_wallets};
+use crate::chains::address::identity_key;
+use crate::chains::substrate::subscan::{SubscanClient, SubscanXcmTransfer};
+use crate::chains::substrate::{chain_for_para, get_config_by_name, relay_for_chain};
+use crate::core::auth_state::AuthState;
+use crate::fetchers::{ApiKeyManager, ApiProvider};
+use crate::log_error;
+
+/// Largest gap between an XCM message's execution and the stored incoming
+/// leg, in seconds, when the delivering block isn't known.
+const RECEIVE_WINDOW_SECS: i64 = 10 * 60;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// An XCM message and the stored records of its two legs.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct XcmTransfer {
+    /// Unique identifier.
+    pub id: String,
+    /// Profile the legs belong to.
+    pub profile_id: String,
+    /// Hash identifying the XCM message.
+    pub message_hash: String,
+    /// Chain the message was sent from.
+    pub origin_chain: String,
+    /// Chain the message was executed on.
+    pub dest_chain: String,
+    /// Sending account.
+    pub from_address: String,
+    /// Receiving account.
+    pub to_address: String,
+    /// Symbol of the asset moved.
+    pub asset_symbol: String,
+    /// Amount moved, in whole units.
+    pub amount: String,
+    /// `success`, `pending`, or `failed`.
+    pub status: String,
+    /// Stored outgoing leg, if synced.
+    pub send_transaction_id: Option<String>,
+    /// Stored incoming leg, if synced.
+    pub receive_transaction_id: Option<String>,
+    /// When the message was sent.
+    pub sent_at: DateTime<Utc>,
+    /// When the message was executed, if it has been.
+    pub received_at: Option<DateTime<Utc>>,
+    /// When the transfer was first recorded.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Outcome of syncing a profile's XCM transfers.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct XcmSyncResult {
+    /// Messages involving the profile's accounts.
+    pub transfers: usize,
+    /// Legs newly typed as bridge transfers.
+    pub legs_linked: usize,
+    /// Legs left alone because their period is closed.
+    pub legs_in_closed_periods: usize,
+    /// Accounts whose XCM history couldn't be fetched.
+    pub failed_accounts: Vec<String>,
+}
+
+// ============================================================================
+// Matching
+// ============================================================================
+
+/// Block number of a Subscan `block-index` reference.
+fn block_of(index: &str) -> Option<i64> {
+    index.split('-').next()?.parse().ok()
+}
+
+fn same_account(a: Option<&str>, b: &str) -> bool {
+    a.is_some_and(|a| identity_key(a) == identity_key(b))
+}
+
+fn timestamp(secs: i64) -> Option<DateTime<Utc>> {
+    (secs > 0)
+        .then(|| Utc.timestamp_opt(secs, 0).single())
+        .flatten()
+}
+
+/// The stored leg sending `message` from `origin_chain`: a transaction in
+/// the sender's wallet on that chain, from the sender, in the sending block.
+pub fn find_send_leg<'a>(
+    message: &SubscanXcmTransfer,
+    origin_chain: &str,
+    wallets: &[Wallet],
+    transactions: &'a [StoredTransaction],
+) -> Option<&'a StoredTransaction> {
+    let block = block_of(&message.extrinsic_index)?;
+    let wallet = wallets.iter().find(|w| {
+        w.chain == origin_chain
+            && identity_key(&w.address) == identity_key(&message.from_account_id)
+    })?;
+    transactions.iter().find(|tx| {
+        tx.wallet_id == wallet.id
+            && tx.block_number == Some(block)
+            && same_account(tx.from_address.as_deref(), &message.from_account_id)
+    })
+}
+
+/// The stored leg receiving `message` on `dest_chain`: a transaction in the
+/// recipient's wallet on that chain, to the recipient, in the delivering
+/// block or, when that isn't known, of the same asset shortly around the
+/// message's execution.
+pub fn find_receive_leg<'a>(
+    message: &SubscanXcmTransfer,
+    dest_chain: &str,
+    wallets: &[Wallet],
+    transactions: &'a [StoredTransaction],
+) -> Option<&'a StoredTransaction> {
+    let wallet = wallets.iter().find(|w| {
+        w.chain == dest_chain && identity_key(&w.address) == identity_key(&message.to_account_id)
+    })?;
+    let mut incoming = transactions.iter().filter(|tx| {
+        tx.wallet_id == wallet.id && same_account(tx.to_address.as_deref(), &message.to_account_id)
+    });
+
+    if let Some(block) = block_of(&message.dest_event_index) {
+        return incoming.find(|tx| tx.block_number == Some(block));
+    }
+
+    let confirmed = timestamp(message.confirm_block_timestamp)?;
+    let symbol = message.assets.first().map(|a| a.symbol.as_str());
+    incoming
+        .filter(|tx| match (tx.token_symbol.as_deref(), symbol) {
+            (Some(held), Some(sent)) => held.eq_ignore_ascii_case(sent),
+            _ => true,
+        })
+        .filter_map(|tx| {
+            let gap = (tx.timestamp? - confirmed).num_seconds().abs();
+            (gap <= RECEIVE_WINDOW_SECS).then_some((gap, tx))
+        })
+        .min_by_key(|(gap, _)| *gap)
+        .map(|(_, tx)| tx)
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Relay chains to ask about each of the profile's XCM-capable accounts.
+fn relay_queries(wallets: &[Wallet]) -> Vec<(&'static str, String)> {
+    let mut queries: Vec<(&'static str, String)> = Vec::new();
+    for wallet in wallets {
+        let Some(relay) = relay_for_chain(&wallet.chain) else {
+            continue;
+        };
+        let key = identity_key(&wallet.address);
+        let seen = queries
+            .iter()
+            .any(|(r, address)| *r == relay && identity_key(address) == key);
+        if !seen {
+            queries.push((relay, wallet.address.clone()));
+        }
+    }
+    queries
+}
+
+/// Sets a matched leg's transaction type, e.g. `bridge`, recording the
+/// change in the audit trail. Returns whether the leg was changed, or `None`
+/// when its period is closed and it was left alone.
+pub(crate) async fn retype_leg(
+    pool: &SqlitePool,
+    user_id: &str,
+    profile_id: &str,
+    tx: &StoredTransaction,
+    tx_type: &str,
+) -> Result<Option<bool>, String> {
+    if tx.tx_type.as_deref() == Some(tx_type) {
+        return Ok(Some(false));
+    }
+    if let Some(at) = tx.timestamp {
+        if ensure_period_open(pool, Some(profile_id), at.date_naive())
+            .await
+            .is_err()
+        {
+            return Ok(None);
+        }
+    }
+
+    sqlx::query("UPDATE transactions SET tx_type = ? WHERE id = ?")
+        .bind(tx_type)
+        .bind(&tx.id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let after = StoredTransaction {
+        tx_type: Some(tx_type.to_string()),
+        ..tx.clone()
+    };
+    record_change(
+        pool,
+        Some(user_id),
+        RecordType::Transaction,
+        &tx.id,
+        Some(profile_id),
+        Some(tx),
+        Some(&after),
+    )
+    .await?;
+
+    Ok(Some(true))
+}
+
+async fn save_transfer(
+    pool: &SqlitePool,
+    profile_id: &str,
+    message: &SubscanXcmTransfer,
+    origin_chain: &str,
+    dest_chain: &str,
+    send: Option<&StoredTransaction>,
+    receive: Option<&StoredTransaction>,
+) -> Result<(), String> {
+    let asset = message.assets.first();
+    let sent_at = timestamp(message.origin_block_timestamp).unwrap_or_else(Utc::now);
+
+    sqlx::query(
+        r#"
+        INSERT INTO xcm_transfers (
+            id, profile_id, message_hash, origin_chain, dest_chain, from_address, to_address,
+            asset_symbol, amount, status, send_transaction_id, receive_transaction_id,
+            sent_at, received_at, created_at
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(profile_id, message_hash) DO UPDATE SET
+            status = excluded.status,
+            send_transaction_id = COALESCE(excluded.send_transaction_id, send_transaction_id),
+            receive_transaction_id = COALESCE(excluded.receive_transaction_id, receive_transaction_id),
+            received_at = COALESCE(excluded.received_at, received_at)
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(profile_id)
+    .bind(&message.message_hash)
+    .bind(origin_chain)
+    .bind(dest_chain)
+    .bind(&message.from_account_id)
+    .bind(&message.to_account_id)
+    .bind(asset.map(|a| a.symbol.as_str()).unwrap_or_default())
+    .bind(asset.map(|a| a.amount.as_str()).unwrap_or("0"))
+    .bind(&message.status)
+    .bind(send.map(|tx| &tx.id))
+    .bind(receive.map(|tx| &tx.id))
+    .bind(sent_at)
+    .bind(timestamp(message.confirm_block_timestamp))
+    .bind(Utc::now())
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/// Fetches the XCM messages the profile's Polkadot and Kusama ecosystem
+/// accounts sent or received, records each with its stored legs, and types
+/// matched legs as bridge transfers.
+#[tauri::command]
+pub async fn sync_xcm_transfers(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<XcmSyncResult, String> {
+    let user_id = authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ManageWallets,
+    )
+    .await?;
+    let wallets = profile_wallets(&state.pool, &profile_id).await?;
+    let transactions = profile_transactions(&state.pool, &profile_id, i32::MAX, 0).await?;
+    let api_key = ApiKeyManager::get_api_key(ApiProvider::Subscan)
+        .ok()
+        .flatten();
+
+    let mut result = XcmSyncResult::default();
+    let mut seen = Vec::new();
+    for (relay, address) in relay_queries(&wallets) {
+        let Some(base_url) = get_config_by_name(relay).and_then(|c| c.subscan_url) else {
+            continue;
+        };
+        let messages = match SubscanClient::new(&base_url, api_key.as_deref()) {
+            Ok(client) => client.get_xcm_transfers(&address).await,
+            Err(e) => Err(e),
+        };
+        let messages = match messages {
+            Ok(messages) => messages,
+            Err(e) => {
+                log_error!("Failed to fetch XCM transfers for {address}: {e}");
+                result.failed_accounts.push(address);
+                continue;
+            }
+        };
+
+        for message in messages {
+            if seen.contains(&message.message_hash) {
+                continue;
+            }
+            let (Some(origin_chain), Some(dest_chain)) = (
+                chain_for_para(relay, message.origin_para_id),
+                chain_for_para(relay, message.dest_para_id),
+            ) else {
+                continue;
+            };
+
+            let send = find_send_leg(&message, origin_chain, &wallets, &transactions);
+            let receive = find_receive_leg(&message, dest_chain, &wallets, &transactions);
+            save_transfer(
+                &state.pool,
+                &profile_id,
+                &message,
+                origin_chain,
+                dest_chain,
+                send,
+                receive,
+            )
+            .await?;
+            result.transfers += 1;
+
+            for leg in send.into_iter().chain(receive) {
+                match retype_leg(&state.pool, &user_id, &profile_id, leg, "bridge").await? {
+                    Some(true) => result.legs_linked += 1,
+                    Some(false) => {}
+                    None => result.legs_in_closed_periods += 1,
+                }
+            }
+            seen.push(message.message_hash);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Lists a profile's recorded XCM transfers, newest first.
+#[tauri::command]
+pub async fn get_xcm_transfers(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    token: String,
+    profile_id: String,
+) -> Result<Vec<XcmTransfer>, String> {
+    authorize_profile(
+        &state.pool,
+        &auth,
+        &token,
+        &profile_id,
+        Permission::ViewTransactions,
+    )
+    .await?;
+    sqlx::query_as::<_, XcmTransfer>(
+        "SELECT * FROM xcm_transfers WHERE profile_id = ? ORDER BY sent_at DESC",
+    )
+    .bind(&profile_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::substrate::subscan::SubscanXcmAsset;
+
+    const RELAY: &str = "12ibPmtvhZg4hheGLyyjudYNSi9XLVr3VmSPJvLrEgEPKiHJ";
+    const ACALA: &str = "22bByhJxYURLGHguQJmygeCcuZaTtL9FPAyhTkmrsEBG94QJ";
+
+    fn wallet(id: &str, chain: &str, address: &str) -> Wallet {
+        Wallet {
+            id: id.to_string(),
+            profile_id: "p1".to_string(),
+            address: address.to_string(),
+            chain: chain.to_string(),
+            name: None,
+            wallet_type: "software".to_string(),
+            created_at: Utc::now(),
+            updated_at: None,
+        }
+    }
+
+    fn tx(
+        id: &str,
+        wallet_id: &str,
+        block: i64,
+        secs: i64,
+        from: &str,
+        to: &str,
+        symbol: &str,
+    ) -> StoredTransaction {
+        StoredTransaction {
+            id: id.to_string(),
+            wallet_id: wallet_id.to_string(),
+            hash: format!("0x{}", id),
+            block_number: Some(block),
+            timestamp: Utc.timestamp_opt(secs, 0).single(),
+            from_address: Some(from.to_string()),
+            to_address: Some(to.to_string()),
+            value: Some("250000000000".to_string()),
+            fee: None,
+            status: Some("success".to_string()),
+            tx_type: Some("transfer".to_string()),
+            token_symbol: Some(symbol.to_string()),
+            token_decimals: Some(10),
+            chain: "polkadot".to_string(),
+            raw_data: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn message(dest_event_index: &str) -> SubscanXcmTransfer {
+        SubscanXcmTransfer {
+            message_hash: "0xabc".to_string(),
+            origin_para_id: 0,
+            dest_para_id: 2000,
+            from_account_id: RELAY.to_string(),
+            to_account_id: ACALA.to_string(),
+            extrinsic_index: "20512345-2".to_string(),
+            dest_event_index: dest_event_index.to_string(),
+            origin_block_timestamp: 1_714_000_000,
+            confirm_block_timestamp: 1_714_000_024,
+            status: "success".to_string(),
+            assets: vec![SubscanXcmAsset {
+                symbol: "DOT".to_string(),
+                amount: "25".to_string(),
+                decimals: 10,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_matches_both_legs_by_block() {
+        let wallets = vec![
+            wallet("w1", "polkadot", RELAY),
+            // The same key on Acala, stored in Acala's encoding.
+            wallet("w2", "acala", ACALA),
+        ];
+        let transactions = vec![
+            tx(
+                "t1",
+                "w1",
+                20_512_345,
+                1_714_000_000,
+                RELAY,
+                "13UVJyLnbVp9x5XDyJv8rGmA8ZtsNkzhuFiPH8VxZ1LUmjkT",
+                "DOT",
+            ),
+            tx(
+                "t2",
+                "w2",
+                5_123_460,
+                1_714_000_024,
+                "23M5ttkmR6KcoUwA7NqBjLuMJFWCvobsD9Zy95MgaAECEhit",
+                ACALA,
+                "DOT",
+            ),
+            tx(
+                "t3",
+                "w2",
+                5_123_999,
+                1_714_000_030,
+                "23M5ttkmR6KcoUwA7NqBjLuMJFWCvobsD9Zy95MgaAECEhit",
+                ACALA,
+                "DOT",
+            ),
+        ];
+
+        let message = message("5123460-7");
+        let send = find_send_leg(&message, "polkadot", &wallets, &transactions).unwrap();
+        assert_eq!(send.id, "t1");
+        let receive = find_receive_leg(&message, "acala", &wallets, &transactions).unwrap();
+        assert_eq!(receive.id, "t2");
+    }
+
+    #[test]
+    fn test_receive_leg_by_time_without_delivery_block() {
+        let wallets = vec![wallet("w2", "acala", ACALA)];
+        let transactions = vec![
+            tx("t2", "w2", 5_123_460, 1_714_000_300, RELAY, ACALA, "DOT"),
+            tx("t3", "w2", 5_123_461, 1_714_000_030, RELAY, ACALA, "AUSD"),
+            tx("t4", "w2", 5_200_000, 1_714_009_000, RELAY, ACALA, "DOT"),
+        ];
+
+        let receive = find_receive_leg(&message(""), "acala", &wallets, &transactions).unwrap();
+        assert_eq!(receive.id, "t2");
+
+        let pending = SubscanXcmTransfer {
+            confirm_block_timestamp: 0,
+            ..message("")
+        };
+        assert!(find_receive_leg(&pending, "acala", &wallets, &transactions).is_none());
+    }
+
+    #[test]
+    fn test_one_relay_query_per_account() {
+        let wallets = vec![
+            wallet("w1", "polkadot", RELAY),
+            wallet("w2", "acala", ACALA),
+            wallet(
+                "w3",
+                "ethereum",
+                "0x00000000000000000000000000000000000000aa",
+            ),
+        ];
+        assert_eq!(
+            relay_queries(&wallets),
+            vec![("polkadot", RELAY.to_string())]
+        );
+    }
+}
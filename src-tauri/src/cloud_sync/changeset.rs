@@ -0,0 +1,166 @@
+//! Change-set format and encryption.
+//!
+//! A change-set is a batch of record changes from one device. It is
+//! serialized to JSON, encrypted with the sync passphrase, and stored as
+//! `changesets/{millis}_{device_id}.json`, so listings sort by time and a
+//! device can skip its own uploads without decrypting them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::crdt::VectorClock;
+use crate::storage::encryption::{decrypt, encrypt, EncryptedData};
+
+/// Format identifier written into every change-set.
+pub const FORMAT_VERSION: &str = "pacioli-sync-v2";
+
+/// Folder change-sets are stored in.
+pub const CHANGESET_DIR: &str = "changesets";
+
+/// One change to one record.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Change {
+    /// Kind of record, as stored in the audit log.
+    pub record_type: String,
+    /// ID of the record.
+    pub record_id: String,
+    /// One of: create, update, delete.
+    pub action: String,
+    /// Profile the record belongs to, when known.
+    pub profile_id: Option<String>,
+    /// Full record after the change; for deletes, the record as it was.
+    pub data: Value,
+    /// Fields the change wrote.
+    pub fields: Vec<String>,
+    /// Change number on its device.
+    pub counter: i64,
+    /// The record's vector clock including this change.
+    pub clock: VectorClock,
+    /// When the change was made on its device.
+    pub changed_at: DateTime<Utc>,
+}
+
+/// A batch of changes from one device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeSet {
+    /// Format identifier.
+    pub format_version: String,
+    /// Device the changes were made on.
+    pub device_id: String,
+    /// When the batch was created.
+    pub created_at: DateTime<Utc>,
+    /// Changes in the order they were made.
+    pub changes: Vec<Change>,
+}
+
+/// An encrypted change-set as stored remotely.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SealedChangeSet {
+    /// Format identifier.
+    pub format_version: String,
+    /// Argon2 salt (base64).
+    pub salt: String,
+    /// AES-GCM nonce (base64).
+    pub nonce: String,
+    /// Encrypted change-set (base64).
+    pub ciphertext: String,
+}
+
+/// Encrypts a change-set with `passphrase` and serializes it for upload.
+pub fn seal(set: &ChangeSet, passphrase: &str) -> Result<Vec<u8>, String> {
+    let json = serde_json::to_vec(set).map_err(|e| e.to_string())?;
+    let encrypted = encrypt(&json, passphrase).map_err(|e| e.to_string())?;
+    serde_json::to_vec(&SealedChangeSet {
+        format_version: FORMAT_VERSION.to_string(),
+        salt: encrypted.salt,
+        nonce: encrypted.nonce,
+        ciphertext: encrypted.ciphertext,
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Decrypts a downloaded change-set.
+pub fn open(bytes: &[u8], passphrase: &str) -> Result<ChangeSet, String> {
+    let sealed: SealedChangeSet =
+        serde_json::from_slice(bytes).map_err(|e| format!("Invalid change-set: {}", e))?;
+    if sealed.format_version != FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported change-set format: {}",
+            sealed.format_version
+        ));
+    }
+    let json = decrypt(
+        &EncryptedData {
+            salt: sealed.salt,
+            nonce: sealed.nonce,
+            ciphertext: sealed.ciphertext,
+        },
+        passphrase,
+    )
+    .map_err(|_| "Wrong sync passphrase or corrupted change-set".to_string())?;
+    serde_json::from_slice(&json).map_err(|e| format!("Invalid change-set: {}", e))
+}
+
+/// Key a change-set from `device_id` created at `created_at` is stored under.
+pub fn object_key(device_id: &str, created_at: DateTime<Utc>) -> String {
+    format!(
+        "{}/{:013}_{}.json",
+        CHANGESET_DIR,
+        created_at.timestamp_millis(),
+        device_id
+    )
+}
+
+/// Device a change-set key belongs to.
+pub fn key_device(key: &str) -> Option<&str> {
+    let name = key.rsplit('/').next()?.strip_suffix(".json")?;
+    name.split_once('_').map(|(_, device)| device)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_seal_and_open() {
+        let set = ChangeSet {
+            format_version: FORMAT_VERSION.to_string(),
+            device_id: "laptop".to_string(),
+            created_at: Utc::now(),
+            changes: vec![Change {
+                record_type: "entity".to_string(),
+                record_id: "e1".to_string(),
+                action: "update".to_string(),
+                profile_id: Some("p1".to_string()),
+                data: json!({ "id": "e1", "name": "Acme Grants" }),
+                fields: vec!["name".to_string()],
+                counter: 12,
+                clock: [("laptop".to_string(), 12)].into_iter().collect(),
+                changed_at: Utc::now(),
+            }],
+        };
+
+        let sealed = seal(&set, "passphrase").unwrap();
+        assert!(!String::from_utf8_lossy(&sealed).contains("Acme"));
+
+        let opened = open(&sealed, "passphrase").unwrap();
+        assert_eq!(opened.changes, set.changes);
+        assert!(open(&sealed, "other").is_err());
+    }
+
+    #[test]
+    fn test_object_key_round_trip() {
+        let at = DateTime::parse_from_rfc3339("2026-04-11T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let key = object_key("3f2a-device", at);
+        assert_eq!(key, "changesets/1775908800000_3f2a-device.json");
+        assert_eq!(key_device(&key), Some("3f2a-device"));
+        assert_eq!(key_device("changesets/readme.txt"), None);
+    }
+}
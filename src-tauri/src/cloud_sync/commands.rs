@@ -0,0 +1,266 @@
+//! Tauri commands for cloud sync.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::changeset::{self, ChangeSet, CHANGESET_DIR, FORMAT_VERSION};
+use super::merge::{self, MergeConflict};
+use super::{
+    delete_secrets, load_credential, load_passphrase, save_credential, save_passphrase,
+    CloudSyncConfig, SETTINGS_KEY,
+};
+use crate::api::persistence::DatabaseState;
+use crate::core::auth_state::AuthState;
+use crate::storage::settings_store;
+
+/// Most changes packed into one change-set.
+const CHANGES_PER_SET: i64 = 500;
+
+/// Set while a sync run is in progress.
+static SYNC_RUNNING: AtomicBool = AtomicBool::new(false);
+
+// =============================================================================
+// RESPONSE TYPES
+// =============================================================================
+
+/// Sync configuration and progress for display in settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudSyncStatus {
+    /// Stored configuration, if sync has been set up.
+    pub config: Option<CloudSyncConfig>,
+    /// This device's sync ID.
+    pub device_id: String,
+    /// Whether a remote credential is stored in the keychain.
+    pub has_credential: bool,
+    /// Whether an encryption passphrase is stored in the keychain.
+    pub has_passphrase: bool,
+    /// Local changes waiting to be pushed.
+    pub pending_changes: i64,
+    /// When changes were last pushed.
+    pub last_push_at: Option<DateTime<Utc>>,
+    /// When change-sets were last pulled.
+    pub last_pull_at: Option<DateTime<Utc>>,
+    /// Error from the last run, if it failed.
+    pub last_error: Option<String>,
+}
+
+/// What a sync run did.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudSyncReport {
+    /// Local changes uploaded.
+    pub pushed_changes: usize,
+    /// Change-sets uploaded.
+    pub pushed_change_sets: usize,
+    /// Change-sets from other devices merged.
+    pub pulled_change_sets: usize,
+    /// Remote changes applied locally.
+    pub applied_changes: usize,
+    /// Concurrent edits where one side was discarded.
+    pub conflicts: Vec<MergeConflict>,
+    /// Remote changes that couldn't be applied, with the reason.
+    pub skipped: Vec<String>,
+}
+
+/// Clears the running flag when a run ends, however it ends.
+struct RunGuard;
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        SYNC_RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+// =============================================================================
+// HELPERS
+// =============================================================================
+
+async fn load_config(pool: &SqlitePool) -> Result<Option<CloudSyncConfig>, String> {
+    settings_store::get_setting_json(pool, SETTINGS_KEY)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Pushes local changes, then pulls and merges other devices' change-sets.
+async fn run(pool: &SqlitePool, config: &CloudSyncConfig) -> Result<CloudSyncReport, String> {
+    let credential = load_credential()?.ok_or("No sync credential is stored")?;
+    let passphrase = load_passphrase()?.ok_or("No sync passphrase is stored")?;
+    let remote = config.remote(&credential)?;
+    let state = merge::load_state(pool).await?;
+    let mut report = CloudSyncReport::default();
+
+    // Push first so local edits are versioned before remote ones are weighed
+    // against them.
+    let mut cursor = state.last_pushed_rowid;
+    loop {
+        let (changes, last_rowid) =
+            merge::pending_changes(pool, &state.device_id, cursor, CHANGES_PER_SET).await?;
+        if last_rowid == cursor {
+            break;
+        }
+        if !changes.is_empty() {
+            let set = ChangeSet {
+                format_version: FORMAT_VERSION.to_string(),
+                device_id: state.device_id.clone(),
+                created_at: Utc::now(),
+                changes: changes.clone(),
+            };
+            let key = changeset::object_key(&state.device_id, set.created_at);
+            remote
+                .put(&key, changeset::seal(&set, &passphrase)?)
+                .await?;
+            report.pushed_changes += changes.len();
+            report.pushed_change_sets += 1;
+        }
+        merge::mark_pushed(pool, &state.device_id, &changes, last_rowid).await?;
+        cursor = last_rowid;
+    }
+
+    let mut keys = remote.list(CHANGESET_DIR).await?;
+    keys.sort();
+    for key in keys {
+        let Some(device) = changeset::key_device(&key) else {
+            continue;
+        };
+        if device == state.device_id || merge::is_applied(pool, &key).await? {
+            continue;
+        }
+
+        // A change-set this device can't read is left for a later run
+        // rather than blocking everything after it.
+        let set = match changeset::open(&remote.get(&key).await?, &passphrase) {
+            Ok(set) => set,
+            Err(e) => {
+                report.skipped.push(format!("{}: {}", key, e));
+                continue;
+            }
+        };
+        let outcome = merge::apply_change_set(pool, &key, &set).await?;
+        report.pulled_change_sets += 1;
+        report.applied_changes += outcome.applied;
+        report.conflicts.extend(outcome.conflicts);
+        report.skipped.extend(outcome.skipped);
+    }
+
+    Ok(report)
+}
+
+// =============================================================================
+// COMMANDS
+// =============================================================================
+
+/// Returns the sync configuration and this device's progress.
+#[tauri::command]
+pub async fn get_cloud_sync_status(
+    state: State<'_, DatabaseState>,
+) -> Result<CloudSyncStatus, String> {
+    let pool = &state.pool;
+    let sync_state = merge::load_state(pool).await?;
+    Ok(CloudSyncStatus {
+        config: load_config(pool).await?,
+        device_id: sync_state.device_id,
+        has_credential: load_credential()?.is_some(),
+        has_passphrase: load_passphrase()?.is_some(),
+        pending_changes: merge::count_pending(pool, sync_state.last_pushed_rowid).await?,
+        last_push_at: sync_state.last_push_at,
+        last_pull_at: sync_state.last_pull_at,
+        last_error: sync_state.last_error,
+    })
+}
+
+/// Saves the sync configuration.
+///
+/// `credential` and `passphrase` replace the stored values when provided;
+/// pass `None` to keep the existing ones. Every device must use the same
+/// passphrase, and it can't be recovered if lost.
+#[tauri::command]
+pub async fn save_cloud_sync_config(
+    state: State<'_, DatabaseState>,
+    config: CloudSyncConfig,
+    credential: Option<String>,
+    passphrase: Option<String>,
+) -> Result<(), String> {
+    config.validate()?;
+
+    if let Some(credential) = credential.as_deref().filter(|c| !c.is_empty()) {
+        save_credential(credential)?;
+    }
+    if let Some(passphrase) = passphrase.as_deref() {
+        if passphrase.chars().count() < 12 {
+            return Err("The sync passphrase must be at least 12 characters".to_string());
+        }
+        save_passphrase(passphrase)?;
+    }
+
+    settings_store::set_setting_json(&state.pool, SETTINGS_KEY, &config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Turns sync off and removes its settings and keychain entries. Local data
+/// and anything already uploaded are left as they are.
+#[tauri::command]
+pub async fn remove_cloud_sync_config(state: State<'_, DatabaseState>) -> Result<(), String> {
+    settings_store::delete_setting(&state.pool, SETTINGS_KEY)
+        .await
+        .map_err(|e| e.to_string())?;
+    delete_secrets()
+}
+
+/// Runs one sync: uploads local changes and merges other devices' changes.
+///
+/// Local data stays authoritative; a failed run changes nothing that wasn't
+/// already committed and is retried on the next run.
+#[tauri::command]
+pub async fn run_cloud_sync(state: State<'_, DatabaseState>) -> Result<CloudSyncReport, String> {
+    let pool = &state.pool;
+    let config = load_config(pool)
+        .await?
+        .filter(|config| config.enabled)
+        .ok_or("Cloud sync is not enabled")?;
+
+    if SYNC_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err("A sync is already running".to_string());
+    }
+    let _guard = RunGuard;
+
+    let result = run(pool, &config).await;
+    merge::record_run(pool, result.as_ref().err().map(String::as_str)).await?;
+    result
+}
+
+/// Lists conflicts found while merging, newest first. Reviewed conflicts are
+/// included only when `include_resolved` is set.
+#[tauri::command]
+pub async fn get_cloud_sync_conflicts(
+    state: State<'_, DatabaseState>,
+    include_resolved: Option<bool>,
+) -> Result<Vec<MergeConflict>, String> {
+    merge::list_conflicts(&state.pool, include_resolved.unwrap_or(false)).await
+}
+
+/// Marks a conflict as reviewed. With `use_discarded`, the value that lost
+/// replaces the kept one, and the change syncs to the other devices.
+#[tauri::command]
+pub async fn resolve_cloud_sync_conflict(
+    state: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    conflict_id: String,
+    use_discarded: bool,
+) -> Result<MergeConflict, String> {
+    merge::resolve_conflict(
+        &state.pool,
+        auth.active_user().as_deref(),
+        &conflict_id,
+        use_discarded,
+    )
+    .await
+}
@@ -0,0 +1,268 @@
+//! Vector clocks and field-level merge rules.
+//!
+//! Each record is treated as a map of last-writer-wins registers, one per
+//! field. Every local change is numbered by its audit log rowid, and every
+//! record carries a vector clock of the highest change number seen from each
+//! device. Comparing clocks tells a remote change that is already known, one
+//! that supersedes everything local, and one made concurrently with local
+//! edits. Only concurrent writes to the same field are conflicts; those are
+//! settled by change time (device ID breaking ties) so every device keeps the
+//! same value, and reported so the user can pick the other one.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+/// Highest change number seen from each device.
+pub type VectorClock = BTreeMap<String, i64>;
+
+/// How one clock relates to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Causality {
+    /// Both have seen the same changes.
+    Equal,
+    /// The first has seen a subset of the second's changes.
+    Before,
+    /// The first has seen a superset of the second's changes.
+    After,
+    /// Each has seen changes the other hasn't.
+    Concurrent,
+}
+
+/// Which side of a conflict was kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// The value on this device.
+    Local,
+    /// The value from the other device.
+    Remote,
+}
+
+impl Side {
+    /// Value stored in the `kept` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Side::Local => "local",
+            Side::Remote => "remote",
+        }
+    }
+}
+
+/// The change that last wrote a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldStamp {
+    /// Device the change was made on.
+    pub device_id: String,
+    /// Change number on that device.
+    pub counter: i64,
+    /// When the change was made.
+    pub changed_at: DateTime<Utc>,
+}
+
+/// What to do with one field of a remote change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldDecision {
+    /// Write the remote value.
+    TakeRemote,
+    /// Leave the local value.
+    KeepLocal,
+    /// Both sides wrote different values concurrently; the given side wins.
+    Conflict(Side),
+}
+
+/// How records of one type merge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergePolicy {
+    /// Columns that identify the same record created separately on two
+    /// devices under different IDs.
+    pub natural_key: &'static [&'static str],
+    /// Whether a concurrent edit silently beats a delete. Set for set-like
+    /// records such as tags, where keeping the member is always safe.
+    pub add_wins: bool,
+}
+
+/// Merge policy for a synced record type.
+pub fn policy(record_type: &str) -> MergePolicy {
+    match record_type {
+        "transaction_tag" => MergePolicy {
+            natural_key: &["transaction_id", "category"],
+            add_wins: true,
+        },
+        "entity_address" => MergePolicy {
+            natural_key: &["entity_id", "address", "chain"],
+            add_wins: true,
+        },
+        "entity" => MergePolicy {
+            natural_key: &["profile_id", "name", "entity_type"],
+            add_wins: false,
+        },
+        "recurring_series" => MergePolicy {
+            natural_key: &["wallet_id", "counterparty", "direction", "token_symbol"],
+            add_wins: false,
+        },
+        _ => MergePolicy {
+            natural_key: &[],
+            add_wins: false,
+        },
+    }
+}
+
+/// How `a` relates to `b`.
+pub fn compare(a: &VectorClock, b: &VectorClock) -> Causality {
+    let mut a_ahead = false;
+    let mut b_ahead = false;
+    for device in a.keys().chain(b.keys()) {
+        let x = a.get(device).copied().unwrap_or(0);
+        let y = b.get(device).copied().unwrap_or(0);
+        a_ahead |= x > y;
+        b_ahead |= y > x;
+    }
+    match (a_ahead, b_ahead) {
+        (false, false) => Causality::Equal,
+        (false, true) => Causality::Before,
+        (true, false) => Causality::After,
+        (true, true) => Causality::Concurrent,
+    }
+}
+
+/// Clock that has seen everything either clock has.
+pub fn merge_clocks(a: &VectorClock, b: &VectorClock) -> VectorClock {
+    let mut merged = a.clone();
+    for (device, counter) in b {
+        let entry = merged.entry(device.clone()).or_insert(0);
+        *entry = (*entry).max(*counter);
+    }
+    merged
+}
+
+/// Whether `clock` has seen the change that wrote `stamp`.
+pub fn covers(clock: &VectorClock, stamp: &FieldStamp) -> bool {
+    clock.get(&stamp.device_id).copied().unwrap_or(0) >= stamp.counter
+}
+
+/// Whether a write made at `remote_changed_at` on `remote_device` is later
+/// than the local write. Device IDs break ties so both sides agree.
+pub fn remote_wins(
+    local: &FieldStamp,
+    remote_changed_at: DateTime<Utc>,
+    remote_device: &str,
+) -> bool {
+    (remote_changed_at, remote_device) > (local.changed_at, local.device_id.as_str())
+}
+
+/// Decides one field of a remote change that isn't already known locally.
+///
+/// The remote value is taken when the local value was written by a change
+/// the remote device had seen. Otherwise both sides wrote the field
+/// concurrently: equal values need nothing, different values are a conflict
+/// won by the later write.
+pub fn decide_field(
+    local_stamp: Option<&FieldStamp>,
+    local_value: &Value,
+    remote_value: &Value,
+    remote_clock: &VectorClock,
+    remote_changed_at: DateTime<Utc>,
+    remote_device: &str,
+) -> FieldDecision {
+    let Some(stamp) = local_stamp.filter(|stamp| !covers(remote_clock, stamp)) else {
+        return FieldDecision::TakeRemote;
+    };
+    let wins = remote_wins(stamp, remote_changed_at, remote_device);
+    match (local_value == remote_value, wins) {
+        (true, true) => FieldDecision::TakeRemote,
+        (true, false) => FieldDecision::KeepLocal,
+        (false, true) => FieldDecision::Conflict(Side::Remote),
+        (false, false) => FieldDecision::Conflict(Side::Local),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_json::json;
+
+    fn clock(entries: &[(&str, i64)]) -> VectorClock {
+        entries
+            .iter()
+            .map(|(device, counter)| (device.to_string(), *counter))
+            .collect()
+    }
+
+    fn stamp(device: &str, counter: i64, changed_at: DateTime<Utc>) -> FieldStamp {
+        FieldStamp {
+            device_id: device.to_string(),
+            counter,
+            changed_at,
+        }
+    }
+
+    #[test]
+    fn test_compare_clocks() {
+        let a = clock(&[("laptop", 3), ("desktop", 1)]);
+        assert_eq!(compare(&a, &a), Causality::Equal);
+        assert_eq!(compare(&clock(&[("laptop", 2)]), &a), Causality::Before);
+        assert_eq!(compare(&a, &clock(&[("laptop", 2)])), Causality::After);
+        assert_eq!(
+            compare(&a, &clock(&[("laptop", 2), ("desktop", 5)])),
+            Causality::Concurrent
+        );
+        assert_eq!(
+            merge_clocks(&a, &clock(&[("laptop", 2), ("phone", 4)])),
+            clock(&[("laptop", 3), ("desktop", 1), ("phone", 4)])
+        );
+    }
+
+    #[test]
+    fn test_decide_field() {
+        let now = Utc::now();
+        let local = stamp("laptop", 7, now);
+        let name = json!("Acme");
+        let other = json!("Acme Inc");
+
+        // The remote device saw the local write, so its value supersedes it.
+        let seen = clock(&[("laptop", 7), ("desktop", 2)]);
+        assert_eq!(
+            decide_field(Some(&local), &name, &other, &seen, now, "desktop"),
+            FieldDecision::TakeRemote
+        );
+        assert_eq!(
+            decide_field(None, &name, &other, &clock(&[]), now, "desktop"),
+            FieldDecision::TakeRemote
+        );
+
+        // Concurrent writes: the later one wins and the other is reported.
+        let unseen = clock(&[("laptop", 6), ("desktop", 2)]);
+        let later = now + Duration::seconds(5);
+        let earlier = now - Duration::seconds(5);
+        assert_eq!(
+            decide_field(Some(&local), &name, &other, &unseen, later, "desktop"),
+            FieldDecision::Conflict(Side::Remote)
+        );
+        assert_eq!(
+            decide_field(Some(&local), &name, &other, &unseen, earlier, "desktop"),
+            FieldDecision::Conflict(Side::Local)
+        );
+        assert_eq!(
+            decide_field(Some(&local), &name, &name, &unseen, earlier, "desktop"),
+            FieldDecision::KeepLocal
+        );
+    }
+
+    #[test]
+    fn test_concurrent_field_conflicts_settle_the_same_way_on_both_devices() {
+        let now = Utc::now();
+        let laptop = stamp("laptop", 4, now);
+        let desktop = stamp("desktop", 9, now);
+        let laptop_clock = clock(&[("laptop", 4)]);
+        let desktop_clock = clock(&[("desktop", 9)]);
+        let (a, b) = (json!("a"), json!("b"));
+
+        let on_laptop = decide_field(Some(&laptop), &a, &b, &desktop_clock, now, "desktop");
+        let on_desktop = decide_field(Some(&desktop), &b, &a, &laptop_clock, now, "laptop");
+        // Same time, so the greater device ID wins everywhere: both keep "a".
+        assert_eq!(on_laptop, FieldDecision::Conflict(Side::Local));
+        assert_eq!(on_desktop, FieldDecision::Conflict(Side::Remote));
+    }
+}
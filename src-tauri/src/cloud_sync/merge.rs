@@ -0,0 +1,1236 @@
+//! Collecting local changes and merging remote ones.
+//!
+//! Local changes are read from `data_audit_log` after the push cursor. Changes
+//! applied from other devices are recorded there too, with a `sync:` actor, and
+//! are never pushed back. Each record's vector clock is kept in
+//! `cloud_sync_clocks` and the change that last wrote each of its fields in
+//! `cloud_sync_field_stamps`; [`super::crdt`] uses them to merge remote
+//! changes field by field. Conflicts are kept in `cloud_sync_conflicts` until
+//! the user reviews them.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::query::Query;
+use sqlx::sqlite::{Sqlite, SqliteArguments};
+use sqlx::{Connection, FromRow, SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use super::changeset::{Change, ChangeSet};
+use super::crdt::{self, Causality, FieldDecision, FieldStamp, MergePolicy, Side, VectorClock};
+use crate::api::audit_trail::{record_change, RecordType};
+
+/// Actor prefix for changes applied from another device.
+pub const SYNC_ACTOR_PREFIX: &str = "sync:";
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// This device's sync identity and progress.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncState {
+    /// ID this device signs its change-sets with.
+    pub device_id: String,
+    /// rowid of the last audit log entry pushed.
+    pub last_pushed_rowid: i64,
+    /// When changes were last pushed.
+    pub last_push_at: Option<DateTime<Utc>>,
+    /// When change-sets were last pulled.
+    pub last_pull_at: Option<DateTime<Utc>>,
+    /// Error from the last sync run, if it failed.
+    pub last_error: Option<String>,
+}
+
+/// Concurrent edits on two devices that couldn't both be kept.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeConflict {
+    /// Unique identifier.
+    pub id: String,
+    /// Kind of record.
+    pub record_type: String,
+    /// ID of the record on this device.
+    pub record_id: String,
+    /// Profile the record belongs to, when known.
+    pub profile_id: Option<String>,
+    /// Field both sides wrote; absent when one side deleted the record.
+    pub field: Option<String>,
+    /// Which side was kept: local or remote.
+    pub kept: String,
+    /// This device's value as JSON; absent if it had deleted the record.
+    pub local_value: Option<String>,
+    /// The other device's value as JSON; absent if it had deleted the record.
+    pub remote_value: Option<String>,
+    /// When the local value was written.
+    pub local_changed_at: DateTime<Utc>,
+    /// When the remote value was written.
+    pub remote_changed_at: DateTime<Utc>,
+    /// Device the remote value came from.
+    pub remote_device: String,
+    /// When the conflict was found.
+    pub detected_at: DateTime<Utc>,
+    /// When the user reviewed it.
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// Result of merging one change-set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeOutcome {
+    /// Changes that altered the local database.
+    pub applied: usize,
+    /// Concurrent edits where one side was discarded.
+    pub conflicts: Vec<MergeConflict>,
+    /// Changes that couldn't be applied, with the reason.
+    pub skipped: Vec<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Table a synced record type is stored in. Journal entries and raw chain
+/// transactions are rebuilt on each device and aren't synced.
+pub fn record_table(record_type: &str) -> Option<&'static str> {
+    match record_type {
+        "wallet" => Some("wallets"),
+        "transaction" => Some("transactions"),
+        "transaction_tag" => Some("transaction_tags"),
+        "entity" => Some("entities"),
+        "entity_address" => Some("entity_addresses"),
+        "recurring_series" => Some("recurring_series"),
+        _ => None,
+    }
+}
+
+/// Loads this device's sync state, assigning a device ID on first use.
+pub async fn load_state(pool: &SqlitePool) -> Result<SyncState, String> {
+    sqlx::query("INSERT OR IGNORE INTO cloud_sync_state (id, device_id) VALUES (1, ?)")
+        .bind(Uuid::new_v4().to_string())
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    sqlx::query_as::<_, SyncState>(
+        r#"
+        SELECT device_id, last_pushed_rowid, last_push_at, last_pull_at, last_error
+        FROM cloud_sync_state WHERE id = 1
+        "#,
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Number of local changes not yet pushed.
+pub async fn count_pending(pool: &SqlitePool, after_rowid: i64) -> Result<i64, String> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM data_audit_log WHERE rowid > ? AND actor NOT LIKE ?")
+        .bind(after_rowid)
+        .bind(format!("{}%", SYNC_ACTOR_PREFIX))
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[derive(FromRow)]
+struct AuditRow {
+    rowid: i64,
+    record_type: String,
+    record_id: String,
+    action: String,
+    profile_id: Option<String>,
+    before_data: Option<String>,
+    after_data: Option<String>,
+    changes: String,
+    created_at: DateTime<Utc>,
+}
+
+/// Up to `limit` local changes made after `after_rowid`, with the rowid of
+/// the last one. Each change is numbered by its rowid and carries its
+/// record's clock advanced to that number.
+pub async fn pending_changes(
+    pool: &SqlitePool,
+    device_id: &str,
+    after_rowid: i64,
+    limit: i64,
+) -> Result<(Vec<Change>, i64), String> {
+    let rows = sqlx::query_as::<_, AuditRow>(
+        r#"
+        SELECT rowid, record_type, record_id, action, profile_id, before_data, after_data,
+               changes, created_at
+        FROM data_audit_log
+        WHERE rowid > ? AND actor NOT LIKE ?
+        ORDER BY rowid ASC
+        LIMIT ?
+        "#,
+    )
+    .bind(after_rowid)
+    .bind(format!("{}%", SYNC_ACTOR_PREFIX))
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let last_rowid = rows.last().map_or(after_rowid, |row| row.rowid);
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    let mut clocks: HashMap<(String, String), VectorClock> = HashMap::new();
+    let mut changes = Vec::new();
+
+    for row in rows {
+        if record_table(&row.record_type).is_none() {
+            continue;
+        }
+        let key = (row.record_type.clone(), row.record_id.clone());
+        let mut clock = match clocks.remove(&key) {
+            Some(clock) => clock,
+            None => load_clock(&mut conn, &row.record_type, &row.record_id).await?,
+        };
+        clock.insert(device_id.to_string(), row.rowid);
+        clocks.insert(key, clock.clone());
+
+        let snapshot = if row.action == "delete" {
+            row.before_data
+        } else {
+            row.after_data
+        };
+        let fields = serde_json::from_str::<Map<String, Value>>(&row.changes)
+            .map(|changed| changed.into_iter().map(|(field, _)| field).collect())
+            .unwrap_or_default();
+        changes.push(Change {
+            data: snapshot
+                .as_deref()
+                .and_then(|data| serde_json::from_str(data).ok())
+                .unwrap_or(Value::Null),
+            record_type: row.record_type,
+            record_id: row.record_id,
+            action: row.action,
+            profile_id: row.profile_id,
+            fields,
+            counter: row.rowid,
+            clock,
+            changed_at: row.created_at,
+        });
+    }
+    Ok((changes, last_rowid))
+}
+
+/// Records pushed changes in their records' clocks and field stamps, and
+/// advances the cursor.
+pub async fn mark_pushed(
+    pool: &SqlitePool,
+    device_id: &str,
+    changes: &[Change],
+    last_rowid: i64,
+) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for change in changes {
+        let stored = load_clock(&mut tx, &change.record_type, &change.record_id).await?;
+        save_clock(
+            &mut tx,
+            &change.record_type,
+            &change.record_id,
+            &crdt::merge_clocks(&stored, &change.clock),
+        )
+        .await?;
+        let stamp = FieldStamp {
+            device_id: device_id.to_string(),
+            counter: change.counter,
+            changed_at: change.changed_at,
+        };
+        for field in &change.fields {
+            save_stamp(
+                &mut tx,
+                &change.record_type,
+                &change.record_id,
+                field,
+                &stamp,
+            )
+            .await?;
+        }
+    }
+    sqlx::query(
+        r#"
+        UPDATE cloud_sync_state
+        SET last_pushed_rowid = ?, last_push_at = ?, updated_at = ?
+        WHERE id = 1
+        "#,
+    )
+    .bind(last_rowid)
+    .bind(Utc::now())
+    .bind(Utc::now())
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+/// Records the end of a sync run.
+pub async fn record_run(pool: &SqlitePool, error: Option<&str>) -> Result<(), String> {
+    let now = Utc::now();
+    sqlx::query(
+        r#"
+        UPDATE cloud_sync_state
+        SET last_error = ?,
+            last_pull_at = CASE WHEN ? IS NULL THEN ? ELSE last_pull_at END,
+            updated_at = ?
+        WHERE id = 1
+        "#,
+    )
+    .bind(error)
+    .bind(error)
+    .bind(now)
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Whether a change-set has already been merged.
+pub async fn is_applied(pool: &SqlitePool, object_key: &str) -> Result<bool, String> {
+    let found: Option<String> =
+        sqlx::query_scalar("SELECT object_key FROM cloud_sync_applied WHERE object_key = ?")
+            .bind(object_key)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    Ok(found.is_some())
+}
+
+async fn load_clock(
+    conn: &mut SqliteConnection,
+    record_type: &str,
+    record_id: &str,
+) -> Result<VectorClock, String> {
+    let clock: Option<String> = sqlx::query_scalar(
+        "SELECT clock FROM cloud_sync_clocks WHERE record_type = ? AND record_id = ?",
+    )
+    .bind(record_type)
+    .bind(record_id)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(clock
+        .and_then(|clock| serde_json::from_str(&clock).ok())
+        .unwrap_or_default())
+}
+
+async fn save_clock(
+    conn: &mut SqliteConnection,
+    record_type: &str,
+    record_id: &str,
+    clock: &VectorClock,
+) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        INSERT INTO cloud_sync_clocks (record_type, record_id, clock) VALUES (?, ?, ?)
+        ON CONFLICT(record_type, record_id) DO UPDATE SET clock = excluded.clock
+        "#,
+    )
+    .bind(record_type)
+    .bind(record_id)
+    .bind(serde_json::to_string(clock).map_err(|e| e.to_string())?)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn load_stamps(
+    conn: &mut SqliteConnection,
+    record_type: &str,
+    record_id: &str,
+) -> Result<HashMap<String, FieldStamp>, String> {
+    let rows: Vec<(String, String, i64, DateTime<Utc>)> = sqlx::query_as(
+        r#"
+        SELECT field, device_id, counter, changed_at FROM cloud_sync_field_stamps
+        WHERE record_type = ? AND record_id = ?
+        "#,
+    )
+    .bind(record_type)
+    .bind(record_id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(rows
+        .into_iter()
+        .map(|(field, device_id, counter, changed_at)| {
+            (
+                field,
+                FieldStamp {
+                    device_id,
+                    counter,
+                    changed_at,
+                },
+            )
+        })
+        .collect())
+}
+
+async fn save_stamp(
+    conn: &mut SqliteConnection,
+    record_type: &str,
+    record_id: &str,
+    field: &str,
+    stamp: &FieldStamp,
+) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        INSERT INTO cloud_sync_field_stamps
+            (record_type, record_id, field, device_id, counter, changed_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT(record_type, record_id, field) DO UPDATE SET
+            device_id = excluded.device_id,
+            counter = excluded.counter,
+            changed_at = excluded.changed_at
+        "#,
+    )
+    .bind(record_type)
+    .bind(record_id)
+    .bind(field)
+    .bind(&stamp.device_id)
+    .bind(stamp.counter)
+    .bind(stamp.changed_at)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn table_columns(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>, String> {
+    sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// The current row as a JSON object, or None if it doesn't exist.
+async fn row_snapshot(
+    conn: &mut SqliteConnection,
+    table: &str,
+    columns: &[String],
+    id: &str,
+) -> Result<Option<Value>, String> {
+    let fields: Vec<String> = columns
+        .iter()
+        .map(|column| format!("'{}', \"{}\"", column, column))
+        .collect();
+    let sql = format!(
+        "SELECT json_object({}) FROM {} WHERE id = ?",
+        fields.join(", "),
+        table
+    );
+    let row: Option<String> = sqlx::query_scalar(&sql)
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(row.and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+/// Binds a JSON value as the matching SQLite type.
+fn bind_json<'q>(
+    query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    value: &Value,
+) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+    match value {
+        Value::Null => query.bind(None::<String>),
+        Value::Bool(b) => query.bind(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => query.bind(i),
+            None => query.bind(n.as_f64()),
+        },
+        Value::String(s) => query.bind(s.clone()),
+        other => query.bind(other.to_string()),
+    }
+}
+
+/// Inserts or replaces a row from a snapshot, ignoring fields that aren't
+/// columns of the table.
+async fn upsert_row(
+    conn: &mut SqliteConnection,
+    table: &str,
+    columns: &[String],
+    data: &Value,
+) -> Result<(), String> {
+    let object = data
+        .as_object()
+        .ok_or_else(|| "record snapshot is not an object".to_string())?;
+    let fields: Vec<(&String, &Value)> = columns
+        .iter()
+        .filter_map(|column| object.get(column).map(|value| (column, value)))
+        .collect();
+    if !fields.iter().any(|(column, _)| column.as_str() == "id") {
+        return Err("record snapshot has no id".to_string());
+    }
+
+    let names: Vec<String> = fields.iter().map(|(c, _)| format!("\"{}\"", c)).collect();
+    let updates: Vec<String> = names
+        .iter()
+        .map(|name| format!("{} = excluded.{}", name, name))
+        .collect();
+    let sql = format!(
+        "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT(id) DO UPDATE SET {}",
+        table,
+        names.join(", "),
+        vec!["?"; names.len()].join(", "),
+        updates.join(", ")
+    );
+
+    let mut query = sqlx::query(&sql);
+    for (_, value) in &fields {
+        query = bind_json(query, value);
+    }
+    query.execute(&mut *conn).await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Writes some fields of an existing row.
+async fn update_fields(
+    conn: &mut SqliteConnection,
+    table: &str,
+    id: &str,
+    fields: &Map<String, Value>,
+) -> Result<(), String> {
+    if fields.is_empty() {
+        return Ok(());
+    }
+    let sets: Vec<String> = fields
+        .keys()
+        .map(|field| format!("\"{}\" = ?", field))
+        .collect();
+    let sql = format!("UPDATE {} SET {} WHERE id = ?", table, sets.join(", "));
+    let mut query = sqlx::query(&sql);
+    for value in fields.values() {
+        query = bind_json(query, value);
+    }
+    query
+        .bind(id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// ID of the local row a remote change applies to. A record created
+/// separately on each device is matched by its natural key.
+async fn resolve_local_id(
+    conn: &mut SqliteConnection,
+    table: &str,
+    policy: &MergePolicy,
+    change: &Change,
+) -> Result<String, String> {
+    let exists: Option<String> =
+        sqlx::query_scalar(&format!("SELECT id FROM {} WHERE id = ?", table))
+            .bind(&change.record_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    let key_values: Option<Vec<&Value>> = policy
+        .natural_key
+        .iter()
+        .map(|column| change.data.get(*column).filter(|value| !value.is_null()))
+        .collect();
+    let (None, Some(key_values)) = (exists, key_values.filter(|values| !values.is_empty())) else {
+        return Ok(change.record_id.clone());
+    };
+
+    let conditions: Vec<String> = policy
+        .natural_key
+        .iter()
+        .map(|column| format!("\"{}\" = ?", column))
+        .collect();
+    let sql = format!(
+        "SELECT id FROM {} WHERE {}",
+        table,
+        conditions.join(" AND ")
+    );
+    let mut query = sqlx::query_scalar::<_, String>(&sql);
+    for value in key_values {
+        query = match value {
+            Value::String(s) => query.bind(s.clone()),
+            other => query.bind(other.to_string()),
+        };
+    }
+    let matched = query
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(matched.unwrap_or_else(|| change.record_id.clone()))
+}
+
+/// Details of one conflict found while merging a change.
+struct ConflictInput<'a> {
+    record_id: &'a str,
+    field: Option<&'a str>,
+    kept: Side,
+    local_value: Option<&'a Value>,
+    remote_value: Option<&'a Value>,
+    local_changed_at: DateTime<Utc>,
+}
+
+async fn insert_conflict(
+    conn: &mut SqliteConnection,
+    set: &ChangeSet,
+    change: &Change,
+    input: ConflictInput<'_>,
+) -> Result<MergeConflict, String> {
+    let conflict = MergeConflict {
+        id: Uuid::new_v4().to_string(),
+        record_type: change.record_type.clone(),
+        record_id: input.record_id.to_string(),
+        profile_id: change.profile_id.clone(),
+        field: input.field.map(str::to_string),
+        kept: input.kept.as_str().to_string(),
+        local_value: input.local_value.map(Value::to_string),
+        remote_value: input.remote_value.map(Value::to_string),
+        local_changed_at: input.local_changed_at,
+        remote_changed_at: change.changed_at,
+        remote_device: set.device_id.clone(),
+        detected_at: Utc::now(),
+        resolved_at: None,
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO cloud_sync_conflicts (
+            id, record_type, record_id, profile_id, field, kept, local_value, remote_value,
+            local_changed_at, remote_changed_at, remote_device, detected_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&conflict.id)
+    .bind(&conflict.record_type)
+    .bind(&conflict.record_id)
+    .bind(&conflict.profile_id)
+    .bind(&conflict.field)
+    .bind(&conflict.kept)
+    .bind(&conflict.local_value)
+    .bind(&conflict.remote_value)
+    .bind(conflict.local_changed_at)
+    .bind(conflict.remote_changed_at)
+    .bind(&conflict.remote_device)
+    .bind(conflict.detected_at)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(conflict)
+}
+
+/// Merges one remote change into the local row. Returns whether the row
+/// changed, and the conflicts found.
+async fn apply_change(
+    conn: &mut SqliteConnection,
+    set: &ChangeSet,
+    change: &Change,
+    table: &str,
+    record_type: RecordType,
+) -> Result<(bool, Vec<MergeConflict>), String> {
+    let policy = crdt::policy(&change.record_type);
+    let columns = table_columns(conn, table).await?;
+    let local_id = resolve_local_id(conn, table, &policy, change).await?;
+    let local_clock = load_clock(conn, &change.record_type, &local_id).await?;
+
+    // Already seen, directly or through a later change.
+    let causality = crdt::compare(&change.clock, &local_clock);
+    if matches!(causality, Causality::Equal | Causality::Before) {
+        return Ok((false, Vec::new()));
+    }
+    let concurrent = causality == Causality::Concurrent;
+
+    let stamps = load_stamps(conn, &change.record_type, &local_id).await?;
+    let remote_stamp = FieldStamp {
+        device_id: set.device_id.clone(),
+        counter: change.counter,
+        changed_at: change.changed_at,
+    };
+    let local_changed_at = stamps
+        .values()
+        .map(|stamp| stamp.changed_at)
+        .max()
+        .unwrap_or(change.changed_at);
+    let before = row_snapshot(conn, table, &columns, &local_id).await?;
+    let mut written: Vec<String> = Vec::new();
+    let mut conflicts = Vec::new();
+
+    match (change.action == "delete", before.as_ref()) {
+        (true, None) => {}
+        (true, Some(row)) => {
+            if !concurrent {
+                sqlx::query(&format!("DELETE FROM {} WHERE id = ?", table))
+                    .bind(&local_id)
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                written = columns.clone();
+            } else if !policy.add_wins {
+                // Edits this device made without seeing the delete keep the
+                // record alive.
+                let conflict = insert_conflict(
+                    conn,
+                    set,
+                    change,
+                    ConflictInput {
+                        record_id: &local_id,
+                        field: None,
+                        kept: Side::Local,
+                        local_value: Some(row),
+                        remote_value: None,
+                        local_changed_at,
+                    },
+                )
+                .await?;
+                conflicts.push(conflict);
+            }
+        }
+        (false, None) => {
+            if concurrent && !policy.add_wins {
+                let conflict = insert_conflict(
+                    conn,
+                    set,
+                    change,
+                    ConflictInput {
+                        record_id: &local_id,
+                        field: None,
+                        kept: Side::Remote,
+                        local_value: None,
+                        remote_value: Some(&change.data),
+                        local_changed_at,
+                    },
+                )
+                .await?;
+                conflicts.push(conflict);
+            }
+            upsert_row(conn, table, &columns, &change.data).await?;
+            written = columns.clone();
+        }
+        (false, Some(row)) => {
+            let fields: Vec<&String> = if change.fields.is_empty() {
+                change
+                    .data
+                    .as_object()
+                    .map_or_else(Vec::new, |data| data.keys().collect())
+            } else {
+                change.fields.iter().collect()
+            };
+            let mut patch = Map::new();
+            for field in fields {
+                if field == "id" || !columns.contains(field) {
+                    continue;
+                }
+                let local_value = row.get(field).unwrap_or(&Value::Null);
+                let remote_value = change.data.get(field).unwrap_or(&Value::Null);
+                let decision = crdt::decide_field(
+                    stamps.get(field),
+                    local_value,
+                    remote_value,
+                    &change.clock,
+                    change.changed_at,
+                    &set.device_id,
+                );
+                if let FieldDecision::Conflict(kept) = decision {
+                    let conflict = insert_conflict(
+                        conn,
+                        set,
+                        change,
+                        ConflictInput {
+                            record_id: &local_id,
+                            field: Some(field),
+                            kept,
+                            local_value: Some(local_value),
+                            remote_value: Some(remote_value),
+                            local_changed_at: stamps
+                                .get(field)
+                                .map_or(local_changed_at, |stamp| stamp.changed_at),
+                        },
+                    )
+                    .await?;
+                    conflicts.push(conflict);
+                }
+                if matches!(
+                    decision,
+                    FieldDecision::TakeRemote | FieldDecision::Conflict(Side::Remote)
+                ) {
+                    patch.insert(field.clone(), remote_value.clone());
+                }
+            }
+            update_fields(conn, table, &local_id, &patch).await?;
+            written = patch.keys().cloned().collect();
+        }
+    }
+
+    for field in &written {
+        save_stamp(conn, &change.record_type, &local_id, field, &remote_stamp).await?;
+    }
+    save_clock(
+        conn,
+        &change.record_type,
+        &local_id,
+        &crdt::merge_clocks(&local_clock, &change.clock),
+    )
+    .await?;
+
+    if written.is_empty() {
+        return Ok((false, conflicts));
+    }
+    let after = row_snapshot(conn, table, &columns, &local_id).await?;
+    record_change(
+        &mut *conn,
+        Some(&format!("{}{}", SYNC_ACTOR_PREFIX, set.device_id)),
+        record_type,
+        &local_id,
+        change.profile_id.as_deref(),
+        before.as_ref(),
+        after.as_ref(),
+    )
+    .await?;
+    Ok((true, conflicts))
+}
+
+/// Merges a change-set from another device in one database transaction.
+/// Change-sets already merged are ignored.
+pub async fn apply_change_set(
+    pool: &SqlitePool,
+    object_key: &str,
+    set: &ChangeSet,
+) -> Result<MergeOutcome, String> {
+    let mut outcome = MergeOutcome::default();
+    if is_applied(pool, object_key).await? {
+        return Ok(outcome);
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+    for change in &set.changes {
+        let (Some(table), Some(record_type)) = (
+            record_table(&change.record_type),
+            RecordType::parse(&change.record_type),
+        ) else {
+            outcome.skipped.push(format!(
+                "{} {}: not synced",
+                change.record_type, change.record_id
+            ));
+            continue;
+        };
+
+        // A change that fails part-way is rolled back on its own.
+        let mut savepoint = Connection::begin(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        match apply_change(&mut savepoint, set, change, table, record_type).await {
+            Ok((changed, conflicts)) => {
+                savepoint.commit().await.map_err(|e| e.to_string())?;
+                outcome.applied += usize::from(changed);
+                outcome.conflicts.extend(conflicts);
+            }
+            Err(e) => {
+                savepoint.rollback().await.map_err(|e| e.to_string())?;
+                outcome.skipped.push(format!(
+                    "{} {}: {}",
+                    change.record_type, change.record_id, e
+                ));
+            }
+        }
+    }
+
+    sqlx::query(
+        "INSERT INTO cloud_sync_applied (object_key, device_id, change_count, applied_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(object_key)
+    .bind(&set.device_id)
+    .bind(set.changes.len() as i64)
+    .bind(Utc::now())
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(outcome)
+}
+
+/// Merge conflicts, newest first; reviewed ones only if `include_resolved`.
+pub async fn list_conflicts(
+    pool: &SqlitePool,
+    include_resolved: bool,
+) -> Result<Vec<MergeConflict>, String> {
+    sqlx::query_as::<_, MergeConflict>(
+        r#"
+        SELECT * FROM cloud_sync_conflicts
+        WHERE ? OR resolved_at IS NULL
+        ORDER BY detected_at DESC
+        "#,
+    )
+    .bind(include_resolved)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Marks a conflict as reviewed. With `use_discarded`, the value that lost
+/// is written back as a new local change by `actor`, so it syncs to the
+/// other devices like any other edit.
+pub async fn resolve_conflict(
+    pool: &SqlitePool,
+    actor: Option<&str>,
+    conflict_id: &str,
+    use_discarded: bool,
+) -> Result<MergeConflict, String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let mut conflict =
+        sqlx::query_as::<_, MergeConflict>("SELECT * FROM cloud_sync_conflicts WHERE id = ?")
+            .bind(conflict_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Conflict not found: {}", conflict_id))?;
+
+    if use_discarded {
+        let (Some(table), Some(record_type)) = (
+            record_table(&conflict.record_type),
+            RecordType::parse(&conflict.record_type),
+        ) else {
+            return Err(format!("{} records aren't synced", conflict.record_type));
+        };
+        let discarded = if conflict.kept == Side::Local.as_str() {
+            conflict.remote_value.as_deref()
+        } else {
+            conflict.local_value.as_deref()
+        };
+        let discarded: Option<Value> = discarded
+            .map(serde_json::from_str)
+            .transpose()
+            .map_err(|e| e.to_string())?;
+
+        let columns = table_columns(&mut tx, table).await?;
+        let before = row_snapshot(&mut tx, table, &columns, &conflict.record_id).await?;
+        match (conflict.field.as_deref(), discarded) {
+            (Some(field), Some(value)) => {
+                if !columns.iter().any(|column| column == field) {
+                    return Err(format!("Unknown field: {}", field));
+                }
+                let mut patch = Map::new();
+                patch.insert(field.to_string(), value);
+                update_fields(&mut tx, table, &conflict.record_id, &patch).await?;
+            }
+            (None, Some(record)) => upsert_row(&mut tx, table, &columns, &record).await?,
+            (None, None) => {
+                sqlx::query(&format!("DELETE FROM {} WHERE id = ?", table))
+                    .bind(&conflict.record_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            (Some(_), None) => {}
+        }
+        let after = row_snapshot(&mut tx, table, &columns, &conflict.record_id).await?;
+        record_change(
+            &mut *tx,
+            actor,
+            record_type,
+            &conflict.record_id,
+            conflict.profile_id.as_deref(),
+            before.as_ref(),
+            after.as_ref(),
+        )
+        .await?;
+    }
+
+    let resolved_at = Utc::now();
+    sqlx::query("UPDATE cloud_sync_conflicts SET resolved_at = ? WHERE id = ?")
+        .bind(resolved_at)
+        .bind(conflict_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    conflict.resolved_at = Some(resolved_at);
+    Ok(conflict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud_sync::changeset::FORMAT_VERSION;
+    use chrono::Duration;
+    use serde_json::json;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for ddl in [
+            r#"
+            CREATE TABLE data_audit_log (
+                id TEXT PRIMARY KEY,
+                record_type TEXT NOT NULL,
+                record_id TEXT NOT NULL,
+                action TEXT NOT NULL,
+                actor TEXT NOT NULL,
+                profile_id TEXT,
+                before_data TEXT,
+                after_data TEXT,
+                changes TEXT NOT NULL,
+                created_at DATETIME NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE cloud_sync_clocks (
+                record_type TEXT NOT NULL,
+                record_id TEXT NOT NULL,
+                clock TEXT NOT NULL,
+                PRIMARY KEY (record_type, record_id)
+            )
+            "#,
+            r#"
+            CREATE TABLE cloud_sync_field_stamps (
+                record_type TEXT NOT NULL,
+                record_id TEXT NOT NULL,
+                field TEXT NOT NULL,
+                device_id TEXT NOT NULL,
+                counter INTEGER NOT NULL,
+                changed_at DATETIME NOT NULL,
+                PRIMARY KEY (record_type, record_id, field)
+            )
+            "#,
+            r#"
+            CREATE TABLE cloud_sync_conflicts (
+                id TEXT PRIMARY KEY,
+                record_type TEXT NOT NULL,
+                record_id TEXT NOT NULL,
+                profile_id TEXT,
+                field TEXT,
+                kept TEXT NOT NULL,
+                local_value TEXT,
+                remote_value TEXT,
+                local_changed_at DATETIME NOT NULL,
+                remote_changed_at DATETIME NOT NULL,
+                remote_device TEXT NOT NULL,
+                detected_at DATETIME NOT NULL,
+                resolved_at DATETIME
+            )
+            "#,
+            r#"
+            CREATE TABLE cloud_sync_applied (
+                object_key TEXT PRIMARY KEY,
+                device_id TEXT NOT NULL,
+                change_count INTEGER NOT NULL,
+                applied_at DATETIME NOT NULL
+            )
+            "#,
+            "CREATE TABLE entities (id TEXT PRIMARY KEY, profile_id TEXT NOT NULL, name TEXT NOT NULL, notes TEXT)",
+            r#"
+            CREATE TABLE transaction_tags (
+                id TEXT PRIMARY KEY,
+                transaction_id TEXT NOT NULL,
+                category TEXT NOT NULL,
+                amount TEXT NOT NULL,
+                UNIQUE(transaction_id, category)
+            )
+            "#,
+        ] {
+            sqlx::query(ddl).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    fn clock(entries: &[(&str, i64)]) -> VectorClock {
+        entries
+            .iter()
+            .map(|(device, counter)| (device.to_string(), *counter))
+            .collect()
+    }
+
+    fn entity_change(
+        fields: &[&str],
+        name: &str,
+        notes: &str,
+        counter: i64,
+        clock: VectorClock,
+        changed_at: DateTime<Utc>,
+    ) -> Change {
+        Change {
+            record_type: "entity".to_string(),
+            record_id: "e1".to_string(),
+            action: "update".to_string(),
+            profile_id: Some("p1".to_string()),
+            data: json!({ "id": "e1", "profile_id": "p1", "name": name, "notes": notes, "not_a_column": 1 }),
+            fields: fields.iter().map(|field| field.to_string()).collect(),
+            counter,
+            clock,
+            changed_at,
+        }
+    }
+
+    fn change_set(device: &str, changes: Vec<Change>) -> ChangeSet {
+        ChangeSet {
+            format_version: FORMAT_VERSION.to_string(),
+            device_id: device.to_string(),
+            created_at: Utc::now(),
+            changes,
+        }
+    }
+
+    async fn entity(pool: &SqlitePool) -> (String, Option<String>) {
+        sqlx::query_as("SELECT name, notes FROM entities WHERE id = 'e1'")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_edits_merge_by_field() {
+        let pool = setup_test_db().await;
+        let now = Utc::now();
+
+        let created = change_set(
+            "laptop",
+            vec![entity_change(
+                &["name"],
+                "Acme",
+                "",
+                1,
+                clock(&[("laptop", 1)]),
+                now,
+            )],
+        );
+        let outcome = apply_change_set(&pool, "k1", &created).await.unwrap();
+        assert_eq!(outcome.applied, 1);
+
+        // Applying the same change-set again does nothing.
+        let again = apply_change_set(&pool, "k1", &created).await.unwrap();
+        assert_eq!(again.applied, 0);
+
+        // This device renames the entity after seeing the create.
+        sqlx::query("UPDATE entities SET name = 'Acme Local' WHERE id = 'e1'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let local = entity_change(
+            &["name"],
+            "Acme Local",
+            "",
+            5,
+            clock(&[("laptop", 1), ("here", 5)]),
+            now + Duration::minutes(1),
+        );
+        mark_pushed(&pool, "here", &[local], 5).await.unwrap();
+
+        // Meanwhile the laptop edited the notes: both edits are kept.
+        let notes = change_set(
+            "laptop",
+            vec![entity_change(
+                &["notes"],
+                "Acme",
+                "Quarterly donor",
+                2,
+                clock(&[("laptop", 2)]),
+                now + Duration::minutes(2),
+            )],
+        );
+        let outcome = apply_change_set(&pool, "k2", &notes).await.unwrap();
+        assert_eq!(outcome.applied, 1);
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(
+            entity(&pool).await,
+            (
+                "Acme Local".to_string(),
+                Some("Quarterly donor".to_string())
+            )
+        );
+
+        // An earlier concurrent rename loses and is reported.
+        let rename = change_set(
+            "laptop",
+            vec![entity_change(
+                &["name"],
+                "Acme Remote",
+                "Quarterly donor",
+                3,
+                clock(&[("laptop", 3)]),
+                now,
+            )],
+        );
+        let outcome = apply_change_set(&pool, "k3", &rename).await.unwrap();
+        assert_eq!(outcome.applied, 0);
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.conflicts[0].field.as_deref(), Some("name"));
+        assert_eq!(outcome.conflicts[0].kept, "local");
+        assert_eq!(entity(&pool).await.0, "Acme Local");
+        let conflict_id = outcome.conflicts[0].id.clone();
+
+        // A rename made after seeing the local one simply applies.
+        let later = change_set(
+            "laptop",
+            vec![entity_change(
+                &["name"],
+                "Acme Foundation",
+                "Quarterly donor",
+                4,
+                clock(&[("laptop", 4), ("here", 5)]),
+                now + Duration::minutes(3),
+            )],
+        );
+        let outcome = apply_change_set(&pool, "k4", &later).await.unwrap();
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(entity(&pool).await.0, "Acme Foundation");
+
+        // Applied changes are logged but never queued for pushing back.
+        assert_eq!(count_pending(&pool, 0).await.unwrap(), 0);
+        let logged: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM data_audit_log")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(logged, 3);
+
+        // Choosing the discarded value writes it back as a local edit.
+        let resolved = resolve_conflict(&pool, Some("u1"), &conflict_id, true)
+            .await
+            .unwrap();
+        assert!(resolved.resolved_at.is_some());
+        assert_eq!(entity(&pool).await.0, "Acme Remote");
+        assert_eq!(count_pending(&pool, 0).await.unwrap(), 1);
+        assert!(list_conflicts(&pool, false).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_same_tag_added_on_two_devices_merges() {
+        let pool = setup_test_db().await;
+        sqlx::query("INSERT INTO transaction_tags VALUES ('t-local', 'tx1', 'grants', '10')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let remote = change_set(
+            "laptop",
+            vec![Change {
+                record_type: "transaction_tag".to_string(),
+                record_id: "t-remote".to_string(),
+                action: "create".to_string(),
+                profile_id: None,
+                data: json!({ "id": "t-remote", "transaction_id": "tx1", "category": "grants", "amount": "12" }),
+                fields: vec!["amount".to_string()],
+                counter: 1,
+                clock: clock(&[("laptop", 1)]),
+                changed_at: Utc::now(),
+            }],
+        );
+        let outcome = apply_change_set(&pool, "k1", &remote).await.unwrap();
+        assert!(outcome.skipped.is_empty());
+
+        let tags: Vec<(String, String)> = sqlx::query_as("SELECT id, amount FROM transaction_tags")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(tags, vec![("t-local".to_string(), "12".to_string())]);
+    }
+}
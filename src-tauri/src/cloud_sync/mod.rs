@@ -0,0 +1,159 @@
+//! Optional end-to-end encrypted sync between devices.
+//!
+//! Sync is opt-in and local-first: every command reads and writes the local
+//! database, and sync only moves change-sets in the background. Local changes
+//! are taken from the data audit log, encrypted client-side with AES-256-GCM
+//! under a passphrase that never leaves the device, and uploaded to a
+//! user-provided S3-compatible bucket or WebDAV endpoint. Other devices
+//! download and decrypt them and merge field by field using vector clocks
+//! (see [`crdt`]), reporting concurrent edits that can't both be kept. The
+//! remote only ever sees ciphertext and object names.
+
+pub mod changeset;
+pub mod commands;
+pub mod crdt;
+pub mod merge;
+pub mod remote;
+pub mod s3;
+pub mod webdav;
+
+use keyring::Entry;
+use serde::{Deserialize, Serialize};
+
+use remote::SyncRemote;
+use s3::S3Remote;
+use webdav::WebDavRemote;
+
+/// Settings key holding the serialized [`CloudSyncConfig`].
+pub const SETTINGS_KEY: &str = "cloud_sync.config";
+
+/// Service name for keychain entries
+const KEYCHAIN_SERVICE: &str = "pacioli";
+
+/// Keychain entry holding the S3 secret access key or WebDAV password.
+const KEYCHAIN_CREDENTIAL_KEY: &str = "cloud_sync_credential";
+
+/// Keychain entry holding the encryption passphrase.
+const KEYCHAIN_PASSPHRASE_KEY: &str = "cloud_sync_passphrase";
+
+/// Kind of remote storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    /// An S3-compatible bucket (AWS S3, MinIO, Backblaze B2, R2, ...).
+    S3,
+    /// A WebDAV endpoint (Nextcloud, ownCloud, ...).
+    WebDav,
+}
+
+/// Where change-sets are stored. Credentials are kept in the OS keychain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudSyncConfig {
+    /// Whether sync runs.
+    pub enabled: bool,
+    /// Kind of remote storage.
+    pub backend: BackendKind,
+    /// Base URL, e.g. `https://s3.eu-central-1.amazonaws.com` or
+    /// `https://cloud.example.org/remote.php/dav/files/alice`.
+    pub endpoint: String,
+    /// S3 bucket name.
+    pub bucket: Option<String>,
+    /// S3 region; defaults to us-east-1.
+    pub region: Option<String>,
+    /// Folder or key prefix the change-sets are stored under.
+    pub prefix: String,
+    /// S3 access key ID or WebDAV username.
+    pub username: String,
+}
+
+impl CloudSyncConfig {
+    /// Checks the settings are complete.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.endpoint.starts_with("https://") && !self.endpoint.starts_with("http://") {
+            return Err("Sync endpoint must be an http(s) URL".to_string());
+        }
+        if self.username.trim().is_empty() {
+            return Err("Sync username or access key is required".to_string());
+        }
+        if self.backend == BackendKind::S3
+            && self.bucket.as_deref().map_or(true, |b| b.trim().is_empty())
+        {
+            return Err("An S3 bucket is required".to_string());
+        }
+        Ok(())
+    }
+
+    /// Builds the remote client for these settings.
+    pub fn remote(&self, credential: &str) -> Result<Box<dyn SyncRemote>, String> {
+        self.validate()?;
+        let prefix = self.prefix.trim_matches('/').to_string();
+        Ok(match self.backend {
+            BackendKind::S3 => Box::new(S3Remote::new(
+                &self.endpoint,
+                self.bucket.as_deref().unwrap_or_default(),
+                self.region.as_deref().unwrap_or("us-east-1"),
+                &prefix,
+                &self.username,
+                credential,
+            )),
+            BackendKind::WebDav => Box::new(WebDavRemote::new(
+                &self.endpoint,
+                &prefix,
+                &self.username,
+                credential,
+            )),
+        })
+    }
+}
+
+fn keychain_entry(key: &str) -> Result<Entry, String> {
+    Entry::new(KEYCHAIN_SERVICE, key).map_err(|e| format!("Keychain access failed: {}", e))
+}
+
+fn load_secret(key: &str) -> Result<Option<String>, String> {
+    match keychain_entry(key)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Keychain access failed: {}", e)),
+    }
+}
+
+fn save_secret(key: &str, secret: &str) -> Result<(), String> {
+    keychain_entry(key)?
+        .set_password(secret)
+        .map_err(|e| format!("Keychain access failed: {}", e))
+}
+
+fn delete_secret(key: &str) -> Result<(), String> {
+    match keychain_entry(key)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Keychain access failed: {}", e)),
+    }
+}
+
+/// Retrieves the remote credential from the system keychain.
+pub fn load_credential() -> Result<Option<String>, String> {
+    load_secret(KEYCHAIN_CREDENTIAL_KEY)
+}
+
+/// Stores the remote credential in the system keychain.
+pub fn save_credential(credential: &str) -> Result<(), String> {
+    save_secret(KEYCHAIN_CREDENTIAL_KEY, credential)
+}
+
+/// Retrieves the encryption passphrase from the system keychain.
+pub fn load_passphrase() -> Result<Option<String>, String> {
+    load_secret(KEYCHAIN_PASSPHRASE_KEY)
+}
+
+/// Stores the encryption passphrase in the system keychain.
+pub fn save_passphrase(passphrase: &str) -> Result<(), String> {
+    save_secret(KEYCHAIN_PASSPHRASE_KEY, passphrase)
+}
+
+/// Removes the credential and passphrase from the system keychain.
+pub fn delete_secrets() -> Result<(), String> {
+    delete_secret(KEYCHAIN_CREDENTIAL_KEY)?;
+    delete_secret(KEYCHAIN_PASSPHRASE_KEY)
+}
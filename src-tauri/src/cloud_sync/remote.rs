@@ -0,0 +1,92 @@
+//! Remote object storage for change-sets.
+
+use async_trait::async_trait;
+
+/// A place change-sets are uploaded to and downloaded from. Keys are
+/// relative to the configured prefix and use `/` as the separator.
+#[async_trait]
+pub trait SyncRemote: Send + Sync {
+    /// Uploads an object, replacing any with the same key.
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), String>;
+
+    /// Downloads an object.
+    async fn get(&self, key: &str) -> Result<Vec<u8>, String>;
+
+    /// Lists the keys directly inside `dir`, e.g. `changesets`. A missing
+    /// directory lists as empty.
+    async fn list(&self, dir: &str) -> Result<Vec<String>, String>;
+}
+
+/// Joins a prefix and a key with a single `/`.
+pub fn join_key(prefix: &str, key: &str) -> String {
+    let key = key.trim_start_matches('/');
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}/{}", prefix.trim_end_matches('/'), key)
+    }
+}
+
+/// Text content of every element named `name` in an XML document, ignoring
+/// namespace prefixes, with the basic entities unescaped.
+///
+/// S3 and WebDAV listings are simple enough that this avoids pulling in an
+/// XML parser.
+pub fn xml_values(xml: &str, name: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('>') else { break };
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+
+        let local = tag.rsplit(':').next().unwrap_or(tag);
+        if tag.starts_with('/') || tag.ends_with('/') || local != name {
+            continue;
+        }
+        let close = format!("</{}>", tag);
+        if let Some(close_at) = rest.find(&close) {
+            values.push(unescape_xml(&rest[..close_at]));
+            rest = &rest[close_at + close.len()..];
+        }
+    }
+    values
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_key() {
+        assert_eq!(join_key("", "changesets/a.json"), "changesets/a.json");
+        assert_eq!(join_key("pacioli/", "/changesets"), "pacioli/changesets");
+    }
+
+    #[test]
+    fn test_xml_values_ignores_namespaces() {
+        let xml = r#"<?xml version="1.0"?>
+            <d:multistatus xmlns:d="DAV:">
+              <d:response><d:href>/dav/changesets/</d:href></d:response>
+              <d:response><d:href>/dav/changesets/a&amp;b.json</d:href></d:response>
+            </d:multistatus>"#;
+        assert_eq!(
+            xml_values(xml, "href"),
+            vec!["/dav/changesets/", "/dav/changesets/a&b.json"]
+        );
+
+        let s3 = "<ListBucketResult><Contents><Key>p/x.json</Key></Contents>\
+                  <IsTruncated>false</IsTruncated></ListBucketResult>";
+        assert_eq!(xml_values(s3, "Key"), vec!["p/x.json"]);
+        assert_eq!(xml_values(s3, "IsTruncated"), vec!["false"]);
+    }
+}
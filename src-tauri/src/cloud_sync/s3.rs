@@ -0,0 +1,334 @@
+//! S3-compatible object storage with AWS Signature Version 4.
+//!
+//! Requests use path-style addressing (`{endpoint}/{bucket}/{key}`), which
+//! every S3-compatible service accepts.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Method, StatusCode};
+use sha2::{Digest, Sha256};
+
+use super::remote::{join_key, xml_values, SyncRemote};
+
+/// Block size of SHA-256, for HMAC.
+const SHA256_BLOCK: usize = 64;
+
+/// An S3 bucket reached with an access key.
+pub struct S3Remote {
+    client: Client,
+    endpoint: String,
+    bucket: String,
+    region: String,
+    prefix: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+impl S3Remote {
+    /// Creates a client for `bucket` at `endpoint`.
+    pub fn new(
+        endpoint: &str,
+        bucket: &str,
+        region: &str,
+        prefix: &str,
+        access_key_id: &str,
+        secret_access_key: &str,
+    ) -> Self {
+        Self {
+            client: Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            bucket: bucket.trim().to_string(),
+            region: region.trim().to_string(),
+            prefix: prefix.to_string(),
+            access_key_id: access_key_id.trim().to_string(),
+            secret_access_key: secret_access_key.to_string(),
+        }
+    }
+
+    /// Sends a signed request for `key` (empty for the bucket itself) with
+    /// the given query parameters.
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<reqwest::Response, String> {
+        let path = if key.is_empty() {
+            format!("/{}", uri_encode(&self.bucket, true))
+        } else {
+            format!(
+                "/{}/{}",
+                uri_encode(&self.bucket, true),
+                uri_encode(key, false)
+            )
+        };
+        let query = canonical_query(query);
+        let url = if query.is_empty() {
+            format!("{}{}", self.endpoint, path)
+        } else {
+            format!("{}{}?{}", self.endpoint, path, query)
+        };
+        let parsed = reqwest::Url::parse(&url).map_err(|e| e.to_string())?;
+        let host = match (parsed.host_str(), parsed.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(format!("Invalid S3 endpoint: {}", self.endpoint)),
+        };
+
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let signed = sign_request(
+            &SigningInput {
+                method: method.as_str(),
+                path: &path,
+                query: &query,
+                host: &host,
+                payload_hash: &payload_hash,
+                region: &self.region,
+                access_key_id: &self.access_key_id,
+                secret_access_key: &self.secret_access_key,
+            },
+            Utc::now(),
+        );
+
+        self.client
+            .request(method, parsed)
+            .header("x-amz-date", signed.amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("authorization", signed.authorization)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("S3 request failed: {}", e))
+    }
+}
+
+#[async_trait]
+impl SyncRemote for S3Remote {
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), String> {
+        let key = join_key(&self.prefix, key);
+        let response = self.send(Method::PUT, &key, &[], body).await?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "S3 upload of {} failed: {}",
+                key,
+                response.status()
+            ));
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+        let key = join_key(&self.prefix, key);
+        let response = self.send(Method::GET, &key, &[], Vec::new()).await?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "S3 download of {} failed: {}",
+                key,
+                response.status()
+            ));
+        }
+        response
+            .bytes()
+            .await
+            .map(|bytes| bytes.to_vec())
+            .map_err(|e| e.to_string())
+    }
+
+    async fn list(&self, dir: &str) -> Result<Vec<String>, String> {
+        let dir_prefix = format!("{}/", join_key(&self.prefix, dir).trim_end_matches('/'));
+        let mut keys = Vec::new();
+        let mut continuation: Option<String> = None;
+
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", dir_prefix.as_str())];
+            if let Some(token) = continuation.as_deref() {
+                query.push(("continuation-token", token));
+            }
+            let response = self.send(Method::GET, "", &query, Vec::new()).await?;
+            if response.status() == StatusCode::NOT_FOUND {
+                return Ok(keys);
+            }
+            if !response.status().is_success() {
+                return Err(format!("S3 listing failed: {}", response.status()));
+            }
+            let xml = response.text().await.map_err(|e| e.to_string())?;
+
+            keys.extend(xml_values(&xml, "Key").into_iter().filter_map(|key| {
+                let name = key.strip_prefix(&dir_prefix)?;
+                (!name.is_empty() && !name.contains('/'))
+                    .then(|| format!("{}/{}", dir.trim_matches('/'), name))
+            }));
+
+            let truncated =
+                xml_values(&xml, "IsTruncated").first().map(String::as_str) == Some("true");
+            continuation = xml_values(&xml, "NextContinuationToken").into_iter().next();
+            if !truncated || continuation.is_none() {
+                return Ok(keys);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Signature Version 4
+// ============================================================================
+
+/// Request details covered by the signature.
+pub struct SigningInput<'a> {
+    /// HTTP method.
+    pub method: &'a str,
+    /// URI-encoded path.
+    pub path: &'a str,
+    /// Canonical query string.
+    pub query: &'a str,
+    /// Host header value.
+    pub host: &'a str,
+    /// Hex SHA-256 of the body.
+    pub payload_hash: &'a str,
+    /// Signing region.
+    pub region: &'a str,
+    /// Access key ID.
+    pub access_key_id: &'a str,
+    /// Secret access key.
+    pub secret_access_key: &'a str,
+}
+
+/// Headers to attach to a signed request.
+pub struct SignedHeaders {
+    /// Value of `x-amz-date`.
+    pub amz_date: String,
+    /// Value of `Authorization`.
+    pub authorization: String,
+}
+
+/// HMAC-SHA256 of `data` under `key`.
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; SHA256_BLOCK];
+    if key.len() > SHA256_BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(data);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+/// Derives the SigV4 signing key for a day, region, and service.
+pub fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> [u8; 32] {
+    let k_date = hmac_sha256(format!("AWS4{}", secret).as_bytes(), date.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+/// Signs a request made at `now`.
+pub fn sign_request(input: &SigningInput<'_>, now: DateTime<Utc>) -> SignedHeaders {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        input.method,
+        input.path,
+        input.query,
+        input.host,
+        input.payload_hash,
+        amz_date,
+        signed_headers,
+        input.payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, input.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let key = signing_key(input.secret_access_key, &date, input.region, "s3");
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    SignedHeaders {
+        authorization: format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            input.access_key_id, scope, signed_headers, signature
+        ),
+        amz_date,
+    }
+}
+
+/// Percent-encodes everything except unreserved characters, and `/` unless
+/// `encode_slash` is set.
+pub fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Query parameters encoded and sorted by name.
+fn canonical_query(params: &[(&str, &str)]) -> String {
+    let mut encoded: Vec<(String, String)> = params
+        .iter()
+        .map(|(name, value)| (uri_encode(name, true), uri_encode(value, true)))
+        .collect();
+    encoded.sort();
+    encoded
+        .into_iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_sha256() {
+        // RFC 4231, test case 2.
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            hex::encode(mac),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_signing_key() {
+        // Example from the AWS Signature Version 4 documentation.
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_canonical_query() {
+        assert_eq!(
+            canonical_query(&[("prefix", "sync/changesets/"), ("list-type", "2")]),
+            "list-type=2&prefix=sync%2Fchangesets%2F"
+        );
+        assert_eq!(uri_encode("a b/c.json", false), "a%20b/c.json");
+    }
+}
@@ -0,0 +1,138 @@
+//! WebDAV storage with basic authentication.
+
+use async_trait::async_trait;
+use reqwest::{Client, Method, StatusCode};
+
+use super::remote::{join_key, xml_values, SyncRemote};
+
+/// Body of a PROPFIND request asking only for resource types.
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:resourcetype/></d:prop></d:propfind>"#;
+
+/// A WebDAV folder reached with a username and password.
+pub struct WebDavRemote {
+    client: Client,
+    endpoint: String,
+    prefix: String,
+    username: String,
+    password: String,
+}
+
+impl WebDavRemote {
+    /// Creates a client for the folder at `endpoint`.
+    pub fn new(endpoint: &str, prefix: &str, username: &str, password: &str) -> Self {
+        Self {
+            client: Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            prefix: prefix.to_string(),
+            username: username.trim().to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.endpoint, path)
+    }
+
+    fn request(&self, method: Method, path: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, self.url(path))
+            .basic_auth(&self.username, Some(&self.password))
+    }
+
+    /// Creates each folder on the way to `path`. Folders that already exist
+    /// are left alone.
+    async fn ensure_folders(&self, path: &str) -> Result<(), String> {
+        let mkcol = Method::from_bytes(b"MKCOL").map_err(|e| e.to_string())?;
+        let segments: Vec<&str> = path.split('/').collect();
+        for depth in 1..segments.len() {
+            let folder = format!("{}/", segments[..depth].join("/"));
+            let response = self
+                .request(mkcol.clone(), &folder)
+                .send()
+                .await
+                .map_err(|e| format!("WebDAV request failed: {}", e))?;
+            let status = response.status();
+            if !status.is_success() && status != StatusCode::METHOD_NOT_ALLOWED {
+                return Err(format!("WebDAV could not create {}: {}", folder, status));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SyncRemote for WebDavRemote {
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), String> {
+        let path = join_key(&self.prefix, key);
+        self.ensure_folders(&path).await?;
+        let response = self
+            .request(Method::PUT, &path)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "WebDAV upload of {} failed: {}",
+                path,
+                response.status()
+            ));
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+        let path = join_key(&self.prefix, key);
+        let response = self
+            .request(Method::GET, &path)
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "WebDAV download of {} failed: {}",
+                path,
+                response.status()
+            ));
+        }
+        response
+            .bytes()
+            .await
+            .map(|bytes| bytes.to_vec())
+            .map_err(|e| e.to_string())
+    }
+
+    async fn list(&self, dir: &str) -> Result<Vec<String>, String> {
+        let dir = dir.trim_matches('/');
+        let folder = format!("{}/", join_key(&self.prefix, dir));
+        let propfind = Method::from_bytes(b"PROPFIND").map_err(|e| e.to_string())?;
+        let response = self
+            .request(propfind, &folder)
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml")
+            .body(PROPFIND_BODY)
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        if !response.status().is_success() {
+            return Err(format!("WebDAV listing failed: {}", response.status()));
+        }
+        let xml = response.text().await.map_err(|e| e.to_string())?;
+
+        // Each href is the full path of an entry; the folder itself is listed
+        // too and ends with a slash.
+        Ok(xml_values(&xml, "href")
+            .into_iter()
+            .filter(|href| !href.ends_with('/'))
+            .filter_map(|href| {
+                href.rsplit('/')
+                    .next()
+                    .map(|name| format!("{}/{}", dir, name))
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,116 @@
+//! Deep links handled by the desktop app.
+//!
+//! Links use the `pacioli://` URL scheme registered through the deep-link
+//! plugin. Incoming links are parsed into a [`DeepLink`] and forwarded to the
+//! frontend as a [`DEEP_LINK_EVENT`] event, which routes to the matching flow.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::log_error;
+
+/// URL scheme registered for the app.
+pub const SCHEME: &str = "pacioli";
+
+/// Event emitted to the frontend when a deep link is opened.
+pub const DEEP_LINK_EVENT: &str = "deep-link";
+
+/// An action requested through a deep link.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum DeepLink {
+    /// Open the accept-invitation flow for the given token.
+    #[serde(rename_all = "camelCase")]
+    AcceptInvitation {
+        /// Invitation token.
+        token: String,
+    },
+}
+
+/// Builds the link that opens the accept-invitation flow.
+pub fn invitation_url(token: &str) -> String {
+    format!("{}://invite/accept?token={}", SCHEME, token)
+}
+
+/// Parses a `pacioli://` URL. Returns `None` for other schemes, unknown
+/// paths, or missing parameters.
+pub fn parse(url: &str) -> Option<DeepLink> {
+    let rest = url.strip_prefix(SCHEME)?.strip_prefix("://")?;
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let param = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    match path.trim_end_matches('/') {
+        "invite/accept" => Some(DeepLink::AcceptInvitation {
+            token: param("token")?,
+        }),
+        _ => None,
+    }
+}
+
+/// Forwards deep links to the frontend, including the one the app was
+/// launched with.
+pub fn listen(app: &AppHandle) {
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            dispatch(&handle, url.as_str());
+        }
+    });
+
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        for url in urls {
+            dispatch(app, url.as_str());
+        }
+    }
+}
+
+fn dispatch(app: &AppHandle, url: &str) {
+    match parse(url) {
+        Some(link) => {
+            let _ = app.emit(DEEP_LINK_EVENT, link);
+        }
+        None => log_error!("Ignoring unrecognized deep link: {}", url),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invitation_round_trip() {
+        let url = invitation_url("abc_DEF-123");
+        assert_eq!(url, "pacioli://invite/accept?token=abc_DEF-123");
+        assert_eq!(
+            parse(&url),
+            Some(DeepLink::AcceptInvitation {
+                token: "abc_DEF-123".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_links() {
+        assert_eq!(parse("https://invite/accept?token=x"), None);
+        assert_eq!(parse("pacioli://invite/accept"), None);
+        assert_eq!(parse("pacioli://invite/accept?token="), None);
+        assert_eq!(parse("pacioli://unknown?token=x"), None);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let json = serde_json::to_string(&DeepLink::AcceptInvitation {
+            token: "t".to_string(),
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"action":"acceptInvitation","token":"t"}"#);
+    }
+}
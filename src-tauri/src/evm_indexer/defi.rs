@@ -1,229 +0,0 @@
-#![allow(dead_code)]
-
-use anyhow::Result;
-use ethers::prelude::*;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Arc;
-
-/// Scanner for decentralized finance protocols.
-/// Manages a collection of protocol configurations.
-pub struct DeFiProtocolScanner {
-    protocols: HashMap<String, ProtocolConfig>,
-}
-
-#[derive(Clone)]
-/// Configuration for a decentralized finance protocol.
-/// Includes the protocol's name, blockchain network, type, and deployed contracts.
-pub struct ProtocolConfig {
-    /// Human-readable name of the protocol.
-    pub name: String,
-    /// Identifier of the blockchain network where the protocol is deployed.
-    pub chain: String,
-    /// Classification of the protocol's functionality.
-    pub protocol_type: ProtocolType,
-    /// Mapping of contract identifiers to their blockchain addresses.
-    pub contracts: HashMap<String, Address>,
-}
-
-#[derive(Clone, Debug)]
-/// Enumeration of supported decentralized finance protocol types.
-pub enum ProtocolType {
-    /// Decentralized exchange protocol.
-    Dex,
-    /// Lending protocol.
-    Lending,
-    /// Staking protocol.
-    Staking,
-    /// Farming protocol.
-    Farming,
-    /// Bridge protocol for asset transfers between networks.
-    Bridge,
-}
-
-impl DeFiProtocolScanner {
-    /// Creates a new DeFiProtocolScanner with default protocol configurations.
-    pub fn new() -> Self {
-        let mut protocols = HashMap::new();
-
-        // Moonbeam protocols
-        protocols.insert(
-            "stellaswap".to_string(),
-            ProtocolConfig {
-                name: "StellaSwap".to_string(),
-                chain: "moonbeam".to_string(),
-                protocol_type: ProtocolType::Dex,
-                contracts: {
-                    let mut contracts = HashMap::new();
-                    contracts.insert(
-                        "router".to_string(),
-                        "0xd0A01ec574D1fC6652eDF79cb2F880fd47D34Ab1"
-                            .parse()
-                            .unwrap(),
-                    );
-                    contracts.insert(
-                        "factory".to_string(),
-                        "0x68A384D826D3678f78BB9FB1533c7E9577dACc0E"
-                            .parse()
-                            .unwrap(),
-                    );
-                    contracts
-                },
-            },
-        );
-
-        protocols.insert(
-            "moonwell".to_string(),
-            ProtocolConfig {
-                name: "Moonwell".to_string(),
-                chain: "moonbeam".to_string(),
-                protocol_type: ProtocolType::Lending,
-                contracts: {
-                    let mut contracts = HashMap::new();
-                    contracts.insert(
-                        "comptroller".to_string(),
-                        "0x8E00D5e02E65A19337Cdba98bbA9F84d4186a180"
-                            .parse()
-                            .unwrap(),
-                    );
-                    contracts
-                },
-            },
-        );
-
-        // Astar protocols
-        protocols.insert(
-            "arthswap".to_string(),
-            ProtocolConfig {
-                name: "ArthSwap".to_string(),
-                chain: "astar".to_string(),
-                protocol_type: ProtocolType::Dex,
-                contracts: {
-                    let mut contracts = HashMap::new();
-                    contracts.insert(
-                        "router".to_string(),
-                        "0xE915D2393a08a00c5A463053edD31bAe2199b9e7"
-                            .parse()
-                            .unwrap(),
-                    );
-                    contracts
-                },
-            },
-        );
-
-        // Acala protocols
-        protocols.insert(
-            "acala-swap".to_string(),
-            ProtocolConfig {
-                name: "Acala Swap".to_string(),
-                chain: "acala-evm".to_string(),
-                protocol_type: ProtocolType::Dex,
-                contracts: HashMap::new(), // Acala uses substrate-native DEX
-            },
-        );
-
-        Self { protocols }
-    }
-
-    /// Scans DeFi positions for a user on a specified protocol.
-    ///
-    /// # Parameters
-    ///
-    /// * `provider` - An `Arc<Provider<Ws>>` used to query the blockchain.
-    /// * `protocol` - A string slice identifying the protocol to scan.
-    /// * `user_address` - The Ethereum address of the user.
-    ///
-    /// # Returns
-    ///
-    /// Returns a `Result` with a vector of `DeFiPosition` on success, or an `anyhow::Error` if
-    /// the protocol is unknown or scanning fails.
-    pub async fn scan_defi_positions(
-        &self,
-        provider: Arc<Provider<Ws>>,
-        protocol: &str,
-        user_address: Address,
-    ) -> Result<Vec<DeFiPosition>> {
-        let config = self
-            .protocols
-            .get(protocol)
-            .ok_or_else(|| anyhow::anyhow!("Unknown protocol"))?;
-
-        match config.protocol_type {
-            ProtocolType::Dex => {
-                self.scan_dex_positions(provider, config, user_address)
-                    .await
-            }
-            ProtocolType::Lending => {
-                self.scan_lending_positions(provider, config, user_address)
-                    .await
-            }
-            ProtocolType::Staking => {
-                self.scan_staking_positions(provider, config, user_address)
-                    .await
-            }
-            _ => Ok(Vec::new()),
-        }
-    }
-
-    async fn scan_dex_positions(
-        &self,
-        _provider: Arc<Provider<Ws>>,
-        _config: &ProtocolConfig,
-        _user_address: Address,
-    ) -> Result<Vec<DeFiPosition>> {
-        // Scan for liquidity positions
-        // This would involve querying LP token balances and calculating underlying assets
-        Ok(Vec::new())
-    }
-
-    async fn scan_lending_positions(
-        &self,
-        _provider: Arc<Provider<Ws>>,
-        _config: &ProtocolConfig,
-        _user_address: Address,
-    ) -> Result<Vec<DeFiPosition>> {
-        // Scan for lending/borrowing positions
-        // Query cToken balances, borrow balances, etc.
-        Ok(Vec::new())
-    }
-
-    async fn scan_staking_positions(
-        &self,
-        _provider: Arc<Provider<Ws>>,
-        _config: &ProtocolConfig,
-        _user_address: Address,
-    ) -> Result<Vec<DeFiPosition>> {
-        // Scan for staking positions
-        Ok(Vec::new())
-    }
-}
-
-/// A position in a decentralized finance (DeFi) protocol, including supplied assets, debts, and earned rewards.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DeFiPosition {
-    /// The name of the DeFi protocol (e.g., Compound, Aave).
-    pub protocol: String,
-    /// The type of position (e.g., "lending", "borrowing", "staking").
-    pub position_type: String,
-    /// Assets supplied or staked by the user.
-    pub assets: Vec<AssetAmount>,
-    /// Assets borrowed by the user.
-    pub debt: Vec<AssetAmount>,
-    /// Rewards earned by the user (e.g., liquidity mining rewards).
-    pub rewards: Vec<AssetAmount>,
-    /// The total USD value of the position, if available.
-    pub value_usd: Option<f64>,
-}
-
-/// Represents an amount of a specific token, identified by its address or symbol.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AssetAmount {
-    /// The blockchain address of the token contract, if available.
-    pub token_address: Option<Address>,
-    /// The symbol of the token (e.g., "ETH", "DAI").
-    pub token_symbol: String,
-    /// The raw token amount in the smallest units.
-    pub amount: U256,
-    /// Number of decimal places used by the token.
-    pub decimals: u8,
-}
@@ -1,9 +1,14 @@
 //! Tauri Commands for Fetcher System
 //!
-//! Exposes API key management and rate limit status to the frontend.
+//! Exposes API key management, rate limit status, API usage and connectivity
+//! to the frontend.
 
 use super::api_keys::{ApiKeyManager, ApiProvider};
+use super::offline::{self, ConnectivityStatus};
+use super::usage::{self, ApiUsageReport};
+use crate::api::persistence::DatabaseState;
 use serde::Serialize;
+use tauri::State;
 
 // =============================================================================
 // RESPONSE TYPES
@@ -159,6 +164,22 @@ pub async fn get_configured_providers() -> Vec<String> {
         .collect()
 }
 
+/// Get outbound API call counts per provider over the last `days` days
+/// (default 30), with warnings for providers near their free-tier quota.
+#[tauri::command]
+pub async fn get_api_usage(
+    state: State<'_, DatabaseState>,
+    days: Option<u32>,
+) -> Result<ApiUsageReport, String> {
+    usage::current_usage(&state.pool, days).await
+}
+
+/// Get whether the app is online, and since when.
+#[tauri::command]
+pub async fn get_connectivity() -> ConnectivityStatus {
+    offline::status()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,343 @@
+//! Offline Mode
+//!
+//! Lets the app open and show data without a network connection. A
+//! background check probes a few well-known hosts and tracks whether the
+//! machine is online, emitting [`CONNECTIVITY_EVENT`] when that changes.
+//!
+//! Fetch commands go through [`fetch_or_cached`], which saves each
+//! successful result to the `offline_cache` table. While offline, or when a
+//! fetch fails and the probe finds the network gone, the last saved result
+//! is returned as [`Fetched::Offline`] with the time it was fetched, instead
+//! of an error.
+//!
+//! Queued jobs, including user-triggered wallet syncs, stay in the job queue
+//! while offline and run once connectivity returns.
+
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::net::TcpStream;
+
+use crate::api::error::ApiError;
+use crate::jobs::queue::JobQueueState;
+use crate::log_error;
+
+/// Event emitted when the app goes offline or comes back online.
+pub const CONNECTIVITY_EVENT: &str = "connectivity:changed";
+
+/// Interval between connectivity checks.
+const CHECK_INTERVAL: Duration = Duration::from_secs(20);
+
+/// How long a probe waits for each host.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Hosts probed for connectivity; reaching any one means online.
+const PROBE_HOSTS: &[&str] = &["1.1.1.1:443", "8.8.8.8:443", "9.9.9.9:443"];
+
+/// Current connectivity, assumed online until a probe says otherwise.
+static STATUS: Mutex<Option<ConnectivityStatus>> = Mutex::new(None);
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// Whether the app can reach the network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectivityStatus {
+    /// Whether the last check reached the network.
+    pub online: bool,
+    /// When connectivity last changed, or the app started.
+    pub since: DateTime<Utc>,
+}
+
+/// Result of a fetch command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Fetched<T> {
+    /// Fetched from the network just now.
+    Live {
+        /// The result.
+        data: T,
+    },
+    /// The network is unreachable, so this is the last result fetched.
+    Offline {
+        /// The cached result.
+        data: T,
+        /// When the cached result was fetched.
+        cached_at: DateTime<Utc>,
+        /// "Offline, showing cached data as of ..." for display.
+        message: String,
+    },
+}
+
+impl<T> Fetched<T> {
+    /// Cached data fetched at `cached_at`.
+    pub fn offline(data: T, cached_at: DateTime<Utc>) -> Self {
+        Fetched::Offline {
+            data,
+            cached_at,
+            message: format!(
+                "Offline, showing cached data as of {}",
+                cached_at.format("%Y-%m-%d %H:%M UTC")
+            ),
+        }
+    }
+
+    /// The result, live or cached.
+    pub fn data(&self) -> &T {
+        match self {
+            Fetched::Live { data } | Fetched::Offline { data, .. } => data,
+        }
+    }
+
+    /// When the cached result was fetched, if it is cached.
+    pub fn cached_at(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Fetched::Live { .. } => None,
+            Fetched::Offline { cached_at, .. } => Some(*cached_at),
+        }
+    }
+
+    /// Applies `f` to the result, keeping whether it is cached.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Fetched<U> {
+        match self {
+            Fetched::Live { data } => Fetched::Live { data: f(data) },
+            Fetched::Offline {
+                data,
+                cached_at,
+                message,
+            } => Fetched::Offline {
+                data: f(data),
+                cached_at,
+                message,
+            },
+        }
+    }
+}
+
+/// Several results fetched together, cached as of the oldest cached one if
+/// any of them are cached.
+pub fn combine<T>(results: Vec<Fetched<T>>) -> Fetched<Vec<T>> {
+    let oldest = results.iter().filter_map(Fetched::cached_at).min();
+    let data = results
+        .into_iter()
+        .map(|r| match r {
+            Fetched::Live { data } | Fetched::Offline { data, .. } => data,
+        })
+        .collect();
+    match oldest {
+        Some(cached_at) => Fetched::offline(data, cached_at),
+        None => Fetched::Live { data },
+    }
+}
+
+// =============================================================================
+// CONNECTIVITY
+// =============================================================================
+
+/// Current connectivity.
+pub fn status() -> ConnectivityStatus {
+    *STATUS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(|| ConnectivityStatus {
+            online: true,
+            since: Utc::now(),
+        })
+}
+
+/// Whether the app was online at the last check.
+pub fn is_online() -> bool {
+    status().online
+}
+
+/// Records connectivity; returns whether it changed.
+pub fn set_online(online: bool) -> bool {
+    let mut status = STATUS.lock().unwrap_or_else(|e| e.into_inner());
+    match status.as_mut() {
+        Some(current) if current.online == online => false,
+        _ => {
+            *status = Some(ConnectivityStatus {
+                online,
+                since: Utc::now(),
+            });
+            true
+        }
+    }
+}
+
+/// Whether any probe host accepts a connection.
+async fn probe() -> bool {
+    for host in PROBE_HOSTS {
+        if let Ok(Ok(_)) = tokio::time::timeout(PROBE_TIMEOUT, TcpStream::connect(host)).await {
+            return true;
+        }
+    }
+    false
+}
+
+/// Probes the network now and records the result.
+pub async fn check_now() -> bool {
+    let online = probe().await;
+    set_online(online);
+    online
+}
+
+/// Starts the background connectivity check. Emits [`CONNECTIVITY_EVENT`]
+/// whenever connectivity changes and wakes the job queue when it returns,
+/// so jobs held while offline run.
+pub fn spawn_connectivity_loop(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut reported = true;
+        loop {
+            let online = check_now().await;
+            if online != reported {
+                reported = online;
+                let _ = app.emit(CONNECTIVITY_EVENT, status());
+                if online {
+                    app.state::<JobQueueState>().resume();
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+// =============================================================================
+// CACHE
+// =============================================================================
+
+/// Saves a fetch result under `key`.
+pub async fn store<T: Serialize>(pool: &SqlitePool, key: &str, data: &T) -> Result<(), String> {
+    let payload = serde_json::to_string(data).map_err(|e| e.to_string())?;
+    sqlx::query(
+        r#"
+        INSERT INTO offline_cache (cache_key, payload, fetched_at) VALUES (?, ?, ?)
+        ON CONFLICT(cache_key) DO UPDATE SET payload = excluded.payload, fetched_at = excluded.fetched_at
+        "#,
+    )
+    .bind(key)
+    .bind(payload)
+    .bind(Utc::now())
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Loads the result saved under `key` and when it was fetched.
+pub async fn load<T: DeserializeOwned>(
+    pool: &SqlitePool,
+    key: &str,
+) -> Result<Option<(T, DateTime<Utc>)>, String> {
+    let row: Option<(String, DateTime<Utc>)> =
+        sqlx::query_as("SELECT payload, fetched_at FROM offline_cache WHERE cache_key = ?")
+            .bind(key)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    match row {
+        Some((payload, fetched_at)) => {
+            let data = serde_json::from_str(&payload).map_err(|e| e.to_string())?;
+            Ok(Some((data, fetched_at)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Runs `fetch` and caches its result under `key`. While offline the fetch
+/// is skipped and the cached result returned; a failed fetch also falls back
+/// to the cache if a probe then finds the network gone. Other failures are
+/// returned as they are, in the fetch's own error type.
+pub async fn fetch_or_cached<T, E, F>(
+    pool: &SqlitePool,
+    key: &str,
+    fetch: F,
+) -> Result<Fetched<T>, E>
+where
+    T: Serialize + DeserializeOwned,
+    E: From<ApiError>,
+    F: Future<Output = Result<T, E>>,
+{
+    if is_online() {
+        match fetch.await {
+            Ok(data) => {
+                if let Err(e) = store(pool, key, &data).await {
+                    log_error!("Failed to cache {}: {}", key, e);
+                }
+                return Ok(Fetched::Live { data });
+            }
+            Err(e) => {
+                if check_now().await {
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    match load(pool, key).await.map_err(ApiError::from)? {
+        Some((data, cached_at)) => Ok(Fetched::offline(data, cached_at)),
+        None => Err(ApiError::offline("Offline, and this hasn't been fetched before").into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(include_str!(
+            "../../migrations/20260505000001_create_offline_cache.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_cache_round_trip() {
+        let pool = setup_pool().await;
+        assert!(load::<Vec<u64>>(&pool, "blocks").await.unwrap().is_none());
+
+        store(&pool, "blocks", &vec![1u64, 2]).await.unwrap();
+        store(&pool, "blocks", &vec![3u64]).await.unwrap();
+        let (data, fetched_at) = load::<Vec<u64>>(&pool, "blocks").await.unwrap().unwrap();
+        assert_eq!(data, vec![3]);
+        assert!(fetched_at <= Utc::now());
+    }
+
+    #[test]
+    fn test_fetched_serializes_with_status() {
+        let cached_at = DateTime::parse_from_rfc3339("2026-03-01T09:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let live = serde_json::to_value(Fetched::Live { data: 7 }).unwrap();
+        assert_eq!(live, serde_json::json!({ "status": "live", "data": 7 }));
+
+        let offline = Fetched::offline(7, cached_at);
+        let json = serde_json::to_value(&offline).unwrap();
+        assert_eq!(json["status"], "offline");
+        assert_eq!(
+            json["message"],
+            "Offline, showing cached data as of 2026-03-01 09:30 UTC"
+        );
+
+        let combined = combine(vec![Fetched::Live { data: 1 }, offline]);
+        assert_eq!(combined.data(), &vec![1, 7]);
+        assert_eq!(combined.cached_at(), Some(cached_at));
+    }
+}
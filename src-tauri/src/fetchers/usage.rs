@@ -0,0 +1,53 @@
+//! API usage metering in the app.
+//!
+//! Call counts are kept by `pacioli_core::fetchers::usage`; the app flushes
+//! them in the background and tells the frontend when a provider nears its
+//! free-tier quota.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDate, Utc};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::api::persistence::DatabaseState;
+use crate::log_error;
+
+pub use pacioli_core::fetchers::usage::*;
+
+/// Event emitted when a provider first nears its free-tier quota.
+pub const API_USAGE_WARNING_EVENT: &str = "api-usage-warning";
+
+/// Interval between flushes of the in-memory counts.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Starts the background task that periodically saves the call counts and
+/// emits [`API_USAGE_WARNING_EVENT`] the first time in a quota period that a
+/// provider nears its quota.
+pub fn spawn_usage_flush_loop(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut warned: HashSet<(String, NaiveDate)> = HashSet::new();
+        loop {
+            tokio::time::sleep(FLUSH_INTERVAL).await;
+            let pool = app.state::<DatabaseState>().pool.clone();
+            let report = match current_usage(&pool, Some(1)).await {
+                Ok(report) => report,
+                Err(e) => {
+                    log_error!("Failed to save API usage: {}", e);
+                    continue;
+                }
+            };
+
+            let today = Utc::now().date_naive();
+            for usage in report.providers.iter().filter(|u| u.near_limit) {
+                let period_start = match usage.quota.map(|q| q.period) {
+                    Some(QuotaPeriod::Day) => today,
+                    _ => today.with_day(1).unwrap_or(today),
+                };
+                if warned.insert((usage.provider.clone(), period_start)) {
+                    let _ = app.emit(API_USAGE_WARNING_EVENT, usage);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,20 @@
+//! Tauri commands for background jobs.
+
+use tauri::State;
+
+use super::queue::JobQueueState;
+use super::{JobInfo, JobRegistryState};
+
+/// Lists running and queued jobs, then recently finished ones.
+#[tauri::command]
+pub async fn list_jobs(jobs: State<'_, JobRegistryState>) -> Result<Vec<JobInfo>, String> {
+    Ok(jobs.list())
+}
+
+/// Cancels a queued or running job. A queued job is dropped; a running one
+/// stops, keeping the work already saved so the next run resumes from
+/// there. Returns false if the job isn't queued or running.
+#[tauri::command]
+pub async fn cancel_job(queue: State<'_, JobQueueState>, job_id: String) -> Result<bool, String> {
+    Ok(queue.cancel(&job_id).await)
+}
@@ -0,0 +1,350 @@
+//! Long-running background jobs and their cancellation.
+//!
+//! Syncs and large fetches run as jobs. Each job gets an ID the frontend can
+//! pass to `cancel_job`, and a [`CancelToken`] that the work checks between
+//! steps and races its network calls against. Cancellation is cooperative:
+//! work already saved stays saved, so a later run picks up where the
+//! cancelled one stopped.
+//!
+//! Background work goes through the [`queue`], which orders it by priority,
+//! caps how much runs against each provider at once, and retries failures.
+
+pub mod commands;
+pub mod queue;
+
+pub use pacioli_core::jobs::{CancelToken, CANCELLED};
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use uuid::Uuid;
+
+/// Event emitted when a job finishes, fails, or is cancelled.
+pub const JOB_FINISHED_EVENT: &str = "job:finished";
+
+/// Finished jobs kept for display before the oldest are dropped.
+const FINISHED_JOBS_KEPT: usize = 100;
+
+// =============================================================================
+// REGISTRY
+// =============================================================================
+
+/// What a job does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Syncing a profile's wallets.
+    WalletSync,
+    /// Fetching transactions from a chain.
+    Fetch,
+    /// Checking watched addresses for new activity.
+    AddressWatch,
+    /// Checking open invoices for payment.
+    InvoiceCheck,
+    /// Checking pending Bitcoin transactions for confirmation or replacement.
+    PendingTxCheck,
+    /// Checking watched Bitcoin addresses' mempools for incoming payments.
+    MempoolWatch,
+    /// Generating and sending the scheduled reports that are due.
+    ScheduledReports,
+    /// Writing transactions to an export file.
+    Export,
+}
+
+/// Which jobs run first. Jobs a user started run before background ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPriority {
+    /// Started by the user, who is waiting on it.
+    User,
+    /// Started by the app on a schedule.
+    Background,
+}
+
+/// Where a job is in its life.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Waiting in the queue, possibly to be retried.
+    Queued,
+    /// Still working.
+    Running,
+    /// Finished normally.
+    Completed,
+    /// Stopped by `cancel_job`.
+    Cancelled,
+    /// Stopped by an error.
+    Failed,
+}
+
+/// A job as shown to the frontend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    /// Job ID, passed to `cancel_job`.
+    pub id: String,
+    /// What the job does.
+    pub kind: JobKind,
+    /// Short description, e.g. the profile or address being synced.
+    pub label: String,
+    /// Whether the job was started by the user or in the background.
+    pub priority: JobPriority,
+    /// Current status.
+    pub status: JobStatus,
+    /// Attempts started so far.
+    pub attempts: u32,
+    /// When the job was created.
+    pub queued_at: DateTime<Utc>,
+    /// When the latest attempt started.
+    pub started_at: Option<DateTime<Utc>>,
+    /// When the job ended.
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Why the job, or its latest attempt, failed.
+    pub error: Option<String>,
+}
+
+/// A registered job and its cancellation token.
+struct JobEntry {
+    info: JobInfo,
+    token: CancelToken,
+}
+
+/// Running and recently finished jobs.
+#[derive(Default)]
+pub struct JobRegistry {
+    jobs: Mutex<HashMap<String, JobEntry>>,
+}
+
+/// Shared job registry managed by Tauri.
+pub type JobRegistryState = Arc<JobRegistry>;
+
+/// Creates the job registry for Tauri.
+pub fn create_job_registry_state() -> JobRegistryState {
+    Arc::new(JobRegistry::default())
+}
+
+impl JobRegistry {
+    /// Registers a job that starts running straight away, under `id` if
+    /// given, and returns its ID and cancellation token. Registering an ID
+    /// already in use replaces that job.
+    pub fn start(&self, kind: JobKind, label: &str, id: Option<String>) -> (String, CancelToken) {
+        let id = id.unwrap_or_else(|| Uuid::new_v4().to_string());
+        let token = self.insert(&id, kind, label, JobPriority::User, Utc::now());
+        self.mark_running(&id);
+        (id, token)
+    }
+
+    /// Registers a queued job.
+    pub fn enqueue(
+        &self,
+        id: &str,
+        kind: JobKind,
+        label: &str,
+        priority: JobPriority,
+        queued_at: DateTime<Utc>,
+    ) {
+        self.insert(id, kind, label, priority, queued_at);
+    }
+
+    fn insert(
+        &self,
+        id: &str,
+        kind: JobKind,
+        label: &str,
+        priority: JobPriority,
+        queued_at: DateTime<Utc>,
+    ) -> CancelToken {
+        let token = CancelToken::new();
+        let entry = JobEntry {
+            info: JobInfo {
+                id: id.to_string(),
+                kind,
+                label: label.to_string(),
+                priority,
+                status: JobStatus::Queued,
+                attempts: 0,
+                queued_at,
+                started_at: None,
+                finished_at: None,
+                error: None,
+            },
+            token: token.clone(),
+        };
+
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = jobs.insert(id.to_string(), entry) {
+            previous.token.cancel();
+        }
+        prune_finished(&mut jobs);
+        token
+    }
+
+    /// Marks a job as starting an attempt and returns its token.
+    pub fn mark_running(&self, id: &str) -> Option<CancelToken> {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = jobs.get_mut(id)?;
+        entry.info.status = JobStatus::Running;
+        entry.info.attempts += 1;
+        entry.info.started_at = Some(Utc::now());
+        Some(entry.token.clone())
+    }
+
+    /// Puts a job back in the queue after a failed attempt.
+    pub fn mark_retrying(&self, id: &str, error: &str) {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = jobs.get_mut(id) {
+            entry.info.status = JobStatus::Queued;
+            entry.info.error = Some(error.to_string());
+        }
+    }
+
+    /// Raises a queued job to user priority.
+    pub fn set_priority(&self, id: &str, priority: JobPriority) {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = jobs.get_mut(id) {
+            entry.info.priority = priority;
+        }
+    }
+
+    /// Running and queued jobs first, then finished ones, newest first
+    /// within each group.
+    pub fn list(&self) -> Vec<JobInfo> {
+        let jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<JobInfo> = jobs.values().map(|e| e.info.clone()).collect();
+        list.sort_by_key(|j| (j.finished_at.is_some(), Reverse(j.queued_at)));
+        list
+    }
+
+    /// Records how a job ended and returns its final state. An error of
+    /// [`CANCELLED`], or any error after cancellation, marks it cancelled.
+    pub fn finish(&self, id: &str, result: &Result<(), String>) -> Option<JobInfo> {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = jobs.get_mut(id)?;
+        entry.info.finished_at = Some(Utc::now());
+        entry.info.status = match result {
+            Ok(()) => JobStatus::Completed,
+            Err(_) if entry.token.is_cancelled() => JobStatus::Cancelled,
+            Err(e) if e == CANCELLED => JobStatus::Cancelled,
+            Err(e) => {
+                entry.info.error = Some(e.clone());
+                JobStatus::Failed
+            }
+        };
+        Some(entry.info.clone())
+    }
+
+    /// Cancels a queued or running job. Returns false if no such job is
+    /// waiting or running.
+    pub fn cancel(&self, id: &str) -> bool {
+        let jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        match jobs.get(id) {
+            Some(entry) if entry.info.finished_at.is_none() => {
+                entry.token.cancel();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Records how a job ended and tells the frontend with [`JOB_FINISHED_EVENT`].
+pub fn report_finished(
+    app: &AppHandle,
+    registry: &JobRegistry,
+    id: &str,
+    result: &Result<(), String>,
+) {
+    if let Some(info) = registry.finish(id, result) {
+        let _ = app.emit(JOB_FINISHED_EVENT, &info);
+    }
+}
+
+/// Drops the oldest finished jobs beyond [`FINISHED_JOBS_KEPT`].
+fn prune_finished(jobs: &mut HashMap<String, JobEntry>) {
+    let mut finished: Vec<(DateTime<Utc>, String)> = jobs
+        .values()
+        .filter_map(|e| e.info.finished_at.map(|at| (at, e.info.id.clone())))
+        .collect();
+    if finished.len() <= FINISHED_JOBS_KEPT {
+        return;
+    }
+    finished.sort();
+    for (_, id) in finished.iter().take(finished.len() - FINISHED_JOBS_KEPT) {
+        jobs.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_stops_pending_work() {
+        let registry = JobRegistry::default();
+        let (id, token) = registry.start(JobKind::Fetch, "ethereum 0xabc", None);
+
+        let worker = token.clone();
+        let handle = tokio::spawn(async move {
+            worker
+                .run(tokio::time::sleep(Duration::from_secs(60)))
+                .await
+        });
+        assert!(registry.cancel(&id));
+        assert_eq!(handle.await.unwrap(), Err(CANCELLED.to_string()));
+
+        let info = registry.finish(&id, &Err(CANCELLED.to_string())).unwrap();
+        assert_eq!(info.status, JobStatus::Cancelled);
+        assert!(!registry.cancel(&id));
+        assert!(token.run(async {}).await.is_err());
+    }
+
+    #[test]
+    fn test_finish_records_outcome() {
+        let registry = JobRegistry::default();
+        let (ok, _) = registry.start(JobKind::WalletSync, "p1", Some("job-1".to_string()));
+        assert_eq!(ok, "job-1");
+        assert_eq!(
+            registry.finish(&ok, &Ok(())).unwrap().status,
+            JobStatus::Completed
+        );
+
+        let (failed, _) = registry.start(JobKind::WalletSync, "p1", None);
+        let info = registry
+            .finish(&failed, &Err("rate limited".to_string()))
+            .unwrap();
+        assert_eq!(info.status, JobStatus::Failed);
+        assert_eq!(info.error.as_deref(), Some("rate limited"));
+        assert!(registry.finish("unknown", &Ok(())).is_none());
+    }
+
+    #[test]
+    fn test_queued_jobs_list_before_finished() {
+        let registry = JobRegistry::default();
+        let (done, _) = registry.start(JobKind::Fetch, "done", None);
+        registry.finish(&done, &Ok(())).unwrap();
+        registry.enqueue(
+            "queued",
+            JobKind::WalletSync,
+            "wallet",
+            JobPriority::Background,
+            Utc::now(),
+        );
+
+        let list = registry.list();
+        assert_eq!(list[0].id, "queued");
+        assert_eq!(list[0].status, JobStatus::Queued);
+        assert_eq!(list[1].id, done);
+
+        let token = registry.mark_running("queued").unwrap();
+        registry.mark_retrying("queued", "timeout");
+        let info = &registry.list()[0];
+        assert_eq!((info.status, info.attempts), (JobStatus::Queued, 1));
+        assert!(registry.cancel("queued"));
+        assert!(token.is_cancelled());
+    }
+}
@@ -0,0 +1,612 @@
+//! Priority queue for background jobs.
+//!
+//! Jobs are queued rather than spawned directly so that fetches don't pile
+//! up against one provider. The dispatcher starts the highest-priority job
+//! that is due, as long as fewer than [`MAX_RUNNING_JOBS`] are running and
+//! its provider is under its limit. Failed attempts are retried with
+//! exponential backoff. Queued jobs are kept in the `job_queue` table and
+//! restored on the next start. While the app is offline no jobs start; they
+//! wait in the queue until connectivity returns.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+use super::{report_finished, CancelToken, JobKind, JobPriority, JobRegistryState, CANCELLED};
+use crate::api::address_watch::run_watch_checks;
+use crate::api::confirmations::run_confirmation_checks;
+use crate::api::invoices::run_invoice_checks;
+use crate::api::mempool_watch::run_mempool_checks;
+use crate::api::pending_bitcoin::run_pending_checks;
+use crate::api::persistence::DatabaseState;
+use crate::api::report_schedules::run_due_reports;
+use crate::api::wallet_sync::{run_wallet_sync, AppSyncEvents};
+use crate::chains::ChainManagerState;
+use crate::fetchers::offline;
+use crate::log_error;
+
+/// Most jobs running at once across all providers.
+const MAX_RUNNING_JOBS: usize = 4;
+
+/// Most jobs running at once against one chain's explorer or RPC.
+const CHAIN_PROVIDER_LIMIT: usize = 2;
+
+/// Provider name for address watch checks, which touch every watched chain.
+const ADDRESS_WATCH_PROVIDER: &str = "address_watch";
+
+/// Provider name for invoice checks, which touch every invoiced chain.
+const INVOICE_PROVIDER: &str = "invoices";
+
+/// Provider name for pending Bitcoin transaction checks.
+const PENDING_TX_PROVIDER: &str = "bitcoin_pending";
+
+/// Provider name for mempool checks of watched Bitcoin addresses.
+const MEMPOOL_WATCH_PROVIDER: &str = "bitcoin_mempool";
+
+/// Provider name for scheduled report runs, which send email.
+const SCHEDULED_REPORTS_PROVIDER: &str = "scheduled_reports";
+
+/// Longest wait between retries.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(15 * 60);
+
+// =============================================================================
+// TASKS
+// =============================================================================
+
+/// Work a queued job does. Stored as JSON so it can be restored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobTask {
+    /// Sync one wallet's transactions.
+    WalletSync {
+        /// Profile the wallet belongs to.
+        profile_id: String,
+        /// User the transactions are saved for.
+        user_id: String,
+        /// Wallet to sync.
+        wallet_id: String,
+        /// Chain the wallet is on, which is the provider it uses.
+        chain: String,
+    },
+    /// Check watched addresses for new activity.
+    AddressWatch,
+    /// Check open invoices for payment.
+    InvoiceCheck,
+    /// Check pending Bitcoin transactions for confirmation or replacement,
+    /// and pending transactions on every chain for confirmation depth.
+    PendingTxCheck,
+    /// Check watched Bitcoin addresses' mempools for incoming payments.
+    MempoolWatch,
+    /// Generate and send the scheduled reports that are due.
+    ScheduledReports,
+}
+
+/// How often, and how far apart, failed attempts are retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts before the job fails for good.
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubled for each one after.
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Wait before retrying after `attempts` attempts.
+    pub fn delay(&self, attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        self.base_delay
+            .checked_mul(factor)
+            .map_or(MAX_RETRY_DELAY, |d| d.min(MAX_RETRY_DELAY))
+    }
+}
+
+impl JobTask {
+    /// Kind shown for the job.
+    pub fn kind(&self) -> JobKind {
+        match self {
+            JobTask::WalletSync { .. } => JobKind::WalletSync,
+            JobTask::AddressWatch => JobKind::AddressWatch,
+            JobTask::InvoiceCheck => JobKind::InvoiceCheck,
+            JobTask::PendingTxCheck => JobKind::PendingTxCheck,
+            JobTask::MempoolWatch => JobKind::MempoolWatch,
+            JobTask::ScheduledReports => JobKind::ScheduledReports,
+        }
+    }
+
+    /// Short description shown for the job.
+    pub fn label(&self) -> String {
+        match self {
+            JobTask::WalletSync {
+                chain, wallet_id, ..
+            } => format!("{} wallet {}", chain, wallet_id),
+            JobTask::AddressWatch => "Address watch check".to_string(),
+            JobTask::InvoiceCheck => "Invoice payment check".to_string(),
+            JobTask::PendingTxCheck => "Pending transaction check".to_string(),
+            JobTask::MempoolWatch => "Mempool payment check".to_string(),
+            JobTask::ScheduledReports => "Scheduled reports".to_string(),
+        }
+    }
+
+    /// Provider the job's requests go to, for concurrency limits.
+    pub fn provider(&self) -> &str {
+        match self {
+            JobTask::WalletSync { chain, .. } => chain,
+            JobTask::AddressWatch => ADDRESS_WATCH_PROVIDER,
+            JobTask::InvoiceCheck => INVOICE_PROVIDER,
+            JobTask::PendingTxCheck => PENDING_TX_PROVIDER,
+            JobTask::MempoolWatch => MEMPOOL_WATCH_PROVIDER,
+            JobTask::ScheduledReports => SCHEDULED_REPORTS_PROVIDER,
+        }
+    }
+
+    /// Retry policy for the job. Checks and report runs aren't retried
+    /// because the next scheduled run does the same work.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        match self {
+            JobTask::WalletSync { .. } => RetryPolicy {
+                max_attempts: 3,
+                base_delay: Duration::from_secs(30),
+            },
+            JobTask::AddressWatch
+            | JobTask::InvoiceCheck
+            | JobTask::PendingTxCheck
+            | JobTask::MempoolWatch
+            | JobTask::ScheduledReports => RetryPolicy {
+                max_attempts: 1,
+                base_delay: Duration::ZERO,
+            },
+        }
+    }
+
+    /// Runs one attempt of the job.
+    async fn run(&self, app: &AppHandle, cancel: &CancelToken) -> Result<(), String> {
+        let pool = app.state::<DatabaseState>().pool.clone();
+        let chains = app.state::<ChainManagerState>().inner().clone();
+        match self {
+            JobTask::WalletSync {
+                profile_id,
+                user_id,
+                wallet_id,
+                ..
+            } => {
+                run_wallet_sync(
+                    &AppSyncEvents(app),
+                    &pool,
+                    &chains,
+                    profile_id,
+                    user_id,
+                    wallet_id,
+                    cancel,
+                )
+                .await
+            }
+            JobTask::AddressWatch => cancel
+                .run(run_watch_checks(&pool, &chains, Some(app)))
+                .await?
+                .map(|_| ()),
+            JobTask::InvoiceCheck => cancel
+                .run(run_invoice_checks(&pool, &chains, Some(app)))
+                .await?
+                .map(|_| ()),
+            JobTask::PendingTxCheck => cancel
+                .run(async {
+                    run_pending_checks(&pool, Some(app), None).await?;
+                    run_confirmation_checks(&pool, &chains, None).await
+                })
+                .await?
+                .map(|_| ()),
+            JobTask::MempoolWatch => cancel
+                .run(run_mempool_checks(&pool, Some(app)))
+                .await?
+                .map(|_| ()),
+            JobTask::ScheduledReports => cancel.run(run_due_reports(&pool)).await?.map(|_| ()),
+        }
+    }
+}
+
+/// Concurrent jobs allowed against `provider`.
+fn provider_limit(provider: &str) -> usize {
+    match provider {
+        ADDRESS_WATCH_PROVIDER
+        | INVOICE_PROVIDER
+        | PENDING_TX_PROVIDER
+        | MEMPOOL_WATCH_PROVIDER
+        | SCHEDULED_REPORTS_PROVIDER => 1,
+        _ => CHAIN_PROVIDER_LIMIT,
+    }
+}
+
+// =============================================================================
+// QUEUE
+// =============================================================================
+
+/// A job waiting in or running from the queue.
+#[derive(Debug, Clone)]
+struct QueuedJob {
+    id: String,
+    task: JobTask,
+    priority: JobPriority,
+    attempts: u32,
+    run_after: DateTime<Utc>,
+    created_at: DateTime<Utc>,
+}
+
+/// A `job_queue` row.
+#[derive(Debug, FromRow)]
+struct QueuedJobRow {
+    id: String,
+    task: String,
+    priority: String,
+    attempts: i64,
+    run_after: DateTime<Utc>,
+    created_at: DateTime<Utc>,
+}
+
+impl QueuedJobRow {
+    fn into_job(self) -> Result<QueuedJob, String> {
+        let priority = match self.priority.as_str() {
+            "user" => JobPriority::User,
+            _ => JobPriority::Background,
+        };
+        Ok(QueuedJob {
+            task: serde_json::from_str(&self.task).map_err(|e| e.to_string())?,
+            id: self.id,
+            priority,
+            attempts: self.attempts.max(0) as u32,
+            run_after: self.run_after,
+            created_at: self.created_at,
+        })
+    }
+}
+
+fn priority_name(priority: JobPriority) -> &'static str {
+    match priority {
+        JobPriority::User => "user",
+        JobPriority::Background => "background",
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    pending: Vec<QueuedJob>,
+    running: Vec<QueuedJob>,
+}
+
+/// Picks the next job to start: the highest-priority, then oldest, job that
+/// is due and whose provider has room.
+fn next_runnable(
+    pending: &[QueuedJob],
+    running: &[QueuedJob],
+    now: DateTime<Utc>,
+) -> Option<usize> {
+    if running.len() >= MAX_RUNNING_JOBS {
+        return None;
+    }
+    let mut per_provider: HashMap<&str, usize> = HashMap::new();
+    for job in running {
+        *per_provider.entry(job.task.provider()).or_default() += 1;
+    }
+
+    pending
+        .iter()
+        .enumerate()
+        .filter(|(_, job)| job.run_after <= now)
+        .filter(|(_, job)| {
+            let provider = job.task.provider();
+            per_provider.get(provider).copied().unwrap_or(0) < provider_limit(provider)
+        })
+        .min_by_key(|(_, job)| (job.priority, job.created_at))
+        .map(|(i, _)| i)
+}
+
+/// Queue of background jobs, run by a dispatcher task.
+pub struct JobQueue {
+    app: AppHandle,
+    pool: SqlitePool,
+    registry: JobRegistryState,
+    state: Mutex<QueueState>,
+    wake: Notify,
+}
+
+/// Shared job queue managed by Tauri.
+pub type JobQueueState = Arc<JobQueue>;
+
+impl JobQueue {
+    /// Creates the queue, restores jobs saved by the last run, and starts
+    /// dispatching.
+    pub fn start(app: AppHandle, pool: SqlitePool, registry: JobRegistryState) -> JobQueueState {
+        let queue = Arc::new(Self {
+            app,
+            pool,
+            registry,
+            state: Mutex::new(QueueState::default()),
+            wake: Notify::new(),
+        });
+        tauri::async_runtime::spawn(queue.clone().dispatch());
+        queue
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queues a task and returns its job ID. A task already queued or
+    /// running is not queued twice; its ID is returned instead, and its
+    /// priority raised if this request's is higher.
+    pub async fn enqueue(&self, task: JobTask, priority: JobPriority) -> Result<String, String> {
+        let raised = {
+            let mut state = self.lock();
+            if let Some(job) = state.running.iter().find(|j| j.task == task) {
+                return Ok(job.id.clone());
+            }
+            match state.pending.iter_mut().find(|j| j.task == task) {
+                Some(job) if priority < job.priority => {
+                    job.priority = priority;
+                    Some(job.id.clone())
+                }
+                Some(job) => return Ok(job.id.clone()),
+                None => None,
+            }
+        };
+        if let Some(id) = raised {
+            self.registry.set_priority(&id, priority);
+            sqlx::query("UPDATE job_queue SET priority = ? WHERE id = ?")
+                .bind(priority_name(priority))
+                .bind(&id)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            self.wake.notify_one();
+            return Ok(id);
+        }
+
+        let now = Utc::now();
+        let job = QueuedJob {
+            id: Uuid::new_v4().to_string(),
+            task,
+            priority,
+            attempts: 0,
+            run_after: now,
+            created_at: now,
+        };
+        sqlx::query(
+            "INSERT INTO job_queue (id, task, priority, attempts, run_after, created_at) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&job.id)
+        .bind(serde_json::to_string(&job.task).map_err(|e| e.to_string())?)
+        .bind(priority_name(priority))
+        .bind(0i64)
+        .bind(job.run_after)
+        .bind(job.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let id = job.id.clone();
+        self.add_pending(job);
+        Ok(id)
+    }
+
+    /// Cancels a job. A queued job is dropped from the queue; a running one
+    /// is signalled to stop. Returns false if the job isn't queued or running.
+    pub async fn cancel(&self, id: &str) -> bool {
+        if !self.registry.cancel(id) {
+            return false;
+        }
+        let removed = {
+            let mut state = self.lock();
+            let index = state.pending.iter().position(|j| j.id == id);
+            index.map(|i| state.pending.remove(i))
+        };
+        if removed.is_some() {
+            self.forget(id).await;
+            report_finished(&self.app, &self.registry, id, &Err(CANCELLED.to_string()));
+        }
+        true
+    }
+
+    /// Wakes the dispatcher, e.g. when connectivity returns.
+    pub fn resume(&self) {
+        self.wake.notify_one();
+    }
+
+    fn add_pending(&self, job: QueuedJob) {
+        self.registry.enqueue(
+            &job.id,
+            job.task.kind(),
+            &job.task.label(),
+            job.priority,
+            job.created_at,
+        );
+        self.lock().pending.push(job);
+        self.wake.notify_one();
+    }
+
+    /// Loads the jobs queued when the app last closed.
+    async fn restore(&self) -> Result<(), String> {
+        let rows: Vec<QueuedJobRow> = sqlx::query_as("SELECT * FROM job_queue ORDER BY created_at")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        for row in rows {
+            let id = row.id.clone();
+            match row.into_job() {
+                Ok(job) => {
+                    let known = {
+                        let state = self.lock();
+                        state
+                            .pending
+                            .iter()
+                            .chain(&state.running)
+                            .any(|j| j.id == id)
+                    };
+                    if !known {
+                        self.add_pending(job);
+                    }
+                }
+                Err(e) => {
+                    log_error!("Dropping unreadable queued job {}: {}", id, e);
+                    self.forget(&id).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes a job from the saved queue.
+    async fn forget(&self, id: &str) {
+        if let Err(e) = sqlx::query("DELETE FROM job_queue WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+        {
+            log_error!("Failed to remove queued job {}: {}", id, e);
+        }
+    }
+
+    /// Starts jobs as they become runnable, until the app exits.
+    async fn dispatch(self: Arc<Self>) {
+        if let Err(e) = self.restore().await {
+            log_error!("Failed to restore queued jobs: {}", e);
+        }
+
+        loop {
+            if !offline::is_online() {
+                self.wake.notified().await;
+                continue;
+            }
+
+            let next_due = {
+                let mut state = self.lock();
+                let now = Utc::now();
+                while let Some(i) = next_runnable(&state.pending, &state.running, now) {
+                    let job = state.pending.remove(i);
+                    state.running.push(job.clone());
+                    tauri::async_runtime::spawn(self.clone().run(job));
+                }
+                state.pending.iter().map(|j| j.run_after).min()
+            };
+
+            match next_due {
+                Some(due) => {
+                    let wait = (due - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+                    tokio::select! {
+                        _ = self.wake.notified() => {}
+                        _ = tokio::time::sleep(wait.max(Duration::from_millis(100))) => {}
+                    }
+                }
+                None => self.wake.notified().await,
+            }
+        }
+    }
+
+    /// Runs one attempt of a job, then finishes it or queues a retry.
+    async fn run(self: Arc<Self>, mut job: QueuedJob) {
+        let token = self
+            .registry
+            .mark_running(&job.id)
+            .unwrap_or_else(CancelToken::new);
+        job.attempts += 1;
+        let result = match token.check() {
+            Ok(()) => job.task.run(&self.app, &token).await,
+            Err(e) => Err(e),
+        };
+
+        self.lock().running.retain(|j| j.id != job.id);
+        let policy = job.task.retry_policy();
+        match result {
+            Err(e) if !token.is_cancelled() && job.attempts < policy.max_attempts => {
+                job.run_after = Utc::now()
+                    + chrono::Duration::from_std(policy.delay(job.attempts))
+                        .unwrap_or_else(|_| chrono::Duration::zero());
+                if let Err(e) =
+                    sqlx::query("UPDATE job_queue SET attempts = ?, run_after = ? WHERE id = ?")
+                        .bind(job.attempts as i64)
+                        .bind(job.run_after)
+                        .bind(&job.id)
+                        .execute(&self.pool)
+                        .await
+                {
+                    log_error!("Failed to save retry for job {}: {}", job.id, e);
+                }
+                self.registry.mark_retrying(&job.id, &e);
+                self.lock().pending.push(job);
+            }
+            result => {
+                self.forget(&job.id).await;
+                report_finished(&self.app, &self.registry, &job.id, &result);
+            }
+        }
+        self.wake.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: &str, chain: &str, priority: JobPriority, age_secs: i64) -> QueuedJob {
+        let now = Utc::now();
+        QueuedJob {
+            id: id.to_string(),
+            task: JobTask::WalletSync {
+                profile_id: "p1".to_string(),
+                user_id: "u1".to_string(),
+                wallet_id: id.to_string(),
+                chain: chain.to_string(),
+            },
+            priority,
+            attempts: 0,
+            run_after: now,
+            created_at: now - chrono::Duration::seconds(age_secs),
+        }
+    }
+
+    #[test]
+    fn test_next_runnable_prefers_user_jobs_within_limits() {
+        let now = Utc::now() + chrono::Duration::seconds(1);
+        let pending = vec![
+            job("old-background", "ethereum", JobPriority::Background, 60),
+            job("user-eth", "ethereum", JobPriority::User, 10),
+            job("user-polkadot", "polkadot", JobPriority::User, 5),
+        ];
+        assert_eq!(next_runnable(&pending, &[], now), Some(1));
+
+        // Ethereum is at its limit, so the Polkadot job goes next.
+        let running = vec![
+            job("a", "ethereum", JobPriority::User, 0),
+            job("b", "ethereum", JobPriority::User, 0),
+        ];
+        assert_eq!(next_runnable(&pending, &running, now), Some(2));
+
+        let mut later = pending.clone();
+        later[2].run_after = now + chrono::Duration::seconds(30);
+        assert_eq!(next_runnable(&later, &running, now), None);
+
+        let full: Vec<QueuedJob> = (0..MAX_RUNNING_JOBS)
+            .map(|i| job(&i.to_string(), &format!("chain{}", i), JobPriority::User, 0))
+            .collect();
+        assert_eq!(next_runnable(&pending, &full, now), None);
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off() {
+        let policy = JobTask::AddressWatch.retry_policy();
+        assert_eq!(policy.max_attempts, 1);
+
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(30),
+        };
+        assert_eq!(policy.delay(1), Duration::from_secs(30));
+        assert_eq!(policy.delay(3), Duration::from_secs(120));
+        assert_eq!(policy.delay(40), MAX_RETRY_DELAY);
+    }
+}
@@ -1,31 +1,23 @@
 mod api;
 mod chains;
+mod cloud_sync;
 mod core;
-mod db;
-mod evm_indexer;
 mod fetchers;
 mod indexer;
+mod jobs;
+mod local_api;
+mod startup;
 mod storage;
 mod sync;
 
-use api::persistence::DatabaseState;
-use chains::commands::create_chain_manager_state;
+use chains::create_chain_manager_state;
 use core::auth_state::AuthState;
 use core::email;
-use evm_indexer::EVMIndexer;
-use storage::commands::StorageState;
-use tauri::{Manager, State};
-use tokio::sync::Mutex;
+use pacioli_core::{db, log_error, log_info, DATABASE_FILE};
+use tauri::Manager;
 
-// Environment variable names
-const ENV_RESEND_API_KEY: &str = "RESEND_API_KEY";
-const ENV_ETHERSCAN_API_KEY: &str = "ETHERSCAN_API_KEY";
-const ENV_POLYGONSCAN_API_KEY: &str = "POLYGONSCAN_API_KEY";
-const ENV_ARBISCAN_API_KEY: &str = "ARBISCAN_API_KEY";
-const ENV_HELIUS_API_KEY: &str = "HELIUS_API_KEY";
-
-// Global EVM indexer state
-type EVMIndexerState = Mutex<EVMIndexer>;
+/// Environment variable holding the Resend API key.
+pub(crate) const ENV_RESEND_API_KEY: &str = "RESEND_API_KEY";
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
@@ -33,151 +25,16 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
-#[tauri::command]
-async fn connect_evm_chain(
-    state: State<'_, EVMIndexerState>,
-    chain: String,
-) -> Result<String, String> {
-    let mut indexer = state.lock().await;
-    indexer.connect(&chain).await.map_err(|e| e.to_string())?;
-    Ok(format!("Connected to {}", chain))
-}
-
-#[tauri::command]
-async fn get_evm_balance(
-    state: State<'_, EVMIndexerState>,
-    chain: String,
-    address: String,
-) -> Result<String, String> {
-    let indexer = state.lock().await;
-    let balance = indexer
-        .get_balance(&chain, &address)
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(balance.to_string())
-}
-
-#[tauri::command]
-async fn get_evm_token_balances(
-    state: State<'_, EVMIndexerState>,
-    chain: String,
-    address: String,
-) -> Result<Vec<(String, String)>, String> {
-    // Common ERC20 tokens for each chain
-    let tokens = match chain.as_str() {
-        "moonbeam" => vec![
-            "0xAcc15dC74880C9944775448304B263D191c6077F", // WGLMR
-            "0x818ec0A7Fe18Ff94269904fCED6AE3DaE6d6dC0b", // USDC
-        ],
-        "moonriver" => vec![
-            "0x98878B06940aE243284CA214f92Bb71a2b032B8A", // WMOVR
-            "0xE3F5a90F9cb311505cd691a46596599aA1A0AD7D", // USDC
-        ],
-        "astar" => vec![
-            "0xAeaaf0e2c81Af264101B9129C00F4440cCF0F720", // WASTR
-        ],
-        _ => vec![],
-    };
-
-    let indexer = state.lock().await;
-    let balances = indexer
-        .scan_erc20_balances(&chain, &address, tokens)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(balances
-        .into_iter()
-        .map(|(addr, balance)| (addr, balance.to_string()))
-        .collect())
-}
-
-#[tauri::command]
-async fn get_evm_transactions(
-    state: State<'_, EVMIndexerState>,
-    chain: String,
-    address: String,
-    from_block: u64,
-    to_block: String,
-) -> Result<Vec<String>, String> {
-    let to_block_num = if to_block == "latest" {
-        let indexer = state.lock().await;
-        indexer
-            .get_block_number(&chain)
-            .await
-            .map_err(|e| e.to_string())?
-    } else {
-        to_block.parse::<u64>().map_err(|e| e.to_string())?
-    };
-
-    let indexer = state.lock().await;
-    let transactions = indexer
-        .get_transactions(&chain, &address, from_block, to_block_num)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    // Convert transactions to JSON strings for frontend
-    Ok(transactions
-        .into_iter()
-        .map(|tx| serde_json::to_string(&tx).unwrap_or_default())
-        .collect())
-}
-
-#[tauri::command]
-async fn scan_defi_positions(
-    state: State<'_, EVMIndexerState>,
-    chain: String,
-    address: String,
-) -> Result<Vec<String>, String> {
-    let protocols = match chain.as_str() {
-        "moonbeam" => vec!["stellaswap", "moonwell"],
-        "astar" => vec!["arthswap"],
-        "acala" => vec!["acala-swap"],
-        _ => vec![],
-    };
-
-    let indexer = state.lock().await;
-    let positions = indexer
-        .scan_defi_positions(&chain, &address, protocols)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    // Convert positions to JSON strings for frontend
-    Ok(positions
-        .into_iter()
-        .map(|pos| serde_json::to_string(&pos).unwrap_or_default())
-        .collect())
-}
-
-#[tauri::command]
-async fn sync_evm_transactions(
-    state: State<'_, EVMIndexerState>,
-    chain: String,
-    address: String,
-) -> Result<String, String> {
-    // Get latest block and sync from last 1000 blocks
-    let indexer = state.lock().await;
-    let latest_block = indexer
-        .get_block_number(&chain)
-        .await
-        .map_err(|e| e.to_string())?;
-    let from_block = latest_block.saturating_sub(1000);
-
-    let transactions = indexer
-        .get_transactions(&chain, &address, from_block, latest_block)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(format!("Synced {} transactions", transactions.len()))
-}
-
 /// Runs the Tauri application with all configured plugins and commands.
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_deep_link::init())
         .setup(|app| {
-            // Initialize database
+            // Resolve the app data directory
             let app_data_dir = app
                 .path()
                 .app_data_dir()
@@ -186,25 +43,19 @@ pub fn run() {
             // Ensure directory exists
             std::fs::create_dir_all(&app_data_dir).expect("Failed to create app data directory");
 
-            let db_path = app_data_dir.join("pacioli.db");
-            let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
-
-            // Initialize database state using tokio runtime
-            let db_state = tauri::async_runtime::block_on(async {
-                DatabaseState::new(&db_url)
-                    .await
-                    .expect("Failed to initialize database")
-            });
+            // Write a local crash report if the app panics
+            core::diagnostics::install_panic_hook(
+                app_data_dir.join(core::diagnostics::CRASH_REPORT_DIR),
+            );
 
-            app.manage(db_state);
+            // Cache explorer responses for finalized block ranges on disk
+            chains::evm::response_cache::init(app_data_dir.join("explorer_cache"));
 
-            // Initialize storage state (uses the same pool, cloned)
-            let storage_pool = tauri::async_runtime::block_on(async {
-                sqlx::SqlitePool::connect(&db_url)
-                    .await
-                    .expect("Failed to initialize storage pool")
-            });
-            app.manage(StorageState::new(storage_pool));
+            // Register chains defined by manifests in the plugin directory
+            for error in chains::plugins::load_dir(&app_data_dir.join(chains::plugins::PLUGIN_DIR))
+            {
+                log_error!("Warning: skipped chain plugin {}", error);
+            }
 
             // Initialize authentication state
             app.manage(AuthState::new());
@@ -214,68 +65,39 @@ pub fn run() {
             let _ = dotenvy::dotenv(); // Ignore error if .env doesn't exist
             if let Ok(api_key) = std::env::var(ENV_RESEND_API_KEY) {
                 email::init(api_key);
-                println!("Email service initialized");
-            } else {
-                eprintln!(
-                    "Warning: {} not set, email sending disabled",
-                    ENV_RESEND_API_KEY
-                );
+                log_info!("Email service initialized");
             }
 
-            // Initialize chain manager
-            let chain_manager = create_chain_manager_state();
+            // Initialize chain manager; explorer API keys are read from the
+            // environment when the first adapter is created
+            app.manage(create_chain_manager_state());
 
-            // Set up API keys from environment if available
-            if let Ok(etherscan_key) = std::env::var(ENV_ETHERSCAN_API_KEY) {
-                let manager = chain_manager.blocking_read();
-                tauri::async_runtime::block_on(async {
-                    manager
-                        .set_explorer_api_key("ethereum", etherscan_key.clone())
-                        .await;
-                    manager.set_explorer_api_key("1", etherscan_key).await;
-                });
-            }
-            if let Ok(polygonscan_key) = std::env::var(ENV_POLYGONSCAN_API_KEY) {
-                let manager = chain_manager.blocking_read();
-                tauri::async_runtime::block_on(async {
-                    manager
-                        .set_explorer_api_key("polygon", polygonscan_key.clone())
-                        .await;
-                    manager.set_explorer_api_key("137", polygonscan_key).await;
-                });
-            }
-            if let Ok(arbiscan_key) = std::env::var(ENV_ARBISCAN_API_KEY) {
-                let manager = chain_manager.blocking_read();
-                tauri::async_runtime::block_on(async {
-                    manager
-                        .set_explorer_api_key("arbitrum", arbiscan_key.clone())
-                        .await;
-                    manager.set_explorer_api_key("42161", arbiscan_key).await;
-                });
-            }
-            if let Ok(helius_key) = std::env::var(ENV_HELIUS_API_KEY) {
-                let manager = chain_manager.blocking_read();
-                tauri::async_runtime::block_on(async {
-                    manager.set_explorer_api_key("solana", helius_key).await;
-                });
+            // Register the pacioli:// scheme (bundled installs register it at
+            // install time on macOS and Windows) and forward links to the UI
+            #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+                if let Err(e) = app.deep_link().register_all() {
+                    log_error!("Warning: failed to register deep link scheme: {}", e);
+                }
             }
+            core::deep_link::listen(app.handle());
 
-            app.manage(chain_manager);
-            println!("Chain manager initialized");
+            // Open the database and start background services without
+            // blocking the window
+            startup::spawn(app.handle().clone(), app_data_dir);
 
             Ok(())
         })
-        .manage(EVMIndexerState::new(EVMIndexer::new()))
+        .manage(jobs::create_job_registry_state())
+        .manage(local_api::LocalApiState::default())
+        .manage(startup::StartupState::default())
         .invoke_handler(tauri::generate_handler![
             greet,
-            connect_evm_chain,
-            get_evm_balance,
-            get_evm_token_balances,
-            get_evm_transactions,
-            scan_defi_positions,
-            sync_evm_transactions,
+            startup::get_startup_status,
             api::export::export_transactions_csv,
             api::export::export_tax_report,
+            api::statement_export::export_wallet_statement,
             api::backup::create_backup,
             api::backup::restore_backup,
             // Persistence commands
@@ -285,16 +107,92 @@ pub fn run() {
             api::persistence::delete_profile,
             api::persistence::save_wallet,
             api::persistence::get_wallets,
+            api::wallet_identity::get_wallet_identities,
+            api::wallet_identity::detect_internal_transfers,
+            api::wallet_identity::get_account_balances,
             api::persistence::get_wallet_by_id,
             api::persistence::delete_wallet,
             api::persistence::save_transactions,
             api::persistence::get_transactions,
             api::persistence::get_all_transactions,
+            api::wallet_sync::get_sync_status,
+            api::wallet_sync::sync_wallets,
+            api::transaction_query::query_transactions,
+            api::swaps::get_transaction_swaps,
+            api::transaction_fees::get_transaction_fees,
+            api::perp_import::import_perp_history,
+            api::lightning_import::save_lightning_node,
+            api::lightning_import::delete_lightning_node,
+            api::lightning_import::import_lightning_payments,
+            api::utxos::list_wallet_utxos,
+            api::utxos::set_utxo_label,
+            api::utxos::get_utxo_spent_lots,
+            api::pending_bitcoin::get_pending_bitcoin_txs,
+            api::pending_bitcoin::check_pending_bitcoin_txs,
+            api::mempool_watch::get_confirmation_settings,
+            api::mempool_watch::update_confirmation_settings,
+            api::mempool_watch::get_mempool_payments,
+            api::mempool_watch::check_mempool_payments,
+            api::confirmations::get_chain_confirmations,
+            api::confirmations::set_chain_confirmations,
+            api::approvals::get_approval_exposure,
+            api::balance_history::get_historical_balances,
+            api::balance_history::reconcile_wallet_balances,
+            api::sync_gaps::detect_sync_gaps,
+            api::sync_gaps::resync_gaps,
+            api::duplicate_records::find_duplicate_transfers,
+            api::duplicate_records::get_transaction_merges,
+            api::duplicate_records::merge_transactions,
+            api::duplicate_records::unmerge_transaction,
+            api::entity_statements::get_entity_statement,
+            api::entity_statements::export_entity_statement,
+            api::entity_statements::get_payee_report,
+            api::entity_statements::export_payee_report,
+            api::report_schedules::get_report_schedules,
+            api::report_schedules::save_report_schedule,
+            api::report_schedules::delete_report_schedule,
+            api::report_schedules::run_report_schedule,
+            api::report_schedules::get_generated_reports,
+            api::report_schedules::download_generated_report,
+            api::export_templates::get_export_templates,
+            api::export_templates::save_export_template,
+            api::export_templates::delete_export_template,
+            api::export_templates::export_transactions_with_template,
+            api::tax_tool_export::export_tax_tool_csv,
+            api::permissions::get_role_permissions,
+            api::permissions::get_my_permissions,
+            api::data_retention::get_data_retention,
+            api::data_retention::save_data_retention,
+            api::data_retention::preview_raw_data_prune,
+            api::data_retention::prune_raw_data,
+            api::invoices::create_invoice,
+            api::invoices::get_invoices,
+            api::invoices::cancel_invoice,
+            api::invoices::mark_invoice_paid,
+            api::invoices::check_invoices,
+            api::invoices::export_invoices_csv,
+            api::vesting::create_vesting_schedule,
+            api::vesting::get_vesting_schedules,
+            api::vesting::delete_vesting_schedule,
+            api::vesting::get_vesting_status,
+            api::account_links::get_account_links,
+            api::account_links::get_account_link_suggestions,
+            api::account_links::link_paired_account,
+            api::account_links::unlink_account,
+            api::wallet_clusters::get_wallet_cluster_suggestions,
+            api::xcm_transfers::sync_xcm_transfers,
+            api::xcm_transfers::get_xcm_transfers,
+            api::crowdloans::sync_crowdloans,
+            api::crowdloans::get_crowdloan_status,
             api::persistence::delete_transactions,
             api::persistence::get_setting,
             api::persistence::set_setting,
             api::persistence::delete_setting,
             api::persistence::get_all_settings,
+            api::persistence::get_schema_version,
+            api::persistence::db_integrity_check,
+            api::persistence::db_vacuum,
+            api::persistence::db_stats,
             // Entity commands
             api::entities::create_entity,
             api::entities::get_entities,
@@ -320,10 +218,19 @@ pub fn run() {
             api::auth::get_current_user,
             api::auth::update_user,
             api::auth::change_password,
+            api::auth::request_password_reset,
+            api::auth::reset_password,
+            api::auth::get_password_hash_settings,
+            api::auth::update_password_hash_settings,
+            api::auth::get_password_hash_report,
+            api::jwt_keys::get_jwt_keys,
+            api::jwt_keys::rotate_jwt_key,
             api::auth::request_email_change,
             api::auth::verify_email_change,
             api::auth::cancel_email_change,
             api::auth::get_email_change_status,
+            api::auth::get_email_change_settings,
+            api::auth::update_email_change_settings,
             api::auth::get_user_sessions,
             api::auth::revoke_session,
             api::auth::revoke_all_sessions,
@@ -335,6 +242,7 @@ pub fn run() {
             api::auth::get_profile_invitations,
             api::auth::accept_invitation,
             api::auth::revoke_invitation,
+            api::auth::resend_invitation,
             // Wallet authentication commands
             api::wallet_auth::generate_wallet_challenge,
             api::wallet_auth::verify_wallet_signature,
@@ -346,6 +254,9 @@ pub fn run() {
             chains::chain_get_supported_chains,
             chains::chain_is_supported,
             chains::chain_validate_address,
+            chains::validate_any_address,
+            chains::chain_discover_wallets,
+            chains::convert_ss58_address,
             chains::chain_fetch_transactions,
             chains::chain_fetch_balances,
             chains::chain_fetch_transaction,
@@ -354,7 +265,17 @@ pub fn run() {
             chains::chain_connect,
             chains::chain_set_explorer_api_key,
             chains::chain_set_rpc_url,
+            chains::chain_set_rpc_urls,
             chains::chain_get_block_number,
+            chains::chain_get_explorer_cache_stats,
+            chains::chain_purge_explorer_cache,
+            // EVM commands
+            chains::connect_evm_chain,
+            chains::get_evm_balance,
+            chains::get_evm_token_balances,
+            chains::get_evm_transactions,
+            chains::sync_evm_transactions,
+            chains::scan_defi_positions,
             // Bitcoin commands
             chains::get_bitcoin_transactions,
             chains::get_bitcoin_balance,
@@ -412,6 +333,8 @@ pub fn run() {
             fetchers::commands::get_provider_status,
             fetchers::commands::get_all_provider_statuses,
             fetchers::commands::get_configured_providers,
+            fetchers::commands::get_api_usage,
+            fetchers::commands::get_connectivity,
             // Price feed commands (CoinGecko integration)
             api::prices::get_crypto_price,
             api::prices::get_crypto_prices,
@@ -433,7 +356,105 @@ pub fn run() {
             api::accounting::get_account_balances,
             api::accounting::get_trial_balance,
             api::accounting::get_unclassified_transaction_count,
-            api::accounting::get_draft_journal_entry_count
+            api::accounting::get_draft_journal_entry_count,
+            api::accounting_events::expand_transaction_events,
+            api::accounting_events::get_accounting_events,
+            api::accounting_events::tag_accounting_event,
+            api::accounting_events::book_accounting_event,
+            // Budget commands
+            api::budgets::create_budget,
+            api::budgets::get_budgets,
+            api::budgets::update_budget,
+            api::budgets::delete_budget,
+            api::budgets::tag_transaction,
+            api::budgets::untag_transaction,
+            api::budgets::get_transaction_tags,
+            api::budgets::get_budget_report,
+            // Donation receipt commands
+            api::donation_receipts::generate_donation_receipt,
+            api::donation_receipts::get_donation_receipts,
+            api::donation_receipts::export_donation_receipt_pdf,
+            // Recurring transaction commands
+            api::recurring::detect_recurring_transactions,
+            api::recurring::get_recurring_series,
+            api::recurring::get_recurring_series_members,
+            api::recurring::update_recurring_series,
+            api::recurring::delete_recurring_series,
+            api::recurring::apply_recurring_auto_tags,
+            // Address watch commands
+            api::address_watch::add_address_watch,
+            api::address_watch::get_address_watches,
+            api::address_watch::update_address_watch,
+            api::address_watch::remove_address_watch,
+            api::address_watch::get_watch_alerts,
+            api::address_watch::mark_watch_alerts_read,
+            api::address_watch::check_address_watches,
+            // Email settings commands
+            api::email_settings::get_email_settings,
+            api::email_settings::save_smtp_settings,
+            api::email_settings::remove_smtp_settings,
+            api::email_settings::send_test_email,
+            // Search commands
+            api::search::search_everything,
+            api::search::rebuild_search_index,
+            // Cost basis commands
+            api::cost_basis::get_tax_jurisdictions,
+            api::cost_basis::get_profile_tax_settings,
+            api::cost_basis::update_profile_tax_settings,
+            api::cost_basis::calculate_cost_basis,
+            api::cost_basis::track_liquidity_positions,
+            api::unrealized_gains::get_unrealized_gains,
+            api::price_overrides::get_price_overrides,
+            api::price_overrides::save_price_override,
+            api::price_overrides::delete_price_override,
+            api::price_ids::get_price_id_mappings,
+            api::price_ids::set_price_id_mapping,
+            // Token spam commands
+            api::token_spam::mark_token_spam,
+            api::token_spam::mark_token_allowed,
+            api::token_spam::remove_token_mark,
+            api::token_spam::get_token_marks,
+            api::token_spam::import_token_blocklist,
+            api::token_spam::assess_tokens,
+            // Transfer simulation commands
+            api::transfer_simulation::simulate_transfer,
+            // Period close commands
+            api::period_close::close_period,
+            api::period_close::reopen_period,
+            api::period_close::get_period_closes,
+            // Audit trail commands
+            api::audit_trail::get_record_history,
+            api::audit_trail::get_audit_trail,
+            api::auditor_bundle::export_auditor_bundle,
+            // Diagnostics commands
+            api::diagnostics::get_crash_reports,
+            api::diagnostics::export_diagnostics,
+            api::diagnostics::clear_crash_reports,
+            // Report attestation commands
+            api::report_attestation::export_report_attestation,
+            api::report_attestation::verify_report_attestation,
+            // Consolidated reporting commands
+            api::consolidation::get_consolidated_report,
+            // Config bundle commands
+            api::config_bundle::export_config_bundle,
+            api::config_bundle::import_config_bundle,
+            // Portfolio snapshot commands
+            api::portfolio_snapshot::export_portfolio_snapshot,
+            api::portfolio_snapshot::open_portfolio_snapshot,
+            // Cloud sync commands
+            cloud_sync::commands::get_cloud_sync_status,
+            cloud_sync::commands::save_cloud_sync_config,
+            cloud_sync::commands::remove_cloud_sync_config,
+            cloud_sync::commands::run_cloud_sync,
+            cloud_sync::commands::get_cloud_sync_conflicts,
+            cloud_sync::commands::resolve_cloud_sync_conflict,
+            // Local API commands
+            local_api::commands::get_local_api_status,
+            local_api::commands::enable_local_api,
+            local_api::commands::disable_local_api,
+            // Background jobs
+            jobs::commands::list_jobs,
+            jobs::commands::cancel_job
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,135 @@
+//! Tauri commands for the local API.
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::State;
+
+use super::{
+    generate_token, hash_token, load_config, start, stop, LocalApiConfig, LocalApiState, AVAILABLE,
+    DEFAULT_PORT, SETTINGS_KEY,
+};
+use crate::api::auth::verify_admin;
+use crate::api::persistence::DatabaseState;
+use crate::api::profile_scope::authenticate;
+use crate::core::auth_state::AuthState;
+use crate::storage::settings_store;
+
+/// Lowest port the server may use; lower ones need elevated privileges.
+const MIN_PORT: u16 = 1024;
+
+/// Refuses to change a server configured by another user unless `user_id`
+/// is the app admin.
+async fn ensure_config_owner(
+    pool: &SqlitePool,
+    user_id: &str,
+    config: Option<&LocalApiConfig>,
+) -> Result<(), String> {
+    match config {
+        Some(config) if config.user_id != user_id => verify_admin(pool, user_id).await,
+        _ => Ok(()),
+    }
+}
+
+// =============================================================================
+// RESPONSE TYPES
+// =============================================================================
+
+/// Server state for display in settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalApiStatus {
+    /// Whether this build includes the server.
+    pub available: bool,
+    /// Whether the server is turned on.
+    pub enabled: bool,
+    /// Configured port.
+    pub port: u16,
+    /// Address the server is listening on, if running.
+    pub address: Option<String>,
+}
+
+/// Returned once when the server is turned on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalApiEnabled {
+    /// Bearer token for requests. Not stored; it can't be shown again.
+    pub token: String,
+    /// Address the server is listening on.
+    pub address: String,
+}
+
+// =============================================================================
+// COMMANDS
+// =============================================================================
+
+/// Returns whether the local API is available, enabled, and running.
+#[tauri::command]
+pub async fn get_local_api_status(
+    db: State<'_, DatabaseState>,
+    state: State<'_, LocalApiState>,
+) -> Result<LocalApiStatus, String> {
+    let config = load_config(&db.pool).await?;
+    Ok(LocalApiStatus {
+        available: AVAILABLE,
+        enabled: config.as_ref().is_some_and(|c| c.enabled),
+        port: config.map_or(DEFAULT_PORT, |c| c.port),
+        address: state.address().await.map(|addr| addr.to_string()),
+    })
+}
+
+/// Turns on the local API for the signed-in user with a new token,
+/// replacing any previous token, and starts the server. Replacing another
+/// user's server requires the app admin.
+#[tauri::command]
+pub async fn enable_local_api(
+    db: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    state: State<'_, LocalApiState>,
+    token: String,
+    port: Option<u16>,
+) -> Result<LocalApiEnabled, String> {
+    let user_id = authenticate(&auth, &token)?;
+    let existing = load_config(&db.pool).await?;
+    ensure_config_owner(&db.pool, &user_id, existing.as_ref()).await?;
+    let port = port.unwrap_or(DEFAULT_PORT);
+    if port < MIN_PORT {
+        return Err(format!("Port must be {} or higher", MIN_PORT));
+    }
+
+    let api_token = generate_token();
+    let config = LocalApiConfig {
+        enabled: true,
+        port,
+        user_id,
+        token_hash: hash_token(&api_token),
+    };
+    let addr = start(&state, &db.pool, &config).await?;
+
+    if let Err(e) = settings_store::set_setting_json(&db.pool, SETTINGS_KEY, &config).await {
+        stop(&state).await;
+        return Err(e.to_string());
+    }
+
+    Ok(LocalApiEnabled {
+        token: api_token,
+        address: addr.to_string(),
+    })
+}
+
+/// Stops the local API and revokes its token. Only the user who turned it on
+/// or the app admin may do so.
+#[tauri::command]
+pub async fn disable_local_api(
+    db: State<'_, DatabaseState>,
+    auth: State<'_, AuthState>,
+    state: State<'_, LocalApiState>,
+    token: String,
+) -> Result<(), String> {
+    let user_id = authenticate(&auth, &token)?;
+    let config = load_config(&db.pool).await?;
+    ensure_config_owner(&db.pool, &user_id, config.as_ref()).await?;
+    stop(&state).await;
+    settings_store::delete_setting(&db.pool, SETTINGS_KEY)
+        .await
+        .map_err(|e| e.to_string())
+}
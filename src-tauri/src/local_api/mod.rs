@@ -0,0 +1,170 @@
+//! Optional read-only API on localhost.
+//!
+//! Lets scripts, spreadsheets, and other local tools pull profiles, wallets,
+//! transactions, and ledger reports without going through the UI. The server
+//! is compiled only with the `local-api` feature and runs only after a user
+//! turns it on. It listens on the loopback interface, answers GET requests
+//! only, and requires a bearer token generated on this device. The token is
+//! shown once; only its SHA-256 hash is stored, alongside the user it acts
+//! for, so requests see exactly the profiles that user can.
+
+pub mod commands;
+#[cfg(feature = "local-api")]
+pub mod server;
+
+use std::net::SocketAddr;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use tokio::sync::{oneshot, Mutex};
+
+use crate::storage::settings_store;
+use crate::{log_error, log_info};
+
+/// Settings key holding the serialized [`LocalApiConfig`].
+pub const SETTINGS_KEY: &str = "local_api.config";
+
+/// Port used when none is chosen.
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// Prefix of generated tokens, so they are recognizable in scripts.
+const TOKEN_PREFIX: &str = "pacioli_";
+
+/// Whether this build includes the server.
+pub const AVAILABLE: bool = cfg!(feature = "local-api");
+
+/// Stored server settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalApiConfig {
+    /// Whether the server starts with the app.
+    pub enabled: bool,
+    /// Loopback port to listen on.
+    pub port: u16,
+    /// User whose profile access the token carries.
+    pub user_id: String,
+    /// Hex SHA-256 of the token.
+    pub token_hash: String,
+}
+
+/// A server that is listening.
+pub struct RunningServer {
+    /// Address it is bound to.
+    pub addr: SocketAddr,
+    shutdown: oneshot::Sender<()>,
+}
+
+/// The running server, if any.
+#[derive(Default)]
+pub struct LocalApiState {
+    running: Mutex<Option<RunningServer>>,
+}
+
+impl LocalApiState {
+    /// Address of the running server.
+    pub async fn address(&self) -> Option<SocketAddr> {
+        self.running.lock().await.as_ref().map(|server| server.addr)
+    }
+}
+
+/// Creates a new random token.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    format!("{}{}", TOKEN_PREFIX, URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// Hex SHA-256 of a token, as stored.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Whether `token` hashes to `token_hash`, compared in constant time.
+pub fn token_matches(token_hash: &str, token: &str) -> bool {
+    let presented = hash_token(token);
+    presented.len() == token_hash.len()
+        && presented
+            .bytes()
+            .zip(token_hash.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Loads the stored settings.
+pub async fn load_config(pool: &SqlitePool) -> Result<Option<LocalApiConfig>, String> {
+    settings_store::get_setting_json(pool, SETTINGS_KEY)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Starts the server for `config`, replacing any that is running, and
+/// returns the address it listens on.
+pub async fn start(
+    state: &LocalApiState,
+    pool: &SqlitePool,
+    config: &LocalApiConfig,
+) -> Result<SocketAddr, String> {
+    stop(state).await;
+
+    #[cfg(feature = "local-api")]
+    {
+        let server = server::start(pool.clone(), config).await?;
+        let addr = server.addr;
+        *state.running.lock().await = Some(server);
+        Ok(addr)
+    }
+
+    #[cfg(not(feature = "local-api"))]
+    {
+        let _ = (pool, config);
+        Err("This build doesn't include the local API server".to_string())
+    }
+}
+
+/// Stops the server if it is running.
+pub async fn stop(state: &LocalApiState) {
+    if let Some(server) = state.running.lock().await.take() {
+        let _ = server.shutdown.send(());
+    }
+}
+
+/// Starts the server at launch if the user turned it on. Failures are
+/// logged so they don't stop the app from starting.
+pub async fn start_if_enabled(state: &LocalApiState, pool: &SqlitePool) {
+    let config = match load_config(pool).await {
+        Ok(Some(config)) if config.enabled => config,
+        Ok(_) => return,
+        Err(e) => {
+            log_error!("Warning: invalid local API settings: {}", e);
+            return;
+        }
+    };
+
+    match start(state, pool, &config).await {
+        Ok(addr) => log_info!("Local API listening on http://{}", addr),
+        Err(e) => log_error!("Warning: failed to start local API: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_matches_only_its_hash() {
+        let token = generate_token();
+        let hash = hash_token(&token);
+
+        assert!(token.starts_with(TOKEN_PREFIX));
+        assert_eq!(hash.len(), 64);
+        assert!(token_matches(&hash, &token));
+        assert!(!token_matches(&hash, &generate_token()));
+        assert!(!token_matches(&hash, ""));
+        assert!(!token_matches("", &token));
+    }
+}
@@ -0,0 +1,421 @@
+//! HTTP server for the local API.
+//!
+//! Routes (all GET, all under `/api/v1`, all requiring
+//! `Authorization: Bearer <token>`):
+//!
+//! - `/profiles`
+//! - `/profiles/:profile_id/wallets`
+//! - `/profiles/:profile_id/transactions?limit=&offset=`
+//! - `/profiles/:profile_id/wallets/:wallet_id/transactions?limit=&offset=`
+//! - `/profiles/:profile_id/reports/trial-balance`
+//! - `/profiles/:profile_id/reports/account-balances`
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::SqlitePool;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+use super::{token_matches, LocalApiConfig, RunningServer};
+use crate::api::accounting::{account_balances, trial_balance, AccountBalance, TrialBalanceRow};
+use crate::api::auth::verify_profile_access;
+use crate::api::permissions::Permission;
+use crate::api::persistence::{Profile, StoredTransaction, Wallet};
+use crate::api::profile_scope::{
+    profile_transactions, profile_wallets, profiles_for_user, wallet_transactions,
+};
+use crate::log_error;
+
+/// Page size when `limit` isn't given.
+const DEFAULT_LIMIT: i32 = 100;
+
+/// Largest page a request may ask for.
+const MAX_LIMIT: i32 = 1000;
+
+/// What handlers share.
+struct ApiContext {
+    pool: SqlitePool,
+    user_id: String,
+    token_hash: String,
+}
+
+type Ctx = State<Arc<ApiContext>>;
+
+/// An error response with a JSON `{"error": ...}` body.
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, message)
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// `limit` and `offset` query parameters.
+#[derive(Debug, Deserialize)]
+struct Page {
+    limit: Option<i32>,
+    offset: Option<i32>,
+}
+
+impl Page {
+    fn limit(&self) -> i32 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    fn offset(&self) -> i32 {
+        self.offset.unwrap_or(0).max(0)
+    }
+}
+
+/// Binds to the loopback interface on `config.port` and serves until the
+/// returned server's shutdown signal fires.
+pub(super) async fn start(
+    pool: SqlitePool,
+    config: &LocalApiConfig,
+) -> Result<RunningServer, String> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, config.port))
+        .await
+        .map_err(|e| format!("Failed to bind port {}: {}", config.port, e))?;
+    let addr = listener.local_addr().map_err(|e| e.to_string())?;
+
+    let context = Arc::new(ApiContext {
+        pool,
+        user_id: config.user_id.clone(),
+        token_hash: config.token_hash.clone(),
+    });
+    let (shutdown, shutdown_rx) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let result = axum::serve(listener, router(context))
+            .with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            })
+            .await;
+        if let Err(e) = result {
+            log_error!("Local API server stopped: {}", e);
+        }
+    });
+
+    Ok(RunningServer { addr, shutdown })
+}
+
+fn router(context: Arc<ApiContext>) -> Router {
+    Router::new()
+        .route("/api/v1/profiles", get(list_profiles))
+        .route("/api/v1/profiles/:profile_id/wallets", get(list_wallets))
+        .route(
+            "/api/v1/profiles/:profile_id/transactions",
+            get(list_profile_transactions),
+        )
+        .route(
+            "/api/v1/profiles/:profile_id/wallets/:wallet_id/transactions",
+            get(list_wallet_transactions),
+        )
+        .route(
+            "/api/v1/profiles/:profile_id/reports/trial-balance",
+            get(get_trial_balance),
+        )
+        .route(
+            "/api/v1/profiles/:profile_id/reports/account-balances",
+            get(get_account_balances),
+        )
+        .layer(middleware::from_fn_with_state(
+            context.clone(),
+            require_token,
+        ))
+        .with_state(context)
+}
+
+/// Rejects requests without the configured bearer token.
+async fn require_token(ctx: Ctx, request: Request, next: Next) -> Response {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match token {
+        Some(token) if token_matches(&ctx.token_hash, token) => next.run(request).await,
+        _ => ApiError(StatusCode::UNAUTHORIZED, "Invalid or missing token".into()).into_response(),
+    }
+}
+
+/// Checks the token's user can read `profile_id`. Profiles they can't see
+/// are reported as missing.
+async fn check_profile(ctx: &ApiContext, profile_id: &str) -> Result<(), ApiError> {
+    verify_profile_access(
+        &ctx.pool,
+        &ctx.user_id,
+        profile_id,
+        Permission::ViewTransactions,
+    )
+    .await
+    .map_err(|_| {
+        ApiError(
+            StatusCode::NOT_FOUND,
+            format!("Profile not found: {}", profile_id),
+        )
+    })
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+async fn list_profiles(ctx: Ctx) -> ApiResult<Vec<Profile>> {
+    Ok(Json(profiles_for_user(&ctx.pool, &ctx.user_id).await?))
+}
+
+async fn list_wallets(ctx: Ctx, Path(profile_id): Path<String>) -> ApiResult<Vec<Wallet>> {
+    check_profile(&ctx, &profile_id).await?;
+    Ok(Json(profile_wallets(&ctx.pool, &profile_id).await?))
+}
+
+async fn list_profile_transactions(
+    ctx: Ctx,
+    Path(profile_id): Path<String>,
+    Query(page): Query<Page>,
+) -> ApiResult<Vec<StoredTransaction>> {
+    check_profile(&ctx, &profile_id).await?;
+    Ok(Json(
+        profile_transactions(&ctx.pool, &profile_id, page.limit(), page.offset()).await?,
+    ))
+}
+
+async fn list_wallet_transactions(
+    ctx: Ctx,
+    Path((profile_id, wallet_id)): Path<(String, String)>,
+    Query(page): Query<Page>,
+) -> ApiResult<Vec<StoredTransaction>> {
+    check_profile(&ctx, &profile_id).await?;
+    Ok(Json(
+        wallet_transactions(
+            &ctx.pool,
+            &profile_id,
+            &wallet_id,
+            page.limit(),
+            page.offset(),
+        )
+        .await?,
+    ))
+}
+
+async fn get_trial_balance(
+    ctx: Ctx,
+    Path(profile_id): Path<String>,
+) -> ApiResult<Vec<TrialBalanceRow>> {
+    check_profile(&ctx, &profile_id).await?;
+    Ok(Json(trial_balance(&ctx.pool, &profile_id).await?))
+}
+
+async fn get_account_balances(
+    ctx: Ctx,
+    Path(profile_id): Path<String>,
+) -> ApiResult<Vec<AccountBalance>> {
+    check_profile(&ctx, &profile_id).await?;
+    Ok(Json(account_balances(&ctx.pool, &profile_id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local_api::{generate_token, hash_token};
+    use chrono::Utc;
+    use serde_json::Value;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for ddl in [
+            r#"
+            CREATE TABLE profiles (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                avatar_url TEXT,
+                created_at DATETIME NOT NULL,
+                updated_at DATETIME NOT NULL
+            )
+            "#,
+            r#"
+            CREATE TABLE user_profile_roles (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                profile_id TEXT NOT NULL,
+                role TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'active',
+                UNIQUE(user_id, profile_id)
+            )
+            "#,
+        ] {
+            sqlx::query(ddl).execute(&pool).await.unwrap();
+        }
+
+        let now = Utc::now();
+        for (profile, user) in [("alpha", "alice"), ("beta", "bob")] {
+            sqlx::query("INSERT INTO profiles VALUES (?, ?, NULL, ?, ?)")
+                .bind(profile)
+                .bind(profile)
+                .bind(now)
+                .bind(now)
+                .execute(&pool)
+                .await
+                .unwrap();
+            sqlx::query("INSERT INTO user_profile_roles VALUES (?, ?, ?, 'owner', 'active')")
+                .bind(format!("r-{}", profile))
+                .bind(user)
+                .bind(profile)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        pool
+    }
+
+    async fn start_for(user_id: &str) -> (RunningServer, String) {
+        let token = generate_token();
+        let config = LocalApiConfig {
+            enabled: true,
+            port: 0,
+            user_id: user_id.to_string(),
+            token_hash: hash_token(&token),
+        };
+        let server = start(setup_test_db().await, &config).await.unwrap();
+        (server, token)
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_token_are_rejected() {
+        let (server, token) = start_for("alice").await;
+        let url = format!("http://{}/api/v1/profiles", server.addr);
+        let client = reqwest::Client::new();
+
+        let missing = client.get(&url).send().await.unwrap();
+        assert_eq!(missing.status(), 401);
+
+        let wrong = client
+            .get(&url)
+            .bearer_auth(format!("{}x", token))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(wrong.status(), 401);
+    }
+
+    #[tokio::test]
+    async fn test_profiles_are_scoped_to_token_user() {
+        let (server, token) = start_for("alice").await;
+        let client = reqwest::Client::new();
+
+        let profiles: Value = client
+            .get(format!("http://{}/api/v1/profiles", server.addr))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let ids: Vec<&str> = profiles
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["alpha"]);
+
+        let other = client
+            .get(format!(
+                "http://{}/api/v1/profiles/beta/wallets",
+                server.addr
+            ))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(other.status(), 404);
+        let body: Value = other.json().await.unwrap();
+        assert_eq!(body["error"], "Profile not found: beta");
+    }
+
+    #[tokio::test]
+    async fn test_ledger_reports_are_scoped_to_token_user() {
+        let (server, token) = start_for("alice").await;
+        let client = reqwest::Client::new();
+
+        for report in ["trial-balance", "account-balances"] {
+            let response = client
+                .get(format!(
+                    "http://{}/api/v1/profiles/beta/reports/{}",
+                    server.addr, report
+                ))
+                .bearer_auth(&token)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 404);
+        }
+
+        let unscoped = client
+            .get(format!(
+                "http://{}/api/v1/reports/trial-balance",
+                server.addr
+            ))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(unscoped.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_only_get_is_served() {
+        let (server, token) = start_for("alice").await;
+        let response = reqwest::Client::new()
+            .post(format!("http://{}/api/v1/profiles", server.addr))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 405);
+    }
+
+    #[test]
+    fn test_page_limits_are_clamped() {
+        let page = Page {
+            limit: Some(50_000),
+            offset: Some(-3),
+        };
+        assert_eq!(page.limit(), MAX_LIMIT);
+        assert_eq!(page.offset(), 0);
+
+        let page = Page {
+            limit: None,
+            offset: None,
+        };
+        assert_eq!(page.limit(), DEFAULT_LIMIT);
+        assert_eq!(page.offset(), 0);
+    }
+}
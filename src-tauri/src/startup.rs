@@ -0,0 +1,175 @@
+//! App startup.
+//!
+//! Opening the database and starting the services that depend on it run as
+//! a background task once the window is up, so a slow disk or a long
+//! migration doesn't hold up the first frame. Progress is emitted as
+//! [`STARTUP_EVENT`]; the frontend shows a loading screen until the app is
+//! ready, and can ask [`get_startup_status`] if it missed an event. Commands
+//! that need the database fail until then.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::api::persistence::DatabaseState;
+use crate::core::auth_state::AuthState;
+use crate::core::email;
+use crate::storage::commands::StorageState;
+use crate::{api, fetchers, jobs, local_api, log_error, log_info};
+use crate::{DATABASE_FILE, ENV_RESEND_API_KEY};
+
+/// Event emitted as startup progresses.
+pub const STARTUP_EVENT: &str = "startup:progress";
+
+/// How far startup has got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupStage {
+    /// The startup task hasn't begun.
+    #[default]
+    Starting,
+    /// Opening the database and running migrations.
+    Database,
+    /// Loading settings and starting background services.
+    Services,
+    /// Everything is available.
+    Ready,
+    /// Startup failed; see the error.
+    Failed,
+}
+
+/// Startup progress reported to the frontend.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupStatus {
+    /// Current stage.
+    pub stage: StartupStage,
+    /// Why startup failed, if it did.
+    pub error: Option<String>,
+}
+
+/// Shared state holding the latest startup status.
+#[derive(Default)]
+pub struct StartupState(Mutex<StartupStatus>);
+
+/// Records and emits a startup status.
+fn report(app: &AppHandle, stage: StartupStage, error: Option<String>) {
+    let status = StartupStatus { stage, error };
+    *app.state::<StartupState>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = status.clone();
+    if let Err(e) = app.emit(STARTUP_EVENT, status) {
+        log_error!("Failed to emit startup progress: {}", e);
+    }
+}
+
+/// Starts the startup task.
+pub fn spawn(app: AppHandle, app_data_dir: PathBuf) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = initialize(&app, &app_data_dir).await {
+            log_error!("Startup failed: {}", e);
+            report(&app, StartupStage::Failed, Some(e));
+        }
+    });
+}
+
+/// Opens the database, loads settings that live in it, and starts the job
+/// queue and background loops.
+async fn initialize(app: &AppHandle, app_data_dir: &Path) -> Result<(), String> {
+    report(app, StartupStage::Database, None);
+
+    let db_path = app_data_dir.join(DATABASE_FILE);
+    let db_url = format!("sqlite:{}?mode=rwc", db_path.display());
+    let db_state = DatabaseState::new(&db_url)
+        .await
+        .map_err(|e| format!("Failed to initialize database: {}", e))?;
+    app.manage(db_state);
+
+    // Storage state uses its own pool on the same file
+    let storage_pool = sqlx::SqlitePool::connect(&db_url)
+        .await
+        .map_err(|e| format!("Failed to initialize storage pool: {}", e))?;
+    app.manage(StorageState::new(storage_pool));
+
+    report(app, StartupStage::Services, None);
+    let pool = app.state::<DatabaseState>().pool.clone();
+
+    if let Err(e) = api::auth::load_password_hash_settings(&pool).await {
+        log_error!(
+            "Warning: invalid password hash settings, using defaults: {}",
+            e
+        );
+    }
+    if let Err(e) = api::jwt_keys::init_jwt_keys(&pool, &app.state::<AuthState>()).await {
+        log_error!(
+            "Warning: JWT keys could not be saved, sessions end on exit: {}",
+            e
+        );
+    }
+
+    // Register the SMTP provider from settings, used when Resend is
+    // unavailable (self-hosted installs) or fails
+    match api::email_settings::load_smtp_provider(&pool).await {
+        Ok(true) => log_info!("SMTP email provider initialized"),
+        Ok(false) => {}
+        Err(e) => log_error!("Warning: invalid SMTP settings: {}", e),
+    }
+    if !email::is_configured() {
+        log_error!(
+            "Warning: {} not set and no SMTP server configured, email sending disabled",
+            ENV_RESEND_API_KEY
+        );
+    }
+
+    // Start the job queue, restoring jobs left from the last run
+    let job_queue = jobs::queue::JobQueue::start(
+        app.clone(),
+        pool.clone(),
+        app.state::<jobs::JobRegistryState>().inner().clone(),
+    );
+    app.manage(job_queue);
+
+    // Start the local API if the user turned it on
+    local_api::start_if_enabled(&app.state::<local_api::LocalApiState>(), &pool).await;
+
+    // Start background monitoring of watched addresses
+    api::address_watch::spawn_watch_loop(app.clone());
+
+    // Start background checks of open invoices for payment
+    api::invoices::spawn_invoice_loop(app.clone());
+
+    // Start background checks of pending Bitcoin transactions
+    api::pending_bitcoin::spawn_pending_loop(app.clone());
+
+    // Start background mempool checks of watched Bitcoin addresses
+    api::mempool_watch::spawn_mempool_loop(app.clone());
+
+    // Start background generation of scheduled reports
+    api::report_schedules::spawn_report_loop(app.clone());
+
+    // Start periodic saving of API usage counts
+    fetchers::usage::spawn_usage_flush_loop(app.clone());
+
+    // Start background connectivity checks for offline mode
+    fetchers::offline::spawn_connectivity_loop(app.clone());
+
+    // Start daily pruning of old raw transaction payloads
+    api::data_retention::spawn_retention_loop(app.clone());
+    api::jwt_keys::spawn_key_rotation_loop(app.clone());
+    api::auth::spawn_email_change_loop(app.clone());
+
+    report(app, StartupStage::Ready, None);
+    log_info!("Startup complete");
+    Ok(())
+}
+
+/// Get how far startup has got
+///
+/// For a frontend that loaded after the last [`STARTUP_EVENT`].
+#[tauri::command]
+pub fn get_startup_status(state: State<'_, StartupState>) -> StartupStatus {
+    state.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
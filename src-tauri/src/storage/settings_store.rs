@@ -9,6 +9,20 @@ use sqlx::SqlitePool;
 
 use super::Setting;
 
+/// Key prefixes of settings that only their own commands may read or write.
+const RESERVED_PREFIXES: &[&str] = &["local_api.", "auth.", "email.", "cloud_sync."];
+
+/// Settings that only their own commands may read or write.
+const RESERVED_KEYS: &[&str] = &["email_change_settings", "data_retention", "jwt_keyring"];
+
+/// Whether `key` holds server, credential, or security configuration.
+///
+/// The generic settings commands refuse these keys so that the checks in the
+/// commands owning them can't be bypassed.
+pub fn is_reserved_setting(key: &str) -> bool {
+    RESERVED_KEYS.contains(&key) || RESERVED_PREFIXES.iter().any(|p| key.starts_with(p))
+}
+
 /// Gets a setting value by key.
 ///
 /// # Arguments
@@ -34,7 +48,6 @@ pub async fn get_setting(pool: &SqlitePool, key: &str) -> Result<Option<String>>
 ///
 /// # Returns
 /// The deserialized value if found and valid, None otherwise
-#[allow(dead_code)]
 pub async fn get_setting_json<T: DeserializeOwned>(
     pool: &SqlitePool,
     key: &str,
@@ -83,7 +96,6 @@ pub async fn set_setting(pool: &SqlitePool, key: &str, value: &str) -> Result<()
 /// * `pool` - Database connection pool
 /// * `key` - Setting key
 /// * `value` - Value to serialize and store
-#[allow(dead_code)]
 pub async fn set_setting_json<T: Serialize>(pool: &SqlitePool, key: &str, value: &T) -> Result<()> {
     let json_str = serde_json::to_string(value)?;
     set_setting(pool, key, &json_str).await
@@ -207,6 +219,7 @@ pub async fn delete_settings(pool: &SqlitePool, keys: &[&str]) -> Result<u64> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::auth_helpers::ARGON2_PARAMS_SETTING;
     use serde::{Deserialize, Serialize};
     use sqlx::sqlite::SqlitePoolOptions;
 
@@ -263,6 +276,19 @@ mod tests {
         assert_eq!(fetched, Some(config));
     }
 
+    #[test]
+    fn test_is_reserved_setting() {
+        assert!(is_reserved_setting("local_api.config"));
+        assert!(is_reserved_setting("email.smtp"));
+        assert!(is_reserved_setting("cloud_sync.config"));
+        assert!(is_reserved_setting("data_retention"));
+        assert!(is_reserved_setting("jwt_keyring"));
+        assert!(is_reserved_setting(ARGON2_PARAMS_SETTING));
+        assert!(is_reserved_setting("email_change_settings"));
+        assert!(!is_reserved_setting("theme"));
+        assert!(!is_reserved_setting("setup_complete"));
+    }
+
     #[tokio::test]
     async fn test_delete_setting() {
         let pool = setup_test_db().await;
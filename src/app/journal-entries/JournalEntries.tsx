@@ -12,6 +12,8 @@ import {
   ChevronRight,
 } from 'lucide-react'
 import { useNavBadges } from '../../contexts/NavBadgeContext'
+import { useProfile } from '../../contexts/ProfileContext'
+import { requireAccessToken } from '../../services/auth/tokenStorage'
 import type {
   JournalEntryWithLines,
   GLAccount,
@@ -92,6 +94,7 @@ const JournalEntries: React.FC = () => {
     JournalEntryWithLines | undefined
   >()
   const { refreshCounts } = useNavBadges()
+  const { currentProfile } = useProfile()
 
   const accountMap = useMemo(() => {
     const m = new Map<number, GLAccount>()
@@ -100,12 +103,23 @@ const JournalEntries: React.FC = () => {
   }, [accounts])
 
   const fetchEntries = useCallback(async () => {
+    if (!currentProfile) {
+      setEntries([])
+      setLoading(false)
+      return
+    }
     setLoading(true)
     try {
       const statusFilter = filterParam === 'all' ? null : filterParam
       const result = await invoke<JournalEntryWithLines[]>(
         'get_journal_entries',
-        { status_filter: statusFilter, limit: 200, offset: 0 }
+        {
+          token: requireAccessToken(),
+          profileId: currentProfile.id,
+          statusFilter,
+          limit: 200,
+          offset: 0,
+        }
       )
       setEntries(result)
     } catch (err) {
@@ -113,11 +127,13 @@ const JournalEntries: React.FC = () => {
     } finally {
       setLoading(false)
     }
-  }, [filterParam])
+  }, [filterParam, currentProfile])
 
   const fetchAccounts = useCallback(async () => {
     try {
-      const result = await invoke<GLAccount[]>('get_chart_of_accounts')
+      const result = await invoke<GLAccount[]>('get_chart_of_accounts', {
+        token: requireAccessToken(),
+      })
       setAccounts(result)
     } catch (err) {
       console.error('Failed to fetch accounts:', err)
@@ -177,7 +193,7 @@ const JournalEntries: React.FC = () => {
     async (id: number) => {
       setActionError(null)
       try {
-        await invoke('post_journal_entry', { id })
+        await invoke('post_journal_entry', { token: requireAccessToken(), id })
         fetchEntries()
         refreshCounts()
       } catch (err) {
@@ -192,7 +208,7 @@ const JournalEntries: React.FC = () => {
   const handleVoid = useCallback(
     async (id: number) => {
       try {
-        await invoke('void_journal_entry', { id })
+        await invoke('void_journal_entry', { token: requireAccessToken(), id })
         fetchEntries()
         refreshCounts()
       } catch (err) {
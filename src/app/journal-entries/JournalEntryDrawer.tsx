@@ -1,6 +1,8 @@
 import React, { useState, useCallback, useMemo } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import { X, Plus, Trash2 } from 'lucide-react'
+import { useProfile } from '../../contexts/ProfileContext'
+import { requireAccessToken } from '../../services/auth/tokenStorage'
 import type {
   GLAccount,
   JournalEntryWithLines,
@@ -43,6 +45,7 @@ const JournalEntryDrawer: React.FC<JournalEntryDrawerProps> = ({
   onClose,
   onSaved,
 }) => {
+  const { currentProfile } = useProfile()
   const isView = Boolean(entry)
   const [entryDate, setEntryDate] = useState(
     entry?.entryDate
@@ -120,65 +123,60 @@ const JournalEntryDrawer: React.FC<JournalEntryDrawerProps> = ({
     [lines.length]
   )
 
+  /** Saves the form as a draft entry in the current profile */
+  const createEntry = useCallback(async () => {
+    if (!currentProfile) throw new Error('No profile selected')
+    const input = {
+      profileId: currentProfile.id,
+      entryDate,
+      description,
+      referenceNumber: referenceNumber || null,
+      rawTransactionId: null,
+      lines: lines
+        .filter(l => l.glAccountId !== '')
+        .map(l => ({
+          glAccountId: l.glAccountId as number,
+          tokenId: null,
+          debitAmount: parseFloat(l.debitAmount) || 0,
+          creditAmount: parseFloat(l.creditAmount) || 0,
+          description: l.description || null,
+        })),
+    }
+    return invoke<JournalEntryWithLines>('create_journal_entry', {
+      token: requireAccessToken(),
+      input,
+    })
+  }, [currentProfile, entryDate, description, referenceNumber, lines])
+
   const handleSaveDraft = useCallback(async () => {
     setError(null)
     setSaving(true)
     try {
-      const input = {
-        entry_date: entryDate,
-        description,
-        reference_number: referenceNumber || null,
-        raw_transaction_id: null,
-        lines: lines
-          .filter(l => l.glAccountId !== '')
-          .map(l => ({
-            gl_account_id: l.glAccountId as number,
-            token_id: null,
-            debit_amount: parseFloat(l.debitAmount) || 0,
-            credit_amount: parseFloat(l.creditAmount) || 0,
-            description: l.description || null,
-          })),
-      }
-      await invoke('create_journal_entry', { input })
+      await createEntry()
       onSaved()
     } catch (err) {
       setError(typeof err === 'string' ? err : 'Failed to save entry')
     } finally {
       setSaving(false)
     }
-  }, [entryDate, description, referenceNumber, lines, onSaved])
+  }, [createEntry, onSaved])
 
   const handlePostEntry = useCallback(async () => {
     setError(null)
     setSaving(true)
     try {
-      const input = {
-        entry_date: entryDate,
-        description,
-        reference_number: referenceNumber || null,
-        raw_transaction_id: null,
-        lines: lines
-          .filter(l => l.glAccountId !== '')
-          .map(l => ({
-            gl_account_id: l.glAccountId as number,
-            token_id: null,
-            debit_amount: parseFloat(l.debitAmount) || 0,
-            credit_amount: parseFloat(l.creditAmount) || 0,
-            description: l.description || null,
-          })),
-      }
-      const created = await invoke<JournalEntryWithLines>(
-        'create_journal_entry',
-        { input }
-      )
-      await invoke('post_journal_entry', { id: created.id })
+      const created = await createEntry()
+      await invoke('post_journal_entry', {
+        token: requireAccessToken(),
+        id: created.id,
+      })
       onSaved()
     } catch (err) {
       setError(typeof err === 'string' ? err : 'Failed to post entry')
     } finally {
       setSaving(false)
     }
-  }, [entryDate, description, referenceNumber, lines, onSaved])
+  }, [createEntry, onSaved])
 
   return ( // skipcq: JS-0415 — drawer layout requires nested containers
     <div className="fixed inset-0 z-50 flex justify-end">
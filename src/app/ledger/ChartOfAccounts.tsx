@@ -8,6 +8,7 @@ import {
   Scale,
 } from 'lucide-react'
 import type { GLAccount } from '../../types/database'
+import { requireAccessToken } from '../../services/auth/tokenStorage'
 
 const accountTypeOrder = ['Asset', 'Liability', 'Equity', 'Income', 'Expense']
 
@@ -150,6 +151,7 @@ const AddAccountForm: React.FC<AddAccountFormProps> = ({
     setSaving(true)
     try {
       await invoke('create_gl_account', {
+        token: requireAccessToken(),
         input: {
           accountNumber,
           accountName,
@@ -294,7 +296,9 @@ const ChartOfAccounts: React.FC = () => {
   const fetchAccounts = useCallback(async () => {
     setLoading(true)
     try {
-      const result = await invoke<GLAccount[]>('get_chart_of_accounts')
+      const result = await invoke<GLAccount[]>('get_chart_of_accounts', {
+        token: requireAccessToken(),
+      })
       setAccounts(result)
     } catch (err) {
       console.error('Failed to fetch chart of accounts:', err)
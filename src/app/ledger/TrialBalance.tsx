@@ -1,6 +1,8 @@
 import React, { useState, useEffect, useCallback, useMemo } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import { Scale } from 'lucide-react'
+import { useProfile } from '../../contexts/ProfileContext'
+import { requireAccessToken } from '../../services/auth/tokenStorage'
 
 interface TrialBalanceRow {
   accountNumber: string
@@ -12,20 +14,29 @@ interface TrialBalanceRow {
 
 /** Trial Balance page */
 const TrialBalance: React.FC = () => {
+  const { currentProfile } = useProfile()
   const [rows, setRows] = useState<TrialBalanceRow[]>([])
   const [loading, setLoading] = useState(true)
 
   const fetchTrialBalance = useCallback(async () => {
+    if (!currentProfile) {
+      setRows([])
+      setLoading(false)
+      return
+    }
     setLoading(true)
     try {
-      const result = await invoke<TrialBalanceRow[]>('get_trial_balance')
+      const result = await invoke<TrialBalanceRow[]>('get_trial_balance', {
+        token: requireAccessToken(),
+        profileId: currentProfile.id,
+      })
       setRows(result)
     } catch (err) {
       console.error('Failed to fetch trial balance:', err)
     } finally {
       setLoading(false)
     }
-  }, [])
+  }, [currentProfile])
 
   useEffect(() => {
     fetchTrialBalance()
@@ -0,0 +1,89 @@
+/**
+ * Startup Gate
+ * Shows a loading screen until the backend has opened the database and
+ * started its services
+ */
+
+import React, { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { isTauriAvailable } from '../utils/tauri'
+
+type StartupStage = 'starting' | 'database' | 'services' | 'ready' | 'failed'
+
+interface StartupStatus {
+  stage: StartupStage
+  error?: string | null
+}
+
+const STARTUP_EVENT = 'startup:progress'
+
+const STAGE_LABELS: Record<StartupStage, string> = {
+  starting: 'Starting...',
+  database: 'Opening database...',
+  services: 'Starting services...',
+  ready: 'Ready',
+  failed: 'Startup failed',
+}
+
+interface StartupGateProps {
+  children: React.ReactNode
+}
+
+export const StartupGate: React.FC<StartupGateProps> = ({ children }) => {
+  const [status, setStatus] = useState<StartupStatus>(() => ({
+    stage: isTauriAvailable() ? 'starting' : 'ready',
+  }))
+
+  useEffect(() => {
+    if (!isTauriAvailable()) {
+      return undefined
+    }
+
+    let cancelled = false
+    const unlisten = listen<StartupStatus>(STARTUP_EVENT, event => {
+      if (!cancelled) {
+        setStatus(event.payload)
+      }
+    })
+    // The backend may have finished before the listener was registered
+    invoke<StartupStatus>('get_startup_status')
+      .then(current => {
+        if (!cancelled) {
+          setStatus(previous =>
+            previous.stage === 'starting' ? current : previous
+          )
+        }
+      })
+      .catch(() => {
+        // Keep waiting for the event
+      })
+
+    return () => {
+      cancelled = true
+      unlisten.then(fn => fn()).catch(() => {})
+    }
+  }, [])
+
+  if (status.stage === 'ready') {
+    return <>{children}</>
+  }
+
+  return (
+    <div className="flex h-screen w-full items-center justify-center bg-background">
+      <div className="flex flex-col items-center gap-4 text-center">
+        {status.stage !== 'failed' && (
+          <div className="h-8 w-8 animate-spin rounded-full border-4 border-primary border-t-transparent" />
+        )}
+        <p className="text-sm text-muted-foreground">
+          {STAGE_LABELS[status.stage]}
+        </p>
+        {status.error && (
+          <p className="max-w-md text-sm text-destructive">{status.error}</p>
+        )}
+      </div>
+    </div>
+  )
+}
+
+export default StartupGate
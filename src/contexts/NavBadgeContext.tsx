@@ -1,5 +1,7 @@
 import React, { createContext, useContext, useState, useCallback, useRef, useEffect } from 'react'
 import { invoke } from '@tauri-apps/api/core'
+import { useProfile } from './ProfileContext'
+import { getAccessToken } from '../services/auth/tokenStorage'
 
 interface NavBadgeCounts {
   unclassifiedTransactions: number
@@ -31,11 +33,18 @@ export const NavBadgeProvider: React.FC<{ children: React.ReactNode }> = ({
     draftJournalEntries: 0,
   })
   const mountedRef = useRef(true)
+  const { currentProfile } = useProfile()
 
   const refreshCounts = useCallback(() => {
+    const token = getAccessToken()
+    if (!currentProfile || !token) {
+      setCounts({ unclassifiedTransactions: 0, draftJournalEntries: 0 })
+      return
+    }
+    const args = { token, profileId: currentProfile.id }
     Promise.all([
-      invoke<number>('get_unclassified_transaction_count'),
-      invoke<number>('get_draft_journal_entry_count'),
+      invoke<number>('get_unclassified_transaction_count', args),
+      invoke<number>('get_draft_journal_entry_count', args),
     ])
       .then(([unclassified, drafts]) => {
         if (mountedRef.current) {
@@ -48,7 +57,7 @@ export const NavBadgeProvider: React.FC<{ children: React.ReactNode }> = ({
       .catch(err => {
         console.error('Failed to fetch nav badge counts:', err)
       })
-  }, [])
+  }, [currentProfile])
 
   useEffect(() => {
     mountedRef.current = true
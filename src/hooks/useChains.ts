@@ -19,6 +19,7 @@ import {
   fetchAllBalances as apiFetchAllBalances,
   validateAddress as apiValidateAddress,
 } from '../api/chains'
+import { isApiError } from '../types/errors'
 
 /**
  * Hook state for async operations.
@@ -46,6 +47,9 @@ function getErrorMessage(error: unknown): string {
   if (typeof error === 'string') {
     return error
   }
+  if (isApiError(error)) {
+    return error.message
+  }
   return 'An unknown error occurred'
 }
 
@@ -7,6 +7,7 @@ import { ThemeProvider } from './contexts/ThemeContext'
 import { CurrencyProvider } from './contexts/CurrencyContext'
 import { OrganizationProvider } from './contexts/OrganizationContext'
 import { AuthProvider } from './contexts/AuthContext'
+import { StartupGate } from './components/StartupGate'
 
 const Providers: React.FC<{ children: React.ReactNode }> = ({ children }) => (
   <AuthProvider>
@@ -20,8 +21,10 @@ const Providers: React.FC<{ children: React.ReactNode }> = ({ children }) => (
 
 ReactDOM.createRoot(document.getElementById('root') as HTMLElement).render(
   <React.StrictMode>
-    <Providers>
-      <App />
-    </Providers>
+    <StartupGate>
+      <Providers>
+        <App />
+      </Providers>
+    </StartupGate>
   </React.StrictMode>
 )
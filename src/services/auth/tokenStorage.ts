@@ -29,6 +29,18 @@ export function getAccessToken(): string | null {
   return localStorage.getItem(TOKEN_KEYS.ACCESS_TOKEN)
 }
 
+/**
+ * Access token for commands that check the caller's profile access.
+ * Throws when no one is signed in.
+ */
+export function requireAccessToken(): string {
+  const token = getAccessToken()
+  if (!token) {
+    throw new Error('Not signed in')
+  }
+  return token
+}
+
 export function getRefreshToken(): string | null {
   return localStorage.getItem(TOKEN_KEYS.REFRESH_TOKEN)
 }
@@ -403,6 +403,7 @@ class IndexedDBPersistenceService implements PersistenceService {
   }
 
   async getTransactions(
+    _profileId: string,
     walletId: string,
     options?: PaginationOptions
   ): Promise<StoredTransaction[]> {
@@ -437,7 +438,7 @@ class IndexedDBPersistenceService implements PersistenceService {
     const allTransactions: StoredTransaction[] = []
 
     for (const wallet of wallets) {
-      const transactions = await this.getTransactions(wallet.id, {
+      const transactions = await this.getTransactions(profileId, wallet.id, {
         limit: 10000,
       })
       allTransactions.push(...transactions)
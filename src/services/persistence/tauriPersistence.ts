@@ -21,6 +21,7 @@ import type {
   AddressMatch,
   KnownAddress,
 } from './types'
+import { requireAccessToken } from '../auth/tokenStorage'
 
 /**
  * Tauri persistence implementation using plain object
@@ -29,36 +30,52 @@ import type {
 export const tauriPersistence: PersistenceService = {
   // Profile Operations
   createProfile: (name: string): Promise<Profile> => {
-    return invoke<Profile>('create_profile', { name })
+    return invoke<Profile>('create_profile', {
+      token: requireAccessToken(),
+      name,
+    })
   },
 
   getProfiles: (): Promise<Profile[]> => {
-    return invoke<Profile[]>('get_profiles')
+    return invoke<Profile[]>('get_profiles', { token: requireAccessToken() })
   },
 
   updateProfile: (id: string, name: string): Promise<Profile> => {
-    return invoke<Profile>('update_profile', { id, name })
+    return invoke<Profile>('update_profile', {
+      token: requireAccessToken(),
+      id,
+      name,
+    })
   },
 
   deleteProfile: (id: string): Promise<void> => {
-    return invoke('delete_profile', { id })
+    return invoke('delete_profile', { token: requireAccessToken(), id })
   },
 
   // Wallet Operations
   saveWallet: (wallet: WalletInput): Promise<Wallet> => {
-    return invoke<Wallet>('save_wallet', { wallet })
+    return invoke<Wallet>('save_wallet', {
+      token: requireAccessToken(),
+      wallet,
+    })
   },
 
   getWallets: (profileId: string): Promise<Wallet[]> => {
-    return invoke<Wallet[]>('get_wallets', { profileId })
+    return invoke<Wallet[]>('get_wallets', {
+      token: requireAccessToken(),
+      profileId,
+    })
   },
 
   getWalletById: (id: string): Promise<Wallet | null> => {
-    return invoke<Wallet | null>('get_wallet_by_id', { id })
+    return invoke<Wallet | null>('get_wallet_by_id', {
+      token: requireAccessToken(),
+      id,
+    })
   },
 
   deleteWallet: (id: string): Promise<void> => {
-    return invoke('delete_wallet', { id })
+    return invoke('delete_wallet', { token: requireAccessToken(), id })
   },
 
   // Transaction Operations
@@ -66,14 +83,21 @@ export const tauriPersistence: PersistenceService = {
     walletId: string,
     transactions: TransactionInput[]
   ): Promise<number> => {
-    return invoke<number>('save_transactions', { walletId, transactions })
+    return invoke<number>('save_transactions', {
+      token: requireAccessToken(),
+      walletId,
+      transactions,
+    })
   },
 
   getTransactions: (
+    profileId: string,
     walletId: string,
     options?: PaginationOptions
   ): Promise<StoredTransaction[]> => {
     return invoke<StoredTransaction[]>('get_transactions', {
+      token: requireAccessToken(),
+      profileId,
       walletId,
       limit: options?.limit ?? null,
       offset: options?.offset ?? null,
@@ -85,6 +109,7 @@ export const tauriPersistence: PersistenceService = {
     options?: PaginationOptions
   ): Promise<StoredTransaction[]> => {
     return invoke<StoredTransaction[]>('get_all_transactions', {
+      token: requireAccessToken(),
       profileId,
       limit: options?.limit ?? null,
       offset: options?.offset ?? null,
@@ -92,7 +117,10 @@ export const tauriPersistence: PersistenceService = {
   },
 
   deleteTransactions: (walletId: string): Promise<number> => {
-    return invoke<number>('delete_transactions', { walletId })
+    return invoke<number>('delete_transactions', {
+      token: requireAccessToken(),
+      walletId,
+    })
   },
 
   // Settings Operations
@@ -114,7 +142,10 @@ export const tauriPersistence: PersistenceService = {
 
   // Entity operations
   createEntity: (entity: EntityInput): Promise<Entity> => {
-    return invoke<Entity>('create_entity', { entity })
+    return invoke<Entity>('create_entity', {
+      token: requireAccessToken(),
+      entity,
+    })
   },
 
   getEntities: (
@@ -122,6 +153,7 @@ export const tauriPersistence: PersistenceService = {
     filter?: EntityFilter
   ): Promise<Entity[]> => {
     return invoke<Entity[]>('get_entities', {
+      token: requireAccessToken(),
       profileId,
       entityType: filter?.entity_type ?? null,
       isActive: filter?.is_active ?? null,
@@ -129,15 +161,22 @@ export const tauriPersistence: PersistenceService = {
   },
 
   getEntityById: (id: string): Promise<Entity | null> => {
-    return invoke<Entity | null>('get_entity_by_id', { id })
+    return invoke<Entity | null>('get_entity_by_id', {
+      token: requireAccessToken(),
+      id,
+    })
   },
 
   updateEntity: (id: string, update: EntityUpdate): Promise<Entity> => {
-    return invoke<Entity>('update_entity', { id, update })
+    return invoke<Entity>('update_entity', {
+      token: requireAccessToken(),
+      id,
+      update,
+    })
   },
 
   deleteEntity: (id: string): Promise<void> => {
-    return invoke('delete_entity', { id })
+    return invoke('delete_entity', { token: requireAccessToken(), id })
   },
 
   searchEntities: (
@@ -146,6 +185,7 @@ export const tauriPersistence: PersistenceService = {
     limit?: number
   ): Promise<Entity[]> => {
     return invoke<Entity[]>('search_entities', {
+      token: requireAccessToken(),
       profileId,
       query,
       limit: limit ?? null,
@@ -158,6 +198,7 @@ export const tauriPersistence: PersistenceService = {
     chain?: string
   ): Promise<Entity | null> => {
     return invoke<Entity | null>('find_entity_by_address', {
+      token: requireAccessToken(),
       profileId,
       address,
       chain: chain ?? null,
@@ -168,15 +209,21 @@ export const tauriPersistence: PersistenceService = {
   addEntityAddress: (
     addressInput: EntityAddressInput
   ): Promise<EntityAddress> => {
-    return invoke<EntityAddress>('add_entity_address', { addressInput })
+    return invoke<EntityAddress>('add_entity_address', {
+      token: requireAccessToken(),
+      addressInput,
+    })
   },
 
   getEntityAddresses: (entityId: string): Promise<EntityAddress[]> => {
-    return invoke<EntityAddress[]>('get_entity_addresses', { entityId })
+    return invoke<EntityAddress[]>('get_entity_addresses', {
+      token: requireAccessToken(),
+      entityId,
+    })
   },
 
   deleteEntityAddress: (id: string): Promise<void> => {
-    return invoke('delete_entity_address', { id })
+    return invoke('delete_entity_address', { token: requireAccessToken(), id })
   },
 
   // Address detection operations
@@ -186,6 +233,7 @@ export const tauriPersistence: PersistenceService = {
     chain: string
   ): Promise<AddressMatch | null> => {
     return invoke<AddressMatch | null>('lookup_address', {
+      token: requireAccessToken(),
       profileId,
       address,
       chain,
@@ -197,6 +245,7 @@ export const tauriPersistence: PersistenceService = {
     addresses: Array<[string, string]>
   ): Promise<AddressMatch[]> => {
     return invoke<AddressMatch[]>('batch_lookup_addresses', {
+      token: requireAccessToken(),
       profileId,
       addresses,
     })
@@ -207,6 +256,7 @@ export const tauriPersistence: PersistenceService = {
     entityType?: string
   ): Promise<KnownAddress[]> => {
     return invoke<KnownAddress[]>('get_known_addresses', {
+      token: requireAccessToken(),
       chain: chain ?? null,
       entityType: entityType ?? null,
     })
@@ -218,6 +268,7 @@ export const tauriPersistence: PersistenceService = {
     chain: string
   ): Promise<Entity> => {
     return invoke<Entity>('create_entity_from_known', {
+      token: requireAccessToken(),
       profileId,
       address,
       chain,
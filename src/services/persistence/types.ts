@@ -242,6 +242,7 @@ export interface PersistenceService {
     transactions: TransactionInput[]
   ): Promise<number>
   getTransactions(
+    profileId: string,
     walletId: string,
     options?: PaginationOptions
   ): Promise<StoredTransaction[]>
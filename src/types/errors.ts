@@ -49,3 +49,46 @@ export function getErrorCode(error: unknown): number | undefined {
 
   return undefined
 }
+
+/**
+ * Kind of failure reported by a command returning a structured error
+ */
+export type ApiErrorCode =
+  | 'rate_limited'
+  | 'network'
+  | 'timeout'
+  | 'offline'
+  | 'provider'
+  | 'unsupported_chain'
+  | 'invalid_input'
+  | 'not_found'
+  | 'config'
+  | 'unauthorized'
+  | 'forbidden'
+  | 'cancelled'
+  | 'database'
+  | 'internal'
+
+/**
+ * Structured error returned by backend commands
+ */
+export interface ApiError {
+  code: ApiErrorCode
+  message: string
+  /** Whether retrying the same request later may succeed */
+  retryable: boolean
+  /** API provider or chain the error came from */
+  provider?: string
+}
+
+/**
+ * Type guard to check if an error is a structured backend error
+ */
+export function isApiError(error: unknown): error is ApiError {
+  return (
+    isErrorWithMessage(error) &&
+    'code' in error &&
+    typeof (error as { code: unknown }).code === 'string' &&
+    'retryable' in error
+  )
+}